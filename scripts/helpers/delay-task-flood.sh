@@ -168,6 +168,7 @@ get_delay_task_payload() {
             \"test_scenario\": \"multi_worker_chaos\"
         },
         \"priority\": \"$PRIORITY\",
+        \"tags\": [\"$TASK_TAG\"],
         \"metadata\": {
             \"chaos_test\": true,
             \"tag\": \"$TASK_TAG\",
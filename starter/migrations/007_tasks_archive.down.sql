@@ -0,0 +1,7 @@
+-- Drop tasks_archive table and related objects
+DROP INDEX IF EXISTS idx_tasks_terminal_completed_at;
+DROP INDEX IF EXISTS idx_tasks_archive_archived_at;
+DROP INDEX IF EXISTS idx_tasks_archive_created_by;
+DROP INDEX IF EXISTS idx_tasks_archive_status;
+DROP INDEX IF EXISTS idx_tasks_archive_task_type;
+DROP TABLE IF EXISTS tasks_archive;
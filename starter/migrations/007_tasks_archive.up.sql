@@ -0,0 +1,32 @@
+-- Archive table for old terminal tasks (mirrors tasks, plus archived_at)
+CREATE TABLE tasks_archive (
+    id UUID PRIMARY KEY,
+    task_type TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL
+        CONSTRAINT valid_archived_task_status CHECK (status IN ('completed', 'failed')),
+    priority TEXT NOT NULL DEFAULT 'normal'
+        CONSTRAINT valid_archived_task_priority CHECK (priority IN ('low', 'normal', 'high', 'critical')),
+    retry_strategy JSONB NOT NULL DEFAULT '{}',
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    current_attempt INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    scheduled_at TIMESTAMPTZ,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Indexes for tasks_archive
+CREATE INDEX idx_tasks_archive_task_type ON tasks_archive(task_type);
+CREATE INDEX idx_tasks_archive_status ON tasks_archive(status);
+CREATE INDEX idx_tasks_archive_created_by ON tasks_archive(created_by) WHERE created_by IS NOT NULL;
+CREATE INDEX idx_tasks_archive_archived_at ON tasks_archive(archived_at);
+
+-- Supports the archive job's age scan over terminal tasks
+CREATE INDEX idx_tasks_terminal_completed_at ON tasks(completed_at)
+WHERE status IN ('completed', 'failed');
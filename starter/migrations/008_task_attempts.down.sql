@@ -0,0 +1,3 @@
+-- Drop task_attempts table and related objects
+DROP INDEX IF EXISTS idx_task_attempts_task_id;
+DROP TABLE IF EXISTS task_attempts;
@@ -0,0 +1,14 @@
+-- Per-attempt execution history for tasks
+CREATE TABLE task_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    attempt_number INTEGER NOT NULL,
+    worker_id TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+    duration_ms BIGINT,
+    error TEXT
+);
+
+-- Indexes for task_attempts
+CREATE INDEX idx_task_attempts_task_id ON task_attempts(task_id, started_at);
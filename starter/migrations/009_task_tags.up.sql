@@ -0,0 +1,13 @@
+-- First-class task tags (previously stored informally as metadata->>'tag')
+ALTER TABLE tasks ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE tasks_archive ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+-- Backfill tags from the legacy metadata field
+UPDATE tasks SET tags = ARRAY[metadata->>'tag']
+WHERE metadata->>'tag' IS NOT NULL AND metadata->>'tag' <> '';
+UPDATE tasks_archive SET tags = ARRAY[metadata->>'tag']
+WHERE metadata->>'tag' IS NOT NULL AND metadata->>'tag' <> '';
+
+-- Indexes for tag containment queries
+CREATE INDEX idx_tasks_tags ON tasks USING GIN (tags);
+CREATE INDEX idx_tasks_archive_tags ON tasks_archive USING GIN (tags);
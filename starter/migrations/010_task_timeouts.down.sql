@@ -0,0 +1,5 @@
+-- Drop execution timeout overrides
+ALTER TABLE task_attempts DROP COLUMN IF EXISTS error_class;
+ALTER TABLE task_types DROP COLUMN IF EXISTS timeout_seconds;
+ALTER TABLE tasks_archive DROP COLUMN IF EXISTS timeout_seconds;
+ALTER TABLE tasks DROP COLUMN IF EXISTS timeout_seconds;
@@ -0,0 +1,9 @@
+-- Per-task and per-type execution timeout overrides
+ALTER TABLE tasks ADD COLUMN timeout_seconds INTEGER
+    CONSTRAINT valid_task_timeout CHECK (timeout_seconds > 0);
+ALTER TABLE tasks_archive ADD COLUMN timeout_seconds INTEGER;
+ALTER TABLE task_types ADD COLUMN timeout_seconds INTEGER
+    CONSTRAINT valid_task_type_timeout CHECK (timeout_seconds > 0);
+
+-- Classify failed attempts (error, timed_out, circuit_open, handler_not_found)
+ALTER TABLE task_attempts ADD COLUMN error_class TEXT;
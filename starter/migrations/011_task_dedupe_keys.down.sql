@@ -0,0 +1,4 @@
+-- Drop task dedupe keys
+DROP INDEX IF EXISTS idx_tasks_active_dedupe_key;
+ALTER TABLE tasks_archive DROP COLUMN IF EXISTS dedupe_key;
+ALTER TABLE tasks DROP COLUMN IF EXISTS dedupe_key;
@@ -0,0 +1,7 @@
+-- Deduplicate active tasks by a caller-supplied key, scoped to the creator
+ALTER TABLE tasks ADD COLUMN dedupe_key TEXT;
+ALTER TABLE tasks_archive ADD COLUMN dedupe_key TEXT;
+
+CREATE UNIQUE INDEX idx_tasks_active_dedupe_key ON tasks (created_by, dedupe_key)
+    NULLS NOT DISTINCT
+    WHERE dedupe_key IS NOT NULL AND status IN ('pending', 'running', 'retrying');
@@ -0,0 +1,5 @@
+-- Worker leases on running tasks so tasks orphaned by a dead worker can be recovered
+ALTER TABLE tasks ADD COLUMN claimed_by TEXT;
+ALTER TABLE tasks ADD COLUMN lease_expires_at TIMESTAMPTZ;
+
+CREATE INDEX idx_tasks_running_lease ON tasks (lease_expires_at) WHERE status = 'running';
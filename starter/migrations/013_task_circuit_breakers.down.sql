@@ -0,0 +1,2 @@
+-- Drop circuit breaker state
+DROP TABLE IF EXISTS task_circuit_breakers;
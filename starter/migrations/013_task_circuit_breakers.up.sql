@@ -0,0 +1,14 @@
+-- Circuit breaker state reported by workers, plus manual trip/reset controls
+CREATE TABLE task_circuit_breakers (
+    task_type TEXT PRIMARY KEY REFERENCES task_types(task_type) ON DELETE CASCADE,
+    state TEXT NOT NULL DEFAULT 'closed'
+        CONSTRAINT valid_circuit_state CHECK (state IN ('closed', 'open', 'halfopen')),
+    failure_count INTEGER NOT NULL DEFAULT 0,
+    -- Manually tripped breakers stay open until reset
+    forced BOOLEAN NOT NULL DEFAULT false,
+    -- Incremented on every manual trip/reset so workers apply each control once
+    control_version BIGINT NOT NULL DEFAULT 0,
+    reported_by TEXT,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
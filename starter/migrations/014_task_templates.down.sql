@@ -0,0 +1,2 @@
+-- Drop task templates
+DROP TABLE IF EXISTS task_templates;
@@ -0,0 +1,20 @@
+-- Reusable task definitions that clients instantiate by name
+CREATE TABLE task_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    task_type TEXT NOT NULL REFERENCES task_types(task_type) ON DELETE CASCADE,
+    payload JSONB NOT NULL DEFAULT '{}',
+    priority TEXT NOT NULL DEFAULT 'normal'
+        CONSTRAINT valid_template_priority CHECK (priority IN ('low', 'normal', 'high', 'critical')),
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_task_templates_task_type ON task_templates(task_type);
+
+-- Update trigger for task templates
+CREATE TRIGGER update_task_templates_updated_at BEFORE UPDATE ON task_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
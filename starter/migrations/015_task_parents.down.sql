@@ -0,0 +1,4 @@
+-- Drop task parent links
+DROP INDEX IF EXISTS idx_tasks_parent_task_id;
+ALTER TABLE tasks_archive DROP COLUMN IF EXISTS parent_task_id;
+ALTER TABLE tasks DROP COLUMN IF EXISTS parent_task_id;
@@ -0,0 +1,6 @@
+-- Link tasks spawned by a running handler to the task that spawned them.
+-- No foreign key: parents may be archived or deleted before their children.
+ALTER TABLE tasks ADD COLUMN parent_task_id UUID;
+ALTER TABLE tasks_archive ADD COLUMN parent_task_id UUID;
+
+CREATE INDEX idx_tasks_parent_task_id ON tasks(parent_task_id) WHERE parent_task_id IS NOT NULL;
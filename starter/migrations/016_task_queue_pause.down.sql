@@ -0,0 +1,4 @@
+-- Drop task queue pause flags
+ALTER TABLE task_types DROP COLUMN IF EXISTS paused_by;
+ALTER TABLE task_types DROP COLUMN IF EXISTS paused_at;
+ALTER TABLE task_types DROP COLUMN IF EXISTS paused;
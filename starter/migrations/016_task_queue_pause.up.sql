@@ -0,0 +1,4 @@
+-- Paused task types keep accepting tasks, but workers stop claiming them
+ALTER TABLE task_types ADD COLUMN paused BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE task_types ADD COLUMN paused_at TIMESTAMPTZ;
+ALTER TABLE task_types ADD COLUMN paused_by UUID REFERENCES users(id) ON DELETE SET NULL;
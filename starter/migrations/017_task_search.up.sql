@@ -0,0 +1,12 @@
+-- Full-text search over the string and numeric values of task payloads and metadata.
+-- The 'simple' configuration keeps tokens such as email addresses and ids intact
+-- instead of stemming them. Queries must repeat this exact expression to use the index.
+CREATE INDEX idx_tasks_search ON tasks USING GIN (
+    (jsonb_to_tsvector('simple', payload, '["string", "numeric"]')
+     || jsonb_to_tsvector('simple', metadata, '["string", "numeric"]'))
+);
+
+CREATE INDEX idx_tasks_archive_search ON tasks_archive USING GIN (
+    (jsonb_to_tsvector('simple', payload, '["string", "numeric"]')
+     || jsonb_to_tsvector('simple', metadata, '["string", "numeric"]'))
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_metrics_created_at;
+DROP TABLE IF EXISTS metric_rollup_state;
+DROP TABLE IF EXISTS metric_retention_policies;
+DROP TABLE IF EXISTS metric_rollups;
@@ -0,0 +1,37 @@
+-- Downsampled metrics: one row per series (name + labels) per time bucket
+CREATE TABLE metric_rollups (
+    name TEXT NOT NULL,
+    metric_type TEXT NOT NULL
+        CONSTRAINT valid_rollup_metric_type CHECK (metric_type IN ('counter', 'gauge', 'histogram', 'summary')),
+    labels JSONB NOT NULL DEFAULT '{}',
+    resolution_secs INTEGER NOT NULL CHECK (resolution_secs > 0),
+    bucket_start TIMESTAMPTZ NOT NULL,
+    count BIGINT NOT NULL,
+    sum DOUBLE PRECISION NOT NULL,
+    min DOUBLE PRECISION NOT NULL,
+    max DOUBLE PRECISION NOT NULL,
+    -- Latest raw value in the bucket, for counters and gauges
+    last DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (name, resolution_secs, bucket_start, labels)
+);
+
+CREATE INDEX idx_metric_rollups_bucket_start ON metric_rollups(bucket_start);
+
+-- Per-metric retention overrides; metrics without a row use the configured defaults
+CREATE TABLE metric_retention_policies (
+    metric_name TEXT PRIMARY KEY,
+    raw_retention_days INTEGER NOT NULL CHECK (raw_retention_days > 0),
+    rollup_retention_days INTEGER NOT NULL CHECK (rollup_retention_days > 0),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- How far (by metrics.created_at) raw metrics have been rolled up
+CREATE TABLE metric_rollup_state (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    rolled_up_until TIMESTAMPTZ NOT NULL
+);
+
+INSERT INTO metric_rollup_state (rolled_up_until) VALUES (to_timestamp(0));
+
+CREATE INDEX idx_metrics_created_at ON metrics(created_at);
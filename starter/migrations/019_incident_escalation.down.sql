@@ -0,0 +1,10 @@
+DROP TABLE IF EXISTS incident_escalations;
+DROP INDEX IF EXISTS idx_incidents_next_escalation_at;
+ALTER TABLE incidents
+    DROP COLUMN IF EXISTS next_escalation_at,
+    DROP COLUMN IF EXISTS escalation_step,
+    DROP COLUMN IF EXISTS escalation_policy_id,
+    DROP COLUMN IF EXISTS acknowledged_by,
+    DROP COLUMN IF EXISTS acknowledged_at;
+DROP TABLE IF EXISTS incident_escalation_steps;
+DROP TABLE IF EXISTS incident_escalation_policies;
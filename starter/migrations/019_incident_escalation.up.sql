@@ -0,0 +1,48 @@
+-- Escalation policies: who to notify, in order, while an incident stays unacknowledged
+CREATE TABLE incident_escalation_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Step N notifies its user delay_minutes after step N-1 (step 0: after the incident starts)
+CREATE TABLE incident_escalation_steps (
+    policy_id UUID NOT NULL REFERENCES incident_escalation_policies(id) ON DELETE CASCADE,
+    step_order INTEGER NOT NULL CHECK (step_order >= 0),
+    notify_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    delay_minutes INTEGER NOT NULL CHECK (delay_minutes >= 0),
+    PRIMARY KEY (policy_id, step_order)
+);
+
+ALTER TABLE incidents
+    ADD COLUMN acknowledged_at TIMESTAMPTZ,
+    ADD COLUMN acknowledged_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN escalation_policy_id UUID REFERENCES incident_escalation_policies(id) ON DELETE SET NULL,
+    -- Last step notified (NULL before the first notification)
+    ADD COLUMN escalation_step INTEGER,
+    -- When the next step is due; NULL once acknowledged, resolved or out of steps
+    ADD COLUMN next_escalation_at TIMESTAMPTZ;
+
+CREATE INDEX idx_incidents_next_escalation_at ON incidents(next_escalation_at)
+    WHERE next_escalation_at IS NOT NULL;
+
+-- Audit trail of every escalation notification
+CREATE TABLE incident_escalations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    incident_id UUID NOT NULL REFERENCES incidents(id) ON DELETE CASCADE,
+    step_order INTEGER NOT NULL,
+    notified_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- 'policy' when the worker escalated, 'manual' when a user did
+    reason TEXT NOT NULL CHECK (reason IN ('policy', 'manual')),
+    triggered_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    escalated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Set once the worker has emailed the notified user
+    notified_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_incident_escalations_incident_id ON incident_escalations(incident_id, escalated_at);
+CREATE INDEX idx_incident_escalations_pending ON incident_escalations(escalated_at)
+    WHERE notified_at IS NULL;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS postmortem_action_items;
+DROP TABLE IF EXISTS incident_postmortems;
+ALTER TABLE incidents DROP COLUMN IF EXISTS postmortem_required;
@@ -0,0 +1,32 @@
+-- High and critical incidents need a postmortem once resolved
+ALTER TABLE incidents
+    ADD COLUMN postmortem_required BOOLEAN NOT NULL GENERATED ALWAYS AS (severity IN ('high', 'critical')) STORED;
+
+-- One postmortem document per incident
+CREATE TABLE incident_postmortems (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    incident_id UUID NOT NULL UNIQUE REFERENCES incidents(id) ON DELETE CASCADE,
+    -- Markdown document
+    body TEXT NOT NULL,
+    contributing_factors TEXT[] NOT NULL DEFAULT '{}',
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Follow-up work; task_id has no foreign key so links survive task archiving
+CREATE TABLE postmortem_action_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    postmortem_id UUID NOT NULL REFERENCES incident_postmortems(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    task_id UUID,
+    assigned_to UUID REFERENCES users(id) ON DELETE SET NULL,
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_postmortem_action_items_postmortem_id ON postmortem_action_items(postmortem_id, created_at);
+CREATE INDEX idx_postmortem_action_items_task_id ON postmortem_action_items(task_id)
+    WHERE task_id IS NOT NULL;
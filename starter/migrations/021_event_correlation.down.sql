@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_events_error_recorded_at;
+DROP TABLE IF EXISTS incident_correlated_events;
+DROP INDEX IF EXISTS idx_incidents_active_correlation_key;
+ALTER TABLE incidents
+    DROP COLUMN IF EXISTS last_correlated_at,
+    DROP COLUMN IF EXISTS correlated_event_count,
+    DROP COLUMN IF EXISTS correlation_key;
@@ -0,0 +1,22 @@
+-- Incidents opened by the worker when error events from one source and tag set
+-- exceed the configured rate. The key is an md5 of the source and tags.
+ALTER TABLE incidents
+    ADD COLUMN correlation_key TEXT,
+    ADD COLUMN correlated_event_count INTEGER NOT NULL DEFAULT 0,
+    -- recorded_at of the latest correlated event; drives auto-resolution
+    ADD COLUMN last_correlated_at TIMESTAMPTZ;
+
+-- At most one active incident per key; later events attach to it
+CREATE UNIQUE INDEX idx_incidents_active_correlation_key ON incidents(correlation_key)
+    WHERE correlation_key IS NOT NULL AND status IN ('open', 'investigating');
+
+-- Events deduplicated into a correlated incident; each event belongs to at most one
+CREATE TABLE incident_correlated_events (
+    event_id UUID PRIMARY KEY REFERENCES events(id) ON DELETE CASCADE,
+    incident_id UUID NOT NULL REFERENCES incidents(id) ON DELETE CASCADE,
+    correlated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_incident_correlated_events_incident_id ON incident_correlated_events(incident_id);
+CREATE INDEX idx_events_error_recorded_at ON events(recorded_at)
+    WHERE lower(level) IN ('error', 'critical', 'fatal');
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS event_rate_counters;
+DROP TABLE IF EXISTS event_source_limits;
@@ -0,0 +1,20 @@
+-- Per-source ingestion overrides; sources without a row use the configured defaults
+CREATE TABLE event_source_limits (
+    source TEXT PRIMARY KEY,
+    -- Events accepted per minute; 0 disables rate limiting for the source
+    events_per_minute INTEGER NOT NULL CHECK (events_per_minute >= 0),
+    sample_ratio DOUBLE PRECISION NOT NULL CHECK (sample_ratio >= 0 AND sample_ratio <= 1),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Events admitted per source or user in fixed one-minute windows
+CREATE TABLE event_rate_counters (
+    scope TEXT NOT NULL CONSTRAINT valid_rate_counter_scope CHECK (scope IN ('source', 'user')),
+    key TEXT NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (scope, key, window_start)
+);
+
+CREATE INDEX idx_event_rate_counters_window_start ON event_rate_counters(window_start);
@@ -0,0 +1,16 @@
+-- Distinct label combinations seen per metric name, for cardinality limits
+CREATE TABLE metric_series (
+    name TEXT NOT NULL,
+    labels JSONB NOT NULL,
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Refreshed at most hourly; series unseen for the raw retention are pruned
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (name, labels)
+);
+
+CREATE INDEX idx_metric_series_last_seen_at ON metric_series(last_seen_at);
+
+INSERT INTO metric_series (name, labels, first_seen_at, last_seen_at)
+SELECT name, labels, min(created_at), max(created_at)
+FROM metrics
+GROUP BY name, labels;
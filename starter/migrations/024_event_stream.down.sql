@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS notify_events_inserted ON events;
+DROP FUNCTION IF EXISTS notify_event_inserted();
@@ -0,0 +1,11 @@
+-- Notify live event stream listeners of each stored event; the payload is the event id
+CREATE OR REPLACE FUNCTION notify_event_inserted()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('monitoring_events', NEW.id::TEXT);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER notify_events_inserted AFTER INSERT ON events
+    FOR EACH ROW EXECUTE FUNCTION notify_event_inserted();
@@ -0,0 +1,9 @@
+ALTER TABLE incident_escalations DROP COLUMN IF EXISTS oncall_schedule_id;
+DELETE FROM incident_escalation_steps WHERE notify_user_id IS NULL;
+ALTER TABLE incident_escalation_steps
+    DROP CONSTRAINT IF EXISTS incident_escalation_steps_target_check,
+    DROP COLUMN IF EXISTS notify_schedule_id,
+    ALTER COLUMN notify_user_id SET NOT NULL;
+DROP TABLE IF EXISTS oncall_overrides;
+DROP TABLE IF EXISTS oncall_schedule_members;
+DROP TABLE IF EXISTS oncall_schedules;
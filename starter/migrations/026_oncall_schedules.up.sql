@@ -0,0 +1,44 @@
+-- On-call schedules: members take turns, each for one rotation period
+CREATE TABLE oncall_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    rotation_period_hours INTEGER NOT NULL CHECK (rotation_period_hours > 0),
+    -- When the first member's first shift starts; later shifts follow back to back
+    handoff_at TIMESTAMPTZ NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Members in rotation order
+CREATE TABLE oncall_schedule_members (
+    schedule_id UUID NOT NULL REFERENCES oncall_schedules(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL CHECK (position >= 0),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (schedule_id, position)
+);
+
+-- Temporary replacements for the rotation; the latest override wins when they overlap
+CREATE TABLE oncall_overrides (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    schedule_id UUID NOT NULL REFERENCES oncall_schedules(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX idx_oncall_overrides_schedule_id ON oncall_overrides(schedule_id, ends_at);
+
+-- Escalation steps notify either a fixed user or whoever is on call when the step runs
+ALTER TABLE incident_escalation_steps
+    ALTER COLUMN notify_user_id DROP NOT NULL,
+    ADD COLUMN notify_schedule_id UUID REFERENCES oncall_schedules(id) ON DELETE RESTRICT,
+    ADD CONSTRAINT incident_escalation_steps_target_check
+        CHECK (num_nonnulls(notify_user_id, notify_schedule_id) = 1);
+
+ALTER TABLE incident_escalations
+    ADD COLUMN oncall_schedule_id UUID REFERENCES oncall_schedules(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS alert_notification_channels;
+DROP TABLE IF EXISTS notification_channels;
@@ -0,0 +1,20 @@
+-- Destinations alert notifications are sent to
+CREATE TABLE notification_channels (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    kind TEXT NOT NULL CHECK (kind IN ('webhook', 'slack', 'email')),
+    -- URL for webhook and Slack channels, address for email channels
+    target TEXT NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Channels each alert notifies
+CREATE TABLE alert_notification_channels (
+    alert_id UUID NOT NULL REFERENCES alerts(id) ON DELETE CASCADE,
+    channel_id UUID NOT NULL REFERENCES notification_channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (alert_id, channel_id)
+);
+
+CREATE INDEX idx_alert_notification_channels_channel_id ON alert_notification_channels(channel_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS ingestion_usage;
+DROP TABLE IF EXISTS ingestion_quotas;
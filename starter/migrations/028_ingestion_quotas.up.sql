@@ -0,0 +1,24 @@
+-- Daily ingestion budgets per user or event source; NULL leaves a kind unlimited
+CREATE TABLE ingestion_quotas (
+    scope TEXT NOT NULL CONSTRAINT valid_ingestion_quota_scope CHECK (scope IN ('source', 'user')),
+    key TEXT NOT NULL,
+    daily_events BIGINT CHECK (daily_events >= 0),
+    -- Metrics have no source, so only user quotas can limit them
+    daily_metrics BIGINT CHECK (daily_metrics >= 0),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (scope, key),
+    CONSTRAINT source_quotas_limit_events_only CHECK (scope = 'user' OR daily_metrics IS NULL)
+);
+
+-- Events and metric rows stored per user or source and UTC day
+CREATE TABLE ingestion_usage (
+    scope TEXT NOT NULL CONSTRAINT valid_ingestion_usage_scope CHECK (scope IN ('source', 'user')),
+    key TEXT NOT NULL,
+    day DATE NOT NULL,
+    events BIGINT NOT NULL DEFAULT 0,
+    metrics BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (scope, key, day)
+);
+
+CREATE INDEX idx_ingestion_usage_day ON ingestion_usage(day);
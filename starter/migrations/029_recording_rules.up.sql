@@ -0,0 +1,15 @@
+-- Metric queries evaluated on an interval and stored as new metric series
+CREATE TABLE recording_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Metric name the results are stored under
+    name TEXT NOT NULL UNIQUE,
+    expression TEXT NOT NULL,
+    interval_secs INTEGER NOT NULL CHECK (interval_secs > 0),
+    -- End of the last window stored; the next evaluation continues from there
+    last_window_end TIMESTAMPTZ,
+    last_evaluated_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- HTTP checks the worker runs on an interval
+CREATE TABLE uptime_checks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    url TEXT NOT NULL,
+    interval_secs INTEGER NOT NULL CHECK (interval_secs > 0),
+    timeout_secs INTEGER NOT NULL CHECK (timeout_secs > 0),
+    expected_status INTEGER NOT NULL,
+    -- Text the response body must contain, if set
+    expected_body TEXT,
+    -- Consecutive failures before the check is down and its alert fires
+    failure_threshold INTEGER NOT NULL CHECK (failure_threshold > 0),
+    -- Alert fired while the check is down
+    alert_id UUID REFERENCES alerts(id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'up', 'down')),
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    next_check_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_checked_at TIMESTAMPTZ,
+    last_status_code INTEGER,
+    last_latency_ms DOUBLE PRECISION,
+    last_error TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_uptime_checks_next_check_at ON uptime_checks(next_check_at);
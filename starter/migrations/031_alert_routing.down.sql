@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS alert_routing_rule_channels;
+DROP TABLE IF EXISTS alert_routing_rules;
+
+ALTER TABLE alerts
+    DROP COLUMN IF EXISTS labels,
+    DROP COLUMN IF EXISTS severity;
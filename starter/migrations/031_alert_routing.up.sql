@@ -0,0 +1,28 @@
+-- Alert severity and labels, matched by routing rules
+ALTER TABLE alerts
+    ADD COLUMN severity TEXT NOT NULL DEFAULT 'warning'
+        CONSTRAINT valid_alert_severity CHECK (severity IN ('info', 'warning', 'critical')),
+    ADD COLUMN labels JSONB NOT NULL DEFAULT '{}';
+
+-- Where firing alerts are sent, besides the channels chosen on the alert itself
+CREATE TABLE alert_routing_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    -- Severity the alert must have; NULL matches every severity
+    severity TEXT CHECK (severity IN ('info', 'warning', 'critical')),
+    -- Labels the alert must have with these values
+    labels JSONB NOT NULL DEFAULT '{}',
+    -- Policy of the incident opened while a matching alert fires
+    escalation_policy_id UUID REFERENCES incident_escalation_policies(id) ON DELETE SET NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE alert_routing_rule_channels (
+    rule_id UUID NOT NULL REFERENCES alert_routing_rules(id) ON DELETE CASCADE,
+    channel_id UUID NOT NULL REFERENCES notification_channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (rule_id, channel_id)
+);
+
+CREATE INDEX idx_alert_routing_rule_channels_channel_id ON alert_routing_rule_channels(channel_id);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS status_page_incidents;
+DROP TABLE IF EXISTS status_page_checks;
+DROP TABLE IF EXISTS status_pages;
@@ -0,0 +1,30 @@
+-- Public pages showing selected uptime checks and incidents
+CREATE TABLE status_pages (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Served unauthenticated at /status/{slug}
+    slug TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE status_page_checks (
+    page_id UUID NOT NULL REFERENCES status_pages(id) ON DELETE CASCADE,
+    check_id UUID NOT NULL REFERENCES uptime_checks(id) ON DELETE CASCADE,
+    -- Display order on the page
+    position INTEGER NOT NULL,
+    PRIMARY KEY (page_id, check_id)
+);
+
+CREATE INDEX idx_status_page_checks_check_id ON status_page_checks(check_id);
+
+-- Incidents shown on the page while active, besides those opened by its checks' alerts
+CREATE TABLE status_page_incidents (
+    page_id UUID NOT NULL REFERENCES status_pages(id) ON DELETE CASCADE,
+    incident_id UUID NOT NULL REFERENCES incidents(id) ON DELETE CASCADE,
+    PRIMARY KEY (page_id, incident_id)
+);
+
+CREATE INDEX idx_status_page_incidents_incident_id ON status_page_incidents(incident_id);
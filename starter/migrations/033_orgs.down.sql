@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS idx_events_org_id;
+DROP INDEX IF EXISTS idx_tasks_org_id;
+
+ALTER TABLE events DROP COLUMN IF EXISTS org_id;
+ALTER TABLE tasks_archive DROP COLUMN IF EXISTS org_id;
+ALTER TABLE tasks DROP COLUMN IF EXISTS org_id;
+
+DROP TABLE IF EXISTS org_members;
+DROP TABLE IF EXISTS orgs;
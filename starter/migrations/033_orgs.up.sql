@@ -0,0 +1,29 @@
+-- Organizations grouping users; tasks and events can be scoped to one
+CREATE TABLE orgs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    slug TEXT NOT NULL UNIQUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE org_members (
+    org_id UUID NOT NULL REFERENCES orgs(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL DEFAULT 'member'
+        CONSTRAINT valid_org_role CHECK (role IN ('owner', 'admin', 'member')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (org_id, user_id)
+);
+
+CREATE INDEX idx_org_members_user_id ON org_members(user_id);
+
+-- Deleting an organization deletes the data scoped to it
+ALTER TABLE tasks ADD COLUMN org_id UUID REFERENCES orgs(id) ON DELETE CASCADE;
+ALTER TABLE tasks_archive ADD COLUMN org_id UUID;
+ALTER TABLE events ADD COLUMN org_id UUID REFERENCES orgs(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_tasks_org_id ON tasks(org_id) WHERE org_id IS NOT NULL;
+CREATE INDEX idx_events_org_id ON events(org_id, recorded_at) WHERE org_id IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Emailed invitations to join an organization; only a hash of the token is stored
+CREATE TABLE org_invitations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    org_id UUID NOT NULL REFERENCES orgs(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    role TEXT NOT NULL DEFAULT 'member'
+        CONSTRAINT valid_invitation_role CHECK (role IN ('owner', 'admin', 'member')),
+    token_hash BYTEA NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CONSTRAINT valid_invitation_status CHECK (status IN ('pending', 'accepted', 'expired', 'revoked')),
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    accepted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    accepted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One open invitation per address and organization
+CREATE UNIQUE INDEX idx_org_invitations_pending ON org_invitations(org_id, email)
+    WHERE status = 'pending';
@@ -0,0 +1,2 @@
+-- Profile picture served from file storage
+ALTER TABLE users ADD COLUMN avatar_url TEXT;
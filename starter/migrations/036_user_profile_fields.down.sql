@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS profile_fields;
+
+ALTER TABLE users DROP COLUMN IF EXISTS profile;
@@ -0,0 +1,16 @@
+-- Custom profile values, validated against profile_fields
+ALTER TABLE users ADD COLUMN profile JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- Admin-managed schema for users.profile
+CREATE TABLE profile_fields (
+    name TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    field_type TEXT NOT NULL
+        CONSTRAINT valid_profile_field_type CHECK (field_type IN ('text', 'number', 'boolean', 'date', 'select')),
+    required BOOLEAN NOT NULL DEFAULT false,
+    visibility TEXT NOT NULL DEFAULT 'user'
+        CONSTRAINT valid_profile_field_visibility CHECK (visibility IN ('user', 'staff')),
+    options TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,20 @@
+-- Archives of a user's data built by the user_data_export task
+CREATE TABLE data_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    task_id UUID,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CONSTRAINT valid_data_export_status CHECK (status IN ('pending', 'ready', 'failed', 'expired')),
+    size_bytes BIGINT,
+    download_token TEXT NOT NULL UNIQUE,
+    error TEXT,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_data_exports_user_id ON data_exports(user_id, created_at DESC);
+CREATE INDEX idx_data_exports_expires_at ON data_exports(expires_at) WHERE status = 'ready';
+
+-- One export in progress per user
+CREATE UNIQUE INDEX idx_data_exports_pending ON data_exports(user_id) WHERE status = 'pending';
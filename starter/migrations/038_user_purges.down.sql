@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS user_purges;
+
+ALTER TABLE users DROP COLUMN IF EXISTS deleted_at;
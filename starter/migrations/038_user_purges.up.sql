@@ -0,0 +1,20 @@
+-- When the account was deleted by its owner or an admin; suspended accounts keep NULL
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_users_deleted_at ON users(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Audit trail of deleted accounts purged after their retention period
+CREATE TABLE user_purges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign key: in delete mode the user row is gone
+    user_id UUID NOT NULL,
+    mode TEXT NOT NULL
+        CONSTRAINT valid_purge_mode CHECK (mode IN ('delete', 'anonymize')),
+    deleted_at TIMESTAMPTZ NOT NULL,
+    sessions_deleted BIGINT NOT NULL DEFAULT 0,
+    api_keys_deleted BIGINT NOT NULL DEFAULT 0,
+    tasks_deleted BIGINT NOT NULL DEFAULT 0,
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_purges_purged_at ON user_purges(purged_at DESC);
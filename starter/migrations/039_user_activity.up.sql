@@ -0,0 +1,12 @@
+-- Trail of significant actions on each account, shown to the user and to admins
+CREATE TABLE user_activity (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Who performed the action: the user themselves or a moderator/admin
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_activity_user_created ON user_activity(user_id, created_at DESC);
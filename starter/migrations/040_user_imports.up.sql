@@ -0,0 +1,22 @@
+-- Bulk user imports from CSV, processed by the user_import background task
+CREATE TABLE user_imports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status TEXT NOT NULL DEFAULT 'pending'
+        CONSTRAINT valid_user_import_status CHECK (status IN ('pending', 'completed', 'failed')),
+    password_policy TEXT NOT NULL
+        CONSTRAINT valid_import_password_policy CHECK (password_policy IN ('provided', 'locked')),
+    -- The uploaded file; cleared once processed since it may contain passwords
+    csv TEXT,
+    task_id UUID,
+    total_rows INTEGER NOT NULL DEFAULT 0,
+    created_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    -- Outcome of each row, in file order
+    results JSONB NOT NULL DEFAULT '[]',
+    error TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_user_imports_created_at ON user_imports(created_at DESC);
@@ -0,0 +1,4 @@
+-- Identifier an identity provider assigns to a user it provisions over SCIM
+ALTER TABLE users ADD COLUMN external_id TEXT;
+
+CREATE UNIQUE INDEX idx_users_external_id ON users(external_id) WHERE external_id IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Deactivated accounts with an end date are reactivated by the worker once it passes
+ALTER TABLE users ADD COLUMN suspended_until TIMESTAMPTZ;
+
+CREATE INDEX idx_users_suspended_until ON users(suspended_until) WHERE suspended_until IS NOT NULL;
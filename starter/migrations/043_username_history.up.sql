@@ -0,0 +1,11 @@
+-- Usernames an account has given up; they stay reserved for it for a while
+-- so nobody else can take them over and impersonate the previous owner
+CREATE TABLE username_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    username TEXT NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_username_history_user_changed ON username_history(user_id, changed_at DESC);
+CREATE INDEX idx_username_history_username ON username_history(lower(username));
@@ -0,0 +1,13 @@
+-- Emailed links that confirm an account's address; only a hash of the token is stored
+CREATE TABLE email_verifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- The address the link was sent to; it no longer verifies once the user changes it
+    email TEXT NOT NULL,
+    token_hash BYTEA NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_email_verifications_user_sent ON email_verifications(user_id, sent_at DESC);
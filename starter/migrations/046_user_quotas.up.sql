@@ -0,0 +1,9 @@
+-- Per-user limits that replace the defaults of the user's role; NULL keeps the default.
+-- Daily event quotas live in ingestion_quotas with the other ingestion limits.
+CREATE TABLE user_quotas (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    max_tasks_per_day INTEGER CHECK (max_tasks_per_day >= 0),
+    max_api_keys INTEGER CHECK (max_api_keys >= 0),
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,23 @@
+-- Versioned terms of service and privacy policy; the highest version of each kind is current.
+CREATE TABLE legal_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL CHECK (kind IN ('terms', 'privacy')),
+    version INTEGER NOT NULL CHECK (version > 0),
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    published_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, version)
+);
+
+-- One row per user and document version they accepted
+CREATE TABLE legal_acceptances (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document_id UUID NOT NULL REFERENCES legal_documents(id) ON DELETE CASCADE,
+    accepted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip_address TEXT,
+    user_agent TEXT,
+    PRIMARY KEY (user_id, document_id)
+);
+
+CREATE INDEX idx_legal_acceptances_document ON legal_acceptances(document_id);
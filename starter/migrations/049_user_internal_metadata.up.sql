@@ -0,0 +1,2 @@
+-- Support annotations on an account (e.g. VIP customer, fraud flags), only ever shown to staff
+ALTER TABLE users ADD COLUMN internal_metadata JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,20 @@
+-- Self-serve account deletions waiting out their grace period before the
+-- account is erased; the cancel link only stores a hash of its token
+CREATE TABLE account_deletions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    status TEXT NOT NULL DEFAULT 'scheduled'
+        CHECK (status IN ('scheduled', 'cancelled', 'completed')),
+    -- Where the confirmations go; cleared once the deletion is cancelled or completed
+    email TEXT,
+    cancel_token_hash BYTEA NOT NULL UNIQUE,
+    task_id UUID,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    erase_after TIMESTAMPTZ NOT NULL,
+    cancelled_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+-- One scheduled deletion per account
+CREATE UNIQUE INDEX idx_account_deletions_scheduled
+    ON account_deletions(user_id) WHERE status = 'scheduled';
@@ -0,0 +1,20 @@
+-- Access to one object shared with a user or with everyone holding a custom role
+CREATE TABLE resource_permissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    resource_type TEXT NOT NULL CHECK (resource_type IN ('tasks')),
+    resource_id UUID NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID REFERENCES roles(id) ON DELETE CASCADE,
+    permission TEXT NOT NULL CHECK (permission IN ('read', 'write', 'delete')),
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((user_id IS NULL) <> (role_id IS NULL))
+);
+
+CREATE UNIQUE INDEX idx_resource_permissions_user
+    ON resource_permissions(resource_type, resource_id, user_id, permission)
+    WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_resource_permissions_role
+    ON resource_permissions(resource_type, resource_id, role_id, permission)
+    WHERE role_id IS NOT NULL;
+CREATE INDEX idx_resource_permissions_user_id ON resource_permissions(user_id);
@@ -0,0 +1,3 @@
+-- Sessions limited to a subset of their owner's permissions; NULL scopes give full access
+ALTER TABLE sessions ADD COLUMN scopes TEXT[];
+ALTER TABLE sessions ADD COLUMN name TEXT;
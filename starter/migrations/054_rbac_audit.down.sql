@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS rbac_audit;
+DROP FUNCTION IF EXISTS reject_rbac_audit_change();
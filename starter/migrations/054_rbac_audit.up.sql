@@ -0,0 +1,29 @@
+-- Append-only log of role, custom role and share changes
+CREATE TABLE rbac_audit (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign keys: entries outlive the users, roles and shares they mention.
+    -- NULL when an identity provider made the change
+    actor_id UUID,
+    action TEXT NOT NULL,
+    target_type TEXT NOT NULL CHECK (target_type IN ('user', 'role', 'share')),
+    target_id UUID NOT NULL,
+    before JSONB,
+    after JSONB,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_rbac_audit_created_at ON rbac_audit(created_at DESC);
+CREATE INDEX idx_rbac_audit_target ON rbac_audit(target_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION reject_rbac_audit_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'rbac_audit entries cannot be changed or removed';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER rbac_audit_append_only BEFORE UPDATE OR DELETE ON rbac_audit
+    FOR EACH ROW EXECUTE FUNCTION reject_rbac_audit_change();
+CREATE TRIGGER rbac_audit_no_truncate BEFORE TRUNCATE ON rbac_audit
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_rbac_audit_change();
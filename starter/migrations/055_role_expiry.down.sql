@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_users_role_expires_at;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS role_expiry_pair;
+ALTER TABLE users DROP COLUMN IF EXISTS role_revert_to;
+ALTER TABLE users DROP COLUMN IF EXISTS role_expires_at;
@@ -0,0 +1,8 @@
+-- Temporary roles: at role_expires_at the worker restores role_revert_to
+ALTER TABLE users ADD COLUMN role_expires_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN role_revert_to TEXT
+    CHECK (role_revert_to IN ('user', 'moderator', 'admin'));
+ALTER TABLE users ADD CONSTRAINT role_expiry_pair
+    CHECK ((role_expires_at IS NULL) = (role_revert_to IS NULL));
+
+CREATE INDEX idx_users_role_expires_at ON users(role_expires_at) WHERE role_expires_at IS NOT NULL;
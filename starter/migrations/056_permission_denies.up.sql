@@ -0,0 +1,11 @@
+-- Permissions blocked for one user, overriding their built-in and custom roles
+CREATE TABLE permission_denies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- resource:permission, e.g. monitoring:write
+    permission TEXT NOT NULL,
+    reason TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, permission)
+);
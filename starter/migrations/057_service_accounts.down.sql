@@ -0,0 +1,3 @@
+ALTER TABLE api_keys DROP COLUMN IF EXISTS scopes;
+ALTER TABLE api_keys DROP COLUMN IF EXISTS issued_by;
+ALTER TABLE users DROP COLUMN IF EXISTS is_service_account;
@@ -0,0 +1,8 @@
+-- Non-human accounts for workers and integrations; they have no usable
+-- password and authenticate only with API keys
+ALTER TABLE users ADD COLUMN is_service_account BOOLEAN NOT NULL DEFAULT false;
+
+-- API keys belong to their created_by account; issued_by is the admin who
+-- created the key, and scopes limit it like a scoped session (NULL for full access)
+ALTER TABLE api_keys ADD COLUMN issued_by UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE api_keys ADD COLUMN scopes TEXT[];
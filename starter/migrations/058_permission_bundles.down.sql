@@ -0,0 +1,9 @@
+-- Bundle entries can't be removed from the append-only audit log, so the
+-- restored constraint only applies to new entries
+ALTER TABLE rbac_audit DROP CONSTRAINT rbac_audit_target_type_check;
+ALTER TABLE rbac_audit ADD CONSTRAINT rbac_audit_target_type_check
+    CHECK (target_type IN ('user', 'role', 'share')) NOT VALID;
+
+DROP TABLE IF EXISTS user_bundles;
+DROP TABLE IF EXISTS role_bundles;
+DROP TABLE IF EXISTS permission_bundles;
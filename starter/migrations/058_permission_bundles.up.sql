@@ -0,0 +1,35 @@
+-- Named sets of `resource:permission` entries that can be attached to custom
+-- roles or straight to users, so related permissions are granted together
+CREATE TABLE permission_bundles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    permissions TEXT[] NOT NULL DEFAULT '{}',
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE role_bundles (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    bundle_id UUID NOT NULL REFERENCES permission_bundles(id) ON DELETE CASCADE,
+    attached_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    attached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role_id, bundle_id)
+);
+
+CREATE INDEX idx_role_bundles_bundle ON role_bundles(bundle_id);
+
+CREATE TABLE user_bundles (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    bundle_id UUID NOT NULL REFERENCES permission_bundles(id) ON DELETE CASCADE,
+    attached_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    attached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, bundle_id)
+);
+
+CREATE INDEX idx_user_bundles_bundle ON user_bundles(bundle_id);
+
+ALTER TABLE rbac_audit DROP CONSTRAINT rbac_audit_target_type_check;
+ALTER TABLE rbac_audit ADD CONSTRAINT rbac_audit_target_type_check
+    CHECK (target_type IN ('user', 'role', 'share', 'bundle'));
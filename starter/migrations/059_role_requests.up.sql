@@ -0,0 +1,20 @@
+-- Requests to raise a user's built-in role, applied only once an admin other
+-- than the requester approves them
+CREATE TABLE role_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('moderator', 'admin')),
+    -- Grant the role only until this time, as with a temporary role change
+    expires_at TIMESTAMPTZ,
+    reason TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at TIMESTAMPTZ,
+    decision_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A user has at most one request waiting for a decision
+CREATE UNIQUE INDEX idx_role_requests_pending_user ON role_requests(user_id) WHERE status = 'pending';
+CREATE INDEX idx_role_requests_status ON role_requests(status, created_at DESC);
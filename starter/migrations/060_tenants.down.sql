@@ -0,0 +1,13 @@
+DROP INDEX IF EXISTS idx_metrics_tenant_id;
+DROP INDEX IF EXISTS idx_events_tenant_id;
+DROP INDEX IF EXISTS idx_tasks_archive_tenant_id;
+DROP INDEX IF EXISTS idx_tasks_tenant_id;
+DROP INDEX IF EXISTS idx_users_tenant_id;
+
+ALTER TABLE metrics DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE events DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE tasks_archive DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE tasks DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
+
+DROP TABLE IF EXISTS tenants;
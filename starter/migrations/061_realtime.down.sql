@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS notify_alerts_status_changed ON alerts;
+DROP FUNCTION IF EXISTS notify_alert_status_changed();
+DROP TRIGGER IF EXISTS notify_tasks_status_changed ON tasks;
+DROP FUNCTION IF EXISTS notify_task_status_changed();
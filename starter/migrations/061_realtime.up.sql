@@ -0,0 +1,52 @@
+-- Notify WebSocket listeners of task and alert status changes; the payload is
+-- a JSON message with its topic, audience and data
+CREATE OR REPLACE FUNCTION notify_task_status_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('realtime', json_build_object(
+        'topic', 'tasks',
+        'tenant_id', NEW.tenant_id,
+        'user_id', NEW.created_by,
+        'org_id', NEW.org_id,
+        'data', json_build_object(
+            'id', NEW.id,
+            'task_type', NEW.task_type,
+            'status', NEW.status,
+            'current_attempt', NEW.current_attempt,
+            'last_error', left(NEW.last_error, 1000),
+            'updated_at', NEW.updated_at
+        )
+    )::TEXT);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER notify_tasks_status_changed AFTER INSERT OR UPDATE OF status ON tasks
+    FOR EACH ROW EXECUTE FUNCTION notify_task_status_changed();
+
+CREATE OR REPLACE FUNCTION notify_alert_status_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('realtime', json_build_object(
+        'topic', 'alerts',
+        'data', json_build_object(
+            'id', NEW.id,
+            'name', NEW.name,
+            'status', NEW.status,
+            'severity', NEW.severity,
+            'triggered_at', NEW.triggered_at,
+            'resolved_at', NEW.resolved_at
+        )
+    )::TEXT);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER notify_alerts_status_changed AFTER INSERT OR UPDATE OF status ON alerts
+    FOR EACH ROW EXECUTE FUNCTION notify_alert_status_changed();
@@ -0,0 +1,12 @@
+-- Feature flags for dark launches: a flag is on for the listed users and the
+-- rollout percentage of everyone else, and off for all while disabled
+CREATE TABLE feature_flags (
+    key TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    rollout_percentage SMALLINT NOT NULL DEFAULT 100
+        CHECK (rollout_percentage BETWEEN 0 AND 100),
+    user_ids UUID[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
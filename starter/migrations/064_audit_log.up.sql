@@ -0,0 +1,44 @@
+-- Append-only log of changes across the application: who made them, to what,
+-- the state before and after, and why. Only the retention purge may delete
+-- entries, by setting `starter.audit_purge` for its transaction.
+CREATE TABLE audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- No foreign keys: entries outlive the users and resources they mention
+    tenant_id UUID NOT NULL,
+    -- NULL for changes made by the system, e.g. a background job
+    actor_id UUID,
+    action TEXT NOT NULL,
+    resource_type TEXT NOT NULL,
+    resource_id TEXT NOT NULL,
+    before JSONB,
+    after JSONB,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_tenant_created ON audit_log(tenant_id, created_at DESC);
+CREATE INDEX idx_audit_log_resource ON audit_log(resource_type, resource_id, created_at DESC);
+CREATE INDEX idx_audit_log_actor ON audit_log(actor_id, created_at DESC);
+
+CREATE OR REPLACE FUNCTION reject_audit_log_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' AND current_setting('starter.audit_purge', true) = 'on' THEN
+        RETURN OLD;
+    END IF;
+    RAISE EXCEPTION 'audit_log entries cannot be changed or removed';
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER audit_log_append_only BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION reject_audit_log_change();
+CREATE TRIGGER audit_log_no_truncate BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT EXECUTE FUNCTION reject_audit_log_change();
+
+-- Retention overriding `audit.retention_days` for one resource type
+CREATE TABLE audit_retention_policies (
+    resource_type TEXT PRIMARY KEY,
+    retention_days INTEGER NOT NULL CHECK (retention_days > 0),
+    updated_by UUID,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- Maintenance mode toggled by admins; a single row shared by every server
+-- and worker
+CREATE TABLE maintenance_mode (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    -- Shown to clients in the 503 response
+    message TEXT,
+    -- Seconds clients are told to wait; NULL for `maintenance.retry_after_secs`
+    retry_after_secs INTEGER CHECK (retry_after_secs > 0),
+    updated_by UUID,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO maintenance_mode (id) VALUES (true);
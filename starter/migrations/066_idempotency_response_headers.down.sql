@@ -0,0 +1,11 @@
+ALTER TABLE idempotency_keys ADD COLUMN content_type TEXT;
+
+UPDATE idempotency_keys
+SET content_type = (
+    SELECT header->>1
+    FROM jsonb_array_elements(headers) AS header
+    WHERE header->>0 = 'content-type'
+    LIMIT 1
+);
+
+ALTER TABLE idempotency_keys DROP COLUMN headers;
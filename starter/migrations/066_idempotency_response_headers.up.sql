@@ -0,0 +1,9 @@
+-- Replay every end-to-end header of a stored response, not just its
+-- Content-Type; stored as [name, value] pairs in response order
+ALTER TABLE idempotency_keys ADD COLUMN headers JSONB NOT NULL DEFAULT '[]';
+
+UPDATE idempotency_keys
+SET headers = jsonb_build_array(jsonb_build_array('content-type', content_type))
+WHERE content_type IS NOT NULL;
+
+ALTER TABLE idempotency_keys DROP COLUMN content_type;
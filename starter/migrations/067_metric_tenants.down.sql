@@ -0,0 +1,9 @@
+DELETE FROM metric_series WHERE tenant_id <> '00000000-0000-0000-0000-000000000001';
+ALTER TABLE metric_series DROP CONSTRAINT metric_series_pkey;
+ALTER TABLE metric_series DROP COLUMN tenant_id;
+ALTER TABLE metric_series ADD PRIMARY KEY (name, labels);
+
+DELETE FROM metric_rollups WHERE tenant_id <> '00000000-0000-0000-0000-000000000001';
+ALTER TABLE metric_rollups DROP CONSTRAINT metric_rollups_pkey;
+ALTER TABLE metric_rollups DROP COLUMN tenant_id;
+ALTER TABLE metric_rollups ADD PRIMARY KEY (name, resolution_secs, bucket_start, labels);
@@ -0,0 +1,22 @@
+-- Rollups and series belong to the tenant of their raw metrics, so metric
+-- queries, Grafana listings and cardinality limits stay within a tenant.
+-- Existing rows go to the default tenant.
+ALTER TABLE metric_rollups ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+ALTER TABLE metric_rollups DROP CONSTRAINT metric_rollups_pkey,
+    ADD PRIMARY KEY (tenant_id, name, resolution_secs, bucket_start, labels);
+
+ALTER TABLE metric_series ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+ALTER TABLE metric_series DROP CONSTRAINT metric_series_pkey,
+    ADD PRIMARY KEY (tenant_id, name, labels);
+
+INSERT INTO metric_series (tenant_id, name, labels, first_seen_at, last_seen_at)
+SELECT tenant_id, name, labels, min(created_at), max(created_at)
+FROM metrics
+WHERE tenant_id <> '00000000-0000-0000-0000-000000000001'
+GROUP BY tenant_id, name, labels;
+
+-- Rebuild the rollups still within raw retention per tenant; older ones
+-- stay with the default tenant
+UPDATE metric_rollup_state SET rolled_up_until = to_timestamp(0);
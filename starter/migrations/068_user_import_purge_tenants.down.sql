@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_user_purges_tenant_id;
+ALTER TABLE user_purges DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE user_imports DROP COLUMN IF EXISTS tenant_id;
@@ -0,0 +1,15 @@
+-- Imports and purge records belong to a tenant, so each tenant's admins only
+-- see their own; the purged user's row may be gone, so it can't be joined
+ALTER TABLE user_imports ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE user_imports i SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = i.created_by;
+
+ALTER TABLE user_purges ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE user_purges p SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = p.user_id;
+
+CREATE INDEX idx_user_purges_tenant_id ON user_purges(tenant_id, purged_at DESC);
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS idx_incidents_tenant_id;
+DROP INDEX IF EXISTS idx_alerts_tenant_id;
+DROP INDEX IF EXISTS idx_incidents_active_correlation_key;
+CREATE UNIQUE INDEX idx_incidents_active_correlation_key ON incidents(correlation_key)
+    WHERE correlation_key IS NOT NULL AND status IN ('open', 'investigating');
+ALTER TABLE incidents DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE alerts DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE task_templates DROP CONSTRAINT IF EXISTS task_templates_tenant_id_name_key;
+ALTER TABLE task_templates DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE task_templates ADD CONSTRAINT task_templates_name_key UNIQUE (name);
@@ -0,0 +1,38 @@
+-- Task templates, alerts and incidents belong to a tenant like tasks and
+-- events. Existing rows go to their creator's tenant, correlated incidents
+-- to the tenant of their events, and the rest to the default tenant.
+ALTER TABLE task_templates ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE task_templates t SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = t.created_by;
+-- Template names are unique within a tenant
+ALTER TABLE task_templates DROP CONSTRAINT task_templates_name_key,
+    ADD CONSTRAINT task_templates_tenant_id_name_key UNIQUE (tenant_id, name);
+
+ALTER TABLE alerts ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE alerts a SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = a.created_by;
+
+ALTER TABLE incidents ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE incidents i SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = i.created_by;
+UPDATE incidents i SET tenant_id = e.tenant_id
+FROM incident_correlated_events c
+JOIN events e ON e.id = c.event_id
+WHERE c.incident_id = i.id;
+UPDATE incidents i SET tenant_id = a.tenant_id
+FROM alerts a
+WHERE i.correlation_key = 'alert:' || a.id;
+
+-- Each tenant correlates its own events
+DROP INDEX idx_incidents_active_correlation_key;
+CREATE UNIQUE INDEX idx_incidents_active_correlation_key ON incidents(tenant_id, correlation_key)
+    WHERE correlation_key IS NOT NULL AND status IN ('open', 'investigating');
+
+CREATE INDEX idx_alerts_tenant_id ON alerts(tenant_id, created_at);
+CREATE INDEX idx_incidents_tenant_id ON incidents(tenant_id, created_at);
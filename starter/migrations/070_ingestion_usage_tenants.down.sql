@@ -0,0 +1,14 @@
+-- Merge each source's usage across tenants before dropping the column
+CREATE TEMP TABLE merged_ingestion_usage AS
+SELECT scope, key, day, SUM(events)::BIGINT AS events, SUM(metrics)::BIGINT AS metrics
+FROM ingestion_usage
+GROUP BY scope, key, day;
+
+DELETE FROM ingestion_usage;
+ALTER TABLE ingestion_usage DROP CONSTRAINT ingestion_usage_pkey;
+ALTER TABLE ingestion_usage DROP COLUMN tenant_id;
+ALTER TABLE ingestion_usage ADD PRIMARY KEY (scope, key, day);
+
+INSERT INTO ingestion_usage (scope, key, day, events, metrics)
+SELECT scope, key, day, events, metrics FROM merged_ingestion_usage;
+DROP TABLE merged_ingestion_usage;
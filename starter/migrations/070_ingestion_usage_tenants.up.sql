@@ -0,0 +1,12 @@
+-- Ingestion usage is counted per tenant so each tenant's stats only show its
+-- own users and sources. Quotas stay instance-wide: a source quota applies to
+-- the source's usage summed across tenants. Existing user rows go to the
+-- user's tenant and source rows to the default tenant.
+ALTER TABLE ingestion_usage ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE ingestion_usage i SET tenant_id = u.tenant_id
+FROM users u
+WHERE i.scope = 'user' AND i.key = u.id::TEXT;
+
+ALTER TABLE ingestion_usage DROP CONSTRAINT ingestion_usage_pkey,
+    ADD PRIMARY KEY (tenant_id, scope, key, day);
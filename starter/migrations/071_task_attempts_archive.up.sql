@@ -0,0 +1,15 @@
+-- Attempts of archived tasks, moved together with their task so the
+-- execution history survives archiving
+CREATE TABLE task_attempts_archive (
+    id UUID PRIMARY KEY,
+    task_id UUID NOT NULL REFERENCES tasks_archive(id) ON DELETE CASCADE,
+    attempt_number INTEGER NOT NULL,
+    worker_id TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ,
+    duration_ms BIGINT,
+    error TEXT,
+    error_class TEXT
+);
+
+CREATE INDEX idx_task_attempts_archive_task_id ON task_attempts_archive(task_id, started_at);
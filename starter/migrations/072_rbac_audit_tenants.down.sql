@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_rbac_audit_tenant_id;
+ALTER TABLE rbac_audit DROP COLUMN IF EXISTS tenant_id;
@@ -0,0 +1,15 @@
+-- RBAC audit entries belong to a tenant so each tenant's admins only see
+-- their own: the actor's tenant, or the target user's for changes made by an
+-- identity provider or the CLI, and otherwise the default tenant
+ALTER TABLE rbac_audit ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+
+ALTER TABLE rbac_audit DISABLE TRIGGER rbac_audit_append_only;
+UPDATE rbac_audit a SET tenant_id = COALESCE(
+    (SELECT tenant_id FROM users WHERE id = a.actor_id),
+    (SELECT tenant_id FROM users WHERE id = a.target_id AND a.target_type = 'user'),
+    a.tenant_id
+);
+ALTER TABLE rbac_audit ENABLE TRIGGER rbac_audit_append_only;
+
+CREATE INDEX idx_rbac_audit_tenant_id ON rbac_audit(tenant_id, created_at DESC);
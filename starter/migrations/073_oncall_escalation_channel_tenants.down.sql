@@ -0,0 +1,12 @@
+ALTER TABLE alert_routing_rules DROP CONSTRAINT IF EXISTS alert_routing_rules_tenant_id_name_key;
+ALTER TABLE alert_routing_rules DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE alert_routing_rules ADD CONSTRAINT alert_routing_rules_name_key UNIQUE (name);
+ALTER TABLE notification_channels DROP CONSTRAINT IF EXISTS notification_channels_tenant_id_name_key;
+ALTER TABLE notification_channels DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE notification_channels ADD CONSTRAINT notification_channels_name_key UNIQUE (name);
+ALTER TABLE incident_escalation_policies DROP CONSTRAINT IF EXISTS incident_escalation_policies_tenant_id_name_key;
+ALTER TABLE incident_escalation_policies DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE incident_escalation_policies ADD CONSTRAINT incident_escalation_policies_name_key UNIQUE (name);
+ALTER TABLE oncall_schedules DROP CONSTRAINT IF EXISTS oncall_schedules_tenant_id_name_key;
+ALTER TABLE oncall_schedules DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE oncall_schedules ADD CONSTRAINT oncall_schedules_name_key UNIQUE (name);
@@ -0,0 +1,36 @@
+-- On-call schedules, escalation policies, notification channels and alert
+-- routing rules belong to a tenant like the alerts and incidents using them.
+-- Existing rows go to their creator's tenant, and the rest to the default tenant.
+ALTER TABLE oncall_schedules ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE oncall_schedules s SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = s.created_by;
+
+ALTER TABLE incident_escalation_policies ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE incident_escalation_policies p SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = p.created_by;
+
+ALTER TABLE notification_channels ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE notification_channels c SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = c.created_by;
+
+ALTER TABLE alert_routing_rules ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE alert_routing_rules r SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = r.created_by;
+
+-- Names are unique within a tenant
+ALTER TABLE oncall_schedules DROP CONSTRAINT oncall_schedules_name_key,
+    ADD CONSTRAINT oncall_schedules_tenant_id_name_key UNIQUE (tenant_id, name);
+ALTER TABLE incident_escalation_policies DROP CONSTRAINT incident_escalation_policies_name_key,
+    ADD CONSTRAINT incident_escalation_policies_tenant_id_name_key UNIQUE (tenant_id, name);
+ALTER TABLE notification_channels DROP CONSTRAINT notification_channels_name_key,
+    ADD CONSTRAINT notification_channels_tenant_id_name_key UNIQUE (tenant_id, name);
+ALTER TABLE alert_routing_rules DROP CONSTRAINT alert_routing_rules_name_key,
+    ADD CONSTRAINT alert_routing_rules_tenant_id_name_key UNIQUE (tenant_id, name);
@@ -0,0 +1,20 @@
+CREATE OR REPLACE FUNCTION notify_alert_status_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('realtime', json_build_object(
+        'topic', 'alerts',
+        'data', json_build_object(
+            'id', NEW.id,
+            'name', NEW.name,
+            'status', NEW.status,
+            'severity', NEW.severity,
+            'triggered_at', NEW.triggered_at,
+            'resolved_at', NEW.resolved_at
+        )
+    )::TEXT);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
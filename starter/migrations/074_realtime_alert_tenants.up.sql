@@ -0,0 +1,22 @@
+-- Alert notifications carry the alert's tenant so that only its users receive them
+CREATE OR REPLACE FUNCTION notify_alert_status_changed()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.status IS NOT DISTINCT FROM NEW.status THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('realtime', json_build_object(
+        'topic', 'alerts',
+        'tenant_id', NEW.tenant_id,
+        'data', json_build_object(
+            'id', NEW.id,
+            'name', NEW.name,
+            'status', NEW.status,
+            'severity', NEW.severity,
+            'triggered_at', NEW.triggered_at,
+            'resolved_at', NEW.resolved_at
+        )
+    )::TEXT);
+    RETURN NULL;
+END;
+$$ language 'plpgsql';
@@ -0,0 +1,3 @@
+-- Bearer token identity providers use to provision a tenant's users through SCIM;
+-- only the SHA-256 hash is kept
+ALTER TABLE tenants ADD COLUMN scim_token_hash TEXT UNIQUE;
@@ -0,0 +1,3 @@
+ALTER TABLE uptime_checks DROP CONSTRAINT IF EXISTS uptime_checks_tenant_id_name_key;
+ALTER TABLE uptime_checks DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE uptime_checks ADD CONSTRAINT uptime_checks_name_key UNIQUE (name);
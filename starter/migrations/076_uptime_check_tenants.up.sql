@@ -0,0 +1,15 @@
+-- Uptime checks belong to a tenant like the alerts they fire. Existing checks
+-- go to their alert's tenant, then their creator's, and the rest to the
+-- default tenant.
+ALTER TABLE uptime_checks ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE uptime_checks c SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = c.created_by;
+UPDATE uptime_checks c SET tenant_id = a.tenant_id
+FROM alerts a
+WHERE a.id = c.alert_id;
+
+-- Names are unique within a tenant
+ALTER TABLE uptime_checks DROP CONSTRAINT uptime_checks_name_key,
+    ADD CONSTRAINT uptime_checks_tenant_id_name_key UNIQUE (tenant_id, name);
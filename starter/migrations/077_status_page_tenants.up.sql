@@ -0,0 +1,8 @@
+-- Status pages belong to a tenant like the checks and incidents they list.
+-- Existing pages go to their creator's tenant, and the rest to the default
+-- tenant. Slugs stay unique across the deployment, as pages are served by slug.
+ALTER TABLE status_pages ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE status_pages p SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = p.created_by;
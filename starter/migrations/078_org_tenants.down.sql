@@ -0,0 +1,3 @@
+ALTER TABLE orgs DROP CONSTRAINT IF EXISTS orgs_tenant_id_slug_key;
+ALTER TABLE orgs DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE orgs ADD CONSTRAINT orgs_slug_key UNIQUE (slug);
@@ -0,0 +1,12 @@
+-- Organizations belong to a tenant like the tasks and events scoped to them.
+-- Existing organizations go to their creator's tenant, and the rest to the
+-- default tenant.
+ALTER TABLE orgs ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE orgs o SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = o.created_by;
+
+-- Slugs are unique within a tenant
+ALTER TABLE orgs DROP CONSTRAINT orgs_slug_key,
+    ADD CONSTRAINT orgs_tenant_id_slug_key UNIQUE (tenant_id, slug);
@@ -0,0 +1,3 @@
+ALTER TABLE recording_rules DROP CONSTRAINT IF EXISTS recording_rules_tenant_id_name_key;
+ALTER TABLE recording_rules DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE recording_rules ADD CONSTRAINT recording_rules_name_key UNIQUE (name);
@@ -0,0 +1,12 @@
+-- Recording rules belong to a tenant and read and write its metrics.
+-- Existing rules go to their creator's tenant, and the rest to the default
+-- tenant.
+ALTER TABLE recording_rules ADD COLUMN tenant_id UUID NOT NULL
+    DEFAULT '00000000-0000-0000-0000-000000000001' REFERENCES tenants(id);
+UPDATE recording_rules r SET tenant_id = u.tenant_id
+FROM users u
+WHERE u.id = r.created_by;
+
+-- Each tenant stores its own metric names
+ALTER TABLE recording_rules DROP CONSTRAINT recording_rules_name_key,
+    ADD CONSTRAINT recording_rules_tenant_id_name_key UNIQUE (tenant_id, name);
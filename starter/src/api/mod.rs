@@ -1,11 +1,14 @@
 //! API layer types and utilities
 //!
 //! This module contains types and utilities specific to the HTTP API layer,
-//! including response formats, pagination, and request handling utilities.
+//! including response formats, pagination, API versions, and request handling
+//! utilities.
 
 pub mod pagination;
 pub mod response;
+pub mod version;
 
 // Re-export commonly used API types
 pub use pagination::{PaginatedResponse, PaginationInfo, PaginationParams};
 pub use response::{ApiResponse, ErrorDetail, ErrorResponse};
+pub use version::ApiVersion;
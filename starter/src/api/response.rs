@@ -51,4 +51,7 @@ pub struct ErrorDetail {
     pub code: String,
     /// Human-readable error message
     pub message: String,
+    /// Machine-readable context for some errors, such as the metric and limit of `CARDINALITY_LIMIT_EXCEEDED`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
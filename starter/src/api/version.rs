@@ -0,0 +1,77 @@
+//! API versions
+//!
+//! Every version serves the same router under its own prefix, and handlers
+//! take [`ApiVersion`] to pick the response shape of the version the client
+//! called. Endpoints whose responses didn't change need nothing.
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use chrono::{DateTime, TimeZone, Utc};
+use std::convert::Infallible;
+
+/// Version of the API a request was made to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiVersion {
+    /// `/api/v1`, deprecated in favor of v2
+    #[default]
+    V1,
+    /// `/api/v2`
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    /// The newest version, linked from responses of deprecated ones
+    pub const LATEST: ApiVersion = ApiVersion::V2;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+            ApiVersion::V2 => "v2",
+        }
+    }
+
+    /// Path the version is served under, e.g. `/api/v2`
+    pub fn prefix(&self) -> String {
+        format!("/api/{}", self.as_str())
+    }
+
+    /// When the version was superseded, announced in its `Deprecation` header
+    pub fn deprecated_at(&self) -> Option<DateTime<Utc>> {
+        match self {
+            ApiVersion::V1 => Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).single(),
+            ApiVersion::V2 => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Read from the request extensions set by the version's router; v1 when unset
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(ApiVersion::V2.prefix(), "/api/v2");
+        assert!(ApiVersion::V1.deprecated_at().is_some());
+        assert!(ApiVersion::LATEST.deprecated_at().is_none());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::audit::{
+    models::{
+        Actor, AuditEntry, AuditResourceType, AuditRetentionPolicy, AuditRetentionSettings,
+        SetAuditRetentionRequest,
+    },
+    services::{self as audit_services, AuditFilter},
+};
+use crate::auth::AuthUser;
+use crate::tenants::services as tenant_services;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, Path, Query, State},
+    response::Json,
+    routing::{get, put},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditLogQuery {
+    /// Only entries with this action, e.g. `task_cancelled`
+    pub action: Option<String>,
+    /// Only changes made by this user
+    pub actor_id: Option<Uuid>,
+    /// Only changes to this kind of resource, e.g. `user`
+    pub resource_type: Option<String>,
+    /// Only changes to this resource; combine with `resource_type`
+    pub resource_id: Option<String>,
+    /// Only entries recorded at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries recorded before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Maximum number of entries to return (default 50, max 100)
+    pub limit: Option<i64>,
+    /// Number of entries to skip
+    pub offset: Option<i64>,
+}
+
+/// List the audit log (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/audit",
+    tag = "Audit",
+    summary = "List audit log (Admin)",
+    description = "Changes to users, tasks, roles, shares, alerts and retention settings in the admin's tenant, with who made them, the state before and after, and the reason given, newest first",
+    params(AuditLogQuery),
+    responses(
+        (status = 200, description = "Audit log entries", body = ApiResponse<Vec<AuditEntry>>),
+        (status = 400, description = "Unknown resource type", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_audit_log(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<ApiResponse<Vec<AuditEntry>>>, Error> {
+    let filter = AuditFilter {
+        action: query.action.as_deref(),
+        actor_id: query.actor_id,
+        resource_type: query
+            .resource_type
+            .as_deref()
+            .map(AuditResourceType::parse)
+            .transpose()?,
+        resource_id: query.resource_id.as_deref(),
+        since: query.since,
+        until: query.until,
+    };
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let entries = audit_services::find_entries(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        filter,
+        query.limit,
+        query.offset,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+/// Get audit log retention (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/audit/retention",
+    tag = "Audit",
+    summary = "Get audit log retention (Admin)",
+    description = "The default retention of audit log entries and the overrides per resource type",
+    responses(
+        (status = 200, description = "Audit log retention settings", body = ApiResponse<AuditRetentionSettings>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_audit_retention(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<AuditRetentionSettings>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let policies = audit_services::list_retention_policies(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(AuditRetentionSettings {
+        default_retention_days: app_state.config.audit.retention_days,
+        policies,
+    })))
+}
+
+/// Set a resource type's audit log retention (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/audit/retention/{resource_type}",
+    tag = "Audit",
+    summary = "Set audit log retention (Admin)",
+    description = "Keep entries about one kind of resource for a number of days instead of the default (admins of the default tenant only)",
+    params(
+        ("resource_type" = String, Path, description = "Resource type, e.g. `task`")
+    ),
+    request_body = SetAuditRetentionRequest,
+    responses(
+        (status = 200, description = "Retention policy saved", body = ApiResponse<AuditRetentionPolicy>),
+        (status = 400, description = "Unknown resource type or invalid retention", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_audit_retention(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+    Path(resource_type): Path<String>,
+    Json(request): Json<SetAuditRetentionRequest>,
+) -> Result<Json<ApiResponse<AuditRetentionPolicy>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Audit log retention is managed")?;
+    let resource_type = AuditResourceType::parse(&resource_type)?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let policy =
+        audit_services::set_retention_policy(conn.as_mut(), &actor, resource_type, request).await?;
+    Ok(Json(ApiResponse::success(policy)))
+}
+
+/// Remove a resource type's audit log retention (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/audit/retention/{resource_type}",
+    tag = "Audit",
+    summary = "Remove audit log retention (Admin)",
+    description = "Keep entries about one kind of resource for the default retention again (admins of the default tenant only)",
+    params(
+        ("resource_type" = String, Path, description = "Resource type, e.g. `task`")
+    ),
+    responses(
+        (status = 200, description = "Retention policy removed", body = ApiResponse<String>),
+        (status = 400, description = "Unknown resource type", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "No retention policy for this resource type", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_audit_retention(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+    Path(resource_type): Path<String>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Audit log retention is managed")?;
+    let resource_type = AuditResourceType::parse(&resource_type)?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    audit_services::delete_retention_policy(conn.as_mut(), &actor, resource_type).await?;
+    Ok(Json(ApiResponse::success(format!(
+        "Retention policy for '{resource_type}' entries removed"
+    ))))
+}
+
+/// Audit log routes (admin role required), nested next to the RBAC audit log
+pub fn audit_log_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_audit_log))
+        .route("/retention", get(get_audit_retention))
+        .route(
+            "/retention/{resource_type}",
+            put(set_audit_retention).delete(delete_audit_retention),
+        )
+}
@@ -0,0 +1,16 @@
+//! Append-only audit log of changes across the application
+//!
+//! Services record who changed what with [`record`] (or [`try_record`] inside
+//! a transaction that should roll back with the log entry), the state before
+//! and after, and a reason. The reason comes from the request body where one
+//! exists, otherwise from the `X-Audit-Reason` header, so any endpoint can
+//! carry one without growing a `reason` field. Entries are kept for
+//! `audit.retention_days` unless their resource type has its own policy, and
+//! the database rejects any other edit or delete.
+
+pub mod api;
+pub mod models;
+pub mod services;
+
+pub use models::{Actor, AuditEntry, AuditResource, AuditResourceType};
+pub use services::{record, try_record};
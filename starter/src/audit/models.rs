@@ -0,0 +1,269 @@
+use crate::auth::AuthUser;
+use crate::{Error, Result};
+use axum::{extract::FromRequestParts, http::HeaderName, http::request::Parts};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use uuid::Uuid;
+
+/// Header carrying the reason for a change, for endpoints without a `reason` field
+pub const AUDIT_REASON_HEADER: HeaderName = HeaderName::from_static("x-audit-reason");
+
+pub const MAX_REASON_LENGTH: usize = 500;
+pub const MAX_RETENTION_DAYS: i32 = 3650;
+
+/// What kind of thing an entry's `resource_id` refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditResourceType {
+    User,
+    Task,
+    /// A custom role
+    Role,
+    /// A permission bundle
+    Bundle,
+    Share,
+    /// A monitoring alert
+    Alert,
+    /// A metric's retention override
+    Metric,
+    /// An audit log retention policy
+    Audit,
+    /// The maintenance mode toggle
+    Maintenance,
+}
+
+impl AuditResourceType {
+    pub const ALL: [AuditResourceType; 9] = [
+        AuditResourceType::User,
+        AuditResourceType::Task,
+        AuditResourceType::Role,
+        AuditResourceType::Bundle,
+        AuditResourceType::Share,
+        AuditResourceType::Alert,
+        AuditResourceType::Metric,
+        AuditResourceType::Audit,
+        AuditResourceType::Maintenance,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditResourceType::User => "user",
+            AuditResourceType::Task => "task",
+            AuditResourceType::Role => "role",
+            AuditResourceType::Bundle => "bundle",
+            AuditResourceType::Share => "share",
+            AuditResourceType::Alert => "alert",
+            AuditResourceType::Metric => "metric",
+            AuditResourceType::Audit => "audit",
+            AuditResourceType::Maintenance => "maintenance",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|resource_type| resource_type.as_str() == value)
+            .ok_or_else(|| {
+                Error::validation("resource_type", &format!("Unknown resource type '{value}'"))
+            })
+    }
+}
+
+impl fmt::Display for AuditResourceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The thing a change was made to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditResource {
+    pub resource_type: AuditResourceType,
+    pub id: String,
+}
+
+impl AuditResource {
+    pub fn new(resource_type: AuditResourceType, id: impl ToString) -> Self {
+        Self {
+            resource_type,
+            id: id.to_string(),
+        }
+    }
+}
+
+/// Who made a change, in which tenant, and why
+///
+/// Extracted from a request, it is the signed-in user with the reason from
+/// the `X-Audit-Reason` header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Actor {
+    /// `None` for changes made by the system, e.g. a background job
+    pub user_id: Option<Uuid>,
+    /// `None` to use the actor's tenant
+    pub tenant_id: Option<Uuid>,
+    pub reason: Option<String>,
+}
+
+impl Actor {
+    pub fn user(user_id: Uuid) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..Self::default()
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// Use a reason given in the request body over the header's
+    pub fn with_reason(mut self, reason: Option<String>) -> Self {
+        if let Some(reason) = normalize_reason(reason) {
+            self.reason = Some(reason);
+        }
+        self
+    }
+}
+
+impl From<&AuthUser> for Actor {
+    fn from(auth_user: &AuthUser) -> Self {
+        Self {
+            user_id: Some(auth_user.id),
+            tenant_id: Some(auth_user.tenant_id),
+            reason: None,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let actor = parts
+            .extensions
+            .get::<AuthUser>()
+            .map(Actor::from)
+            .unwrap_or_default();
+        let reason = parts
+            .headers
+            .get(AUDIT_REASON_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok(actor.with_reason(reason))
+    }
+}
+
+/// Trimmed and capped at [`MAX_REASON_LENGTH`] characters; `None` when blank
+pub fn normalize_reason(reason: Option<String>) -> Option<String> {
+    let reason = reason?;
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return None;
+    }
+    Some(reason.chars().take(MAX_REASON_LENGTH).collect())
+}
+
+/// Entry in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    /// Who made the change; `None` for the system
+    pub actor_id: Option<Uuid>,
+    /// What was done, e.g. `user_status_changed` or `task_cancelled`
+    pub action: String,
+    /// See AuditResourceType, e.g. `task`
+    pub resource_type: String,
+    pub resource_id: String,
+    /// State before the change; `None` when something was created
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    /// State after the change; `None` when something was deleted
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Retention overriding `audit.retention_days` for one resource type
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditRetentionPolicy {
+    pub resource_type: String,
+    pub retention_days: i32,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Configured default plus per-resource-type overrides
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditRetentionSettings {
+    /// Days entries are kept; 0 keeps them forever
+    pub default_retention_days: u32,
+    pub policies: Vec<AuditRetentionPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetAuditRetentionRequest {
+    #[schema(minimum = 1, maximum = 3650)]
+    pub retention_days: i32,
+}
+
+impl SetAuditRetentionRequest {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_RETENTION_DAYS).contains(&self.retention_days) {
+            return Err(Error::validation(
+                "retention_days",
+                &format!("Retention must be between 1 and {MAX_RETENTION_DAYS} days"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_types_round_trip() {
+        for resource_type in AuditResourceType::ALL {
+            assert_eq!(
+                AuditResourceType::parse(resource_type.as_str()).unwrap(),
+                resource_type
+            );
+            assert_eq!(
+                serde_json::to_value(resource_type).unwrap(),
+                resource_type.as_str()
+            );
+        }
+        assert!(AuditResourceType::parse("invoice").is_err());
+    }
+
+    #[test]
+    fn test_body_reason_wins_over_header() {
+        let actor = Actor {
+            reason: Some("from header".to_string()),
+            ..Actor::user(Uuid::new_v4())
+        };
+        assert_eq!(
+            actor
+                .clone()
+                .with_reason(Some(" from body ".to_string()))
+                .reason,
+            Some("from body".to_string())
+        );
+        assert_eq!(
+            actor.with_reason(Some("  ".to_string())).reason,
+            Some("from header".to_string())
+        );
+        assert_eq!(
+            normalize_reason(Some("x".repeat(600))).map(|r| r.len()),
+            Some(MAX_REASON_LENGTH)
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::audit::models::{
+    Actor, AuditEntry, AuditResource, AuditResourceType, AuditRetentionPolicy,
+    SetAuditRetentionRequest, normalize_reason,
+};
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Append a change to the log, returning a failure so a surrounding
+/// transaction rolls the change back with it
+///
+/// Entries are written to the actor's tenant unless one is given, and to the
+/// default tenant for the system.
+pub async fn try_record(
+    conn: &mut DbConn,
+    actor: &Actor,
+    action: &str,
+    resource: &AuditResource,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_log
+            (tenant_id, actor_id, action, resource_type, resource_id, before, after, reason)
+        VALUES (
+            COALESCE($1, (SELECT tenant_id FROM users WHERE id = $2), $3),
+            $2, $4, $5, $6, $7, $8, $9
+        )
+        "#,
+        actor.tenant_id,
+        actor.user_id,
+        DEFAULT_TENANT_ID,
+        action,
+        resource.resource_type.as_str(),
+        resource.id,
+        before,
+        after,
+        normalize_reason(actor.reason.clone())
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Append a change to the log after it was made, logging a failure instead
+/// of failing the request
+pub async fn record(
+    conn: &mut DbConn,
+    actor: &Actor,
+    action: &str,
+    resource: &AuditResource,
+    before: Option<Value>,
+    after: Option<Value>,
+) {
+    if let Err(e) = try_record(conn, actor, action, resource, before, after).await {
+        warn!(
+            "Failed to record {} of {} {} in the audit log: {}",
+            action, resource.resource_type, resource.id, e
+        );
+    }
+}
+
+/// Filters of [`find_entries`]; unset fields match everything
+#[derive(Debug, Default)]
+pub struct AuditFilter<'a> {
+    pub action: Option<&'a str>,
+    pub actor_id: Option<Uuid>,
+    pub resource_type: Option<AuditResourceType>,
+    pub resource_id: Option<&'a str>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// A tenant's entries matching every filter, newest first
+pub async fn find_entries(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    filter: AuditFilter<'_>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<AuditEntry>> {
+    let limit = limit.unwrap_or(50).clamp(1, 100);
+    let offset = offset.unwrap_or(0).max(0);
+
+    sqlx::query_as!(
+        AuditEntry,
+        r#"
+        SELECT id, tenant_id, actor_id, action, resource_type, resource_id,
+               before, after, reason, created_at
+        FROM audit_log
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR action = $2)
+          AND ($3::uuid IS NULL OR actor_id = $3)
+          AND ($4::text IS NULL OR resource_type = $4)
+          AND ($5::text IS NULL OR resource_id = $5)
+          AND ($6::timestamptz IS NULL OR created_at >= $6)
+          AND ($7::timestamptz IS NULL OR created_at < $7)
+        ORDER BY created_at DESC, id
+        LIMIT $8 OFFSET $9
+        "#,
+        tenant_id,
+        filter.action,
+        filter.actor_id,
+        filter
+            .resource_type
+            .map(|resource_type| resource_type.as_str()),
+        filter.resource_id,
+        filter.since,
+        filter.until,
+        limit,
+        offset
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+pub async fn list_retention_policies(conn: &mut DbConn) -> Result<Vec<AuditRetentionPolicy>> {
+    sqlx::query_as!(
+        AuditRetentionPolicy,
+        r#"
+        SELECT resource_type, retention_days, updated_by, updated_at
+        FROM audit_retention_policies
+        ORDER BY resource_type
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+async fn find_retention_policy(
+    conn: &mut DbConn,
+    resource_type: AuditResourceType,
+) -> Result<Option<AuditRetentionPolicy>> {
+    sqlx::query_as!(
+        AuditRetentionPolicy,
+        r#"
+        SELECT resource_type, retention_days, updated_by, updated_at
+        FROM audit_retention_policies
+        WHERE resource_type = $1
+        "#,
+        resource_type.as_str()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Create or replace the retention of one resource type's entries
+pub async fn set_retention_policy(
+    conn: &mut DbConn,
+    actor: &Actor,
+    resource_type: AuditResourceType,
+    request: SetAuditRetentionRequest,
+) -> Result<AuditRetentionPolicy> {
+    request.validate()?;
+
+    let before = find_retention_policy(conn, resource_type).await?;
+    let policy = sqlx::query_as!(
+        AuditRetentionPolicy,
+        r#"
+        INSERT INTO audit_retention_policies (resource_type, retention_days, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (resource_type) DO UPDATE
+        SET retention_days = EXCLUDED.retention_days,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING resource_type, retention_days, updated_by, updated_at
+        "#,
+        resource_type.as_str(),
+        request.retention_days,
+        actor.user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    record(
+        conn,
+        actor,
+        "retention_policy_set",
+        &AuditResource::new(AuditResourceType::Audit, resource_type),
+        before.map(|before| serde_json::json!({ "retention_days": before.retention_days })),
+        Some(serde_json::json!({ "retention_days": policy.retention_days })),
+    )
+    .await;
+    Ok(policy)
+}
+
+/// Remove a resource type's retention so its entries use the default again
+pub async fn delete_retention_policy(
+    conn: &mut DbConn,
+    actor: &Actor,
+    resource_type: AuditResourceType,
+) -> Result<()> {
+    let deleted = sqlx::query_scalar!(
+        r#"
+        DELETE FROM audit_retention_policies
+        WHERE resource_type = $1
+        RETURNING retention_days
+        "#,
+        resource_type.as_str()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound(format!("No retention policy for '{resource_type}' entries")))?;
+
+    record(
+        conn,
+        actor,
+        "retention_policy_removed",
+        &AuditResource::new(AuditResourceType::Audit, resource_type),
+        Some(serde_json::json!({ "retention_days": deleted })),
+        None,
+    )
+    .await;
+    Ok(())
+}
+
+/// Delete entries older than their resource type's retention, or the default
+/// (`None` keeps entries without a policy forever)
+pub async fn purge_expired(pool: &DbPool, default_retention_days: Option<i32>) -> Result<u64> {
+    let mut tx = pool.begin().await.map_err(Error::from_sqlx)?;
+    // The append-only trigger lets deletes through for this transaction only
+    sqlx::query_scalar!("SELECT set_config('starter.audit_purge', 'on', true)")
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM audit_log
+        WHERE created_at < NOW() - make_interval(days => COALESCE(
+            (SELECT retention_days FROM audit_retention_policies p
+             WHERE p.resource_type = audit_log.resource_type),
+            $1
+        ))
+        "#,
+        default_retention_days
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(result.rows_affected())
+}
+
+/// Background job deleting audit log entries past their retention
+pub async fn audit_purge_job(pool: DbPool, retention_days: u32, run_interval: Duration) {
+    let default_retention_days = (retention_days > 0).then_some(retention_days as i32);
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        match purge_expired(&pool, default_retention_days).await {
+            Ok(deleted) if deleted > 0 => {
+                info!("Audit log purge: {} entries deleted", deleted)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to purge the audit log: {}", e),
+        }
+    }
+}
@@ -1,35 +1,104 @@
 use crate::auth::{
-    AuthUser,
-    models::{LoginRequest, LoginResponse, RefreshResponse, RegisterRequest},
-    services as auth_services,
+    AuthUser, legal,
+    models::{
+        AcceptLegalDocumentsRequest, CreateApiKeyRequest, CreateScopedSessionRequest,
+        CreateServiceAccountRequest, GenerateRecoveryCodesRequest, LegalDocument,
+        LegalDocumentVersion, LegalStatus, LoginRequest, LoginResponse,
+        PublishLegalDocumentRequest, RecoverAccountRequest, RecoveryCodeStatus, RecoveryCodes,
+        RefreshResponse, RegisterRequest, Registration, ResendVerificationRequest, ScopedSession,
+        ScopedSessionToken, ServiceAccount, ServiceAccountKey, ServiceAccountKeyToken,
+        VerifyEmailRequest,
+    },
+    recovery, scoped, service_accounts, services as auth_services, verification,
 };
-use crate::users::models::UserProfile;
+use crate::core::config::AppConfig;
+use crate::maintenance::services as maintenance_services;
+use crate::rbac::models::EffectivePermissions;
+use crate::rbac::{UserRole, role_requests, services as rbac_services, sharing};
+use crate::tenants::{RequestTenant, services as tenant_services};
+use crate::users::activity;
+use crate::users::models::UserActivityAction;
 use crate::{
     AppState, Error,
     api::{ApiResponse, ErrorResponse},
 };
 use axum::{
     Router,
-    extract::{Extension, Request, State},
+    extract::{ConnectInfo, Extension, Path, Request, State},
+    http::{HeaderMap, header},
     response::Json,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
+use chrono::Utc;
+use serde_json::json;
+use sqlx::Acquire;
+use std::net::{IpAddr, SocketAddr};
+use uuid::Uuid;
+
+/// Address a request came from
+///
+/// `X-Forwarded-For` and `X-Real-IP` are only believed when the peer is one of
+/// the configured `trusted_proxies`. The client is then the last forwarded
+/// address that isn't a trusted proxy, so addresses a client puts in the
+/// header itself are never used.
+pub(crate) fn client_ip(
+    config: &AppConfig,
+    headers: &HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Option<String> {
+    let Extension(ConnectInfo(peer)) = peer?;
+    let mut client = peer.ip();
+    let mut forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    if forwarded.is_empty()
+        && let Some(real_ip) = headers.get("x-real-ip").and_then(|v| v.to_str().ok())
+    {
+        forwarded.push(real_ip.trim());
+    }
+    for address in forwarded.into_iter().rev() {
+        if !config.is_trusted_proxy(client) {
+            break;
+        }
+        match address.parse::<IpAddr>() {
+            Ok(address) => client = address,
+            Err(_) => break,
+        }
+    }
+    Some(client.to_string())
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
 
 #[utoipa::path(
     post,
     path = "/auth/login",
     tag = "Authentication",
     summary = "User login",
-    description = "Authenticate user with username/email and password",
+    description = "Authenticate user with username/email and password. Legal document versions listed in `accepted_documents` are recorded as accepted",
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = ApiResponse<LoginResponse>),
-        (status = 401, description = "Invalid credentials", body = ErrorResponse)
+        (status = 400, description = "Accepted document version is not current", body = ErrorResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 503, description = "In maintenance mode, where only admins can sign in", body = ErrorResponse)
     )
 )]
 pub async fn login(
     State(app_state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
+    Extension(tenant): Extension<RequestTenant>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(mut payload): Json<LoginRequest>,
 ) -> Result<Json<ApiResponse<crate::auth::models::LoginResponse>>, Error> {
     let mut conn = app_state
         .database
@@ -37,7 +106,37 @@ pub async fn login(
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
-    let login_response = auth_services::login(conn.as_mut(), payload).await?;
+    let accepted_documents = std::mem::take(&mut payload.accepted_documents);
+    let agent = payload.user_agent.clone().or_else(|| user_agent(&headers));
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let login_response = auth_services::login(&mut tx, payload).await?;
+    // Accounts of another tenant, or of a deactivated one, can't sign in here;
+    // dropping the transaction discards the new session
+    let user_tenant = tenant_services::user_tenant_cached(
+        &mut tx,
+        &app_state.cache,
+        app_state.config.tenant_cache_ttl(),
+        login_response.user.id,
+    )
+    .await?;
+    if !user_tenant.is_active || !tenant.admits(user_tenant.id) {
+        return Err(Error::InvalidCredentials);
+    }
+    // Only admins can sign in during maintenance
+    if login_response.user.role != UserRole::Admin {
+        maintenance_services::check(&app_state).await?;
+    }
+    legal::accept_documents(
+        &mut tx,
+        login_response.user.id,
+        &accepted_documents,
+        client_ip(&app_state.config, &headers, peer).as_deref(),
+        agent.as_deref(),
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
     Ok(Json(ApiResponse::success(login_response)))
 }
 
@@ -46,26 +145,58 @@ pub async fn login(
     path = "/auth/register",
     tag = "Authentication",
     summary = "User registration",
-    description = "Register a new user account",
+    description = "Register a new user account in the tenant named with `X-Tenant` or the subdomain, or the default tenant. Legal document versions listed in `accepted_documents` are recorded as accepted. With `generate_recovery_codes`, the response includes the account's recovery codes, shown this once",
     request_body = RegisterRequest,
     responses(
-        (status = 200, description = "Registration successful", body = ApiResponse<UserProfile>),
+        (status = 200, description = "Registration successful", body = ApiResponse<Registration>),
         (status = 400, description = "Validation error", body = ErrorResponse),
         (status = 409, description = "User already exists", body = ErrorResponse)
     )
 )]
 pub async fn register(
     State(app_state): State<AppState>,
-    Json(payload): Json<RegisterRequest>,
-) -> Result<Json<ApiResponse<crate::users::models::UserProfile>>, Error> {
+    Extension(tenant): Extension<RequestTenant>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(mut payload): Json<RegisterRequest>,
+) -> Result<Json<ApiResponse<Registration>>, Error> {
     let mut conn = app_state
         .database
         .pool
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
-    let user_profile = auth_services::register(conn.as_mut(), payload).await?;
-    Ok(Json(ApiResponse::success(user_profile)))
+    let accepted_documents = std::mem::take(&mut payload.accepted_documents);
+    let generate_recovery_codes = payload.generate_recovery_codes;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let user_profile = auth_services::register(&mut tx, payload, tenant.id_or_default()).await?;
+    legal::accept_documents(
+        &mut tx,
+        user_profile.id,
+        &accepted_documents,
+        client_ip(&app_state.config, &headers, peer).as_deref(),
+        user_agent(&headers).as_deref(),
+    )
+    .await?;
+    let recovery_codes = if generate_recovery_codes {
+        Some(
+            recovery::generate_codes(
+                &mut tx,
+                user_profile.id,
+                app_state.config.auth.recovery_code_count,
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(Json(ApiResponse::success(Registration {
+        user: user_profile,
+        recovery_codes,
+    })))
 }
 
 #[utoipa::path(
@@ -84,7 +215,7 @@ pub async fn register(
 )]
 pub async fn logout(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
+    Extension(auth_user): Extension<AuthUser>,
     req: Request,
 ) -> Result<Json<ApiResponse<String>>, Error> {
     // Extract token from Authorization header
@@ -103,6 +234,8 @@ pub async fn logout(
         .map_err(Error::from_sqlx)?;
 
     auth_services::logout(conn.as_mut(), token).await?;
+    auth_services::forget_cached_session(&app_state.cache, token).await;
+    app_state.permission_cache.invalidate(auth_user.id).await;
 
     Ok(Json(ApiResponse::success(
         "Logged out successfully".to_string(),
@@ -126,6 +259,7 @@ pub async fn logout(
 pub async fn logout_all(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    req: Request,
 ) -> Result<Json<ApiResponse<String>>, Error> {
     let mut conn = app_state
         .database
@@ -134,6 +268,21 @@ pub async fn logout_all(
         .await
         .map_err(Error::from_sqlx)?;
     let sessions_deleted = auth_services::logout_all(conn.as_mut(), auth_user.id).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        auth_user.id,
+    )
+    .await;
+    if let Some(token) = req
+        .headers()
+        .get("authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|auth_header| auth_header.strip_prefix("Bearer "))
+    {
+        auth_services::forget_cached_session(&app_state.cache, token).await;
+    }
+    app_state.permission_cache.invalidate(auth_user.id).await;
 
     Ok(Json(ApiResponse::success_with_message(
         "Logged out from all devices".to_string(),
@@ -159,6 +308,40 @@ pub async fn me(Extension(auth_user): Extension<AuthUser>) -> Json<ApiResponse<A
     Json(ApiResponse::success(auth_user))
 }
 
+#[utoipa::path(
+    get,
+    path = "/auth/me/permissions",
+    tag = "Authentication",
+    summary = "Get current user's permissions",
+    description = "The permissions the current session holds, after the built-in role, custom roles, denies and session scopes, plus objects shared with the user. Frontends can use it to show or hide actions; the API still checks every request",
+    responses(
+        (status = 200, description = "Effective permissions", body = ApiResponse<EffectivePermissions>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn my_permissions(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<EffectivePermissions>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let shared = sharing::shared_object_permissions(conn.as_mut(), &auth_user).await?;
+
+    Ok(Json(ApiResponse::success(EffectivePermissions {
+        role: auth_user.role,
+        permissions: rbac_services::effective_permissions(&auth_user),
+        shared,
+        scoped: auth_user.scopes.is_some(),
+    })))
+}
+
 #[utoipa::path(
     post,
     path = "/auth/refresh",
@@ -216,11 +399,674 @@ pub async fn refresh(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/resend-verification",
+    tag = "Authentication",
+    summary = "Resend verification email",
+    description = "Email a new verification link to an unverified account. The response is the same whether or not an email was sent, so it doesn't reveal which addresses have accounts",
+    request_body = ResendVerificationRequest,
+    responses(
+        (status = 200, description = "Request accepted", body = ApiResponse<String>)
+    )
+)]
+pub async fn resend_verification(
+    State(app_state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    // Failures are logged rather than returned; they only happen for real accounts
+    match verification::resend_verification(
+        conn.as_mut(),
+        app_state.services.email(),
+        &app_state.config,
+        &payload.email,
+        Utc::now(),
+    )
+    .await
+    {
+        Ok(outcome) => tracing::debug!("Verification resend: {:?}", outcome),
+        Err(e) => tracing::error!("Failed to resend verification email: {}", e),
+    }
+
+    Ok(Json(ApiResponse::success(
+        "If an unverified account uses this address, a verification email is on its way"
+            .to_string(),
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/verify-email",
+    tag = "Authentication",
+    summary = "Verify email",
+    description = "Confirm an account's email address with the token from a verification link",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 200, description = "Email verified", body = ApiResponse<String>),
+        (status = 400, description = "Invalid, used or expired link", body = ErrorResponse)
+    )
+)]
+pub async fn verify_email(
+    State(app_state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let user_id = verification::verify_email(conn.as_mut(), &payload.token).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        user_id,
+        Some(user_id),
+        UserActivityAction::EmailVerified,
+        json!({ "method": "link" }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success("Email verified".to_string())))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/recovery-codes",
+    tag = "Authentication",
+    summary = "Recovery code status",
+    description = "How many of the signed-in user's recovery codes are left. The codes themselves are only shown when they are generated",
+    responses(
+        (status = 200, description = "Recovery code status", body = ApiResponse<RecoveryCodeStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn recovery_code_status(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<RecoveryCodeStatus>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let status = recovery::code_status(conn.as_mut(), auth_user.id).await?;
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/recovery-codes",
+    tag = "Authentication",
+    summary = "Generate recovery codes",
+    description = "Replace the signed-in user's recovery codes with a new set after confirming the password. The codes are returned this once; earlier codes stop working",
+    request_body = GenerateRecoveryCodesRequest,
+    responses(
+        (status = 200, description = "New recovery codes", body = ApiResponse<RecoveryCodes>),
+        (status = 400, description = "Invalid password", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn generate_recovery_codes(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<GenerateRecoveryCodesRequest>,
+) -> Result<Json<ApiResponse<RecoveryCodes>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let codes = recovery::regenerate_codes(
+        conn.as_mut(),
+        auth_user.id,
+        &payload.password,
+        app_state.config.auth.recovery_code_count,
+    )
+    .await?;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::RecoveryCodesGenerated,
+        json!({ "count": codes.codes.len() }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success(codes)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/recover",
+    tag = "Authentication",
+    summary = "Recover account",
+    description = "Sign in with a recovery code instead of the password and set a new password, in the tenant named with `X-Tenant` or the subdomain. The code is used up and the account's other sessions end",
+    request_body = RecoverAccountRequest,
+    responses(
+        (status = 200, description = "Signed in with the new password", body = ApiResponse<LoginResponse>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Invalid or used recovery code", body = ErrorResponse)
+    )
+)]
+pub async fn recover_account(
+    State(app_state): State<AppState>,
+    Extension(tenant): Extension<RequestTenant>,
+    Json(payload): Json<RecoverAccountRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let login_response = recovery::recover_account(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.tenant_cache_ttl(),
+        &tenant,
+        payload,
+    )
+    .await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        login_response.user.id,
+    )
+    .await;
+    let status = recovery::code_status(conn.as_mut(), login_response.user.id).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        login_response.user.id,
+        Some(login_response.user.id),
+        UserActivityAction::AccountRecovered,
+        json!({ "codes_remaining": status.remaining }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success(login_response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/legal",
+    tag = "Authentication",
+    summary = "Current legal documents",
+    description = "The current version of the terms of service and privacy policy",
+    responses(
+        (status = 200, description = "Current documents", body = ApiResponse<Vec<LegalDocument>>)
+    )
+)]
+pub async fn current_legal_documents(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<LegalDocument>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let documents = legal::current_documents(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(documents)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/legal/status",
+    tag = "Authentication",
+    summary = "Legal acceptance status",
+    description = "Which current legal documents the signed-in user has accepted",
+    responses(
+        (status = 200, description = "Acceptance status", body = ApiResponse<LegalStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn legal_status(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<LegalStatus>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let status = legal::legal_status(conn.as_mut(), auth_user.id).await?;
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/legal/accept",
+    tag = "Authentication",
+    summary = "Accept legal documents",
+    description = "Record that the signed-in user accepts the given document versions, with the time, client address and user agent. Only current versions can be accepted",
+    request_body = AcceptLegalDocumentsRequest,
+    responses(
+        (status = 200, description = "Acceptance recorded", body = ApiResponse<LegalStatus>),
+        (status = 400, description = "Unknown or outdated version", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn accept_legal_documents(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+    Json(payload): Json<AcceptLegalDocumentsRequest>,
+) -> Result<Json<ApiResponse<LegalStatus>>, Error> {
+    payload.validate()?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    legal::accept_documents(
+        &mut tx,
+        auth_user.id,
+        &payload.documents,
+        client_ip(&app_state.config, &headers, peer).as_deref(),
+        user_agent(&headers).as_deref(),
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    let status = legal::legal_status(conn.as_mut(), auth_user.id).await?;
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/legal",
+    tag = "Authentication",
+    summary = "List legal document versions (Admin)",
+    description = "Every published version of the legal documents with how many users accepted it",
+    responses(
+        (status = 200, description = "Published versions", body = ApiResponse<Vec<LegalDocumentVersion>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_legal_documents(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<LegalDocumentVersion>>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Legal documents are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let documents = legal::list_documents(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(documents)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/legal",
+    tag = "Authentication",
+    summary = "Publish legal document (Admin)",
+    description = "Publish the next version of the terms of service or privacy policy. Users have to accept the new version",
+    request_body = PublishLegalDocumentRequest,
+    responses(
+        (status = 200, description = "Document published", body = ApiResponse<LegalDocument>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn publish_legal_document(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<PublishLegalDocumentRequest>,
+) -> Result<Json<ApiResponse<LegalDocument>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Legal documents are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let document = legal::publish_document(conn.as_mut(), payload, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(document)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/scoped-sessions",
+    tag = "Authentication",
+    summary = "Create scoped session",
+    description = "Issue a session token limited to some of your permissions, e.g. `tasks:read`, for automation. Requests made with it need both your permission and a matching scope; it can only reach task and user endpoints and can't be refreshed. The token is returned this once",
+    request_body = CreateScopedSessionRequest,
+    responses(
+        (status = 200, description = "Scoped session created", body = ApiResponse<ScopedSessionToken>),
+        (status = 400, description = "Invalid scope, or a permission you don't have", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Scoped sessions can't create sessions", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_scoped_session(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateScopedSessionRequest>,
+) -> Result<Json<ApiResponse<ScopedSessionToken>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let created = scoped::create_scoped_session(
+        conn.as_mut(),
+        &auth_user,
+        payload,
+        app_state.config.session_duration(),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/auth/scoped-sessions",
+    tag = "Authentication",
+    summary = "List scoped sessions",
+    description = "Your scoped sessions that haven't expired or been revoked, newest first",
+    responses(
+        (status = 200, description = "Scoped sessions", body = ApiResponse<Vec<ScopedSession>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_scoped_sessions(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ScopedSession>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let sessions = scoped::list_scoped_sessions(conn.as_mut(), auth_user.id).await?;
+    Ok(Json(ApiResponse::success(sessions)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/auth/scoped-sessions/{id}",
+    tag = "Authentication",
+    summary = "Revoke scoped session",
+    params(
+        ("id" = Uuid, Path, description = "Scoped session ID")
+    ),
+    responses(
+        (status = 200, description = "Scoped session revoked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Scoped session not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_scoped_session(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    scoped::revoke_scoped_session(conn.as_mut(), auth_user.id, id).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        auth_user.id,
+    )
+    .await;
+    Ok(Json(ApiResponse::success(
+        "Scoped session revoked".to_string(),
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/service-accounts",
+    tag = "Authentication",
+    summary = "List service accounts (Admin)",
+    description = "Service accounts of the admin's tenant that aren't deleted, by username, with how many active API keys each has",
+    responses(
+        (status = 200, description = "Service accounts", body = ApiResponse<Vec<ServiceAccount>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_service_accounts(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ServiceAccount>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let accounts =
+        service_accounts::list_service_accounts(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(accounts)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/service-accounts",
+    tag = "Authentication",
+    summary = "Create service account (Admin)",
+    description = "Create a non-human account for a worker or integration. It has no password and can't log in; it authenticates only with the API keys issued for it, and is left out of user listings",
+    request_body = CreateServiceAccountRequest,
+    responses(
+        (status = 200, description = "Service account created", body = ApiResponse<ServiceAccount>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required, or the admin role without an approved role request", body = ErrorResponse),
+        (status = 409, description = "Username already taken", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_service_account(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateServiceAccountRequest>,
+) -> Result<Json<ApiResponse<ServiceAccount>>, Error> {
+    role_requests::require_admin_approval(
+        app_state.config.auth.admin_role_requires_approval,
+        payload.role.unwrap_or(UserRole::User),
+        None,
+    )?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let account =
+        service_accounts::create_service_account(conn.as_mut(), payload, auth_user.tenant_id)
+            .await?;
+    Ok(Json(ApiResponse::success(account)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/service-accounts/{id}/api-keys",
+    tag = "Authentication",
+    summary = "List service account API keys (Admin)",
+    description = "The account's API keys, including revoked ones, newest first. The keys themselves are never shown again",
+    params(
+        ("id" = Uuid, Path, description = "Service account ID")
+    ),
+    responses(
+        (status = 200, description = "API keys", body = ApiResponse<Vec<ServiceAccountKey>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Service account not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_service_account_keys(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<ServiceAccountKey>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let keys = service_accounts::list_api_keys(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(keys)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/service-accounts/{id}/api-keys",
+    tag = "Authentication",
+    summary = "Issue service account API key (Admin)",
+    description = "Issue an API key the account sends as `Authorization: Bearer <key>`. Scopes limit the key like a scoped session; without them it has the account's full permissions. The key is returned this once",
+    params(
+        ("id" = Uuid, Path, description = "Service account ID")
+    ),
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "API key issued", body = ApiResponse<ServiceAccountKeyToken>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Service account not found", body = ErrorResponse),
+        (status = 429, description = "API key quota reached", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_service_account_key(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<Json<ApiResponse<ServiceAccountKeyToken>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let issued = service_accounts::issue_api_key(
+        conn.as_mut(),
+        &app_state.config,
+        id,
+        auth_user.tenant_id,
+        payload,
+        auth_user.id,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(issued)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/service-accounts/{id}/api-keys/{key_id}",
+    tag = "Authentication",
+    summary = "Revoke service account API key (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Service account ID"),
+        ("key_id" = Uuid, Path, description = "API key ID")
+    ),
+    responses(
+        (status = 200, description = "API key revoked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_service_account_key(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, key_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    service_accounts::revoke_api_key(conn.as_mut(), id, auth_user.tenant_id, key_id).await?;
+    Ok(Json(ApiResponse::success("API key revoked".to_string())))
+}
+
 /// Public authentication routes (no authentication required)
 pub fn auth_public_routes() -> Router<AppState> {
     Router::new()
-        .route("/login", post(login))
         .route("/register", post(register))
+        .route("/resend-verification", post(resend_verification))
+        .route("/verify-email", post(verify_email))
+        .route("/legal", get(current_legal_documents))
+        .route("/recover", post(recover_account))
+}
+
+/// Sign-in route, kept open during maintenance for admins
+pub fn auth_login_routes() -> Router<AppState> {
+    Router::new().route("/login", post(login))
 }
 
 /// Protected authentication routes (authentication required)
@@ -229,5 +1075,36 @@ pub fn auth_routes() -> Router<AppState> {
         .route("/logout", post(logout))
         .route("/logout-all", post(logout_all))
         .route("/me", get(me))
+        .route("/me/permissions", get(my_permissions))
         .route("/refresh", post(refresh))
+        .route("/legal/status", get(legal_status))
+        .route("/legal/accept", post(accept_legal_documents))
+        .route(
+            "/recovery-codes",
+            get(recovery_code_status).post(generate_recovery_codes),
+        )
+        .route(
+            "/scoped-sessions",
+            get(list_scoped_sessions).post(create_scoped_session),
+        )
+        .route("/scoped-sessions/{id}", delete(revoke_scoped_session))
+}
+
+/// Legal document administration (admin role required)
+pub fn legal_admin_routes() -> Router<AppState> {
+    Router::new().route("/", get(list_legal_documents).post(publish_legal_document))
+}
+
+/// Service account administration (admin role required)
+pub fn service_accounts_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_service_accounts).post(create_service_account))
+        .route(
+            "/{id}/api-keys",
+            get(list_service_account_keys).post(create_service_account_key),
+        )
+        .route(
+            "/{id}/api-keys/{key_id}",
+            delete(revoke_service_account_key),
+        )
 }
@@ -0,0 +1,215 @@
+//! Versioned terms of service and privacy policy
+//!
+//! Publishing a document adds the next version of its kind, which becomes the
+//! current one. Users accept a specific version, and each acceptance keeps
+//! when it happened and the address and user agent it came from.
+
+use crate::auth::models::{
+    LegalDocument, LegalDocumentRef, LegalDocumentStatus, LegalDocumentVersion, LegalStatus,
+    PublishLegalDocumentRequest,
+};
+use crate::{DbConn, Error, Result};
+use uuid::Uuid;
+
+/// The current version of each document kind that has been published
+pub async fn current_documents(conn: &mut DbConn) -> Result<Vec<LegalDocument>> {
+    sqlx::query_as!(
+        LegalDocument,
+        r#"
+        SELECT DISTINCT ON (kind) id, kind, version, title, content, published_by, published_at
+        FROM legal_documents
+        ORDER BY kind, version DESC
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Every published version, newest first within each kind
+pub async fn list_documents(conn: &mut DbConn) -> Result<Vec<LegalDocumentVersion>> {
+    sqlx::query_as!(
+        LegalDocumentVersion,
+        r#"
+        SELECT d.id, d.kind, d.version, d.title, d.published_by, d.published_at,
+               (SELECT COUNT(*) FROM legal_acceptances a WHERE a.document_id = d.id)
+                   AS "acceptances!"
+        FROM legal_documents d
+        ORDER BY d.kind, d.version DESC
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Publish the next version of a document; users must accept it again
+pub async fn publish_document(
+    conn: &mut DbConn,
+    request: PublishLegalDocumentRequest,
+    published_by: Uuid,
+) -> Result<LegalDocument> {
+    request.validate()?;
+
+    sqlx::query_as!(
+        LegalDocument,
+        r#"
+        INSERT INTO legal_documents (kind, version, title, content, published_by)
+        SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4
+        FROM legal_documents WHERE kind = $1
+        RETURNING id, kind, version, title, content, published_by, published_at
+        "#,
+        request.kind.as_str(),
+        request.title.trim(),
+        request.content,
+        published_by
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::Conflict(format!(
+            "Another version of the {} was published at the same time",
+            request.kind.as_str()
+        )),
+        _ => Error::from_sqlx(e),
+    })
+}
+
+/// Record that a user accepted the given document versions
+///
+/// Only current versions can be accepted, so a client showing an outdated
+/// text gets an error instead of an acceptance the user never gave.
+/// Accepting a version again keeps the first acceptance.
+pub async fn accept_documents(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    documents: &[LegalDocumentRef],
+    ip_address: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<()> {
+    for document in documents {
+        let found = sqlx::query!(
+            r#"
+            SELECT d.id,
+                   d.version = (SELECT MAX(version) FROM legal_documents WHERE kind = d.kind)
+                       AS "current!"
+            FROM legal_documents d
+            WHERE d.kind = $1 AND d.version = $2
+            "#,
+            document.kind.as_str(),
+            document.version
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        let document_id = match found {
+            Some(found) if found.current => found.id,
+            Some(_) => {
+                return Err(Error::validation(
+                    "documents",
+                    &format!(
+                        "Version {} of the {} has been replaced by a newer one",
+                        document.version,
+                        document.kind.as_str()
+                    ),
+                ));
+            }
+            None => {
+                return Err(Error::validation(
+                    "documents",
+                    &format!(
+                        "There is no version {} of the {}",
+                        document.version,
+                        document.kind.as_str()
+                    ),
+                ));
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO legal_acceptances (user_id, document_id, ip_address, user_agent)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, document_id) DO NOTHING
+            "#,
+            user_id,
+            document_id,
+            ip_address,
+            user_agent
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    }
+
+    Ok(())
+}
+
+/// Which current documents the user has accepted
+pub async fn legal_status(conn: &mut DbConn, user_id: Uuid) -> Result<LegalStatus> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT d.kind, d.version, d.title, d.published_at,
+               accepted.version AS "accepted_version?",
+               accepted.accepted_at AS "accepted_at?"
+        FROM (
+            SELECT DISTINCT ON (kind) kind, version, title, published_at
+            FROM legal_documents
+            ORDER BY kind, version DESC
+        ) d
+        LEFT JOIN LATERAL (
+            SELECT o.version, a.accepted_at
+            FROM legal_acceptances a
+            JOIN legal_documents o ON o.id = a.document_id
+            WHERE a.user_id = $1 AND o.kind = d.kind
+            ORDER BY o.version DESC
+            LIMIT 1
+        ) accepted ON true
+        ORDER BY d.kind
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let documents: Vec<LegalDocumentStatus> = rows
+        .into_iter()
+        .map(|row| LegalDocumentStatus {
+            kind: row.kind.into(),
+            version: row.version,
+            title: row.title,
+            published_at: row.published_at,
+            accepted: row.accepted_version == Some(row.version),
+            accepted_version: row.accepted_version,
+            accepted_at: row.accepted_at,
+        })
+        .collect();
+    let acceptance_required = documents.iter().any(|document| !document.accepted);
+
+    Ok(LegalStatus {
+        documents,
+        acceptance_required,
+    })
+}
+
+/// Whether a current document is still waiting for the user's acceptance
+pub async fn acceptance_required(conn: &mut DbConn, user_id: Uuid) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM legal_documents d
+            WHERE d.version = (SELECT MAX(version) FROM legal_documents WHERE kind = d.kind)
+              AND NOT EXISTS(
+                  SELECT 1 FROM legal_acceptances a
+                  WHERE a.document_id = d.id AND a.user_id = $1
+              )
+        ) AS "required!"
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
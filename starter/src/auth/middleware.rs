@@ -1,12 +1,18 @@
 use crate::AppState;
 use crate::Error;
-use crate::auth::services;
-use crate::rbac::UserRole;
+use crate::auth::models::LegalAcceptanceMode;
+use crate::auth::{legal, services};
+use crate::orgs::{OrgContext, services as org_services};
+use crate::rbac::models::permission_key;
+use crate::rbac::{Permission, Resource, UserRole, services as rbac_services};
+use crate::tenants::{RequestTenant, services as tenant_services};
 use axum::{
     extract::{Request, State},
+    http::{HeaderMap, header::SEC_WEBSOCKET_PROTOCOL},
     middleware::Next,
     response::Response,
 };
+use chrono::Utc;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
@@ -15,28 +21,121 @@ pub struct AuthUser {
     pub username: String,
     pub email: String,
     pub role: UserRole,
+    /// Permissions granted by the user's custom roles and permission bundles,
+    /// as `resource:permission`
+    pub permissions: Vec<String>,
+    /// Permissions denied to the user, which override any grant
+    pub denied: Vec<String>,
+    /// Permissions a scoped session or API key is limited to; `None` for full access
+    pub scopes: Option<Vec<String>>,
+    /// Organization selected with the `X-Org-Id` header, with the user's role there
+    pub org: Option<OrgContext>,
+    /// Tenant the user belongs to, which every request of theirs acts in
+    pub tenant_id: Uuid,
 }
 
-/// Extract Bearer token from Authorization header
-fn extract_bearer_token(req: &Request) -> Option<String> {
+impl AuthUser {
+    /// Whether one of the user's custom roles or permission bundles grants the permission
+    pub fn grants(&self, resource: Resource, permission: Permission) -> bool {
+        self.permissions
+            .contains(&permission_key(resource, permission))
+    }
+
+    /// Whether the permission is denied to the user, whatever their roles allow
+    pub fn denies(&self, resource: Resource, permission: Permission) -> bool {
+        self.denied.contains(&permission_key(resource, permission))
+    }
+
+    /// Whether the session's scopes, if any, cover the permission
+    pub fn in_scope(&self, resource: Resource, permission: Permission) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&permission_key(resource, permission)))
+    }
+
+    /// Organization a request targets: the one it names, or else the selected one
+    ///
+    /// Naming a different organization than the one selected is rejected.
+    pub fn org_scope(&self, requested: Option<Uuid>) -> Result<Option<Uuid>, Error> {
+        match (requested, self.org) {
+            (Some(requested), Some(org)) if requested != org.id => Err(Error::validation(
+                "org_id",
+                "Does not match the organization selected with X-Org-Id",
+            )),
+            (Some(requested), _) => Ok(Some(requested)),
+            (None, org) => Ok(org.map(|org| org.id)),
+        }
+    }
+}
+
+/// Header selecting the organization a request acts in
+pub const ORG_HEADER: &str = "x-org-id";
+
+/// Organization selected with the `X-Org-Id` header, if any
+fn selected_org(req: &Request) -> Result<Option<Uuid>, Error> {
     req.headers()
+        .get(ORG_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| Uuid::parse_str(value.trim()).ok())
+                .ok_or_else(|| Error::validation("X-Org-Id", "Expected an organization ID"))
+        })
+        .transpose()
+}
+
+/// Subprotocol browsers offer with the token to authenticate a WebSocket,
+/// since they can't set the `Authorization` header
+pub const WEBSOCKET_TOKEN_PROTOCOL: &str = "bearer";
+
+/// Extract Bearer token from the Authorization header, or else from the
+/// `bearer, <token>` protocols of a WebSocket handshake
+pub(crate) fn extract_bearer_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(auth_header) = headers
         .get("authorization")
         .and_then(|header| header.to_str().ok())
-        .and_then(|auth_header| {
-            auth_header
-                .strip_prefix("Bearer ")
-                .map(|token| token.to_string())
-        })
+    {
+        return auth_header
+            .strip_prefix("Bearer ")
+            .map(|token| token.to_string());
+    }
+    let mut protocols = headers
+        .get(SEC_WEBSOCKET_PROTOCOL)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .map(str::trim);
+    protocols.find(|protocol| *protocol == WEBSOCKET_TOKEN_PROTOCOL)?;
+    protocols.next().map(str::to_string)
+}
+
+/// Endpoints a user can reach before accepting the current legal documents
+const LEGAL_EXEMPT_PATHS: &[&str] = &[
+    "/auth/me",
+    "/auth/me/permissions",
+    "/auth/logout",
+    "/auth/logout-all",
+    "/auth/refresh",
+    "/auth/legal/status",
+    "/auth/legal/accept",
+];
+
+/// Note the user as seen; the time is stored with the next batch
+fn record_presence(app_state: &AppState, user_id: Uuid) {
+    if app_state.config.auth.last_seen_flush_interval_secs > 0 {
+        app_state.presence.observe(user_id, Utc::now());
+    }
 }
 
-/// Session-based authentication middleware
+/// Authentication middleware for session tokens and service account API keys
 pub async fn auth_middleware(
     State(app_state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, Error> {
     // Extract token from Authorization header
-    let token = match extract_bearer_token(&req) {
+    let token = match extract_bearer_token(req.headers()) {
         Some(token) => token,
         None => return Err(Error::Unauthorized),
     };
@@ -50,11 +149,18 @@ pub async fn auth_middleware(
         }
     };
 
-    // Validate session and get user
-    let user = match services::validate_session_with_user(conn.as_mut(), &token).await {
-        Ok(Some(user)) => user,
+    // Validate the session or API key and get user
+    let (user, scopes) = match services::validate_token_cached(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        &token,
+    )
+    .await
+    {
+        Ok(Some(found)) => found,
         Ok(None) => {
-            tracing::debug!("Invalid or expired session token");
+            tracing::debug!("Invalid or expired session token or API key");
             return Err(Error::Unauthorized);
         }
         Err(e) => {
@@ -69,13 +175,77 @@ pub async fn auth_middleware(
         return Err(Error::Unauthorized);
     }
 
-    // Add user info to request extensions
-    req.extensions_mut().insert(AuthUser {
+    // A token only works in its user's tenant, and not once the tenant is deactivated
+    let tenant = tenant_services::user_tenant_cached(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.tenant_cache_ttl(),
+        user.id,
+    )
+    .await?;
+    let request_tenant = req
+        .extensions()
+        .get::<RequestTenant>()
+        .copied()
+        .unwrap_or_default();
+    if !tenant.is_active || !request_tenant.admits(tenant.id) {
+        tracing::debug!("User {} is not usable in the requested tenant", user.id);
+        return Err(Error::Unauthorized);
+    }
+
+    // In enforce mode the rest of the API waits until the current documents are accepted;
+    // service accounts have nobody to accept them
+    if app_state.config.auth.legal_acceptance_mode == LegalAcceptanceMode::Enforce
+        && !user.is_service_account
+        && !LEGAL_EXEMPT_PATHS.contains(&req.uri().path())
+    {
+        match legal::acceptance_required(conn.as_mut(), user.id).await {
+            Ok(false) => {}
+            Ok(true) => return Err(Error::LegalAcceptanceRequired),
+            Err(e) => {
+                tracing::error!("Error checking legal acceptance: {}", e);
+                return Err(Error::Internal("Legal acceptance check failed".to_string()));
+            }
+        }
+    }
+
+    let permissions = match app_state
+        .permission_cache
+        .user_permissions(conn.as_mut(), user.id)
+        .await
+    {
+        Ok(permissions) => permissions,
+        Err(e) => {
+            tracing::error!("Error loading user permissions: {}", e);
+            return Err(Error::Internal("Permission lookup failed".to_string()));
+        }
+    };
+
+    let org = match selected_org(&req)? {
+        Some(org_id) => Some(
+            org_services::org_context(conn.as_mut(), user.id, user.role, tenant.id, org_id).await?,
+        ),
+        None => None,
+    };
+
+    let auth_user = AuthUser {
         id: user.id,
         username: user.username,
         email: user.email,
         role: user.role,
-    });
+        permissions: permissions.granted,
+        denied: permissions.denied,
+        scopes,
+        org,
+        tenant_id: tenant.id,
+    };
+    rbac_services::check_session_scope(&auth_user, req.uri().path(), req.method())?;
+
+    record_presence(&app_state, user.id);
+    tracing::Span::current().record("enduser.id", user.id.to_string());
+
+    // Add user info to request extensions
+    req.extensions_mut().insert(auth_user);
 
     Ok(next.run(req).await)
 }
@@ -87,20 +257,51 @@ pub async fn optional_auth_middleware(
     next: Next,
 ) -> Response {
     // Try to extract token
-    if let Some(token) = extract_bearer_token(&req) {
+    if let Some(token) = extract_bearer_token(req.headers()) {
         // Try to get database connection
         if let Ok(mut conn) = app_state.database.pool.acquire().await {
-            // Try to validate session
-            if let Ok(Some(user)) =
-                services::validate_session_with_user(conn.as_mut(), &token).await
+            // Try to validate the session or API key
+            if let Ok(Some((user, scopes))) = services::validate_token_cached(
+                conn.as_mut(),
+                &app_state.cache,
+                app_state.config.session_cache_ttl(),
+                &token,
+            )
+            .await
                 && user.is_active
+                && let Ok(tenant) = tenant_services::user_tenant_cached(
+                    conn.as_mut(),
+                    &app_state.cache,
+                    app_state.config.tenant_cache_ttl(),
+                    user.id,
+                )
+                .await
+                && tenant.is_active
+                && req
+                    .extensions()
+                    .get::<RequestTenant>()
+                    .copied()
+                    .unwrap_or_default()
+                    .admits(tenant.id)
+                && let Ok(permissions) = app_state
+                    .permission_cache
+                    .user_permissions(conn.as_mut(), user.id)
+                    .await
             {
+                record_presence(&app_state, user.id);
+                tracing::Span::current().record("enduser.id", user.id.to_string());
+
                 // Add user info to request extensions
                 req.extensions_mut().insert(AuthUser {
                     id: user.id,
                     username: user.username,
                     email: user.email,
                     role: user.role,
+                    permissions: permissions.granted,
+                    denied: permissions.denied,
+                    scopes,
+                    org: None,
+                    tenant_id: tenant.id,
                 });
             }
         }
@@ -1,8 +1,13 @@
 pub mod api;
 pub mod cleanup;
+pub mod legal;
 pub mod middleware;
 pub mod models;
+pub mod recovery;
+pub mod scoped;
+pub mod service_accounts;
 pub mod services;
+pub mod verification;
 
 pub use middleware::{AuthUser, admin_middleware, auth_middleware, security_headers_middleware};
 pub use models::{ApiKey, LoginRequest, LoginResponse, RefreshResponse, RegisterRequest, Session};
@@ -15,6 +15,8 @@ pub struct Session {
     pub last_refreshed_at: Option<DateTime<Utc>>,
     pub user_agent: Option<String>,
     pub is_active: bool,
+    /// Permissions a scoped session is limited to; `None` for a full session
+    pub scopes: Option<Vec<String>>,
 }
 
 impl Session {
@@ -53,6 +55,9 @@ pub struct LoginRequest {
     #[schema(example = "securepassword123")]
     pub password: String,
     pub user_agent: Option<String>,
+    /// Legal document versions the user accepts while signing in
+    #[serde(default)]
+    pub accepted_documents: Vec<LegalDocumentRef>,
 }
 
 impl LoginRequest {
@@ -100,6 +105,12 @@ pub struct RegisterRequest {
     pub email: String,
     #[schema(example = "securepassword123")]
     pub password: String,
+    /// Legal document versions the user accepts by registering
+    #[serde(default)]
+    pub accepted_documents: Vec<LegalDocumentRef>,
+    /// Also create account recovery codes, returned once in the response
+    #[serde(default)]
+    pub generate_recovery_codes: bool,
 }
 
 impl RegisterRequest {
@@ -111,6 +122,27 @@ impl RegisterRequest {
     }
 }
 
+/// A new account, with its recovery codes when they were requested
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Registration {
+    #[serde(flatten)]
+    pub user: crate::users::models::UserProfile,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<RecoveryCodes>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ResendVerificationRequest {
+    #[schema(example = "john@example.com")]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct VerifyEmailRequest {
+    /// Token from the verification link
+    pub token: String,
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct LoginResponse {
     pub session_token: String,
@@ -118,12 +150,284 @@ pub struct LoginResponse {
     pub user: crate::users::models::UserProfile,
 }
 
+/// Session limited to some of its owner's permissions, e.g. for automation
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct ScopedSession {
+    pub id: Uuid,
+    pub name: String,
+    /// `resource:permission` entries the session is limited to
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+}
+
+/// A new scoped session with its token, which is only returned this once
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ScopedSessionToken {
+    pub session_token: String,
+    pub session: ScopedSession,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateScopedSessionRequest {
+    #[schema(example = "nightly-report")]
+    pub name: String,
+    /// `resource:permission` entries, each one you hold yourself; resources
+    /// are `tasks` and `users`, permissions `read`, `write` and `delete`
+    #[schema(example = json!(["tasks:read", "tasks:write"]))]
+    pub scopes: Vec<String>,
+    /// Lifetime in hours, up to a year; defaults to the regular session duration
+    pub expires_in_hours: Option<u32>,
+}
+
+impl CreateScopedSessionRequest {
+    /// Longest lifetime of a scoped session, in hours
+    pub const MAX_EXPIRES_IN_HOURS: u32 = 24 * 365;
+
+    /// Validate the request and normalize its scopes
+    pub fn validate(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.len() > 100 {
+            return Err(Error::validation(
+                "name",
+                "Name must be between 1 and 100 characters",
+            ));
+        }
+        if self.scopes.is_empty() {
+            return Err(Error::validation(
+                "scopes",
+                "At least one scope is required",
+            ));
+        }
+        let mut scopes = self
+            .scopes
+            .iter()
+            .map(|scope| {
+                crate::rbac::models::parse_role_permission(scope)
+                    .map_err(|_| Error::validation("scopes", &format!("Invalid scope: {scope}")))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        scopes.sort();
+        scopes.dedup();
+        self.scopes = scopes;
+        if let Some(hours) = self.expires_in_hours
+            && !(1..=Self::MAX_EXPIRES_IN_HOURS).contains(&hours)
+        {
+            return Err(Error::validation(
+                "expires_in_hours",
+                &format!(
+                    "Lifetime must be between 1 and {} hours",
+                    Self::MAX_EXPIRES_IN_HOURS
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, utoipa::ToSchema)]
 pub struct RefreshResponse {
     pub expires_at: DateTime<Utc>,
     pub refreshed_at: DateTime<Utc>,
 }
 
+/// A fresh set of recovery codes; they can't be shown again
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RecoveryCodes {
+    #[schema(example = json!(["k7m2p-q9xw4", "h3tz8-c6rna"]))]
+    pub codes: Vec<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// How many of a user's recovery codes are left, without the codes themselves
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct RecoveryCodeStatus {
+    pub remaining: i64,
+    pub used: i64,
+    /// When the current set was created; absent if the user never generated one
+    pub generated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GenerateRecoveryCodesRequest {
+    /// Current password, confirming the user is at the keyboard
+    pub password: String,
+}
+
+/// Sign in with a recovery code instead of the password, setting a new one
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct RecoverAccountRequest {
+    #[schema(example = "johndoe")]
+    pub username: Option<String>,
+    #[schema(example = "john@example.com")]
+    pub email: Option<String>,
+    #[schema(example = "k7m2p-q9xw4")]
+    pub recovery_code: String,
+    #[schema(example = "newsecurepassword123")]
+    pub new_password: String,
+    pub user_agent: Option<String>,
+}
+
+impl RecoverAccountRequest {
+    pub fn validate(&self) -> Result<()> {
+        match (&self.username, &self.email) {
+            (Some(_), None) | (None, Some(_)) => {}
+            (Some(_), Some(_)) => {
+                return Err(Error::validation(
+                    "login",
+                    "Provide either username or email, not both",
+                ));
+            }
+            (None, None) => {
+                return Err(Error::validation(
+                    "login",
+                    "Either username or email must be provided",
+                ));
+            }
+        }
+        if self.recovery_code.trim().is_empty() {
+            return Err(Error::validation(
+                "recovery_code",
+                "Recovery code cannot be empty",
+            ));
+        }
+        crate::users::models::validate_password(&self.new_password)
+    }
+}
+
+/// Kind of legal document users are asked to accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LegalDocumentKind {
+    Terms,
+    Privacy,
+}
+
+impl LegalDocumentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegalDocumentKind::Terms => "terms",
+            LegalDocumentKind::Privacy => "privacy",
+        }
+    }
+}
+
+impl From<String> for LegalDocumentKind {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "privacy" => LegalDocumentKind::Privacy,
+            _ => LegalDocumentKind::Terms,
+        }
+    }
+}
+
+/// Whether signed-in users must accept the current legal documents to use the API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegalAcceptanceMode {
+    /// Acceptances are recorded but nothing is blocked
+    #[default]
+    Record,
+    /// Only the legal and session endpoints answer until the user accepts
+    Enforce,
+}
+
+pub const MAX_LEGAL_TITLE_LENGTH: usize = 200;
+
+/// One published version of a legal document
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LegalDocument {
+    pub id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: i32,
+    pub title: String,
+    pub content: String,
+    pub published_by: Option<Uuid>,
+    pub published_at: DateTime<Utc>,
+}
+
+/// A published version with how many users accepted it
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LegalDocumentVersion {
+    pub id: Uuid,
+    pub kind: LegalDocumentKind,
+    pub version: i32,
+    pub title: String,
+    pub published_by: Option<Uuid>,
+    pub published_at: DateTime<Utc>,
+    pub acceptances: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PublishLegalDocumentRequest {
+    pub kind: LegalDocumentKind,
+    #[schema(example = "Terms of Service")]
+    pub title: String,
+    pub content: String,
+}
+
+impl PublishLegalDocumentRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() || self.title.len() > MAX_LEGAL_TITLE_LENGTH {
+            return Err(Error::validation(
+                "title",
+                &format!("Title must be between 1 and {MAX_LEGAL_TITLE_LENGTH} characters"),
+            ));
+        }
+        if self.content.trim().is_empty() {
+            return Err(Error::validation("content", "Content cannot be empty"));
+        }
+        Ok(())
+    }
+}
+
+/// The version of a document the user saw and accepts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct LegalDocumentRef {
+    pub kind: LegalDocumentKind,
+    #[schema(example = 1)]
+    pub version: i32,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct AcceptLegalDocumentsRequest {
+    pub documents: Vec<LegalDocumentRef>,
+}
+
+impl AcceptLegalDocumentsRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.documents.is_empty() {
+            return Err(Error::validation(
+                "documents",
+                "At least one document must be accepted",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Where a user stands with the current version of one document
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LegalDocumentStatus {
+    pub kind: LegalDocumentKind,
+    pub version: i32,
+    pub title: String,
+    pub published_at: DateTime<Utc>,
+    /// Whether the user accepted this version
+    pub accepted: bool,
+    /// Latest version the user accepted, if any
+    pub accepted_version: Option<i32>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct LegalStatus {
+    pub documents: Vec<LegalDocumentStatus>,
+    /// Whether any current document still needs the user's acceptance
+    pub acceptance_required: bool,
+}
+
 // API Keys model
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ApiKey {
@@ -142,3 +446,119 @@ pub struct ApiKey {
     pub last_used_at: Option<DateTime<Utc>>,
     pub usage_count: i64,
 }
+
+/// Non-human account for a worker or integration; it signs in only with API keys
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct ServiceAccount {
+    pub id: Uuid,
+    pub username: String,
+    pub role: crate::rbac::UserRole,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// API keys that are active and unexpired
+    pub active_api_keys: i64,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateServiceAccountRequest {
+    #[schema(example = "billing-worker")]
+    pub username: String,
+    /// Built-in role; defaults to `user`. Custom roles are assigned as for any user
+    pub role: Option<crate::rbac::UserRole>,
+}
+
+impl CreateServiceAccountRequest {
+    pub fn validate(&mut self) -> Result<()> {
+        self.username = self.username.trim().to_string();
+        crate::users::models::validate_username(&self.username)
+    }
+}
+
+/// API key of a service account; the key itself is never shown after it is issued
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, utoipa::ToSchema)]
+pub struct ServiceAccountKey {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// First characters of the key, to tell keys apart
+    #[schema(example = "sak_Xy3kP9")]
+    pub key_prefix: String,
+    /// Permissions the key is limited to; `None` for the account's full permissions
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    /// Admin who issued the key
+    pub issued_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub usage_count: i64,
+}
+
+/// A new API key with its secret, which is only returned this once
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ServiceAccountKeyToken {
+    /// Send as `Authorization: Bearer <api_key>`
+    pub api_key: String,
+    pub key: ServiceAccountKey,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateApiKeyRequest {
+    #[schema(example = "production")]
+    pub name: String,
+    pub description: Option<String>,
+    /// `resource:permission` entries limiting the key, as for scoped sessions;
+    /// leave out for the account's full permissions
+    #[schema(example = json!(["tasks:read", "tasks:write"]))]
+    pub scopes: Option<Vec<String>>,
+    /// Lifetime in days; leave out for a key that doesn't expire
+    pub expires_in_days: Option<u32>,
+}
+
+impl CreateApiKeyRequest {
+    /// Longest lifetime of an expiring key, in days
+    pub const MAX_EXPIRES_IN_DAYS: u32 = 365 * 5;
+
+    /// Validate the request and normalize its scopes
+    pub fn validate(&mut self) -> Result<()> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.len() > 100 {
+            return Err(Error::validation(
+                "name",
+                "Name must be between 1 and 100 characters",
+            ));
+        }
+        if let Some(scopes) = &self.scopes {
+            if scopes.is_empty() {
+                return Err(Error::validation(
+                    "scopes",
+                    "Leave scopes out for full access, or list at least one",
+                ));
+            }
+            let mut parsed = scopes
+                .iter()
+                .map(|scope| {
+                    crate::rbac::models::parse_role_permission(scope).map_err(|_| {
+                        Error::validation("scopes", &format!("Invalid scope: {scope}"))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            parsed.sort();
+            parsed.dedup();
+            self.scopes = Some(parsed);
+        }
+        if let Some(days) = self.expires_in_days
+            && !(1..=Self::MAX_EXPIRES_IN_DAYS).contains(&days)
+        {
+            return Err(Error::validation(
+                "expires_in_days",
+                &format!(
+                    "Lifetime must be between 1 and {} days",
+                    Self::MAX_EXPIRES_IN_DAYS
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
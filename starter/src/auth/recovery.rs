@@ -0,0 +1,209 @@
+//! One-time account recovery codes
+//!
+//! A user gets a set of random codes that each sign them in once in place of
+//! their password, for when they lose it. The codes are shown when they are
+//! generated and never again; only their SHA-256 hashes are stored.
+//! Generating a new set retires the old one.
+
+use crate::auth::models::{
+    LoginResponse, RecoverAccountRequest, RecoveryCodeStatus, RecoveryCodes,
+};
+use crate::auth::services as auth_services;
+use crate::core::cache::AppCache;
+use crate::tenants::{RequestTenant, services as tenant_services};
+use crate::users::services as user_services;
+use crate::{DbConn, Error, Result};
+use chrono::Utc;
+use sqlx::Acquire;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Letters and digits that can't be mistaken for one another when read aloud or copied
+const CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const CODE_LENGTH: usize = 10;
+
+/// Random code shown as two groups of five, e.g. `k7m2p-q9xw4`
+fn generate_code() -> String {
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    let chars: String = (0..CODE_LENGTH)
+        .map(|_| CODE_ALPHABET[rng.random_range(0..CODE_ALPHABET.len())] as char)
+        .collect();
+    format!(
+        "{}-{}",
+        &chars[..CODE_LENGTH / 2],
+        &chars[CODE_LENGTH / 2..]
+    )
+}
+
+/// The form a code is hashed in, so case, spaces and dashes don't matter
+fn normalize_code(code: &str) -> String {
+    let chars: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if chars.len() == CODE_LENGTH {
+        format!(
+            "{}-{}",
+            &chars[..CODE_LENGTH / 2],
+            &chars[CODE_LENGTH / 2..]
+        )
+    } else {
+        chars
+    }
+}
+
+/// Replace the user's recovery codes with `count` new ones
+pub async fn generate_codes(conn: &mut DbConn, user_id: Uuid, count: u32) -> Result<RecoveryCodes> {
+    let codes: Vec<String> = (0..count).map(|_| generate_code()).collect();
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    sqlx::query!(
+        r#"
+        INSERT INTO recovery_codes (user_id, code_hash)
+        SELECT $1, sha256(convert_to(code, 'UTF8')) FROM UNNEST($2::TEXT[]) AS code
+        "#,
+        user_id,
+        &codes
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(RecoveryCodes {
+        codes,
+        generated_at: Utc::now(),
+    })
+}
+
+/// Confirm the password, then replace the user's recovery codes
+pub async fn regenerate_codes(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    password: &str,
+    count: u32,
+) -> Result<RecoveryCodes> {
+    let user = user_services::find_user_by_id(conn, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    if !user_services::verify_password(password, &user.password_hash)? {
+        return Err(Error::validation("password", "Invalid password"));
+    }
+
+    generate_codes(conn, user_id, count).await
+}
+
+/// How many codes the user has left
+pub async fn code_status(conn: &mut DbConn, user_id: Uuid) -> Result<RecoveryCodeStatus> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) FILTER (WHERE used_at IS NULL) AS "remaining!",
+               COUNT(*) FILTER (WHERE used_at IS NOT NULL) AS "used!",
+               MIN(created_at) AS generated_at
+        FROM recovery_codes
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(RecoveryCodeStatus {
+        remaining: row.remaining,
+        used: row.used,
+        generated_at: row.generated_at,
+    })
+}
+
+/// Sign in with a recovery code, which is used up, and set a new password
+///
+/// Every other session of the account ends, since whoever held the old
+/// password should lose access with it. Like signing in, only accounts of an
+/// active tenant the request admits can be recovered.
+pub async fn recover_account(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    tenant_cache_ttl: Duration,
+    tenant: &RequestTenant,
+    req: RecoverAccountRequest,
+) -> Result<LoginResponse> {
+    req.validate()?;
+    // Hashed before the lookup so unknown accounts take as long to reject
+    let password_hash = user_services::hash_password(&req.new_password)?;
+
+    let user = match (&req.username, &req.email) {
+        (Some(username), _) => user_services::find_user_by_username(conn, username).await?,
+        (None, Some(email)) => user_services::find_user_by_email(conn, email).await?,
+        (None, None) => None,
+    };
+    let user = user
+        .filter(|user| user.is_active && !user.is_service_account)
+        .ok_or(Error::InvalidCredentials)?;
+    // Checked before the code is used up, so it still works in the right tenant
+    let user_tenant =
+        tenant_services::user_tenant_cached(conn, cache, tenant_cache_ttl, user.id).await?;
+    if !user_tenant.is_active || !tenant.admits(user_tenant.id) {
+        return Err(Error::InvalidCredentials);
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let consumed = sqlx::query!(
+        r#"
+        UPDATE recovery_codes SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = sha256(convert_to($2, 'UTF8')) AND used_at IS NULL
+        "#,
+        user.id,
+        normalize_code(&req.recovery_code)
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if consumed == 0 {
+        return Err(Error::InvalidCredentials);
+    }
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $1, last_login_at = NOW(), updated_at = NOW() WHERE id = $2",
+        password_hash,
+        user.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    auth_services::delete_all_user_sessions(&mut tx, user.id).await?;
+    let session =
+        auth_services::create_session(&mut tx, user.id, req.user_agent.as_deref()).await?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(LoginResponse {
+        session_token: session.token,
+        expires_at: session.expires_at,
+        user: user.to_profile(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_codes_survive_normalization() {
+        for _ in 0..20 {
+            let code = generate_code();
+            assert_eq!(code.len(), CODE_LENGTH + 1);
+            assert_eq!(normalize_code(&code), code);
+            assert_eq!(normalize_code(&code.to_uppercase().replace('-', " ")), code);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Sessions limited to a subset of their owner's permissions
+//!
+//! A scoped session carries `resource:permission` scopes, each of which its
+//! owner held when it was created. Requests made with it need both the
+//! owner's current permission and a matching scope, so a token handed to
+//! automation never carries the owner's full power. Scoped sessions can only
+//! reach task and user endpoints, and can't be refreshed or create sessions.
+
+use crate::auth::AuthUser;
+use crate::auth::models::{CreateScopedSessionRequest, ScopedSession, ScopedSessionToken};
+use crate::auth::services::generate_session_token;
+use crate::rbac::{Permission, Resource, services as rbac_services};
+use crate::{DbConn, Error, Result};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+/// Create a scoped session for `owner`, who must hold every requested scope
+///
+/// Without `expires_in_hours` the session lasts `default_ttl`.
+pub async fn create_scoped_session(
+    conn: &mut DbConn,
+    owner: &AuthUser,
+    mut request: CreateScopedSessionRequest,
+    default_ttl: Duration,
+) -> Result<ScopedSessionToken> {
+    request.validate()?;
+    for scope in &request.scopes {
+        let (resource, permission) = scope
+            .split_once(':')
+            .ok_or_else(|| Error::validation("scopes", &format!("Invalid scope: {scope}")))?;
+        let resource: Resource = resource.parse()?;
+        let permission: Permission = permission.parse()?;
+        if rbac_services::check_permission(owner, resource, permission).is_err() {
+            return Err(Error::validation(
+                "scopes",
+                &format!("You don't have the {scope} permission"),
+            ));
+        }
+    }
+
+    let ttl = request
+        .expires_in_hours
+        .map(|hours| Duration::hours(hours.into()))
+        .unwrap_or(default_ttl);
+    let token = generate_session_token();
+
+    let session = sqlx::query_as!(
+        ScopedSession,
+        r#"
+        INSERT INTO sessions (user_id, token, expires_at, name, scopes)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name AS "name!", scopes AS "scopes!", expires_at, created_at,
+                  last_activity_at
+        "#,
+        owner.id,
+        token,
+        Utc::now() + ttl,
+        request.name,
+        &request.scopes
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(ScopedSessionToken {
+        session_token: token,
+        session,
+    })
+}
+
+/// The user's scoped sessions that are still usable, newest first
+pub async fn list_scoped_sessions(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<ScopedSession>> {
+    sqlx::query_as!(
+        ScopedSession,
+        r#"
+        SELECT id, name AS "name!", scopes AS "scopes!", expires_at, created_at,
+               last_activity_at
+        FROM sessions
+        WHERE user_id = $1 AND scopes IS NOT NULL AND is_active = true AND expires_at > NOW()
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// End one of the user's scoped sessions
+pub async fn revoke_scoped_session(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<()> {
+    let revoked = sqlx::query!(
+        r#"
+        UPDATE sessions SET is_active = false
+        WHERE id = $1 AND user_id = $2 AND scopes IS NOT NULL AND is_active = true
+        "#,
+        session_id,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if revoked == 0 {
+        return Err(Error::NotFound("Scoped session not found".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,256 @@
+//! Service accounts for workers and integrations
+//!
+//! A service account is a user row flagged `is_service_account`. Its password
+//! hash is of a random secret nobody holds, and login and account recovery
+//! refuse it, so it authenticates only with the API keys an admin issues for
+//! it. A key is shown when it is issued and never again; only its SHA-256 hash
+//! is stored. A key can carry scopes, which limit it the way they limit a
+//! scoped session. Service accounts are left out of user listings, exports,
+//! presence and SCIM, and are deactivated or deleted like any other account.
+
+use crate::auth::models::{
+    CreateApiKeyRequest, CreateServiceAccountRequest, ServiceAccount, ServiceAccountKey,
+    ServiceAccountKeyToken,
+};
+use crate::auth::services::generate_session_token;
+use crate::core::config::AppConfig;
+use crate::rbac::UserRole;
+use crate::users::models::User;
+use crate::users::{quotas, services as user_services};
+use crate::{DbConn, Error, Result};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+/// Start of every API key, which tells it apart from a session token
+pub const API_KEY_PREFIX: &str = "sak_";
+
+/// Characters of a key kept in the clear so admins can tell keys apart
+const DISPLAY_PREFIX_LEN: usize = 10;
+
+fn generate_api_key() -> String {
+    format!("{API_KEY_PREFIX}{}", generate_session_token())
+}
+
+/// Create a service account with the requested built-in role in the tenant
+pub async fn create_service_account(
+    conn: &mut DbConn,
+    mut request: CreateServiceAccountRequest,
+    tenant_id: Uuid,
+) -> Result<ServiceAccount> {
+    request.validate()?;
+    // A password nobody knows, so every password check fails
+    let password_hash = user_services::hash_password(&generate_session_token())?;
+    let email = format!("{}@service-accounts.invalid", request.username);
+
+    sqlx::query_as!(
+        ServiceAccount,
+        r#"
+        INSERT INTO users (username, email, password_hash, role, email_verified, is_service_account, tenant_id)
+        VALUES ($1, $2, $3, $4, true, true, $5)
+        RETURNING id, username, role, is_active, created_at, last_seen_at,
+                  0::BIGINT AS "active_api_keys!"
+        "#,
+        request.username,
+        email,
+        password_hash,
+        request.role.unwrap_or(UserRole::User).to_string(),
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Service accounts of the tenant that aren't deleted, by username
+pub async fn list_service_accounts(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<Vec<ServiceAccount>> {
+    sqlx::query_as!(
+        ServiceAccount,
+        r#"
+        SELECT u.id, u.username, u.role, u.is_active, u.created_at, u.last_seen_at,
+               (SELECT COUNT(*) FROM api_keys k
+                WHERE k.created_by = u.id AND k.is_active = true
+                  AND (k.expires_at IS NULL OR k.expires_at > NOW())) AS "active_api_keys!"
+        FROM users u
+        WHERE u.is_service_account = true AND u.deleted_at IS NULL AND u.tenant_id = $1
+        ORDER BY u.username
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Fail unless `account_id` is a service account of the tenant that isn't deleted
+async fn ensure_service_account(
+    conn: &mut DbConn,
+    account_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<()> {
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM users
+            WHERE id = $1 AND tenant_id = $2 AND is_service_account = true AND deleted_at IS NULL
+        ) AS "exists!"
+        "#,
+        account_id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !exists {
+        return Err(Error::NotFound("Service account not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Issue an API key for a service account of the tenant, within its API key quota
+pub async fn issue_api_key(
+    conn: &mut DbConn,
+    config: &AppConfig,
+    account_id: Uuid,
+    tenant_id: Uuid,
+    mut request: CreateApiKeyRequest,
+    issued_by: Uuid,
+) -> Result<ServiceAccountKeyToken> {
+    request.validate()?;
+    ensure_service_account(conn, account_id, tenant_id).await?;
+
+    let quota = quotas::get_user_quotas(conn, config, account_id)
+        .await?
+        .api_keys;
+    if let Some(limit) = quota.limit
+        && quota.used >= limit
+    {
+        return Err(Error::QuotaExceeded(format!(
+            "The service account already has {} of its {limit} API keys",
+            quota.used
+        )));
+    }
+
+    let api_key = generate_api_key();
+    let expires_at = request
+        .expires_in_days
+        .map(|days| Utc::now() + Duration::days(days.into()));
+    let key = sqlx::query_as!(
+        ServiceAccountKey,
+        r#"
+        INSERT INTO api_keys (name, description, key_hash, key_prefix, created_by, issued_by,
+                              expires_at, scopes)
+        VALUES ($1, $2, encode(sha256(convert_to($3, 'UTF8')), 'hex'), $4, $5, $6, $7, $8)
+        RETURNING id, name, description, key_prefix, scopes, expires_at, is_active, issued_by,
+                  created_at, last_used_at, usage_count
+        "#,
+        request.name,
+        request.description,
+        api_key,
+        &api_key[..DISPLAY_PREFIX_LEN],
+        account_id,
+        issued_by,
+        expires_at,
+        request.scopes.as_deref()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(ServiceAccountKeyToken { api_key, key })
+}
+
+/// API keys of a service account of the tenant, including revoked ones, newest first
+pub async fn list_api_keys(
+    conn: &mut DbConn,
+    account_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Vec<ServiceAccountKey>> {
+    ensure_service_account(conn, account_id, tenant_id).await?;
+    sqlx::query_as!(
+        ServiceAccountKey,
+        r#"
+        SELECT id, name, description, key_prefix, scopes, expires_at, is_active, issued_by,
+               created_at, last_used_at, usage_count
+        FROM api_keys
+        WHERE created_by = $1
+        ORDER BY created_at DESC, id
+        "#,
+        account_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Revoke one of the API keys of a service account of the tenant
+pub async fn revoke_api_key(
+    conn: &mut DbConn,
+    account_id: Uuid,
+    tenant_id: Uuid,
+    key_id: Uuid,
+) -> Result<()> {
+    ensure_service_account(conn, account_id, tenant_id).await?;
+    let revoked = sqlx::query!(
+        r#"
+        UPDATE api_keys SET is_active = false
+        WHERE id = $1 AND created_by = $2 AND is_active = true
+        "#,
+        key_id,
+        account_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if revoked == 0 {
+        return Err(Error::NotFound("API key not found".to_string()));
+    }
+    Ok(())
+}
+
+/// The service account an API key belongs to, with the key's scopes
+///
+/// Records the use. Revoked and expired keys give `None`, as do keys of
+/// inactive accounts and of accounts that aren't service accounts.
+pub async fn validate_api_key(
+    conn: &mut DbConn,
+    api_key: &str,
+) -> Result<Option<(User, Option<Vec<String>>)>> {
+    let key = sqlx::query!(
+        r#"
+        UPDATE api_keys SET last_used_at = NOW(), usage_count = usage_count + 1
+        WHERE key_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+          AND is_active = true
+          AND (expires_at IS NULL OR expires_at > NOW())
+        RETURNING created_by, scopes
+        "#,
+        api_key
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let Some(key) = key else {
+        return Ok(None);
+    };
+    let user = user_services::find_user_by_id(conn, key.created_by)
+        .await?
+        .filter(|user| user.is_service_account);
+    Ok(user.map(|user| (user, key.scopes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_keys_carry_the_prefix() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert!(key.len() > DISPLAY_PREFIX_LEN);
+        assert_ne!(key, generate_api_key());
+    }
+}
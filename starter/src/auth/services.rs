@@ -1,17 +1,27 @@
 use crate::auth::models::{LoginRequest, LoginResponse, RegisterRequest, Session};
+use crate::auth::service_accounts;
+use crate::core::cache::AppCache;
 use crate::users::{models::UserProfile, services as user_services};
 use crate::{DbConn, Error, Result};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::Acquire;
 use uuid::Uuid;
 
+/// Cache namespace of validated sessions, keyed by a hash of their token
+pub const SESSION_CACHE_NAMESPACE: &str = "sessions";
+
+/// Cache namespace of the generation of each user's cached sessions, keyed by user ID
+pub const SESSION_GENERATION_CACHE_NAMESPACE: &str = "session_generations";
+
 // Dummy hash with valid Argon2 format for timing attack protection.
 // Using a validly formatted hash is crucial to prevent the password verification
 // function from returning early due to a parsing error, which would reintroduce
 // a timing vulnerability. This is a pre-computed Argon2 hash that will always fail verification.
 const DUMMY_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$YWFhYWFhYWFhYWFhYWFhYQ$L2QVZ8LBhz/3BLvW+hBf1e4NkLYBu+GeBxdJJ1+BW5Q";
 
-fn generate_session_token() -> String {
+pub(crate) fn generate_session_token() -> String {
     use base64::Engine;
     use rand::Rng;
 
@@ -37,7 +47,7 @@ pub async fn create_session(
         INSERT INTO sessions (user_id, token, expires_at, user_agent)
         VALUES ($1, $2, $3, $4)
         RETURNING id, user_id, token, expires_at, created_at, updated_at,
-                  last_activity_at, last_refreshed_at, user_agent, is_active
+                  last_activity_at, last_refreshed_at, user_agent, is_active, scopes
         "#,
         user_id,
         token,
@@ -59,6 +69,7 @@ pub async fn create_session(
         last_refreshed_at: session.last_refreshed_at,
         user_agent: session.user_agent,
         is_active: session.is_active,
+        scopes: session.scopes,
     };
 
     Ok(session)
@@ -68,7 +79,7 @@ pub async fn find_session_by_token(conn: &mut DbConn, token: &str) -> Result<Opt
     let session = sqlx::query!(
         r#"
         SELECT id, user_id, token, expires_at, created_at, updated_at,
-               last_activity_at, last_refreshed_at, user_agent, is_active
+               last_activity_at, last_refreshed_at, user_agent, is_active, scopes
         FROM sessions 
         WHERE token = $1 AND is_active = true
         "#,
@@ -89,6 +100,7 @@ pub async fn find_session_by_token(conn: &mut DbConn, token: &str) -> Result<Opt
         last_refreshed_at: s.last_refreshed_at,
         user_agent: s.user_agent,
         is_active: s.is_active,
+        scopes: s.scopes,
     });
 
     Ok(session)
@@ -141,10 +153,11 @@ pub async fn cleanup_expired_sessions(conn: &mut DbConn) -> Result<u64> {
     Ok(result.rows_affected())
 }
 
-pub async fn validate_session_with_user(
+/// Validate a session token, returning the session with its user
+pub async fn validate_session(
     conn: &mut DbConn,
     token: &str,
-) -> Result<Option<crate::users::models::User>> {
+) -> Result<Option<(Session, crate::users::models::User)>> {
     let session = find_session_by_token(conn, token).await?;
 
     if let Some(session) = session
@@ -152,12 +165,132 @@ pub async fn validate_session_with_user(
     {
         update_session_activity(conn, session.id).await?;
         let user = user_services::find_user_by_id(conn, session.user_id).await?;
-        return Ok(user);
+        return Ok(user.map(|user| (session, user)));
     }
 
     Ok(None)
 }
 
+/// Validate a bearer token, a session token or a service account's API key,
+/// returning its user and the scopes limiting it
+pub async fn validate_token(
+    conn: &mut DbConn,
+    token: &str,
+) -> Result<Option<(crate::users::models::User, Option<Vec<String>>)>> {
+    if token.starts_with(service_accounts::API_KEY_PREFIX) {
+        return service_accounts::validate_api_key(conn, token).await;
+    }
+    Ok(validate_session(conn, token)
+        .await?
+        .map(|(session, user)| (user, session.scopes)))
+}
+
+pub async fn validate_session_with_user(
+    conn: &mut DbConn,
+    token: &str,
+) -> Result<Option<crate::users::models::User>> {
+    Ok(validate_session(conn, token).await?.map(|(_, user)| user))
+}
+
+/// The parts of a validated session a request needs
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedSession {
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+    scopes: Option<Vec<String>>,
+    /// The user's session generation when the session was cached
+    generation: Option<Uuid>,
+}
+
+async fn session_generation(cache: &AppCache, user_id: Uuid) -> Option<Uuid> {
+    cache
+        .get(SESSION_GENERATION_CACHE_NAMESPACE, &user_id.to_string())
+        .await
+}
+
+/// Cache key of a session; the token itself is never stored in the cache
+fn session_cache_key(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Validate a bearer token like [`validate_token`], keeping validated sessions
+/// in the cache for `ttl` (a zero TTL disables caching)
+///
+/// A cached session skips the session lookup and its activity update, so
+/// `last_activity_at` moves at most once per `ttl`. The user is still loaded
+/// on every call, so deactivated accounts are refused right away. Entries
+/// cached before [`forget_cached_sessions`] last ran for the user are checked
+/// against the database again, so revoked sessions are refused right away too.
+pub async fn validate_token_cached(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    ttl: std::time::Duration,
+    token: &str,
+) -> Result<Option<(crate::users::models::User, Option<Vec<String>>)>> {
+    if ttl.is_zero() || token.starts_with(service_accounts::API_KEY_PREFIX) {
+        return validate_token(conn, token).await;
+    }
+
+    let key = session_cache_key(token);
+    // A refresh may have extended an entry that looks expired, so those are checked again
+    if let Some(session) = cache
+        .get::<CachedSession>(SESSION_CACHE_NAMESPACE, &key)
+        .await
+        && Utc::now() < session.expires_at
+        && session.generation == session_generation(cache, session.user_id).await
+    {
+        let user = user_services::find_user_by_id(conn, session.user_id).await?;
+        return Ok(user.map(|user| (user, session.scopes)));
+    }
+
+    let Some(user_id) = find_session_by_token(conn, token)
+        .await?
+        .map(|session| session.user_id)
+    else {
+        return Ok(None);
+    };
+    // Read before the session is validated, so a revocation in between
+    // leaves the entry with an outdated generation
+    let generation = session_generation(cache, user_id).await;
+    let Some((session, user)) = validate_session(conn, token).await? else {
+        return Ok(None);
+    };
+    let cached = CachedSession {
+        user_id: session.user_id,
+        expires_at: session.expires_at,
+        scopes: session.scopes,
+        generation,
+    };
+    cache.set(SESSION_CACHE_NAMESPACE, &key, &cached, ttl).await;
+    Ok(Some((user, cached.scopes)))
+}
+
+/// Drop a session from the cache once it has ended
+pub async fn forget_cached_session(cache: &AppCache, token: &str) {
+    cache
+        .delete(SESSION_CACHE_NAMESPACE, &session_cache_key(token))
+        .await;
+}
+
+/// Make every cached session of a user be checked against the database again
+///
+/// Call it after revoking any of the user's sessions other than by token.
+/// Starting a new generation outdates the cached entries without having to
+/// find them; it is kept for `ttl`, as long as any of them can live.
+pub async fn forget_cached_sessions(cache: &AppCache, ttl: std::time::Duration, user_id: Uuid) {
+    if ttl.is_zero() {
+        return;
+    }
+    cache
+        .set(
+            SESSION_GENERATION_CACHE_NAMESPACE,
+            &user_id.to_string(),
+            &Uuid::new_v4(),
+            ttl,
+        )
+        .await;
+}
+
 pub async fn login(conn: &mut DbConn, req: LoginRequest) -> Result<LoginResponse> {
     req.validate()?;
 
@@ -170,6 +303,8 @@ pub async fn login(conn: &mut DbConn, req: LoginRequest) -> Result<LoginResponse
             return Err(Error::InvalidCredentials);
         }
     };
+    // Service accounts only authenticate with API keys
+    let user_option = user_option.filter(|user| !user.is_service_account);
 
     // Always verify password, even if user doesn't exist (using dummy hash)
     let password_valid = match &user_option {
@@ -213,7 +348,7 @@ pub async fn login(conn: &mut DbConn, req: LoginRequest) -> Result<LoginResponse
         INSERT INTO sessions (user_id, token, expires_at, user_agent)
         VALUES ($1, $2, $3, $4)
         RETURNING id, user_id, token, expires_at, created_at, updated_at,
-                  last_activity_at, last_refreshed_at, user_agent, is_active
+                  last_activity_at, last_refreshed_at, user_agent, is_active, scopes
         "#,
         user.id,
         token,
@@ -246,6 +381,7 @@ pub async fn login(conn: &mut DbConn, req: LoginRequest) -> Result<LoginResponse
         last_refreshed_at: session.last_refreshed_at,
         user_agent: session.user_agent,
         is_active: session.is_active,
+        scopes: session.scopes,
     };
 
     Ok(LoginResponse {
@@ -297,7 +433,7 @@ pub async fn refresh_session_token(
             SET expires_at = $1, last_refreshed_at = $2, updated_at = $2
             WHERE token = $3 AND is_active = true
             RETURNING id, user_id, token, expires_at, created_at, updated_at,
-                      last_activity_at, last_refreshed_at, user_agent, is_active
+                      last_activity_at, last_refreshed_at, user_agent, is_active, scopes
             "#,
             new_expires_at,
             now,
@@ -319,6 +455,7 @@ pub async fn refresh_session_token(
                 last_refreshed_at: s.last_refreshed_at,
                 user_agent: s.user_agent,
                 is_active: s.is_active,
+                scopes: s.scopes,
             };
             return Ok(Some(refreshed_session));
         }
@@ -327,7 +464,12 @@ pub async fn refresh_session_token(
     Ok(None)
 }
 
-pub async fn register(conn: &mut DbConn, req: RegisterRequest) -> Result<UserProfile> {
+/// Register a user in the tenant
+pub async fn register(
+    conn: &mut DbConn,
+    req: RegisterRequest,
+    tenant_id: Uuid,
+) -> Result<UserProfile> {
     req.validate()?;
 
     // Convert RegisterRequest to CreateUserRequest
@@ -338,5 +480,5 @@ pub async fn register(conn: &mut DbConn, req: RegisterRequest) -> Result<UserPro
         role: None, // Default role
     };
 
-    user_services::create_user(conn, create_req).await
+    user_services::create_user(conn, create_req, tenant_id).await
 }
@@ -0,0 +1,205 @@
+use crate::core::config::AppConfig;
+use crate::tasks::services::{EmailMessage, EmailSender};
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Random verification token; only its SHA-256 hash is stored
+fn generate_verification_token() -> String {
+    use base64::Engine;
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    let bytes: [u8; 32] = rng.random();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// What came of a resend request; never shown to the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResendOutcome {
+    Sent,
+    /// No active, unverified account uses the address
+    NotApplicable,
+    /// The previous email went out less than the cooldown ago
+    CoolingDown,
+    /// The account reached its emails for the day
+    DailyLimitReached,
+}
+
+/// Email a new verification link to the unverified account using `email`
+///
+/// Callers respond the same way whatever the outcome, so the endpoint
+/// doesn't reveal which addresses have accounts.
+pub async fn resend_verification(
+    conn: &mut DbConn,
+    sender: &dyn EmailSender,
+    config: &AppConfig,
+    email: &str,
+    now: DateTime<Utc>,
+) -> Result<ResendOutcome> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let Some(user) = sqlx::query!(
+        r#"
+        SELECT id, email
+        FROM users
+        WHERE lower(email) = lower($1)
+          AND is_active = true AND deleted_at IS NULL AND email_verified = false
+        FOR UPDATE
+        "#,
+        email.trim()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    else {
+        return Ok(ResendOutcome::NotApplicable);
+    };
+
+    let recent = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "count!", MAX(sent_at) AS last_sent_at
+        FROM email_verifications
+        WHERE user_id = $1 AND sent_at > $2
+        "#,
+        user.id,
+        now - chrono::Duration::days(1)
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let cooldown = chrono::Duration::seconds(config.auth.verification_resend_cooldown_secs as i64);
+    if recent
+        .last_sent_at
+        .is_some_and(|last_sent_at| last_sent_at + cooldown > now)
+    {
+        return Ok(ResendOutcome::CoolingDown);
+    }
+    if recent.count >= i64::from(config.auth.verification_daily_limit) {
+        return Ok(ResendOutcome::DailyLimitReached);
+    }
+
+    let token = generate_verification_token();
+    let expires_at = now + chrono::Duration::hours(config.auth.verification_ttl_hours as i64);
+    sqlx::query!(
+        r#"
+        INSERT INTO email_verifications (user_id, email, token_hash, expires_at, sent_at)
+        VALUES ($1, $2, sha256(convert_to($3, 'UTF8')), $4, $5)
+        "#,
+        user.id,
+        user.email,
+        token,
+        expires_at,
+        now
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // Only count the email against the limits if it goes out
+    send_verification_email(sender, config, &user.email, &token, expires_at).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(ResendOutcome::Sent)
+}
+
+async fn send_verification_email(
+    sender: &dyn EmailSender,
+    config: &AppConfig,
+    email: &str,
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<()> {
+    let link = format!(
+        "{}/verify-email?token={token}",
+        config.server.public_url.trim_end_matches('/')
+    );
+    let message = EmailMessage {
+        to: email.to_string(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Confirm this is your email address: {link}\n\n\
+             This link expires at {expires}. If you didn't ask for it, ignore this email.",
+            expires = expires_at.to_rfc3339(),
+        ),
+    };
+    sender.send(&message).await.map_err(|e| {
+        tracing::error!("Failed to send verification email to {email}: {e}");
+        Error::Internal("Failed to send verification email".to_string())
+    })
+}
+
+/// Mark the address a verification link was sent to as verified
+///
+/// Links are single use and stop working once the account's email changes.
+/// Returns the verified user.
+pub async fn verify_email(conn: &mut DbConn, token: &str) -> Result<Uuid> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let user_id = sqlx::query_scalar!(
+        r#"
+        UPDATE users u
+        SET email_verified = true, updated_at = NOW()
+        FROM email_verifications v
+        WHERE v.token_hash = sha256(convert_to($1, 'UTF8'))
+          AND v.used_at IS NULL
+          AND v.expires_at > NOW()
+          AND u.id = v.user_id
+          AND u.email = v.email
+          AND u.deleted_at IS NULL
+        RETURNING u.id
+        "#,
+        token
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::validation("token", "Invalid or expired verification link"))?;
+
+    sqlx::query!(
+        "UPDATE email_verifications SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(user_id)
+}
+
+/// Mark a user's current email as verified without a link
+///
+/// Returns whether it was unverified before.
+pub async fn force_verify_email(conn: &mut DbConn, user_id: Uuid) -> Result<bool> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let was_unverified = sqlx::query_scalar!(
+        r#"
+        UPDATE users u
+        SET email_verified = true, updated_at = NOW()
+        FROM users old
+        WHERE u.id = old.id AND u.id = $1 AND u.deleted_at IS NULL
+        RETURNING NOT old.email_verified AS "was_unverified!"
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    // Outstanding links have nothing left to do
+    sqlx::query!(
+        "UPDATE email_verifications SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(was_unverified)
+}
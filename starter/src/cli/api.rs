@@ -1,9 +1,19 @@
 use super::{
-    models::{Cli, Commands, GenerateCommands, RevertCommands},
+    models::{Cli, Commands, ConfigCommands, GenerateCommands, RevertCommands},
     services::{TaskTypeService, execute_admin_command},
 };
-use crate::{AppConfig, Database, core::server, tasks};
+use crate::{
+    AppConfig, Database,
+    core::{
+        config,
+        reload::{self, LiveConfig},
+        secrets, server, telemetry,
+    },
+    tasks,
+    tasks::HandlerRegistry,
+};
 use clap::Parser;
+use std::sync::Arc;
 
 /// Main CLI application handler
 pub struct CliApp {
@@ -17,16 +27,15 @@ impl CliApp {
 
     /// Parse and execute CLI commands
     pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
-        // Initialize logging with info level by default
-        tracing_subscriber::fmt()
-            .with_env_filter(std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))
-            .init();
-
         // Load environment variables
         dotenvy::dotenv().ok();
 
         let cli = Cli::parse();
         let config = AppConfig::load()?;
+
+        // Initialize logging (info level by default) and optional trace export
+        let _telemetry = telemetry::init_tracing(&config.observability)?;
+
         let app = CliApp::new(config);
 
         app.execute_command(cli.command).await
@@ -42,6 +51,7 @@ impl CliApp {
             Commands::Worker => self.run_worker().await,
             Commands::HealthCheck => self.run_health_check().await,
             Commands::ExportOpenApi { output } => self.export_openapi(output).await,
+            Commands::Config { config_command } => self.run_config_command(config_command),
             Commands::Admin { admin_command } => self.run_admin_command(admin_command).await,
             Commands::Generate { generator } => self.run_generate_command(generator).await,
             Commands::Revert { revert } => self.run_revert_command(revert).await,
@@ -61,6 +71,12 @@ impl CliApp {
         database.migrate().await?;
         database.ensure_initial_admin(&config).await?;
 
+        // Store logs queued since startup as monitoring events
+        crate::monitoring::app_logs::spawn_writer(
+            database.pool.clone(),
+            config.observability.service_name.clone(),
+        );
+
         server::start_server(config, database).await?;
         Ok(())
     }
@@ -70,19 +86,195 @@ impl CliApp {
         let database = Database::connect(&self.config).await?;
         database.migrate().await?;
 
+        // Store logs queued since startup as monitoring events
+        crate::monitoring::app_logs::spawn_writer(
+            database.pool.clone(),
+            format!("{}-worker", self.config.observability.service_name),
+        );
+
         // Create task processor with configuration
         let processor_config = tasks::processor::ProcessorConfig {
             poll_interval: self.config.poll_interval(),
             task_timeout: std::time::Duration::from_secs(300),
             max_concurrent_tasks: self.config.worker.concurrency,
-            batch_size: 50,
+            batch_size: self.config.worker.batch_size,
+            claim_strategy: self.config.worker.claim_strategy,
+            lease_duration: self.config.lease_duration(),
             enable_circuit_breaker: true,
         };
 
-        let processor = tasks::processor::TaskProcessor::new(database, processor_config);
+        // Periodically move old completed/failed tasks out of the hot table
+        if self.config.worker.archive_after_days > 0 {
+            tokio::spawn(tasks::archive::task_archive_job(
+                database.pool.clone(),
+                self.config.archive_interval(),
+                self.config.worker.archive_after_days,
+            ));
+        }
+
+        // Roll up raw metrics into 5-minute buckets and prune expired data
+        if self.config.monitoring.metric_rollup_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::retention::metric_retention_job(
+                database.pool.clone(),
+                self.config.metric_rollup_interval(),
+                self.config.monitoring.clone(),
+            ));
+        }
+
+        // Escalate unacknowledged incidents and email the notified users
+        if self.config.monitoring.incident_escalation_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::escalation::incident_escalation_job(
+                database.pool.clone(),
+                self.config.incident_escalation_interval(),
+                tasks::services::TaskServices::from_config(&self.config),
+            ));
+        }
+
+        // Open incidents for bursts of error events and resolve them once quiet
+        if self.config.monitoring.event_correlation_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::correlation::event_correlation_job(
+                database.pool.clone(),
+                self.config.event_correlation_interval(),
+                self.config.monitoring.clone(),
+            ));
+        }
+
+        // Delete events past their per type and level retention
+        if self.config.monitoring.event_retention_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::event_retention::event_retention_job(
+                database.pool.clone(),
+                self.config.event_retention_interval(),
+                self.config.monitoring.clone(),
+            ));
+        }
 
-        // Register example task handlers
-        tasks::handlers::register_example_handlers(&processor).await;
+        // Store the results of recording rules as new metric series
+        if self.config.monitoring.recording_rule_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::recording::recording_rule_job(
+                database.pool.clone(),
+                self.config.recording_rule_interval(),
+                self.config.monitoring.clone(),
+            ));
+        }
+
+        // Probe configured URLs and fire the alerts of failing checks
+        if self.config.monitoring.uptime_check_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::uptime::uptime_check_job(
+                database.pool.clone(),
+                self.config.uptime_check_interval(),
+                tasks::services::TaskServices::from_config(&self.config),
+            ));
+        }
+
+        // Purge deleted accounts once their retention period ends
+        if self.config.auth.deleted_user_retention_days > 0
+            && self.config.auth.user_purge_interval_secs > 0
+        {
+            tokio::spawn(crate::users::purge::user_purge_job(
+                database.pool.clone(),
+                self.config.user_purge_interval(),
+                self.config.auth.clone(),
+                tasks::services::TaskServices::from_config(&self.config),
+            ));
+        }
+
+        // Reactivate suspended accounts once their suspension ends
+        if self.config.auth.suspension_check_interval_secs > 0 {
+            tokio::spawn(crate::users::suspension::suspension_expiry_job(
+                database.pool.clone(),
+                self.config.suspension_check_interval(),
+            ));
+        }
+
+        // Restore the previous role once a temporary role ends
+        if self.config.auth.role_expiry_check_interval_secs > 0 {
+            tokio::spawn(crate::users::role_expiry::role_expiry_job(
+                database.pool.clone(),
+                self.config.role_expiry_check_interval(),
+            ));
+        }
+
+        // Delete data export archives once their download links expire
+        if self.config.storage.export_cleanup_interval_secs > 0 {
+            tokio::spawn(crate::users::export::data_export_cleanup_job(
+                database.pool.clone(),
+                self.config.export_cleanup_interval(),
+                tasks::services::TaskServices::from_config(&self.config),
+            ));
+        }
+
+        // Forget responses of requests sent with an Idempotency-Key once they expire
+        if self.config.server.idempotency_cleanup_interval_secs > 0 {
+            tokio::spawn(crate::core::idempotency::idempotency_cleanup_job(
+                database.pool.clone(),
+                self.config.idempotency_cleanup_interval(),
+            ));
+        }
+
+        // Delete audit log entries past their retention
+        if self.config.audit.purge_interval_secs > 0 {
+            tokio::spawn(crate::audit::services::audit_purge_job(
+                database.pool.clone(),
+                self.config.audit.retention_days,
+                self.config.audit_purge_interval(),
+            ));
+        }
+
+        // Recover tasks left running by workers that died mid-task
+        tokio::spawn(tasks::leases::task_lease_reaper_job(
+            database.pool.clone(),
+            self.config.lease_reap_interval(),
+        ));
+
+        // Handlers share one HTTP client and email sender built from the config
+        let processor = tasks::processor::TaskProcessor::new(database.clone(), processor_config)
+            .with_services(tasks::services::TaskServices::from_config(&self.config));
+        processor.set_maintenance_mode(self.config.maintenance.enabled);
+
+        // Apply reloaded worker concurrency, maintenance mode and log level on
+        // SIGHUP or config file changes
+        let live_config = Arc::new(LiveConfig::new(self.config.clone()));
+        let reloaded_processor = processor.clone();
+        tokio::spawn(reload::config_reload_job(live_config, move |config| {
+            reloaded_processor.set_max_concurrent_tasks(config.worker.concurrency);
+            reloaded_processor.set_maintenance_mode(config.maintenance.enabled);
+        }));
+
+        // Pick up a rotated database password from the secrets manager
+        if let Some(interval) = self.config.secrets_refresh_interval()
+            && !self.config.secret_sources.is_empty()
+        {
+            tokio::spawn(secrets::secret_refresh_job(
+                database.pool.clone(),
+                interval,
+                self.config.secret_sources.clone(),
+                secrets::SecretResolver::from_env(self.config.secrets_timeout()),
+            ));
+        }
+
+        // Store task throughput and pool usage alongside the other monitoring metrics
+        if self.config.monitoring.self_metrics_interval_secs > 0 {
+            tokio::spawn(crate::monitoring::instrumentation::worker_metrics_job(
+                database.pool.clone(),
+                self.config.self_metrics_interval(),
+                processor.metrics(),
+            ));
+        }
+
+        // Expose execution counters and queue depth for Prometheus scraping
+        if let Some(address) = self.config.worker_metrics_address() {
+            let listener = tokio::net::TcpListener::bind(&address).await?;
+            println!("Worker metrics listening on http://{address}/metrics");
+            tokio::spawn(tasks::metrics::serve_metrics(
+                listener,
+                processor.metrics(),
+                database,
+            ));
+        }
+
+        // Register every handler submitted with `register_task_handler!`
+        let handler_count = processor.register_all().await;
+        println!("Registered {handler_count} task handlers");
 
         // Register task types with the API
         if let Err(e) = TaskTypeService::register_task_types_with_api(None).await {
@@ -102,6 +294,49 @@ impl CliApp {
         Ok(())
     }
 
+    /// Show where the configuration comes from and, optionally, its merged values
+    fn run_config_command(
+        &self,
+        command: ConfigCommands,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let ConfigCommands::Show { resolved } = command;
+
+        println!("Profile: {}", config::config_profile()?);
+        println!("Sources, later ones overriding earlier ones:");
+        println!("  built-in defaults");
+        for layer in config::config_layers()? {
+            let status = match (layer.path.is_file(), layer.required) {
+                (true, _) => "",
+                (false, true) => " (missing, required)",
+                (false, false) => " (not found)",
+            };
+            println!("  {}{status}", layer.path.display());
+        }
+        println!("  STARTER__* environment variables and .env");
+
+        if resolved {
+            let mut config = self.config.clone();
+            config.database.password = "********".to_string();
+            let mut values = toml::Value::try_from(&config)?;
+            // Settings from secrets show where they come from rather than their values
+            for (path, uri) in &config.secret_sources {
+                let setting = path
+                    .split('.')
+                    .try_fold(&mut values, |value, key| match value {
+                        toml::Value::Table(table) => table.get_mut(key),
+                        toml::Value::Array(items) => items.get_mut(key.parse::<usize>().ok()?),
+                        _ => None,
+                    });
+                if let Some(setting) = setting {
+                    *setting = toml::Value::String(uri.clone());
+                }
+            }
+            println!();
+            print!("{}", toml::to_string_pretty(&values)?);
+        }
+        Ok(())
+    }
+
     /// Run health check for Docker/Kubernetes
     async fn run_health_check(&self) -> Result<(), Box<dyn std::error::Error>> {
         // Simple health check for Docker/Kubernetes
@@ -184,7 +419,7 @@ impl CliApp {
         admin_command: super::models::AdminCommands,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let database = Database::connect(&self.config).await?;
-        execute_admin_command(database, admin_command).await
+        execute_admin_command(database, &self.config, admin_command).await
     }
 
     /// Run generate commands
@@ -8,6 +8,7 @@ mod tests;
 // Re-export commonly used items
 pub use api::CliApp;
 pub use models::{
-    AdminCommands, Cli, Commands, GenerateCommands, TaskInfo, TaskStats, TaskStatsSummary,
+    AdminCommands, Cli, Commands, ConfigCommands, GenerateCommands, RbacCommands, TaskInfo,
+    TaskStats, TaskStatsSummary,
 };
 pub use services::{AdminService, TaskTypeService, execute_admin_command};
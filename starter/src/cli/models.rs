@@ -28,6 +28,11 @@ pub enum Commands {
         #[arg(long, default_value = "docs/openapi.json")]
         output: String,
     },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        config_command: ConfigCommands,
+    },
     /// Admin commands for direct database access
     Admin {
         #[command(subcommand)]
@@ -45,6 +50,16 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Show the configuration files in order of precedence
+    Show {
+        /// Also print the merged configuration, with the database password hidden
+        #[arg(long)]
+        resolved: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum AdminCommands {
     /// List tasks with optional filtering
@@ -56,6 +71,9 @@ pub enum AdminCommands {
         /// Filter by task type
         #[arg(long)]
         task_type: Option<String>,
+        /// Filter by task tag
+        #[arg(long)]
+        tag: Option<String>,
         /// Limit number of results
         #[arg(long, default_value = "50")]
         limit: i32,
@@ -66,7 +84,7 @@ pub enum AdminCommands {
     /// Show task statistics
     #[command(name = "task-stats")]
     TaskStats {
-        /// Filter by task tag in metadata
+        /// Filter by task tag
         #[arg(long)]
         tag: Option<String>,
     },
@@ -78,6 +96,47 @@ pub enum AdminCommands {
         #[arg(long)]
         dry_run: bool,
     },
+    /// Move completed/failed tasks older than specified days to tasks_archive
+    #[command(name = "archive-tasks")]
+    ArchiveTasks {
+        #[arg(long, default_value = "30")]
+        older_than_days: i32,
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete monitoring events past their retention (STARTER__MONITORING__EVENT_RETENTION_*)
+    #[command(name = "prune-events")]
+    PruneEvents {
+        /// Show what would be deleted, per event type and level
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export or import custom roles, denies and shares as YAML
+    Rbac {
+        #[command(subcommand)]
+        rbac_command: RbacCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RbacCommands {
+    /// Write the RBAC policy as YAML
+    Export {
+        /// Output file path (default: stdout)
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Make roles, denies and shares match a YAML policy, removing anything missing from it
+    Import {
+        /// Policy file, as written by `admin rbac export`
+        file: String,
+        /// Only show the changes the import would make
+        #[arg(long)]
+        dry_run: bool,
+        /// Recorded in the RBAC audit log for every change
+        #[arg(long)]
+        reason: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -123,6 +182,7 @@ pub struct TaskInfo {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
     pub metadata: serde_json::Value,
+    pub tags: Vec<String>,
 }
 
 /// Task statistics for CLI display
@@ -1,5 +1,8 @@
-use super::models::{AdminCommands, TaskInfo, TaskStats, TaskStatsSummary};
-use crate::{Database, Error};
+use super::models::{AdminCommands, RbacCommands, TaskInfo, TaskStats, TaskStatsSummary};
+use crate::monitoring::event_retention::{self, EventRetention};
+use crate::rbac::models::RbacPolicyChange;
+use crate::rbac::policy;
+use crate::{AppConfig, Database, Error, tasks::archive};
 use serde_json::json;
 use sqlx::Row;
 
@@ -13,11 +16,12 @@ impl AdminService {
         Self { database }
     }
 
-    /// List tasks with optional filtering by status and task_type
+    /// List tasks with optional filtering by status, task_type and tag
     pub async fn list_tasks(
         &self,
         status: Option<String>,
         task_type: Option<String>,
+        tag: Option<String>,
         limit: i32,
         _verbose: bool,
     ) -> Result<Vec<TaskInfo>, Error> {
@@ -25,7 +29,7 @@ impl AdminService {
         use sqlx::QueryBuilder;
 
         let mut builder: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(
-            "SELECT id, task_type, status::text as status, priority::text as priority, created_at, updated_at, metadata FROM tasks",
+            "SELECT id, task_type, status::text as status, priority::text as priority, created_at, updated_at, metadata, tags FROM tasks",
         );
 
         let mut has_where = false;
@@ -42,6 +46,17 @@ impl AdminService {
                 builder.push(" WHERE task_type = ");
             }
             builder.push_bind(t);
+            has_where = true;
+        }
+
+        if let Some(tag) = tag {
+            if has_where {
+                builder.push(" AND tags @> ARRAY[");
+            } else {
+                builder.push(" WHERE tags @> ARRAY[");
+            }
+            builder.push_bind(tag);
+            builder.push("]::TEXT[]");
         }
 
         builder.push(" ORDER BY created_at DESC LIMIT ");
@@ -63,6 +78,7 @@ impl AdminService {
                 created_at: task.get("created_at"),
                 updated_at: task.get("updated_at"),
                 metadata: task.get("metadata"),
+                tags: task.get("tags"),
             };
             task_infos.push(task_info);
         }
@@ -89,6 +105,9 @@ impl AdminService {
                     task.created_at.format("%Y-%m-%d %H:%M:%S"),
                     task.updated_at.format("%Y-%m-%d %H:%M:%S")
                 );
+                if !task.tags.is_empty() {
+                    println!("   Tags: {}", task.tags.join(", "));
+                }
                 println!("   Metadata: {}", task.metadata);
             } else {
                 println!(
@@ -108,7 +127,7 @@ impl AdminService {
             sqlx::query(
                 "SELECT status::text as status, COUNT(*) as count 
                  FROM tasks 
-                 WHERE metadata->>'tag' = $1 
+                 WHERE $1 = ANY(tags) 
                  GROUP BY status",
             )
             .bind(tag_filter)
@@ -141,7 +160,7 @@ impl AdminService {
             sqlx::query(
                 "SELECT AVG(EXTRACT(EPOCH FROM (updated_at - created_at))) as avg_duration 
                  FROM tasks 
-                 WHERE status = 'completed' AND metadata->>'tag' = $1",
+                 WHERE status = 'completed' AND $1 = ANY(tags)",
             )
             .bind(tag_filter)
             .fetch_optional(&self.database.pool)
@@ -228,6 +247,129 @@ impl AdminService {
             Ok(deleted_count)
         }
     }
+
+    /// Move completed/failed tasks older than specified days to tasks_archive
+    pub async fn archive_tasks(&self, older_than_days: i32, dry_run: bool) -> Result<i64, Error> {
+        let cutoff_time = chrono::Utc::now() - chrono::Duration::days(older_than_days as i64);
+        let mut conn = self
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::Database)?;
+
+        if dry_run {
+            let count = archive::count_archivable_tasks(conn.as_mut(), cutoff_time).await?;
+            println!(
+                "🔍 DRY RUN: Would archive {count} completed/failed tasks older than {older_than_days} days"
+            );
+            Ok(count)
+        } else {
+            let archived_count = archive::archive_tasks(conn.as_mut(), cutoff_time).await? as i64;
+            println!(
+                "📦 Archived {archived_count} completed/failed tasks older than {older_than_days} days"
+            );
+            Ok(archived_count)
+        }
+    }
+
+    /// Delete monitoring events past their per type and level retention
+    pub async fn prune_events(
+        &self,
+        retention: &EventRetention,
+        dry_run: bool,
+    ) -> Result<i64, Error> {
+        let mut conn = self
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::Database)?;
+        let now = chrono::Utc::now();
+
+        if dry_run {
+            let expired =
+                event_retention::count_expired_events(conn.as_mut(), retention, now).await?;
+            let count: i64 = expired.iter().map(|group| group.count).sum();
+            println!("🔍 DRY RUN: Would delete {count} events past their retention");
+            if !expired.is_empty() {
+                println!(
+                    "{:<20} {:<10} {:>10} {:>10}",
+                    "EVENT TYPE", "LEVEL", "RETENTION", "COUNT"
+                );
+                for group in &expired {
+                    println!(
+                        "{:<20} {:<10} {:>9}d {:>10}",
+                        group.event_type,
+                        group.level.as_deref().unwrap_or("-"),
+                        group.retention_days,
+                        group.count
+                    );
+                }
+            }
+            Ok(count)
+        } else {
+            let deleted_count =
+                event_retention::prune_events(conn.as_mut(), retention, now).await? as i64;
+            println!("🗑️  Deleted {deleted_count} events past their retention");
+            Ok(deleted_count)
+        }
+    }
+
+    /// Write the RBAC policy as YAML to a file, or to stdout
+    pub async fn export_rbac_policy(&self, output: Option<&str>) -> Result<(), Error> {
+        let mut conn = self
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::Database)?;
+        let yaml = policy::to_yaml(&policy::export_policy(conn.as_mut()).await?)?;
+
+        match output {
+            Some(path) => {
+                std::fs::write(path, yaml)
+                    .map_err(|e| Error::internal(&format!("Failed to write {path}: {e}")))?;
+                println!("✅ RBAC policy exported to: {path}");
+            }
+            None => print!("{yaml}"),
+        }
+        Ok(())
+    }
+
+    /// Import an RBAC policy from a YAML file, or only show its changes
+    pub async fn import_rbac_policy(
+        &self,
+        path: &str,
+        dry_run: bool,
+        reason: Option<String>,
+    ) -> Result<Vec<RbacPolicyChange>, Error> {
+        let yaml = std::fs::read_to_string(path)
+            .map_err(|e| Error::internal(&format!("Failed to read {path}: {e}")))?;
+        let rbac_policy = policy::from_yaml(&yaml)?;
+        let mut conn = self
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::Database)?;
+        let changes =
+            policy::import_policy(conn.as_mut(), rbac_policy, None, reason, dry_run).await?;
+
+        if changes.is_empty() {
+            println!("✅ RBAC policy already matches {path}");
+            return Ok(changes);
+        }
+        if dry_run {
+            println!("🔍 DRY RUN: Would make {} changes", changes.len());
+        } else {
+            println!("✅ Made {} changes", changes.len());
+        }
+        for change in &changes {
+            println!("  {change}");
+        }
+        Ok(changes)
+    }
 }
 
 /// Service for handling task type registration with API
@@ -244,18 +386,11 @@ impl TaskTypeService {
 
         let client = reqwest::Client::new();
 
-        // Define task types that this worker handles
-        let task_types = [
-            ("email", "Email notification tasks"),
-            ("data_processing", "Data processing and analysis tasks"),
-            ("file_cleanup", "File system cleanup tasks"),
-            ("report_generation", "Report generation tasks"),
-            ("webhook", "Webhook notification tasks"),
-            (
-                "delay_task",
-                "Delay/sleep tasks for testing and chaos scenarios",
-            ),
-        ];
+        // Register the task types of every auto-registered handler
+        let task_types: Vec<_> = crate::tasks::registry::registrations()
+            .into_iter()
+            .map(|r| (r.task_type, r.description))
+            .collect();
 
         for (task_type, description) in task_types.iter() {
             let response = client
@@ -286,6 +421,7 @@ impl TaskTypeService {
 /// Execute admin command
 pub async fn execute_admin_command(
     database: Database,
+    config: &AppConfig,
     admin_command: AdminCommands,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let admin_service = AdminService::new(database);
@@ -294,11 +430,12 @@ pub async fn execute_admin_command(
         AdminCommands::ListTasks {
             status,
             task_type,
+            tag,
             limit,
             verbose,
         } => {
             let tasks = admin_service
-                .list_tasks(status, task_type, limit, verbose)
+                .list_tasks(status, task_type, tag, limit, verbose)
                 .await?;
             admin_service.display_tasks(&tasks, verbose);
             Ok(())
@@ -317,5 +454,35 @@ pub async fn execute_admin_command(
                 .await?;
             Ok(())
         }
+        AdminCommands::ArchiveTasks {
+            older_than_days,
+            dry_run,
+        } => {
+            admin_service
+                .archive_tasks(older_than_days, dry_run)
+                .await?;
+            Ok(())
+        }
+        AdminCommands::PruneEvents { dry_run } => {
+            let retention = EventRetention::from_config(&config.monitoring)?;
+            admin_service.prune_events(&retention, dry_run).await?;
+            Ok(())
+        }
+        AdminCommands::Rbac { rbac_command } => match rbac_command {
+            RbacCommands::Export { output } => {
+                admin_service.export_rbac_policy(output.as_deref()).await?;
+                Ok(())
+            }
+            RbacCommands::Import {
+                file,
+                dry_run,
+                reason,
+            } => {
+                admin_service
+                    .import_rbac_policy(&file, dry_run, reason)
+                    .await?;
+                Ok(())
+            }
+        },
     }
 }
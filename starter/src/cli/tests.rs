@@ -36,6 +36,7 @@ fn test_admin_commands_parsing() {
             AdminCommands::ListTasks {
                 status: _,
                 task_type: _,
+                tag: _,
                 limit,
                 verbose,
             } => {
@@ -48,6 +49,25 @@ fn test_admin_commands_parsing() {
     }
 }
 
+#[test]
+fn test_list_tasks_tag_parsing() {
+    use clap::Parser;
+
+    let args = vec!["starter", "admin", "list-tasks", "--tag", "billing"];
+    let cli = Cli::try_parse_from(args).unwrap();
+
+    match cli.command {
+        Commands::Admin { admin_command } => match admin_command {
+            AdminCommands::ListTasks { tag, limit, .. } => {
+                assert_eq!(tag, Some("billing".to_string()));
+                assert_eq!(limit, 50);
+            }
+            _ => panic!("Expected ListTasks command"),
+        },
+        _ => panic!("Expected Admin command"),
+    }
+}
+
 #[test]
 fn test_task_stats_command_parsing() {
     use clap::Parser;
@@ -97,6 +117,62 @@ fn test_clear_completed_command_parsing() {
     }
 }
 
+#[test]
+fn test_archive_tasks_command_parsing() {
+    use clap::Parser;
+
+    // Test archive-tasks command with defaults
+    let args = vec!["starter", "admin", "archive-tasks"];
+    let cli = Cli::try_parse_from(args).unwrap();
+
+    match cli.command {
+        Commands::Admin { admin_command } => match admin_command {
+            AdminCommands::ArchiveTasks {
+                older_than_days,
+                dry_run,
+            } => {
+                assert_eq!(older_than_days, 30);
+                assert!(!dry_run);
+            }
+            _ => panic!("Expected ArchiveTasks command"),
+        },
+        _ => panic!("Expected Admin command"),
+    }
+}
+
+#[test]
+fn test_rbac_import_command_parsing() {
+    use clap::Parser;
+
+    let args = vec![
+        "starter",
+        "admin",
+        "rbac",
+        "import",
+        "rbac-policy.yaml",
+        "--dry-run",
+    ];
+    let cli = Cli::try_parse_from(args).unwrap();
+
+    match cli.command {
+        Commands::Admin {
+            admin_command: AdminCommands::Rbac { rbac_command },
+        } => match rbac_command {
+            RbacCommands::Import {
+                file,
+                dry_run,
+                reason,
+            } => {
+                assert_eq!(file, "rbac-policy.yaml");
+                assert!(dry_run);
+                assert_eq!(reason, None);
+            }
+            _ => panic!("Expected Import command"),
+        },
+        _ => panic!("Expected Admin rbac command"),
+    }
+}
+
 #[test]
 fn test_export_openapi_command_parsing() {
     use clap::Parser;
@@ -118,6 +194,19 @@ fn test_export_openapi_command_parsing() {
     }
 }
 
+#[test]
+fn test_config_show_command_parsing() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["starter", "config", "show", "--resolved"]).unwrap();
+    match cli.command {
+        Commands::Config {
+            config_command: ConfigCommands::Show { resolved },
+        } => assert!(resolved),
+        _ => panic!("Expected Config Show command"),
+    }
+}
+
 #[test]
 fn test_worker_command_parsing() {
     use clap::Parser;
@@ -165,6 +254,7 @@ fn test_task_info_serialization() {
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
         metadata: serde_json::json!({"test": true}),
+        tags: vec!["billing".to_string()],
     };
 
     // Test that the struct can be serialized and deserialized
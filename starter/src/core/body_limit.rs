@@ -0,0 +1,156 @@
+//! Request body size limits
+//!
+//! Each route group gets its own limit from [`BodyLimitConfig`], enforced by
+//! tower-http's `RequestBodyLimitLayer`: a request whose `Content-Length` is
+//! over the limit is refused before it is read, and a streamed body is cut off
+//! once it grows past it. Either way the client gets a 413 with the usual
+//! error body, whose `details.limit_bytes` holds the limit.
+//!
+//! [`BodyLimitConfig`]: crate::core::config::BodyLimitConfig
+
+use crate::core::error::Error;
+use crate::core::types::Result;
+use axum::{
+    Router,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Request, State},
+    http::{StatusCode, header::CONTENT_TYPE},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use http_body_util::LengthLimitError;
+use tower_http::limit::RequestBodyLimitLayer;
+
+/// Limit the bodies of the requests to `router`'s routes to `limit_bytes`
+///
+/// Like `Router::layer`, this applies to the routes added so far, so routes
+/// added afterwards can have a limit of their own.
+pub fn limit_body<S>(router: Router<S>, limit_bytes: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        // The limit below replaces axum's default one for extractors
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(limit_bytes))
+        .layer(middleware::from_fn_with_state(
+            limit_bytes,
+            payload_too_large,
+        ))
+}
+
+/// Replace the plain text 413s of body limits with the API's error response
+pub async fn payload_too_large(
+    State(limit_bytes): State<usize>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return Error::PayloadTooLarge { limit_bytes }.into_response();
+    }
+    response
+}
+
+/// Read a whole request body, refusing one over `limit_bytes` with a 413
+pub async fn to_bytes(body: Body, limit_bytes: usize) -> Result<Bytes> {
+    axum::body::to_bytes(body, limit_bytes).await.map_err(|e| {
+        if is_length_limit_error(&e) {
+            Error::PayloadTooLarge { limit_bytes }
+        } else {
+            Error::validation("body", &format!("Request body could not be read: {e}"))
+        }
+    })
+}
+
+/// Whether reading a body failed because it went over a limit, this one or
+/// a route group's
+fn is_length_limit_error(error: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(error) = source {
+        if error.is::<LengthLimitError>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use tower::ServiceExt;
+
+    async fn echo(body: Bytes) -> Bytes {
+        body
+    }
+
+    async fn send(router: Router, body: Body) -> Response {
+        router
+            .oneshot(
+                Request::post("/")
+                    .body(body)
+                    .expect("Failed to build request"),
+            )
+            .await
+            .expect("Router is infallible")
+    }
+
+    fn streamed(len: usize) -> Body {
+        let chunks = vec![Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; len]))];
+        Body::from_stream(futures_util::stream::iter(chunks))
+    }
+
+    async fn assert_payload_too_large(response: Response, limit_bytes: usize) {
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+        assert_eq!(json["error"]["details"]["limit_bytes"], limit_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_limit_body_refuses_large_bodies() {
+        let router = || limit_body(Router::new().route("/", post(echo)), 16);
+
+        let response = send(router(), Body::from(vec![b'x'; 16])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Refused by Content-Length, and while reading a streamed body
+        assert_payload_too_large(send(router(), Body::from(vec![b'x'; 17])).await, 16).await;
+        assert_payload_too_large(send(router(), streamed(17)).await, 16).await;
+    }
+
+    #[tokio::test]
+    async fn test_limit_body_replaces_axum_default() {
+        let limit_bytes = 3 * 1024 * 1024;
+        let router = limit_body(Router::new().route("/", post(echo)), limit_bytes);
+
+        let response = send(router, streamed(limit_bytes)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_to_bytes_tells_limit_errors_apart() {
+        assert!(matches!(
+            to_bytes(streamed(17), 16).await,
+            Err(Error::PayloadTooLarge { limit_bytes: 16 })
+        ));
+
+        let failing = Body::from_stream(futures_util::stream::iter(vec![Err::<Bytes, _>(
+            std::io::Error::other("connection reset"),
+        )]));
+        assert!(matches!(
+            to_bytes(failing, 16).await,
+            Err(Error::ValidationError { .. })
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::Result;
+use crate::core::cache::Cache;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Entries kept before expired ones are dropped
+const MAX_ENTRIES: usize = 50_000;
+
+#[derive(Debug)]
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// Keeps entries in the memory of this server
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(entries
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(key) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(
+            key.to_string(),
+            Entry {
+                value,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(key);
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|key, _| !key.starts_with(prefix));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let cache = MemoryCache::new();
+        cache
+            .set("a", b"1".to_vec(), Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), Some(b"1".to_vec()));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(cache.get("a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix() {
+        let cache = MemoryCache::new();
+        let ttl = Duration::from_secs(60);
+        for key in ["permissions:1", "permissions:2", "sessions:1"] {
+            cache.set(key, b"1".to_vec(), ttl).await.unwrap();
+        }
+
+        cache.delete_prefix("permissions:").await.unwrap();
+        assert_eq!(cache.get("permissions:1").await.unwrap(), None);
+        assert_eq!(cache.get("permissions:2").await.unwrap(), None);
+        assert!(cache.get("sessions:1").await.unwrap().is_some());
+
+        cache.delete("sessions:1").await.unwrap();
+        assert_eq!(cache.get("sessions:1").await.unwrap(), None);
+    }
+}
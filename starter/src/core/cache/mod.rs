@@ -0,0 +1,207 @@
+//! Cache for hot lookups
+//!
+//! Lookups that run on most requests, such as sessions, custom role
+//! permissions and task type registrations, are kept through the [`Cache`]
+//! trait so the backend can be swapped: [`MemoryCache`] keeps entries in the
+//! server's own memory, while [`RedisCache`] shares them between servers, so
+//! an invalidation on one server reaches all of them. Callers go through
+//! [`AppCache`], which stores JSON values under namespaced keys and counts
+//! hits and misses per namespace. Backend errors are logged and read as
+//! misses, so an unreachable cache slows requests down instead of failing them.
+
+pub mod memory;
+pub mod redis;
+
+pub use self::memory::MemoryCache;
+pub use self::redis::RedisCache;
+
+use crate::Result;
+use crate::core::config::CacheConfig;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Stores byte values under string keys, each kept for its own TTL
+#[async_trait]
+pub trait Cache: Send + Sync {
+    /// The value under the key, or `None` when it is missing or expired
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store a value, replacing any existing one, until `ttl` passes
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()>;
+
+    /// Remove a value; missing keys are not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// Remove every value whose key starts with `prefix`
+    async fn delete_prefix(&self, prefix: &str) -> Result<()>;
+}
+
+/// Where cached lookups are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    /// In the memory of each server
+    #[default]
+    Memory,
+    /// In Redis, shared by every server
+    Redis,
+}
+
+/// Hits and misses of one namespace since they were last taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheCounts {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Namespaced JSON access to a [`Cache`] backend, with hit and miss counts
+pub struct AppCache {
+    backend: Arc<dyn Cache>,
+    key_prefix: String,
+    counts: Mutex<BTreeMap<&'static str, CacheCounts>>,
+}
+
+impl AppCache {
+    /// Cache on `backend`, with every key starting with `key_prefix`
+    pub fn new(backend: Arc<dyn Cache>, key_prefix: impl Into<String>) -> Self {
+        Self {
+            backend,
+            key_prefix: key_prefix.into(),
+            counts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Cache on the configured backend; Redis is connected to right away
+    pub async fn from_config(config: &CacheConfig) -> Result<Self> {
+        let backend: Arc<dyn Cache> = match config.backend {
+            CacheBackend::Memory => Arc::new(MemoryCache::new()),
+            CacheBackend::Redis => Arc::new(RedisCache::connect(&config.redis_url).await?),
+        };
+        Ok(Self::new(backend, &config.key_prefix))
+    }
+
+    fn key(&self, namespace: &str, key: &str) -> String {
+        format!("{}{namespace}:{key}", self.key_prefix)
+    }
+
+    /// The value cached under the key, counting the lookup as a hit or miss
+    pub async fn get<T: DeserializeOwned>(&self, namespace: &'static str, key: &str) -> Option<T> {
+        let value = match self.backend.get(&self.key(namespace, key)).await {
+            Ok(bytes) => bytes.and_then(|bytes| match serde_json::from_slice(&bytes) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Discarding unreadable {namespace} cache entry: {e}");
+                    None
+                }
+            }),
+            Err(e) => {
+                tracing::warn!("Failed to read {namespace} cache entry: {e}");
+                None
+            }
+        };
+        self.count(namespace, value.is_some());
+        value
+    }
+
+    /// Cache a value under the key until `ttl` passes
+    pub async fn set<T: Serialize>(
+        &self,
+        namespace: &'static str,
+        key: &str,
+        value: &T,
+        ttl: Duration,
+    ) {
+        let bytes = match serde_json::to_vec(value) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Failed to encode {namespace} cache entry: {e}");
+                return;
+            }
+        };
+        if let Err(e) = self
+            .backend
+            .set(&self.key(namespace, key), bytes, ttl)
+            .await
+        {
+            tracing::warn!("Failed to store {namespace} cache entry: {e}");
+        }
+    }
+
+    /// Forget the value under the key
+    pub async fn delete(&self, namespace: &'static str, key: &str) {
+        if let Err(e) = self.backend.delete(&self.key(namespace, key)).await {
+            tracing::warn!("Failed to delete {namespace} cache entry: {e}");
+        }
+    }
+
+    /// Forget every value in the namespace
+    pub async fn clear(&self, namespace: &'static str) {
+        if let Err(e) = self.backend.delete_prefix(&self.key(namespace, "")).await {
+            tracing::warn!("Failed to clear {namespace} cache: {e}");
+        }
+    }
+
+    fn count(&self, namespace: &'static str, hit: bool) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let counts = counts.entry(namespace).or_default();
+        if hit {
+            counts.hits += 1;
+        } else {
+            counts.misses += 1;
+        }
+    }
+
+    /// Hits and misses by namespace since the last call, which are then reset
+    pub fn take_counts(&self) -> BTreeMap<&'static str, CacheCounts> {
+        std::mem::take(&mut *self.counts.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Default for AppCache {
+    /// In-memory cache without a key prefix
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryCache::new()), "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_namespaced_values_and_counts() {
+        let cache = AppCache::default();
+
+        assert_eq!(cache.get::<u32>("sessions", "a").await, None);
+        cache
+            .set("sessions", "a", &1u32, Duration::from_secs(60))
+            .await;
+        cache
+            .set("task_types", "a", &2u32, Duration::from_secs(60))
+            .await;
+        assert_eq!(cache.get::<u32>("sessions", "a").await, Some(1));
+        assert_eq!(cache.get::<u32>("task_types", "a").await, Some(2));
+
+        cache.clear("sessions").await;
+        assert_eq!(cache.get::<u32>("sessions", "a").await, None);
+        assert_eq!(cache.get::<u32>("task_types", "a").await, Some(2));
+
+        let counts = cache.take_counts();
+        assert_eq!(counts["sessions"], CacheCounts { hits: 1, misses: 2 });
+        assert_eq!(counts["task_types"], CacheCounts { hits: 2, misses: 0 });
+        assert!(cache.take_counts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_entries_are_misses() {
+        let cache = AppCache::default();
+        cache
+            .set("sessions", "a", &"text", Duration::from_secs(60))
+            .await;
+        assert_eq!(cache.get::<u32>("sessions", "a").await, None);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::core::cache::Cache;
+use crate::{Error, Result};
+use ::redis::AsyncCommands;
+use ::redis::aio::ConnectionManager;
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Keys deleted per round trip when clearing a prefix
+const SCAN_BATCH: usize = 500;
+
+/// Keeps entries in Redis, shared by every server using the same database
+#[derive(Clone)]
+pub struct RedisCache {
+    connection: ConnectionManager,
+}
+
+impl RedisCache {
+    /// Connect to the Redis server at `url`, e.g. `redis://localhost:6379/0`
+    ///
+    /// The connection is re-established in the background if it drops.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = ::redis::Client::open(url)
+            .map_err(|e| Error::ConfigurationError(format!("Invalid cache Redis URL: {e}")))?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to connect to cache Redis: {e}")))?;
+        Ok(Self { connection })
+    }
+}
+
+fn redis_error(action: &str, e: ::redis::RedisError) -> Error {
+    Error::Internal(format!("Failed to {action} cache entry: {e}"))
+}
+
+/// Escape the characters SCAN patterns treat as wildcards
+fn escape_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('*');
+    pattern
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut connection = self.connection.clone();
+        connection
+            .get(key)
+            .await
+            .map_err(|e| redis_error("read", e))
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let ttl_ms = (ttl.as_millis() as u64).max(1);
+        connection
+            .pset_ex(key, value, ttl_ms)
+            .await
+            .map_err(|e| redis_error("store", e))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let mut connection = self.connection.clone();
+        connection
+            .del(key)
+            .await
+            .map_err(|e| redis_error("delete", e))
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let pattern = escape_pattern(prefix);
+        let mut cursor = 0u64;
+        loop {
+            let (next, keys): (u64, Vec<String>) = ::redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(SCAN_BATCH)
+                .query_async(&mut connection)
+                .await
+                .map_err(|e| redis_error("find", e))?;
+            if !keys.is_empty() {
+                let _: () = connection
+                    .del(keys)
+                    .await
+                    .map_err(|e| redis_error("delete", e))?;
+            }
+            if next == 0 {
+                return Ok(());
+            }
+            cursor = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_pattern() {
+        assert_eq!(
+            escape_pattern("starter:permissions:"),
+            "starter:permissions:*"
+        );
+        assert_eq!(escape_pattern("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\*");
+    }
+}
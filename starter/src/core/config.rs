@@ -1,7 +1,18 @@
+use crate::auth::models::LegalAcceptanceMode;
+use crate::core::cache::CacheBackend;
 use crate::core::error::Error;
+use crate::core::secrets::{SecretResolver, SecretSources, resolve_config_secrets};
 use crate::core::types::Result;
+use crate::monitoring::cardinality::CardinalityLimitAction;
+use crate::rbac::UserRole;
+use crate::tasks::processor::ClaimStrategy;
+use crate::users::models::PurgeMode;
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
 use secrecy::SecretString;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,18 +21,56 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
     pub worker: WorkerConfig,
+    pub tasks: TasksConfig,
+    pub monitoring: MonitoringConfig,
+    pub observability: ObservabilityConfig,
+    pub storage: StorageConfig,
+    pub cache: CacheConfig,
+    pub rate_limit: RateLimitConfig,
+    pub tenancy: TenancyConfig,
+    pub secrets: SecretsConfig,
+    pub realtime: RealtimeConfig,
+    pub audit: AuditConfig,
+    pub maintenance: MaintenanceConfig,
+    pub body_limit: BodyLimitConfig,
     #[serde(skip)]
     pub initial_admin_password: Option<SecretString>,
+    /// Bearer token identity providers use for the SCIM API (unset disables it)
+    #[serde(skip)]
+    pub scim_token: Option<SecretString>,
+    /// Settings resolved from `secret://` URIs when loading, by path
+    #[serde(skip)]
+    pub secret_sources: SecretSources,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
-    #[serde(deserialize_with = "deserialize_cors_origins")]
+    #[serde(deserialize_with = "deserialize_comma_separated")]
     pub cors_origins: Vec<String>,
+    /// Addresses or CIDR ranges of reverse proxies whose `X-Forwarded-For` and
+    /// `X-Real-IP` headers are believed; from anyone else they are ignored
+    #[serde(default, deserialize_with = "deserialize_comma_separated")]
+    pub trusted_proxies: Vec<String>,
     pub request_timeout_secs: u64,
     pub web_build_path: String,
+    /// Base URL of the web app, used for links sent by email
+    pub public_url: String,
+    /// Compress API responses with gzip or brotli when the client accepts it
+    pub compression_enabled: bool,
+    /// Responses smaller than this many bytes are sent uncompressed
+    pub compression_min_bytes: u16,
+    /// Comma-separated content types that are compressed, matched by prefix,
+    /// e.g. `application/json,text/`
+    pub compression_content_types: String,
+    /// RFC 3339 time after which `/api/v1` may be removed, announced in its
+    /// `Sunset` header; empty for none
+    pub api_v1_sunset: String,
+    /// How long responses to requests with an `Idempotency-Key` are replayed
+    pub idempotency_ttl_secs: u64,
+    /// How often the worker deletes expired idempotency keys
+    pub idempotency_cleanup_interval_secs: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +93,44 @@ pub struct AuthConfig {
     pub cleanup_interval_secs: u64,
     pub refresh_extend_hours: u64,
     pub refresh_min_interval_minutes: u64,
+    /// How long an organization invitation link stays valid
+    pub invitation_ttl_hours: u64,
+    /// Days a deleted account is kept for recovery before it is purged (0 never purges)
+    pub deleted_user_retention_days: u64,
+    /// Whether purged accounts are removed or kept with their personal data scrubbed
+    pub deleted_user_purge_mode: PurgeMode,
+    /// How often the worker purges deleted accounts past their retention
+    pub user_purge_interval_secs: u64,
+    /// How often the worker reactivates accounts whose suspension has ended
+    pub suspension_check_interval_secs: u64,
+    /// How often the worker reverts temporary roles that have expired
+    pub role_expiry_check_interval_secs: u64,
+    /// Days a user must wait between changes of their own username (0 disables the cooldown)
+    pub username_change_cooldown_days: u64,
+    /// Days a previous username stays reserved for its former owner (0 reserves it forever)
+    pub username_reservation_days: u64,
+    /// How often the server stores the last-seen times of active users (0 disables tracking)
+    pub last_seen_flush_interval_secs: u64,
+    /// Users seen within this many minutes count as online
+    pub online_window_minutes: u64,
+    /// How long an email verification link stays valid
+    pub verification_ttl_hours: u64,
+    /// Seconds before another verification email can be sent to the same account
+    pub verification_resend_cooldown_secs: u64,
+    /// Verification emails an account can receive per 24 hours
+    pub verification_daily_limit: u32,
+    /// Active API keys a user may hold by role, unless the user has their own limit
+    pub api_key_quotas: RoleQuotaLimits,
+    /// Whether signed-in users are blocked until they accept the current legal documents
+    pub legal_acceptance_mode: LegalAcceptanceMode,
+    /// Recovery codes in each set a user generates
+    pub recovery_code_count: u32,
+    /// Days a self-serve account deletion can be cancelled before the account is erased
+    pub account_deletion_grace_days: u64,
+    /// Seconds the permissions granted by custom roles are cached per user (0 disables the cache)
+    pub permission_cache_ttl_secs: u64,
+    /// Whether the admin role can only be granted through an approved role request
+    pub admin_role_requires_approval: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,24 +139,348 @@ pub struct WorkerConfig {
     pub poll_interval_secs: u64,
     pub max_retries: u32,
     pub retry_backoff_base_secs: u64,
+    /// Completed/failed tasks older than this are moved to tasks_archive (0 disables archiving)
+    pub archive_after_days: u32,
+    pub archive_interval_secs: u64,
+    /// Maximum number of tasks claimed per poll
+    pub batch_size: usize,
+    pub claim_strategy: ClaimStrategy,
+    /// Running tasks whose lease is not renewed within this window are reset by the reaper
+    pub lease_secs: u64,
+    pub lease_reap_interval_secs: u64,
+    /// Port for the worker's Prometheus `/metrics` endpoint (0 disables it)
+    pub metrics_port: u16,
+    /// Timeout for each request made with the handlers' shared HTTP client
+    pub http_timeout_secs: u64,
+    /// Retries of transient HTTP failures (connection errors, 429, 5xx) before giving up
+    pub http_max_retries: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TasksConfig {
+    /// Maximum tasks a single user may create per minute (0 disables the limit)
+    pub rate_limit_per_minute: u32,
+    pub quotas: TaskQuotasConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoringConfig {
+    /// Days raw metrics are kept, unless a metric has its own retention policy
+    pub metric_raw_retention_days: u32,
+    /// Days 5-minute metric rollups are kept, unless a metric has its own retention policy
+    pub metric_rollup_retention_days: u32,
+    /// How often the worker rolls up and prunes metrics (0 disables the job)
+    pub metric_rollup_interval_secs: u64,
+    /// How often the worker escalates unacknowledged incidents and sends notifications (0 disables the job)
+    pub incident_escalation_interval_secs: u64,
+    /// How often the worker correlates error events into incidents (0 disables the job)
+    pub event_correlation_interval_secs: u64,
+    /// Error events from one source and tag set within the window that open an incident
+    pub event_correlation_threshold: u32,
+    pub event_correlation_window_secs: u64,
+    /// Correlated incidents resolve once no matching error event arrived for this long
+    pub event_correlation_quiet_secs: u64,
+    /// Maximum events in one batch ingestion request
+    pub event_batch_max_items: usize,
+    /// Maximum batch ingestion body size in bytes, after decompression
+    pub event_batch_max_bytes: usize,
+    /// Events accepted per source per minute, unless the source has its own limit (0 disables)
+    pub event_rate_limit_per_source: u32,
+    /// Events accepted per user per minute across all sources (0 disables)
+    pub event_rate_limit_per_user: u32,
+    /// Share of events stored (0.0-1.0), unless the source has its own ratio
+    pub event_sample_ratio: f64,
+    /// Events stored per user per UTC day by role, unless the user has an ingestion quota
+    pub event_daily_quotas: RoleQuotaLimits,
+    /// Distinct label combinations stored per metric name (0 disables the limit)
+    pub metric_max_series_per_name: u32,
+    /// What happens to metrics that would start a series over the limit
+    pub metric_cardinality_action: CardinalityLimitAction,
+    /// Days events are kept when no retention rule matches them (0 keeps them forever)
+    pub event_retention_days: u32,
+    /// Per type and level retention in days, e.g. `debug=3,error=180,trace:*=7`
+    pub event_retention_rules: String,
+    /// How often the worker deletes events past their retention (0 disables the job)
+    pub event_retention_interval_secs: u64,
+    /// How often the worker stores the results of due recording rules (0 disables the job)
+    pub recording_rule_interval_secs: u64,
+    /// How often the worker runs due uptime checks (0 disables the job)
+    pub uptime_check_interval_secs: u64,
+    /// How often the server and worker store metrics about themselves (0 disables)
+    pub self_metrics_interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservabilityConfig {
+    /// OTLP/HTTP collector base URL, e.g. `http://localhost:4318` (empty disables trace export)
+    pub otlp_endpoint: String,
+    /// Fraction of new traces exported (0.0-1.0); spans with a sampled parent are always kept
+    pub sampling_ratio: f64,
+    /// `service.name` reported with every exported span; also the source of stored logs
+    pub service_name: String,
+    /// Filter of the logs written to stdout, such as `info` or `starter=debug,info`;
+    /// `RUST_LOG` takes precedence when set
+    pub log_level: String,
+    /// Logs stored as monitoring events, as a target filter such as `warn` or
+    /// `starter=debug,warn` (`off` disables); independent of `RUST_LOG`
+    pub log_events_filter: String,
+    /// Fraction of debug and trace logs stored (0.0-1.0)
+    pub log_events_debug_sample_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Directory uploaded files such as avatars are kept in
+    pub path: String,
+    /// How long a user data export can be downloaded once built
+    pub export_ttl_hours: u64,
+    /// How often expired data export archives are deleted (0 disables)
+    pub export_cleanup_interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Where cached lookups are kept: `memory` on each server, or `redis` shared by all
+    pub backend: CacheBackend,
+    /// Redis connection URL for the `redis` backend, e.g. `redis://localhost:6379/0`
+    pub redis_url: String,
+    /// Prepended to every key, so several deployments can share one Redis database
+    pub key_prefix: String,
+    /// Seconds a validated session is cached (0 disables)
+    pub session_ttl_secs: u64,
+    /// Seconds a task type is remembered as registered (0 disables)
+    pub task_type_ttl_secs: u64,
+    /// Seconds feature flags are cached (0 disables); changes reach other
+    /// servers once their entry expires
+    pub feature_flag_ttl_secs: u64,
+    /// Seconds the maintenance toggle is cached (0 disables); changes reach
+    /// other servers once their entry expires
+    pub maintenance_ttl_secs: u64,
+}
+
+/// Request rate limits by route group, counted per client IP for public
+/// routes and per user for authenticated ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Whether requests are rate limited at all
+    pub enabled: bool,
+    /// Public routes other than the authentication ones
+    pub public: RateLimitRule,
+    /// Login, registration, verification and account recovery
+    pub auth: RateLimitRule,
+    /// Routes for signed-in users, including moderator routes
+    pub authenticated: RateLimitRule,
+    /// Admin routes
+    pub admin: RateLimitRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    /// Sustained requests allowed per minute (0 = unlimited)
+    pub requests_per_minute: u32,
+    /// Requests that can be made at once before the sustained rate applies
+    pub burst: u32,
+}
+
+/// Tenants sharing the deployment, each with its own users and data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenancyConfig {
+    /// Whether requests may name a tenant; when off everyone signs up in the default tenant
+    pub enabled: bool,
+    /// Domain whose subdomains name tenants, e.g. `example.com` for `acme.example.com`
+    /// (empty to rely on the `X-Tenant` header alone)
+    pub base_domain: String,
+    /// Seconds tenant lookups and users' tenants are cached
+    pub cache_ttl_secs: u64,
+}
+
+/// Fetching `secret://` settings from files, Vault or AWS Secrets Manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Seconds between fetching secrets again to pick up rotated values (0 = never)
+    pub refresh_interval_secs: u64,
+    /// Timeout of each request to Vault or AWS Secrets Manager
+    pub timeout_secs: u64,
+}
+
+/// WebSocket connections for live updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealtimeConfig {
+    /// WebSocket connections a user may have open on one server at once (0 = unlimited)
+    pub max_connections_per_user: u32,
+    /// Seconds between pings, which also check that the connection's session is still valid
+    pub heartbeat_interval_secs: u64,
+}
+
+/// Retention of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Days entries are kept unless their resource type has its own policy (0 = forever)
+    pub retention_days: u32,
+    /// How often the worker deletes entries past their retention
+    pub purge_interval_secs: u64,
+}
+
+/// Maintenance mode forced from the configuration, on top of the admin toggle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// Keep the API in maintenance mode whatever the admin toggle says; reloadable
+    pub enabled: bool,
+    /// Seconds clients are told to wait, unless the admin toggle sets its own
+    pub retry_after_secs: u64,
+}
+
+/// Largest request bodies accepted, in bytes, by route group
+///
+/// Larger requests are refused with `413 Payload Too Large`. Compressed
+/// bodies are counted as sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BodyLimitConfig {
+    /// Routes without a limit of their own
+    pub default_bytes: usize,
+    /// Login, registration and the other `/auth` routes
+    pub auth_bytes: usize,
+    /// Task routes, such as creating a task with its payload
+    pub tasks_bytes: usize,
+    /// Monitoring event, metric and OTLP ingestion
+    pub ingestion_bytes: usize,
+    /// File uploads, such as avatars and user imports
+    pub uploads_bytes: usize,
+}
+
+/// Task quotas resolved by the creating user's role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQuotasConfig {
+    pub user: TaskQuotaLimits,
+    pub moderator: TaskQuotaLimits,
+    pub admin: TaskQuotaLimits,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQuotaLimits {
+    /// Maximum unfinished (pending, running, retrying) tasks (0 = unlimited)
+    pub max_pending: u32,
+    /// Maximum tasks created per UTC day (0 = unlimited)
+    pub max_per_day: u32,
+}
+
+/// One limit per role (0 = unlimited)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleQuotaLimits {
+    pub user: u64,
+    pub moderator: u64,
+    pub admin: u64,
+}
+
+impl RoleQuotaLimits {
+    /// The limit for `role`, or None when it is unlimited
+    pub fn for_role(&self, role: UserRole) -> Option<i64> {
+        let limit = match role {
+            UserRole::User => self.user,
+            UserRole::Moderator => self.moderator,
+            UserRole::Admin => self.admin,
+        };
+        (limit > 0).then_some(limit as i64)
+    }
+}
+
+/// Environment variable naming the configuration profile, e.g. `development` or `staging`
+pub const CONFIG_PROFILE_ENV: &str = "STARTER_ENV";
+/// Environment variable naming the directory of the configuration files
+pub const CONFIG_DIR_ENV: &str = "STARTER_CONFIG_DIR";
+/// Environment variable naming an extra TOML configuration file
+pub const CONFIG_FILE_ENV: &str = "STARTER_CONFIG_FILE";
+
+const DEFAULT_CONFIG_PROFILE: &str = "development";
+const DEFAULT_CONFIG_DIR: &str = "config";
+
+/// A TOML configuration file read by [`AppConfig::load`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLayer {
+    pub path: PathBuf,
+    /// Whether loading fails when the file is missing
+    pub required: bool,
+}
+
+/// Configuration profile from `STARTER_ENV`, `development` by default
+pub fn config_profile() -> Result<String> {
+    let profile = std::env::var(CONFIG_PROFILE_ENV)
+        .ok()
+        .filter(|profile| !profile.is_empty())
+        .unwrap_or_else(|| DEFAULT_CONFIG_PROFILE.to_string());
+    if !profile
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::ConfigurationError(format!(
+            "{CONFIG_PROFILE_ENV} must only contain letters, digits, dashes and underscores, got '{profile}'"
+        )));
+    }
+    Ok(profile)
+}
+
+/// Configuration files of the current profile, lowest precedence first
+pub fn config_layers() -> Result<Vec<ConfigLayer>> {
+    let dir = std::env::var_os(CONFIG_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from(DEFAULT_CONFIG_DIR), PathBuf::from);
+    let file = std::env::var_os(CONFIG_FILE_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    Ok(layers_in(&dir, &config_profile()?, file))
+}
+
+/// `default.toml`, `{profile}.toml` and `local.toml` in `dir`, which may be
+/// missing, then `file`, which must exist
+fn layers_in(dir: &Path, profile: &str, file: Option<PathBuf>) -> Vec<ConfigLayer> {
+    let mut layers: Vec<ConfigLayer> = ["default", profile, "local"]
+        .into_iter()
+        .map(|name| ConfigLayer {
+            path: dir.join(format!("{name}.toml")),
+            required: false,
+        })
+        .collect();
+    layers.extend(file.map(|path| ConfigLayer {
+        path,
+        required: true,
+    }));
+    layers
+}
+
+/// `STARTER__*` environment variables, using `__` as the separator
+pub fn env_source() -> config::Environment {
+    config::Environment::with_prefix("STARTER")
+        .separator("__")
+        .try_parsing(true)
 }
 
 impl AppConfig {
-    /// Load configuration from environment variables only
+    /// Load configuration from the profile's files and environment variables
+    ///
+    /// Later sources override earlier ones: built-in defaults, then
+    /// `config/default.toml`, `config/{STARTER_ENV}.toml`, `config/local.toml`,
+    /// the file named by `STARTER_CONFIG_FILE`, and finally `STARTER__*`
+    /// environment variables.
     pub fn load() -> Result<Self> {
         // Load .env file if present
         dotenvy::dotenv().ok();
 
-        let config = config::Config::builder()
+        Self::load_from(&config_layers()?, env_source())
+    }
+
+    /// Load configuration from `layers`, overridden by the `env` source
+    pub fn load_from(layers: &[ConfigLayer], env: config::Environment) -> Result<Self> {
+        let mut builder = config::Config::builder()
             // Start with defaults
-            .add_source(config::Config::try_from(&Self::default())?)
-            // Override with environment variables using __ separator
-            .add_source(
-                config::Environment::with_prefix("STARTER")
-                    .separator("__")
-                    .try_parsing(true),
-            )
-            .build()?;
+            .add_source(config::Config::try_from(&Self::default())?);
+        for layer in layers {
+            builder = builder.add_source(
+                config::File::from(layer.path.as_path())
+                    .format(config::FileFormat::Toml)
+                    .required(layer.required),
+            );
+        }
+        let config = builder.add_source(env).build()?;
 
         let mut app_config: AppConfig = config
             .try_deserialize()
@@ -79,6 +490,14 @@ impl AppConfig {
         if let Ok(password) = std::env::var("STARTER__INITIAL_ADMIN_PASSWORD") {
             app_config.initial_admin_password = Some(SecretString::new(password.into()));
         }
+        if let Ok(token) = std::env::var("STARTER__SCIM_TOKEN")
+            && !token.is_empty()
+        {
+            app_config.scim_token = Some(SecretString::new(token.into()));
+        }
+
+        let resolver = SecretResolver::from_env(app_config.secrets_timeout());
+        app_config.secret_sources = resolve_config_secrets(&mut app_config, &resolver)?;
 
         app_config.validate()?;
         Ok(app_config)
@@ -115,6 +534,31 @@ impl AppConfig {
             ));
         }
 
+        if !self.server.api_v1_sunset.is_empty()
+            && DateTime::parse_from_rfc3339(&self.server.api_v1_sunset).is_err()
+        {
+            return Err(Error::ConfigurationError(
+                "api_v1_sunset must be an RFC 3339 time, e.g. 2027-06-30T00:00:00Z".to_string(),
+            ));
+        }
+
+        if let Some(proxy) = self
+            .server
+            .trusted_proxies
+            .iter()
+            .find(|proxy| parse_trusted_proxy(proxy).is_none())
+        {
+            return Err(Error::ConfigurationError(format!(
+                "trusted_proxies entry '{proxy}' is not an IP address or CIDR range"
+            )));
+        }
+
+        if self.server.idempotency_ttl_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "idempotency_ttl_secs must be greater than 0".to_string(),
+            ));
+        }
+
         // Validate connection pool settings
         if self.database.max_connections < self.database.min_connections {
             return Err(Error::ConfigurationError(
@@ -128,6 +572,13 @@ impl AppConfig {
             ));
         }
 
+        // Erasure tasks can be scheduled at most a year ahead
+        if self.auth.account_deletion_grace_days > 365 {
+            return Err(Error::ConfigurationError(
+                "Account deletion grace period must be at most 365 days".to_string(),
+            ));
+        }
+
         // Validate worker settings
         if self.worker.concurrency == 0 {
             return Err(Error::ConfigurationError(
@@ -135,9 +586,158 @@ impl AppConfig {
             ));
         }
 
+        if self.worker.batch_size == 0 {
+            return Err(Error::ConfigurationError(
+                "Worker batch_size must be > 0".to_string(),
+            ));
+        }
+
+        if self.worker.lease_secs == 0 || self.worker.lease_reap_interval_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "Worker lease_secs and lease_reap_interval_secs must be > 0".to_string(),
+            ));
+        }
+
+        if self.realtime.heartbeat_interval_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "Realtime heartbeat_interval_secs must be > 0".to_string(),
+            ));
+        }
+
+        if self.maintenance.retry_after_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "Maintenance retry_after_secs must be > 0".to_string(),
+            ));
+        }
+
+        let limits = &self.body_limit;
+        if [
+            limits.default_bytes,
+            limits.auth_bytes,
+            limits.tasks_bytes,
+            limits.ingestion_bytes,
+            limits.uploads_bytes,
+        ]
+        .contains(&0)
+        {
+            return Err(Error::ConfigurationError(
+                "Body limits must be > 0".to_string(),
+            ));
+        }
+
+        if self.audit.retention_days > crate::audit::models::MAX_RETENTION_DAYS as u32 {
+            return Err(Error::ConfigurationError(format!(
+                "Audit retention_days must be at most {}",
+                crate::audit::models::MAX_RETENTION_DAYS
+            )));
+        }
+
+        if self.monitoring.metric_raw_retention_days == 0
+            || self.monitoring.metric_rollup_retention_days == 0
+        {
+            return Err(Error::ConfigurationError(
+                "Monitoring metric retention days must be > 0".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.monitoring.event_sample_ratio) {
+            return Err(Error::ConfigurationError(
+                "Monitoring event_sample_ratio must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        crate::monitoring::event_retention::EventRetention::from_config(&self.monitoring)?;
+
+        if !(0.0..=1.0).contains(&self.observability.sampling_ratio) {
+            return Err(Error::ConfigurationError(
+                "Observability sampling_ratio must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+
+        if self.observability.service_name.is_empty() {
+            return Err(Error::ConfigurationError(
+                "Observability service_name cannot be empty".to_string(),
+            ));
+        }
+
+        tracing_subscriber::EnvFilter::try_new(&self.observability.log_level).map_err(|e| {
+            Error::ConfigurationError(format!("Invalid observability log_level: {e}"))
+        })?;
+
+        crate::monitoring::app_logs::parse_filter(&self.observability.log_events_filter)?;
+
+        if !(0.0..=1.0).contains(&self.observability.log_events_debug_sample_ratio) {
+            return Err(Error::ConfigurationError(
+                "Observability log_events_debug_sample_ratio must be between 0.0 and 1.0"
+                    .to_string(),
+            ));
+        }
+
+        if self.worker.http_timeout_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "Worker http_timeout_secs must be > 0".to_string(),
+            ));
+        }
+
+        if self.cache.backend == CacheBackend::Redis && self.cache.redis_url.is_empty() {
+            return Err(Error::ConfigurationError(
+                "Cache redis_url must be set for the redis backend".to_string(),
+            ));
+        }
+
+        for (group, rule) in [
+            ("public", self.rate_limit.public),
+            ("auth", self.rate_limit.auth),
+            ("authenticated", self.rate_limit.authenticated),
+            ("admin", self.rate_limit.admin),
+        ] {
+            if rule.requests_per_minute > 0 && rule.burst == 0 {
+                return Err(Error::ConfigurationError(format!(
+                    "Rate limit {group} burst must be > 0 when requests_per_minute is set"
+                )));
+            }
+        }
+
+        if self.worker.archive_after_days > 0 && self.worker.archive_interval_secs == 0 {
+            return Err(Error::ConfigurationError(
+                "Worker archive_interval_secs must be > 0 when archiving is enabled".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
+    /// This configuration with the settings that can be reloaded taken from `new`
+    ///
+    /// Everything else, such as the database and the listening address, only
+    /// changes on restart.
+    pub fn with_reloadable(&self, new: &AppConfig) -> AppConfig {
+        let mut config = self.clone();
+        config.rate_limit = new.rate_limit.clone();
+        config.observability.log_level = new.observability.log_level.clone();
+        config.worker.concurrency = new.worker.concurrency;
+        config.maintenance.enabled = new.maintenance.enabled;
+        config
+    }
+
+    /// Names of the reloadable settings that differ between this configuration and `other`
+    pub fn reloadable_changes(&self, other: &AppConfig) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+        if self.rate_limit != other.rate_limit {
+            changes.push("rate_limit");
+        }
+        if self.observability.log_level != other.observability.log_level {
+            changes.push("observability.log_level");
+        }
+        if self.worker.concurrency != other.worker.concurrency {
+            changes.push("worker.concurrency");
+        }
+        if self.maintenance.enabled != other.maintenance.enabled {
+            changes.push("maintenance.enabled");
+        }
+        changes
+    }
+
     /// Get server bind address
     pub fn bind_address(&self) -> String {
         format!("{}:{}", self.server.host, self.server.port)
@@ -168,6 +768,12 @@ impl AppConfig {
         )
     }
 
+    /// Get worker metrics bind address, if the endpoint is enabled
+    pub fn worker_metrics_address(&self) -> Option<String> {
+        (self.worker.metrics_port > 0)
+            .then(|| format!("{}:{}", self.server.host, self.worker.metrics_port))
+    }
+
     /// Get server request timeout
     pub fn request_timeout(&self) -> Duration {
         Duration::from_secs(self.server.request_timeout_secs)
@@ -193,6 +799,133 @@ impl AppConfig {
         chrono::Duration::hours(self.auth.session_duration_hours as i64)
     }
 
+    /// Get organization invitation lifetime
+    pub fn invitation_ttl(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.auth.invitation_ttl_hours as i64)
+    }
+
+    /// Get how long a self-serve account deletion can be cancelled
+    pub fn account_deletion_grace(&self) -> chrono::Duration {
+        chrono::Duration::days(self.auth.account_deletion_grace_days as i64)
+    }
+
+    /// Get how long custom role permissions are cached per user
+    pub fn permission_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.auth.permission_cache_ttl_secs)
+    }
+
+    /// Get when `/api/v1` may be removed, if announced
+    pub fn api_v1_sunset(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.server.api_v1_sunset)
+            .ok()
+            .map(|sunset| sunset.with_timezone(&Utc))
+    }
+
+    /// Get how long idempotent responses are replayed
+    pub fn idempotency_ttl(&self) -> Duration {
+        Duration::from_secs(self.server.idempotency_ttl_secs)
+    }
+
+    /// Get expired idempotency key cleanup interval
+    pub fn idempotency_cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.server.idempotency_cleanup_interval_secs)
+    }
+
+    /// Whether `ip` is one of the trusted reverse proxies
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.server
+            .trusted_proxies
+            .iter()
+            .filter_map(|proxy| parse_trusted_proxy(proxy))
+            .any(|proxy| proxy.contains(&ip))
+    }
+
+    /// Get the content type prefixes of compressed responses
+    pub fn compression_content_types(&self) -> Vec<String> {
+        self.server
+            .compression_content_types
+            .split(',')
+            .map(|content_type| content_type.trim().to_string())
+            .filter(|content_type| !content_type.is_empty())
+            .collect()
+    }
+
+    /// Get how long validated sessions are cached
+    pub fn session_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache.session_ttl_secs)
+    }
+
+    /// Get how long task type registrations are cached
+    pub fn task_type_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache.task_type_ttl_secs)
+    }
+
+    /// Get how long feature flags are cached
+    pub fn feature_flag_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache.feature_flag_ttl_secs)
+    }
+
+    /// Get how long the maintenance toggle is cached
+    pub fn maintenance_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache.maintenance_ttl_secs)
+    }
+
+    /// Get how long tenant lookups are cached
+    pub fn tenant_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.tenancy.cache_ttl_secs)
+    }
+
+    /// Get the timeout of each secrets manager request
+    pub fn secrets_timeout(&self) -> Duration {
+        Duration::from_secs(self.secrets.timeout_secs)
+    }
+
+    /// Get how often secrets are fetched again, or None when they aren't
+    pub fn secrets_refresh_interval(&self) -> Option<Duration> {
+        (self.secrets.refresh_interval_secs > 0)
+            .then(|| Duration::from_secs(self.secrets.refresh_interval_secs))
+    }
+
+    /// Get audit log purge interval
+    pub fn audit_purge_interval(&self) -> Duration {
+        Duration::from_secs(self.audit.purge_interval_secs)
+    }
+
+    /// Get the time between WebSocket heartbeats
+    pub fn realtime_heartbeat_interval(&self) -> Duration {
+        Duration::from_secs(self.realtime.heartbeat_interval_secs)
+    }
+
+    /// Get user data export download lifetime
+    pub fn export_ttl(&self) -> chrono::Duration {
+        chrono::Duration::hours(self.storage.export_ttl_hours as i64)
+    }
+
+    /// Get expired data export cleanup interval
+    pub fn export_cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.storage.export_cleanup_interval_secs)
+    }
+
+    /// Get deleted account purge interval
+    pub fn user_purge_interval(&self) -> Duration {
+        Duration::from_secs(self.auth.user_purge_interval_secs)
+    }
+
+    /// Get suspension expiry check interval
+    pub fn suspension_check_interval(&self) -> Duration {
+        Duration::from_secs(self.auth.suspension_check_interval_secs)
+    }
+
+    /// Get temporary role expiry check interval
+    pub fn role_expiry_check_interval(&self) -> Duration {
+        Duration::from_secs(self.auth.role_expiry_check_interval_secs)
+    }
+
+    /// Get last-seen flush interval
+    pub fn last_seen_flush_interval(&self) -> Duration {
+        Duration::from_secs(self.auth.last_seen_flush_interval_secs)
+    }
+
     /// Get auth cleanup interval
     pub fn cleanup_interval(&self) -> Duration {
         Duration::from_secs(self.auth.cleanup_interval_secs)
@@ -208,6 +941,61 @@ impl AppConfig {
         Duration::from_secs(self.worker.retry_backoff_base_secs)
     }
 
+    /// Get worker task lease duration
+    pub fn lease_duration(&self) -> Duration {
+        Duration::from_secs(self.worker.lease_secs)
+    }
+
+    /// Get worker lease reaper interval
+    pub fn lease_reap_interval(&self) -> Duration {
+        Duration::from_secs(self.worker.lease_reap_interval_secs)
+    }
+
+    /// Get timeout for requests made by task handlers
+    pub fn worker_http_timeout(&self) -> Duration {
+        Duration::from_secs(self.worker.http_timeout_secs)
+    }
+
+    /// Get worker task archive interval
+    pub fn archive_interval(&self) -> Duration {
+        Duration::from_secs(self.worker.archive_interval_secs)
+    }
+
+    /// Get metric rollup job interval
+    pub fn metric_rollup_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.metric_rollup_interval_secs)
+    }
+
+    /// Get incident escalation job interval
+    pub fn incident_escalation_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.incident_escalation_interval_secs)
+    }
+
+    /// Get event correlation job interval
+    pub fn event_correlation_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.event_correlation_interval_secs)
+    }
+
+    /// Get event retention job interval
+    pub fn event_retention_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.event_retention_interval_secs)
+    }
+
+    /// Get recording rule job interval
+    pub fn recording_rule_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.recording_rule_interval_secs)
+    }
+
+    /// Get uptime check job interval
+    pub fn uptime_check_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.uptime_check_interval_secs)
+    }
+
+    /// Get self-instrumentation flush interval
+    pub fn self_metrics_interval(&self) -> Duration {
+        Duration::from_secs(self.monitoring.self_metrics_interval_secs)
+    }
+
     /// Get refresh extend hours
     pub fn refresh_extend_hours(&self) -> i64 {
         self.auth.refresh_extend_hours as i64
@@ -226,8 +1014,18 @@ impl Default for AppConfig {
                 host: "127.0.0.1".to_string(),
                 port: 3000,
                 cors_origins: vec!["http://localhost:5173".to_string()],
+                trusted_proxies: Vec::new(),
                 request_timeout_secs: 30,
                 web_build_path: "web/dist".to_string(),
+                public_url: "http://localhost:5173".to_string(),
+                compression_enabled: true,
+                compression_min_bytes: 1024,
+                compression_content_types:
+                    "application/json,application/x-ndjson,text/csv,text/plain,application/yaml"
+                        .to_string(),
+                api_v1_sunset: String::new(),
+                idempotency_ttl_secs: 86400,
+                idempotency_cleanup_interval_secs: 3600,
             },
             database: DatabaseConfig {
                 user: "starter_user".to_string(),
@@ -246,33 +1044,191 @@ impl Default for AppConfig {
                 cleanup_interval_secs: 3600,     // 1 hour
                 refresh_extend_hours: 24,        // Default 24 hours extension
                 refresh_min_interval_minutes: 5, // Default 5 minutes minimum between refreshes
+                invitation_ttl_hours: 168,       // 7 days
+                deleted_user_retention_days: 30,
+                deleted_user_purge_mode: PurgeMode::Delete,
+                user_purge_interval_secs: 3600,
+                suspension_check_interval_secs: 60,
+                role_expiry_check_interval_secs: 60,
+                username_change_cooldown_days: 30,
+                username_reservation_days: 180,
+                last_seen_flush_interval_secs: 60,
+                online_window_minutes: 5,
+                verification_ttl_hours: 24,
+                verification_resend_cooldown_secs: 60,
+                verification_daily_limit: 5,
+                api_key_quotas: RoleQuotaLimits {
+                    user: 10,
+                    moderator: 25,
+                    admin: 0,
+                },
+                legal_acceptance_mode: LegalAcceptanceMode::Record,
+                recovery_code_count: 10,
+                account_deletion_grace_days: 14,
+                permission_cache_ttl_secs: 60,
+                admin_role_requires_approval: true,
             },
             worker: WorkerConfig {
                 concurrency: 4,
                 poll_interval_secs: 5,
                 max_retries: 3,
                 retry_backoff_base_secs: 2,
+                archive_after_days: 30,
+                archive_interval_secs: 3600, // 1 hour
+                batch_size: 50,
+                claim_strategy: ClaimStrategy::Batch,
+                lease_secs: 60,
+                lease_reap_interval_secs: 30,
+                metrics_port: 0,
+                http_timeout_secs: 30,
+                http_max_retries: 3,
+            },
+            tasks: TasksConfig {
+                rate_limit_per_minute: 120,
+                quotas: TaskQuotasConfig {
+                    user: TaskQuotaLimits {
+                        max_pending: 100,
+                        max_per_day: 1000,
+                    },
+                    moderator: TaskQuotaLimits {
+                        max_pending: 500,
+                        max_per_day: 5000,
+                    },
+                    admin: TaskQuotaLimits {
+                        max_pending: 0,
+                        max_per_day: 0,
+                    },
+                },
+            },
+            monitoring: MonitoringConfig {
+                metric_raw_retention_days: 7,
+                metric_rollup_retention_days: 90,
+                metric_rollup_interval_secs: 300, // 5 minutes
+                incident_escalation_interval_secs: 60,
+                event_correlation_interval_secs: 60,
+                event_correlation_threshold: 10,
+                event_correlation_window_secs: 300, // 5 minutes
+                event_correlation_quiet_secs: 1800, // 30 minutes
+                event_batch_max_items: 1000,
+                event_batch_max_bytes: 5 * 1024 * 1024, // 5MB
+                event_rate_limit_per_source: 6000,
+                event_rate_limit_per_user: 0,
+                event_sample_ratio: 1.0,
+                event_daily_quotas: RoleQuotaLimits {
+                    user: 0,
+                    moderator: 0,
+                    admin: 0,
+                },
+                metric_max_series_per_name: 1000,
+                metric_cardinality_action: CardinalityLimitAction::Reject,
+                event_retention_days: 30,
+                event_retention_rules: "trace=3,debug=3,error=180,fatal=180".to_string(),
+                event_retention_interval_secs: 3600, // 1 hour
+                recording_rule_interval_secs: 15,
+                uptime_check_interval_secs: 5,
+                self_metrics_interval_secs: 60,
+            },
+            observability: ObservabilityConfig {
+                otlp_endpoint: String::new(),
+                sampling_ratio: 1.0,
+                service_name: "starter".to_string(),
+                log_level: "info".to_string(),
+                log_events_filter: "warn".to_string(),
+                log_events_debug_sample_ratio: 0.1,
+            },
+            storage: StorageConfig {
+                path: "storage".to_string(),
+                export_ttl_hours: 24,
+                export_cleanup_interval_secs: 3600,
+            },
+            cache: CacheConfig {
+                backend: CacheBackend::Memory,
+                redis_url: String::new(),
+                key_prefix: "starter:".to_string(),
+                session_ttl_secs: 0,
+                task_type_ttl_secs: 300,
+                feature_flag_ttl_secs: 30,
+                maintenance_ttl_secs: 5,
+            },
+            rate_limit: RateLimitConfig {
+                enabled: true,
+                public: RateLimitRule {
+                    requests_per_minute: 300,
+                    burst: 60,
+                },
+                auth: RateLimitRule {
+                    requests_per_minute: 30,
+                    burst: 20,
+                },
+                authenticated: RateLimitRule {
+                    requests_per_minute: 600,
+                    burst: 120,
+                },
+                admin: RateLimitRule {
+                    requests_per_minute: 600,
+                    burst: 120,
+                },
+            },
+            tenancy: TenancyConfig {
+                enabled: false,
+                base_domain: String::new(),
+                cache_ttl_secs: 60,
+            },
+            secrets: SecretsConfig {
+                refresh_interval_secs: 0,
+                timeout_secs: 10,
+            },
+            realtime: RealtimeConfig {
+                max_connections_per_user: 5,
+                heartbeat_interval_secs: 30,
+            },
+            audit: AuditConfig {
+                retention_days: 365,
+                purge_interval_secs: 3600,
+            },
+            maintenance: MaintenanceConfig {
+                enabled: false,
+                retry_after_secs: 300,
+            },
+            body_limit: BodyLimitConfig {
+                default_bytes: 2 * 1024 * 1024,   // 2MB
+                auth_bytes: 64 * 1024,            // 64KB
+                tasks_bytes: 1536 * 1024,         // 1.5MB
+                ingestion_bytes: 8 * 1024 * 1024, // 8MB
+                uploads_bytes: 6 * 1024 * 1024,   // 6MB
             },
             initial_admin_password: None,
+            scim_token: None,
+            secret_sources: SecretSources::new(),
         }
     }
 }
 
-/// Custom deserializer for CORS origins that handles comma-separated strings
-fn deserialize_cors_origins<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+/// A single address or a CIDR range of trusted proxies
+fn parse_trusted_proxy(proxy: &str) -> Option<IpNet> {
+    proxy
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| proxy.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// Custom deserializer for lists, such as CORS origins, that handles comma-separated strings
+fn deserialize_comma_separated<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
     use serde::de::{self, Visitor};
     use std::fmt;
 
-    struct CorsOriginsVisitor;
+    struct CommaSeparatedVisitor;
 
-    impl<'de> Visitor<'de> for CorsOriginsVisitor {
+    impl<'de> Visitor<'de> for CommaSeparatedVisitor {
         type Value = Vec<String>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a string of comma-separated CORS origins or an array of strings")
+            formatter.write_str("a string of comma-separated values or an array of strings")
         }
 
         fn visit_str<E>(self, value: &str) -> std::result::Result<Self::Value, E>
@@ -298,7 +1254,7 @@ where
         }
     }
 
-    deserializer.deserialize_any(CorsOriginsVisitor)
+    deserializer.deserialize_any(CommaSeparatedVisitor)
 }
 
 // Conversion from config::ConfigError to our Error type
@@ -307,3 +1263,60 @@ impl From<config::ConfigError> for Error {
         Error::ConfigurationError(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_override_in_order() {
+        // Variables set in the test's environment must not override the files
+        let load = |layers: &[ConfigLayer]| {
+            AppConfig::load_from(layers, env_source().source(Some(Default::default())))
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            std::fs::write(dir.path().join(name), contents).unwrap();
+        };
+        write(
+            "default.toml",
+            "[tenancy]\ncache_ttl_secs = 10\nbase_domain = \"default.test\"\n",
+        );
+        write("staging.toml", "[tenancy]\ncache_ttl_secs = 20\n");
+        write("development.toml", "[tenancy]\ncache_ttl_secs = 30\n");
+        write("local.toml", "[tenancy]\nbase_domain = \"local.test\"\n");
+
+        let config = load(&layers_in(dir.path(), "staging", None)).unwrap();
+        assert_eq!(config.tenancy.cache_ttl_secs, 20);
+        assert_eq!(config.tenancy.base_domain, "local.test");
+        assert_eq!(config.server.port, AppConfig::default().server.port);
+
+        // Profile and local files are optional, an explicit file is not
+        let missing = tempfile::tempdir().unwrap();
+        assert!(load(&layers_in(missing.path(), "staging", None)).is_ok());
+        let explicit = layers_in(
+            missing.path(),
+            "staging",
+            Some(missing.path().join("x.toml")),
+        );
+        assert!(load(&explicit).is_err());
+    }
+
+    #[test]
+    fn test_trusted_proxies() {
+        let mut config = AppConfig::default();
+        assert!(!config.is_trusted_proxy("127.0.0.1".parse().unwrap()));
+
+        config.server.trusted_proxies = vec!["10.0.0.0/8".to_string(), "::1".to_string()];
+        assert!(config.validate().is_ok());
+        assert!(config.is_trusted_proxy("10.1.2.3".parse().unwrap()));
+        assert!(config.is_trusted_proxy("::1".parse().unwrap()));
+        assert!(!config.is_trusted_proxy("11.0.0.1".parse().unwrap()));
+
+        config
+            .server
+            .trusted_proxies
+            .push("proxy.internal".to_string());
+        assert!(config.validate().is_err());
+    }
+}
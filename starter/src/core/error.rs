@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde_json::json;
@@ -30,6 +30,9 @@ pub enum Error {
     #[error("Token has expired")]
     TokenExpired,
 
+    #[error("The current legal documents must be accepted")]
+    LegalAcceptanceRequired,
+
     // Validation errors
     #[error("Validation failed for {field}: {message}")]
     ValidationError { field: String, message: String },
@@ -56,6 +59,12 @@ pub enum Error {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Request body exceeds {limit_bytes} bytes")]
+    PayloadTooLarge { limit_bytes: usize },
+
     // System errors
     #[error("Internal error: {0}")]
     Internal(String),
@@ -63,6 +72,21 @@ pub enum Error {
     #[error("Service unavailable")]
     ServiceUnavailable,
 
+    #[error("Rate limit exceeded, retry after {retry_after_secs} seconds")]
+    RateLimited { retry_after_secs: u64 },
+
+    #[error("Down for maintenance: {message}")]
+    Maintenance {
+        message: String,
+        retry_after_secs: u64,
+    },
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Metric '{metric}' has reached its limit of {limit} label combinations")]
+    CardinalityLimitExceeded { metric: String, limit: u32 },
+
     // Task/Worker errors
     #[error("Task not found")]
     TaskNotFound,
@@ -127,10 +151,10 @@ impl Error {
     }
 }
 
-// Axum response conversion
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        let (status, error_message, error_code) = match &self {
+impl Error {
+    /// Status code, client-facing message and code of the error response
+    pub fn parts(&self) -> (StatusCode, String, &'static str) {
+        match self {
             Error::Database(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Database error occurred".to_string(),
@@ -157,6 +181,11 @@ impl IntoResponse for Error {
                 "Token has expired".to_string(),
                 "TOKEN_EXPIRED",
             ),
+            Error::LegalAcceptanceRequired => (
+                StatusCode::FORBIDDEN,
+                "The current terms and privacy policy must be accepted".to_string(),
+                "LEGAL_ACCEPTANCE_REQUIRED",
+            ),
             Error::ValidationError { field, message } => (
                 StatusCode::BAD_REQUEST,
                 format!("Validation failed for {field}: {message}"),
@@ -185,6 +214,16 @@ impl IntoResponse for Error {
                 "USERNAME_ALREADY_EXISTS",
             ),
             Error::Conflict(msg) => (StatusCode::CONFLICT, msg.clone(), "CONFLICT"),
+            Error::UnsupportedMediaType(msg) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                msg.clone(),
+                "UNSUPPORTED_MEDIA_TYPE",
+            ),
+            Error::PayloadTooLarge { limit_bytes } => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body exceeds the limit of {limit_bytes} bytes"),
+                "PAYLOAD_TOO_LARGE",
+            ),
             Error::Internal(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
@@ -200,6 +239,24 @@ impl IntoResponse for Error {
                 "Service unavailable".to_string(),
                 "SERVICE_UNAVAILABLE",
             ),
+            Error::RateLimited { retry_after_secs } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Rate limit exceeded, retry after {retry_after_secs} seconds"),
+                "RATE_LIMITED",
+            ),
+            Error::Maintenance { message, .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                message.clone(),
+                "MAINTENANCE",
+            ),
+            Error::QuotaExceeded(msg) => {
+                (StatusCode::TOO_MANY_REQUESTS, msg.clone(), "QUOTA_EXCEEDED")
+            }
+            Error::CardinalityLimitExceeded { .. } => (
+                StatusCode::BAD_REQUEST,
+                self.to_string(),
+                "CARDINALITY_LIMIT_EXCEEDED",
+            ),
             Error::TaskNotFound => (
                 StatusCode::NOT_FOUND,
                 "Task not found".to_string(),
@@ -215,23 +272,56 @@ impl IntoResponse for Error {
                 format!("Worker error: {msg}"),
                 "WORKER_ERROR",
             ),
-        };
+        }
+    }
 
-        // Log internal errors for debugging
+    /// Log errors whose details are hidden from clients, for debugging
+    pub fn log_internal(&self) {
         if matches!(
             self,
             Error::Database(_) | Error::Internal(_) | Error::ConfigurationError(_)
         ) {
             tracing::error!("Internal error: {}", self);
         }
+    }
+}
 
-        let body = Json(json!({
-            "error": {
-                "code": error_code,
-                "message": error_message,
-            }
-        }));
+// Axum response conversion
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let (status, error_message, error_code) = self.parts();
+        self.log_internal();
+
+        let mut error = json!({
+            "code": error_code,
+            "message": error_message,
+        });
+        if let Error::CardinalityLimitExceeded { metric, limit } = &self {
+            error["details"] = json!({ "metric": metric, "limit": limit });
+        }
+        if let Error::PayloadTooLarge { limit_bytes } = &self {
+            error["details"] = json!({ "limit_bytes": limit_bytes });
+        }
+        if let Error::Maintenance {
+            retry_after_secs, ..
+        } = &self
+        {
+            error["details"] = json!({ "retry_after_secs": retry_after_secs });
+        }
+        let body = Json(json!({ "error": error }));
+
+        let mut response = (status, body).into_response();
+
+        if let Error::RateLimited { retry_after_secs }
+        | Error::Maintenance {
+            retry_after_secs, ..
+        } = self
+        {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
 
-        (status, body).into_response()
+        response
     }
 }
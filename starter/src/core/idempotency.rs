@@ -0,0 +1,508 @@
+//! Replay of requests sent with an `Idempotency-Key`
+//!
+//! A client that may retry a `POST` or `PATCH`, e.g. after a timeout, sends the
+//! same `Idempotency-Key` header with every attempt. The first attempt runs and
+//! its response is stored with a hash of the request; retries within
+//! `idempotency_ttl_secs` get the stored response back, marked with
+//! `Idempotent-Replayed: true`, instead of running the request again. Keys
+//! belong to the user who sent them, so the middleware runs on authenticated
+//! routes only.
+//!
+//! A retry while the first attempt still runs gets a 409, and a key reused for
+//! a different request (another method, path or body) a 400. Server errors,
+//! rate limits (429) and conflicts (409) are not stored, so a retry after one
+//! runs the request again; neither are responses over
+//! [`MAX_STORED_RESPONSE_BYTES`] or of unknown length, such as streamed exports.
+//!
+//! The request body is read with the body limit of the route group, so the
+//! middleware is layered inside each group's limit; see [`limit_idempotent_body`].
+
+use crate::auth::AuthUser;
+use crate::core::body_limit::{self, limit_body};
+use crate::core::error::Error;
+use crate::{AppState, DbConn, DbPool, Result};
+use axum::{
+    Router,
+    body::{Body, Bytes, HttpBody},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+pub const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+pub const REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Longest key accepted
+const MAX_KEY_LEN: usize = 255;
+
+/// Largest response stored for replay; larger ones run again on a retry
+pub const MAX_STORED_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// Headers not stored for replay, besides `Proxy-*` ones: hop-by-hop ones,
+/// which only apply to the connection they were sent on, `Date`, which is set
+/// again, and `Content-Length`, which the replayed body sets
+const UNSTORED_HEADERS: [HeaderName; 8] = [
+    header::CONNECTION,
+    HeaderName::from_static("keep-alive"),
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+    header::DATE,
+    header::CONTENT_LENGTH,
+];
+
+/// A response stored for replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status_code: i16,
+    /// Header names and values in response order
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    /// The headers of `headers` worth replaying; values that aren't visible
+    /// ASCII are left out
+    pub fn replayable_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .filter(|(name, _)| {
+                !UNSTORED_HEADERS.contains(name) && !name.as_str().starts_with("proxy-")
+            })
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status_code as u16).unwrap_or(StatusCode::OK);
+        let mut response = (status, self.body).into_response();
+        let headers = response.headers_mut();
+        headers.remove(header::CONTENT_TYPE);
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+/// What claiming a key for a request found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Claim {
+    /// The key is new or expired; the request runs
+    Acquired,
+    /// The request already ran; its response is replayed
+    Completed(StoredResponse),
+    /// The request is still running
+    InProgress,
+    /// The key was used for another request
+    Mismatch,
+}
+
+/// Hash of the method, path with query, and body identifying a request
+pub fn request_hash(method: &Method, path_and_query: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b"\n");
+    hasher.update(path_and_query);
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Claim a key for a request, unless it is already held or done
+pub async fn claim(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    key: &str,
+    request_hash: &str,
+    ttl: Duration,
+) -> Result<Claim> {
+    let expires_at = Utc::now() + ttl;
+    let acquired = sqlx::query_scalar!(
+        r#"
+        INSERT INTO idempotency_keys (user_id, key, request_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, key) DO UPDATE
+            SET request_hash = EXCLUDED.request_hash,
+                status_code = NULL,
+                headers = '[]',
+                body = NULL,
+                created_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            WHERE idempotency_keys.expires_at <= NOW()
+        RETURNING user_id
+        "#,
+        user_id,
+        key,
+        request_hash,
+        expires_at
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if acquired.is_some() {
+        return Ok(Claim::Acquired);
+    }
+
+    let existing = sqlx::query!(
+        r#"
+        SELECT request_hash, status_code, headers, body
+        FROM idempotency_keys
+        WHERE user_id = $1 AND key = $2
+        "#,
+        user_id,
+        key
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // The first attempt failed and released the key in between; the client
+    // retries again
+    let Some(existing) = existing else {
+        return Ok(Claim::InProgress);
+    };
+    if existing.request_hash != request_hash {
+        return Ok(Claim::Mismatch);
+    }
+    Ok(match existing.status_code {
+        Some(status_code) => Claim::Completed(StoredResponse {
+            status_code,
+            headers: serde_json::from_value(existing.headers).unwrap_or_default(),
+            body: existing.body.unwrap_or_default(),
+        }),
+        None => Claim::InProgress,
+    })
+}
+
+/// Store the response of a claimed key for replay
+pub async fn complete(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    key: &str,
+    response: &StoredResponse,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE idempotency_keys
+        SET status_code = $3, headers = $4, body = $5
+        WHERE user_id = $1 AND key = $2
+        "#,
+        user_id,
+        key,
+        response.status_code,
+        serde_json::json!(response.headers),
+        response.body
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Release a claimed key so a retry runs the request again
+pub async fn release(conn: &mut DbConn, user_id: Uuid, key: &str) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2 AND status_code IS NULL",
+        user_id,
+        key
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Delete keys past their expiry
+pub async fn delete_expired(conn: &mut DbConn) -> Result<u64> {
+    let result = sqlx::query!("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    Ok(result.rows_affected())
+}
+
+/// Releases a claimed key if the request is dropped before it completes,
+/// e.g. when the client disconnects
+struct ClaimGuard {
+    pool: DbPool,
+    user_id: Uuid,
+    key: String,
+    armed: bool,
+}
+
+impl Drop for ClaimGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let (pool, user_id, key) = (self.pool.clone(), self.user_id, self.key.clone());
+        tokio::spawn(async move {
+            if let Err(e) = release_with(&pool, user_id, &key).await {
+                error!("Failed to release idempotency key: {}", e);
+            }
+        });
+    }
+}
+
+async fn release_with(pool: &DbPool, user_id: Uuid, key: &str) -> Result<()> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    release(conn.as_mut(), user_id, key).await
+}
+
+/// Limit the bodies of the requests to `router`'s routes to `limit_bytes`,
+/// like [`limit_body`], and replay their responses with
+/// [`idempotency_middleware`]
+pub fn limit_idempotent_body(
+    router: Router<AppState>,
+    state: &AppState,
+    limit_bytes: usize,
+) -> Router<AppState> {
+    limit_body(
+        router.layer(middleware::from_fn_with_state(
+            (state.clone(), limit_bytes),
+            idempotency_middleware,
+        )),
+        limit_bytes,
+    )
+}
+
+/// Replay responses of `POST` and `PATCH` requests retried with the same
+/// `Idempotency-Key`; needs the [`AuthUser`] of the auth middleware and the
+/// body limit of the route group
+pub async fn idempotency_middleware(
+    State((app_state, limit_bytes)): State<(AppState, usize)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !matches!(*request.method(), Method::POST | Method::PATCH) {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let Some(user_id) = request.extensions().get::<AuthUser>().map(|user| user.id) else {
+        return next.run(request).await;
+    };
+    match idempotent(app_state, limit_bytes, user_id, key.clone(), request, next).await {
+        Ok(response) => response,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn idempotent(
+    app_state: AppState,
+    limit_bytes: usize,
+    user_id: Uuid,
+    key: HeaderValue,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LEN)
+        .ok_or_else(|| {
+            Error::validation(
+                "Idempotency-Key",
+                &format!("Must be 1 to {MAX_KEY_LEN} visible ASCII characters"),
+            )
+        })?
+        .to_string();
+
+    let (parts, body) = request.into_parts();
+    let body = body_limit::to_bytes(body, limit_bytes).await?;
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or_default();
+    let hash = request_hash(&parts.method, path_and_query, &body);
+
+    let pool = app_state.database.pool.clone();
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    let ttl = app_state.config.idempotency_ttl();
+    match claim(conn.as_mut(), user_id, &key, &hash, ttl).await? {
+        Claim::Acquired => {}
+        Claim::Completed(stored) => return Ok(stored.into_response()),
+        Claim::InProgress => {
+            return Err(Error::conflict(
+                "A request with this Idempotency-Key is still being processed",
+            ));
+        }
+        Claim::Mismatch => {
+            return Err(Error::validation(
+                "Idempotency-Key",
+                "Already used for a different request",
+            ));
+        }
+    }
+    // Don't hold a connection while the handler runs
+    drop(conn);
+
+    let mut guard = ClaimGuard {
+        pool: pool.clone(),
+        user_id,
+        key: key.clone(),
+        armed: true,
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    // Bodies are only buffered when their length is known to fit, so larger
+    // and streamed ones reach the client untouched
+    let storable = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|len| len <= MAX_STORED_RESPONSE_BYTES as u64);
+    if is_retryable(response.status()) || !storable {
+        release(conn.as_mut(), user_id, &key).await?;
+        guard.armed = false;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body: Bytes = axum::body::to_bytes(body, MAX_STORED_RESPONSE_BYTES)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to read response: {e}")))?;
+    let stored = StoredResponse {
+        status_code: parts.status.as_u16() as i16,
+        headers: StoredResponse::replayable_headers(&parts.headers),
+        body: body.to_vec(),
+    };
+    complete(conn.as_mut(), user_id, &key, &stored).await?;
+    guard.armed = false;
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Whether a retry may get a different response, so the key is released
+/// instead of storing this one: server errors, rate limits and conflicts
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::CONFLICT)
+}
+
+/// Background job deleting expired idempotency keys
+pub async fn idempotency_cleanup_job(pool: DbPool, run_interval: Duration) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            delete_expired(conn.as_mut()).await
+        }
+        .await;
+        match result {
+            Ok(deleted) if deleted > 0 => {
+                info!("Idempotency key cleanup: {} keys deleted", deleted)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to clean up idempotency keys: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hash_covers_method_path_and_body() {
+        let hash = request_hash(&Method::POST, "/tasks", b"{}");
+        assert_eq!(hash, request_hash(&Method::POST, "/tasks", b"{}"));
+        assert_ne!(hash, request_hash(&Method::PATCH, "/tasks", b"{}"));
+        assert_ne!(hash, request_hash(&Method::POST, "/users", b"{}"));
+        assert_ne!(hash, request_hash(&Method::POST, "/tasks", b"{\"a\":1}"));
+    }
+
+    #[test]
+    fn test_replayable_headers_skip_hop_by_hop_and_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+        headers.insert(header::RETRY_AFTER, HeaderValue::from_static("30"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("b=2"));
+        headers.insert(header::CONNECTION, HeaderValue::from_static("close"));
+        headers.insert(
+            header::TRANSFER_ENCODING,
+            HeaderValue::from_static("chunked"),
+        );
+        headers.insert("proxy-connection", HeaderValue::from_static("close"));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("2"));
+        headers.insert(
+            header::DATE,
+            HeaderValue::from_static("Sat, 17 Oct 2026 00:00:00 GMT"),
+        );
+
+        let stored = StoredResponse {
+            status_code: 202,
+            headers: StoredResponse::replayable_headers(&headers),
+            body: b"{}".to_vec(),
+        };
+        assert_eq!(
+            stored.headers,
+            [
+                ("content-type", "text/csv"),
+                ("retry-after", "30"),
+                ("set-cookie", "a=1"),
+                ("set-cookie", "b=2"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+
+        let response = stored.into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response
+                .headers()
+                .get_all(header::SET_COOKIE)
+                .iter()
+                .count(),
+            2
+        );
+        assert_eq!(response.headers()[REPLAYED_HEADER], "true");
+    }
+
+    #[test]
+    fn test_transient_failures_are_not_stored() {
+        for status in [
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::SERVICE_UNAVAILABLE,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::CONFLICT,
+        ] {
+            assert!(is_retryable(status), "{status}");
+        }
+        for status in [
+            StatusCode::OK,
+            StatusCode::CREATED,
+            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
+        ] {
+            assert!(!is_retryable(status), "{status}");
+        }
+    }
+}
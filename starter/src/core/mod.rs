@@ -1,15 +1,24 @@
 //! Core application infrastructure
 //!
 //! This module contains the fundamental infrastructure components that form
-//! the backbone of the application, including configuration, database,
-//! error handling, application state, server setup, and OpenAPI documentation.
+//! the backbone of the application, including configuration, database, caching,
+//! error handling, application state, request body limits, idempotent request replay, rate limiting, configuration reload,
+//! secrets resolution, Server-Sent Events streams, server setup, telemetry, and OpenAPI documentation.
 
+pub mod body_limit;
+pub mod cache;
 pub mod config;
 pub mod database;
 pub mod error;
+pub mod idempotency;
 pub mod openapi;
+pub mod rate_limit;
+pub mod reload;
+pub mod secrets;
 pub mod server;
+pub mod sse;
 pub mod state;
+pub mod telemetry;
 pub mod types;
 
 // Re-export commonly used types for convenience
@@ -1,27 +1,104 @@
+use axum::http::Method;
+use serde_json::json;
 use utoipa::{
     Modify, OpenApi,
-    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    openapi::{
+        extensions::Extensions,
+        path::{Operation, PathItem},
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
 };
 use utoipa_swagger_ui::SwaggerUi;
 
+use crate::rbac::routes::RoutePermission;
+
+use crate::rbac::models::{
+    CreateCustomRoleRequest, CreatePermissionBundleRequest, CreatePermissionDenyRequest,
+    CreateRoleRequest, CustomRole, CustomRoleMember, EffectivePermissions, PermissionBundle,
+    PermissionBundleUser, PermissionDeny, PolicyChangeOp, PolicyDeny, PolicyRole, PolicyShare,
+    RbacAuditEntry, RbacPolicy, RbacPolicyChange, RbacPolicyImport, ResourceShare, RoleRequest,
+    RoleRequestStatus, ShareResourceRequest, SharedObjectPermissions, UpdateCustomRoleRequest,
+    UpdatePermissionBundleRequest,
+};
+
+use crate::audit::models::{
+    AuditEntry, AuditResourceType, AuditRetentionPolicy, AuditRetentionSettings,
+    SetAuditRetentionRequest,
+};
 use crate::auth::{
     AuthUser,
-    models::{LoginRequest, LoginResponse, RegisterRequest},
+    models::{
+        AcceptLegalDocumentsRequest, CreateApiKeyRequest, CreateScopedSessionRequest,
+        CreateServiceAccountRequest, GenerateRecoveryCodesRequest, LegalDocument,
+        LegalDocumentKind, LegalDocumentRef, LegalDocumentStatus, LegalDocumentVersion,
+        LegalStatus, LoginRequest, LoginResponse, PublishLegalDocumentRequest,
+        RecoverAccountRequest, RecoveryCodeStatus, RecoveryCodes, RegisterRequest, Registration,
+        ResendVerificationRequest, ScopedSession, ScopedSessionToken, ServiceAccount,
+        ServiceAccountKey, ServiceAccountKeyToken, VerifyEmailRequest,
+    },
+};
+use crate::features::models::{CreateFeatureFlagRequest, FeatureFlag, UpdateFeatureFlagRequest};
+use crate::maintenance::models::{MaintenanceStatus, SetMaintenanceRequest};
+use crate::monitoring::grafana::{
+    GrafanaAdhocFilter, GrafanaMetricOption, GrafanaMetricsRequest, GrafanaQueryRange,
+    GrafanaQueryRequest, GrafanaQueryTarget, GrafanaTagKey, GrafanaTagValue,
+    GrafanaTagValuesRequest, GrafanaTimeSeries,
 };
 use crate::monitoring::models::{
-    Alert, CreateAlertRequest, CreateEventRequest, CreateIncidentRequest, CreateMetricRequest,
-    Event, EventFilter, EventType, Incident, IncidentSeverity, IncidentStatus, IncidentTimeline,
-    Metric, MetricFilter, MetricType, MonitoringStats, TimelineEntry, UpdateIncidentRequest,
+    Alert, AlertNotification, AlertRoutingRule, AlertSeverity, AlertTestResult, AppActivity,
+    ChannelDelivery, CreateActionItemRequest, CreateAlertRequest, CreateAlertRoutingRuleRequest,
+    CreateEscalationPolicyRequest, CreateEventRequest, CreateHistogramRequest,
+    CreateIncidentNoteRequest, CreateIncidentRequest, CreateMetricRequest,
+    CreateNotificationChannelRequest, CreateOncallOverrideRequest, CreateOncallScheduleRequest,
+    CreateRecordingRuleRequest, CreateStatusPageRequest, CreateSummaryRequest,
+    CreateUptimeCheckRequest, EscalationPolicy, EscalationStep, EscalationStepRequest, Event,
+    EventBatchError, EventBatchResult, EventFilter, EventLimitSettings, EventSourceLimit,
+    EventType, Incident, IncidentEscalation, IncidentNote, IncidentSeverity, IncidentStatus,
+    IncidentTimeline, IngestionQuota, IngestionUsage, Metric, MetricCardinality, MetricFilter,
+    MetricPoint, MetricQueryPoint, MetricQueryResult, MetricQuerySeries, MetricResolution,
+    MetricRetentionPolicy, MetricRetentionSettings, MetricRowsStored, MetricSeries, MetricType,
+    MonitoringStats, NotificationChannel, NotificationChannelKind, OncallOverride, OncallSchedule,
+    OncallShift, Postmortem, PostmortemActionItem, PublicStatusPage, QuotaScope, RecordingRule,
+    ResolveIncidentRequest, SetAlertChannelsRequest, SetEventSourceLimitRequest,
+    SetIngestionQuotaRequest, SetMetricRetentionRequest, StatusPage, StatusPageCheck,
+    StatusPageIncident, StatusPageStatus, SummaryQuantile, TimelineEntry, TimelineEntryType,
+    UpdateActionItemRequest, UpdateIncidentNoteRequest, UpdateIncidentRequest,
+    UpdateStatusPageRequest, UpsertPostmortemRequest, UptimeCheck, UptimeCheckStatus,
+};
+use crate::monitoring::otlp::{OtlpExportResponse, OtlpPartialSuccess};
+use crate::orgs::models::{
+    AcceptOrgInvitationRequest, AcceptedOrgInvitation, CreateOrgInvitationRequest,
+    CreateOrgRequest, Org, OrgInvitation, OrgInvitationStatus, OrgMember, OrgRole,
+    SetOrgMemberRequest,
 };
 use crate::rbac::models::UserRole;
+use crate::realtime::models::{ClientMessage, ConnectionInfo, Notification, ServerMessage, Topic};
+use crate::scim::models::{
+    PatchOperation, ScimEmail, ScimErrorBody, ScimGroup, ScimGroupRef, ScimGroupRequest,
+    ScimMember, ScimMeta, ScimPatchRequest, ScimUser, ScimUserRequest,
+};
 use crate::tasks::api::{
-    CreateTaskApiRequest, RegisterTaskTypeRequest, TaskQueryParams, TaskTypeResponse,
+    CreateTaskApiRequest, RegisterTaskTypeRequest, TaskExportParams, TaskQueryParams,
+    TaskTypeResponse,
+};
+use crate::tasks::retry::CircuitState;
+use crate::tasks::types::{
+    ArchivedTaskResponse, CircuitBreakerStatus, CreateTaskRequest, CreateTaskTemplateRequest,
+    ExportFormat, QuotaUsage, SchedulePreview, SchedulePreviewRequest, TaskAttempt,
+    TaskFromTemplateRequest, TaskPage, TaskPriority, TaskQueueState, TaskQueueStats, TaskQuota,
+    TaskResponse, TaskStats, TaskStatus, TaskTemplate, UpdateTaskTemplateRequest,
 };
-use crate::tasks::types::{CreateTaskRequest, TaskPriority, TaskResponse, TaskStats, TaskStatus};
+use crate::tenants::models::{CreateTenantRequest, Tenant, TenantScimToken, UpdateTenantRequest};
 use crate::users::models::{
-    ChangePasswordRequest, CreateUserRequest, DeleteAccountRequest, DeleteUserRequest,
-    RecentRegistrations, ResetPasswordRequest, UpdateProfileRequest, UpdateUserProfileRequest,
-    UpdateUserRoleRequest, UpdateUserStatusRequest, User, UserProfile, UserRoleStats, UserStats,
+    AccountDeletion, AccountDeletionStatus, AvatarUpload, CancelAccountDeletionRequest,
+    ChangePasswordRequest, CreateUserRequest, DataExport, DataExportStatus, DeactivationSummary,
+    DeleteAccountRequest, DeleteUserRequest, ImportPasswordPolicy, ImportRowStatus, OnlineUser,
+    OnlineUsersSummary, PaginationInfo, ProfileField, ProfileFieldType, ProfileFieldVisibility,
+    PurgeMode, RecentRegistrations, ResetPasswordRequest, SortOrder, UpdateInternalMetadataRequest,
+    UpdateProfileRequest, UpdateUserProfileRequest, UpdateUserQuotasRequest, UpdateUserRoleRequest,
+    UpdateUserStatusRequest, UpsertProfileFieldRequest, User, UserActivity, UserImport,
+    UserImportRow, UserImportStatus, UserImportUpload, UserListResponse, UserProfile, UserPurge,
+    UserQuota, UserQuotas, UserRoleStats, UserStats, UserStatusUpdate,
 };
 use crate::{
     api::ErrorResponse,
@@ -58,7 +135,62 @@ use crate::{
         crate::auth::api::logout,
         crate::auth::api::logout_all,
         crate::auth::api::me,
+        crate::auth::api::my_permissions,
         crate::auth::api::refresh,
+        crate::auth::api::resend_verification,
+        crate::auth::api::verify_email,
+        crate::auth::api::current_legal_documents,
+        crate::auth::api::legal_status,
+        crate::auth::api::accept_legal_documents,
+        crate::auth::api::list_legal_documents,
+        crate::auth::api::publish_legal_document,
+        crate::auth::api::recovery_code_status,
+        crate::auth::api::generate_recovery_codes,
+        crate::auth::api::recover_account,
+        crate::auth::api::create_scoped_session,
+        crate::auth::api::list_scoped_sessions,
+        crate::auth::api::revoke_scoped_session,
+        crate::auth::api::list_service_accounts,
+        crate::auth::api::create_service_account,
+        crate::auth::api::list_service_account_keys,
+        crate::auth::api::create_service_account_key,
+        crate::auth::api::revoke_service_account_key,
+
+        // Custom role endpoints
+        crate::rbac::api::list_roles,
+        crate::rbac::api::create_role,
+        crate::rbac::api::get_role,
+        crate::rbac::api::update_role,
+        crate::rbac::api::delete_role,
+        crate::rbac::api::list_role_members,
+        crate::rbac::api::assign_role,
+        crate::rbac::api::unassign_role,
+        crate::rbac::api::list_bundles,
+        crate::rbac::api::create_bundle,
+        crate::rbac::api::get_bundle,
+        crate::rbac::api::update_bundle,
+        crate::rbac::api::delete_bundle,
+        crate::rbac::api::attach_bundle_to_role,
+        crate::rbac::api::detach_bundle_from_role,
+        crate::rbac::api::list_bundle_users,
+        crate::rbac::api::attach_bundle_to_user,
+        crate::rbac::api::detach_bundle_from_user,
+        crate::rbac::api::list_role_requests,
+        crate::rbac::api::create_role_request,
+        crate::rbac::api::approve_role_request,
+        crate::rbac::api::reject_role_request,
+        crate::rbac::api::list_rbac_audit,
+        crate::rbac::api::list_permission_denies,
+        crate::rbac::api::create_permission_deny,
+        crate::rbac::api::delete_permission_deny,
+        crate::rbac::api::export_rbac_policy,
+        crate::rbac::api::import_rbac_policy,
+
+        // Sharing endpoints
+        crate::rbac::api::list_shares,
+        crate::rbac::api::share_resource,
+        crate::rbac::api::unshare_resource,
+        crate::rbac::api::list_received_shares,
 
         // User endpoints
         crate::users::api::get_profile,
@@ -66,40 +198,206 @@ use crate::{
         crate::users::api::list_users,
         crate::users::api::create_user,
         crate::users::api::update_own_profile,
+        crate::users::api::upload_own_avatar,
         crate::users::api::change_own_password,
         crate::users::api::delete_own_account,
+        crate::users::api::cancel_account_deletion,
+        crate::users::api::request_own_data_export,
+        crate::users::api::list_own_data_exports,
+        crate::users::api::get_own_data_export,
+        crate::users::api::download_data_export,
         crate::users::api::update_user_profile,
         crate::users::api::update_user_status,
         crate::users::api::update_user_role,
         crate::users::api::reset_user_password,
+        crate::users::api::update_internal_metadata,
         crate::users::api::delete_user,
         crate::users::api::get_user_stats,
+        crate::users::api::get_online_users,
+        crate::users::api::export_users,
+        crate::users::api::force_verify_email,
+        crate::users::api::get_user_quotas,
+        crate::users::api::update_user_quotas,
+        crate::users::api::list_user_purges,
+        crate::users::api::list_own_activity,
+        crate::users::api::list_user_activity,
+        crate::users::api::import_users,
+        crate::users::api::get_user_import,
+        crate::users::api::list_profile_fields,
+        crate::users::api::upsert_profile_field,
+        crate::users::api::delete_profile_field,
+        crate::storage::api::get_file,
+
+        // Organization endpoints
+        crate::orgs::api::create_org,
+        crate::orgs::api::list_orgs,
+        crate::orgs::api::get_org,
+        crate::orgs::api::delete_org,
+        crate::orgs::api::list_org_members,
+        crate::orgs::api::set_org_member,
+        crate::orgs::api::remove_org_member,
+        crate::orgs::api::create_org_invitation,
+        crate::orgs::api::list_org_invitations,
+        crate::orgs::api::resend_org_invitation,
+        crate::orgs::api::revoke_org_invitation,
+        crate::orgs::api::accept_org_invitation,
+
+        // Tenant endpoints
+        crate::tenants::api::list_tenants,
+        crate::tenants::api::create_tenant,
+        crate::tenants::api::update_tenant,
+        crate::tenants::api::issue_scim_token,
+        crate::tenants::api::revoke_scim_token,
+
+        // Audit log endpoints
+        crate::audit::api::list_audit_log,
+        crate::audit::api::get_audit_retention,
+        crate::audit::api::set_audit_retention,
+        crate::audit::api::delete_audit_retention,
+
+        // Maintenance endpoints
+        crate::maintenance::api::get_maintenance,
+        crate::maintenance::api::set_maintenance,
+
+        // Feature flag endpoints
+        crate::features::api::get_features,
+        crate::features::api::list_flags,
+        crate::features::api::create_flag,
+        crate::features::api::update_flag,
+        crate::features::api::delete_flag,
+
+        // Realtime endpoints
+        crate::realtime::api::websocket,
+        crate::realtime::api::list_connections,
+        crate::realtime::api::stream_notifications,
+
+        // SCIM provisioning endpoints
+        crate::scim::api::get_service_provider_config,
+        crate::scim::api::list_resource_types,
+        crate::scim::api::list_users,
+        crate::scim::api::create_user,
+        crate::scim::api::get_user,
+        crate::scim::api::replace_user,
+        crate::scim::api::patch_user,
+        crate::scim::api::delete_user,
+        crate::scim::api::list_groups,
+        crate::scim::api::get_group,
+        crate::scim::api::replace_group,
+        crate::scim::api::patch_group,
 
         // Task endpoints
         crate::tasks::api::create_task,
         crate::tasks::api::list_tasks,
         crate::tasks::api::get_task,
         crate::tasks::api::get_stats,
+        crate::tasks::api::get_queue_stats,
+        crate::tasks::api::get_quota,
+        crate::tasks::api::preview_schedule,
+        crate::tasks::api::list_task_templates,
+        crate::tasks::api::get_task_template,
+        crate::tasks::api::create_task_template,
+        crate::tasks::api::update_task_template,
+        crate::tasks::api::delete_task_template,
+        crate::tasks::api::create_task_from_template,
         crate::tasks::api::cancel_task,
+        crate::tasks::api::get_task_attempts,
+        crate::tasks::api::get_child_tasks,
         crate::tasks::api::register_task_type,
         crate::tasks::api::list_task_types,
         crate::tasks::api::get_dead_letter_queue,
+        crate::tasks::api::list_archived_tasks,
+        crate::tasks::api::export_tasks,
+        crate::tasks::api::stream_tasks,
         crate::tasks::api::retry_task,
         crate::tasks::api::delete_task,
+        crate::tasks::api::list_circuit_breakers,
+        crate::tasks::api::trip_circuit_breaker,
+        crate::tasks::api::reset_circuit_breaker,
+        crate::tasks::api::list_task_queues,
+        crate::tasks::api::pause_task_queue,
+        crate::tasks::api::resume_task_queue,
 
         // Monitoring endpoints with utoipa::path attributes (annotated endpoints only)
         crate::monitoring::api::create_event,
+        crate::monitoring::api::create_event_batch,
         crate::monitoring::api::get_events,
+        crate::monitoring::api::export_events,
+        crate::monitoring::api::stream_events,
         crate::monitoring::api::get_event_by_id,
         crate::monitoring::api::create_metric,
+        crate::monitoring::api::create_histogram,
+        crate::monitoring::api::create_summary,
         crate::monitoring::api::get_metrics,
+        crate::monitoring::api::export_metrics,
+        crate::monitoring::api::get_metric_series,
+        crate::monitoring::api::query_metrics,
+        crate::monitoring::api::grafana_health,
+        crate::monitoring::api::grafana_metrics,
+        crate::monitoring::api::grafana_query,
+        crate::monitoring::api::grafana_tag_keys,
+        crate::monitoring::api::grafana_tag_values,
+        crate::monitoring::api::ingest_otlp_traces,
+        crate::monitoring::api::ingest_otlp_metrics,
+        crate::monitoring::api::ingest_otlp_logs,
+        crate::monitoring::api::get_metric_retention,
+        crate::monitoring::api::set_metric_retention,
+        crate::monitoring::api::delete_metric_retention,
+        crate::monitoring::api::get_event_limits,
+        crate::monitoring::api::set_event_limit,
+        crate::monitoring::api::delete_event_limit,
+        crate::monitoring::api::get_ingestion_quotas,
+        crate::monitoring::api::set_ingestion_quota,
+        crate::monitoring::api::delete_ingestion_quota,
         crate::monitoring::api::create_alert,
         crate::monitoring::api::get_alerts,
+        crate::monitoring::api::test_alert,
+        crate::monitoring::api::get_alert_channels,
+        crate::monitoring::api::set_alert_channels,
+        crate::monitoring::api::create_notification_channel,
+        crate::monitoring::api::get_notification_channels,
+        crate::monitoring::api::delete_notification_channel,
+        crate::monitoring::api::create_recording_rule,
+        crate::monitoring::api::get_recording_rules,
+        crate::monitoring::api::delete_recording_rule,
+        crate::monitoring::api::create_uptime_check,
+        crate::monitoring::api::get_uptime_checks,
+        crate::monitoring::api::delete_uptime_check,
+        crate::monitoring::api::create_alert_routing_rule,
+        crate::monitoring::api::get_alert_routing_rules,
+        crate::monitoring::api::delete_alert_routing_rule,
+        crate::monitoring::api::create_status_page,
+        crate::monitoring::api::get_status_pages,
+        crate::monitoring::api::update_status_page,
+        crate::monitoring::api::delete_status_page,
+        crate::monitoring::api::get_public_status_page,
         crate::monitoring::api::create_incident,
         crate::monitoring::api::get_incidents,
         crate::monitoring::api::get_incident_by_id,
         crate::monitoring::api::update_incident,
         crate::monitoring::api::get_incident_timeline,
+        crate::monitoring::api::create_incident_note,
+        crate::monitoring::api::update_incident_note,
+        crate::monitoring::api::delete_incident_note,
+        crate::monitoring::api::acknowledge_incident,
+        crate::monitoring::api::resolve_incident,
+        crate::monitoring::api::escalate_incident,
+        crate::monitoring::api::get_incident_escalations,
+        crate::monitoring::api::get_incident_postmortem,
+        crate::monitoring::api::upsert_incident_postmortem,
+        crate::monitoring::api::create_postmortem_action_item,
+        crate::monitoring::api::update_postmortem_action_item,
+        crate::monitoring::api::delete_postmortem_action_item,
+        crate::monitoring::api::create_escalation_policy,
+        crate::monitoring::api::get_escalation_policies,
+        crate::monitoring::api::get_escalation_policy_by_id,
+        crate::monitoring::api::delete_escalation_policy,
+        crate::monitoring::api::get_current_oncall,
+        crate::monitoring::api::create_oncall_schedule,
+        crate::monitoring::api::get_oncall_schedules,
+        crate::monitoring::api::get_oncall_schedule_by_id,
+        crate::monitoring::api::delete_oncall_schedule,
+        crate::monitoring::api::create_oncall_override,
+        crate::monitoring::api::delete_oncall_override,
         crate::monitoring::api::get_monitoring_stats,
         crate::monitoring::api::get_prometheus_metrics,
 
@@ -109,12 +407,83 @@ use crate::{
             // Auth models
             LoginRequest,
             RegisterRequest,
+            ResendVerificationRequest,
+            VerifyEmailRequest,
             LoginResponse,
             AuthUser,
+            LegalDocumentKind,
+            LegalDocument,
+            LegalDocumentVersion,
+            PublishLegalDocumentRequest,
+            LegalDocumentRef,
+            AcceptLegalDocumentsRequest,
+            LegalDocumentStatus,
+            LegalStatus,
+            Registration,
+            // Custom role models
+            CustomRole,
+            CreateCustomRoleRequest,
+            UpdateCustomRoleRequest,
+            CustomRoleMember,
+            PermissionBundle,
+            CreatePermissionBundleRequest,
+            UpdatePermissionBundleRequest,
+            PermissionBundleUser,
+            RoleRequest,
+            RoleRequestStatus,
+            CreateRoleRequest,
+            RbacAuditEntry,
+            EffectivePermissions,
+            SharedObjectPermissions,
+            PermissionDeny,
+            CreatePermissionDenyRequest,
+            RbacPolicy,
+            PolicyRole,
+            PolicyDeny,
+            PolicyShare,
+            RbacPolicyImport,
+            RbacPolicyChange,
+            PolicyChangeOp,
+            ResourceShare,
+            ShareResourceRequest,
+            RecoveryCodes,
+            CreateScopedSessionRequest,
+            ScopedSession,
+            ScopedSessionToken,
+            ServiceAccount,
+            CreateServiceAccountRequest,
+            ServiceAccountKey,
+            ServiceAccountKeyToken,
+            CreateApiKeyRequest,
+            RecoveryCodeStatus,
+            GenerateRecoveryCodesRequest,
+            RecoverAccountRequest,
 
             // User models
             User,
             UserProfile,
+            AvatarUpload,
+            ProfileFieldType,
+            ProfileFieldVisibility,
+            ProfileField,
+            UpsertProfileFieldRequest,
+            DataExportStatus,
+            DataExport,
+            AccountDeletionStatus,
+            AccountDeletion,
+            CancelAccountDeletionRequest,
+            PurgeMode,
+            UserPurge,
+            UserActivity,
+            ImportPasswordPolicy,
+            UserImportStatus,
+            ImportRowStatus,
+            UserImportRow,
+            UserImport,
+            UserImportUpload,
+            UserListResponse,
+            PaginationInfo,
+            SortOrder,
             CreateUserRequest,
             UpdateProfileRequest,
             ChangePasswordRequest,
@@ -123,42 +492,193 @@ use crate::{
             UpdateUserStatusRequest,
             UpdateUserRoleRequest,
             ResetPasswordRequest,
+            UpdateInternalMetadataRequest,
             DeleteUserRequest,
+            DeactivationSummary,
+            UserStatusUpdate,
             UserStats,
             UserRoleStats,
             RecentRegistrations,
+            OnlineUser,
+            OnlineUsersSummary,
+            UserQuota,
+            UserQuotas,
+            UpdateUserQuotasRequest,
             UserRole,
 
+            // Organization models
+            Org,
+            OrgMember,
+            OrgRole,
+            CreateOrgRequest,
+            SetOrgMemberRequest,
+            OrgInvitationStatus,
+            OrgInvitation,
+            CreateOrgInvitationRequest,
+            AcceptOrgInvitationRequest,
+            AcceptedOrgInvitation,
+
+            // Tenant models
+            Tenant,
+            TenantScimToken,
+            CreateTenantRequest,
+            UpdateTenantRequest,
+            AuditEntry,
+            AuditResourceType,
+            AuditRetentionPolicy,
+            AuditRetentionSettings,
+            SetAuditRetentionRequest,
+            MaintenanceStatus,
+            SetMaintenanceRequest,
+            FeatureFlag,
+            CreateFeatureFlagRequest,
+            UpdateFeatureFlagRequest,
+
+            // Realtime models
+            Topic,
+            ClientMessage,
+            ServerMessage,
+            Notification,
+            ConnectionInfo,
+
+            // SCIM models
+            ScimUser,
+            ScimEmail,
+            ScimGroupRef,
+            ScimGroup,
+            ScimMember,
+            ScimMeta,
+            ScimUserRequest,
+            ScimGroupRequest,
+            ScimPatchRequest,
+            PatchOperation,
+            ScimErrorBody,
+
             // Task models
             CreateTaskRequest,
             CreateTaskApiRequest,
             TaskResponse,
+            TaskPage,
+            ArchivedTaskResponse,
+            TaskAttempt,
+            TaskQueueStats,
+            TaskQueueState,
+            SchedulePreviewRequest,
+            SchedulePreview,
+            TaskTemplate,
+            CreateTaskTemplateRequest,
+            UpdateTaskTemplateRequest,
+            TaskFromTemplateRequest,
+            CircuitBreakerStatus,
+            CircuitState,
+            TaskQuota,
+            QuotaUsage,
             TaskStatus,
             TaskPriority,
             TaskStats,
             TaskQueryParams,
+            TaskExportParams,
+            ExportFormat,
             RegisterTaskTypeRequest,
             TaskTypeResponse,
 
             // Monitoring models
             Event,
             CreateEventRequest,
+            EventBatchResult,
+            EventBatchError,
             EventType,
             EventFilter,
             Metric,
             CreateMetricRequest,
+            CreateHistogramRequest,
+            CreateSummaryRequest,
+            SummaryQuantile,
+            MetricRowsStored,
             MetricType,
             MetricFilter,
+            MetricResolution,
+            MetricPoint,
+            MetricSeries,
+            MetricQueryPoint,
+            MetricQuerySeries,
+            MetricQueryResult,
+            GrafanaMetricsRequest,
+            GrafanaMetricOption,
+            GrafanaQueryRequest,
+            GrafanaQueryRange,
+            GrafanaQueryTarget,
+            GrafanaAdhocFilter,
+            GrafanaTimeSeries,
+            GrafanaTagKey,
+            GrafanaTagValuesRequest,
+            GrafanaTagValue,
+            MetricRetentionPolicy,
+            MetricRetentionSettings,
+            SetMetricRetentionRequest,
+            EventSourceLimit,
+            EventLimitSettings,
+            SetAlertChannelsRequest, SetEventSourceLimitRequest,
+            QuotaScope,
+            IngestionQuota,
+            SetIngestionQuotaRequest,
+            IngestionUsage,
+            OtlpExportResponse,
+            OtlpPartialSuccess,
             Alert,
+            AlertSeverity,
             CreateAlertRequest,
+            NotificationChannel,
+            RecordingRule,
+            CreateRecordingRuleRequest,
+            UptimeCheck,
+            UptimeCheckStatus,
+            CreateUptimeCheckRequest,
+            AlertRoutingRule,
+            CreateAlertRoutingRuleRequest,
+            StatusPage,
+            CreateStatusPageRequest,
+            UpdateStatusPageRequest,
+            StatusPageStatus,
+            StatusPageCheck,
+            StatusPageIncident,
+            PublicStatusPage,
+            NotificationChannelKind,
+            CreateNotificationChannelRequest,
+            SetAlertChannelsRequest,
+            AlertNotification,
+            ChannelDelivery,
+            AlertTestResult,
             Incident,
             CreateIncidentRequest,
             UpdateIncidentRequest,
+            ResolveIncidentRequest,
+            IncidentEscalation,
+            Postmortem,
+            PostmortemActionItem,
+            UpsertPostmortemRequest,
+            CreateActionItemRequest,
+            UpdateActionItemRequest,
+            EscalationPolicy,
+            EscalationStep,
+            EscalationStepRequest,
+            CreateEscalationPolicyRequest,
+            OncallSchedule,
+            OncallOverride,
+            OncallShift,
+            CreateOncallScheduleRequest,
+            CreateOncallOverrideRequest,
             IncidentSeverity,
             IncidentStatus,
             IncidentTimeline,
             TimelineEntry,
+            TimelineEntryType,
+            IncidentNote,
+            CreateIncidentNoteRequest,
+            UpdateIncidentNoteRequest,
             MonitoringStats,
+            AppActivity,
+            MetricCardinality,
 
             // Common response types
             ErrorResponse,
@@ -168,13 +688,22 @@ use crate::{
             DetailedHealthResponse,
         )
     ),
-    modifiers(&SecurityAddon),
+    modifiers(&SecurityAddon, &PermissionMapAddon),
     tags(
         (name = "Health", description = "Health check and monitoring endpoints"),
         (name = "Authentication", description = "User authentication and session management"),
         (name = "Users", description = "User management operations"),
+        (name = "Roles", description = "Custom roles with granular permissions"),
+        (name = "Sharing", description = "Access to single objects shared with users or roles"),
+        (name = "Organizations", description = "Organizations and team membership"),
+        (name = "Tenants", description = "Tenants sharing the deployment"),
+        (name = "Audit", description = "Append-only log of changes and its retention"),
+        (name = "Features", description = "Feature flags for dark launches"),
+        (name = "Realtime", description = "Live updates over WebSocket"),
         (name = "Tasks", description = "Background task management"),
         (name = "Monitoring", description = "Observability and monitoring system"),
+        (name = "Files", description = "Stored uploads such as avatars"),
+        (name = "SCIM", description = "SCIM 2.0 user provisioning for identity providers"),
     )
 )]
 pub struct ApiDoc;
@@ -187,11 +716,69 @@ impl Modify for SecurityAddon {
             components.add_security_scheme(
                 "bearer_auth",
                 SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+            components.add_security_scheme(
+                "scim_token",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .description(Some("Token configured with STARTER__SCIM_TOKEN"))
+                        .build(),
+                ),
             )
         }
     }
 }
 
+/// Requirements declared with [`PermissionRoutes`](crate::rbac::PermissionRoutes),
+/// with the paths they are served at
+pub fn route_permissions() -> Vec<RoutePermission> {
+    crate::users::api::users_staff_routes().permissions("/users")
+}
+
+/// Adds each declared requirement to its operation as `x-required-permission`,
+/// and the whole endpoint to permission map to the document as `x-permission-map`
+struct PermissionMapAddon;
+
+impl Modify for PermissionMapAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let mut map = serde_json::Map::new();
+        for route in route_permissions() {
+            let requirement = json!({
+                "permission": route.requirement.key(),
+                "staff": route.requirement.is_staff(),
+            });
+            if let Some(operation) = openapi
+                .paths
+                .paths
+                .get_mut(&route.path)
+                .and_then(|item| operation_mut(item, &route.method))
+            {
+                operation
+                    .extensions
+                    .get_or_insert_with(Extensions::default)
+                    .insert("x-required-permission".to_string(), requirement.clone());
+            }
+            map.insert(format!("{} {}", route.method, route.path), requirement);
+        }
+        openapi
+            .extensions
+            .get_or_insert_with(Extensions::default)
+            .insert("x-permission-map".to_string(), map.into());
+    }
+}
+
+fn operation_mut<'a>(item: &'a mut PathItem, method: &Method) -> Option<&'a mut Operation> {
+    match *method {
+        Method::GET => item.get.as_mut(),
+        Method::POST => item.post.as_mut(),
+        Method::PUT => item.put.as_mut(),
+        Method::PATCH => item.patch.as_mut(),
+        Method::DELETE => item.delete.as_mut(),
+        _ => None,
+    }
+}
+
 /// Create Swagger UI service (to be added manually to server)
 pub fn create_swagger_ui() -> SwaggerUi {
     SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi())
@@ -226,6 +813,31 @@ mod tests {
         assert!(json.contains("Rust Full-Stack Starter API"));
     }
 
+    #[test]
+    fn test_permission_map() {
+        let openapi = ApiDoc::openapi();
+
+        // Every declared route is documented and carries its requirement
+        for route in route_permissions() {
+            let operation = openapi
+                .paths
+                .paths
+                .get(&route.path)
+                .cloned()
+                .and_then(|mut item| operation_mut(&mut item, &route.method).cloned())
+                .unwrap_or_else(|| panic!("{} {} is not documented", route.method, route.path));
+            let extensions = operation.extensions.expect("extensions");
+            assert_eq!(
+                extensions["x-required-permission"]["permission"],
+                route.requirement.key()
+            );
+        }
+
+        let map = &openapi.extensions.expect("extensions")["x-permission-map"];
+        assert_eq!(map["PUT /users/{id}/status"]["permission"], "users:write");
+        assert_eq!(map["GET /users"]["staff"], true);
+    }
+
     #[test]
     fn test_swagger_ui_creation() {
         // Just verify it creates without panicking
@@ -0,0 +1,317 @@
+//! Request rate limiting by route group
+//!
+//! Each route group has its own [`RateLimitRule`], enforced with the generic
+//! cell rate algorithm (GCRA): a client may send `burst` requests at once and
+//! then `requests_per_minute` spread over the minute. Requests are counted per
+//! user on authenticated routes and per client IP elsewhere. The client IP is
+//! the connection's peer address; forwarded headers only count when the peer
+//! is one of `server.trusted_proxies`, so clients can't pick a fresh counter
+//! by sending a different `X-Forwarded-For`. The counters live
+//! in the memory of each server, so with several servers a client gets the
+//! limit on each of them. Limits are read from the [`LiveConfig`] on every
+//! request, so reloaded limits apply right away.
+//!
+//! Every limited response carries `X-RateLimit-Limit` (the burst size),
+//! `X-RateLimit-Remaining` (requests that can be sent right away) and
+//! `X-RateLimit-Reset` (seconds until the full burst is available again).
+//! Rejected requests get a 429 with `Retry-After`.
+
+use crate::auth::AuthUser;
+use crate::auth::api::client_ip;
+use crate::core::config::{RateLimitConfig, RateLimitRule};
+use crate::core::error::Error;
+use crate::core::reload::LiveConfig;
+use axum::{
+    Extension,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Clients tracked before those with a full burst available again are dropped
+const MAX_ENTRIES: usize = 100_000;
+
+const LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const REMAINING_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Route groups with their own rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitGroup {
+    Public,
+    Auth,
+    Authenticated,
+    Admin,
+}
+
+/// Outcome of counting one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Requests that can be sent at once
+    pub limit: u32,
+    /// Requests that can still be sent right away
+    pub remaining: u32,
+    /// Time until the full burst is available again
+    pub reset_after: Duration,
+    /// Time until a rejected request would be allowed
+    pub retry_after: Duration,
+}
+
+/// Per-client request counters for every route group
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: Arc<LiveConfig>,
+    /// Theoretical arrival time of each client's next request, per group
+    entries: Mutex<HashMap<(RateLimitGroup, String), Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: Arc<LiveConfig>) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn rule(config: &RateLimitConfig, group: RateLimitGroup) -> RateLimitRule {
+        match group {
+            RateLimitGroup::Public => config.public,
+            RateLimitGroup::Auth => config.auth,
+            RateLimitGroup::Authenticated => config.authenticated,
+            RateLimitGroup::Admin => config.admin,
+        }
+    }
+
+    /// Count a request of `client` at `now`, or None when the group is not limited
+    pub fn check(
+        &self,
+        group: RateLimitGroup,
+        client: &str,
+        now: Instant,
+    ) -> Option<RateLimitDecision> {
+        let config = self.config.get();
+        let rule = Self::rule(&config.rate_limit, group);
+        if !config.rate_limit.enabled || rule.requests_per_minute == 0 {
+            return None;
+        }
+        let interval = Duration::from_secs(60) / rule.requests_per_minute;
+        let capacity = interval * rule.burst;
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (group, client.to_string());
+        let tat = entries
+            .get(&key)
+            .copied()
+            .filter(|tat| *tat > now)
+            .unwrap_or(now);
+        let next_tat = tat + interval;
+        let used = next_tat - now;
+
+        if used > capacity {
+            return Some(RateLimitDecision {
+                allowed: false,
+                limit: rule.burst,
+                remaining: 0,
+                reset_after: tat - now,
+                retry_after: used - capacity,
+            });
+        }
+
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            entries.retain(|_, tat| *tat > now);
+        }
+        entries.insert(key, next_tat);
+        Some(RateLimitDecision {
+            allowed: true,
+            limit: rule.burst,
+            remaining: ((capacity - used).as_nanos() / interval.as_nanos()) as u32,
+            reset_after: used,
+            retry_after: Duration::ZERO,
+        })
+    }
+}
+
+/// Whole seconds, rounded up
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(LIMIT_HEADER, HeaderValue::from(decision.limit));
+    headers.insert(REMAINING_HEADER, HeaderValue::from(decision.remaining));
+    headers.insert(
+        RESET_HEADER,
+        HeaderValue::from(ceil_secs(decision.reset_after)),
+    );
+}
+
+/// Middleware limiting the requests of each user, or client IP when signed out
+///
+/// Layer it inside `auth_middleware` so authenticated requests are counted per user.
+pub async fn rate_limit(
+    State((limiter, group)): State<(Arc<RateLimiter>, RateLimitGroup)>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = match request.extensions().get::<AuthUser>() {
+        Some(auth_user) => format!("user:{}", auth_user.id),
+        None => format!(
+            "ip:{}",
+            client_ip(&limiter.config.get(), request.headers(), peer).unwrap_or_default()
+        ),
+    };
+
+    let Some(decision) = limiter.check(group, &client, Instant::now()) else {
+        return next.run(request).await;
+    };
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::debug!("Rate limited {client} on {group:?} routes");
+        Error::RateLimited {
+            retry_after_secs: ceil_secs(decision.retry_after).max(1),
+        }
+        .into_response()
+    };
+    insert_headers(response.headers_mut(), &decision);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AppConfig;
+
+    fn rate_limit_config(requests_per_minute: u32, burst: u32) -> AppConfig {
+        let rule = RateLimitRule {
+            requests_per_minute,
+            burst,
+        };
+        AppConfig {
+            rate_limit: RateLimitConfig {
+                enabled: true,
+                public: rule,
+                auth: rule,
+                authenticated: rule,
+                admin: rule,
+            },
+            ..AppConfig::default()
+        }
+    }
+
+    fn limiter(requests_per_minute: u32, burst: u32) -> RateLimiter {
+        RateLimiter::new(Arc::new(LiveConfig::new(rate_limit_config(
+            requests_per_minute,
+            burst,
+        ))))
+    }
+
+    #[test]
+    fn test_burst_then_sustained_rate() {
+        let limiter = limiter(60, 3);
+        let now = Instant::now();
+
+        let remaining: Vec<u32> = (0..3)
+            .map(|_| {
+                let decision = limiter.check(RateLimitGroup::Public, "a", now).unwrap();
+                assert!(decision.allowed);
+                decision.remaining
+            })
+            .collect();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let rejected = limiter.check(RateLimitGroup::Public, "a", now).unwrap();
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.retry_after, Duration::from_secs(1));
+        assert_eq!(rejected.reset_after, Duration::from_secs(3));
+
+        // One request frees up per second at 60 per minute
+        let later = now + Duration::from_secs(1);
+        assert!(
+            limiter
+                .check(RateLimitGroup::Public, "a", later)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !limiter
+                .check(RateLimitGroup::Public, "a", later)
+                .unwrap()
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_clients_and_groups_are_counted_separately() {
+        let limiter = limiter(60, 1);
+        let now = Instant::now();
+
+        assert!(
+            limiter
+                .check(RateLimitGroup::Auth, "a", now)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !limiter
+                .check(RateLimitGroup::Auth, "a", now)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            limiter
+                .check(RateLimitGroup::Auth, "b", now)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            limiter
+                .check(RateLimitGroup::Admin, "a", now)
+                .unwrap()
+                .allowed
+        );
+    }
+
+    #[test]
+    fn test_unlimited_groups() {
+        let now = Instant::now();
+        assert_eq!(limiter(0, 0).check(RateLimitGroup::Public, "a", now), None);
+
+        let limiter = limiter(60, 1);
+        let mut config = rate_limit_config(60, 1);
+        config.rate_limit.enabled = false;
+        limiter.config.update(&config);
+        assert_eq!(limiter.check(RateLimitGroup::Public, "a", now), None);
+    }
+
+    #[test]
+    fn test_reloaded_limits_apply_to_tracked_clients() {
+        let limiter = limiter(60, 1);
+        let now = Instant::now();
+        assert!(
+            limiter
+                .check(RateLimitGroup::Public, "a", now)
+                .unwrap()
+                .allowed
+        );
+        assert!(
+            !limiter
+                .check(RateLimitGroup::Public, "a", now)
+                .unwrap()
+                .allowed
+        );
+
+        limiter.config.update(&rate_limit_config(60, 5));
+        let decision = limiter.check(RateLimitGroup::Public, "a", now).unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.limit, 5);
+    }
+}
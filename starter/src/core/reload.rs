@@ -0,0 +1,176 @@
+//! Reloading configuration without a restart
+//!
+//! On `SIGHUP`, or when one of the configuration files changes, the
+//! configuration is loaded again and its reloadable settings are swapped into
+//! the running [`LiveConfig`]: rate limits, the stdout log level, worker
+//! concurrency and forced maintenance mode. Other settings only change on
+//! restart. A configuration that
+//! fails to load or validate is logged and the running one is kept.
+//!
+//! The `.env` file and `STARTER_ENV` are only read at startup, so reloaded
+//! values come from the configuration files of the startup profile.
+
+use crate::Result;
+use crate::core::config::{AppConfig, config_layers};
+use crate::core::telemetry;
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// How often the configuration files are checked for changes
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Configuration of the running process, swapped atomically on reload
+#[derive(Debug)]
+pub struct LiveConfig {
+    current: ArcSwap<AppConfig>,
+}
+
+impl LiveConfig {
+    pub fn new(config: AppConfig) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(config),
+        }
+    }
+
+    /// Snapshot of the current configuration
+    pub fn get(&self) -> Arc<AppConfig> {
+        self.current.load_full()
+    }
+
+    /// Swap in the reloadable settings of `config` and return the names of those that changed
+    pub fn update(&self, config: &AppConfig) -> Vec<&'static str> {
+        let current = self.current.load();
+        let changes = current.reloadable_changes(config);
+        if !changes.is_empty() {
+            self.current
+                .store(Arc::new(current.with_reloadable(config)));
+        }
+        changes
+    }
+
+    /// Load the configuration again and apply its reloadable settings
+    pub fn reload(&self) -> Result<Vec<&'static str>> {
+        let config = AppConfig::load()?;
+        Ok(self.update(&config))
+    }
+}
+
+/// Modification times of the configuration files, None for missing ones
+fn config_files_modified() -> Vec<Option<SystemTime>> {
+    config_layers()
+        .unwrap_or_default()
+        .iter()
+        .map(|layer| std::fs::metadata(&layer.path).ok()?.modified().ok())
+        .collect()
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = ();
+
+#[cfg(unix)]
+fn listen_for_hangup() -> Hangup {
+    use tokio::signal::unix::{SignalKind, signal};
+    signal(SignalKind::hangup())
+        .inspect_err(|e| tracing::warn!("Failed to listen for SIGHUP: {e}"))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn listen_for_hangup() -> Hangup {}
+
+/// Wait for the next `SIGHUP`; never completes once the listener is gone
+#[cfg(unix)]
+async fn next_hangup(hangup: &mut Hangup) {
+    if let Some(signal) = hangup
+        && signal.recv().await.is_some()
+    {
+        return;
+    }
+    *hangup = None;
+    std::future::pending().await
+}
+
+#[cfg(not(unix))]
+async fn next_hangup(_hangup: &mut Hangup) {
+    std::future::pending().await
+}
+
+/// Reload the configuration on `SIGHUP` and when its files change
+///
+/// `on_reload` runs with the new configuration after every reload that changed
+/// a setting, for settings the caller applies itself such as worker concurrency.
+/// Runs until the process exits.
+pub async fn config_reload_job<F>(live: Arc<LiveConfig>, on_reload: F)
+where
+    F: Fn(&AppConfig) + Send + 'static,
+{
+    let mut hangup = listen_for_hangup();
+    let mut modified = config_files_modified();
+    let mut poll = tokio::time::interval(FILE_POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = next_hangup(&mut hangup) => tracing::info!("Received SIGHUP, reloading configuration"),
+            _ = poll.tick() => {
+                if config_files_modified() == modified {
+                    continue;
+                }
+                tracing::info!("Configuration files changed, reloading configuration");
+            }
+        }
+
+        // A signal sent after editing a file covers the edit too
+        modified = config_files_modified();
+        match live.reload() {
+            Ok(changes) if changes.is_empty() => {
+                tracing::info!("Configuration reloaded, no reloadable setting changed");
+            }
+            Ok(changes) => {
+                let config = live.get();
+                if changes.contains(&"observability.log_level")
+                    && let Err(e) = telemetry::reload_log_filter(&config.observability)
+                {
+                    tracing::error!("Failed to apply reloaded log level: {e}");
+                }
+                on_reload(&config);
+                tracing::info!("Configuration reloaded, changed {}", changes.join(", "));
+            }
+            Err(e) => {
+                tracing::error!("Failed to reload configuration, keeping the current one: {e}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::RateLimitRule;
+
+    #[test]
+    fn test_update_swaps_only_reloadable_settings() {
+        let live = LiveConfig::new(AppConfig::default());
+
+        let mut config = AppConfig::default();
+        config.rate_limit.auth = RateLimitRule {
+            requests_per_minute: 5,
+            burst: 1,
+        };
+        config.worker.concurrency = 3;
+        config.server.port = 4000;
+
+        assert_eq!(
+            live.update(&config),
+            vec!["rate_limit", "worker.concurrency"]
+        );
+        let current = live.get();
+        assert_eq!(current.rate_limit.auth.requests_per_minute, 5);
+        assert_eq!(current.worker.concurrency, 3);
+        assert_eq!(current.server.port, AppConfig::default().server.port);
+
+        assert!(live.update(&config).is_empty());
+    }
+}
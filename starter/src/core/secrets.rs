@@ -0,0 +1,602 @@
+//! Configuration values kept in a secrets manager
+//!
+//! Any string setting can be a `secret://` URI, resolved when the
+//! configuration is loaded:
+//!
+//! - `secret://file/run/secrets/db_password` reads a file, trimming the
+//!   trailing newline
+//! - `secret://vault/secret/starter#db_password` reads a field of a Vault KV v2
+//!   secret from `VAULT_ADDR` with `VAULT_TOKEN`
+//! - `secret://aws/prod/starter#password` reads an AWS Secrets Manager secret
+//!   in `AWS_REGION` with the `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`
+//!   credentials (`AWS_ENDPOINT_URL` points it elsewhere)
+//!
+//! The `#field` suffix picks a field of a JSON secret; Vault secrets with a
+//! single field don't need it. With `secrets.refresh_interval_secs` set,
+//! secrets are fetched again periodically and a rotated database password is
+//! used for new pool connections.
+
+use crate::core::config::AppConfig;
+use crate::{Error, Result};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const SECRET_URI_PREFIX: &str = "secret://";
+
+/// Settings resolved from secrets, by dotted path such as `database.password`, with their URIs
+pub type SecretSources = BTreeMap<String, String>;
+
+/// Where a secret is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretProvider {
+    File,
+    Vault,
+    Aws,
+}
+
+/// A parsed `secret://<provider>/<path>#<field>` URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub provider: SecretProvider,
+    /// File path, Vault mount and path, or AWS secret id
+    pub path: String,
+    /// Field of a JSON secret
+    pub field: Option<String>,
+}
+
+impl SecretRef {
+    pub fn parse(uri: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Error::ConfigurationError(format!("Invalid secret URI '{uri}': {reason}"))
+        };
+        let rest = uri
+            .strip_prefix(SECRET_URI_PREFIX)
+            .ok_or_else(|| invalid("must start with secret://"))?;
+        let (rest, field) = match rest.split_once('#') {
+            Some((rest, field)) if !field.is_empty() => (rest, Some(field.to_string())),
+            Some(_) => return Err(invalid("empty field after #")),
+            None => (rest, None),
+        };
+        let (provider, path) = rest
+            .split_once('/')
+            .ok_or_else(|| invalid("missing path"))?;
+        let provider = match provider {
+            "file" => SecretProvider::File,
+            "vault" => SecretProvider::Vault,
+            "aws" => SecretProvider::Aws,
+            _ => return Err(invalid("provider must be file, vault or aws")),
+        };
+        if path.is_empty() {
+            return Err(invalid("missing path"));
+        }
+        let path = match provider {
+            // The path after the provider is absolute, like a URL path
+            SecretProvider::File => format!("/{path}"),
+            _ => path.to_string(),
+        };
+        Ok(Self {
+            provider,
+            path,
+            field,
+        })
+    }
+}
+
+/// Credentials and endpoints of the secrets managers, from the standard environment variables
+#[derive(Debug, Clone, Default)]
+pub struct SecretResolver {
+    pub vault_addr: Option<String>,
+    pub vault_token: Option<SecretString>,
+    pub vault_namespace: Option<String>,
+    pub aws_region: Option<String>,
+    pub aws_access_key_id: Option<String>,
+    pub aws_secret_access_key: Option<SecretString>,
+    pub aws_session_token: Option<SecretString>,
+    /// Secrets Manager endpoint, by default `https://secretsmanager.{region}.amazonaws.com`
+    pub aws_endpoint: Option<String>,
+    pub timeout: Duration,
+}
+
+impl SecretResolver {
+    pub fn from_env(timeout: Duration) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let secret = |name: &str| var(name).map(|value| SecretString::new(value.into()));
+        Self {
+            vault_addr: var("VAULT_ADDR"),
+            vault_token: secret("VAULT_TOKEN"),
+            vault_namespace: var("VAULT_NAMESPACE"),
+            aws_region: var("AWS_REGION").or_else(|| var("AWS_DEFAULT_REGION")),
+            aws_access_key_id: var("AWS_ACCESS_KEY_ID"),
+            aws_secret_access_key: secret("AWS_SECRET_ACCESS_KEY"),
+            aws_session_token: secret("AWS_SESSION_TOKEN"),
+            aws_endpoint: var("AWS_ENDPOINT_URL_SECRETS_MANAGER")
+                .or_else(|| var("AWS_ENDPOINT_URL")),
+            timeout,
+        }
+    }
+
+    /// Fetch the value of a `secret://` URI
+    ///
+    /// Blocks the calling thread; call it from `spawn_blocking` in async code.
+    pub fn resolve(&self, uri: &str) -> Result<String> {
+        let secret = SecretRef::parse(uri)?;
+        let value = match secret.provider {
+            SecretProvider::File => std::fs::read_to_string(&secret.path)
+                .map(|contents| contents.trim_end_matches(['\r', '\n']).to_string())
+                .map_err(|e| {
+                    Error::ConfigurationError(format!(
+                        "Failed to read secret file {}: {e}",
+                        secret.path
+                    ))
+                })?,
+            SecretProvider::Vault => return self.fetch_vault(&secret),
+            SecretProvider::Aws => self.fetch_aws(&secret)?,
+        };
+        match &secret.field {
+            Some(field) => json_field(&value, field, uri),
+            None => Ok(value),
+        }
+    }
+
+    fn http<T: Send>(
+        &self,
+        request: impl FnOnce(&reqwest::blocking::Client) -> reqwest::Result<T> + Send,
+    ) -> Result<T> {
+        // The blocking client must not run on an async runtime thread
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let client = reqwest::blocking::Client::builder()
+                        .timeout(self.timeout)
+                        .build()?;
+                    request(&client)
+                })
+                .join()
+                .map_err(|_| Error::Internal("Secret fetch panicked".to_string()))?
+                .map_err(|e| Error::ConfigurationError(format!("Failed to fetch secret: {e}")))
+        })
+    }
+
+    fn fetch_vault(&self, secret: &SecretRef) -> Result<String> {
+        let (Some(addr), Some(token)) = (&self.vault_addr, &self.vault_token) else {
+            return Err(Error::ConfigurationError(
+                "VAULT_ADDR and VAULT_TOKEN must be set for secret://vault URIs".to_string(),
+            ));
+        };
+        let (mount, path) = secret.path.split_once('/').ok_or_else(|| {
+            Error::ConfigurationError(format!(
+                "Vault secret path '{}' must be <mount>/<path>",
+                secret.path
+            ))
+        })?;
+        let url = format!("{}/v1/{mount}/data/{path}", addr.trim_end_matches('/'));
+
+        let body: Value = self.http(|client| {
+            let mut request = client
+                .get(&url)
+                .header("X-Vault-Token", token.expose_secret());
+            if let Some(namespace) = &self.vault_namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            request.send()?.error_for_status()?.json()
+        })?;
+
+        let data = body["data"]["data"].as_object().ok_or_else(|| {
+            Error::ConfigurationError(format!("Vault secret {} has no data", secret.path))
+        })?;
+        let value = match &secret.field {
+            Some(field) => data.get(field),
+            None if data.len() == 1 => data.values().next(),
+            None => {
+                return Err(Error::ConfigurationError(format!(
+                    "Vault secret {} has several fields; name one with #field",
+                    secret.path
+                )));
+            }
+        };
+        value.map(value_to_string).ok_or_else(|| {
+            Error::ConfigurationError(format!(
+                "Vault secret {} has no field {}",
+                secret.path,
+                secret.field.as_deref().unwrap_or_default()
+            ))
+        })
+    }
+
+    fn fetch_aws(&self, secret: &SecretRef) -> Result<String> {
+        let (Some(region), Some(access_key), Some(secret_key)) = (
+            &self.aws_region,
+            &self.aws_access_key_id,
+            &self.aws_secret_access_key,
+        ) else {
+            return Err(Error::ConfigurationError(
+                "AWS_REGION, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set for secret://aws URIs"
+                    .to_string(),
+            ));
+        };
+        let endpoint = self
+            .aws_endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://secretsmanager.{region}.amazonaws.com"));
+        let endpoint = endpoint.trim_end_matches('/');
+        let host = endpoint
+            .split_once("://")
+            .map_or(endpoint, |(_, host)| host)
+            .to_string();
+        let payload = serde_json::json!({ "SecretId": secret.path }).to_string();
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let target = "secretsmanager.GetSecretValue";
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+            ("x-amz-target", target.to_string()),
+        ];
+        if let Some(token) = &self.aws_session_token {
+            headers.push(("x-amz-security-token", token.expose_secret().to_string()));
+        }
+        let authorization = sigv4_authorization(
+            &SigV4Request {
+                method: "POST",
+                path: "/",
+                headers: &headers,
+                payload: &payload,
+                amz_date: &amz_date,
+                region,
+                service: "secretsmanager",
+            },
+            access_key,
+            secret_key.expose_secret(),
+        );
+
+        let body: Value = self.http(|client| {
+            let mut request = client
+                .post(format!("{endpoint}/"))
+                .header("Authorization", authorization);
+            for (name, value) in &headers {
+                if *name != "host" {
+                    request = request.header(*name, value);
+                }
+            }
+            request.body(payload).send()?.error_for_status()?.json()
+        })?;
+
+        body["SecretString"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::ConfigurationError(format!("AWS secret {} has no SecretString", secret.path))
+            })
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Field `field` of a JSON secret
+fn json_field(secret: &str, field: &str, uri: &str) -> Result<String> {
+    let json: Value = serde_json::from_str(secret).map_err(|_| {
+        Error::ConfigurationError(format!("Secret {uri} is not JSON, so it has no fields"))
+    })?;
+    json.get(field)
+        .map(value_to_string)
+        .ok_or_else(|| Error::ConfigurationError(format!("Secret {uri} has no field {field}")))
+}
+
+struct SigV4Request<'a> {
+    method: &'a str,
+    path: &'a str,
+    /// Lowercase names, sorted
+    headers: &'a [(&'a str, String)],
+    payload: &'a str,
+    amz_date: &'a str,
+    region: &'a str,
+    service: &'a str,
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// AWS Signature Version 4 signing key for a day, region and service
+fn sigv4_signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+/// `Authorization` header signing a request with AWS Signature Version 4
+fn sigv4_authorization(request: &SigV4Request, access_key: &str, secret_key: &str) -> String {
+    let date = &request.amz_date[..8];
+    let canonical_headers: String = request
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = request
+        .headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{canonical_headers}\n{signed_headers}\n{}",
+        request.method,
+        request.path,
+        hex(&Sha256::digest(request.payload.as_bytes()))
+    );
+    let scope = format!("{date}/{}/{}/aws4_request", request.region, request.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        request.amz_date,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = sigv4_signing_key(secret_key, date, request.region, request.service);
+    let signature = hex(&hmac_sha256(&key, &string_to_sign));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={access_key}/{scope}, SignedHeaders={signed_headers}, Signature={signature}"
+    )
+}
+
+/// Replace the `secret://` URIs among `config`'s string settings with their values
+///
+/// Returns the settings that came from secrets, by path.
+pub fn resolve_config_secrets(
+    config: &mut AppConfig,
+    resolver: &SecretResolver,
+) -> Result<SecretSources> {
+    let mut sources = SecretSources::new();
+    let mut value = serde_json::to_value(&*config)
+        .map_err(|e| Error::ConfigurationError(format!("Failed to read config: {e}")))?;
+    resolve_value(&mut value, String::new(), resolver, &mut sources)?;
+    if !sources.is_empty() {
+        let resolved: AppConfig = serde_json::from_value(value)
+            .map_err(|e| Error::ConfigurationError(format!("Failed to parse config: {e}")))?;
+        // Settings skipped by serde are kept as they are
+        *config = AppConfig {
+            initial_admin_password: config.initial_admin_password.take(),
+            scim_token: config.scim_token.take(),
+            ..resolved
+        };
+    }
+
+    for (path, secret) in [
+        ("initial_admin_password", &mut config.initial_admin_password),
+        ("scim_token", &mut config.scim_token),
+    ] {
+        if let Some(uri) = secret
+            .as_ref()
+            .map(|value| value.expose_secret().to_string())
+            .filter(|value| value.starts_with(SECRET_URI_PREFIX))
+        {
+            *secret = Some(SecretString::new(resolver.resolve(&uri)?.into()));
+            sources.insert(path.to_string(), uri);
+        }
+    }
+    Ok(sources)
+}
+
+fn resolve_value(
+    value: &mut Value,
+    path: String,
+    resolver: &SecretResolver,
+    sources: &mut SecretSources,
+) -> Result<()> {
+    let child = |key: &str| match path.is_empty() {
+        true => key.to_string(),
+        false => format!("{path}.{key}"),
+    };
+    match value {
+        Value::String(uri) if uri.starts_with(SECRET_URI_PREFIX) => {
+            let resolved = resolver.resolve(uri).map_err(|e| match e {
+                Error::ConfigurationError(message) => {
+                    Error::ConfigurationError(format!("{path}: {message}"))
+                }
+                other => other,
+            })?;
+            sources.insert(path, std::mem::replace(uri, resolved));
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                resolve_value(field, child(key), resolver, sources)?;
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                resolve_value(item, child(&index.to_string()), resolver, sources)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Fetch secrets again every `interval` and use a rotated database password for new connections
+///
+/// Other rotated settings are logged and only apply after a restart. Runs
+/// until the process exits.
+pub async fn secret_refresh_job(
+    pool: PgPool,
+    interval: Duration,
+    sources: SecretSources,
+    resolver: SecretResolver,
+) {
+    let mut values = BTreeMap::new();
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick completes immediately and records the current values
+    loop {
+        ticker.tick().await;
+        for (path, uri) in &sources {
+            let fetched = {
+                let resolver = resolver.clone();
+                let uri = uri.clone();
+                tokio::task::spawn_blocking(move || resolver.resolve(&uri)).await
+            };
+            let value = match fetched {
+                Ok(Ok(value)) => value,
+                Ok(Err(e)) => {
+                    tracing::warn!("Failed to refresh secret for {path}: {e}");
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to refresh secret for {path}: {e}");
+                    continue;
+                }
+            };
+            let previous = values.insert(path.clone(), value.clone());
+            if previous.is_none() || previous.as_ref() == Some(&value) {
+                continue;
+            }
+            if path == "database.password" {
+                let options = pool.connect_options().as_ref().clone().password(&value);
+                pool.set_connect_options(options);
+                tracing::info!("Database password rotated, new connections use it");
+            } else {
+                tracing::warn!("Secret for {path} changed; restart to apply it");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secret_uris() {
+        assert_eq!(
+            SecretRef::parse("secret://file/run/secrets/db").unwrap(),
+            SecretRef {
+                provider: SecretProvider::File,
+                path: "/run/secrets/db".to_string(),
+                field: None,
+            }
+        );
+        assert_eq!(
+            SecretRef::parse("secret://vault/secret/starter#password").unwrap(),
+            SecretRef {
+                provider: SecretProvider::Vault,
+                path: "secret/starter".to_string(),
+                field: Some("password".to_string()),
+            }
+        );
+        assert!(SecretRef::parse("secret://gcp/x").is_err());
+        assert!(SecretRef::parse("secret://aws/").is_err());
+        assert!(SecretRef::parse("secret://aws/x#").is_err());
+    }
+
+    #[test]
+    fn test_resolve_config_file_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let password = dir.path().join("db_password");
+        std::fs::write(&password, "s3cret\n").unwrap();
+        let redis = dir.path().join("redis.json");
+        std::fs::write(&redis, r#"{"url": "redis://cache:6379/0"}"#).unwrap();
+
+        let mut config = AppConfig::default();
+        config.database.password = format!("secret://file{}", password.display());
+        config.cache.redis_url = format!("secret://file{}#url", redis.display());
+        config.scim_token = Some(SecretString::new(
+            format!("secret://file{}", password.display()).into(),
+        ));
+
+        let sources = resolve_config_secrets(&mut config, &SecretResolver::default()).unwrap();
+        assert_eq!(config.database.password, "s3cret");
+        assert_eq!(config.cache.redis_url, "redis://cache:6379/0");
+        assert_eq!(config.scim_token.unwrap().expose_secret(), "s3cret");
+        assert_eq!(
+            sources.keys().collect::<Vec<_>>(),
+            vec!["cache.redis_url", "database.password", "scim_token"]
+        );
+
+        config = AppConfig::default();
+        config.database.password = "secret://file/missing/file".to_string();
+        let error = resolve_config_secrets(&mut config, &SecretResolver::default()).unwrap_err();
+        assert!(error.to_string().contains("database.password"));
+    }
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = sigv4_signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_vault_and_aws_secrets() {
+        use axum::{Json, Router, http::HeaderMap, routing::get, routing::post};
+
+        let app = Router::new()
+            .route(
+                "/v1/secret/data/starter",
+                get(|headers: HeaderMap| async move {
+                    assert_eq!(headers["x-vault-token"], "vault-token");
+                    Json(serde_json::json!({"data": {"data": {"password": "from-vault"}}}))
+                }),
+            )
+            .route(
+                "/",
+                post(|headers: HeaderMap, body: String| async move {
+                    assert_eq!(headers["x-amz-target"], "secretsmanager.GetSecretValue");
+                    assert!(
+                        headers["authorization"]
+                            .to_str()
+                            .unwrap()
+                            .starts_with("AWS4-HMAC-SHA256 Credential=AKID/")
+                    );
+                    assert_eq!(body, r#"{"SecretId":"app/db"}"#);
+                    Json(serde_json::json!({"SecretString": r#"{"password":"from-aws"}"#}))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let resolver = SecretResolver {
+            vault_addr: Some(address.clone()),
+            vault_token: Some(SecretString::new("vault-token".into())),
+            aws_region: Some("us-east-1".to_string()),
+            aws_access_key_id: Some("AKID".to_string()),
+            aws_secret_access_key: Some(SecretString::new("secret".into())),
+            aws_endpoint: Some(address),
+            timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let (vault, aws) = tokio::task::spawn_blocking(move || {
+            (
+                resolver.resolve("secret://vault/secret/starter"),
+                resolver.resolve("secret://aws/app/db#password"),
+            )
+        })
+        .await
+        .unwrap();
+        assert_eq!(vault.unwrap(), "from-vault");
+        assert_eq!(aws.unwrap(), "from-aws");
+    }
+}
@@ -1,29 +1,95 @@
 use crate::{
+    api::ApiVersion,
+    audit::api::audit_log_admin_routes,
     auth::{
-        api::{auth_public_routes, auth_routes},
+        api::{
+            auth_login_routes, auth_public_routes, auth_routes, legal_admin_routes,
+            service_accounts_admin_routes,
+        },
         middleware::{admin_middleware, auth_middleware},
     },
     core::{
-        config::AppConfig, database::Database, error::Error, openapi, state::AppState,
+        body_limit::limit_body,
+        cache::AppCache,
+        config::AppConfig,
+        database::Database,
+        error::Error,
+        idempotency::limit_idempotent_body,
+        openapi,
+        rate_limit::{RateLimitGroup, RateLimiter, rate_limit},
+        reload::{self, LiveConfig},
+        secrets,
+        state::AppState,
+        telemetry,
         types::Result,
     },
+    features::api::{features_admin_routes, features_routes},
     health::{detailed_health, handlers::health_routes},
-    monitoring::api::{monitoring_moderator_routes, monitoring_public_routes, monitoring_routes},
-    rbac::middleware::require_moderator_role,
-    tasks::api::{tasks_public_routes, tasks_routes},
-    users::api::{admin_users_routes, users_admin_routes, users_moderator_routes, users_routes},
+    maintenance::{api::maintenance_admin_routes, maintenance_middleware},
+    monitoring::{
+        api::{
+            monitoring_admin_routes, monitoring_ingestion_routes, monitoring_moderator_routes,
+            monitoring_public_routes, monitoring_routes, status_page_public_routes,
+        },
+        instrumentation::{self, HttpMetrics},
+    },
+    orgs::api::{invitations_public_routes, orgs_routes},
+    rbac::{
+        api::{
+            audit_admin_routes, bundles_admin_routes, denies_admin_routes,
+            rbac_policy_admin_routes, role_requests_admin_routes, role_requests_moderator_routes,
+            roles_admin_routes, shares_routes,
+        },
+        cache::PermissionCache,
+        middleware::require_moderator_role,
+    },
+    realtime::{
+        RealtimeHub,
+        api::{realtime_admin_routes, realtime_routes},
+    },
+    scim::api::{scim_auth_middleware, scim_routes},
+    storage::{LocalFileStorage, api::files_public_routes},
+    tasks::{
+        api::{tasks_admin_routes, tasks_public_routes, tasks_routes},
+        services::TaskServices,
+    },
+    tenants::{api::tenants_admin_routes, middleware::tenant_middleware},
+    users::{
+        api::{
+            account_deletion_public_routes, admin_users_routes, data_exports_public_routes,
+            user_import_routes, users_admin_routes, users_routes, users_upload_routes,
+        },
+        presence::{self, Presence},
+    },
 };
-use axum::{Json, Router, middleware, response::IntoResponse, routing::get};
+use axum::{
+    Json, Router,
+    extract::{MatchedPath, Request, State},
+    http::{
+        Extensions, HeaderMap, HeaderValue, Response, StatusCode, Version,
+        header::{CONTENT_TYPE, LINK},
+    },
+    middleware::{self, Next},
+    response::IntoResponse,
+    routing::get,
+};
+use std::net::SocketAddr;
 use std::path::Path;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::{
+    compression::{
+        CompressionLayer, Predicate,
+        predicate::{NotForContentType, SizeAbove},
+    },
     cors::{Any, CorsLayer},
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
     services::{ServeDir, ServeFile},
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{Span, info};
 use utoipa::OpenApi;
 
 /// Handle 404 Not Found errors
@@ -31,6 +97,131 @@ async fn not_found_handler() -> impl IntoResponse {
     Error::NotFound("The requested resource was not found".to_string())
 }
 
+/// Root span for each API request
+///
+/// Carries the request ID so log lines and exported spans can be matched, and
+/// continues the caller's trace when it sends a W3C `traceparent` header. The
+/// authenticated user's ID is added as `enduser.id` once they are known.
+fn make_request_span(request: &Request) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id,
+        otel.name = %request.method(),
+        otel.kind = "server",
+        http.route = tracing::field::Empty,
+        http.response.status_code = tracing::field::Empty,
+        enduser.id = tracing::field::Empty,
+    );
+    telemetry::set_parent_from_headers(&span, request.headers());
+    span
+}
+
+fn record_response<B>(response: &Response<B>, latency: Duration, span: &Span) {
+    span.record("http.response.status_code", response.status().as_u16());
+    tracing::debug!(
+        parent: span,
+        status = response.status().as_u16(),
+        latency_ms = latency.as_millis() as u64,
+        "finished processing request"
+    );
+}
+
+/// Name the request span after the matched route, e.g. `GET /tasks/{id}`
+async fn record_matched_route(request: Request, next: Next) -> impl IntoResponse {
+    if let Some(path) = request.extensions().get::<MatchedPath>() {
+        let span = Span::current();
+        span.record("http.route", path.as_str());
+        span.record(
+            "otel.name",
+            format!("{} {}", request.method(), path.as_str()),
+        );
+    }
+    next.run(request).await
+}
+
+/// Headers announcing a deprecated API version's successor and removal
+#[derive(Clone)]
+struct Deprecation {
+    deprecation: HeaderValue,
+    sunset: Option<HeaderValue>,
+    link: HeaderValue,
+}
+
+impl Deprecation {
+    fn new(config: &AppConfig, version: ApiVersion) -> Option<Self> {
+        let deprecated_at = version.deprecated_at()?;
+        let sunset = match version {
+            ApiVersion::V1 => config.api_v1_sunset(),
+            ApiVersion::V2 => None,
+        };
+        let header = |value: String| HeaderValue::from_str(&value).ok();
+        Some(Self {
+            // Structured date of RFC 9745
+            deprecation: header(format!("@{}", deprecated_at.timestamp()))?,
+            sunset: sunset
+                .and_then(|sunset| header(sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string())),
+            link: header(format!(
+                "<{}>; rel=\"successor-version\"",
+                ApiVersion::LATEST.prefix()
+            ))?,
+        })
+    }
+}
+
+/// Tell handlers which version was called and mark deprecated versions' responses
+async fn api_version_middleware(
+    State((version, deprecation)): State<(ApiVersion, Option<Deprecation>)>,
+    mut request: Request,
+    next: Next,
+) -> impl IntoResponse {
+    request.extensions_mut().insert(version);
+    let mut response = next.run(request).await;
+    if let Some(deprecation) = deprecation {
+        let headers = response.headers_mut();
+        headers.insert("deprecation", deprecation.deprecation);
+        if let Some(sunset) = deprecation.sunset {
+            headers.insert("sunset", sunset);
+        }
+        headers.append(LINK, deprecation.link);
+    }
+    response
+}
+
+/// Compress responses with gzip or brotli, as the client accepts
+///
+/// Only responses of the configured content types and size are compressed;
+/// event streams never are, so events are not held back in the encoder.
+fn compression_layer(config: &AppConfig) -> CompressionLayer<impl Predicate + use<>> {
+    let content_types = config.compression_content_types();
+    let compressible = move |_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions| {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|content_type| {
+                content_types
+                    .iter()
+                    .any(|prefix| content_type.starts_with(prefix.as_str()))
+            })
+    };
+    let enabled = config.server.compression_enabled;
+    CompressionLayer::new()
+        .gzip(enabled)
+        .br(enabled)
+        .compress_when(
+            SizeAbove::new(config.server.compression_min_bytes)
+                .and(NotForContentType::SSE)
+                .and(compressible),
+        )
+}
+
 /// Serve OpenAPI JSON specification
 async fn openapi_json() -> impl IntoResponse {
     Json(openapi::ApiDoc::openapi())
@@ -100,21 +291,112 @@ async fn api_docs() -> impl IntoResponse {
     )
 }
 
+/// GraphiQL page when built with the `graphql` feature
+fn graphql_public_routes() -> Router<AppState> {
+    #[cfg(feature = "graphql")]
+    return crate::graphql::api::graphql_public_routes();
+    #[cfg(not(feature = "graphql"))]
+    Router::new()
+}
+
+/// GraphQL endpoint when built with the `graphql` feature
+fn graphql_routes() -> Router<AppState> {
+    #[cfg(feature = "graphql")]
+    return crate::graphql::api::graphql_routes();
+    #[cfg(not(feature = "graphql"))]
+    Router::new()
+}
+
 /// Create the application router with all routes and middleware
 pub fn create_router(state: AppState) -> Router {
+    let limiter = Arc::new(RateLimiter::new(state.live_config.clone()));
+    let compression = compression_layer(&state.config);
+    let rate_limit_layer = |group: RateLimitGroup| {
+        middleware::from_fn_with_state((limiter.clone(), group), rate_limit)
+    };
+    // Refuses everyone but admins in maintenance mode; inside auth_middleware
+    // so it sees who is signed in
+    let maintenance_layer =
+        || middleware::from_fn_with_state(state.clone(), maintenance_middleware);
+
+    // Request body limits by route group; each applies to the routes added
+    // before it, so routes added after it can have their own
+    let limits = state.config.body_limit.clone();
+    // Authenticated groups replay retried requests, reading bodies with the
+    // group's limit
+    let limit_idempotent = |routes: Router<AppState>, limit_bytes: usize| {
+        limit_idempotent_body(routes, &state, limit_bytes)
+    };
+
+    // Sign-in and registration routes, limited more tightly than other public
+    // routes; in maintenance mode the login handler admits admins only
+    let sign_in_routes = Router::new()
+        .nest(
+            "/auth",
+            auth_public_routes()
+                .layer(maintenance_layer())
+                .merge(auth_login_routes()),
+        )
+        .layer(rate_limit_layer(RateLimitGroup::Auth));
+    let sign_in_routes = limit_body(sign_in_routes, limits.auth_bytes);
+
+    // Health checks, answered in maintenance mode too
+    let health_check_routes = limit_body(
+        Router::new().nest("/health", health_routes()),
+        limits.default_bytes,
+    )
+    .layer(rate_limit_layer(RateLimitGroup::Public));
+
     // Public routes (no authentication required)
     let public_routes = Router::new()
-        .nest("/health", health_routes())
-        .nest("/auth", auth_public_routes())
-        .nest("/tasks", tasks_public_routes())
-        .nest("/monitoring", monitoring_public_routes());
+        .nest("/monitoring", monitoring_public_routes())
+        .nest("/status", status_page_public_routes())
+        .nest("/invitations", invitations_public_routes())
+        .nest("/files", files_public_routes())
+        .nest("/exports", data_exports_public_routes())
+        .nest("/account-deletion", account_deletion_public_routes())
+        .merge(graphql_public_routes());
+    let public_routes = limit_body(public_routes, limits.default_bytes)
+        .nest(
+            "/tasks",
+            limit_body(tasks_public_routes(), limits.tasks_bytes),
+        )
+        .layer(maintenance_layer())
+        .layer(rate_limit_layer(RateLimitGroup::Public));
 
     // Protected routes (authentication required)
     let protected_routes = Router::new()
-        .nest("/auth", auth_routes())
         .nest("/users", users_routes())
-        .nest("/tasks", tasks_routes())
         .nest("/monitoring", monitoring_routes())
+        .nest("/orgs", orgs_routes())
+        .nest("/shares", shares_routes())
+        .nest("/features", features_routes())
+        .merge(realtime_routes());
+    let protected_routes = limit_idempotent(protected_routes, limits.default_bytes)
+        // GraphQL mutations create tasks and store events and metrics, so a
+        // request may be no larger than those routes take
+        .merge(limit_idempotent(
+            graphql_routes(),
+            limits
+                .default_bytes
+                .min(limits.tasks_bytes)
+                .min(limits.ingestion_bytes),
+        ))
+        .nest("/auth", limit_idempotent(auth_routes(), limits.auth_bytes))
+        .nest(
+            "/tasks",
+            limit_idempotent(tasks_routes(), limits.tasks_bytes),
+        )
+        .nest(
+            "/monitoring",
+            limit_idempotent(monitoring_ingestion_routes(), limits.ingestion_bytes),
+        )
+        .nest(
+            "/users",
+            limit_idempotent(users_upload_routes(), limits.uploads_bytes),
+        )
+        .layer(maintenance_layer())
+        .layer(rate_limit_layer(RateLimitGroup::Authenticated))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -122,9 +404,12 @@ pub fn create_router(state: AppState) -> Router {
 
     // Moderator routes (moderator role or higher required)
     let moderator_routes = Router::new()
-        .nest("/users", users_moderator_routes())
         .nest("/monitoring", monitoring_moderator_routes())
+        .nest("/role-requests", role_requests_moderator_routes());
+    let moderator_routes = limit_idempotent(moderator_routes, limits.default_bytes)
         .layer(middleware::from_fn(require_moderator_role))
+        .layer(maintenance_layer())
+        .layer(rate_limit_layer(RateLimitGroup::Authenticated))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -134,29 +419,80 @@ pub fn create_router(state: AppState) -> Router {
     let admin_routes = Router::new()
         .nest("/users", users_admin_routes())
         .nest("/admin/users", admin_users_routes())
-        .route("/admin/health", get(detailed_health))
+        .nest("/admin/monitoring", monitoring_admin_routes())
+        .nest("/admin/legal", legal_admin_routes())
+        .nest("/admin/service-accounts", service_accounts_admin_routes())
+        .nest("/admin/roles", roles_admin_routes())
+        .nest("/admin/role-requests", role_requests_admin_routes())
+        .nest(
+            "/admin/audit",
+            audit_admin_routes().merge(audit_log_admin_routes()),
+        )
+        .nest("/admin/permission-bundles", bundles_admin_routes())
+        .nest("/admin/permission-denies", denies_admin_routes())
+        .nest("/admin/rbac", rbac_policy_admin_routes())
+        .nest("/admin/tenants", tenants_admin_routes())
+        .nest("/admin/features", features_admin_routes())
+        .nest("/admin/realtime", realtime_admin_routes())
+        .nest("/admin/maintenance", maintenance_admin_routes())
+        .route("/admin/health", get(detailed_health));
+    let admin_routes = limit_idempotent(admin_routes, limits.default_bytes)
+        .nest(
+            "/admin/tasks",
+            limit_idempotent(tasks_admin_routes(), limits.tasks_bytes),
+        )
+        .nest(
+            "/admin/users",
+            limit_idempotent(user_import_routes(), limits.uploads_bytes),
+        )
         .layer(middleware::from_fn(admin_middleware))
+        .layer(rate_limit_layer(RateLimitGroup::Admin))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    // SCIM provisioning routes (SCIM bearer token required)
+    let provisioning_routes = limit_body(
+        Router::new().nest("/scim/v2", scim_routes()),
+        limits.default_bytes,
+    )
+    .layer(maintenance_layer())
+    .layer(middleware::from_fn_with_state(
+        state.clone(),
+        scim_auth_middleware,
+    ));
+
     // Combine all routes
     Router::new()
+        .merge(sign_in_routes)
+        .merge(health_check_routes)
         .merge(public_routes)
         .merge(protected_routes)
         .merge(moderator_routes)
         .merge(admin_routes)
+        .merge(provisioning_routes)
+        .route_layer(middleware::from_fn(record_matched_route))
+        .route_layer(middleware::from_fn_with_state(
+            state.http_metrics.clone(),
+            instrumentation::record_http_metrics,
+        ))
+        // Resolve the named tenant before any route authenticates the request
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            tenant_middleware,
+        ))
         .fallback(not_found_handler)
         .with_state(state)
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                // Keep a caller-supplied X-Request-Id, otherwise generate one
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(PropagateRequestIdLayer::x_request_id())
                 .layer(
-                    tower_http::set_header::SetResponseHeaderLayer::if_not_present(
-                        axum::http::header::HeaderName::from_static("x-request-id"),
-                        axum::http::HeaderValue::from_static("test-request-id"),
-                    ),
+                    TraceLayer::new_for_http()
+                        .make_span_with(make_request_span)
+                        .on_response(record_response),
                 )
                 .layer(
                     tower_http::set_header::SetResponseHeaderLayer::if_not_present(
@@ -175,24 +511,91 @@ pub fn create_router(state: AppState) -> Router {
                         .allow_origin(Any)
                         .allow_methods(Any)
                         .allow_headers(Any),
-                ),
+                )
+                .layer(compression),
         )
 }
 
+/// Serve the API router under the prefix of every [`ApiVersion`]
+///
+/// Versions share routes and handlers; handlers whose response shape changed
+/// take the [`ApiVersion`] to pick it.
+pub fn create_versioned_router(state: AppState) -> Router {
+    let deprecations = ApiVersion::ALL.map(|version| Deprecation::new(&state.config, version));
+    let api_router = create_router(state);
+    ApiVersion::ALL.into_iter().zip(deprecations).fold(
+        Router::new(),
+        |router, (version, deprecation)| {
+            router.nest(
+                &version.prefix(),
+                api_router.clone().layer(middleware::from_fn_with_state(
+                    (version, deprecation),
+                    api_version_middleware,
+                )),
+            )
+        },
+    )
+}
+
 /// Start the HTTP server
 pub async fn start_server(config: AppConfig, database: Database) -> Result<()> {
+    let cache = Arc::new(AppCache::from_config(&config.cache).await?);
+    let live_config = Arc::new(LiveConfig::new(config.clone()));
     let state = AppState {
         config: config.clone(),
+        live_config: live_config.clone(),
         database,
         start_time: Instant::now(),
+        event_stream: Default::default(),
+        realtime: Arc::new(RealtimeHub::new()),
+        services: TaskServices::from_config(&config),
+        http_metrics: Arc::new(HttpMetrics::new()),
+        storage: Arc::new(LocalFileStorage::new(&config.storage.path, "/api/v1/files")),
+        presence: Arc::new(Presence::new()),
+        permission_cache: Arc::new(PermissionCache::new(
+            cache.clone(),
+            config.permission_cache_ttl(),
+        )),
+        cache,
     };
-    let api_router = create_router(state);
+
+    // Apply reloaded rate limits and log level on SIGHUP or config file changes
+    tokio::spawn(reload::config_reload_job(live_config, |_| {}));
+
+    // Pick up a rotated database password from the secrets manager
+    if let Some(interval) = config.secrets_refresh_interval()
+        && !config.secret_sources.is_empty()
+    {
+        tokio::spawn(secrets::secret_refresh_job(
+            state.database.pool.clone(),
+            interval,
+            config.secret_sources.clone(),
+            secrets::SecretResolver::from_env(config.secrets_timeout()),
+        ));
+    }
+
+    // Store request latency and pool usage so the monitoring module covers the app itself
+    if config.monitoring.self_metrics_interval_secs > 0 {
+        tokio::spawn(instrumentation::server_metrics_job(
+            state.database.pool.clone(),
+            config.self_metrics_interval(),
+            state.http_metrics.clone(),
+            state.cache.clone(),
+        ));
+    }
+
+    if config.auth.last_seen_flush_interval_secs > 0 {
+        tokio::spawn(presence::last_seen_flush_job(
+            state.database.pool.clone(),
+            config.last_seen_flush_interval(),
+            state.presence.clone(),
+        ));
+    }
 
     // Setup static file serving for web frontend
     let web_build_path = &config.server.web_build_path;
 
-    let mut app = Router::new()
-        .nest("/api/v1", api_router)
+    let mut app = create_versioned_router(state)
         // Keep documentation routes at root level
         .route("/api-docs", get(api_docs))
         .route("/api-docs/openapi.json", get(openapi_json));
@@ -224,9 +627,12 @@ pub async fn start_server(config: AppConfig, database: Database) -> Result<()> {
         config.server.web_build_path
     );
 
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| Error::Internal(format!("Server error: {e}")))?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(|e| Error::Internal(format!("Server error: {e}")))?;
 
     Ok(())
 }
@@ -0,0 +1,171 @@
+//! Server-Sent Events streams fed by broadcast channels
+//!
+//! Live feeds publish items to a broadcast channel, either one per feed or one
+//! per key with [`KeyedBroadcast`] (a user or a topic), and each client turns
+//! its receiver into a response with [`stream`]. Channels are bounded: a
+//! client that can't keep up skips ahead and gets a `lagged` message with the
+//! number of items it missed, rather than holding back the others. Idle
+//! streams send keep-alive comments so proxies don't close them.
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{Stream, stream};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Items buffered per subscriber before it starts missing some
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Interval of keep-alive comments on idle streams
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Broadcast channels created on first subscription, one per key
+pub struct KeyedBroadcast<K, T> {
+    channels: Mutex<HashMap<K, broadcast::Sender<Arc<T>>>>,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, T> Default for KeyedBroadcast<K, T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl<K: Eq + Hash + Clone, T> KeyedBroadcast<K, T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            channels: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Receive the items sent to `key` from now on
+    pub fn subscribe(&self, key: &K) -> broadcast::Receiver<Arc<T>> {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        match channels.get(key) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(self.capacity);
+                channels.insert(key.clone(), sender);
+                receiver
+            }
+        }
+    }
+
+    /// Send an item to the subscribers of `key` and return how many there are
+    ///
+    /// Channels whose subscribers have all gone are dropped.
+    pub fn send(&self, key: &K, item: Arc<T>) -> usize {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = channels.get(key) else {
+            return 0;
+        };
+        match sender.send(item) {
+            Ok(receivers) => receivers,
+            Err(_) => {
+                channels.remove(key);
+                0
+            }
+        }
+    }
+
+    /// Keys with at least one subscriber
+    pub fn len(&self) -> usize {
+        let channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels
+            .values()
+            .filter(|sender| sender.receiver_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Message telling a client how many items it missed by falling behind
+pub fn lagged_event(missed: u64) -> Event {
+    Event::default().event("lagged").data(missed.to_string())
+}
+
+/// Stream the items a receiver gets as Server-Sent Events
+///
+/// `to_event` turns an item into its message, or None to skip it, e.g. when a
+/// filter doesn't match. The stream ends when the channel closes.
+pub fn stream<T, F>(
+    receiver: broadcast::Receiver<Arc<T>>,
+    to_event: F,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>> + Send + 'static>
+where
+    T: Send + Sync + 'static,
+    F: FnMut(&T) -> Option<Event> + Send + 'static,
+{
+    let events = stream::unfold(
+        (receiver, to_event),
+        |(mut receiver, mut to_event)| async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(item) => match to_event(&item) {
+                        Some(event) => event,
+                        None => continue,
+                    },
+                    Err(RecvError::Lagged(missed)) => lagged_event(missed),
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(event), (receiver, to_event)));
+            }
+        },
+    );
+    Sse::new(events).keep_alive(KeepAlive::new().interval(KEEP_ALIVE_INTERVAL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    #[test]
+    fn test_keyed_channels_only_reach_their_subscribers() {
+        let channels = KeyedBroadcast::<&str, u32>::new(4);
+        assert_eq!(channels.send(&"a", Arc::new(1)), 0);
+
+        let mut a = channels.subscribe(&"a");
+        let mut b = channels.subscribe(&"b");
+        assert_eq!(channels.send(&"a", Arc::new(2)), 1);
+        assert_eq!(*a.try_recv().unwrap(), 2);
+        assert!(b.try_recv().is_err());
+        assert_eq!(channels.len(), 2);
+
+        // Channels are dropped once their last subscriber leaves
+        drop(b);
+        assert_eq!(channels.send(&"b", Arc::new(3)), 0);
+        assert_eq!(channels.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_skips_filtered_items_and_reports_lag() {
+        let (sender, receiver) = broadcast::channel(2);
+        let response = stream(receiver, |item: &u32| {
+            item.is_multiple_of(2)
+                .then(|| Event::default().data(item.to_string()))
+        })
+        .into_response();
+
+        // Four items into a channel of two: the first two are missed
+        for item in 1..=4 {
+            sender.send(Arc::new(item)).unwrap();
+        }
+        drop(sender);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(body.to_vec()).unwrap(),
+            "event: lagged\ndata: 2\n\ndata: 4\n\n"
+        );
+    }
+}
@@ -4,7 +4,17 @@
 //! all request handlers and contains configuration, database connections,
 //! and other global application context.
 
+use crate::core::cache::AppCache;
+use crate::core::reload::LiveConfig;
 use crate::core::{config::AppConfig, database::Database};
+use crate::monitoring::instrumentation::HttpMetrics;
+use crate::monitoring::stream::EventStream;
+use crate::rbac::cache::PermissionCache;
+use crate::realtime::RealtimeHub;
+use crate::storage::FileStorage;
+use crate::tasks::services::TaskServices;
+use crate::users::presence::Presence;
+use std::sync::Arc;
 use std::time::Instant;
 
 /// Application state shared across all handlers
@@ -15,8 +25,26 @@ use std::time::Instant;
 pub struct AppState {
     /// Database connection pool and utilities
     pub database: Database,
-    /// Application configuration
+    /// Application configuration as loaded at startup
     pub config: AppConfig,
+    /// Configuration with the settings reloaded since startup, such as rate limits
+    pub live_config: Arc<LiveConfig>,
     /// Application start time for uptime calculations
     pub start_time: Instant,
+    /// Live feed of stored monitoring events
+    pub event_stream: EventStream,
+    /// Open WebSocket connections and the live updates sent to them
+    pub realtime: Arc<RealtimeHub>,
+    /// HTTP client and email sender for outgoing notifications
+    pub services: TaskServices,
+    /// Requests served since the server last stored its own metrics
+    pub http_metrics: Arc<HttpMetrics>,
+    /// Where uploaded files are kept
+    pub storage: Arc<dyn FileStorage>,
+    /// Users seen since their last-seen times were stored
+    pub presence: Arc<Presence>,
+    /// Cached sessions, permissions and task type registrations
+    pub cache: Arc<AppCache>,
+    /// Custom role permissions of recently seen users, kept in `cache`
+    pub permission_cache: Arc<PermissionCache>,
 }
@@ -0,0 +1,253 @@
+//! Logging and distributed tracing setup
+//!
+//! Logs always go to stdout. When `observability.otlp_endpoint` is set, spans
+//! are also exported over OTLP/HTTP to a collector such as Jaeger or Tempo:
+//! one span per HTTP request and per task execution, with SQL statements
+//! attached as span events. The W3C trace context is passed on to outbound
+//! HTTP requests and to tasks created while handling a request, so a request,
+//! the tasks it queued and the calls they made share one trace. Logs passing
+//! `observability.log_events_filter` are stored as monitoring events too.
+
+use crate::core::config::ObservabilityConfig;
+use crate::{Error, Result};
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, layer::SubscriberExt, reload, util::SubscriberInitExt,
+};
+
+/// Exported span filter: the log filter plus SQL statements, which sqlx logs at debug
+const TRACE_EXTRA_DIRECTIVES: &str = "sqlx::query=debug";
+
+/// Flushes and shuts down the span exporter when dropped
+///
+/// Keep it alive for the life of the process so buffered spans are sent on exit.
+#[must_use = "dropping the guard shuts down trace export"]
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Failed to flush trace exporter: {e}");
+        }
+    }
+}
+
+/// Handle swapping the stdout log filter of the installed subscriber
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Log filter from `RUST_LOG`, or else `observability.log_level`
+fn log_filter(config: &ObservabilityConfig) -> String {
+    std::env::var("RUST_LOG").unwrap_or_else(|_| config.log_level.clone())
+}
+
+/// Install the global tracing subscriber
+///
+/// The log filter comes from `RUST_LOG`, or else `observability.log_level`.
+pub fn init_tracing(config: &ObservabilityConfig) -> Result<TelemetryGuard> {
+    let log_filter = log_filter(config);
+    let (stdout_filter, filter_handle) = reload::Layer::new(EnvFilter::new(&log_filter));
+    let fmt_layer = tracing_subscriber::fmt::layer().with_filter(stdout_filter);
+
+    let provider = if config.otlp_endpoint.is_empty() {
+        None
+    } else {
+        Some(build_tracer_provider(config)?)
+    };
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer(config.service_name.clone()))
+            .with_filter(EnvFilter::new(format!(
+                "{log_filter},{TRACE_EXTRA_DIRECTIVES}"
+            )))
+    });
+
+    let log_events_layer = crate::monitoring::app_logs::layer(config)?;
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otel_layer)
+        .with(log_events_layer)
+        .try_init()
+        .map_err(|e| Error::ConfigurationError(format!("Failed to initialize tracing: {e}")))?;
+    let _ = LOG_FILTER.set(filter_handle);
+
+    if provider.is_some() {
+        tracing::info!(
+            "Exporting traces to {} (sampling ratio {})",
+            traces_endpoint(&config.otlp_endpoint),
+            config.sampling_ratio
+        );
+    }
+
+    Ok(TelemetryGuard { provider })
+}
+
+/// Apply a reloaded `observability.log_level` to stdout logs
+///
+/// Does nothing while `RUST_LOG` is set. Exported spans keep the filter they started with.
+pub fn reload_log_filter(config: &ObservabilityConfig) -> Result<()> {
+    let Some(handle) = LOG_FILTER.get() else {
+        return Ok(());
+    };
+    handle
+        .reload(EnvFilter::new(log_filter(config)))
+        .map_err(|e| Error::Internal(format!("Failed to reload log filter: {e}")))
+}
+
+fn build_tracer_provider(config: &ObservabilityConfig) -> Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(&config.otlp_endpoint))
+        .build()
+        .map_err(|e| {
+            Error::ConfigurationError(format!("Failed to build OTLP span exporter: {e}"))
+        })?;
+
+    // Honor the caller's sampling decision so distributed traces stay complete
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sampling_ratio)));
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(sampler)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build())
+}
+
+/// OTLP/HTTP traces URL for a collector base URL
+fn traces_endpoint(base: &str) -> String {
+    let base = base.trim_end_matches('/');
+    if base.ends_with("/v1/traces") {
+        base.to_string()
+    } else {
+        format!("{base}/v1/traces")
+    }
+}
+
+/// Continue a caller's trace from its W3C `traceparent` header, if any
+pub fn set_parent_from_headers(span: &tracing::Span, headers: &HeaderMap) {
+    let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(headers))
+    });
+    // Fails only when the span is disabled, in which case there is nothing to link
+    let _ = span.set_parent(context);
+}
+
+/// Continue a trace from a context saved with [`current_trace_context`]
+pub fn set_parent_from_map(span: &tracing::Span, context: &HashMap<String, String>) {
+    let context =
+        opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(context));
+    let _ = span.set_parent(context);
+}
+
+/// The current span's trace context, e.g. `traceparent`, to continue it later
+///
+/// Empty when trace export is off.
+pub fn current_trace_context() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut fields = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut fields)
+    });
+    fields
+}
+
+/// Headers carrying the current span's trace context to an outbound request
+pub fn trace_headers() -> HeaderMap {
+    let context = tracing::Span::current().context();
+    let mut headers = HeaderMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+    headers
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+
+    #[test]
+    fn test_trace_context_round_trip() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request");
+            let _entered = request.enter();
+            let trace_id = request.context().span().span_context().trace_id();
+
+            let headers = trace_headers();
+            let traceparent = headers["traceparent"].to_str().unwrap();
+            assert!(traceparent.contains(&trace_id.to_string()));
+
+            // A task created now continues the request's trace when it runs
+            let saved = current_trace_context();
+            let task = tracing::info_span!(parent: None, "task");
+            set_parent_from_map(&task, &saved);
+            assert_eq!(task.context().span().span_context().trace_id(), trace_id);
+        });
+    }
+
+    #[test]
+    fn test_traces_endpoint() {
+        assert_eq!(
+            traces_endpoint("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://tempo:4318/"),
+            "http://tempo:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://tempo:4318/v1/traces"),
+            "http://tempo:4318/v1/traces"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::auth::AuthUser;
+use crate::features::{
+    models::{CreateFeatureFlagRequest, FeatureFlag, UpdateFeatureFlagRequest},
+    services as feature_services,
+};
+use crate::tenants::services as tenant_services;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, Path, State},
+    response::Json,
+    routing::{get, patch},
+};
+use std::collections::BTreeMap;
+
+#[utoipa::path(
+    get,
+    path = "/features",
+    tag = "Features",
+    summary = "Get feature flags",
+    description = "Every feature flag by key, with whether it is on for the signed-in user",
+    responses(
+        (status = 200, description = "Flags evaluated for the user", body = ApiResponse<BTreeMap<String, bool>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_features(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<BTreeMap<String, bool>>>, Error> {
+    let flags = feature_services::list_flags_cached(&app_state).await?;
+    Ok(Json(ApiResponse::success(feature_services::evaluate_all(
+        &flags,
+        Some(auth_user.id),
+    ))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/features",
+    tag = "Features",
+    summary = "List feature flags",
+    description = "List every feature flag with its rules (admins of the default tenant only)",
+    responses(
+        (status = 200, description = "Flags retrieved", body = ApiResponse<Vec<FeatureFlag>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_flags(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<FeatureFlag>>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Feature flags are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let flags = feature_services::list_flags(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(flags)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/features",
+    tag = "Features",
+    summary = "Create feature flag",
+    description = "Create a feature flag; it stays off until enabled (admins of the default tenant only)",
+    request_body = CreateFeatureFlagRequest,
+    responses(
+        (status = 200, description = "Flag created", body = ApiResponse<FeatureFlag>),
+        (status = 400, description = "Invalid key, percentage or users", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 409, description = "Key already taken", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_flag(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateFeatureFlagRequest>,
+) -> Result<Json<ApiResponse<FeatureFlag>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Feature flags are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let flag = feature_services::create_flag(conn.as_mut(), &app_state.cache, request).await?;
+    Ok(Json(ApiResponse::success(flag)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/admin/features/{key}",
+    tag = "Features",
+    summary = "Update feature flag",
+    description = "Enable or disable a flag, change its rollout percentage or replace its targeted users (admins of the default tenant only)",
+    params(
+        ("key" = String, Path, description = "Flag key")
+    ),
+    request_body = UpdateFeatureFlagRequest,
+    responses(
+        (status = 200, description = "Flag updated", body = ApiResponse<FeatureFlag>),
+        (status = 400, description = "Invalid percentage or users", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Flag not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_flag(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(key): Path<String>,
+    Json(request): Json<UpdateFeatureFlagRequest>,
+) -> Result<Json<ApiResponse<FeatureFlag>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Feature flags are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let flag =
+        feature_services::update_flag(conn.as_mut(), &app_state.cache, &key, request).await?;
+    Ok(Json(ApiResponse::success(flag)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/features/{key}",
+    tag = "Features",
+    summary = "Delete feature flag",
+    description = "Delete a flag; checks of its key then read it as off (admins of the default tenant only)",
+    params(
+        ("key" = String, Path, description = "Flag key")
+    ),
+    responses(
+        (status = 200, description = "Flag deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Flag not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_flag(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(key): Path<String>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Feature flags are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    feature_services::delete_flag(conn.as_mut(), &app_state.cache, &key).await?;
+    Ok(Json(ApiResponse::success(
+        "Feature flag deleted".to_string(),
+    )))
+}
+
+/// Feature flags of the signed-in user (authentication required)
+pub fn features_routes() -> Router<AppState> {
+    Router::new().route("/", get(get_features))
+}
+
+/// Feature flag management routes (admin role required)
+pub fn features_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_flags).post(create_flag))
+        .route("/{key}", patch(update_flag).delete(delete_flag))
+}
@@ -0,0 +1,14 @@
+//! Feature flags for dark launches
+//!
+//! A flag is on for the users it targets and for a stable percentage of the
+//! rest, and off for everyone while disabled. Clients read their flags from
+//! `GET /features`; server code checks one with [`services::is_enabled`].
+//! Flags are cached for `feature_flag_ttl_secs`, so changes reach other
+//! servers once their entries expire.
+
+pub mod api;
+pub mod models;
+pub mod services;
+
+pub use models::FeatureFlag;
+pub use services::is_enabled;
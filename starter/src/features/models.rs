@@ -0,0 +1,203 @@
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const MAX_FLAG_KEY_LENGTH: usize = 100;
+pub const MAX_FLAG_DESCRIPTION_LENGTH: usize = 500;
+/// Users a flag may target by ID
+pub const MAX_FLAG_USERS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct FeatureFlag {
+    /// Name server code and clients check the flag by, e.g. `new-dashboard`
+    pub key: String,
+    pub description: String,
+    /// Disabled flags are off for everyone
+    pub enabled: bool,
+    /// Share of users, 0 to 100, the flag is on for
+    pub rollout_percentage: i16,
+    /// Users the flag is on for regardless of the rollout percentage
+    pub user_ids: Vec<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeatureFlag {
+    /// Whether the flag is on for the user; without one, only at a full rollout
+    pub fn is_enabled_for(&self, user_id: Option<Uuid>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match user_id {
+            Some(user_id) => {
+                self.user_ids.contains(&user_id)
+                    || i16::from(rollout_bucket(&self.key, user_id)) < self.rollout_percentage
+            }
+            None => self.rollout_percentage >= 100,
+        }
+    }
+}
+
+/// Bucket from 0 to 99 the user falls into for the flag
+///
+/// Stable for a user, so raising the percentage only adds users, and
+/// independent between flags, so the same users aren't always first.
+pub fn rollout_bucket(key: &str, user_id: Uuid) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(key.as_bytes())
+        .chain_update(b":")
+        .chain_update(user_id.as_bytes())
+        .finalize();
+    let value = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    (value % 100) as u8
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateFeatureFlagRequest {
+    /// Lowercase letters, digits, dashes, underscores and dots
+    pub key: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub enabled: bool,
+    /// 100 by default
+    pub rollout_percentage: Option<i16>,
+    #[serde(default)]
+    pub user_ids: Vec<Uuid>,
+}
+
+impl CreateFeatureFlagRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_key(&self.key)?;
+        validate_description(&self.description)?;
+        self.rollout_percentage
+            .map_or(Ok(()), validate_rollout_percentage)?;
+        validate_user_ids(&self.user_ids)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateFeatureFlagRequest {
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    pub rollout_percentage: Option<i16>,
+    /// Replaces the targeted users
+    pub user_ids: Option<Vec<Uuid>>,
+}
+
+impl UpdateFeatureFlagRequest {
+    pub fn validate(&self) -> Result<()> {
+        self.description
+            .as_deref()
+            .map_or(Ok(()), validate_description)?;
+        self.rollout_percentage
+            .map_or(Ok(()), validate_rollout_percentage)?;
+        self.user_ids.as_deref().map_or(Ok(()), validate_user_ids)
+    }
+}
+
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_FLAG_KEY_LENGTH
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(Error::validation(
+            "key",
+            &format!(
+                "Key must be 1 to {MAX_FLAG_KEY_LENGTH} lowercase letters, digits, dashes, underscores or dots"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_description(description: &str) -> Result<()> {
+    if description.len() > MAX_FLAG_DESCRIPTION_LENGTH {
+        return Err(Error::validation(
+            "description",
+            &format!("Description must be at most {MAX_FLAG_DESCRIPTION_LENGTH} characters"),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rollout_percentage(percentage: i16) -> Result<()> {
+    if !(0..=100).contains(&percentage) {
+        return Err(Error::validation(
+            "rollout_percentage",
+            "Rollout percentage must be between 0 and 100",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_user_ids(user_ids: &[Uuid]) -> Result<()> {
+    if user_ids.len() > MAX_FLAG_USERS {
+        return Err(Error::validation(
+            "user_ids",
+            &format!("A flag can target at most {MAX_FLAG_USERS} users"),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(enabled: bool, rollout_percentage: i16, user_ids: Vec<Uuid>) -> FeatureFlag {
+        FeatureFlag {
+            key: "new-dashboard".to_string(),
+            description: String::new(),
+            enabled,
+            rollout_percentage,
+            user_ids,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_targeted_users_and_rollout() {
+        let targeted = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        let dark = flag(true, 0, vec![targeted]);
+        assert!(dark.is_enabled_for(Some(targeted)));
+        assert!(!dark.is_enabled_for(Some(other)));
+        assert!(!dark.is_enabled_for(None));
+
+        let full = flag(true, 100, vec![]);
+        assert!(full.is_enabled_for(Some(other)));
+        assert!(full.is_enabled_for(None));
+
+        // Disabled flags are off even for targeted users
+        assert!(!flag(false, 100, vec![targeted]).is_enabled_for(Some(targeted)));
+    }
+
+    #[test]
+    fn test_rollout_reaches_about_its_percentage() {
+        let half = flag(true, 50, vec![]);
+        let enabled = (0..2000)
+            .filter(|_| half.is_enabled_for(Some(Uuid::new_v4())))
+            .count();
+        assert!((800..1200).contains(&enabled), "{enabled} of 2000");
+
+        let user_id = Uuid::new_v4();
+        assert_eq!(
+            rollout_bucket("new-dashboard", user_id),
+            rollout_bucket("new-dashboard", user_id)
+        );
+    }
+
+    #[test]
+    fn test_key_validation() {
+        assert!(validate_key("checkout.v2_beta-1").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("New Dashboard").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::core::cache::AppCache;
+use crate::features::models::{CreateFeatureFlagRequest, FeatureFlag, UpdateFeatureFlagRequest};
+use crate::{AppState, DbConn, Error, Result};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Cached list of every flag
+pub const FEATURE_FLAG_CACHE_NAMESPACE: &str = "feature_flags";
+const ALL_FLAGS_KEY: &str = "all";
+
+pub async fn list_flags(conn: &mut DbConn) -> Result<Vec<FeatureFlag>> {
+    sqlx::query_as!(
+        FeatureFlag,
+        r#"
+        SELECT key, description, enabled, rollout_percentage, user_ids, created_at, updated_at
+        FROM feature_flags
+        ORDER BY key
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// [`list_flags`], through the cache
+///
+/// A database connection is only acquired on a cache miss, so flag checks on
+/// hot paths don't compete for the pool.
+pub async fn list_flags_cached(app_state: &AppState) -> Result<Vec<FeatureFlag>> {
+    let cache = &app_state.cache;
+    let ttl = app_state.config.feature_flag_cache_ttl();
+    if !ttl.is_zero()
+        && let Some(flags) = cache.get(FEATURE_FLAG_CACHE_NAMESPACE, ALL_FLAGS_KEY).await
+    {
+        return Ok(flags);
+    }
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let flags = list_flags(conn.as_mut()).await?;
+    if !ttl.is_zero() {
+        cache
+            .set(FEATURE_FLAG_CACHE_NAMESPACE, ALL_FLAGS_KEY, &flags, ttl)
+            .await;
+    }
+    Ok(flags)
+}
+
+/// Every flag by key, evaluated for the user
+pub fn evaluate_all(flags: &[FeatureFlag], user_id: Option<Uuid>) -> BTreeMap<String, bool> {
+    flags
+        .iter()
+        .map(|flag| (flag.key.clone(), flag.is_enabled_for(user_id)))
+        .collect()
+}
+
+/// Whether the flag is on for the user, for server code gating a feature
+///
+/// Unknown flags are off, and so are all flags when they can't be loaded, so
+/// dark-launched code stays dark when the database is unavailable.
+pub async fn is_enabled(app_state: &AppState, key: &str, user_id: Option<Uuid>) -> bool {
+    match list_flags_cached(app_state).await {
+        Ok(flags) => flags
+            .iter()
+            .find(|flag| flag.key == key)
+            .is_some_and(|flag| flag.is_enabled_for(user_id)),
+        Err(e) => {
+            tracing::warn!("Failed to load feature flags, treating '{key}' as off: {e}");
+            false
+        }
+    }
+}
+
+pub async fn create_flag(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    request: CreateFeatureFlagRequest,
+) -> Result<FeatureFlag> {
+    request.validate()?;
+    let flag = sqlx::query_as!(
+        FeatureFlag,
+        r#"
+        INSERT INTO feature_flags (key, description, enabled, rollout_percentage, user_ids)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING key, description, enabled, rollout_percentage, user_ids, created_at, updated_at
+        "#,
+        request.key,
+        request.description.trim(),
+        request.enabled,
+        request.rollout_percentage.unwrap_or(100),
+        &request.user_ids
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Feature flag '{}' already exists", request.key))
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    cache.clear(FEATURE_FLAG_CACHE_NAMESPACE).await;
+    Ok(flag)
+}
+
+/// Change a flag; cached flags are dropped so the change applies on every
+/// server once their entries expire
+pub async fn update_flag(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    key: &str,
+    request: UpdateFeatureFlagRequest,
+) -> Result<FeatureFlag> {
+    request.validate()?;
+    let flag = sqlx::query_as!(
+        FeatureFlag,
+        r#"
+        UPDATE feature_flags
+        SET description = COALESCE($2, description),
+            enabled = COALESCE($3, enabled),
+            rollout_percentage = COALESCE($4, rollout_percentage),
+            user_ids = COALESCE($5, user_ids),
+            updated_at = NOW()
+        WHERE key = $1
+        RETURNING key, description, enabled, rollout_percentage, user_ids, created_at, updated_at
+        "#,
+        key,
+        request.description.as_deref().map(str::trim),
+        request.enabled,
+        request.rollout_percentage,
+        request.user_ids.as_deref()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Feature flag not found".to_string()))?;
+
+    cache.clear(FEATURE_FLAG_CACHE_NAMESPACE).await;
+    Ok(flag)
+}
+
+pub async fn delete_flag(conn: &mut DbConn, cache: &AppCache, key: &str) -> Result<()> {
+    let result = sqlx::query!("DELETE FROM feature_flags WHERE key = $1", key)
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Feature flag not found".to_string()));
+    }
+
+    cache.clear(FEATURE_FLAG_CACHE_NAMESPACE).await;
+    Ok(())
+}
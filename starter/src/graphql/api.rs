@@ -0,0 +1,44 @@
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::graphql::schema::{AppSchema, build_schema};
+use async_graphql::http::GraphiQLSource;
+use axum::{
+    Extension, Json, Router,
+    extract::State,
+    response::Html,
+    routing::{get, post},
+};
+
+/// Path the API is served at, for GraphiQL
+const GRAPHQL_PATH: &str = "/api/v2/graphql";
+
+/// Execute a GraphQL query or mutation as the signed-in user
+///
+/// Errors are reported in the response's `errors` with a 200 status, as
+/// GraphQL clients expect.
+pub async fn graphql(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Extension(schema): Extension<AppSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let request = request.data(app_state).data(auth_user);
+    Json(schema.execute(request).await)
+}
+
+/// GraphiQL page for exploring the schema; queries send its Authorization header
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint(GRAPHQL_PATH).finish())
+}
+
+/// GraphiQL page (no authentication required)
+pub fn graphql_public_routes() -> Router<AppState> {
+    Router::new().route("/graphql", get(graphiql))
+}
+
+/// GraphQL endpoint (authentication required)
+pub fn graphql_routes() -> Router<AppState> {
+    Router::new()
+        .route("/graphql", post(graphql))
+        .layer(Extension(build_schema()))
+}
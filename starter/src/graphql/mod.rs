@@ -0,0 +1,12 @@
+//! GraphQL API over the users, tasks and monitoring endpoints
+//!
+//! Built with the `graphql` cargo feature. Resolvers call the REST handlers, so
+//! both APIs share validation, RBAC checks, limits and activity records; errors
+//! carry the REST error code in `extensions.code`. Queries are served to
+//! signed-in users at `POST /api/v1/graphql`, and `GET` opens GraphiQL.
+
+pub mod api;
+pub mod schema;
+pub mod types;
+
+pub use schema::{AppSchema, build_schema};
@@ -0,0 +1,313 @@
+use crate::api::ApiResponse;
+use crate::audit::Actor;
+use crate::auth::AuthUser;
+use crate::graphql::types::{
+    CreateEventInput, CreateMetricInput, CreateTaskInput, Event, EventFilter, Metric, MetricFilter,
+    Task, TaskFilter, UpdateProfileInput, User, UserFilter, UserList,
+};
+use crate::monitoring::{api as monitoring_api, models as monitoring};
+use crate::rbac::{Permission, Resource, services as rbac_services};
+use crate::tasks::{api as tasks_api, types as tasks};
+use crate::users::{api as users_api, models as users};
+use crate::{AppState, Error};
+use async_graphql::{Context, EmptySubscription, ErrorExtensions, Object, Schema};
+use axum::extract::{Extension, Json, Path, Query, State};
+use uuid::Uuid;
+
+/// Deepest selection a query may nest
+const MAX_DEPTH: usize = 10;
+
+/// Most fields a query may select
+const MAX_COMPLEXITY: usize = 500;
+
+pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// Build the schema; requests need the [`AppState`] and the caller's [`AuthUser`] as data
+pub fn build_schema() -> AppSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// GraphQL error with the message and code of the REST error response
+pub fn graphql_error(error: Error) -> async_graphql::Error {
+    error.log_internal();
+    let (_, message, code) = error.parts();
+    async_graphql::Error::new(message).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+/// State and caller to pass to a REST handler
+fn caller(ctx: &Context<'_>) -> async_graphql::Result<(State<AppState>, Extension<AuthUser>)> {
+    let app_state = ctx.data::<AppState>()?.clone();
+    let auth_user = ctx.data::<AuthUser>()?.clone();
+    Ok((State(app_state), Extension(auth_user)))
+}
+
+/// Data of a REST handler's response
+fn data<T>(response: Result<Json<ApiResponse<T>>, Error>) -> async_graphql::Result<T> {
+    let Json(response) = response.map_err(graphql_error)?;
+    response
+        .data
+        .ok_or_else(|| graphql_error(Error::internal("Response without data")))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The signed-in user
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let (state, user) = caller(ctx)?;
+        data(users_api::get_profile(state, user).await).map(User::from)
+    }
+
+    /// A user of the tenant; users may only look themselves up
+    async fn user(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<User> {
+        let (state, user) = caller(ctx)?;
+        data(users_api::get_user_by_id(state, user, Path(id)).await).map(User::from)
+    }
+
+    /// Search users (moderators, or a custom role with `users:read`)
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: UserFilter,
+    ) -> async_graphql::Result<UserList> {
+        let (state, user) = caller(ctx)?;
+        rbac_services::require_staff_permission(&user, Resource::Users, Permission::Read)
+            .map_err(graphql_error)?;
+        let query = users_api::ListUsersQuery {
+            search: filter.search,
+            role: filter.role.map(Into::into),
+            is_active: filter.is_active,
+            email_verified: filter.email_verified,
+            created_after: None,
+            created_before: None,
+            sort: None,
+            order: None,
+            limit: filter.limit,
+            offset: filter.offset,
+        };
+        let users: users::UserListResponse =
+            data(users_api::list_users(state, user, Query(query)).await)?;
+        Ok(UserList {
+            total_count: users.pagination.total_count,
+            items: users.items.into_iter().map(User::from).collect(),
+        })
+    }
+
+    /// A task the user can see, or null
+    async fn task(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Option<Task>> {
+        let (state, user) = caller(ctx)?;
+        let task = data(tasks_api::get_task(state, Path(id), user).await)?;
+        Ok(task.map(Task::from))
+    }
+
+    /// Tasks the user can see, newest first
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: TaskFilter,
+    ) -> async_graphql::Result<Vec<Task>> {
+        let (state, user) = caller(ctx)?;
+        let params = tasks_api::TaskQueryParams {
+            task_type: filter.task_type,
+            status: filter
+                .status
+                .map(|status| tasks::TaskStatus::from(status).as_str().to_string()),
+            priority: filter
+                .priority
+                .map(|priority| tasks::TaskPriority::from(priority).as_str().to_string()),
+            tag: filter.tag,
+            q: filter.q,
+            org_id: filter.org_id,
+            limit: filter.limit,
+            offset: filter.offset,
+        };
+        let tasks = data(tasks_api::list_tasks(state, Query(params), user).await)?;
+        Ok(tasks.into_iter().map(Task::from).collect())
+    }
+
+    /// Stored events the user can see
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: EventFilter,
+    ) -> async_graphql::Result<Vec<Event>> {
+        let (state, user) = caller(ctx)?;
+        rbac_services::check_permission(&user, Resource::Monitoring, Permission::Read)
+            .map_err(graphql_error)?;
+        let params = monitoring_api::EventQueryParams {
+            event_type: filter.event_type.map(Into::into),
+            source: filter.source,
+            level: filter.level,
+            start_time: filter.start_time,
+            end_time: filter.end_time,
+            limit: filter.limit,
+            offset: filter.offset,
+            tags: filter.tags,
+            org_id: filter.org_id,
+        };
+        let events = data(monitoring_api::get_events(state, user, Query(params)).await)?;
+        Ok(events.into_iter().map(Event::from).collect())
+    }
+
+    /// Stored metric points
+    async fn metrics(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: MetricFilter,
+    ) -> async_graphql::Result<Vec<Metric>> {
+        let (state, user) = caller(ctx)?;
+        rbac_services::check_permission(&user, Resource::Monitoring, Permission::Read)
+            .map_err(graphql_error)?;
+        let params = monitoring_api::MetricQueryParams {
+            name: filter.name,
+            metric_type: filter.metric_type.map(Into::into),
+            start_time: filter.start_time,
+            end_time: filter.end_time,
+            limit: filter.limit,
+            offset: filter.offset,
+        };
+        let metrics = data(monitoring_api::get_metrics(state, user, Query(params)).await)?;
+        Ok(metrics.into_iter().map(Metric::from).collect())
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Create a background task
+    async fn create_task(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateTaskInput,
+    ) -> async_graphql::Result<Task> {
+        let (state, user) = caller(ctx)?;
+        let request = tasks_api::CreateTaskApiRequest {
+            task_type: input.task_type,
+            payload: input.payload.0,
+            priority: input
+                .priority
+                .map(|priority| tasks::TaskPriority::from(priority).as_str().to_string()),
+            scheduled_at: input.scheduled_at,
+            metadata: input
+                .metadata
+                .map(|metadata| metadata.0)
+                .unwrap_or_default(),
+            tags: input.tags,
+            timeout_seconds: input.timeout_seconds,
+            dedupe_key: input.dedupe_key,
+            org_id: input.org_id,
+        };
+        data(tasks_api::create_task(state, user, Json(request)).await).map(Task::from)
+    }
+
+    /// Cancel a pending or retrying task and return it
+    async fn cancel_task(&self, ctx: &Context<'_>, id: Uuid) -> async_graphql::Result<Task> {
+        let (state, user) = caller(ctx)?;
+        let actor = Actor::from(&user.0);
+        data(tasks_api::cancel_task(state.clone(), Path(id), user.clone(), actor).await)?;
+        data(tasks_api::get_task(state, Path(id), user).await)?
+            .map(Task::from)
+            .ok_or_else(|| graphql_error(Error::TaskNotFound))
+    }
+
+    /// Update the signed-in user's username, email or custom profile fields
+    async fn update_profile(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateProfileInput,
+    ) -> async_graphql::Result<User> {
+        let (state, user) = caller(ctx)?;
+        let request = users::UpdateProfileRequest {
+            username: input.username,
+            email: input.email,
+            profile: input.profile.map(|profile| profile.0),
+        };
+        data(users_api::update_own_profile(state, user, Json(request)).await).map(User::from)
+    }
+
+    /// Store an event
+    async fn create_event(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateEventInput,
+    ) -> async_graphql::Result<Event> {
+        let (state, user) = caller(ctx)?;
+        rbac_services::check_permission(&user, Resource::Monitoring, Permission::Write)
+            .map_err(graphql_error)?;
+        let request = monitoring::CreateEventRequest {
+            event_type: monitoring::EventType::from(input.event_type)
+                .as_str()
+                .to_string(),
+            source: input.source,
+            message: input.message,
+            level: input.level,
+            tags: input.tags.map(|tags| tags.0).unwrap_or_default(),
+            payload: input.payload.map(|payload| payload.0).unwrap_or_default(),
+            recorded_at: input.recorded_at,
+            org_id: input.org_id,
+            tenant_id: None,
+        };
+        let response = monitoring_api::create_event(state, user, Json(request))
+            .await
+            .map(|(_, response)| response);
+        data(response).map(Event::from)
+    }
+
+    /// Store a metric point
+    async fn create_metric(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateMetricInput,
+    ) -> async_graphql::Result<Metric> {
+        let (state, user) = caller(ctx)?;
+        rbac_services::check_permission(&user, Resource::Monitoring, Permission::Write)
+            .map_err(graphql_error)?;
+        let request = monitoring::CreateMetricRequest {
+            name: input.name,
+            metric_type: input.metric_type.into(),
+            value: input.value,
+            labels: input.labels.map(|labels| labels.0).unwrap_or_default(),
+            recorded_at: input.recorded_at,
+            tenant_id: None,
+        };
+        data(monitoring_api::create_metric(state, user, Json(request)).await).map(Metric::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::Value;
+
+    #[test]
+    fn test_errors_carry_the_rest_error_code() {
+        let code = |error: &async_graphql::Error| error.extensions.as_ref()?.get("code").cloned();
+
+        let error = graphql_error(Error::Forbidden("Moderator role required".to_string()));
+        assert_eq!(error.message, "Moderator role required");
+        assert_eq!(code(&error), Some(Value::from("FORBIDDEN")));
+
+        // Internal details stay hidden, as in REST responses
+        let error = graphql_error(Error::Internal("connection refused".to_string()));
+        assert_eq!(error.message, "Internal server error");
+        assert_eq!(code(&error), Some(Value::from("INTERNAL_ERROR")));
+    }
+
+    #[test]
+    fn test_schema_exposes_users_tasks_and_monitoring() {
+        let sdl = build_schema().sdl();
+        for field in [
+            "me: User!",
+            "tasks(filter: TaskFilter!",
+            "events(",
+            "createTask(",
+        ] {
+            assert!(sdl.contains(field), "missing {field}");
+        }
+    }
+}
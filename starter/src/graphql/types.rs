@@ -0,0 +1,309 @@
+use crate::monitoring::models as monitoring;
+use crate::tasks::types::TaskResponse;
+use crate::users::models::UserProfile;
+use async_graphql::{Enum, InputObject, Json, SimpleObject};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::rbac::UserRole")]
+pub enum UserRole {
+    User,
+    Moderator,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::tasks::types::TaskStatus")]
+pub enum TaskStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    Retrying,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::tasks::types::TaskPriority")]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::monitoring::models::EventType")]
+pub enum EventType {
+    Log,
+    Metric,
+    Trace,
+    Alert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "crate::monitoring::models::MetricType")]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+}
+
+/// A user's profile, as returned by the user endpoints
+#[derive(Debug, SimpleObject)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: UserRole,
+    pub is_active: bool,
+    pub email_verified: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub avatar_url: Option<String>,
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub role_expires_at: Option<DateTime<Utc>>,
+    /// Custom profile fields the caller may see
+    pub profile: Option<Json<serde_json::Value>>,
+    /// Support annotations, only for moderator and admin viewers
+    pub internal_metadata: Option<Json<serde_json::Value>>,
+}
+
+impl From<UserProfile> for User {
+    fn from(user: UserProfile) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            role: user.role.into(),
+            is_active: user.is_active,
+            email_verified: user.email_verified,
+            created_at: user.created_at,
+            last_login_at: user.last_login_at,
+            avatar_url: user.avatar_url,
+            suspended_until: user.suspended_until,
+            last_seen_at: user.last_seen_at,
+            role_expires_at: user.role_expires_at,
+            profile: user.profile.map(Json),
+            internal_metadata: user.internal_metadata.map(Json),
+        }
+    }
+}
+
+/// One page of users
+#[derive(Debug, SimpleObject)]
+pub struct UserList {
+    pub items: Vec<User>,
+    /// Users matching the filter across all pages
+    pub total_count: i64,
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct UserFilter {
+    /// Case-insensitive match anywhere in the username or email
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub is_active: Option<bool>,
+    pub email_verified: Option<bool>,
+    /// At most 100, 50 by default
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, InputObject)]
+pub struct UpdateProfileInput {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    /// Custom field values to set; `null` clears a field
+    pub profile: Option<Json<serde_json::Map<String, serde_json::Value>>>,
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct Task {
+    pub id: Uuid,
+    pub task_type: String,
+    pub status: TaskStatus,
+    pub priority: TaskPriority,
+    pub max_attempts: i32,
+    pub current_attempt: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub scheduled_at: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub metadata: Json<HashMap<String, serde_json::Value>>,
+    pub tags: Vec<String>,
+    pub timeout_seconds: Option<i32>,
+    pub dedupe_key: Option<String>,
+    /// Task whose handler spawned this one
+    pub parent_task_id: Option<Uuid>,
+    /// Organization whose members can see the task
+    pub org_id: Option<Uuid>,
+}
+
+impl From<TaskResponse> for Task {
+    fn from(task: TaskResponse) -> Self {
+        Self {
+            id: task.id,
+            task_type: task.task_type,
+            status: task.status.into(),
+            priority: task.priority.into(),
+            max_attempts: task.max_attempts,
+            current_attempt: task.current_attempt,
+            last_error: task.last_error,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            scheduled_at: task.scheduled_at,
+            started_at: task.started_at,
+            completed_at: task.completed_at,
+            created_by: task.created_by,
+            metadata: Json(task.metadata),
+            tags: task.tags,
+            timeout_seconds: task.timeout_seconds,
+            dedupe_key: task.dedupe_key,
+            parent_task_id: task.parent_task_id,
+            org_id: task.org_id,
+        }
+    }
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct TaskFilter {
+    pub task_type: Option<String>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub tag: Option<String>,
+    /// Full-text search over payload and metadata values
+    pub q: Option<String>,
+    /// Only tasks scoped to this organization
+    pub org_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, InputObject)]
+pub struct CreateTaskInput {
+    pub task_type: String,
+    pub payload: Json<serde_json::Value>,
+    pub priority: Option<TaskPriority>,
+    pub scheduled_at: Option<DateTime<Utc>>,
+    pub metadata: Option<Json<HashMap<String, serde_json::Value>>>,
+    #[graphql(default)]
+    pub tags: Vec<String>,
+    /// Execution timeout override in seconds
+    pub timeout_seconds: Option<i32>,
+    /// Return the existing pending or running task with this key instead of creating a new one
+    pub dedupe_key: Option<String>,
+    /// Organization to scope the task to; its members can see it
+    pub org_id: Option<Uuid>,
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct Event {
+    pub id: Uuid,
+    pub event_type: EventType,
+    pub source: String,
+    pub message: Option<String>,
+    pub level: Option<String>,
+    pub tags: Json<serde_json::Value>,
+    pub payload: Json<serde_json::Value>,
+    pub recorded_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    /// Organization whose members can see the event
+    pub org_id: Option<Uuid>,
+}
+
+impl From<monitoring::Event> for Event {
+    fn from(event: monitoring::Event) -> Self {
+        Self {
+            id: event.id,
+            event_type: event.event_type.into(),
+            source: event.source,
+            message: event.message,
+            level: event.level,
+            tags: Json(event.tags),
+            payload: Json(event.payload),
+            recorded_at: event.recorded_at,
+            created_at: event.created_at,
+            org_id: event.org_id,
+        }
+    }
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct EventFilter {
+    pub event_type: Option<EventType>,
+    pub source: Option<String>,
+    pub level: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// Comma-separated `key:value` pairs the event's tags must all match
+    pub tags: Option<String>,
+    /// Only events scoped to this organization
+    pub org_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, InputObject)]
+pub struct CreateEventInput {
+    pub event_type: EventType,
+    pub source: String,
+    pub message: Option<String>,
+    pub level: Option<String>,
+    pub tags: Option<Json<HashMap<String, serde_json::Value>>>,
+    pub payload: Option<Json<HashMap<String, serde_json::Value>>>,
+    pub recorded_at: Option<DateTime<Utc>>,
+    /// Organization to scope the event to; requires membership
+    pub org_id: Option<Uuid>,
+}
+
+#[derive(Debug, SimpleObject)]
+pub struct Metric {
+    pub id: Uuid,
+    pub name: String,
+    pub metric_type: MetricType,
+    pub value: f64,
+    pub labels: Json<serde_json::Value>,
+    pub recorded_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<monitoring::Metric> for Metric {
+    fn from(metric: monitoring::Metric) -> Self {
+        Self {
+            id: metric.id,
+            name: metric.name,
+            metric_type: metric.metric_type.into(),
+            value: metric.value,
+            labels: Json(metric.labels),
+            recorded_at: metric.recorded_at,
+            created_at: metric.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct MetricFilter {
+    pub name: Option<String>,
+    pub metric_type: Option<MetricType>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, InputObject)]
+pub struct CreateMetricInput {
+    pub name: String,
+    pub metric_type: MetricType,
+    pub value: f64,
+    pub labels: Option<Json<HashMap<String, String>>>,
+    pub recorded_at: Option<DateTime<Utc>>,
+}
@@ -56,6 +56,7 @@ pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
             error: ErrorDetail {
                 code: "SERVICE_UNHEALTHY".to_string(),
                 message: format!("Service unhealthy: {}", overall_status),
+                details: None,
             },
         };
         (StatusCode::SERVICE_UNAVAILABLE, Json(error_response)).into_response()
@@ -1,11 +1,21 @@
 pub mod api;
+pub mod audit;
 pub mod auth;
 pub mod cli;
 pub mod core;
+pub mod features;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod health;
+pub mod maintenance;
 pub mod monitoring;
+pub mod orgs;
 pub mod rbac;
+pub mod realtime;
+pub mod scim;
+pub mod storage;
 pub mod tasks;
+pub mod tenants;
 pub mod users;
 
 // Re-export most commonly used core types for convenience
@@ -0,0 +1,82 @@
+use crate::audit::Actor;
+use crate::auth::AuthUser;
+use crate::maintenance::{
+    models::{MaintenanceStatus, SetMaintenanceRequest},
+    services as maintenance_services,
+};
+use crate::tenants::services as tenant_services;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, State},
+    response::Json,
+    routing::get,
+};
+
+#[utoipa::path(
+    get,
+    path = "/admin/maintenance",
+    tag = "Admin",
+    summary = "Get maintenance mode",
+    description = "Whether the API is in maintenance mode, from the admin toggle or the configuration, and what clients are told (Admin only)",
+    responses(
+        (status = 200, description = "Maintenance status", body = ApiResponse<MaintenanceStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_maintenance(
+    State(app_state): State<AppState>,
+) -> Result<Json<ApiResponse<MaintenanceStatus>>, Error> {
+    let status = maintenance_services::current_status(&app_state).await?;
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/admin/maintenance",
+    tag = "Admin",
+    summary = "Toggle maintenance mode",
+    description = "Turn maintenance mode on or off. While on, requests of non-admins other than health checks get 503 with `Retry-After`, only admins can sign in, and the worker stops claiming tasks (admins of the default tenant only)",
+    request_body = SetMaintenanceRequest,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = ApiResponse<MaintenanceStatus>),
+        (status = 400, description = "Invalid message or wait", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_maintenance(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+    Json(request): Json<SetMaintenanceRequest>,
+) -> Result<Json<ApiResponse<MaintenanceStatus>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Maintenance mode is toggled")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let toggle =
+        maintenance_services::set_toggle(conn.as_mut(), &app_state.cache, &actor, request).await?;
+    Ok(Json(ApiResponse::success(MaintenanceStatus::new(
+        &app_state.live_config.get(),
+        toggle,
+    ))))
+}
+
+/// Maintenance mode routes (admin role required)
+pub fn maintenance_admin_routes() -> Router<AppState> {
+    Router::new().route("/", get(get_maintenance).put(set_maintenance))
+}
@@ -0,0 +1,28 @@
+use crate::auth::AuthUser;
+use crate::maintenance::services as maintenance_services;
+use crate::rbac::UserRole;
+use crate::{AppState, Error};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+/// Refuse requests of everyone but admins while in maintenance mode
+///
+/// Runs inside `auth_middleware` on authenticated routes, so admins are let
+/// through by their [`AuthUser`]; on public routes every request is refused.
+pub async fn maintenance_middleware(
+    State(app_state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let is_admin = req
+        .extensions()
+        .get::<AuthUser>()
+        .is_some_and(|auth_user| auth_user.role == UserRole::Admin);
+    if !is_admin {
+        maintenance_services::check(&app_state).await?;
+    }
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,18 @@
+//! Maintenance mode
+//!
+//! While it is on, requests other than those of admins and the health checks
+//! get a `503` with a `MAINTENANCE` error and a `Retry-After` header, only
+//! admins can sign in, and the worker stops claiming new tasks (running ones
+//! finish). Admins toggle it with `PUT /admin/maintenance`; setting
+//! `maintenance.enabled` keeps it on whatever the toggle says, e.g. during a
+//! deploy, and takes effect on a config reload. The toggle is cached for
+//! `maintenance_ttl_secs`, so changes reach other servers once their entries
+//! expire.
+
+pub mod api;
+pub mod middleware;
+pub mod models;
+pub mod services;
+
+pub use middleware::maintenance_middleware;
+pub use models::MaintenanceStatus;
@@ -0,0 +1,157 @@
+use crate::{AppConfig, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const DEFAULT_MESSAGE: &str = "The API is down for maintenance";
+pub const MAX_MESSAGE_LENGTH: usize = 500;
+/// Longest wait clients can be told about, one day
+pub const MAX_RETRY_AFTER_SECS: i32 = 86_400;
+
+/// The admin toggle as stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceToggle {
+    pub enabled: bool,
+    pub message: Option<String>,
+    pub retry_after_secs: Option<i32>,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MaintenanceToggle {
+    /// The toggle as first stored
+    pub fn off() -> Self {
+        Self {
+            enabled: false,
+            message: None,
+            retry_after_secs: None,
+            updated_by: None,
+            updated_at: DateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+/// Whether the API is in maintenance mode, and what clients are told
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MaintenanceStatus {
+    /// Whether non-admin requests are refused
+    pub enabled: bool,
+    /// Set by `maintenance.enabled` in the configuration; the toggle can't turn it off
+    pub forced_by_config: bool,
+    pub message: String,
+    /// Sent as `Retry-After`
+    pub retry_after_secs: u64,
+    /// Admin who last changed the toggle
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MaintenanceStatus {
+    /// The toggle combined with the configuration
+    pub fn new(config: &AppConfig, toggle: MaintenanceToggle) -> Self {
+        Self {
+            enabled: toggle.enabled || config.maintenance.enabled,
+            forced_by_config: config.maintenance.enabled,
+            message: toggle
+                .message
+                .unwrap_or_else(|| DEFAULT_MESSAGE.to_string()),
+            retry_after_secs: toggle
+                .retry_after_secs
+                .map_or(config.maintenance.retry_after_secs, |secs| secs as u64),
+            updated_by: toggle.updated_by,
+            updated_at: toggle.updated_at,
+        }
+    }
+
+    /// The error refused requests get, if enabled
+    pub fn check(&self) -> Result<()> {
+        if self.enabled {
+            return Err(Error::Maintenance {
+                message: self.message.clone(),
+                retry_after_secs: self.retry_after_secs,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetMaintenanceRequest {
+    pub enabled: bool,
+    /// Shown to clients; a generic message by default
+    pub message: Option<String>,
+    /// Seconds clients are told to wait; `maintenance.retry_after_secs` by default
+    #[schema(minimum = 1, maximum = 86400)]
+    pub retry_after_secs: Option<i32>,
+}
+
+impl SetMaintenanceRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .message
+            .as_ref()
+            .is_some_and(|message| message.len() > MAX_MESSAGE_LENGTH)
+        {
+            return Err(Error::validation(
+                "message",
+                &format!("Message must be at most {MAX_MESSAGE_LENGTH} characters"),
+            ));
+        }
+        if self
+            .retry_after_secs
+            .is_some_and(|secs| !(1..=MAX_RETRY_AFTER_SECS).contains(&secs))
+        {
+            return Err(Error::validation(
+                "retry_after_secs",
+                &format!("Must be between 1 and {MAX_RETRY_AFTER_SECS} seconds"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toggle(enabled: bool) -> MaintenanceToggle {
+        MaintenanceToggle {
+            enabled,
+            ..MaintenanceToggle::off()
+        }
+    }
+
+    #[test]
+    fn test_config_forces_maintenance_on() {
+        let mut config = AppConfig::default();
+        let status = MaintenanceStatus::new(&config, toggle(false));
+        assert!(!status.enabled);
+        assert!(status.check().is_ok());
+
+        config.maintenance.enabled = true;
+        let status = MaintenanceStatus::new(&config, toggle(false));
+        assert!(status.enabled && status.forced_by_config);
+        assert_eq!(status.message, DEFAULT_MESSAGE);
+        assert!(matches!(
+            status.check(),
+            Err(Error::Maintenance {
+                retry_after_secs: 300,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_toggle_overrides_retry_after() {
+        let status = MaintenanceStatus::new(
+            &AppConfig::default(),
+            MaintenanceToggle {
+                message: Some("Upgrading the database".to_string()),
+                retry_after_secs: Some(60),
+                ..toggle(true)
+            },
+        );
+        assert_eq!(status.message, "Upgrading the database");
+        assert_eq!(status.retry_after_secs, 60);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::audit::{self, Actor, AuditResource, AuditResourceType};
+use crate::core::cache::AppCache;
+use crate::maintenance::models::{MaintenanceStatus, MaintenanceToggle, SetMaintenanceRequest};
+use crate::{AppState, DbConn, Error, Result};
+use std::time::Duration;
+use tracing::warn;
+
+/// Cached admin toggle
+pub const MAINTENANCE_CACHE_NAMESPACE: &str = "maintenance";
+const TOGGLE_KEY: &str = "toggle";
+
+pub async fn get_toggle(conn: &mut DbConn) -> Result<MaintenanceToggle> {
+    sqlx::query_as!(
+        MaintenanceToggle,
+        r#"
+        SELECT enabled, message, retry_after_secs, updated_by, updated_at
+        FROM maintenance_mode
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// [`get_toggle`], through the cache
+pub async fn get_toggle_cached(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    ttl: Duration,
+) -> Result<MaintenanceToggle> {
+    if ttl.is_zero() {
+        return get_toggle(conn).await;
+    }
+    if let Some(toggle) = cache.get(MAINTENANCE_CACHE_NAMESPACE, TOGGLE_KEY).await {
+        return Ok(toggle);
+    }
+    let toggle = get_toggle(conn).await?;
+    cache
+        .set(MAINTENANCE_CACHE_NAMESPACE, TOGGLE_KEY, &toggle, ttl)
+        .await;
+    Ok(toggle)
+}
+
+/// Turn maintenance mode on or off, replacing the message and wait
+pub async fn set_toggle(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    actor: &Actor,
+    request: SetMaintenanceRequest,
+) -> Result<MaintenanceToggle> {
+    request.validate()?;
+    let message = request
+        .message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty());
+
+    let before = get_toggle(conn).await?;
+    let toggle = sqlx::query_as!(
+        MaintenanceToggle,
+        r#"
+        UPDATE maintenance_mode
+        SET enabled = $1, message = $2, retry_after_secs = $3,
+            updated_by = $4, updated_at = NOW()
+        RETURNING enabled, message, retry_after_secs, updated_by, updated_at
+        "#,
+        request.enabled,
+        message,
+        request.retry_after_secs,
+        actor.user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    cache.clear(MAINTENANCE_CACHE_NAMESPACE).await;
+    let snapshot = |toggle: &MaintenanceToggle| {
+        serde_json::json!({
+            "enabled": toggle.enabled,
+            "message": toggle.message,
+            "retry_after_secs": toggle.retry_after_secs,
+        })
+    };
+    audit::record(
+        conn,
+        actor,
+        if toggle.enabled {
+            "maintenance_enabled"
+        } else {
+            "maintenance_disabled"
+        },
+        &AuditResource::new(AuditResourceType::Maintenance, "api"),
+        Some(snapshot(&before)),
+        Some(snapshot(&toggle)),
+    )
+    .await;
+    Ok(toggle)
+}
+
+async fn load_toggle(app_state: &AppState) -> Result<MaintenanceToggle> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    get_toggle_cached(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.maintenance_cache_ttl(),
+    )
+    .await
+}
+
+/// Current maintenance status of the API
+pub async fn current_status(app_state: &AppState) -> Result<MaintenanceStatus> {
+    let toggle = load_toggle(app_state).await?;
+    Ok(MaintenanceStatus::new(&app_state.live_config.get(), toggle))
+}
+
+/// Refuse the request if the API is in maintenance mode
+///
+/// A toggle that can't be loaded is treated as off, leaving the request to
+/// fail on its own if the database is down; the configuration still applies.
+pub async fn check(app_state: &AppState) -> Result<()> {
+    let toggle = load_toggle(app_state).await.unwrap_or_else(|e| {
+        warn!("Failed to load maintenance mode, treating the toggle as off: {e}");
+        MaintenanceToggle::off()
+    });
+    MaintenanceStatus::new(&app_state.live_config.get(), toggle).check()
+}
@@ -1,22 +1,40 @@
+use super::export::{self, ExportFormat};
+use super::limits::{self, EventAdmission};
 use super::models::*;
-use super::services;
+use super::stream::EventStreamFilter;
+use super::{
+    cardinality, escalation, grafana, notes, notifications, oncall, otlp, postmortem, query,
+    quotas, recording, retention, routing, services, status_page, uptime,
+};
 use crate::Error;
+use crate::audit::{self, Actor, AuditResource, AuditResourceType};
 use crate::auth::AuthUser;
-use crate::rbac::services as rbac_services;
+use crate::core::body_limit::{self, limit_body};
+use crate::core::sse;
+use crate::orgs::{OrgRole, services as org_services};
+use crate::rbac::{Permission, Resource, services as rbac_services};
+use crate::tenants::services as tenant_services;
 use crate::{
     AppState,
     api::{ApiResponse, ErrorResponse},
 };
 use axum::{
     Extension, Router,
-    body::Body,
-    extract::{Path, Query, State},
-    http::{StatusCode, header},
-    response::{Json, Response},
-    routing::{get, post},
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path, Query, Request, State},
+    http::{HeaderMap, Method, StatusCode, header},
+    middleware::{self, Next},
+    response::{
+        IntoResponse, Json, Response,
+        sse::{Event as SseEvent, Sse},
+    },
+    routing::{delete, get, post, put},
 };
-use serde::Deserialize;
+use futures_util::Stream;
+use serde::{Deserialize, de::DeserializeOwned};
 use std::collections::HashMap;
+use std::convert::Infallible;
+use tower_http::decompression::RequestDecompressionLayer;
 use utoipa::IntoParams;
 use uuid::Uuid;
 
@@ -253,6 +271,21 @@ pub struct EventQueryParams {
     /// Tag filtering: supports key=value pairs separated by commas
     /// Example: ?tags=user_id:123,environment:production
     pub tags: Option<String>,
+    /// Only events scoped to this organization
+    pub org_id: Option<Uuid>,
+}
+
+/// Query parameters for the live event stream
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct EventStreamParams {
+    pub event_type: Option<EventType>,
+    pub source: Option<String>,
+    pub level: Option<String>,
+    /// Tag filtering: supports key=value pairs separated by commas
+    /// Example: ?tags=user_id:123,environment:production
+    pub tags: Option<String>,
+    /// Only events scoped to this organization
+    pub org_id: Option<Uuid>,
 }
 
 /// Query parameters for metric listing
@@ -268,173 +301,343 @@ pub struct MetricQueryParams {
     pub offset: Option<i64>,
 }
 
-/// Query parameters for incident listing
+/// Query parameters for event and metric exports
 #[derive(Debug, Deserialize, IntoParams)]
-pub struct IncidentQueryParams {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+pub struct MonitoringExportParams {
+    /// `csv` (default), `ndjson` or `parquet`
+    pub format: Option<String>,
 }
 
-/// Query parameters for timeline
-#[derive(Debug, Deserialize, IntoParams)]
-pub struct TimelineQueryParams {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-    pub lookback_hours: Option<i64>,
+impl MonitoringExportParams {
+    fn format(&self) -> Result<ExportFormat, Error> {
+        match self.format.as_deref() {
+            Some(format) => format.parse(),
+            None => Ok(ExportFormat::default()),
+        }
+    }
 }
 
-/// Create a new event
-#[utoipa::path(
-    post,
-    path = "/monitoring/events",
-    request_body = CreateEventRequest,
-    responses(
-        (status = 200, description = "Event created successfully", body = ApiResponse<Event>),
-        (status = 400, description = "Invalid input", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
-    ),
-    security(
-        ("bearer_auth" = [])
-    ),
-    tag = "Monitoring"
-)]
-pub async fn create_event(
-    State(app_state): State<AppState>,
-    Extension(auth_user): Extension<AuthUser>,
-    Json(request): Json<CreateEventRequest>,
-) -> Result<Json<ApiResponse<Event>>, Error> {
+/// Decode an OTLP/HTTP request body
+///
+/// Only the JSON encoding is supported; protobuf exporters get a 415 that
+/// names the exporter setting to change.
+fn decode_otlp<T: DeserializeOwned>(headers: &HeaderMap, body: &Bytes) -> Result<T, Error> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(str::trim)
+        .unwrap_or_default();
+
+    if !content_type.eq_ignore_ascii_case("application/json") {
+        return Err(Error::UnsupportedMediaType(format!(
+            "OTLP endpoints accept application/json only (got '{content_type}'); \
+             set OTEL_EXPORTER_OTLP_PROTOCOL=http/json"
+        )));
+    }
+
+    serde_json::from_slice(body)
+        .map_err(|e| Error::InvalidInput(format!("Invalid OTLP JSON payload: {e}")))
+}
+
+fn check_otlp_item_count(count: usize) -> Result<(), Error> {
+    if count > otlp::MAX_OTLP_ITEMS {
+        return Err(Error::validation(
+            "body",
+            &format!(
+                "Too many items in one export (max {}); lower the exporter batch size",
+                otlp::MAX_OTLP_ITEMS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that an ingested event is valid and its source may be used by the user
+///
+/// The outer error is a failure to check; the inner one why the event is rejected.
+fn check_ingested_event(
+    auth_user: &AuthUser,
+    is_moderator: bool,
+    event: &CreateEventRequest,
+) -> Result<Result<(), String>, Error> {
+    Ok(
+        if is_moderator || is_user_authorized_for_source(auth_user, &event.source)? {
+            event.validate().map_err(|e| e.to_string())
+        } else {
+            Err(format!(
+                "Source '{}' is not authorized for user '{}'",
+                event.source, auth_user.username
+            ))
+        },
+    )
+}
+
+/// Check that an event scoped to an organization comes from one of its members
+///
+/// Admins may scope events to any organization.
+fn check_event_org(
+    auth_user: &AuthUser,
+    org_ids: &[Uuid],
+    event: &CreateEventRequest,
+) -> Result<(), String> {
+    match event.org_id {
+        Some(org_id)
+            if auth_user.role != crate::rbac::models::UserRole::Admin
+                && !org_ids.contains(&org_id) =>
+        {
+            Err(format!("Organization '{org_id}' not found"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Keep the events the user may store, returning them with the rejected count and first reason
+fn accept_otlp_events(
+    auth_user: &AuthUser,
+    events: Vec<CreateEventRequest>,
+) -> Result<(Vec<CreateEventRequest>, i64, Option<String>), Error> {
+    let is_moderator = auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator);
+
+    let mut accepted = Vec::with_capacity(events.len());
+    let mut rejected = 0;
+    let mut reason = None;
+    for mut event in events {
+        match check_ingested_event(auth_user, is_moderator, &event)? {
+            Ok(()) => {
+                event.tenant_id = Some(auth_user.tenant_id);
+                accepted.push(event);
+            }
+            Err(e) => {
+                rejected += 1;
+                reason.get_or_insert(e);
+            }
+        }
+    }
+    Ok((accepted, rejected, reason))
+}
+
+/// Apply the ingestion limits to accepted OTLP events, keeping those to store
+///
+/// OTLP cannot report dropped items, so sampled events count as accepted;
+/// rate-limited ones are added to the rejected count.
+async fn limit_otlp_events(
+    app_state: &AppState,
+    auth_user: &AuthUser,
+    events: Vec<CreateEventRequest>,
+    rejected: &mut i64,
+    reason: &mut Option<String>,
+) -> Result<Vec<CreateEventRequest>, Error> {
     let mut conn = app_state
         .database
         .pool
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
+    let sources: Vec<&str> = events.iter().map(|event| event.source.as_str()).collect();
+    let admissions = limits::admit_events(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        auth_user.id,
+        auth_user.tenant_id,
+        auth_user.role,
+        &sources,
+    )
+    .await?;
 
-    // Authorization: Users can create events for sources they own, moderators+ can create any events
-    if !auth_user
-        .role
-        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
-    {
-        // Basic users can only create events for sources they own or generic sources
-        // Source ownership is determined by source prefix matching username or user ID
-        let is_authorized_source = is_user_authorized_for_source(&auth_user, &request.source)?;
-
-        if !is_authorized_source {
-            return Err(Error::Forbidden(format!(
-                "Users can only create events for their own sources. Source '{}' is not authorized for user '{}'",
-                request.source, auth_user.username
-            )));
+    let mut stored = Vec::with_capacity(events.len());
+    for (event, admission) in events.into_iter().zip(admissions) {
+        match admission {
+            EventAdmission::Accepted => stored.push(event),
+            EventAdmission::Sampled => {}
+            EventAdmission::RateLimited { retry_after_secs } => {
+                *rejected += 1;
+                reason.get_or_insert_with(|| rate_limited_message(&event.source, retry_after_secs));
+            }
+            EventAdmission::QuotaExceeded { scope, limit } => {
+                *rejected += 1;
+                reason.get_or_insert_with(|| {
+                    quotas::quota_exceeded_message("event", scope, &event.source, limit)
+                });
+            }
         }
     }
+    Ok(stored)
+}
 
-    let event = services::create_event(conn.as_mut(), request).await?;
-    Ok(Json(ApiResponse::success(event)))
+fn rate_limited_message(source: &str, retry_after_secs: u64) -> String {
+    format!("Rate limit exceeded for source '{source}', retry after {retry_after_secs} seconds")
 }
 
-/// Get events with filters
+/// Ingest OTLP spans as `trace` events
 #[utoipa::path(
-    get,
-    path = "/monitoring/events",
-    params(EventQueryParams),
+    post,
+    path = "/monitoring/otlp/traces",
+    request_body(
+        content = serde_json::Value,
+        content_type = "application/json",
+        description = "OTLP/HTTP `ExportTraceServiceRequest` (JSON encoding, optionally gzip-compressed)"
+    ),
     responses(
-        (status = 200, description = "Events retrieved successfully", body = ApiResponse<Vec<Event>>),
-        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Spans ingested; `partialSuccess` lists rejected spans", body = otlp::OtlpExportResponse),
+        (status = 400, description = "Invalid OTLP payload", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "Body too large", body = ErrorResponse),
+        (status = 415, description = "Protobuf or other unsupported encoding", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Monitoring"
 )]
-pub async fn get_events(
+pub async fn ingest_otlp_traces(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Query(params): Query<EventQueryParams>,
-) -> Result<Json<ApiResponse<Vec<Event>>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<otlp::OtlpExportResponse>, Error> {
+    let request: otlp::ExportTraceServiceRequest = decode_otlp(&headers, &body)?;
+    check_otlp_item_count(request.item_count())?;
+
+    let (events, mut rejected, mut reason) = accept_otlp_events(&auth_user, request.into_events())?;
+    let events =
+        limit_otlp_events(&app_state, &auth_user, events, &mut rejected, &mut reason).await?;
+
     let mut conn = app_state
         .database
         .pool
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
+    services::create_events_batch(conn.as_mut(), &events).await?;
 
-    // Parse tags parameter if provided
-    let tags = if let Some(tags_str) = &params.tags {
-        Some(parse_tags_query(tags_str)?)
-    } else {
-        None
-    };
-
-    // Users can view all events, but in production you might want to filter by source ownership
-    let filter = EventFilter {
-        event_type: params.event_type,
-        source: params.source,
-        level: params.level,
-        start_time: params.start_time,
-        end_time: params.end_time,
-        tags,
-        limit: params.limit,
-        offset: params.offset,
-    };
-
-    let events = services::find_events_with_filter(conn.as_mut(), filter).await?;
-    Ok(Json(ApiResponse::success(events)))
+    Ok(Json(otlp::OtlpExportResponse {
+        partial_success: reason.map(|error_message| otlp::OtlpPartialSuccess {
+            rejected_spans: Some(rejected),
+            rejected_data_points: None,
+            rejected_log_records: None,
+            error_message,
+        }),
+    }))
 }
 
-/// Get a specific event by ID
+/// Ingest OTLP log records as `log` events
 #[utoipa::path(
-    get,
-    path = "/monitoring/events/{id}",
-    params(
-        ("id" = Uuid, Path, description = "Event ID")
+    post,
+    path = "/monitoring/otlp/logs",
+    request_body(
+        content = serde_json::Value,
+        content_type = "application/json",
+        description = "OTLP/HTTP `ExportLogsServiceRequest` (JSON encoding, optionally gzip-compressed)"
     ),
     responses(
-        (status = 200, description = "Event retrieved successfully", body = ApiResponse<Event>),
-        (status = 404, description = "Event not found", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Log records ingested; `partialSuccess` lists rejected records", body = otlp::OtlpExportResponse),
+        (status = 400, description = "Invalid OTLP payload", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "Body too large", body = ErrorResponse),
+        (status = 415, description = "Protobuf or other unsupported encoding", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Monitoring"
 )]
-pub async fn get_event_by_id(
+pub async fn ingest_otlp_logs(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Path(id): Path<Uuid>,
-) -> Result<Json<ApiResponse<Event>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<otlp::OtlpExportResponse>, Error> {
+    let request: otlp::ExportLogsServiceRequest = decode_otlp(&headers, &body)?;
+    check_otlp_item_count(request.item_count())?;
+
+    let (events, mut rejected, mut reason) = accept_otlp_events(&auth_user, request.into_events())?;
+    let events =
+        limit_otlp_events(&app_state, &auth_user, events, &mut rejected, &mut reason).await?;
+
     let mut conn = app_state
         .database
         .pool
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
+    services::create_events_batch(conn.as_mut(), &events).await?;
 
-    let event = services::find_event_by_id(conn.as_mut(), id).await?;
-    let event = event.ok_or_else(|| Error::NotFound("Event not found".to_string()))?;
-    Ok(Json(ApiResponse::success(event)))
+    Ok(Json(otlp::OtlpExportResponse {
+        partial_success: reason.map(|error_message| otlp::OtlpPartialSuccess {
+            rejected_spans: None,
+            rejected_data_points: None,
+            rejected_log_records: Some(rejected),
+            error_message,
+        }),
+    }))
 }
 
-/// Create a new metric
+/// Ingest OTLP metric data points into the metrics table
 #[utoipa::path(
     post,
-    path = "/monitoring/metrics",
-    request_body = CreateMetricRequest,
+    path = "/monitoring/otlp/metrics",
+    request_body(
+        content = serde_json::Value,
+        content_type = "application/json",
+        description = "OTLP/HTTP `ExportMetricsServiceRequest` (JSON encoding, optionally gzip-compressed)"
+    ),
     responses(
-        (status = 200, description = "Metric created successfully", body = ApiResponse<Metric>),
-        (status = 400, description = "Invalid input", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Data points ingested; `partialSuccess` lists rejected points", body = otlp::OtlpExportResponse),
+        (status = 400, description = "Invalid OTLP payload", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "Body too large", body = ErrorResponse),
+        (status = 415, description = "Protobuf or other unsupported encoding", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Monitoring"
 )]
-pub async fn create_metric(
+pub async fn ingest_otlp_metrics(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-    Json(request): Json<CreateMetricRequest>,
-) -> Result<Json<ApiResponse<Metric>>, Error> {
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<otlp::OtlpExportResponse>, Error> {
+    let request: otlp::ExportMetricsServiceRequest = decode_otlp(&headers, &body)?;
+    check_otlp_item_count(request.item_count())?;
+
+    let is_moderator = auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator);
+
+    // A data point is stored whole or rejected whole, including its histogram rows
+    let mut points = Vec::new();
+    let mut rejected = 0;
+    let mut reason = None;
+    for rows in request.into_metrics() {
+        let mut outcome = Ok(());
+        for row in &rows {
+            outcome = if is_moderator || is_user_authorized_for_metric_name(&auth_user, &row.name)?
+            {
+                row.validate().map_err(|e| e.to_string())
+            } else {
+                Err(format!(
+                    "Metric '{}' is not authorized for user '{}'",
+                    row.name, auth_user.username
+                ))
+            };
+            if outcome.is_err() {
+                break;
+            }
+        }
+        match outcome {
+            Ok(()) => points.push(rows),
+            Err(e) => {
+                rejected += 1;
+                reason.get_or_insert(e);
+            }
+        }
+    }
+
     let mut conn = app_state
         .database
         .pool
@@ -442,87 +645,161 @@ pub async fn create_metric(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // Authorization: Users can create metrics with names they own, moderators+ can create any metrics
-    if !auth_user
-        .role
-        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
+    // Whole points are kept while the user's daily metric quota has room
+    if let Some((mut remaining, limit)) =
+        quotas::metric_quota_remaining(conn.as_mut(), auth_user.id).await?
     {
-        let is_authorized_metric = is_user_authorized_for_metric_name(&auth_user, &request.name)?;
+        points.retain(|point| {
+            if remaining <= 0 {
+                rejected += 1;
+                reason.get_or_insert_with(|| {
+                    quotas::quota_exceeded_message(
+                        "metric",
+                        QuotaScope::User,
+                        &auth_user.id.to_string(),
+                        limit,
+                    )
+                });
+                return false;
+            }
+            remaining -= point.len() as i64;
+            true
+        });
+    }
 
-        if !is_authorized_metric {
-            return Err(Error::Forbidden(format!(
-                "Users can only create metrics with names they own. Metric '{}' is not authorized for user '{}'",
-                request.name, auth_user.username
-            )));
+    let config = &app_state.config.monitoring;
+    let mut rows: Vec<CreateMetricRequest> = points.iter().flatten().cloned().collect();
+    let mut fits = cardinality::limit_series(conn.as_mut(), config, &mut rows)
+        .await?
+        .into_iter();
+    let mut rows = rows.into_iter();
+    let mut metrics = Vec::with_capacity(rows.len());
+    for point in &points {
+        let point_rows: Vec<_> = rows.by_ref().take(point.len()).collect();
+        let point_fits: Vec<_> = fits.by_ref().take(point.len()).collect();
+        match point_rows.iter().zip(point_fits).find(|(_, fits)| !fits) {
+            None => metrics.extend(point_rows),
+            Some((row, _)) => {
+                rejected += 1;
+                reason.get_or_insert_with(|| {
+                    Error::CardinalityLimitExceeded {
+                        metric: row.name.clone(),
+                        limit: config.metric_max_series_per_name,
+                    }
+                    .to_string()
+                });
+            }
         }
     }
+    for metric in &mut metrics {
+        metric.tenant_id = Some(auth_user.tenant_id);
+    }
+    services::create_metrics_batch(conn.as_mut(), &metrics).await?;
+    quotas::record_metric_usage(
+        conn.as_mut(),
+        auth_user.id,
+        auth_user.tenant_id,
+        metrics.len(),
+    )
+    .await?;
 
-    let metric = services::create_metric(conn.as_mut(), request).await?;
-    Ok(Json(ApiResponse::success(metric)))
+    Ok(Json(otlp::OtlpExportResponse {
+        partial_success: reason.map(|error_message| otlp::OtlpPartialSuccess {
+            rejected_spans: None,
+            rejected_data_points: Some(rejected),
+            rejected_log_records: None,
+            error_message,
+        }),
+    }))
 }
 
-/// Get metrics with filters
-#[utoipa::path(
-    get,
-    path = "/monitoring/metrics",
-    params(MetricQueryParams),
-    responses(
-        (status = 200, description = "Metrics retrieved successfully", body = ApiResponse<Vec<Metric>>),
-        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
-    ),
-    security(
-        ("bearer_auth" = [])
-    ),
-    tag = "Monitoring"
-)]
-pub async fn get_metrics(
-    State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Query(params): Query<MetricQueryParams>,
-) -> Result<Json<ApiResponse<Vec<Metric>>>, Error> {
-    let mut conn = app_state
-        .database
-        .pool
-        .acquire()
-        .await
-        .map_err(Error::from_sqlx)?;
+/// Query parameters for a metric series
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct MetricSeriesQueryParams {
+    pub name: String,
+    /// Defaults to one hour before `end_time`
+    #[param(format = "date-time")]
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Defaults to now
+    #[param(format = "date-time")]
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// `auto` (default), `raw` or `rollup`
+    pub resolution: Option<MetricResolution>,
+    /// Maximum points returned (default 1000, max 10000)
+    pub limit: Option<i64>,
+}
 
-    let filter = MetricFilter {
-        name: params.name,
-        metric_type: params.metric_type,
-        start_time: params.start_time,
-        end_time: params.end_time,
-        labels: None, // Future enhancement: Add label filtering from query params
-        limit: params.limit,
-        offset: params.offset,
-    };
+/// Query parameters for an aggregated metric query
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct MetricAggregateParams {
+    pub name: String,
+    /// Comma-separated label matchers: `key=value`, `key!=value`, `key=~regex`, `key!~regex`
+    pub labels: Option<String>,
+    /// Comma-separated label names; one series is returned per combination of their values
+    pub by: Option<String>,
+    /// Defaults to one hour before `end_time`
+    #[param(format = "date-time")]
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Defaults to now
+    #[param(format = "date-time")]
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Step width in seconds (defaults to about 250 steps over the range)
+    pub step: Option<i64>,
+    /// `avg` (default), `sum`, `min`, `max`, `count` or `p1`-`p99`
+    #[param(value_type = Option<String>)]
+    pub aggregation: Option<MetricAggregation>,
+    /// `auto` (default), `raw` or `rollup`
+    pub resolution: Option<MetricResolution>,
+}
 
-    let metrics = services::find_metrics_with_filter(conn.as_mut(), filter).await?;
-    Ok(Json(ApiResponse::success(metrics)))
+/// Query parameters for incident listing
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct IncidentQueryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Only incidents that need a postmortem and have none (or, if false, all others)
+    pub postmortem_pending: Option<bool>,
 }
 
-/// Create a new alert (requires moderator or higher)
+/// Query parameters for timeline
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TimelineQueryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub lookback_hours: Option<i64>,
+    /// Only events correlated into the incident
+    pub correlated: Option<bool>,
+}
+
+/// Query parameters for the current on-call lookup
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OncallQueryParams {
+    /// Only this schedule; all schedules when omitted
+    pub schedule_id: Option<Uuid>,
+}
+
+/// Create a new event
 #[utoipa::path(
     post,
-    path = "/monitoring/alerts",
-    request_body = CreateAlertRequest,
+    path = "/monitoring/events",
+    request_body = CreateEventRequest,
     responses(
-        (status = 200, description = "Alert created successfully", body = ApiResponse<Alert>),
+        (status = 200, description = "Event created successfully", body = ApiResponse<Event>),
+        (status = 202, description = "Event sampled out by the source's sampling ratio and not stored", body = ApiResponse<Event>),
         (status = 400, description = "Invalid input", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+        (status = 429, description = "Source or user over the event rate limit", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Monitoring"
 )]
-pub async fn create_alert(
+pub async fn create_event(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-    Json(request): Json<CreateAlertRequest>,
-) -> Result<Json<ApiResponse<Alert>>, Error> {
+    Json(request): Json<CreateEventRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Event>>), Error> {
     let mut conn = app_state
         .database
         .pool
@@ -530,18 +807,238 @@ pub async fn create_alert(
         .await
         .map_err(Error::from_sqlx)?;
 
-    rbac_services::require_moderator_or_higher(&auth_user)?;
-
-    let alert = services::create_alert(conn.as_mut(), request, Some(auth_user.id)).await?;
-    Ok(Json(ApiResponse::success(alert)))
+    // Authorization: Users can create events for sources they own, moderators+ can create any events
+    if !auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
+    {
+        // Basic users can only create events for sources they own or generic sources
+        // Source ownership is determined by source prefix matching username or user ID
+        let is_authorized_source = is_user_authorized_for_source(&auth_user, &request.source)?;
+
+        if !is_authorized_source {
+            return Err(Error::Forbidden(format!(
+                "Users can only create events for their own sources. Source '{}' is not authorized for user '{}'",
+                request.source, auth_user.username
+            )));
+        }
+    }
+
+    let mut request = request;
+    request.tenant_id = Some(auth_user.tenant_id);
+    request.org_id = auth_user.org_scope(request.org_id)?;
+    if let Some(org_id) = request.org_id {
+        org_services::require_org_role(conn.as_mut(), &auth_user, org_id, OrgRole::Member).await?;
+    }
+
+    let admissions = limits::admit_events(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        auth_user.id,
+        auth_user.tenant_id,
+        auth_user.role,
+        &[request.source.as_str()],
+    )
+    .await?;
+    match admissions[0] {
+        EventAdmission::Accepted => {}
+        EventAdmission::Sampled => {
+            return Ok((
+                StatusCode::ACCEPTED,
+                Json(ApiResponse {
+                    success: true,
+                    data: None,
+                    message: Some(format!(
+                        "Event sampled: source '{}' stores only a share of its events",
+                        request.source
+                    )),
+                }),
+            ));
+        }
+        EventAdmission::RateLimited { retry_after_secs } => {
+            return Err(Error::RateLimited { retry_after_secs });
+        }
+        EventAdmission::QuotaExceeded { scope, limit } => {
+            return Err(Error::QuotaExceeded(quotas::quota_exceeded_message(
+                "event",
+                scope,
+                &request.source,
+                limit,
+            )));
+        }
+    }
+
+    let event = services::create_event(conn.as_mut(), request).await?;
+    Ok((StatusCode::OK, Json(ApiResponse::success(event))))
 }
 
-/// Get all alerts
+/// Split an event batch into items, decoding each on its own
+///
+/// NDJSON bodies hold one event per line (blank lines are skipped); JSON
+/// bodies hold an array of events.
+fn parse_event_batch(
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<Vec<Result<CreateEventRequest, String>>, Error> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let decode = |value: serde_json::Value| {
+        serde_json::from_value::<CreateEventRequest>(value)
+            .map_err(|e| format!("Invalid event: {e}"))
+    };
+    match content_type.as_str() {
+        "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Ok(body
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .map(|line| {
+                serde_json::from_slice(line)
+                    .map_err(|e| format!("Invalid JSON: {e}"))
+                    .and_then(decode)
+            })
+            .collect()),
+        "application/json" => {
+            let items: Vec<serde_json::Value> = serde_json::from_slice(body).map_err(|e| {
+                Error::InvalidInput(format!("Expected a JSON array of events: {e}"))
+            })?;
+            Ok(items.into_iter().map(decode).collect())
+        }
+        _ => Err(Error::UnsupportedMediaType(format!(
+            "Event batches accept application/x-ndjson or application/json (got '{content_type}')"
+        ))),
+    }
+}
+
+/// Create events in bulk from NDJSON or a JSON array
+///
+/// Valid items are stored in one transaction; invalid, unauthorized or
+/// rate-limited items are reported by index without failing the rest of the
+/// batch, and items dropped by sampling are only counted.
+#[utoipa::path(
+    post,
+    path = "/monitoring/events/batch",
+    request_body(
+        content = Vec<CreateEventRequest>,
+        description = "JSON array of events, or one event per line with `Content-Type: application/x-ndjson` (optionally gzip-compressed)"
+    ),
+    responses(
+        (status = 200, description = "Batch processed; `errors` lists rejected items, including rate-limited ones", body = ApiResponse<EventBatchResult>),
+        (status = 400, description = "Too many items or not a JSON array", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "Body too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported content type", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_event_batch(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ApiResponse<EventBatchResult>>, Error> {
+    let max_items = app_state.config.monitoring.event_batch_max_items;
+    let max_bytes = app_state.config.monitoring.event_batch_max_bytes;
+
+    let body = body_limit::to_bytes(body, max_bytes).await?;
+    let items = parse_event_batch(&headers, &body)?;
+    if items.len() > max_items {
+        return Err(Error::validation(
+            "body",
+            &format!("Too many events in one batch (max {max_items})"),
+        ));
+    }
+
+    let is_moderator = auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator);
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let org_ids = org_services::user_org_ids(conn.as_mut(), auth_user.id).await?;
+
+    let mut events = Vec::with_capacity(items.len());
+    let mut errors = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let outcome = match item {
+            Ok(event) => check_ingested_event(&auth_user, is_moderator, &event)?
+                .and_then(|()| check_event_org(&auth_user, &org_ids, &event))
+                .map(|()| event),
+            Err(message) => Err(message),
+        };
+        match outcome {
+            Ok(mut event) => {
+                event.tenant_id = Some(auth_user.tenant_id);
+                events.push((index, event));
+            }
+            Err(message) => errors.push(EventBatchError { index, message }),
+        }
+    }
+
+    let sources: Vec<&str> = events
+        .iter()
+        .map(|(_, event)| event.source.as_str())
+        .collect();
+    let admissions = limits::admit_events(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        auth_user.id,
+        auth_user.tenant_id,
+        auth_user.role,
+        &sources,
+    )
+    .await?;
+
+    let mut stored = Vec::with_capacity(events.len());
+    let mut sampled = 0;
+    for ((index, event), admission) in events.into_iter().zip(admissions) {
+        match admission {
+            EventAdmission::Accepted => stored.push(event),
+            EventAdmission::Sampled => sampled += 1,
+            EventAdmission::RateLimited { retry_after_secs } => errors.push(EventBatchError {
+                index,
+                message: rate_limited_message(&event.source, retry_after_secs),
+            }),
+            EventAdmission::QuotaExceeded { scope, limit } => errors.push(EventBatchError {
+                index,
+                message: quotas::quota_exceeded_message("event", scope, &event.source, limit),
+            }),
+        }
+    }
+    errors.sort_by_key(|error| error.index);
+    let accepted = services::create_events_batch(conn.as_mut(), &stored).await?;
+
+    Ok(Json(ApiResponse::success(EventBatchResult {
+        accepted,
+        rejected: errors.len() as u64,
+        sampled,
+        errors,
+    })))
+}
+
+/// Stream newly stored events as Server-Sent Events
+///
+/// Each matching event is sent as an `event` message with the event as JSON.
+/// A client too slow to keep up gets a `lagged` message with the number of
+/// events it missed.
 #[utoipa::path(
     get,
-    path = "/monitoring/alerts",
+    path = "/monitoring/events/stream",
+    params(EventStreamParams),
     responses(
-        (status = 200, description = "Alerts retrieved successfully", body = ApiResponse<Vec<Alert>>),
+        (status = 200, description = "Stream of events as they are stored", content_type = "text/event-stream", body = Event),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     ),
     security(
@@ -549,10 +1046,74 @@ pub async fn create_alert(
     ),
     tag = "Monitoring"
 )]
-pub async fn get_alerts(
+pub async fn stream_events(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<Vec<Alert>>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<EventStreamParams>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, Error> {
+    // Memberships are read once; changes apply to streams opened afterwards
+    let visible_orgs = match events_visible_to(&auth_user) {
+        Some(user_id) => {
+            let mut conn = app_state
+                .database
+                .pool
+                .acquire()
+                .await
+                .map_err(Error::from_sqlx)?;
+            let org_ids = org_services::user_org_ids(conn.as_mut(), user_id).await?;
+            Some(org_ids.into_iter().collect())
+        }
+        None => None,
+    };
+    let filter = EventStreamFilter {
+        tenant_id: auth_user.tenant_id,
+        event_type: params.event_type,
+        source: params.source,
+        level: params.level,
+        tags: match &params.tags {
+            Some(tags_str) => parse_tags_query(tags_str)?,
+            None => HashMap::new(),
+        },
+        org_id: auth_user.org_scope(params.org_id)?,
+        visible_orgs,
+    };
+    let receiver = app_state
+        .event_stream
+        .subscribe(&app_state.database.pool)
+        .await?;
+
+    Ok(sse::stream(receiver, move |streamed| {
+        if !filter.matches(streamed) {
+            return None;
+        }
+        SseEvent::default()
+            .event("event")
+            .id(streamed.event.id.to_string())
+            .json_data(&streamed.event)
+            .ok()
+    }))
+}
+
+/// Get events with filters
+#[utoipa::path(
+    get,
+    path = "/monitoring/events",
+    params(EventQueryParams),
+    responses(
+        (status = 200, description = "Events retrieved successfully", body = ApiResponse<Vec<Event>>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_events(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<EventQueryParams>,
+) -> Result<Json<ApiResponse<Vec<Event>>>, Error> {
     let mut conn = app_state
         .database
         .pool
@@ -560,30 +1121,2775 @@ pub async fn get_alerts(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let alerts = services::find_all_alerts(conn.as_mut()).await?;
-    Ok(Json(ApiResponse::success(alerts)))
+    // Users can view unscoped events and those of their organizations
+    let filter = event_filter(params, &auth_user)?;
+
+    let events = services::find_events_with_filter(conn.as_mut(), filter).await?;
+    Ok(Json(ApiResponse::success(events)))
 }
 
-/// Create a new incident
+/// Moderators and admins see every organization's events, users only their own organizations'
+fn events_visible_to(auth_user: &AuthUser) -> Option<Uuid> {
+    match rbac_services::has_role_or_higher(auth_user, crate::rbac::UserRole::Moderator) {
+        true => None,
+        false => Some(auth_user.id),
+    }
+}
+
+/// Build the event filter shared by the list and export endpoints
+fn event_filter(params: EventQueryParams, auth_user: &AuthUser) -> Result<EventFilter, Error> {
+    // Parse tags parameter if provided
+    let tags = if let Some(tags_str) = &params.tags {
+        Some(parse_tags_query(tags_str)?)
+    } else {
+        None
+    };
+
+    Ok(EventFilter {
+        event_type: params.event_type,
+        source: params.source,
+        level: params.level,
+        start_time: params.start_time,
+        end_time: params.end_time,
+        tags,
+        org_id: auth_user.org_scope(params.org_id)?,
+        visible_to: events_visible_to(auth_user),
+        tenant_id: Some(auth_user.tenant_id),
+        limit: params.limit,
+        offset: params.offset,
+    })
+}
+
+/// Export events as CSV, NDJSON or Parquet
+#[utoipa::path(
+    get,
+    path = "/monitoring/events/export",
+    summary = "Export events",
+    description = "Stream every event matching the list filters, oldest first. Unlike the list endpoint, results are unlimited unless `limit` is set. Parquet is only available when the server is built with the `parquet` feature.",
+    params(EventQueryParams, MonitoringExportParams),
+    responses(
+        (status = 200, description = "Event export", content(
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+            (Vec<u8> = "application/vnd.apache.parquet")
+        )),
+        (status = 400, description = "Invalid export format or query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn export_events(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<EventQueryParams>,
+    Query(export_params): Query<MonitoringExportParams>,
+) -> Result<Response, Error> {
+    let format = export_params.format()?;
+    let filter = event_filter(params, &auth_user)?;
+    let body = export::export_events(app_state.database.pool.clone(), filter, format);
+    Ok(export_response("events", format, body))
+}
+
+/// Get a specific event by ID
+#[utoipa::path(
+    get,
+    path = "/monitoring/events/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Event ID")
+    ),
+    responses(
+        (status = 200, description = "Event retrieved successfully", body = ApiResponse<Event>),
+        (status = 404, description = "Event not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_event_by_id(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Event>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let event = services::find_event_by_id(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let event = event.ok_or_else(|| Error::NotFound("Event not found".to_string()))?;
+    if let (Some(user_id), Some(org_id)) = (events_visible_to(&auth_user), event.org_id)
+        && org_services::member_role(conn.as_mut(), org_id, user_id)
+            .await?
+            .is_none()
+    {
+        return Err(Error::NotFound("Event not found".to_string()));
+    }
+    Ok(Json(ApiResponse::success(event)))
+}
+
+/// Create a new metric
 #[utoipa::path(
     post,
-    path = "/monitoring/incidents",
-    request_body = CreateIncidentRequest,
+    path = "/monitoring/metrics",
+    request_body = CreateMetricRequest,
+    responses(
+        (status = 200, description = "Metric created successfully", body = ApiResponse<Metric>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_metric(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateMetricRequest>,
+) -> Result<Json<ApiResponse<Metric>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    require_metric_name_access(&auth_user, &request.name)?;
+    quotas::check_metric_quota(conn.as_mut(), auth_user.id).await?;
+
+    let mut request = request;
+    request.tenant_id = Some(auth_user.tenant_id);
+    let metric =
+        services::create_metric(conn.as_mut(), request, &app_state.config.monitoring).await?;
+    quotas::record_metric_usage(conn.as_mut(), auth_user.id, auth_user.tenant_id, 1).await?;
+    Ok(Json(ApiResponse::success(metric)))
+}
+
+/// Users can create metrics with names they own, moderators+ can create any metrics
+fn require_metric_name_access(auth_user: &AuthUser, name: &str) -> Result<(), Error> {
+    if !auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
+        && !is_user_authorized_for_metric_name(auth_user, name)?
+    {
+        return Err(Error::Forbidden(format!(
+            "Users can only create metrics with names they own. Metric '{}' is not authorized for user '{}'",
+            name, auth_user.username
+        )));
+    }
+    Ok(())
+}
+
+/// Submit a histogram observation
+///
+/// Stores `<name>_bucket` rows with cumulative counts labelled `le`, plus
+/// `<name>_sum` and `<name>_count`. Bucket counts are the observations since
+/// the previous submission, so they add up across a query step.
+#[utoipa::path(
+    post,
+    path = "/monitoring/metrics/histogram",
+    request_body = CreateHistogramRequest,
+    responses(
+        (status = 200, description = "Histogram stored successfully", body = ApiResponse<MetricRowsStored>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Metric name not owned by the user", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_histogram(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateHistogramRequest>,
+) -> Result<Json<ApiResponse<MetricRowsStored>>, Error> {
+    require_metric_name_access(&auth_user, &request.name)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    quotas::check_metric_quota(conn.as_mut(), auth_user.id).await?;
+
+    let stored = services::create_histogram(
+        conn.as_mut(),
+        request,
+        auth_user.tenant_id,
+        &app_state.config.monitoring,
+    )
+    .await?;
+    quotas::record_metric_usage(
+        conn.as_mut(),
+        auth_user.id,
+        auth_user.tenant_id,
+        stored.stored as usize,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(stored)))
+}
+
+/// Submit a summary observation
+///
+/// Stores `<name>` rows labelled `quantile`, plus `<name>_sum` and `<name>_count`.
+#[utoipa::path(
+    post,
+    path = "/monitoring/metrics/summary",
+    request_body = CreateSummaryRequest,
+    responses(
+        (status = 200, description = "Summary stored successfully", body = ApiResponse<MetricRowsStored>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Metric name not owned by the user", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_summary(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateSummaryRequest>,
+) -> Result<Json<ApiResponse<MetricRowsStored>>, Error> {
+    require_metric_name_access(&auth_user, &request.name)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    quotas::check_metric_quota(conn.as_mut(), auth_user.id).await?;
+
+    let stored = services::create_summary(
+        conn.as_mut(),
+        request,
+        auth_user.tenant_id,
+        &app_state.config.monitoring,
+    )
+    .await?;
+    quotas::record_metric_usage(
+        conn.as_mut(),
+        auth_user.id,
+        auth_user.tenant_id,
+        stored.stored as usize,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(stored)))
+}
+
+/// Get metrics with filters
+#[utoipa::path(
+    get,
+    path = "/monitoring/metrics",
+    params(MetricQueryParams),
+    responses(
+        (status = 200, description = "Metrics retrieved successfully", body = ApiResponse<Vec<Metric>>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_metrics(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<MetricQueryParams>,
+) -> Result<Json<ApiResponse<Vec<Metric>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let filter = MetricFilter {
+        name: params.name,
+        metric_type: params.metric_type,
+        start_time: params.start_time,
+        end_time: params.end_time,
+        labels: None, // Future enhancement: Add label filtering from query params
+        tenant_id: Some(auth_user.tenant_id),
+        limit: params.limit,
+        offset: params.offset,
+    };
+
+    let metrics = services::find_metrics_with_filter(conn.as_mut(), filter).await?;
+    Ok(Json(ApiResponse::success(metrics)))
+}
+
+/// Export metric points as CSV, NDJSON or Parquet
+#[utoipa::path(
+    get,
+    path = "/monitoring/metrics/export",
+    summary = "Export metrics",
+    description = "Stream every metric point matching the list filters, oldest first. Unlike the list endpoint, results are unlimited unless `limit` is set. Parquet is only available when the server is built with the `parquet` feature.",
+    params(MetricQueryParams, MonitoringExportParams),
+    responses(
+        (status = 200, description = "Metric export", content(
+            (String = "text/csv"),
+            (String = "application/x-ndjson"),
+            (Vec<u8> = "application/vnd.apache.parquet")
+        )),
+        (status = 400, description = "Invalid export format or query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn export_metrics(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<MetricQueryParams>,
+    Query(export_params): Query<MonitoringExportParams>,
+) -> Result<Response, Error> {
+    let format = export_params.format()?;
+    let filter = MetricFilter {
+        name: params.name,
+        metric_type: params.metric_type,
+        start_time: params.start_time,
+        end_time: params.end_time,
+        labels: None,
+        tenant_id: Some(auth_user.tenant_id),
+        limit: params.limit,
+        offset: params.offset,
+    };
+    let body = export::export_metrics(app_state.database.pool.clone(), filter, format);
+    Ok(export_response("metrics", format, body))
+}
+
+/// Attach download headers to an export body
+fn export_response(kind: &str, format: ExportFormat, body: Body) -> Response {
+    let filename = format!(
+        "{kind}-{}.{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+        format.as_str()
+    );
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Get a metric's time series at raw or rollup resolution
+#[utoipa::path(
+    get,
+    path = "/monitoring/metrics/series",
+    params(MetricSeriesQueryParams),
+    responses(
+        (status = 200, description = "Metric series retrieved successfully", body = ApiResponse<MetricSeries>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_metric_series(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<MetricSeriesQueryParams>,
+) -> Result<Json<ApiResponse<MetricSeries>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let end_time = params.end_time.unwrap_or_else(chrono::Utc::now);
+    let start_time = params
+        .start_time
+        .unwrap_or(end_time - chrono::Duration::hours(1));
+
+    let series = retention::query_metric_series(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        &retention::MetricSeriesQuery {
+            tenant_id: auth_user.tenant_id,
+            name: &params.name,
+            start_time,
+            end_time,
+            resolution: params.resolution.unwrap_or_default(),
+            limit: params.limit,
+        },
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(series)))
+}
+
+/// Query a metric aggregated per step, grouped into series by label
+#[utoipa::path(
+    get,
+    path = "/monitoring/metrics/query",
+    params(MetricAggregateParams),
+    responses(
+        (status = 200, description = "Metric query executed successfully", body = ApiResponse<MetricQueryResult>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn query_metrics(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<MetricAggregateParams>,
+) -> Result<Json<ApiResponse<MetricQueryResult>>, Error> {
+    let end_time = params.end_time.unwrap_or_else(chrono::Utc::now);
+    let metric_query = query::MetricQuery {
+        tenant_id: auth_user.tenant_id,
+        name: params.name,
+        matchers: query::LabelMatcher::parse_list(params.labels.as_deref().unwrap_or_default())?,
+        group_by: query::parse_group_by(params.by.as_deref().unwrap_or_default())?,
+        start_time: params
+            .start_time
+            .unwrap_or(end_time - chrono::Duration::hours(1)),
+        end_time,
+        step_secs: params.step,
+        aggregation: params.aggregation.unwrap_or(MetricAggregation::Avg),
+        resolution: params.resolution.unwrap_or_default(),
+    };
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let result =
+        query::query_metrics(conn.as_mut(), &app_state.config.monitoring, &metric_query).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Grafana JSON datasource connection test
+///
+/// The Grafana endpoints return bare JSON, without the `ApiResponse` envelope,
+/// as the datasource plugin expects.
+#[utoipa::path(
+    get,
+    path = "/monitoring/grafana",
+    responses(
+        (status = 200, description = "Datasource is reachable"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn grafana_health(Extension(_auth_user): Extension<AuthUser>) -> StatusCode {
+    StatusCode::OK
+}
+
+/// List metric names for the Grafana query editor
+#[utoipa::path(
+    post,
+    path = "/monitoring/grafana/metrics",
+    request_body = grafana::GrafanaMetricsRequest,
+    responses(
+        (status = 200, description = "Metric names listed successfully", body = Vec<grafana::GrafanaMetricOption>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn grafana_metrics(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<grafana::GrafanaMetricsRequest>,
+) -> Result<Json<Vec<grafana::GrafanaMetricOption>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let metrics = grafana::list_metrics(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request.metric.as_deref(),
+    )
+    .await?;
+    Ok(Json(metrics))
+}
+
+/// Query metrics for Grafana panels
+#[utoipa::path(
+    post,
+    path = "/monitoring/grafana/query",
+    request_body = grafana::GrafanaQueryRequest,
+    responses(
+        (status = 200, description = "Metric query executed successfully", body = Vec<grafana::GrafanaTimeSeries>),
+        (status = 400, description = "Invalid query", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn grafana_query(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<grafana::GrafanaQueryRequest>,
+) -> Result<Json<Vec<grafana::GrafanaTimeSeries>>, Error> {
+    let queries = request.metric_queries(auth_user.tenant_id)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let mut series = Vec::new();
+    for (ref_id, metric_query) in queries {
+        let result =
+            query::query_metrics(conn.as_mut(), &app_state.config.monitoring, &metric_query)
+                .await?;
+        series.extend(grafana::time_series(&ref_id, result));
+    }
+    Ok(Json(series))
+}
+
+/// List label names for Grafana ad hoc filters
+#[utoipa::path(
+    post,
+    path = "/monitoring/grafana/tag-keys",
+    responses(
+        (status = 200, description = "Label names listed successfully", body = Vec<grafana::GrafanaTagKey>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn grafana_tag_keys(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<Vec<grafana::GrafanaTagKey>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let keys = grafana::list_tag_keys(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(keys))
+}
+
+/// List the values of a label for Grafana ad hoc filters
+#[utoipa::path(
+    post,
+    path = "/monitoring/grafana/tag-values",
+    request_body = grafana::GrafanaTagValuesRequest,
+    responses(
+        (status = 200, description = "Label values listed successfully", body = Vec<grafana::GrafanaTagValue>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn grafana_tag_values(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<grafana::GrafanaTagValuesRequest>,
+) -> Result<Json<Vec<grafana::GrafanaTagValue>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let values = grafana::list_tag_values(conn.as_mut(), auth_user.tenant_id, &request.key).await?;
+    Ok(Json(values))
+}
+
+/// Create a new alert (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/alerts",
+    request_body = CreateAlertRequest,
+    responses(
+        (status = 200, description = "Alert created successfully", body = ApiResponse<Alert>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_alert(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+    Json(request): Json<CreateAlertRequest>,
+) -> Result<Json<ApiResponse<Alert>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let alert = services::create_alert(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        "alert_created",
+        &AuditResource::new(AuditResourceType::Alert, alert.id),
+        None,
+        serde_json::to_value(&alert).ok(),
+    )
+    .await;
+    Ok(Json(ApiResponse::success(alert)))
+}
+
+/// Get all alerts
+#[utoipa::path(
+    get,
+    path = "/monitoring/alerts",
+    responses(
+        (status = 200, description = "Alerts retrieved successfully", body = ApiResponse<Vec<Alert>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_alerts(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<Alert>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let alerts = services::find_all_alerts(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(alerts)))
+}
+
+/// Send a test notification for an alert (requires moderator or higher)
+///
+/// Each of the alert's channels receives a firing notification marked as a
+/// test; the alert's status is not changed.
+#[utoipa::path(
+    post,
+    path = "/monitoring/alerts/{id}/test",
+    params(
+        ("id" = Uuid, Path, description = "Alert ID")
+    ),
+    responses(
+        (status = 200, description = "Test notification sent; see each delivery for failures", body = ApiResponse<AlertTestResult>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Alert not found", body = ErrorResponse),
+        (status = 409, description = "Alert has no notification channels and matches no routing rule", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn test_alert(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<AlertTestResult>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    services::ensure_alert(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let result = notifications::test_fire_alert(conn.as_mut(), &app_state.services, id).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// Get the channels an alert notifies (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/alerts/{id}/channels",
+    params(
+        ("id" = Uuid, Path, description = "Alert ID")
+    ),
+    responses(
+        (status = 200, description = "Alert channels retrieved successfully", body = ApiResponse<Vec<NotificationChannel>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_alert_channels(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<NotificationChannel>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    services::ensure_alert(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let channels = notifications::find_alert_channels(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success(channels)))
+}
+
+/// Set the channels an alert notifies (requires moderator or higher)
+#[utoipa::path(
+    put,
+    path = "/monitoring/alerts/{id}/channels",
+    params(
+        ("id" = Uuid, Path, description = "Alert ID")
+    ),
+    request_body = SetAlertChannelsRequest,
+    responses(
+        (status = 200, description = "Alert channels updated successfully", body = ApiResponse<Vec<NotificationChannel>>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Alert not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn set_alert_channels(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SetAlertChannelsRequest>,
+) -> Result<Json<ApiResponse<Vec<NotificationChannel>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    services::ensure_alert(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let channels =
+        notifications::set_alert_channels(conn.as_mut(), id, auth_user.tenant_id, request).await?;
+    Ok(Json(ApiResponse::success(channels)))
+}
+
+/// Create a notification channel (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/notification-channels",
+    request_body = CreateNotificationChannelRequest,
+    responses(
+        (status = 200, description = "Notification channel created successfully", body = ApiResponse<NotificationChannel>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "A channel with this name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_notification_channel(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateNotificationChannelRequest>,
+) -> Result<Json<ApiResponse<NotificationChannel>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let channel = notifications::create_channel(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(channel)))
+}
+
+/// Get all notification channels (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/notification-channels",
+    responses(
+        (status = 200, description = "Notification channels retrieved successfully", body = ApiResponse<Vec<NotificationChannel>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_notification_channels(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<NotificationChannel>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let channels = notifications::find_channels(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(channels)))
+}
+
+/// Delete a notification channel (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/notification-channels/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Notification channel ID")
+    ),
+    responses(
+        (status = 200, description = "Notification channel deleted; alerts stop notifying it", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Notification channel not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_notification_channel(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    notifications::delete_channel(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Notification channel deleted".to_string(),
+    )))
+}
+
+/// Create a recording rule (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/recording-rules",
+    request_body = CreateRecordingRuleRequest,
+    responses(
+        (status = 200, description = "Recording rule created successfully", body = ApiResponse<RecordingRule>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "A rule already stores this metric name", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_recording_rule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateRecordingRuleRequest>,
+) -> Result<Json<ApiResponse<RecordingRule>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let rule = recording::create_recording_rule(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(rule)))
+}
+
+/// Get all recording rules
+#[utoipa::path(
+    get,
+    path = "/monitoring/recording-rules",
+    responses(
+        (status = 200, description = "Recording rules retrieved successfully", body = ApiResponse<Vec<RecordingRule>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_recording_rules(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<RecordingRule>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let rules = recording::find_recording_rules(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(rules)))
+}
+
+/// Delete a recording rule (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/recording-rules/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Recording rule ID")
+    ),
+    responses(
+        (status = 200, description = "Recording rule deleted; metrics it stored are kept", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Recording rule not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_recording_rule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    recording::delete_recording_rule(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Recording rule deleted".to_string(),
+    )))
+}
+
+/// Create an uptime check and its alert (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/uptime-checks",
+    request_body = CreateUptimeCheckRequest,
+    responses(
+        (status = 200, description = "Uptime check created successfully", body = ApiResponse<UptimeCheck>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "Uptime check name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_uptime_check(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateUptimeCheckRequest>,
+) -> Result<Json<ApiResponse<UptimeCheck>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let check = uptime::create_uptime_check(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(check)))
+}
+
+/// Get all uptime checks with their last result (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/uptime-checks",
+    responses(
+        (status = 200, description = "Uptime checks retrieved successfully", body = ApiResponse<Vec<UptimeCheck>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_uptime_checks(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<UptimeCheck>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let checks = uptime::find_uptime_checks(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(checks)))
+}
+
+/// Delete an uptime check and its alert (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/uptime-checks/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Uptime check ID")
+    ),
+    responses(
+        (status = 200, description = "Uptime check deleted; metrics it recorded are kept", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Uptime check not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_uptime_check(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    uptime::delete_uptime_check(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Uptime check deleted".to_string(),
+    )))
+}
+
+/// Create an alert routing rule (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/alert-routing-rules",
+    request_body = CreateAlertRoutingRuleRequest,
+    responses(
+        (status = 200, description = "Routing rule created successfully", body = ApiResponse<AlertRoutingRule>),
+        (status = 400, description = "Invalid input or unknown channel or policy", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "Routing rule name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_alert_routing_rule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateAlertRoutingRuleRequest>,
+) -> Result<Json<ApiResponse<AlertRoutingRule>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let rule = routing::create_routing_rule(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(rule)))
+}
+
+/// Get all alert routing rules (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/alert-routing-rules",
+    responses(
+        (status = 200, description = "Routing rules retrieved successfully", body = ApiResponse<Vec<AlertRoutingRule>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_alert_routing_rules(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<AlertRoutingRule>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let rules = routing::find_routing_rules(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(rules)))
+}
+
+/// Delete an alert routing rule (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/alert-routing-rules/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Routing rule ID")
+    ),
+    responses(
+        (status = 200, description = "Routing rule deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Routing rule not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_alert_routing_rule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    routing::delete_routing_rule(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Routing rule deleted".to_string(),
+    )))
+}
+
+/// Create a status page (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/status-pages",
+    request_body = CreateStatusPageRequest,
+    responses(
+        (status = 200, description = "Status page created successfully", body = ApiResponse<StatusPage>),
+        (status = 400, description = "Invalid input or unknown check or incident", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "Status page slug already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_status_page(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateStatusPageRequest>,
+) -> Result<Json<ApiResponse<StatusPage>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let page = status_page::create_status_page(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(page)))
+}
+
+/// Get all status pages (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/status-pages",
+    responses(
+        (status = 200, description = "Status pages retrieved successfully", body = ApiResponse<Vec<StatusPage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_status_pages(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<StatusPage>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let pages = status_page::find_status_pages(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(pages)))
+}
+
+/// Update a status page (requires moderator or higher)
+#[utoipa::path(
+    put,
+    path = "/monitoring/status-pages/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Status page ID")
+    ),
+    request_body = UpdateStatusPageRequest,
+    responses(
+        (status = 200, description = "Status page updated successfully", body = ApiResponse<StatusPage>),
+        (status = 400, description = "Invalid input or unknown check or incident", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Status page not found", body = ErrorResponse),
+        (status = 409, description = "Status page slug already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn update_status_page(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStatusPageRequest>,
+) -> Result<Json<ApiResponse<StatusPage>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let page =
+        status_page::update_status_page(conn.as_mut(), id, auth_user.tenant_id, request).await?;
+    Ok(Json(ApiResponse::success(page)))
+}
+
+/// Delete a status page (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/status-pages/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Status page ID")
+    ),
+    responses(
+        (status = 200, description = "Status page deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Status page not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_status_page(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    status_page::delete_status_page(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Status page deleted".to_string(),
+    )))
+}
+
+/// Get a status page with its checks' uptime and active incidents (publicly accessible)
+#[utoipa::path(
+    get,
+    path = "/status/{slug}",
+    params(
+        ("slug" = String, Path, description = "Status page slug")
+    ),
+    responses(
+        (status = 200, description = "Status page retrieved successfully", body = ApiResponse<PublicStatusPage>),
+        (status = 404, description = "Status page not found", body = ErrorResponse)
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_public_status_page(
+    State(app_state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<Json<ApiResponse<PublicStatusPage>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let page = status_page::public_status_page(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        &slug,
+        chrono::Utc::now(),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(page)))
+}
+
+/// Create a new incident
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents",
+    request_body = CreateIncidentRequest,
+    responses(
+        (status = 200, description = "Incident created successfully", body = ApiResponse<Incident>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_incident(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateIncidentRequest>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    // Users can create incidents, but moderators+ can assign them or attach escalation policies
+    if request.assigned_to.is_some() || request.escalation_policy_id.is_some() {
+        rbac_services::require_moderator_or_higher(&auth_user)?;
+    }
+
+    let incident = services::create_incident(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Get incidents with pagination
+#[utoipa::path(
+    get,
+    path = "/monitoring/incidents",
+    params(IncidentQueryParams),
+    responses(
+        (status = 200, description = "Incidents retrieved successfully", body = ApiResponse<Vec<Incident>>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_incidents(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<IncidentQueryParams>,
+) -> Result<Json<ApiResponse<Vec<Incident>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let incidents = services::find_incidents_with_pagination(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        params.limit,
+        params.offset,
+        params.postmortem_pending,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(incidents)))
+}
+
+/// Get incident by ID
+#[utoipa::path(
+    get,
+    path = "/monitoring/incidents/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    responses(
+        (status = 200, description = "Incident retrieved successfully", body = ApiResponse<Incident>),
+        (status = 404, description = "Incident not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_incident_by_id(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let incident = services::find_incident_by_id(conn.as_mut(), id).await?;
+    let incident = incident.ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Update incident (requires moderator or higher, or be the creator)
+#[utoipa::path(
+    put,
+    path = "/monitoring/incidents/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    request_body = UpdateIncidentRequest,
+    responses(
+        (status = 200, description = "Incident updated successfully", body = ApiResponse<Incident>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role or incident ownership", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn update_incident(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateIncidentRequest>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    // Use transaction to prevent race conditions
+    let mut tx = app_state
+        .database
+        .pool
+        .begin()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    // Get the incident first to check ownership (with SELECT FOR UPDATE to prevent race conditions)
+    services::ensure_incident(tx.as_mut(), id, auth_user.tenant_id).await?;
+    let current_incident = services::find_incident_by_id_for_update(tx.as_mut(), id).await?;
+    let current_incident =
+        current_incident.ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+
+    // Check if user can update this incident
+    let can_update = auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
+        || current_incident.created_by == Some(auth_user.id);
+
+    if !can_update {
+        return Err(Error::Forbidden("Cannot update this incident".to_string()));
+    }
+
+    // Update the incident within the transaction
+    let incident = services::update_incident_in_transaction(tx.as_mut(), id, request).await?;
+
+    // Commit the transaction
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Check that a user may respond to an incident: moderators+, its creator or its assignee
+fn require_incident_responder(auth_user: &AuthUser, incident: &Incident) -> Result<(), Error> {
+    let can_respond = auth_user
+        .role
+        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
+        || incident.created_by == Some(auth_user.id)
+        || incident.assigned_to == Some(auth_user.id);
+
+    if !can_respond {
+        return Err(Error::Forbidden(
+            "Cannot respond to this incident".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Load an incident and check that the user may respond to it
+async fn find_incident_for_responder(
+    conn: &mut crate::DbConn,
+    auth_user: &AuthUser,
+    id: Uuid,
+) -> Result<Incident, Error> {
+    services::ensure_incident(conn, id, auth_user.tenant_id).await?;
+    let incident = services::find_incident_by_id(conn, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    require_incident_responder(auth_user, &incident)?;
+    Ok(incident)
+}
+
+/// Acknowledge an incident, stopping automatic escalation
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents/{id}/acknowledge",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    responses(
+        (status = 200, description = "Incident acknowledged", body = ApiResponse<Incident>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse),
+        (status = 409, description = "Incident already acknowledged or resolved", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn acknowledge_incident(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let incident = escalation::acknowledge_incident(conn.as_mut(), id, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Resolve an incident, stopping automatic escalation
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents/{id}/resolve",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    request_body(content = Option<ResolveIncidentRequest>, description = "Optional root cause"),
+    responses(
+        (status = 200, description = "Incident resolved", body = ApiResponse<Incident>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse),
+        (status = 409, description = "Incident already resolved", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn resolve_incident(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    request: Option<Json<ResolveIncidentRequest>>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let incident = escalation::resolve_incident(conn.as_mut(), id, request).await?;
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Escalate an incident to the next step of its policy now
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents/{id}/escalate",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    responses(
+        (status = 200, description = "Incident escalated; the worker emails the notified user", body = ApiResponse<Incident>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse),
+        (status = 409, description = "Incident resolved, without a policy, or out of escalation steps", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn escalate_incident(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Incident>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let incident = escalation::escalate_incident(conn.as_mut(), id, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(incident)))
+}
+
+/// Get an incident's escalation history
+#[utoipa::path(
+    get,
+    path = "/monitoring/incidents/{id}/escalations",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    responses(
+        (status = 200, description = "Escalations retrieved successfully", body = ApiResponse<Vec<IncidentEscalation>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_incident_escalations(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<IncidentEscalation>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let escalations = escalation::find_incident_escalations(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success(escalations)))
+}
+
+/// Get an incident's postmortem
+#[utoipa::path(
+    get,
+    path = "/monitoring/incidents/{id}/postmortem",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    responses(
+        (status = 200, description = "Postmortem retrieved successfully", body = ApiResponse<Postmortem>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Incident or postmortem not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_incident_postmortem(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Postmortem>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let postmortem = postmortem::find_postmortem(conn.as_mut(), id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Postmortem not found".to_string()))?;
+    Ok(Json(ApiResponse::success(postmortem)))
+}
+
+/// Write an incident's postmortem, replacing any previous version
+#[utoipa::path(
+    put,
+    path = "/monitoring/incidents/{id}/postmortem",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    request_body = UpsertPostmortemRequest,
+    responses(
+        (status = 200, description = "Postmortem saved successfully", body = ApiResponse<Postmortem>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn upsert_incident_postmortem(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpsertPostmortemRequest>,
+) -> Result<Json<ApiResponse<Postmortem>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let postmortem =
+        postmortem::upsert_postmortem(conn.as_mut(), id, request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(postmortem)))
+}
+
+/// Add an action item to an incident's postmortem
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents/{id}/postmortem/action-items",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    request_body = CreateActionItemRequest,
+    responses(
+        (status = 200, description = "Action item created successfully", body = ApiResponse<PostmortemActionItem>),
+        (status = 400, description = "Invalid input or unknown task", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident or postmortem not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_postmortem_action_item(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateActionItemRequest>,
+) -> Result<Json<ApiResponse<PostmortemActionItem>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let item = postmortem::create_action_item(conn.as_mut(), id, request).await?;
+    Ok(Json(ApiResponse::success(item)))
+}
+
+/// Update an action item of an incident's postmortem
+#[utoipa::path(
+    put,
+    path = "/monitoring/incidents/{id}/postmortem/action-items/{item_id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID"),
+        ("item_id" = Uuid, Path, description = "Action item ID")
+    ),
+    request_body = UpdateActionItemRequest,
+    responses(
+        (status = 200, description = "Action item updated successfully", body = ApiResponse<PostmortemActionItem>),
+        (status = 400, description = "Invalid input or unknown task", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident, postmortem or action item not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn update_postmortem_action_item(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, item_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateActionItemRequest>,
+) -> Result<Json<ApiResponse<PostmortemActionItem>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    let item = postmortem::update_action_item(conn.as_mut(), id, item_id, request).await?;
+    Ok(Json(ApiResponse::success(item)))
+}
+
+/// Delete an action item of an incident's postmortem
+#[utoipa::path(
+    delete,
+    path = "/monitoring/incidents/{id}/postmortem/action-items/{item_id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID"),
+        ("item_id" = Uuid, Path, description = "Action item ID")
+    ),
+    responses(
+        (status = 200, description = "Action item deleted successfully", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role, incident ownership or assignment", body = ErrorResponse),
+        (status = 404, description = "Incident, postmortem or action item not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_postmortem_action_item(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    find_incident_for_responder(conn.as_mut(), &auth_user, id).await?;
+    postmortem::delete_action_item(conn.as_mut(), id, item_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Action item deleted".to_string(),
+    )))
+}
+
+/// Create an escalation policy (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/escalation-policies",
+    request_body = CreateEscalationPolicyRequest,
+    responses(
+        (status = 200, description = "Escalation policy created successfully", body = ApiResponse<EscalationPolicy>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "A policy with this name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_escalation_policy(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateEscalationPolicyRequest>,
+) -> Result<Json<ApiResponse<EscalationPolicy>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let policy = escalation::create_escalation_policy(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(policy)))
+}
+
+/// Get all escalation policies
+#[utoipa::path(
+    get,
+    path = "/monitoring/escalation-policies",
+    responses(
+        (status = 200, description = "Escalation policies retrieved successfully", body = ApiResponse<Vec<EscalationPolicy>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_escalation_policies(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<EscalationPolicy>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let policies = escalation::find_escalation_policies(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(policies)))
+}
+
+/// Get an escalation policy by ID
+#[utoipa::path(
+    get,
+    path = "/monitoring/escalation-policies/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Escalation policy ID")
+    ),
+    responses(
+        (status = 200, description = "Escalation policy retrieved successfully", body = ApiResponse<EscalationPolicy>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Escalation policy not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_escalation_policy_by_id(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EscalationPolicy>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let policy = escalation::find_escalation_policy_by_id(conn.as_mut(), id, auth_user.tenant_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Escalation policy not found".to_string()))?;
+    Ok(Json(ApiResponse::success(policy)))
+}
+
+/// Delete an escalation policy (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/escalation-policies/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Escalation policy ID")
+    ),
+    responses(
+        (status = 200, description = "Escalation policy deleted; incidents using it stop escalating", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "Escalation policy not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_escalation_policy(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    escalation::delete_escalation_policy(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Escalation policy deleted".to_string(),
+    )))
+}
+
+/// Get who is currently on call
+#[utoipa::path(
+    get,
+    path = "/monitoring/oncall/current",
+    params(
+        OncallQueryParams
+    ),
+    responses(
+        (status = 200, description = "Current on-call shift of each schedule", body = ApiResponse<Vec<OncallShift>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "On-call schedule not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_current_oncall(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<OncallQueryParams>,
+) -> Result<Json<ApiResponse<Vec<OncallShift>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let shifts =
+        oncall::find_current_oncall(conn.as_mut(), auth_user.tenant_id, params.schedule_id).await?;
+    Ok(Json(ApiResponse::success(shifts)))
+}
+
+/// Create an on-call schedule (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/oncall/schedules",
+    request_body = CreateOncallScheduleRequest,
+    responses(
+        (status = 200, description = "On-call schedule created successfully", body = ApiResponse<OncallSchedule>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 409, description = "A schedule with this name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_oncall_schedule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateOncallScheduleRequest>,
+) -> Result<Json<ApiResponse<OncallSchedule>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let schedule = oncall::create_oncall_schedule(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(schedule)))
+}
+
+/// Get all on-call schedules
+#[utoipa::path(
+    get,
+    path = "/monitoring/oncall/schedules",
+    responses(
+        (status = 200, description = "On-call schedules retrieved successfully", body = ApiResponse<Vec<OncallSchedule>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_oncall_schedules(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<OncallSchedule>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let schedules = oncall::find_oncall_schedules(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(schedules)))
+}
+
+/// Get an on-call schedule by ID
+#[utoipa::path(
+    get,
+    path = "/monitoring/oncall/schedules/{id}",
+    params(
+        ("id" = Uuid, Path, description = "On-call schedule ID")
+    ),
+    responses(
+        (status = 200, description = "On-call schedule retrieved successfully", body = ApiResponse<OncallSchedule>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "On-call schedule not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_oncall_schedule_by_id(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<OncallSchedule>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let schedule = oncall::find_oncall_schedule_by_id(conn.as_mut(), id, auth_user.tenant_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("On-call schedule not found".to_string()))?;
+    Ok(Json(ApiResponse::success(schedule)))
+}
+
+/// Delete an on-call schedule (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/oncall/schedules/{id}",
+    params(
+        ("id" = Uuid, Path, description = "On-call schedule ID")
+    ),
+    responses(
+        (status = 200, description = "On-call schedule deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "On-call schedule not found", body = ErrorResponse),
+        (status = 409, description = "Schedule is used by an escalation policy", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_oncall_schedule(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    oncall::delete_oncall_schedule(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "On-call schedule deleted".to_string(),
+    )))
+}
+
+/// Override an on-call schedule for a while (requires moderator or higher)
+#[utoipa::path(
+    post,
+    path = "/monitoring/oncall/schedules/{id}/overrides",
+    params(
+        ("id" = Uuid, Path, description = "On-call schedule ID")
+    ),
+    request_body = CreateOncallOverrideRequest,
+    responses(
+        (status = 200, description = "On-call override created successfully", body = ApiResponse<OncallOverride>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "On-call schedule not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_oncall_override(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateOncallOverrideRequest>,
+) -> Result<Json<ApiResponse<OncallOverride>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let cover = oncall::create_oncall_override(
+        conn.as_mut(),
+        id,
+        auth_user.tenant_id,
+        request,
+        Some(auth_user.id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(cover)))
+}
+
+/// Delete an on-call override (requires moderator or higher)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/oncall/schedules/{id}/overrides/{override_id}",
+    params(
+        ("id" = Uuid, Path, description = "On-call schedule ID"),
+        ("override_id" = Uuid, Path, description = "Override ID")
+    ),
+    responses(
+        (status = 200, description = "On-call override deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse),
+        (status = 404, description = "On-call override not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_oncall_override(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, override_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    oncall::delete_oncall_override(conn.as_mut(), id, auth_user.tenant_id, override_id).await?;
+    Ok(Json(ApiResponse::success(
+        "On-call override deleted".to_string(),
+    )))
+}
+
+/// Get incident timeline
+#[utoipa::path(
+    get,
+    path = "/monitoring/incidents/{id}/timeline",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID"),
+        TimelineQueryParams
+    ),
+    responses(
+        (status = 200, description = "Incident timeline retrieved successfully", body = ApiResponse<IncidentTimeline>),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_incident_timeline(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<TimelineQueryParams>,
+) -> Result<Json<ApiResponse<IncidentTimeline>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let timeline = services::get_incident_timeline(
+        conn.as_mut(),
+        id,
+        params.limit,
+        params.offset,
+        params.lookback_hours,
+        params.correlated.unwrap_or(false),
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(timeline)))
+}
+
+/// Post a note onto an incident timeline
+#[utoipa::path(
+    post,
+    path = "/monitoring/incidents/{id}/timeline/notes",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID")
+    ),
+    request_body = CreateIncidentNoteRequest,
+    responses(
+        (status = 200, description = "Note posted successfully", body = ApiResponse<IncidentNote>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Incident not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn create_incident_note(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateIncidentNoteRequest>,
+) -> Result<Json<ApiResponse<IncidentNote>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let note = notes::create_note(conn.as_mut(), id, auth_user.id, request).await?;
+    Ok(Json(ApiResponse::success(note)))
+}
+
+/// Edit a note on an incident timeline (author or moderator)
+#[utoipa::path(
+    put,
+    path = "/monitoring/incidents/{id}/timeline/notes/{note_id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID"),
+        ("note_id" = Uuid, Path, description = "Note ID")
+    ),
+    request_body = UpdateIncidentNoteRequest,
+    responses(
+        (status = 200, description = "Note updated successfully", body = ApiResponse<IncidentNote>),
+        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Note not found or not yours", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn update_incident_note(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, note_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateIncidentNoteRequest>,
+) -> Result<Json<ApiResponse<IncidentNote>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    let note = notes::update_note(conn.as_mut(), &auth_user, id, note_id, request).await?;
+    Ok(Json(ApiResponse::success(note)))
+}
+
+/// Delete a note from an incident timeline (author or moderator)
+#[utoipa::path(
+    delete,
+    path = "/monitoring/incidents/{id}/timeline/notes/{note_id}",
+    params(
+        ("id" = Uuid, Path, description = "Incident ID"),
+        ("note_id" = Uuid, Path, description = "Note ID")
+    ),
+    responses(
+        (status = 200, description = "Note deleted successfully", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Note not found or not yours", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn delete_incident_note(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, note_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    services::ensure_incident(conn.as_mut(), id, auth_user.tenant_id).await?;
+    notes::delete_note(conn.as_mut(), &auth_user, id, note_id).await?;
+    Ok(Json(ApiResponse::success("Note deleted".to_string())))
+}
+
+/// Get monitoring system statistics (requires moderator or higher)
+#[utoipa::path(
+    get,
+    path = "/monitoring/stats",
+    responses(
+        (status = 200, description = "Monitoring statistics retrieved successfully", body = ApiResponse<MonitoringStats>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_monitoring_stats(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<MonitoringStats>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let stats = services::get_monitoring_stats(
+        conn.as_mut(),
+        &app_state.config.monitoring,
+        Some(auth_user.tenant_id),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+/// Export metrics in Prometheus format (publicly accessible for scraping)
+#[utoipa::path(
+    get,
+    path = "/monitoring/metrics/prometheus",
+    responses(
+        (status = 200, description = "Prometheus metrics in text/plain format (version=0.0.4; charset=utf-8)", body = String)
+    ),
+    tag = "Monitoring"
+)]
+pub async fn get_prometheus_metrics(State(app_state): State<AppState>) -> Result<Response, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    // Get system statistics across all tenants
+    let stats =
+        services::get_monitoring_stats(conn.as_mut(), &app_state.config.monitoring, None).await?;
+
+    // Get recent metrics from the database (last 24 hours)
+    let recent_metrics = services::get_prometheus_metrics(conn.as_mut()).await?;
+
+    let mut prometheus_output = String::new();
+
+    // Add system-level monitoring metrics
+    prometheus_output.push_str(&format!(
+        r#"# HELP monitoring_total_events Total number of events in the system
+# TYPE monitoring_total_events counter
+monitoring_total_events {}
+
+# HELP monitoring_total_metrics Total number of metrics in the system
+# TYPE monitoring_total_metrics counter
+monitoring_total_metrics {}
+
+# HELP monitoring_active_alerts Number of currently active alerts
+# TYPE monitoring_active_alerts gauge
+monitoring_active_alerts {}
+
+# HELP monitoring_open_incidents Number of currently open incidents
+# TYPE monitoring_open_incidents gauge
+monitoring_open_incidents {}
+
+# HELP monitoring_events_last_hour Number of events in the last hour
+# TYPE monitoring_events_last_hour gauge
+monitoring_events_last_hour {}
+
+# HELP monitoring_metrics_last_hour Number of metrics in the last hour
+# TYPE monitoring_metrics_last_hour gauge
+monitoring_metrics_last_hour {}
+
+"#,
+        stats.total_events,
+        stats.total_metrics,
+        stats.active_alerts,
+        stats.open_incidents,
+        stats.events_last_hour,
+        stats.metrics_last_hour
+    ));
+
+    // Add task queue gauges of all tenants for autoscaling on queue depth
+    let queue_stats = crate::tasks::queue::get_queue_stats(conn.as_mut(), None).await?;
+    prometheus_output.push_str(&crate::tasks::queue::render_prometheus(&queue_stats));
+
+    // Add user-submitted metrics from the database
+    prometheus_output.push_str(&recent_metrics);
+
+    // Create response with proper Prometheus content type
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )
+        .body(Body::from(prometheus_output))
+        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+}
+
+/// Get metric retention defaults and per-metric overrides (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/monitoring/retention",
+    responses(
+        (status = 200, description = "Metric retention settings", body = ApiResponse<MetricRetentionSettings>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin"
+)]
+pub async fn get_metric_retention(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<MetricRetentionSettings>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let policies = retention::list_retention_policies(conn.as_mut()).await?;
+    let config = &app_state.config.monitoring;
+    Ok(Json(ApiResponse::success(MetricRetentionSettings {
+        default_raw_retention_days: config.metric_raw_retention_days,
+        default_rollup_retention_days: config.metric_rollup_retention_days,
+        rollup_resolution_secs: retention::ROLLUP_RESOLUTION_SECS,
+        policies,
+    })))
+}
+
+/// Set a metric's retention (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/monitoring/retention/{name}",
+    params(
+        ("name" = String, Path, description = "Metric name")
+    ),
+    request_body = SetMetricRetentionRequest,
+    responses(
+        (status = 200, description = "Retention policy saved", body = ApiResponse<MetricRetentionPolicy>),
+        (status = 400, description = "Invalid retention", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin"
+)]
+pub async fn set_metric_retention(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+    Path(name): Path<String>,
+    Json(request): Json<SetMetricRetentionRequest>,
+) -> Result<Json<ApiResponse<MetricRetentionPolicy>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Metric retention is managed")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let before = find_metric_retention(conn.as_mut(), &name).await?;
+    let policy =
+        retention::set_retention_policy(conn.as_mut(), &name, request, auth_user.id).await?;
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        "metric_retention_set",
+        &AuditResource::new(AuditResourceType::Metric, &name),
+        before,
+        Some(serde_json::json!({
+            "raw_retention_days": policy.raw_retention_days,
+            "rollup_retention_days": policy.rollup_retention_days,
+        })),
+    )
+    .await;
+    Ok(Json(ApiResponse::success(policy)))
+}
+
+/// Remove a metric's retention override (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/monitoring/retention/{name}",
+    params(
+        ("name" = String, Path, description = "Metric name")
+    ),
     responses(
-        (status = 200, description = "Incident created successfully", body = ApiResponse<Incident>),
-        (status = 400, description = "Invalid input", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Retention policy removed; the metric uses the defaults again", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "No retention policy for this metric", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn create_incident(
+pub async fn delete_metric_retention(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-    Json(request): Json<CreateIncidentRequest>,
-) -> Result<Json<ApiResponse<Incident>>, Error> {
+    actor: Actor,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Metric retention is managed")?;
+
     let mut conn = app_state
         .database
         .pool
@@ -591,35 +3897,59 @@ pub async fn create_incident(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // Users can create incidents, but moderators+ can assign them
-    if request.assigned_to.is_some() {
-        rbac_services::require_moderator_or_higher(&auth_user)?;
-    }
+    let before = find_metric_retention(conn.as_mut(), &name).await?;
+    retention::delete_retention_policy(conn.as_mut(), &name).await?;
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        "metric_retention_removed",
+        &AuditResource::new(AuditResourceType::Metric, &name),
+        before,
+        None,
+    )
+    .await;
+    Ok(Json(ApiResponse::success(format!(
+        "Retention policy for metric '{name}' removed"
+    ))))
+}
 
-    let incident = services::create_incident(conn.as_mut(), request, Some(auth_user.id)).await?;
-    Ok(Json(ApiResponse::success(incident)))
+/// A metric's retention override as recorded in the audit log
+async fn find_metric_retention(
+    conn: &mut crate::DbConn,
+    name: &str,
+) -> Result<Option<serde_json::Value>, Error> {
+    Ok(retention::list_retention_policies(conn)
+        .await?
+        .into_iter()
+        .find(|policy| policy.metric_name == name)
+        .map(|policy| {
+            serde_json::json!({
+                "raw_retention_days": policy.raw_retention_days,
+                "rollup_retention_days": policy.rollup_retention_days,
+            })
+        }))
 }
 
-/// Get incidents with pagination
+/// Get event ingestion defaults and per-source overrides (Admin only)
 #[utoipa::path(
     get,
-    path = "/monitoring/incidents",
-    params(IncidentQueryParams),
+    path = "/admin/monitoring/event-limits",
     responses(
-        (status = 200, description = "Incidents retrieved successfully", body = ApiResponse<Vec<Incident>>),
-        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Event ingestion limits", body = ApiResponse<EventLimitSettings>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn get_incidents(
+pub async fn get_event_limits(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Query(params): Query<IncidentQueryParams>,
-) -> Result<Json<ApiResponse<Vec<Incident>>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<EventLimitSettings>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
     let mut conn = app_state
         .database
         .pool
@@ -627,34 +3957,44 @@ pub async fn get_incidents(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let incidents =
-        services::find_incidents_with_pagination(conn.as_mut(), params.limit, params.offset)
-            .await?;
-    Ok(Json(ApiResponse::success(incidents)))
+    let limits = limits::list_source_limits(conn.as_mut()).await?;
+    let config = &app_state.config.monitoring;
+    Ok(Json(ApiResponse::success(EventLimitSettings {
+        default_events_per_minute: config.event_rate_limit_per_source,
+        default_sample_ratio: config.event_sample_ratio,
+        user_events_per_minute: config.event_rate_limit_per_user,
+        limits,
+    })))
 }
 
-/// Get incident by ID
+/// Set a source's event ingestion limits (Admin only)
 #[utoipa::path(
-    get,
-    path = "/monitoring/incidents/{id}",
+    put,
+    path = "/admin/monitoring/event-limits/{source}",
     params(
-        ("id" = Uuid, Path, description = "Incident ID")
+        ("source" = String, Path, description = "Event source")
     ),
+    request_body = SetEventSourceLimitRequest,
     responses(
-        (status = 200, description = "Incident retrieved successfully", body = ApiResponse<Incident>),
-        (status = 404, description = "Incident not found", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Ingestion limits saved", body = ApiResponse<EventSourceLimit>),
+        (status = 400, description = "Invalid limits", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn get_incident_by_id(
+pub async fn set_event_limit(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Path(id): Path<Uuid>,
-) -> Result<Json<ApiResponse<Incident>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+    Path(source): Path<String>,
+    Json(request): Json<SetEventSourceLimitRequest>,
+) -> Result<Json<ApiResponse<EventSourceLimit>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Event ingestion limits are managed")?;
+
     let mut conn = app_state
         .database
         .pool
@@ -662,94 +4002,70 @@ pub async fn get_incident_by_id(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let incident = services::find_incident_by_id(conn.as_mut(), id).await?;
-    let incident = incident.ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
-    Ok(Json(ApiResponse::success(incident)))
+    let limit = limits::set_source_limit(conn.as_mut(), &source, request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(limit)))
 }
 
-/// Update incident (requires moderator or higher, or be the creator)
+/// Remove a source's event ingestion override (Admin only)
 #[utoipa::path(
-    put,
-    path = "/monitoring/incidents/{id}",
+    delete,
+    path = "/admin/monitoring/event-limits/{source}",
     params(
-        ("id" = Uuid, Path, description = "Incident ID")
+        ("source" = String, Path, description = "Event source")
     ),
-    request_body = UpdateIncidentRequest,
     responses(
-        (status = 200, description = "Incident updated successfully", body = ApiResponse<Incident>),
-        (status = 400, description = "Invalid input", body = ErrorResponse),
+        (status = 200, description = "Ingestion limits removed; the source uses the defaults again", body = ApiResponse<String>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - requires moderator role or incident ownership", body = ErrorResponse),
-        (status = 404, description = "Incident not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "No ingestion limits for this source", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn update_incident(
+pub async fn delete_event_limit(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-    Path(id): Path<Uuid>,
-    Json(request): Json<UpdateIncidentRequest>,
-) -> Result<Json<ApiResponse<Incident>>, Error> {
-    // Use transaction to prevent race conditions
-    let mut tx = app_state
+    Path(source): Path<String>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Event ingestion limits are managed")?;
+
+    let mut conn = app_state
         .database
         .pool
-        .begin()
+        .acquire()
         .await
         .map_err(Error::from_sqlx)?;
 
-    // Get the incident first to check ownership (with SELECT FOR UPDATE to prevent race conditions)
-    let current_incident = services::find_incident_by_id_for_update(tx.as_mut(), id).await?;
-    let current_incident =
-        current_incident.ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
-
-    // Check if user can update this incident
-    let can_update = auth_user
-        .role
-        .has_role_or_higher(crate::rbac::models::UserRole::Moderator)
-        || current_incident.created_by == Some(auth_user.id);
-
-    if !can_update {
-        return Err(Error::Forbidden("Cannot update this incident".to_string()));
-    }
-
-    // Update the incident within the transaction
-    let incident = services::update_incident_in_transaction(tx.as_mut(), id, request).await?;
-
-    // Commit the transaction
-    tx.commit().await.map_err(Error::from_sqlx)?;
-
-    Ok(Json(ApiResponse::success(incident)))
+    limits::delete_source_limit(conn.as_mut(), &source).await?;
+    Ok(Json(ApiResponse::success(format!(
+        "Ingestion limits for source '{source}' removed"
+    ))))
 }
 
-/// Get incident timeline
+/// List daily ingestion quotas (Admin only)
 #[utoipa::path(
     get,
-    path = "/monitoring/incidents/{id}/timeline",
-    params(
-        ("id" = Uuid, Path, description = "Incident ID"),
-        TimelineQueryParams
-    ),
+    path = "/admin/monitoring/quotas",
     responses(
-        (status = 200, description = "Incident timeline retrieved successfully", body = ApiResponse<IncidentTimeline>),
-        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 200, description = "Daily ingestion quotas", body = ApiResponse<Vec<IngestionQuota>>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 404, description = "Incident not found", body = ErrorResponse)
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn get_incident_timeline(
+pub async fn get_ingestion_quotas(
     State(app_state): State<AppState>,
-    Extension(_auth_user): Extension<AuthUser>,
-    Path(id): Path<Uuid>,
-    Query(params): Query<TimelineQueryParams>,
-) -> Result<Json<ApiResponse<IncidentTimeline>>, Error> {
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<IngestionQuota>>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Ingestion quotas are managed")?;
+
     let mut conn = app_state
         .database
         .pool
@@ -757,36 +4073,40 @@ pub async fn get_incident_timeline(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let timeline = services::get_incident_timeline(
-        conn.as_mut(),
-        id,
-        params.limit,
-        params.offset,
-        params.lookback_hours,
-    )
-    .await?;
-
-    Ok(Json(ApiResponse::success(timeline)))
+    let quotas = quotas::list_quotas(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(quotas)))
 }
 
-/// Get monitoring system statistics (requires moderator or higher)
+/// Set the daily ingestion quota of a user or event source (Admin only)
 #[utoipa::path(
-    get,
-    path = "/monitoring/stats",
+    put,
+    path = "/admin/monitoring/quotas/{scope}/{key}",
+    params(
+        ("scope" = QuotaScope, Path, description = "`user` or `source`"),
+        ("key" = String, Path, description = "User ID or event source")
+    ),
+    request_body = SetIngestionQuotaRequest,
     responses(
-        (status = 200, description = "Monitoring statistics retrieved successfully", body = ApiResponse<MonitoringStats>),
+        (status = 200, description = "Quota saved", body = ApiResponse<IngestionQuota>),
+        (status = 400, description = "Invalid quota", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - requires moderator role", body = ErrorResponse)
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     ),
-    tag = "Monitoring"
+    tag = "Admin"
 )]
-pub async fn get_monitoring_stats(
+pub async fn set_ingestion_quota(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<MonitoringStats>>, Error> {
+    Path((scope, key)): Path<(QuotaScope, String)>,
+    Json(request): Json<SetIngestionQuotaRequest>,
+) -> Result<Json<ApiResponse<IngestionQuota>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Ingestion quotas are managed")?;
+
     let mut conn = app_state
         .database
         .pool
@@ -794,22 +4114,37 @@ pub async fn get_monitoring_stats(
         .await
         .map_err(Error::from_sqlx)?;
 
-    rbac_services::require_moderator_or_higher(&auth_user)?;
-
-    let stats = services::get_monitoring_stats(conn.as_mut()).await?;
-    Ok(Json(ApiResponse::success(stats)))
+    let quota = quotas::set_quota(conn.as_mut(), scope, &key, request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(quota)))
 }
 
-/// Export metrics in Prometheus format (publicly accessible for scraping)
+/// Remove the daily ingestion quota of a user or event source (Admin only)
 #[utoipa::path(
-    get,
-    path = "/monitoring/metrics/prometheus",
+    delete,
+    path = "/admin/monitoring/quotas/{scope}/{key}",
+    params(
+        ("scope" = QuotaScope, Path, description = "`user` or `source`"),
+        ("key" = String, Path, description = "User ID or event source")
+    ),
     responses(
-        (status = 200, description = "Prometheus metrics in text/plain format (version=0.0.4; charset=utf-8)", body = String)
+        (status = 200, description = "Quota removed", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "No quota for this user or source", body = ErrorResponse)
     ),
-    tag = "Monitoring"
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Admin"
 )]
-pub async fn get_prometheus_metrics(State(app_state): State<AppState>) -> Result<Response, Error> {
+pub async fn delete_ingestion_quota(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((scope, key)): Path<(QuotaScope, String)>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Ingestion quotas are managed")?;
+
     let mut conn = app_state
         .database
         .pool
@@ -817,61 +4152,10 @@ pub async fn get_prometheus_metrics(State(app_state): State<AppState>) -> Result
         .await
         .map_err(Error::from_sqlx)?;
 
-    // Get system statistics
-    let stats = services::get_monitoring_stats(conn.as_mut()).await?;
-
-    // Get recent metrics from the database (last 24 hours)
-    let recent_metrics = services::get_prometheus_metrics(conn.as_mut()).await?;
-
-    let mut prometheus_output = String::new();
-
-    // Add system-level monitoring metrics
-    prometheus_output.push_str(&format!(
-        r#"# HELP monitoring_total_events Total number of events in the system
-# TYPE monitoring_total_events counter
-monitoring_total_events {}
-
-# HELP monitoring_total_metrics Total number of metrics in the system
-# TYPE monitoring_total_metrics counter
-monitoring_total_metrics {}
-
-# HELP monitoring_active_alerts Number of currently active alerts
-# TYPE monitoring_active_alerts gauge
-monitoring_active_alerts {}
-
-# HELP monitoring_open_incidents Number of currently open incidents
-# TYPE monitoring_open_incidents gauge
-monitoring_open_incidents {}
-
-# HELP monitoring_events_last_hour Number of events in the last hour
-# TYPE monitoring_events_last_hour gauge
-monitoring_events_last_hour {}
-
-# HELP monitoring_metrics_last_hour Number of metrics in the last hour
-# TYPE monitoring_metrics_last_hour gauge
-monitoring_metrics_last_hour {}
-
-"#,
-        stats.total_events,
-        stats.total_metrics,
-        stats.active_alerts,
-        stats.open_incidents,
-        stats.events_last_hour,
-        stats.metrics_last_hour
-    ));
-
-    // Add user-submitted metrics from the database
-    prometheus_output.push_str(&recent_metrics);
-
-    // Create response with proper Prometheus content type
-    Response::builder()
-        .status(StatusCode::OK)
-        .header(
-            header::CONTENT_TYPE,
-            "text/plain; version=0.0.4; charset=utf-8",
-        )
-        .body(Body::from(prometheus_output))
-        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+    quotas::delete_quota(conn.as_mut(), scope, &key).await?;
+    Ok(Json(ApiResponse::success(format!(
+        "Ingestion quota for {scope} '{key}' removed"
+    ))))
 }
 
 /// Public monitoring routes (no authentication required)
@@ -879,12 +4163,28 @@ pub fn monitoring_public_routes() -> Router<AppState> {
     Router::new().route("/metrics/prometheus", get(get_prometheus_metrics))
 }
 
+/// Public status page routes (no authentication required)
+pub fn status_page_public_routes() -> Router<AppState> {
+    Router::new().route("/{slug}", get(get_public_status_page))
+}
+
 /// Protected monitoring routes (authentication required)
 pub fn monitoring_routes() -> Router<AppState> {
     Router::new()
-        .route("/events", post(create_event).get(get_events))
+        .route("/events", get(get_events))
+        .route("/events/stream", get(stream_events))
+        .route("/events/export", get(export_events))
         .route("/events/{id}", get(get_event_by_id))
-        .route("/metrics", post(create_metric).get(get_metrics))
+        .route("/metrics", get(get_metrics))
+        .route("/metrics/series", get(get_metric_series))
+        .route("/metrics/export", get(export_metrics))
+        .route("/metrics/query", get(query_metrics))
+        .route("/recording-rules", get(get_recording_rules))
+        .route("/grafana", get(grafana_health))
+        .route("/grafana/metrics", post(grafana_metrics))
+        .route("/grafana/query", post(grafana_query))
+        .route("/grafana/tag-keys", post(grafana_tag_keys))
+        .route("/grafana/tag-values", post(grafana_tag_values))
         .route("/alerts", get(get_alerts))
         .route("/incidents", post(create_incident).get(get_incidents))
         .route(
@@ -892,11 +4192,169 @@ pub fn monitoring_routes() -> Router<AppState> {
             get(get_incident_by_id).put(update_incident),
         )
         .route("/incidents/{id}/timeline", get(get_incident_timeline))
+        .route("/incidents/{id}/timeline/notes", post(create_incident_note))
+        .route(
+            "/incidents/{id}/timeline/notes/{note_id}",
+            put(update_incident_note).delete(delete_incident_note),
+        )
+        .route("/incidents/{id}/acknowledge", post(acknowledge_incident))
+        .route("/incidents/{id}/resolve", post(resolve_incident))
+        .route("/incidents/{id}/escalate", post(escalate_incident))
+        .route("/incidents/{id}/escalations", get(get_incident_escalations))
+        .route(
+            "/incidents/{id}/postmortem",
+            get(get_incident_postmortem).put(upsert_incident_postmortem),
+        )
+        .route(
+            "/incidents/{id}/postmortem/action-items",
+            post(create_postmortem_action_item),
+        )
+        .route(
+            "/incidents/{id}/postmortem/action-items/{item_id}",
+            put(update_postmortem_action_item).delete(delete_postmortem_action_item),
+        )
+        .route("/escalation-policies", get(get_escalation_policies))
+        .route(
+            "/escalation-policies/{id}",
+            get(get_escalation_policy_by_id),
+        )
+        .route("/oncall/current", get(get_current_oncall))
+        .route("/oncall/schedules", get(get_oncall_schedules))
+        .route("/oncall/schedules/{id}", get(get_oncall_schedule_by_id))
+        .route_layer(middleware::from_fn(require_monitoring_access))
+}
+
+/// Event, metric and OTLP ingestion routes (authentication required), which
+/// take larger bodies than the other monitoring routes
+pub fn monitoring_ingestion_routes() -> Router<AppState> {
+    Router::new()
+        .route("/events", post(create_event))
+        .route("/metrics", post(create_metric))
+        .route("/metrics/histogram", post(create_histogram))
+        .route("/metrics/summary", post(create_summary))
+        .nest("/otlp", otlp_routes())
+        .merge(event_batch_routes())
+        .route_layer(middleware::from_fn(require_monitoring_access))
+}
+
+/// Monitoring endpoints need `monitoring:read` for GET and `monitoring:write`
+/// otherwise
+///
+/// Every built-in role has both, so this only turns away users with a
+/// [deny](crate::rbac::denies), e.g. to stop one user's ingestion.
+async fn require_monitoring_access(req: Request, next: Next) -> Result<Response, Error> {
+    let auth_user = req
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(Error::Unauthorized)?;
+    let permission = match *req.method() {
+        Method::GET | Method::HEAD => Permission::Read,
+        _ => Permission::Write,
+    };
+    rbac_services::check_permission(auth_user, Resource::Monitoring, permission)?;
+    Ok(next.run(req).await)
+}
+
+/// Bulk event ingestion, which reads its body up to the configured size itself
+fn event_batch_routes() -> Router<AppState> {
+    Router::new()
+        .route("/events/batch", post(create_event_batch))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestDecompressionLayer::new())
+}
+
+/// OTLP/HTTP ingestion routes
+///
+/// Each signal is served at `/otlp/{signal}` and at `/otlp/v1/{signal}`, so
+/// exporters can use either a per-signal endpoint or the base
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (which appends `/v1/{signal}`).
+fn otlp_routes() -> Router<AppState> {
+    let routes = Router::new()
+        .route("/traces", post(ingest_otlp_traces))
+        .route("/metrics", post(ingest_otlp_metrics))
+        .route("/logs", post(ingest_otlp_logs))
+        .route("/v1/traces", post(ingest_otlp_traces))
+        .route("/v1/metrics", post(ingest_otlp_metrics))
+        .route("/v1/logs", post(ingest_otlp_logs));
+    // Limits the decompressed body, on top of the limit of ingestion routes
+    limit_body(routes, otlp::MAX_OTLP_BODY_BYTES).layer(RequestDecompressionLayer::new())
 }
 
 /// Moderator monitoring routes (moderator role required)
 pub fn monitoring_moderator_routes() -> Router<AppState> {
     Router::new()
         .route("/alerts", post(create_alert))
+        .route("/alerts/{id}/test", post(test_alert))
+        .route(
+            "/alerts/{id}/channels",
+            get(get_alert_channels).put(set_alert_channels),
+        )
+        .route(
+            "/notification-channels",
+            get(get_notification_channels).post(create_notification_channel),
+        )
+        .route(
+            "/notification-channels/{id}",
+            delete(delete_notification_channel),
+        )
+        .route("/recording-rules", post(create_recording_rule))
+        .route("/recording-rules/{id}", delete(delete_recording_rule))
+        .route(
+            "/uptime-checks",
+            get(get_uptime_checks).post(create_uptime_check),
+        )
+        .route("/uptime-checks/{id}", delete(delete_uptime_check))
+        .route(
+            "/alert-routing-rules",
+            get(get_alert_routing_rules).post(create_alert_routing_rule),
+        )
+        .route(
+            "/alert-routing-rules/{id}",
+            delete(delete_alert_routing_rule),
+        )
+        .route(
+            "/status-pages",
+            get(get_status_pages).post(create_status_page),
+        )
+        .route(
+            "/status-pages/{id}",
+            put(update_status_page).delete(delete_status_page),
+        )
+        .route("/escalation-policies", post(create_escalation_policy))
+        .route(
+            "/escalation-policies/{id}",
+            delete(delete_escalation_policy),
+        )
+        .route("/oncall/schedules", post(create_oncall_schedule))
+        .route("/oncall/schedules/{id}", delete(delete_oncall_schedule))
+        .route(
+            "/oncall/schedules/{id}/overrides",
+            post(create_oncall_override),
+        )
+        .route(
+            "/oncall/schedules/{id}/overrides/{override_id}",
+            delete(delete_oncall_override),
+        )
         .route("/stats", get(get_monitoring_stats))
+        .route_layer(middleware::from_fn(require_monitoring_access))
+}
+
+/// Admin monitoring routes (admin role required)
+pub fn monitoring_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/retention", get(get_metric_retention))
+        .route(
+            "/retention/{name}",
+            put(set_metric_retention).delete(delete_metric_retention),
+        )
+        .route("/event-limits", get(get_event_limits))
+        .route(
+            "/event-limits/{source}",
+            put(set_event_limit).delete(delete_event_limit),
+        )
+        .route("/quotas", get(get_ingestion_quotas))
+        .route(
+            "/quotas/{scope}/{key}",
+            put(set_ingestion_quota).delete(delete_ingestion_quota),
+        )
 }
@@ -0,0 +1,375 @@
+//! Application log ingestion
+//!
+//! Bridges the process's own `tracing` output into monitoring events, so
+//! server and worker logs can be searched next to submitted events. A
+//! tracing layer queues log records as they are emitted; once the database
+//! is up, a background task stores them in batches as `log` events. Records
+//! are dropped, not waited on, when the queue is full.
+
+use crate::core::config::ObservabilityConfig;
+use crate::monitoring::models::{CreateEventRequest, MAX_MESSAGE_LENGTH, MAX_TAGS_COUNT};
+use crate::monitoring::services;
+use crate::{DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::{LevelFilter, Targets, filter_fn};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Log records queued before new ones are dropped
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Log records stored per insert
+const WRITE_BATCH_SIZE: usize = 500;
+
+/// Maximum length of a field value kept as a tag
+const MAX_FIELD_LENGTH: usize = 1000;
+
+/// Target of the writer's own logs
+const WRITER_TARGET: &str = concat!(module_path!(), "::writer");
+
+/// Target prefixes never stored: the writer's own queries and logs would feed back into it
+const EXCLUDED_TARGETS: [&str; 2] = ["sqlx", WRITER_TARGET];
+
+/// Receiver of the installed layer, taken by [`spawn_writer`]
+static PENDING_LOGS: Mutex<Option<mpsc::Receiver<AppLog>>> = Mutex::new(None);
+
+/// Records dropped because the queue was full
+static DROPPED_LOGS: AtomicU64 = AtomicU64::new(0);
+
+/// A log record waiting to be stored
+#[derive(Debug)]
+pub struct AppLog {
+    level: Level,
+    target: String,
+    module: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: Option<String>,
+    fields: HashMap<String, Value>,
+    request_id: Option<String>,
+    recorded_at: DateTime<Utc>,
+}
+
+impl AppLog {
+    fn into_event(self, source: &str) -> CreateEventRequest {
+        let mut tags: HashMap<String, Value> =
+            self.fields.into_iter().take(MAX_TAGS_COUNT - 2).collect();
+        if let Some(module) = self.module {
+            tags.insert("module".to_string(), json!(module));
+        }
+        if let Some(request_id) = self.request_id {
+            tags.insert("request_id".to_string(), json!(request_id));
+        }
+
+        CreateEventRequest {
+            event_type: "log".to_string(),
+            source: source.to_string(),
+            message: self.message,
+            level: Some(self.level.as_str().to_ascii_lowercase()),
+            tags,
+            payload: HashMap::from([
+                ("target".to_string(), json!(self.target)),
+                ("file".to_string(), json!(self.file)),
+                ("line".to_string(), json!(self.line)),
+            ]),
+            recorded_at: Some(self.recorded_at),
+            org_id: None,
+            tenant_id: None,
+        }
+    }
+}
+
+/// Build the log ingestion layer, or `None` when `log_events_filter` is `off`
+///
+/// The queued records are stored once [`spawn_writer`] is called.
+pub fn layer<S>(config: &ObservabilityConfig) -> Result<Option<impl Layer<S> + use<S>>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let targets = parse_filter(&config.log_events_filter)?;
+    if targets
+        .default_level()
+        .is_none_or(|level| level == LevelFilter::OFF)
+        && targets.iter().next().is_none()
+    {
+        return Ok(None);
+    }
+
+    let (layer, receiver) = filtered_layer(targets, config.log_events_debug_sample_ratio);
+    *PENDING_LOGS
+        .lock()
+        .map_err(|_| Error::internal("Application log queue lock poisoned"))? = Some(receiver);
+    Ok(Some(layer))
+}
+
+/// Parse a `log_events_filter` such as `warn` or `starter=debug,warn`
+pub fn parse_filter(filter: &str) -> Result<Targets> {
+    filter.parse().map_err(|e| {
+        Error::ConfigurationError(format!("Invalid observability log_events_filter: {e}"))
+    })
+}
+
+fn filtered_layer<S>(
+    targets: Targets,
+    debug_sample_ratio: f64,
+) -> (impl Layer<S>, mpsc::Receiver<AppLog>)
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    let layer = AppLogLayer {
+        sender,
+        debug_sample_ratio,
+    };
+    // Spans pass at info and above, whatever the filter, so events keep their request ID
+    let filter = filter_fn(move |metadata: &Metadata<'_>| {
+        if EXCLUDED_TARGETS
+            .iter()
+            .any(|excluded| metadata.target().starts_with(excluded))
+        {
+            return false;
+        }
+        if metadata.is_span() {
+            return *metadata.level() <= Level::INFO;
+        }
+        targets.would_enable(metadata.target(), metadata.level())
+    });
+    (layer.with_filter(filter), receiver)
+}
+
+/// Start storing queued log records as events of `source`
+///
+/// Does nothing when no ingestion layer is installed, as in tests.
+pub fn spawn_writer(pool: DbPool, source: String) {
+    let receiver = PENDING_LOGS
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take());
+    if let Some(receiver) = receiver {
+        tokio::spawn(write_logs(receiver, pool, source));
+    }
+}
+
+async fn write_logs(mut receiver: mpsc::Receiver<AppLog>, pool: DbPool, source: String) {
+    let mut logs = Vec::with_capacity(WRITE_BATCH_SIZE);
+    while receiver.recv_many(&mut logs, WRITE_BATCH_SIZE).await > 0 {
+        let events: Vec<CreateEventRequest> =
+            logs.drain(..).map(|log| log.into_event(&source)).collect();
+        if let Err(e) = store_events(&pool, &events).await {
+            tracing::warn!(
+                target: WRITER_TARGET,
+                "Failed to store {} application logs: {}", events.len(), e);
+        }
+
+        let dropped = DROPPED_LOGS.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            tracing::warn!(
+                target: WRITER_TARGET,
+                "Dropped {} application logs; the queue was full",
+                dropped
+            );
+        }
+    }
+}
+
+async fn store_events(pool: &DbPool, events: &[CreateEventRequest]) -> Result<()> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    services::create_events_batch(conn.as_mut(), events).await?;
+    Ok(())
+}
+
+/// Queues each enabled event as an [`AppLog`]
+struct AppLogLayer {
+    sender: mpsc::Sender<AppLog>,
+    /// Fraction of debug and trace records kept
+    debug_sample_ratio: f64,
+}
+
+/// Request ID recorded on a span
+struct SpanRequestId(String);
+
+impl<S> Layer<S> for AppLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let request_id = visitor
+            .fields
+            .remove("request_id")
+            .and_then(|value| value.as_str().map(str::to_string))
+            .filter(|request_id| !request_id.is_empty());
+        if let (Some(request_id), Some(span)) = (request_id, ctx.span(id)) {
+            span.extensions_mut().insert(SpanRequestId(request_id));
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() >= Level::DEBUG
+            && self.debug_sample_ratio < 1.0
+            && rand::random::<f64>() >= self.debug_sample_ratio
+        {
+            return;
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let request_id = ctx.event_scope(event).and_then(|scope| {
+            scope
+                .into_iter()
+                .find_map(|span| Some(span.extensions().get::<SpanRequestId>()?.0.clone()))
+        });
+
+        let log = AppLog {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            module: metadata.module_path().map(str::to_string),
+            file: metadata.file().map(str::to_string),
+            line: metadata.line(),
+            message: visitor.message,
+            fields: visitor.fields,
+            request_id,
+            recorded_at: Utc::now(),
+        };
+        if self.sender.try_send(log).is_err() {
+            DROPPED_LOGS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Collects an event's message and its other fields as JSON
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: HashMap<String, Value>,
+}
+
+impl FieldVisitor {
+    fn record(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            "message" => {
+                self.message = value
+                    .as_str()
+                    .map(|message| truncate(message, MAX_MESSAGE_LENGTH));
+            }
+            // Metadata added by the `log` compatibility layer
+            name if name.starts_with("log.") => {}
+            name => {
+                let value = match value {
+                    Value::String(value) => json!(truncate(&value, MAX_FIELD_LENGTH)),
+                    value => value,
+                };
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, json!(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, json!(value));
+    }
+}
+
+fn truncate(value: &str, max_length: usize) -> String {
+    if value.len() <= max_length {
+        return value.to_string();
+    }
+    let mut end = max_length;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    value[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn capture(filter: &str, debug_sample_ratio: f64, emit: impl FnOnce()) -> Vec<AppLog> {
+        let (layer, mut receiver) =
+            filtered_layer(parse_filter(filter).unwrap(), debug_sample_ratio);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, emit);
+
+        let mut logs = Vec::new();
+        while let Ok(log) = receiver.try_recv() {
+            logs.push(log);
+        }
+        logs
+    }
+
+    #[test]
+    fn test_logs_carry_fields_and_request_id() {
+        let logs = capture("warn", 1.0, || {
+            let span = tracing::info_span!("request", request_id = "req-1");
+            let _entered = span.enter();
+            tracing::info!("not stored");
+            tracing::warn!(attempt = 3, "Slow query took {}ms", 1200);
+        });
+
+        assert_eq!(logs.len(), 1);
+        let event = logs.into_iter().next().unwrap().into_event("starter");
+        assert_eq!(event.event_type, "log");
+        assert_eq!(event.source, "starter");
+        assert_eq!(event.level.as_deref(), Some("warn"));
+        assert_eq!(event.message.as_deref(), Some("Slow query took 1200ms"));
+        assert_eq!(event.tags["attempt"], json!(3));
+        assert_eq!(event.tags["request_id"], json!("req-1"));
+        assert_eq!(event.tags["module"], json!(module_path!()));
+    }
+
+    #[test]
+    fn test_debug_logs_are_sampled() {
+        let logs = capture("debug", 0.0, || {
+            tracing::debug!("dropped by sampling");
+            tracing::info!("kept");
+        });
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].message.as_deref(), Some("kept"));
+    }
+
+    #[test]
+    fn test_filter_directives_and_excluded_targets() {
+        let logs = capture("warn,noisy=error", 1.0, || {
+            tracing::warn!(target: "noisy", "filtered out");
+            tracing::warn!(target: "sqlx::query", "never stored");
+            tracing::warn!(target: "other", "stored");
+        });
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].target, "other");
+    }
+}
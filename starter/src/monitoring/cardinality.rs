@@ -0,0 +1,245 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::histogram::{BUCKET_LABEL, QUANTILE_LABEL};
+use crate::monitoring::models::{CreateMetricRequest, MetricCardinality};
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+/// Label marking the series that collects metrics over a name's limit
+pub const OVERFLOW_LABEL: &str = "overflow";
+
+/// Metric names listed in the monitoring stats
+pub const TOP_CARDINALITY_NAMES: i64 = 20;
+
+/// What happens to metrics that would start a series over the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CardinalityLimitAction {
+    /// Reject them with a `CARDINALITY_LIMIT_EXCEEDED` error
+    #[default]
+    Reject,
+    /// Store them in the name's overflow series, dropping their labels
+    /// except `le` and `quantile`
+    Truncate,
+}
+
+type SeriesKey = (Uuid, String, BTreeMap<String, String>);
+
+fn series_key(metric: &CreateMetricRequest) -> SeriesKey {
+    (
+        metric.tenant_id.unwrap_or(DEFAULT_TENANT_ID),
+        metric.name.clone(),
+        metric
+            .labels
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+    )
+}
+
+/// Labels of the overflow series a metric is moved into
+fn overflow_labels(labels: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut kept: HashMap<String, String> = labels
+        .iter()
+        .filter(|(label, _)| *label == BUCKET_LABEL || *label == QUANTILE_LABEL)
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    kept.insert(OVERFLOW_LABEL.to_string(), "true".to_string());
+    kept
+}
+
+/// Apply the per-name series limit to metrics about to be stored
+///
+/// Each tenant has its own series of a name, counted against its own limit.
+/// Returns whether each row may be stored. Rows of known series always may;
+/// new series are admitted in order while their name is under the limit.
+/// With [`CardinalityLimitAction::Truncate`] the other rows are moved into
+/// the overflow series, which does not count toward the limit, and may be
+/// stored too. Concurrent requests may briefly overshoot the limit.
+pub async fn limit_series(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    rows: &mut [CreateMetricRequest],
+) -> Result<Vec<bool>> {
+    let max_series = config.metric_max_series_per_name;
+    if max_series == 0 || rows.is_empty() {
+        return Ok(vec![true; rows.len()]);
+    }
+
+    let tenant_ids = tenant_ids(rows);
+    let names: Vec<String> = rows.iter().map(|row| row.name.clone()).collect();
+    let labels: Vec<serde_json::Value> = rows.iter().map(|row| json!(row.labels)).collect();
+    let known: HashSet<SeriesKey> = sqlx::query!(
+        r#"
+        SELECT DISTINCT s.tenant_id, s.name, s.labels
+        FROM metric_series s
+        JOIN UNNEST($1::UUID[], $2::TEXT[], $3::JSONB[]) AS r(tenant_id, name, labels)
+          ON s.tenant_id = r.tenant_id AND s.name = r.name AND s.labels = r.labels
+        "#,
+        &tenant_ids,
+        &names,
+        &labels
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .into_iter()
+    .filter_map(|row| {
+        Some((
+            row.tenant_id,
+            row.name,
+            serde_json::from_value(row.labels).ok()?,
+        ))
+    })
+    .collect();
+
+    let mut counts: HashMap<(Uuid, String), i64> = sqlx::query!(
+        r#"
+        SELECT s.tenant_id, s.name, count(*) AS "series!"
+        FROM metric_series s
+        JOIN (SELECT DISTINCT * FROM UNNEST($1::UUID[], $2::TEXT[]) AS r(tenant_id, name)) r
+          ON s.tenant_id = r.tenant_id AND s.name = r.name
+        GROUP BY s.tenant_id, s.name
+        "#,
+        &tenant_ids,
+        &names
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .into_iter()
+    .map(|row| ((row.tenant_id, row.name), row.series))
+    .collect();
+
+    let mut admitted = HashSet::new();
+    let mut fits = Vec::with_capacity(rows.len());
+    for row in rows.iter_mut() {
+        let key = series_key(row);
+        if known.contains(&key) || admitted.contains(&key) {
+            fits.push(true);
+            continue;
+        }
+
+        let count = counts.entry((key.0, row.name.clone())).or_default();
+        if *count < i64::from(max_series) {
+            *count += 1;
+            admitted.insert(key);
+            fits.push(true);
+        } else if config.metric_cardinality_action == CardinalityLimitAction::Truncate {
+            row.labels = overflow_labels(&row.labels);
+            fits.push(true);
+        } else {
+            fits.push(false);
+        }
+    }
+    Ok(fits)
+}
+
+/// Record the series of stored metrics
+///
+/// `last_seen_at` is refreshed at most hourly to keep ingestion writes low.
+pub async fn record_series(conn: &mut DbConn, rows: &[CreateMetricRequest]) -> Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let tenant_ids = tenant_ids(rows);
+    let names: Vec<String> = rows.iter().map(|row| row.name.clone()).collect();
+    let labels: Vec<serde_json::Value> = rows.iter().map(|row| json!(row.labels)).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO metric_series (tenant_id, name, labels)
+        SELECT DISTINCT tenant_id, name, labels
+        FROM UNNEST($1::UUID[], $2::TEXT[], $3::JSONB[]) AS r(tenant_id, name, labels)
+        ON CONFLICT (tenant_id, name, labels) DO UPDATE
+        SET last_seen_at = NOW()
+        WHERE metric_series.last_seen_at < NOW() - INTERVAL '1 hour'
+        "#,
+        &tenant_ids,
+        &names,
+        &labels
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(())
+}
+
+/// Tenants of metrics about to be stored, like they are stored
+fn tenant_ids(rows: &[CreateMetricRequest]) -> Vec<Uuid> {
+    rows.iter()
+        .map(|row| row.tenant_id.unwrap_or(DEFAULT_TENANT_ID))
+        .collect()
+}
+
+/// Forget series not seen within their metric's raw retention
+///
+/// Their raw samples are pruned by then, so they no longer count toward the limit.
+pub async fn prune_series(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    now: DateTime<Utc>,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM metric_series s
+        WHERE s.last_seen_at < $1::TIMESTAMPTZ - INTERVAL '1 hour' - make_interval(days => COALESCE(
+            (SELECT p.raw_retention_days FROM metric_retention_policies p WHERE p.metric_name = s.name),
+            $2
+        ))
+        "#,
+        now,
+        config.metric_raw_retention_days as i32
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Metric names with the most series in a tenant, or in all, highest first
+pub async fn top_series_counts(
+    conn: &mut DbConn,
+    tenant_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<MetricCardinality>> {
+    sqlx::query_as!(
+        MetricCardinality,
+        r#"
+        SELECT name, count(*) AS "series!"
+        FROM metric_series
+        WHERE $2::UUID IS NULL OR tenant_id = $2
+        GROUP BY name
+        ORDER BY count(*) DESC, name
+        LIMIT $1
+        "#,
+        limit,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow_labels_keep_reserved_labels() {
+        let labels = HashMap::from([
+            ("user_id".to_string(), "42".to_string()),
+            ("le".to_string(), "0.5".to_string()),
+        ]);
+        let overflow = overflow_labels(&labels);
+        assert_eq!(overflow.len(), 2);
+        assert_eq!(overflow["le"], "0.5");
+        assert_eq!(overflow[OVERFLOW_LABEL], "true");
+    }
+}
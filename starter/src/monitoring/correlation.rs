@@ -0,0 +1,176 @@
+use crate::core::config::MonitoringConfig;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+
+/// Outcome of one correlation run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CorrelationRun {
+    /// Incidents opened for sources over the threshold
+    pub opened: u64,
+    /// Error events deduplicated into active incidents
+    pub attached: u64,
+    /// Incidents resolved after their quiet period
+    pub resolved: u64,
+}
+
+/// Background job that correlates error events into incidents
+pub async fn event_correlation_job(pool: DbPool, run_interval: Duration, config: MonitoringConfig) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = match pool.acquire().await {
+            Ok(mut conn) => correlate_events(conn.as_mut(), &config, Utc::now()).await,
+            Err(e) => Err(Error::from_sqlx(e)),
+        };
+        match result {
+            Ok(run) => {
+                if run != CorrelationRun::default() {
+                    info!(
+                        "Event correlation: {} incidents opened, {} events attached, {} incidents resolved",
+                        run.opened, run.attached, run.resolved
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to correlate events: {}", e);
+            }
+        }
+    }
+}
+
+/// Correlate error events recorded within the window before `now`
+///
+/// Error events (levels `error`, `critical` and `fatal`) match when they share
+/// a tenant, source and tag set. Once the uncorrelated matches within the window reach
+/// the threshold, an incident is opened for them; matching events then attach
+/// to that incident, once each, until it is resolved or closed. Incidents with
+/// no matching event for the quiet period resolve automatically.
+pub async fn correlate_events(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    now: DateTime<Utc>,
+) -> Result<CorrelationRun> {
+    let window_start = now - chrono::Duration::seconds(config.event_correlation_window_secs as i64);
+    let quiet_cutoff = now - chrono::Duration::seconds(config.event_correlation_quiet_secs as i64);
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let opened = open_incidents(&mut tx, window_start, config.event_correlation_threshold).await?;
+    let attached = attach_events(&mut tx, window_start).await?;
+    let resolved = resolve_quiet_incidents(&mut tx, quiet_cutoff).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(CorrelationRun {
+        opened,
+        attached,
+        resolved,
+    })
+}
+
+/// Open an incident for each tenant, source and tag set over the threshold without an active one
+async fn open_incidents(
+    conn: &mut DbConn,
+    window_start: DateTime<Utc>,
+    threshold: u32,
+) -> Result<u64> {
+    // Events already in a (possibly resolved) incident do not count again;
+    // the partial unique index keeps concurrent workers from opening duplicates
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO incidents (title, description, severity, started_at, correlation_key, tenant_id)
+        SELECT 'Error events from ' || source,
+               format('Opened automatically after %s error events with tags %s', event_count, tags),
+               'medium', first_recorded_at, correlation_key, tenant_id
+        FROM (
+            SELECT e.tenant_id, e.source, e.tags,
+                   md5(e.source || ':' || e.tags::TEXT) AS correlation_key,
+                   count(*) AS event_count, min(e.recorded_at) AS first_recorded_at
+            FROM events e
+            WHERE e.recorded_at >= $1
+              AND lower(e.level) IN ('error', 'critical', 'fatal')
+              AND NOT EXISTS (SELECT 1 FROM incident_correlated_events c WHERE c.event_id = e.id)
+            GROUP BY e.tenant_id, e.source, e.tags
+            HAVING count(*) >= $2
+        ) candidates
+        ON CONFLICT (tenant_id, correlation_key)
+            WHERE correlation_key IS NOT NULL AND status IN ('open', 'investigating')
+            DO NOTHING
+        "#,
+        window_start,
+        i64::from(threshold.max(1))
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Attach uncorrelated error events within the window to the active incident of their
+/// tenant and key
+async fn attach_events(conn: &mut DbConn, window_start: DateTime<Utc>) -> Result<u64> {
+    let attached = sqlx::query_scalar!(
+        r#"
+        WITH attached AS (
+            INSERT INTO incident_correlated_events (event_id, incident_id)
+            SELECT e.id, i.id
+            FROM events e
+            JOIN incidents i
+              ON i.tenant_id = e.tenant_id
+             AND i.correlation_key = md5(e.source || ':' || e.tags::TEXT)
+             AND i.status IN ('open', 'investigating')
+            WHERE e.recorded_at >= $1
+              AND lower(e.level) IN ('error', 'critical', 'fatal')
+            ON CONFLICT (event_id) DO NOTHING
+            RETURNING event_id, incident_id
+        ), counts AS (
+            SELECT a.incident_id, count(*) AS event_count, max(e.recorded_at) AS last_recorded_at
+            FROM attached a
+            JOIN events e ON e.id = a.event_id
+            GROUP BY a.incident_id
+        ), updated AS (
+            UPDATE incidents i
+            SET correlated_event_count = i.correlated_event_count + c.event_count::INTEGER,
+                last_correlated_at = GREATEST(i.last_correlated_at, c.last_recorded_at),
+                updated_at = NOW()
+            FROM counts c
+            WHERE i.id = c.incident_id
+            RETURNING c.event_count
+        )
+        SELECT COALESCE(sum(event_count), 0)::BIGINT AS "attached!" FROM updated
+        "#,
+        window_start
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(attached as u64)
+}
+
+/// Resolve active correlated incidents whose latest event is older than `quiet_cutoff`
+async fn resolve_quiet_incidents(conn: &mut DbConn, quiet_cutoff: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET status = 'resolved',
+            resolved_at = NOW(),
+            next_escalation_at = NULL,
+            updated_at = NOW()
+        WHERE correlation_key IS NOT NULL
+          AND status IN ('open', 'investigating')
+          AND last_correlated_at < $1
+        "#,
+        quiet_cutoff
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(result.rows_affected())
+}
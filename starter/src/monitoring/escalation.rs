@@ -0,0 +1,661 @@
+use crate::monitoring::models::{
+    CreateEscalationPolicyRequest, EscalationPolicy, EscalationStep, Incident, IncidentEscalation,
+    IncidentStatus, ResolveIncidentRequest, Validate,
+};
+use crate::monitoring::{oncall, services};
+use crate::tasks::services::{EmailMessage, EmailSender, TaskServices};
+use crate::tenants::services as tenant_services;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Maximum number of incidents escalated, or notifications sent, per run
+const ESCALATION_BATCH_SIZE: i64 = 100;
+
+const REASON_POLICY: &str = "policy";
+const REASON_MANUAL: &str = "manual";
+
+/// Background job that escalates unacknowledged incidents and emails the notified users
+pub async fn incident_escalation_job(pool: DbPool, run_interval: Duration, services: TaskServices) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        match run_escalations(&pool, services.email()).await {
+            Ok((escalated, notified)) => {
+                if escalated + notified > 0 {
+                    info!(
+                        "Incident escalation: {} incidents escalated, {} notifications sent",
+                        escalated, notified
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to run incident escalations: {}", e);
+            }
+        }
+    }
+}
+
+async fn run_escalations(pool: &DbPool, email: &dyn EmailSender) -> Result<(u64, u64)> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    let escalated = escalate_due_incidents(conn.as_mut(), Utc::now()).await?;
+    let notified = send_pending_notifications(conn.as_mut(), email).await?;
+    Ok((escalated, notified))
+}
+
+/// When the first step of a policy is due for an incident starting at `started_at`
+pub async fn first_step_due_at(
+    conn: &mut DbConn,
+    policy_id: Uuid,
+    started_at: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let delay_minutes = step_delay(conn, policy_id, 0)
+        .await?
+        .ok_or_else(|| Error::validation("escalation_policy_id", "Escalation policy not found"))?;
+    Ok(started_at + chrono::Duration::minutes(delay_minutes.into()))
+}
+
+async fn step_delay(conn: &mut DbConn, policy_id: Uuid, step_order: i32) -> Result<Option<i32>> {
+    sqlx::query_scalar!(
+        "SELECT delay_minutes FROM incident_escalation_steps WHERE policy_id = $1 AND step_order = $2",
+        policy_id,
+        step_order
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Notify the next step of every unacknowledged incident whose escalation is due by `now`
+///
+/// Returns the number of incidents escalated. Incidents whose policy has no
+/// further steps stop escalating.
+pub async fn escalate_due_incidents(conn: &mut DbConn, now: DateTime<Utc>) -> Result<u64> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    // SKIP LOCKED lets several workers share the queue without double notifications
+    let due = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM incidents
+        WHERE next_escalation_at <= $1
+          AND acknowledged_at IS NULL
+          AND status IN ('open', 'investigating')
+        ORDER BY next_escalation_at
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+        now,
+        ESCALATION_BATCH_SIZE
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut escalated = 0;
+    for incident_id in due {
+        let incident = services::find_incident_by_id(&mut tx, incident_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+        if escalate_to_next_step(&mut tx, &incident, REASON_POLICY, None, now)
+            .await?
+            .is_some()
+        {
+            escalated += 1;
+        }
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(escalated)
+}
+
+/// Assign the incident to its policy's next step and record the escalation
+///
+/// Returns `None`, and stops automatic escalation, when the incident has no
+/// policy or the policy has no further steps.
+async fn escalate_to_next_step(
+    conn: &mut DbConn,
+    incident: &Incident,
+    reason: &str,
+    triggered_by: Option<Uuid>,
+    now: DateTime<Utc>,
+) -> Result<Option<IncidentEscalation>> {
+    let next_step = incident.escalation_step.map_or(0, |step| step + 1);
+    let step = match incident.escalation_policy_id {
+        Some(policy_id) => sqlx::query_as!(
+            EscalationStep,
+            r#"
+            SELECT step_order, notify_user_id, notify_schedule_id, delay_minutes
+            FROM incident_escalation_steps
+            WHERE policy_id = $1 AND step_order = $2
+            "#,
+            policy_id,
+            next_step
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .map(|step| (policy_id, step)),
+        None => None,
+    };
+
+    let Some((policy_id, step)) = step else {
+        sqlx::query!(
+            "UPDATE incidents SET next_escalation_at = NULL, updated_at = NOW() WHERE id = $1",
+            incident.id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+        return Ok(None);
+    };
+
+    // Schedule steps notify whoever is on call at the time of the escalation
+    let notify_user_id = match step.notify_schedule_id {
+        Some(schedule_id) => {
+            let user_id = oncall::find_oncall_user(conn, schedule_id, now).await?;
+            if user_id.is_none() {
+                warn!(
+                    "Nobody is on call for schedule {}; incident {} stays with its assignee",
+                    schedule_id, incident.id
+                );
+            }
+            user_id
+        }
+        None => step.notify_user_id,
+    };
+
+    // Keep escalating on schedule only while nobody has acknowledged
+    let next_escalation_at = match incident.acknowledged_at {
+        Some(_) => None,
+        None => step_delay(conn, policy_id, next_step + 1)
+            .await?
+            .map(|delay| now + chrono::Duration::minutes(delay.into())),
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET assigned_to = COALESCE($2, assigned_to),
+            escalation_step = $3,
+            next_escalation_at = $4,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        incident.id,
+        notify_user_id,
+        step.step_order,
+        next_escalation_at
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let escalation = sqlx::query_as!(
+        IncidentEscalation,
+        r#"
+        INSERT INTO incident_escalations
+            (incident_id, step_order, notified_user_id, oncall_schedule_id, reason,
+             triggered_by, escalated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, incident_id, step_order, notified_user_id, oncall_schedule_id, reason,
+                  triggered_by, escalated_at, notified_at
+        "#,
+        incident.id,
+        step.step_order,
+        notify_user_id,
+        step.notify_schedule_id,
+        reason,
+        triggered_by,
+        now
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(Some(escalation))
+}
+
+/// Email every escalation notification not yet delivered
+///
+/// Failed sends are logged and retried on the next run. Returns the number
+/// of notifications sent.
+pub async fn send_pending_notifications(conn: &mut DbConn, email: &dyn EmailSender) -> Result<u64> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let pending = sqlx::query!(
+        r#"
+        SELECT e.id, e.step_order, e.reason, u.email, i.title, i.severity, i.id AS incident_id
+        FROM incident_escalations e
+        JOIN users u ON u.id = e.notified_user_id
+        JOIN incidents i ON i.id = e.incident_id
+        WHERE e.notified_at IS NULL
+        ORDER BY e.escalated_at
+        LIMIT $1
+        FOR UPDATE OF e SKIP LOCKED
+        "#,
+        ESCALATION_BATCH_SIZE
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut sent = 0;
+    for notification in pending {
+        let message = EmailMessage {
+            to: notification.email,
+            subject: format!(
+                "[{}] Incident escalated: {}",
+                notification.severity, notification.title
+            ),
+            body: format!(
+                "Incident {} was escalated to you (step {}, {}).\n\nAcknowledge it with POST /api/v1/monitoring/incidents/{}/acknowledge to stop further escalation.",
+                notification.incident_id,
+                notification.step_order + 1,
+                notification.reason,
+                notification.incident_id
+            ),
+        };
+        if let Err(e) = email.send(&message).await {
+            warn!(
+                "Failed to send escalation notification {}: {}",
+                notification.id, e
+            );
+            continue;
+        }
+
+        sqlx::query!(
+            "UPDATE incident_escalations SET notified_at = NOW() WHERE id = $1",
+            notification.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+        sent += 1;
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(sent)
+}
+
+/// Lock an incident that is still open or under investigation
+async fn find_active_incident_for_update(conn: &mut DbConn, id: Uuid) -> Result<Incident> {
+    let incident = services::find_incident_by_id_for_update(conn, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    if matches!(
+        incident.status,
+        IncidentStatus::Resolved | IncidentStatus::Closed
+    ) {
+        return Err(Error::conflict("Incident is already resolved"));
+    }
+    Ok(incident)
+}
+
+/// Acknowledge an incident, stopping automatic escalation
+///
+/// Open incidents move to `investigating`, and unassigned incidents are
+/// assigned to the acknowledging user.
+pub async fn acknowledge_incident(conn: &mut DbConn, id: Uuid, user_id: Uuid) -> Result<Incident> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let incident = find_active_incident_for_update(&mut tx, id).await?;
+    if incident.acknowledged_at.is_some() {
+        return Err(Error::conflict("Incident is already acknowledged"));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET acknowledged_at = NOW(),
+            acknowledged_by = $2,
+            assigned_to = COALESCE(assigned_to, $2),
+            status = CASE WHEN status = 'open' THEN 'investigating' ELSE status END,
+            next_escalation_at = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let incident = services::find_incident_by_id(&mut tx, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(incident)
+}
+
+/// Resolve an incident, stopping automatic escalation
+pub async fn resolve_incident(
+    conn: &mut DbConn,
+    id: Uuid,
+    request: ResolveIncidentRequest,
+) -> Result<Incident> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    find_active_incident_for_update(&mut tx, id).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET status = 'resolved',
+            resolved_at = NOW(),
+            root_cause = COALESCE($2, root_cause),
+            next_escalation_at = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        request.root_cause
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let incident = services::find_incident_by_id(&mut tx, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(incident)
+}
+
+/// Escalate an incident to its policy's next step now, without waiting for the delay
+///
+/// The notification is delivered by the worker on its next run.
+pub async fn escalate_incident(
+    conn: &mut DbConn,
+    id: Uuid,
+    triggered_by: Uuid,
+) -> Result<Incident> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let incident = find_active_incident_for_update(&mut tx, id).await?;
+    if incident.escalation_policy_id.is_none() {
+        return Err(Error::conflict("Incident has no escalation policy"));
+    }
+    escalate_to_next_step(
+        &mut tx,
+        &incident,
+        REASON_MANUAL,
+        Some(triggered_by),
+        Utc::now(),
+    )
+    .await?
+    .ok_or_else(|| Error::conflict("Escalation policy has no further steps"))?;
+
+    let incident = services::find_incident_by_id(&mut tx, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Incident not found".to_string()))?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(incident)
+}
+
+/// Escalations recorded for an incident, oldest first
+pub async fn find_incident_escalations(
+    conn: &mut DbConn,
+    incident_id: Uuid,
+) -> Result<Vec<IncidentEscalation>> {
+    sqlx::query_as!(
+        IncidentEscalation,
+        r#"
+        SELECT id, incident_id, step_order, notified_user_id, oncall_schedule_id, reason,
+               triggered_by, escalated_at, notified_at
+        FROM incident_escalations
+        WHERE incident_id = $1
+        ORDER BY escalated_at, step_order
+        "#,
+        incident_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+// Escalation policy management functions
+
+/// Create a policy in the tenant; its steps notify the tenant's users and schedules
+pub async fn create_escalation_policy(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateEscalationPolicyRequest,
+    created_by: Option<Uuid>,
+) -> Result<EscalationPolicy> {
+    request.validate()?;
+    let user_ids: Vec<Uuid> = request
+        .steps
+        .iter()
+        .filter_map(|step| step.notify_user_id)
+        .collect();
+    tenant_services::require_users_in_tenant(conn, "notify_user_id", &user_ids, tenant_id).await?;
+    let schedule_ids: Vec<Uuid> = request
+        .steps
+        .iter()
+        .filter_map(|step| step.notify_schedule_id)
+        .collect();
+    oncall::require_schedules_in_tenant(conn, &schedule_ids, tenant_id).await?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let policy = sqlx::query!(
+        r#"
+        INSERT INTO incident_escalation_policies (name, description, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, created_at, updated_at
+        "#,
+        request.name.trim(),
+        request.description,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| map_write_error(e, &request.name))?;
+
+    let mut steps = Vec::with_capacity(request.steps.len());
+    for (step_order, step) in (0..).zip(&request.steps) {
+        sqlx::query!(
+            r#"
+            INSERT INTO incident_escalation_steps
+                (policy_id, step_order, notify_user_id, notify_schedule_id, delay_minutes)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            policy.id,
+            step_order,
+            step.notify_user_id,
+            step.notify_schedule_id,
+            step.delay_minutes
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| map_write_error(e, &request.name))?;
+
+        steps.push(EscalationStep {
+            step_order,
+            notify_user_id: step.notify_user_id,
+            notify_schedule_id: step.notify_schedule_id,
+            delay_minutes: step.delay_minutes,
+        });
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(EscalationPolicy {
+        id: policy.id,
+        name: request.name.trim().to_string(),
+        description: request.description,
+        steps,
+        created_by,
+        created_at: policy.created_at,
+        updated_at: policy.updated_at,
+    })
+}
+
+/// Policies of the tenant, by name
+pub async fn find_escalation_policies(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<Vec<EscalationPolicy>> {
+    let policies = sqlx::query!(
+        r#"
+        SELECT id, name, description, created_by, created_at, updated_at
+        FROM incident_escalation_policies
+        WHERE tenant_id = $1
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut result = Vec::with_capacity(policies.len());
+    for policy in policies {
+        result.push(EscalationPolicy {
+            steps: find_policy_steps(conn, policy.id).await?,
+            id: policy.id,
+            name: policy.name,
+            description: policy.description,
+            created_by: policy.created_by,
+            created_at: policy.created_at,
+            updated_at: policy.updated_at,
+        });
+    }
+    Ok(result)
+}
+
+/// Policy of the tenant with the given ID
+pub async fn find_escalation_policy_by_id(
+    conn: &mut DbConn,
+    id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<EscalationPolicy>> {
+    let policy = sqlx::query!(
+        r#"
+        SELECT id, name, description, created_by, created_at, updated_at
+        FROM incident_escalation_policies
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    match policy {
+        Some(policy) => Ok(Some(EscalationPolicy {
+            steps: find_policy_steps(conn, policy.id).await?,
+            id: policy.id,
+            name: policy.name,
+            description: policy.description,
+            created_by: policy.created_by,
+            created_at: policy.created_at,
+            updated_at: policy.updated_at,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Fail with a validation error unless the policy belongs to the tenant
+pub async fn require_policy_in_tenant(
+    conn: &mut DbConn,
+    policy_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<()> {
+    let found = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM incident_escalation_policies WHERE id = $1 AND tenant_id = $2
+        ) AS "exists!"
+        "#,
+        policy_id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !found {
+        return Err(Error::validation(
+            "escalation_policy_id",
+            "Escalation policy not found",
+        ));
+    }
+    Ok(())
+}
+
+async fn find_policy_steps(conn: &mut DbConn, policy_id: Uuid) -> Result<Vec<EscalationStep>> {
+    sqlx::query_as!(
+        EscalationStep,
+        r#"
+        SELECT step_order, notify_user_id, notify_schedule_id, delay_minutes
+        FROM incident_escalation_steps
+        WHERE policy_id = $1
+        ORDER BY step_order
+        "#,
+        policy_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a policy; incidents using it stop escalating
+pub async fn delete_escalation_policy(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET next_escalation_at = NULL, updated_at = NOW()
+        WHERE escalation_policy_id = $1 AND next_escalation_at IS NOT NULL
+        "#,
+        id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let result = sqlx::query!(
+        "DELETE FROM incident_escalation_policies WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Escalation policy not found".to_string()));
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+fn map_write_error(err: sqlx::Error, name: &str) -> Error {
+    match &err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::Conflict(format!(
+            "Escalation policy '{}' already exists",
+            name.trim()
+        )),
+        sqlx::Error::Database(db)
+            if db.is_foreign_key_violation()
+                && db.constraint().is_some_and(|c| c.contains("schedule")) =>
+        {
+            Error::validation("notify_schedule_id", "On-call schedule not found")
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("notify_user_id", "User not found")
+        }
+        _ => Error::from_sqlx(err),
+    }
+}
@@ -0,0 +1,312 @@
+use crate::core::config::MonitoringConfig;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+
+/// Maximum number of events deleted per statement to keep transactions short
+const PRUNE_BATCH_SIZE: i64 = 5000;
+
+/// How long events of one type and level are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRetentionRule {
+    /// `None` matches any event type
+    pub event_type: Option<String>,
+    /// `None` matches any level
+    pub level: Option<String>,
+    /// 0 keeps matching events forever
+    pub days: u32,
+}
+
+/// Event retention rules, most specific first, ending with the default
+#[derive(Debug, Clone)]
+pub struct EventRetention {
+    rules: Vec<EventRetentionRule>,
+}
+
+/// Events past their retention, per event type and level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredEvents {
+    pub event_type: String,
+    pub level: Option<String>,
+    pub retention_days: i32,
+    pub count: i64,
+}
+
+impl EventRetention {
+    pub fn from_config(config: &MonitoringConfig) -> Result<Self> {
+        Self::parse(&config.event_retention_rules, config.event_retention_days)
+    }
+
+    /// Parse comma-separated rules such as `debug=3,error=180,trace:*=7`
+    ///
+    /// A rule's selector is a level, `event_type:level` or `event_type:*`.
+    /// Rules naming both win over level-only rules, which win over type-only
+    /// rules; events matching none are kept for `default_days`.
+    pub fn parse(rules: &str, default_days: u32) -> Result<Self> {
+        let mut parsed = rules
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(parse_rule)
+            .collect::<Result<Vec<_>>>()?;
+
+        for (i, rule) in parsed.iter().enumerate() {
+            if parsed[..i]
+                .iter()
+                .any(|other| other.event_type == rule.event_type && other.level == rule.level)
+            {
+                return Err(Error::ConfigurationError(format!(
+                    "Duplicate event retention rule for {}",
+                    selector(rule)
+                )));
+            }
+        }
+
+        parsed.sort_by_key(|rule| match (&rule.event_type, &rule.level) {
+            (Some(_), Some(_)) => 0,
+            (None, Some(_)) => 1,
+            _ => 2,
+        });
+        parsed.push(EventRetentionRule {
+            event_type: None,
+            level: None,
+            days: default_days,
+        });
+        Ok(Self { rules: parsed })
+    }
+
+    /// Days events of this type and level are kept (0 = forever)
+    pub fn days_for(&self, event_type: &str, level: Option<&str>) -> u32 {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.event_type
+                    .as_deref()
+                    .is_none_or(|rule_type| rule_type == event_type)
+                    && rule
+                        .level
+                        .as_deref()
+                        .is_none_or(|rule_level| Some(rule_level) == level)
+            })
+            .map_or(0, |rule| rule.days)
+    }
+
+    /// Shortest retention of any rule, or `None` when every event is kept forever
+    fn shortest_days(&self) -> Option<u32> {
+        self.rules
+            .iter()
+            .map(|rule| rule.days)
+            .filter(|days| *days > 0)
+            .min()
+    }
+
+    fn columns(&self) -> (Vec<Option<String>>, Vec<Option<String>>, Vec<i32>) {
+        (
+            self.rules
+                .iter()
+                .map(|rule| rule.event_type.clone())
+                .collect(),
+            self.rules.iter().map(|rule| rule.level.clone()).collect(),
+            self.rules.iter().map(|rule| rule.days as i32).collect(),
+        )
+    }
+}
+
+fn parse_rule(rule: &str) -> Result<EventRetentionRule> {
+    let invalid = || {
+        Error::ConfigurationError(format!(
+            "Invalid event retention rule '{rule}'; use level=days, event_type:level=days or event_type:*=days"
+        ))
+    };
+
+    let (selector, days) = rule.split_once('=').ok_or_else(invalid)?;
+    let days: u32 = days.trim().parse().map_err(|_| invalid())?;
+    let (event_type, level) = match selector.trim().split_once(':') {
+        Some((event_type, level)) => (Some(event_type.trim()), level.trim()),
+        None => (None, selector.trim()),
+    };
+    if event_type.is_some_and(|event_type| event_type.is_empty() || event_type == "*")
+        || level.is_empty()
+        || (event_type.is_none() && level == "*")
+    {
+        return Err(invalid());
+    }
+
+    Ok(EventRetentionRule {
+        event_type: event_type.map(str::to_string),
+        level: (level != "*").then(|| level.to_string()),
+        days,
+    })
+}
+
+fn selector(rule: &EventRetentionRule) -> String {
+    match (&rule.event_type, &rule.level) {
+        (Some(event_type), Some(level)) => format!("{event_type}:{level}"),
+        (Some(event_type), None) => format!("{event_type}:*"),
+        (None, Some(level)) => level.clone(),
+        (None, None) => "*".to_string(),
+    }
+}
+
+/// Background job that deletes events past their retention
+pub async fn event_retention_job(pool: DbPool, run_interval: Duration, config: MonitoringConfig) {
+    let retention = match EventRetention::from_config(&config) {
+        Ok(retention) => retention,
+        Err(e) => {
+            error!("Event retention disabled: {}", e);
+            return;
+        }
+    };
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            prune_events(conn.as_mut(), &retention, Utc::now()).await
+        }
+        .await;
+        match result {
+            Ok(deleted) if deleted > 0 => info!("Event retention: {} events pruned", deleted),
+            Ok(_) => {}
+            Err(e) => error!("Failed to prune events: {}", e),
+        }
+    }
+}
+
+/// Delete events recorded before their retention, returning the number deleted
+pub async fn prune_events(
+    conn: &mut DbConn,
+    retention: &EventRetention,
+    now: DateTime<Utc>,
+) -> Result<u64> {
+    let Some(shortest_days) = retention.shortest_days() else {
+        return Ok(0);
+    };
+    let (event_types, levels, days) = retention.columns();
+
+    let mut deleted = 0;
+    loop {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM events
+            WHERE id IN (
+                SELECT e.id FROM events e
+                CROSS JOIN LATERAL (
+                    SELECT r.days
+                    FROM UNNEST($2::TEXT[], $3::TEXT[], $4::INT[])
+                        WITH ORDINALITY AS r(event_type, level, days, position)
+                    WHERE (r.event_type IS NULL OR r.event_type = e.event_type)
+                      AND (r.level IS NULL OR r.level = e.level)
+                    ORDER BY r.position
+                    LIMIT 1
+                ) rule
+                WHERE e.recorded_at < $1::TIMESTAMPTZ - make_interval(days => $5)
+                  AND rule.days > 0
+                  AND e.recorded_at < $1::TIMESTAMPTZ - make_interval(days => rule.days)
+                LIMIT $6
+            )
+            "#,
+            now,
+            &event_types as &[Option<String>],
+            &levels as &[Option<String>],
+            &days,
+            shortest_days as i32,
+            PRUNE_BATCH_SIZE
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        deleted += result.rows_affected();
+        if result.rows_affected() < PRUNE_BATCH_SIZE as u64 {
+            break;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Count the events [`prune_events`] would delete, per event type and level
+pub async fn count_expired_events(
+    conn: &mut DbConn,
+    retention: &EventRetention,
+    now: DateTime<Utc>,
+) -> Result<Vec<ExpiredEvents>> {
+    let Some(shortest_days) = retention.shortest_days() else {
+        return Ok(Vec::new());
+    };
+    let (event_types, levels, days) = retention.columns();
+
+    sqlx::query_as!(
+        ExpiredEvents,
+        r#"
+        SELECT e.event_type, e.level, rule.days AS "retention_days!", count(*) AS "count!"
+        FROM events e
+        CROSS JOIN LATERAL (
+            SELECT r.days
+            FROM UNNEST($2::TEXT[], $3::TEXT[], $4::INT[])
+                WITH ORDINALITY AS r(event_type, level, days, position)
+            WHERE (r.event_type IS NULL OR r.event_type = e.event_type)
+              AND (r.level IS NULL OR r.level = e.level)
+            ORDER BY r.position
+            LIMIT 1
+        ) rule
+        WHERE e.recorded_at < $1::TIMESTAMPTZ - make_interval(days => $5)
+          AND rule.days > 0
+          AND e.recorded_at < $1::TIMESTAMPTZ - make_interval(days => rule.days)
+        GROUP BY e.event_type, e.level, rule.days
+        ORDER BY e.event_type, e.level
+        "#,
+        now,
+        &event_types as &[Option<String>],
+        &levels as &[Option<String>],
+        &days,
+        shortest_days as i32
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_rule_wins() {
+        let retention =
+            EventRetention::parse("trace:*=7, debug=3, error=180, log:error=365", 30).unwrap();
+
+        assert_eq!(retention.days_for("log", Some("error")), 365);
+        assert_eq!(retention.days_for("trace", Some("error")), 180);
+        assert_eq!(retention.days_for("trace", Some("info")), 7);
+        assert_eq!(retention.days_for("log", Some("debug")), 3);
+        assert_eq!(retention.days_for("log", Some("info")), 30);
+        assert_eq!(retention.days_for("log", None), 30);
+        assert_eq!(retention.shortest_days(), Some(3));
+    }
+
+    #[test]
+    fn test_forever_rules() {
+        let retention = EventRetention::parse("error=0", 0).unwrap();
+        assert_eq!(retention.days_for("log", Some("error")), 0);
+        assert_eq!(retention.shortest_days(), None);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        for rules in [
+            "debug",
+            "debug=x",
+            "*=3",
+            ":debug=3",
+            "log:=3",
+            "debug=3,debug=5",
+        ] {
+            assert!(EventRetention::parse(rules, 30).is_err(), "{rules}");
+        }
+    }
+}
@@ -0,0 +1,404 @@
+//! Grafana JSON datasource API
+//!
+//! Serves the endpoints of the Grafana JSON datasource plugin
+//! (`simpod-json-datasource`) on top of the aggregated metric query, so
+//! Grafana can chart stored metrics directly. A panel target is a metric
+//! name; its payload holds the aggregation, label matchers and grouping
+//! labels. Dashboard ad hoc filters become label matchers.
+
+use crate::monitoring::models::{MetricAggregation, MetricQueryResult, MetricResolution};
+use crate::monitoring::query::{
+    LabelMatcher, MAX_QUERY_STEPS, MatchOp, MetricQuery, parse_group_by, validate_label_key,
+};
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+/// Maximum metric names, label names or label values listed
+const MAX_LISTED_ITEMS: i64 = 1000;
+
+/// Maximum targets in one query
+pub const MAX_QUERY_TARGETS: usize = 20;
+
+/// Aggregations offered in the query editor
+const AGGREGATION_OPTIONS: [&str; 9] = [
+    "avg", "sum", "min", "max", "count", "p50", "p90", "p95", "p99",
+];
+
+// Requests
+
+/// Body of `POST /metrics`; `metric` is set when editing a target
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct GrafanaMetricsRequest {
+    pub metric: Option<String>,
+}
+
+/// Body of `POST /tag-values`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GrafanaTagValuesRequest {
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GrafanaQueryRange {
+    #[schema(format = "date-time")]
+    pub from: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaQueryTarget {
+    /// Metric name
+    #[serde(default)]
+    pub target: String,
+    #[serde(default)]
+    pub ref_id: String,
+    #[serde(default)]
+    pub hide: bool,
+    /// `aggregation`, `labels` (matchers) and `by` (grouping labels), all optional
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct GrafanaAdhocFilter {
+    pub key: String,
+    pub operator: String,
+    pub value: String,
+}
+
+/// Body of `POST /query`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GrafanaQueryRequest {
+    pub range: GrafanaQueryRange,
+    /// Panel interval; used as the step, in milliseconds
+    pub interval_ms: Option<i64>,
+    pub max_data_points: Option<i64>,
+    #[serde(default)]
+    pub targets: Vec<GrafanaQueryTarget>,
+    #[serde(default)]
+    pub adhoc_filters: Vec<GrafanaAdhocFilter>,
+}
+
+// Responses
+
+/// A selectable metric, with the payload fields its targets accept
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GrafanaMetricOption {
+    pub label: String,
+    pub value: String,
+    #[schema(value_type = Vec<Object>)]
+    pub payloads: Vec<Value>,
+}
+
+/// A time series in the format Grafana expects: `[value, unix milliseconds]` pairs
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GrafanaTimeSeries {
+    pub target: String,
+    #[serde(rename = "refId")]
+    pub ref_id: String,
+    #[schema(value_type = Vec<Vec<f64>>)]
+    pub datapoints: Vec<(f64, i64)>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GrafanaTagKey {
+    #[serde(rename = "type")]
+    pub key_type: &'static str,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GrafanaTagValue {
+    pub text: String,
+}
+
+/// Payload fields offered for every metric in the query editor
+fn payload_options() -> Vec<Value> {
+    let aggregations: Vec<Value> = AGGREGATION_OPTIONS
+        .iter()
+        .map(|aggregation| json!({"label": aggregation, "value": aggregation}))
+        .collect();
+    vec![
+        json!({
+            "label": "Aggregation",
+            "name": "aggregation",
+            "type": "select",
+            "placeholder": "avg",
+            "options": aggregations,
+        }),
+        json!({
+            "label": "Label matchers",
+            "name": "labels",
+            "type": "input",
+            "placeholder": "env=prod,host=~web-.*",
+        }),
+        json!({
+            "label": "Group by",
+            "name": "by",
+            "type": "input",
+            "placeholder": "host",
+        }),
+    ]
+}
+
+/// List the tenant's metric names containing `search`, for the query editor
+pub async fn list_metrics(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    search: Option<&str>,
+) -> Result<Vec<GrafanaMetricOption>> {
+    let names = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT name AS "name!"
+        FROM metric_series
+        WHERE tenant_id = $1 AND ($2::TEXT IS NULL OR strpos(name, $2) > 0)
+        ORDER BY name
+        LIMIT $3
+        "#,
+        tenant_id,
+        search.filter(|search| !search.is_empty()),
+        MAX_LISTED_ITEMS
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(names
+        .into_iter()
+        .map(|name| GrafanaMetricOption {
+            label: name.clone(),
+            value: name,
+            payloads: payload_options(),
+        })
+        .collect())
+}
+
+/// List the tenant's label names, for ad hoc filters
+pub async fn list_tag_keys(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<GrafanaTagKey>> {
+    let keys = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT jsonb_object_keys(labels) AS "key!"
+        FROM metric_series
+        WHERE tenant_id = $1
+        ORDER BY 1
+        LIMIT $2
+        "#,
+        tenant_id,
+        MAX_LISTED_ITEMS
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(keys
+        .into_iter()
+        .map(|text| GrafanaTagKey {
+            key_type: "string",
+            text,
+        })
+        .collect())
+}
+
+/// List the values of one of the tenant's labels, for ad hoc filters
+pub async fn list_tag_values(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    key: &str,
+) -> Result<Vec<GrafanaTagValue>> {
+    let values = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT labels->>$2 AS "value!"
+        FROM metric_series
+        WHERE tenant_id = $1 AND labels ? $2
+        ORDER BY 1
+        LIMIT $3
+        "#,
+        tenant_id,
+        key,
+        MAX_LISTED_ITEMS
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(values
+        .into_iter()
+        .map(|text| GrafanaTagValue { text })
+        .collect())
+}
+
+impl GrafanaQueryRequest {
+    /// Build the metric queries of the visible targets over the tenant's
+    /// metrics, with their refIds
+    pub fn metric_queries(&self, tenant_id: Uuid) -> Result<Vec<(String, MetricQuery)>> {
+        let targets: Vec<_> = self
+            .targets
+            .iter()
+            .filter(|target| !target.hide && !target.target.is_empty())
+            .collect();
+        if targets.len() > MAX_QUERY_TARGETS {
+            return Err(Error::validation(
+                "targets",
+                &format!("At most {MAX_QUERY_TARGETS} targets per query"),
+            ));
+        }
+
+        let adhoc_matchers = self
+            .adhoc_filters
+            .iter()
+            .map(adhoc_matcher)
+            .collect::<Result<Vec<_>>>()?;
+        let step_secs = self.step_secs();
+
+        targets
+            .into_iter()
+            .map(|target| {
+                let payload_str = |field: &str| target.payload.get(field).and_then(Value::as_str);
+                let mut matchers =
+                    LabelMatcher::parse_list(payload_str("labels").unwrap_or_default())?;
+                matchers.extend(adhoc_matchers.iter().cloned());
+                let query = MetricQuery {
+                    tenant_id,
+                    name: target.target.clone(),
+                    matchers,
+                    group_by: parse_group_by(payload_str("by").unwrap_or_default())?,
+                    start_time: self.range.from,
+                    end_time: self.range.to,
+                    step_secs: Some(step_secs),
+                    aggregation: payload_str("aggregation")
+                        .filter(|aggregation| !aggregation.is_empty())
+                        .map(str::parse)
+                        .transpose()?
+                        .unwrap_or(MetricAggregation::Avg),
+                    resolution: MetricResolution::Auto,
+                };
+                Ok((target.ref_id.clone(), query))
+            })
+            .collect()
+    }
+
+    /// Step from the panel interval, widened to fit `maxDataPoints` and the step limit
+    fn step_secs(&self) -> i64 {
+        let range_secs = (self.range.to - self.range.from).num_seconds().max(1);
+        let interval_secs = self.interval_ms.unwrap_or(0) / 1000;
+        let max_points = self
+            .max_data_points
+            .filter(|points| *points > 0)
+            .unwrap_or(MAX_QUERY_STEPS)
+            .min(MAX_QUERY_STEPS);
+        interval_secs
+            .max((range_secs + max_points - 1) / max_points)
+            .max(1)
+    }
+}
+
+fn adhoc_matcher(filter: &GrafanaAdhocFilter) -> Result<LabelMatcher> {
+    let op = match filter.operator.as_str() {
+        "=" => MatchOp::Equal,
+        "!=" => MatchOp::NotEqual,
+        "=~" => MatchOp::Regex,
+        "!~" => MatchOp::NotRegex,
+        operator => {
+            return Err(Error::validation(
+                "adhocFilters",
+                &format!("Unsupported filter operator '{operator}'"),
+            ));
+        }
+    };
+    validate_label_key(&filter.key, "adhocFilters")?;
+    Ok(LabelMatcher {
+        key: filter.key.clone(),
+        op,
+        value: filter.value.clone(),
+    })
+}
+
+/// Convert a query result into one Grafana series per label group
+pub fn time_series(ref_id: &str, result: MetricQueryResult) -> Vec<GrafanaTimeSeries> {
+    result
+        .series
+        .into_iter()
+        .map(|series| {
+            let labels: Vec<String> = series
+                .labels
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| format!("{key}=\"{}\"", value.as_str().unwrap_or_default()))
+                .collect();
+            GrafanaTimeSeries {
+                target: if labels.is_empty() {
+                    result.name.clone()
+                } else {
+                    format!("{}{{{}}}", result.name, labels.join(","))
+                },
+                ref_id: ref_id.to_string(),
+                datapoints: series
+                    .points
+                    .into_iter()
+                    .map(|point| (point.value, point.timestamp.timestamp_millis()))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: Value) -> GrafanaQueryRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_metric_queries_from_targets_and_filters() {
+        let request = request(json!({
+            "range": {"from": "2024-01-01T00:00:00Z", "to": "2024-01-01T01:00:00Z"},
+            "intervalMs": 30000,
+            "maxDataPoints": 1000,
+            "targets": [
+                {"refId": "A", "target": "latency", "payload": {"aggregation": "p95", "by": "host"}},
+                {"refId": "B", "target": "latency", "hide": true},
+                {"refId": "C", "target": ""}
+            ],
+            "adhocFilters": [{"key": "env", "operator": "=", "value": "prod"}]
+        }));
+
+        let queries = request.metric_queries(Uuid::nil()).unwrap();
+        assert_eq!(queries.len(), 1);
+        let (ref_id, query) = &queries[0];
+        assert_eq!(ref_id, "A");
+        assert_eq!(query.aggregation, MetricAggregation::Percentile(95));
+        assert_eq!(query.group_by, ["host"]);
+        assert_eq!(query.step_secs, Some(30));
+        assert_eq!(query.matchers[0].key, "env");
+        assert_eq!(query.matchers[0].value, "prod");
+    }
+
+    #[test]
+    fn test_step_fits_max_data_points() {
+        let request = request(json!({
+            "range": {"from": "2024-01-01T00:00:00Z", "to": "2024-01-02T00:00:00Z"},
+            "intervalMs": 1000,
+            "maxDataPoints": 100
+        }));
+        assert_eq!(request.step_secs(), 864);
+    }
+
+    #[test]
+    fn test_unsupported_adhoc_operator_is_rejected() {
+        let request = request(json!({
+            "range": {"from": "2024-01-01T00:00:00Z", "to": "2024-01-01T01:00:00Z"},
+            "targets": [{"refId": "A", "target": "latency"}],
+            "adhocFilters": [{"key": "env", "operator": "<", "value": "1"}]
+        }));
+        assert!(request.metric_queries(Uuid::nil()).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::monitoring::models::{CreateMetricRequest, MetricType};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Label holding a histogram bucket's upper bound
+pub const BUCKET_LABEL: &str = "le";
+/// Label holding a summary quantile
+pub const QUANTILE_LABEL: &str = "quantile";
+
+/// Expand a histogram into Prometheus-style rows
+///
+/// `counts` holds the observations per bucket, one more than `bounds` for the
+/// `+Inf` overflow bucket, and `count` the total. Rows are `<name>_count`,
+/// `<name>_sum` (when given) and cumulative `<name>_bucket` rows labelled `le`.
+pub fn histogram_rows(
+    name: &str,
+    labels: &HashMap<String, String>,
+    bounds: &[f64],
+    counts: &[u64],
+    sum: Option<f64>,
+    count: u64,
+    recorded_at: Option<DateTime<Utc>>,
+) -> Vec<CreateMetricRequest> {
+    let row = |name: String, value: f64, labels: HashMap<String, String>| CreateMetricRequest {
+        name,
+        metric_type: MetricType::Histogram,
+        value,
+        labels,
+        recorded_at,
+        tenant_id: None,
+    };
+
+    let mut rows = vec![row(format!("{name}_count"), count as f64, labels.clone())];
+    if let Some(sum) = sum {
+        rows.push(row(format!("{name}_sum"), sum, labels.clone()));
+    }
+
+    let mut cumulative = 0;
+    for (i, count) in counts.iter().enumerate() {
+        cumulative += count;
+        let le = bounds
+            .get(i)
+            .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+        let mut bucket_labels = labels.clone();
+        bucket_labels.insert(BUCKET_LABEL.to_string(), le);
+        rows.push(row(
+            format!("{name}_bucket"),
+            cumulative as f64,
+            bucket_labels,
+        ));
+    }
+    rows
+}
+
+/// Expand a summary into Prometheus-style rows
+///
+/// Rows are `<name>_count`, `<name>_sum` (when given) and `<name>` rows
+/// labelled `quantile`.
+pub fn summary_rows(
+    name: &str,
+    labels: &HashMap<String, String>,
+    quantiles: &[(f64, f64)],
+    sum: Option<f64>,
+    count: u64,
+    recorded_at: Option<DateTime<Utc>>,
+) -> Vec<CreateMetricRequest> {
+    let row = |name: String, value: f64, labels: HashMap<String, String>| CreateMetricRequest {
+        name,
+        metric_type: MetricType::Summary,
+        value,
+        labels,
+        recorded_at,
+        tenant_id: None,
+    };
+
+    let mut rows = vec![row(format!("{name}_count"), count as f64, labels.clone())];
+    if let Some(sum) = sum {
+        rows.push(row(format!("{name}_sum"), sum, labels.clone()));
+    }
+    for (quantile, value) in quantiles {
+        let mut quantile_labels = labels.clone();
+        quantile_labels.insert(QUANTILE_LABEL.to_string(), quantile.to_string());
+        rows.push(row(name.to_string(), *value, quantile_labels));
+    }
+    rows
+}
+
+/// Name of the histogram or summary a stored row belongs to
+pub fn family_name<'a>(name: &'a str, metric_type: &str) -> &'a str {
+    let suffixes: &[&str] = match metric_type {
+        "histogram" => &["_bucket", "_sum", "_count"],
+        "summary" => &["_sum", "_count"],
+        _ => &[],
+    };
+    suffixes
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
+/// Parse a bucket's `le` label; `+Inf` is the overflow bucket
+pub fn parse_bound(le: &str) -> Option<f64> {
+    match le {
+        "+Inf" | "Inf" | "inf" => Some(f64::INFINITY),
+        _ => le.parse().ok().filter(|bound: &f64| !bound.is_nan()),
+    }
+}
+
+/// Estimate a quantile from cumulative `(upper bound, count)` buckets
+///
+/// Interpolates linearly within the bucket holding the rank, like
+/// Prometheus' `histogram_quantile`. Returns `None` without observations or
+/// an `+Inf` bucket; ranks in the overflow bucket report the highest finite bound.
+pub fn histogram_quantile(quantile: f64, buckets: &mut [(f64, f64)]) -> Option<f64> {
+    buckets.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let &(last_bound, total) = buckets.last()?;
+    if last_bound != f64::INFINITY || total <= 0.0 {
+        return None;
+    }
+
+    let rank = quantile * total;
+    let index = buckets.iter().position(|&(_, count)| count >= rank)?;
+    let (upper, count) = buckets[index];
+    if upper == f64::INFINITY {
+        return Some(if index == 0 {
+            0.0
+        } else {
+            buckets[index - 1].0
+        });
+    }
+
+    let (lower, lower_count) = match index {
+        0 if upper > 0.0 => (0.0, 0.0),
+        0 => return Some(upper),
+        _ => buckets[index - 1],
+    };
+    if count == lower_count {
+        return Some(upper);
+    }
+    Some(lower + (upper - lower) * (rank - lower_count) / (count - lower_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_rows_are_cumulative() {
+        let labels = HashMap::from([("route".to_string(), "/".to_string())]);
+        let rows = histogram_rows(
+            "latency",
+            &labels,
+            &[0.1, 0.5],
+            &[1, 2, 1],
+            Some(1.2),
+            4,
+            None,
+        );
+
+        let names: Vec<_> = rows.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "latency_count",
+                "latency_sum",
+                "latency_bucket",
+                "latency_bucket",
+                "latency_bucket"
+            ]
+        );
+        assert_eq!(rows[0].value, 4.0);
+        assert_eq!(rows[3].labels["le"], "0.5");
+        assert_eq!(rows[3].value, 3.0);
+        assert_eq!(rows[4].labels["le"], "+Inf");
+        assert_eq!(rows[4].value, 4.0);
+        assert_eq!(rows[4].labels["route"], "/");
+    }
+
+    #[test]
+    fn test_histogram_quantile_interpolates_within_bucket() {
+        let mut buckets = vec![(f64::INFINITY, 10.0), (1.0, 2.0), (5.0, 8.0)];
+        // Rank 5 is halfway through the (1, 5] bucket
+        assert_eq!(histogram_quantile(0.5, &mut buckets), Some(3.0));
+        // Rank 1 is halfway through the first bucket, which starts at 0
+        assert_eq!(histogram_quantile(0.1, &mut buckets), Some(0.5));
+        // Ranks in the overflow bucket report the highest finite bound
+        assert_eq!(histogram_quantile(0.99, &mut buckets), Some(5.0));
+    }
+
+    #[test]
+    fn test_histogram_quantile_needs_observations() {
+        assert_eq!(histogram_quantile(0.5, &mut []), None);
+        assert_eq!(histogram_quantile(0.5, &mut [(0.1, 2.0)]), None);
+        assert_eq!(
+            histogram_quantile(0.5, &mut [(0.1, 0.0), (f64::INFINITY, 0.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_family_name_strips_suffixes() {
+        assert_eq!(family_name("latency_bucket", "histogram"), "latency");
+        assert_eq!(family_name("latency_count", "summary"), "latency");
+        assert_eq!(family_name("latency", "summary"), "latency");
+        assert_eq!(family_name("jobs_count", "counter"), "jobs_count");
+    }
+}
@@ -0,0 +1,449 @@
+use crate::core::cache::{AppCache, CacheCounts};
+use crate::monitoring::histogram::histogram_rows;
+use crate::monitoring::models::{AppActivity, CreateMetricRequest, MetricType};
+use crate::monitoring::services;
+use crate::tasks::metrics::{DURATION_BUCKETS, TaskMetrics, TaskTypeSnapshot};
+use crate::{DbConn, DbPool, Error, Result};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::interval;
+use tracing::error;
+use uuid::Uuid;
+
+/// Histogram of API request durations in seconds, by method, route and status
+pub const HTTP_REQUEST_DURATION_METRIC: &str = "http_server_request_duration_seconds";
+/// Open database connections, by process and state (`in_use` or `idle`)
+pub const DB_POOL_CONNECTIONS_METRIC: &str = "db_pool_connections";
+/// Configured maximum database connections, by process
+pub const DB_POOL_MAX_CONNECTIONS_METRIC: &str = "db_pool_max_connections";
+/// Cache lookups answered from the cache since the previous sample, by cache namespace
+pub const CACHE_HITS_METRIC: &str = "cache_hits";
+/// Cache lookups that went to the database since the previous sample, by cache namespace
+pub const CACHE_MISSES_METRIC: &str = "cache_misses";
+/// Share of cache lookups answered from the cache, from 0 to 1, by cache namespace
+pub const CACHE_HIT_RATIO_METRIC: &str = "cache_hit_ratio";
+/// Task attempts completed since the previous sample, by task type
+pub const TASK_COMPLETED_METRIC: &str = "task_completed";
+/// Task attempts failed since the previous sample, by task type and error class
+pub const TASK_FAILED_METRIC: &str = "task_failed";
+/// Failed task attempts scheduled for retry since the previous sample, by task type
+pub const TASK_RETRIED_METRIC: &str = "task_retried";
+/// Histogram of task handler durations in seconds, by task type
+pub const TASK_DURATION_METRIC: &str = "task_handler_duration_seconds";
+
+/// Upper bounds (seconds) of the request duration histogram buckets
+const REQUEST_DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// API requests observed since the metrics were last stored
+///
+/// Requests are keyed by their matched route rather than the URI, so the
+/// number of series stays bounded; unmatched requests are not counted.
+#[derive(Debug, Default)]
+pub struct HttpMetrics {
+    requests: Mutex<HashMap<RequestKey, RequestDurations>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RequestKey {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Debug, Default)]
+struct RequestDurations {
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`
+    counts: [u64; REQUEST_DURATION_BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+impl HttpMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished request
+    pub fn observe(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = REQUEST_DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(REQUEST_DURATION_BUCKETS.len());
+
+        let key = RequestKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        let durations = requests.entry(key).or_default();
+        durations.counts[bucket] += 1;
+        durations.sum += seconds;
+        durations.count += 1;
+    }
+
+    /// Histogram rows for the requests observed since the last call, which are then reset
+    pub fn take_rows(&self, recorded_at: DateTime<Utc>) -> Vec<CreateMetricRequest> {
+        let requests =
+            std::mem::take(&mut *self.requests.lock().unwrap_or_else(|e| e.into_inner()));
+
+        let mut rows = Vec::new();
+        for (key, durations) in requests {
+            let labels = HashMap::from([
+                ("method".to_string(), key.method),
+                ("route".to_string(), key.route),
+                ("status".to_string(), key.status.to_string()),
+            ]);
+            rows.extend(histogram_rows(
+                HTTP_REQUEST_DURATION_METRIC,
+                &labels,
+                &REQUEST_DURATION_BUCKETS,
+                &durations.counts,
+                Some(durations.sum),
+                durations.count,
+                Some(recorded_at),
+            ));
+        }
+        rows
+    }
+}
+
+/// Middleware recording the duration and status of each routed request
+pub async fn record_http_metrics(
+    State(metrics): State<Arc<HttpMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(route) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+    else {
+        return next.run(request).await;
+    };
+    let method = request.method().clone();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    metrics.observe(
+        method.as_str(),
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Gauges of the pool's open connections and its limit
+fn pool_rows(pool: &DbPool, process: &str, recorded_at: DateTime<Utc>) -> Vec<CreateMetricRequest> {
+    let idle = pool.num_idle() as f64;
+    let in_use = (f64::from(pool.size()) - idle).max(0.0);
+    let gauge = |name: &str, value: f64, state: Option<&str>| {
+        let mut labels = HashMap::from([("process".to_string(), process.to_string())]);
+        if let Some(state) = state {
+            labels.insert("state".to_string(), state.to_string());
+        }
+        CreateMetricRequest {
+            name: name.to_string(),
+            metric_type: MetricType::Gauge,
+            value,
+            labels,
+            recorded_at: Some(recorded_at),
+            tenant_id: None,
+        }
+    };
+
+    vec![
+        gauge(DB_POOL_CONNECTIONS_METRIC, in_use, Some("in_use")),
+        gauge(DB_POOL_CONNECTIONS_METRIC, idle, Some("idle")),
+        gauge(
+            DB_POOL_MAX_CONNECTIONS_METRIC,
+            f64::from(pool.options().get_max_connections()),
+            None,
+        ),
+    ]
+}
+
+/// Rows for the task executions between two snapshots
+///
+/// Counters hold the increase since `previous` and are only written when
+/// something happened, so idle task types add no rows.
+pub fn task_rows(
+    current: &BTreeMap<String, TaskTypeSnapshot>,
+    previous: &BTreeMap<String, TaskTypeSnapshot>,
+    recorded_at: DateTime<Utc>,
+) -> Vec<CreateMetricRequest> {
+    let empty = TaskTypeSnapshot::default();
+    let mut rows = Vec::new();
+
+    for (task_type, snapshot) in current {
+        let previous = previous.get(task_type).unwrap_or(&empty);
+        let labels = HashMap::from([("task_type".to_string(), task_type.clone())]);
+        let counter =
+            |name: &str, value: u64, labels: HashMap<String, String>| CreateMetricRequest {
+                name: name.to_string(),
+                metric_type: MetricType::Counter,
+                value: value as f64,
+                labels,
+                recorded_at: Some(recorded_at),
+                tenant_id: None,
+            };
+
+        let completed = snapshot.completed.saturating_sub(previous.completed);
+        if completed > 0 {
+            rows.push(counter(TASK_COMPLETED_METRIC, completed, labels.clone()));
+        }
+        for (error_class, count) in &snapshot.failed {
+            let failed =
+                count.saturating_sub(previous.failed.get(error_class).copied().unwrap_or(0));
+            if failed > 0 {
+                let mut labels = labels.clone();
+                labels.insert("error_class".to_string(), error_class.to_string());
+                rows.push(counter(TASK_FAILED_METRIC, failed, labels));
+            }
+        }
+        let retried = snapshot.retries.saturating_sub(previous.retries);
+        if retried > 0 {
+            rows.push(counter(TASK_RETRIED_METRIC, retried, labels.clone()));
+        }
+
+        let count = snapshot
+            .duration_count
+            .saturating_sub(previous.duration_count);
+        if count > 0 {
+            let counts: Vec<u64> = snapshot
+                .duration_buckets
+                .iter()
+                .enumerate()
+                .map(|(i, bucket)| {
+                    bucket.saturating_sub(previous.duration_buckets.get(i).copied().unwrap_or(0))
+                })
+                .collect();
+            rows.extend(histogram_rows(
+                TASK_DURATION_METRIC,
+                &labels,
+                &DURATION_BUCKETS,
+                &counts,
+                Some(snapshot.duration_sum - previous.duration_sum),
+                count,
+                Some(recorded_at),
+            ));
+        }
+    }
+    rows
+}
+
+/// Rows for the cache lookups since the previous sample, labelled with their namespace
+///
+/// Nothing is written for a namespace that was idle, like the task counters.
+pub fn cache_rows(
+    counts: &BTreeMap<&'static str, CacheCounts>,
+    recorded_at: DateTime<Utc>,
+) -> Vec<CreateMetricRequest> {
+    let mut rows = Vec::new();
+    for (namespace, counts) in counts {
+        let lookups = counts.hits + counts.misses;
+        if lookups == 0 {
+            continue;
+        }
+        let row = |name: &str, metric_type: MetricType, value: f64| CreateMetricRequest {
+            name: name.to_string(),
+            metric_type,
+            value,
+            labels: HashMap::from([("cache".to_string(), namespace.to_string())]),
+            recorded_at: Some(recorded_at),
+            tenant_id: None,
+        };
+        rows.push(row(
+            CACHE_HITS_METRIC,
+            MetricType::Counter,
+            counts.hits as f64,
+        ));
+        rows.push(row(
+            CACHE_MISSES_METRIC,
+            MetricType::Counter,
+            counts.misses as f64,
+        ));
+        rows.push(row(
+            CACHE_HIT_RATIO_METRIC,
+            MetricType::Gauge,
+            counts.hits as f64 / lookups as f64,
+        ));
+    }
+    rows
+}
+
+/// Background job that stores the server's request, cache and database pool metrics
+pub async fn server_metrics_job(
+    pool: DbPool,
+    run_interval: Duration,
+    http: Arc<HttpMetrics>,
+    cache: Arc<AppCache>,
+) {
+    let mut interval = interval(run_interval);
+    // The first tick completes immediately; start with a full interval of requests
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let now = Utc::now();
+        let mut rows = http.take_rows(now);
+        rows.extend(cache_rows(&cache.take_counts(), now));
+        rows.extend(pool_rows(&pool, "server", now));
+        if let Err(e) = store_rows(&pool, &rows).await {
+            error!("Failed to store server metrics: {}", e);
+        }
+    }
+}
+
+/// Background job that stores the worker's task throughput and database pool metrics
+pub async fn worker_metrics_job(pool: DbPool, run_interval: Duration, tasks: Arc<TaskMetrics>) {
+    let mut interval = interval(run_interval);
+    interval.tick().await;
+    let mut previous = BTreeMap::new();
+
+    loop {
+        interval.tick().await;
+
+        let now = Utc::now();
+        let current = tasks.snapshot();
+        let mut rows = task_rows(&current, &previous, now);
+        rows.extend(pool_rows(&pool, "worker", now));
+        match store_rows(&pool, &rows).await {
+            Ok(()) => previous = current,
+            Err(e) => error!("Failed to store worker metrics: {}", e),
+        }
+    }
+}
+
+async fn store_rows(pool: &DbPool, rows: &[CreateMetricRequest]) -> Result<()> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    services::create_metrics_batch(conn.as_mut(), rows).await?;
+    Ok(())
+}
+
+/// Requests and task executions the app recorded about itself since `since`
+///
+/// They are stored in the default tenant, so other tenants see none.
+pub async fn app_activity(
+    conn: &mut DbConn,
+    tenant_id: Option<Uuid>,
+    since: DateTime<Utc>,
+) -> Result<AppActivity> {
+    let request_count = format!("{HTTP_REQUEST_DURATION_METRIC}_count");
+    let activity = sqlx::query!(
+        r#"
+        SELECT
+            COALESCE(SUM(value) FILTER (WHERE name = $2), 0) AS "http_requests!",
+            COALESCE(SUM(value) FILTER (WHERE name = $2 AND labels->>'status' LIKE '5%'), 0)
+                AS "http_server_errors!",
+            COALESCE(SUM(value) FILTER (WHERE name = $3), 0) AS "tasks_completed!",
+            COALESCE(SUM(value) FILTER (WHERE name = $4), 0) AS "tasks_failed!"
+        FROM metrics
+        WHERE recorded_at >= $1 AND name IN ($2, $3, $4)
+          AND ($5::UUID IS NULL OR tenant_id = $5)
+        "#,
+        since,
+        request_count,
+        TASK_COMPLETED_METRIC,
+        TASK_FAILED_METRIC,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(AppActivity {
+        http_requests: activity.http_requests as i64,
+        http_server_errors: activity.http_server_errors as i64,
+        tasks_completed: activity.tasks_completed as i64,
+        tasks_failed: activity.tasks_failed as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::types::TaskErrorClass;
+
+    #[test]
+    fn test_http_metrics_are_reset_when_taken() {
+        let metrics = HttpMetrics::new();
+        metrics.observe("GET", "/tasks/{id}", 200, Duration::from_millis(20));
+        metrics.observe("GET", "/tasks/{id}", 200, Duration::from_secs(20));
+
+        let rows = metrics.take_rows(Utc::now());
+        let count = rows
+            .iter()
+            .find(|row| row.name == "http_server_request_duration_seconds_count")
+            .unwrap();
+        assert_eq!(count.value, 2.0);
+        assert_eq!(count.labels["route"], "/tasks/{id}");
+        assert_eq!(count.labels["status"], "200");
+        let bucket = |le: &str| {
+            rows.iter()
+                .find(|row| row.labels.get("le").map(String::as_str) == Some(le))
+                .unwrap()
+                .value
+        };
+        assert_eq!(bucket("0.01"), 0.0);
+        assert_eq!(bucket("0.025"), 1.0);
+        assert_eq!(bucket("10"), 1.0);
+        assert_eq!(bucket("+Inf"), 2.0);
+
+        assert!(metrics.take_rows(Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_cache_rows() {
+        let idle = BTreeMap::from([("sessions", CacheCounts::default())]);
+        assert!(cache_rows(&idle, Utc::now()).is_empty());
+
+        let counts = BTreeMap::from([
+            ("permissions", CacheCounts { hits: 3, misses: 1 }),
+            ("sessions", CacheCounts::default()),
+        ]);
+        let rows = cache_rows(&counts, Utc::now());
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row.labels["cache"] == "permissions"));
+        let value = |name: &str| rows.iter().find(|row| row.name == name).unwrap().value;
+        assert_eq!(value(CACHE_HITS_METRIC), 3.0);
+        assert_eq!(value(CACHE_MISSES_METRIC), 1.0);
+        assert_eq!(value(CACHE_HIT_RATIO_METRIC), 0.75);
+    }
+
+    #[test]
+    fn test_task_rows_hold_the_increase() {
+        let tasks = TaskMetrics::new();
+        tasks.record_completed("email");
+        tasks.observe_duration("email", Duration::from_millis(40));
+        let first = tasks.snapshot();
+
+        tasks.record_completed("email");
+        tasks.record_completed("email");
+        tasks.record_failed("email", TaskErrorClass::TimedOut);
+        tasks.observe_duration("email", Duration::from_millis(40));
+        let second = tasks.snapshot();
+
+        let rows = task_rows(&second, &first, Utc::now());
+        let value = |name: &str| rows.iter().find(|row| row.name == name).unwrap().value;
+        assert_eq!(value("task_completed"), 2.0);
+        assert_eq!(value("task_failed"), 1.0);
+        assert_eq!(value("task_handler_duration_seconds_count"), 1.0);
+        assert!(rows.iter().all(|row| row.name != "task_retried"));
+
+        // Nothing happened since
+        assert!(task_rows(&second, &second, Utc::now()).is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::models::{
+    EventSourceLimit, MAX_SOURCE_LENGTH, QuotaScope, SetEventSourceLimitRequest, Validate,
+};
+use crate::monitoring::quotas;
+use crate::rbac::UserRole;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, DurationRound, Utc};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Length of the fixed event rate limiting window
+const RATE_LIMIT_WINDOW_SECS: i64 = 60;
+
+/// What ingestion does with an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventAdmission {
+    /// Store the event
+    Accepted,
+    /// Drop the event; the source's sampling ratio left it out
+    Sampled,
+    /// Reject the event; its source or user is over the rate limit
+    RateLimited { retry_after_secs: u64 },
+    /// Reject the event; its source or user has used up today's quota
+    QuotaExceeded { scope: QuotaScope, limit: i64 },
+}
+
+/// Decide which events of one request are stored
+///
+/// Events are sampled first, by the source's ratio, and the rest counted
+/// against the per-source and per-user limits in fixed one-minute windows.
+/// Every counted event uses up the window, including those rejected, so a
+/// client retrying in a loop stays limited until the window ends. Events
+/// within the rate limits are then checked against the daily quotas, which
+/// count only stored events; a user without their own event quota gets the
+/// default of their role. Concurrent requests may briefly overshoot a limit.
+pub async fn admit_events(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    user_id: Uuid,
+    tenant_id: Uuid,
+    role: UserRole,
+    sources: &[&str],
+) -> Result<Vec<EventAdmission>> {
+    let now = Utc::now();
+    let window_start = now
+        .duration_trunc(chrono::Duration::seconds(RATE_LIMIT_WINDOW_SECS))
+        .map_err(|e| Error::internal(&format!("Invalid rate limit window: {e}")))?;
+    let retry_after_secs =
+        (RATE_LIMIT_WINDOW_SECS - (now - window_start).num_seconds()).max(1) as u64;
+
+    let overrides = find_source_limits(conn, sources).await?;
+    let limits_for = |source: &str| {
+        overrides.get(source).copied().unwrap_or((
+            config.event_rate_limit_per_source,
+            config.event_sample_ratio,
+        ))
+    };
+
+    let mut admissions: Vec<EventAdmission> = sources
+        .iter()
+        .map(|source| {
+            let (_, sample_ratio) = limits_for(source);
+            if sample_ratio < 1.0 && rand::random::<f64>() >= sample_ratio {
+                EventAdmission::Sampled
+            } else {
+                EventAdmission::Accepted
+            }
+        })
+        .collect();
+
+    let mut per_source: BTreeMap<&str, u32> = BTreeMap::new();
+    for (source, admission) in sources.iter().zip(&admissions) {
+        if *admission == EventAdmission::Accepted {
+            *per_source.entry(source).or_default() += 1;
+        }
+    }
+
+    let mut counted = false;
+    let mut allowed_per_source = HashMap::new();
+    for (source, count) in per_source {
+        let (limit, _) = limits_for(source);
+        if limit > 0 {
+            let allowed =
+                take_from_window(conn, "source", source, window_start, count, limit).await?;
+            allowed_per_source.insert(source, allowed);
+            counted = true;
+        }
+    }
+    for (source, admission) in sources.iter().zip(admissions.iter_mut()) {
+        if *admission != EventAdmission::Accepted {
+            continue;
+        }
+        if let Some(allowed) = allowed_per_source.get_mut(source) {
+            if *allowed == 0 {
+                *admission = EventAdmission::RateLimited { retry_after_secs };
+            } else {
+                *allowed -= 1;
+            }
+        }
+    }
+
+    let accepted = admissions
+        .iter()
+        .filter(|admission| **admission == EventAdmission::Accepted)
+        .count() as u32;
+    if config.event_rate_limit_per_user > 0 && accepted > 0 {
+        let mut allowed = take_from_window(
+            conn,
+            "user",
+            &user_id.to_string(),
+            window_start,
+            accepted,
+            config.event_rate_limit_per_user,
+        )
+        .await?;
+        for admission in admissions.iter_mut() {
+            if *admission == EventAdmission::Accepted {
+                if allowed == 0 {
+                    *admission = EventAdmission::RateLimited { retry_after_secs };
+                } else {
+                    allowed -= 1;
+                }
+            }
+        }
+        counted = true;
+    }
+
+    if counted {
+        sqlx::query!(
+            "DELETE FROM event_rate_counters WHERE window_start < $1",
+            window_start
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    }
+
+    let default_user_quota = config.event_daily_quotas.for_role(role);
+    quotas::admit_events(
+        conn,
+        user_id,
+        tenant_id,
+        default_user_quota,
+        sources,
+        &mut admissions,
+    )
+    .await?;
+    Ok(admissions)
+}
+
+/// Count `count` events in the window, returning how many fit under the limit
+async fn take_from_window(
+    conn: &mut DbConn,
+    scope: &str,
+    key: &str,
+    window_start: DateTime<Utc>,
+    count: u32,
+    limit: u32,
+) -> Result<u32> {
+    let total = sqlx::query_scalar!(
+        r#"
+        INSERT INTO event_rate_counters (scope, key, window_start, count)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (scope, key, window_start) DO UPDATE
+        SET count = event_rate_counters.count + EXCLUDED.count
+        RETURNING count
+        "#,
+        scope,
+        key,
+        window_start,
+        count as i32
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let before = (total as u32).saturating_sub(count);
+    Ok(limit.saturating_sub(before).min(count))
+}
+
+/// Overrides of the given sources as `(events per minute, sample ratio)`
+async fn find_source_limits(
+    conn: &mut DbConn,
+    sources: &[&str],
+) -> Result<HashMap<String, (u32, f64)>> {
+    let mut distinct: Vec<String> = sources.iter().map(|source| source.to_string()).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT source, events_per_minute, sample_ratio
+        FROM event_source_limits
+        WHERE source = ANY($1)
+        "#,
+        &distinct
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.source,
+                (row.events_per_minute.max(0) as u32, row.sample_ratio),
+            )
+        })
+        .collect())
+}
+
+/// List all per-source ingestion overrides
+pub async fn list_source_limits(conn: &mut DbConn) -> Result<Vec<EventSourceLimit>> {
+    sqlx::query_as!(
+        EventSourceLimit,
+        r#"
+        SELECT source, events_per_minute, sample_ratio, updated_by, updated_at
+        FROM event_source_limits
+        ORDER BY source
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Create or replace a source's ingestion override
+pub async fn set_source_limit(
+    conn: &mut DbConn,
+    source: &str,
+    request: SetEventSourceLimitRequest,
+    updated_by: Uuid,
+) -> Result<EventSourceLimit> {
+    if source.is_empty() || source.len() > MAX_SOURCE_LENGTH {
+        return Err(Error::validation(
+            "source",
+            &format!("Source must be 1-{MAX_SOURCE_LENGTH} characters"),
+        ));
+    }
+    request.validate()?;
+
+    sqlx::query_as!(
+        EventSourceLimit,
+        r#"
+        INSERT INTO event_source_limits (source, events_per_minute, sample_ratio, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (source) DO UPDATE
+        SET events_per_minute = EXCLUDED.events_per_minute,
+            sample_ratio = EXCLUDED.sample_ratio,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING source, events_per_minute, sample_ratio, updated_by, updated_at
+        "#,
+        source,
+        request.events_per_minute,
+        request.sample_ratio,
+        updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Remove a source's ingestion override so it falls back to the defaults
+pub async fn delete_source_limit(conn: &mut DbConn, source: &str) -> Result<()> {
+    let result = sqlx::query!("DELETE FROM event_source_limits WHERE source = $1", source)
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!(
+            "No ingestion limits for source '{source}'"
+        )));
+    }
+    Ok(())
+}
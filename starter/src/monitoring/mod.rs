@@ -1,4 +1,27 @@
 pub mod api;
+pub mod app_logs;
+pub mod cardinality;
+pub mod correlation;
+pub mod escalation;
+pub mod event_retention;
+pub mod export;
+pub mod grafana;
 pub mod handlers;
+pub mod histogram;
+pub mod instrumentation;
+pub mod limits;
 pub mod models;
+pub mod notes;
+pub mod notifications;
+pub mod oncall;
+pub mod otlp;
+pub mod postmortem;
+pub mod query;
+pub mod quotas;
+pub mod recording;
+pub mod retention;
+pub mod routing;
 pub mod services;
+pub mod status_page;
+pub mod stream;
+pub mod uptime;
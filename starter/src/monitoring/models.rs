@@ -1,5 +1,7 @@
 use crate::Error;
 use crate::Result;
+use crate::monitoring::histogram;
+use crate::tasks::types::TaskStatus;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -18,6 +20,43 @@ pub const MAX_PAYLOAD_FIELDS: usize = 100;
 pub const MAX_TAGS_JSON_SIZE: usize = 65_536; // 64KB
 pub const MAX_PAYLOAD_JSON_SIZE: usize = 1_048_576; // 1MB
 pub const MAX_LABELS_COUNT: usize = 50;
+pub const MAX_LABEL_NAME_LENGTH: usize = 100;
+pub const MAX_LABEL_VALUE_LENGTH: usize = 1024;
+pub const MAX_HISTOGRAM_BUCKETS: usize = 100;
+pub const MAX_SUMMARY_QUANTILES: usize = 20;
+pub const MAX_RETENTION_DAYS: i32 = 3650;
+pub const MAX_ESCALATION_POLICY_NAME_LENGTH: usize = 100;
+pub const MAX_ESCALATION_STEPS: usize = 20;
+pub const MAX_ESCALATION_DELAY_MINUTES: i32 = 10_080; // 1 week
+pub const MAX_POSTMORTEM_BODY_LENGTH: usize = 100_000;
+pub const MAX_CONTRIBUTING_FACTORS: usize = 50;
+pub const MAX_CONTRIBUTING_FACTOR_LENGTH: usize = 500;
+pub const MAX_ACTION_ITEM_TITLE_LENGTH: usize = 200;
+pub const MAX_INCIDENT_NOTE_LENGTH: usize = 10_000;
+pub const MAX_ONCALL_SCHEDULE_NAME_LENGTH: usize = 100;
+pub const MAX_ONCALL_MEMBERS: usize = 50;
+pub const MAX_ONCALL_ROTATION_HOURS: i32 = 2160; // 90 days
+pub const MAX_ONCALL_OVERRIDE_HOURS: i64 = 2160;
+pub const MAX_NOTIFICATION_CHANNEL_NAME_LENGTH: usize = 100;
+pub const MAX_ALERT_CHANNELS: usize = 20;
+pub const MAX_RECORDING_EXPRESSION_LENGTH: usize = 1000;
+pub const MIN_RECORDING_INTERVAL_SECS: i32 = 10;
+pub const MAX_RECORDING_INTERVAL_SECS: i32 = 86_400; // 1 day
+pub const MAX_UPTIME_CHECK_NAME_LENGTH: usize = 100;
+pub const MAX_UPTIME_CHECK_URL_LENGTH: usize = 2048;
+pub const MAX_UPTIME_EXPECTED_BODY_LENGTH: usize = 1000;
+pub const MIN_UPTIME_CHECK_INTERVAL_SECS: i32 = 10;
+pub const MAX_UPTIME_CHECK_INTERVAL_SECS: i32 = 86_400; // 1 day
+pub const MAX_UPTIME_CHECK_TIMEOUT_SECS: i32 = 60;
+pub const MAX_UPTIME_FAILURE_THRESHOLD: i32 = 10;
+pub const DEFAULT_UPTIME_CHECK_INTERVAL_SECS: i32 = 60;
+pub const DEFAULT_UPTIME_CHECK_TIMEOUT_SECS: i32 = 10;
+pub const MAX_ROUTING_RULE_NAME_LENGTH: usize = 100;
+pub const MAX_STATUS_PAGE_SLUG_LENGTH: usize = 64;
+pub const MAX_STATUS_PAGE_TITLE_LENGTH: usize = 200;
+pub const MAX_STATUS_PAGE_DESCRIPTION_LENGTH: usize = 2000;
+pub const MAX_STATUS_PAGE_CHECKS: usize = 50;
+pub const MAX_STATUS_PAGE_INCIDENTS: usize = 50;
 
 // Helper trait for input validation
 pub trait Validate {
@@ -25,7 +64,7 @@ pub trait Validate {
 }
 
 // Event types for observability data
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum EventType {
     Log,
@@ -158,6 +197,178 @@ impl std::str::FromStr for AlertStatus {
     }
 }
 
+// How urgent a firing alert is; routing rules send each severity to different channels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "info",
+            AlertSeverity::Warning => "warning",
+            AlertSeverity::Critical => "critical",
+        }
+    }
+
+    /// Severity of the incident opened for a firing alert
+    pub fn incident_severity(&self) -> IncidentSeverity {
+        match self {
+            AlertSeverity::Info => IncidentSeverity::Low,
+            AlertSeverity::Warning => IncidentSeverity::Medium,
+            AlertSeverity::Critical => IncidentSeverity::Critical,
+        }
+    }
+}
+
+impl std::fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AlertSeverity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "info" => Ok(AlertSeverity::Info),
+            "warning" => Ok(AlertSeverity::Warning),
+            "critical" => Ok(AlertSeverity::Critical),
+            _ => Err(Error::validation("severity", "Invalid alert severity")),
+        }
+    }
+}
+
+// Where a notification channel delivers alert notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationChannelKind {
+    /// JSON POST of the notification to `target`
+    Webhook,
+    /// Slack incoming webhook at `target`
+    Slack,
+    /// Email to the `target` address
+    Email,
+}
+
+impl NotificationChannelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannelKind::Webhook => "webhook",
+            NotificationChannelKind::Slack => "slack",
+            NotificationChannelKind::Email => "email",
+        }
+    }
+}
+
+impl std::fmt::Display for NotificationChannelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for NotificationChannelKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "webhook" => Ok(NotificationChannelKind::Webhook),
+            "slack" => Ok(NotificationChannelKind::Slack),
+            "email" => Ok(NotificationChannelKind::Email),
+            _ => Err(Error::validation(
+                "kind",
+                "Invalid notification channel kind",
+            )),
+        }
+    }
+}
+
+// State of an uptime check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UptimeCheckStatus {
+    /// Not run yet, or failing fewer times than its threshold since creation
+    Pending,
+    Up,
+    /// Failed `failure_threshold` times in a row; its alert is firing
+    Down,
+}
+
+impl UptimeCheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UptimeCheckStatus::Pending => "pending",
+            UptimeCheckStatus::Up => "up",
+            UptimeCheckStatus::Down => "down",
+        }
+    }
+}
+
+impl std::fmt::Display for UptimeCheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for UptimeCheckStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(UptimeCheckStatus::Pending),
+            "up" => Ok(UptimeCheckStatus::Up),
+            "down" => Ok(UptimeCheckStatus::Down),
+            _ => Err(Error::validation("status", "Invalid uptime check status")),
+        }
+    }
+}
+
+// Whom a daily ingestion quota applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaScope {
+    /// Everything a user ingests; the key is the user ID
+    User,
+    /// Events with this source
+    Source,
+}
+
+impl QuotaScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaScope::User => "user",
+            QuotaScope::Source => "source",
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for QuotaScope {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "user" => Ok(QuotaScope::User),
+            "source" => Ok(QuotaScope::Source),
+            _ => Err(Error::validation(
+                "scope",
+                "Quota scope must be 'user' or 'source'",
+            )),
+        }
+    }
+}
+
 // Incident severity levels
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -270,6 +481,8 @@ pub struct Event {
     pub recorded_at: DateTime<Utc>,
     #[schema(format = "date-time")]
     pub created_at: DateTime<Utc>,
+    /// Organization whose members can see the event; unscoped events are visible to everyone
+    pub org_id: Option<Uuid>,
 }
 
 /// API request structure for creating events
@@ -297,6 +510,12 @@ pub struct CreateEventRequest {
     pub payload: HashMap<String, serde_json::Value>,
     #[schema(format = "date-time")]
     pub recorded_at: Option<DateTime<Utc>>,
+    /// Organization to scope the event to; requires membership
+    #[serde(default)]
+    pub org_id: Option<Uuid>,
+    /// Tenant the event belongs to, set from the signed-in user; the default tenant when unset
+    #[serde(skip)]
+    pub tenant_id: Option<Uuid>,
 }
 
 impl Validate for CreateEventRequest {
@@ -375,6 +594,24 @@ impl Validate for CreateEventRequest {
     }
 }
 
+// Item of an event batch that was not stored
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventBatchError {
+    /// Position of the item in the batch, starting at 0
+    pub index: usize,
+    pub message: String,
+}
+
+// Event batch ingestion response
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventBatchResult {
+    pub accepted: u64,
+    pub rejected: u64,
+    /// Valid items dropped by the source's sampling ratio
+    pub sampled: u64,
+    pub errors: Vec<EventBatchError>,
+}
+
 // Metrics structure for time-series data
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Metric {
@@ -393,6 +630,9 @@ pub struct Metric {
 /// - name: max [`MAX_METRIC_NAME_LENGTH`] characters
 /// - value: must be finite number
 /// - labels: max [`MAX_LABELS_COUNT`] entries, max [`MAX_TAGS_JSON_SIZE`] bytes JSON
+/// - label names: letters, digits, `_` and `.`, not starting with a digit or `__`,
+///   max [`MAX_LABEL_NAME_LENGTH`] characters
+/// - label values: max [`MAX_LABEL_VALUE_LENGTH`] characters
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateMetricRequest {
     #[schema(max_length = 100)]
@@ -403,6 +643,9 @@ pub struct CreateMetricRequest {
     pub labels: HashMap<String, String>,
     #[schema(format = "date-time")]
     pub recorded_at: Option<DateTime<Utc>>,
+    /// Tenant the metric belongs to, set from the signed-in user; the default tenant when unset
+    #[serde(skip)]
+    pub tenant_id: Option<Uuid>,
 }
 
 impl Validate for CreateMetricRequest {
@@ -422,6 +665,9 @@ impl Validate for CreateMetricRequest {
                 &format!("Too many labels (max {})", MAX_LABELS_COUNT),
             ));
         }
+        for (label, value) in &self.labels {
+            validate_label(label, value)?;
+        }
 
         // Validate labels JSON size
         let labels_json = serde_json::to_string(&self.labels)
@@ -448,6 +694,224 @@ impl Validate for CreateMetricRequest {
     }
 }
 
+/// API request structure for submitting a histogram observation
+///
+/// `counts` holds the observations per bucket since the previous submission:
+/// one entry per upper bound in `bounds` plus a final `+Inf` overflow bucket.
+/// Stored as `<name>_bucket`, `<name>_sum` and `<name>_count` rows.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateHistogramRequest {
+    #[schema(max_length = 93)]
+    pub name: String,
+    /// Bucket upper bounds, strictly increasing
+    #[schema(example = json!([0.05, 0.1, 0.25, 0.5, 1.0]))]
+    pub bounds: Vec<f64>,
+    /// Observations per bucket; one more entry than `bounds`
+    #[schema(example = json!([12, 30, 8, 3, 1, 0]))]
+    pub counts: Vec<u64>,
+    /// Sum of the observed values
+    pub sum: Option<f64>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[schema(format = "date-time")]
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+impl CreateHistogramRequest {
+    /// Rows stored for this histogram, all recorded at the same time
+    pub fn into_rows(self) -> Vec<CreateMetricRequest> {
+        let count = self.counts.iter().sum();
+        histogram::histogram_rows(
+            &self.name,
+            &self.labels,
+            &self.bounds,
+            &self.counts,
+            self.sum,
+            count,
+            Some(self.recorded_at.unwrap_or_else(Utc::now)),
+        )
+    }
+}
+
+impl Validate for CreateHistogramRequest {
+    fn validate(&self) -> Result<()> {
+        if self.bounds.len() > MAX_HISTOGRAM_BUCKETS {
+            return Err(Error::validation(
+                "bounds",
+                &format!("Too many buckets (max {})", MAX_HISTOGRAM_BUCKETS),
+            ));
+        }
+        if self.bounds.iter().any(|bound| !bound.is_finite())
+            || self.bounds.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return Err(Error::validation(
+                "bounds",
+                "Bucket bounds must be finite and strictly increasing",
+            ));
+        }
+        if self.counts.len() != self.bounds.len() + 1 {
+            return Err(Error::validation(
+                "counts",
+                "counts must have one entry per bound plus one for the +Inf bucket",
+            ));
+        }
+        if self.sum.is_some_and(|sum| !sum.is_finite()) {
+            return Err(Error::validation("sum", "Sum must be a finite number"));
+        }
+        validate_reserved_label(&self.labels, histogram::BUCKET_LABEL)?;
+
+        // Suffixed names and the added `le` label must fit metric limits
+        self.clone()
+            .into_rows()
+            .iter()
+            .try_for_each(Validate::validate)
+    }
+}
+
+// One precomputed quantile of a summary
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SummaryQuantile {
+    #[schema(minimum = 0, maximum = 1, example = 0.95)]
+    pub quantile: f64,
+    pub value: f64,
+}
+
+/// API request structure for submitting a summary observation
+///
+/// Quantiles are computed by the client. Stored as `<name>` rows labelled
+/// `quantile` plus `<name>_sum` and `<name>_count` rows.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateSummaryRequest {
+    #[schema(max_length = 94)]
+    pub name: String,
+    pub quantiles: Vec<SummaryQuantile>,
+    /// Sum of the observed values
+    pub sum: Option<f64>,
+    /// Number of observations
+    pub count: u64,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[schema(format = "date-time")]
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+impl CreateSummaryRequest {
+    /// Rows stored for this summary, all recorded at the same time
+    pub fn into_rows(self) -> Vec<CreateMetricRequest> {
+        let quantiles: Vec<(f64, f64)> = self
+            .quantiles
+            .iter()
+            .map(|q| (q.quantile, q.value))
+            .collect();
+        histogram::summary_rows(
+            &self.name,
+            &self.labels,
+            &quantiles,
+            self.sum,
+            self.count,
+            Some(self.recorded_at.unwrap_or_else(Utc::now)),
+        )
+    }
+}
+
+impl Validate for CreateSummaryRequest {
+    fn validate(&self) -> Result<()> {
+        if self.quantiles.len() > MAX_SUMMARY_QUANTILES {
+            return Err(Error::validation(
+                "quantiles",
+                &format!("Too many quantiles (max {})", MAX_SUMMARY_QUANTILES),
+            ));
+        }
+        for quantile in &self.quantiles {
+            if !(0.0..=1.0).contains(&quantile.quantile) {
+                return Err(Error::validation(
+                    "quantiles",
+                    "Quantiles must be between 0 and 1",
+                ));
+            }
+            if !quantile.value.is_finite() {
+                return Err(Error::validation(
+                    "quantiles",
+                    "Quantile values must be finite numbers",
+                ));
+            }
+        }
+        if self.sum.is_some_and(|sum| !sum.is_finite()) {
+            return Err(Error::validation("sum", "Sum must be a finite number"));
+        }
+        validate_reserved_label(&self.labels, histogram::QUANTILE_LABEL)?;
+
+        // Suffixed names and the added `quantile` label must fit metric limits
+        self.clone()
+            .into_rows()
+            .iter()
+            .try_for_each(Validate::validate)
+    }
+}
+
+/// Check a label's name and value against the metric label rules
+/// Check the number of labels and each name and value
+fn validate_labels(labels: &HashMap<String, String>) -> Result<()> {
+    if labels.len() > MAX_LABELS_COUNT {
+        return Err(Error::validation(
+            "labels",
+            &format!("Too many labels (max {})", MAX_LABELS_COUNT),
+        ));
+    }
+    for (label, value) in labels {
+        validate_label(label, value)?;
+    }
+    Ok(())
+}
+
+fn validate_label(name: &str, value: &str) -> Result<()> {
+    let valid_name = name
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && !name.starts_with("__");
+    if !valid_name || name.len() > MAX_LABEL_NAME_LENGTH {
+        return Err(Error::validation(
+            "labels",
+            &format!(
+                "Invalid label name '{}': use 1-{} letters, digits, '_' or '.', not starting with a digit or '__'",
+                name, MAX_LABEL_NAME_LENGTH
+            ),
+        ));
+    }
+    if value.chars().count() > MAX_LABEL_VALUE_LENGTH {
+        return Err(Error::validation(
+            "labels",
+            &format!(
+                "Value of label '{}' too long (max {} characters)",
+                name, MAX_LABEL_VALUE_LENGTH
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject labels the stored rows would overwrite
+fn validate_reserved_label(labels: &HashMap<String, String>, reserved: &str) -> Result<()> {
+    if labels.contains_key(reserved) {
+        return Err(Error::validation(
+            "labels",
+            &format!("The '{}' label is reserved", reserved),
+        ));
+    }
+    Ok(())
+}
+
+// Response for endpoints that store several metric rows at once
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricRowsStored {
+    /// Rows written to `metrics`
+    pub stored: u64,
+}
+
 // Alert structure for monitoring rules
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct Alert {
@@ -456,6 +920,9 @@ pub struct Alert {
     pub description: Option<String>,
     pub query: String,
     pub threshold_value: Option<f64>,
+    pub severity: AlertSeverity,
+    /// Matched by routing rules, e.g. `{"team": "payments"}`
+    pub labels: serde_json::Value,
     pub status: AlertStatus,
     pub triggered_at: Option<DateTime<Utc>>,
     pub resolved_at: Option<DateTime<Utc>>,
@@ -471,74 +938,1042 @@ pub struct CreateAlertRequest {
     pub description: Option<String>,
     pub query: String,
     pub threshold_value: Option<f64>,
+    /// Defaults to `warning`
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
 }
 
-// Incident structure for tracking outages and issues
+impl Validate for CreateAlertRequest {
+    fn validate(&self) -> Result<()> {
+        validate_labels(&self.labels)
+    }
+}
+
+// Destination for alert notifications
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct Incident {
+pub struct NotificationChannel {
     pub id: Uuid,
-    pub title: String,
-    pub description: Option<String>,
-    pub severity: IncidentSeverity,
-    pub status: IncidentStatus,
-    pub started_at: DateTime<Utc>,
-    pub resolved_at: Option<DateTime<Utc>>,
-    pub root_cause: Option<String>,
+    pub name: String,
+    pub kind: NotificationChannelKind,
+    /// URL for webhook and Slack channels, address for email channels
+    pub target: String,
     pub created_by: Option<Uuid>,
-    pub assigned_to: Option<Uuid>,
+    #[schema(format = "date-time")]
     pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
     pub updated_at: DateTime<Utc>,
 }
 
-// API request structure for creating incidents
+// API request structure for creating notification channels
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct CreateIncidentRequest {
-    pub title: String,
-    pub description: Option<String>,
-    pub severity: IncidentSeverity,
-    pub assigned_to: Option<Uuid>,
+pub struct CreateNotificationChannelRequest {
+    pub name: String,
+    pub kind: NotificationChannelKind,
+    pub target: String,
 }
 
-// API request structure for updating incidents
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct UpdateIncidentRequest {
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub severity: Option<IncidentSeverity>,
-    pub status: Option<IncidentStatus>,
-    pub root_cause: Option<String>,
-    pub assigned_to: Option<Uuid>,
+impl Validate for CreateNotificationChannelRequest {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_NOTIFICATION_CHANNEL_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!(
+                    "Name must be between 1 and {} characters",
+                    MAX_NOTIFICATION_CHANNEL_NAME_LENGTH
+                ),
+            ));
+        }
+        match self.kind {
+            NotificationChannelKind::Webhook | NotificationChannelKind::Slack => {
+                let valid = reqwest::Url::parse(self.target.trim())
+                    .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+                if !valid {
+                    return Err(Error::validation(
+                        "target",
+                        "Target must be an http or https URL",
+                    ));
+                }
+            }
+            NotificationChannelKind::Email => {
+                crate::users::models::validate_email(self.target.trim())
+                    .map_err(|_| Error::validation("target", "Target must be an email address"))?;
+            }
+        }
+        Ok(())
+    }
 }
 
-// Query filters for events
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
-pub struct EventFilter {
-    pub event_type: Option<EventType>,
-    pub source: Option<String>,
-    pub level: Option<String>,
-    pub start_time: Option<DateTime<Utc>>,
-    pub end_time: Option<DateTime<Utc>>,
-    pub tags: Option<HashMap<String, String>>,
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
+// Metric query stored on an interval as a new metric series
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RecordingRule {
+    pub id: Uuid,
+    /// Metric name the results are stored under
+    pub name: String,
+    /// Query such as `p95(http_request_duration_ms{env=prod}) by (route)`
+    pub expression: String,
+    pub interval_secs: i32,
+    /// End of the last window stored
+    #[schema(format = "date-time")]
+    pub last_window_end: Option<DateTime<Utc>>,
+    #[schema(format = "date-time")]
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    /// Error of the last evaluation, if it failed
+    pub last_error: Option<String>,
+    /// Tenant whose metrics the rule reads and writes
+    #[serde(skip)]
+    pub tenant_id: Uuid,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
 }
 
-impl Default for EventFilter {
-    fn default() -> Self {
-        Self {
-            event_type: None,
-            source: None,
-            level: None,
-            start_time: None,
-            end_time: None,
-            tags: None,
-            limit: Some(100),
-            offset: Some(0),
-        }
-    }
+// API request structure for creating recording rules
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateRecordingRuleRequest {
+    #[schema(max_length = 100)]
+    pub name: String,
+    #[schema(max_length = 1000)]
+    pub expression: String,
+    #[schema(minimum = 10, maximum = 86400)]
+    pub interval_secs: i32,
 }
 
-// Query filters for metrics
+impl Validate for CreateRecordingRuleRequest {
+    fn validate(&self) -> Result<()> {
+        crate::monitoring::retention::validate_metric_name(self.name.trim())?;
+        if self.expression.len() > MAX_RECORDING_EXPRESSION_LENGTH {
+            return Err(Error::validation(
+                "expression",
+                &format!(
+                    "Expression must be at most {} characters",
+                    MAX_RECORDING_EXPRESSION_LENGTH
+                ),
+            ));
+        }
+        let expression =
+            crate::monitoring::recording::RecordingExpression::parse(&self.expression)?;
+        if expression.metric == self.name.trim() {
+            return Err(Error::validation(
+                "name",
+                "A recording rule cannot store its results under the metric it reads",
+            ));
+        }
+        if !(MIN_RECORDING_INTERVAL_SECS..=MAX_RECORDING_INTERVAL_SECS)
+            .contains(&self.interval_secs)
+        {
+            return Err(Error::validation(
+                "interval_secs",
+                &format!(
+                    "Interval must be between {} and {} seconds",
+                    MIN_RECORDING_INTERVAL_SECS, MAX_RECORDING_INTERVAL_SECS
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// HTTP check the worker runs on an interval
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UptimeCheck {
+    pub id: Uuid,
+    pub name: String,
+    pub url: String,
+    pub interval_secs: i32,
+    pub timeout_secs: i32,
+    pub expected_status: i32,
+    /// Text the response body must contain
+    pub expected_body: Option<String>,
+    /// Consecutive failures before the check is down
+    pub failure_threshold: i32,
+    /// Alert fired while the check is down; choose its channels with
+    /// `PUT /monitoring/alerts/{id}/channels`
+    pub alert_id: Option<Uuid>,
+    pub status: UptimeCheckStatus,
+    pub consecutive_failures: i32,
+    #[schema(format = "date-time")]
+    pub next_check_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_latency_ms: Option<f64>,
+    /// Why the last check failed
+    pub last_error: Option<String>,
+    /// Tenant the check and its metrics belong to
+    #[serde(skip)]
+    pub tenant_id: Uuid,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for creating uptime checks
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateUptimeCheckRequest {
+    #[schema(max_length = 100)]
+    pub name: String,
+    /// http or https URL requested with GET
+    #[schema(max_length = 2048)]
+    pub url: String,
+    /// Defaults to 60
+    #[schema(minimum = 10, maximum = 86400)]
+    pub interval_secs: Option<i32>,
+    /// Defaults to 10
+    #[schema(minimum = 1, maximum = 60)]
+    pub timeout_secs: Option<i32>,
+    /// Defaults to 200
+    #[schema(minimum = 100, maximum = 599)]
+    pub expected_status: Option<i32>,
+    #[schema(max_length = 1000)]
+    pub expected_body: Option<String>,
+    /// Defaults to 1
+    #[schema(minimum = 1, maximum = 10)]
+    pub failure_threshold: Option<i32>,
+    /// Severity of the check's alert; defaults to `critical`
+    pub severity: Option<AlertSeverity>,
+}
+
+impl Validate for CreateUptimeCheckRequest {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_UPTIME_CHECK_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!(
+                    "Name must be between 1 and {} characters",
+                    MAX_UPTIME_CHECK_NAME_LENGTH
+                ),
+            ));
+        }
+        let valid = self.url.len() <= MAX_UPTIME_CHECK_URL_LENGTH
+            && reqwest::Url::parse(self.url.trim())
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+        if !valid {
+            return Err(Error::validation("url", "URL must be an http or https URL"));
+        }
+        if let Some(interval_secs) = self.interval_secs
+            && !(MIN_UPTIME_CHECK_INTERVAL_SECS..=MAX_UPTIME_CHECK_INTERVAL_SECS)
+                .contains(&interval_secs)
+        {
+            return Err(Error::validation(
+                "interval_secs",
+                &format!(
+                    "Interval must be between {} and {} seconds",
+                    MIN_UPTIME_CHECK_INTERVAL_SECS, MAX_UPTIME_CHECK_INTERVAL_SECS
+                ),
+            ));
+        }
+        if let Some(timeout_secs) = self.timeout_secs
+            && !(1..=MAX_UPTIME_CHECK_TIMEOUT_SECS).contains(&timeout_secs)
+        {
+            return Err(Error::validation(
+                "timeout_secs",
+                &format!(
+                    "Timeout must be between 1 and {} seconds",
+                    MAX_UPTIME_CHECK_TIMEOUT_SECS
+                ),
+            ));
+        }
+        if let Some(expected_status) = self.expected_status
+            && !(100..=599).contains(&expected_status)
+        {
+            return Err(Error::validation(
+                "expected_status",
+                "Expected status must be an HTTP status code",
+            ));
+        }
+        if let Some(expected_body) = &self.expected_body
+            && (expected_body.is_empty() || expected_body.len() > MAX_UPTIME_EXPECTED_BODY_LENGTH)
+        {
+            return Err(Error::validation(
+                "expected_body",
+                &format!(
+                    "Expected body must be between 1 and {} characters",
+                    MAX_UPTIME_EXPECTED_BODY_LENGTH
+                ),
+            ));
+        }
+        if let Some(failure_threshold) = self.failure_threshold
+            && !(1..=MAX_UPTIME_FAILURE_THRESHOLD).contains(&failure_threshold)
+        {
+            return Err(Error::validation(
+                "failure_threshold",
+                &format!(
+                    "Failure threshold must be between 1 and {}",
+                    MAX_UPTIME_FAILURE_THRESHOLD
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Sends firing alerts with a severity and labels to channels and escalation policies
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AlertRoutingRule {
+    pub id: Uuid,
+    pub name: String,
+    /// Severity the alert must have; `null` matches every severity
+    pub severity: Option<AlertSeverity>,
+    /// Labels the alert must have with these values
+    pub labels: serde_json::Value,
+    pub channel_ids: Vec<Uuid>,
+    /// Policy of the incident opened while a matching alert fires
+    pub escalation_policy_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for creating alert routing rules
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateAlertRoutingRuleRequest {
+    #[schema(max_length = 100)]
+    pub name: String,
+    pub severity: Option<AlertSeverity>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub channel_ids: Vec<Uuid>,
+    pub escalation_policy_id: Option<Uuid>,
+}
+
+impl Validate for CreateAlertRoutingRuleRequest {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_ROUTING_RULE_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!(
+                    "Name must be between 1 and {} characters",
+                    MAX_ROUTING_RULE_NAME_LENGTH
+                ),
+            ));
+        }
+        validate_labels(&self.labels)?;
+        if self.channel_ids.len() > MAX_ALERT_CHANNELS {
+            return Err(Error::validation(
+                "channel_ids",
+                &format!(
+                    "A routing rule can notify at most {} channels",
+                    MAX_ALERT_CHANNELS
+                ),
+            ));
+        }
+        if self.channel_ids.is_empty() && self.escalation_policy_id.is_none() {
+            return Err(Error::validation(
+                "channel_ids",
+                "A routing rule needs channels or an escalation policy",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// API request structure for choosing the channels an alert notifies
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetAlertChannelsRequest {
+    pub channel_ids: Vec<Uuid>,
+}
+
+// Notification sent when an alert fires
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AlertNotification {
+    pub alert_id: Uuid,
+    pub alert_name: String,
+    pub description: Option<String>,
+    pub query: String,
+    pub threshold_value: Option<f64>,
+    pub severity: AlertSeverity,
+    pub labels: serde_json::Value,
+    /// `firing` or `resolved`
+    pub status: String,
+    /// Set for notifications sent by the test endpoint rather than a real alert
+    pub test: bool,
+    #[schema(format = "date-time")]
+    pub fired_at: DateTime<Utc>,
+}
+
+// Outcome of sending a notification to one channel
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ChannelDelivery {
+    pub channel_id: Uuid,
+    pub channel_name: String,
+    pub kind: NotificationChannelKind,
+    pub delivered: bool,
+    /// Why delivery failed
+    pub error: Option<String>,
+}
+
+// Result of test-firing an alert
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AlertTestResult {
+    pub notification: AlertNotification,
+    pub deliveries: Vec<ChannelDelivery>,
+}
+
+// Incident structure for tracking outages and issues
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Incident {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub severity: IncidentSeverity,
+    pub status: IncidentStatus,
+    pub started_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub root_cause: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub assigned_to: Option<Uuid>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<Uuid>,
+    pub escalation_policy_id: Option<Uuid>,
+    /// Last escalation step notified, if any
+    pub escalation_step: Option<i32>,
+    /// When the next escalation step is due; unset once acknowledged or resolved
+    pub next_escalation_at: Option<DateTime<Utc>>,
+    /// Set for high and critical incidents, which need a postmortem
+    pub postmortem_required: bool,
+    /// Set for incidents opened by event correlation
+    pub correlation_key: Option<String>,
+    /// Error events deduplicated into this incident by correlation
+    pub correlated_event_count: i32,
+    /// When the latest correlated event was recorded
+    pub last_correlated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for creating incidents
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateIncidentRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub severity: IncidentSeverity,
+    pub assigned_to: Option<Uuid>,
+    /// Escalation policy to run while the incident is unacknowledged
+    pub escalation_policy_id: Option<Uuid>,
+}
+
+// API request structure for updating incidents
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateIncidentRequest {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub severity: Option<IncidentSeverity>,
+    pub status: Option<IncidentStatus>,
+    pub root_cause: Option<String>,
+    pub assigned_to: Option<Uuid>,
+}
+
+// API request structure for resolving incidents
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ResolveIncidentRequest {
+    pub root_cause: Option<String>,
+}
+
+// One step of an escalation policy
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EscalationStep {
+    pub step_order: i32,
+    /// Fixed user to notify; unset when the step notifies an on-call schedule
+    pub notify_user_id: Option<Uuid>,
+    /// Schedule whose current on-call user is notified when the step runs
+    pub notify_schedule_id: Option<Uuid>,
+    /// Minutes after the previous step (or the incident start, for the first step)
+    pub delay_minutes: i32,
+}
+
+// Ordered list of users to notify while an incident stays unacknowledged
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EscalationPolicy {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<EscalationStep>,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for one escalation step
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EscalationStepRequest {
+    /// Set exactly one of `notify_user_id` and `notify_schedule_id`
+    pub notify_user_id: Option<Uuid>,
+    pub notify_schedule_id: Option<Uuid>,
+    #[schema(minimum = 0, maximum = 10080)]
+    pub delay_minutes: i32,
+}
+
+// API request structure for creating escalation policies
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateEscalationPolicyRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub steps: Vec<EscalationStepRequest>,
+}
+
+impl Validate for CreateEscalationPolicyRequest {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_ESCALATION_POLICY_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!(
+                    "Name must be between 1 and {} characters",
+                    MAX_ESCALATION_POLICY_NAME_LENGTH
+                ),
+            ));
+        }
+        if self.steps.is_empty() || self.steps.len() > MAX_ESCALATION_STEPS {
+            return Err(Error::validation(
+                "steps",
+                &format!(
+                    "Policy must have between 1 and {} steps",
+                    MAX_ESCALATION_STEPS
+                ),
+            ));
+        }
+        if self
+            .steps
+            .iter()
+            .any(|step| !(0..=MAX_ESCALATION_DELAY_MINUTES).contains(&step.delay_minutes))
+        {
+            return Err(Error::validation(
+                "delay_minutes",
+                &format!(
+                    "Step delay must be between 0 and {} minutes",
+                    MAX_ESCALATION_DELAY_MINUTES
+                ),
+            ));
+        }
+        if self
+            .steps
+            .iter()
+            .any(|step| step.notify_user_id.is_some() == step.notify_schedule_id.is_some())
+        {
+            return Err(Error::validation(
+                "steps",
+                "Each step must notify either a user or an on-call schedule",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Record of one escalation notification
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IncidentEscalation {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub step_order: i32,
+    pub notified_user_id: Option<Uuid>,
+    /// On-call schedule the notified user was looked up from, for schedule steps
+    pub oncall_schedule_id: Option<Uuid>,
+    /// `policy` when the worker escalated, `manual` when a user did
+    pub reason: String,
+    pub triggered_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub escalated_at: DateTime<Utc>,
+    /// When the notification email was sent; unset while delivery is pending
+    pub notified_at: Option<DateTime<Utc>>,
+}
+
+// Rotation of users taking turns on call
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OncallSchedule {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Length of each member's shift
+    pub rotation_period_hours: i32,
+    /// When the first member's first shift starts
+    #[schema(format = "date-time")]
+    pub handoff_at: DateTime<Utc>,
+    /// Members in rotation order
+    pub members: Vec<Uuid>,
+    /// Current and upcoming overrides
+    pub overrides: Vec<OncallOverride>,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// A user covering a schedule in place of the rotation for a while
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OncallOverride {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    pub user_id: Uuid,
+    #[schema(format = "date-time")]
+    pub starts_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub ends_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+}
+
+// Who is on call for a schedule, and for how long
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OncallShift {
+    pub schedule_id: Uuid,
+    pub schedule_name: String,
+    pub user_id: Uuid,
+    #[schema(format = "date-time")]
+    pub starts_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub ends_at: DateTime<Utc>,
+    /// Set when an override replaces the rotation
+    pub override_id: Option<Uuid>,
+}
+
+// API request structure for creating on-call schedules
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateOncallScheduleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[schema(minimum = 1, maximum = 2160)]
+    pub rotation_period_hours: i32,
+    /// Defaults to now
+    #[schema(format = "date-time")]
+    pub handoff_at: Option<DateTime<Utc>>,
+    /// Members in rotation order; a user may appear more than once
+    pub members: Vec<Uuid>,
+}
+
+impl Validate for CreateOncallScheduleRequest {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_ONCALL_SCHEDULE_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!(
+                    "Name must be between 1 and {} characters",
+                    MAX_ONCALL_SCHEDULE_NAME_LENGTH
+                ),
+            ));
+        }
+        if !(1..=MAX_ONCALL_ROTATION_HOURS).contains(&self.rotation_period_hours) {
+            return Err(Error::validation(
+                "rotation_period_hours",
+                &format!(
+                    "Rotation period must be between 1 and {} hours",
+                    MAX_ONCALL_ROTATION_HOURS
+                ),
+            ));
+        }
+        if self.members.is_empty() || self.members.len() > MAX_ONCALL_MEMBERS {
+            return Err(Error::validation(
+                "members",
+                &format!(
+                    "Schedule must have between 1 and {} members",
+                    MAX_ONCALL_MEMBERS
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// API request structure for creating on-call overrides
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateOncallOverrideRequest {
+    pub user_id: Uuid,
+    #[schema(format = "date-time")]
+    pub starts_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub ends_at: DateTime<Utc>,
+}
+
+impl Validate for CreateOncallOverrideRequest {
+    fn validate(&self) -> Result<()> {
+        if self.ends_at <= self.starts_at {
+            return Err(Error::validation(
+                "ends_at",
+                "Override must end after it starts",
+            ));
+        }
+        if self.ends_at - self.starts_at > chrono::Duration::hours(MAX_ONCALL_OVERRIDE_HOURS) {
+            return Err(Error::validation(
+                "ends_at",
+                &format!(
+                    "Override cannot last longer than {} hours",
+                    MAX_ONCALL_OVERRIDE_HOURS
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Postmortem document written after an incident
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Postmortem {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    /// Markdown document
+    pub body: String,
+    pub contributing_factors: Vec<String>,
+    pub action_items: Vec<PostmortemActionItem>,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// Follow-up work from a postmortem, optionally tracked by a task
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PostmortemActionItem {
+    pub id: Uuid,
+    pub postmortem_id: Uuid,
+    pub title: String,
+    pub task_id: Option<Uuid>,
+    /// Status of the linked task; unset when unlinked or the task was archived
+    pub task_status: Option<TaskStatus>,
+    pub assigned_to: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for writing a postmortem (creates or replaces it)
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpsertPostmortemRequest {
+    pub body: String,
+    #[serde(default)]
+    pub contributing_factors: Vec<String>,
+}
+
+impl Validate for UpsertPostmortemRequest {
+    fn validate(&self) -> Result<()> {
+        if self.body.trim().is_empty() || self.body.len() > MAX_POSTMORTEM_BODY_LENGTH {
+            return Err(Error::validation(
+                "body",
+                &format!(
+                    "Body must be between 1 and {} characters",
+                    MAX_POSTMORTEM_BODY_LENGTH
+                ),
+            ));
+        }
+        if self.contributing_factors.len() > MAX_CONTRIBUTING_FACTORS {
+            return Err(Error::validation(
+                "contributing_factors",
+                &format!(
+                    "A postmortem cannot have more than {} contributing factors",
+                    MAX_CONTRIBUTING_FACTORS
+                ),
+            ));
+        }
+        if self
+            .contributing_factors
+            .iter()
+            .any(|factor| factor.trim().is_empty() || factor.len() > MAX_CONTRIBUTING_FACTOR_LENGTH)
+        {
+            return Err(Error::validation(
+                "contributing_factors",
+                &format!(
+                    "Contributing factors must be between 1 and {} characters",
+                    MAX_CONTRIBUTING_FACTOR_LENGTH
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn validate_action_item_title(title: &str) -> Result<()> {
+    if title.trim().is_empty() || title.len() > MAX_ACTION_ITEM_TITLE_LENGTH {
+        return Err(Error::validation(
+            "title",
+            &format!(
+                "Title must be between 1 and {} characters",
+                MAX_ACTION_ITEM_TITLE_LENGTH
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// API request structure for adding postmortem action items
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateActionItemRequest {
+    pub title: String,
+    /// Task tracking the work
+    pub task_id: Option<Uuid>,
+    pub assigned_to: Option<Uuid>,
+}
+
+impl Validate for CreateActionItemRequest {
+    fn validate(&self) -> Result<()> {
+        validate_action_item_title(&self.title)
+    }
+}
+
+// API request structure for updating postmortem action items
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateActionItemRequest {
+    pub title: Option<String>,
+    pub task_id: Option<Uuid>,
+    pub assigned_to: Option<Uuid>,
+    /// Mark the item done (`true`) or reopen it (`false`)
+    pub completed: Option<bool>,
+}
+
+impl Validate for UpdateActionItemRequest {
+    fn validate(&self) -> Result<()> {
+        match &self.title {
+            Some(title) => validate_action_item_title(title),
+            None => Ok(()),
+        }
+    }
+}
+
+// Public page showing selected uptime checks and incidents
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StatusPage {
+    pub id: Uuid,
+    /// Served unauthenticated at `GET /status/{slug}`
+    pub slug: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// Uptime checks in display order
+    pub check_ids: Vec<Uuid>,
+    /// Incidents shown while active, besides those opened by the checks' alerts
+    pub incident_ids: Vec<Uuid>,
+    pub created_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for creating status pages
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateStatusPageRequest {
+    /// Lowercase letters, digits and dashes
+    #[schema(max_length = 64)]
+    pub slug: String,
+    #[schema(max_length = 200)]
+    pub title: String,
+    #[schema(max_length = 2000)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub check_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub incident_ids: Vec<Uuid>,
+}
+
+impl Validate for CreateStatusPageRequest {
+    fn validate(&self) -> Result<()> {
+        validate_status_page(
+            Some(&self.slug),
+            Some(&self.title),
+            self.description.as_deref(),
+            Some(&self.check_ids),
+            Some(&self.incident_ids),
+        )
+    }
+}
+
+// API request structure for updating status pages; lists given replace the previous ones
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateStatusPageRequest {
+    pub slug: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub check_ids: Option<Vec<Uuid>>,
+    pub incident_ids: Option<Vec<Uuid>>,
+}
+
+impl Validate for UpdateStatusPageRequest {
+    fn validate(&self) -> Result<()> {
+        validate_status_page(
+            self.slug.as_deref(),
+            self.title.as_deref(),
+            self.description.as_deref(),
+            self.check_ids.as_deref(),
+            self.incident_ids.as_deref(),
+        )
+    }
+}
+
+fn validate_status_page(
+    slug: Option<&str>,
+    title: Option<&str>,
+    description: Option<&str>,
+    check_ids: Option<&[Uuid]>,
+    incident_ids: Option<&[Uuid]>,
+) -> Result<()> {
+    if let Some(slug) = slug {
+        let valid = !slug.is_empty()
+            && slug.len() <= MAX_STATUS_PAGE_SLUG_LENGTH
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !slug.starts_with('-')
+            && !slug.ends_with('-');
+        if !valid {
+            return Err(Error::validation(
+                "slug",
+                &format!(
+                    "Slug must be 1 to {} lowercase letters, digits or dashes",
+                    MAX_STATUS_PAGE_SLUG_LENGTH
+                ),
+            ));
+        }
+    }
+    if let Some(title) = title
+        && (title.trim().is_empty() || title.len() > MAX_STATUS_PAGE_TITLE_LENGTH)
+    {
+        return Err(Error::validation(
+            "title",
+            &format!(
+                "Title must be between 1 and {} characters",
+                MAX_STATUS_PAGE_TITLE_LENGTH
+            ),
+        ));
+    }
+    if let Some(description) = description
+        && description.len() > MAX_STATUS_PAGE_DESCRIPTION_LENGTH
+    {
+        return Err(Error::validation(
+            "description",
+            &format!(
+                "Description must be at most {} characters",
+                MAX_STATUS_PAGE_DESCRIPTION_LENGTH
+            ),
+        ));
+    }
+    if let Some(check_ids) = check_ids
+        && check_ids.len() > MAX_STATUS_PAGE_CHECKS
+    {
+        return Err(Error::validation(
+            "check_ids",
+            &format!(
+                "A status page can show at most {} checks",
+                MAX_STATUS_PAGE_CHECKS
+            ),
+        ));
+    }
+    if let Some(incident_ids) = incident_ids
+        && incident_ids.len() > MAX_STATUS_PAGE_INCIDENTS
+    {
+        return Err(Error::validation(
+            "incident_ids",
+            &format!(
+                "A status page can show at most {} incidents",
+                MAX_STATUS_PAGE_INCIDENTS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// Overall state at the top of a status page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPageStatus {
+    /// Every check is up or pending and no incident is active
+    Operational,
+    /// An incident is active
+    Degraded,
+    /// A check is down
+    Outage,
+}
+
+// Uptime check as shown on a public status page
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StatusPageCheck {
+    pub name: String,
+    pub status: UptimeCheckStatus,
+    #[schema(format = "date-time")]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// Percentage of passing checks over the window; unset without results
+    pub uptime_24h: Option<f64>,
+    pub uptime_7d: Option<f64>,
+    pub uptime_30d: Option<f64>,
+}
+
+// Active incident as shown on a public status page
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct StatusPageIncident {
+    pub title: String,
+    pub severity: IncidentSeverity,
+    pub status: IncidentStatus,
+    #[schema(format = "date-time")]
+    pub started_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// Unauthenticated status page response
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PublicStatusPage {
+    pub slug: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: StatusPageStatus,
+    pub checks: Vec<StatusPageCheck>,
+    /// Active incidents, newest first
+    pub incidents: Vec<StatusPageIncident>,
+    #[schema(format = "date-time")]
+    pub generated_at: DateTime<Utc>,
+}
+
+// Query filters for events
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventFilter {
+    pub event_type: Option<EventType>,
+    pub source: Option<String>,
+    pub level: Option<String>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub tags: Option<HashMap<String, String>>,
+    /// Only events scoped to this organization
+    pub org_id: Option<Uuid>,
+    /// Hide events scoped to organizations this user doesn't belong to
+    #[serde(skip)]
+    pub visible_to: Option<Uuid>,
+    /// Only events of this tenant
+    #[serde(skip)]
+    pub tenant_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl Default for EventFilter {
+    fn default() -> Self {
+        Self {
+            event_type: None,
+            source: None,
+            level: None,
+            start_time: None,
+            end_time: None,
+            tags: None,
+            org_id: None,
+            visible_to: None,
+            tenant_id: None,
+            limit: Some(100),
+            offset: Some(0),
+        }
+    }
+}
+
+// Query filters for metrics
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct MetricFilter {
     pub name: Option<String>,
@@ -546,6 +1981,9 @@ pub struct MetricFilter {
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub labels: Option<HashMap<String, String>>,
+    /// Only metrics of this tenant
+    #[serde(skip)]
+    pub tenant_id: Option<Uuid>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -558,6 +1996,7 @@ impl Default for MetricFilter {
             start_time: None,
             end_time: None,
             labels: None,
+            tenant_id: None,
             limit: Some(100),
             offset: Some(0),
         }
@@ -568,12 +2007,86 @@ impl Default for MetricFilter {
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct TimelineEntry {
     pub id: Uuid,
+    pub entry_type: TimelineEntryType,
     pub recorded_at: DateTime<Utc>,
-    pub event_type: EventType,
+    /// Unset for notes
+    pub event_type: Option<EventType>,
+    /// Event source, or the note author's username
     pub source: String,
+    /// Event message, or the note body
     pub message: String,
     pub level: Option<String>,
     pub tags: HashMap<String, serde_json::Value>,
+    /// Note author; unset for events and notes of deleted users
+    pub author_id: Option<Uuid>,
+}
+
+// Kind of an incident timeline entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineEntryType {
+    Event,
+    Note,
+}
+
+// Note posted onto an incident timeline
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IncidentNote {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    #[schema(format = "date-time")]
+    pub noted_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+fn validate_note_body(body: &str) -> Result<()> {
+    if body.trim().is_empty() || body.len() > MAX_INCIDENT_NOTE_LENGTH {
+        return Err(Error::validation(
+            "body",
+            &format!(
+                "Note must be between 1 and {} characters",
+                MAX_INCIDENT_NOTE_LENGTH
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// API request structure for posting incident notes
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateIncidentNoteRequest {
+    pub body: String,
+    /// Where the note sits on the timeline (default: now)
+    #[schema(format = "date-time")]
+    pub noted_at: Option<DateTime<Utc>>,
+}
+
+impl Validate for CreateIncidentNoteRequest {
+    fn validate(&self) -> Result<()> {
+        validate_note_body(&self.body)
+    }
+}
+
+// API request structure for editing incident notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateIncidentNoteRequest {
+    pub body: Option<String>,
+    #[schema(format = "date-time")]
+    pub noted_at: Option<DateTime<Utc>>,
+}
+
+impl Validate for UpdateIncidentNoteRequest {
+    fn validate(&self) -> Result<()> {
+        match &self.body {
+            Some(body) => validate_note_body(body),
+            None => Ok(()),
+        }
+    }
 }
 
 // Incident timeline response
@@ -595,6 +2108,330 @@ pub struct MonitoringStats {
     pub open_incidents: i64,
     pub events_last_hour: i64,
     pub metrics_last_hour: i64,
+    /// Configured series limit per metric name; 0 when disabled
+    pub metric_series_limit: u32,
+    /// Metric names with the most series, highest first
+    pub metric_cardinality: Vec<MetricCardinality>,
+    /// Today's ingestion, sources and users with quotas first, then the busiest
+    pub ingestion_usage: Vec<IngestionUsage>,
+    /// When daily ingestion quotas reset (next UTC midnight)
+    #[schema(format = "date-time")]
+    pub quotas_reset_at: DateTime<Utc>,
+    /// The app's own requests and task executions in the last hour
+    pub app_activity: AppActivity,
+}
+
+// Activity recorded by the server and worker about themselves
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AppActivity {
+    pub http_requests: i64,
+    /// Requests answered with a 5xx status
+    pub http_server_errors: i64,
+    pub tasks_completed: i64,
+    pub tasks_failed: i64,
+}
+
+// Distinct label combinations stored for one metric name
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricCardinality {
+    pub name: String,
+    pub series: i64,
+}
+
+/// Which data a metric series query reads
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricResolution {
+    /// Raw samples when the range is still within raw retention, rollups otherwise
+    #[default]
+    Auto,
+    /// Raw samples as submitted
+    Raw,
+    /// 5-minute rollups
+    Rollup,
+}
+
+// One point of a metric series; raw samples are reported as single-sample buckets
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricPoint {
+    #[schema(format = "date-time")]
+    pub timestamp: DateTime<Utc>,
+    pub labels: serde_json::Value,
+    pub count: i64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub last: f64,
+}
+
+// Metric series response
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricSeries {
+    pub name: String,
+    /// `raw` or `rollup`; never `auto`
+    pub resolution: MetricResolution,
+    /// Bucket width in seconds, or null for raw samples
+    pub resolution_secs: Option<i32>,
+    pub points: Vec<MetricPoint>,
+}
+
+// Aggregation applied to the samples in each step of a metric query
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum MetricAggregation {
+    Avg,
+    Sum,
+    Min,
+    Max,
+    Count,
+    /// `p1` to `p99`; needs raw samples
+    Percentile(u8),
+}
+
+impl std::fmt::Display for MetricAggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetricAggregation::Avg => write!(f, "avg"),
+            MetricAggregation::Sum => write!(f, "sum"),
+            MetricAggregation::Min => write!(f, "min"),
+            MetricAggregation::Max => write!(f, "max"),
+            MetricAggregation::Count => write!(f, "count"),
+            MetricAggregation::Percentile(p) => write!(f, "p{p}"),
+        }
+    }
+}
+
+impl std::str::FromStr for MetricAggregation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "avg" => Ok(MetricAggregation::Avg),
+            "sum" => Ok(MetricAggregation::Sum),
+            "min" => Ok(MetricAggregation::Min),
+            "max" => Ok(MetricAggregation::Max),
+            "count" => Ok(MetricAggregation::Count),
+            _ => s
+                .strip_prefix('p')
+                .and_then(|p| p.parse::<u8>().ok())
+                .filter(|p| (1..=99).contains(p))
+                .map(MetricAggregation::Percentile)
+                .ok_or_else(|| {
+                    Error::validation(
+                        "aggregation",
+                        "Aggregation must be avg, sum, min, max, count or p1-p99",
+                    )
+                }),
+        }
+    }
+}
+
+impl TryFrom<String> for MetricAggregation {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<MetricAggregation> for String {
+    fn from(aggregation: MetricAggregation) -> Self {
+        aggregation.to_string()
+    }
+}
+
+// One aggregated value of a metric query series
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricQueryPoint {
+    /// Start of the step
+    #[schema(format = "date-time")]
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+// Series for one combination of the grouping labels
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricQuerySeries {
+    /// Values of the `by` labels; empty when not grouping
+    pub labels: serde_json::Value,
+    pub points: Vec<MetricQueryPoint>,
+}
+
+// Metric query response
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricQueryResult {
+    pub name: String,
+    /// `avg`, `sum`, `min`, `max`, `count` or `p1`-`p99`
+    #[schema(value_type = String, example = "p95")]
+    pub aggregation: MetricAggregation,
+    pub step_secs: i64,
+    /// `raw` or `rollup`; never `auto`
+    pub resolution: MetricResolution,
+    pub series: Vec<MetricQuerySeries>,
+}
+
+// Retention override for a single metric name
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricRetentionPolicy {
+    pub metric_name: String,
+    pub raw_retention_days: i32,
+    pub rollup_retention_days: i32,
+    pub updated_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// Configured defaults plus per-metric overrides
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MetricRetentionSettings {
+    pub default_raw_retention_days: u32,
+    pub default_rollup_retention_days: u32,
+    pub rollup_resolution_secs: i32,
+    pub policies: Vec<MetricRetentionPolicy>,
+}
+
+// API request structure for setting a metric's retention
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetMetricRetentionRequest {
+    #[schema(minimum = 1, maximum = 3650)]
+    pub raw_retention_days: i32,
+    #[schema(minimum = 1, maximum = 3650)]
+    pub rollup_retention_days: i32,
+}
+
+impl Validate for SetMetricRetentionRequest {
+    fn validate(&self) -> Result<()> {
+        for (field, days) in [
+            ("raw_retention_days", self.raw_retention_days),
+            ("rollup_retention_days", self.rollup_retention_days),
+        ] {
+            if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+                return Err(Error::validation(
+                    field,
+                    &format!(
+                        "Retention must be between 1 and {} days",
+                        MAX_RETENTION_DAYS
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Ingestion limit override for a single event source
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventSourceLimit {
+    pub source: String,
+    /// Events accepted per minute; 0 disables rate limiting for the source
+    pub events_per_minute: i32,
+    pub sample_ratio: f64,
+    pub updated_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// Configured ingestion limits plus per-source overrides
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EventLimitSettings {
+    pub default_events_per_minute: u32,
+    pub default_sample_ratio: f64,
+    pub user_events_per_minute: u32,
+    pub limits: Vec<EventSourceLimit>,
+}
+
+// API request structure for setting a source's ingestion limits
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetEventSourceLimitRequest {
+    #[schema(minimum = 0)]
+    pub events_per_minute: i32,
+    #[schema(minimum = 0.0, maximum = 1.0)]
+    pub sample_ratio: f64,
+}
+
+impl Validate for SetEventSourceLimitRequest {
+    fn validate(&self) -> Result<()> {
+        if self.events_per_minute < 0 {
+            return Err(Error::validation(
+                "events_per_minute",
+                "Rate limit cannot be negative",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.sample_ratio) {
+            return Err(Error::validation(
+                "sample_ratio",
+                "Sample ratio must be between 0.0 and 1.0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Daily ingestion quota of a user or event source
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IngestionQuota {
+    pub scope: QuotaScope,
+    pub key: String,
+    /// Events stored per UTC day; None when unlimited
+    pub daily_events: Option<i64>,
+    /// Metric rows stored per UTC day; None when unlimited (user quotas only)
+    pub daily_metrics: Option<i64>,
+    pub updated_by: Option<Uuid>,
+    #[schema(format = "date-time")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// API request structure for setting a daily ingestion quota
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetIngestionQuotaRequest {
+    /// Events stored per UTC day; omit for no event quota
+    #[schema(minimum = 0)]
+    pub daily_events: Option<i64>,
+    /// Metric rows stored per UTC day; omit for no metric quota
+    #[schema(minimum = 0)]
+    pub daily_metrics: Option<i64>,
+}
+
+impl SetIngestionQuotaRequest {
+    pub fn validate_for(&self, scope: QuotaScope) -> Result<()> {
+        if self.daily_events.is_none() && self.daily_metrics.is_none() {
+            return Err(Error::validation(
+                "daily_events",
+                "Set daily_events, daily_metrics or both",
+            ));
+        }
+        if self.daily_events.is_some_and(|quota| quota < 0) {
+            return Err(Error::validation(
+                "daily_events",
+                "Quota cannot be negative",
+            ));
+        }
+        if self.daily_metrics.is_some_and(|quota| quota < 0) {
+            return Err(Error::validation(
+                "daily_metrics",
+                "Quota cannot be negative",
+            ));
+        }
+        if scope == QuotaScope::Source && self.daily_metrics.is_some() {
+            return Err(Error::validation(
+                "daily_metrics",
+                "Metrics have no source; set metric quotas per user",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Today's ingestion by one user or source, with its quota if any
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IngestionUsage {
+    pub scope: QuotaScope,
+    pub key: String,
+    pub events: i64,
+    pub metrics: i64,
+    pub daily_events: Option<i64>,
+    pub daily_metrics: Option<i64>,
 }
 
 // IMPORTANT: From<String> implementations are REQUIRED by SQLx query_as! macros
@@ -660,6 +2497,66 @@ impl From<String> for AlertStatus {
     }
 }
 
+// Required by SQLx query_as! macro - see EventType implementation above for details
+impl From<String> for AlertSeverity {
+    fn from(s: String) -> Self {
+        AlertSeverity::from_str(&s).unwrap_or_else(|_| {
+            tracing::error!(
+                security.data_corruption = true,
+                alert_severity = %s,
+                "CRITICAL: Invalid alert_severity in database '{}' - this indicates data corruption. Falling back to 'warning'",
+                s
+            );
+            AlertSeverity::Warning
+        })
+    }
+}
+
+// Required by SQLx query_as! macro - see EventType implementation above for details
+impl From<String> for NotificationChannelKind {
+    fn from(s: String) -> Self {
+        NotificationChannelKind::from_str(&s).unwrap_or_else(|_| {
+            tracing::error!(
+                security.data_corruption = true,
+                notification_channel_kind = %s,
+                "CRITICAL: Invalid notification_channel_kind in database '{}' - this indicates data corruption. Falling back to 'webhook'",
+                s
+            );
+            NotificationChannelKind::Webhook
+        })
+    }
+}
+
+// Required by SQLx query_as! macro - see EventType implementation above for details
+impl From<String> for UptimeCheckStatus {
+    fn from(s: String) -> Self {
+        UptimeCheckStatus::from_str(&s).unwrap_or_else(|_| {
+            tracing::error!(
+                security.data_corruption = true,
+                uptime_check_status = %s,
+                "CRITICAL: Invalid uptime_check_status in database '{}' - this indicates data corruption. Falling back to 'pending'",
+                s
+            );
+            UptimeCheckStatus::Pending
+        })
+    }
+}
+
+// Required by SQLx query_as! macro - see EventType implementation above for details
+impl From<String> for QuotaScope {
+    fn from(s: String) -> Self {
+        QuotaScope::from_str(&s).unwrap_or_else(|_| {
+            tracing::error!(
+                security.data_corruption = true,
+                quota_scope = %s,
+                "CRITICAL: Invalid quota_scope in database '{}' - this indicates data corruption. Falling back to 'source'",
+                s
+            );
+            QuotaScope::Source
+        })
+    }
+}
+
 // Required by SQLx query_as! macro - see EventType implementation above for details
 impl From<String> for IncidentSeverity {
     fn from(s: String) -> Self {
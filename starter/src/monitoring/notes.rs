@@ -0,0 +1,143 @@
+use crate::auth::AuthUser;
+use crate::monitoring::models::{
+    CreateIncidentNoteRequest, IncidentNote, UpdateIncidentNoteRequest, Validate,
+};
+use crate::rbac::services as rbac_services;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// How far ahead of the server clock a note may be placed, for client clock skew
+const MAX_NOTE_CLOCK_SKEW: chrono::Duration = chrono::Duration::minutes(5);
+
+fn validate_noted_at(noted_at: Option<DateTime<Utc>>) -> Result<()> {
+    if noted_at.is_some_and(|noted_at| noted_at > Utc::now() + MAX_NOTE_CLOCK_SKEW) {
+        return Err(Error::validation(
+            "noted_at",
+            "Notes cannot be placed in the future",
+        ));
+    }
+    Ok(())
+}
+
+/// Post a note onto the timeline of an incident
+pub async fn create_note(
+    conn: &mut DbConn,
+    incident_id: Uuid,
+    author_id: Uuid,
+    request: CreateIncidentNoteRequest,
+) -> Result<IncidentNote> {
+    request.validate()?;
+    validate_noted_at(request.noted_at)?;
+
+    let incident_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM incidents WHERE id = $1) AS "exists!""#,
+        incident_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !incident_exists {
+        return Err(Error::NotFound("Incident not found".to_string()));
+    }
+
+    sqlx::query_as!(
+        IncidentNote,
+        r#"
+        INSERT INTO incident_notes (incident_id, author_id, body, noted_at)
+        VALUES ($1, $2, $3, COALESCE($4, NOW()))
+        RETURNING id, incident_id, author_id, body, noted_at, created_at, updated_at
+        "#,
+        incident_id,
+        author_id,
+        request.body.trim(),
+        request.noted_at
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Find a note and check the user may change it
+///
+/// Authors may change their own notes and moderators any note.
+async fn find_note_for_editor(
+    conn: &mut DbConn,
+    auth_user: &AuthUser,
+    incident_id: Uuid,
+    note_id: Uuid,
+) -> Result<IncidentNote> {
+    let note = sqlx::query_as!(
+        IncidentNote,
+        r#"
+        SELECT id, incident_id, author_id, body, noted_at, created_at, updated_at
+        FROM incident_notes
+        WHERE incident_id = $1 AND id = $2
+        "#,
+        incident_id,
+        note_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Note not found".to_string()))?;
+
+    match note.author_id {
+        Some(author_id) => rbac_services::can_access_own_resource(auth_user, author_id)?,
+        None => rbac_services::require_moderator_or_higher(auth_user)?,
+    }
+    Ok(note)
+}
+
+/// Edit a note on the timeline of an incident
+pub async fn update_note(
+    conn: &mut DbConn,
+    auth_user: &AuthUser,
+    incident_id: Uuid,
+    note_id: Uuid,
+    request: UpdateIncidentNoteRequest,
+) -> Result<IncidentNote> {
+    request.validate()?;
+    validate_noted_at(request.noted_at)?;
+    find_note_for_editor(conn, auth_user, incident_id, note_id).await?;
+
+    sqlx::query_as!(
+        IncidentNote,
+        r#"
+        UPDATE incident_notes
+        SET body = COALESCE($3, body),
+            noted_at = COALESCE($4, noted_at),
+            updated_at = NOW()
+        WHERE incident_id = $1 AND id = $2
+        RETURNING id, incident_id, author_id, body, noted_at, created_at, updated_at
+        "#,
+        incident_id,
+        note_id,
+        request.body.as_deref().map(str::trim),
+        request.noted_at
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Note not found".to_string()))
+}
+
+/// Delete a note from the timeline of an incident
+pub async fn delete_note(
+    conn: &mut DbConn,
+    auth_user: &AuthUser,
+    incident_id: Uuid,
+    note_id: Uuid,
+) -> Result<()> {
+    find_note_for_editor(conn, auth_user, incident_id, note_id).await?;
+
+    sqlx::query!(
+        "DELETE FROM incident_notes WHERE incident_id = $1 AND id = $2",
+        incident_id,
+        note_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
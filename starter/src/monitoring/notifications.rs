@@ -0,0 +1,387 @@
+use crate::monitoring::models::{
+    Alert, AlertNotification, AlertTestResult, ChannelDelivery, CreateNotificationChannelRequest,
+    MAX_ALERT_CHANNELS, NotificationChannel, NotificationChannelKind, SetAlertChannelsRequest,
+    Validate,
+};
+use crate::monitoring::routing;
+use crate::tasks::services::{EmailMessage, TaskServices};
+use crate::{DbConn, Error, Result};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::Acquire;
+use tracing::warn;
+use uuid::Uuid;
+
+impl AlertNotification {
+    /// A firing notification for `alert`
+    pub fn firing(alert: &Alert, test: bool) -> Self {
+        Self::new(alert, "firing", test)
+    }
+
+    /// A notification that `alert` stopped firing
+    pub fn resolved(alert: &Alert) -> Self {
+        Self::new(alert, "resolved", false)
+    }
+
+    fn new(alert: &Alert, status: &str, test: bool) -> Self {
+        Self {
+            alert_id: alert.id,
+            alert_name: alert.name.clone(),
+            description: alert.description.clone(),
+            query: alert.query.clone(),
+            threshold_value: alert.threshold_value,
+            severity: alert.severity,
+            labels: alert.labels.clone(),
+            status: status.to_string(),
+            test,
+            fired_at: Utc::now(),
+        }
+    }
+
+    fn summary(&self) -> String {
+        let prefix = if self.test { "[TEST] " } else { "" };
+        let mut summary = format!("{prefix}Alert {}: {}", self.status, self.alert_name);
+        if let Some(description) = &self.description {
+            summary.push_str(&format!("\n{description}"));
+        }
+        summary.push_str(&format!("\nQuery: {}", self.query));
+        if let Some(threshold) = self.threshold_value {
+            summary.push_str(&format!("\nThreshold: {threshold}"));
+        }
+        summary
+    }
+}
+
+/// Send a notification to one channel
+///
+/// Webhooks receive the notification as JSON, Slack channels a text message
+/// and email channels a plain-text email.
+pub async fn deliver(
+    services: &TaskServices,
+    channel: &NotificationChannel,
+    notification: &AlertNotification,
+) -> std::result::Result<(), String> {
+    let request = match channel.kind {
+        NotificationChannelKind::Webhook => {
+            services.http().post(&channel.target).json(notification)
+        }
+        NotificationChannelKind::Slack => services
+            .http()
+            .post(&channel.target)
+            .json(&json!({ "text": notification.summary() })),
+        NotificationChannelKind::Email => {
+            let message = EmailMessage {
+                to: channel.target.clone(),
+                subject: notification
+                    .summary()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                body: notification.summary(),
+            };
+            return services
+                .email()
+                .send(&message)
+                .await
+                .map_err(|e| e.to_string());
+        }
+    };
+
+    let response = services
+        .http()
+        .send(request)
+        .await
+        .map_err(|e| format!("Request failed: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("Endpoint returned HTTP {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Send an alert's notification to its channels and those of matching routing rules
+///
+/// A firing notification also opens an incident when a matching rule has an
+/// escalation policy, and a resolved one resolves it. Test notifications are
+/// only delivered. Returns a delivery per channel; failures do not fail the
+/// call.
+pub async fn dispatch(
+    conn: &mut DbConn,
+    services: &TaskServices,
+    alert: &Alert,
+    notification: &AlertNotification,
+) -> Result<Vec<ChannelDelivery>> {
+    let channels = routing::routed_channels(conn, alert).await?;
+    let mut deliveries = Vec::with_capacity(channels.len());
+    for channel in channels {
+        let result = deliver(services, &channel, notification).await;
+        if let Err(e) = &result {
+            warn!(
+                "Failed to notify channel '{}' of alert '{}': {}",
+                channel.name, alert.name, e
+            );
+        }
+        deliveries.push(ChannelDelivery {
+            channel_id: channel.id,
+            channel_name: channel.name,
+            kind: channel.kind,
+            delivered: result.is_ok(),
+            error: result.err(),
+        });
+    }
+
+    if !notification.test {
+        match notification.status.as_str() {
+            "firing" => {
+                routing::open_alert_incident(conn, alert, notification.fired_at).await?;
+            }
+            "resolved" => {
+                routing::resolve_alert_incident(conn, alert.id).await?;
+            }
+            _ => {}
+        }
+    }
+    Ok(deliveries)
+}
+
+/// Send a test notification for an alert to each of its routed channels
+///
+/// The alert itself is left unchanged and no incident is opened.
+pub async fn test_fire_alert(
+    conn: &mut DbConn,
+    services: &TaskServices,
+    alert_id: Uuid,
+) -> Result<AlertTestResult> {
+    let alert = crate::monitoring::services::find_alert_by_id(conn, alert_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Alert not found".to_string()))?;
+
+    let notification = AlertNotification::firing(&alert, true);
+    let deliveries = dispatch(conn, services, &alert, &notification).await?;
+    if deliveries.is_empty() {
+        return Err(Error::conflict(
+            "Alert has no notification channels and matches no routing rule",
+        ));
+    }
+
+    Ok(AlertTestResult {
+        notification,
+        deliveries,
+    })
+}
+
+// Notification channel management functions
+
+/// Create a channel in the tenant
+pub async fn create_channel(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateNotificationChannelRequest,
+    created_by: Option<Uuid>,
+) -> Result<NotificationChannel> {
+    request.validate()?;
+
+    sqlx::query_as!(
+        NotificationChannel,
+        r#"
+        INSERT INTO notification_channels (name, kind, target, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name, kind, target, created_by, created_at, updated_at
+        "#,
+        request.name.trim(),
+        request.kind.as_str(),
+        request.target.trim(),
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::Conflict(format!(
+            "Notification channel '{}' already exists",
+            request.name.trim()
+        )),
+        _ => Error::from_sqlx(e),
+    })
+}
+
+/// Channels of the tenant, by name
+pub async fn find_channels(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<NotificationChannel>> {
+    sqlx::query_as!(
+        NotificationChannel,
+        r#"
+        SELECT id, name, kind, target, created_by, created_at, updated_at
+        FROM notification_channels
+        WHERE tenant_id = $1
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a channel; alerts using it stop notifying it
+pub async fn delete_channel(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM notification_channels WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(
+            "Notification channel not found".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Fail with a validation error unless every channel belongs to the tenant
+pub async fn require_channels_in_tenant(
+    conn: &mut DbConn,
+    channel_ids: &[Uuid],
+    tenant_id: Uuid,
+) -> Result<()> {
+    let missing = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM UNNEST($1::UUID[]) AS requested(id)
+            WHERE NOT EXISTS(
+                SELECT 1 FROM notification_channels c
+                WHERE c.id = requested.id AND c.tenant_id = $2
+            )
+        ) AS "missing!"
+        "#,
+        channel_ids,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if missing {
+        return Err(Error::validation(
+            "channel_ids",
+            "Notification channel not found",
+        ));
+    }
+    Ok(())
+}
+
+/// Channels an alert notifies, by name
+pub async fn find_alert_channels(
+    conn: &mut DbConn,
+    alert_id: Uuid,
+) -> Result<Vec<NotificationChannel>> {
+    sqlx::query_as!(
+        NotificationChannel,
+        r#"
+        SELECT c.id, c.name, c.kind, c.target, c.created_by, c.created_at, c.updated_at
+        FROM notification_channels c
+        JOIN alert_notification_channels ac ON ac.channel_id = c.id
+        WHERE ac.alert_id = $1
+        ORDER BY c.name
+        "#,
+        alert_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Replace the channels an alert of the tenant notifies with channels of the tenant
+pub async fn set_alert_channels(
+    conn: &mut DbConn,
+    alert_id: Uuid,
+    tenant_id: Uuid,
+    request: SetAlertChannelsRequest,
+) -> Result<Vec<NotificationChannel>> {
+    if request.channel_ids.len() > MAX_ALERT_CHANNELS {
+        return Err(Error::validation(
+            "channel_ids",
+            &format!(
+                "An alert can notify at most {} channels",
+                MAX_ALERT_CHANNELS
+            ),
+        ));
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    crate::monitoring::services::ensure_alert(&mut tx, alert_id, tenant_id).await?;
+    require_channels_in_tenant(&mut tx, &request.channel_ids, tenant_id).await?;
+
+    sqlx::query!(
+        "DELETE FROM alert_notification_channels WHERE alert_id = $1",
+        alert_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO alert_notification_channels (alert_id, channel_id)
+        SELECT $1, channel_id FROM UNNEST($2::UUID[]) AS channel_id
+        ON CONFLICT DO NOTHING
+        "#,
+        alert_id,
+        &request.channel_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("channel_ids", "Notification channel not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let channels = find_alert_channels(&mut tx, alert_id).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::models::{AlertSeverity, AlertStatus};
+
+    #[test]
+    fn test_summary_marks_test_notifications() {
+        let now = Utc::now();
+        let alert = Alert {
+            id: Uuid::new_v4(),
+            name: "High error rate".to_string(),
+            description: Some("5xx above 5%".to_string()),
+            query: "error_rate".to_string(),
+            threshold_value: Some(0.05),
+            severity: AlertSeverity::Critical,
+            labels: serde_json::json!({}),
+            status: AlertStatus::Resolved,
+            triggered_at: None,
+            resolved_at: None,
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let summary = AlertNotification::firing(&alert, true).summary();
+        assert!(summary.starts_with("[TEST] Alert firing: High error rate\n"));
+        assert!(summary.contains("Threshold: 0.05"));
+        assert!(
+            AlertNotification::firing(&alert, false)
+                .summary()
+                .starts_with("Alert firing")
+        );
+        assert!(
+            AlertNotification::resolved(&alert)
+                .summary()
+                .starts_with("Alert resolved: High error rate")
+        );
+    }
+}
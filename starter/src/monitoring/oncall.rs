@@ -0,0 +1,470 @@
+use crate::monitoring::models::{
+    CreateOncallOverrideRequest, CreateOncallScheduleRequest, OncallOverride, OncallSchedule,
+    OncallShift, Validate,
+};
+use crate::tenants::services as tenant_services;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::Acquire;
+use uuid::Uuid;
+
+impl OncallSchedule {
+    /// Who is on call at `at`: the latest override covering it, otherwise the rotation
+    pub fn shift_at(&self, at: DateTime<Utc>) -> Option<OncallShift> {
+        if let Some(cover) = self
+            .overrides
+            .iter()
+            .filter(|cover| cover.starts_at <= at && at < cover.ends_at)
+            .max_by_key(|cover| cover.created_at)
+        {
+            return Some(OncallShift {
+                schedule_id: self.id,
+                schedule_name: self.name.clone(),
+                user_id: cover.user_id,
+                starts_at: cover.starts_at,
+                ends_at: cover.ends_at,
+                override_id: Some(cover.id),
+            });
+        }
+
+        if self.members.is_empty() || self.rotation_period_hours <= 0 {
+            return None;
+        }
+        let period = Duration::hours(self.rotation_period_hours.into());
+        // Shifts also repeat backwards, so times before the first handoff resolve too
+        let shift = (at - self.handoff_at)
+            .num_seconds()
+            .div_euclid(period.num_seconds());
+        let member = shift.rem_euclid(self.members.len() as i64) as usize;
+        let starts_at = self.handoff_at + Duration::seconds(period.num_seconds() * shift);
+
+        Some(OncallShift {
+            schedule_id: self.id,
+            schedule_name: self.name.clone(),
+            user_id: self.members[member],
+            starts_at,
+            ends_at: starts_at + period,
+            override_id: None,
+        })
+    }
+}
+
+/// Who is on call now for every schedule of the tenant, or only for `schedule_id`
+pub async fn find_current_oncall(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    schedule_id: Option<Uuid>,
+) -> Result<Vec<OncallShift>> {
+    let now = Utc::now();
+    let schedules = match schedule_id {
+        Some(id) => vec![
+            find_schedule_at(conn, id, Some(tenant_id), now)
+                .await?
+                .ok_or_else(|| Error::NotFound("On-call schedule not found".to_string()))?,
+        ],
+        None => find_schedules_at(conn, tenant_id, now).await?,
+    };
+
+    Ok(schedules
+        .iter()
+        .filter_map(|schedule| schedule.shift_at(now))
+        .collect())
+}
+
+/// The user on call for a schedule at `at`, if the schedule exists and has members
+pub async fn find_oncall_user(
+    conn: &mut DbConn,
+    schedule_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Option<Uuid>> {
+    Ok(find_schedule_at(conn, schedule_id, None, at)
+        .await?
+        .and_then(|schedule| schedule.shift_at(at))
+        .map(|shift| shift.user_id))
+}
+
+// On-call schedule management functions
+
+/// Create a schedule in the tenant; its members must be users of the tenant
+pub async fn create_oncall_schedule(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateOncallScheduleRequest,
+    created_by: Option<Uuid>,
+) -> Result<OncallSchedule> {
+    request.validate()?;
+    tenant_services::require_users_in_tenant(conn, "members", &request.members, tenant_id).await?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let schedule = sqlx::query!(
+        r#"
+        INSERT INTO oncall_schedules (name, description, rotation_period_hours, handoff_at,
+                                      created_by, tenant_id)
+        VALUES ($1, $2, $3, COALESCE($4, NOW()), $5, $6)
+        RETURNING id, handoff_at, created_at, updated_at
+        "#,
+        request.name.trim(),
+        request.description,
+        request.rotation_period_hours,
+        request.handoff_at,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| map_write_error(e, &request.name))?;
+
+    for (position, user_id) in (0..).zip(&request.members) {
+        sqlx::query!(
+            "INSERT INTO oncall_schedule_members (schedule_id, position, user_id) VALUES ($1, $2, $3)",
+            schedule.id,
+            position,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| map_write_error(e, &request.name))?;
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(OncallSchedule {
+        id: schedule.id,
+        name: request.name.trim().to_string(),
+        description: request.description,
+        rotation_period_hours: request.rotation_period_hours,
+        handoff_at: schedule.handoff_at,
+        members: request.members,
+        overrides: Vec::new(),
+        created_by,
+        created_at: schedule.created_at,
+        updated_at: schedule.updated_at,
+    })
+}
+
+/// Schedules of the tenant, by name
+pub async fn find_oncall_schedules(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<Vec<OncallSchedule>> {
+    find_schedules_at(conn, tenant_id, Utc::now()).await
+}
+
+/// Schedule of the tenant with the given ID
+pub async fn find_oncall_schedule_by_id(
+    conn: &mut DbConn,
+    id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<OncallSchedule>> {
+    find_schedule_at(conn, id, Some(tenant_id), Utc::now()).await
+}
+
+/// Fail with a validation error unless every schedule belongs to the tenant
+pub async fn require_schedules_in_tenant(
+    conn: &mut DbConn,
+    schedule_ids: &[Uuid],
+    tenant_id: Uuid,
+) -> Result<()> {
+    let missing = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM UNNEST($1::UUID[]) AS requested(id)
+            WHERE NOT EXISTS(
+                SELECT 1 FROM oncall_schedules s
+                WHERE s.id = requested.id AND s.tenant_id = $2
+            )
+        ) AS "missing!"
+        "#,
+        schedule_ids,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if missing {
+        return Err(Error::validation(
+            "notify_schedule_id",
+            "On-call schedule not found",
+        ));
+    }
+    Ok(())
+}
+
+async fn find_schedules_at(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Vec<OncallSchedule>> {
+    let ids = sqlx::query_scalar!(
+        "SELECT id FROM oncall_schedules WHERE tenant_id = $1 ORDER BY name",
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut schedules = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(schedule) = find_schedule_at(conn, id, Some(tenant_id), at).await? {
+            schedules.push(schedule);
+        }
+    }
+    Ok(schedules)
+}
+
+/// Load a schedule, of the tenant when given, with the overrides still running at `at`
+async fn find_schedule_at(
+    conn: &mut DbConn,
+    id: Uuid,
+    tenant_id: Option<Uuid>,
+    at: DateTime<Utc>,
+) -> Result<Option<OncallSchedule>> {
+    let Some(schedule) = sqlx::query!(
+        r#"
+        SELECT id, name, description, rotation_period_hours, handoff_at,
+               created_by, created_at, updated_at
+        FROM oncall_schedules
+        WHERE id = $1 AND ($2::UUID IS NULL OR tenant_id = $2)
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    else {
+        return Ok(None);
+    };
+
+    let members = sqlx::query_scalar!(
+        "SELECT user_id FROM oncall_schedule_members WHERE schedule_id = $1 ORDER BY position",
+        id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let overrides = sqlx::query_as!(
+        OncallOverride,
+        r#"
+        SELECT id, schedule_id, user_id, starts_at, ends_at, created_by, created_at
+        FROM oncall_overrides
+        WHERE schedule_id = $1 AND ends_at > $2
+        ORDER BY starts_at, created_at
+        "#,
+        id,
+        at
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(Some(OncallSchedule {
+        id: schedule.id,
+        name: schedule.name,
+        description: schedule.description,
+        rotation_period_hours: schedule.rotation_period_hours,
+        handoff_at: schedule.handoff_at,
+        members,
+        overrides,
+        created_by: schedule.created_by,
+        created_at: schedule.created_at,
+        updated_at: schedule.updated_at,
+    }))
+}
+
+/// Delete a schedule; fails while an escalation policy still notifies it
+pub async fn delete_oncall_schedule(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM oncall_schedules WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => Error::conflict(
+            "On-call schedule is used by an escalation policy; delete the policy first",
+        ),
+        _ => Error::from_sqlx(e),
+    })?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("On-call schedule not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Put a user of the tenant on call for one of its schedules for a while, in
+/// place of the rotation
+pub async fn create_oncall_override(
+    conn: &mut DbConn,
+    schedule_id: Uuid,
+    tenant_id: Uuid,
+    request: CreateOncallOverrideRequest,
+    created_by: Option<Uuid>,
+) -> Result<OncallOverride> {
+    request.validate()?;
+
+    let schedule_exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM oncall_schedules WHERE id = $1 AND tenant_id = $2)
+            AS "exists!"
+        "#,
+        schedule_id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !schedule_exists {
+        return Err(Error::NotFound("On-call schedule not found".to_string()));
+    }
+    tenant_services::require_users_in_tenant(conn, "user_id", &[request.user_id], tenant_id)
+        .await?;
+
+    sqlx::query_as!(
+        OncallOverride,
+        r#"
+        INSERT INTO oncall_overrides (schedule_id, user_id, starts_at, ends_at, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, schedule_id, user_id, starts_at, ends_at, created_by, created_at
+        "#,
+        schedule_id,
+        request.user_id,
+        request.starts_at,
+        request.ends_at,
+        created_by
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("user_id", "User not found")
+        }
+        _ => Error::from_sqlx(e),
+    })
+}
+
+pub async fn delete_oncall_override(
+    conn: &mut DbConn,
+    schedule_id: Uuid,
+    tenant_id: Uuid,
+    override_id: Uuid,
+) -> Result<()> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM oncall_overrides
+        WHERE schedule_id = $1 AND id = $2
+          AND schedule_id IN (SELECT id FROM oncall_schedules WHERE tenant_id = $3)
+        "#,
+        schedule_id,
+        override_id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("On-call override not found".to_string()));
+    }
+    Ok(())
+}
+
+fn map_write_error(err: sqlx::Error, name: &str) -> Error {
+    match &err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("On-call schedule '{}' already exists", name.trim()))
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("members", "User not found")
+        }
+        _ => Error::from_sqlx(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(members: Vec<Uuid>, overrides: Vec<OncallOverride>) -> OncallSchedule {
+        let handoff_at = Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+        OncallSchedule {
+            id: Uuid::new_v4(),
+            name: "Platform".to_string(),
+            description: None,
+            rotation_period_hours: 24,
+            handoff_at,
+            members,
+            overrides,
+            created_by: None,
+            created_at: handoff_at,
+            updated_at: handoff_at,
+        }
+    }
+
+    #[test]
+    fn test_rotation_hands_off_each_period() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let schedule = schedule(vec![alice, bob], Vec::new());
+        let at = |day, hour| Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+
+        let shift = schedule.shift_at(at(1, 9)).unwrap();
+        assert_eq!(shift.user_id, alice);
+        assert_eq!(shift.starts_at, at(1, 9));
+        assert_eq!(shift.ends_at, at(2, 9));
+
+        assert_eq!(schedule.shift_at(at(2, 8)).unwrap().user_id, alice);
+        assert_eq!(schedule.shift_at(at(2, 9)).unwrap().user_id, bob);
+        assert_eq!(schedule.shift_at(at(3, 12)).unwrap().user_id, alice);
+
+        // Before the first handoff the rotation runs backwards
+        let shift = schedule
+            .shift_at(Utc.with_ymd_and_hms(2023, 12, 31, 12, 0, 0).unwrap())
+            .unwrap();
+        assert_eq!(shift.user_id, bob);
+        assert_eq!(shift.starts_at, at(1, 9) - Duration::hours(24));
+    }
+
+    #[test]
+    fn test_latest_override_wins() {
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let at = |hour| Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+        let cover = |user_id, starts_at, ends_at, created_at| OncallOverride {
+            id: Uuid::new_v4(),
+            schedule_id: Uuid::new_v4(),
+            user_id,
+            starts_at,
+            ends_at,
+            created_by: None,
+            created_at,
+        };
+        let schedule = schedule(
+            vec![alice],
+            vec![
+                cover(bob, at(10), at(14), at(1)),
+                cover(carol, at(12), at(13), at(2)),
+            ],
+        );
+
+        assert_eq!(schedule.shift_at(at(9)).unwrap().user_id, alice);
+        let shift = schedule.shift_at(at(11)).unwrap();
+        assert_eq!(shift.user_id, bob);
+        assert!(shift.override_id.is_some());
+        assert_eq!(schedule.shift_at(at(12)).unwrap().user_id, carol);
+        assert_eq!(schedule.shift_at(at(13)).unwrap().user_id, bob);
+        assert_eq!(schedule.shift_at(at(14)).unwrap().user_id, alice);
+    }
+
+    #[test]
+    fn test_empty_schedule_has_nobody_on_call() {
+        assert!(
+            schedule(Vec::new(), Vec::new())
+                .shift_at(Utc::now())
+                .is_none()
+        );
+    }
+}
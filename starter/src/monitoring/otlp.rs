@@ -0,0 +1,906 @@
+//! OpenTelemetry OTLP/HTTP ingestion (JSON encoding)
+//!
+//! Decodes `ExportTraceServiceRequest`, `ExportMetricsServiceRequest` and
+//! `ExportLogsServiceRequest` bodies and maps them onto the existing tables:
+//! spans become `trace` events, log records become `log` events and metric
+//! data points become rows in `metrics`. Only the field subset the starter
+//! stores is decoded; unknown fields are ignored.
+
+use crate::monitoring::histogram;
+use crate::monitoring::models::{CreateEventRequest, CreateMetricRequest, MetricType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// Source used when a resource has no `service.name`, as the OTel SDKs do
+pub const UNKNOWN_SERVICE: &str = "unknown_service";
+
+/// Maximum spans, log records or data points accepted in one export
+pub const MAX_OTLP_ITEMS: usize = 10_000;
+
+/// Maximum decompressed request body accepted by the OTLP endpoints
+pub const MAX_OTLP_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+// Common types
+
+/// OTLP `AnyValue`; exactly one field is set
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnyValue {
+    string_value: Option<String>,
+    bool_value: Option<bool>,
+    #[serde(default, deserialize_with = "i64_or_string")]
+    int_value: Option<i64>,
+    #[serde(default, deserialize_with = "f64_or_string")]
+    double_value: Option<f64>,
+    array_value: Option<ArrayValue>,
+    kvlist_value: Option<KeyValueList>,
+    bytes_value: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArrayValue {
+    #[serde(default)]
+    values: Vec<AnyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct KeyValueList {
+    #[serde(default)]
+    values: Vec<KeyValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyValue {
+    key: String,
+    #[serde(default)]
+    value: AnyValue,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Resource {
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct InstrumentationScope {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    version: String,
+}
+
+impl AnyValue {
+    fn to_json(&self) -> Value {
+        if let Some(s) = &self.string_value {
+            json!(s)
+        } else if let Some(b) = self.bool_value {
+            json!(b)
+        } else if let Some(i) = self.int_value {
+            json!(i)
+        } else if let Some(d) = self.double_value {
+            // Non-finite doubles have no JSON representation
+            serde_json::Number::from_f64(d).map_or_else(|| json!(d.to_string()), Value::Number)
+        } else if let Some(array) = &self.array_value {
+            Value::Array(array.values.iter().map(AnyValue::to_json).collect())
+        } else if let Some(kvlist) = &self.kvlist_value {
+            Value::Object(
+                kvlist
+                    .values
+                    .iter()
+                    .map(|kv| (kv.key.clone(), kv.value.to_json()))
+                    .collect(),
+            )
+        } else if let Some(bytes) = &self.bytes_value {
+            json!(bytes)
+        } else {
+            Value::Null
+        }
+    }
+
+    /// Render as a plain string: strings as-is, everything else as JSON
+    fn to_text(&self) -> String {
+        match self.to_json() {
+            Value::String(s) => s,
+            other => other.to_string(),
+        }
+    }
+}
+
+fn attributes_json(attributes: &[KeyValue]) -> HashMap<String, Value> {
+    attributes
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.to_json()))
+        .collect()
+}
+
+fn service_name(resource: &Resource) -> String {
+    resource
+        .attributes
+        .iter()
+        .find(|kv| kv.key == "service.name")
+        .map(|kv| kv.value.to_text())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| UNKNOWN_SERVICE.to_string())
+}
+
+/// Convert OTLP nanoseconds since the epoch; 0 means unset
+fn nanos_to_time(nanos: u64) -> Option<DateTime<Utc>> {
+    (nanos > 0)
+        .then(|| i64::try_from(nanos).ok())
+        .flatten()
+        .map(DateTime::from_timestamp_nanos)
+}
+
+fn scope_fields(scope: &InstrumentationScope, payload: &mut HashMap<String, Value>) {
+    if !scope.name.is_empty() {
+        payload.insert("scope".to_string(), json!(scope.name));
+    }
+    if !scope.version.is_empty() {
+        payload.insert("scope_version".to_string(), json!(scope.version));
+    }
+}
+
+// OTLP/JSON encodes 64-bit integers as strings, but some exporters send numbers
+
+fn u64_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(u64),
+        String(String),
+    }
+    match Repr::deserialize(deserializer)? {
+        Repr::Number(n) => Ok(n),
+        Repr::String(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn i64_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(i64),
+        String(String),
+    }
+    match Option::<Repr>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Repr::Number(n)) => Ok(Some(n)),
+        Some(Repr::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Doubles may also arrive as `"NaN"`, `"Infinity"` or `"-Infinity"`
+fn f64_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(f64),
+        String(String),
+    }
+    match Option::<Repr>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Repr::Number(n)) => Ok(Some(n)),
+        Some(Repr::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+fn u64_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "u64_or_string")] u64);
+    Ok(Vec::<Wrapper>::deserialize(deserializer)?
+        .into_iter()
+        .map(|w| w.0)
+        .collect())
+}
+
+// Traces
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTraceServiceRequest {
+    #[serde(default)]
+    resource_spans: Vec<ResourceSpans>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSpans {
+    #[serde(default)]
+    resource: Resource,
+    #[serde(default)]
+    scope_spans: Vec<ScopeSpans>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ScopeSpans {
+    #[serde(default)]
+    scope: InstrumentationScope,
+    #[serde(default)]
+    spans: Vec<Span>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Span {
+    #[serde(default)]
+    trace_id: String,
+    #[serde(default)]
+    span_id: String,
+    #[serde(default)]
+    parent_span_id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    kind: i32,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    start_time_unix_nano: u64,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    end_time_unix_nano: u64,
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    #[serde(default)]
+    events: Vec<SpanEvent>,
+    #[serde(default)]
+    status: SpanStatus,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanEvent {
+    #[serde(default, deserialize_with = "u64_or_string")]
+    time_unix_nano: u64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SpanStatus {
+    #[serde(default)]
+    code: i32,
+    #[serde(default)]
+    message: String,
+}
+
+const STATUS_CODE_ERROR: i32 = 2;
+
+fn span_kind(kind: i32) -> &'static str {
+    match kind {
+        1 => "internal",
+        2 => "server",
+        3 => "client",
+        4 => "producer",
+        5 => "consumer",
+        _ => "unspecified",
+    }
+}
+
+fn status_code(code: i32) -> &'static str {
+    match code {
+        1 => "ok",
+        STATUS_CODE_ERROR => "error",
+        _ => "unset",
+    }
+}
+
+impl ExportTraceServiceRequest {
+    pub fn item_count(&self) -> usize {
+        self.resource_spans
+            .iter()
+            .flat_map(|rs| &rs.scope_spans)
+            .map(|ss| ss.spans.len())
+            .sum()
+    }
+
+    /// One `trace` event per span
+    ///
+    /// Resource and span attributes become tags, together with `trace_id` and
+    /// `span_id` so a trace's spans and logs can be found with a tag filter.
+    pub fn into_events(self) -> Vec<CreateEventRequest> {
+        let mut events = Vec::new();
+        for resource_spans in self.resource_spans {
+            let source = service_name(&resource_spans.resource);
+            let resource_tags = attributes_json(&resource_spans.resource.attributes);
+
+            for scope_spans in resource_spans.scope_spans {
+                for span in scope_spans.spans {
+                    let mut tags = resource_tags.clone();
+                    tags.extend(attributes_json(&span.attributes));
+                    tags.insert("trace_id".to_string(), json!(span.trace_id));
+                    tags.insert("span_id".to_string(), json!(span.span_id));
+
+                    let start = nanos_to_time(span.start_time_unix_nano);
+                    let end = nanos_to_time(span.end_time_unix_nano);
+                    let mut payload = HashMap::from([
+                        ("trace_id".to_string(), json!(span.trace_id)),
+                        ("span_id".to_string(), json!(span.span_id)),
+                        ("kind".to_string(), json!(span_kind(span.kind))),
+                        ("status".to_string(), json!(status_code(span.status.code))),
+                    ]);
+                    if !span.parent_span_id.is_empty() {
+                        payload.insert("parent_span_id".to_string(), json!(span.parent_span_id));
+                    }
+                    if !span.status.message.is_empty() {
+                        payload.insert("status_message".to_string(), json!(span.status.message));
+                    }
+                    if let Some(start) = start {
+                        payload.insert("start_time".to_string(), json!(start));
+                    }
+                    if let Some(end) = end {
+                        payload.insert("end_time".to_string(), json!(end));
+                    }
+                    if span.end_time_unix_nano >= span.start_time_unix_nano && start.is_some() {
+                        let duration_nanos = span.end_time_unix_nano - span.start_time_unix_nano;
+                        payload.insert(
+                            "duration_ms".to_string(),
+                            json!(duration_nanos as f64 / 1_000_000.0),
+                        );
+                    }
+                    if !span.events.is_empty() {
+                        let span_events: Vec<Value> = span
+                            .events
+                            .iter()
+                            .map(|event| {
+                                json!({
+                                    "name": event.name,
+                                    "time": nanos_to_time(event.time_unix_nano),
+                                    "attributes": attributes_json(&event.attributes),
+                                })
+                            })
+                            .collect();
+                        payload.insert("events".to_string(), json!(span_events));
+                    }
+                    scope_fields(&scope_spans.scope, &mut payload);
+
+                    let level = if span.status.code == STATUS_CODE_ERROR {
+                        "error"
+                    } else {
+                        "info"
+                    };
+
+                    events.push(CreateEventRequest {
+                        event_type: "trace".to_string(),
+                        source: source.clone(),
+                        message: Some(span.name),
+                        level: Some(level.to_string()),
+                        tags,
+                        payload,
+                        recorded_at: start,
+                        org_id: None,
+                        tenant_id: None,
+                    });
+                }
+            }
+        }
+        events
+    }
+}
+
+// Logs
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportLogsServiceRequest {
+    #[serde(default)]
+    resource_logs: Vec<ResourceLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLogs {
+    #[serde(default)]
+    resource: Resource,
+    #[serde(default)]
+    scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeLogs {
+    #[serde(default)]
+    scope: InstrumentationScope,
+    #[serde(default)]
+    log_records: Vec<LogRecord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    #[serde(default, deserialize_with = "u64_or_string")]
+    time_unix_nano: u64,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    observed_time_unix_nano: u64,
+    #[serde(default)]
+    severity_number: i32,
+    #[serde(default)]
+    severity_text: String,
+    #[serde(default)]
+    body: AnyValue,
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    #[serde(default)]
+    trace_id: String,
+    #[serde(default)]
+    span_id: String,
+}
+
+/// Map an OTel severity number (1-24) onto the starter's log levels
+fn severity_level(severity_number: i32, severity_text: &str) -> Option<String> {
+    let level = match severity_number {
+        1..=4 => "trace",
+        5..=8 => "debug",
+        9..=12 => "info",
+        13..=16 => "warn",
+        17..=20 => "error",
+        21..=24 => "fatal",
+        _ if severity_text.is_empty() => return None,
+        _ => return Some(severity_text.to_lowercase()),
+    };
+    Some(level.to_string())
+}
+
+impl ExportLogsServiceRequest {
+    pub fn item_count(&self) -> usize {
+        self.resource_logs
+            .iter()
+            .flat_map(|rl| &rl.scope_logs)
+            .map(|sl| sl.log_records.len())
+            .sum()
+    }
+
+    /// One `log` event per log record
+    ///
+    /// A string body becomes the message; structured bodies are stored as
+    /// JSON text in the message and as-is under `body` in the payload.
+    pub fn into_events(self) -> Vec<CreateEventRequest> {
+        let mut events = Vec::new();
+        for resource_logs in self.resource_logs {
+            let source = service_name(&resource_logs.resource);
+            let resource_tags = attributes_json(&resource_logs.resource.attributes);
+
+            for scope_logs in resource_logs.scope_logs {
+                for record in scope_logs.log_records {
+                    let mut tags = resource_tags.clone();
+                    tags.extend(attributes_json(&record.attributes));
+
+                    let mut payload = HashMap::new();
+                    if record.severity_number > 0 {
+                        payload
+                            .insert("severity_number".to_string(), json!(record.severity_number));
+                    }
+                    if !record.severity_text.is_empty() {
+                        payload.insert("severity_text".to_string(), json!(record.severity_text));
+                    }
+                    if !record.trace_id.is_empty() {
+                        tags.insert("trace_id".to_string(), json!(record.trace_id));
+                        payload.insert("trace_id".to_string(), json!(record.trace_id));
+                    }
+                    if !record.span_id.is_empty() {
+                        tags.insert("span_id".to_string(), json!(record.span_id));
+                        payload.insert("span_id".to_string(), json!(record.span_id));
+                    }
+                    if let Some(observed) = nanos_to_time(record.observed_time_unix_nano) {
+                        payload.insert("observed_time".to_string(), json!(observed));
+                    }
+                    let body = record.body.to_json();
+                    let message = match &body {
+                        Value::Null => None,
+                        Value::String(s) => Some(s.clone()),
+                        other => {
+                            payload.insert("body".to_string(), other.clone());
+                            Some(other.to_string())
+                        }
+                    };
+                    scope_fields(&scope_logs.scope, &mut payload);
+
+                    events.push(CreateEventRequest {
+                        event_type: "log".to_string(),
+                        source: source.clone(),
+                        message,
+                        level: severity_level(record.severity_number, &record.severity_text),
+                        tags,
+                        payload,
+                        recorded_at: nanos_to_time(record.time_unix_nano)
+                            .or_else(|| nanos_to_time(record.observed_time_unix_nano)),
+                        org_id: None,
+                        tenant_id: None,
+                    });
+                }
+            }
+        }
+        events
+    }
+}
+
+// Metrics
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportMetricsServiceRequest {
+    #[serde(default)]
+    resource_metrics: Vec<ResourceMetrics>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceMetrics {
+    #[serde(default)]
+    resource: Resource,
+    #[serde(default)]
+    scope_metrics: Vec<ScopeMetrics>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ScopeMetrics {
+    #[serde(default)]
+    metrics: Vec<OtlpMetric>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpMetric {
+    #[serde(default)]
+    name: String,
+    gauge: Option<Gauge>,
+    sum: Option<Sum>,
+    histogram: Option<Histogram>,
+    exponential_histogram: Option<Histogram>,
+    summary: Option<Summary>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gauge {
+    #[serde(default)]
+    data_points: Vec<NumberDataPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sum {
+    #[serde(default)]
+    data_points: Vec<NumberDataPoint>,
+    #[serde(default)]
+    is_monotonic: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    #[serde(default)]
+    data_points: Vec<HistogramDataPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    #[serde(default)]
+    data_points: Vec<SummaryDataPoint>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberDataPoint {
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    time_unix_nano: u64,
+    #[serde(default, deserialize_with = "f64_or_string")]
+    as_double: Option<f64>,
+    #[serde(default, deserialize_with = "i64_or_string")]
+    as_int: Option<i64>,
+}
+
+/// Explicit-bucket and exponential histogram points; exponential buckets are not stored
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramDataPoint {
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    time_unix_nano: u64,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    count: u64,
+    #[serde(default, deserialize_with = "f64_or_string")]
+    sum: Option<f64>,
+    #[serde(default, deserialize_with = "u64_vec")]
+    bucket_counts: Vec<u64>,
+    #[serde(default)]
+    explicit_bounds: Vec<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryDataPoint {
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    time_unix_nano: u64,
+    #[serde(default, deserialize_with = "u64_or_string")]
+    count: u64,
+    #[serde(default, deserialize_with = "f64_or_string")]
+    sum: Option<f64>,
+    #[serde(default)]
+    quantile_values: Vec<QuantileValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct QuantileValue {
+    #[serde(default)]
+    quantile: f64,
+    #[serde(default)]
+    value: f64,
+}
+
+fn attributes_labels(attributes: &[KeyValue]) -> HashMap<String, String> {
+    attributes
+        .iter()
+        .map(|kv| (kv.key.clone(), kv.value.to_text()))
+        .collect()
+}
+
+impl OtlpMetric {
+    fn data_point_count(&self) -> usize {
+        self.gauge.as_ref().map_or(0, |g| g.data_points.len())
+            + self.sum.as_ref().map_or(0, |s| s.data_points.len())
+            + self.histogram.as_ref().map_or(0, |h| h.data_points.len())
+            + self
+                .exponential_histogram
+                .as_ref()
+                .map_or(0, |h| h.data_points.len())
+            + self.summary.as_ref().map_or(0, |s| s.data_points.len())
+    }
+}
+
+impl ExportMetricsServiceRequest {
+    /// Number of OTLP data points (before histograms and summaries are expanded)
+    pub fn item_count(&self) -> usize {
+        self.resource_metrics
+            .iter()
+            .flat_map(|rm| &rm.scope_metrics)
+            .flat_map(|sm| &sm.metrics)
+            .map(OtlpMetric::data_point_count)
+            .sum()
+    }
+
+    /// Metric rows grouped by the OTLP data point they came from
+    ///
+    /// Gauges and non-monotonic sums are stored as gauges and monotonic sums
+    /// as counters. Histograms and summaries are expanded the way Prometheus
+    /// exposes them: `<name>_count` and `<name>_sum`, plus cumulative
+    /// `<name>_bucket` rows labelled `le` for histograms and `<name>` rows
+    /// labelled `quantile` for summaries. Resource attributes, including
+    /// `service.name`, become labels alongside the data point's attributes.
+    pub fn into_metrics(self) -> Vec<Vec<CreateMetricRequest>> {
+        let mut points = Vec::new();
+        for resource_metrics in self.resource_metrics {
+            let resource_labels = attributes_labels(&resource_metrics.resource.attributes);
+            let labels_for = |attributes: &[KeyValue]| {
+                let mut labels = resource_labels.clone();
+                labels.extend(attributes_labels(attributes));
+                labels
+            };
+
+            for metric in resource_metrics
+                .scope_metrics
+                .into_iter()
+                .flat_map(|sm| sm.metrics)
+            {
+                let name = metric.name;
+                let row = |name: String, metric_type, value, labels, time| CreateMetricRequest {
+                    name,
+                    metric_type,
+                    value,
+                    labels,
+                    recorded_at: nanos_to_time(time),
+                    tenant_id: None,
+                };
+
+                let number_points = metric
+                    .gauge
+                    .map(|g| (MetricType::Gauge, g.data_points))
+                    .into_iter()
+                    .chain(metric.sum.map(|s| {
+                        let metric_type = if s.is_monotonic {
+                            MetricType::Counter
+                        } else {
+                            MetricType::Gauge
+                        };
+                        (metric_type, s.data_points)
+                    }));
+                for (metric_type, data_points) in number_points {
+                    for point in data_points {
+                        let value = point
+                            .as_double
+                            .or(point.as_int.map(|i| i as f64))
+                            .unwrap_or_default();
+                        points.push(vec![row(
+                            name.clone(),
+                            metric_type.clone(),
+                            value,
+                            labels_for(&point.attributes),
+                            point.time_unix_nano,
+                        )]);
+                    }
+                }
+
+                let histogram_points = metric
+                    .histogram
+                    .into_iter()
+                    .chain(metric.exponential_histogram)
+                    .flat_map(|h| h.data_points);
+                for point in histogram_points {
+                    points.push(histogram::histogram_rows(
+                        &name,
+                        &labels_for(&point.attributes),
+                        &point.explicit_bounds,
+                        &point.bucket_counts,
+                        point.sum,
+                        point.count,
+                        nanos_to_time(point.time_unix_nano),
+                    ));
+                }
+
+                for point in metric.summary.into_iter().flat_map(|s| s.data_points) {
+                    let quantiles: Vec<(f64, f64)> = point
+                        .quantile_values
+                        .iter()
+                        .map(|q| (q.quantile, q.value))
+                        .collect();
+                    points.push(histogram::summary_rows(
+                        &name,
+                        &labels_for(&point.attributes),
+                        &quantiles,
+                        point.sum,
+                        point.count,
+                        nanos_to_time(point.time_unix_nano),
+                    ));
+                }
+            }
+        }
+        points
+    }
+}
+
+// Responses
+
+/// Items the server did not store, reported back to the exporter
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpPartialSuccess {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_spans: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_data_points: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_log_records: Option<i64>,
+    pub error_message: String,
+}
+
+/// OTLP `Export*ServiceResponse`; `partialSuccess` is omitted when everything was stored
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpExportResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial_success: Option<OtlpPartialSuccess>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_request_maps_spans_to_events() {
+        let request: ExportTraceServiceRequest = serde_json::from_value(json!({
+            "resourceSpans": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "checkout"}}
+                ]},
+                "scopeSpans": [{
+                    "scope": {"name": "my-lib", "version": "1.0"},
+                    "spans": [{
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "name": "GET /cart",
+                        "kind": 2,
+                        "startTimeUnixNano": "1700000000000000000",
+                        "endTimeUnixNano": 1700000000250000000u64,
+                        "attributes": [{"key": "http.status_code", "value": {"intValue": "500"}}],
+                        "status": {"code": 2, "message": "boom"}
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(request.item_count(), 1);
+        let events = request.into_events();
+        let event = &events[0];
+        assert_eq!(event.event_type, "trace");
+        assert_eq!(event.source, "checkout");
+        assert_eq!(event.message.as_deref(), Some("GET /cart"));
+        assert_eq!(event.level.as_deref(), Some("error"));
+        assert_eq!(event.tags["http.status_code"], json!(500));
+        assert_eq!(event.tags["trace_id"], "5b8efff798038103d269b633813fc60c");
+        assert_eq!(event.payload["kind"], "server");
+        assert_eq!(event.payload["duration_ms"], json!(250.0));
+        assert_eq!(event.payload["scope"], "my-lib");
+        assert_eq!(event.recorded_at.unwrap().timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_log_severity_and_body() {
+        assert_eq!(severity_level(9, "Information").as_deref(), Some("info"));
+        assert_eq!(severity_level(18, "").as_deref(), Some("error"));
+        assert_eq!(severity_level(0, "NOTICE").as_deref(), Some("notice"));
+        assert_eq!(severity_level(0, ""), None);
+
+        let request: ExportLogsServiceRequest = serde_json::from_value(json!({
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "observedTimeUnixNano": "1700000000000000000",
+                        "severityNumber": 13,
+                        "body": {"kvlistValue": {"values": [
+                            {"key": "order", "value": {"intValue": 42}}
+                        ]}}
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let events = request.into_events();
+        let event = &events[0];
+        assert_eq!(event.source, UNKNOWN_SERVICE);
+        assert_eq!(event.level.as_deref(), Some("warn"));
+        assert_eq!(event.message.as_deref(), Some(r#"{"order":42}"#));
+        assert_eq!(event.payload["body"], json!({"order": 42}));
+        assert!(event.recorded_at.is_some());
+    }
+
+    #[test]
+    fn test_histogram_expands_to_prometheus_style_rows() {
+        let request: ExportMetricsServiceRequest = serde_json::from_value(json!({
+            "resourceMetrics": [{
+                "scopeMetrics": [{
+                    "metrics": [{
+                        "name": "latency",
+                        "histogram": {"dataPoints": [{
+                            "timeUnixNano": "1700000000000000000",
+                            "count": "3",
+                            "sum": 0.6,
+                            "bucketCounts": ["1", "2"],
+                            "explicitBounds": [0.1]
+                        }]}
+                    }, {
+                        "name": "requests",
+                        "sum": {"isMonotonic": true, "dataPoints": [{"asInt": "7"}]}
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(request.item_count(), 2);
+        let points = request.into_metrics();
+        let names: Vec<_> = points[0].iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "latency_count",
+                "latency_sum",
+                "latency_bucket",
+                "latency_bucket"
+            ]
+        );
+        assert_eq!(points[0][3].labels["le"], "+Inf");
+        assert_eq!(points[0][3].value, 3.0);
+        assert!(matches!(points[1][0].metric_type, MetricType::Counter));
+        assert_eq!(points[1][0].value, 7.0);
+    }
+}
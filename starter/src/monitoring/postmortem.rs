@@ -0,0 +1,256 @@
+use crate::monitoring::models::{
+    CreateActionItemRequest, Postmortem, PostmortemActionItem, UpdateActionItemRequest,
+    UpsertPostmortemRequest, Validate,
+};
+use crate::{DbConn, Error, Result};
+use uuid::Uuid;
+
+/// Find the postmortem of an incident, with its action items
+pub async fn find_postmortem(conn: &mut DbConn, incident_id: Uuid) -> Result<Option<Postmortem>> {
+    let postmortem = sqlx::query!(
+        r#"
+        SELECT id, incident_id, body, contributing_factors,
+               created_by, updated_by, created_at, updated_at
+        FROM incident_postmortems
+        WHERE incident_id = $1
+        "#,
+        incident_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    match postmortem {
+        Some(postmortem) => Ok(Some(Postmortem {
+            action_items: find_action_items(conn, postmortem.id).await?,
+            id: postmortem.id,
+            incident_id: postmortem.incident_id,
+            body: postmortem.body,
+            contributing_factors: postmortem.contributing_factors,
+            created_by: postmortem.created_by,
+            updated_by: postmortem.updated_by,
+            created_at: postmortem.created_at,
+            updated_at: postmortem.updated_at,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Write the postmortem of an incident, replacing any previous version
+///
+/// Action items are kept when the document is replaced.
+pub async fn upsert_postmortem(
+    conn: &mut DbConn,
+    incident_id: Uuid,
+    request: UpsertPostmortemRequest,
+    user_id: Uuid,
+) -> Result<Postmortem> {
+    request.validate()?;
+
+    let contributing_factors: Vec<String> = request
+        .contributing_factors
+        .iter()
+        .map(|factor| factor.trim().to_string())
+        .collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO incident_postmortems (incident_id, body, contributing_factors, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $4)
+        ON CONFLICT (incident_id) DO UPDATE
+        SET body = EXCLUDED.body,
+            contributing_factors = EXCLUDED.contributing_factors,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        "#,
+        incident_id,
+        request.body,
+        &contributing_factors,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    find_postmortem(conn, incident_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Postmortem not found".to_string()))
+}
+
+async fn find_postmortem_id(conn: &mut DbConn, incident_id: Uuid) -> Result<Uuid> {
+    sqlx::query_scalar!(
+        "SELECT id FROM incident_postmortems WHERE incident_id = $1",
+        incident_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Postmortem not found".to_string()))
+}
+
+async fn find_action_items(
+    conn: &mut DbConn,
+    postmortem_id: Uuid,
+) -> Result<Vec<PostmortemActionItem>> {
+    sqlx::query_as!(
+        PostmortemActionItem,
+        r#"
+        SELECT a.id, a.postmortem_id, a.title, a.task_id,
+               t.status AS "task_status?: _",
+               a.assigned_to, a.completed_at, a.created_at, a.updated_at
+        FROM postmortem_action_items a
+        LEFT JOIN tasks t ON t.id = a.task_id
+        WHERE a.postmortem_id = $1
+        ORDER BY a.created_at, a.id
+        "#,
+        postmortem_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+async fn find_action_item(
+    conn: &mut DbConn,
+    postmortem_id: Uuid,
+    item_id: Uuid,
+) -> Result<PostmortemActionItem> {
+    sqlx::query_as!(
+        PostmortemActionItem,
+        r#"
+        SELECT a.id, a.postmortem_id, a.title, a.task_id,
+               t.status AS "task_status?: _",
+               a.assigned_to, a.completed_at, a.created_at, a.updated_at
+        FROM postmortem_action_items a
+        LEFT JOIN tasks t ON t.id = a.task_id
+        WHERE a.postmortem_id = $1 AND a.id = $2
+        "#,
+        postmortem_id,
+        item_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Action item not found".to_string()))
+}
+
+/// Check that a task linked to an action item exists
+async fn require_task(conn: &mut DbConn, task_id: Option<Uuid>) -> Result<()> {
+    let Some(task_id) = task_id else {
+        return Ok(());
+    };
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM tasks WHERE id = $1) AS "exists!""#,
+        task_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if !exists {
+        return Err(Error::validation("task_id", "Task not found"));
+    }
+    Ok(())
+}
+
+/// Add an action item to the postmortem of an incident
+pub async fn create_action_item(
+    conn: &mut DbConn,
+    incident_id: Uuid,
+    request: CreateActionItemRequest,
+) -> Result<PostmortemActionItem> {
+    request.validate()?;
+
+    let postmortem_id = find_postmortem_id(conn, incident_id).await?;
+    require_task(conn, request.task_id).await?;
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO postmortem_action_items (postmortem_id, title, task_id, assigned_to)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        postmortem_id,
+        request.title.trim(),
+        request.task_id,
+        request.assigned_to
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(map_write_error)?;
+
+    find_action_item(conn, postmortem_id, id).await
+}
+
+/// Update an action item of the postmortem of an incident
+pub async fn update_action_item(
+    conn: &mut DbConn,
+    incident_id: Uuid,
+    item_id: Uuid,
+    request: UpdateActionItemRequest,
+) -> Result<PostmortemActionItem> {
+    request.validate()?;
+
+    let postmortem_id = find_postmortem_id(conn, incident_id).await?;
+    require_task(conn, request.task_id).await?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE postmortem_action_items
+        SET title = COALESCE($3, title),
+            task_id = COALESCE($4, task_id),
+            assigned_to = COALESCE($5, assigned_to),
+            completed_at = CASE
+                WHEN $6::BOOLEAN IS NULL THEN completed_at
+                WHEN $6 THEN COALESCE(completed_at, NOW())
+                ELSE NULL
+            END,
+            updated_at = NOW()
+        WHERE postmortem_id = $1 AND id = $2
+        "#,
+        postmortem_id,
+        item_id,
+        request.title.as_deref().map(str::trim),
+        request.task_id,
+        request.assigned_to,
+        request.completed
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(map_write_error)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Action item not found".to_string()));
+    }
+
+    find_action_item(conn, postmortem_id, item_id).await
+}
+
+/// Delete an action item of the postmortem of an incident
+pub async fn delete_action_item(conn: &mut DbConn, incident_id: Uuid, item_id: Uuid) -> Result<()> {
+    let postmortem_id = find_postmortem_id(conn, incident_id).await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM postmortem_action_items WHERE postmortem_id = $1 AND id = $2",
+        postmortem_id,
+        item_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Action item not found".to_string()));
+    }
+    Ok(())
+}
+
+fn map_write_error(err: sqlx::Error) -> Error {
+    match &err {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("assigned_to", "User not found")
+        }
+        _ => Error::from_sqlx(err),
+    }
+}
@@ -0,0 +1,443 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::histogram;
+use crate::monitoring::models::{
+    MetricAggregation, MetricQueryPoint, MetricQueryResult, MetricQuerySeries, MetricResolution,
+};
+use crate::monitoring::retention::{self, ROLLUP_RESOLUTION_SECS};
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// Steps per query when no step is given
+const DEFAULT_QUERY_STEPS: i64 = 250;
+/// Maximum steps per query
+pub const MAX_QUERY_STEPS: i64 = 11_000;
+/// Maximum points across all series of a query
+pub const MAX_QUERY_POINTS: i64 = 100_000;
+const MAX_LABEL_MATCHERS: usize = 20;
+const MAX_GROUP_BY_LABELS: usize = 10;
+const MAX_LABEL_KEY_LENGTH: usize = 100;
+
+/// Grouping labels, step start and `(upper bound, count)` buckets of a histogram step
+type HistogramStep = (serde_json::Value, DateTime<Utc>, Vec<(f64, f64)>);
+
+/// How a label matcher compares the label value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOp {
+    /// `key=value`
+    Equal,
+    /// `key!=value`
+    NotEqual,
+    /// `key=~regex`
+    Regex,
+    /// `key!~regex`
+    NotRegex,
+}
+
+/// A label matcher; a missing label matches as the empty string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelMatcher {
+    pub key: String,
+    pub op: MatchOp,
+    pub value: String,
+}
+
+impl LabelMatcher {
+    /// Parse comma-separated matchers such as `env=prod,host=~web-.*`
+    pub fn parse_list(input: &str) -> Result<Vec<LabelMatcher>> {
+        let matchers = input
+            .split(',')
+            .map(str::trim)
+            .filter(|matcher| !matcher.is_empty())
+            .map(Self::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        if matchers.len() > MAX_LABEL_MATCHERS {
+            return Err(Error::validation(
+                "labels",
+                &format!("At most {MAX_LABEL_MATCHERS} label matchers are allowed"),
+            ));
+        }
+        Ok(matchers)
+    }
+
+    fn parse(matcher: &str) -> Result<LabelMatcher> {
+        let invalid = || {
+            Error::validation(
+                "labels",
+                &format!(
+                    "Invalid label matcher '{matcher}'; use key=value, key!=value, key=~regex or key!~regex"
+                ),
+            )
+        };
+
+        let split = matcher.find(['=', '!']).ok_or_else(invalid)?;
+        let (key, rest) = matcher.split_at(split);
+        let (op, value) = if let Some(value) = rest.strip_prefix("=~") {
+            (MatchOp::Regex, value)
+        } else if let Some(value) = rest.strip_prefix("!~") {
+            (MatchOp::NotRegex, value)
+        } else if let Some(value) = rest.strip_prefix("!=") {
+            (MatchOp::NotEqual, value)
+        } else if let Some(value) = rest.strip_prefix('=') {
+            (MatchOp::Equal, value)
+        } else {
+            return Err(invalid());
+        };
+
+        let key = key.trim();
+        validate_label_key(key, "labels")?;
+        Ok(LabelMatcher {
+            key: key.to_string(),
+            op,
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+pub(crate) fn validate_label_key(key: &str, field: &str) -> Result<()> {
+    if key.is_empty()
+        || key.len() > MAX_LABEL_KEY_LENGTH
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        return Err(Error::validation(
+            field,
+            &format!("Invalid label name '{key}'"),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse comma-separated label names to group series by
+pub fn parse_group_by(input: &str) -> Result<Vec<String>> {
+    let keys: Vec<String> = input
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if keys.len() > MAX_GROUP_BY_LABELS {
+        return Err(Error::validation(
+            "by",
+            &format!("At most {MAX_GROUP_BY_LABELS} grouping labels are allowed"),
+        ));
+    }
+    for key in &keys {
+        validate_label_key(key, "by")?;
+    }
+    Ok(keys)
+}
+
+/// An aggregated metric query over a time range
+#[derive(Debug, Clone)]
+pub struct MetricQuery {
+    /// Tenant whose metrics are queried
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub matchers: Vec<LabelMatcher>,
+    pub group_by: Vec<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Step width; defaults to splitting the range into about 250 steps
+    pub step_secs: Option<i64>,
+    pub aggregation: MetricAggregation,
+    pub resolution: MetricResolution,
+}
+
+/// Run an aggregated metric query, returning one series per group of the `by` labels
+///
+/// Each step aggregates the samples from its start up to the next step. Raw
+/// samples are used while the range is within raw retention (or when asked
+/// for), rollups otherwise. Percentiles of a metric with `<name>_bucket`
+/// histogram rows are estimated from the bucket counts summed over each step,
+/// which works on rollups too; other percentiles need raw samples.
+pub async fn query_metrics(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    query: &MetricQuery,
+) -> Result<MetricQueryResult> {
+    retention::validate_metric_name(&query.name)?;
+    if query.start_time > query.end_time {
+        return Err(Error::validation(
+            "start_time",
+            "start_time must not be after end_time",
+        ));
+    }
+
+    let bucket_name = match query.aggregation {
+        MetricAggregation::Percentile(_) => {
+            let bucket_name = format!("{}_bucket", query.name);
+            has_histogram(conn, query.tenant_id, &bucket_name)
+                .await?
+                .then_some(bucket_name)
+        }
+        _ => None,
+    };
+    let series_name = bucket_name.as_deref().unwrap_or(&query.name);
+
+    let resolution = retention::resolve_resolution(
+        conn,
+        config,
+        series_name,
+        query.start_time,
+        query.resolution,
+    )
+    .await?;
+    if resolution == MetricResolution::Rollup
+        && bucket_name.is_none()
+        && matches!(query.aggregation, MetricAggregation::Percentile(_))
+    {
+        return Err(Error::validation(
+            "aggregation",
+            "Percentiles need raw samples or a histogram; query a range within raw retention",
+        ));
+    }
+
+    let range_secs = (query.end_time - query.start_time).num_seconds().max(1);
+    let mut step_secs = match query.step_secs {
+        Some(step) if step < 1 => {
+            return Err(Error::validation("step", "step must be at least 1 second"));
+        }
+        Some(step) => step,
+        None => (range_secs + DEFAULT_QUERY_STEPS - 1) / DEFAULT_QUERY_STEPS,
+    };
+    if resolution == MetricResolution::Rollup {
+        // Rollup buckets cannot be split further
+        step_secs = step_secs.max(ROLLUP_RESOLUTION_SECS as i64);
+    }
+    if range_secs / step_secs > MAX_QUERY_STEPS {
+        return Err(Error::validation(
+            "step",
+            &format!("Query would return more than {MAX_QUERY_STEPS} steps; increase step"),
+        ));
+    }
+
+    let mut builder = QueryBuilder::<Postgres>::new("SELECT date_bin(make_interval(secs => ");
+    builder.push_bind(step_secs as f64);
+    builder.push("), ");
+    builder.push(if resolution == MetricResolution::Raw {
+        "recorded_at"
+    } else {
+        "bucket_start"
+    });
+    builder.push(", ");
+    builder.push_bind(query.start_time);
+    builder.push(") AS bucket, ");
+    push_group_labels(&mut builder, &query.group_by);
+    builder.push(" AS labels, ");
+
+    if bucket_name.is_some() {
+        // Bucket counts per upper bound; the quantile is estimated below
+        builder.push("labels->>");
+        builder.push_bind(histogram::BUCKET_LABEL);
+        builder.push(if resolution == MetricResolution::Raw {
+            " AS le, sum(value) AS value FROM metrics WHERE name = "
+        } else {
+            " AS le, sum(sum) AS value FROM metric_rollups WHERE name = "
+        });
+        builder.push_bind(series_name);
+    } else if resolution == MetricResolution::Raw {
+        builder.push("NULL::TEXT AS le, ");
+        match query.aggregation {
+            MetricAggregation::Avg => builder.push("avg(value)"),
+            MetricAggregation::Sum => builder.push("sum(value)"),
+            MetricAggregation::Min => builder.push("min(value)"),
+            MetricAggregation::Max => builder.push("max(value)"),
+            MetricAggregation::Count => builder.push("count(*)::DOUBLE PRECISION"),
+            MetricAggregation::Percentile(p) => {
+                builder.push("percentile_cont(");
+                builder.push_bind(f64::from(p) / 100.0);
+                builder.push(") WITHIN GROUP (ORDER BY value)")
+            }
+        };
+        builder.push(" AS value FROM metrics WHERE name = ");
+        builder.push_bind(&query.name);
+    } else {
+        builder.push("NULL::TEXT AS le, ");
+        match query.aggregation {
+            MetricAggregation::Avg => builder.push("sum(sum) / NULLIF(sum(count), 0)"),
+            MetricAggregation::Sum => builder.push("sum(sum)"),
+            MetricAggregation::Min => builder.push("min(min)"),
+            MetricAggregation::Max => builder.push("max(max)"),
+            MetricAggregation::Count => builder.push("sum(count)::DOUBLE PRECISION"),
+            MetricAggregation::Percentile(_) => unreachable!("rejected above"),
+        };
+        builder.push(" AS value FROM metric_rollups WHERE name = ");
+        builder.push_bind(&query.name);
+    }
+
+    builder.push(" AND tenant_id = ");
+    builder.push_bind(query.tenant_id);
+    if resolution == MetricResolution::Raw {
+        builder.push(" AND recorded_at >= ");
+        builder.push_bind(query.start_time);
+        builder.push(" AND recorded_at <= ");
+        builder.push_bind(query.end_time);
+    } else {
+        builder.push(" AND resolution_secs = ");
+        builder.push_bind(ROLLUP_RESOLUTION_SECS);
+        builder.push(" AND bucket_start >= ");
+        builder.push_bind(query.start_time);
+        builder.push(" AND bucket_start <= ");
+        builder.push_bind(query.end_time);
+    }
+
+    for matcher in &query.matchers {
+        builder.push(" AND COALESCE(labels->>");
+        builder.push_bind(&matcher.key);
+        builder.push(", '')");
+        match matcher.op {
+            MatchOp::Equal => {
+                builder.push(" = ");
+                builder.push_bind(&matcher.value);
+            }
+            MatchOp::NotEqual => {
+                builder.push(" <> ");
+                builder.push_bind(&matcher.value);
+            }
+            MatchOp::Regex | MatchOp::NotRegex => {
+                // Anchored like Prometheus matchers
+                builder.push(if matcher.op == MatchOp::Regex {
+                    " ~ "
+                } else {
+                    " !~ "
+                });
+                builder.push_bind(format!("^(?:{})$", matcher.value));
+            }
+        }
+    }
+
+    builder.push(" GROUP BY 1, 2, 3 ORDER BY 2, 1 LIMIT ");
+    builder.push_bind(MAX_QUERY_POINTS + 1);
+
+    let rows = builder
+        .build_query_as::<(
+            DateTime<Utc>,
+            serde_json::Value,
+            Option<String>,
+            Option<f64>,
+        )>()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(map_query_error)?;
+
+    if rows.len() as i64 > MAX_QUERY_POINTS {
+        return Err(Error::validation(
+            "step",
+            &format!(
+                "Query would return more than {MAX_QUERY_POINTS} points; narrow the labels or increase step"
+            ),
+        ));
+    }
+
+    let mut series: Vec<MetricQuerySeries> = Vec::new();
+    if let (Some(_), MetricAggregation::Percentile(p)) = (&bucket_name, query.aggregation) {
+        let quantile = f64::from(p) / 100.0;
+        let mut steps: Vec<HistogramStep> = Vec::new();
+        for (timestamp, labels, le, count) in rows {
+            let (Some(bound), Some(count)) =
+                (le.as_deref().and_then(histogram::parse_bound), count)
+            else {
+                continue;
+            };
+            match steps.last_mut() {
+                Some((last_labels, last_timestamp, buckets))
+                    if *last_labels == labels && *last_timestamp == timestamp =>
+                {
+                    buckets.push((bound, count))
+                }
+                _ => steps.push((labels, timestamp, vec![(bound, count)])),
+            }
+        }
+        for (labels, timestamp, mut buckets) in steps {
+            let Some(value) = histogram::histogram_quantile(quantile, &mut buckets) else {
+                continue;
+            };
+            push_point(&mut series, labels, MetricQueryPoint { timestamp, value });
+        }
+    } else {
+        for (timestamp, labels, _, value) in rows {
+            let Some(value) = value else {
+                continue;
+            };
+            push_point(&mut series, labels, MetricQueryPoint { timestamp, value });
+        }
+    }
+
+    Ok(MetricQueryResult {
+        name: query.name.clone(),
+        aggregation: query.aggregation,
+        step_secs,
+        resolution,
+        series,
+    })
+}
+
+/// Append a point to the last series, or start a new one for other labels
+fn push_point(
+    series: &mut Vec<MetricQuerySeries>,
+    labels: serde_json::Value,
+    point: MetricQueryPoint,
+) {
+    match series.last_mut() {
+        Some(last) if last.labels == labels => last.points.push(point),
+        _ => series.push(MetricQuerySeries {
+            labels,
+            points: vec![point],
+        }),
+    }
+}
+
+/// Whether the tenant stored histogram rows under a `<name>_bucket` name
+async fn has_histogram(conn: &mut DbConn, tenant_id: Uuid, bucket_name: &str) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM metrics
+            WHERE tenant_id = $1 AND name = $2 AND metric_type = 'histogram'
+        ) OR EXISTS (
+            SELECT 1 FROM metric_rollups
+            WHERE tenant_id = $1 AND name = $2 AND metric_type = 'histogram'
+        ) AS "exists!"
+        "#,
+        tenant_id,
+        bucket_name
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Push the JSON object of the grouping labels present on each row
+fn push_group_labels(builder: &mut QueryBuilder<'_, Postgres>, group_by: &[String]) {
+    if group_by.is_empty() {
+        builder.push("'{}'::JSONB");
+        return;
+    }
+
+    builder.push("jsonb_strip_nulls(jsonb_build_object(");
+    for (i, key) in group_by.iter().enumerate() {
+        if i > 0 {
+            builder.push(", ");
+        }
+        builder.push_bind(key.clone());
+        builder.push("::TEXT, labels->");
+        builder.push_bind(key.clone());
+        builder.push("::TEXT");
+    }
+    builder.push("))");
+}
+
+fn map_query_error(err: sqlx::Error) -> Error {
+    match &err {
+        // invalid_regular_expression
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("2201B") => {
+            Error::validation("labels", "Invalid regular expression in label matcher")
+        }
+        _ => Error::from_sqlx(err),
+    }
+}
@@ -0,0 +1,436 @@
+//! Daily ingestion quotas per user and event source
+//!
+//! Usage is counted per tenant and UTC day for every user and source, whether
+//! or not it has a quota, so `/monitoring/stats` can show who is ingesting
+//! what. Quotas are instance-wide, so a source's quota counts its usage in
+//! every tenant.
+
+use crate::monitoring::limits::EventAdmission;
+use crate::monitoring::models::{
+    IngestionQuota, IngestionUsage, MAX_SOURCE_LENGTH, QuotaScope, SetIngestionQuotaRequest,
+};
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Days of usage kept; only today's counts are enforced
+const USAGE_RETENTION_DAYS: i64 = 7;
+
+/// Users and sources listed in the stats beyond those with quotas
+const TOP_USAGE_KEYS: i64 = 20;
+
+/// When today's quotas reset (next UTC midnight)
+pub fn quotas_reset_at(now: DateTime<Utc>) -> DateTime<Utc> {
+    (now.date_naive() + chrono::Days::new(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+}
+
+/// Why an item was rejected by a daily quota
+pub fn quota_exceeded_message(kind: &str, scope: QuotaScope, key: &str, limit: i64) -> String {
+    let owner = match scope {
+        QuotaScope::User => "your account".to_string(),
+        QuotaScope::Source => format!("source '{key}'"),
+    };
+    format!(
+        "Daily {kind} quota of {limit} for {owner} reached; it resets at {}",
+        quotas_reset_at(Utc::now()).to_rfc3339()
+    )
+}
+
+/// Apply the daily event quotas to events admitted so far, and count the rest
+///
+/// Events still `Accepted` in `admissions` are checked in order against the
+/// quota of their source and then the user's, which is `default_user_quota`
+/// unless the user has their own; those over either become `QuotaExceeded`.
+/// The remaining events are added to today's usage. Like the rate limits,
+/// concurrent requests may briefly overshoot a quota.
+pub(crate) async fn admit_events(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    tenant_id: Uuid,
+    default_user_quota: Option<i64>,
+    sources: &[&str],
+    admissions: &mut [EventAdmission],
+) -> Result<()> {
+    let today = Utc::now().date_naive();
+    let user_key = user_id.to_string();
+
+    let mut distinct: Vec<String> = sources.iter().map(|source| source.to_string()).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    let quotas = sqlx::query!(
+        r#"
+        SELECT q.key, q.daily_events AS "daily_events!",
+               COALESCE((
+                   SELECT SUM(u.events) FROM ingestion_usage u
+                   WHERE u.scope = q.scope AND u.key = q.key AND u.day = $2
+               ), 0)::BIGINT AS "used!"
+        FROM ingestion_quotas q
+        WHERE q.daily_events IS NOT NULL AND q.scope = 'source' AND q.key = ANY($1)
+        "#,
+        &distinct,
+        today
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // (remaining, limit) per quota
+    let mut source_quotas: HashMap<String, (i64, i64)> = quotas
+        .into_iter()
+        .map(|quota| {
+            let remaining = (quota.daily_events - quota.used).max(0);
+            (quota.key, (remaining, quota.daily_events))
+        })
+        .collect();
+
+    let user = sqlx::query!(
+        r#"
+        SELECT q.daily_events, COALESCE(u.events, 0) AS "used!"
+        FROM (SELECT $1::TEXT AS key) k
+        LEFT JOIN ingestion_quotas q ON q.scope = 'user' AND q.key = k.key
+        LEFT JOIN ingestion_usage u ON u.scope = 'user' AND u.key = k.key AND u.day = $2
+        "#,
+        user_key,
+        today
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let mut user_quota = user
+        .daily_events
+        .or(default_user_quota)
+        .map(|limit| ((limit - user.used).max(0), limit));
+
+    let mut stored: BTreeMap<&str, i64> = BTreeMap::new();
+    for (source, admission) in sources.iter().zip(admissions.iter_mut()) {
+        if *admission != EventAdmission::Accepted {
+            continue;
+        }
+        let source_quota = source_quotas.get_mut(*source);
+        if let Some((0, limit)) = source_quota.as_deref() {
+            *admission = EventAdmission::QuotaExceeded {
+                scope: QuotaScope::Source,
+                limit: *limit,
+            };
+            continue;
+        }
+        if let Some((0, limit)) = user_quota {
+            *admission = EventAdmission::QuotaExceeded {
+                scope: QuotaScope::User,
+                limit,
+            };
+            continue;
+        }
+        if let Some((remaining, _)) = source_quota {
+            *remaining -= 1;
+        }
+        if let Some((remaining, _)) = user_quota.as_mut() {
+            *remaining -= 1;
+        }
+        *stored.entry(source).or_default() += 1;
+    }
+
+    let total: i64 = stored.values().sum();
+    if total > 0 {
+        let mut usage: Vec<(QuotaScope, String, i64, i64)> = stored
+            .into_iter()
+            .map(|(source, events)| (QuotaScope::Source, source.to_string(), events, 0))
+            .collect();
+        usage.push((QuotaScope::User, user_key, total, 0));
+        record_usage(conn, tenant_id, today, &usage).await?;
+    }
+    Ok(())
+}
+
+/// Reject a metric submission once the user's daily metric quota is used up
+///
+/// The check is made before storing, so a histogram or summary that crosses
+/// the quota is still stored whole.
+pub async fn check_metric_quota(conn: &mut DbConn, user_id: Uuid) -> Result<()> {
+    if let Some((remaining, limit)) = metric_quota_remaining(conn, user_id).await?
+        && remaining == 0
+    {
+        return Err(Error::QuotaExceeded(quota_exceeded_message(
+            "metric",
+            QuotaScope::User,
+            &user_id.to_string(),
+            limit,
+        )));
+    }
+    Ok(())
+}
+
+/// Metric rows the user may still store today, with their quota
+pub async fn metric_quota_remaining(
+    conn: &mut DbConn,
+    user_id: Uuid,
+) -> Result<Option<(i64, i64)>> {
+    let quota = sqlx::query!(
+        r#"
+        SELECT q.daily_metrics AS "daily_metrics!", COALESCE(u.metrics, 0) AS "used!"
+        FROM ingestion_quotas q
+        LEFT JOIN ingestion_usage u ON u.scope = q.scope AND u.key = q.key AND u.day = $2
+        WHERE q.scope = 'user' AND q.key = $1 AND q.daily_metrics IS NOT NULL
+        "#,
+        user_id.to_string(),
+        Utc::now().date_naive()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(quota.map(|quota| {
+        (
+            (quota.daily_metrics - quota.used).max(0),
+            quota.daily_metrics,
+        )
+    }))
+}
+
+/// Add stored metric rows to the user's usage for today
+pub async fn record_metric_usage(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    tenant_id: Uuid,
+    rows: usize,
+) -> Result<()> {
+    if rows == 0 {
+        return Ok(());
+    }
+    record_usage(
+        conn,
+        tenant_id,
+        Utc::now().date_naive(),
+        &[(QuotaScope::User, user_id.to_string(), 0, rows as i64)],
+    )
+    .await
+}
+
+/// Add `(scope, key, events, metrics)` counts to a tenant's usage for a day
+async fn record_usage(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    day: NaiveDate,
+    usage: &[(QuotaScope, String, i64, i64)],
+) -> Result<()> {
+    let scopes: Vec<String> = usage.iter().map(|u| u.0.to_string()).collect();
+    let keys: Vec<String> = usage.iter().map(|u| u.1.clone()).collect();
+    let events: Vec<i64> = usage.iter().map(|u| u.2).collect();
+    let metrics: Vec<i64> = usage.iter().map(|u| u.3).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO ingestion_usage (tenant_id, scope, key, day, events, metrics)
+        SELECT $6, scope, key, $1, events, metrics
+        FROM UNNEST($2::TEXT[], $3::TEXT[], $4::BIGINT[], $5::BIGINT[])
+            AS usage(scope, key, events, metrics)
+        ON CONFLICT (tenant_id, scope, key, day) DO UPDATE
+        SET events = ingestion_usage.events + EXCLUDED.events,
+            metrics = ingestion_usage.metrics + EXCLUDED.metrics
+        "#,
+        day,
+        &scopes,
+        &keys,
+        &events,
+        &metrics,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        "DELETE FROM ingestion_usage WHERE day < $1",
+        day - chrono::Days::new(USAGE_RETENTION_DAYS as u64)
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Today's usage of every user and source with a quota, then the busiest others
+///
+/// With a tenant, only its users and the sources it used today are listed,
+/// with its own usage; without one, usage is summed across tenants.
+pub async fn todays_usage(
+    conn: &mut DbConn,
+    tenant_id: Option<Uuid>,
+) -> Result<Vec<IngestionUsage>> {
+    sqlx::query_as!(
+        IngestionUsage,
+        r#"
+        (
+            SELECT q.scope AS "scope!", q.key AS "key!",
+                   COALESCE(SUM(u.events), 0)::BIGINT AS "events!",
+                   COALESCE(SUM(u.metrics), 0)::BIGINT AS "metrics!",
+                   q.daily_events, q.daily_metrics
+            FROM ingestion_quotas q
+            LEFT JOIN ingestion_usage u
+                ON u.scope = q.scope AND u.key = q.key AND u.day = $1
+               AND ($3::UUID IS NULL OR u.tenant_id = $3)
+            WHERE $3::UUID IS NULL
+               OR q.scope = 'source'
+               OR q.key IN (SELECT id::TEXT FROM users WHERE tenant_id = $3)
+            GROUP BY q.scope, q.key, q.daily_events, q.daily_metrics
+            HAVING $3::UUID IS NULL OR q.scope = 'user' OR COUNT(u.key) > 0
+            ORDER BY q.scope, q.key
+        )
+        UNION ALL
+        (
+            SELECT u.scope AS "scope!", u.key AS "key!", SUM(u.events)::BIGINT AS "events!",
+                   SUM(u.metrics)::BIGINT AS "metrics!", NULL::BIGINT AS daily_events,
+                   NULL::BIGINT AS daily_metrics
+            FROM ingestion_usage u
+            WHERE u.day = $1
+              AND ($3::UUID IS NULL OR u.tenant_id = $3)
+              AND NOT EXISTS (
+                  SELECT 1 FROM ingestion_quotas q WHERE q.scope = u.scope AND q.key = u.key
+              )
+            GROUP BY u.scope, u.key
+            ORDER BY SUM(u.events + u.metrics) DESC, u.scope, u.key
+            LIMIT $2
+        )
+        "#,
+        Utc::now().date_naive(),
+        TOP_USAGE_KEYS,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// List all daily ingestion quotas
+pub async fn list_quotas(conn: &mut DbConn) -> Result<Vec<IngestionQuota>> {
+    sqlx::query_as!(
+        IngestionQuota,
+        r#"
+        SELECT scope, key, daily_events, daily_metrics, updated_by, updated_at
+        FROM ingestion_quotas
+        ORDER BY scope, key
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Create or replace the daily quota of a user or source
+pub async fn set_quota(
+    conn: &mut DbConn,
+    scope: QuotaScope,
+    key: &str,
+    request: SetIngestionQuotaRequest,
+    updated_by: Uuid,
+) -> Result<IngestionQuota> {
+    let key = quota_key(conn, scope, key).await?;
+    request.validate_for(scope)?;
+
+    sqlx::query_as!(
+        IngestionQuota,
+        r#"
+        INSERT INTO ingestion_quotas (scope, key, daily_events, daily_metrics, updated_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (scope, key) DO UPDATE
+        SET daily_events = EXCLUDED.daily_events,
+            daily_metrics = EXCLUDED.daily_metrics,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING scope, key, daily_events, daily_metrics, updated_by, updated_at
+        "#,
+        scope.as_str(),
+        key,
+        request.daily_events,
+        request.daily_metrics,
+        updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Remove the daily quota of a user or source
+pub async fn delete_quota(conn: &mut DbConn, scope: QuotaScope, key: &str) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM ingestion_quotas WHERE scope = $1 AND key = $2",
+        scope.as_str(),
+        key
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!(
+            "No ingestion quota for {scope} '{key}'"
+        )));
+    }
+    Ok(())
+}
+
+/// Check a quota key: a source name, or the ID of an existing user
+async fn quota_key(conn: &mut DbConn, scope: QuotaScope, key: &str) -> Result<String> {
+    match scope {
+        QuotaScope::Source => {
+            if key.is_empty() || key.len() > MAX_SOURCE_LENGTH {
+                return Err(Error::validation(
+                    "key",
+                    &format!("Source must be 1-{MAX_SOURCE_LENGTH} characters"),
+                ));
+            }
+            Ok(key.to_string())
+        }
+        QuotaScope::User => {
+            let user_id = Uuid::parse_str(key)
+                .map_err(|_| Error::validation("key", "User quotas are keyed by user ID"))?;
+            let exists = sqlx::query_scalar!(
+                r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+                user_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            if !exists {
+                return Err(Error::NotFound("User not found".to_string()));
+            }
+            Ok(user_id.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_quotas_reset_at_next_utc_midnight() {
+        let now = Utc.with_ymd_and_hms(2024, 2, 29, 23, 59, 59).unwrap();
+        assert_eq!(
+            quotas_reset_at(now),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_source_quotas_cannot_limit_metrics() {
+        let request = SetIngestionQuotaRequest {
+            daily_events: Some(100),
+            daily_metrics: Some(100),
+        };
+        assert!(request.validate_for(QuotaScope::User).is_ok());
+        assert!(request.validate_for(QuotaScope::Source).is_err());
+
+        let empty = SetIngestionQuotaRequest {
+            daily_events: None,
+            daily_metrics: None,
+        };
+        assert!(empty.validate_for(QuotaScope::User).is_err());
+    }
+}
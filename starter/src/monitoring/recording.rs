@@ -0,0 +1,402 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::models::{
+    CreateMetricRequest, CreateRecordingRuleRequest, MetricAggregation, MetricResolution,
+    MetricType, RecordingRule, Validate,
+};
+use crate::monitoring::query::{self, LabelMatcher, MetricQuery};
+use crate::monitoring::services;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Only evaluate windows that ended at least this long ago, so samples that
+/// were still committing when the window closed are included
+const RECORDING_COMMIT_MARGIN: chrono::Duration = chrono::Duration::seconds(30);
+
+/// Windows a rule catches up on after the worker was down; older ones are skipped
+const MAX_CATCHUP_WINDOWS: i32 = 100;
+
+/// Outcome of one recording rule run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecordingRun {
+    /// Rules evaluated successfully
+    pub evaluated: u64,
+    /// Metric rows stored for them
+    pub recorded: u64,
+    /// Rules whose evaluation failed; see their `last_error`
+    pub failed: u64,
+}
+
+/// A parsed recording rule expression
+///
+/// The syntax is `<aggregation>(<metric>{<label matchers>}) by (<labels>)`,
+/// where the matchers and the `by` clause are optional, for example
+/// `p95(http_request_duration_ms{env=prod}) by (route)`. Aggregations and
+/// matchers are those of the metric query API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingExpression {
+    pub aggregation: MetricAggregation,
+    pub metric: String,
+    pub matchers: Vec<LabelMatcher>,
+    pub group_by: Vec<String>,
+}
+
+impl RecordingExpression {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = || {
+            Error::validation(
+                "expression",
+                "Expression must look like avg(metric_name{label=value}) by (label)",
+            )
+        };
+
+        let (call, group_by) = split_by_clause(expression.trim());
+        let (aggregation, selector) = call
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+            .ok_or_else(invalid)?;
+        let aggregation = aggregation
+            .trim()
+            .parse::<MetricAggregation>()
+            .map_err(|_| {
+                Error::validation(
+                    "expression",
+                    "Aggregation must be avg, sum, min, max, count or p1-p99",
+                )
+            })?;
+
+        let selector = selector.trim();
+        let (metric, matchers) = match selector.split_once('{') {
+            Some((metric, matchers)) => {
+                let matchers = matchers.strip_suffix('}').ok_or_else(invalid)?;
+                (metric.trim(), LabelMatcher::parse_list(matchers)?)
+            }
+            None => (selector, Vec::new()),
+        };
+        if metric.is_empty() || metric.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        crate::monitoring::retention::validate_metric_name(metric)?;
+
+        Ok(Self {
+            aggregation,
+            metric: metric.to_string(),
+            matchers,
+            group_by: query::parse_group_by(group_by.unwrap_or_default())?,
+        })
+    }
+}
+
+/// Split a trailing `by (labels)` clause off an expression
+fn split_by_clause(expression: &str) -> (&str, Option<&str>) {
+    let Some(clause) = expression.strip_suffix(')') else {
+        return (expression, None);
+    };
+    let Some(open) = clause.rfind('(') else {
+        return (expression, None);
+    };
+    let Some(call) = clause[..open].trim_end().strip_suffix("by") else {
+        return (expression, None);
+    };
+    if !call.ends_with(|c: char| c == ')' || c.is_whitespace()) {
+        return (expression, None);
+    }
+    (call.trim_end(), Some(&clause[open + 1..]))
+}
+
+/// Background job that stores the results of due recording rules
+pub async fn recording_rule_job(pool: DbPool, run_interval: Duration, config: MonitoringConfig) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = match pool.acquire().await {
+            Ok(mut conn) => evaluate_rules(conn.as_mut(), &config, Utc::now()).await,
+            Err(e) => Err(Error::from_sqlx(e)),
+        };
+        match result {
+            Ok(run) => {
+                if run != RecordingRun::default() {
+                    info!(
+                        "Recording rules: {} evaluated, {} metric rows stored, {} failed",
+                        run.evaluated, run.recorded, run.failed
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to evaluate recording rules: {}", e);
+            }
+        }
+    }
+}
+
+/// Evaluate every rule with a complete window ending before `now`
+///
+/// Windows are aligned to multiples of the rule's interval since the Unix
+/// epoch. Each window's aggregate is stored as a gauge under the rule's
+/// name, stamped with the window start and labelled with the `by` labels,
+/// so querying the recorded metric with the rule's interval as step returns
+/// what the expression would. Rules read and write their tenant's metrics. A rule continues from the last window it
+/// stored, catching up on up to 100 windows; a failed evaluation is retried
+/// on the next run.
+pub async fn evaluate_rules(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    now: DateTime<Utc>,
+) -> Result<RecordingRun> {
+    let cutoff = now - RECORDING_COMMIT_MARGIN;
+    let rules = sqlx::query_as!(
+        RecordingRule,
+        r#"
+        SELECT id, name, expression, interval_secs, last_window_end, last_evaluated_at,
+               last_error, tenant_id, created_by, created_at, updated_at
+        FROM recording_rules
+        WHERE last_window_end IS NULL
+           OR last_window_end + make_interval(secs => interval_secs) <= $1
+        ORDER BY name
+        "#,
+        cutoff
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut run = RecordingRun::default();
+    for rule in rules {
+        let window_end = align_window(cutoff, rule.interval_secs);
+        match evaluate_rule(conn, config, &rule, window_end).await {
+            Ok(recorded) => {
+                run.evaluated += 1;
+                run.recorded += recorded;
+                mark_evaluated(conn, rule.id, now, Some(window_end), None).await?;
+            }
+            Err(e) => {
+                run.failed += 1;
+                error!("Recording rule '{}' failed: {}", rule.name, e);
+                mark_evaluated(conn, rule.id, now, None, Some(&e.to_string())).await?;
+            }
+        }
+    }
+    Ok(run)
+}
+
+/// Start of the rule window `at` falls in
+fn align_window(at: DateTime<Utc>, interval_secs: i32) -> DateTime<Utc> {
+    let secs = at.timestamp();
+    let aligned = secs - secs.rem_euclid(i64::from(interval_secs));
+    DateTime::from_timestamp(aligned, 0).unwrap_or(at)
+}
+
+/// Store the rule's windows up to `window_end`, returning the rows stored
+async fn evaluate_rule(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    rule: &RecordingRule,
+    window_end: DateTime<Utc>,
+) -> Result<u64> {
+    let expression = RecordingExpression::parse(&rule.expression)?;
+    let interval = chrono::Duration::seconds(i64::from(rule.interval_secs));
+    let earliest = window_end - interval * MAX_CATCHUP_WINDOWS;
+    let window_start = rule
+        .last_window_end
+        .map_or(window_end - interval, |last| last.max(earliest));
+    if window_start >= window_end {
+        return Ok(0);
+    }
+
+    let result = query::query_metrics(
+        conn,
+        config,
+        &MetricQuery {
+            tenant_id: rule.tenant_id,
+            name: expression.metric,
+            matchers: expression.matchers,
+            group_by: expression.group_by,
+            start_time: window_start,
+            // The query range is inclusive; samples at the end belong to the next window
+            end_time: window_end - chrono::Duration::microseconds(1),
+            step_secs: Some(i64::from(rule.interval_secs)),
+            aggregation: expression.aggregation,
+            resolution: MetricResolution::Auto,
+        },
+    )
+    .await?;
+
+    let rows: Vec<CreateMetricRequest> = result
+        .series
+        .iter()
+        .flat_map(|series| {
+            let labels = series
+                .labels
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(key, value)| {
+                    let value = value
+                        .as_str()
+                        .map_or_else(|| value.to_string(), str::to_string);
+                    (key.clone(), value)
+                })
+                .collect::<std::collections::HashMap<_, _>>();
+            series.points.iter().map(move |point| CreateMetricRequest {
+                name: rule.name.clone(),
+                metric_type: MetricType::Gauge,
+                value: point.value,
+                labels: labels.clone(),
+                recorded_at: Some(point.timestamp),
+                tenant_id: Some(rule.tenant_id),
+            })
+        })
+        .collect();
+    services::create_metrics_batch(conn, &rows).await
+}
+
+async fn mark_evaluated(
+    conn: &mut DbConn,
+    id: Uuid,
+    now: DateTime<Utc>,
+    window_end: Option<DateTime<Utc>>,
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE recording_rules
+        SET last_window_end = COALESCE($2, last_window_end),
+            last_evaluated_at = $3,
+            last_error = $4
+        WHERE id = $1
+        "#,
+        id,
+        window_end,
+        now,
+        error
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+// Recording rule management functions
+
+pub async fn create_recording_rule(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateRecordingRuleRequest,
+    created_by: Option<Uuid>,
+) -> Result<RecordingRule> {
+    request.validate()?;
+
+    sqlx::query_as!(
+        RecordingRule,
+        r#"
+        INSERT INTO recording_rules (name, expression, interval_secs, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name, expression, interval_secs, last_window_end, last_evaluated_at,
+                  last_error, tenant_id, created_by, created_at, updated_at
+        "#,
+        request.name.trim(),
+        request.expression.trim(),
+        request.interval_secs,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::Conflict(format!(
+            "A recording rule already stores '{}'",
+            request.name.trim()
+        )),
+        _ => Error::from_sqlx(e),
+    })
+}
+
+pub async fn find_recording_rules(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<Vec<RecordingRule>> {
+    sqlx::query_as!(
+        RecordingRule,
+        r#"
+        SELECT id, name, expression, interval_secs, last_window_end, last_evaluated_at,
+               last_error, tenant_id, created_by, created_at, updated_at
+        FROM recording_rules
+        WHERE tenant_id = $1
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a tenant's rule; the metric rows it already stored are kept
+pub async fn delete_recording_rule(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM recording_rules WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Recording rule not found".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::query::MatchOp;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_expression() {
+        let expression = RecordingExpression::parse(
+            "p95(http_request_duration_ms{env=prod, route=~/api/.*}) by (route)",
+        )
+        .unwrap();
+        assert_eq!(expression.aggregation, MetricAggregation::Percentile(95));
+        assert_eq!(expression.metric, "http_request_duration_ms");
+        assert_eq!(expression.matchers.len(), 2);
+        assert_eq!(expression.matchers[1].op, MatchOp::Regex);
+        assert_eq!(expression.group_by, ["route"]);
+
+        let plain = RecordingExpression::parse(" sum( requests_total ) ").unwrap();
+        assert_eq!(plain.aggregation, MetricAggregation::Sum);
+        assert_eq!(plain.metric, "requests_total");
+        assert!(plain.matchers.is_empty() && plain.group_by.is_empty());
+
+        for invalid in [
+            "requests_total",
+            "median(requests_total)",
+            "avg(requests_total{env=prod)",
+            "avg(requests total)",
+            "avg(requests_total) by (bad label)",
+        ] {
+            assert!(RecordingExpression::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_align_window() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 15, 10, 7, 42).unwrap();
+        assert_eq!(
+            align_window(at, 300),
+            Utc.with_ymd_and_hms(2024, 1, 15, 10, 5, 0).unwrap()
+        );
+        assert_eq!(
+            align_window(at, 3600),
+            Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap()
+        );
+    }
+}
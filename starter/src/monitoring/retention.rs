@@ -0,0 +1,453 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::cardinality;
+use crate::monitoring::models::{
+    MAX_METRIC_NAME_LENGTH, MetricPoint, MetricResolution, MetricRetentionPolicy, MetricSeries,
+    SetMetricRetentionRequest, Validate,
+};
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Width of each rollup bucket
+pub const ROLLUP_RESOLUTION_SECS: i32 = 300;
+
+/// Only roll up rows created at least this long ago, so inserts that were
+/// still committing when the job ran are picked up by the next run
+const ROLLUP_COMMIT_MARGIN: chrono::Duration = chrono::Duration::seconds(30);
+
+/// Maximum number of rows deleted per statement to keep transactions short
+const PRUNE_BATCH_SIZE: i64 = 5000;
+
+const DEFAULT_SERIES_LIMIT: i64 = 1000;
+const MAX_SERIES_LIMIT: i64 = 10_000;
+
+/// Background job that rolls up new raw metrics and prunes expired data
+pub async fn metric_retention_job(pool: DbPool, run_interval: Duration, config: MonitoringConfig) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        match run_metric_maintenance(&pool, &config).await {
+            Ok((rolled_up, raw_deleted, rollups_deleted)) => {
+                if rolled_up + raw_deleted + rollups_deleted > 0 {
+                    info!(
+                        "Metric maintenance: {} buckets rolled up, {} raw metrics and {} rollups pruned",
+                        rolled_up, raw_deleted, rollups_deleted
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to roll up and prune metrics: {}", e);
+            }
+        }
+    }
+}
+
+async fn run_metric_maintenance(
+    pool: &DbPool,
+    config: &MonitoringConfig,
+) -> Result<(u64, u64, u64)> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    let now = Utc::now();
+    let rolled_up = rollup_metrics(conn.as_mut(), config, now - ROLLUP_COMMIT_MARGIN).await?;
+    let (raw_deleted, rollups_deleted) = prune_metrics(conn.as_mut(), config, now).await?;
+    cardinality::prune_series(conn.as_mut(), config, now).await?;
+    Ok((rolled_up, raw_deleted, rollups_deleted))
+}
+
+/// Recompute the rollup buckets touched by raw metrics created up to `until`
+///
+/// Each touched bucket is rebuilt from all of its raw samples, so late
+/// samples and repeated runs give the same result. Buckets already past raw
+/// retention are skipped because their raw samples may be gone. Returns the
+/// number of buckets written.
+pub async fn rollup_metrics(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    until: DateTime<Utc>,
+) -> Result<u64> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    // Locking the state row keeps concurrent workers from rolling up the same range
+    let since =
+        sqlx::query_scalar!("SELECT rolled_up_until FROM metric_rollup_state WHERE id FOR UPDATE")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+
+    if until <= since {
+        return Ok(0);
+    }
+
+    let result = sqlx::query!(
+        r#"
+        WITH touched AS (
+            SELECT DISTINCT tenant_id, name, labels,
+                   date_bin(make_interval(secs => $4::INT), recorded_at, TIMESTAMPTZ 'epoch') AS bucket_start
+            FROM metrics
+            WHERE created_at > $1 AND created_at <= $2
+        ),
+        live AS (
+            SELECT t.tenant_id, t.name, t.labels, t.bucket_start
+            FROM touched t
+            LEFT JOIN metric_retention_policies p ON p.metric_name = t.name
+            WHERE t.bucket_start >= $2::TIMESTAMPTZ - make_interval(days => COALESCE(p.raw_retention_days, $3))
+        )
+        INSERT INTO metric_rollups
+            (tenant_id, name, metric_type, labels, resolution_secs, bucket_start, count, sum, min, max, last)
+        SELECT m.tenant_id, m.name,
+               (array_agg(m.metric_type ORDER BY m.recorded_at DESC, m.created_at DESC))[1],
+               m.labels, $4::INT, l.bucket_start,
+               COUNT(*), SUM(m.value), MIN(m.value), MAX(m.value),
+               (array_agg(m.value ORDER BY m.recorded_at DESC, m.created_at DESC))[1]
+        FROM live l
+        JOIN metrics m
+          ON m.tenant_id = l.tenant_id
+         AND m.name = l.name
+         AND m.labels = l.labels
+         AND m.recorded_at >= l.bucket_start
+         AND m.recorded_at < l.bucket_start + make_interval(secs => $4::INT)
+        GROUP BY m.tenant_id, m.name, m.labels, l.bucket_start
+        ON CONFLICT (tenant_id, name, resolution_secs, bucket_start, labels) DO UPDATE
+        SET metric_type = EXCLUDED.metric_type,
+            count = EXCLUDED.count,
+            sum = EXCLUDED.sum,
+            min = EXCLUDED.min,
+            max = EXCLUDED.max,
+            last = EXCLUDED.last
+        "#,
+        since,
+        until,
+        config.metric_raw_retention_days as i32,
+        ROLLUP_RESOLUTION_SECS
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        "UPDATE metric_rollup_state SET rolled_up_until = $1 WHERE id",
+        until
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(result.rows_affected())
+}
+
+/// Delete raw metrics and rollups older than their metric's retention
+///
+/// Returns the number of raw metrics and rollup buckets deleted.
+pub async fn prune_metrics(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    now: DateTime<Utc>,
+) -> Result<(u64, u64)> {
+    let mut raw_deleted = 0;
+    loop {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM metrics
+            WHERE id IN (
+                SELECT m.id FROM metrics m
+                LEFT JOIN metric_retention_policies p ON p.metric_name = m.name
+                WHERE m.recorded_at < $1::TIMESTAMPTZ - make_interval(days => COALESCE(p.raw_retention_days, $2))
+                LIMIT $3
+            )
+            "#,
+            now,
+            config.metric_raw_retention_days as i32,
+            PRUNE_BATCH_SIZE
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        raw_deleted += result.rows_affected();
+        if result.rows_affected() < PRUNE_BATCH_SIZE as u64 {
+            break;
+        }
+    }
+
+    let mut rollups_deleted = 0;
+    loop {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM metric_rollups
+            WHERE ctid IN (
+                SELECT r.ctid FROM metric_rollups r
+                LEFT JOIN metric_retention_policies p ON p.metric_name = r.name
+                WHERE r.bucket_start < $1::TIMESTAMPTZ - make_interval(days => COALESCE(p.rollup_retention_days, $2))
+                LIMIT $3
+            )
+            "#,
+            now,
+            config.metric_rollup_retention_days as i32,
+            PRUNE_BATCH_SIZE
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        rollups_deleted += result.rows_affected();
+        if result.rows_affected() < PRUNE_BATCH_SIZE as u64 {
+            break;
+        }
+    }
+
+    Ok((raw_deleted, rollups_deleted))
+}
+
+/// A tenant's samples of a metric over a time range
+#[derive(Debug, Clone, Copy)]
+pub struct MetricSeriesQuery<'a> {
+    pub tenant_id: Uuid,
+    pub name: &'a str,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub resolution: MetricResolution,
+    pub limit: Option<i64>,
+}
+
+/// Read a metric's samples over a time range at the requested resolution
+///
+/// `Auto` serves raw samples while the whole range is still within the
+/// metric's raw retention and rollups once it reaches further back.
+pub async fn query_metric_series(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    query: &MetricSeriesQuery<'_>,
+) -> Result<MetricSeries> {
+    let MetricSeriesQuery {
+        tenant_id,
+        name,
+        start_time,
+        end_time,
+        resolution,
+        limit,
+    } = *query;
+    validate_metric_name(name)?;
+    if start_time > end_time {
+        return Err(Error::validation(
+            "start_time",
+            "start_time must not be after end_time",
+        ));
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_SERIES_LIMIT)
+        .clamp(1, MAX_SERIES_LIMIT);
+
+    let resolution = resolve_resolution(conn, config, name, start_time, resolution).await?;
+
+    let (resolution_secs, points) = if resolution == MetricResolution::Raw {
+        let rows = sqlx::query!(
+            r#"
+            SELECT recorded_at, labels, value
+            FROM metrics
+            WHERE tenant_id = $1 AND name = $2 AND recorded_at >= $3 AND recorded_at <= $4
+            ORDER BY recorded_at ASC
+            LIMIT $5
+            "#,
+            tenant_id,
+            name,
+            start_time,
+            end_time,
+            limit
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        let points = rows
+            .into_iter()
+            .map(|row| MetricPoint {
+                timestamp: row.recorded_at,
+                labels: row.labels,
+                count: 1,
+                sum: row.value,
+                min: row.value,
+                max: row.value,
+                avg: row.value,
+                last: row.value,
+            })
+            .collect();
+        (None, points)
+    } else {
+        // Include the bucket that contains start_time
+        let rows = sqlx::query!(
+            r#"
+            SELECT bucket_start, labels, count, sum, min, max, last
+            FROM metric_rollups
+            WHERE tenant_id = $6 AND name = $1 AND resolution_secs = $2
+              AND bucket_start > $3::TIMESTAMPTZ - make_interval(secs => $2)
+              AND bucket_start <= $4
+            ORDER BY bucket_start ASC
+            LIMIT $5
+            "#,
+            name,
+            ROLLUP_RESOLUTION_SECS,
+            start_time,
+            end_time,
+            limit,
+            tenant_id
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        let points = rows
+            .into_iter()
+            .map(|row| MetricPoint {
+                timestamp: row.bucket_start,
+                labels: row.labels,
+                count: row.count,
+                sum: row.sum,
+                min: row.min,
+                max: row.max,
+                avg: row.sum / row.count.max(1) as f64,
+                last: row.last,
+            })
+            .collect();
+        (Some(ROLLUP_RESOLUTION_SECS), points)
+    };
+
+    Ok(MetricSeries {
+        name: name.to_string(),
+        resolution,
+        resolution_secs,
+        points,
+    })
+}
+
+/// Pick raw samples when `start_time` is still within the metric's raw retention, rollups otherwise
+///
+/// Explicit `raw` and `rollup` resolutions are returned unchanged.
+pub(crate) async fn resolve_resolution(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    name: &str,
+    start_time: DateTime<Utc>,
+    resolution: MetricResolution,
+) -> Result<MetricResolution> {
+    if resolution != MetricResolution::Auto {
+        return Ok(resolution);
+    }
+
+    let (raw_days, _) = effective_retention(conn, config, name).await?;
+    let raw_cutoff = Utc::now() - chrono::Duration::days(raw_days as i64);
+    if start_time >= raw_cutoff {
+        Ok(MetricResolution::Raw)
+    } else {
+        Ok(MetricResolution::Rollup)
+    }
+}
+
+/// Raw and rollup retention in days for a metric, falling back to the configured defaults
+async fn effective_retention(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    name: &str,
+) -> Result<(i32, i32)> {
+    let policy = sqlx::query!(
+        r#"
+        SELECT raw_retention_days, rollup_retention_days
+        FROM metric_retention_policies
+        WHERE metric_name = $1
+        "#,
+        name
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(match policy {
+        Some(policy) => (policy.raw_retention_days, policy.rollup_retention_days),
+        None => (
+            config.metric_raw_retention_days as i32,
+            config.metric_rollup_retention_days as i32,
+        ),
+    })
+}
+
+/// List per-metric retention overrides ordered by metric name
+pub async fn list_retention_policies(conn: &mut DbConn) -> Result<Vec<MetricRetentionPolicy>> {
+    sqlx::query_as!(
+        MetricRetentionPolicy,
+        r#"
+        SELECT metric_name, raw_retention_days, rollup_retention_days, updated_by, updated_at
+        FROM metric_retention_policies
+        ORDER BY metric_name
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Create or replace a metric's retention override
+pub async fn set_retention_policy(
+    conn: &mut DbConn,
+    name: &str,
+    request: SetMetricRetentionRequest,
+    updated_by: Uuid,
+) -> Result<MetricRetentionPolicy> {
+    validate_metric_name(name)?;
+    request.validate()?;
+
+    sqlx::query_as!(
+        MetricRetentionPolicy,
+        r#"
+        INSERT INTO metric_retention_policies
+            (metric_name, raw_retention_days, rollup_retention_days, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (metric_name) DO UPDATE
+        SET raw_retention_days = EXCLUDED.raw_retention_days,
+            rollup_retention_days = EXCLUDED.rollup_retention_days,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING metric_name, raw_retention_days, rollup_retention_days, updated_by, updated_at
+        "#,
+        name,
+        request.raw_retention_days,
+        request.rollup_retention_days,
+        updated_by
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Remove a metric's retention override so it falls back to the defaults
+pub async fn delete_retention_policy(conn: &mut DbConn, name: &str) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM metric_retention_policies WHERE metric_name = $1",
+        name
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!(
+            "No retention policy for metric '{name}'"
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_metric_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_METRIC_NAME_LENGTH {
+        return Err(Error::validation(
+            "name",
+            &format!("Metric name must be 1-{MAX_METRIC_NAME_LENGTH} characters"),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use crate::monitoring::models::{
+    Alert, AlertRoutingRule, CreateAlertRoutingRuleRequest, NotificationChannel, Validate,
+};
+use crate::monitoring::{escalation, notifications};
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Correlation key of the incident opened while an alert fires
+pub(crate) fn alert_incident_key(alert_id: Uuid) -> String {
+    format!("alert:{alert_id}")
+}
+
+/// Rules of the alert's tenant whose severity and labels match it, by name
+pub async fn matching_rules(conn: &mut DbConn, alert: &Alert) -> Result<Vec<AlertRoutingRule>> {
+    let tenant_id = sqlx::query_scalar!("SELECT tenant_id FROM alerts WHERE id = $1", alert.id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .ok_or_else(|| Error::NotFound("Alert not found".to_string()))?;
+    query_rules(conn, tenant_id, Some(alert)).await
+}
+
+/// All rules of the tenant, or only those matching `alert`
+async fn query_rules(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    alert: Option<&Alert>,
+) -> Result<Vec<AlertRoutingRule>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT r.id, r.name, r.severity, r.labels, r.escalation_policy_id,
+               r.created_by, r.created_at, r.updated_at,
+               COALESCE(array_agg(c.channel_id) FILTER (WHERE c.channel_id IS NOT NULL), '{}')
+                   AS "channel_ids!"
+        FROM alert_routing_rules r
+        LEFT JOIN alert_routing_rule_channels c ON c.rule_id = r.id
+        WHERE r.tenant_id = $3
+          AND ($1::TEXT IS NULL OR r.severity IS NULL OR r.severity = $1)
+          AND ($2::JSONB IS NULL OR $2 @> r.labels)
+        GROUP BY r.id
+        ORDER BY r.name
+        "#,
+        alert.map(|alert| alert.severity.as_str()),
+        alert.map(|alert| &alert.labels),
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| AlertRoutingRule {
+            id: row.id,
+            name: row.name,
+            severity: row.severity.map(Into::into),
+            labels: row.labels,
+            channel_ids: row.channel_ids,
+            escalation_policy_id: row.escalation_policy_id,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+        .collect())
+}
+
+/// The alert's own channels plus those of the rules matching it, each once
+pub async fn routed_channels(conn: &mut DbConn, alert: &Alert) -> Result<Vec<NotificationChannel>> {
+    let rules = matching_rules(conn, alert).await?;
+    let rule_channel_ids: Vec<Uuid> = rules
+        .iter()
+        .flat_map(|rule| rule.channel_ids.iter().copied())
+        .collect();
+
+    sqlx::query_as!(
+        NotificationChannel,
+        r#"
+        SELECT id, name, kind, target, created_by, created_at, updated_at
+        FROM notification_channels
+        WHERE id = ANY($2)
+           OR id IN (SELECT channel_id FROM alert_notification_channels WHERE alert_id = $1)
+        ORDER BY name
+        "#,
+        alert.id,
+        &rule_channel_ids
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Open an incident for a firing alert when a matching rule has an escalation policy
+///
+/// The first matching rule with a policy wins, and the incident belongs to
+/// the alert's tenant. Returns `None` when no rule pages, or an incident for
+/// the alert is already active.
+pub async fn open_alert_incident(
+    conn: &mut DbConn,
+    alert: &Alert,
+    now: DateTime<Utc>,
+) -> Result<Option<Uuid>> {
+    let rules = matching_rules(conn, alert).await?;
+    let Some(policy_id) = rules.iter().find_map(|rule| rule.escalation_policy_id) else {
+        return Ok(None);
+    };
+    let next_escalation_at = escalation::first_step_due_at(conn, policy_id, now).await?;
+
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO incidents (title, description, severity, started_at, escalation_policy_id,
+                               next_escalation_at, correlation_key, tenant_id)
+        SELECT $1, $2, $3, $4, $5, $6, $7, tenant_id
+        FROM alerts
+        WHERE id = $8
+        ON CONFLICT (tenant_id, correlation_key)
+            WHERE correlation_key IS NOT NULL AND status IN ('open', 'investigating')
+            DO NOTHING
+        RETURNING id
+        "#,
+        format!("Alert firing: {}", alert.name),
+        alert.description,
+        alert.severity.incident_severity().as_str(),
+        now,
+        policy_id,
+        next_escalation_at,
+        alert_incident_key(alert.id),
+        alert.id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Resolve the active incident opened for an alert, if any
+pub async fn resolve_alert_incident(conn: &mut DbConn, alert_id: Uuid) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE incidents
+        SET status = 'resolved',
+            resolved_at = NOW(),
+            next_escalation_at = NULL,
+            updated_at = NOW()
+        WHERE correlation_key = $1 AND status IN ('open', 'investigating')
+        "#,
+        alert_incident_key(alert_id)
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Routing rule management functions
+
+/// Create a rule in the tenant, routing to its channels and escalation policies
+pub async fn create_routing_rule(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateAlertRoutingRuleRequest,
+    created_by: Option<Uuid>,
+) -> Result<AlertRoutingRule> {
+    request.validate()?;
+    let name = request.name.trim();
+    notifications::require_channels_in_tenant(conn, &request.channel_ids, tenant_id).await?;
+    if let Some(policy_id) = request.escalation_policy_id {
+        escalation::require_policy_in_tenant(conn, policy_id, tenant_id).await?;
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let rule = sqlx::query!(
+        r#"
+        INSERT INTO alert_routing_rules (name, severity, labels, escalation_policy_id,
+                                         created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, labels, created_at, updated_at
+        "#,
+        name,
+        request.severity.map(|severity| severity.as_str()),
+        json!(request.labels),
+        request.escalation_policy_id,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Routing rule '{name}' already exists"))
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("escalation_policy_id", "Escalation policy not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let channel_ids = sqlx::query_scalar!(
+        r#"
+        INSERT INTO alert_routing_rule_channels (rule_id, channel_id)
+        SELECT $1, channel_id FROM UNNEST($2::UUID[]) AS channel_id
+        ON CONFLICT DO NOTHING
+        RETURNING channel_id
+        "#,
+        rule.id,
+        &request.channel_ids
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("channel_ids", "Notification channel not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(AlertRoutingRule {
+        id: rule.id,
+        name: name.to_string(),
+        severity: request.severity,
+        labels: rule.labels,
+        channel_ids,
+        escalation_policy_id: request.escalation_policy_id,
+        created_by,
+        created_at: rule.created_at,
+        updated_at: rule.updated_at,
+    })
+}
+
+/// Rules of the tenant, by name
+pub async fn find_routing_rules(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<Vec<AlertRoutingRule>> {
+    query_rules(conn, tenant_id, None).await
+}
+
+/// Delete a rule; alerts it matched keep their own channels
+pub async fn delete_routing_rule(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM alert_routing_rules WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Routing rule not found".to_string()));
+    }
+    Ok(())
+}
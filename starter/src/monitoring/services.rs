@@ -1,7 +1,15 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::cardinality;
+use crate::monitoring::escalation;
+use crate::monitoring::histogram;
+use crate::monitoring::instrumentation;
 use crate::monitoring::models::*;
+use crate::monitoring::quotas;
+use crate::tenants::DEFAULT_TENANT_ID;
 use crate::{DbConn, Error, Result};
 use chrono::Utc;
 use serde_json::json;
+use sqlx::{Acquire, Postgres, QueryBuilder};
 use std::collections::HashMap;
 use std::str::FromStr;
 use uuid::Uuid;
@@ -22,9 +30,9 @@ pub async fn create_event(conn: &mut DbConn, request: CreateEventRequest) -> Res
 
     let event = sqlx::query!(
         r#"
-        INSERT INTO events (id, event_type, source, message, level, tags, payload, recorded_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id, event_type, source, message, level, tags, payload, recorded_at, created_at
+        INSERT INTO events (id, event_type, source, message, level, tags, payload, recorded_at, org_id, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, event_type, source, message, level, tags, payload, recorded_at, created_at, org_id
         "#,
         id,
         event_type.to_string(),
@@ -33,7 +41,9 @@ pub async fn create_event(conn: &mut DbConn, request: CreateEventRequest) -> Res
         request.level,
         tags_json,
         payload_json,
-        recorded_at
+        recorded_at,
+        request.org_id,
+        request.tenant_id.unwrap_or(DEFAULT_TENANT_ID)
     )
     .fetch_one(&mut *conn)
     .await
@@ -54,14 +64,29 @@ pub async fn create_event(conn: &mut DbConn, request: CreateEventRequest) -> Res
         payload: event.payload,
         recorded_at: event.recorded_at,
         created_at: event.created_at,
+        org_id: event.org_id,
     };
 
     Ok(event)
 }
 
 pub async fn find_events_with_filter(conn: &mut DbConn, filter: EventFilter) -> Result<Vec<Event>> {
-    let mut query_builder = sqlx::QueryBuilder::new(
-        "SELECT id, event_type, source, message, level, tags, payload, recorded_at, created_at FROM events WHERE 1=1",
+    let events = events_query(&filter, true)
+        .build_query_as::<Event>()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    Ok(events)
+}
+
+/// Query for the events matching `filter`, newest or oldest first
+pub(crate) fn events_query(
+    filter: &EventFilter,
+    newest_first: bool,
+) -> QueryBuilder<'static, Postgres> {
+    let mut query_builder = QueryBuilder::new(
+        "SELECT id, event_type, source, message, level, tags, payload, recorded_at, created_at, org_id FROM events WHERE 1=1",
     );
 
     if let Some(event_type) = &filter.event_type {
@@ -71,20 +96,20 @@ pub async fn find_events_with_filter(conn: &mut DbConn, filter: EventFilter) ->
 
     if let Some(source) = &filter.source {
         query_builder.push(" AND source = ");
-        query_builder.push_bind(source);
+        query_builder.push_bind(source.clone());
     }
 
     if let Some(level) = &filter.level {
         query_builder.push(" AND level = ");
-        query_builder.push_bind(level);
+        query_builder.push_bind(level.clone());
     }
 
-    if let Some(start_time) = &filter.start_time {
+    if let Some(start_time) = filter.start_time {
         query_builder.push(" AND recorded_at >= ");
         query_builder.push_bind(start_time);
     }
 
-    if let Some(end_time) = &filter.end_time {
+    if let Some(end_time) = filter.end_time {
         query_builder.push(" AND recorded_at <= ");
         query_builder.push_bind(end_time);
     }
@@ -99,36 +124,65 @@ pub async fn find_events_with_filter(conn: &mut DbConn, filter: EventFilter) ->
         }
     }
 
-    query_builder.push(" ORDER BY recorded_at DESC");
+    if let Some(org_id) = filter.org_id {
+        query_builder.push(" AND org_id = ");
+        query_builder.push_bind(org_id);
+    }
+
+    if let Some(tenant_id) = filter.tenant_id {
+        query_builder.push(" AND tenant_id = ");
+        query_builder.push_bind(tenant_id);
+    }
+
+    // Unscoped events stay visible to everyone
+    if let Some(user_id) = filter.visible_to {
+        query_builder.push(
+            " AND (org_id IS NULL OR org_id IN (SELECT org_id FROM org_members WHERE user_id = ",
+        );
+        query_builder.push_bind(user_id);
+        query_builder.push("))");
+    }
+
+    if newest_first {
+        query_builder.push(" ORDER BY recorded_at DESC");
+    } else {
+        query_builder.push(" ORDER BY recorded_at ASC, id ASC");
+    }
+    push_limit_offset(&mut query_builder, filter.limit, filter.offset);
+    query_builder
+}
 
-    if let Some(limit) = filter.limit {
+fn push_limit_offset(
+    query_builder: &mut QueryBuilder<'static, Postgres>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) {
+    if let Some(limit) = limit {
         query_builder.push(" LIMIT ");
         query_builder.push_bind(limit);
     }
 
-    if let Some(offset) = filter.offset {
+    if let Some(offset) = offset {
         query_builder.push(" OFFSET ");
         query_builder.push_bind(offset);
     }
-
-    let events = query_builder
-        .build_query_as::<Event>()
-        .fetch_all(&mut *conn)
-        .await
-        .map_err(Error::from_sqlx)?;
-
-    Ok(events)
 }
 
-pub async fn find_event_by_id(conn: &mut DbConn, id: Uuid) -> Result<Option<Event>> {
+/// Event of the tenant with the given ID
+pub async fn find_event_by_id(
+    conn: &mut DbConn,
+    id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Option<Event>> {
     let event = sqlx::query_as!(
         Event,
         r#"
-        SELECT id, event_type, source, message, level, tags, payload, recorded_at, created_at
+        SELECT id, event_type, source, message, level, tags, payload, recorded_at, created_at, org_id
         FROM events
-        WHERE id = $1
+        WHERE id = $1 AND tenant_id = $2
         "#,
-        id
+        id,
+        tenant_id
     )
     .fetch_optional(&mut *conn)
     .await
@@ -139,18 +193,26 @@ pub async fn find_event_by_id(conn: &mut DbConn, id: Uuid) -> Result<Option<Even
 
 // Metric management functions
 
-pub async fn create_metric(conn: &mut DbConn, request: CreateMetricRequest) -> Result<Metric> {
+pub async fn create_metric(
+    conn: &mut DbConn,
+    request: CreateMetricRequest,
+    config: &MonitoringConfig,
+) -> Result<Metric> {
     // Validate input using the Validate trait
     request.validate()?;
 
+    let mut rows = [request];
+    limit_request_series(conn, config, &mut rows).await?;
+    let [request] = rows;
+
     let id = Uuid::new_v4();
     let recorded_at = request.recorded_at.unwrap_or_else(Utc::now);
     let labels = json!(request.labels);
 
     let metric = sqlx::query!(
         r#"
-        INSERT INTO metrics (id, name, metric_type, value, labels, recorded_at)
-        VALUES ($1, $2, $3, $4, $5, $6)
+        INSERT INTO metrics (id, name, metric_type, value, labels, recorded_at, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         RETURNING id, name, metric_type, value, labels, recorded_at, created_at
         "#,
         id,
@@ -158,11 +220,13 @@ pub async fn create_metric(conn: &mut DbConn, request: CreateMetricRequest) -> R
         request.metric_type.to_string(),
         request.value,
         labels,
-        recorded_at
+        recorded_at,
+        request.tenant_id.unwrap_or(DEFAULT_TENANT_ID)
     )
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?;
+    cardinality::record_series(conn, std::slice::from_ref(&request)).await?;
 
     let metric = Metric {
         id: metric.id,
@@ -186,13 +250,27 @@ pub async fn find_metrics_with_filter(
     conn: &mut DbConn,
     filter: MetricFilter,
 ) -> Result<Vec<Metric>> {
-    let mut query_builder = sqlx::QueryBuilder::new(
+    let metrics = metrics_query(&filter, true)
+        .build_query_as::<Metric>()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    Ok(metrics)
+}
+
+/// Query for the metric points matching `filter`, newest or oldest first
+pub(crate) fn metrics_query(
+    filter: &MetricFilter,
+    newest_first: bool,
+) -> QueryBuilder<'static, Postgres> {
+    let mut query_builder = QueryBuilder::new(
         "SELECT id, name, metric_type, value, labels, recorded_at, created_at FROM metrics WHERE 1=1",
     );
 
     if let Some(name) = &filter.name {
         query_builder.push(" AND name = ");
-        query_builder.push_bind(name);
+        query_builder.push_bind(name.clone());
     }
 
     if let Some(metric_type) = &filter.metric_type {
@@ -200,51 +278,166 @@ pub async fn find_metrics_with_filter(
         query_builder.push_bind(metric_type.to_string());
     }
 
-    if let Some(start_time) = &filter.start_time {
+    if let Some(start_time) = filter.start_time {
         query_builder.push(" AND recorded_at >= ");
         query_builder.push_bind(start_time);
     }
 
-    if let Some(end_time) = &filter.end_time {
+    if let Some(end_time) = filter.end_time {
         query_builder.push(" AND recorded_at <= ");
         query_builder.push_bind(end_time);
     }
 
-    query_builder.push(" ORDER BY recorded_at DESC");
+    if let Some(tenant_id) = filter.tenant_id {
+        query_builder.push(" AND tenant_id = ");
+        query_builder.push_bind(tenant_id);
+    }
 
-    if let Some(limit) = filter.limit {
-        query_builder.push(" LIMIT ");
-        query_builder.push_bind(limit);
+    if newest_first {
+        query_builder.push(" ORDER BY recorded_at DESC");
+    } else {
+        query_builder.push(" ORDER BY recorded_at ASC, id ASC");
     }
+    push_limit_offset(&mut query_builder, filter.limit, filter.offset);
+    query_builder
+}
 
-    if let Some(offset) = filter.offset {
-        query_builder.push(" OFFSET ");
-        query_builder.push_bind(offset);
+/// Rows per multi-row INSERT, keeping bind parameters well under Postgres' limit
+const BATCH_INSERT_ROWS: usize = 1000;
+
+/// Insert already-validated events in one transaction, returning the number stored
+pub async fn create_events_batch(conn: &mut DbConn, events: &[CreateEventRequest]) -> Result<u64> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let mut inserted = 0;
+    for chunk in events.chunks(BATCH_INSERT_ROWS) {
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT INTO events (event_type, source, message, level, tags, payload, recorded_at, org_id, tenant_id) ",
+        );
+        query_builder.push_values(chunk, |mut row, event| {
+            row.push_bind(&event.event_type)
+                .push_bind(&event.source)
+                .push_bind(&event.message)
+                .push_bind(&event.level)
+                .push_bind(json!(event.tags))
+                .push_bind(json!(event.payload))
+                .push_bind(event.recorded_at.unwrap_or_else(Utc::now))
+                .push_bind(event.org_id)
+                .push_bind(event.tenant_id.unwrap_or(DEFAULT_TENANT_ID));
+        });
+        inserted += query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?
+            .rows_affected();
     }
+    tx.commit().await.map_err(Error::from_sqlx)?;
 
-    let metrics = query_builder
-        .build_query_as::<Metric>()
-        .fetch_all(&mut *conn)
-        .await
-        .map_err(Error::from_sqlx)?;
+    Ok(inserted)
+}
 
-    Ok(metrics)
+/// Apply the series limit to the rows of one request, failing on the first row over it
+pub async fn limit_request_series(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    rows: &mut [CreateMetricRequest],
+) -> Result<()> {
+    let fits = cardinality::limit_series(conn, config, rows).await?;
+    match rows.iter().zip(fits).find(|(_, fits)| !fits) {
+        Some((row, _)) => Err(Error::CardinalityLimitExceeded {
+            metric: row.name.clone(),
+            limit: config.metric_max_series_per_name,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Insert already-validated metrics in one transaction, returning the number stored
+///
+/// Series limits are not checked here; callers apply them first.
+pub async fn create_metrics_batch(
+    conn: &mut DbConn,
+    metrics: &[CreateMetricRequest],
+) -> Result<u64> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let mut inserted = 0;
+    for chunk in metrics.chunks(BATCH_INSERT_ROWS) {
+        let mut query_builder = sqlx::QueryBuilder::new(
+            "INSERT INTO metrics (name, metric_type, value, labels, recorded_at, tenant_id) ",
+        );
+        query_builder.push_values(chunk, |mut row, metric| {
+            row.push_bind(&metric.name)
+                .push_bind(metric.metric_type.to_string())
+                .push_bind(metric.value)
+                .push_bind(json!(metric.labels))
+                .push_bind(metric.recorded_at.unwrap_or_else(Utc::now))
+                .push_bind(metric.tenant_id.unwrap_or(DEFAULT_TENANT_ID));
+        });
+        inserted += query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?
+            .rows_affected();
+    }
+    cardinality::record_series(&mut tx, metrics).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(inserted)
+}
+
+/// Store a histogram observation as its bucket, sum and count rows
+pub async fn create_histogram(
+    conn: &mut DbConn,
+    request: CreateHistogramRequest,
+    tenant_id: Uuid,
+    config: &MonitoringConfig,
+) -> Result<MetricRowsStored> {
+    request.validate()?;
+    let mut rows = request.into_rows();
+    for row in &mut rows {
+        row.tenant_id = Some(tenant_id);
+    }
+    limit_request_series(conn, config, &mut rows).await?;
+    let stored = create_metrics_batch(conn, &rows).await?;
+    Ok(MetricRowsStored { stored })
+}
+
+/// Store a summary observation as its quantile, sum and count rows
+pub async fn create_summary(
+    conn: &mut DbConn,
+    request: CreateSummaryRequest,
+    tenant_id: Uuid,
+    config: &MonitoringConfig,
+) -> Result<MetricRowsStored> {
+    request.validate()?;
+    let mut rows = request.into_rows();
+    for row in &mut rows {
+        row.tenant_id = Some(tenant_id);
+    }
+    limit_request_series(conn, config, &mut rows).await?;
+    let stored = create_metrics_batch(conn, &rows).await?;
+    Ok(MetricRowsStored { stored })
 }
 
 // Alert management functions
 
+/// Create an alert in the tenant
 pub async fn create_alert(
     conn: &mut DbConn,
+    tenant_id: Uuid,
     request: CreateAlertRequest,
     created_by: Option<Uuid>,
 ) -> Result<Alert> {
+    request.validate()?;
     let id = Uuid::new_v4();
 
     let alert = sqlx::query!(
         r#"
-        INSERT INTO alerts (id, name, description, query, threshold_value, created_by)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        RETURNING id, name, description, query, threshold_value, 
+        INSERT INTO alerts (id, name, description, query, threshold_value, severity, labels,
+                            created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, name, description, query, threshold_value, severity, labels,
                  status, triggered_at, resolved_at, 
                  created_by, created_at, updated_at
         "#,
@@ -253,7 +446,10 @@ pub async fn create_alert(
         request.description,
         request.query,
         request.threshold_value,
-        created_by
+        request.severity.as_str(),
+        json!(request.labels),
+        created_by,
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -265,6 +461,8 @@ pub async fn create_alert(
         description: alert.description,
         query: alert.query,
         threshold_value: alert.threshold_value,
+        severity: alert.severity.into(),
+        labels: alert.labels,
         status: AlertStatus::from_str(&alert.status).map_err(|_| {
             Error::InvalidInput(format!(
                 "Invalid alert status in database: {}",
@@ -281,16 +479,19 @@ pub async fn create_alert(
     Ok(alert)
 }
 
-pub async fn find_all_alerts(conn: &mut DbConn) -> Result<Vec<Alert>> {
+/// Alerts of the tenant, newest first
+pub async fn find_all_alerts(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<Alert>> {
     let alerts = sqlx::query_as!(
         Alert,
         r#"
-        SELECT id, name, description, query, threshold_value, 
+        SELECT id, name, description, query, threshold_value, severity, labels,
                status, triggered_at, resolved_at, 
                created_by, created_at, updated_at
         FROM alerts
+        WHERE tenant_id = $1
         ORDER BY created_at DESC
-        "#
+        "#,
+        tenant_id
     )
     .fetch_all(&mut *conn)
     .await
@@ -299,30 +500,83 @@ pub async fn find_all_alerts(conn: &mut DbConn) -> Result<Vec<Alert>> {
     Ok(alerts)
 }
 
+pub async fn find_alert_by_id(conn: &mut DbConn, id: Uuid) -> Result<Option<Alert>> {
+    sqlx::query_as!(
+        Alert,
+        r#"
+        SELECT id, name, description, query, threshold_value, severity, labels,
+               status, triggered_at, resolved_at,
+               created_by, created_at, updated_at
+        FROM alerts
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Fail unless `id` is an alert of the tenant
+pub async fn ensure_alert(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM alerts WHERE id = $1 AND tenant_id = $2) AS "exists!""#,
+        id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if !exists {
+        return Err(Error::NotFound("Alert not found".to_string()));
+    }
+    Ok(())
+}
+
 // Incident management functions
 
+/// Open an incident in the tenant
 pub async fn create_incident(
     conn: &mut DbConn,
+    tenant_id: Uuid,
     request: CreateIncidentRequest,
     created_by: Option<Uuid>,
 ) -> Result<Incident> {
     let id = Uuid::new_v4();
 
+    // The first escalation step is due its delay after the incident starts
+    let next_escalation_at = match request.escalation_policy_id {
+        Some(policy_id) => {
+            escalation::require_policy_in_tenant(conn, policy_id, tenant_id).await?;
+            Some(escalation::first_step_due_at(conn, policy_id, Utc::now()).await?)
+        }
+        None => None,
+    };
+
     let incident = sqlx::query!(
         r#"
-        INSERT INTO incidents (id, title, description, severity, created_by, assigned_to)
-        VALUES ($1, $2, $3, $4, $5, $6)
+        INSERT INTO incidents (id, title, description, severity, created_by, assigned_to,
+                               escalation_policy_id, next_escalation_at, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING id, title, description, 
                  severity, status, 
                  started_at, resolved_at, root_cause, 
-                 created_by, assigned_to, created_at, updated_at
+                 created_by, assigned_to, acknowledged_at, acknowledged_by,
+                 escalation_policy_id, escalation_step, next_escalation_at,
+                 postmortem_required,
+                 correlation_key, correlated_event_count, last_correlated_at,
+                 created_at, updated_at
         "#,
         id,
         request.title,
         request.description,
         request.severity.to_string(),
         created_by,
-        request.assigned_to
+        request.assigned_to,
+        request.escalation_policy_id,
+        next_escalation_at,
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -349,6 +603,15 @@ pub async fn create_incident(
         root_cause: incident.root_cause,
         created_by: incident.created_by,
         assigned_to: incident.assigned_to,
+        acknowledged_at: incident.acknowledged_at,
+        acknowledged_by: incident.acknowledged_by,
+        escalation_policy_id: incident.escalation_policy_id,
+        escalation_step: incident.escalation_step,
+        next_escalation_at: incident.next_escalation_at,
+        postmortem_required: incident.postmortem_required,
+        correlation_key: incident.correlation_key,
+        correlated_event_count: incident.correlated_event_count,
+        last_correlated_at: incident.last_correlated_at,
         created_at: incident.created_at,
         updated_at: incident.updated_at,
     };
@@ -356,10 +619,16 @@ pub async fn create_incident(
     Ok(incident)
 }
 
+/// List the tenant's incidents, newest first
+///
+/// `postmortem_pending` keeps only incidents that need a postmortem and have
+/// none (`true`), or excludes them (`false`).
 pub async fn find_incidents_with_pagination(
     conn: &mut DbConn,
+    tenant_id: Uuid,
     limit: Option<i64>,
     offset: Option<i64>,
+    postmortem_pending: Option<bool>,
 ) -> Result<Vec<Incident>> {
     let limit = limit.unwrap_or(50);
     let offset = offset.unwrap_or(0);
@@ -370,13 +639,23 @@ pub async fn find_incidents_with_pagination(
         SELECT id, title, description, 
                severity, status, 
                started_at, resolved_at, root_cause, 
-               created_by, assigned_to, created_at, updated_at
-        FROM incidents
+               created_by, assigned_to, acknowledged_at, acknowledged_by,
+               escalation_policy_id, escalation_step, next_escalation_at,
+               postmortem_required,
+               correlation_key, correlated_event_count, last_correlated_at,
+               created_at, updated_at
+        FROM incidents i
+        WHERE tenant_id = $4
+          AND ($3::BOOLEAN IS NULL
+               OR $3 = (postmortem_required
+                        AND NOT EXISTS (SELECT 1 FROM incident_postmortems p WHERE p.incident_id = i.id)))
         ORDER BY created_at DESC
         LIMIT $1 OFFSET $2
         "#,
         limit,
-        offset
+        offset,
+        postmortem_pending,
+        tenant_id
     )
     .fetch_all(&mut *conn)
     .await
@@ -385,6 +664,23 @@ pub async fn find_incidents_with_pagination(
     Ok(incidents)
 }
 
+/// Fail unless `id` is an incident of the tenant
+pub async fn ensure_incident(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM incidents WHERE id = $1 AND tenant_id = $2) AS "exists!""#,
+        id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if !exists {
+        return Err(Error::NotFound("Incident not found".to_string()));
+    }
+    Ok(())
+}
+
 pub async fn find_incident_by_id(conn: &mut DbConn, id: Uuid) -> Result<Option<Incident>> {
     let incident = sqlx::query_as!(
         Incident,
@@ -392,7 +688,11 @@ pub async fn find_incident_by_id(conn: &mut DbConn, id: Uuid) -> Result<Option<I
         SELECT id, title, description, 
                severity, status, 
                started_at, resolved_at, root_cause, 
-               created_by, assigned_to, created_at, updated_at
+               created_by, assigned_to, acknowledged_at, acknowledged_by,
+               escalation_policy_id, escalation_step, next_escalation_at,
+               postmortem_required,
+               correlation_key, correlated_event_count, last_correlated_at,
+               created_at, updated_at
         FROM incidents
         WHERE id = $1
         "#,
@@ -416,7 +716,11 @@ pub async fn find_incident_by_id_for_update(
         SELECT id, title, description, 
                severity, status, 
                started_at, resolved_at, root_cause, 
-               created_by, assigned_to, created_at, updated_at
+               created_by, assigned_to, acknowledged_at, acknowledged_by,
+               escalation_policy_id, escalation_step, next_escalation_at,
+               postmortem_required,
+               correlation_key, correlated_event_count, last_correlated_at,
+               created_at, updated_at
         FROM incidents
         WHERE id = $1
         FOR UPDATE
@@ -449,12 +753,20 @@ pub async fn update_incident(
                 THEN NOW() 
                 ELSE resolved_at 
             END,
+            next_escalation_at = CASE
+                WHEN $5 IN ('resolved', 'closed') THEN NULL
+                ELSE next_escalation_at
+            END,
             updated_at = NOW()
         WHERE id = $1
         RETURNING id, title, description, 
                  severity, status, 
                  started_at, resolved_at, root_cause, 
-                 created_by, assigned_to, created_at, updated_at
+                 created_by, assigned_to, acknowledged_at, acknowledged_by,
+                 escalation_policy_id, escalation_step, next_escalation_at,
+                 postmortem_required,
+                 correlation_key, correlated_event_count, last_correlated_at,
+                 created_at, updated_at
         "#,
         id,
         request.title,
@@ -489,6 +801,15 @@ pub async fn update_incident(
         root_cause: updated_incident.root_cause,
         created_by: updated_incident.created_by,
         assigned_to: updated_incident.assigned_to,
+        acknowledged_at: updated_incident.acknowledged_at,
+        acknowledged_by: updated_incident.acknowledged_by,
+        escalation_policy_id: updated_incident.escalation_policy_id,
+        escalation_step: updated_incident.escalation_step,
+        next_escalation_at: updated_incident.next_escalation_at,
+        postmortem_required: updated_incident.postmortem_required,
+        correlation_key: updated_incident.correlation_key,
+        correlated_event_count: updated_incident.correlated_event_count,
+        last_correlated_at: updated_incident.last_correlated_at,
         created_at: updated_incident.created_at,
         updated_at: updated_incident.updated_at,
     };
@@ -516,12 +837,20 @@ pub async fn update_incident_in_transaction(
                 THEN NOW() 
                 ELSE resolved_at 
             END,
+            next_escalation_at = CASE
+                WHEN $5 IN ('resolved', 'closed') THEN NULL
+                ELSE next_escalation_at
+            END,
             updated_at = NOW()
         WHERE id = $1
         RETURNING id, title, description, 
                  severity, status, 
                  started_at, resolved_at, root_cause, 
-                 created_by, assigned_to, created_at, updated_at
+                 created_by, assigned_to, acknowledged_at, acknowledged_by,
+                 escalation_policy_id, escalation_step, next_escalation_at,
+                 postmortem_required,
+                 correlation_key, correlated_event_count, last_correlated_at,
+                 created_at, updated_at
         "#,
         id,
         request.title,
@@ -556,6 +885,15 @@ pub async fn update_incident_in_transaction(
         root_cause: updated_incident.root_cause,
         created_by: updated_incident.created_by,
         assigned_to: updated_incident.assigned_to,
+        acknowledged_at: updated_incident.acknowledged_at,
+        acknowledged_by: updated_incident.acknowledged_by,
+        escalation_policy_id: updated_incident.escalation_policy_id,
+        escalation_step: updated_incident.escalation_step,
+        next_escalation_at: updated_incident.next_escalation_at,
+        postmortem_required: updated_incident.postmortem_required,
+        correlation_key: updated_incident.correlation_key,
+        correlated_event_count: updated_incident.correlated_event_count,
+        last_correlated_at: updated_incident.last_correlated_at,
         created_at: updated_incident.created_at,
         updated_at: updated_incident.updated_at,
     };
@@ -569,6 +907,7 @@ pub async fn get_incident_timeline(
     limit: Option<i64>,
     offset: Option<i64>,
     lookback_hours: Option<i64>,
+    correlated_only: bool,
 ) -> Result<IncidentTimeline> {
     // Get incident details first
     let incident = find_incident_by_id(conn, incident_id)
@@ -583,28 +922,60 @@ pub async fn get_incident_timeline(
     let start_time = incident.started_at - chrono::Duration::hours(lookback_hours);
     let end_time = incident.resolved_at.unwrap_or_else(Utc::now);
 
-    let events = sqlx::query!(
+    // Events come from the incident's tenant; notes are always listed,
+    // whatever the time window and correlation filter
+    let rows = sqlx::query!(
         r#"
-        SELECT id, recorded_at, event_type, source, 
-               COALESCE(message, '') as message, level, tags
-        FROM events
-        WHERE recorded_at BETWEEN $1 AND $2
-        ORDER BY recorded_at ASC
+        SELECT id AS "id!", entry_type AS "entry_type!", recorded_at AS "recorded_at!",
+               event_type, source AS "source!", message AS "message!", level,
+               tags AS "tags!", author_id
+        FROM (
+            SELECT e.id, 'event' AS entry_type, e.recorded_at, e.event_type, e.source,
+                   COALESCE(e.message, '') AS message, e.level, e.tags, NULL::UUID AS author_id
+            FROM events e
+            WHERE e.recorded_at BETWEEN $1 AND $2
+              AND e.tenant_id = (SELECT tenant_id FROM incidents WHERE id = $6)
+              AND (NOT $5 OR EXISTS (
+                  SELECT 1 FROM incident_correlated_events c
+                  WHERE c.event_id = e.id AND c.incident_id = $6
+              ))
+            UNION ALL
+            SELECT n.id, 'note', n.noted_at, NULL, COALESCE(u.username, ''),
+                   n.body, NULL, '{}'::JSONB, n.author_id
+            FROM incident_notes n
+            LEFT JOIN users u ON u.id = n.author_id
+            WHERE n.incident_id = $6
+        ) entries
+        ORDER BY recorded_at ASC, id
         LIMIT $3 OFFSET $4
         "#,
         start_time,
         end_time,
         limit,
-        offset
+        offset,
+        correlated_only,
+        incident_id
     )
     .fetch_all(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?;
 
     let total_count = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM events WHERE recorded_at BETWEEN $1 AND $2",
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM events e
+             WHERE recorded_at BETWEEN $1 AND $2
+               AND e.tenant_id = (SELECT tenant_id FROM incidents WHERE id = $4)
+               AND (NOT $3 OR EXISTS (
+                   SELECT 1 FROM incident_correlated_events c
+                   WHERE c.event_id = e.id AND c.incident_id = $4
+               )))
+            + (SELECT COUNT(*) FROM incident_notes WHERE incident_id = $4)
+        "#,
         start_time,
-        end_time
+        end_time,
+        correlated_only,
+        incident_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -612,26 +983,34 @@ pub async fn get_incident_timeline(
     .unwrap_or(0);
 
     let mut entries: Vec<TimelineEntry> = Vec::new();
-    for row in events {
+    for row in rows {
         let tags: HashMap<String, serde_json::Value> =
             serde_json::from_value(row.tags).unwrap_or_default();
 
         // Parse event_type string back to enum for API response
-        let event_type = row.event_type.parse().map_err(|_| {
-            Error::InvalidInput(format!(
-                "Invalid event_type in database: {}",
-                row.event_type
-            ))
-        })?;
+        let event_type = row
+            .event_type
+            .map(|event_type| {
+                event_type.parse().map_err(|_| {
+                    Error::InvalidInput(format!("Invalid event_type in database: {}", event_type))
+                })
+            })
+            .transpose()?;
 
         entries.push(TimelineEntry {
             id: row.id,
+            entry_type: if row.entry_type == "note" {
+                TimelineEntryType::Note
+            } else {
+                TimelineEntryType::Event
+            },
             recorded_at: row.recorded_at,
             event_type,
             source: row.source,
-            message: row.message.unwrap_or_default(),
+            message: row.message,
             level: row.level,
             tags,
+            author_id: row.author_id,
         });
     }
 
@@ -646,7 +1025,12 @@ pub async fn get_incident_timeline(
 
 // Statistics and monitoring functions
 
-pub async fn get_monitoring_stats(conn: &mut DbConn) -> Result<MonitoringStats> {
+/// Monitoring statistics of a tenant, or of the whole deployment without one
+pub async fn get_monitoring_stats(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    tenant_id: Option<Uuid>,
+) -> Result<MonitoringStats> {
     let one_hour_ago = Utc::now() - chrono::Duration::hours(1);
 
     // Optimize with a single query using CTEs (Common Table Expressions) to reduce database roundtrips
@@ -655,19 +1039,27 @@ pub async fn get_monitoring_stats(conn: &mut DbConn) -> Result<MonitoringStats>
         r#"
         WITH stats AS (
             SELECT 
-                (SELECT COUNT(*) FROM events) as total_events,
-                (SELECT COUNT(*) FROM metrics) as total_metrics,
-                (SELECT COUNT(*) FROM alerts WHERE status = 'active') as active_alerts,
-                (SELECT COUNT(*) FROM incidents WHERE status IN ('open', 'investigating')) as open_incidents,
-                (SELECT COUNT(*) FROM events WHERE created_at >= $1) as events_last_hour,
-                (SELECT COUNT(*) FROM metrics WHERE created_at >= $1) as metrics_last_hour
+                (SELECT COUNT(*) FROM events
+                 WHERE $2::UUID IS NULL OR tenant_id = $2) as total_events,
+                (SELECT COUNT(*) FROM metrics
+                 WHERE $2::UUID IS NULL OR tenant_id = $2) as total_metrics,
+                (SELECT COUNT(*) FROM alerts
+                 WHERE status = 'active' AND ($2::UUID IS NULL OR tenant_id = $2)) as active_alerts,
+                (SELECT COUNT(*) FROM incidents
+                 WHERE status IN ('open', 'investigating')
+                   AND ($2::UUID IS NULL OR tenant_id = $2)) as open_incidents,
+                (SELECT COUNT(*) FROM events
+                 WHERE created_at >= $1 AND ($2::UUID IS NULL OR tenant_id = $2)) as events_last_hour,
+                (SELECT COUNT(*) FROM metrics
+                 WHERE created_at >= $1 AND ($2::UUID IS NULL OR tenant_id = $2)) as metrics_last_hour
         )
         SELECT 
             total_events, total_metrics, active_alerts, 
             open_incidents, events_last_hour, metrics_last_hour
         FROM stats
         "#,
-        one_hour_ago
+        one_hour_ago,
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -680,6 +1072,16 @@ pub async fn get_monitoring_stats(conn: &mut DbConn) -> Result<MonitoringStats>
         open_incidents: stats.open_incidents.unwrap_or(0),
         events_last_hour: stats.events_last_hour.unwrap_or(0),
         metrics_last_hour: stats.metrics_last_hour.unwrap_or(0),
+        metric_series_limit: config.metric_max_series_per_name,
+        metric_cardinality: cardinality::top_series_counts(
+            conn,
+            tenant_id,
+            cardinality::TOP_CARDINALITY_NAMES,
+        )
+        .await?,
+        ingestion_usage: quotas::todays_usage(conn, tenant_id).await?,
+        quotas_reset_at: quotas::quotas_reset_at(Utc::now()),
+        app_activity: instrumentation::app_activity(conn, tenant_id, one_hour_ago).await?,
     })
 }
 
@@ -691,11 +1093,30 @@ pub async fn get_prometheus_metrics(conn: &mut DbConn) -> Result<String> {
     // This protects against scenarios where a system has accumulated millions of metrics
     const MAX_PROMETHEUS_METRICS: i64 = 10_000;
 
-    let metrics = sqlx::query!(
+    // Counters and gauges are exposed sample by sample. Histogram rows hold
+    // per-submission bucket counts, so they are summed over the window into
+    // cumulative series; summaries expose their latest quantiles.
+    let mut metrics = sqlx::query!(
         r#"
-        SELECT name, metric_type, value, labels, recorded_at
-        FROM metrics 
-        WHERE recorded_at >= $1
+        SELECT name AS "name!", metric_type AS "metric_type!", value AS "value!",
+               labels AS "labels!", recorded_at AS "recorded_at!"
+        FROM (
+            SELECT name, metric_type, value, labels, recorded_at
+            FROM metrics
+            WHERE recorded_at >= $1 AND metric_type IN ('counter', 'gauge')
+            UNION ALL
+            SELECT name, metric_type, sum(value), labels, max(recorded_at)
+            FROM metrics
+            WHERE recorded_at >= $1 AND metric_type = 'histogram'
+            GROUP BY name, metric_type, labels
+            UNION ALL
+            (
+                SELECT DISTINCT ON (name, labels) name, metric_type, value, labels, recorded_at
+                FROM metrics
+                WHERE recorded_at >= $1 AND metric_type = 'summary'
+                ORDER BY name, labels, recorded_at DESC
+            )
+        ) exposed
         ORDER BY name, recorded_at DESC
         LIMIT $2
         "#,
@@ -706,8 +1127,23 @@ pub async fn get_prometheus_metrics(conn: &mut DbConn) -> Result<String> {
     .await
     .map_err(Error::from_sqlx)?;
 
+    // Keep each histogram or summary together, with buckets in bound order
+    let bucket_bound = |labels: &serde_json::Value| {
+        labels
+            .get(histogram::BUCKET_LABEL)
+            .and_then(|le| le.as_str())
+            .and_then(histogram::parse_bound)
+            .unwrap_or(f64::NEG_INFINITY)
+    };
+    metrics.sort_by(|a, b| {
+        histogram::family_name(&a.name, &a.metric_type)
+            .cmp(histogram::family_name(&b.name, &b.metric_type))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| bucket_bound(&a.labels).total_cmp(&bucket_bound(&b.labels)))
+    });
+
     let mut output = String::new();
-    let mut current_metric = String::new();
+    let mut current_family = String::new();
 
     for metric in metrics {
         // Parse labels from JSONB
@@ -735,18 +1171,19 @@ pub async fn get_prometheus_metrics(conn: &mut DbConn) -> Result<String> {
             format!("{{{}}}", label_pairs.join(","))
         };
 
-        // Add metric type header if this is a new metric
-        if current_metric != metric.name {
-            if !current_metric.is_empty() {
+        // Add metric type header if this is a new metric family
+        let family = histogram::family_name(&metric.name, &metric.metric_type);
+        if current_family != family {
+            if !current_family.is_empty() {
                 output.push('\n');
             }
 
-            current_metric = metric.name.clone();
+            current_family = family.to_string();
 
             // Add HELP and TYPE comments
             output.push_str(&format!(
                 "# HELP {} User-submitted metric\n# TYPE {} {}\n",
-                metric.name, metric.name, metric.metric_type
+                family, family, metric.metric_type
             ));
         }
 
@@ -0,0 +1,434 @@
+use crate::core::config::MonitoringConfig;
+use crate::monitoring::models::{
+    CreateStatusPageRequest, MetricResolution, PublicStatusPage, StatusPage, StatusPageCheck,
+    StatusPageIncident, StatusPageStatus, UpdateStatusPageRequest, UptimeCheckStatus, Validate,
+};
+use crate::monitoring::retention::{self, ROLLUP_RESOLUTION_SECS};
+use crate::monitoring::routing;
+use crate::monitoring::uptime::UPTIME_METRIC;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::Acquire;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Build the public view of a page: its checks with uptime over the last
+/// day, week and month, and its active incidents
+///
+/// Active incidents are those attached to the page plus those opened by
+/// routing rules for the alerts of its checks.
+pub async fn public_status_page(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    slug: &str,
+    now: DateTime<Utc>,
+) -> Result<PublicStatusPage> {
+    let page = sqlx::query!(
+        "SELECT id, slug, title, description, tenant_id FROM status_pages WHERE slug = $1",
+        slug
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Status page not found".to_string()))?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT c.id, c.name, c.status, c.last_checked_at, c.alert_id
+        FROM status_page_checks s
+        JOIN uptime_checks c ON c.id = s.check_id
+        WHERE s.page_id = $1
+        ORDER BY s.position
+        "#,
+        page.id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let check_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let uptime_24h = uptime_percentages(conn, config, &check_ids, now - Duration::days(1)).await?;
+    let uptime_7d = uptime_percentages(conn, config, &check_ids, now - Duration::days(7)).await?;
+    let uptime_30d = uptime_percentages(conn, config, &check_ids, now - Duration::days(30)).await?;
+
+    let alert_keys: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.alert_id.map(routing::alert_incident_key))
+        .collect();
+    let checks: Vec<StatusPageCheck> = rows
+        .into_iter()
+        .map(|row| StatusPageCheck {
+            uptime_24h: uptime_24h.get(&row.id).copied(),
+            uptime_7d: uptime_7d.get(&row.id).copied(),
+            uptime_30d: uptime_30d.get(&row.id).copied(),
+            name: row.name,
+            status: UptimeCheckStatus::from(row.status),
+            last_checked_at: row.last_checked_at,
+        })
+        .collect();
+
+    let incidents = sqlx::query_as!(
+        StatusPageIncident,
+        r#"
+        SELECT title, severity, status, started_at, updated_at
+        FROM incidents
+        WHERE tenant_id = $3 AND status IN ('open', 'investigating')
+          AND (id IN (SELECT incident_id FROM status_page_incidents WHERE page_id = $1)
+               OR correlation_key = ANY($2))
+        ORDER BY started_at DESC
+        "#,
+        page.id,
+        &alert_keys,
+        page.tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let status = if checks
+        .iter()
+        .any(|check| check.status == UptimeCheckStatus::Down)
+    {
+        StatusPageStatus::Outage
+    } else if !incidents.is_empty() {
+        StatusPageStatus::Degraded
+    } else {
+        StatusPageStatus::Operational
+    };
+
+    Ok(PublicStatusPage {
+        slug: page.slug,
+        title: page.title,
+        description: page.description,
+        status,
+        checks,
+        incidents,
+        generated_at: now,
+    })
+}
+
+/// Percentage of passing results per check since `since`, from the results
+/// each check records under its name in its tenant
+///
+/// Reads raw results while `since` is within raw retention and 5-minute
+/// rollups otherwise, like metric queries do. Checks without results are
+/// left out.
+async fn uptime_percentages(
+    conn: &mut DbConn,
+    config: &MonitoringConfig,
+    check_ids: &[Uuid],
+    since: DateTime<Utc>,
+) -> Result<HashMap<Uuid, f64>> {
+    if check_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let resolution =
+        retention::resolve_resolution(conn, config, UPTIME_METRIC, since, MetricResolution::Auto)
+            .await?;
+    let ratios: Vec<(Uuid, f64)> = if resolution == MetricResolution::Raw {
+        sqlx::query!(
+            r#"
+            SELECT c.id AS "check!", avg(m.value) AS "ratio!"
+            FROM uptime_checks c
+            JOIN metrics m ON m.tenant_id = c.tenant_id AND m.labels->>'check' = c.name
+            WHERE c.id = ANY($2) AND m.name = $1 AND m.recorded_at >= $3
+            GROUP BY 1
+            "#,
+            UPTIME_METRIC,
+            check_ids,
+            since
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .into_iter()
+        .map(|row| (row.check, row.ratio))
+        .collect()
+    } else {
+        sqlx::query!(
+            r#"
+            SELECT c.id AS "check!", sum(r.sum) / sum(r.count) AS "ratio!"
+            FROM uptime_checks c
+            JOIN metric_rollups r ON r.tenant_id = c.tenant_id AND r.labels->>'check' = c.name
+            WHERE c.id = ANY($3) AND r.name = $1 AND r.resolution_secs = $2
+              AND r.bucket_start >= $4
+            GROUP BY 1
+            "#,
+            UPTIME_METRIC,
+            ROLLUP_RESOLUTION_SECS,
+            check_ids,
+            since
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .into_iter()
+        .map(|row| (row.check, row.ratio))
+        .collect()
+    };
+
+    Ok(ratios
+        .into_iter()
+        .map(|(check, ratio)| (check, ratio * 100.0))
+        .collect())
+}
+
+// Status page management functions
+
+/// Create a status page of the tenant; only its checks and incidents can be listed
+pub async fn create_status_page(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateStatusPageRequest,
+    created_by: Option<Uuid>,
+) -> Result<StatusPage> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO status_pages (slug, title, description, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        request.slug,
+        request.title.trim(),
+        request.description,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| map_slug_conflict(e, &request.slug))?;
+
+    replace_checks(&mut tx, id, tenant_id, &request.check_ids).await?;
+    replace_incidents(&mut tx, id, tenant_id, &request.incident_ids).await?;
+    let page = find_status_page_by_id(&mut tx, tenant_id, id).await?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(page)
+}
+
+/// Update the fields of a page of the tenant given; `check_ids` and
+/// `incident_ids` replace the previous lists
+///
+/// Only checks and incidents of the tenant can be listed.
+pub async fn update_status_page(
+    conn: &mut DbConn,
+    id: Uuid,
+    tenant_id: Uuid,
+    request: UpdateStatusPageRequest,
+) -> Result<StatusPage> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let updated = sqlx::query_scalar!(
+        r#"
+        UPDATE status_pages
+        SET slug = COALESCE($2, slug),
+            title = COALESCE($3, title),
+            description = COALESCE($4, description),
+            updated_at = NOW()
+        WHERE id = $1 AND tenant_id = $5
+        RETURNING id
+        "#,
+        id,
+        request.slug,
+        request.title.as_deref().map(str::trim),
+        request.description,
+        tenant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| map_slug_conflict(e, request.slug.as_deref().unwrap_or_default()))?;
+    if updated.is_none() {
+        return Err(Error::NotFound("Status page not found".to_string()));
+    }
+
+    if let Some(check_ids) = &request.check_ids {
+        replace_checks(&mut tx, id, tenant_id, check_ids).await?;
+    }
+    if let Some(incident_ids) = &request.incident_ids {
+        replace_incidents(&mut tx, id, tenant_id, incident_ids).await?;
+    }
+    let page = find_status_page_by_id(&mut tx, tenant_id, id).await?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(page)
+}
+
+fn map_slug_conflict(e: sqlx::Error, slug: &str) -> Error {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Status page '{slug}' already exists"))
+        }
+        _ => Error::from_sqlx(e),
+    }
+}
+
+async fn replace_checks(
+    conn: &mut DbConn,
+    page_id: Uuid,
+    tenant_id: Uuid,
+    check_ids: &[Uuid],
+) -> Result<()> {
+    let all_in_tenant = sqlx::query_scalar!(
+        r#"
+        SELECT NOT EXISTS (
+            SELECT 1 FROM UNNEST($1::UUID[]) AS t(check_id)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM uptime_checks c WHERE c.id = t.check_id AND c.tenant_id = $2
+            )
+        ) AS "all_in_tenant!"
+        "#,
+        check_ids,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !all_in_tenant {
+        return Err(Error::validation("check_ids", "Uptime check not found"));
+    }
+
+    sqlx::query!("DELETE FROM status_page_checks WHERE page_id = $1", page_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO status_page_checks (page_id, check_id, position)
+        SELECT $1, check_id, position::INT
+        FROM UNNEST($2::UUID[]) WITH ORDINALITY AS t(check_id, position)
+        ON CONFLICT DO NOTHING
+        "#,
+        page_id,
+        check_ids
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("check_ids", "Uptime check not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+    Ok(())
+}
+
+async fn replace_incidents(
+    conn: &mut DbConn,
+    page_id: Uuid,
+    tenant_id: Uuid,
+    incident_ids: &[Uuid],
+) -> Result<()> {
+    let all_in_tenant = sqlx::query_scalar!(
+        r#"
+        SELECT NOT EXISTS (
+            SELECT 1 FROM UNNEST($1::UUID[]) AS t(incident_id)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM incidents i WHERE i.id = t.incident_id AND i.tenant_id = $2
+            )
+        ) AS "all_in_tenant!"
+        "#,
+        incident_ids,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !all_in_tenant {
+        return Err(Error::validation("incident_ids", "Incident not found"));
+    }
+
+    sqlx::query!(
+        "DELETE FROM status_page_incidents WHERE page_id = $1",
+        page_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO status_page_incidents (page_id, incident_id)
+        SELECT $1, incident_id FROM UNNEST($2::UUID[]) AS incident_id
+        ON CONFLICT DO NOTHING
+        "#,
+        page_id,
+        incident_ids
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("incident_ids", "Incident not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+    Ok(())
+}
+
+/// The tenant's pages, by slug
+pub async fn find_status_pages(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<StatusPage>> {
+    query_pages(conn, tenant_id, None).await
+}
+
+async fn find_status_page_by_id(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    id: Uuid,
+) -> Result<StatusPage> {
+    query_pages(conn, tenant_id, Some(id))
+        .await?
+        .pop()
+        .ok_or_else(|| Error::NotFound("Status page not found".to_string()))
+}
+
+/// All pages of the tenant by slug, or only the one with `id`
+async fn query_pages(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    id: Option<Uuid>,
+) -> Result<Vec<StatusPage>> {
+    sqlx::query_as!(
+        StatusPage,
+        r#"
+        SELECT p.id, p.slug, p.title, p.description,
+               ARRAY(SELECT check_id FROM status_page_checks
+                     WHERE page_id = p.id ORDER BY position) AS "check_ids!",
+               ARRAY(SELECT incident_id FROM status_page_incidents
+                     WHERE page_id = p.id ORDER BY incident_id) AS "incident_ids!",
+               p.created_by, p.created_at, p.updated_at
+        FROM status_pages p
+        WHERE p.tenant_id = $2 AND ($1::UUID IS NULL OR p.id = $1)
+        ORDER BY p.slug
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a page of the tenant; its checks and incidents are left alone
+pub async fn delete_status_page(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM status_pages WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Status page not found".to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use crate::core::sse;
+use crate::monitoring::models::{Event, EventType};
+use crate::{DbPool, Error, Result};
+use sqlx::postgres::PgListener;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OnceCell, broadcast};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// Channel the `events` insert trigger notifies with each new event id
+const EVENTS_CHANNEL: &str = "monitoring_events";
+
+/// Notifications collected into one fetch
+const FETCH_BATCH_SIZE: usize = 500;
+
+/// How long to wait for more notifications before fetching a batch
+const FETCH_BATCH_WAIT: Duration = Duration::from_millis(20);
+
+/// A newly stored event and the tenant it belongs to
+#[derive(Debug, Clone)]
+pub struct StreamedEvent {
+    pub tenant_id: Uuid,
+    pub event: Event,
+}
+
+/// Fan-out of newly stored events to live subscribers
+///
+/// The first subscriber starts a background task that listens for
+/// notifications from the `events` insert trigger, so events stored by any
+/// server instance reach subscribers of every instance.
+#[derive(Clone, Default)]
+pub struct EventStream {
+    sender: Arc<OnceCell<broadcast::Sender<Arc<StreamedEvent>>>>,
+}
+
+impl EventStream {
+    /// Receive events stored from now on
+    pub async fn subscribe(
+        &self,
+        pool: &DbPool,
+    ) -> Result<broadcast::Receiver<Arc<StreamedEvent>>> {
+        let sender = self
+            .sender
+            .get_or_try_init(|| async {
+                let mut listener = PgListener::connect_with(pool)
+                    .await
+                    .map_err(Error::from_sqlx)?;
+                listener
+                    .listen(EVENTS_CHANNEL)
+                    .await
+                    .map_err(Error::from_sqlx)?;
+
+                let (sender, _) = broadcast::channel(sse::DEFAULT_CAPACITY);
+                tokio::spawn(forward_events(listener, pool.clone(), sender.clone()));
+                Ok::<_, Error>(sender)
+            })
+            .await?;
+        Ok(sender.subscribe())
+    }
+}
+
+/// Fetch notified events and send them to subscribers, oldest first
+async fn forward_events(
+    mut listener: PgListener,
+    pool: DbPool,
+    sender: broadcast::Sender<Arc<StreamedEvent>>,
+) {
+    loop {
+        // The listener reconnects by itself; notifications sent while it was
+        // disconnected are lost
+        let mut ids = match listener.recv().await {
+            Ok(notification) => Vec::from_iter(notification.payload().parse::<Uuid>().ok()),
+            Err(e) => {
+                warn!("Event stream listener error: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        while ids.len() < FETCH_BATCH_SIZE {
+            match tokio::time::timeout(FETCH_BATCH_WAIT, listener.recv()).await {
+                Ok(Ok(notification)) => ids.extend(notification.payload().parse::<Uuid>().ok()),
+                _ => break,
+            }
+        }
+
+        if sender.receiver_count() == 0 || ids.is_empty() {
+            continue;
+        }
+        match fetch_events(&pool, &ids).await {
+            Ok(events) => {
+                for event in events {
+                    let _ = sender.send(Arc::new(event));
+                }
+            }
+            Err(e) => error!("Failed to fetch streamed events: {}", e),
+        }
+    }
+}
+
+async fn fetch_events(pool: &DbPool, ids: &[Uuid]) -> Result<Vec<StreamedEvent>> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, event_type, source, message, level, tags, payload, recorded_at, created_at,
+               org_id, tenant_id
+        FROM events
+        WHERE id = ANY($1)
+        ORDER BY created_at, recorded_at
+        "#,
+        ids
+    )
+    .fetch_all(conn.as_mut())
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| StreamedEvent {
+            tenant_id: row.tenant_id,
+            event: Event {
+                id: row.id,
+                event_type: EventType::from(row.event_type),
+                source: row.source,
+                message: row.message,
+                level: row.level,
+                tags: row.tags,
+                payload: row.payload,
+                recorded_at: row.recorded_at,
+                created_at: row.created_at,
+                org_id: row.org_id,
+            },
+        })
+        .collect())
+}
+
+/// Filter applied to streamed events, matching the event list filters
+#[derive(Debug, Clone, Default)]
+pub struct EventStreamFilter {
+    /// Tenant of the subscriber; other tenants' events are never sent
+    pub tenant_id: Uuid,
+    pub event_type: Option<EventType>,
+    pub source: Option<String>,
+    pub level: Option<String>,
+    pub tags: HashMap<String, String>,
+    pub org_id: Option<Uuid>,
+    /// Organizations whose scoped events may be sent; `None` sends all of them
+    pub visible_orgs: Option<HashSet<Uuid>>,
+}
+
+impl EventStreamFilter {
+    pub fn matches(&self, streamed: &StreamedEvent) -> bool {
+        let event = &streamed.event;
+        streamed.tenant_id == self.tenant_id
+            && self
+                .event_type
+                .as_ref()
+                .is_none_or(|event_type| *event_type == event.event_type)
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|source| *source == event.source)
+            && self
+                .level
+                .as_ref()
+                .is_none_or(|level| event.level.as_ref() == Some(level))
+            && self.tags.iter().all(|(key, value)| {
+                event.tags.get(key).and_then(|tag| tag.as_str()) == Some(value.as_str())
+            })
+            && self
+                .org_id
+                .is_none_or(|org_id| event.org_id == Some(org_id))
+            && event.org_id.is_none_or(|org_id| {
+                self.visible_orgs
+                    .as_ref()
+                    .is_none_or(|orgs| orgs.contains(&org_id))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn event() -> StreamedEvent {
+        StreamedEvent {
+            tenant_id: Uuid::nil(),
+            event: Event {
+                id: Uuid::new_v4(),
+                event_type: EventType::Log,
+                source: "app-web".to_string(),
+                message: Some("hello".to_string()),
+                level: Some("error".to_string()),
+                tags: json!({"region": "eu", "attempt": 2}),
+                payload: json!({}),
+                recorded_at: Utc::now(),
+                created_at: Utc::now(),
+                org_id: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_like_the_list_endpoint() {
+        let event = event();
+        assert!(EventStreamFilter::default().matches(&event));
+
+        let filter = EventStreamFilter {
+            event_type: Some(EventType::Log),
+            level: Some("error".to_string()),
+            tags: HashMap::from([("region".to_string(), "eu".to_string())]),
+            ..Default::default()
+        };
+        assert!(filter.matches(&event));
+
+        // Tags match string values only, as with the list endpoint's containment check
+        let filter = EventStreamFilter {
+            tags: HashMap::from([("attempt".to_string(), "2".to_string())]),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&event));
+
+        let filter = EventStreamFilter {
+            source: Some("app-api".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&event));
+
+        let filter = EventStreamFilter {
+            tenant_id: Uuid::new_v4(),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&event));
+    }
+}
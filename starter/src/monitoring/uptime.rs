@@ -0,0 +1,458 @@
+use crate::monitoring::models::{
+    Alert, AlertNotification, AlertSeverity, CreateMetricRequest, CreateUptimeCheckRequest,
+    DEFAULT_UPTIME_CHECK_INTERVAL_SECS, DEFAULT_UPTIME_CHECK_TIMEOUT_SECS, MetricType, UptimeCheck,
+    UptimeCheckStatus, Validate,
+};
+use crate::monitoring::{notifications, services};
+use crate::tasks::services::{HttpClient, TaskServices};
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use serde_json::json;
+use sqlx::Acquire;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Metric recording 1 for each passing check and 0 for each failing one
+pub const UPTIME_METRIC: &str = "uptime_check_up";
+
+/// Maximum number of checks run per job tick
+const UPTIME_BATCH_SIZE: i64 = 50;
+
+/// Response bytes searched for a check's expected body
+const MAX_UPTIME_BODY_BYTES: usize = 1_048_576; // 1MB
+
+/// Outcome of one uptime check run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UptimeRun {
+    pub checked: u64,
+    /// Checks whose request failed or returned an unexpected response
+    pub failed: u64,
+    /// Alerts fired for checks that went down
+    pub alerts_fired: u64,
+    /// Alerts resolved for checks that came back up
+    pub alerts_resolved: u64,
+}
+
+/// Result of requesting a check's URL once
+#[derive(Debug, Default)]
+struct Probe {
+    status_code: Option<u16>,
+    latency_ms: Option<f64>,
+    /// Why the check failed; `None` when it passed
+    error: Option<String>,
+}
+
+/// Background job that runs due uptime checks and notifies the channels of their alerts
+pub async fn uptime_check_job(pool: DbPool, run_interval: Duration, services: TaskServices) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = match pool.acquire().await {
+            Ok(mut conn) => run_due_checks(conn.as_mut(), &services, Utc::now()).await,
+            Err(e) => Err(Error::from_sqlx(e)),
+        };
+        match result {
+            Ok(run) => {
+                if run != UptimeRun::default() {
+                    info!(
+                        "Uptime checks: {} run, {} failed, {} alerts fired, {} resolved",
+                        run.checked, run.failed, run.alerts_fired, run.alerts_resolved
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to run uptime checks: {}", e);
+            }
+        }
+    }
+}
+
+/// Run every check due by `now`
+///
+/// Due checks are claimed by moving their next run one interval ahead, so
+/// several workers can share them, and then requested concurrently. Each
+/// result is stored as `uptime_check_up` (1 or 0) and, when a response
+/// arrived, `uptime_check_latency_ms`, both labelled with the check name.
+/// A check goes down after `failure_threshold` failures in a row, which
+/// fires its alert; the next success resolves it. Silenced alerts are left
+/// alone.
+pub async fn run_due_checks(
+    conn: &mut DbConn,
+    services: &TaskServices,
+    now: DateTime<Utc>,
+) -> Result<UptimeRun> {
+    let checks = sqlx::query_as!(
+        UptimeCheck,
+        r#"
+        UPDATE uptime_checks
+        SET next_check_at = $1 + make_interval(secs => interval_secs)
+        WHERE id IN (
+            SELECT id FROM uptime_checks
+            WHERE next_check_at <= $1
+            ORDER BY next_check_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, name, url, interval_secs, timeout_secs, expected_status, expected_body,
+                  failure_threshold, alert_id, status, consecutive_failures, next_check_at,
+                  last_checked_at, last_status_code, last_latency_ms, last_error,
+                  tenant_id, created_by, created_at, updated_at
+        "#,
+        now,
+        UPTIME_BATCH_SIZE
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let probes = join_all(checks.iter().map(|check| probe(services.http(), check))).await;
+
+    let mut run = UptimeRun::default();
+    let mut metrics = Vec::with_capacity(checks.len() * 2);
+    for (check, probe) in checks.iter().zip(probes) {
+        run.checked += 1;
+        if probe.error.is_some() {
+            run.failed += 1;
+        }
+
+        let labels = HashMap::from([("check".to_string(), check.name.clone())]);
+        metrics.push(CreateMetricRequest {
+            name: UPTIME_METRIC.to_string(),
+            metric_type: MetricType::Gauge,
+            value: if probe.error.is_none() { 1.0 } else { 0.0 },
+            labels: labels.clone(),
+            recorded_at: Some(now),
+            tenant_id: Some(check.tenant_id),
+        });
+        if let Some(latency_ms) = probe.latency_ms {
+            metrics.push(CreateMetricRequest {
+                name: "uptime_check_latency_ms".to_string(),
+                metric_type: MetricType::Gauge,
+                value: latency_ms,
+                labels,
+                recorded_at: Some(now),
+                tenant_id: Some(check.tenant_id),
+            });
+        }
+
+        let (status, consecutive_failures) = next_state(check, probe.error.is_none());
+        sqlx::query!(
+            r#"
+            UPDATE uptime_checks
+            SET status = $2,
+                consecutive_failures = $3,
+                last_checked_at = $4,
+                last_status_code = $5,
+                last_latency_ms = $6,
+                last_error = $7,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            check.id,
+            status.as_str(),
+            consecutive_failures,
+            now,
+            probe.status_code.map(i32::from),
+            probe.latency_ms,
+            probe.error
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        let Some(alert_id) = check.alert_id else {
+            continue;
+        };
+        if status == UptimeCheckStatus::Down && check.status != UptimeCheckStatus::Down {
+            if let Some(alert) = set_alert_firing(conn, alert_id, true, now).await? {
+                run.alerts_fired += 1;
+                let notification = AlertNotification::firing(&alert, false);
+                notifications::dispatch(conn, services, &alert, &notification).await?;
+            }
+        } else if status == UptimeCheckStatus::Up
+            && check.status == UptimeCheckStatus::Down
+            && let Some(alert) = set_alert_firing(conn, alert_id, false, now).await?
+        {
+            run.alerts_resolved += 1;
+            let notification = AlertNotification::resolved(&alert);
+            notifications::dispatch(conn, services, &alert, &notification).await?;
+        }
+    }
+
+    services::create_metrics_batch(conn, &metrics).await?;
+    Ok(run)
+}
+
+/// Status and failure streak of a check after a run
+fn next_state(check: &UptimeCheck, passed: bool) -> (UptimeCheckStatus, i32) {
+    if passed {
+        return (UptimeCheckStatus::Up, 0);
+    }
+    let failures = check.consecutive_failures.saturating_add(1);
+    if failures >= check.failure_threshold {
+        (UptimeCheckStatus::Down, failures)
+    } else {
+        (check.status, failures)
+    }
+}
+
+/// Request the check's URL and compare the response with what it expects
+///
+/// Requests are not retried, so every failure counts towards the threshold.
+async fn probe(http: &HttpClient, check: &UptimeCheck) -> Probe {
+    let timeout_secs = u64::try_from(check.timeout_secs).unwrap_or(1);
+    let started = Instant::now();
+    let response = match http
+        .get(&check.url)
+        .timeout(Duration::from_secs(timeout_secs))
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => {
+            return Probe {
+                error: Some(format!("Timed out after {timeout_secs}s")),
+                ..Default::default()
+            };
+        }
+        Err(e) => {
+            return Probe {
+                error: Some(format!("Request failed: {e}")),
+                ..Default::default()
+            };
+        }
+    };
+    let mut probe = Probe {
+        status_code: Some(response.status().as_u16()),
+        latency_ms: Some(started.elapsed().as_secs_f64() * 1000.0),
+        error: None,
+    };
+
+    if i32::from(response.status().as_u16()) != check.expected_status {
+        probe.error = Some(format!(
+            "Expected HTTP {}, got {}",
+            check.expected_status,
+            response.status().as_u16()
+        ));
+    } else if let Some(expected_body) = &check.expected_body {
+        match read_body(response).await {
+            Ok(body) if body.contains(expected_body.as_str()) => {}
+            Ok(_) => {
+                probe.error = Some("Response body does not contain the expected text".to_string())
+            }
+            Err(e) => probe.error = Some(format!("Failed to read response body: {e}")),
+        }
+    }
+    probe
+}
+
+async fn read_body(mut response: reqwest::Response) -> reqwest::Result<String> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_UPTIME_BODY_BYTES {
+            body.truncate(MAX_UPTIME_BODY_BYTES);
+            break;
+        }
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Fire or resolve a check's alert, unless it is silenced
+async fn set_alert_firing(
+    conn: &mut DbConn,
+    alert_id: Uuid,
+    firing: bool,
+    now: DateTime<Utc>,
+) -> Result<Option<Alert>> {
+    sqlx::query_as!(
+        Alert,
+        r#"
+        UPDATE alerts
+        SET status = CASE WHEN $2 THEN 'active' ELSE 'resolved' END,
+            triggered_at = CASE WHEN $2 THEN $3 ELSE triggered_at END,
+            resolved_at = CASE WHEN $2 THEN NULL ELSE $3 END,
+            updated_at = NOW()
+        WHERE id = $1 AND status <> 'silenced'
+        RETURNING id, name, description, query, threshold_value, severity, labels,
+                  status, triggered_at, resolved_at, created_by, created_at, updated_at
+        "#,
+        alert_id,
+        firing,
+        now
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+// Uptime check management functions
+
+/// Create a check along with the alert it fires while down
+///
+/// The check and its alert belong to the tenant; the alert starts resolved.
+/// Choose where it notifies with `PUT /monitoring/alerts/{id}/channels`.
+pub async fn create_uptime_check(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateUptimeCheckRequest,
+    created_by: Option<Uuid>,
+) -> Result<UptimeCheck> {
+    request.validate()?;
+    let name = request.name.trim();
+    let url = request.url.trim();
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let alert_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO alerts (name, description, query, severity, labels, status, created_by,
+                            tenant_id)
+        VALUES ($1, $2, $3, $4, $5, 'resolved', $6, $7)
+        RETURNING id
+        "#,
+        format!("Uptime check {name} is down"),
+        format!("GET {url} failed"),
+        format!("uptime_check_up{{check={name}}}"),
+        request.severity.unwrap_or(AlertSeverity::Critical).as_str(),
+        json!({ "check": name }),
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let check = sqlx::query_as!(
+        UptimeCheck,
+        r#"
+        INSERT INTO uptime_checks (name, url, interval_secs, timeout_secs, expected_status,
+                                   expected_body, failure_threshold, alert_id, created_by,
+                                   tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING id, name, url, interval_secs, timeout_secs, expected_status, expected_body,
+                  failure_threshold, alert_id, status, consecutive_failures, next_check_at,
+                  last_checked_at, last_status_code, last_latency_ms, last_error,
+                  tenant_id, created_by, created_at, updated_at
+        "#,
+        name,
+        url,
+        request
+            .interval_secs
+            .unwrap_or(DEFAULT_UPTIME_CHECK_INTERVAL_SECS),
+        request
+            .timeout_secs
+            .unwrap_or(DEFAULT_UPTIME_CHECK_TIMEOUT_SECS),
+        request.expected_status.unwrap_or(200),
+        request.expected_body,
+        request.failure_threshold.unwrap_or(1),
+        alert_id,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Uptime check '{name}' already exists"))
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(check)
+}
+
+/// The tenant's checks, by name
+pub async fn find_uptime_checks(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<UptimeCheck>> {
+    sqlx::query_as!(
+        UptimeCheck,
+        r#"
+        SELECT id, name, url, interval_secs, timeout_secs, expected_status, expected_body,
+               failure_threshold, alert_id, status, consecutive_failures, next_check_at,
+               last_checked_at, last_status_code, last_latency_ms, last_error,
+               tenant_id, created_by, created_at, updated_at
+        FROM uptime_checks
+        WHERE tenant_id = $1
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a check of the tenant and its alert; the metrics it recorded are kept
+pub async fn delete_uptime_check(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let alert_id = sqlx::query_scalar!(
+        "DELETE FROM uptime_checks WHERE id = $1 AND tenant_id = $2 RETURNING alert_id",
+        id,
+        tenant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Uptime check not found".to_string()))?;
+
+    if let Some(alert_id) = alert_id {
+        sqlx::query!("DELETE FROM alerts WHERE id = $1", alert_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: UptimeCheckStatus, consecutive_failures: i32) -> UptimeCheck {
+        let now = Utc::now();
+        UptimeCheck {
+            id: Uuid::new_v4(),
+            name: "api".to_string(),
+            url: "http://localhost:3000/api/v1/health".to_string(),
+            interval_secs: 60,
+            timeout_secs: 10,
+            expected_status: 200,
+            expected_body: None,
+            failure_threshold: 3,
+            alert_id: None,
+            status,
+            consecutive_failures,
+            next_check_at: now,
+            last_checked_at: None,
+            last_status_code: None,
+            last_latency_ms: None,
+            last_error: None,
+            tenant_id: Uuid::nil(),
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_next_state_goes_down_at_threshold() {
+        use UptimeCheckStatus::*;
+
+        assert_eq!(next_state(&check(Pending, 0), false), (Pending, 1));
+        assert_eq!(next_state(&check(Up, 1), false), (Up, 2));
+        assert_eq!(next_state(&check(Up, 2), false), (Down, 3));
+        assert_eq!(next_state(&check(Down, 3), false), (Down, 4));
+        assert_eq!(next_state(&check(Down, 4), true), (Up, 0));
+        assert_eq!(next_state(&check(Pending, 2), true), (Up, 0));
+    }
+}
@@ -0,0 +1,497 @@
+use crate::auth::AuthUser;
+use crate::orgs::{
+    invitations as invitation_services,
+    models::{
+        AcceptOrgInvitationRequest, AcceptedOrgInvitation, CreateOrgInvitationRequest,
+        CreateOrgRequest, Org, OrgInvitation, OrgMember, OrgRole, SetOrgMemberRequest,
+    },
+    services as org_services,
+};
+use crate::rbac::UserRole;
+use crate::rbac::api::AuditReasonQuery;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, Path, Query, State},
+    response::Json,
+    routing::{delete, get, post, put},
+};
+use uuid::Uuid;
+
+#[utoipa::path(
+    post,
+    path = "/orgs",
+    tag = "Organizations",
+    summary = "Create organization",
+    description = "Create an organization; the caller becomes its owner",
+    request_body = CreateOrgRequest,
+    responses(
+        (status = 200, description = "Organization created", body = ApiResponse<Org>),
+        (status = 400, description = "Invalid name or slug", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "Slug already taken", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_org(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateOrgRequest>,
+) -> Result<Json<ApiResponse<Org>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let org =
+        org_services::create_org(conn.as_mut(), auth_user.tenant_id, request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(org)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs",
+    tag = "Organizations",
+    summary = "List organizations",
+    description = "List the caller's organizations; admins see all of their tenant's",
+    responses(
+        (status = 200, description = "Organizations retrieved", body = ApiResponse<Vec<Org>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_orgs(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<Org>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let member = (auth_user.role != UserRole::Admin).then_some(auth_user.id);
+    let orgs = org_services::find_orgs(conn.as_mut(), auth_user.tenant_id, member).await?;
+    Ok(Json(ApiResponse::success(orgs)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{id}",
+    tag = "Organizations",
+    summary = "Get organization",
+    description = "Get an organization the caller belongs to",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Organization found", body = ApiResponse<Org>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_org(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Org>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::require_org_role(conn.as_mut(), &auth_user, id, OrgRole::Member).await?;
+
+    match org_services::find_org_by_id(conn.as_mut(), auth_user.tenant_id, id).await? {
+        Some(org) => Ok(Json(ApiResponse::success(org))),
+        None => Err(Error::NotFound("Organization not found".to_string())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{id}",
+    tag = "Organizations",
+    summary = "Delete organization",
+    description = "Delete an organization with its tasks and events (owner only)",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Organization deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not an owner", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_org(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::require_org_role(conn.as_mut(), &auth_user, id, OrgRole::Owner).await?;
+    org_services::delete_org(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(
+        "Organization deleted".to_string(),
+    )))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{id}/members",
+    tag = "Organizations",
+    summary = "List members",
+    description = "List the members of an organization and their roles",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Members retrieved", body = ApiResponse<Vec<OrgMember>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_org_members(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<OrgMember>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::require_org_role(conn.as_mut(), &auth_user, id, OrgRole::Member).await?;
+    let members = org_services::find_members(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success(members)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orgs/{id}/members/{user_id}",
+    tag = "Organizations",
+    summary = "Add or update member",
+    description = "Add a user to an organization or change their role (org admin or owner)",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = SetOrgMemberRequest,
+    responses(
+        (status = 200, description = "Member saved", body = ApiResponse<OrgMember>),
+        (status = 400, description = "User not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse),
+        (status = 409, description = "Would leave the organization without an owner", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn set_org_member(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<SetOrgMemberRequest>,
+) -> Result<Json<ApiResponse<OrgMember>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let member = org_services::set_member_role(
+        conn.as_mut(),
+        &auth_user,
+        id,
+        user_id,
+        request.role,
+        request.reason,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(member)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{id}/members/{user_id}",
+    tag = "Organizations",
+    summary = "Remove member",
+    description = "Remove a member (org admin or owner), or leave the organization",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "User ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Member removed", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization or member not found", body = ErrorResponse),
+        (status = 409, description = "Would leave the organization without an owner", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn remove_org_member(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::remove_member(conn.as_mut(), &auth_user, id, user_id, query.reason).await?;
+    Ok(Json(ApiResponse::success("Member removed".to_string())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{id}/invitations",
+    tag = "Organizations",
+    summary = "Invite by email",
+    description = "Email an invite link to join the organization (org admin or owner; owner invites need an owner)",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID")
+    ),
+    request_body = CreateOrgInvitationRequest,
+    responses(
+        (status = 200, description = "Invitation sent", body = ApiResponse<OrgInvitation>),
+        (status = 400, description = "Invalid email", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse),
+        (status = 409, description = "Already a member or already invited", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_org_invitation(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateOrgInvitationRequest>,
+) -> Result<Json<ApiResponse<OrgInvitation>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let invitation = invitation_services::create_invitation(
+        conn.as_mut(),
+        app_state.services.email(),
+        &app_state.config,
+        &auth_user,
+        id,
+        request,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(invitation)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{id}/invitations",
+    tag = "Organizations",
+    summary = "List invitations",
+    description = "List the organization's invitations with their status (org admin or owner)",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Invitations retrieved", body = ApiResponse<Vec<OrgInvitation>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_org_invitations(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<OrgInvitation>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::require_org_role(conn.as_mut(), &auth_user, id, OrgRole::Admin).await?;
+    let invitations = invitation_services::find_invitations(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success(invitations)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{id}/invitations/{invitation_id}/resend",
+    tag = "Organizations",
+    summary = "Resend invitation",
+    description = "Email a new link for a pending or expired invitation; the previous link stops working",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("invitation_id" = Uuid, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 200, description = "Invitation resent", body = ApiResponse<OrgInvitation>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization or invitation not found", body = ErrorResponse),
+        (status = 409, description = "Invitation already accepted or revoked", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn resend_org_invitation(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<OrgInvitation>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let invitation = invitation_services::resend_invitation(
+        conn.as_mut(),
+        app_state.services.email(),
+        &app_state.config,
+        &auth_user,
+        id,
+        invitation_id,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(invitation)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{id}/invitations/{invitation_id}",
+    tag = "Organizations",
+    summary = "Revoke invitation",
+    description = "Revoke an invitation so its link can't be used (org admin or owner)",
+    params(
+        ("id" = Uuid, Path, description = "Organization ID"),
+        ("invitation_id" = Uuid, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 200, description = "Invitation revoked", body = ApiResponse<OrgInvitation>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Insufficient org role", body = ErrorResponse),
+        (status = 404, description = "Organization or invitation not found", body = ErrorResponse),
+        (status = 409, description = "Invitation already accepted", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_org_invitation(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<OrgInvitation>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let invitation =
+        invitation_services::revoke_invitation(conn.as_mut(), &auth_user, id, invitation_id)
+            .await?;
+    Ok(Json(ApiResponse::success(invitation)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/invitations/accept",
+    tag = "Organizations",
+    summary = "Accept invitation",
+    description = "Join an organization with an emailed invitation token, registering an account for the invited email if none exists",
+    request_body = AcceptOrgInvitationRequest,
+    responses(
+        (status = 200, description = "Invitation accepted", body = ApiResponse<AcceptedOrgInvitation>),
+        (status = 400, description = "Account details missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Invitation not found or revoked", body = ErrorResponse),
+        (status = 409, description = "Invitation expired or already accepted", body = ErrorResponse)
+    )
+)]
+pub async fn accept_org_invitation(
+    State(app_state): State<AppState>,
+    Json(request): Json<AcceptOrgInvitationRequest>,
+) -> Result<Json<ApiResponse<AcceptedOrgInvitation>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let accepted = invitation_services::accept_invitation(conn.as_mut(), request).await?;
+    Ok(Json(ApiResponse::success(accepted)))
+}
+
+/// Public invitation routes (no authentication required; the token is the credential)
+pub fn invitations_public_routes() -> Router<AppState> {
+    Router::new().route("/accept", post(accept_org_invitation))
+}
+
+/// Organization routes (authentication required, org roles checked per request)
+pub fn orgs_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_orgs).post(create_org))
+        .route("/{id}", get(get_org).delete(delete_org))
+        .route("/{id}/members", get(list_org_members))
+        .route(
+            "/{id}/members/{user_id}",
+            put(set_org_member).delete(remove_org_member),
+        )
+        .route(
+            "/{id}/invitations",
+            get(list_org_invitations).post(create_org_invitation),
+        )
+        .route(
+            "/{id}/invitations/{invitation_id}",
+            delete(revoke_org_invitation),
+        )
+        .route(
+            "/{id}/invitations/{invitation_id}/resend",
+            post(resend_org_invitation),
+        )
+}
@@ -0,0 +1,436 @@
+use crate::auth::{AuthUser, models::RegisterRequest, services as auth_services};
+use crate::core::config::AppConfig;
+use crate::orgs::models::{
+    AcceptOrgInvitationRequest, AcceptedOrgInvitation, CreateOrgInvitationRequest, OrgInvitation,
+    OrgInvitationStatus, OrgRole,
+};
+use crate::orgs::services::{
+    find_member, find_org_by_id, org_tenant_id, record_org_role_change, require_org_role,
+};
+use crate::tasks::services::{EmailMessage, EmailSender};
+use crate::tenants::services as tenant_services;
+use crate::users::services as user_services;
+use crate::{DbConn, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Random invitation token; only its SHA-256 hash is stored
+fn generate_invitation_token() -> String {
+    use base64::Engine;
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    let bytes: [u8; 32] = rng.random();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+async fn send_invitation_email(
+    email: &dyn EmailSender,
+    config: &AppConfig,
+    org_name: &str,
+    invitation: &OrgInvitation,
+    token: &str,
+) -> Result<()> {
+    let link = format!(
+        "{}/invitations/accept?token={token}",
+        config.server.public_url.trim_end_matches('/')
+    );
+    let message = EmailMessage {
+        to: invitation.email.clone(),
+        subject: format!("You're invited to join {org_name}"),
+        body: format!(
+            "You have been invited to join {org_name} as {role}.\n\n\
+             Accept the invitation: {link}\n\n\
+             This link expires at {expires}.",
+            role = invitation.role,
+            expires = invitation.expires_at.to_rfc3339(),
+        ),
+    };
+    email.send(&message).await.map_err(|e| {
+        tracing::error!("Failed to send invitation to {}: {e}", invitation.email);
+        Error::Internal("Failed to send invitation email".to_string())
+    })
+}
+
+async fn find_invitation(
+    conn: &mut DbConn,
+    org_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<OrgInvitation> {
+    sqlx::query_as!(
+        OrgInvitation,
+        r#"
+        SELECT id, org_id, email, role,
+               CASE WHEN status = 'pending' AND expires_at <= NOW() THEN 'expired'
+                    ELSE status END AS "status!",
+               invited_by, accepted_by, expires_at, sent_at, accepted_at, created_at
+        FROM org_invitations
+        WHERE id = $1 AND org_id = $2
+        "#,
+        invitation_id,
+        org_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Invitation not found".to_string()))
+}
+
+/// Require org admin to invite, or owner when handing out ownership
+async fn require_inviter(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+    role: OrgRole,
+) -> Result<()> {
+    require_org_role(conn, user, org_id, OrgRole::Admin).await?;
+    if role == OrgRole::Owner {
+        require_org_role(conn, user, org_id, OrgRole::Owner).await?;
+    }
+    Ok(())
+}
+
+/// Invite an email address to the organization and send the link
+///
+/// The invitation is only stored if the email goes out.
+pub async fn create_invitation(
+    conn: &mut DbConn,
+    email: &dyn EmailSender,
+    config: &AppConfig,
+    user: &AuthUser,
+    org_id: Uuid,
+    request: CreateOrgInvitationRequest,
+) -> Result<OrgInvitation> {
+    request.validate()?;
+    let role = request.role.unwrap_or(OrgRole::Member);
+    let address = request.email.trim();
+    require_inviter(conn, user, org_id, role).await?;
+
+    let org = find_org_by_id(conn, user.tenant_id, org_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let is_member = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM org_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.org_id = $1 AND u.email = $2
+        ) AS "exists!"
+        "#,
+        org_id,
+        address
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if is_member {
+        return Err(Error::conflict(
+            "User is already a member of this organization",
+        ));
+    }
+    // Accounts of other tenants can't join the organization
+    if let Some(invitee) = user_services::find_user_by_email(&mut tx, address).await?
+        && tenant_services::user_tenant_id(&mut tx, invitee.id).await? != Some(user.tenant_id)
+    {
+        return Err(Error::validation("email", "User belongs to another tenant"));
+    }
+
+    // Lapsed invitations no longer hold the pending slot
+    sqlx::query!(
+        r#"
+        UPDATE org_invitations SET status = 'expired', updated_at = NOW()
+        WHERE org_id = $1 AND email = $2 AND status = 'pending' AND expires_at <= NOW()
+        "#,
+        org_id,
+        address
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let token = generate_invitation_token();
+    let expires_at = Utc::now() + config.invitation_ttl();
+    let invitation = sqlx::query_as!(
+        OrgInvitation,
+        r#"
+        INSERT INTO org_invitations (org_id, email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, sha256(convert_to($4, 'UTF8')), $5, $6)
+        RETURNING id, org_id, email, role, status, invited_by, accepted_by,
+                  expires_at, sent_at, accepted_at, created_at
+        "#,
+        org_id,
+        address,
+        role.as_str(),
+        token,
+        user.id,
+        expires_at
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("A pending invitation for this email already exists; resend it instead")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    send_invitation_email(email, config, &org.name, &invitation, &token).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(invitation)
+}
+
+/// Invitations of the organization, newest first
+pub async fn find_invitations(conn: &mut DbConn, org_id: Uuid) -> Result<Vec<OrgInvitation>> {
+    sqlx::query_as!(
+        OrgInvitation,
+        r#"
+        SELECT id, org_id, email, role,
+               CASE WHEN status = 'pending' AND expires_at <= NOW() THEN 'expired'
+                    ELSE status END AS "status!",
+               invited_by, accepted_by, expires_at, sent_at, accepted_at, created_at
+        FROM org_invitations
+        WHERE org_id = $1
+        ORDER BY created_at DESC
+        "#,
+        org_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Send a fresh link for a pending or expired invitation
+///
+/// The old link stops working and the expiry starts over.
+pub async fn resend_invitation(
+    conn: &mut DbConn,
+    email: &dyn EmailSender,
+    config: &AppConfig,
+    user: &AuthUser,
+    org_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<OrgInvitation> {
+    require_org_role(conn, user, org_id, OrgRole::Admin).await?;
+    let current = find_invitation(conn, org_id, invitation_id).await?;
+    if current.role == OrgRole::Owner {
+        require_org_role(conn, user, org_id, OrgRole::Owner).await?;
+    }
+    match current.status {
+        OrgInvitationStatus::Pending | OrgInvitationStatus::Expired => {}
+        status => {
+            return Err(Error::Conflict(format!(
+                "Invitation is already {}",
+                status.as_str()
+            )));
+        }
+    }
+
+    let org = find_org_by_id(conn, user.tenant_id, org_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let token = generate_invitation_token();
+    let expires_at = Utc::now() + config.invitation_ttl();
+    let invitation = sqlx::query_as!(
+        OrgInvitation,
+        r#"
+        UPDATE org_invitations
+        SET token_hash = sha256(convert_to($3, 'UTF8')), expires_at = $4,
+            status = 'pending', sent_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND org_id = $2 AND status IN ('pending', 'expired')
+        RETURNING id, org_id, email, role, status, invited_by, accepted_by,
+                  expires_at, sent_at, accepted_at, created_at
+        "#,
+        invitation_id,
+        org_id,
+        token,
+        expires_at
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("Another pending invitation for this email exists")
+        }
+        _ => Error::from_sqlx(e),
+    })?
+    .ok_or_else(|| Error::conflict("Invitation can no longer be resent"))?;
+
+    send_invitation_email(email, config, &org.name, &invitation, &token).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(invitation)
+}
+
+/// Revoke an invitation so its link stops working
+pub async fn revoke_invitation(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<OrgInvitation> {
+    require_org_role(conn, user, org_id, OrgRole::Admin).await?;
+    let current = find_invitation(conn, org_id, invitation_id).await?;
+    if current.role == OrgRole::Owner {
+        require_org_role(conn, user, org_id, OrgRole::Owner).await?;
+    }
+    if current.status == OrgInvitationStatus::Accepted {
+        return Err(Error::conflict("Invitation has already been accepted"));
+    }
+
+    sqlx::query_as!(
+        OrgInvitation,
+        r#"
+        UPDATE org_invitations SET status = 'revoked', updated_at = NOW()
+        WHERE id = $1 AND org_id = $2 AND status <> 'accepted'
+        RETURNING id, org_id, email, role, status, invited_by, accepted_by,
+                  expires_at, sent_at, accepted_at, created_at
+        "#,
+        invitation_id,
+        org_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::conflict("Invitation has already been accepted"))
+}
+
+struct PendingInvitation {
+    id: Uuid,
+    org_id: Uuid,
+    email: String,
+    role: String,
+    status: String,
+    invited_by: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Accept an invitation by token and join the organization
+///
+/// An account is registered in the organization's tenant for the invited
+/// email when none exists yet, which needs a username and password in the
+/// request. Existing accounts of another tenant can't accept.
+pub async fn accept_invitation(
+    conn: &mut DbConn,
+    request: AcceptOrgInvitationRequest,
+) -> Result<AcceptedOrgInvitation> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let invitation = sqlx::query_as!(
+        PendingInvitation,
+        r#"
+        SELECT id, org_id, email, role, status, invited_by, expires_at
+        FROM org_invitations
+        WHERE token_hash = sha256(convert_to($1, 'UTF8'))
+        FOR UPDATE
+        "#,
+        request.token
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .filter(|invitation| invitation.status != "revoked")
+    .ok_or_else(|| Error::NotFound("Invitation not found".to_string()))?;
+
+    if invitation.status == "accepted" {
+        return Err(Error::conflict("Invitation has already been accepted"));
+    }
+    if invitation.status == "expired" || invitation.expires_at <= Utc::now() {
+        return Err(Error::conflict("Invitation has expired; ask for a new one"));
+    }
+
+    let tenant_id = org_tenant_id(&mut tx, invitation.org_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))?;
+    let (user_id, registered) =
+        match user_services::find_user_by_email(&mut tx, &invitation.email).await? {
+            Some(user) => {
+                if tenant_services::user_tenant_id(&mut tx, user.id).await? != Some(tenant_id) {
+                    return Err(Error::Forbidden(
+                        "Invitation is for an organization of another tenant".to_string(),
+                    ));
+                }
+                (user.id, false)
+            }
+            None => {
+                let (Some(username), Some(password)) = (request.username, request.password) else {
+                    return Err(Error::validation(
+                        "username",
+                        "Username and password are required to create an account",
+                    ));
+                };
+                let register = RegisterRequest {
+                    username,
+                    email: invitation.email.clone(),
+                    password,
+                    accepted_documents: Vec::new(),
+                    generate_recovery_codes: false,
+                };
+                (
+                    auth_services::register(&mut tx, register, tenant_id)
+                        .await?
+                        .id,
+                    true,
+                )
+            }
+        };
+
+    let joined = sqlx::query!(
+        r#"
+        INSERT INTO org_members (org_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (org_id, user_id) DO NOTHING
+        "#,
+        invitation.org_id,
+        user_id,
+        invitation.role
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if joined > 0 {
+        record_org_role_change(
+            &mut tx,
+            invitation.invited_by,
+            invitation.org_id,
+            user_id,
+            None,
+            Some(OrgRole::from(invitation.role)),
+            Some("Invitation accepted".to_string()),
+        )
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE org_invitations
+        SET status = 'accepted', accepted_by = $2, accepted_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#,
+        invitation.id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let org = find_org_by_id(&mut tx, tenant_id, invitation.org_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))?;
+    let member = find_member(&mut tx, invitation.org_id, user_id).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(AcceptedOrgInvitation {
+        org,
+        member,
+        registered,
+    })
+}
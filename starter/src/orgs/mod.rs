@@ -0,0 +1,6 @@
+pub mod api;
+pub mod invitations;
+pub mod models;
+pub mod services;
+
+pub use models::{OrgContext, OrgRole};
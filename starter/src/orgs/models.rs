@@ -0,0 +1,249 @@
+use crate::Error;
+use crate::Result;
+use crate::rbac::{Permission, Resource};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub const MAX_ORG_NAME_LENGTH: usize = 100;
+pub const MAX_ORG_SLUG_LENGTH: usize = 64;
+
+/// Organization roles with hierarchy: Member < Admin < Owner
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgRole {
+    /// Can see and create the organization's tasks and events
+    Member = 1,
+    /// Can also manage the organization's tasks and its members
+    Admin = 2,
+    /// Can also manage owners and delete the organization
+    Owner = 3,
+}
+
+impl OrgRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgRole::Member => "member",
+            OrgRole::Admin => "admin",
+            OrgRole::Owner => "owner",
+        }
+    }
+
+    /// Whether the role allows a permission on an organization's tasks and events
+    ///
+    /// Only [`Resource::is_org_scoped`] resources are decided by the org role.
+    pub fn can_access(&self, resource: Resource, permission: Permission) -> bool {
+        // Members see and create items; deleting them is for admins and owners
+        resource.is_org_scoped() && (*self >= OrgRole::Admin || permission != Permission::Delete)
+    }
+}
+
+impl fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OrgRole {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "member" => Ok(OrgRole::Member),
+            "admin" => Ok(OrgRole::Admin),
+            "owner" => Ok(OrgRole::Owner),
+            _ => Err(Error::validation("role", &format!("Invalid role: {s}"))),
+        }
+    }
+}
+
+// Required by SQLx query_as! macro
+impl From<String> for OrgRole {
+    fn from(s: String) -> Self {
+        OrgRole::from_str(&s).unwrap_or_else(|_| {
+            tracing::error!(
+                "Invalid org role '{}' in database, falling back to member",
+                s
+            );
+            OrgRole::Member
+        })
+    }
+}
+
+/// Organization a request acts in, selected with the `X-Org-Id` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+pub struct OrgContext {
+    pub id: Uuid,
+    /// The user's role in the organization; site admins act as owners
+    pub role: OrgRole,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Org {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OrgMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: OrgRole,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateOrgRequest {
+    pub name: String,
+    /// Lowercase letters, digits and dashes
+    pub slug: String,
+}
+
+impl CreateOrgRequest {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_ORG_NAME_LENGTH {
+            return Err(Error::validation(
+                "name",
+                &format!("Name must be between 1 and {MAX_ORG_NAME_LENGTH} characters"),
+            ));
+        }
+        validate_slug(&self.slug)
+    }
+}
+
+pub fn validate_slug(slug: &str) -> Result<()> {
+    let valid = !slug.is_empty()
+        && slug.len() <= MAX_ORG_SLUG_LENGTH
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-');
+    if !valid {
+        return Err(Error::validation(
+            "slug",
+            &format!("Slug must be 1 to {MAX_ORG_SLUG_LENGTH} lowercase letters, digits or dashes"),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SetOrgMemberRequest {
+    pub role: OrgRole,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OrgInvitationStatus {
+    Pending,
+    Accepted,
+    /// Pending past its expiry; resending renews it
+    Expired,
+    Revoked,
+}
+
+impl OrgInvitationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrgInvitationStatus::Pending => "pending",
+            OrgInvitationStatus::Accepted => "accepted",
+            OrgInvitationStatus::Expired => "expired",
+            OrgInvitationStatus::Revoked => "revoked",
+        }
+    }
+}
+
+impl From<String> for OrgInvitationStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "accepted" => OrgInvitationStatus::Accepted,
+            "expired" => OrgInvitationStatus::Expired,
+            "revoked" => OrgInvitationStatus::Revoked,
+            _ => OrgInvitationStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct OrgInvitation {
+    pub id: Uuid,
+    pub org_id: Uuid,
+    pub email: String,
+    pub role: OrgRole,
+    pub status: OrgInvitationStatus,
+    pub invited_by: Option<Uuid>,
+    pub accepted_by: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    /// When the invitation email was last sent
+    pub sent_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateOrgInvitationRequest {
+    pub email: String,
+    /// Defaults to `member`
+    pub role: Option<OrgRole>,
+}
+
+impl CreateOrgInvitationRequest {
+    pub fn validate(&self) -> Result<()> {
+        crate::users::models::validate_email(self.email.trim())
+    }
+}
+
+/// Accept an invitation from its emailed token
+///
+/// `username` and `password` are only needed when no account uses the
+/// invited email yet; one is registered with them.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AcceptOrgInvitationRequest {
+    pub token: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AcceptedOrgInvitation {
+    pub org: Org,
+    pub member: OrgMember,
+    /// Whether an account was registered for the invited email
+    pub registered: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_hierarchy() {
+        assert!(OrgRole::Owner > OrgRole::Admin);
+        assert!(OrgRole::Admin > OrgRole::Member);
+        assert_eq!("admin".parse::<OrgRole>().unwrap(), OrgRole::Admin);
+        assert!("Admin".parse::<OrgRole>().is_err());
+        assert_eq!(OrgRole::from("owner".to_string()), OrgRole::Owner);
+    }
+
+    #[test]
+    fn test_validate_slug() {
+        assert!(validate_slug("acme").is_ok());
+        assert!(validate_slug("acme-2").is_ok());
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug("Acme").is_err());
+        assert!(validate_slug("acme inc").is_err());
+        assert!(validate_slug("-acme").is_err());
+        assert!(validate_slug(&"a".repeat(MAX_ORG_SLUG_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,453 @@
+use crate::auth::AuthUser;
+use crate::orgs::models::{CreateOrgRequest, Org, OrgContext, OrgMember, OrgRole};
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::RbacAuditAction;
+use crate::rbac::{Permission, UserRole, services as rbac_services};
+use crate::tenants::services as tenant_services;
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Create an organization in the tenant; the creator becomes its owner
+pub async fn create_org(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateOrgRequest,
+    created_by: Uuid,
+) -> Result<Org> {
+    request.validate()?;
+    let name = request.name.trim();
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let org = sqlx::query_as!(
+        Org,
+        r#"
+        INSERT INTO orgs (name, slug, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, name, slug, created_by, created_at, updated_at
+        "#,
+        name,
+        request.slug,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Organization '{}' already exists", request.slug))
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    sqlx::query!(
+        "INSERT INTO org_members (org_id, user_id, role) VALUES ($1, $2, $3)",
+        org.id,
+        created_by,
+        OrgRole::Owner.as_str()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(org)
+}
+
+/// All organizations of the tenant, or only those `member` belongs to
+pub async fn find_orgs(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    member: Option<Uuid>,
+) -> Result<Vec<Org>> {
+    sqlx::query_as!(
+        Org,
+        r#"
+        SELECT id, name, slug, created_by, created_at, updated_at
+        FROM orgs
+        WHERE tenant_id = $2
+          AND ($1::UUID IS NULL
+               OR id IN (SELECT org_id FROM org_members WHERE user_id = $1))
+        ORDER BY name
+        "#,
+        member,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Organization of the tenant with `id`
+pub async fn find_org_by_id(conn: &mut DbConn, tenant_id: Uuid, id: Uuid) -> Result<Option<Org>> {
+    sqlx::query_as!(
+        Org,
+        r#"
+        SELECT id, name, slug, created_by, created_at, updated_at
+        FROM orgs
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Tenant the organization belongs to, if it exists
+pub(crate) async fn org_tenant_id(conn: &mut DbConn, id: Uuid) -> Result<Option<Uuid>> {
+    sqlx::query_scalar!("SELECT tenant_id FROM orgs WHERE id = $1", id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)
+}
+
+/// Delete an organization of the tenant along with the tasks and events scoped to it
+pub async fn delete_org(conn: &mut DbConn, id: Uuid, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM orgs WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Organization not found".to_string()));
+    }
+    Ok(())
+}
+
+/// The user's role in the organization, if they are a member
+///
+/// Memberships across tenants don't count.
+pub async fn member_role(
+    conn: &mut DbConn,
+    org_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<OrgRole>> {
+    let role = sqlx::query_scalar!(
+        r#"
+        SELECT m.role
+        FROM org_members m
+        JOIN orgs o ON o.id = m.org_id
+        JOIN users u ON u.id = m.user_id
+        WHERE m.org_id = $1 AND m.user_id = $2 AND o.tenant_id = u.tenant_id
+        "#,
+        org_id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(role.map(Into::into))
+}
+
+/// The user's role in the organization, from the request's org context when
+/// it selected this organization
+async fn effective_role(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+) -> Result<Option<OrgRole>> {
+    match user.org {
+        Some(org) if org.id == org_id => Ok(Some(org.role)),
+        _ => member_role(conn, org_id, user.id).await,
+    }
+}
+
+/// Resolve the organization a request selected
+///
+/// Site admins act as owners of any organization of their tenant; for anyone
+/// else who isn't a member it is not found.
+pub async fn org_context(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    site_role: UserRole,
+    tenant_id: Uuid,
+    org_id: Uuid,
+) -> Result<OrgContext> {
+    let role = if site_role == UserRole::Admin {
+        find_org_by_id(conn, tenant_id, org_id)
+            .await?
+            .map(|_| OrgRole::Owner)
+    } else {
+        member_role(conn, org_id, user_id).await?
+    };
+    role.map(|role| OrgContext { id: org_id, role })
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))
+}
+
+/// Require the user to hold `required` or higher in the organization
+///
+/// Site admins pass every check on organizations of their tenant. Non-members
+/// get a not found error so organizations can't be enumerated.
+pub async fn require_org_role(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+    required: OrgRole,
+) -> Result<()> {
+    if user.role == UserRole::Admin {
+        return match find_org_by_id(conn, user.tenant_id, org_id).await? {
+            Some(_) => Ok(()),
+            None => Err(Error::NotFound("Organization not found".to_string())),
+        };
+    }
+    match effective_role(conn, user, org_id).await? {
+        Some(role) if role >= required => Ok(()),
+        Some(role) => Err(Error::Forbidden(format!(
+            "Insufficient permissions: org {role} cannot perform this action"
+        ))),
+        None => Err(Error::NotFound("Organization not found".to_string())),
+    }
+}
+
+/// Organizations the user belongs to within their tenant
+pub async fn user_org_ids(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<Uuid>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT m.org_id
+        FROM org_members m
+        JOIN orgs o ON o.id = m.org_id
+        JOIN users u ON u.id = m.user_id
+        WHERE m.user_id = $1 AND o.tenant_id = u.tenant_id
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Check task access by ownership and staff `permission`, then by org membership
+///
+/// A task scoped to an organization is also open to its members holding
+/// `required` or higher. Failures stay "Task not found" to prevent enumeration.
+pub async fn can_access_task(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    task_created_by: Option<Uuid>,
+    task_org_id: Option<Uuid>,
+    required: OrgRole,
+    permission: Permission,
+) -> Result<()> {
+    let denied = match rbac_services::can_access_task(user, task_created_by, permission) {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    let Some(org_id) = task_org_id else {
+        return Err(denied);
+    };
+    match effective_role(conn, user, org_id).await? {
+        Some(role) if role >= required => Ok(()),
+        Some(_) => Err(Error::Forbidden(
+            "Insufficient permissions: org role cannot modify this task".to_string(),
+        )),
+        None => Err(denied),
+    }
+}
+
+pub async fn find_members(conn: &mut DbConn, org_id: Uuid) -> Result<Vec<OrgMember>> {
+    sqlx::query_as!(
+        OrgMember,
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM org_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.org_id = $1
+        ORDER BY u.username
+        "#,
+        org_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+pub(crate) async fn find_member(
+    conn: &mut DbConn,
+    org_id: Uuid,
+    user_id: Uuid,
+) -> Result<OrgMember> {
+    sqlx::query_as!(
+        OrgMember,
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM org_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.org_id = $1 AND m.user_id = $2
+        "#,
+        org_id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Member not found".to_string()))
+}
+
+async fn owner_count(conn: &mut DbConn, org_id: Uuid) -> Result<i64> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM org_members WHERE org_id = $1 AND role = 'owner'"#,
+        org_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Record a change of `user_id`'s role in the organization in the RBAC audit log
+pub(crate) async fn record_org_role_change(
+    conn: &mut DbConn,
+    actor_id: Option<Uuid>,
+    org_id: Uuid,
+    user_id: Uuid,
+    before: Option<OrgRole>,
+    after: Option<OrgRole>,
+    reason: Option<String>,
+) -> Result<()> {
+    let state = |role: Option<OrgRole>| role.map(|role| json!({ "org_id": org_id, "role": role }));
+    audit::record_change(
+        conn,
+        RbacChange {
+            actor_id,
+            action: RbacAuditAction::OrgRoleChanged,
+            target_id: user_id,
+            before: state(before),
+            after: state(after),
+            reason,
+        },
+    )
+    .await
+}
+
+/// Add a member or change their role
+///
+/// Org admins manage members; only owners grant or revoke ownership, and
+/// the last owner can't be demoted. Only users of the organization's tenant
+/// can join it.
+pub async fn set_member_role(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+    user_id: Uuid,
+    role: OrgRole,
+    reason: Option<String>,
+) -> Result<OrgMember> {
+    require_org_role(conn, user, org_id, OrgRole::Admin).await?;
+    let org_tenant_id = org_tenant_id(conn, org_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("Organization not found".to_string()))?;
+    if tenant_services::user_tenant_id(conn, user_id).await? != Some(org_tenant_id) {
+        return Err(Error::validation("user_id", "User not found"));
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let current = member_role(&mut tx, org_id, user_id).await?;
+    let touches_owner = role == OrgRole::Owner || current == Some(OrgRole::Owner);
+    if touches_owner {
+        require_org_role(&mut tx, user, org_id, OrgRole::Owner).await?;
+    }
+    if current == Some(OrgRole::Owner)
+        && role != OrgRole::Owner
+        && owner_count(&mut tx, org_id).await? <= 1
+    {
+        return Err(Error::conflict("Organization must keep at least one owner"));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO org_members (org_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (org_id, user_id) DO UPDATE
+        SET role = EXCLUDED.role, updated_at = NOW()
+        "#,
+        org_id,
+        user_id,
+        role.as_str()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::validation("user_id", "User not found")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    if current != Some(role) {
+        record_org_role_change(
+            &mut tx,
+            Some(user.id),
+            org_id,
+            user_id,
+            current,
+            Some(role),
+            reason,
+        )
+        .await?;
+    }
+    let member = find_member(&mut tx, org_id, user_id).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(member)
+}
+
+/// Remove a member; members may always leave, but the last owner can't
+pub async fn remove_member(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    org_id: Uuid,
+    user_id: Uuid,
+    reason: Option<String>,
+) -> Result<()> {
+    if user.id == user_id {
+        require_org_role(conn, user, org_id, OrgRole::Member).await?;
+    } else {
+        require_org_role(conn, user, org_id, OrgRole::Admin).await?;
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let Some(current) = member_role(&mut tx, org_id, user_id).await? else {
+        return Err(Error::NotFound("Member not found".to_string()));
+    };
+    if current == OrgRole::Owner {
+        if user.id != user_id {
+            require_org_role(&mut tx, user, org_id, OrgRole::Owner).await?;
+        }
+        if owner_count(&mut tx, org_id).await? <= 1 {
+            return Err(Error::conflict("Organization must keep at least one owner"));
+        }
+    }
+
+    sqlx::query!(
+        "DELETE FROM org_members WHERE org_id = $1 AND user_id = $2",
+        org_id,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    record_org_role_change(
+        &mut tx,
+        Some(user.id),
+        org_id,
+        user_id,
+        Some(current),
+        None,
+        reason,
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
@@ -0,0 +1,1409 @@
+use crate::auth::AuthUser;
+use crate::rbac::models::{
+    CreateCustomRoleRequest, CreatePermissionBundleRequest, CreatePermissionDenyRequest,
+    CreateRoleRequest, CustomRole, CustomRoleMember, PermissionBundle, PermissionBundleUser,
+    PermissionDeny, RbacAuditEntry, RbacPolicy, RbacPolicyImport, ResourceShare, RoleRequest,
+    RoleRequestStatus, ShareResourceRequest, UpdateCustomRoleRequest,
+    UpdatePermissionBundleRequest, parse_shareable_resource,
+};
+use crate::rbac::{audit, bundles, denies, policy, role_requests, roles, sharing};
+use crate::tenants::services as tenant_services;
+use crate::users::activity;
+use crate::users::models::UserActivityAction;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, Path, Query, State},
+    http::{StatusCode, header},
+    response::{Json, Response},
+    routing::{delete, get, post, put},
+};
+use serde::Deserialize;
+use serde_json::json;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+/// Why an RBAC change without a request body was made
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditReasonQuery {
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+/// List custom roles (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/roles",
+    tag = "Roles",
+    summary = "List custom roles (Admin)",
+    description = "Custom roles with their permissions and member counts, by name",
+    responses(
+        (status = 200, description = "Custom roles", body = ApiResponse<Vec<CustomRole>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_roles(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<CustomRole>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let roles = roles::list_roles(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(roles)))
+}
+
+/// Create a custom role (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/roles",
+    tag = "Roles",
+    summary = "Create custom role (Admin)",
+    description = "Define a named role granting `resource:permission` entries, such as `users:read`, on top of its members' built-in roles",
+    request_body = CreateCustomRoleRequest,
+    responses(
+        (status = 200, description = "Role created", body = ApiResponse<CustomRole>),
+        (status = 400, description = "Invalid name or permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 409, description = "A role with this name exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateCustomRoleRequest>,
+) -> Result<Json<ApiResponse<CustomRole>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Custom roles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let role = roles::create_role(conn.as_mut(), request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(role)))
+}
+
+/// Get a custom role (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/roles/{id}",
+    tag = "Roles",
+    summary = "Get custom role (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    responses(
+        (status = 200, description = "Custom role", body = ApiResponse<CustomRole>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<CustomRole>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let role = roles::find_role_in_tenant(conn.as_mut(), id, Some(auth_user.tenant_id)).await?;
+    Ok(Json(ApiResponse::success(role)))
+}
+
+/// Update a custom role (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{id}",
+    tag = "Roles",
+    summary = "Update custom role (Admin)",
+    description = "Change the description or replace the permissions of a role; members get the new permissions on their next request",
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    request_body = UpdateCustomRoleRequest,
+    responses(
+        (status = 200, description = "Role updated", body = ApiResponse<CustomRole>),
+        (status = 400, description = "Invalid permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateCustomRoleRequest>,
+) -> Result<Json<ApiResponse<CustomRole>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Custom roles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let role = roles::update_role(conn.as_mut(), id, request, auth_user.id).await?;
+    app_state.permission_cache.clear().await;
+    Ok(Json(ApiResponse::success(role)))
+}
+
+/// Delete a custom role (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{id}",
+    tag = "Roles",
+    summary = "Delete custom role (Admin)",
+    description = "Delete a role; its members lose its permissions",
+    params(
+        ("id" = Uuid, Path, description = "Role ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Role deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Custom roles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    roles::delete_role(conn.as_mut(), id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.clear().await;
+    Ok(Json(ApiResponse::success("Role deleted".to_string())))
+}
+
+/// List the members of a custom role (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/roles/{id}/members",
+    tag = "Roles",
+    summary = "List role members (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    responses(
+        (status = 200, description = "Users holding the role", body = ApiResponse<Vec<CustomRoleMember>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_role_members(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<CustomRoleMember>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let members = roles::list_members(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(members)))
+}
+
+/// Give a user a custom role (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/roles/{id}/members/{user_id}",
+    tag = "Roles",
+    summary = "Assign role (Admin)",
+    description = "Give a user the role's permissions on top of their built-in role. Assigning a role the user already has changes nothing",
+    params(
+        ("id" = Uuid, Path, description = "Role ID"),
+        ("user_id" = Uuid, Path, description = "User ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Role assigned", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Role or user not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn assign_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), user_id, auth_user.tenant_id).await?;
+    if let Some(name) =
+        roles::assign_role(conn.as_mut(), id, user_id, auth_user.id, query.reason).await?
+    {
+        app_state.permission_cache.invalidate(user_id).await;
+        activity::record_activity(
+            conn.as_mut(),
+            user_id,
+            Some(auth_user.id),
+            UserActivityAction::RoleChanged,
+            json!({ "custom_role": name, "assigned": true }),
+        )
+        .await;
+    }
+    Ok(Json(ApiResponse::success("Role assigned".to_string())))
+}
+
+/// Take a custom role away from a user (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/roles/{id}/members/{user_id}",
+    tag = "Roles",
+    summary = "Unassign role (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Role ID"),
+        ("user_id" = Uuid, Path, description = "User ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Role unassigned", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "The user doesn't have the role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unassign_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), user_id, auth_user.tenant_id).await?;
+    let name = roles::unassign_role(conn.as_mut(), id, user_id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.invalidate(user_id).await;
+    activity::record_activity(
+        conn.as_mut(),
+        user_id,
+        Some(auth_user.id),
+        UserActivityAction::RoleChanged,
+        json!({ "custom_role": name, "assigned": false }),
+    )
+    .await;
+    Ok(Json(ApiResponse::success("Role unassigned".to_string())))
+}
+
+/// Custom role administration (admin role required)
+pub fn roles_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_roles).post(create_role))
+        .route("/{id}", get(get_role).put(update_role).delete(delete_role))
+        .route("/{id}/members", get(list_role_members))
+        .route(
+            "/{id}/members/{user_id}",
+            put(assign_role).delete(unassign_role),
+        )
+}
+
+/// List permission bundles (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/permission-bundles",
+    tag = "Roles",
+    summary = "List permission bundles (Admin)",
+    description = "Permission bundles with their permissions, the custom roles they are attached to and how many users hold them directly, by name",
+    responses(
+        (status = 200, description = "Permission bundles", body = ApiResponse<Vec<PermissionBundle>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_bundles(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<PermissionBundle>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let bundles = bundles::list_bundles(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(bundles)))
+}
+
+/// Create a permission bundle (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/permission-bundles",
+    tag = "Roles",
+    summary = "Create permission bundle (Admin)",
+    description = "Define a named set of `resource:permission` entries, such as `monitoring-readonly`, to attach to custom roles or users",
+    request_body = CreatePermissionBundleRequest,
+    responses(
+        (status = 200, description = "Bundle created", body = ApiResponse<PermissionBundle>),
+        (status = 400, description = "Invalid name or permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 409, description = "A bundle with this name exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_bundle(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreatePermissionBundleRequest>,
+) -> Result<Json<ApiResponse<PermissionBundle>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Permission bundles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let bundle = bundles::create_bundle(conn.as_mut(), request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(bundle)))
+}
+
+/// Get a permission bundle (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/permission-bundles/{id}",
+    tag = "Roles",
+    summary = "Get permission bundle (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID")
+    ),
+    responses(
+        (status = 200, description = "Permission bundle", body = ApiResponse<PermissionBundle>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Bundle not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_bundle(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<PermissionBundle>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let bundle =
+        bundles::find_bundle_in_tenant(conn.as_mut(), id, Some(auth_user.tenant_id)).await?;
+    Ok(Json(ApiResponse::success(bundle)))
+}
+
+/// Update a permission bundle (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/permission-bundles/{id}",
+    tag = "Roles",
+    summary = "Update permission bundle (Admin)",
+    description = "Change the description or replace the permissions of a bundle; everyone holding it gets the new permissions on their next request",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID")
+    ),
+    request_body = UpdatePermissionBundleRequest,
+    responses(
+        (status = 200, description = "Bundle updated", body = ApiResponse<PermissionBundle>),
+        (status = 400, description = "Invalid permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Bundle not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_bundle(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdatePermissionBundleRequest>,
+) -> Result<Json<ApiResponse<PermissionBundle>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Permission bundles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let bundle = bundles::update_bundle(conn.as_mut(), id, request, auth_user.id).await?;
+    app_state.permission_cache.clear().await;
+    Ok(Json(ApiResponse::success(bundle)))
+}
+
+/// Delete a permission bundle (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/permission-bundles/{id}",
+    tag = "Roles",
+    summary = "Delete permission bundle (Admin)",
+    description = "Delete a bundle; the roles and users it was attached to lose its permissions",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Bundle deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Bundle not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_bundle(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Permission bundles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    bundles::delete_bundle(conn.as_mut(), id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.clear().await;
+    Ok(Json(ApiResponse::success(
+        "Permission bundle deleted".to_string(),
+    )))
+}
+
+/// Attach a permission bundle to a custom role (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/permission-bundles/{id}/roles/{role_id}",
+    tag = "Roles",
+    summary = "Attach bundle to role (Admin)",
+    description = "Grant the bundle's permissions to every member of a custom role. Attaching a bundle that is already attached changes nothing",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID"),
+        ("role_id" = Uuid, Path, description = "Custom role ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Bundle attached", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Bundle or role not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn attach_bundle_to_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, role_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Permission bundles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    if bundles::attach_to_role(conn.as_mut(), id, role_id, auth_user.id, query.reason).await? {
+        app_state.permission_cache.clear().await;
+    }
+    Ok(Json(ApiResponse::success(
+        "Permission bundle attached".to_string(),
+    )))
+}
+
+/// Detach a permission bundle from a custom role (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/permission-bundles/{id}/roles/{role_id}",
+    tag = "Roles",
+    summary = "Detach bundle from role (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID"),
+        ("role_id" = Uuid, Path, description = "Custom role ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Bundle detached", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "The bundle isn't attached to the role", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn detach_bundle_from_role(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, role_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Permission bundles are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    bundles::detach_from_role(conn.as_mut(), id, role_id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.clear().await;
+    Ok(Json(ApiResponse::success(
+        "Permission bundle detached".to_string(),
+    )))
+}
+
+/// List the users a permission bundle is attached to (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/permission-bundles/{id}/users",
+    tag = "Roles",
+    summary = "List bundle users (Admin)",
+    description = "Users holding the bundle directly; members of its roles are listed on the roles",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID")
+    ),
+    responses(
+        (status = 200, description = "Users holding the bundle directly", body = ApiResponse<Vec<PermissionBundleUser>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Bundle not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_bundle_users(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<PermissionBundleUser>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let users = bundles::list_users(conn.as_mut(), id, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(users)))
+}
+
+/// Attach a permission bundle to a user (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/permission-bundles/{id}/users/{user_id}",
+    tag = "Roles",
+    summary = "Attach bundle to user (Admin)",
+    description = "Grant the bundle's permissions to one user on top of their roles. Attaching a bundle the user already holds changes nothing",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID"),
+        ("user_id" = Uuid, Path, description = "User ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Bundle attached", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Bundle or user not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn attach_bundle_to_user(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), user_id, auth_user.tenant_id).await?;
+    if let Some(name) =
+        bundles::attach_to_user(conn.as_mut(), id, user_id, auth_user.id, query.reason).await?
+    {
+        app_state.permission_cache.invalidate(user_id).await;
+        activity::record_activity(
+            conn.as_mut(),
+            user_id,
+            Some(auth_user.id),
+            UserActivityAction::RoleChanged,
+            json!({ "permission_bundle": name, "assigned": true }),
+        )
+        .await;
+    }
+    Ok(Json(ApiResponse::success(
+        "Permission bundle attached".to_string(),
+    )))
+}
+
+/// Detach a permission bundle from a user (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/permission-bundles/{id}/users/{user_id}",
+    tag = "Roles",
+    summary = "Detach bundle from user (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Bundle ID"),
+        ("user_id" = Uuid, Path, description = "User ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Bundle detached", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "The bundle isn't attached to the user", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn detach_bundle_from_user(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), user_id, auth_user.tenant_id).await?;
+    let name =
+        bundles::detach_from_user(conn.as_mut(), id, user_id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.invalidate(user_id).await;
+    activity::record_activity(
+        conn.as_mut(),
+        user_id,
+        Some(auth_user.id),
+        UserActivityAction::RoleChanged,
+        json!({ "permission_bundle": name, "assigned": false }),
+    )
+    .await;
+    Ok(Json(ApiResponse::success(
+        "Permission bundle detached".to_string(),
+    )))
+}
+
+/// Permission bundle administration (admin role required)
+pub fn bundles_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_bundles).post(create_bundle))
+        .route(
+            "/{id}",
+            get(get_bundle).put(update_bundle).delete(delete_bundle),
+        )
+        .route(
+            "/{id}/roles/{role_id}",
+            put(attach_bundle_to_role).delete(detach_bundle_from_role),
+        )
+        .route("/{id}/users", get(list_bundle_users))
+        .route(
+            "/{id}/users/{user_id}",
+            put(attach_bundle_to_user).delete(detach_bundle_from_user),
+        )
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RoleRequestQuery {
+    /// Only requests with this status, e.g. `pending`
+    pub status: Option<RoleRequestStatus>,
+}
+
+/// List role requests (Moderator/Admin)
+#[utoipa::path(
+    get,
+    path = "/role-requests",
+    tag = "Roles",
+    summary = "List role requests (Moderator/Admin)",
+    description = "Requests to raise users to the moderator or admin role, newest first",
+    params(RoleRequestQuery),
+    responses(
+        (status = 200, description = "Role requests", body = ApiResponse<Vec<RoleRequest>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Moderator role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_role_requests(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RoleRequestQuery>,
+) -> Result<Json<ApiResponse<Vec<RoleRequest>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let requests =
+        role_requests::list_requests(conn.as_mut(), auth_user.tenant_id, query.status).await?;
+    Ok(Json(ApiResponse::success(requests)))
+}
+
+/// Request a higher role for a user (Moderator/Admin)
+#[utoipa::path(
+    post,
+    path = "/role-requests",
+    tag = "Roles",
+    summary = "Request role escalation (Moderator/Admin)",
+    description = "Ask for a user to be given the moderator or admin role. Nothing changes until an admin other than the requester and the user approves the request",
+    request_body = CreateRoleRequest,
+    responses(
+        (status = 200, description = "Request filed", body = ApiResponse<RoleRequest>),
+        (status = 400, description = "The user already has the role, or the expiry has passed", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Moderator role required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "The user already has a pending request", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_role_request(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateRoleRequest>,
+) -> Result<Json<ApiResponse<RoleRequest>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), request.user_id, auth_user.tenant_id)
+        .await?;
+    let request = role_requests::create_request(conn.as_mut(), request, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(request)))
+}
+
+/// Approve a role request (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/role-requests/{id}/approve",
+    tag = "Roles",
+    summary = "Approve role request (Admin)",
+    description = "Grant the requested role. The approving admin must be someone other than the requester and the user being promoted",
+    params(
+        ("id" = Uuid, Path, description = "Role request ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Request approved and role granted", body = ApiResponse<RoleRequest>),
+        (status = 400, description = "The requested expiry has passed", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required, or the admin requested the change or is its subject", body = ErrorResponse),
+        (status = 404, description = "Role request not found", body = ErrorResponse),
+        (status = 409, description = "The request was already decided", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn approve_role_request(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<RoleRequest>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let before = role_requests::find_request(conn.as_mut(), id).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), before.user_id, auth_user.tenant_id)
+        .await
+        .map_err(|_| Error::NotFound("Role request not found".to_string()))?;
+    let request =
+        role_requests::approve_request(conn.as_mut(), id, auth_user.id, query.reason).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        request.user_id,
+        Some(auth_user.id),
+        UserActivityAction::RoleChanged,
+        json!({
+            "from": before.current_role,
+            "to": request.role,
+            "reason": request.reason,
+            "expires_at": request.expires_at,
+            "role_request_id": request.id,
+        }),
+    )
+    .await;
+    Ok(Json(ApiResponse::success(request)))
+}
+
+/// Reject a role request (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/role-requests/{id}/reject",
+    tag = "Roles",
+    summary = "Reject role request (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Role request ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Request rejected", body = ApiResponse<RoleRequest>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Role request not found", body = ErrorResponse),
+        (status = 409, description = "The request was already decided", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reject_role_request(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<RoleRequest>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let pending = role_requests::find_request(conn.as_mut(), id).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), pending.user_id, auth_user.tenant_id)
+        .await
+        .map_err(|_| Error::NotFound("Role request not found".to_string()))?;
+    let request =
+        role_requests::reject_request(conn.as_mut(), id, auth_user.id, query.reason).await?;
+    Ok(Json(ApiResponse::success(request)))
+}
+
+/// Filing and listing role requests (moderator role or higher required)
+pub fn role_requests_moderator_routes() -> Router<AppState> {
+    Router::new().route("/", get(list_role_requests).post(create_role_request))
+}
+
+/// Deciding on role requests (admin role required)
+pub fn role_requests_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/{id}/approve", post(approve_role_request))
+        .route("/{id}/reject", post(reject_role_request))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RbacAuditQuery {
+    /// Only entries with this action, e.g. `role_assigned`
+    pub action: Option<String>,
+    /// Only changes made by this user
+    pub actor_id: Option<Uuid>,
+    /// Only changes to this user, role, share or bundle
+    pub target_id: Option<Uuid>,
+    /// Maximum number of entries to return (default 50, max 100)
+    pub limit: Option<i64>,
+    /// Number of entries to skip
+    pub offset: Option<i64>,
+}
+
+/// List the RBAC audit log (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/audit/rbac",
+    tag = "Roles",
+    summary = "List RBAC audit log (Admin)",
+    description = "Built-in role changes and role requests, custom role and permission bundle edits and assignments, organization membership changes and shares, with who made them, the state before and after, and the reason given, newest first",
+    params(RbacAuditQuery),
+    responses(
+        (status = 200, description = "Audit log entries", body = ApiResponse<Vec<RbacAuditEntry>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_rbac_audit(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<RbacAuditQuery>,
+) -> Result<Json<ApiResponse<Vec<RbacAuditEntry>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let entries = audit::find_entries(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        query.action.as_deref(),
+        query.actor_id,
+        query.target_id,
+        query.limit,
+        query.offset,
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+/// Audit logs (admin role required)
+pub fn audit_admin_routes() -> Router<AppState> {
+    Router::new().route("/rbac", get(list_rbac_audit))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PermissionDenyQuery {
+    /// Only denies of this user
+    pub user_id: Option<Uuid>,
+}
+
+/// List permission denies (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/permission-denies",
+    tag = "Roles",
+    summary = "List permission denies (Admin)",
+    description = "Permissions blocked for individual users, newest first",
+    params(PermissionDenyQuery),
+    responses(
+        (status = 200, description = "Denies", body = ApiResponse<Vec<PermissionDeny>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_permission_denies(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<PermissionDenyQuery>,
+) -> Result<Json<ApiResponse<Vec<PermissionDeny>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let denies = denies::list_denies(conn.as_mut(), auth_user.tenant_id, query.user_id).await?;
+    Ok(Json(ApiResponse::success(denies)))
+}
+
+/// Deny a permission to a user (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/permission-denies",
+    tag = "Roles",
+    summary = "Deny permission (Admin)",
+    description = "Block a `resource:permission`, such as `monitoring:write`, for one user. A deny overrides whatever the user's built-in and custom roles allow",
+    request_body = CreatePermissionDenyRequest,
+    responses(
+        (status = 200, description = "Permission denied", body = ApiResponse<PermissionDeny>),
+        (status = 400, description = "Invalid permission", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "The permission is already denied to the user", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_permission_deny(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreatePermissionDenyRequest>,
+) -> Result<Json<ApiResponse<PermissionDeny>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), request.user_id, auth_user.tenant_id)
+        .await?;
+    let deny = denies::create_deny(conn.as_mut(), request, auth_user.id).await?;
+    app_state.permission_cache.invalidate(deny.user_id).await;
+    Ok(Json(ApiResponse::success(deny)))
+}
+
+/// Lift a permission deny (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/permission-denies/{id}",
+    tag = "Roles",
+    summary = "Remove permission deny (Admin)",
+    params(
+        ("id" = Uuid, Path, description = "Deny ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Deny removed", body = ApiResponse<PermissionDeny>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Deny not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_permission_deny(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<PermissionDeny>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let existing = denies::find_deny(conn.as_mut(), id).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), existing.user_id, auth_user.tenant_id)
+        .await
+        .map_err(|_| Error::NotFound("Deny not found".to_string()))?;
+    let deny = denies::delete_deny(conn.as_mut(), id, auth_user.id, query.reason).await?;
+    app_state.permission_cache.invalidate(deny.user_id).await;
+    Ok(Json(ApiResponse::success(deny)))
+}
+
+/// Permission denies (admin role required)
+pub fn denies_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(list_permission_denies).post(create_permission_deny),
+        )
+        .route("/{id}", delete(delete_permission_deny))
+}
+
+/// Export the RBAC policy as YAML (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/rbac/policy",
+    tag = "Roles",
+    summary = "Export RBAC policy (Admin)",
+    description = "Custom roles with their members, permission denies and shares as one YAML document, sorted so it can be versioned in git",
+    responses(
+        (status = 200, description = "RBAC policy", content_type = "application/yaml", body = RbacPolicy),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn export_rbac_policy(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Response, Error> {
+    tenant_services::require_default_tenant(&auth_user, "RBAC policies are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let yaml = policy::to_yaml(&policy::export_policy(conn.as_mut()).await?)?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/yaml; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"rbac-policy.yaml\"",
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(yaml.into())
+        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ImportPolicyQuery {
+    /// Only report the changes the import would make
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Recorded in the RBAC audit log for every change
+    pub reason: Option<String>,
+}
+
+/// Import an RBAC policy from YAML (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/rbac/policy",
+    tag = "Roles",
+    summary = "Import RBAC policy (Admin)",
+    description = "Make custom roles, their members, permission denies and shares match a YAML policy as exported, removing anything missing from it. Users are matched by username and must exist. With `dry_run` nothing is changed and the changes are only listed",
+    params(ImportPolicyQuery),
+    request_body(content = RbacPolicy, content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Changes made, or that would be made on a dry run", body = ApiResponse<RbacPolicyImport>),
+        (status = 400, description = "Invalid policy or unknown user", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn import_rbac_policy(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ImportPolicyQuery>,
+    body: String,
+) -> Result<Json<ApiResponse<RbacPolicyImport>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "RBAC policies are managed")?;
+    let rbac_policy = policy::from_yaml(&body)?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let changes = policy::import_policy(
+        conn.as_mut(),
+        rbac_policy,
+        Some(auth_user.id),
+        query.reason,
+        query.dry_run,
+    )
+    .await?;
+    if !query.dry_run && !changes.is_empty() {
+        app_state.permission_cache.clear().await;
+    }
+    Ok(Json(ApiResponse::success(RbacPolicyImport {
+        dry_run: query.dry_run,
+        changes,
+    })))
+}
+
+/// RBAC policy export and import (admin role required)
+pub fn rbac_policy_admin_routes() -> Router<AppState> {
+    Router::new().route("/policy", get(export_rbac_policy).post(import_rbac_policy))
+}
+
+/// The object whose shares to list
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ResourceSharesQuery {
+    /// Kind of object, e.g. `tasks`
+    pub resource_type: String,
+    pub resource_id: Uuid,
+}
+
+/// List the shares of an object
+#[utoipa::path(
+    get,
+    path = "/shares",
+    tag = "Sharing",
+    summary = "List object shares",
+    description = "Users and custom roles an object is shared with; available to those who can share it, such as a task's owner",
+    params(ResourceSharesQuery),
+    responses(
+        (status = 200, description = "Shares of the object", body = ApiResponse<Vec<ResourceShare>>),
+        (status = 400, description = "Objects of this type can't be shared", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Object not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_shares(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(query): Query<ResourceSharesQuery>,
+) -> Result<Json<ApiResponse<Vec<ResourceShare>>>, Error> {
+    let resource = parse_shareable_resource(&query.resource_type)?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    sharing::require_share_manager(conn.as_mut(), &auth_user, resource, query.resource_id).await?;
+    let shares = sharing::list_shares(conn.as_mut(), resource, query.resource_id).await?;
+    Ok(Json(ApiResponse::success(shares)))
+}
+
+/// Share an object with a user or a custom role
+#[utoipa::path(
+    post,
+    path = "/shares",
+    tag = "Sharing",
+    summary = "Share object",
+    description = "Give a user, or every member of a custom role, access to one object. Any share allows reading; `write` and `delete` also allow that action",
+    request_body = ShareResourceRequest,
+    responses(
+        (status = 200, description = "Object shared", body = ApiResponse<ResourceShare>),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not allowed to share this object", body = ErrorResponse),
+        (status = 404, description = "Object, user or role not found", body = ErrorResponse),
+        (status = 409, description = "Already shared with this permission", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn share_resource(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<ShareResourceRequest>,
+) -> Result<Json<ApiResponse<ResourceShare>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let share = sharing::share_resource(conn.as_mut(), &auth_user, request).await?;
+    Ok(Json(ApiResponse::success(share)))
+}
+
+/// Remove a share
+#[utoipa::path(
+    delete,
+    path = "/shares/{id}",
+    tag = "Sharing",
+    summary = "Unshare object",
+    params(
+        ("id" = Uuid, Path, description = "Share ID"),
+        AuditReasonQuery
+    ),
+    responses(
+        (status = 200, description = "Share removed", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Not allowed to change the shares of this object", body = ErrorResponse),
+        (status = 404, description = "Share not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn unshare_resource(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AuditReasonQuery>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    sharing::unshare_resource(conn.as_mut(), &auth_user, id, query.reason).await?;
+    Ok(Json(ApiResponse::success("Share removed".to_string())))
+}
+
+/// List objects shared with the current user
+#[utoipa::path(
+    get,
+    path = "/shares/received",
+    tag = "Sharing",
+    summary = "List shares received",
+    description = "Objects shared with the current user, directly or through their custom roles, newest first",
+    responses(
+        (status = 200, description = "Shares giving the user access", body = ApiResponse<Vec<ResourceShare>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_received_shares(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ResourceShare>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let shares = sharing::shared_with(conn.as_mut(), auth_user.id).await?;
+    Ok(Json(ApiResponse::success(shares)))
+}
+
+/// Per-object sharing (authentication required)
+pub fn shares_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_shares).post(share_resource))
+        .route("/received", get(list_received_shares))
+        .route("/{id}", delete(unshare_resource))
+}
@@ -0,0 +1,124 @@
+//! Append-only log of RBAC changes
+//!
+//! Built-in role changes, custom role edits and assignments, organization
+//! membership changes and shares are recorded with who made them, the state
+//! before and after, and an optional reason. Entries are written in the same
+//! transaction as the change, and the database rejects edits and deletes.
+//! Every change is also written to the application-wide [`crate::audit`] log.
+
+use crate::audit::{self, Actor, AuditResource, AuditResourceType};
+use crate::rbac::models::{RbacAuditAction, RbacAuditEntry};
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::{DbConn, Error, Result};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// One change to record
+#[derive(Debug)]
+pub struct RbacChange {
+    /// `None` for changes made by an identity provider, a background job or
+    /// an RBAC policy import from the CLI
+    pub actor_id: Option<Uuid>,
+    pub action: RbacAuditAction,
+    pub target_id: Uuid,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+}
+
+/// Append a change to the log
+///
+/// Unlike the account activity trail, a failure is returned so the change
+/// itself is rolled back. The entry belongs to the actor's tenant, or without
+/// an actor to the target user's, and otherwise to the default tenant.
+pub async fn record_change(conn: &mut DbConn, change: RbacChange) -> Result<()> {
+    let reason = change
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+
+    let tenant_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO rbac_audit
+            (tenant_id, actor_id, action, target_type, target_id, before, after, reason)
+        VALUES (
+            COALESCE(
+                (SELECT tenant_id FROM users WHERE id = $1),
+                (SELECT tenant_id FROM users WHERE id = $4 AND $3 = 'user'),
+                $8
+            ),
+            $1, $2, $3, $4, $5, $6, $7
+        )
+        RETURNING tenant_id
+        "#,
+        change.actor_id,
+        change.action.as_str(),
+        change.action.target_type(),
+        change.target_id,
+        change.before,
+        change.after,
+        reason,
+        DEFAULT_TENANT_ID
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let resource_type = match change.action.target_type() {
+        "role" => AuditResourceType::Role,
+        "bundle" => AuditResourceType::Bundle,
+        "share" => AuditResourceType::Share,
+        _ => AuditResourceType::User,
+    };
+    let actor = Actor {
+        user_id: change.actor_id,
+        tenant_id: Some(tenant_id),
+        reason,
+    };
+    audit::try_record(
+        conn,
+        &actor,
+        change.action.as_str(),
+        &AuditResource::new(resource_type, change.target_id),
+        change.before,
+        change.after,
+    )
+    .await
+}
+
+/// A tenant's log entries matching every given filter, newest first
+pub async fn find_entries(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    action: Option<&str>,
+    actor_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<RbacAuditEntry>> {
+    let limit = limit.unwrap_or(50).min(100);
+    let offset = offset.unwrap_or(0);
+
+    sqlx::query_as!(
+        RbacAuditEntry,
+        r#"
+        SELECT id, actor_id, action, target_type, target_id, before, after, reason, created_at
+        FROM rbac_audit
+        WHERE tenant_id = $6
+          AND ($1::text IS NULL OR action = $1)
+          AND ($2::uuid IS NULL OR actor_id = $2)
+          AND ($3::uuid IS NULL OR target_id = $3)
+        ORDER BY created_at DESC, id
+        LIMIT $4 OFFSET $5
+        "#,
+        action,
+        actor_id,
+        target_id,
+        limit,
+        offset,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
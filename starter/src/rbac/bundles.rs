@@ -0,0 +1,450 @@
+//! Permission bundles defined by admins
+//!
+//! A bundle is a named set of `resource:permission` entries, such as a
+//! `monitoring-readonly` bundle holding `monitoring:read`, so permissions that
+//! go together are managed in one place. A bundle attached to a custom role
+//! grants its permissions to the role's members; one attached to a user
+//! grants them to that user alone. Denies still override what a bundle
+//! grants. Every change is recorded in the [audit log](crate::rbac::audit).
+
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{
+    CreatePermissionBundleRequest, PermissionBundle, PermissionBundleUser, RbacAuditAction,
+    UpdatePermissionBundleRequest,
+};
+use crate::rbac::roles;
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Permissions the user's bundles grant, directly or through their custom
+/// roles, without duplicates
+pub async fn granted_permissions(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT permission AS "permission!"
+        FROM permission_bundles b
+        CROSS JOIN LATERAL UNNEST(b.permissions) AS permission
+        WHERE b.id IN (
+            SELECT ub.bundle_id FROM user_bundles ub WHERE ub.user_id = $1
+            UNION
+            SELECT rb.bundle_id FROM role_bundles rb
+            JOIN user_roles ur ON ur.role_id = rb.role_id
+            WHERE ur.user_id = $1
+        )
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Every permission bundle, with the tenant's users holding it directly counted
+pub async fn list_bundles(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<PermissionBundle>> {
+    sqlx::query_as!(
+        PermissionBundle,
+        r#"
+        SELECT b.id, b.name, b.description, b.permissions, b.created_by, b.created_at,
+               b.updated_at,
+               ARRAY(
+                   SELECT r.name FROM role_bundles rb
+                   JOIN roles r ON r.id = rb.role_id
+                   WHERE rb.bundle_id = b.id
+                   ORDER BY r.name
+               ) AS "roles!",
+               (SELECT COUNT(*) FROM user_bundles ub
+                JOIN users u ON u.id = ub.user_id
+                WHERE ub.bundle_id = b.id AND u.tenant_id = $1) AS "user_count!"
+        FROM permission_bundles b
+        ORDER BY b.name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+pub async fn find_bundle(conn: &mut DbConn, bundle_id: Uuid) -> Result<PermissionBundle> {
+    find_bundle_in_tenant(conn, bundle_id, None).await
+}
+
+/// A bundle with its users in the tenant counted, or all of them without one
+pub async fn find_bundle_in_tenant(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    tenant_id: Option<Uuid>,
+) -> Result<PermissionBundle> {
+    sqlx::query_as!(
+        PermissionBundle,
+        r#"
+        SELECT b.id, b.name, b.description, b.permissions, b.created_by, b.created_at,
+               b.updated_at,
+               ARRAY(
+                   SELECT r.name FROM role_bundles rb
+                   JOIN roles r ON r.id = rb.role_id
+                   WHERE rb.bundle_id = b.id
+                   ORDER BY r.name
+               ) AS "roles!",
+               (SELECT COUNT(*) FROM user_bundles ub
+                JOIN users u ON u.id = ub.user_id
+                WHERE ub.bundle_id = b.id
+                  AND ($2::UUID IS NULL OR u.tenant_id = $2)) AS "user_count!"
+        FROM permission_bundles b
+        WHERE b.id = $1
+        "#,
+        bundle_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Permission bundle not found".to_string()))
+}
+
+pub async fn create_bundle(
+    conn: &mut DbConn,
+    mut request: CreatePermissionBundleRequest,
+    created_by: Uuid,
+) -> Result<PermissionBundle> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let bundle_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO permission_bundles (name, description, permissions, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        request.name,
+        request.description,
+        &request.permissions,
+        created_by
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::conflict(&format!(
+            "A permission bundle named {} already exists",
+            request.name
+        )),
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let bundle = find_bundle(&mut tx, bundle_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(created_by),
+            action: RbacAuditAction::BundleCreated,
+            target_id: bundle_id,
+            before: None,
+            after: Some(json!(bundle)),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(bundle)
+}
+
+/// Change a bundle; everyone holding it gets the new permissions on their next request
+pub async fn update_bundle(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    mut request: UpdatePermissionBundleRequest,
+    actor_id: Uuid,
+) -> Result<PermissionBundle> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let before = find_bundle(&mut tx, bundle_id).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE permission_bundles
+        SET description = COALESCE($2, description),
+            permissions = COALESCE($3, permissions),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        bundle_id,
+        request.description,
+        request.permissions.as_deref()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let bundle = find_bundle(&mut tx, bundle_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::BundleUpdated,
+            target_id: bundle_id,
+            before: Some(json!(before)),
+            after: Some(json!(bundle)),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(bundle)
+}
+
+/// Delete a bundle, detaching it from every role and user
+pub async fn delete_bundle(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let bundle = find_bundle(&mut tx, bundle_id).await?;
+
+    sqlx::query!("DELETE FROM permission_bundles WHERE id = $1", bundle_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::BundleDeleted,
+            target_id: bundle_id,
+            before: Some(json!(bundle)),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Attach a bundle to a custom role; returns `false` if it already was
+pub async fn attach_to_role(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    role_id: Uuid,
+    attached_by: Uuid,
+    reason: Option<String>,
+) -> Result<bool> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let bundle = find_bundle(&mut tx, bundle_id).await?;
+    let role = roles::find_role(&mut tx, role_id).await?;
+
+    let attached = sqlx::query!(
+        r#"
+        INSERT INTO role_bundles (role_id, bundle_id, attached_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (role_id, bundle_id) DO NOTHING
+        "#,
+        role_id,
+        bundle_id,
+        attached_by
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if attached == 0 {
+        return Ok(false);
+    }
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(attached_by),
+            action: RbacAuditAction::BundleAttached,
+            target_id: role_id,
+            before: None,
+            after: Some(json!({
+                "role_name": role.name,
+                "bundle_id": bundle_id,
+                "bundle_name": bundle.name,
+            })),
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(true)
+}
+
+/// Detach a bundle from a custom role
+pub async fn detach_from_role(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    role_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let names = sqlx::query!(
+        r#"
+        DELETE FROM role_bundles rb
+        USING permission_bundles b, roles r
+        WHERE rb.bundle_id = b.id AND rb.role_id = r.id
+          AND rb.bundle_id = $1 AND rb.role_id = $2
+        RETURNING b.name AS bundle_name, r.name AS role_name
+        "#,
+        bundle_id,
+        role_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("The bundle isn't attached to this role".to_string()))?;
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::BundleDetached,
+            target_id: role_id,
+            before: Some(json!({
+                "role_name": names.role_name,
+                "bundle_id": bundle_id,
+                "bundle_name": names.bundle_name,
+            })),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// The tenant's users the bundle is attached to directly, by username
+pub async fn list_users(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Vec<PermissionBundleUser>> {
+    find_bundle(conn, bundle_id).await?;
+
+    sqlx::query_as!(
+        PermissionBundleUser,
+        r#"
+        SELECT u.id AS user_id, u.username, u.email, ub.attached_by, ub.attached_at
+        FROM user_bundles ub
+        JOIN users u ON u.id = ub.user_id
+        WHERE ub.bundle_id = $1 AND u.tenant_id = $2
+        ORDER BY u.username
+        "#,
+        bundle_id,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Attach a bundle to a user; returns the bundle's name, or `None` if it already was
+pub async fn attach_to_user(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    user_id: Uuid,
+    attached_by: Uuid,
+    reason: Option<String>,
+) -> Result<Option<String>> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let bundle = find_bundle(&mut tx, bundle_id).await?;
+    let user_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !user_exists {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    let attached = sqlx::query!(
+        r#"
+        INSERT INTO user_bundles (user_id, bundle_id, attached_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, bundle_id) DO NOTHING
+        "#,
+        user_id,
+        bundle_id,
+        attached_by
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if attached == 0 {
+        return Ok(None);
+    }
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(attached_by),
+            action: RbacAuditAction::BundleAssigned,
+            target_id: user_id,
+            before: None,
+            after: Some(json!({ "bundle_id": bundle_id, "bundle_name": bundle.name })),
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(Some(bundle.name))
+}
+
+/// Detach a bundle from a user; returns the bundle's name
+pub async fn detach_from_user(
+    conn: &mut DbConn,
+    bundle_id: Uuid,
+    user_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<String> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let name = sqlx::query_scalar!(
+        r#"
+        DELETE FROM user_bundles ub
+        USING permission_bundles b
+        WHERE ub.bundle_id = b.id AND ub.bundle_id = $1 AND ub.user_id = $2
+        RETURNING b.name
+        "#,
+        bundle_id,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("The bundle isn't attached to this user".to_string()))?;
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::BundleUnassigned,
+            target_id: user_id,
+            before: Some(json!({ "bundle_id": bundle_id, "bundle_name": name })),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(name)
+}
@@ -0,0 +1,162 @@
+//! Per-user cache of the permissions granted by custom roles and bundles and of denies
+//!
+//! Every authenticated request needs the user's custom role and bundle
+//! permissions and denies, so they are kept in the [shared cache](crate::core::cache)
+//! for `auth.permission_cache_ttl_secs`. Role, bundle and deny changes
+//! invalidate the affected users right away; with the in-memory backend, changes
+//! made on another server show up once the entries expire.
+
+use crate::core::cache::AppCache;
+use crate::rbac::{bundles, denies, roles};
+use crate::{DbConn, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Cache namespace of the entries, keyed by user ID
+pub const NAMESPACE: &str = "permissions";
+
+/// A user's permissions beyond their built-in role, as `resource:permission`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserPermissions {
+    /// Granted by custom roles and permission bundles, sorted
+    pub granted: Vec<String>,
+    /// Denied to the user, overriding any grant
+    pub denied: Vec<String>,
+}
+
+impl UserPermissions {
+    async fn load(conn: &mut DbConn, user_id: Uuid) -> Result<Self> {
+        let mut granted = roles::granted_permissions(conn, user_id).await?;
+        granted.extend(bundles::granted_permissions(conn, user_id).await?);
+        granted.sort();
+        granted.dedup();
+        Ok(Self {
+            granted,
+            denied: denies::denied_permissions(conn, user_id).await?,
+        })
+    }
+}
+
+/// Cached permissions by user
+pub struct PermissionCache {
+    cache: Arc<AppCache>,
+    ttl: Duration,
+    /// Bumped on every invalidation, so a load that raced with one is not kept
+    generation: AtomicU64,
+}
+
+impl PermissionCache {
+    /// Cache keeping entries in `cache` for `ttl`; a zero TTL disables caching
+    pub fn new(cache: Arc<AppCache>, ttl: Duration) -> Self {
+        Self {
+            cache,
+            ttl,
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    /// Granted and denied permissions of a user, from the cache or the database
+    pub async fn user_permissions(
+        &self,
+        conn: &mut DbConn,
+        user_id: Uuid,
+    ) -> Result<UserPermissions> {
+        if self.ttl.is_zero() {
+            return UserPermissions::load(conn, user_id).await;
+        }
+        if let Some(permissions) = self.cache.get(NAMESPACE, &user_id.to_string()).await {
+            return Ok(permissions);
+        }
+        let generation = self.generation.load(Ordering::SeqCst);
+        let permissions = UserPermissions::load(conn, user_id).await?;
+        self.insert(user_id, &permissions, generation).await;
+        Ok(permissions)
+    }
+
+    /// Keep permissions loaded at `generation`, unless an invalidation happened since
+    async fn insert(&self, user_id: Uuid, permissions: &UserPermissions, generation: u64) {
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let key = user_id.to_string();
+        self.cache.set(NAMESPACE, &key, permissions, self.ttl).await;
+        // An invalidation that finished while the entry was stored may have missed it
+        if self.generation.load(Ordering::SeqCst) != generation {
+            self.cache.delete(NAMESPACE, &key).await;
+        }
+    }
+
+    /// Forget a user's permissions, after their roles, bundles or denies changed or they logged out
+    pub async fn invalidate(&self, user_id: Uuid) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.cache.delete(NAMESPACE, &user_id.to_string()).await;
+    }
+
+    /// Forget every user's permissions, after a role or bundle itself changed
+    pub async fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.cache.clear(NAMESPACE).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cache::CacheCounts;
+
+    fn permissions() -> UserPermissions {
+        UserPermissions {
+            granted: vec!["users:read".to_string()],
+            denied: vec!["monitoring:write".to_string()],
+        }
+    }
+
+    async fn cached(cache: &PermissionCache, user_id: Uuid) -> Option<UserPermissions> {
+        cache.cache.get(NAMESPACE, &user_id.to_string()).await
+    }
+
+    #[tokio::test]
+    async fn test_cached_until_invalidated() {
+        let app_cache = Arc::new(AppCache::default());
+        let cache = PermissionCache::new(app_cache.clone(), Duration::from_secs(60));
+        let user_id = Uuid::new_v4();
+
+        assert_eq!(cached(&cache, user_id).await, None);
+        cache.insert(user_id, &permissions(), 0).await;
+        assert_eq!(cached(&cache, user_id).await, Some(permissions()));
+        assert_eq!(
+            app_cache.take_counts()[NAMESPACE],
+            CacheCounts { hits: 1, misses: 1 }
+        );
+
+        cache.invalidate(user_id).await;
+        assert_eq!(cached(&cache, user_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire() {
+        let cache = PermissionCache::new(Arc::new(AppCache::default()), Duration::from_millis(10));
+        let user_id = Uuid::new_v4();
+
+        cache.insert(user_id, &permissions(), 0).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(cached(&cache, user_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_load_racing_an_invalidation_is_dropped() {
+        let cache = PermissionCache::new(Arc::new(AppCache::default()), Duration::from_secs(60));
+        let user_id = Uuid::new_v4();
+
+        // Loaded before the role change, stored after it
+        cache.clear().await;
+        cache.insert(user_id, &permissions(), 0).await;
+        assert_eq!(cached(&cache, user_id).await, None);
+
+        cache.insert(user_id, &permissions(), 1).await;
+        assert_eq!(cached(&cache, user_id).await, Some(permissions()));
+    }
+}
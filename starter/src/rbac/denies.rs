@@ -0,0 +1,155 @@
+//! Permissions blocked for one user
+//!
+//! A deny takes precedence over anything the user's built-in and custom roles
+//! allow, so an admin can cut off one user from, say, monitoring ingestion
+//! without changing any role. Denies are checked by
+//! [`check_permission`](crate::rbac::services::check_permission) and recorded
+//! in the [audit log](crate::rbac::audit).
+
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{CreatePermissionDenyRequest, PermissionDeny, RbacAuditAction};
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Permissions denied to the user, as `resource:permission`
+pub async fn denied_permissions(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT permission FROM permission_denies WHERE user_id = $1",
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+pub async fn find_deny(conn: &mut DbConn, deny_id: Uuid) -> Result<PermissionDeny> {
+    sqlx::query_as!(
+        PermissionDeny,
+        r#"
+        SELECT d.id, d.user_id, u.username, d.permission, d.reason, d.created_by, d.created_at
+        FROM permission_denies d
+        JOIN users u ON u.id = d.user_id
+        WHERE d.id = $1
+        "#,
+        deny_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Deny not found".to_string()))
+}
+
+/// Denies, of one user or of everyone in the tenant, newest first
+pub async fn list_denies(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    user_id: Option<Uuid>,
+) -> Result<Vec<PermissionDeny>> {
+    sqlx::query_as!(
+        PermissionDeny,
+        r#"
+        SELECT d.id, d.user_id, u.username, d.permission, d.reason, d.created_by, d.created_at
+        FROM permission_denies d
+        JOIN users u ON u.id = d.user_id
+        WHERE u.tenant_id = $1 AND ($2::uuid IS NULL OR d.user_id = $2)
+        ORDER BY d.created_at DESC, d.id
+        "#,
+        tenant_id,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Block a permission for a user
+pub async fn create_deny(
+    conn: &mut DbConn,
+    mut request: CreatePermissionDenyRequest,
+    actor_id: Uuid,
+) -> Result<PermissionDeny> {
+    request.validate()?;
+
+    let user_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        request.user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !user_exists {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let deny_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO permission_denies (user_id, permission, reason, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        request.user_id,
+        request.permission,
+        request.reason,
+        actor_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("This permission is already denied to the user")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let deny = find_deny(&mut tx, deny_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::PermissionDenied,
+            target_id: deny.user_id,
+            before: None,
+            after: Some(json!({ "permission": deny.permission })),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(deny)
+}
+
+/// Lift a deny, giving the user back what their roles allow
+pub async fn delete_deny(
+    conn: &mut DbConn,
+    deny_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<PermissionDeny> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let deny = find_deny(&mut tx, deny_id).await?;
+    sqlx::query!("DELETE FROM permission_denies WHERE id = $1", deny_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::PermissionDenyRemoved,
+            target_id: deny.user_id,
+            before: Some(json!({ "permission": deny.permission })),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(deny)
+}
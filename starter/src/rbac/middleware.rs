@@ -116,6 +116,11 @@ mod tests {
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
             role: role.to_string().into(),
+            permissions: Vec::new(),
+            denied: Vec::new(),
+            scopes: None,
+            org: None,
+            tenant_id: crate::tenants::DEFAULT_TENANT_ID,
         }
     }
 
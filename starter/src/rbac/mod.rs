@@ -1,8 +1,21 @@
+pub mod api;
+pub mod audit;
+pub mod bundles;
+pub mod cache;
+pub mod denies;
 pub mod middleware;
 pub mod models;
+pub mod ownership;
+pub mod policy;
+pub mod role_requests;
+pub mod roles;
+pub mod routes;
 pub mod services;
+pub mod sharing;
 
 // Re-export main types for convenience
 pub use middleware::{require_permission, require_role, require_role_or_higher};
 pub use models::{Permission, Resource, UserRole};
-pub use services::{check_permission, has_role_or_higher};
+pub use ownership::{Owned, OwnerScope, owner_scope};
+pub use routes::{PermissionRoutes, Requirement};
+pub use services::{check_permission, has_role_or_higher, require_staff_permission};
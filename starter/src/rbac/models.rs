@@ -1,7 +1,9 @@
 use crate::Error;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
+use uuid::Uuid;
 
 /// User roles with hierarchy: User < Moderator < Admin
 #[derive(
@@ -55,6 +57,7 @@ impl UserRole {
             (UserRole::Moderator, Resource::Users, Permission::Write) => true,
             (UserRole::Moderator, Resource::Users, Permission::Delete) => false, // Moderators can't delete users
             (UserRole::Moderator, Resource::Admin, _) => false,                  // No admin access
+            (UserRole::Moderator, Resource::Monitoring, _) => true,
 
             // User permissions - only their own resources
             (UserRole::User, Resource::Tasks, Permission::Read) => true, // Own tasks only (checked elsewhere)
@@ -63,6 +66,8 @@ impl UserRole {
             (UserRole::User, Resource::Users, Permission::Read) => true, // Own profile only
             (UserRole::User, Resource::Users, Permission::Write) => true, // Own profile only
             (UserRole::User, Resource::Admin, _) => false,
+            (UserRole::User, Resource::Monitoring, Permission::Read) => true,
+            (UserRole::User, Resource::Monitoring, Permission::Write) => true, // Ingestion
             (UserRole::User, _, Permission::Delete) => false, // Users can't delete others' resources
         }
     }
@@ -134,6 +139,8 @@ pub enum Resource {
     Users,
     /// Admin-only endpoints
     Admin,
+    /// Monitoring events and metrics
+    Monitoring,
 }
 
 /// Types of permissions that can be granted
@@ -147,12 +154,32 @@ pub enum Permission {
     Delete,
 }
 
+impl Resource {
+    pub const ALL: [Resource; 4] = [
+        Resource::Tasks,
+        Resource::Users,
+        Resource::Admin,
+        Resource::Monitoring,
+    ];
+
+    /// Whether organizations own items of this resource, so that in an
+    /// organization the user's org role decides what they may do with it
+    pub fn is_org_scoped(&self) -> bool {
+        matches!(self, Resource::Tasks | Resource::Monitoring)
+    }
+}
+
+impl Permission {
+    pub const ALL: [Permission; 3] = [Permission::Read, Permission::Write, Permission::Delete];
+}
+
 impl fmt::Display for Resource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Resource::Tasks => write!(f, "tasks"),
             Resource::Users => write!(f, "users"),
             Resource::Admin => write!(f, "admin"),
+            Resource::Monitoring => write!(f, "monitoring"),
         }
     }
 }
@@ -167,6 +194,840 @@ impl fmt::Display for Permission {
     }
 }
 
+impl FromStr for Resource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tasks" => Ok(Resource::Tasks),
+            "users" => Ok(Resource::Users),
+            "admin" => Ok(Resource::Admin),
+            "monitoring" => Ok(Resource::Monitoring),
+            _ => Err(Error::validation(
+                "permissions",
+                &format!("Unknown resource: {s}"),
+            )),
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Permission::Read),
+            "write" => Ok(Permission::Write),
+            "delete" => Ok(Permission::Delete),
+            _ => Err(Error::validation(
+                "permissions",
+                &format!("Unknown permission: {s}"),
+            )),
+        }
+    }
+}
+
+/// A `resource:permission` entry as stored on custom roles
+pub fn permission_key(resource: Resource, permission: Permission) -> String {
+    format!("{resource}:{permission}")
+}
+
+/// Check a custom role permission and return it in its stored form
+///
+/// Admin access only comes from the built-in admin role, so `admin:*`
+/// entries are rejected.
+pub fn parse_role_permission(entry: &str) -> Result<String, Error> {
+    let entry = entry.trim().to_lowercase();
+    let Some((resource, permission)) = entry.split_once(':') else {
+        return Err(Error::validation(
+            "permissions",
+            &format!("Expected resource:permission, got {entry}"),
+        ));
+    };
+    let resource = Resource::from_str(resource)?;
+    let permission = Permission::from_str(permission)?;
+    if resource == Resource::Admin {
+        return Err(Error::validation(
+            "permissions",
+            "Admin access can only be granted with the admin role",
+        ));
+    }
+    Ok(permission_key(resource, permission))
+}
+
+/// Check a denied permission and return it in its stored form
+///
+/// Admin access is removed by changing the built-in role, so `admin:*`
+/// entries are rejected.
+pub fn parse_denied_permission(entry: &str) -> Result<String, Error> {
+    let entry = entry.trim().to_lowercase();
+    let invalid = || {
+        Error::validation(
+            "permission",
+            &format!("Expected resource:permission, got {entry}"),
+        )
+    };
+    let (resource, permission) = entry.split_once(':').ok_or_else(invalid)?;
+    let resource = Resource::from_str(resource).map_err(|_| invalid())?;
+    let permission = Permission::from_str(permission).map_err(|_| invalid())?;
+    if resource == Resource::Admin {
+        return Err(Error::validation(
+            "permission",
+            "Admin access can only be removed by changing the built-in role",
+        ));
+    }
+    Ok(permission_key(resource, permission))
+}
+
+const BUILT_IN_ROLE_NAMES: &[&str] = &["user", "moderator", "admin"];
+
+fn validate_role_name(name: &str) -> Result<(), Error> {
+    if name.len() < 2 || name.len() > 50 {
+        return Err(Error::validation(
+            "name",
+            "Role name must be between 2 and 50 characters",
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(Error::validation(
+            "name",
+            "Role name can only contain lowercase letters, digits and underscores",
+        ));
+    }
+    if BUILT_IN_ROLE_NAMES.contains(&name) {
+        return Err(Error::validation(
+            "name",
+            &format!("{name} is a built-in role"),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate and normalize the permissions of a custom role, dropping duplicates
+fn normalize_role_permissions(permissions: &[String]) -> Result<Vec<String>, Error> {
+    let mut normalized = permissions
+        .iter()
+        .map(|entry| parse_role_permission(entry))
+        .collect::<Result<Vec<_>, _>>()?;
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
+/// Named role defined by an admin, granting its permissions to its members
+/// in addition to their built-in role
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CustomRole {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Granted permissions as `resource:permission`, e.g. `users:read`
+    pub permissions: Vec<String>,
+    /// Users holding the role
+    pub member_count: i64,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateCustomRoleRequest {
+    /// Lowercase letters, digits and underscores; not a built-in role name
+    pub name: String,
+    pub description: Option<String>,
+    /// `resource:permission` entries; resources are `tasks`, `users` and
+    /// `monitoring`, permissions `read`, `write` and `delete`
+    pub permissions: Vec<String>,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl CreateCustomRoleRequest {
+    /// Validate the request and normalize its permissions
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.name = self.name.trim().to_string();
+        validate_role_name(&self.name)?;
+        self.permissions = normalize_role_permissions(&self.permissions)?;
+        Ok(())
+    }
+}
+
+/// Fields to change on a custom role; omitted fields are kept
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateCustomRoleRequest {
+    pub description: Option<String>,
+    /// Replaces the role's permissions
+    pub permissions: Option<Vec<String>>,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl UpdateCustomRoleRequest {
+    /// Validate the request and normalize its permissions
+    pub fn validate(&mut self) -> Result<(), Error> {
+        if let Some(permissions) = &self.permissions {
+            self.permissions = Some(normalize_role_permissions(permissions)?);
+        }
+        Ok(())
+    }
+}
+
+/// User holding a custom role
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CustomRoleMember {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub assigned_by: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+fn validate_bundle_name(name: &str) -> Result<(), Error> {
+    if name.len() < 2 || name.len() > 50 {
+        return Err(Error::validation(
+            "name",
+            "Bundle name must be between 2 and 50 characters",
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    {
+        return Err(Error::validation(
+            "name",
+            "Bundle name can only contain lowercase letters, digits, hyphens and underscores",
+        ));
+    }
+    Ok(())
+}
+
+/// Named set of permissions, granted to the members of the custom roles it
+/// is attached to and to the users it is attached to directly
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PermissionBundle {
+    pub id: Uuid,
+    /// e.g. `monitoring-readonly`
+    pub name: String,
+    pub description: Option<String>,
+    /// Granted permissions as `resource:permission`, e.g. `monitoring:read`
+    pub permissions: Vec<String>,
+    /// Names of the custom roles the bundle is attached to
+    pub roles: Vec<String>,
+    /// Users the bundle is attached to directly
+    pub user_count: i64,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreatePermissionBundleRequest {
+    /// Lowercase letters, digits, hyphens and underscores
+    pub name: String,
+    pub description: Option<String>,
+    /// `resource:permission` entries, as on a custom role
+    pub permissions: Vec<String>,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl CreatePermissionBundleRequest {
+    /// Validate the request and normalize its permissions
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.name = self.name.trim().to_string();
+        validate_bundle_name(&self.name)?;
+        self.permissions = normalize_role_permissions(&self.permissions)?;
+        Ok(())
+    }
+}
+
+/// Fields to change on a permission bundle; omitted fields are kept
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdatePermissionBundleRequest {
+    pub description: Option<String>,
+    /// Replaces the bundle's permissions
+    pub permissions: Option<Vec<String>>,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl UpdatePermissionBundleRequest {
+    /// Validate the request and normalize its permissions
+    pub fn validate(&mut self) -> Result<(), Error> {
+        if let Some(permissions) = &self.permissions {
+            self.permissions = Some(normalize_role_permissions(permissions)?);
+        }
+        Ok(())
+    }
+}
+
+/// User a permission bundle is attached to directly
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PermissionBundleUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub attached_by: Option<Uuid>,
+    pub attached_at: DateTime<Utc>,
+}
+
+/// Permission blocked for one user, whatever their roles grant
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PermissionDeny {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    /// Denied permission as `resource:permission`, e.g. `monitoring:write`
+    pub permission: String,
+    pub reason: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Block a permission for one user
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreatePermissionDenyRequest {
+    pub user_id: Uuid,
+    /// `resource:permission`; resources are `tasks`, `users` and `monitoring`,
+    /// permissions `read`, `write` and `delete`
+    pub permission: String,
+    /// Recorded with the deny and in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl CreatePermissionDenyRequest {
+    /// Validate the request and normalize its permission
+    pub fn validate(&mut self) -> Result<(), Error> {
+        self.permission = parse_denied_permission(&self.permission)?;
+        self.reason = self
+            .reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|reason| !reason.is_empty())
+            .map(str::to_string);
+        Ok(())
+    }
+}
+
+/// Resources whose objects can be shared one at a time
+const SHAREABLE_RESOURCES: &[Resource] = &[Resource::Tasks];
+
+/// Access to one object, shared with a user or with the members of a custom role
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ResourceShare {
+    pub id: Uuid,
+    /// Kind of the shared object, e.g. `tasks`
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub username: Option<String>,
+    pub role_id: Option<Uuid>,
+    pub role_name: Option<String>,
+    /// `read`, `write` or `delete`; any share also allows reading
+    pub permission: String,
+    pub granted_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Share one object with a user or with a custom role
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ShareResourceRequest {
+    /// Kind of object; only `tasks` can be shared
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    /// Share with this user; give either `user_id` or `role_id`
+    pub user_id: Option<Uuid>,
+    /// Share with every member of this custom role
+    pub role_id: Option<Uuid>,
+    /// `read`, `write` or `delete`
+    pub permission: String,
+    /// Recorded in the RBAC audit log
+    pub reason: Option<String>,
+}
+
+impl ShareResourceRequest {
+    /// Validate the request and return the parsed resource and permission
+    pub fn validate(&self) -> Result<(Resource, Permission), Error> {
+        let resource = parse_shareable_resource(&self.resource_type)?;
+        let permission = Permission::from_str(&self.permission)
+            .map_err(|_| Error::validation("permission", "Expected read, write or delete"))?;
+        if self.user_id.is_some() == self.role_id.is_some() {
+            return Err(Error::validation(
+                "user_id",
+                "Give either user_id or role_id",
+            ));
+        }
+        Ok((resource, permission))
+    }
+}
+
+/// Parse the type of an object that can be shared
+pub fn parse_shareable_resource(resource_type: &str) -> Result<Resource, Error> {
+    Resource::from_str(resource_type)
+        .ok()
+        .filter(|resource| SHAREABLE_RESOURCES.contains(resource))
+        .ok_or_else(|| {
+            Error::validation(
+                "resource_type",
+                &format!("{resource_type} objects can't be shared"),
+            )
+        })
+}
+
+/// Kind of change recorded in the RBAC audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RbacAuditAction {
+    /// A user's built-in role changed
+    UserRoleChanged,
+    /// A temporary built-in role ended and the previous role was restored
+    UserRoleExpired,
+    RoleCreated,
+    RoleUpdated,
+    RoleDeleted,
+    RoleAssigned,
+    RoleUnassigned,
+    ResourceShared,
+    ResourceUnshared,
+    /// A user joined, left or changed role in an organization
+    OrgRoleChanged,
+    PermissionDenied,
+    PermissionDenyRemoved,
+    BundleCreated,
+    BundleUpdated,
+    BundleDeleted,
+    /// A bundle was attached to a custom role
+    BundleAttached,
+    /// A bundle was detached from a custom role
+    BundleDetached,
+    /// A bundle was attached to a user
+    BundleAssigned,
+    /// A bundle was detached from a user
+    BundleUnassigned,
+    /// A higher built-in role was requested for a user
+    RoleRequested,
+    RoleRequestApproved,
+    RoleRequestRejected,
+}
+
+impl RbacAuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RbacAuditAction::UserRoleChanged => "user_role_changed",
+            RbacAuditAction::UserRoleExpired => "user_role_expired",
+            RbacAuditAction::RoleCreated => "role_created",
+            RbacAuditAction::RoleUpdated => "role_updated",
+            RbacAuditAction::RoleDeleted => "role_deleted",
+            RbacAuditAction::RoleAssigned => "role_assigned",
+            RbacAuditAction::RoleUnassigned => "role_unassigned",
+            RbacAuditAction::ResourceShared => "resource_shared",
+            RbacAuditAction::ResourceUnshared => "resource_unshared",
+            RbacAuditAction::OrgRoleChanged => "org_role_changed",
+            RbacAuditAction::PermissionDenied => "permission_denied",
+            RbacAuditAction::PermissionDenyRemoved => "permission_deny_removed",
+            RbacAuditAction::BundleCreated => "bundle_created",
+            RbacAuditAction::BundleUpdated => "bundle_updated",
+            RbacAuditAction::BundleDeleted => "bundle_deleted",
+            RbacAuditAction::BundleAttached => "bundle_attached",
+            RbacAuditAction::BundleDetached => "bundle_detached",
+            RbacAuditAction::BundleAssigned => "bundle_assigned",
+            RbacAuditAction::BundleUnassigned => "bundle_unassigned",
+            RbacAuditAction::RoleRequested => "role_requested",
+            RbacAuditAction::RoleRequestApproved => "role_request_approved",
+            RbacAuditAction::RoleRequestRejected => "role_request_rejected",
+        }
+    }
+
+    /// What the entry's `target_id` refers to
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            RbacAuditAction::RoleCreated
+            | RbacAuditAction::RoleUpdated
+            | RbacAuditAction::RoleDeleted
+            | RbacAuditAction::BundleAttached
+            | RbacAuditAction::BundleDetached => "role",
+            RbacAuditAction::BundleCreated
+            | RbacAuditAction::BundleUpdated
+            | RbacAuditAction::BundleDeleted => "bundle",
+            RbacAuditAction::ResourceShared | RbacAuditAction::ResourceUnshared => "share",
+            RbacAuditAction::UserRoleChanged
+            | RbacAuditAction::UserRoleExpired
+            | RbacAuditAction::RoleAssigned
+            | RbacAuditAction::RoleUnassigned
+            | RbacAuditAction::OrgRoleChanged
+            | RbacAuditAction::PermissionDenied
+            | RbacAuditAction::PermissionDenyRemoved
+            | RbacAuditAction::BundleAssigned
+            | RbacAuditAction::BundleUnassigned
+            | RbacAuditAction::RoleRequested
+            | RbacAuditAction::RoleRequestApproved
+            | RbacAuditAction::RoleRequestRejected => "user",
+        }
+    }
+}
+
+impl fmt::Display for RbacAuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Entry in the RBAC audit log
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RbacAuditEntry {
+    pub id: Uuid,
+    /// Who made the change; `None` for an identity provider, a background
+    /// job or a CLI policy import
+    pub actor_id: Option<Uuid>,
+    /// See RbacAuditAction, e.g. `role_assigned`
+    pub action: String,
+    /// `user`, `role`, `share` or `bundle`
+    pub target_type: String,
+    pub target_id: Uuid,
+    /// State before the change; `None` when something was created or granted
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    /// State after the change; `None` when something was deleted or revoked
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a role request stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleRequestStatus {
+    /// Waiting for an admin's decision
+    Pending,
+    /// Approved, and the role was granted
+    Approved,
+    Rejected,
+}
+
+impl RoleRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoleRequestStatus::Pending => "pending",
+            RoleRequestStatus::Approved => "approved",
+            RoleRequestStatus::Rejected => "rejected",
+        }
+    }
+}
+
+impl From<String> for RoleRequestStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "approved" => RoleRequestStatus::Approved,
+            "rejected" => RoleRequestStatus::Rejected,
+            _ => RoleRequestStatus::Pending,
+        }
+    }
+}
+
+/// Request to raise a user's built-in role, applied once a second admin approves it
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RoleRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    /// Role the user holds now
+    pub current_role: UserRole,
+    /// Role requested for the user
+    pub role: UserRole,
+    /// Until when the role is granted; `None` for a permanent change
+    pub expires_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub status: RoleRequestStatus,
+    pub requested_by: Option<Uuid>,
+    /// Admin who approved or rejected the request
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Ask for a user to be given a higher built-in role
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateRoleRequest {
+    pub user_id: Uuid,
+    /// `moderator` or `admin`
+    pub role: UserRole,
+    /// Revert to the previous role at this time once approved
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Why the user needs the role, shown to the approving admin
+    pub reason: Option<String>,
+}
+
+impl CreateRoleRequest {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), Error> {
+        if self.role == UserRole::User {
+            return Err(Error::validation(
+                "role",
+                "Only moderator and admin roles need a request",
+            ));
+        }
+        match self.expires_at {
+            Some(expires_at) if expires_at <= now => {
+                Err(Error::validation("expires_at", "Must be in the future"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Permissions the current user holds right now
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct EffectivePermissions {
+    pub role: UserRole,
+    /// `resource:permission` entries from the built-in role, custom roles and
+    /// permission bundles, minus denies and limited to the session's scopes. A regular user's
+    /// `tasks` and `users` entries only cover their own tasks and profile.
+    pub permissions: Vec<String>,
+    /// Objects shared with the user, directly or through their custom roles
+    pub shared: Vec<SharedObjectPermissions>,
+    /// Whether the session is limited to scopes
+    pub scoped: bool,
+}
+
+/// Permissions the user holds on one shared object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SharedObjectPermissions {
+    /// Kind of the shared object, e.g. `tasks`
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    /// `read`, `write` and `delete` as allowed by the object's shares
+    pub permissions: Vec<String>,
+}
+
+/// Custom roles with their members, denies and shares as one document
+///
+/// Users and roles are referred to by name so a policy can be promoted from
+/// one environment to another, and every list is sorted so exports diff
+/// cleanly in git.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RbacPolicy {
+    #[serde(default)]
+    pub roles: Vec<PolicyRole>,
+    #[serde(default)]
+    pub denies: Vec<PolicyDeny>,
+    #[serde(default)]
+    pub shares: Vec<PolicyShare>,
+}
+
+/// Custom role in an RBAC policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRole {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `resource:permission` entries, as on a custom role
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Usernames of the role's members
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+/// Permission deny in an RBAC policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyDeny {
+    /// Username of the user the permission is denied to
+    pub user: String,
+    /// `resource:permission`, e.g. `monitoring:write`
+    pub permission: String,
+    /// Only set when the deny is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Object share in an RBAC policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyShare {
+    /// Kind of object; only `tasks` can be shared
+    pub resource_type: String,
+    pub resource_id: Uuid,
+    /// Username to share with; give either `user` or `role`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Custom role to share with, which must be in the policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// `read`, `write` or `delete`
+    pub permission: String,
+}
+
+impl PolicyShare {
+    /// The user or role shared with, e.g. `user alice`
+    pub fn grantee(&self) -> String {
+        match (&self.user, &self.role) {
+            (Some(user), _) => format!("user {user}"),
+            (None, Some(role)) => format!("role {role}"),
+            (None, None) => "nobody".to_string(),
+        }
+    }
+}
+
+impl RbacPolicy {
+    /// Validate the policy, normalize its entries and sort every list
+    pub fn validate(&mut self) -> Result<(), Error> {
+        for role in &mut self.roles {
+            role.name = role.name.trim().to_string();
+            validate_role_name(&role.name)?;
+            role.description = role
+                .description
+                .as_deref()
+                .map(str::trim)
+                .filter(|description| !description.is_empty())
+                .map(str::to_string);
+            role.permissions = normalize_role_permissions(&role.permissions)?;
+            role.members = normalize_usernames(&role.members);
+        }
+        self.roles.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(pair) = self
+            .roles
+            .windows(2)
+            .find(|pair| pair[0].name == pair[1].name)
+        {
+            return Err(Error::validation(
+                "roles",
+                &format!("Role {} is listed more than once", pair[0].name),
+            ));
+        }
+
+        for deny in &mut self.denies {
+            deny.user = deny.user.trim().to_string();
+            deny.permission = parse_denied_permission(&deny.permission)?;
+            deny.reason = deny
+                .reason
+                .as_deref()
+                .map(str::trim)
+                .filter(|reason| !reason.is_empty())
+                .map(str::to_string);
+        }
+        self.denies
+            .sort_by(|a, b| (&a.user, &a.permission).cmp(&(&b.user, &b.permission)));
+        if let Some(pair) = self
+            .denies
+            .windows(2)
+            .find(|pair| pair[0].user == pair[1].user && pair[0].permission == pair[1].permission)
+        {
+            return Err(Error::validation(
+                "denies",
+                &format!(
+                    "{} is denied to {} more than once",
+                    pair[0].permission, pair[0].user
+                ),
+            ));
+        }
+
+        for share in &mut self.shares {
+            share.resource_type = parse_shareable_resource(share.resource_type.trim())?.to_string();
+            share.permission = Permission::from_str(share.permission.trim())
+                .map_err(|_| Error::validation("permission", "Expected read, write or delete"))?
+                .to_string();
+            share.user = share.user.as_deref().map(|user| user.trim().to_string());
+            share.role = share.role.as_deref().map(|role| role.trim().to_string());
+            if share.user.is_some() == share.role.is_some() {
+                return Err(Error::validation(
+                    "shares",
+                    "Give either user or role on every share",
+                ));
+            }
+            if let Some(role) = &share.role
+                && !self.roles.iter().any(|r| &r.name == role)
+            {
+                return Err(Error::validation(
+                    "shares",
+                    &format!("Role {role} is shared with but not in the policy"),
+                ));
+            }
+        }
+        self.shares.sort_by(|a, b| share_key(a).cmp(&share_key(b)));
+        self.shares.dedup();
+        Ok(())
+    }
+
+    /// Usernames the policy refers to, without duplicates
+    pub fn usernames(&self) -> Vec<String> {
+        let mut usernames: Vec<String> = self
+            .roles
+            .iter()
+            .flat_map(|role| role.members.iter().cloned())
+            .chain(self.denies.iter().map(|deny| deny.user.clone()))
+            .chain(self.shares.iter().filter_map(|share| share.user.clone()))
+            .collect();
+        usernames.sort();
+        usernames.dedup();
+        usernames
+    }
+}
+
+/// Sort key of a share, grouping the shares of one object together
+pub fn share_key(share: &PolicyShare) -> (&str, Uuid, String, &str) {
+    (
+        &share.resource_type,
+        share.resource_id,
+        share.grantee(),
+        &share.permission,
+    )
+}
+
+fn normalize_usernames(usernames: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = usernames
+        .iter()
+        .map(|username| username.trim().to_string())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+/// What an RBAC policy import does to one entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyChangeOp {
+    Create,
+    Update,
+    Remove,
+}
+
+/// One change made by an RBAC policy import, or that a dry run would make
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RbacPolicyChange {
+    pub op: PolicyChangeOp,
+    /// `role`, `member`, `deny` or `share`
+    pub kind: String,
+    /// What changes, e.g. `support_agents` or `support_agents/alice`
+    pub target: String,
+}
+
+impl fmt::Display for RbacPolicyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = match self.op {
+            PolicyChangeOp::Create => "+",
+            PolicyChangeOp::Update => "~",
+            PolicyChangeOp::Remove => "-",
+        };
+        write!(f, "{sign} {} {}", self.kind, self.target)
+    }
+}
+
+/// Outcome of an RBAC policy import
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct RbacPolicyImport {
+    /// Whether the changes were only computed, not made
+    pub dry_run: bool,
+    /// Removals first, then creations and updates
+    pub changes: Vec<RbacPolicyChange>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +1073,93 @@ mod tests {
         assert_eq!(UserRole::Admin.to_string(), "admin");
     }
 
+    #[test]
+    fn test_parse_role_permission() {
+        assert_eq!(parse_role_permission(" Users:Read ").unwrap(), "users:read");
+        assert_eq!(
+            parse_role_permission("tasks:delete").unwrap(),
+            "tasks:delete"
+        );
+        assert!(parse_role_permission("users").is_err());
+        assert!(parse_role_permission("billing:read").is_err());
+        assert!(parse_role_permission("users:approve").is_err());
+        assert!(parse_role_permission("admin:read").is_err());
+    }
+
+    #[test]
+    fn test_parse_denied_permission() {
+        assert_eq!(
+            parse_denied_permission(" Monitoring:Write ").unwrap(),
+            "monitoring:write"
+        );
+        assert!(parse_denied_permission("admin:read").is_err());
+        assert!(parse_denied_permission("monitoring").is_err());
+        assert!(parse_denied_permission("billing:read").is_err());
+    }
+
+    #[test]
+    fn test_role_request_validation() {
+        let now = Utc::now();
+        let mut request = CreateRoleRequest {
+            user_id: Uuid::new_v4(),
+            role: UserRole::Admin,
+            expires_at: Some(now + chrono::Duration::hours(1)),
+            reason: None,
+        };
+        assert!(request.validate(now).is_ok());
+        request.expires_at = Some(now);
+        assert!(request.validate(now).is_err());
+        request.expires_at = None;
+        request.role = UserRole::User;
+        assert!(request.validate(now).is_err());
+    }
+
+    #[test]
+    fn test_bundle_request_validation() {
+        let mut request = CreatePermissionBundleRequest {
+            name: " monitoring-readonly ".to_string(),
+            description: None,
+            permissions: vec!["monitoring:read".to_string(), "Monitoring:Read".to_string()],
+            reason: None,
+        };
+        request.validate().unwrap();
+        assert_eq!(request.name, "monitoring-readonly");
+        assert_eq!(request.permissions, vec!["monitoring:read"]);
+
+        request.name = "Monitoring Readonly".to_string();
+        assert!(request.validate().is_err());
+        request.name = "monitoring-readonly".to_string();
+        request.permissions = vec!["monitoring".to_string()];
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_share_request_validation() {
+        let mut request = ShareResourceRequest {
+            resource_type: "tasks".to_string(),
+            resource_id: Uuid::new_v4(),
+            user_id: Some(Uuid::new_v4()),
+            role_id: None,
+            permission: "write".to_string(),
+            reason: None,
+        };
+        assert_eq!(
+            request.validate().unwrap(),
+            (Resource::Tasks, Permission::Write)
+        );
+
+        request.role_id = Some(Uuid::new_v4());
+        assert!(request.validate().is_err());
+        request.user_id = None;
+        assert!(request.validate().is_ok());
+
+        request.resource_type = "users".to_string();
+        assert!(request.validate().is_err());
+        request.resource_type = "tasks".to_string();
+        request.permission = "own".to_string();
+        assert!(request.validate().is_err());
+    }
+
     #[test]
     fn test_permissions() {
         // Admin can do everything
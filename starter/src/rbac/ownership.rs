@@ -0,0 +1,167 @@
+//! Ownership scoping for records that keep the account that created them
+//!
+//! Generated modules store the `tenant_id` and `created_by` owner of every
+//! row. Users only reach their tenant's rows; within it, regular users reach
+//! only their own rows while moderators and admins reach every row. Queries
+//! bind both from [`owner_scope`] and filter with
+//! `tenant_id = $n AND ($m::UUID IS NULL OR created_by = $m)`, so listings
+//! never return other users' rows and a single row of someone else reads as
+//! not found, which keeps IDs from being probed. Records loaded without that
+//! filter go through [`authorize_owned`] before they are returned or changed.
+
+use crate::auth::AuthUser;
+use crate::rbac::{UserRole, services as rbac_services};
+use crate::{Error, Result};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// A record owned by the account that created it
+pub trait Owned {
+    /// Account the record belongs to
+    fn owner_id(&self) -> Uuid;
+    /// Tenant the record belongs to
+    fn tenant_id(&self) -> Uuid;
+}
+
+/// Records a user's queries are limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OwnerScope {
+    /// The user's tenant; other tenants' records are never reached
+    pub tenant_id: Uuid,
+    /// Owner within the tenant; `None` when the user may reach every record of it
+    pub owner: Option<Uuid>,
+}
+
+/// Tenant and owner the user's queries are limited to
+pub fn owner_scope(user: &AuthUser) -> OwnerScope {
+    let owner = if user.role.has_role_or_higher(UserRole::Moderator) {
+        None
+    } else {
+        Some(user.id)
+    };
+    OwnerScope {
+        tenant_id: user.tenant_id,
+        owner,
+    }
+}
+
+/// Append the tenant and ownership conditions to a query that already has a WHERE clause
+pub fn push_owner_scope(query_builder: &mut QueryBuilder<'_, Postgres>, user: &AuthUser) {
+    let scope = owner_scope(user);
+    query_builder.push(" AND tenant_id = ");
+    query_builder.push_bind(scope.tenant_id);
+    if let Some(owner) = scope.owner {
+        query_builder.push(" AND created_by = ");
+        query_builder.push_bind(owner);
+    }
+}
+
+/// The record, if the user may reach it; other users' records read as not found
+pub fn authorize_owned<T: Owned>(user: &AuthUser, record: T) -> Result<T> {
+    if record.tenant_id() != user.tenant_id {
+        return Err(Error::NotFound("Resource not found".to_string()));
+    }
+    rbac_services::can_access_own_resource(user, record.owner_id())?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Note {
+        created_by: Uuid,
+        tenant_id: Uuid,
+    }
+
+    impl Owned for Note {
+        fn owner_id(&self) -> Uuid {
+            self.created_by
+        }
+
+        fn tenant_id(&self) -> Uuid {
+            self.tenant_id
+        }
+    }
+
+    fn note(owner: &AuthUser) -> Note {
+        Note {
+            created_by: owner.id,
+            tenant_id: owner.tenant_id,
+        }
+    }
+
+    fn user(role: UserRole) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            username: "owner".to_string(),
+            email: "owner@example.com".to_string(),
+            role,
+            permissions: vec![],
+            denied: vec![],
+            scopes: None,
+            org: None,
+            tenant_id: crate::tenants::DEFAULT_TENANT_ID,
+        }
+    }
+
+    #[test]
+    fn users_only_reach_their_own_records() {
+        let owner = user(UserRole::User);
+        let other = user(UserRole::User);
+        assert_eq!(
+            owner_scope(&owner),
+            OwnerScope {
+                tenant_id: owner.tenant_id,
+                owner: Some(owner.id),
+            }
+        );
+
+        assert!(authorize_owned(&owner, note(&owner)).is_ok());
+        assert!(matches!(
+            authorize_owned(&other, note(&owner)),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn moderators_reach_every_record_of_their_tenant() {
+        let owner = user(UserRole::User);
+        for role in [UserRole::Moderator, UserRole::Admin] {
+            let mut staff = user(role);
+            assert_eq!(
+                owner_scope(&staff),
+                OwnerScope {
+                    tenant_id: staff.tenant_id,
+                    owner: None,
+                }
+            );
+            assert!(authorize_owned(&staff, note(&owner)).is_ok());
+
+            // Other tenants' records read as not found
+            staff.tenant_id = Uuid::new_v4();
+            assert_eq!(owner_scope(&staff).tenant_id, staff.tenant_id);
+            assert!(matches!(
+                authorize_owned(&staff, note(&owner)),
+                Err(Error::NotFound(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn tenant_condition_is_always_added() {
+        let mut query_builder = QueryBuilder::new("SELECT id FROM notes WHERE 1=1");
+        push_owner_scope(&mut query_builder, &user(UserRole::User));
+        assert_eq!(
+            query_builder.sql(),
+            "SELECT id FROM notes WHERE 1=1 AND tenant_id = $1 AND created_by = $2"
+        );
+
+        let mut query_builder = QueryBuilder::new("SELECT id FROM notes WHERE 1=1");
+        push_owner_scope(&mut query_builder, &user(UserRole::Moderator));
+        assert_eq!(
+            query_builder.sql(),
+            "SELECT id FROM notes WHERE 1=1 AND tenant_id = $1"
+        );
+    }
+}
@@ -0,0 +1,669 @@
+//! RBAC policy export and import
+//!
+//! Custom roles with their members, denies and shares are exported as one
+//! YAML [`RbacPolicy`] that can be versioned in git and imported into another
+//! environment. An import makes the database match the policy, so entries
+//! missing from it are removed; a dry run only reports the changes. Every
+//! change is recorded in the [audit log](crate::rbac::audit). Permission
+//! bundles are managed on their own and aren't part of the policy; roles are
+//! updated in place, so their bundles stay attached.
+
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{
+    PolicyChangeOp, PolicyDeny, PolicyRole, PolicyShare, RbacAuditAction, RbacPolicy,
+    RbacPolicyChange, Resource, share_key,
+};
+use crate::rbac::{denies, roles, sharing};
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Serialize a policy as YAML
+pub fn to_yaml(policy: &RbacPolicy) -> Result<String> {
+    serde_yaml_ng::to_string(policy)
+        .map_err(|e| Error::internal(&format!("Failed to serialize RBAC policy: {e}")))
+}
+
+/// Parse a policy from YAML
+pub fn from_yaml(yaml: &str) -> Result<RbacPolicy> {
+    serde_yaml_ng::from_str(yaml)
+        .map_err(|e| Error::validation("policy", &format!("Invalid RBAC policy: {e}")))
+}
+
+/// Current custom roles, denies and shares, sorted
+pub async fn export_policy(conn: &mut DbConn) -> Result<RbacPolicy> {
+    let roles = sqlx::query_as!(
+        PolicyRole,
+        r#"
+        SELECT r.name, r.description, r.permissions,
+               ARRAY(
+                   SELECT u.username FROM user_roles ur
+                   JOIN users u ON u.id = ur.user_id
+                   WHERE ur.role_id = r.id AND u.deleted_at IS NULL
+                   ORDER BY u.username
+               ) AS "members!"
+        FROM roles r
+        ORDER BY r.name
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let denies = sqlx::query_as!(
+        PolicyDeny,
+        r#"
+        SELECT u.username AS "user", d.permission, d.reason
+        FROM permission_denies d
+        JOIN users u ON u.id = d.user_id
+        WHERE u.deleted_at IS NULL
+        ORDER BY u.username, d.permission
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut shares = sqlx::query_as!(
+        PolicyShare,
+        r#"
+        SELECT rp.resource_type, rp.resource_id, u.username AS "user?", r.name AS "role?",
+               rp.permission
+        FROM resource_permissions rp
+        LEFT JOIN users u ON u.id = rp.user_id
+        LEFT JOIN roles r ON r.id = rp.role_id
+        WHERE u.deleted_at IS NULL
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    shares.sort_by(|a, b| share_key(a).cmp(&share_key(b)));
+
+    Ok(RbacPolicy {
+        roles,
+        denies,
+        shares,
+    })
+}
+
+/// Make the database match the policy, or with `dry_run` only report what
+/// would change
+///
+/// `actor_id` is `None` for imports from the CLI.
+pub async fn import_policy(
+    conn: &mut DbConn,
+    mut policy: RbacPolicy,
+    actor_id: Option<Uuid>,
+    reason: Option<String>,
+    dry_run: bool,
+) -> Result<Vec<RbacPolicyChange>> {
+    policy.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let user_ids = find_users(&mut tx, &policy.usernames()).await?;
+    check_shared_objects(&mut tx, &policy.shares).await?;
+
+    let current = export_policy(&mut tx).await?;
+    let steps = plan(&current, &policy);
+    let changes = steps.iter().map(PolicyStep::change).collect();
+    if dry_run {
+        return Ok(changes);
+    }
+
+    for step in &steps {
+        apply(&mut tx, step, &user_ids, actor_id, reason.clone()).await?;
+    }
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(changes)
+}
+
+/// Ids of the users with these usernames, failing if any is unknown
+async fn find_users(conn: &mut DbConn, usernames: &[String]) -> Result<HashMap<String, Uuid>> {
+    let users = sqlx::query!(
+        "SELECT id, username FROM users WHERE username = ANY($1) AND deleted_at IS NULL",
+        usernames
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let user_ids: HashMap<String, Uuid> = users
+        .into_iter()
+        .map(|user| (user.username, user.id))
+        .collect();
+
+    let unknown: Vec<&str> = usernames
+        .iter()
+        .filter(|username| !user_ids.contains_key(*username))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(Error::validation(
+            "policy",
+            &format!("Unknown users: {}", unknown.join(", ")),
+        ));
+    }
+    Ok(user_ids)
+}
+
+/// Check that every shared object exists
+async fn check_shared_objects(conn: &mut DbConn, shares: &[PolicyShare]) -> Result<()> {
+    let mut task_ids: Vec<Uuid> = shares
+        .iter()
+        .filter(|share| share.resource_type == Resource::Tasks.to_string())
+        .map(|share| share.resource_id)
+        .collect();
+    task_ids.sort();
+    task_ids.dedup();
+
+    let found = sqlx::query_scalar!("SELECT id FROM tasks WHERE id = ANY($1)", &task_ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    if let Some(missing) = task_ids.iter().find(|id| !found.contains(id)) {
+        return Err(Error::validation(
+            "policy",
+            &format!("Shared task {missing} doesn't exist"),
+        ));
+    }
+    Ok(())
+}
+
+/// One change needed to go from the current policy to the imported one
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PolicyStep {
+    RemoveShare(PolicyShare),
+    RemoveDeny(PolicyDeny),
+    RemoveMember { role: String, user: String },
+    RemoveRole(String),
+    CreateRole(PolicyRole),
+    UpdateRole(PolicyRole),
+    AddMember { role: String, user: String },
+    AddDeny(PolicyDeny),
+    AddShare(PolicyShare),
+}
+
+impl PolicyStep {
+    fn change(&self) -> RbacPolicyChange {
+        let (op, kind, target) = match self {
+            PolicyStep::RemoveShare(share) => {
+                (PolicyChangeOp::Remove, "share", share_target(share))
+            }
+            PolicyStep::RemoveDeny(deny) => (PolicyChangeOp::Remove, "deny", deny_target(deny)),
+            PolicyStep::RemoveMember { role, user } => {
+                (PolicyChangeOp::Remove, "member", format!("{role}/{user}"))
+            }
+            PolicyStep::RemoveRole(name) => (PolicyChangeOp::Remove, "role", name.clone()),
+            PolicyStep::CreateRole(role) => (PolicyChangeOp::Create, "role", role.name.clone()),
+            PolicyStep::UpdateRole(role) => (PolicyChangeOp::Update, "role", role.name.clone()),
+            PolicyStep::AddMember { role, user } => {
+                (PolicyChangeOp::Create, "member", format!("{role}/{user}"))
+            }
+            PolicyStep::AddDeny(deny) => (PolicyChangeOp::Create, "deny", deny_target(deny)),
+            PolicyStep::AddShare(share) => (PolicyChangeOp::Create, "share", share_target(share)),
+        };
+        RbacPolicyChange {
+            op,
+            kind: kind.to_string(),
+            target,
+        }
+    }
+}
+
+fn deny_target(deny: &PolicyDeny) -> String {
+    format!("{} {}", deny.user, deny.permission)
+}
+
+fn share_target(share: &PolicyShare) -> String {
+    format!(
+        "{}/{} {} to {}",
+        share.resource_type,
+        share.resource_id,
+        share.permission,
+        share.grantee()
+    )
+}
+
+/// Steps from `current` to `desired`, removals first so a removed role's
+/// members and shares go before the role itself
+///
+/// A deny's reason is only set when it is created, so a changed reason is
+/// not a change.
+fn plan(current: &RbacPolicy, desired: &RbacPolicy) -> Vec<PolicyStep> {
+    let mut removals = Vec::new();
+    let mut additions = Vec::new();
+
+    for share in &current.shares {
+        if !desired.shares.contains(share) {
+            removals.push(PolicyStep::RemoveShare(share.clone()));
+        }
+    }
+    for deny in &current.denies {
+        if !desired.denies.iter().any(|d| same_deny(d, deny)) {
+            removals.push(PolicyStep::RemoveDeny(deny.clone()));
+        }
+    }
+
+    let no_members: Vec<String> = Vec::new();
+    for role in &current.roles {
+        let desired_role = desired.roles.iter().find(|r| r.name == role.name);
+        let desired_members = desired_role.map_or(&no_members, |r| &r.members);
+        for user in &role.members {
+            if !desired_members.contains(user) {
+                removals.push(PolicyStep::RemoveMember {
+                    role: role.name.clone(),
+                    user: user.clone(),
+                });
+            }
+        }
+        if desired_role.is_none() {
+            removals.push(PolicyStep::RemoveRole(role.name.clone()));
+        }
+    }
+
+    for role in &desired.roles {
+        let current_role = current.roles.iter().find(|r| r.name == role.name);
+        match current_role {
+            None => additions.push(PolicyStep::CreateRole(role.clone())),
+            Some(current_role)
+                if current_role.description != role.description
+                    || current_role.permissions != role.permissions =>
+            {
+                additions.push(PolicyStep::UpdateRole(role.clone()))
+            }
+            Some(_) => {}
+        }
+        let current_members = current_role.map_or(&no_members, |r| &r.members);
+        for user in &role.members {
+            if !current_members.contains(user) {
+                additions.push(PolicyStep::AddMember {
+                    role: role.name.clone(),
+                    user: user.clone(),
+                });
+            }
+        }
+    }
+
+    for deny in &desired.denies {
+        if !current.denies.iter().any(|d| same_deny(d, deny)) {
+            additions.push(PolicyStep::AddDeny(deny.clone()));
+        }
+    }
+    for share in &desired.shares {
+        if !current.shares.contains(share) {
+            additions.push(PolicyStep::AddShare(share.clone()));
+        }
+    }
+
+    removals.extend(additions);
+    removals
+}
+
+fn same_deny(a: &PolicyDeny, b: &PolicyDeny) -> bool {
+    a.user == b.user && a.permission == b.permission
+}
+
+async fn role_id(conn: &mut DbConn, name: &str) -> Result<Uuid> {
+    sqlx::query_scalar!("SELECT id FROM roles WHERE name = $1", name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .ok_or_else(|| Error::NotFound(format!("Role {name} not found")))
+}
+
+/// Make one change, recording it in the audit log like the matching endpoint
+async fn apply(
+    conn: &mut DbConn,
+    step: &PolicyStep,
+    user_ids: &HashMap<String, Uuid>,
+    actor_id: Option<Uuid>,
+    reason: Option<String>,
+) -> Result<()> {
+    // Removals match users by name, as they may no longer be in the policy
+    let user_id = |username: &str| {
+        user_ids
+            .get(username)
+            .copied()
+            .ok_or_else(|| Error::NotFound(format!("User {username} not found")))
+    };
+
+    let change = match step {
+        PolicyStep::RemoveShare(share) => {
+            let share_id = sqlx::query_scalar!(
+                r#"
+                SELECT rp.id
+                FROM resource_permissions rp
+                LEFT JOIN users u ON u.id = rp.user_id
+                LEFT JOIN roles r ON r.id = rp.role_id
+                WHERE rp.resource_type = $1 AND rp.resource_id = $2 AND rp.permission = $3
+                  AND u.username IS NOT DISTINCT FROM $4 AND r.name IS NOT DISTINCT FROM $5
+                "#,
+                share.resource_type,
+                share.resource_id,
+                share.permission,
+                share.user,
+                share.role
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            let before = sharing::find_share(conn, share_id).await?;
+            sqlx::query!("DELETE FROM resource_permissions WHERE id = $1", share_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(Error::from_sqlx)?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::ResourceUnshared,
+                target_id: share_id,
+                before: Some(json!(before)),
+                after: None,
+                reason,
+            }
+        }
+        PolicyStep::RemoveDeny(deny) => {
+            let target_id = sqlx::query_scalar!(
+                r#"
+                DELETE FROM permission_denies d
+                USING users u
+                WHERE u.id = d.user_id AND u.username = $1 AND d.permission = $2
+                RETURNING d.user_id
+                "#,
+                deny.user,
+                deny.permission
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::PermissionDenyRemoved,
+                target_id,
+                before: Some(json!({ "permission": deny.permission })),
+                after: None,
+                reason,
+            }
+        }
+        PolicyStep::RemoveMember { role, user } => {
+            let role_id = role_id(conn, role).await?;
+            let target_id = sqlx::query_scalar!(
+                r#"
+                DELETE FROM user_roles ur
+                USING users u
+                WHERE u.id = ur.user_id AND ur.role_id = $1 AND u.username = $2
+                RETURNING ur.user_id
+                "#,
+                role_id,
+                user
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::RoleUnassigned,
+                target_id,
+                before: Some(json!({ "role_id": role_id, "role_name": role })),
+                after: None,
+                reason,
+            }
+        }
+        PolicyStep::RemoveRole(name) => {
+            let role_id = role_id(conn, name).await?;
+            let before = roles::find_role(conn, role_id).await?;
+            sqlx::query!("DELETE FROM roles WHERE id = $1", role_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(Error::from_sqlx)?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::RoleDeleted,
+                target_id: role_id,
+                before: Some(json!(before)),
+                after: None,
+                reason,
+            }
+        }
+        PolicyStep::CreateRole(role) => {
+            let role_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO roles (name, description, permissions, created_by)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id
+                "#,
+                role.name,
+                role.description,
+                &role.permissions,
+                actor_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            let after = roles::find_role(conn, role_id).await?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::RoleCreated,
+                target_id: role_id,
+                before: None,
+                after: Some(json!(after)),
+                reason,
+            }
+        }
+        PolicyStep::UpdateRole(role) => {
+            let role_id = role_id(conn, &role.name).await?;
+            let before = roles::find_role(conn, role_id).await?;
+            sqlx::query!(
+                r#"
+                UPDATE roles
+                SET description = $2, permissions = $3, updated_at = NOW()
+                WHERE id = $1
+                "#,
+                role_id,
+                role.description,
+                &role.permissions
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            let after = roles::find_role(conn, role_id).await?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::RoleUpdated,
+                target_id: role_id,
+                before: Some(json!(before)),
+                after: Some(json!(after)),
+                reason,
+            }
+        }
+        PolicyStep::AddMember { role, user } => {
+            let role_id = role_id(conn, role).await?;
+            let target_id = user_id(user)?;
+            sqlx::query!(
+                "INSERT INTO user_roles (user_id, role_id, assigned_by) VALUES ($1, $2, $3)",
+                target_id,
+                role_id,
+                actor_id
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::RoleAssigned,
+                target_id,
+                before: None,
+                after: Some(json!({ "role_id": role_id, "role_name": role })),
+                reason,
+            }
+        }
+        PolicyStep::AddDeny(deny) => {
+            let target_id = user_id(&deny.user)?;
+            let deny_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO permission_denies (user_id, permission, reason, created_by)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id
+                "#,
+                target_id,
+                deny.permission,
+                deny.reason,
+                actor_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            let created = denies::find_deny(conn, deny_id).await?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::PermissionDenied,
+                target_id,
+                before: None,
+                after: Some(json!({ "permission": created.permission })),
+                reason: deny.reason.clone().or(reason),
+            }
+        }
+        PolicyStep::AddShare(share) => {
+            let grantee_user_id = share.user.as_deref().map(user_id).transpose()?;
+            let grantee_role_id = match &share.role {
+                Some(role) => Some(role_id(conn, role).await?),
+                None => None,
+            };
+            let share_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO resource_permissions
+                    (resource_type, resource_id, user_id, role_id, permission, granted_by)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id
+                "#,
+                share.resource_type,
+                share.resource_id,
+                grantee_user_id,
+                grantee_role_id,
+                share.permission,
+                actor_id
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?;
+            let after = sharing::find_share(conn, share_id).await?;
+            RbacChange {
+                actor_id,
+                action: RbacAuditAction::ResourceShared,
+                target_id: share_id,
+                before: None,
+                after: Some(json!(after)),
+                reason,
+            }
+        }
+    };
+    audit::record_change(conn, change).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(name: &str, permissions: &[&str], members: &[&str]) -> PolicyRole {
+        PolicyRole {
+            name: name.to_string(),
+            description: None,
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            members: members.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn deny(user: &str, reason: Option<&str>) -> PolicyDeny {
+        PolicyDeny {
+            user: user.to_string(),
+            permission: "monitoring:write".to_string(),
+            reason: reason.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_plan_removes_before_adding() {
+        let current = RbacPolicy {
+            roles: vec![
+                role("auditors", &["users:read"], &["alice"]),
+                role("support", &["users:read"], &["alice", "bob"]),
+            ],
+            denies: vec![deny("bob", None)],
+            shares: Vec::new(),
+        };
+        let desired = RbacPolicy {
+            roles: vec![role(
+                "support",
+                &["users:read", "users:write"],
+                &["bob", "carol"],
+            )],
+            denies: vec![deny("carol", None)],
+            shares: Vec::new(),
+        };
+
+        let changes: Vec<String> = plan(&current, &desired)
+            .iter()
+            .map(|step| step.change().to_string())
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "- deny bob monitoring:write",
+                "- member auditors/alice",
+                "- role auditors",
+                "- member support/alice",
+                "~ role support",
+                "+ member support/carol",
+                "+ deny carol monitoring:write",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_ignores_deny_reason() {
+        let current = RbacPolicy {
+            denies: vec![deny("bob", Some("Flooding"))],
+            ..Default::default()
+        };
+        let desired = RbacPolicy {
+            denies: vec![deny("bob", None)],
+            ..Default::default()
+        };
+
+        assert!(plan(&current, &desired).is_empty());
+        assert!(plan(&desired, &desired).is_empty());
+    }
+
+    #[test]
+    fn test_policy_yaml_round_trip() {
+        let yaml = r#"
+roles:
+  - name: support
+    description: Support agents
+    permissions: [Users:Read, users:read]
+    members: [bob, alice]
+denies:
+  - user: bob
+    permission: monitoring:write
+shares:
+  - resource_type: tasks
+    resource_id: 6f1c1f44-3d4b-4cf4-9a43-2a4e0d0ad7c1
+    role: support
+    permission: write
+"#;
+        let mut policy = from_yaml(yaml).unwrap();
+        policy.validate().unwrap();
+        assert_eq!(policy.roles[0].permissions, vec!["users:read"]);
+        assert_eq!(policy.roles[0].members, vec!["alice", "bob"]);
+        assert_eq!(from_yaml(&to_yaml(&policy).unwrap()).unwrap(), policy);
+
+        assert!(from_yaml("roles: [{ name: support, color: red }]").is_err());
+        let mut policy = from_yaml(
+            "shares: [{ resource_type: tasks, resource_id: 6f1c1f44-3d4b-4cf4-9a43-2a4e0d0ad7c1, role: missing, permission: read }]",
+        )
+        .unwrap();
+        assert!(policy.validate().is_err());
+    }
+}
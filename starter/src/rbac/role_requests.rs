@@ -0,0 +1,310 @@
+//! Four-eyes approval of built-in role escalations
+//!
+//! A moderator or admin files a request to raise a user to `moderator` or
+//! `admin`; the role only changes once an admin approves it. The approving
+//! admin can't be the requester or the user being promoted, so no one can
+//! grant elevated access on their own. With `auth.admin_role_requires_approval`
+//! set, this is the only way to grant the admin role through the API. Filing,
+//! approving and rejecting are recorded in the [audit log](crate::rbac::audit),
+//! and the approved change itself as `user_role_changed`.
+
+use crate::rbac::UserRole;
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{CreateRoleRequest, RbacAuditAction, RoleRequest, RoleRequestStatus};
+use crate::realtime::{Notification, services as realtime_services};
+use crate::users::models::UpdateUserRoleRequest;
+use crate::users::services as user_services;
+use crate::{DbConn, Error, Result};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Fail when `role` would grant admin directly while `auth.admin_role_requires_approval`
+/// is set, on every path that creates an account or changes its role outside a
+/// role request; `current` is the role before the change, `None` for a new account
+pub fn require_admin_approval(
+    requires_approval: bool,
+    role: UserRole,
+    current: Option<UserRole>,
+) -> Result<()> {
+    if requires_approval && role == UserRole::Admin && current != Some(UserRole::Admin) {
+        return Err(Error::Forbidden(
+            "The admin role can only be granted through an approved role request".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn find_request(conn: &mut DbConn, request_id: Uuid) -> Result<RoleRequest> {
+    sqlx::query_as!(
+        RoleRequest,
+        r#"
+        SELECT rr.id, rr.user_id, u.username, u.role AS current_role, rr.role, rr.expires_at,
+               rr.reason, rr.status, rr.requested_by, rr.decided_by, rr.decided_at,
+               rr.decision_reason, rr.created_at
+        FROM role_requests rr
+        JOIN users u ON u.id = rr.user_id
+        WHERE rr.id = $1
+        "#,
+        request_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Role request not found".to_string()))
+}
+
+/// Role requests for users of the tenant, optionally with one status, newest first
+pub async fn list_requests(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    status: Option<RoleRequestStatus>,
+) -> Result<Vec<RoleRequest>> {
+    sqlx::query_as!(
+        RoleRequest,
+        r#"
+        SELECT rr.id, rr.user_id, u.username, u.role AS current_role, rr.role, rr.expires_at,
+               rr.reason, rr.status, rr.requested_by, rr.decided_by, rr.decided_at,
+               rr.decision_reason, rr.created_at
+        FROM role_requests rr
+        JOIN users u ON u.id = rr.user_id
+        WHERE u.tenant_id = $1 AND ($2::text IS NULL OR rr.status = $2)
+        ORDER BY rr.created_at DESC, rr.id
+        "#,
+        tenant_id,
+        status.map(|status| status.as_str())
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// File a request for a higher role; a user has at most one pending request
+pub async fn create_request(
+    conn: &mut DbConn,
+    request: CreateRoleRequest,
+    requested_by: Uuid,
+) -> Result<RoleRequest> {
+    request.validate(Utc::now())?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let current_role = sqlx::query_scalar!(
+        "SELECT role FROM users WHERE id = $1 AND deleted_at IS NULL",
+        request.user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .map(UserRole::from)
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    if current_role >= request.role {
+        return Err(Error::validation(
+            "role",
+            &format!("The user already has the {current_role} role"),
+        ));
+    }
+
+    let request_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO role_requests (user_id, role, expires_at, reason, requested_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        request.user_id,
+        request.role.as_str(),
+        request.expires_at,
+        request.reason,
+        requested_by
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("The user already has a pending role request")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let role_request = find_request(&mut tx, request_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(requested_by),
+            action: RbacAuditAction::RoleRequested,
+            target_id: request.user_id,
+            before: None,
+            after: Some(json!({
+                "request_id": request_id,
+                "role": request.role,
+                "expires_at": request.expires_at,
+            })),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(role_request)
+}
+
+/// Lock a request that is still pending, for a decision
+async fn pending_request(conn: &mut DbConn, request_id: Uuid) -> Result<RoleRequest> {
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM role_requests WHERE id = $1 FOR UPDATE",
+        request_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .map(RoleRequestStatus::from)
+    .ok_or_else(|| Error::NotFound("Role request not found".to_string()))?;
+    if status != RoleRequestStatus::Pending {
+        return Err(Error::conflict(&format!(
+            "The role request was already {}",
+            status.as_str()
+        )));
+    }
+    find_request(conn, request_id).await
+}
+
+/// Record the decision on a request
+async fn decide(
+    conn: &mut DbConn,
+    request_id: Uuid,
+    status: RoleRequestStatus,
+    decided_by: Uuid,
+    reason: Option<&str>,
+) -> Result<RoleRequest> {
+    sqlx::query!(
+        r#"
+        UPDATE role_requests
+        SET status = $2, decided_by = $3, decided_at = NOW(), decision_reason = $4
+        WHERE id = $1
+        "#,
+        request_id,
+        status.as_str(),
+        decided_by,
+        reason
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    find_request(conn, request_id).await
+}
+
+/// Approve a request and grant the role; the approver must be an admin other
+/// than the requester and the user being promoted
+pub async fn approve_request(
+    conn: &mut DbConn,
+    request_id: Uuid,
+    approver_id: Uuid,
+    reason: Option<String>,
+) -> Result<RoleRequest> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let pending = pending_request(&mut tx, request_id).await?;
+
+    if pending.requested_by == Some(approver_id) {
+        return Err(Error::Forbidden(
+            "A role request must be approved by someone other than its requester".to_string(),
+        ));
+    }
+    if pending.user_id == approver_id {
+        return Err(Error::Forbidden(
+            "You can't approve a role request for yourself".to_string(),
+        ));
+    }
+
+    let approved = decide(
+        &mut tx,
+        request_id,
+        RoleRequestStatus::Approved,
+        approver_id,
+        reason.as_deref(),
+    )
+    .await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(approver_id),
+            action: RbacAuditAction::RoleRequestApproved,
+            target_id: approved.user_id,
+            before: Some(json!({ "request_id": request_id, "status": pending.status })),
+            after: Some(json!({ "request_id": request_id, "status": approved.status })),
+            reason,
+        },
+    )
+    .await?;
+
+    // The expiry is checked again, as it may have passed while the request waited
+    user_services::update_user_role(
+        &mut tx,
+        approved.user_id,
+        UpdateUserRoleRequest {
+            role: approved.role,
+            reason: approved.reason.clone(),
+            expires_at: approved.expires_at,
+        },
+        approver_id,
+    )
+    .await?;
+    notify_decision(&mut tx, &approved).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    find_request(conn, request_id).await
+}
+
+/// Turn a request down without changing the user's role
+pub async fn reject_request(
+    conn: &mut DbConn,
+    request_id: Uuid,
+    decided_by: Uuid,
+    reason: Option<String>,
+) -> Result<RoleRequest> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let pending = pending_request(&mut tx, request_id).await?;
+
+    let rejected = decide(
+        &mut tx,
+        request_id,
+        RoleRequestStatus::Rejected,
+        decided_by,
+        reason.as_deref(),
+    )
+    .await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(decided_by),
+            action: RbacAuditAction::RoleRequestRejected,
+            target_id: rejected.user_id,
+            before: Some(json!({ "request_id": request_id, "status": pending.status })),
+            after: Some(json!({ "request_id": request_id, "status": rejected.status })),
+            reason,
+        },
+    )
+    .await?;
+    notify_decision(&mut tx, &rejected).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(rejected)
+}
+
+/// Tell the user about a decided request once the decision commits
+async fn notify_decision(conn: &mut DbConn, request: &RoleRequest) -> Result<()> {
+    let (kind, decision) = match request.status {
+        RoleRequestStatus::Approved => ("role_request_approved", "approved"),
+        _ => ("role_request_rejected", "rejected"),
+    };
+    realtime_services::notify_user(
+        conn,
+        request.user_id,
+        Notification {
+            kind: kind.to_string(),
+            message: format!("The request to make you {} was {decision}", request.role),
+            data: json!({ "request_id": request.id, "role": request.role }),
+        },
+    )
+    .await
+}
@@ -0,0 +1,331 @@
+//! Custom roles defined by admins
+//!
+//! A custom role is a named set of `resource:permission` entries. Its members
+//! keep their built-in role and gain the role's permissions on top, which
+//! [`check_permission`](crate::rbac::services::check_permission) and
+//! [`require_staff_permission`](crate::rbac::services::require_staff_permission)
+//! take into account. Every change is recorded in the [audit log](crate::rbac::audit).
+
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{
+    CreateCustomRoleRequest, CustomRole, CustomRoleMember, RbacAuditAction, UpdateCustomRoleRequest,
+};
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Permissions the user's custom roles grant, without duplicates
+pub async fn granted_permissions(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT permission AS "permission!"
+        FROM user_roles ur
+        JOIN roles r ON r.id = ur.role_id
+        CROSS JOIN LATERAL UNNEST(r.permissions) AS permission
+        WHERE ur.user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Every custom role, with its members in the tenant counted
+pub async fn list_roles(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<CustomRole>> {
+    sqlx::query_as!(
+        CustomRole,
+        r#"
+        SELECT r.id, r.name, r.description, r.permissions, r.created_by, r.created_at,
+               r.updated_at,
+               (SELECT COUNT(*) FROM user_roles ur
+                JOIN users u ON u.id = ur.user_id
+                WHERE ur.role_id = r.id AND u.tenant_id = $1) AS "member_count!"
+        FROM roles r
+        ORDER BY r.name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+pub async fn find_role(conn: &mut DbConn, role_id: Uuid) -> Result<CustomRole> {
+    find_role_in_tenant(conn, role_id, None).await
+}
+
+/// A custom role with its members in the tenant counted, or all of them without one
+pub async fn find_role_in_tenant(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    tenant_id: Option<Uuid>,
+) -> Result<CustomRole> {
+    sqlx::query_as!(
+        CustomRole,
+        r#"
+        SELECT r.id, r.name, r.description, r.permissions, r.created_by, r.created_at,
+               r.updated_at,
+               (SELECT COUNT(*) FROM user_roles ur
+                JOIN users u ON u.id = ur.user_id
+                WHERE ur.role_id = r.id
+                  AND ($2::UUID IS NULL OR u.tenant_id = $2)) AS "member_count!"
+        FROM roles r
+        WHERE r.id = $1
+        "#,
+        role_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Role not found".to_string()))
+}
+
+pub async fn create_role(
+    conn: &mut DbConn,
+    mut request: CreateCustomRoleRequest,
+    created_by: Uuid,
+) -> Result<CustomRole> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let role_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO roles (name, description, permissions, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        request.name,
+        request.description,
+        &request.permissions,
+        created_by
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict(&format!("A role named {} already exists", request.name))
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let role = find_role(&mut tx, role_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(created_by),
+            action: RbacAuditAction::RoleCreated,
+            target_id: role_id,
+            before: None,
+            after: Some(json!(role)),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(role)
+}
+
+/// Change a role; its members get the new permissions on their next request
+pub async fn update_role(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    mut request: UpdateCustomRoleRequest,
+    actor_id: Uuid,
+) -> Result<CustomRole> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let before = find_role(&mut tx, role_id).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE roles
+        SET description = COALESCE($2, description),
+            permissions = COALESCE($3, permissions),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        role_id,
+        request.description,
+        request.permissions.as_deref()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let role = find_role(&mut tx, role_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::RoleUpdated,
+            target_id: role_id,
+            before: Some(json!(before)),
+            after: Some(json!(role)),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(role)
+}
+
+/// Delete a role, taking its permissions away from its members
+pub async fn delete_role(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let role = find_role(&mut tx, role_id).await?;
+
+    sqlx::query!("DELETE FROM roles WHERE id = $1", role_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::RoleDeleted,
+            target_id: role_id,
+            before: Some(json!(role)),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Members of a role in the tenant, by username
+pub async fn list_members(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<Vec<CustomRoleMember>> {
+    find_role(conn, role_id).await?;
+
+    sqlx::query_as!(
+        CustomRoleMember,
+        r#"
+        SELECT u.id AS user_id, u.username, u.email, ur.assigned_by, ur.assigned_at
+        FROM user_roles ur
+        JOIN users u ON u.id = ur.user_id
+        WHERE ur.role_id = $1 AND u.tenant_id = $2
+        ORDER BY u.username
+        "#,
+        role_id,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Give a user a role; returns the role's name, or `None` if they already had it
+pub async fn assign_role(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    user_id: Uuid,
+    assigned_by: Uuid,
+    reason: Option<String>,
+) -> Result<Option<String>> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let role = find_role(&mut tx, role_id).await?;
+    let user_exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !user_exists {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    let assigned = sqlx::query!(
+        r#"
+        INSERT INTO user_roles (user_id, role_id, assigned_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, role_id) DO NOTHING
+        "#,
+        user_id,
+        role_id,
+        assigned_by
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if assigned == 0 {
+        return Ok(None);
+    }
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(assigned_by),
+            action: RbacAuditAction::RoleAssigned,
+            target_id: user_id,
+            before: None,
+            after: Some(json!({ "role_id": role_id, "role_name": role.name })),
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(Some(role.name))
+}
+
+/// Take a role away from a user; returns the role's name
+pub async fn unassign_role(
+    conn: &mut DbConn,
+    role_id: Uuid,
+    user_id: Uuid,
+    actor_id: Uuid,
+    reason: Option<String>,
+) -> Result<String> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let name = sqlx::query_scalar!(
+        r#"
+        DELETE FROM user_roles ur
+        USING roles r
+        WHERE ur.role_id = r.id AND ur.role_id = $1 AND ur.user_id = $2
+        RETURNING r.name
+        "#,
+        role_id,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("The user doesn't have this role".to_string()))?;
+
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(actor_id),
+            action: RbacAuditAction::RoleUnassigned,
+            target_id: user_id,
+            before: Some(json!({ "role_id": role_id, "role_name": name })),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(name)
+}
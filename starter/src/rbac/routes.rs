@@ -0,0 +1,276 @@
+//! Routes that declare the permission they require
+//!
+//! Route files list each permission-gated endpoint together with its
+//! [`Requirement`] instead of checking it inside the handler. The same
+//! declarations enforce the requirement, through a route layer that runs after
+//! authentication, and feed the permission map of the OpenAPI document.
+
+use crate::Error;
+use crate::auth::AuthUser;
+use crate::rbac::models::{Permission, Resource, permission_key};
+use crate::rbac::services;
+use axum::{
+    Router,
+    extract::Request,
+    handler::Handler,
+    http::Method,
+    middleware::{self, Next},
+    routing::{MethodFilter, on},
+};
+
+/// Permission a route requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// Granted by the built-in role or a custom role; see [`services::check_permission`]
+    Permission(Resource, Permission),
+    /// Over other users' resources: moderators, or a custom role granting it;
+    /// see [`services::require_staff_permission`]
+    Staff(Resource, Permission),
+}
+
+impl Requirement {
+    pub fn check(&self, user: &AuthUser) -> Result<(), Error> {
+        match *self {
+            Requirement::Permission(resource, permission) => {
+                services::check_permission(user, resource, permission)
+            }
+            Requirement::Staff(resource, permission) => {
+                services::require_staff_permission(user, resource, permission)
+            }
+        }
+    }
+
+    /// The `resource:permission` key, e.g. `users:write`
+    pub fn key(&self) -> String {
+        match *self {
+            Requirement::Permission(resource, permission)
+            | Requirement::Staff(resource, permission) => permission_key(resource, permission),
+        }
+    }
+
+    pub fn is_staff(&self) -> bool {
+        matches!(self, Requirement::Staff(..))
+    }
+}
+
+/// Declared requirement of one endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePermission {
+    pub method: Method,
+    /// Path as in the OpenAPI document, e.g. `/users/{id}/status`
+    pub path: String,
+    pub requirement: Requirement,
+}
+
+/// Router builder whose routes each declare the permission they require
+pub struct PermissionRoutes<S> {
+    router: Router<S>,
+    permissions: Vec<RoutePermission>,
+}
+
+impl<S> Default for PermissionRoutes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> PermissionRoutes<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            permissions: Vec::new(),
+        }
+    }
+
+    pub fn get<H, T>(self, path: &str, handler: H, requirement: Requirement) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(Method::GET, MethodFilter::GET, path, handler, requirement)
+    }
+
+    pub fn post<H, T>(self, path: &str, handler: H, requirement: Requirement) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(Method::POST, MethodFilter::POST, path, handler, requirement)
+    }
+
+    pub fn put<H, T>(self, path: &str, handler: H, requirement: Requirement) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(Method::PUT, MethodFilter::PUT, path, handler, requirement)
+    }
+
+    pub fn patch<H, T>(self, path: &str, handler: H, requirement: Requirement) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(
+            Method::PATCH,
+            MethodFilter::PATCH,
+            path,
+            handler,
+            requirement,
+        )
+    }
+
+    pub fn delete<H, T>(self, path: &str, handler: H, requirement: Requirement) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.route(
+            Method::DELETE,
+            MethodFilter::DELETE,
+            path,
+            handler,
+            requirement,
+        )
+    }
+
+    fn route<H, T>(
+        mut self,
+        method: Method,
+        filter: MethodFilter,
+        path: &str,
+        handler: H,
+        requirement: Requirement,
+    ) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        let method_router = on(filter, handler).route_layer(middleware::from_fn(
+            move |req: Request, next: Next| async move {
+                let auth_user = req
+                    .extensions()
+                    .get::<AuthUser>()
+                    .ok_or(Error::Unauthorized)?;
+                requirement.check(auth_user)?;
+                Ok::<_, Error>(next.run(req).await)
+            },
+        ));
+        self.router = self.router.route(path, method_router);
+        self.permissions.push(RoutePermission {
+            method,
+            path: path.to_string(),
+            requirement,
+        });
+        self
+    }
+
+    /// Declared requirements, with paths as if nested under `prefix`
+    pub fn permissions(&self, prefix: &str) -> Vec<RoutePermission> {
+        self.permissions
+            .iter()
+            .map(|route| RoutePermission {
+                path: match route.path.as_str() {
+                    "/" => prefix.to_string(),
+                    path => format!("{prefix}{path}"),
+                },
+                ..route.clone()
+            })
+            .collect()
+    }
+
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    async fn test_handler() -> &'static str {
+        "success"
+    }
+
+    fn user_with(role: &str, permissions: &[&str]) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: role.to_string().into(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            denied: Vec::new(),
+            scopes: None,
+            org: None,
+            tenant_id: crate::tenants::DEFAULT_TENANT_ID,
+        }
+    }
+
+    fn routes() -> PermissionRoutes<()> {
+        PermissionRoutes::new()
+            .get(
+                "/",
+                test_handler,
+                Requirement::Staff(Resource::Users, Permission::Read),
+            )
+            .delete(
+                "/",
+                test_handler,
+                Requirement::Permission(Resource::Users, Permission::Delete),
+            )
+    }
+
+    async fn status(method: Method, user: AuthUser) -> StatusCode {
+        let mut req = Request::builder()
+            .method(method)
+            .uri("/")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut().insert(user);
+        routes().into_router().oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_declared_requirements_are_enforced() {
+        assert_eq!(
+            status(Method::GET, user_with("user", &[])).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(Method::GET, user_with("user", &["users:read"])).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(Method::GET, user_with("moderator", &[])).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(Method::DELETE, user_with("moderator", &[])).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(Method::DELETE, user_with("admin", &[])).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn test_permission_map() {
+        let permissions = routes().permissions("/users");
+        assert_eq!(permissions.len(), 2);
+        assert_eq!(permissions[0].method, Method::GET);
+        assert_eq!(permissions[0].path, "/users");
+        assert_eq!(permissions[0].requirement.key(), "users:read");
+        assert!(permissions[0].requirement.is_staff());
+        assert!(!permissions[1].requirement.is_staff());
+    }
+}
@@ -1,21 +1,103 @@
 use crate::Error;
 use crate::auth::AuthUser;
-use crate::rbac::models::{Permission, Resource, UserRole};
+use crate::rbac::models::{Permission, Resource, UserRole, permission_key};
+use axum::http::Method;
 use uuid::Uuid;
 
 /// Check if a user has the required permission for a resource
+///
+/// Granted by the user's built-in role or by one of their custom roles, and
+/// only within the scopes of a scoped session. A [deny](crate::rbac::denies)
+/// of the permission for the user overrides both. In an organization selected
+/// with `X-Org-Id`, the user's org role takes the place of the built-in role
+/// for the organization's tasks and events.
 pub fn check_permission(
     user: &AuthUser,
     resource: Resource,
     permission: Permission,
 ) -> Result<(), Error> {
-    if user.role.can_access(resource, permission) {
-        Ok(())
-    } else {
-        Err(Error::Forbidden(format!(
+    if !user.in_scope(resource, permission) {
+        return Err(out_of_scope(resource, permission));
+    }
+    if user.denies(resource, permission) {
+        return Err(denied(resource, permission));
+    }
+    if user.grants(resource, permission) {
+        return Ok(());
+    }
+    match user.org {
+        Some(org) if resource.is_org_scoped() => {
+            if org.role.can_access(resource, permission) {
+                Ok(())
+            } else {
+                Err(Error::Forbidden(format!(
+                    "Insufficient permissions: {} org role cannot {} {}",
+                    org.role, permission, resource
+                )))
+            }
+        }
+        _ if user.role.can_access(resource, permission) => Ok(()),
+        _ => Err(Error::Forbidden(format!(
             "Insufficient permissions: {} role cannot {} {}",
             user.role, permission, resource
-        )))
+        ))),
+    }
+}
+
+/// Every `resource:permission` the user holds, as [`check_permission`] decides
+pub fn effective_permissions(user: &AuthUser) -> Vec<String> {
+    Resource::ALL
+        .into_iter()
+        .flat_map(|resource| Permission::ALL.map(|permission| (resource, permission)))
+        .filter(|&(resource, permission)| check_permission(user, resource, permission).is_ok())
+        .map(|(resource, permission)| permission_key(resource, permission))
+        .collect()
+}
+
+fn out_of_scope(resource: Resource, permission: Permission) -> Error {
+    Error::Forbidden(format!(
+        "Session scope does not include {}",
+        permission_key(resource, permission)
+    ))
+}
+
+fn denied(resource: Resource, permission: Permission) -> Error {
+    Error::Forbidden(format!(
+        "Permission {} is denied to this user",
+        permission_key(resource, permission)
+    ))
+}
+
+/// Endpoints any scoped session can use
+const SCOPE_EXEMPT_PATHS: &[&str] = &["/auth/me", "/auth/me/permissions", "/auth/logout"];
+
+/// Limit a scoped session to the endpoints its scopes cover
+///
+/// Task and user endpoints need the scope for their resource: `read` for GET,
+/// `delete` for DELETE and `write` otherwise. Every other endpoint is closed
+/// to scoped sessions. Full sessions always pass.
+pub fn check_session_scope(user: &AuthUser, path: &str, method: &Method) -> Result<(), Error> {
+    if user.scopes.is_none() || SCOPE_EXEMPT_PATHS.contains(&path) {
+        return Ok(());
+    }
+    let resource = match path.trim_start_matches('/').split('/').next() {
+        Some("tasks") => Resource::Tasks,
+        Some("users") => Resource::Users,
+        _ => {
+            return Err(Error::Forbidden(
+                "Scoped sessions can't use this endpoint".to_string(),
+            ));
+        }
+    };
+    let permission = match *method {
+        Method::GET | Method::HEAD => Permission::Read,
+        Method::DELETE => Permission::Delete,
+        _ => Permission::Write,
+    };
+    if user.in_scope(resource, permission) {
+        Ok(())
+    } else {
+        Err(out_of_scope(resource, permission))
     }
 }
 
@@ -24,25 +106,19 @@ pub fn has_role_or_higher(user: &AuthUser, required_role: UserRole) -> bool {
     user.role.has_role_or_higher(required_role)
 }
 
-/// Check if a user can access a specific task based on ownership and role
-pub fn can_access_task(user: &AuthUser, task_created_by: Option<Uuid>) -> Result<(), Error> {
-    match user.role {
-        // Admin and Moderator can access any task
-        UserRole::Admin | UserRole::Moderator => Ok(()),
-        // Users can only access their own tasks
-        UserRole::User => {
-            if let Some(created_by) = task_created_by {
-                if created_by == user.id {
-                    Ok(())
-                } else {
-                    Err(Error::NotFound("Task not found".to_string())) // Prevent enumeration
-                }
-            } else {
-                // System tasks (no created_by) are not accessible to regular users
-                Err(Error::NotFound("Task not found".to_string()))
-            }
-        }
+/// Check if a user can access a specific task: their own, or any task with
+/// the staff `permission` on tasks (see [`require_staff_permission`])
+pub fn can_access_task(
+    user: &AuthUser,
+    task_created_by: Option<Uuid>,
+    permission: Permission,
+) -> Result<(), Error> {
+    if task_created_by == Some(user.id) {
+        return Ok(());
     }
+    // System tasks (no created_by) are only open to staff; others look missing to prevent enumeration
+    require_staff_permission(user, Resource::Tasks, permission)
+        .map_err(|_| Error::NotFound("Task not found".to_string()))
 }
 
 /// Check if a user can access another user's profile
@@ -91,6 +167,34 @@ pub fn require_moderator_or_higher(user: &AuthUser) -> Result<(), Error> {
     }
 }
 
+/// Check a permission over other users' resources
+///
+/// The built-in role only counts from moderator up, since a regular user's
+/// permissions cover their own resources; anyone else needs a custom role
+/// granting the permission.
+pub fn require_staff_permission(
+    user: &AuthUser,
+    resource: Resource,
+    permission: Permission,
+) -> Result<(), Error> {
+    if user.role.has_role_or_higher(UserRole::Moderator) {
+        return check_permission(user, resource, permission);
+    }
+    if !user.in_scope(resource, permission) {
+        return Err(out_of_scope(resource, permission));
+    }
+    if user.denies(resource, permission) {
+        return Err(denied(resource, permission));
+    }
+    if user.grants(resource, permission) {
+        Ok(())
+    } else {
+        Err(Error::Forbidden(format!(
+            "Insufficient permissions: cannot {permission} {resource}"
+        )))
+    }
+}
+
 /// Check if a user can access a resource based on ownership and role
 /// Admin/Moderator can access any resource, users can only access their own
 pub fn can_access_own_resource(user: &AuthUser, resource_owner: Uuid) -> Result<(), Error> {
@@ -111,6 +215,7 @@ pub fn can_access_own_resource(user: &AuthUser, resource_owner: Uuid) -> Result<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::orgs::{OrgContext, OrgRole};
     use uuid::Uuid;
 
     fn create_test_user(role: &str) -> AuthUser {
@@ -119,6 +224,11 @@ mod tests {
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
             role: role.to_string().into(),
+            permissions: Vec::new(),
+            denied: Vec::new(),
+            scopes: None,
+            org: None,
+            tenant_id: crate::tenants::DEFAULT_TENANT_ID,
         }
     }
 
@@ -142,6 +252,112 @@ mod tests {
         assert!(check_permission(&user, Resource::Admin, Permission::Read).is_err());
     }
 
+    #[test]
+    fn test_check_permission_in_org() {
+        let mut moderator = create_test_user("moderator");
+        moderator.org = Some(OrgContext {
+            id: Uuid::new_v4(),
+            role: OrgRole::Member,
+        });
+        let mut user = create_test_user("user");
+        user.org = Some(OrgContext {
+            id: Uuid::new_v4(),
+            role: OrgRole::Admin,
+        });
+
+        // The org role decides on the organization's tasks and events
+        assert!(check_permission(&moderator, Resource::Tasks, Permission::Delete).is_err());
+        assert!(check_permission(&moderator, Resource::Tasks, Permission::Write).is_ok());
+        assert!(check_permission(&user, Resource::Monitoring, Permission::Delete).is_ok());
+        assert!(
+            check_permission(
+                &create_test_user("user"),
+                Resource::Monitoring,
+                Permission::Delete
+            )
+            .is_err()
+        );
+
+        // The built-in role still decides on everything else
+        assert!(check_permission(&moderator, Resource::Users, Permission::Write).is_ok());
+        assert!(check_permission(&user, Resource::Users, Permission::Delete).is_err());
+    }
+
+    #[test]
+    fn test_require_staff_permission() {
+        let moderator = create_test_user("moderator");
+        let user = create_test_user("user");
+        let mut support = create_test_user("user");
+        support.permissions = vec!["users:read".to_string()];
+
+        assert!(require_staff_permission(&moderator, Resource::Users, Permission::Read).is_ok());
+        assert!(require_staff_permission(&moderator, Resource::Users, Permission::Delete).is_err());
+        assert!(require_staff_permission(&user, Resource::Users, Permission::Read).is_err());
+        assert!(require_staff_permission(&support, Resource::Users, Permission::Read).is_ok());
+        assert!(require_staff_permission(&support, Resource::Users, Permission::Write).is_err());
+        assert!(check_permission(&support, Resource::Users, Permission::Read).is_ok());
+    }
+
+    #[test]
+    fn test_denies_override_grants() {
+        let mut admin = create_test_user("admin");
+        admin.denied = vec!["monitoring:write".to_string()];
+        let mut support = create_test_user("user");
+        support.permissions = vec!["users:read".to_string()];
+        support.denied = vec!["users:read".to_string()];
+
+        assert!(check_permission(&admin, Resource::Monitoring, Permission::Write).is_err());
+        assert!(check_permission(&admin, Resource::Monitoring, Permission::Read).is_ok());
+        assert!(require_staff_permission(&support, Resource::Users, Permission::Read).is_err());
+        assert!(check_permission(&support, Resource::Users, Permission::Read).is_err());
+    }
+
+    #[test]
+    fn test_session_scopes() {
+        let mut bot = create_test_user("admin");
+        bot.scopes = Some(vec!["tasks:read".to_string()]);
+
+        assert!(check_permission(&bot, Resource::Tasks, Permission::Read).is_ok());
+        assert!(check_permission(&bot, Resource::Tasks, Permission::Write).is_err());
+        assert!(require_staff_permission(&bot, Resource::Users, Permission::Read).is_err());
+
+        assert!(check_session_scope(&bot, "/tasks/stats", &Method::GET).is_ok());
+        assert!(check_session_scope(&bot, "/tasks", &Method::POST).is_err());
+        assert!(check_session_scope(&bot, "/users/me", &Method::GET).is_err());
+        assert!(check_session_scope(&bot, "/admin/tasks/queues", &Method::GET).is_err());
+        assert!(check_session_scope(&bot, "/auth/me", &Method::GET).is_ok());
+        assert!(check_session_scope(&bot, "/auth/refresh", &Method::POST).is_err());
+
+        let admin = create_test_user("admin");
+        assert!(check_session_scope(&admin, "/admin/tasks/queues", &Method::GET).is_ok());
+    }
+
+    #[test]
+    fn test_effective_permissions() {
+        let mut user = create_test_user("user");
+        user.permissions = vec!["users:delete".to_string()];
+        user.denied = vec!["monitoring:write".to_string()];
+        assert_eq!(
+            effective_permissions(&user),
+            vec![
+                "tasks:read",
+                "tasks:write",
+                "tasks:delete",
+                "users:read",
+                "users:write",
+                "users:delete",
+                "monitoring:read",
+            ]
+        );
+
+        let mut bot = create_test_user("admin");
+        bot.scopes = Some(vec!["tasks:read".to_string(), "admin:read".to_string()]);
+        assert_eq!(
+            effective_permissions(&bot),
+            vec!["tasks:read", "admin:read"]
+        );
+    }
+
     #[test]
     fn test_can_access_task() {
         let admin = create_test_user("admin");
@@ -150,14 +366,20 @@ mod tests {
         let other_user_id = Uuid::new_v4();
 
         // Admin and moderator can access any task
-        assert!(can_access_task(&admin, Some(other_user_id)).is_ok());
-        assert!(can_access_task(&moderator, Some(other_user_id)).is_ok());
-        assert!(can_access_task(&admin, None).is_ok());
+        assert!(can_access_task(&admin, Some(other_user_id), Permission::Read).is_ok());
+        assert!(can_access_task(&moderator, Some(other_user_id), Permission::Delete).is_ok());
+        assert!(can_access_task(&admin, None, Permission::Write).is_ok());
 
         // User can only access their own tasks
-        assert!(can_access_task(&user, Some(user.id)).is_ok());
-        assert!(can_access_task(&user, Some(other_user_id)).is_err());
-        assert!(can_access_task(&user, None).is_err());
+        assert!(can_access_task(&user, Some(user.id), Permission::Delete).is_ok());
+        assert!(can_access_task(&user, Some(other_user_id), Permission::Read).is_err());
+        assert!(can_access_task(&user, None, Permission::Read).is_err());
+
+        // unless a custom role grants the permission over others' tasks
+        let mut support = create_test_user("user");
+        support.permissions = vec!["tasks:read".to_string()];
+        assert!(can_access_task(&support, Some(other_user_id), Permission::Read).is_ok());
+        assert!(can_access_task(&support, Some(other_user_id), Permission::Write).is_err());
     }
 
     #[test]
@@ -0,0 +1,332 @@
+//! Per-object access shared with a user or a custom role
+//!
+//! A share gives access to one object on top of what the built-in and custom
+//! roles allow. Any share lets its grantee read the object; `write` and
+//! `delete` shares also allow that action. Only those who manage the object,
+//! such as a task's owner, can share it. Sharing and unsharing are recorded
+//! in the [audit log](crate::rbac::audit).
+
+use crate::auth::AuthUser;
+use crate::orgs::{OrgRole, services as org_services};
+use crate::rbac::audit::{self, RbacChange};
+use crate::rbac::models::{
+    Permission, RbacAuditAction, Resource, ResourceShare, ShareResourceRequest,
+    SharedObjectPermissions,
+};
+use crate::rbac::roles;
+use crate::{DbConn, Error, Result};
+use serde_json::json;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Whether the object is shared with the user, directly or through one of
+/// their custom roles, with `permission`
+pub async fn has_resource_permission(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    resource: Resource,
+    resource_id: Uuid,
+    permission: Permission,
+) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM resource_permissions rp
+            WHERE rp.resource_type = $1 AND rp.resource_id = $2
+              AND ($3 = 'read' OR rp.permission = $3)
+              AND (rp.user_id = $4
+                   OR rp.role_id IN (SELECT role_id FROM user_roles WHERE user_id = $4))
+        ) AS "shared!"
+        "#,
+        resource.to_string(),
+        resource_id,
+        permission.to_string(),
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Check that the user manages the object and so can see and change its shares
+///
+/// Fails with "not found" for objects the user can't see, including those of
+/// other tenants.
+pub async fn require_share_manager(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    resource: Resource,
+    resource_id: Uuid,
+) -> Result<()> {
+    match resource {
+        Resource::Tasks => {
+            let task = sqlx::query!(
+                "SELECT created_by, org_id FROM tasks WHERE id = $1 AND tenant_id = $2",
+                resource_id,
+                user.tenant_id
+            )
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?
+            .ok_or_else(|| Error::NotFound("Task not found".to_string()))?;
+            org_services::can_access_task(
+                conn,
+                user,
+                task.created_by,
+                task.org_id,
+                OrgRole::Admin,
+                Permission::Write,
+            )
+            .await
+        }
+        _ => Err(Error::validation(
+            "resource_type",
+            &format!("{resource} objects can't be shared"),
+        )),
+    }
+}
+
+pub async fn find_share(conn: &mut DbConn, share_id: Uuid) -> Result<ResourceShare> {
+    sqlx::query_as!(
+        ResourceShare,
+        r#"
+        SELECT rp.id, rp.resource_type, rp.resource_id, rp.user_id,
+               u.username AS "username?", rp.role_id, r.name AS "role_name?",
+               rp.permission, rp.granted_by, rp.created_at
+        FROM resource_permissions rp
+        LEFT JOIN users u ON u.id = rp.user_id
+        LEFT JOIN roles r ON r.id = rp.role_id
+        WHERE rp.id = $1
+        "#,
+        share_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Share not found".to_string()))
+}
+
+/// Shares of one object, oldest first
+pub async fn list_shares(
+    conn: &mut DbConn,
+    resource: Resource,
+    resource_id: Uuid,
+) -> Result<Vec<ResourceShare>> {
+    sqlx::query_as!(
+        ResourceShare,
+        r#"
+        SELECT rp.id, rp.resource_type, rp.resource_id, rp.user_id,
+               u.username AS "username?", rp.role_id, r.name AS "role_name?",
+               rp.permission, rp.granted_by, rp.created_at
+        FROM resource_permissions rp
+        LEFT JOIN users u ON u.id = rp.user_id
+        LEFT JOIN roles r ON r.id = rp.role_id
+        WHERE rp.resource_type = $1 AND rp.resource_id = $2
+        ORDER BY rp.created_at
+        "#,
+        resource.to_string(),
+        resource_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Shares giving the user access, directly or through their custom roles,
+/// newest first
+pub async fn shared_with(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<ResourceShare>> {
+    sqlx::query_as!(
+        ResourceShare,
+        r#"
+        SELECT rp.id, rp.resource_type, rp.resource_id, rp.user_id,
+               u.username AS "username?", rp.role_id, r.name AS "role_name?",
+               rp.permission, rp.granted_by, rp.created_at
+        FROM resource_permissions rp
+        LEFT JOIN users u ON u.id = rp.user_id
+        LEFT JOIN roles r ON r.id = rp.role_id
+        WHERE rp.user_id = $1
+           OR rp.role_id IN (SELECT role_id FROM user_roles WHERE user_id = $1)
+        ORDER BY rp.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Permissions the user's shares allow on each object shared with them,
+/// limited to the session's scopes
+pub async fn shared_object_permissions(
+    conn: &mut DbConn,
+    user: &AuthUser,
+) -> Result<Vec<SharedObjectPermissions>> {
+    let mut objects: Vec<SharedObjectPermissions> = Vec::new();
+    for share in shared_with(conn, user.id).await? {
+        let resource: Resource = share.resource_type.parse()?;
+        let permission: Permission = share.permission.parse()?;
+        let allowed: Vec<Permission> = [Permission::Read, permission]
+            .into_iter()
+            .filter(|&permission| user.in_scope(resource, permission))
+            .collect();
+        if allowed.is_empty() {
+            continue;
+        }
+
+        let index = match objects.iter().position(|object| {
+            object.resource_type == share.resource_type && object.resource_id == share.resource_id
+        }) {
+            Some(index) => index,
+            None => {
+                objects.push(SharedObjectPermissions {
+                    resource_type: share.resource_type,
+                    resource_id: share.resource_id,
+                    permissions: Vec::new(),
+                });
+                objects.len() - 1
+            }
+        };
+        objects[index]
+            .permissions
+            .extend(allowed.iter().map(Permission::to_string));
+    }
+
+    for object in &mut objects {
+        object.permissions.sort_by_key(|permission| {
+            Permission::ALL
+                .iter()
+                .position(|p| &p.to_string() == permission)
+        });
+        object.permissions.dedup();
+    }
+    Ok(objects)
+}
+
+/// Share an object the user manages with another user of their tenant or a custom role
+pub async fn share_resource(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    request: ShareResourceRequest,
+) -> Result<ResourceShare> {
+    let (resource, permission) = request.validate()?;
+    require_share_manager(conn, user, resource, request.resource_id).await?;
+
+    if let Some(user_id) = request.user_id {
+        if user_id == user.id {
+            return Err(Error::validation(
+                "user_id",
+                "You can't share with yourself",
+            ));
+        }
+        let user_exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM users WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL
+            ) AS "exists!"
+            "#,
+            user_id,
+            user.tenant_id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+        if !user_exists {
+            return Err(Error::NotFound("User not found".to_string()));
+        }
+    }
+    if let Some(role_id) = request.role_id {
+        roles::find_role(conn, role_id).await?;
+    }
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let share_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO resource_permissions
+            (resource_type, resource_id, user_id, role_id, permission, granted_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+        resource.to_string(),
+        request.resource_id,
+        request.user_id,
+        request.role_id,
+        permission.to_string(),
+        user.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("Already shared with this permission")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let share = find_share(&mut tx, share_id).await?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(user.id),
+            action: RbacAuditAction::ResourceShared,
+            target_id: share_id,
+            before: None,
+            after: Some(json!(share)),
+            reason: request.reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(share)
+}
+
+/// Remove a share from an object the user manages
+pub async fn unshare_resource(
+    conn: &mut DbConn,
+    user: &AuthUser,
+    share_id: Uuid,
+    reason: Option<String>,
+) -> Result<ResourceShare> {
+    let share = find_share(conn, share_id).await?;
+    let resource = share.resource_type.parse()?;
+    require_share_manager(conn, user, resource, share.resource_id).await?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    sqlx::query!("DELETE FROM resource_permissions WHERE id = $1", share_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    audit::record_change(
+        &mut tx,
+        RbacChange {
+            actor_id: Some(user.id),
+            action: RbacAuditAction::ResourceUnshared,
+            target_id: share_id,
+            before: Some(json!(share)),
+            after: None,
+            reason,
+        },
+    )
+    .await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(share)
+}
+
+/// Drop every share of an object, once it is deleted
+pub async fn delete_resource_shares(
+    conn: &mut DbConn,
+    resource: Resource,
+    resource_id: Uuid,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM resource_permissions WHERE resource_type = $1 AND resource_id = $2",
+        resource.to_string(),
+        resource_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use crate::auth::AuthUser;
+use crate::auth::middleware::{WEBSOCKET_TOKEN_PROTOCOL, extract_bearer_token};
+use crate::auth::services as auth_services;
+use crate::core::sse;
+use crate::realtime::hub::ConnectionGuard;
+use crate::realtime::models::{
+    ClientMessage, ConnectionInfo, Notification, RealtimeMessage, ServerMessage, Subscription,
+};
+use crate::realtime::services;
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{
+        Extension, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    http::HeaderMap,
+    response::{
+        Json, Response,
+        sse::{Event as SseEvent, Sse},
+    },
+    routing::get,
+};
+use futures_util::Stream;
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Open a WebSocket for live updates
+///
+/// Browsers authenticate by offering the `bearer` subprotocol followed by the
+/// session token: `new WebSocket(url, ["bearer", token])`. Clients send JSON
+/// messages `{"type": "subscribe", "topic": "tasks"}`, `unsubscribe` and
+/// `ping`; the server answers each one and sends `{"type": "message", "topic",
+/// "data"}` for every published message the user can see.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "Realtime",
+    summary = "Open WebSocket",
+    description = "Upgrade to a WebSocket receiving task status changes, alert status changes and notifications for the topics it subscribes to. Authenticate with the Authorization header or the `bearer, <token>` WebSocket subprotocols. Messages are JSON `ClientMessage` and `ServerMessage` values.",
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol; the server sends `ServerMessage` values", body = ServerMessage),
+        (status = 400, description = "Not a WebSocket handshake", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many open connections for the user", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn websocket(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Result<Response, Error> {
+    let token = extract_bearer_token(&headers).ok_or(Error::Unauthorized)?;
+    let connection = app_state.realtime.register(
+        auth_user.id,
+        auth_user.tenant_id,
+        app_state.config.realtime.max_connections_per_user,
+    )?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let subscription = services::subscription(conn.as_mut(), &auth_user, BTreeSet::new()).await?;
+    drop(conn);
+    let receiver = app_state
+        .realtime
+        .subscribe(&app_state.database.pool)
+        .await?;
+
+    Ok(ws
+        .protocols([WEBSOCKET_TOKEN_PROTOCOL])
+        .on_upgrade(move |socket| {
+            run_connection(socket, app_state, token, connection, subscription, receiver)
+        }))
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text.into())).await
+}
+
+/// Answer one client message, updating the subscribed topics
+fn handle_client_message(
+    text: &str,
+    subscription: &mut Subscription,
+    connection: &ConnectionGuard,
+) -> ServerMessage {
+    let reply = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::Subscribe { topic }) => {
+            subscription.topics.insert(topic);
+            ServerMessage::Subscribed { topic }
+        }
+        Ok(ClientMessage::Unsubscribe { topic }) => {
+            subscription.topics.remove(&topic);
+            ServerMessage::Unsubscribed { topic }
+        }
+        Ok(ClientMessage::Ping) => return ServerMessage::Pong,
+        Err(e) => {
+            return ServerMessage::Error {
+                message: format!("Invalid message: {e}"),
+            };
+        }
+    };
+    connection.set_topics(&subscription.topics);
+    reply
+}
+
+/// Whether the connection's session or API key is still valid
+async fn session_is_valid(app_state: &AppState, token: &str) -> bool {
+    let mut conn = match app_state.database.pool.acquire().await {
+        Ok(conn) => conn,
+        // Keep connections open through database hiccups
+        Err(_) => return true,
+    };
+    match auth_services::validate_token_cached(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        token,
+    )
+    .await
+    {
+        Ok(Some((user, _))) => user.is_active,
+        Ok(None) => false,
+        Err(e) => {
+            tracing::warn!("Failed to check WebSocket session: {}", e);
+            true
+        }
+    }
+}
+
+async fn run_connection(
+    mut socket: WebSocket,
+    app_state: AppState,
+    token: String,
+    connection: ConnectionGuard,
+    mut subscription: Subscription,
+    mut receiver: broadcast::Receiver<Arc<RealtimeMessage>>,
+) {
+    let ready = ServerMessage::Ready {
+        connection_id: connection.id(),
+    };
+    if send(&mut socket, &ready).await.is_err() {
+        return;
+    }
+
+    let mut heartbeat = tokio::time::interval(app_state.config.realtime_heartbeat_interval());
+    heartbeat.tick().await;
+    loop {
+        let reply = tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    handle_client_message(&text, &mut subscription, &connection)
+                }
+                Some(Ok(Message::Binary(_))) => ServerMessage::Error {
+                    message: "Messages must be JSON text".to_string(),
+                },
+                // Pings are answered by the WebSocket library
+                Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            published = receiver.recv() => match published {
+                Ok(message) if subscription.admits(&message) => ServerMessage::Message {
+                    topic: message.topic,
+                    data: message.data.clone(),
+                },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => ServerMessage::Lagged { missed },
+                Err(RecvError::Closed) => break,
+            },
+            _ = heartbeat.tick() => {
+                // Signing out or deactivation ends the connection at the next heartbeat
+                if !session_is_valid(&app_state, &token).await {
+                    let close = CloseFrame {
+                        code: close_code::POLICY,
+                        reason: "Session ended".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(close))).await;
+                    break;
+                }
+                if socket.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+        if send(&mut socket, &reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/realtime/connections",
+    tag = "Realtime",
+    summary = "List WebSocket connections",
+    description = "List the open WebSocket connections of the tenant's users on this server instance (admin only)",
+    responses(
+        (status = 200, description = "Open connections", body = ApiResponse<Vec<ConnectionInfo>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin role required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_connections(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Json<ApiResponse<Vec<ConnectionInfo>>> {
+    Json(ApiResponse::success(
+        app_state.realtime.connections(auth_user.tenant_id),
+    ))
+}
+
+/// Stream the user's notifications as Server-Sent Events
+///
+/// Each notification is sent as a `notification` message. A client too slow to
+/// keep up gets a `lagged` message with the number of notifications it missed.
+#[utoipa::path(
+    get,
+    path = "/notifications/stream",
+    tag = "Realtime",
+    summary = "Stream notifications",
+    description = "Stream the notifications sent to the current user from now on as Server-Sent Events",
+    responses(
+        (status = 200, description = "Stream of notifications as they are sent", content_type = "text/event-stream", body = Notification),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn stream_notifications(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, Error> {
+    let receiver = app_state
+        .realtime
+        .subscribe_notifications(&app_state.database.pool, auth_user.id)
+        .await?;
+    Ok(sse::stream(receiver, |message| {
+        SseEvent::default()
+            .event("notification")
+            .json_data(&message.data)
+            .ok()
+    }))
+}
+
+/// WebSocket and notification stream routes (authentication required)
+pub fn realtime_routes() -> Router<AppState> {
+    Router::new()
+        .route("/ws", get(websocket))
+        .route("/notifications/stream", get(stream_notifications))
+}
+
+/// Realtime administration routes (admin role required)
+pub fn realtime_admin_routes() -> Router<AppState> {
+    Router::new().route("/connections", get(list_connections))
+}
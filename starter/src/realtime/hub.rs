@@ -0,0 +1,234 @@
+use crate::core::sse::{self, KeyedBroadcast};
+use crate::realtime::models::{ConnectionInfo, RealtimeMessage, Topic};
+use crate::{DbPool, Error, Result};
+use chrono::Utc;
+use sqlx::postgres::PgListener;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OnceCell, broadcast};
+use tracing::warn;
+use uuid::Uuid;
+
+/// Channel realtime messages are published on, as JSON
+pub const REALTIME_CHANNEL: &str = "realtime";
+
+/// Open WebSocket connections and the messages published to them
+///
+/// The first subscriber starts a background task that listens on the
+/// [`REALTIME_CHANNEL`] notification channel. WebSockets receive every message
+/// and filter them by their subscription; Server-Sent Events streams receive
+/// one topic's messages, or one user's notifications.
+#[derive(Default)]
+pub struct RealtimeHub {
+    sender: OnceCell<broadcast::Sender<Arc<RealtimeMessage>>>,
+    topics: Arc<KeyedBroadcast<Topic, RealtimeMessage>>,
+    notifications: Arc<KeyedBroadcast<Uuid, RealtimeMessage>>,
+    connections: Mutex<HashMap<Uuid, ConnectionInfo>>,
+}
+
+impl RealtimeHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive messages published from now on
+    pub async fn subscribe(
+        &self,
+        pool: &DbPool,
+    ) -> Result<broadcast::Receiver<Arc<RealtimeMessage>>> {
+        Ok(self.listen(pool).await?.subscribe())
+    }
+
+    /// Receive messages of one topic published from now on
+    pub async fn subscribe_topic(
+        &self,
+        pool: &DbPool,
+        topic: Topic,
+    ) -> Result<broadcast::Receiver<Arc<RealtimeMessage>>> {
+        self.listen(pool).await?;
+        Ok(self.topics.subscribe(&topic))
+    }
+
+    /// Receive the notifications sent to `user_id` from now on
+    pub async fn subscribe_notifications(
+        &self,
+        pool: &DbPool,
+        user_id: Uuid,
+    ) -> Result<broadcast::Receiver<Arc<RealtimeMessage>>> {
+        self.listen(pool).await?;
+        Ok(self.notifications.subscribe(&user_id))
+    }
+
+    /// Start listening for published messages unless already listening
+    async fn listen(&self, pool: &DbPool) -> Result<&broadcast::Sender<Arc<RealtimeMessage>>> {
+        self.sender
+            .get_or_try_init(|| async {
+                let mut listener = PgListener::connect_with(pool)
+                    .await
+                    .map_err(Error::from_sqlx)?;
+                listener
+                    .listen(REALTIME_CHANNEL)
+                    .await
+                    .map_err(Error::from_sqlx)?;
+
+                let (sender, _) = broadcast::channel(sse::DEFAULT_CAPACITY);
+                tokio::spawn(forward_messages(
+                    listener,
+                    sender.clone(),
+                    self.topics.clone(),
+                    self.notifications.clone(),
+                ));
+                Ok::<_, Error>(sender)
+            })
+            .await
+    }
+
+    /// Add a connection of `user_id` to the registry, allowing at most
+    /// `max_per_user` of them at once (0 = unlimited)
+    ///
+    /// The connection is removed when the returned guard is dropped.
+    pub fn register(
+        self: &Arc<Self>,
+        user_id: Uuid,
+        tenant_id: Uuid,
+        max_per_user: u32,
+    ) -> Result<ConnectionGuard> {
+        let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if max_per_user > 0
+            && connections
+                .values()
+                .filter(|connection| connection.user_id == user_id)
+                .count()
+                >= max_per_user as usize
+        {
+            return Err(Error::QuotaExceeded(format!(
+                "At most {max_per_user} WebSocket connections per user"
+            )));
+        }
+        let id = Uuid::new_v4();
+        connections.insert(
+            id,
+            ConnectionInfo {
+                id,
+                user_id,
+                tenant_id,
+                topics: BTreeSet::new(),
+                connected_at: Utc::now(),
+            },
+        );
+        Ok(ConnectionGuard {
+            hub: self.clone(),
+            id,
+        })
+    }
+
+    /// Open connections of a tenant's users, oldest first
+    pub fn connections(&self, tenant_id: Uuid) -> Vec<ConnectionInfo> {
+        let connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        let mut found: Vec<_> = connections
+            .values()
+            .filter(|connection| connection.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        found.sort_by_key(|connection| connection.connected_at);
+        found
+    }
+
+    fn set_topics(&self, id: Uuid, topics: &BTreeSet<Topic>) {
+        let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(connection) = connections.get_mut(&id) {
+            connection.topics = topics.clone();
+        }
+    }
+}
+
+/// A registered connection, removed from the registry on drop
+pub struct ConnectionGuard {
+    hub: Arc<RealtimeHub>,
+    id: Uuid,
+}
+
+impl ConnectionGuard {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Record the connection's topics for the registry
+    pub fn set_topics(&self, topics: &BTreeSet<Topic>) {
+        self.hub.set_topics(self.id, topics);
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self
+            .hub
+            .connections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        connections.remove(&self.id);
+    }
+}
+
+/// Send notified messages to subscribers
+async fn forward_messages(
+    mut listener: PgListener,
+    sender: broadcast::Sender<Arc<RealtimeMessage>>,
+    topics: Arc<KeyedBroadcast<Topic, RealtimeMessage>>,
+    notifications: Arc<KeyedBroadcast<Uuid, RealtimeMessage>>,
+) {
+    loop {
+        // The listener reconnects by itself; messages published while it was
+        // disconnected are lost
+        let notification = match listener.recv().await {
+            Ok(notification) => notification,
+            Err(e) => {
+                warn!("Realtime listener error: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+        if sender.receiver_count() == 0 && topics.is_empty() && notifications.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RealtimeMessage>(notification.payload()) {
+            Ok(message) => {
+                let message = Arc::new(message);
+                if let (Topic::Notifications, Some(user_id)) = (message.topic, message.user_id) {
+                    notifications.send(&user_id, message.clone());
+                }
+                topics.send(&message.topic, message.clone());
+                let _ = sender.send(message);
+            }
+            Err(e) => warn!("Ignoring malformed realtime message: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_limits_connections_per_user() {
+        let hub = Arc::new(RealtimeHub::new());
+        let (user_id, tenant_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first = hub.register(user_id, tenant_id, 2).unwrap();
+        let _second = hub.register(user_id, tenant_id, 2).unwrap();
+        assert!(hub.register(user_id, tenant_id, 2).is_err());
+        assert!(hub.register(Uuid::new_v4(), tenant_id, 2).is_ok());
+
+        first.set_topics(&BTreeSet::from([Topic::Alerts]));
+        let connections = hub.connections(tenant_id);
+        assert_eq!(connections.len(), 2);
+        let registered = connections.iter().find(|c| c.id == first.id()).unwrap();
+        assert_eq!(registered.topics, BTreeSet::from([Topic::Alerts]));
+        assert!(hub.connections(Uuid::new_v4()).is_empty());
+
+        // Closed connections make room again
+        drop(first);
+        assert!(hub.register(user_id, tenant_id, 2).is_ok());
+    }
+}
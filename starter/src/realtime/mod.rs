@@ -0,0 +1,15 @@
+//! Live updates over one WebSocket connection
+//!
+//! Signed-in clients open `/api/v1/ws` and subscribe to topics: status
+//! changes of the tasks they can see, alert status changes, and notifications
+//! addressed to them. Messages are published on a Postgres notification
+//! channel, so changes made by the worker or any server instance reach the
+//! connections of every instance.
+
+pub mod api;
+pub mod hub;
+pub mod models;
+pub mod services;
+
+pub use hub::RealtimeHub;
+pub use models::{Notification, RealtimeMessage, Topic};
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use uuid::Uuid;
+
+/// What a connection can subscribe to
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Topic {
+    /// Status changes of tasks the user can see
+    Tasks,
+    /// Status changes of monitoring alerts
+    Alerts,
+    /// Notifications addressed to the user
+    Notifications,
+}
+
+/// A message published on the realtime channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RealtimeMessage {
+    pub topic: Topic,
+    /// Tenant whose users receive it; `None` only for notifications, which are addressed to one user
+    pub tenant_id: Option<Uuid>,
+    /// The task's creator or the notification's recipient
+    pub user_id: Option<Uuid>,
+    /// Organization of a task
+    pub org_id: Option<Uuid>,
+    pub data: serde_json::Value,
+}
+
+/// Notification sent to one user's connections
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Notification {
+    /// What happened, e.g. `data_export_ready`
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub data: serde_json::Value,
+}
+
+/// Message a client sends
+#[derive(Debug, Clone, PartialEq, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ClientMessage {
+    Subscribe { topic: Topic },
+    Unsubscribe { topic: Topic },
+    Ping,
+}
+
+/// Message the server sends
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerMessage {
+    /// Sent once the connection is open
+    Ready {
+        connection_id: Uuid,
+    },
+    Subscribed {
+        topic: Topic,
+    },
+    Unsubscribed {
+        topic: Topic,
+    },
+    Message {
+        topic: Topic,
+        #[schema(value_type = Object)]
+        data: serde_json::Value,
+    },
+    /// The connection fell behind and missed this many messages
+    Lagged {
+        missed: u64,
+    },
+    Pong,
+    Error {
+        message: String,
+    },
+}
+
+/// An open connection, as listed to admins
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ConnectionInfo {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    pub topics: BTreeSet<Topic>,
+    pub connected_at: DateTime<Utc>,
+}
+
+/// Which published messages a connection receives
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub user_id: Uuid,
+    pub tenant_id: Uuid,
+    /// Whether the user sees every task of the tenant, as moderators do
+    pub all_tasks: bool,
+    /// Organizations whose tasks the user can see, read when the connection opened
+    pub org_ids: HashSet<Uuid>,
+    pub topics: BTreeSet<Topic>,
+}
+
+impl Subscription {
+    pub fn admits(&self, message: &RealtimeMessage) -> bool {
+        if !self.topics.contains(&message.topic) {
+            return false;
+        }
+        match message.tenant_id {
+            Some(tenant_id) if tenant_id != self.tenant_id => return false,
+            // Task and alert messages without a tenant can't be attributed to one
+            None if message.topic != Topic::Notifications => return false,
+            _ => {}
+        }
+        match message.topic {
+            Topic::Tasks => {
+                self.all_tasks
+                    || message.user_id == Some(self.user_id)
+                    || message
+                        .org_id
+                        .is_some_and(|org_id| self.org_ids.contains(&org_id))
+            }
+            Topic::Alerts => true,
+            Topic::Notifications => message.user_id == Some(self.user_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn subscription(all_tasks: bool) -> Subscription {
+        Subscription {
+            user_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            all_tasks,
+            org_ids: HashSet::new(),
+            topics: BTreeSet::from([Topic::Tasks, Topic::Notifications]),
+        }
+    }
+
+    fn message(
+        topic: Topic,
+        subscription: &Subscription,
+        user_id: Option<Uuid>,
+    ) -> RealtimeMessage {
+        RealtimeMessage {
+            topic,
+            tenant_id: Some(subscription.tenant_id),
+            user_id,
+            org_id: None,
+            data: json!({}),
+        }
+    }
+
+    #[test]
+    fn test_subscription_admits_visible_messages() {
+        let mut user = subscription(false);
+        let own_task = message(Topic::Tasks, &user, Some(user.user_id));
+        let other_task = message(Topic::Tasks, &user, Some(Uuid::new_v4()));
+        assert!(user.admits(&own_task));
+        assert!(!user.admits(&other_task));
+
+        // Tasks of the user's organizations are visible too
+        let org_id = Uuid::new_v4();
+        user.org_ids.insert(org_id);
+        assert!(user.admits(&RealtimeMessage {
+            org_id: Some(org_id),
+            ..other_task.clone()
+        }));
+
+        let notification = message(Topic::Notifications, &user, Some(user.user_id));
+        assert!(user.admits(&notification));
+        let someone_else = message(Topic::Notifications, &user, Some(Uuid::new_v4()));
+        assert!(!user.admits(&someone_else));
+
+        // Unsubscribed topics, other tenants and tenant-less alerts are filtered out
+        let alert = message(Topic::Alerts, &user, None);
+        assert!(!user.admits(&alert));
+        user.topics.insert(Topic::Alerts);
+        assert!(user.admits(&alert));
+        assert!(!user.admits(&RealtimeMessage {
+            tenant_id: Some(Uuid::new_v4()),
+            ..alert.clone()
+        }));
+        assert!(!user.admits(&RealtimeMessage {
+            tenant_id: None,
+            ..alert
+        }));
+        assert!(!user.admits(&RealtimeMessage {
+            tenant_id: None,
+            ..own_task.clone()
+        }));
+        assert!(user.admits(&RealtimeMessage {
+            tenant_id: None,
+            ..notification
+        }));
+        assert!(!user.admits(&RealtimeMessage {
+            tenant_id: Some(Uuid::new_v4()),
+            ..own_task
+        }));
+
+        let moderator = subscription(true);
+        assert!(moderator.admits(&message(Topic::Tasks, &moderator, None)));
+    }
+
+    #[test]
+    fn test_protocol_messages() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"subscribe","topic":"tasks"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe {
+                topic: Topic::Tasks
+            }
+        );
+        assert!(
+            serde_json::from_str::<ClientMessage>(r#"{"type":"subscribe","topic":"x"}"#).is_err()
+        );
+
+        let message = ServerMessage::Message {
+            topic: Topic::Alerts,
+            data: json!({"status": "active"}),
+        };
+        assert_eq!(
+            serde_json::to_value(message).unwrap(),
+            json!({"type": "message", "topic": "alerts", "data": {"status": "active"}})
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use crate::auth::AuthUser;
+use crate::orgs::services as org_services;
+use crate::rbac::{Permission, Resource, services as rbac_services};
+use crate::realtime::hub::REALTIME_CHANNEL;
+use crate::realtime::models::{Notification, RealtimeMessage, Subscription, Topic};
+use crate::{DbConn, Error, Result};
+use std::collections::{BTreeSet, HashSet};
+use uuid::Uuid;
+
+/// Subscription of `auth_user` to `topics`
+///
+/// Role and memberships are read once; changes apply to subscriptions made afterwards.
+pub async fn subscription(
+    conn: &mut DbConn,
+    auth_user: &AuthUser,
+    topics: BTreeSet<Topic>,
+) -> Result<Subscription> {
+    let all_tasks =
+        rbac_services::require_staff_permission(auth_user, Resource::Tasks, Permission::Read)
+            .is_ok();
+    let org_ids = match all_tasks {
+        true => HashSet::new(),
+        false => org_services::user_org_ids(conn, auth_user.id)
+            .await?
+            .into_iter()
+            .collect(),
+    };
+    Ok(Subscription {
+        user_id: auth_user.id,
+        tenant_id: auth_user.tenant_id,
+        all_tasks,
+        org_ids,
+        topics,
+    })
+}
+
+/// Publish a message to the connections of every server instance
+///
+/// Inside a transaction the message is only sent once it commits.
+pub async fn publish(conn: &mut DbConn, message: &RealtimeMessage) -> Result<()> {
+    let payload = serde_json::to_string(message)
+        .map_err(|e| Error::Internal(format!("Failed to encode realtime message: {e}")))?;
+    sqlx::query!("SELECT pg_notify($1, $2)", REALTIME_CHANNEL, payload)
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Send a notification to the connections of `user_id` subscribed to notifications
+pub async fn notify_user(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    notification: Notification,
+) -> Result<()> {
+    let data = serde_json::to_value(notification)
+        .map_err(|e| Error::Internal(format!("Failed to encode notification: {e}")))?;
+    publish(
+        conn,
+        &RealtimeMessage {
+            topic: Topic::Notifications,
+            // User ids are unique across tenants
+            tenant_id: None,
+            user_id: Some(user_id),
+            org_id: None,
+            data,
+        },
+    )
+    .await
+}
@@ -0,0 +1,906 @@
+use crate::auth::services as auth_services;
+use crate::rbac::UserRole;
+use crate::scim::models::{
+    ERROR_SCHEMA, MAX_RESULTS, PatchOp, RESOURCE_TYPE_SCHEMA, SCIM_CONTENT_TYPE,
+    SERVICE_PROVIDER_CONFIG_SCHEMA, ScimErrorBody, ScimFilter, ScimGroup, ScimGroupRequest,
+    ScimListQuery, ScimListResponse, ScimMember, ScimPatchRequest, ScimTenant, ScimUser,
+    ScimUserChanges, ScimUserRecord, ScimUserRequest,
+};
+use crate::scim::services as scim_services;
+use crate::tenants::{DEFAULT_TENANT_ID, services as tenant_services};
+use crate::users::{activity, models::UserActivityAction};
+use crate::{AppConfig, AppState, DbConn, Error};
+use axum::{
+    Router,
+    extract::{Extension, Path, Query, Request, State, rejection::JsonRejection},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    routing::get,
+};
+use secrecy::ExposeSecret;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Error rendered as a SCIM error message
+pub struct ScimError(pub Error);
+
+impl From<Error> for ScimError {
+    fn from(error: Error) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        let scim_type = match &self.0 {
+            Error::ValidationError { field, .. } if field == "filter" => Some("invalidFilter"),
+            Error::ValidationError { field, .. } if field == "displayName" => Some("mutability"),
+            Error::ValidationError { .. } | Error::InvalidInput(_) => Some("invalidValue"),
+            Error::UserAlreadyExists | Error::EmailAlreadyExists | Error::UsernameAlreadyExists => {
+                Some("uniqueness")
+            }
+            _ => None,
+        };
+        let detail = match &self.0 {
+            Error::NotFound(msg) | Error::InvalidInput(msg) => msg.clone(),
+            error => error.to_string(),
+        };
+
+        // Reuse the status mapping (and logging) of the regular error responses
+        let status = self.0.into_response().status();
+        let detail = if status.is_server_error() {
+            "Internal server error".to_string()
+        } else {
+            detail
+        };
+
+        scim_response(
+            status,
+            &ScimErrorBody {
+                schemas: vec![ERROR_SCHEMA.to_string()],
+                status: status.as_u16().to_string(),
+                scim_type: scim_type.map(str::to_string),
+                detail,
+            },
+        )
+    }
+}
+
+fn scim_response<T: Serialize>(status: StatusCode, body: &T) -> Response {
+    let mut response = (status, Json(body)).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(SCIM_CONTENT_TYPE),
+    );
+    response
+}
+
+/// Base URL of the SCIM API, used for `meta.location`
+fn base_url(config: &AppConfig) -> String {
+    format!(
+        "{}/api/v1/scim/v2",
+        config.server.public_url.trim_end_matches('/')
+    )
+}
+
+/// Compare without short-circuiting so the token can't be guessed byte by byte
+fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Bearer token authentication for the SCIM API
+///
+/// `STARTER__SCIM_TOKEN` provisions the default tenant. With tenancy enabled,
+/// a token issued for a tenant provisions that tenant's users only. The API
+/// is only served when one of them can be used.
+pub async fn scim_auth_middleware(
+    State(app_state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, ScimError> {
+    let configured = app_state.config.scim_token.as_ref();
+    let tenancy = app_state.config.tenancy.enabled;
+    if configured.is_none() && !tenancy {
+        return Err(Error::NotFound("SCIM provisioning is not enabled".to_string()).into());
+    }
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?
+        .to_string();
+
+    let tenant_id = if configured
+        .is_some_and(|expected| tokens_match(token.as_bytes(), expected.expose_secret().as_bytes()))
+    {
+        Some(DEFAULT_TENANT_ID)
+    } else if tenancy && token.starts_with(tenant_services::SCIM_TOKEN_PREFIX) {
+        let mut conn = app_state
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::from_sqlx)?;
+        tenant_services::find_scim_tenant(conn.as_mut(), &token).await?
+    } else {
+        None
+    };
+    let tenant_id = tenant_id.ok_or(Error::Unauthorized)?;
+
+    req.extensions_mut().insert(ScimTenant(tenant_id));
+    Ok(next.run(req).await)
+}
+
+/// Ids in the path that aren't UUIDs can't name a user
+fn parse_user_id(id: &str) -> Result<Uuid, Error> {
+    Uuid::parse_str(id).map_err(|_| Error::NotFound("User not found".to_string()))
+}
+
+fn parse_group_id(id: &str) -> Result<UserRole, Error> {
+    UserRole::from_str(id).map_err(|_| Error::NotFound("Group not found".to_string()))
+}
+
+fn parse_member_ids(members: &[ScimMember]) -> Result<Vec<Uuid>, Error> {
+    members
+        .iter()
+        .map(|m| {
+            Uuid::parse_str(&m.value).map_err(|_| Error::validation("members", "Unknown member"))
+        })
+        .collect()
+}
+
+fn json_body<T>(payload: Result<Json<T>, JsonRejection>) -> Result<T, Error> {
+    payload
+        .map(|Json(body)| body)
+        .map_err(|rejection| Error::InvalidInput(rejection.body_text()))
+}
+
+/// Record what a SCIM update changed in the account's activity trail
+async fn record_user_changes(
+    conn: &mut DbConn,
+    before: &ScimUserRecord,
+    after: &ScimUserRecord,
+    password_changed: bool,
+) {
+    let mut fields = Vec::new();
+    if before.username != after.username {
+        fields.push("username");
+    }
+    if before.email != after.email {
+        fields.push("email");
+    }
+    if before.external_id != after.external_id {
+        fields.push("external_id");
+    }
+
+    if !fields.is_empty() {
+        activity::record_activity(
+            conn,
+            after.id,
+            None,
+            UserActivityAction::ProfileUpdated,
+            json!({ "fields": fields, "source": "scim" }),
+        )
+        .await;
+    }
+    if before.is_active != after.is_active {
+        activity::record_activity(
+            conn,
+            after.id,
+            None,
+            UserActivityAction::StatusChanged,
+            json!({ "is_active": after.is_active, "source": "scim" }),
+        )
+        .await;
+    }
+    if password_changed {
+        activity::record_activity(
+            conn,
+            after.id,
+            None,
+            UserActivityAction::PasswordReset,
+            json!({ "source": "scim" }),
+        )
+        .await;
+    }
+}
+
+/// Set the role of `user_ids` in the tenant and record each change
+///
+/// While `admin_requires_approval` is set, admin is only granted through an
+/// approved role request, so adding anyone new to the admin group fails.
+async fn change_roles(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    user_ids: &[Uuid],
+    role: UserRole,
+    admin_requires_approval: bool,
+) -> Result<(), Error> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let changed =
+        scim_services::set_role(conn, tenant_id, user_ids, role, admin_requires_approval).await?;
+    for (user_id, from) in changed {
+        activity::record_activity(
+            conn,
+            user_id,
+            None,
+            UserActivityAction::RoleChanged,
+            json!({ "from": from, "to": role, "source": "scim" }),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Move members out of `role`, back to the `user` role
+///
+/// Everyone holds at least the `user` role, so removing from that group does nothing.
+async fn remove_members(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    role: UserRole,
+    user_ids: Option<&[Uuid]>,
+) -> Result<(), Error> {
+    if role == UserRole::User {
+        return Ok(());
+    }
+    let current = scim_services::find_members(conn, tenant_id, role).await?;
+    let removed: Vec<Uuid> = current
+        .iter()
+        .filter_map(|m| Uuid::parse_str(&m.value).ok())
+        .filter(|id| user_ids.is_none_or(|ids| ids.contains(id)))
+        .collect();
+    change_roles(conn, tenant_id, &removed, UserRole::User, false).await
+}
+
+/// Make `user_ids` the exact member list of `role`
+async fn replace_members(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    role: UserRole,
+    user_ids: &[Uuid],
+    admin_requires_approval: bool,
+) -> Result<(), Error> {
+    let current = scim_services::find_members(conn, tenant_id, role).await?;
+    let removed: Vec<Uuid> = current
+        .iter()
+        .filter_map(|m| Uuid::parse_str(&m.value).ok())
+        .filter(|id| !user_ids.contains(id))
+        .collect();
+    // Grant first, so a rejected grant leaves the current members in place
+    change_roles(conn, tenant_id, user_ids, role, admin_requires_approval).await?;
+    if role != UserRole::User {
+        change_roles(conn, tenant_id, &removed, UserRole::User, false).await?;
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/ServiceProviderConfig",
+    tag = "SCIM",
+    summary = "Service provider configuration",
+    description = "Features of the SCIM API supported by the starter",
+    responses(
+        (status = 200, description = "Supported features", body = Object),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn get_service_provider_config(State(app_state): State<AppState>) -> Response {
+    scim_response(
+        StatusCode::OK,
+        &json!({
+            "schemas": [SERVICE_PROVIDER_CONFIG_SCHEMA],
+            "patch": { "supported": true },
+            "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+            "filter": { "supported": true, "maxResults": MAX_RESULTS },
+            "changePassword": { "supported": true },
+            "sort": { "supported": false },
+            "etag": { "supported": false },
+            "authenticationSchemes": [{
+                "type": "oauthbearertoken",
+                "name": "Bearer token",
+                "description": "The token configured with STARTER__SCIM_TOKEN",
+                "primary": true
+            }],
+            "meta": {
+                "resourceType": "ServiceProviderConfig",
+                "location": format!("{}/ServiceProviderConfig", base_url(&app_state.config))
+            }
+        }),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/ResourceTypes",
+    tag = "SCIM",
+    summary = "Resource types",
+    description = "The User and Group resource types",
+    responses(
+        (status = 200, description = "Resource types", body = Object),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn list_resource_types(State(app_state): State<AppState>) -> Response {
+    let base_url = base_url(&app_state.config);
+    let resource_type = |name: &str, endpoint: &str, schema: &str| -> Value {
+        json!({
+            "schemas": [RESOURCE_TYPE_SCHEMA],
+            "id": name,
+            "name": name,
+            "endpoint": endpoint,
+            "schema": schema,
+            "meta": {
+                "resourceType": "ResourceType",
+                "location": format!("{base_url}/ResourceTypes/{name}")
+            }
+        })
+    };
+    let resources = vec![
+        resource_type("User", "/Users", crate::scim::models::USER_SCHEMA),
+        resource_type("Group", "/Groups", crate::scim::models::GROUP_SCHEMA),
+    ];
+
+    scim_response(StatusCode::OK, &ScimListResponse::new(resources, 2, 1))
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Users",
+    tag = "SCIM",
+    summary = "List users",
+    description = "Provisioned accounts, optionally filtered with `userName`, `externalId` or `emails` `eq`",
+    params(ScimListQuery),
+    responses(
+        (status = 200, description = "Matching users", body = ScimListResponse<ScimUser>),
+        (status = 400, description = "Unsupported filter", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn list_users(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Query(query): Query<ScimListQuery>,
+) -> Result<Response, ScimError> {
+    let filter = query.filter.as_deref().map(ScimFilter::parse).transpose()?;
+    let (offset, limit) = query.page();
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let (users, total) =
+        scim_services::list_users(conn.as_mut(), tenant_id, filter.as_ref(), offset, limit).await?;
+
+    let base_url = base_url(&app_state.config);
+    let users = users
+        .into_iter()
+        .map(|u| ScimUser::from_record(u, &base_url))
+        .collect();
+    Ok(scim_response(
+        StatusCode::OK,
+        &ScimListResponse::new(users, total, offset + 1),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/scim/v2/Users",
+    tag = "SCIM",
+    summary = "Provision user",
+    description = "Create an account with the `user` role; without a password it can only sign in through the identity provider",
+    request_body = ScimUserRequest,
+    responses(
+        (status = 201, description = "User created", body = ScimUser),
+        (status = 400, description = "Invalid attributes", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 409, description = "Username, email or externalId already taken", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn create_user(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    payload: Result<Json<ScimUserRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let changes = json_body(payload)?.into_changes()?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let user = scim_services::create_user(conn.as_mut(), tenant_id, changes).await?;
+
+    let user = ScimUser::from_record(user, &base_url(&app_state.config));
+    let mut response = scim_response(StatusCode::CREATED, &user);
+    if let Ok(location) = header::HeaderValue::from_str(&user.meta.location) {
+        response.headers_mut().insert(header::LOCATION, location);
+    }
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Users/{id}",
+    tag = "SCIM",
+    summary = "Get user",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User", body = ScimUser),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 404, description = "User not found or deleted", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn get_user(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+) -> Result<Response, ScimError> {
+    let id = parse_user_id(&id)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let user = scim_services::find_user(conn.as_mut(), tenant_id, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    Ok(scim_response(
+        StatusCode::OK,
+        &ScimUser::from_record(user, &base_url(&app_state.config)),
+    ))
+}
+
+/// Store `changes` for user `id` of the tenant and record what changed
+async fn update_user(
+    app_state: &AppState,
+    tenant_id: Uuid,
+    id: Uuid,
+    changes: impl FnOnce(&ScimUserRecord) -> Result<ScimUserChanges, Error>,
+) -> Result<Response, ScimError> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let before = scim_services::find_user(conn.as_mut(), tenant_id, id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    let changes = changes(&before)?;
+    let password_changed = changes.password.is_some();
+    let after = scim_services::update_user(conn.as_mut(), tenant_id, id, changes).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        id,
+    )
+    .await;
+    record_user_changes(conn.as_mut(), &before, &after, password_changed).await;
+
+    Ok(scim_response(
+        StatusCode::OK,
+        &ScimUser::from_record(after, &base_url(&app_state.config)),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/scim/v2/Users/{id}",
+    tag = "SCIM",
+    summary = "Replace user",
+    description = "Replace the mapped attributes; `active: false` deactivates the account and signs it out",
+    params(("id" = Uuid, Path, description = "User ID")),
+    request_body = ScimUserRequest,
+    responses(
+        (status = 200, description = "User updated", body = ScimUser),
+        (status = 400, description = "Invalid attributes", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 404, description = "User not found or deleted", body = ScimErrorBody),
+        (status = 409, description = "Username, email or externalId already taken", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn replace_user(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+    payload: Result<Json<ScimUserRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let id = parse_user_id(&id)?;
+    let request = json_body(payload)?;
+
+    update_user(&app_state, tenant_id, id, |_| request.into_changes()).await
+}
+
+#[utoipa::path(
+    patch,
+    path = "/scim/v2/Users/{id}",
+    tag = "SCIM",
+    summary = "Patch user",
+    description = "Apply `add`, `replace` and `remove` operations to the mapped attributes",
+    params(("id" = Uuid, Path, description = "User ID")),
+    request_body = ScimPatchRequest,
+    responses(
+        (status = 200, description = "User updated", body = ScimUser),
+        (status = 400, description = "Invalid operation", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 404, description = "User not found or deleted", body = ScimErrorBody),
+        (status = 409, description = "Username, email or externalId already taken", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn patch_user(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+    payload: Result<Json<ScimPatchRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let id = parse_user_id(&id)?;
+    let request = json_body(payload)?;
+
+    update_user(&app_state, tenant_id, id, |user| {
+        let mut changes = ScimUserChanges::from_record(user);
+        for operation in &request.operations {
+            changes.apply(operation)?;
+        }
+        Ok(changes)
+    })
+    .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/scim/v2/Users/{id}",
+    tag = "SCIM",
+    summary = "Deprovision user",
+    description = "Soft-delete the account; it is purged after the deleted user retention period",
+    params(("id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 204, description = "User deleted"),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 404, description = "User not found or already deleted", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn delete_user(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ScimError> {
+    let id = parse_user_id(&id)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    scim_services::delete_user(conn.as_mut(), tenant_id, id).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        id,
+    )
+    .await;
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        None,
+        UserActivityAction::AccountDeleted,
+        json!({ "source": "scim" }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Build the group for `role` in the tenant, with its members unless excluded
+async fn load_group(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    role: UserRole,
+    with_members: bool,
+    base_url: &str,
+) -> Result<ScimGroup, Error> {
+    let members = if with_members {
+        Some(scim_services::find_members(conn, tenant_id, role).await?)
+    } else {
+        None
+    };
+    Ok(ScimGroup::new(role, members, base_url))
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Groups",
+    tag = "SCIM",
+    summary = "List groups",
+    description = "One group per role (`user`, `moderator`, `admin`), optionally filtered with `displayName eq`",
+    params(ScimListQuery),
+    responses(
+        (status = 200, description = "Matching groups", body = ScimListResponse<ScimGroup>),
+        (status = 400, description = "Unsupported filter", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn list_groups(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Query(query): Query<ScimListQuery>,
+) -> Result<Response, ScimError> {
+    let roles: Vec<UserRole> = match query.filter.as_deref().map(ScimFilter::parse).transpose()? {
+        None => vec![UserRole::User, UserRole::Moderator, UserRole::Admin],
+        Some(ScimFilter::DisplayName(name)) => UserRole::from_str(&name).into_iter().collect(),
+        Some(_) => {
+            return Err(
+                Error::validation("filter", "Groups can be filtered by displayName").into(),
+            );
+        }
+    };
+    let (offset, limit) = query.page();
+    let with_members = !query.excludes("members");
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let base_url = base_url(&app_state.config);
+    let mut groups = Vec::new();
+    for role in roles.iter().skip(offset as usize).take(limit as usize) {
+        groups.push(load_group(conn.as_mut(), tenant_id, *role, with_members, &base_url).await?);
+    }
+
+    Ok(scim_response(
+        StatusCode::OK,
+        &ScimListResponse::new(groups, roles.len() as i64, offset + 1),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/scim/v2/Groups/{id}",
+    tag = "SCIM",
+    summary = "Get group",
+    params(
+        ("id" = String, Path, description = "Role name"),
+        ScimListQuery
+    ),
+    responses(
+        (status = 200, description = "Group", body = ScimGroup),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 404, description = "Group not found", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn get_group(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+    Query(query): Query<ScimListQuery>,
+) -> Result<Response, ScimError> {
+    let role = parse_group_id(&id)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let group = load_group(
+        conn.as_mut(),
+        tenant_id,
+        role,
+        !query.excludes("members"),
+        &base_url(&app_state.config),
+    )
+    .await?;
+
+    Ok(scim_response(StatusCode::OK, &group))
+}
+
+#[utoipa::path(
+    put,
+    path = "/scim/v2/Groups/{id}",
+    tag = "SCIM",
+    summary = "Replace group members",
+    description = "Give the listed users this role; current members left out go back to the `user` role",
+    params(("id" = String, Path, description = "Role name")),
+    request_body = ScimGroupRequest,
+    responses(
+        (status = 200, description = "Group updated", body = ScimGroup),
+        (status = 400, description = "Unknown member or renamed group", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 403, description = "Granting admin requires an approved role request", body = ScimErrorBody),
+        (status = 404, description = "Group not found", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn replace_group(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+    payload: Result<Json<ScimGroupRequest>, JsonRejection>,
+) -> Result<Response, ScimError> {
+    let role = parse_group_id(&id)?;
+    let request = json_body(payload)?;
+    if request
+        .display_name
+        .as_deref()
+        .is_some_and(|name| !name.eq_ignore_ascii_case(role.as_str()))
+    {
+        return Err(
+            Error::validation("displayName", "Groups map to roles and can't be renamed").into(),
+        );
+    }
+    let user_ids = parse_member_ids(&request.members)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    replace_members(
+        conn.as_mut(),
+        tenant_id,
+        role,
+        &user_ids,
+        app_state.config.auth.admin_role_requires_approval,
+    )
+    .await?;
+    let group = load_group(
+        conn.as_mut(),
+        tenant_id,
+        role,
+        true,
+        &base_url(&app_state.config),
+    )
+    .await?;
+
+    Ok(scim_response(StatusCode::OK, &group))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/scim/v2/Groups/{id}",
+    tag = "SCIM",
+    summary = "Patch group members",
+    description = "Add members to give them this role, remove members to move them back to the `user` role",
+    params(("id" = String, Path, description = "Role name")),
+    request_body = ScimPatchRequest,
+    responses(
+        (status = 204, description = "Group updated"),
+        (status = 400, description = "Unknown member or unsupported operation", body = ScimErrorBody),
+        (status = 401, description = "Invalid SCIM token", body = ScimErrorBody),
+        (status = 403, description = "Granting admin requires an approved role request", body = ScimErrorBody),
+        (status = 404, description = "Group not found", body = ScimErrorBody)
+    ),
+    security(("scim_token" = []))
+)]
+pub async fn patch_group(
+    State(app_state): State<AppState>,
+    Extension(ScimTenant(tenant_id)): Extension<ScimTenant>,
+    Path(id): Path<String>,
+    payload: Result<Json<ScimPatchRequest>, JsonRejection>,
+) -> Result<StatusCode, ScimError> {
+    let role = parse_group_id(&id)?;
+    let request = json_body(payload)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let admin_requires_approval = app_state.config.auth.admin_role_requires_approval;
+    for operation in &request.operations {
+        let op = operation.op()?;
+        let path = operation.path.as_deref().unwrap_or("members");
+
+        // A filtered path names a single member to remove
+        if let Some(filter) = path
+            .strip_prefix("members[")
+            .and_then(|p| p.strip_suffix(']'))
+        {
+            let ScimFilter::Value(member) = ScimFilter::parse(filter)? else {
+                return Err(Error::validation("path", "Members are selected by value").into());
+            };
+            if op != PatchOp::Remove {
+                return Err(
+                    Error::validation("path", "Only remove accepts a member filter").into(),
+                );
+            }
+            let user_id = parse_user_id(&member)?;
+            remove_members(conn.as_mut(), tenant_id, role, Some(&[user_id])).await?;
+            continue;
+        }
+        if !path.eq_ignore_ascii_case("members") {
+            return Err(Error::validation("displayName", "Only members can be changed").into());
+        }
+
+        // Without a path, Okta and Entra ID send `{"members": [...]}`
+        let members = match &operation.value {
+            Some(Value::Object(attributes)) if operation.path.is_none() => {
+                attributes.get("members").cloned()
+            }
+            value => value.clone(),
+        };
+        let members: Option<Vec<ScimMember>> = members
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|_| Error::validation("members", "Invalid members value"))?;
+        let user_ids = members.as_deref().map(parse_member_ids).transpose()?;
+
+        match (op, user_ids) {
+            (PatchOp::Add, Some(user_ids)) => {
+                change_roles(
+                    conn.as_mut(),
+                    tenant_id,
+                    &user_ids,
+                    role,
+                    admin_requires_approval,
+                )
+                .await?
+            }
+            (PatchOp::Replace, Some(user_ids)) => {
+                replace_members(
+                    conn.as_mut(),
+                    tenant_id,
+                    role,
+                    &user_ids,
+                    admin_requires_approval,
+                )
+                .await?
+            }
+            (PatchOp::Remove, user_ids) => {
+                remove_members(conn.as_mut(), tenant_id, role, user_ids.as_deref()).await?
+            }
+            (_, None) => return Err(Error::validation("members", "A value is required").into()),
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// SCIM 2.0 routes (authenticated with the SCIM token)
+pub fn scim_routes() -> Router<AppState> {
+    Router::new()
+        .route("/ServiceProviderConfig", get(get_service_provider_config))
+        .route("/ResourceTypes", get(list_resource_types))
+        .route("/Users", get(list_users).post(create_user))
+        .route(
+            "/Users/{id}",
+            get(get_user)
+                .put(replace_user)
+                .patch(patch_user)
+                .delete(delete_user),
+        )
+        .route("/Groups", get(list_groups))
+        .route(
+            "/Groups/{id}",
+            get(get_group).put(replace_group).patch(patch_group),
+        )
+}
@@ -0,0 +1,509 @@
+//! SCIM 2.0 resources and messages (RFC 7643 / RFC 7644)
+//!
+//! Only the attributes the starter can map are decoded: `userName`, the
+//! primary email, `active`, `externalId` and `password` for users, and
+//! `members` for groups. Unknown attributes such as `name` are ignored.
+
+use crate::rbac::UserRole;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+pub const SERVICE_PROVIDER_CONFIG_SCHEMA: &str =
+    "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+pub const RESOURCE_TYPE_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:ResourceType";
+
+/// Media type of every SCIM response
+pub const SCIM_CONTENT_TYPE: &str = "application/scim+json";
+
+/// Page size used when a list request has no `count`, and the largest allowed
+pub const MAX_RESULTS: i64 = 200;
+
+/// Tenant whose users a request's SCIM token provisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScimTenant(pub Uuid);
+
+/// Account as seen by the SCIM queries
+#[derive(Debug, Clone)]
+pub struct ScimUserRecord {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub is_active: bool,
+    pub external_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    pub resource_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<DateTime<Utc>>,
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ScimEmail {
+    pub value: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+/// Group a user belongs to, i.e. their role
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ScimGroupRef {
+    pub value: String,
+    pub display: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    pub schemas: Vec<String>,
+    pub id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub user_name: String,
+    pub emails: Vec<ScimEmail>,
+    pub active: bool,
+    pub groups: Vec<ScimGroupRef>,
+    pub meta: ScimMeta,
+}
+
+impl ScimUser {
+    pub fn from_record(record: ScimUserRecord, base_url: &str) -> Self {
+        Self {
+            schemas: vec![USER_SCHEMA.to_string()],
+            id: record.id,
+            external_id: record.external_id,
+            user_name: record.username,
+            emails: vec![ScimEmail {
+                value: record.email,
+                kind: Some("work".to_string()),
+                primary: true,
+            }],
+            active: record.is_active,
+            groups: vec![ScimGroupRef {
+                value: record.role.clone(),
+                display: record.role,
+            }],
+            meta: ScimMeta {
+                resource_type: "User".to_string(),
+                created: Some(record.created_at),
+                last_modified: Some(record.updated_at),
+                location: format!("{base_url}/Users/{}", record.id),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ScimMember {
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// One role exposed as a group; `id` and `displayName` are the role name
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    pub schemas: Vec<String>,
+    pub id: String,
+    pub display_name: String,
+    /// Omitted when the request excludes `members`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<ScimMember>>,
+    pub meta: ScimMeta,
+}
+
+impl ScimGroup {
+    pub fn new(role: UserRole, members: Option<Vec<ScimMember>>, base_url: &str) -> Self {
+        Self {
+            schemas: vec![GROUP_SCHEMA.to_string()],
+            id: role.as_str().to_string(),
+            display_name: role.as_str().to_string(),
+            members,
+            meta: ScimMeta {
+                resource_type: "Group".to_string(),
+                created: None,
+                last_modified: None,
+                location: format!("{base_url}/Groups/{}", role.as_str()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListResponse<T> {
+    pub schemas: Vec<String>,
+    pub total_results: i64,
+    pub start_index: i64,
+    pub items_per_page: i64,
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+impl<T> ScimListResponse<T> {
+    pub fn new(resources: Vec<T>, total_results: i64, start_index: i64) -> Self {
+        Self {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len() as i64,
+            resources,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimErrorBody {
+    pub schemas: Vec<String>,
+    /// HTTP status code, as a string
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    pub detail: String,
+}
+
+/// Query parameters of the list endpoints
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimListQuery {
+    /// Equality filter, e.g. `userName eq "alice"`
+    pub filter: Option<String>,
+    /// 1-based index of the first result
+    pub start_index: Option<i64>,
+    /// Page size (default and max 200)
+    pub count: Option<i64>,
+    /// Comma-separated attributes to leave out; only `members` is honored
+    pub excluded_attributes: Option<String>,
+}
+
+impl ScimListQuery {
+    /// 0-based offset and page size
+    pub fn page(&self) -> (i64, i64) {
+        let offset = self.start_index.unwrap_or(1).max(1) - 1;
+        let limit = self.count.unwrap_or(MAX_RESULTS).clamp(0, MAX_RESULTS);
+        (offset, limit)
+    }
+
+    pub fn excludes(&self, attribute: &str) -> bool {
+        self.excluded_attributes.as_deref().is_some_and(|list| {
+            list.split(',')
+                .any(|a| a.trim().eq_ignore_ascii_case(attribute))
+        })
+    }
+}
+
+/// Body of `POST /Users` and `PUT /Users/{id}`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUserRequest {
+    pub user_name: String,
+    pub external_id: Option<String>,
+    #[serde(default)]
+    pub emails: Vec<ScimEmail>,
+    /// Defaults to true
+    #[schema(value_type = Option<bool>)]
+    #[serde(default, deserialize_with = "deserialize_scim_bool")]
+    pub active: Option<bool>,
+    /// Without one the account can only sign in through the identity provider
+    pub password: Option<String>,
+}
+
+impl ScimUserRequest {
+    pub fn into_changes(self) -> Result<ScimUserChanges> {
+        let email = primary_email(&self.emails)
+            .ok_or_else(|| Error::validation("emails", "At least one email is required"))?;
+        Ok(ScimUserChanges {
+            username: self.user_name,
+            email,
+            external_id: self.external_id,
+            active: self.active.unwrap_or(true),
+            password: self.password,
+        })
+    }
+}
+
+/// Full set of mapped attributes to store for a user
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScimUserChanges {
+    pub username: String,
+    pub email: String,
+    pub external_id: Option<String>,
+    pub active: bool,
+    pub password: Option<String>,
+}
+
+impl ScimUserChanges {
+    pub fn from_record(record: &ScimUserRecord) -> Self {
+        Self {
+            username: record.username.clone(),
+            email: record.email.clone(),
+            external_id: record.external_id.clone(),
+            active: record.is_active,
+            password: None,
+        }
+    }
+
+    /// Apply one PATCH operation on a user
+    pub fn apply(&mut self, operation: &PatchOperation) -> Result<()> {
+        let op = operation.op()?;
+        let Some(path) = operation.path.as_deref() else {
+            // Without a path the value holds attribute/value pairs
+            let Some(Value::Object(attributes)) = &operation.value else {
+                return Err(Error::validation(
+                    "value",
+                    "An object is required when no path is given",
+                ));
+            };
+            for (path, value) in attributes {
+                self.set(path, op, Some(value))?;
+            }
+            return Ok(());
+        };
+        self.set(path, op, operation.value.as_ref())
+    }
+
+    fn set(&mut self, path: &str, op: PatchOp, value: Option<&Value>) -> Result<()> {
+        let path = path
+            .strip_prefix(USER_SCHEMA)
+            .map(|p| p.trim_start_matches(':'))
+            .unwrap_or(path)
+            .to_ascii_lowercase();
+
+        if op == PatchOp::Remove {
+            return match path.as_str() {
+                "externalid" => {
+                    self.external_id = None;
+                    Ok(())
+                }
+                _ => Err(Error::validation(
+                    "path",
+                    &format!("Attribute {path} can't be removed"),
+                )),
+            };
+        }
+        let value = value.ok_or_else(|| Error::validation("value", "A value is required"))?;
+
+        match path.as_str() {
+            "username" => self.username = string_value(value, "userName")?,
+            "externalid" => self.external_id = Some(string_value(value, "externalId")?),
+            "active" => self.active = bool_value(value)?,
+            "password" => self.password = Some(string_value(value, "password")?),
+            // Entra ID uses a filtered path for the work email
+            "emails" | "emails.value" | "emails[type eq \"work\"].value" => {
+                self.email = match value {
+                    Value::Array(_) => {
+                        let emails: Vec<ScimEmail> = serde_json::from_value(value.clone())
+                            .map_err(|_| Error::validation("emails", "Invalid emails value"))?;
+                        primary_email(&emails).ok_or_else(|| {
+                            Error::validation("emails", "At least one email is required")
+                        })?
+                    }
+                    _ => string_value(value, "emails")?,
+                }
+            }
+            // Attributes the starter doesn't store
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Body of `PATCH /Users/{id}` and `PATCH /Groups/{id}`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct ScimPatchRequest {
+    #[serde(rename = "Operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct PatchOperation {
+    /// `add`, `replace` or `remove`, in any case
+    pub op: String,
+    pub path: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOp {
+    Add,
+    Replace,
+    Remove,
+}
+
+impl PatchOperation {
+    pub fn op(&self) -> Result<PatchOp> {
+        match self.op.to_ascii_lowercase().as_str() {
+            "add" => Ok(PatchOp::Add),
+            "replace" => Ok(PatchOp::Replace),
+            "remove" => Ok(PatchOp::Remove),
+            _ => Err(Error::validation(
+                "op",
+                &format!("Unsupported operation: {}", self.op),
+            )),
+        }
+    }
+}
+
+/// Body of `PUT /Groups/{id}`
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroupRequest {
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub members: Vec<ScimMember>,
+}
+
+/// Supported `filter` expressions: a single `eq` comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScimFilter {
+    UserName(String),
+    ExternalId(String),
+    Email(String),
+    DisplayName(String),
+    /// Used in member paths, e.g. `members[value eq "<id>"]`
+    Value(String),
+}
+
+impl ScimFilter {
+    pub fn parse(filter: &str) -> Result<Self> {
+        let invalid = || Error::validation("filter", &format!("Unsupported filter: {filter}"));
+
+        let mut parts = filter.trim().splitn(3, char::is_whitespace);
+        let (Some(attribute), Some(op), Some(value)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if !op.eq_ignore_ascii_case("eq") {
+            return Err(invalid());
+        }
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(invalid)?
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+
+        match attribute.to_ascii_lowercase().as_str() {
+            "username" => Ok(Self::UserName(value)),
+            "externalid" => Ok(Self::ExternalId(value)),
+            "emails" | "emails.value" => Ok(Self::Email(value)),
+            "displayname" => Ok(Self::DisplayName(value)),
+            "value" => Ok(Self::Value(value)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Accepts JSON booleans and the "True"/"False" strings Entra ID sends
+fn bool_value(value: &Value) -> Result<bool> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(true),
+        Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err(Error::validation("active", "Must be a boolean")),
+    }
+}
+
+fn string_value(value: &Value, field: &str) -> Result<String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| Error::validation(field, "Must be a string"))
+}
+
+fn deserialize_scim_bool<'de, D>(deserializer: D) -> std::result::Result<Option<bool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    value
+        .map(|v| bool_value(&v))
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Primary email, or the first one when none is marked primary
+fn primary_email(emails: &[ScimEmail]) -> Option<String> {
+    emails
+        .iter()
+        .find(|e| e.primary)
+        .or_else(|| emails.first())
+        .map(|e| e.value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_eq_filters() {
+        assert_eq!(
+            ScimFilter::parse(r#"userName eq "alice""#).unwrap(),
+            ScimFilter::UserName("alice".to_string())
+        );
+        assert_eq!(
+            ScimFilter::parse(r#"emails.value EQ "a\"b@example.com""#).unwrap(),
+            ScimFilter::Email("a\"b@example.com".to_string())
+        );
+        assert!(ScimFilter::parse(r#"userName sw "a""#).is_err());
+        assert!(ScimFilter::parse(r#"title eq "x""#).is_err());
+        assert!(ScimFilter::parse("userName eq alice").is_err());
+    }
+
+    #[test]
+    fn applies_patch_operations() {
+        let mut changes = ScimUserChanges {
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            external_id: None,
+            active: true,
+            password: None,
+        };
+        let operations: ScimPatchRequest = serde_json::from_value(json!({
+            "schemas": [PATCH_OP_SCHEMA],
+            "Operations": [
+                { "op": "Replace", "path": "active", "value": "False" },
+                { "op": "replace", "path": "emails[type eq \"work\"].value", "value": "new@example.com" },
+                { "op": "add", "value": { "externalId": "00u1", "name.givenName": "Alice" } }
+            ]
+        }))
+        .unwrap();
+        for operation in &operations.operations {
+            changes.apply(operation).unwrap();
+        }
+
+        assert!(!changes.active);
+        assert_eq!(changes.email, "new@example.com");
+        assert_eq!(changes.external_id.as_deref(), Some("00u1"));
+
+        let remove_username = PatchOperation {
+            op: "remove".to_string(),
+            path: Some("userName".to_string()),
+            value: None,
+        };
+        assert!(changes.apply(&remove_username).is_err());
+    }
+}
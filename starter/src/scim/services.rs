@@ -0,0 +1,333 @@
+use crate::rbac::UserRole;
+use crate::rbac::audit::{self as rbac_audit, RbacChange};
+use crate::rbac::models::RbacAuditAction;
+use crate::rbac::role_requests;
+use crate::scim::models::{ScimFilter, ScimMember, ScimUserChanges, ScimUserRecord};
+use crate::users::deactivation;
+use crate::users::models::{validate_email, validate_password, validate_username};
+use crate::users::services as user_services;
+use crate::{DbConn, Error, Result};
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Provisioned account of the tenant, unless it was deleted or is a service account
+pub async fn find_user(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    id: Uuid,
+) -> Result<Option<ScimUserRecord>> {
+    sqlx::query_as!(
+        ScimUserRecord,
+        r#"
+        SELECT id, username, email, role, is_active, external_id, created_at, updated_at
+        FROM users
+        WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL AND is_service_account = false
+        "#,
+        id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// One page of the tenant's accounts matching `filter`, oldest first, and the total count
+///
+/// `userName` and `emails` compare case-insensitively, as RFC 7643 defines them.
+pub async fn list_users(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    filter: Option<&ScimFilter>,
+    offset: i64,
+    limit: i64,
+) -> Result<(Vec<ScimUserRecord>, i64)> {
+    let (username, external_id, email) = match filter {
+        None => (None, None, None),
+        Some(ScimFilter::UserName(v)) => (Some(v.as_str()), None, None),
+        Some(ScimFilter::ExternalId(v)) => (None, Some(v.as_str()), None),
+        Some(ScimFilter::Email(v)) => (None, None, Some(v.as_str())),
+        Some(ScimFilter::DisplayName(_) | ScimFilter::Value(_)) => {
+            return Err(Error::validation(
+                "filter",
+                "Users can be filtered by userName, externalId or emails",
+            ));
+        }
+    };
+
+    let users = sqlx::query_as!(
+        ScimUserRecord,
+        r#"
+        SELECT id, username, email, role, is_active, external_id, created_at, updated_at
+        FROM users
+        WHERE tenant_id = $6 AND deleted_at IS NULL AND is_service_account = false
+          AND ($1::text IS NULL OR lower(username) = lower($1))
+          AND ($2::text IS NULL OR external_id = $2)
+          AND ($3::text IS NULL OR lower(email) = lower($3))
+        ORDER BY created_at, id
+        LIMIT $4 OFFSET $5
+        "#,
+        username,
+        external_id,
+        email,
+        limit,
+        offset,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM users
+        WHERE tenant_id = $4 AND deleted_at IS NULL AND is_service_account = false
+          AND ($1::text IS NULL OR lower(username) = lower($1))
+          AND ($2::text IS NULL OR external_id = $2)
+          AND ($3::text IS NULL OR lower(email) = lower($3))
+        "#,
+        username,
+        external_id,
+        email,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok((users, total))
+}
+
+fn validate_changes(changes: &ScimUserChanges) -> Result<Option<String>> {
+    validate_username(&changes.username)?;
+    validate_email(&changes.email)?;
+    changes
+        .password
+        .as_deref()
+        .map(|password| {
+            validate_password(password)?;
+            user_services::hash_password(password)
+        })
+        .transpose()
+}
+
+/// Create a provisioned account with the `user` role in the tenant
+///
+/// Accounts created without a password are locked to sign-in through the
+/// identity provider.
+pub async fn create_user(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    changes: ScimUserChanges,
+) -> Result<ScimUserRecord> {
+    let password_hash = validate_changes(&changes)?
+        .unwrap_or_else(|| user_services::LOCKED_PASSWORD_HASH.to_string());
+
+    sqlx::query_as!(
+        ScimUserRecord,
+        r#"
+        INSERT INTO users (username, email, password_hash, role, is_active, external_id, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, username, email, role, is_active, external_id, created_at, updated_at
+        "#,
+        changes.username,
+        changes.email,
+        password_hash,
+        UserRole::User.as_str(),
+        changes.active,
+        changes.external_id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Store the mapped attributes of a provisioned account of the tenant
+///
+/// Deactivating the account signs it out everywhere. A replaced username is
+/// kept in the account's username history.
+pub async fn update_user(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    id: Uuid,
+    changes: ScimUserChanges,
+) -> Result<ScimUserRecord> {
+    let password_hash = validate_changes(&changes)?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let previous_username = user_services::locked_username(&mut tx, id).await?;
+    let user = sqlx::query_as!(
+        ScimUserRecord,
+        r#"
+        UPDATE users
+        SET username = $2,
+            email = $3,
+            external_id = $4,
+            is_active = $5,
+            -- The identity provider's status replaces any temporary suspension
+            suspended_until = CASE WHEN is_active = $5 THEN suspended_until END,
+            password_hash = COALESCE($6, password_hash),
+            updated_at = NOW()
+        WHERE id = $1 AND tenant_id = $7 AND deleted_at IS NULL AND is_service_account = false
+        RETURNING id, username, email, role, is_active, external_id, created_at, updated_at
+        "#,
+        id,
+        changes.username,
+        changes.email,
+        changes.external_id,
+        changes.active,
+        password_hash,
+        tenant_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    user_services::record_username_change(&mut tx, id, &previous_username, &user.username).await?;
+    if !changes.active {
+        deactivation::revoke_access(&mut tx, id, None).await?;
+    } else if changes.password.is_some() {
+        sqlx::query!(
+            "UPDATE sessions SET is_active = false WHERE user_id = $1",
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    }
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(user)
+}
+
+/// Deprovision an account of the tenant: it is soft-deleted and purged after the retention period
+pub async fn delete_user(conn: &mut DbConn, tenant_id: Uuid, id: Uuid) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET is_active = false, deleted_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NULL AND is_service_account = false
+        "#,
+        id,
+        tenant_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    deactivation::revoke_access(&mut tx, id, None).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(())
+}
+
+/// Accounts of the tenant holding `role`, by username
+pub async fn find_members(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    role: UserRole,
+) -> Result<Vec<ScimMember>> {
+    let members = sqlx::query!(
+        r#"
+        SELECT id, username
+        FROM users
+        WHERE role = $1 AND tenant_id = $2 AND deleted_at IS NULL AND is_service_account = false
+        ORDER BY username
+        "#,
+        role.as_str(),
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(members
+        .into_iter()
+        .map(|m| ScimMember {
+            value: m.id.to_string(),
+            display: Some(m.username),
+        })
+        .collect())
+}
+
+/// Give each account in `user_ids` the role `role`
+///
+/// Returns the previous role of every account that changed, each also
+/// recorded in the RBAC audit log. Unknown or deleted accounts, and those of
+/// other tenants, fail the whole change, as does granting admin to anyone who
+/// isn't one yet while `admin_requires_approval` is set.
+pub async fn set_role(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    user_ids: &[Uuid],
+    role: UserRole,
+    admin_requires_approval: bool,
+) -> Result<Vec<(Uuid, String)>> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let found = sqlx::query!(
+        r#"
+        SELECT id, role FROM users
+        WHERE id = ANY($1) AND tenant_id = $2 AND deleted_at IS NULL
+          AND is_service_account = false
+        "#,
+        user_ids,
+        tenant_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let mut distinct = user_ids.to_vec();
+    distinct.sort();
+    distinct.dedup();
+    if found.len() != distinct.len() {
+        return Err(Error::validation("members", "Unknown member"));
+    }
+    for member in &found {
+        role_requests::require_admin_approval(
+            admin_requires_approval,
+            role,
+            member.role.parse().ok(),
+        )?;
+    }
+
+    let changed = sqlx::query!(
+        r#"
+        UPDATE users u
+        SET role = $2, role_expires_at = NULL, role_revert_to = NULL, updated_at = NOW()
+        FROM users old
+        WHERE u.id = old.id AND u.id = ANY($1) AND u.tenant_id = $3 AND u.role <> $2
+        RETURNING u.id, old.role
+        "#,
+        &distinct,
+        role.as_str(),
+        tenant_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    for change in &changed {
+        rbac_audit::record_change(
+            &mut tx,
+            RbacChange {
+                actor_id: None,
+                action: RbacAuditAction::UserRoleChanged,
+                target_id: change.id,
+                before: Some(serde_json::json!({ "role": change.role })),
+                after: Some(serde_json::json!({ "role": role })),
+                reason: Some("SCIM provisioning".to_string()),
+            },
+        )
+        .await?;
+    }
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(changed.into_iter().map(|r| (r.id, r.role)).collect())
+}
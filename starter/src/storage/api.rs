@@ -0,0 +1,52 @@
+use crate::storage::{PRIVATE_PREFIX, content_type};
+use crate::{AppState, Error, api::ErrorResponse};
+use axum::{
+    Router,
+    body::Body,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::Response,
+    routing::get,
+};
+
+/// Download a stored file such as an avatar (publicly accessible)
+#[utoipa::path(
+    get,
+    path = "/files/{key}",
+    params(
+        ("key" = String, Path, description = "File key, e.g. avatars/<user_id>.png")
+    ),
+    responses(
+        (status = 200, description = "File contents", content_type = "application/octet-stream"),
+        (status = 400, description = "Invalid file key", body = ErrorResponse),
+        (status = 404, description = "File not found", body = ErrorResponse)
+    ),
+    tag = "Files"
+)]
+pub async fn get_file(
+    State(app_state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<Response, Error> {
+    if key.starts_with(PRIVATE_PREFIX) {
+        return Err(Error::NotFound("File not found".to_string()));
+    }
+
+    let bytes = app_state
+        .storage
+        .get(&key)
+        .await?
+        .ok_or_else(|| Error::NotFound("File not found".to_string()))?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type(&key))
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CACHE_CONTROL, "public, max-age=3600")
+        .body(Body::from(bytes))
+        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+}
+
+/// Public file routes (no authentication required)
+pub fn files_public_routes() -> Router<AppState> {
+    Router::new().route("/{*key}", get(get_file))
+}
@@ -0,0 +1,101 @@
+use crate::storage::{FileStorage, validate_key};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Keeps files in a directory on the local disk
+#[derive(Debug, Clone)]
+pub struct LocalFileStorage {
+    root: PathBuf,
+    base_url: String,
+}
+
+impl LocalFileStorage {
+    /// Store files under `root`, linking to them below `base_url`
+    pub fn new(root: impl Into<PathBuf>, base_url: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+fn io_error(action: &str, key: &str, e: std::io::Error) -> Error {
+    tracing::error!("Failed to {action} file '{key}': {e}");
+    Error::Internal(format!("Failed to {action} file"))
+}
+
+#[async_trait]
+impl FileStorage for LocalFileStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("store", key, e))?;
+        }
+
+        // Write then rename so readers never see a partial file
+        let tmp = path.with_file_name(format!(".{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&tmp, bytes)
+            .await
+            .map_err(|e| io_error("store", key, e))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| io_error("store", key, e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("read", key, e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error("delete", key, e)),
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{key}", self.base_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_storage_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalFileStorage::new(dir.path(), "/api/v1/files/");
+
+        storage.put("avatars/a.png", b"one".to_vec()).await.unwrap();
+        storage.put("avatars/a.png", b"two".to_vec()).await.unwrap();
+        assert_eq!(
+            storage.get("avatars/a.png").await.unwrap(),
+            Some(b"two".to_vec())
+        );
+        assert_eq!(storage.url("avatars/a.png"), "/api/v1/files/avatars/a.png");
+
+        storage.delete("avatars/a.png").await.unwrap();
+        storage.delete("avatars/a.png").await.unwrap();
+        assert_eq!(storage.get("avatars/a.png").await.unwrap(), None);
+
+        assert!(storage.get("../outside").await.is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! File storage
+//!
+//! Uploaded files are written through the [`FileStorage`] trait so the
+//! backend can be swapped; the default keeps them on the local disk and
+//! serves them from `/api/v1/files`, except for keys under [`PRIVATE_PREFIX`].
+
+pub mod api;
+pub mod local;
+
+pub use local::LocalFileStorage;
+
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+/// Keys under this prefix are never served publicly, e.g. user data exports
+pub const PRIVATE_PREFIX: &str = "private/";
+
+/// Stores files under slash-separated keys such as `avatars/<id>.png`
+#[async_trait]
+pub trait FileStorage: Send + Sync {
+    /// Write a file, replacing any existing one under the key
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<()>;
+
+    /// Read a file, or `None` when nothing is stored under the key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Remove a file; missing files are not an error
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// URL clients download the file from
+    fn url(&self, key: &str) -> String;
+}
+
+/// Check a key is a relative path of plain segments
+///
+/// Segments may use letters, digits, `-`, `_` and `.` but can't be empty
+/// or start with a dot, which rules out `..` and hidden files.
+pub fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with('.')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation("key", "Invalid file key"))
+    }
+}
+
+/// Content type served for a key, from its extension
+pub fn content_type(key: &str) -> &'static str {
+    match key
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+    {
+        Some(ext) if ext == "png" => "image/png",
+        Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+        Some(ext) if ext == "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("avatars/abc-123.png").is_ok());
+        assert!(validate_key("file_1.txt").is_ok());
+
+        assert!(validate_key("").is_err());
+        assert!(validate_key("/etc/passwd").is_err());
+        assert!(validate_key("avatars/../secret").is_err());
+        assert!(validate_key("avatars//a.png").is_err());
+        assert!(validate_key("avatars/.hidden").is_err());
+        assert!(validate_key("avatars\\a.png").is_err());
+        assert!(validate_key("a b.png").is_err());
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type("avatars/a.png"), "image/png");
+        assert_eq!(content_type("a.JPG"), "image/jpeg");
+        assert_eq!(content_type("a.webp"), "image/webp");
+        assert_eq!(content_type("a"), "application/octet-stream");
+    }
+}
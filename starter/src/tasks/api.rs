@@ -1,23 +1,49 @@
 use axum::{
     Extension, Router,
     extract::{Path, Query, State},
-    response::Json,
+    http::header,
+    response::{
+        IntoResponse, Json, Response,
+        sse::{Event as SseEvent, Sse},
+    },
     routing::{get, post},
 };
+use futures_util::Stream;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::convert::Infallible;
 use uuid::Uuid;
 
 use crate::{
     AppState, Error,
-    api::{ApiResponse, ErrorResponse},
+    api::{ApiResponse, ApiVersion, ErrorResponse},
+    audit::{self, Actor, AuditResource, AuditResourceType},
     auth::AuthUser,
-    rbac::services as rbac_services,
+    core::sse,
+    orgs::{OrgRole, services as org_services},
+    rbac::{Permission, Resource, services as rbac_services, sharing},
+    realtime::{Topic, services as realtime_services},
     tasks::{
+        archive, circuit, export, limits,
         processor::TaskProcessor,
-        types::{CreateTaskRequest, TaskFilter, TaskPriority, TaskResponse, TaskStats, TaskStatus},
+        queue, schedules, templates,
+        types::{
+            ArchivedTaskResponse, CircuitBreakerStatus, CreateTaskRequest,
+            CreateTaskTemplateRequest, ExportFormat, SchedulePreview, SchedulePreviewRequest, Task,
+            TaskAttempt, TaskFilter, TaskFromTemplateRequest, TaskPage, TaskPriority,
+            TaskQueueState, TaskQueueStats, TaskQuota, TaskResponse, TaskStats, TaskStatus,
+            TaskTemplate, UpdateTaskTemplateRequest,
+        },
     },
+    tenants::services as tenant_services,
+    users::{activity, models::UserActivityAction},
 };
 
+const MAX_SEARCH_QUERY_LEN: usize = 200;
+
+/// Page size of task lists without a `limit`
+const DEFAULT_LIST_LIMIT: i64 = 100;
+
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateTaskApiRequest {
     pub task_type: String,
@@ -27,6 +53,14 @@ pub struct CreateTaskApiRequest {
     pub scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(default)]
     pub metadata: std::collections::HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Execution timeout override in seconds
+    pub timeout_seconds: Option<i32>,
+    /// Return the existing pending/running task with this key instead of creating a new one
+    pub dedupe_key: Option<String>,
+    /// Organization to scope the task to; its members can see it
+    pub org_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
@@ -34,14 +68,29 @@ pub struct TaskQueryParams {
     pub task_type: Option<String>,
     pub status: Option<String>,
     pub priority: Option<String>,
+    pub tag: Option<String>,
+    /// Full-text search over payload and metadata values, e.g. `bob@example.com`
+    /// or `"weekly report" -draft`
+    pub q: Option<String>,
+    /// Only tasks scoped to this organization
+    pub org_id: Option<Uuid>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
+pub struct TaskExportParams {
+    /// `csv` (default) or `ndjson`
+    pub format: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct RegisterTaskTypeRequest {
     pub task_type: String,
     pub description: String,
+    /// Default execution timeout in seconds for tasks of this type
+    #[serde(default)]
+    pub timeout_seconds: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -49,6 +98,7 @@ pub struct TaskTypeResponse {
     pub task_type: String,
     pub description: Option<String>,
     pub is_active: bool,
+    pub timeout_seconds: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -64,7 +114,8 @@ pub struct TaskTypeResponse {
     responses(
         (status = 200, description = "Task created", body = ApiResponse<TaskResponse>),
         (status = 400, description = "Invalid request", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Task submission rate limit or quota exceeded", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
@@ -75,7 +126,17 @@ pub async fn create_task(
     Extension(auth_user): Extension<AuthUser>,
     Json(payload): Json<CreateTaskApiRequest>,
 ) -> Result<Json<ApiResponse<crate::tasks::types::TaskResponse>>, Error> {
-    use crate::tasks::types::TaskPriority;
+    let task = submit_task(&app_state, &auth_user, payload).await?;
+    Ok(Json(ApiResponse::success(task)))
+}
+
+/// Check limits, validate and create a task on behalf of `auth_user`
+async fn submit_task(
+    app_state: &AppState,
+    auth_user: &AuthUser,
+    payload: CreateTaskApiRequest,
+) -> Result<TaskResponse, Error> {
+    rbac_services::check_permission(auth_user, Resource::Tasks, Permission::Write)?;
 
     let priority = match payload.priority.as_deref() {
         Some("low") => TaskPriority::Low,
@@ -92,17 +153,34 @@ pub async fn create_task(
         .await
         .map_err(|e| Error::Internal(format!("Failed to acquire database connection: {e}")))?;
 
-    let task_type_exists = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM task_types WHERE task_type = $1 AND is_active = true)",
-        payload.task_type
+    // Reject clients that submit tasks faster than the configured per-user limit
+    limits::check_submission_rate(
+        conn.as_mut(),
+        auth_user.id,
+        app_state.config.tasks.rate_limit_per_minute,
     )
-    .fetch_one(&mut *conn)
-    .await
-    .map_err(|e| Error::Internal(format!("Failed to validate task type: {e}")))?;
+    .await?;
 
-    let is_valid = task_type_exists.ok_or_else(|| {
-        Error::Internal("Database query returned null for task type validation".to_string())
-    })?;
+    limits::check_quota(
+        conn.as_mut(),
+        auth_user.id,
+        auth_user.role,
+        &app_state.config.tasks.quotas,
+    )
+    .await?;
+
+    let is_valid = queue::is_registered(
+        conn.as_mut(),
+        &app_state.cache,
+        app_state.config.task_type_cache_ttl(),
+        &payload.task_type,
+    )
+    .await?;
+
+    let org_id = auth_user.org_scope(payload.org_id)?;
+    if let Some(org_id) = org_id {
+        org_services::require_org_role(conn.as_mut(), auth_user, org_id, OrgRole::Member).await?;
+    }
 
     if !is_valid {
         return Err(Error::validation(
@@ -125,6 +203,22 @@ pub async fn create_task(
         request = request.with_metadata(key, value);
     }
 
+    for tag in payload.tags {
+        request = request.with_tag(tag);
+    }
+
+    if let Some(timeout_seconds) = payload.timeout_seconds {
+        request = request.with_timeout_seconds(timeout_seconds);
+    }
+
+    if let Some(dedupe_key) = payload.dedupe_key {
+        request = request.with_dedupe_key(dedupe_key);
+    }
+
+    if let Some(org_id) = org_id {
+        request = request.with_org_id(org_id);
+    }
+
     // Validate the request for security and correctness
     if let Err(e) = request.validate() {
         return Err(Error::validation("request", &e));
@@ -142,7 +236,16 @@ pub async fn create_task(
         .await
         .map_err(|e| Error::Internal(format!("Failed to create task: {e}")))?;
 
-    Ok(Json(ApiResponse::success(task.into())))
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::TaskCreated,
+        serde_json::json!({ "task_id": task.id, "task_type": task.task_type }),
+    )
+    .await;
+
+    Ok(task.into())
 }
 
 /// Get a task by ID
@@ -151,7 +254,7 @@ pub async fn create_task(
     path = "/tasks/{id}",
     tag = "Tasks",
     summary = "Get task",
-    description = "Get a task by its ID",
+    description = "Get a task by its ID. In v1 a missing task returns 200 with null `data`; v2 returns 404.",
     params(
         ("id" = Uuid, Path, description = "Task ID")
     ),
@@ -179,26 +282,50 @@ pub async fn get_task(
         .await
         .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?;
 
-    // Check RBAC authorization - Admin/Moderator can access any task, users only their own
+    // Admin/Moderator can access any task, users their own, their organizations' and those shared with them
     if let Some(ref task_data) = task {
-        rbac_services::can_access_task(&auth_user, task_data.created_by)?;
+        check_task_access(
+            &app_state,
+            &auth_user,
+            task_data,
+            OrgRole::Member,
+            Permission::Read,
+        )
+        .await?;
     }
 
     Ok(Json(ApiResponse::success(task.map(|t| t.into()))))
 }
 
+/// [`get_task`] in the response shape of the version called
+pub async fn get_task_versioned(
+    version: ApiVersion,
+    state: State<AppState>,
+    path: Path<Uuid>,
+    auth_user: Extension<AuthUser>,
+) -> Result<Response, Error> {
+    let Json(response) = get_task(state, path, auth_user).await?;
+    match version {
+        ApiVersion::V1 => Ok(Json(response).into_response()),
+        ApiVersion::V2 => {
+            let task = response.data.flatten().ok_or(Error::TaskNotFound)?;
+            Ok(Json(ApiResponse::success(task)).into_response())
+        }
+    }
+}
+
 /// List tasks with optional filtering
 #[utoipa::path(
     get,
     path = "/tasks",
     tag = "Tasks",
     summary = "List tasks",
-    description = "List tasks with optional filtering",
+    description = "List tasks with optional filtering and full-text search over payload and metadata. v1 returns an array of tasks; v2 returns a `TaskPage`.",
     params(
         TaskQueryParams
     ),
     responses(
-        (status = 200, description = "List of tasks", body = ApiResponse<Vec<TaskResponse>>),
+        (status = 200, description = "List of tasks (a `TaskPage` in v2)", body = ApiResponse<Vec<TaskResponse>>),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     ),
     security(
@@ -210,6 +337,113 @@ pub async fn list_tasks(
     Query(params): Query<TaskQueryParams>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> Result<Json<ApiResponse<Vec<crate::tasks::types::TaskResponse>>>, Error> {
+    let filter = list_filter(params, &auth_user)?;
+
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    let tasks = processor
+        .list_tasks(filter)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to list tasks: {e}")))?;
+
+    let task_responses: Vec<crate::tasks::types::TaskResponse> =
+        tasks.into_iter().map(|t| t.into()).collect();
+    Ok(Json(ApiResponse::success(task_responses)))
+}
+
+/// [`list_tasks`] in the response shape of the version called
+pub async fn list_tasks_versioned(
+    version: ApiVersion,
+    state: State<AppState>,
+    Query(mut params): Query<TaskQueryParams>,
+    auth_user: Extension<AuthUser>,
+) -> Result<Response, Error> {
+    if version == ApiVersion::V1 {
+        return Ok(list_tasks(state, Query(params), auth_user)
+            .await?
+            .into_response());
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIST_LIMIT).max(0);
+    let offset = params.offset.unwrap_or(0);
+    // One extra row tells whether another page follows
+    params.limit = Some(limit + 1);
+    let Json(response) = list_tasks(state, Query(params), auth_user).await?;
+    let mut items = response.data.unwrap_or_default();
+    let has_more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+
+    Ok(Json(ApiResponse::success(TaskPage {
+        items,
+        limit,
+        offset,
+        has_more,
+    }))
+    .into_response())
+}
+
+/// Export tasks as CSV or NDJSON
+#[utoipa::path(
+    get,
+    path = "/tasks/export",
+    tag = "Tasks",
+    summary = "Export tasks",
+    description = "Stream every task matching the list filters as CSV or NDJSON. Unlike the list endpoint, results are unlimited unless `limit` is set.",
+    params(
+        TaskQueryParams,
+        TaskExportParams
+    ),
+    responses(
+        (status = 200, description = "Task export", content(
+            (String = "text/csv"),
+            (String = "application/x-ndjson")
+        )),
+        (status = 400, description = "Invalid export format or search query", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn export_tasks(
+    State(app_state): State<AppState>,
+    Query(params): Query<TaskQueryParams>,
+    Query(export_params): Query<TaskExportParams>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Response, Error> {
+    let format = match export_params.format.as_deref() {
+        Some(format) => format.parse::<ExportFormat>()?,
+        None => ExportFormat::default(),
+    };
+    let filter = list_filter(params, &auth_user)?;
+
+    let filename = format!(
+        "tasks-{}.{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ"),
+        format.as_str()
+    );
+    let body = export::export_tasks(app_state.database.pool.clone(), filter, format);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Build the list filter shared by the list and export endpoints
+fn list_filter(params: TaskQueryParams, auth_user: &AuthUser) -> Result<TaskFilter, Error> {
+    rbac_services::check_permission(auth_user, Resource::Tasks, Permission::Read)?;
+
     let status = match params.status.as_deref() {
         Some("pending") => Some(TaskStatus::Pending),
         Some("running") => Some(TaskStatus::Running),
@@ -229,37 +463,153 @@ pub async fn list_tasks(
         _ => None,
     };
 
-    // Determine task filtering based on user role
-    let created_by_filter =
-        match rbac_services::has_role_or_higher(&auth_user, crate::rbac::UserRole::Moderator) {
-            true => None,                // Admin/Moderator can see all tasks
-            false => Some(auth_user.id), // Regular users only see their own tasks
-        };
-
-    let filter = TaskFilter {
+    Ok(TaskFilter {
         task_type: params.task_type,
         status,
         priority, // Now safely parsed from input
-        created_by: created_by_filter,
+        created_by: None,
+        org_id: auth_user.org_scope(params.org_id)?,
+        visible_to: visible_to(auth_user),
+        tenant_id: Some(auth_user.tenant_id),
         created_after: None,
         created_before: None,
+        tag: params.tag,
+        search: search_query(params.q)?,
         limit: params.limit,
         offset: params.offset,
+    })
+}
+
+/// Admin/Moderator, and custom roles granting `tasks:read`, see all tasks of
+/// their tenant; users their own and their organizations'
+fn visible_to(auth_user: &AuthUser) -> Option<Uuid> {
+    match rbac_services::require_staff_permission(auth_user, Resource::Tasks, Permission::Read) {
+        Ok(()) => None,
+        Err(_) => Some(auth_user.id),
+    }
+}
+
+/// Check access to a task; the user needs `permission` on tasks, members of
+/// its organization `required` or higher, and a share with `permission` opens
+/// it to anyone else
+async fn check_task_access(
+    app_state: &AppState,
+    auth_user: &AuthUser,
+    task: &Task,
+    required: OrgRole,
+    permission: Permission,
+) -> Result<(), Error> {
+    rbac_services::check_permission(auth_user, Resource::Tasks, permission)?;
+    // Other tenants' tasks look the same as missing ones
+    if task.tenant_id != auth_user.tenant_id {
+        return Err(Error::NotFound("Task not found".to_string()));
+    }
+    if rbac_services::can_access_task(auth_user, task.created_by, permission).is_ok() {
+        return Ok(());
+    }
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let denied = match org_services::can_access_task(
+        conn.as_mut(),
+        auth_user,
+        task.created_by,
+        task.org_id,
+        required,
+        permission,
+    )
+    .await
+    {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
     };
+    if sharing::has_resource_permission(
+        conn.as_mut(),
+        auth_user.id,
+        Resource::Tasks,
+        task.id,
+        permission,
+    )
+    .await?
+    {
+        Ok(())
+    } else {
+        Err(denied)
+    }
+}
 
-    let processor = TaskProcessor::new(
-        app_state.database.clone(),
-        crate::tasks::processor::ProcessorConfig::default(),
-    );
+/// Organizations whose tasks the user can see, for filtering fetched lists
+async fn visible_org_ids(app_state: &AppState, auth_user: &AuthUser) -> Result<Vec<Uuid>, Error> {
+    if visible_to(auth_user).is_none() {
+        return Ok(Vec::new());
+    }
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    org_services::user_org_ids(conn.as_mut(), auth_user.id).await
+}
 
-    let tasks = processor
-        .list_tasks(filter)
+fn task_visible(auth_user: &AuthUser, org_ids: &[Uuid], task: &Task) -> bool {
+    task.tenant_id == auth_user.tenant_id
+        && (rbac_services::can_access_task(auth_user, task.created_by, Permission::Read).is_ok()
+            || task.org_id.is_some_and(|org_id| org_ids.contains(&org_id)))
+}
+
+/// Normalize the `q` search parameter, treating a blank query as no search
+fn search_query(q: Option<String>) -> Result<Option<String>, Error> {
+    let Some(q) = q.map(|q| q.trim().to_string()).filter(|q| !q.is_empty()) else {
+        return Ok(None);
+    };
+    if q.len() > MAX_SEARCH_QUERY_LEN {
+        return Err(Error::validation(
+            "q",
+            &format!("Search query cannot exceed {MAX_SEARCH_QUERY_LEN} characters"),
+        ));
+    }
+    Ok(Some(q))
+}
+
+/// Get the current user's task quota usage
+#[utoipa::path(
+    get,
+    path = "/tasks/quota",
+    tag = "Tasks",
+    summary = "Get task quota",
+    description = "Get the current user's task quota limits and usage",
+    responses(
+        (status = 200, description = "Task quota usage", body = ApiResponse<TaskQuota>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_quota(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<TaskQuota>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
         .await
-        .map_err(|e| Error::Internal(format!("Failed to list tasks: {e}")))?;
+        .map_err(Error::from_sqlx)?;
 
-    let task_responses: Vec<crate::tasks::types::TaskResponse> =
-        tasks.into_iter().map(|t| t.into()).collect();
-    Ok(Json(ApiResponse::success(task_responses)))
+    let quota = limits::get_quota_usage(
+        conn.as_mut(),
+        auth_user.id,
+        auth_user.role,
+        &app_state.config.tasks.quotas,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(quota)))
 }
 
 /// Get task statistics
@@ -268,7 +618,7 @@ pub async fn list_tasks(
     path = "/tasks/stats",
     tag = "Tasks",
     summary = "Get task statistics",
-    description = "Get statistics about tasks (total, pending, completed, failed, etc.)",
+    description = "Get statistics about the caller's tenant's tasks (total, pending, completed, failed, etc.)",
     responses(
         (status = 200, description = "Task statistics", body = ApiResponse<TaskStats>),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
@@ -281,7 +631,7 @@ pub async fn get_stats(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
 ) -> Result<Json<ApiResponse<TaskStats>>, Error> {
-    // System-wide task statistics require elevated permissions
+    // Tenant-wide task statistics require elevated permissions
     rbac_services::require_moderator_or_higher(&auth_user)?;
 
     let processor = TaskProcessor::new(
@@ -290,156 +640,711 @@ pub async fn get_stats(
     );
 
     let stats = processor
-        .get_stats()
+        .get_stats(auth_user.tenant_id)
         .await
         .map_err(|e| Error::Internal(format!("Failed to get stats: {e}")))?;
 
     Ok(Json(ApiResponse::success(stats)))
 }
 
-/// Cancel a task
+/// Get queue depth and latency per task type
 #[utoipa::path(
-    post,
-    path = "/tasks/{id}/cancel",
+    get,
+    path = "/tasks/queue-stats",
     tag = "Tasks",
-    summary = "Cancel task",
-    description = "Cancel a running or pending task",
-    params(
-        ("id" = Uuid, Path, description = "Task ID")
-    ),
+    summary = "Get queue statistics",
+    description = "Get pending count, running count and oldest pending age of the caller's tenant's tasks per task type (moderator or higher)",
     responses(
-        (status = 200, description = "Task cancelled", body = ApiResponse<String>),
-        (status = 404, description = "Task not found", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Queue statistics per task type", body = ApiResponse<Vec<TaskQueueStats>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
-pub async fn cancel_task(
+pub async fn get_queue_stats(
     State(app_state): State<AppState>,
-    Path(task_id): Path<Uuid>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<String>>, Error> {
-    let processor = TaskProcessor::new(
-        app_state.database.clone(),
-        crate::tasks::processor::ProcessorConfig::default(),
-    );
+) -> Result<Json<ApiResponse<Vec<TaskQueueStats>>>, Error> {
+    rbac_services::require_moderator_or_higher(&auth_user)?;
 
-    // First, get the task to check ownership
-    let task = processor
-        .get_task(task_id)
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
         .await
-        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
-        .ok_or(Error::NotFound("Task not found".to_string()))?;
+        .map_err(Error::from_sqlx)?;
 
-    // Check RBAC authorization - Admin/Moderator can cancel any task, users only their own
-    rbac_services::can_access_task(&auth_user, task.created_by)?;
-
-    processor
-        .cancel_task(task_id)
-        .await
-        .map_err(|e| Error::Internal(format!("Failed to cancel task: {e}")))?;
+    let stats = queue::get_queue_stats(conn.as_mut(), Some(auth_user.tenant_id)).await?;
+    Ok(Json(ApiResponse::success(stats)))
+}
 
-    Ok(Json(ApiResponse::success_with_message(
-        "Task cancelled successfully".to_string(),
-        format!("Task {task_id} has been cancelled"),
-    )))
+/// Validate a cron expression and preview its next runs
+#[utoipa::path(
+    post,
+    path = "/tasks/schedules/preview",
+    tag = "Tasks",
+    summary = "Preview cron schedule",
+    description = "Validate a cron expression and return its next run times in the given timezone",
+    request_body = SchedulePreviewRequest,
+    responses(
+        (status = 200, description = "Upcoming run times", body = ApiResponse<SchedulePreview>),
+        (status = 400, description = "Invalid cron expression, timezone or count", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn preview_schedule(
+    Extension(_auth_user): Extension<AuthUser>,
+    Json(request): Json<SchedulePreviewRequest>,
+) -> Result<Json<ApiResponse<SchedulePreview>>, Error> {
+    let preview = schedules::preview_schedule(&request, chrono::Utc::now())?;
+    Ok(Json(ApiResponse::success(preview)))
 }
 
-/// Get dead letter queue (failed tasks)
+/// List task templates
 #[utoipa::path(
     get,
-    path = "/tasks/dead-letter",
+    path = "/tasks/templates",
     tag = "Tasks",
-    summary = "Get dead letter queue",
-    description = "Get all failed tasks in the dead letter queue",
-    params(
-        ("limit" = Option<i64>, Query, description = "Maximum number of tasks to return"),
-        ("offset" = Option<i64>, Query, description = "Number of tasks to skip")
-    ),
+    summary = "List task templates",
+    description = "List reusable task templates",
     responses(
-        (status = 200, description = "Dead letter queue tasks", body = ApiResponse<Vec<TaskResponse>>),
+        (status = 200, description = "Task templates", body = ApiResponse<Vec<TaskTemplate>>),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
-pub async fn get_dead_letter_queue(
+pub async fn list_task_templates(
     State(app_state): State<AppState>,
-    Query(params): Query<TaskQueryParams>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<Vec<crate::tasks::types::TaskResponse>>>, Error> {
-    let processor = TaskProcessor::new(
-        app_state.database.clone(),
-        crate::tasks::processor::ProcessorConfig::default(),
-    );
-
-    let tasks = processor
-        .get_dead_letter_queue(params.limit, params.offset)
+) -> Result<Json<ApiResponse<Vec<TaskTemplate>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
         .await
-        .map_err(|e| Error::Internal(format!("Failed to get dead letter queue: {e}")))?;
-
-    // Filter tasks based on user role - Admin/Moderator see all, users see only their own
-    let filtered_tasks: Vec<crate::tasks::types::TaskResponse> = tasks
-        .into_iter()
-        .filter(|task| {
-            rbac_services::has_role_or_higher(&auth_user, crate::rbac::UserRole::Moderator)
-                || task.created_by == Some(auth_user.id)
-        })
-        .map(|t| t.into())
-        .collect();
+        .map_err(Error::from_sqlx)?;
 
-    Ok(Json(ApiResponse::success(filtered_tasks)))
+    let templates = templates::list_templates(conn.as_mut(), auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(templates)))
 }
 
-/// Retry a failed task
+/// Get a task template by name
 #[utoipa::path(
-    post,
-    path = "/tasks/{id}/retry",
+    get,
+    path = "/tasks/templates/{name}",
     tag = "Tasks",
-    summary = "Retry failed task",
-    description = "Retry a failed task by resetting it to pending status",
+    summary = "Get task template",
+    description = "Get a reusable task template by name",
     params(
-        ("id" = Uuid, Path, description = "Task ID")
+        ("name" = String, Path, description = "Template name")
     ),
     responses(
-        (status = 200, description = "Task retried successfully", body = ApiResponse<String>),
-        (status = 404, description = "Task not found", body = ErrorResponse),
-        (status = 400, description = "Task is not in failed status", body = ErrorResponse),
+        (status = 200, description = "Task template", body = ApiResponse<TaskTemplate>),
+        (status = 404, description = "Template not found", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
-pub async fn retry_task(
+pub async fn get_task_template(
     State(app_state): State<AppState>,
-    Path(task_id): Path<Uuid>,
+    Path(name): Path<String>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<String>>, Error> {
-    let processor = TaskProcessor::new(
-        app_state.database.clone(),
-        crate::tasks::processor::ProcessorConfig::default(),
-    );
-
-    // First, get the task to check ownership
-    let task = processor
-        .get_task(task_id)
+) -> Result<Json<ApiResponse<TaskTemplate>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
         .await
-        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
-        .ok_or(Error::NotFound("Task not found".to_string()))?;
+        .map_err(Error::from_sqlx)?;
 
-    // Check RBAC authorization - Admin/Moderator can retry any task, users only their own
-    rbac_services::can_access_task(&auth_user, task.created_by)?;
-
-    processor.retry_task(task_id).await.map_err(|e| match e {
-        crate::tasks::types::TaskError::NotFound(_) => {
-            Error::NotFound("Task not found or not in failed status".to_string())
-        }
-        _ => Error::Internal(format!("Failed to retry task: {e}")),
-    })?;
+    let template = templates::get_template(conn.as_mut(), &name, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// Create a task template
+#[utoipa::path(
+    post,
+    path = "/tasks/templates",
+    tag = "Tasks",
+    summary = "Create task template",
+    description = "Create a reusable task template (moderator or higher)",
+    request_body = CreateTaskTemplateRequest,
+    responses(
+        (status = 200, description = "Template created", body = ApiResponse<TaskTemplate>),
+        (status = 400, description = "Invalid template", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Template name already exists", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_task_template(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateTaskTemplateRequest>,
+) -> Result<Json<ApiResponse<TaskTemplate>>, Error> {
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let template =
+        templates::create_template(conn.as_mut(), auth_user.tenant_id, payload, auth_user.id)
+            .await?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// Update a task template
+#[utoipa::path(
+    put,
+    path = "/tasks/templates/{name}",
+    tag = "Tasks",
+    summary = "Update task template",
+    description = "Update a reusable task template (moderator or higher)",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    request_body = UpdateTaskTemplateRequest,
+    responses(
+        (status = 200, description = "Template updated", body = ApiResponse<TaskTemplate>),
+        (status = 400, description = "Invalid template", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_task_template(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateTaskTemplateRequest>,
+) -> Result<Json<ApiResponse<TaskTemplate>>, Error> {
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let template =
+        templates::update_template(conn.as_mut(), &name, auth_user.tenant_id, payload).await?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// Delete a task template
+#[utoipa::path(
+    delete,
+    path = "/tasks/templates/{name}",
+    tag = "Tasks",
+    summary = "Delete task template",
+    description = "Delete a reusable task template (moderator or higher)",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    responses(
+        (status = 200, description = "Template deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_task_template(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_moderator_or_higher(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    templates::delete_template(conn.as_mut(), &name, auth_user.tenant_id).await?;
+    Ok(Json(ApiResponse::success(format!(
+        "Task template '{name}' deleted"
+    ))))
+}
+
+/// Create a task from a template
+#[utoipa::path(
+    post,
+    path = "/tasks/from-template/{name}",
+    tag = "Tasks",
+    summary = "Create task from template",
+    description = "Create a task from a template's defaults merged with per-task overrides",
+    params(
+        ("name" = String, Path, description = "Template name")
+    ),
+    request_body = TaskFromTemplateRequest,
+    responses(
+        (status = 200, description = "Task created", body = ApiResponse<TaskResponse>),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 429, description = "Task submission rate limit or quota exceeded", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_task_from_template(
+    State(app_state): State<AppState>,
+    Path(name): Path<String>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(overrides): Json<TaskFromTemplateRequest>,
+) -> Result<Json<ApiResponse<TaskResponse>>, Error> {
+    let template = {
+        let mut conn = app_state
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::from_sqlx)?;
+        templates::get_template(conn.as_mut(), &name, auth_user.tenant_id).await?
+    };
+
+    let mut payload = template.payload;
+    if let Some(patch) = &overrides.payload {
+        templates::merge_patch(&mut payload, patch);
+    }
+
+    let mut metadata: std::collections::HashMap<String, serde_json::Value> =
+        serde_json::from_value(template.metadata).unwrap_or_default();
+    metadata.extend(overrides.metadata);
+    metadata.insert("template".to_string(), serde_json::json!(template.name));
+
+    let request = CreateTaskApiRequest {
+        task_type: template.task_type,
+        payload,
+        priority: Some(
+            overrides
+                .priority
+                .unwrap_or(template.priority)
+                .as_str()
+                .to_string(),
+        ),
+        scheduled_at: overrides.scheduled_at,
+        metadata,
+        tags: overrides.tags,
+        timeout_seconds: overrides.timeout_seconds,
+        dedupe_key: overrides.dedupe_key,
+        org_id: overrides.org_id,
+    };
+
+    let task = submit_task(&app_state, &auth_user, request).await?;
+    Ok(Json(ApiResponse::success(task)))
+}
+
+/// Cancel a task
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/cancel",
+    tag = "Tasks",
+    summary = "Cancel task",
+    description = "Cancel a running or pending task",
+    params(
+        ("id" = Uuid, Path, description = "Task ID")
+    ),
+    responses(
+        (status = 200, description = "Task cancelled", body = ApiResponse<String>),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn cancel_task(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    // First, get the task to check ownership
+    let task = processor
+        .get_task(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
+        .ok_or(Error::NotFound("Task not found".to_string()))?;
+
+    // Admin/Moderator can cancel any task, users their own and, as org admins, their organizations'
+    check_task_access(
+        &app_state,
+        &auth_user,
+        &task,
+        OrgRole::Admin,
+        Permission::Write,
+    )
+    .await?;
+
+    processor
+        .cancel_task(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to cancel task: {e}")))?;
+    record_task_change(
+        &app_state,
+        &actor,
+        "task_cancelled",
+        &task,
+        TaskStatus::Cancelled,
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success_with_message(
+        "Task cancelled successfully".to_string(),
+        format!("Task {task_id} has been cancelled"),
+    )))
+}
+
+/// Record a change of a task's status in the audit log
+async fn record_task_change(
+    app_state: &AppState,
+    actor: &Actor,
+    action: &str,
+    task: &Task,
+    status: TaskStatus,
+) {
+    match app_state.database.pool.acquire().await {
+        Ok(mut conn) => {
+            audit::record(
+                conn.as_mut(),
+                actor,
+                action,
+                &AuditResource::new(AuditResourceType::Task, task.id),
+                Some(serde_json::json!({ "task_type": task.task_type, "status": task.status })),
+                Some(serde_json::json!({ "task_type": task.task_type, "status": status })),
+            )
+            .await
+        }
+        Err(e) => tracing::warn!("Failed to record {action} of task {}: {e}", task.id),
+    }
+}
+
+/// Get execution attempts for a task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/attempts",
+    tag = "Tasks",
+    summary = "Get task attempts",
+    description = "Get the execution history of a task, one entry per attempt; archived tasks keep theirs",
+    params(
+        ("id" = Uuid, Path, description = "Task ID")
+    ),
+    responses(
+        (status = 200, description = "Task attempts", body = ApiResponse<Vec<TaskAttempt>>),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_task_attempts(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<TaskAttempt>>>, Error> {
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    // First, get the task to check ownership; finished tasks may have been archived
+    let live_task = processor
+        .get_task(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?;
+    let (task, archived) = match live_task {
+        Some(task) => (task, false),
+        None => (
+            archive::find_archived_task(conn.as_mut(), task_id)
+                .await?
+                .ok_or(Error::NotFound("Task not found".to_string()))?,
+            true,
+        ),
+    };
+
+    // Admin/Moderator can view any task, users their own and their organizations'
+    check_task_access(
+        &app_state,
+        &auth_user,
+        &task,
+        OrgRole::Member,
+        Permission::Read,
+    )
+    .await?;
+
+    let attempts = if archived {
+        archive::find_archived_task_attempts(conn.as_mut(), task_id).await?
+    } else {
+        processor
+            .get_task_attempts(task_id)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to get task attempts: {e}")))?
+    };
+
+    Ok(Json(ApiResponse::success(attempts)))
+}
+
+/// Get tasks spawned by a task
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}/children",
+    tag = "Tasks",
+    summary = "Get child tasks",
+    description = "Get the tasks a task's handler enqueued with `enqueue_child`, oldest first",
+    params(
+        ("id" = Uuid, Path, description = "Parent task ID")
+    ),
+    responses(
+        (status = 200, description = "Child tasks", body = ApiResponse<Vec<TaskResponse>>),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_child_tasks(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<TaskResponse>>>, Error> {
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    let task = processor
+        .get_task(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
+        .ok_or(Error::NotFound("Task not found".to_string()))?;
+
+    check_task_access(
+        &app_state,
+        &auth_user,
+        &task,
+        OrgRole::Member,
+        Permission::Read,
+    )
+    .await?;
+    let org_ids = visible_org_ids(&app_state, &auth_user).await?;
+
+    let children = processor
+        .get_child_tasks(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get child tasks: {e}")))?
+        .into_iter()
+        // Children enqueued on behalf of another user stay hidden from this one
+        .filter(|child| task_visible(&auth_user, &org_ids, child))
+        .map(TaskResponse::from)
+        .collect();
+
+    Ok(Json(ApiResponse::success(children)))
+}
+
+/// Get dead letter queue (failed tasks)
+#[utoipa::path(
+    get,
+    path = "/tasks/dead-letter",
+    tag = "Tasks",
+    summary = "Get dead letter queue",
+    description = "Get all failed tasks in the dead letter queue",
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum number of tasks to return"),
+        ("offset" = Option<i64>, Query, description = "Number of tasks to skip")
+    ),
+    responses(
+        (status = 200, description = "Dead letter queue tasks", body = ApiResponse<Vec<TaskResponse>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_dead_letter_queue(
+    State(app_state): State<AppState>,
+    Query(params): Query<TaskQueryParams>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<crate::tasks::types::TaskResponse>>>, Error> {
+    rbac_services::check_permission(&auth_user, Resource::Tasks, Permission::Read)?;
+
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    let tasks = processor
+        .get_dead_letter_queue(params.limit, params.offset)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get dead letter queue: {e}")))?;
+
+    // Filter tasks based on user role - Admin/Moderator see all, users their own and their organizations'
+    let org_ids = visible_org_ids(&app_state, &auth_user).await?;
+    let filtered_tasks: Vec<crate::tasks::types::TaskResponse> = tasks
+        .into_iter()
+        .filter(|task| task_visible(&auth_user, &org_ids, task))
+        .map(|t| t.into())
+        .collect();
+
+    Ok(Json(ApiResponse::success(filtered_tasks)))
+}
+
+/// List archived tasks
+#[utoipa::path(
+    get,
+    path = "/tasks/archive",
+    tag = "Tasks",
+    summary = "List archived tasks",
+    description = "List completed and failed tasks that were moved to the archive",
+    params(
+        TaskQueryParams
+    ),
+    responses(
+        (status = 200, description = "Archived tasks", body = ApiResponse<Vec<ArchivedTaskResponse>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_archived_tasks(
+    State(app_state): State<AppState>,
+    Query(params): Query<TaskQueryParams>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ArchivedTaskResponse>>>, Error> {
+    rbac_services::check_permission(&auth_user, Resource::Tasks, Permission::Read)?;
+
+    let status = params
+        .status
+        .as_deref()
+        .and_then(|s| s.parse::<TaskStatus>().ok());
+
+    let filter = TaskFilter {
+        task_type: params.task_type,
+        status,
+        org_id: auth_user.org_scope(params.org_id)?,
+        visible_to: visible_to(&auth_user),
+        tenant_id: Some(auth_user.tenant_id),
+        tag: params.tag,
+        search: search_query(params.q)?,
+        limit: params.limit,
+        offset: params.offset,
+        ..Default::default()
+    };
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let tasks = archive::find_archived_tasks(conn.as_mut(), filter).await?;
+    Ok(Json(ApiResponse::success(tasks)))
+}
+
+/// Retry a failed task
+#[utoipa::path(
+    post,
+    path = "/tasks/{id}/retry",
+    tag = "Tasks",
+    summary = "Retry failed task",
+    description = "Retry a failed task by resetting it to pending status",
+    params(
+        ("id" = Uuid, Path, description = "Task ID")
+    ),
+    responses(
+        (status = 200, description = "Task retried successfully", body = ApiResponse<String>),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 400, description = "Task is not in failed status", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn retry_task(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    let processor = TaskProcessor::new(
+        app_state.database.clone(),
+        crate::tasks::processor::ProcessorConfig::default(),
+    );
+
+    // First, get the task to check ownership
+    let task = processor
+        .get_task(task_id)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
+        .ok_or(Error::NotFound("Task not found".to_string()))?;
+
+    // Admin/Moderator can retry any task, users their own and, as org admins, their organizations'
+    check_task_access(
+        &app_state,
+        &auth_user,
+        &task,
+        OrgRole::Admin,
+        Permission::Write,
+    )
+    .await?;
+
+    processor.retry_task(task_id).await.map_err(|e| match e {
+        crate::tasks::types::TaskError::NotFound(_) => {
+            Error::NotFound("Task not found or not in failed status".to_string())
+        }
+        _ => Error::Internal(format!("Failed to retry task: {e}")),
+    })?;
+    record_task_change(
+        &app_state,
+        &actor,
+        "task_retried",
+        &task,
+        TaskStatus::Pending,
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
         "Task retried successfully".to_string(),
@@ -471,6 +1376,7 @@ pub async fn delete_task(
     State(app_state): State<AppState>,
     Path(task_id): Path<Uuid>,
     Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
 ) -> Result<Json<ApiResponse<String>>, Error> {
     let processor = TaskProcessor::new(
         app_state.database.clone(),
@@ -484,8 +1390,15 @@ pub async fn delete_task(
         .map_err(|e| Error::Internal(format!("Failed to get task: {e}")))?
         .ok_or(Error::NotFound("Task not found".to_string()))?;
 
-    // Check RBAC authorization - Admin/Moderator can delete any task, users only their own
-    rbac_services::can_access_task(&auth_user, task.created_by)?;
+    // Admin/Moderator can delete any task, users their own and, as org admins, their organizations'
+    check_task_access(
+        &app_state,
+        &auth_user,
+        &task,
+        OrgRole::Admin,
+        Permission::Delete,
+    )
+    .await?;
 
     processor.delete_task(task_id).await.map_err(|e| match e {
         crate::tasks::types::TaskError::NotFound(_) => {
@@ -494,6 +1407,23 @@ pub async fn delete_task(
         _ => Error::Internal(format!("Failed to delete task: {e}")),
     })?;
 
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    sharing::delete_resource_shares(conn.as_mut(), Resource::Tasks, task_id).await?;
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        "task_deleted",
+        &AuditResource::new(AuditResourceType::Task, task_id),
+        Some(serde_json::json!({ "task_type": task.task_type, "status": task.status })),
+        None,
+    )
+    .await;
+
     Ok(Json(ApiResponse::success_with_message(
         "Task deleted successfully".to_string(),
         format!("Task {task_id} has been permanently deleted"),
@@ -517,6 +1447,18 @@ pub async fn register_task_type(
     State(app_state): State<AppState>,
     Json(payload): Json<RegisterTaskTypeRequest>,
 ) -> Result<Json<ApiResponse<TaskTypeResponse>>, Error> {
+    if let Some(timeout_seconds) = payload.timeout_seconds
+        && !(1..=CreateTaskRequest::MAX_TIMEOUT_SECONDS).contains(&timeout_seconds)
+    {
+        return Err(Error::validation(
+            "timeout_seconds",
+            &format!(
+                "Timeout must be between 1 and {} seconds",
+                CreateTaskRequest::MAX_TIMEOUT_SECONDS
+            ),
+        ));
+    }
+
     let mut conn = app_state
         .database
         .pool
@@ -528,15 +1470,17 @@ pub async fn register_task_type(
     let task_type = sqlx::query_as!(
         TaskTypeResponse,
         r#"
-        INSERT INTO task_types (task_type, description)
-        VALUES ($1, $2)
+        INSERT INTO task_types (task_type, description, timeout_seconds)
+        VALUES ($1, $2, $3)
         ON CONFLICT (task_type) DO UPDATE SET
             description = EXCLUDED.description,
+            timeout_seconds = COALESCE(EXCLUDED.timeout_seconds, task_types.timeout_seconds),
             updated_at = NOW()
-        RETURNING task_type, description, is_active, created_at, updated_at
+        RETURNING task_type, description, is_active, timeout_seconds, created_at, updated_at
         "#,
         payload.task_type,
-        payload.description
+        payload.description,
+        payload.timeout_seconds
     )
     .fetch_one(&mut *conn)
     .await
@@ -569,7 +1513,7 @@ pub async fn list_task_types(
     let task_types = sqlx::query_as!(
         TaskTypeResponse,
         r#"
-        SELECT task_type, description, is_active, created_at, updated_at
+        SELECT task_type, description, is_active, timeout_seconds, created_at, updated_at
         FROM task_types
         WHERE is_active = true
         ORDER BY task_type
@@ -582,6 +1526,173 @@ pub async fn list_task_types(
     Ok(Json(ApiResponse::success(task_types)))
 }
 
+/// List circuit breaker state per task type (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/tasks/circuit-breakers",
+    tag = "Admin",
+    summary = "List circuit breakers",
+    description = "Get circuit breaker state for every registered task type (admins of the default tenant only)",
+    responses(
+        (status = 200, description = "Circuit breaker state", body = ApiResponse<Vec<CircuitBreakerStatus>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_circuit_breakers(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<CircuitBreakerStatus>>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Circuit breakers are managed")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let breakers = circuit::list_circuit_breakers(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(breakers)))
+}
+
+/// Manually trip a task type's circuit breaker (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/tasks/circuit-breakers/{task_type}/trip",
+    tag = "Admin",
+    summary = "Trip circuit breaker",
+    description = "Open a task type's circuit breaker until it is reset; workers stop running tasks of this type (admins of the default tenant only)",
+    params(
+        ("task_type" = String, Path, description = "Task type")
+    ),
+    responses(
+        (status = 200, description = "Circuit breaker tripped", body = ApiResponse<CircuitBreakerStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Task type not registered", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn trip_circuit_breaker(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(task_type): Path<String>,
+) -> Result<Json<ApiResponse<CircuitBreakerStatus>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Circuit breakers are managed")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let breaker =
+        circuit::set_circuit_breaker(conn.as_mut(), &task_type, true, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(breaker)))
+}
+
+/// Reset a task type's circuit breaker (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/tasks/circuit-breakers/{task_type}/reset",
+    tag = "Admin",
+    summary = "Reset circuit breaker",
+    description = "Close a task type's circuit breaker and clear its failure count (admins of the default tenant only)",
+    params(
+        ("task_type" = String, Path, description = "Task type")
+    ),
+    responses(
+        (status = 200, description = "Circuit breaker reset", body = ApiResponse<CircuitBreakerStatus>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Task type not registered", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn reset_circuit_breaker(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(task_type): Path<String>,
+) -> Result<Json<ApiResponse<CircuitBreakerStatus>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Circuit breakers are managed")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let breaker =
+        circuit::set_circuit_breaker(conn.as_mut(), &task_type, false, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(breaker)))
+}
+
+/// Stream status changes of the tasks the user can see as Server-Sent Events
+///
+/// Each change is sent as a `task` message with the task's id, type, status,
+/// attempt and last error. A client too slow to keep up gets a `lagged`
+/// message with the number of changes it missed.
+#[utoipa::path(
+    get,
+    path = "/tasks/stream",
+    tag = "Tasks",
+    summary = "Stream task status changes",
+    description = "Stream status changes of tasks visible to the user from now on as Server-Sent Events",
+    responses(
+        (status = 200, description = "Stream of task status changes as they happen", content_type = "text/event-stream"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn stream_tasks(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, Error> {
+    rbac_services::check_permission(&auth_user, Resource::Tasks, Permission::Read)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let subscription =
+        realtime_services::subscription(conn.as_mut(), &auth_user, BTreeSet::from([Topic::Tasks]))
+            .await?;
+    drop(conn);
+    let receiver = app_state
+        .realtime
+        .subscribe_topic(&app_state.database.pool, Topic::Tasks)
+        .await?;
+
+    Ok(sse::stream(receiver, move |message| {
+        if !subscription.admits(message) {
+            return None;
+        }
+        let event = SseEvent::default().event("task");
+        let event = match message.data["id"].as_str() {
+            Some(id) => event.id(id),
+            None => event,
+        };
+        event.json_data(&message.data).ok()
+    }))
+}
+
 /// Public task routes (no authentication required)
 pub fn tasks_public_routes() -> Router<AppState> {
     Router::new().route("/types", get(list_task_types).post(register_task_type))
@@ -590,10 +1701,157 @@ pub fn tasks_public_routes() -> Router<AppState> {
 /// Protected task routes (authentication required)
 pub fn tasks_routes() -> Router<AppState> {
     Router::new()
-        .route("/", get(list_tasks).post(create_task))
+        .route("/", get(list_tasks_versioned).post(create_task))
         .route("/stats", get(get_stats))
+        .route("/queue-stats", get(get_queue_stats))
+        .route("/quota", get(get_quota))
+        .route("/schedules/preview", post(preview_schedule))
+        .route(
+            "/templates",
+            get(list_task_templates).post(create_task_template),
+        )
+        .route(
+            "/templates/{name}",
+            get(get_task_template)
+                .put(update_task_template)
+                .delete(delete_task_template),
+        )
+        .route("/from-template/{name}", post(create_task_from_template))
         .route("/dead-letter", get(get_dead_letter_queue))
-        .route("/{id}", get(get_task).delete(delete_task))
+        .route("/archive", get(list_archived_tasks))
+        .route("/export", get(export_tasks))
+        .route("/stream", get(stream_tasks))
+        .route("/{id}", get(get_task_versioned).delete(delete_task))
+        .route("/{id}/attempts", get(get_task_attempts))
+        .route("/{id}/children", get(get_child_tasks))
         .route("/{id}/cancel", post(cancel_task))
         .route("/{id}/retry", post(retry_task))
 }
+
+/// List task queues with their pause state (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/tasks/queues",
+    tag = "Admin",
+    summary = "List task queues",
+    description = "List every task type's queue with its pause state, pending and running counts (Admin only)",
+    responses(
+        (status = 200, description = "Task queues", body = ApiResponse<Vec<TaskQueueState>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_task_queues(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<TaskQueueState>>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let queues = queue::list_queue_states(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(queues)))
+}
+
+/// Pause a task queue (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/tasks/queues/{queue}/pause",
+    tag = "Admin",
+    summary = "Pause task queue",
+    description = "Stop all workers from claiming new tasks of this type; running tasks finish and new tasks are still accepted (admins of the default tenant only)",
+    params(
+        ("queue" = String, Path, description = "Task type")
+    ),
+    responses(
+        (status = 200, description = "Queue paused", body = ApiResponse<TaskQueueState>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Task type not registered", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn pause_task_queue(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(queue_name): Path<String>,
+) -> Result<Json<ApiResponse<TaskQueueState>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Task queues are controlled")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let state = queue::set_queue_paused(conn.as_mut(), &queue_name, true, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(state)))
+}
+
+/// Resume a paused task queue (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/tasks/queues/{queue}/resume",
+    tag = "Admin",
+    summary = "Resume task queue",
+    description = "Let workers claim tasks of this type again (admins of the default tenant only)",
+    params(
+        ("queue" = String, Path, description = "Task type")
+    ),
+    responses(
+        (status = 200, description = "Queue resumed", body = ApiResponse<TaskQueueState>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Task type not registered", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn resume_task_queue(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(queue_name): Path<String>,
+) -> Result<Json<ApiResponse<TaskQueueState>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Task queues are controlled")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let state = queue::set_queue_paused(conn.as_mut(), &queue_name, false, auth_user.id).await?;
+    Ok(Json(ApiResponse::success(state)))
+}
+
+/// Admin task routes (admin role required)
+pub fn tasks_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/circuit-breakers", get(list_circuit_breakers))
+        .route(
+            "/circuit-breakers/{task_type}/trip",
+            post(trip_circuit_breaker),
+        )
+        .route(
+            "/circuit-breakers/{task_type}/reset",
+            post(reset_circuit_breaker),
+        )
+        .route("/queues", get(list_task_queues))
+        .route("/queues/{queue}/pause", post(pause_task_queue))
+        .route("/queues/{queue}/resume", post(resume_task_queue))
+}
@@ -0,0 +1,255 @@
+use crate::tasks::types::{
+    ArchivedTaskResponse, Task, TaskAttempt, TaskFilter, TaskPriority, TaskStatus,
+};
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use tokio::time::{Duration, interval};
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Maximum number of tasks moved per statement to keep transactions short
+const ARCHIVE_BATCH_SIZE: i64 = 1000;
+
+/// Background job to move old completed/failed tasks into the archive table
+pub async fn task_archive_job(pool: DbPool, run_interval: Duration, archive_after_days: u32) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let cutoff = Utc::now() - chrono::Duration::days(archive_after_days as i64);
+        match archive_old_tasks(&pool, cutoff).await {
+            Ok(count) => {
+                if count > 0 {
+                    info!(
+                        "Archived {} tasks older than {} days",
+                        count, archive_after_days
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to archive old tasks: {}", e);
+            }
+        }
+    }
+}
+
+async fn archive_old_tasks(pool: &DbPool, cutoff: DateTime<Utc>) -> Result<u64> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    archive_tasks(conn.as_mut(), cutoff).await
+}
+
+/// Count completed/failed tasks that finished before the cutoff
+pub async fn count_archivable_tasks(conn: &mut DbConn, cutoff: DateTime<Utc>) -> Result<i64> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) FROM tasks
+        WHERE status IN ('completed', 'failed') AND completed_at < $1
+        "#,
+        cutoff
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(count.unwrap_or(0))
+}
+
+/// Move completed/failed tasks that finished before the cutoff into tasks_archive
+///
+/// Each batch is a single statement, so a task is never visible in both tables.
+/// Their attempts move into task_attempts_archive in the same statement.
+pub async fn archive_tasks(conn: &mut DbConn, cutoff: DateTime<Utc>) -> Result<u64> {
+    let mut total = 0;
+
+    loop {
+        let result = sqlx::query!(
+            r#"
+            WITH moved AS (
+                DELETE FROM tasks
+                WHERE id IN (
+                    SELECT id FROM tasks
+                    WHERE status IN ('completed', 'failed') AND completed_at < $1
+                    ORDER BY completed_at
+                    LIMIT $2
+                )
+                RETURNING
+                    id, task_type, payload, status, priority,
+                    retry_strategy, max_attempts, current_attempt, last_error,
+                    created_at, updated_at, scheduled_at, started_at, completed_at,
+                    created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            ),
+            moved_attempts AS (
+                DELETE FROM task_attempts
+                WHERE task_id IN (SELECT id FROM moved)
+                RETURNING
+                    id, task_id, attempt_number, worker_id, started_at, finished_at,
+                    duration_ms, error, error_class
+            ),
+            archived_attempts AS (
+                INSERT INTO task_attempts_archive (
+                    id, task_id, attempt_number, worker_id, started_at, finished_at,
+                    duration_ms, error, error_class
+                )
+                SELECT
+                    id, task_id, attempt_number, worker_id, started_at, finished_at,
+                    duration_ms, error, error_class
+                FROM moved_attempts
+            )
+            INSERT INTO tasks_archive (
+                id, task_type, payload, status, priority,
+                retry_strategy, max_attempts, current_attempt, last_error,
+                created_at, updated_at, scheduled_at, started_at, completed_at,
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            )
+            SELECT
+                id, task_type, payload, status, priority,
+                retry_strategy, max_attempts, current_attempt, last_error,
+                created_at, updated_at, scheduled_at, started_at, completed_at,
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            FROM moved
+            "#,
+            cutoff,
+            ARCHIVE_BATCH_SIZE
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        let moved = result.rows_affected();
+        total += moved;
+
+        if moved < ARCHIVE_BATCH_SIZE as u64 {
+            break;
+        }
+    }
+
+    Ok(total)
+}
+
+/// Get an archived task by ID
+pub async fn find_archived_task(conn: &mut DbConn, task_id: Uuid) -> Result<Option<Task>> {
+    sqlx::query_as!(
+        Task,
+        r#"
+        SELECT
+            id, task_type, payload,
+            status as "status: TaskStatus",
+            priority as "priority: TaskPriority",
+            retry_strategy, max_attempts, current_attempt, last_error,
+            created_at, updated_at, scheduled_at, started_at, completed_at,
+            created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+        FROM tasks_archive
+        WHERE id = $1
+        "#,
+        task_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// List the attempts of an archived task, oldest first
+pub async fn find_archived_task_attempts(
+    conn: &mut DbConn,
+    task_id: Uuid,
+) -> Result<Vec<TaskAttempt>> {
+    sqlx::query_as!(
+        TaskAttempt,
+        r#"
+        SELECT id, task_id, attempt_number, worker_id, started_at, finished_at,
+               duration_ms, error, error_class
+        FROM task_attempts_archive
+        WHERE task_id = $1
+        ORDER BY started_at ASC, attempt_number ASC
+        "#,
+        task_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// List archived tasks with filtering (most recently archived first)
+pub async fn find_archived_tasks(
+    conn: &mut DbConn,
+    filter: TaskFilter,
+) -> Result<Vec<ArchivedTaskResponse>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            id, task_type, payload,
+            status as "status: TaskStatus",
+            priority as "priority: TaskPriority",
+            retry_strategy, max_attempts, current_attempt, last_error,
+            created_at, updated_at, scheduled_at, started_at, completed_at,
+            created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id, archived_at
+        FROM tasks_archive
+        WHERE ($1::TEXT IS NULL OR task_type = $1)
+          AND ($2::TEXT IS NULL OR status = $2)
+          AND ($3::UUID IS NULL OR created_by = $3)
+          AND ($4::TEXT IS NULL OR tags @> ARRAY[$4::TEXT])
+          AND ($5::TEXT IS NULL OR
+               (jsonb_to_tsvector('simple', payload, '["string", "numeric"]')
+                || jsonb_to_tsvector('simple', metadata, '["string", "numeric"]'))
+               @@ websearch_to_tsquery('simple', $5))
+          AND ($8::UUID IS NULL OR org_id = $8)
+          AND ($9::UUID IS NULL OR created_by = $9
+               OR org_id IN (SELECT org_id FROM org_members WHERE user_id = $9))
+          AND ($10::UUID IS NULL OR tenant_id = $10)
+        ORDER BY archived_at DESC, completed_at DESC
+        LIMIT $6
+        OFFSET $7
+        "#,
+        filter.task_type,
+        filter.status as Option<TaskStatus>,
+        filter.created_by,
+        filter.tag,
+        filter.search,
+        filter.limit.unwrap_or(100),
+        filter.offset.unwrap_or(0),
+        filter.org_id,
+        filter.visible_to,
+        filter.tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let archived = rows
+        .into_iter()
+        .map(|row| {
+            let task = Task {
+                id: row.id,
+                task_type: row.task_type,
+                payload: row.payload,
+                status: row.status,
+                priority: row.priority,
+                retry_strategy: row.retry_strategy,
+                max_attempts: row.max_attempts,
+                current_attempt: row.current_attempt,
+                last_error: row.last_error,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                scheduled_at: row.scheduled_at,
+                started_at: row.started_at,
+                completed_at: row.completed_at,
+                created_by: row.created_by,
+                metadata: row.metadata,
+                tags: row.tags,
+                timeout_seconds: row.timeout_seconds,
+                dedupe_key: row.dedupe_key,
+                parent_task_id: row.parent_task_id,
+                org_id: row.org_id,
+                tenant_id: row.tenant_id,
+            };
+
+            ArchivedTaskResponse {
+                task: task.into(),
+                archived_at: row.archived_at,
+            }
+        })
+        .collect();
+
+    Ok(archived)
+}
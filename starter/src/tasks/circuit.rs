@@ -0,0 +1,237 @@
+use crate::monitoring::{models::CreateEventRequest, services as monitoring_services};
+use crate::tasks::retry::CircuitState;
+use crate::tasks::types::CircuitBreakerStatus;
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::{DbConn, Error, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::warn;
+use uuid::Uuid;
+
+/// A manual trip/reset that workers apply once per `version`
+#[derive(Debug, Clone)]
+pub struct CircuitControl {
+    pub task_type: String,
+    pub forced: bool,
+    pub version: i64,
+}
+
+/// List breaker state for every registered task type
+///
+/// Task types whose breaker never changed state are reported as closed.
+pub async fn list_circuit_breakers(conn: &mut DbConn) -> Result<Vec<CircuitBreakerStatus>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT tt.task_type,
+               COALESCE(cb.state, 'closed') as "state!",
+               COALESCE(cb.failure_count, 0) as "failure_count!",
+               COALESCE(cb.forced, false) as "forced!",
+               cb.reported_by as "reported_by?",
+               cb.updated_by as "updated_by?",
+               cb.updated_at as "updated_at?"
+        FROM task_types tt
+        LEFT JOIN task_circuit_breakers cb ON cb.task_type = tt.task_type
+        ORDER BY tt.task_type
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(CircuitBreakerStatus {
+                state: CircuitState::from_str(&row.state).map_err(Error::Internal)?,
+                task_type: row.task_type,
+                failure_count: row.failure_count,
+                forced: row.forced,
+                reported_by: row.reported_by,
+                updated_by: row.updated_by,
+                updated_at: row.updated_at,
+            })
+        })
+        .collect()
+}
+
+/// Record a state change observed by a worker and emit a monitoring event
+pub async fn record_transition(
+    conn: &mut DbConn,
+    task_type: &str,
+    from: &CircuitState,
+    to: &CircuitState,
+    failure_count: u32,
+    worker_id: &str,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO task_circuit_breakers (task_type, state, failure_count, reported_by, updated_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (task_type) DO UPDATE SET
+            state = EXCLUDED.state,
+            failure_count = EXCLUDED.failure_count,
+            reported_by = EXCLUDED.reported_by,
+            updated_at = NOW()
+        "#,
+        task_type,
+        to.as_str(),
+        failure_count as i32,
+        worker_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    emit_event(
+        conn,
+        task_type,
+        from,
+        to,
+        format!(
+            "Circuit breaker for {task_type} changed from {} to {} on worker {worker_id}",
+            from.as_str(),
+            to.as_str()
+        ),
+        None,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Manually trip (`forced = true`) or reset a task type's breaker
+///
+/// Workers pick the change up on their next poll.
+pub async fn set_circuit_breaker(
+    conn: &mut DbConn,
+    task_type: &str,
+    forced: bool,
+    user_id: Uuid,
+) -> Result<CircuitBreakerStatus> {
+    let state = if forced {
+        CircuitState::Open
+    } else {
+        CircuitState::Closed
+    };
+
+    let previous = sqlx::query_scalar!(
+        "SELECT state FROM task_circuit_breakers WHERE task_type = $1",
+        task_type
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .and_then(|state| CircuitState::from_str(&state).ok())
+    .unwrap_or(CircuitState::Closed);
+
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO task_circuit_breakers
+            (task_type, state, failure_count, forced, control_version, updated_by, updated_at)
+        VALUES ($1, $2, 0, $3, 1, $4, NOW())
+        ON CONFLICT (task_type) DO UPDATE SET
+            state = EXCLUDED.state,
+            failure_count = 0,
+            forced = EXCLUDED.forced,
+            control_version = task_circuit_breakers.control_version + 1,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING task_type, failure_count, forced, reported_by, updated_by, updated_at
+        "#,
+        task_type,
+        state.as_str(),
+        forced,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Error::NotFound(format!("Task type '{task_type}' is not registered"))
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    let action = if forced { "tripped" } else { "reset" };
+    emit_event(
+        conn,
+        task_type,
+        &previous,
+        &state,
+        format!("Circuit breaker for {task_type} manually {action}"),
+        Some(user_id),
+    )
+    .await;
+
+    Ok(CircuitBreakerStatus {
+        task_type: row.task_type,
+        state,
+        failure_count: row.failure_count,
+        forced: row.forced,
+        reported_by: row.reported_by,
+        updated_by: row.updated_by,
+        updated_at: Some(row.updated_at),
+    })
+}
+
+/// Manual controls for workers to apply
+pub async fn list_controls(conn: &mut DbConn) -> Result<Vec<CircuitControl>> {
+    let controls = sqlx::query_as!(
+        CircuitControl,
+        r#"
+        SELECT task_type, forced, control_version as version
+        FROM task_circuit_breakers
+        WHERE control_version > 0
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(controls)
+}
+
+async fn emit_event(
+    conn: &mut DbConn,
+    task_type: &str,
+    from: &CircuitState,
+    to: &CircuitState,
+    message: String,
+    user_id: Option<Uuid>,
+) {
+    let level = if *to == CircuitState::Open {
+        "warn"
+    } else {
+        "info"
+    };
+
+    let mut tags = HashMap::from([
+        ("task_type".to_string(), serde_json::json!(task_type)),
+        ("circuit_state".to_string(), serde_json::json!(to)),
+    ]);
+    if let Some(user_id) = user_id {
+        tags.insert("user_id".to_string(), serde_json::json!(user_id));
+    }
+
+    let event = CreateEventRequest {
+        event_type: "log".to_string(),
+        source: "task-circuit-breaker".to_string(),
+        message: Some(message),
+        level: Some(level.to_string()),
+        tags,
+        payload: HashMap::from([
+            ("from".to_string(), serde_json::json!(from)),
+            ("to".to_string(), serde_json::json!(to)),
+        ]),
+        recorded_at: None,
+        org_id: None,
+        // Breakers are shared by every tenant and managed from the default one
+        tenant_id: Some(DEFAULT_TENANT_ID),
+    };
+
+    if let Err(e) = monitoring_services::create_event(conn, event).await {
+        warn!(
+            "Failed to record circuit breaker event for {}: {}",
+            task_type, e
+        );
+    }
+}
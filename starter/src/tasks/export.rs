@@ -0,0 +1,192 @@
+use axum::body::{Body, Bytes};
+use futures_util::{TryStreamExt, stream};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+use crate::tasks::types::{ExportFormat, Task, TaskFilter, TaskPriority, TaskResponse, TaskStatus};
+
+/// Rows buffered between the database cursor and a slow client
+const EXPORT_BUFFER_ROWS: usize = 256;
+
+const CSV_HEADER: &str = "id,task_type,status,priority,tags,payload,metadata,created_by,\
+parent_task_id,current_attempt,max_attempts,last_error,created_at,scheduled_at,\
+started_at,completed_at\n";
+
+/// One exported task: the API view of the task plus the payload it was created with
+#[derive(Debug, Serialize)]
+struct TaskExportRecord {
+    #[serde(flatten)]
+    task: TaskResponse,
+    payload: serde_json::Value,
+}
+
+/// Stream every task matching `filter` as a response body, oldest first
+///
+/// Rows are read through a database cursor on a background task, so memory use
+/// stays flat however large the export. `limit` and `offset` are honored when
+/// set; an unset limit exports everything. If the query fails part way, the
+/// body ends with an error so the client sees a truncated download rather than
+/// a silently short one.
+pub fn export_tasks(pool: PgPool, filter: TaskFilter, format: ExportFormat) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<Bytes, sqlx::Error>>(EXPORT_BUFFER_ROWS);
+
+    tokio::spawn(async move {
+        if format == ExportFormat::Csv && tx.send(Ok(Bytes::from(CSV_HEADER))).await.is_err() {
+            return;
+        }
+
+        let mut rows = sqlx::query_as!(
+            Task,
+            r#"
+            SELECT
+                id, task_type, payload,
+                status as "status: TaskStatus",
+                priority as "priority: TaskPriority",
+                retry_strategy, max_attempts, current_attempt, last_error,
+                created_at, updated_at, scheduled_at, started_at, completed_at,
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            FROM tasks
+            WHERE ($1::TEXT IS NULL OR task_type = $1)
+              AND ($2::TEXT IS NULL OR status = $2)
+              AND ($3::TEXT IS NULL OR priority = $3)
+              AND ($4::UUID IS NULL OR created_by = $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR created_at <= $6)
+              AND ($7::TEXT IS NULL OR tags @> ARRAY[$7::TEXT])
+              AND ($8::TEXT IS NULL OR
+                   (jsonb_to_tsvector('simple', payload, '["string", "numeric"]')
+                    || jsonb_to_tsvector('simple', metadata, '["string", "numeric"]'))
+                   @@ websearch_to_tsquery('simple', $8))
+              AND ($11::UUID IS NULL OR org_id = $11)
+              AND ($12::UUID IS NULL OR created_by = $12
+                   OR org_id IN (SELECT org_id FROM org_members WHERE user_id = $12))
+              AND ($13::UUID IS NULL OR tenant_id = $13)
+            ORDER BY created_at ASC, id ASC
+            LIMIT $9
+            OFFSET $10
+            "#,
+            filter.task_type,
+            filter.status as Option<TaskStatus>,
+            filter.priority as Option<TaskPriority>,
+            filter.created_by,
+            filter.created_after,
+            filter.created_before,
+            filter.tag,
+            filter.search,
+            filter.limit,
+            filter.offset.unwrap_or(0),
+            filter.org_id,
+            filter.visible_to,
+            filter.tenant_id
+        )
+        .fetch(&pool);
+
+        loop {
+            let item = match rows.try_next().await {
+                Ok(Some(task)) => Ok(Bytes::from(render_task(&task, format))),
+                Ok(None) => return,
+                Err(e) => {
+                    tracing::error!("Task export failed part way: {}", e);
+                    Err(e)
+                }
+            };
+            let failed = item.is_err();
+            // A closed channel means the client went away
+            if tx.send(item).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    Body::from_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    }))
+}
+
+/// Render one task as a newline-terminated CSV row or NDJSON line
+fn render_task(task: &Task, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Ndjson => {
+            let record = TaskExportRecord {
+                task: task.clone().into(),
+                payload: task.payload.clone(),
+            };
+            let mut line = serde_json::to_string(&record).unwrap_or_else(|e| {
+                tracing::error!("Failed to serialize task {} for export: {}", task.id, e);
+                format!(r#"{{"id":"{}"}}"#, task.id)
+            });
+            line.push('\n');
+            line
+        }
+        ExportFormat::Csv => {
+            let optional_time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+                t.map(|t| t.to_rfc3339()).unwrap_or_default()
+            };
+            let fields = [
+                task.id.to_string(),
+                task.task_type.clone(),
+                task.status.to_string(),
+                task.priority.to_string(),
+                task.tags.join(";"),
+                task.payload.to_string(),
+                task.metadata.to_string(),
+                task.created_by.map(|id| id.to_string()).unwrap_or_default(),
+                task.parent_task_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                task.current_attempt.to_string(),
+                task.max_attempts.to_string(),
+                task.last_error.clone().unwrap_or_default(),
+                task.created_at.to_rfc3339(),
+                optional_time(task.scheduled_at),
+                optional_time(task.started_at),
+                optional_time(task.completed_at),
+            ];
+            let mut row = fields
+                .iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",");
+            row.push('\n');
+            row
+        }
+    }
+}
+
+/// Quote a CSV field when needed (RFC 4180)
+///
+/// Values that a spreadsheet would evaluate as a formula are prefixed with `'`
+/// so an exported payload cannot run code when the file is opened.
+pub(crate) fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("email"), "email");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(
+            csv_field(r#"{"to":"bob@example.com"}"#),
+            r#""{""to"":""bob@example.com""}""#
+        );
+        assert_eq!(csv_field("line one\nline two"), "\"line one\nline two\"");
+        assert_eq!(csv_field("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+    }
+}
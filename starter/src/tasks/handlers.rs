@@ -1,8 +1,9 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 
-use crate::tasks::types::{TaskContext, TaskError, TaskResult};
-use crate::{extract_fields, require_field};
+use crate::tasks::services::EmailMessage;
+use crate::tasks::types::{TaskContext, TaskError, TaskLogLevel, TaskResult};
+use crate::{extract_fields, register_task_handler, require_field};
 
 /// Trait that all task handlers must implement
 #[async_trait]
@@ -13,14 +14,20 @@ pub trait TaskHandler {
 /// Example: Email notification task handler
 pub struct EmailTaskHandler;
 
+register_task_handler!("email", "Email notification tasks", EmailTaskHandler);
+
 #[async_trait]
 impl TaskHandler for EmailTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
         // Extract email data from payload using convenience macro
         let (to, subject, body) = extract_fields!(context.payload, "to", "subject", "body")?;
 
-        // Simulate email sending (replace with actual email service)
-        tracing::info!("Sending email to: {}, subject: {}", to, subject);
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Sending email to: {to}, subject: {subject}"),
+            )
+            .await;
 
         // Simulate some processing time
         tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -32,6 +39,18 @@ impl TaskHandler for EmailTaskHandler {
             ));
         }
 
+        // Deliver through the injected sender (logs only unless the processor
+        // was given a real provider)
+        context
+            .services()
+            .email()
+            .send(&EmailMessage {
+                to: to.to_string(),
+                subject: subject.to_string(),
+                body: body.to_string(),
+            })
+            .await?;
+
         // Return success with metadata
         let mut metadata = HashMap::new();
         metadata.insert("recipient".to_string(), serde_json::json!(to));
@@ -44,6 +63,12 @@ impl TaskHandler for EmailTaskHandler {
 /// Example: Data processing task handler
 pub struct DataProcessingTaskHandler;
 
+register_task_handler!(
+    "data_processing",
+    "Data processing and analysis tasks",
+    DataProcessingTaskHandler
+);
+
 #[async_trait]
 impl TaskHandler for DataProcessingTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
@@ -58,7 +83,12 @@ impl TaskHandler for DataProcessingTaskHandler {
             .and_then(|v| v.as_str())
             .unwrap_or("process");
 
-        tracing::info!("Processing data with operation: {}", operation);
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Processing data with operation: {operation}"),
+            )
+            .await;
 
         // Simulate data processing
         tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
@@ -100,6 +130,12 @@ impl TaskHandler for DataProcessingTaskHandler {
 /// Example: File cleanup task handler
 pub struct FileCleanupTaskHandler;
 
+register_task_handler!(
+    "file_cleanup",
+    "File system cleanup tasks",
+    FileCleanupTaskHandler
+);
+
 #[async_trait]
 impl TaskHandler for FileCleanupTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
@@ -111,11 +147,12 @@ impl TaskHandler for FileCleanupTaskHandler {
             .and_then(|v| v.as_u64())
             .unwrap_or(24);
 
-        tracing::info!(
-            "Cleaning up files in path: {}, max age: {} hours",
-            file_path,
-            max_age_hours
-        );
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Cleaning up files in path: {file_path}, max age: {max_age_hours} hours"),
+            )
+            .await;
 
         // Simulate file cleanup operation
         tokio::time::sleep(std::time::Duration::from_millis(300)).await;
@@ -142,18 +179,24 @@ impl TaskHandler for FileCleanupTaskHandler {
 /// Example: Report generation task handler
 pub struct ReportGenerationTaskHandler;
 
+register_task_handler!(
+    "report_generation",
+    "Report generation tasks",
+    ReportGenerationTaskHandler
+);
+
 #[async_trait]
 impl TaskHandler for ReportGenerationTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
         let (report_type, start_date, end_date) =
             extract_fields!(context.payload, "report_type", "start_date", "end_date")?;
 
-        tracing::info!(
-            "Generating {} report from {} to {}",
-            report_type,
-            start_date,
-            end_date
-        );
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Generating {report_type} report from {start_date} to {end_date}"),
+            )
+            .await;
 
         // Simulate report generation (this could be quite long for real reports)
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
@@ -177,6 +220,8 @@ impl TaskHandler for ReportGenerationTaskHandler {
 /// Example: Webhook notification task handler
 pub struct WebhookTaskHandler;
 
+register_task_handler!("webhook", "Webhook notification tasks", WebhookTaskHandler);
+
 #[async_trait]
 impl TaskHandler for WebhookTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
@@ -194,7 +239,12 @@ impl TaskHandler for WebhookTaskHandler {
             .and_then(|v| v.as_str())
             .unwrap_or("POST");
 
-        tracing::info!("Sending webhook {} to: {}", method, url);
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Sending webhook {method} to: {url}"),
+            )
+            .await;
 
         // Simulate HTTP request (replace with actual HTTP client)
         tokio::time::sleep(std::time::Duration::from_millis(800)).await;
@@ -224,6 +274,12 @@ impl TaskHandler for WebhookTaskHandler {
 /// Example: Delay task handler for chaos testing
 pub struct DelayTaskHandler;
 
+register_task_handler!(
+    "delay_task",
+    "Delay/sleep tasks for testing and chaos scenarios",
+    DelayTaskHandler
+);
+
 #[async_trait]
 impl TaskHandler for DelayTaskHandler {
     async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
@@ -329,27 +385,3 @@ impl TaskHandler for DelayTaskHandler {
         Ok(TaskResult::success(result))
     }
 }
-
-/// Helper function to register all example handlers
-pub async fn register_example_handlers(processor: &crate::tasks::processor::TaskProcessor) {
-    processor
-        .register_handler("email".to_string(), EmailTaskHandler)
-        .await;
-    processor
-        .register_handler("data_processing".to_string(), DataProcessingTaskHandler)
-        .await;
-    processor
-        .register_handler("file_cleanup".to_string(), FileCleanupTaskHandler)
-        .await;
-    processor
-        .register_handler("report_generation".to_string(), ReportGenerationTaskHandler)
-        .await;
-    processor
-        .register_handler("webhook".to_string(), WebhookTaskHandler)
-        .await;
-    processor
-        .register_handler("delay_task".to_string(), DelayTaskHandler)
-        .await;
-
-    tracing::info!("Registered all example task handlers including delay_task");
-}
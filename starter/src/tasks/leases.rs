@@ -0,0 +1,154 @@
+use crate::monitoring::{models::CreateEventRequest, services as monitoring_services};
+use crate::tasks::types::{TaskErrorClass, TaskStatus};
+use crate::{DbConn, DbPool, Error, Result};
+use std::collections::HashMap;
+use tokio::time::{Duration, interval};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+const LEASE_EXPIRED_ERROR: &str = "Worker lease expired while the task was running";
+
+/// A running task recovered from a worker that stopped renewing its lease
+#[derive(Debug, Clone)]
+pub struct ReapedTask {
+    pub id: Uuid,
+    pub task_type: String,
+    /// `pending` when the task will run again, `failed` when it was out of attempts
+    pub status: TaskStatus,
+    pub current_attempt: i32,
+    pub claimed_by: Option<String>,
+    /// Tenant of the task; its lease expiry event belongs to it
+    pub tenant_id: Uuid,
+}
+
+/// Background job that recovers tasks orphaned by dead workers
+pub async fn task_lease_reaper_job(pool: DbPool, run_interval: Duration) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        match reap_with_pool(&pool).await {
+            Ok(reaped) => {
+                for task in reaped {
+                    warn!(
+                        "Recovered task {} from expired lease held by {:?}, now {}",
+                        task.id,
+                        task.claimed_by,
+                        task.status.as_str()
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to reap expired task leases: {}", e);
+            }
+        }
+    }
+}
+
+async fn reap_with_pool(pool: &DbPool) -> Result<Vec<ReapedTask>> {
+    let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+    reap_expired_leases(conn.as_mut()).await
+}
+
+/// Reset running tasks whose lease has expired
+///
+/// Each expired run counts as a failed attempt: the task goes back to pending,
+/// or to failed once it has used all of its attempts. The open attempt record
+/// is closed and a monitoring event is emitted per task.
+pub async fn reap_expired_leases(conn: &mut DbConn) -> Result<Vec<ReapedTask>> {
+    let rows = sqlx::query!(
+        r#"
+        WITH expired AS (
+            SELECT id, claimed_by FROM tasks
+            WHERE status = 'running' AND lease_expires_at < NOW()
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE tasks t
+        SET status = CASE WHEN t.current_attempt + 1 >= t.max_attempts THEN 'failed' ELSE 'pending' END,
+            current_attempt = t.current_attempt + 1,
+            last_error = $1,
+            completed_at = CASE WHEN t.current_attempt + 1 >= t.max_attempts THEN NOW() END,
+            claimed_by = NULL,
+            lease_expires_at = NULL,
+            updated_at = NOW()
+        FROM expired e
+        WHERE t.id = e.id
+        RETURNING t.id, t.task_type, t.status as "status: TaskStatus", t.current_attempt,
+            e.claimed_by, t.tenant_id
+        "#,
+        LEASE_EXPIRED_ERROR
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let reaped: Vec<ReapedTask> = rows
+        .into_iter()
+        .map(|row| ReapedTask {
+            id: row.id,
+            task_type: row.task_type,
+            status: row.status,
+            current_attempt: row.current_attempt,
+            claimed_by: row.claimed_by,
+            tenant_id: row.tenant_id,
+        })
+        .collect();
+
+    let task_ids: Vec<Uuid> = reaped.iter().map(|t| t.id).collect();
+    sqlx::query!(
+        r#"
+        UPDATE task_attempts
+        SET finished_at = NOW(),
+            duration_ms = (EXTRACT(EPOCH FROM (NOW() - started_at)) * 1000)::BIGINT,
+            error = $2,
+            error_class = $3
+        WHERE task_id = ANY($1) AND finished_at IS NULL
+        "#,
+        &task_ids,
+        LEASE_EXPIRED_ERROR,
+        TaskErrorClass::LeaseExpired.as_str()
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    for task in &reaped {
+        let event = CreateEventRequest {
+            event_type: "log".to_string(),
+            source: "task-lease-reaper".to_string(),
+            message: Some(format!(
+                "Task {} lease expired on worker {}; task is now {}",
+                task.id,
+                task.claimed_by.as_deref().unwrap_or("unknown"),
+                task.status.as_str()
+            )),
+            level: Some("warn".to_string()),
+            tags: HashMap::from([
+                ("task_id".to_string(), serde_json::json!(task.id)),
+                ("task_type".to_string(), serde_json::json!(task.task_type)),
+                ("worker_id".to_string(), serde_json::json!(task.claimed_by)),
+            ]),
+            payload: HashMap::from([
+                ("status".to_string(), serde_json::json!(task.status)),
+                (
+                    "attempt".to_string(),
+                    serde_json::json!(task.current_attempt),
+                ),
+            ]),
+            recorded_at: None,
+            org_id: None,
+            tenant_id: Some(task.tenant_id),
+        };
+
+        if let Err(e) = monitoring_services::create_event(conn, event).await {
+            warn!("Failed to record lease expiry event for {}: {}", task.id, e);
+        }
+    }
+
+    Ok(reaped)
+}
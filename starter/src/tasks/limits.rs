@@ -0,0 +1,140 @@
+use crate::core::config::{TaskQuotaLimits, TaskQuotasConfig};
+use crate::rbac::UserRole;
+use crate::tasks::types::{QuotaUsage, TaskQuota};
+use crate::{DbConn, Error, Result};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Length of the task submission rate limiting window
+const RATE_LIMIT_WINDOW_SECS: i64 = 60;
+
+/// Enforce the per-user task submission rate limit
+///
+/// Counts tasks the user created within the last minute. Concurrent requests
+/// may briefly overshoot the limit; this is a guard against runaway clients,
+/// not an exact quota.
+pub async fn check_submission_rate(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    limit_per_minute: u32,
+) -> Result<()> {
+    if limit_per_minute == 0 {
+        return Ok(());
+    }
+
+    let window_start = Utc::now() - chrono::Duration::seconds(RATE_LIMIT_WINDOW_SECS);
+
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!", MIN(created_at) as oldest
+        FROM tasks
+        WHERE created_by = $1 AND created_at > $2
+        "#,
+        user_id,
+        window_start
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if row.count < limit_per_minute as i64 {
+        return Ok(());
+    }
+
+    // The window frees up a slot once the oldest task in it expires
+    let retry_after_secs = row
+        .oldest
+        .map(|oldest| {
+            let remaining_ms = (oldest - window_start).num_milliseconds();
+            ((remaining_ms + 999) / 1000).max(1) as u64
+        })
+        .unwrap_or(RATE_LIMIT_WINDOW_SECS as u64);
+
+    Err(Error::RateLimited { retry_after_secs })
+}
+
+/// Resolve the task quota limits that apply to a role
+pub fn quota_limits_for_role(quotas: &TaskQuotasConfig, role: UserRole) -> &TaskQuotaLimits {
+    match role {
+        UserRole::User => &quotas.user,
+        UserRole::Moderator => &quotas.moderator,
+        UserRole::Admin => &quotas.admin,
+    }
+}
+
+/// Get the user's current quota usage
+///
+/// A daily limit set for the user replaces the one of their role.
+pub async fn get_quota_usage(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    role: UserRole,
+    quotas: &TaskQuotasConfig,
+) -> Result<TaskQuota> {
+    let limits = quota_limits_for_role(quotas, role);
+    let day_start = Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc();
+
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status IN ('pending', 'running', 'retrying')) as "pending!",
+            COUNT(*) FILTER (WHERE created_at >= $2) as "daily!",
+            (SELECT max_tasks_per_day FROM user_quotas WHERE user_id = $1) as max_per_day
+        FROM tasks
+        WHERE created_by = $1
+        "#,
+        user_id,
+        day_start
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(TaskQuota {
+        role,
+        pending: QuotaUsage {
+            used: row.pending,
+            limit: (limits.max_pending > 0).then_some(limits.max_pending),
+        },
+        daily: QuotaUsage {
+            used: row.daily,
+            limit: match row.max_per_day {
+                Some(limit) => Some(limit as u32),
+                None => (limits.max_per_day > 0).then_some(limits.max_per_day),
+            },
+        },
+        daily_resets_at: day_start + chrono::Duration::days(1),
+    })
+}
+
+/// Enforce the task quotas of the user and their role before creating a task
+pub async fn check_quota(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    role: UserRole,
+    quotas: &TaskQuotasConfig,
+) -> Result<()> {
+    let quota = get_quota_usage(conn, user_id, role, quotas).await?;
+
+    if let Some(limit) = quota.pending.limit
+        && quota.pending.used >= limit as i64
+    {
+        return Err(Error::QuotaExceeded(format!(
+            "Pending task quota of {limit} reached"
+        )));
+    }
+
+    if let Some(limit) = quota.daily.limit
+        && quota.daily.used >= limit as i64
+    {
+        return Err(Error::QuotaExceeded(format!(
+            "Daily task quota of {limit} reached"
+        )));
+    }
+
+    Ok(())
+}
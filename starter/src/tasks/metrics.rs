@@ -0,0 +1,293 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::State,
+    http::{StatusCode, header},
+    response::Response,
+    routing::get,
+};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+use crate::Database;
+use crate::tasks::queue::{self, escape_label};
+use crate::tasks::types::TaskErrorClass;
+
+/// Upper bounds (seconds) of the handler duration histogram buckets
+pub const DURATION_BUCKETS: [f64; 14] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// In-process counters and histograms for a worker's task executions
+///
+/// Counters reset when the worker restarts, which Prometheus `rate()` and
+/// `increase()` handle. Series are keyed by task type so they can be summed
+/// across workers.
+#[derive(Debug, Default)]
+pub struct TaskMetrics {
+    types: Mutex<BTreeMap<String, TypeMetrics>>,
+}
+
+/// Totals for one task type since the worker started
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskTypeSnapshot {
+    pub completed: u64,
+    /// Failed attempts by error class
+    pub failed: BTreeMap<&'static str, u64>,
+    pub retries: u64,
+    /// Non-cumulative counts per `DURATION_BUCKETS` bucket, plus `+Inf`
+    pub duration_buckets: Vec<u64>,
+    pub duration_sum: f64,
+    pub duration_count: u64,
+}
+
+#[derive(Debug, Default)]
+struct TypeMetrics {
+    completed: u64,
+    failed: BTreeMap<&'static str, u64>,
+    retries: u64,
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`
+    duration_buckets: [u64; DURATION_BUCKETS.len() + 1],
+    duration_sum: f64,
+    duration_count: u64,
+}
+
+impl TaskMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a successful attempt
+    pub fn record_completed(&self, task_type: &str) {
+        self.with_type(task_type, |m| m.completed += 1);
+    }
+
+    /// Record a failed attempt, whether or not it will be retried
+    pub fn record_failed(&self, task_type: &str, error_class: TaskErrorClass) {
+        self.with_type(task_type, |m| {
+            *m.failed.entry(error_class.as_str()).or_default() += 1
+        });
+    }
+
+    /// Record a failed attempt that was scheduled for another try
+    pub fn record_retry(&self, task_type: &str) {
+        self.with_type(task_type, |m| m.retries += 1);
+    }
+
+    /// Record how long a handler ran, including runs cut short by a timeout
+    pub fn observe_duration(&self, task_type: &str, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.with_type(task_type, |m| {
+            m.duration_buckets[bucket] += 1;
+            m.duration_sum += seconds;
+            m.duration_count += 1;
+        });
+    }
+
+    /// Current totals per task type, for storing alongside other metrics
+    pub fn snapshot(&self) -> BTreeMap<String, TaskTypeSnapshot> {
+        let types = self.types.lock().unwrap_or_else(|e| e.into_inner());
+        types
+            .iter()
+            .map(|(task_type, m)| {
+                let snapshot = TaskTypeSnapshot {
+                    completed: m.completed,
+                    failed: m.failed.clone(),
+                    retries: m.retries,
+                    duration_buckets: m.duration_buckets.to_vec(),
+                    duration_sum: m.duration_sum,
+                    duration_count: m.duration_count,
+                };
+                (task_type.clone(), snapshot)
+            })
+            .collect()
+    }
+
+    fn with_type(&self, task_type: &str, f: impl FnOnce(&mut TypeMetrics)) {
+        let mut types = self.types.lock().unwrap_or_else(|e| e.into_inner());
+        f(types.entry(task_type.to_string()).or_default());
+    }
+
+    /// Render all counters and histograms in the Prometheus text format
+    pub fn render_prometheus(&self) -> String {
+        let types = self.types.lock().unwrap_or_else(|e| e.into_inner());
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "# HELP task_completed_total Task attempts that completed successfully"
+        );
+        let _ = writeln!(output, "# TYPE task_completed_total counter");
+        for (task_type, m) in types.iter() {
+            let _ = writeln!(
+                output,
+                "task_completed_total{{task_type=\"{}\"}} {}",
+                escape_label(task_type),
+                m.completed
+            );
+        }
+        output.push('\n');
+
+        let _ = writeln!(
+            output,
+            "# HELP task_failed_total Task attempts that failed, by error class"
+        );
+        let _ = writeln!(output, "# TYPE task_failed_total counter");
+        for (task_type, m) in types.iter() {
+            for (error_class, count) in &m.failed {
+                let _ = writeln!(
+                    output,
+                    "task_failed_total{{task_type=\"{}\",error_class=\"{error_class}\"}} {count}",
+                    escape_label(task_type)
+                );
+            }
+        }
+        output.push('\n');
+
+        let _ = writeln!(
+            output,
+            "# HELP task_retries_total Failed task attempts that were scheduled for retry"
+        );
+        let _ = writeln!(output, "# TYPE task_retries_total counter");
+        for (task_type, m) in types.iter() {
+            let _ = writeln!(
+                output,
+                "task_retries_total{{task_type=\"{}\"}} {}",
+                escape_label(task_type),
+                m.retries
+            );
+        }
+        output.push('\n');
+
+        let _ = writeln!(
+            output,
+            "# HELP task_handler_duration_seconds Time spent in task handlers"
+        );
+        let _ = writeln!(output, "# TYPE task_handler_duration_seconds histogram");
+        for (task_type, m) in types.iter() {
+            let label = escape_label(task_type);
+            let mut cumulative = 0;
+            for (bound, count) in DURATION_BUCKETS.iter().zip(&m.duration_buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    output,
+                    "task_handler_duration_seconds_bucket{{task_type=\"{label}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                output,
+                "task_handler_duration_seconds_bucket{{task_type=\"{label}\",le=\"+Inf\"}} {}",
+                m.duration_count
+            );
+            let _ = writeln!(
+                output,
+                "task_handler_duration_seconds_sum{{task_type=\"{label}\"}} {:.6}",
+                m.duration_sum
+            );
+            let _ = writeln!(
+                output,
+                "task_handler_duration_seconds_count{{task_type=\"{label}\"}} {}",
+                m.duration_count
+            );
+        }
+        output.push('\n');
+
+        output
+    }
+}
+
+#[derive(Clone)]
+struct MetricsState {
+    metrics: Arc<TaskMetrics>,
+    database: Database,
+}
+
+/// Router serving the worker's metrics at `/metrics`
+pub fn metrics_router(metrics: Arc<TaskMetrics>, database: Database) -> Router {
+    Router::new()
+        .route("/metrics", get(get_worker_metrics))
+        .with_state(MetricsState { metrics, database })
+}
+
+/// Serve the worker metrics endpoint until the task is aborted
+pub async fn serve_metrics(
+    listener: TcpListener,
+    metrics: Arc<TaskMetrics>,
+    database: Database,
+) -> std::io::Result<()> {
+    axum::serve(listener, metrics_router(metrics, database)).await
+}
+
+async fn get_worker_metrics(State(state): State<MetricsState>) -> Response {
+    let mut output = state.metrics.render_prometheus();
+
+    // Queue depth is shared by all workers, so it comes from the database
+    match state.database.pool.acquire().await {
+        Ok(mut conn) => match queue::get_queue_stats(conn.as_mut(), None).await {
+            Ok(stats) => output.push_str(&queue::render_prometheus(&stats)),
+            Err(e) => tracing::warn!("Failed to load task queue stats for metrics: {}", e),
+        },
+        Err(e) => tracing::warn!("Failed to acquire connection for metrics: {}", e),
+    }
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )
+        .body(Body::from(output))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_task_metrics() {
+        let metrics = TaskMetrics::new();
+        metrics.record_completed("email");
+        metrics.record_completed("email");
+        metrics.record_failed("email", TaskErrorClass::TimedOut);
+        metrics.record_retry("email");
+        metrics.observe_duration("email", Duration::from_millis(40));
+        metrics.observe_duration("email", Duration::from_secs(400));
+
+        let output = metrics.render_prometheus();
+
+        assert!(output.contains("task_completed_total{task_type=\"email\"} 2"));
+        assert!(
+            output.contains("task_failed_total{task_type=\"email\",error_class=\"timed_out\"} 1")
+        );
+        assert!(output.contains("task_retries_total{task_type=\"email\"} 1"));
+        assert!(output.contains("# TYPE task_handler_duration_seconds histogram"));
+        assert!(
+            output.contains(
+                "task_handler_duration_seconds_bucket{task_type=\"email\",le=\"0.025\"} 0"
+            )
+        );
+        assert!(
+            output.contains(
+                "task_handler_duration_seconds_bucket{task_type=\"email\",le=\"0.05\"} 1"
+            )
+        );
+        assert!(
+            output
+                .contains("task_handler_duration_seconds_bucket{task_type=\"email\",le=\"300\"} 1")
+        );
+        assert!(
+            output.contains(
+                "task_handler_duration_seconds_bucket{task_type=\"email\",le=\"+Inf\"} 2"
+            )
+        );
+        assert!(output.contains("task_handler_duration_seconds_count{task_type=\"email\"} 2"));
+    }
+}
@@ -1,10 +1,22 @@
 pub mod api;
+pub mod archive;
+pub mod circuit;
+pub mod export;
 pub mod handlers;
 pub mod helpers;
+pub mod leases;
+pub mod limits;
+pub mod metrics;
 pub mod processor;
+pub mod queue;
+pub mod registry;
 pub mod retry;
+pub mod schedules;
+pub mod services;
+pub mod templates;
 pub mod types;
 
 pub use processor::TaskProcessor;
+pub use registry::HandlerRegistry;
 pub use retry::{CircuitBreaker, CircuitState, RetryStrategy};
-pub use types::{CreateTaskRequest, Task, TaskContext, TaskPriority, TaskStatus};
+pub use types::{CreateTaskRequest, Task, TaskContext, TaskLogLevel, TaskPriority, TaskStatus};
@@ -1,31 +1,71 @@
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
-use tokio::sync::{RwLock, Semaphore};
-use tokio::time::{interval, timeout};
-use tracing::{debug, error, info, warn};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::time::{sleep, timeout};
+use tracing::{Instrument, Span, debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::Database;
+use crate::core::telemetry;
 use crate::tasks::{
+    circuit,
     handlers::TaskHandler,
+    metrics::TaskMetrics,
     retry::CircuitBreaker,
+    services::TaskServices,
     types::{
-        CreateTaskRequest, Task, TaskContext, TaskError, TaskFilter, TaskPriority, TaskResult,
-        TaskResult2, TaskStats, TaskStatus,
+        CreateTaskRequest, Task, TaskAttempt, TaskContext, TaskError, TaskErrorClass, TaskFilter,
+        TaskPriority, TaskResult, TaskResult2, TaskStats, TaskStatus,
     },
 };
+use crate::tenants::DEFAULT_TENANT_ID;
 
 pub type TaskHandlerFn = Box<dyn TaskHandler + Send + Sync>;
 
+/// Metadata key holding the trace context of the request that created a task
+pub const TRACE_CONTEXT_METADATA_KEY: &str = "trace_context";
+
+/// Span of one task execution, continuing the trace of the request that created the task
+fn task_span(task: &Task) -> Span {
+    let span = tracing::info_span!(
+        parent: None,
+        "task",
+        task_id = %task.id,
+        task_type = %task.task_type,
+        attempt = task.current_attempt + 1,
+        otel.name = format!("task {}", task.task_type),
+        otel.kind = "consumer",
+    );
+    if let Some(context) = task
+        .metadata
+        .get(TRACE_CONTEXT_METADATA_KEY)
+        .and_then(|context| serde_json::from_value(context.clone()).ok())
+    {
+        telemetry::set_parent_from_map(&span, &context);
+    }
+    span
+}
+
 #[derive(Clone)]
 pub struct TaskProcessor {
     database: Database,
     handlers: Arc<RwLock<HashMap<String, TaskHandlerFn>>>,
     circuit_breakers: Arc<RwLock<HashMap<String, CircuitBreaker>>>,
+    /// Last manual circuit control version applied per task type
+    circuit_control_versions: Arc<RwLock<HashMap<String, i64>>>,
     semaphore: Arc<Semaphore>,
+    /// Permits the semaphore is meant to hold, changed by reloads
+    concurrency_limit: Arc<AtomicUsize>,
+    /// Maintenance mode forced by the configuration, changed by reloads
+    maintenance_forced: Arc<AtomicBool>,
+    metrics: Arc<TaskMetrics>,
+    services: TaskServices,
     config: ProcessorConfig,
+    worker_id: String,
 }
 
 #[derive(Debug, Clone)]
@@ -33,10 +73,28 @@ pub struct ProcessorConfig {
     pub poll_interval: Duration,
     pub task_timeout: Duration,
     pub max_concurrent_tasks: usize,
+    /// Maximum number of tasks claimed per poll
     pub batch_size: usize,
+    pub claim_strategy: ClaimStrategy,
+    /// How long a claimed task stays owned by this worker without a heartbeat
+    pub lease_duration: Duration,
     pub enable_circuit_breaker: bool,
 }
 
+/// How a worker takes ownership of ready tasks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimStrategy {
+    /// Claim the whole batch in one `FOR UPDATE SKIP LOCKED` statement.
+    /// Concurrent workers never contend for the same rows.
+    #[default]
+    Batch,
+    /// Read ready tasks without locking, then claim each one with its own
+    /// conditional update. Workers polling at the same time race for the
+    /// same rows and the losers skip them.
+    Single,
+}
+
 impl Default for ProcessorConfig {
     fn default() -> Self {
         Self {
@@ -44,6 +102,8 @@ impl Default for ProcessorConfig {
             task_timeout: Duration::from_secs(300), // 5 minutes
             max_concurrent_tasks: 10,
             batch_size: 50,
+            claim_strategy: ClaimStrategy::default(),
+            lease_duration: Duration::from_secs(60),
             enable_circuit_breaker: true,
         }
     }
@@ -55,18 +115,98 @@ impl TaskProcessor {
             database,
             handlers: Arc::new(RwLock::new(HashMap::new())),
             circuit_breakers: Arc::new(RwLock::new(HashMap::new())),
+            circuit_control_versions: Arc::new(RwLock::new(HashMap::new())),
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_tasks)),
+            concurrency_limit: Arc::new(AtomicUsize::new(config.max_concurrent_tasks)),
+            maintenance_forced: Arc::new(AtomicBool::new(false)),
+            metrics: Arc::new(TaskMetrics::new()),
+            services: TaskServices::default(),
             config,
+            worker_id: default_worker_id(),
+        }
+    }
+
+    /// Replace the services handed to every handler through `TaskContext`
+    pub fn with_services(mut self, services: TaskServices) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Change how many tasks run at once
+    ///
+    /// When the limit shrinks, running tasks finish before the freed slots are dropped.
+    pub fn set_max_concurrent_tasks(&self, max: usize) {
+        let previous = self.concurrency_limit.swap(max, Ordering::SeqCst);
+        if max > previous {
+            self.semaphore.add_permits(max - previous);
+        } else if max < previous {
+            let excess = previous - max;
+            let busy = excess - self.semaphore.forget_permits(excess);
+            if busy > 0 {
+                let semaphore = self.semaphore.clone();
+                tokio::spawn(async move {
+                    if let Ok(permits) = semaphore.acquire_many(busy as u32).await {
+                        permits.forget();
+                    }
+                });
+            }
+        }
+        info!("Worker concurrency changed from {previous} to {max}");
+    }
+
+    /// Keep the worker paused whatever the maintenance toggle says
+    pub fn set_maintenance_mode(&self, forced: bool) {
+        let previous = self.maintenance_forced.swap(forced, Ordering::SeqCst);
+        if previous != forced {
+            info!("Maintenance mode forced by the configuration: {forced}");
+        }
+    }
+
+    /// Whether the API is in maintenance mode, when no new tasks are claimed
+    ///
+    /// Read from the database on every poll, so the admin toggle reaches the
+    /// worker without going through the API's cache.
+    async fn in_maintenance(&self) -> bool {
+        if self.maintenance_forced.load(Ordering::SeqCst) {
+            return true;
+        }
+        let toggle = async {
+            let mut conn = self.database.pool.acquire().await?;
+            crate::maintenance::services::get_toggle(conn.as_mut()).await
+        }
+        .await;
+        match toggle {
+            Ok(toggle) => toggle.enabled,
+            Err(e) => {
+                warn!("Failed to load maintenance mode: {}", e);
+                false
+            }
         }
     }
 
+    /// Identifier recorded on each task attempt executed by this processor
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    /// Execution counters and histograms collected by this processor
+    pub fn metrics(&self) -> Arc<TaskMetrics> {
+        self.metrics.clone()
+    }
+
     /// Register a task handler for a specific task type
     pub async fn register_handler<H>(&self, task_type: String, handler: H)
     where
         H: TaskHandler + Send + Sync + 'static,
     {
+        self.register_boxed_handler(task_type, Box::new(handler))
+            .await;
+    }
+
+    /// Register an already boxed task handler for a specific task type
+    pub async fn register_boxed_handler(&self, task_type: String, handler: TaskHandlerFn) {
         let mut handlers = self.handlers.write().await;
-        handlers.insert(task_type.clone(), Box::new(handler));
+        handlers.insert(task_type.clone(), handler);
 
         if self.config.enable_circuit_breaker {
             let mut circuit_breakers = self.circuit_breakers.write().await;
@@ -88,45 +228,102 @@ impl TaskProcessor {
 
         let task_id = Uuid::new_v4();
         let retry_strategy_json = serde_json::to_value(&request.retry_strategy)?;
-        let metadata_json = serde_json::to_value(&request.metadata)?;
+        let mut metadata_json = serde_json::to_value(&request.metadata)?;
+        // Lets the task's execution continue the trace of the request that created it
+        let trace_context = telemetry::current_trace_context();
+        if !trace_context.is_empty() {
+            metadata_json[TRACE_CONTEXT_METADATA_KEY] = serde_json::json!(trace_context);
+        }
         let max_attempts = request.retry_strategy.max_attempts() as i32;
 
-        let task = sqlx::query_as!(
-            Task,
-            r#"
-            INSERT INTO tasks (
-                id, task_type, payload, status, priority, retry_strategy, 
-                max_attempts, current_attempt, created_at, updated_at, 
-                scheduled_at, created_by, metadata
+        // An existing active task with the same dedupe key wins; if it finishes
+        // between the insert and the lookup, try the insert again
+        for _ in 0..3 {
+            let created = sqlx::query_as!(
+                Task,
+                r#"
+                INSERT INTO tasks (
+                    id, task_type, payload, status, priority, retry_strategy, 
+                    max_attempts, current_attempt, created_at, updated_at, 
+                    scheduled_at, created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id,
+                    tenant_id
+                )
+                VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                    COALESCE((SELECT tenant_id FROM users WHERE id = $12), $19)
+                )
+                ON CONFLICT (created_by, dedupe_key)
+                    WHERE dedupe_key IS NOT NULL AND status IN ('pending', 'running', 'retrying')
+                    DO NOTHING
+                RETURNING 
+                    id, task_type, payload, 
+                    status as "status: TaskStatus", 
+                    priority as "priority: TaskPriority",
+                    retry_strategy, max_attempts, current_attempt, last_error,
+                    created_at, updated_at, scheduled_at, started_at, completed_at,
+                    created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+                "#,
+                task_id,
+                request.task_type,
+                request.payload,
+                TaskStatus::Pending as TaskStatus,
+                request.priority.clone() as TaskPriority,
+                retry_strategy_json,
+                max_attempts,
+                0,
+                Utc::now(),
+                Utc::now(),
+                request.scheduled_at,
+                request.created_by,
+                metadata_json,
+                &request.tags,
+                request.timeout_seconds,
+                request.dedupe_key,
+                request.parent_task_id,
+                request.org_id,
+                DEFAULT_TENANT_ID
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            RETURNING 
-                id, task_type, payload, 
-                status as "status: TaskStatus", 
-                priority as "priority: TaskPriority",
-                retry_strategy, max_attempts, current_attempt, last_error,
-                created_at, updated_at, scheduled_at, started_at, completed_at,
-                created_by, metadata
-            "#,
-            task_id,
-            request.task_type,
-            request.payload,
-            TaskStatus::Pending as TaskStatus,
-            request.priority as TaskPriority,
-            retry_strategy_json,
-            max_attempts,
-            0,
-            Utc::now(),
-            Utc::now(),
-            request.scheduled_at,
-            request.created_by,
-            metadata_json
-        )
-        .fetch_one(&mut *conn)
-        .await?;
+            .fetch_optional(&mut *conn)
+            .await?;
 
-        debug!("Created task {} of type {}", task.id, task.task_type);
-        Ok(task)
+            if let Some(task) = created {
+                debug!("Created task {} of type {}", task.id, task.task_type);
+                return Ok(task);
+            }
+
+            let existing = sqlx::query_as!(
+                Task,
+                r#"
+                SELECT 
+                    id, task_type, payload, 
+                    status as "status: TaskStatus", 
+                    priority as "priority: TaskPriority",
+                    retry_strategy, max_attempts, current_attempt, last_error,
+                    created_at, updated_at, scheduled_at, started_at, completed_at,
+                    created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+                FROM tasks
+                WHERE created_by IS NOT DISTINCT FROM $1 AND dedupe_key = $2
+                  AND status IN ('pending', 'running', 'retrying')
+                "#,
+                request.created_by,
+                request.dedupe_key
+            )
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            if let Some(task) = existing {
+                debug!(
+                    "Deduplicated task creation onto existing task {} (key {:?})",
+                    task.id, task.dedupe_key
+                );
+                return Ok(task);
+            }
+        }
+
+        Err(TaskError::Execution(format!(
+            "Could not create or find task for dedupe key {:?}",
+            request.dedupe_key
+        )))
     }
 
     /// Get task by ID
@@ -142,7 +339,7 @@ impl TaskProcessor {
                 priority as "priority: TaskPriority",
                 retry_strategy, max_attempts, current_attempt, last_error,
                 created_at, updated_at, scheduled_at, started_at, completed_at,
-                created_by, metadata
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
             FROM tasks 
             WHERE id = $1
             "#,
@@ -167,7 +364,7 @@ impl TaskProcessor {
                 priority as "priority: TaskPriority",
                 retry_strategy, max_attempts, current_attempt, last_error,
                 created_at, updated_at, scheduled_at, started_at, completed_at,
-                created_by, metadata
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
             FROM tasks 
             WHERE ($1::TEXT IS NULL OR task_type = $1)
               AND ($2::TEXT IS NULL OR status = $2)
@@ -175,9 +372,18 @@ impl TaskProcessor {
               AND ($4::UUID IS NULL OR created_by = $4)
               AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
               AND ($6::TIMESTAMPTZ IS NULL OR created_at <= $6)
+              AND ($7::TEXT IS NULL OR tags @> ARRAY[$7::TEXT])
+              AND ($8::TEXT IS NULL OR
+                   (jsonb_to_tsvector('simple', payload, '["string", "numeric"]')
+                    || jsonb_to_tsvector('simple', metadata, '["string", "numeric"]'))
+                   @@ websearch_to_tsquery('simple', $8))
+              AND ($11::UUID IS NULL OR org_id = $11)
+              AND ($12::UUID IS NULL OR created_by = $12
+                   OR org_id IN (SELECT org_id FROM org_members WHERE user_id = $12))
+              AND ($13::UUID IS NULL OR tenant_id = $13)
             ORDER BY priority DESC, created_at ASC
-            LIMIT $7
-            OFFSET $8
+            LIMIT $9
+            OFFSET $10
             "#,
             filter.task_type,
             filter.status as Option<TaskStatus>,
@@ -185,8 +391,13 @@ impl TaskProcessor {
             filter.created_by,
             filter.created_after,
             filter.created_before,
+            filter.tag,
+            filter.search,
             filter.limit.unwrap_or(100),
-            filter.offset.unwrap_or(0)
+            filter.offset.unwrap_or(0),
+            filter.org_id,
+            filter.visible_to,
+            filter.tenant_id
         )
         .fetch_all(&mut *conn)
         .await?;
@@ -194,8 +405,8 @@ impl TaskProcessor {
         Ok(tasks)
     }
 
-    /// Get task statistics
-    pub async fn get_stats(&self) -> TaskResult2<TaskStats> {
+    /// Get task statistics of a tenant
+    pub async fn get_stats(&self, tenant_id: Uuid) -> TaskResult2<TaskStats> {
         let mut conn = self.database.pool.acquire().await?;
 
         let stats = sqlx::query!(
@@ -209,7 +420,9 @@ impl TaskProcessor {
                 COUNT(*) FILTER (WHERE status = 'cancelled') as cancelled,
                 COUNT(*) FILTER (WHERE status = 'retrying') as retrying
             FROM tasks
-            "#
+            WHERE tenant_id = $1
+            "#,
+            tenant_id
         )
         .fetch_one(&mut *conn)
         .await?;
@@ -232,36 +445,123 @@ impl TaskProcessor {
             self.config
         );
 
-        let mut interval = interval(self.config.poll_interval);
+        // Keep leases on this worker's running tasks alive; stops when the worker loop is dropped
+        let _heartbeat = AbortOnDrop(tokio::spawn(self.clone().lease_heartbeat()));
 
         loop {
-            interval.tick().await;
+            let (processed, full) = match self.process_batch().await {
+                Ok(batch) => batch,
+                Err(e) => {
+                    error!("Error processing task batch: {}", e);
+                    (0, false)
+                }
+            };
+
+            // A full batch means more tasks may already be due, so go again immediately
+            if full {
+                continue;
+            }
+
+            let mut sleep_for = self.time_until_next_task().await;
+
+            // Overdue tasks we could not claim (e.g. taken by another worker) should not
+            // turn this into a busy loop
+            if sleep_for.is_zero() && processed == 0 {
+                sleep_for = self.config.poll_interval;
+            }
 
-            if let Err(e) = self.process_batch().await {
-                error!("Error processing task batch: {}", e);
+            if !sleep_for.is_zero() {
+                sleep(sleep_for).await;
             }
         }
     }
 
+    /// How long the worker can sleep before the next scheduled task is due
+    ///
+    /// Capped at the poll interval so newly created tasks are still picked up.
+    async fn time_until_next_task(&self) -> Duration {
+        match self.next_scheduled_in_ms().await {
+            Ok(next_due_in_ms) => sleep_duration(self.config.poll_interval, next_due_in_ms),
+            Err(e) => {
+                error!("Failed to look up next scheduled task: {}", e);
+                self.config.poll_interval
+            }
+        }
+    }
+
+    /// Milliseconds until the earliest pending/retrying task becomes due (negative if overdue)
+    async fn next_scheduled_in_ms(&self) -> TaskResult2<Option<f64>> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        // Computed in the database so worker clock skew does not matter
+        let next_due_in_ms = sqlx::query_scalar!(
+            r#"
+            SELECT (EXTRACT(EPOCH FROM (MIN(scheduled_at) - NOW())) * 1000)::FLOAT8
+            FROM tasks
+            WHERE status IN ('pending', 'retrying') AND scheduled_at IS NOT NULL
+              AND task_type NOT IN (SELECT task_type FROM task_types WHERE paused)
+            "#
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(next_due_in_ms)
+    }
+
     /// Process a batch of ready tasks
-    async fn process_batch(&self) -> TaskResult2<()> {
-        let tasks = self.fetch_ready_tasks().await?;
+    ///
+    /// Returns how many were picked up, and whether that was as many as the
+    /// batch could hold, in which case more may already be due.
+    async fn process_batch(&self) -> TaskResult2<(usize, bool)> {
+        if self.in_maintenance().await {
+            debug!("In maintenance mode, not claiming tasks");
+            return Ok((0, false));
+        }
+
+        if self.config.enable_circuit_breaker
+            && let Err(e) = self.apply_circuit_controls().await
+        {
+            warn!("Failed to load circuit breaker controls: {}", e);
+        }
+
+        let (tasks, limit, mut permits) = match self.config.claim_strategy {
+            ClaimStrategy::Batch => {
+                // Claimed tasks are running and their lease is ticking, so only
+                // claim as many as there are free slots to run them in
+                let permits = self.acquire_free_permits(self.config.batch_size).await?;
+                let tasks = self.claim_ready_tasks(permits.len()).await?;
+                (tasks, permits.len(), permits)
+            }
+            ClaimStrategy::Single => (
+                self.fetch_ready_tasks().await?,
+                self.config.batch_size,
+                Vec::new(),
+            ),
+        };
+        permits.truncate(tasks.len());
 
         if tasks.is_empty() {
-            return Ok(());
+            return Ok((0, false));
         }
 
+        let count = tasks.len();
+
         info!("Processing {} ready tasks", tasks.len());
 
         let mut handles = Vec::new();
 
         for task in tasks {
             let processor = self.clone();
-            let handle = tokio::spawn(async move {
-                if let Err(e) = processor.process_task(task).await {
-                    error!("Error processing task: {}", e);
+            let permit = permits.pop();
+            let span = task_span(&task);
+            let handle = tokio::spawn(
+                async move {
+                    if let Err(e) = processor.process_task(task, permit).await {
+                        error!("Error processing task: {}", e);
+                    }
                 }
-            });
+                .instrument(span),
+            );
             handles.push(handle);
         }
 
@@ -272,10 +572,103 @@ impl TaskProcessor {
             }
         }
 
-        Ok(())
+        Ok((count, count >= limit))
+    }
+
+    /// Wait for a free task slot, then take up to `max` slots that are free
+    async fn acquire_free_permits(&self, max: usize) -> TaskResult2<Vec<OwnedSemaphorePermit>> {
+        let first = self.semaphore.clone().acquire_owned().await.map_err(|_| {
+            TaskError::Execution("Failed to acquire semaphore permit for task claims".to_string())
+        })?;
+
+        let mut permits = vec![first];
+        while permits.len() < max {
+            match self.semaphore.clone().try_acquire_owned() {
+                Ok(permit) => permits.push(permit),
+                Err(_) => break,
+            }
+        }
+        Ok(permits)
+    }
+
+    /// Periodically extend the lease on every task this worker is running
+    async fn lease_heartbeat(self) {
+        let mut interval = tokio::time::interval(self.config.lease_duration / 3);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.renew_leases().await {
+                warn!("Failed to renew task leases: {}", e);
+            }
+        }
+    }
+
+    async fn renew_leases(&self) -> TaskResult2<u64> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE tasks
+            SET lease_expires_at = NOW() + $2 * INTERVAL '1 second'
+            WHERE claimed_by = $1 AND status = 'running'
+            "#,
+            self.worker_id,
+            self.config.lease_duration.as_secs_f64()
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Claim up to `limit` ready tasks in a single statement
+    ///
+    /// Rows locked by another worker's claim are skipped rather than waited on,
+    /// so concurrent workers always receive disjoint batches.
+    async fn claim_ready_tasks(&self, limit: usize) -> TaskResult2<Vec<Task>> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let mut tasks = sqlx::query_as!(
+            Task,
+            r#"
+            UPDATE tasks
+            SET status = 'running', started_at = NOW(), updated_at = NOW(),
+                claimed_by = $2, lease_expires_at = NOW() + $3 * INTERVAL '1 second'
+            WHERE id IN (
+                SELECT id FROM tasks
+                WHERE (status = 'pending' OR status = 'retrying')
+                  AND (scheduled_at IS NULL OR scheduled_at <= NOW())
+                  AND task_type NOT IN (SELECT task_type FROM task_types WHERE paused)
+                ORDER BY priority DESC, created_at ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING 
+                id, task_type, payload, 
+                status as "status: TaskStatus", 
+                priority as "priority: TaskPriority",
+                retry_strategy, max_attempts, current_attempt, last_error,
+                created_at, updated_at, scheduled_at, started_at, completed_at,
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            "#,
+            limit as i64,
+            self.worker_id,
+            self.config.lease_duration.as_secs_f64()
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // RETURNING does not preserve the subquery order
+        tasks.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.created_at.cmp(&b.created_at))
+        });
+
+        Ok(tasks)
     }
 
-    /// Fetch ready tasks from database
+    /// Fetch ready tasks from database without claiming them
     async fn fetch_ready_tasks(&self) -> TaskResult2<Vec<Task>> {
         let mut conn = self.database.pool.acquire().await?;
 
@@ -288,10 +681,11 @@ impl TaskProcessor {
                 priority as "priority: TaskPriority",
                 retry_strategy, max_attempts, current_attempt, last_error,
                 created_at, updated_at, scheduled_at, started_at, completed_at,
-                created_by, metadata
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
             FROM tasks 
             WHERE (status = 'pending' OR status = 'retrying')
               AND (scheduled_at IS NULL OR scheduled_at <= NOW())
+              AND task_type NOT IN (SELECT task_type FROM task_types WHERE paused)
             ORDER BY priority DESC, created_at ASC
             LIMIT $1
             "#,
@@ -304,48 +698,81 @@ impl TaskProcessor {
     }
 
     /// Process a single task
-    async fn process_task(&self, mut task: Task) -> TaskResult2<()> {
-        // Acquire semaphore permit to limit concurrency (with proper error handling)
-        let _permit = self.semaphore.acquire().await.map_err(|_| {
-            TaskError::Execution(
-                "Failed to acquire semaphore permit for task processing".to_string(),
-            )
-        })?;
+    ///
+    /// Batch claims hand over the permit they were claimed with; otherwise one
+    /// is acquired here to limit concurrency.
+    async fn process_task(
+        &self,
+        mut task: Task,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> TaskResult2<()> {
+        let _permit = match permit {
+            Some(permit) => permit,
+            None => self.semaphore.clone().acquire_owned().await.map_err(|_| {
+                TaskError::Execution(
+                    "Failed to acquire semaphore permit for task processing".to_string(),
+                )
+            })?,
+        };
 
         debug!("Processing task {} of type {}", task.id, task.task_type);
 
-        // Update task status to running
-        if let Err(e) = self.update_task_status(task.id, TaskStatus::Running).await {
+        // Batch claims already moved the task to running
+        if self.config.claim_strategy == ClaimStrategy::Single
+            && let Err(e) = self.update_task_status(task.id, TaskStatus::Running).await
+        {
             error!("Failed to update task status to running: {}", e);
             return Err(e);
         }
 
-        let context = TaskContext::from(&task);
+        let attempt_id = self
+            .start_attempt(task.id, task.current_attempt + 1)
+            .await?;
+
+        let context = TaskContext::from(&task)
+            .with_pool(self.database.pool.clone())
+            .with_services(self.services.clone());
 
         // Check circuit breaker if enabled
-        if self.config.enable_circuit_breaker {
-            let mut circuit_breakers = self.circuit_breakers.write().await;
-            if let Some(cb) = circuit_breakers.get_mut(&task.task_type)
-                && !cb.should_allow_operation()
-            {
-                let error = "Circuit breaker is open";
-                warn!("Task {} blocked by circuit breaker", task.id);
-                self.mark_task_failed(task.id, error).await?;
-                return Ok(());
-            }
+        if self
+            .with_circuit_breaker(&task.task_type, CircuitBreaker::should_allow_operation)
+            .await
+            == Some(false)
+        {
+            let error = "Circuit breaker is open";
+            warn!("Task {} blocked by circuit breaker", task.id);
+            self.metrics
+                .record_failed(&task.task_type, TaskErrorClass::CircuitOpen);
+            self.finish_attempt(attempt_id, Some((TaskErrorClass::CircuitOpen, error)))
+                .await?;
+            self.mark_task_failed(task.id, error).await?;
+            return Ok(());
         }
 
+        let task_timeout = self.resolve_timeout(&task).await;
+
         // Execute task with timeout
         let result = {
             let handlers = self.handlers.read().await;
             match handlers.get(&task.task_type) {
                 Some(handler) => {
                     // Execute handler with timeout
-                    timeout(self.config.task_timeout, handler.handle(context)).await
+                    let started = std::time::Instant::now();
+                    let result = timeout(task_timeout, handler.handle(context)).await;
+                    self.metrics
+                        .observe_duration(&task.task_type, started.elapsed());
+                    result
                 }
                 None => {
                     let error = format!("No handler registered for task type: {}", task.task_type);
                     error!("{}", error);
+                    self.metrics
+                        .record_failed(&task.task_type, TaskErrorClass::HandlerNotFound);
+                    self.finish_attempt(
+                        attempt_id,
+                        Some((TaskErrorClass::HandlerNotFound, &error)),
+                    )
+                    .await?;
                     self.mark_task_failed(task.id, &error).await?;
                     return Err(TaskError::HandlerNotFound(task.task_type));
                 }
@@ -355,30 +782,29 @@ impl TaskProcessor {
         match result {
             Ok(Ok(task_result)) => {
                 // Task completed successfully
-                if self.config.enable_circuit_breaker {
-                    let mut circuit_breakers = self.circuit_breakers.write().await;
-                    if let Some(cb) = circuit_breakers.get_mut(&task.task_type) {
-                        cb.record_success();
-                    }
-                }
+                self.with_circuit_breaker(&task.task_type, CircuitBreaker::record_success)
+                    .await;
+                self.metrics.record_completed(&task.task_type);
+                self.finish_attempt(attempt_id, None).await?;
                 self.mark_task_completed(task.id, task_result).await?;
                 info!("Task {} completed successfully", task.id);
             }
             Ok(Err(e)) => {
                 // Task failed
-                if self.config.enable_circuit_breaker {
-                    let mut circuit_breakers = self.circuit_breakers.write().await;
-                    if let Some(cb) = circuit_breakers.get_mut(&task.task_type) {
-                        cb.record_failure();
-                    }
-                }
+                self.with_circuit_breaker(&task.task_type, CircuitBreaker::record_failure)
+                    .await;
 
                 task.current_attempt += 1;
                 let error_msg = e.to_string();
                 let task_id = task.id;
                 let current_attempt = task.current_attempt;
+                self.metrics
+                    .record_failed(&task.task_type, TaskErrorClass::Error);
+                self.finish_attempt(attempt_id, Some((TaskErrorClass::Error, &error_msg)))
+                    .await?;
 
                 if task.can_retry() {
+                    self.metrics.record_retry(&task.task_type);
                     self.schedule_retry(task, &error_msg).await?;
                     warn!(
                         "Task {} failed, scheduled for retry (attempt {})",
@@ -394,18 +820,23 @@ impl TaskProcessor {
             }
             Err(_) => {
                 // Task timed out
-                let error = "Task execution timed out";
-                if self.config.enable_circuit_breaker {
-                    let mut circuit_breakers = self.circuit_breakers.write().await;
-                    if let Some(cb) = circuit_breakers.get_mut(&task.task_type) {
-                        cb.record_failure();
-                    }
-                }
+                let error = format!(
+                    "Task execution timed out after {}s",
+                    task_timeout.as_secs_f64()
+                );
+                let error = error.as_str();
+                self.with_circuit_breaker(&task.task_type, CircuitBreaker::record_failure)
+                    .await;
 
                 task.current_attempt += 1;
                 let task_id = task.id;
+                self.metrics
+                    .record_failed(&task.task_type, TaskErrorClass::TimedOut);
+                self.finish_attempt(attempt_id, Some((TaskErrorClass::TimedOut, error)))
+                    .await?;
 
                 if task.can_retry() {
+                    self.metrics.record_retry(&task.task_type);
                     self.schedule_retry(task, error).await?;
                     warn!("Task {} timed out, scheduled for retry", task_id);
                 } else {
@@ -418,6 +849,228 @@ impl TaskProcessor {
         Ok(())
     }
 
+    /// Run an operation against a task type's circuit breaker
+    ///
+    /// Returns `None` when circuit breaking is disabled or the task type has no
+    /// breaker. State changes are persisted so the admin API can report them.
+    async fn with_circuit_breaker<R>(
+        &self,
+        task_type: &str,
+        operation: impl FnOnce(&mut CircuitBreaker) -> R,
+    ) -> Option<R> {
+        if !self.config.enable_circuit_breaker {
+            return None;
+        }
+
+        let (result, transition) = {
+            let mut circuit_breakers = self.circuit_breakers.write().await;
+            let cb = circuit_breakers.get_mut(task_type)?;
+            let before = cb.state().clone();
+            let result = operation(cb);
+            let after = cb.state().clone();
+            let transition = (before != after).then(|| (before, after, cb.failure_count()));
+            (result, transition)
+        };
+
+        if let Some((from, to, failure_count)) = transition {
+            info!(
+                "Circuit breaker for {} changed from {} to {}",
+                task_type,
+                from.as_str(),
+                to.as_str()
+            );
+            let recorded = match self.database.pool.acquire().await {
+                Ok(mut conn) => {
+                    circuit::record_transition(
+                        conn.as_mut(),
+                        task_type,
+                        &from,
+                        &to,
+                        failure_count,
+                        &self.worker_id,
+                    )
+                    .await
+                }
+                Err(e) => Err(crate::Error::from_sqlx(e)),
+            };
+            if let Err(e) = recorded {
+                warn!(
+                    "Failed to record circuit breaker state for {}: {}",
+                    task_type, e
+                );
+            }
+        }
+
+        Some(result)
+    }
+
+    /// Apply manual trips/resets made through the admin API since the last poll
+    async fn apply_circuit_controls(&self) -> TaskResult2<()> {
+        let mut conn = self.database.pool.acquire().await?;
+        let controls = circuit::list_controls(conn.as_mut())
+            .await
+            .map_err(|e| TaskError::Execution(e.to_string()))?;
+
+        let mut versions = self.circuit_control_versions.write().await;
+        let mut circuit_breakers = self.circuit_breakers.write().await;
+        for control in controls {
+            if versions.get(&control.task_type) == Some(&control.version) {
+                continue;
+            }
+            if let Some(cb) = circuit_breakers.get_mut(&control.task_type) {
+                if control.forced {
+                    cb.trip();
+                } else {
+                    cb.reset();
+                }
+                info!(
+                    "Applied manual circuit breaker {} for {}",
+                    if control.forced { "trip" } else { "reset" },
+                    control.task_type
+                );
+            }
+            versions.insert(control.task_type, control.version);
+        }
+
+        Ok(())
+    }
+
+    /// Record the start of a task attempt, returning the attempt id
+    async fn start_attempt(&self, task_id: Uuid, attempt_number: i32) -> TaskResult2<Uuid> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let attempt_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO task_attempts (task_id, attempt_number, worker_id, started_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            task_id,
+            attempt_number,
+            self.worker_id,
+            Utc::now()
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(attempt_id)
+    }
+
+    /// Resolve the execution timeout: task override, then task type default, then processor config
+    async fn resolve_timeout(&self, task: &Task) -> Duration {
+        if let Some(timeout_seconds) = task.timeout_seconds {
+            return Duration::from_secs(timeout_seconds as u64);
+        }
+
+        match self.task_type_timeout(&task.task_type).await {
+            Ok(Some(timeout_seconds)) => Duration::from_secs(timeout_seconds as u64),
+            Ok(None) => self.config.task_timeout,
+            Err(e) => {
+                warn!(
+                    "Failed to look up timeout for task type {}: {}",
+                    task.task_type, e
+                );
+                self.config.task_timeout
+            }
+        }
+    }
+
+    /// Default timeout registered for a task type, if any
+    async fn task_type_timeout(&self, task_type: &str) -> TaskResult2<Option<i32>> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let timeout_seconds = sqlx::query_scalar!(
+            "SELECT timeout_seconds FROM task_types WHERE task_type = $1",
+            task_type
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .flatten();
+
+        Ok(timeout_seconds)
+    }
+
+    /// Record the outcome of a task attempt (None for success)
+    async fn finish_attempt(
+        &self,
+        attempt_id: Uuid,
+        failure: Option<(TaskErrorClass, &str)>,
+    ) -> TaskResult2<()> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let finished_at = Utc::now();
+        let (error_class, error) = match failure {
+            Some((class, error)) => (Some(class.as_str()), Some(error)),
+            None => (None, None),
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE task_attempts
+            SET finished_at = $1,
+                duration_ms = (EXTRACT(EPOCH FROM ($1 - started_at)) * 1000)::BIGINT,
+                error = $2,
+                error_class = $3
+            WHERE id = $4
+            "#,
+            finished_at,
+            error,
+            error_class,
+            attempt_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// List all recorded attempts for a task, oldest first
+    pub async fn get_task_attempts(&self, task_id: Uuid) -> TaskResult2<Vec<TaskAttempt>> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let attempts = sqlx::query_as!(
+            TaskAttempt,
+            r#"
+            SELECT id, task_id, attempt_number, worker_id, started_at, finished_at,
+                   duration_ms, error, error_class
+            FROM task_attempts
+            WHERE task_id = $1
+            ORDER BY started_at ASC, attempt_number ASC
+            "#,
+            task_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(attempts)
+    }
+
+    /// Get tasks enqueued by a task's handler, oldest first
+    pub async fn get_child_tasks(&self, parent_task_id: Uuid) -> TaskResult2<Vec<Task>> {
+        let mut conn = self.database.pool.acquire().await?;
+
+        let tasks = sqlx::query_as!(
+            Task,
+            r#"
+            SELECT 
+                id, task_type, payload, 
+                status as "status: TaskStatus", 
+                priority as "priority: TaskPriority",
+                retry_strategy, max_attempts, current_attempt, last_error,
+                created_at, updated_at, scheduled_at, started_at, completed_at,
+                created_by, metadata, tags, timeout_seconds, dedupe_key, parent_task_id, org_id, tenant_id
+            FROM tasks
+            WHERE parent_task_id = $1
+            ORDER BY created_at ASC, id ASC
+            "#,
+            parent_task_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(tasks)
+    }
+
     /// Update task status with optimistic concurrency control to prevent race conditions
     async fn update_task_status(&self, task_id: Uuid, status: TaskStatus) -> TaskResult2<()> {
         let mut conn = self.database.pool.acquire().await?;
@@ -428,6 +1081,16 @@ impl TaskProcessor {
             None
         };
 
+        // Claiming a task takes a lease on it for this worker
+        let (claimed_by, lease_secs) = if matches!(status, TaskStatus::Running) {
+            (
+                Some(self.worker_id.as_str()),
+                Some(self.config.lease_duration.as_secs_f64()),
+            )
+        } else {
+            (None, None)
+        };
+
         let completed_at = if matches!(
             status,
             TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
@@ -454,7 +1117,9 @@ impl TaskProcessor {
         let result = sqlx::query!(
             r#"
             UPDATE tasks 
-            SET status = $1, updated_at = $2, started_at = COALESCE($3, started_at), completed_at = COALESCE($4, completed_at)
+            SET status = $1, updated_at = $2, started_at = COALESCE($3, started_at), completed_at = COALESCE($4, completed_at),
+                claimed_by = COALESCE($7, claimed_by),
+                lease_expires_at = COALESCE(NOW() + $8 * INTERVAL '1 second', lease_expires_at)
             WHERE id = $5 AND status = ANY($6)
             "#,
             status as TaskStatus,
@@ -462,12 +1127,18 @@ impl TaskProcessor {
             started_at,
             completed_at,
             task_id,
-            &valid_previous_states
+            &valid_previous_states,
+            claimed_by,
+            lease_secs
         )
         .execute(&mut *conn)
         .await?;
 
         if result.rows_affected() == 0 {
+            // Release the connection before looking the task up again so that
+            // contended claims cannot exhaust the pool
+            drop(conn);
+
             // For performance in high-concurrency scenarios, only fetch actual status in non-production
             // environments or when debugging is enabled. In production, the generic error is sufficient.
             if cfg!(debug_assertions) {
@@ -499,44 +1170,46 @@ impl TaskProcessor {
 
         let metadata_json = serde_json::to_value(&result.metadata)?;
 
-        sqlx::query!(
+        let result = sqlx::query!(
             r#"
             UPDATE tasks 
             SET status = $1, updated_at = $2, completed_at = $3, metadata = metadata || $4
-            WHERE id = $5
+            WHERE id = $5 AND status = 'running' AND claimed_by = $6
             "#,
             TaskStatus::Completed as TaskStatus,
             Utc::now(),
             Utc::now(),
             metadata_json,
-            task_id
+            task_id,
+            self.worker_id
         )
         .execute(&mut *conn)
         .await?;
 
-        Ok(())
+        Self::require_lease(task_id, result.rows_affected())
     }
 
     /// Mark task as failed
     async fn mark_task_failed(&self, task_id: Uuid, error: &str) -> TaskResult2<()> {
         let mut conn = self.database.pool.acquire().await?;
 
-        sqlx::query!(
+        let result = sqlx::query!(
             r#"
             UPDATE tasks 
             SET status = $1, updated_at = $2, completed_at = $3, last_error = $4
-            WHERE id = $5
+            WHERE id = $5 AND status = 'running' AND claimed_by = $6
             "#,
             TaskStatus::Failed as TaskStatus,
             Utc::now(),
             Utc::now(),
             error,
-            task_id
+            task_id,
+            self.worker_id
         )
         .execute(&mut *conn)
         .await?;
 
-        Ok(())
+        Self::require_lease(task_id, result.rows_affected())
     }
 
     /// Schedule task for retry
@@ -549,22 +1222,33 @@ impl TaskProcessor {
         let scheduled_at =
             delay.map(|delay| Utc::now() + chrono::Duration::from_std(delay).unwrap());
 
-        sqlx::query!(
+        let result = sqlx::query!(
             r#"
             UPDATE tasks 
             SET status = $1, updated_at = $2, current_attempt = $3, last_error = $4, scheduled_at = $5
-            WHERE id = $6
+            WHERE id = $6 AND status = 'running' AND claimed_by = $7
             "#,
             TaskStatus::Retrying as TaskStatus,
             Utc::now(),
             task.current_attempt,
             error,
             scheduled_at,
-            task.id
+            task.id,
+            self.worker_id
         )
         .execute(&mut *conn)
         .await?;
 
+        Self::require_lease(task.id, result.rows_affected())
+    }
+
+    /// Fail when a task's outcome wasn't recorded because the task is no longer
+    /// running under this worker, e.g. after its lease expired and it was reclaimed
+    fn require_lease(task_id: Uuid, rows_affected: u64) -> TaskResult2<()> {
+        if rows_affected == 0 {
+            warn!("Lost the lease on task {}, discarding its outcome", task_id);
+            return Err(TaskError::LeaseLost(task_id));
+        }
         Ok(())
     }
 
@@ -651,3 +1335,92 @@ impl TaskProcessor {
         self.list_tasks(filter).await
     }
 }
+
+/// Build a worker identifier from the host name and process id
+/// Aborts a spawned background task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn default_worker_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+/// Sleep until the next task is due, but never longer than the poll interval
+fn sleep_duration(poll_interval: Duration, next_due_in_ms: Option<f64>) -> Duration {
+    match next_due_in_ms {
+        Some(ms) if ms <= 0.0 => Duration::ZERO,
+        Some(ms) => poll_interval.min(Duration::from_secs_f64(ms / 1000.0)),
+        None => poll_interval,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sleep_duration_without_scheduled_tasks() {
+        assert_eq!(
+            sleep_duration(Duration::from_secs(5), None),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_until_next_due_task() {
+        assert_eq!(
+            sleep_duration(Duration::from_secs(5), Some(1500.0)),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_capped_at_poll_interval() {
+        assert_eq!(
+            sleep_duration(Duration::from_secs(5), Some(60_000.0)),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_sleep_duration_overdue_task() {
+        assert_eq!(
+            sleep_duration(Duration::from_secs(5), Some(-250.0)),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_max_concurrent_tasks() {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let processor = TaskProcessor::new(
+            Database { pool },
+            ProcessorConfig {
+                max_concurrent_tasks: 2,
+                ..Default::default()
+            },
+        );
+
+        processor.set_max_concurrent_tasks(4);
+        assert_eq!(processor.semaphore.available_permits(), 4);
+
+        // A running task keeps its slot until it finishes
+        let running = processor
+            .semaphore
+            .clone()
+            .acquire_many_owned(3)
+            .await
+            .unwrap();
+        processor.set_max_concurrent_tasks(1);
+        assert_eq!(processor.semaphore.available_permits(), 0);
+        drop(running);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(processor.semaphore.available_permits(), 1);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::core::cache::AppCache;
+use crate::monitoring::{models::CreateEventRequest, services as monitoring_services};
+use crate::tasks::types::{TaskQueueState, TaskQueueStats};
+use crate::tenants::DEFAULT_TENANT_ID;
+use crate::{DbConn, Error, Result};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Cache namespace of task types known to be registered, keyed by name
+pub const TASK_TYPE_CACHE_NAMESPACE: &str = "task_types";
+
+/// Whether tasks of this type can be created, i.e. a worker registered it
+///
+/// Only registered types are cached, for `ttl` (a zero TTL disables caching),
+/// so a type registered a moment ago is accepted right away.
+pub async fn is_registered(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    ttl: Duration,
+    task_type: &str,
+) -> Result<bool> {
+    if !ttl.is_zero()
+        && cache
+            .get::<bool>(TASK_TYPE_CACHE_NAMESPACE, task_type)
+            .await
+            .is_some()
+    {
+        return Ok(true);
+    }
+
+    let registered = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM task_types WHERE task_type = $1 AND is_active = true)",
+        task_type
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| Error::Internal(format!("Failed to validate task type: {e}")))?
+    .ok_or_else(|| {
+        Error::Internal("Database query returned null for task type validation".to_string())
+    })?;
+
+    if registered && !ttl.is_zero() {
+        cache
+            .set(TASK_TYPE_CACHE_NAMESPACE, task_type, &true, ttl)
+            .await;
+    }
+    Ok(registered)
+}
+
+/// Per task type queue depth, running count and oldest due task age
+///
+/// Every registered task type is reported, including idle ones, so that
+/// autoscalers see an explicit zero instead of a missing series. Only the
+/// tenant's tasks are counted when one is given.
+pub async fn get_queue_stats(
+    conn: &mut DbConn,
+    tenant_id: Option<Uuid>,
+) -> Result<Vec<TaskQueueStats>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            tt.task_type,
+            COUNT(t.id) FILTER (WHERE t.status IN ('pending', 'retrying')) as "pending!",
+            COUNT(t.id) FILTER (WHERE t.status = 'running') as "running!",
+            COALESCE(EXTRACT(EPOCH FROM (NOW() - MIN(COALESCE(t.scheduled_at, t.created_at))
+                FILTER (WHERE t.status IN ('pending', 'retrying')
+                    AND COALESCE(t.scheduled_at, t.created_at) <= NOW())))::FLOAT8, 0) as "oldest_pending_age_seconds!"
+        FROM task_types tt
+        LEFT JOIN tasks t ON t.task_type = tt.task_type
+            AND t.status IN ('pending', 'retrying', 'running')
+            AND ($1::UUID IS NULL OR t.tenant_id = $1)
+        GROUP BY tt.task_type
+        ORDER BY tt.task_type
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| TaskQueueStats {
+            task_type: row.task_type,
+            pending: row.pending,
+            running: row.running,
+            oldest_pending_age_seconds: row.oldest_pending_age_seconds.max(0.0),
+        })
+        .collect())
+}
+
+/// Pause state and drain progress for every task type's queue
+pub async fn list_queue_states(conn: &mut DbConn) -> Result<Vec<TaskQueueState>> {
+    sqlx::query_as!(
+        TaskQueueState,
+        r#"
+        SELECT
+            tt.task_type as queue,
+            tt.paused,
+            tt.paused_at,
+            tt.paused_by,
+            COUNT(t.id) FILTER (WHERE t.status IN ('pending', 'retrying')) as "pending!",
+            COUNT(t.id) FILTER (WHERE t.status = 'running') as "running!"
+        FROM task_types tt
+        LEFT JOIN tasks t ON t.task_type = tt.task_type
+            AND t.status IN ('pending', 'retrying', 'running')
+        GROUP BY tt.task_type
+        ORDER BY tt.task_type
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Pause or resume claiming for a task type's queue
+///
+/// Pausing does not touch running tasks, so the queue drains while new
+/// tasks accumulate. Workers see the flag on their next poll.
+pub async fn set_queue_paused(
+    conn: &mut DbConn,
+    queue: &str,
+    paused: bool,
+    user_id: Uuid,
+) -> Result<TaskQueueState> {
+    let updated = sqlx::query!(
+        r#"
+        UPDATE task_types
+        SET paused = $2,
+            paused_at = CASE WHEN $2 THEN COALESCE(paused_at, NOW()) END,
+            paused_by = CASE WHEN $2 THEN COALESCE(paused_by, $3) END
+        WHERE task_type = $1
+        "#,
+        queue,
+        paused,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if updated.rows_affected() == 0 {
+        return Err(Error::NotFound(format!(
+            "Task queue '{queue}' is not registered"
+        )));
+    }
+
+    let state = list_queue_states(conn)
+        .await?
+        .into_iter()
+        .find(|state| state.queue == queue)
+        .ok_or_else(|| Error::NotFound(format!("Task queue '{queue}' is not registered")))?;
+
+    let action = if paused { "paused" } else { "resumed" };
+    let event = CreateEventRequest {
+        event_type: "log".to_string(),
+        source: "task-queue-control".to_string(),
+        message: Some(format!("Task queue {queue} {action}")),
+        level: Some(if paused { "warn" } else { "info" }.to_string()),
+        tags: HashMap::from([
+            ("task_type".to_string(), serde_json::json!(queue)),
+            ("user_id".to_string(), serde_json::json!(user_id)),
+        ]),
+        payload: HashMap::from([
+            ("paused".to_string(), serde_json::json!(paused)),
+            ("pending".to_string(), serde_json::json!(state.pending)),
+            ("running".to_string(), serde_json::json!(state.running)),
+        ]),
+        recorded_at: None,
+        org_id: None,
+        // Queues are shared by every tenant and controlled from the default one
+        tenant_id: Some(DEFAULT_TENANT_ID),
+    };
+    if let Err(e) = monitoring_services::create_event(conn, event).await {
+        warn!("Failed to record queue control event for {}: {}", queue, e);
+    }
+
+    Ok(state)
+}
+
+/// Render queue stats as Prometheus gauges labelled by task type
+pub fn render_prometheus(stats: &[TaskQueueStats]) -> String {
+    let mut output = String::new();
+
+    write_gauge(
+        &mut output,
+        "task_queue_pending",
+        "Tasks waiting to run",
+        stats,
+        |s| s.pending.to_string(),
+    );
+    write_gauge(
+        &mut output,
+        "task_queue_running",
+        "Tasks currently running",
+        stats,
+        |s| s.running.to_string(),
+    );
+    write_gauge(
+        &mut output,
+        "task_queue_oldest_pending_age_seconds",
+        "Age of the oldest due task that has not started",
+        stats,
+        |s| format!("{:.3}", s.oldest_pending_age_seconds),
+    );
+
+    output
+}
+
+fn write_gauge(
+    output: &mut String,
+    name: &str,
+    help: &str,
+    stats: &[TaskQueueStats],
+    value: impl Fn(&TaskQueueStats) -> String,
+) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} gauge");
+    for s in stats {
+        let _ = writeln!(
+            output,
+            "{name}{{task_type=\"{}\"}} {}",
+            escape_label(&s.task_type),
+            value(s)
+        );
+    }
+    output.push('\n');
+}
+
+pub(crate) fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_gauges() {
+        let output = render_prometheus(&[TaskQueueStats {
+            task_type: "email".to_string(),
+            pending: 3,
+            running: 1,
+            oldest_pending_age_seconds: 12.5,
+        }]);
+
+        assert!(output.contains("# TYPE task_queue_pending gauge"));
+        assert!(output.contains("task_queue_pending{task_type=\"email\"} 3"));
+        assert!(output.contains("task_queue_running{task_type=\"email\"} 1"));
+        assert!(
+            output.contains("task_queue_oldest_pending_age_seconds{task_type=\"email\"} 12.500")
+        );
+    }
+}
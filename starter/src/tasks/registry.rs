@@ -0,0 +1,122 @@
+//! Task handler auto-registration
+//!
+//! Handlers announce themselves with [`register_task_handler!`](crate::register_task_handler)
+//! anywhere in the final binary, including downstream crates, and the worker
+//! picks them up at startup without a central list to edit.
+
+use async_trait::async_trait;
+
+use crate::tasks::handlers::TaskHandler;
+use crate::tasks::processor::{TaskHandlerFn, TaskProcessor};
+
+#[doc(hidden)]
+pub use inventory;
+
+/// A task handler submitted for auto-registration
+pub struct HandlerRegistration {
+    pub task_type: &'static str,
+    pub description: &'static str,
+    pub factory: fn() -> TaskHandlerFn,
+}
+
+impl HandlerRegistration {
+    pub const fn new(
+        task_type: &'static str,
+        description: &'static str,
+        factory: fn() -> TaskHandlerFn,
+    ) -> Self {
+        Self {
+            task_type,
+            description,
+            factory,
+        }
+    }
+}
+
+inventory::collect!(HandlerRegistration);
+
+/// All handler registrations linked into the binary, sorted by task type
+pub fn registrations() -> Vec<&'static HandlerRegistration> {
+    let mut registrations: Vec<_> = inventory::iter::<HandlerRegistration>().collect();
+    registrations.sort_by_key(|r| r.task_type);
+    registrations
+}
+
+/// Something task handlers can be registered into
+#[async_trait]
+pub trait HandlerRegistry: Sync {
+    async fn register(&self, task_type: &str, handler: Box<dyn TaskHandler + Send + Sync>);
+
+    /// Register every handler submitted with `register_task_handler!`
+    async fn register_all(&self) -> usize {
+        let registrations = registrations();
+        for registration in &registrations {
+            self.register(registration.task_type, (registration.factory)())
+                .await;
+        }
+        registrations.len()
+    }
+}
+
+#[async_trait]
+impl HandlerRegistry for TaskProcessor {
+    async fn register(&self, task_type: &str, handler: Box<dyn TaskHandler + Send + Sync>) {
+        self.register_boxed_handler(task_type.to_string(), handler)
+            .await;
+    }
+}
+
+/// Submit a task handler for auto-registration
+///
+/// ```ignore
+/// starter::register_task_handler!("email", "Email notification tasks", EmailTaskHandler);
+/// ```
+#[macro_export]
+macro_rules! register_task_handler {
+    ($task_type:literal, $description:literal, $handler:expr) => {
+        $crate::tasks::registry::inventory::submit! {
+            $crate::tasks::registry::HandlerRegistration::new(
+                $task_type,
+                $description,
+                || ::std::boxed::Box::new($handler),
+            )
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingRegistry {
+        task_types: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HandlerRegistry for RecordingRegistry {
+        async fn register(&self, task_type: &str, _handler: Box<dyn TaskHandler + Send + Sync>) {
+            self.task_types.lock().unwrap().push(task_type.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_all_includes_builtin_handlers() {
+        let registry = RecordingRegistry::default();
+        let count = registry.register_all().await;
+
+        let task_types = registry.task_types.lock().unwrap();
+        assert_eq!(count, task_types.len());
+        for expected in [
+            "data_processing",
+            "delay_task",
+            "email",
+            "file_cleanup",
+            "report_generation",
+            "webhook",
+        ] {
+            assert!(task_types.iter().any(|t| t == expected), "{expected}");
+        }
+    }
+}
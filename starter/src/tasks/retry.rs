@@ -129,7 +129,7 @@ impl RetryStrategy {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum CircuitState {
     Closed,   // Normal operation
@@ -137,12 +137,37 @@ pub enum CircuitState {
     HalfOpen, // Testing if service recovered
 }
 
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "halfopen",
+        }
+    }
+}
+
+impl std::str::FromStr for CircuitState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "closed" => Ok(CircuitState::Closed),
+            "open" => Ok(CircuitState::Open),
+            "halfopen" => Ok(CircuitState::HalfOpen),
+            _ => Err(format!("Invalid circuit state: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CircuitBreaker {
     state: CircuitState,
     failure_count: u32,
     success_count: u32,
     last_failure: Option<Instant>,
+    /// Manually tripped: stays open until reset, never probes half-open
+    forced_open: bool,
     failure_threshold: u32,
     success_threshold: u32,
     timeout: Duration,
@@ -155,6 +180,7 @@ impl CircuitBreaker {
             failure_count: 0,
             success_count: 0,
             last_failure: None,
+            forced_open: false,
             failure_threshold,
             success_threshold,
             timeout,
@@ -165,10 +191,36 @@ impl CircuitBreaker {
         &self.state
     }
 
+    pub fn failure_count(&self) -> u32 {
+        self.failure_count
+    }
+
+    pub fn is_forced_open(&self) -> bool {
+        self.forced_open
+    }
+
+    /// Open the circuit until `reset` is called
+    pub fn trip(&mut self) {
+        self.state = CircuitState::Open;
+        self.forced_open = true;
+        self.success_count = 0;
+        self.last_failure = Some(Instant::now());
+    }
+
+    /// Close the circuit and clear all counters
+    pub fn reset(&mut self) {
+        self.state = CircuitState::Closed;
+        self.forced_open = false;
+        self.failure_count = 0;
+        self.success_count = 0;
+        self.last_failure = None;
+    }
+
     /// Check if we should allow the operation to proceed
     pub fn should_allow_operation(&mut self) -> bool {
         match self.state {
             CircuitState::Closed => true,
+            CircuitState::Open if self.forced_open => false,
             CircuitState::Open => {
                 // Check if timeout has passed
                 if let Some(last_failure) = self.last_failure {
@@ -324,6 +376,23 @@ mod tests {
         assert!(!cb.should_allow_operation());
     }
 
+    #[test]
+    fn test_circuit_breaker_trip_and_reset() {
+        let mut cb = CircuitBreaker::new(2, 1, Duration::ZERO);
+
+        cb.trip();
+        assert_eq!(cb.state(), &CircuitState::Open);
+        assert!(cb.is_forced_open());
+        // A manual trip ignores the recovery timeout
+        assert!(!cb.should_allow_operation());
+
+        cb.record_failure();
+        cb.reset();
+        assert_eq!(cb.state(), &CircuitState::Closed);
+        assert_eq!(cb.failure_count(), 0);
+        assert!(cb.should_allow_operation());
+    }
+
     #[tokio::test]
     async fn test_retry_strategy_execution() {
         let strategy = RetryStrategy::Fixed {
@@ -0,0 +1,241 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use crate::tasks::types::{SchedulePreview, SchedulePreviewRequest};
+use crate::{Error, Result};
+
+pub const DEFAULT_PREVIEW_COUNT: u32 = 5;
+pub const MAX_PREVIEW_COUNT: u32 = 50;
+
+/// Parse a cron expression
+///
+/// Accepts the standard 5-field form (`min hour dom month dow`), where days
+/// of the week run from 0 (Sunday) to 6, with 7 also meaning Sunday. The
+/// 6/7-field form with leading seconds and trailing year is passed to the
+/// `cron` crate as is, so its days of the week run from 1 (Sunday) to 7
+/// (Saturday). 5-field expressions run at second zero.
+pub fn parse_cron(expression: &str) -> Result<Schedule> {
+    let expression = expression.trim();
+    if expression.is_empty() {
+        return Err(Error::validation("cron", "Cron expression is required"));
+    }
+
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    let normalized = match fields.len() {
+        5 => format!(
+            "0 {} {}",
+            fields[..4].join(" "),
+            translate_day_of_week(fields[4])?
+        ),
+        6 | 7 => expression.to_string(),
+        n => {
+            return Err(Error::validation(
+                "cron",
+                &format!("Cron expression must have 5, 6 or 7 fields, got {n}"),
+            ));
+        }
+    };
+
+    Schedule::from_str(&normalized)
+        .map_err(|e| Error::validation("cron", &format!("Invalid cron expression: {e}")))
+}
+
+/// Translate a standard day-of-week field to the `cron` crate's numbering
+///
+/// Numeric days, ranges and steps are expanded to a list of days shifted by
+/// one (0 and 7 become 1, n becomes n + 1). Day names mean the same in both
+/// and are kept as they are.
+fn translate_day_of_week(field: &str) -> Result<String> {
+    let mut days = BTreeSet::new();
+    let mut kept = Vec::new();
+    for element in field.split(',') {
+        if element == "*" || element == "?" {
+            return Ok(field.to_string());
+        }
+        if element.chars().any(|c| c.is_ascii_alphabetic()) {
+            kept.push(element.to_string());
+            continue;
+        }
+
+        let invalid = || Error::validation("cron", &format!("Invalid day of week: {element}"));
+        let parse_day = |day: &str| day.parse::<u32>().ok().filter(|day| *day <= 7);
+        let (range, step) = match element.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<usize>().ok().filter(|s| *s > 0))),
+            None => (element, None),
+        };
+        let (start, end) = match (range, range.split_once('-')) {
+            ("*", _) => (Some(0), Some(7)),
+            (_, Some((start, end))) => (parse_day(start), parse_day(end)),
+            // `n/step` runs from n to the end of the week
+            (day, None) if step.is_some() => (parse_day(day), Some(7)),
+            (day, None) => (parse_day(day), parse_day(day)),
+        };
+        let (Some(start), Some(end), Some(step)) = (start, end, step.unwrap_or(Some(1))) else {
+            return Err(invalid());
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        days.extend((start..=end).step_by(step).map(|day| day % 7 + 1));
+    }
+
+    kept.extend(days.iter().map(u32::to_string));
+    Ok(kept.join(","))
+}
+
+/// Parse an IANA timezone name such as `Europe/Berlin`
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    Tz::from_str(name.trim())
+        .map_err(|_| Error::validation("timezone", &format!("Unknown timezone: {name}")))
+}
+
+/// Validate a cron expression and list its next runs after `now`
+pub fn preview_schedule(
+    request: &SchedulePreviewRequest,
+    now: DateTime<Utc>,
+) -> Result<SchedulePreview> {
+    let count = request.count.unwrap_or(DEFAULT_PREVIEW_COUNT);
+    if count == 0 || count > MAX_PREVIEW_COUNT {
+        return Err(Error::validation(
+            "count",
+            &format!("Count must be between 1 and {MAX_PREVIEW_COUNT}"),
+        ));
+    }
+
+    let schedule = parse_cron(&request.cron)?;
+    let timezone = parse_timezone(request.timezone.as_deref().unwrap_or("UTC"))?;
+
+    let next_runs: Vec<_> = schedule
+        .after(&now.with_timezone(&timezone))
+        .take(count as usize)
+        .map(|run| run.fixed_offset())
+        .collect();
+
+    if next_runs.is_empty() {
+        return Err(Error::validation(
+            "cron",
+            "Cron expression has no upcoming runs",
+        ));
+    }
+
+    Ok(SchedulePreview {
+        cron: request.cron.trim().to_string(),
+        timezone: timezone.name().to_string(),
+        next_runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn request(cron: &str, timezone: Option<&str>, count: Option<u32>) -> SchedulePreviewRequest {
+        SchedulePreviewRequest {
+            cron: cron.to_string(),
+            timezone: timezone.map(str::to_string),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_preview_five_field_expression_in_timezone() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 9, 12, 0, 0).unwrap();
+        let preview = preview_schedule(
+            &request("30 9 * * *", Some("America/New_York"), Some(3)),
+            now,
+        )
+        .unwrap();
+
+        let runs: Vec<String> = preview.next_runs.iter().map(|r| r.to_rfc3339()).collect();
+        // New York moves to daylight saving time on 2024-03-10
+        assert_eq!(
+            runs,
+            vec![
+                "2024-03-09T09:30:00-05:00",
+                "2024-03-10T09:30:00-04:00",
+                "2024-03-11T09:30:00-04:00",
+            ]
+        );
+        assert_eq!(preview.timezone, "America/New_York");
+    }
+
+    #[test]
+    fn test_preview_defaults_to_utc() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 30).unwrap();
+        let preview = preview_schedule(&request("0 */15 * * * *", None, None), now).unwrap();
+
+        assert_eq!(preview.timezone, "UTC");
+        assert_eq!(preview.next_runs.len(), DEFAULT_PREVIEW_COUNT as usize);
+        assert_eq!(
+            preview.next_runs[0].to_rfc3339(),
+            "2024-01-01T00:15:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_preview_rejects_invalid_input() {
+        let now = Utc::now();
+        assert!(preview_schedule(&request("61 * * * *", None, None), now).is_err());
+        assert!(preview_schedule(&request("* * *", None, None), now).is_err());
+        assert!(preview_schedule(&request("", None, None), now).is_err());
+        assert!(preview_schedule(&request("* * * * *", Some("Mars/Olympus"), None), now).is_err());
+        assert!(preview_schedule(&request("* * * * *", None, Some(0)), now).is_err());
+        assert!(preview_schedule(&request("* * * * *", None, Some(51)), now).is_err());
+        // A schedule whose only year is in the past never fires again
+        assert!(preview_schedule(&request("0 0 0 1 1 * 2000", None, None), now).is_err());
+    }
+
+    #[test]
+    fn test_five_field_day_of_week_counts_from_sunday_zero() {
+        // 2024-01-01 is a Monday
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let days = |cron: &str, count: u32| -> Vec<String> {
+            preview_schedule(&request(cron, None, Some(count)), now)
+                .unwrap()
+                .next_runs
+                .iter()
+                .map(|run| run.format("%a %H:%M").to_string())
+                .collect()
+        };
+
+        assert_eq!(days("* * * * 0", 1), vec!["Sun 00:00"]);
+        assert_eq!(days("0 0 * * 7", 1), vec!["Sun 00:00"]);
+        assert_eq!(
+            days("0 9 * * 1-5", 6),
+            vec![
+                "Mon 09:00",
+                "Tue 09:00",
+                "Wed 09:00",
+                "Thu 09:00",
+                "Fri 09:00",
+                "Mon 09:00"
+            ]
+        );
+        assert_eq!(
+            days("0 9 * * 5-7", 3),
+            vec!["Fri 09:00", "Sat 09:00", "Sun 09:00"]
+        );
+        assert_eq!(
+            days("0 9 * * */3", 3),
+            vec!["Wed 09:00", "Sat 09:00", "Sun 09:00"]
+        );
+        assert_eq!(days("0 9 * * Sat,Sun", 2), vec!["Sat 09:00", "Sun 09:00"]);
+        // The 6-field form keeps the cron crate's numbering, 1 being Sunday
+        assert_eq!(days("0 0 9 * * 1", 1), vec!["Sun 09:00"]);
+    }
+
+    #[test]
+    fn test_five_field_day_of_week_rejects_invalid_days() {
+        let now = Utc::now();
+        for cron in ["0 9 * * 8", "0 9 * * 5-2", "0 9 * * */0", "0 9 * * 1#2"] {
+            assert!(
+                preview_schedule(&request(cron, None, None), now).is_err(),
+                "{cron}"
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::AppConfig;
+use crate::core::telemetry;
+use crate::storage::{FileStorage, LocalFileStorage};
+use crate::tasks::types::TaskError;
+
+/// Services shared by every handler a processor runs
+///
+/// The processor builds one container and hands a cheap clone to each
+/// `TaskContext`, so handlers reuse connection pools instead of constructing
+/// their own clients. Replace individual services with the `with_*` builders,
+/// e.g. to plug in a real email provider or a mock in tests.
+#[derive(Clone)]
+pub struct TaskServices {
+    http: HttpClient,
+    email: Arc<dyn EmailSender>,
+    config: Option<Arc<AppConfig>>,
+    storage: Option<Arc<dyn FileStorage>>,
+}
+
+static DEFAULT_SERVICES: Lazy<TaskServices> = Lazy::new(|| TaskServices {
+    http: HttpClient::new(RetryPolicy::default(), Duration::from_secs(30)),
+    email: Arc::new(LogEmailSender),
+    config: None,
+    storage: None,
+});
+
+impl Default for TaskServices {
+    /// Shared defaults: a retrying HTTP client, a log-only email sender, no config and no storage
+    fn default() -> Self {
+        DEFAULT_SERVICES.clone()
+    }
+}
+
+impl fmt::Debug for TaskServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskServices")
+            .field("http", &self.http)
+            .field("has_config", &self.config.is_some())
+            .field("has_storage", &self.storage.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl TaskServices {
+    /// Build services from the worker configuration
+    pub fn from_config(config: &AppConfig) -> Self {
+        let retry = RetryPolicy {
+            max_retries: config.worker.http_max_retries,
+            ..RetryPolicy::default()
+        };
+        Self {
+            http: HttpClient::new(retry, config.worker_http_timeout()),
+            email: Arc::new(LogEmailSender),
+            config: Some(Arc::new(config.clone())),
+            storage: Some(Arc::new(LocalFileStorage::new(
+                &config.storage.path,
+                "/api/v1/files",
+            ))),
+        }
+    }
+
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn with_email_sender(mut self, sender: impl EmailSender + 'static) -> Self {
+        self.email = Arc::new(sender);
+        self
+    }
+
+    pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.config = Some(Arc::new(config));
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn FileStorage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    pub fn email(&self) -> &dyn EmailSender {
+        self.email.as_ref()
+    }
+
+    /// Application configuration, when the processor was built with one
+    pub fn config(&self) -> Option<&AppConfig> {
+        self.config.as_deref()
+    }
+
+    /// File storage, when the processor was built with one
+    pub fn storage(&self) -> Option<&dyn FileStorage> {
+        self.storage.as_deref()
+    }
+}
+
+/// When and how often `HttpClient` retries a request
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+/// `reqwest::Client` that retries transient failures
+///
+/// Connection errors, timeouts, `429 Too Many Requests` and `5xx` responses
+/// are retried with exponential backoff. Other responses, including `4xx`,
+/// are returned as-is for the handler to inspect. Requests built with
+/// [`get`](Self::get) and [`post`](Self::post) carry the current trace context.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    retry: RetryPolicy,
+}
+
+impl HttpClient {
+    pub fn new(retry: RetryPolicy, timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to build HTTP client, using defaults: {}", e);
+                reqwest::Client::new()
+            });
+        Self { client, retry }
+    }
+
+    /// The underlying client, for building requests
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url).headers(telemetry::trace_headers())
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url).headers(telemetry::trace_headers())
+    }
+
+    /// Send a request, retrying transient failures
+    ///
+    /// Requests whose body cannot be cloned (streams) are sent once.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let Some(retry) = (attempt < self.retry.max_retries)
+                .then(|| request.try_clone())
+                .flatten()
+            else {
+                return request.send().await;
+            };
+
+            match retry.send().await {
+                Ok(response) if !is_retryable_status(response.status()) => return Ok(response),
+                Err(e) if !(e.is_connect() || e.is_timeout()) => return Err(e),
+                Ok(response) => {
+                    tracing::debug!(
+                        "HTTP {} from {}, retrying",
+                        response.status(),
+                        response.url()
+                    );
+                }
+                Err(e) => tracing::debug!("HTTP request failed, retrying: {}", e),
+            }
+
+            tokio::time::sleep(self.retry.base_delay * 2u32.saturating_pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// An email to deliver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers email on behalf of task handlers
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), TaskError>;
+}
+
+/// Email sender that only logs; replace it with a real provider
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogEmailSender;
+
+#[async_trait]
+impl EmailSender for LogEmailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), TaskError> {
+        tracing::info!(
+            "Email to {} with subject {:?} ({} bytes)",
+            message.to,
+            message.subject,
+            message.body.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable_status(StatusCode::OK));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::tasks::types::{
+    CreateTaskRequest, CreateTaskTemplateRequest, TaskPriority, TaskTemplate,
+    UpdateTaskTemplateRequest,
+};
+use crate::{DbConn, Error, Result};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 100;
+const MAX_DESCRIPTION_LEN: usize = 500;
+
+/// List the tenant's templates ordered by name
+pub async fn list_templates(conn: &mut DbConn, tenant_id: Uuid) -> Result<Vec<TaskTemplate>> {
+    sqlx::query_as!(
+        TaskTemplate,
+        r#"
+        SELECT id, name, description, task_type, payload,
+               priority as "priority: TaskPriority",
+               metadata, created_by, created_at, updated_at
+        FROM task_templates
+        WHERE tenant_id = $1
+        ORDER BY name
+        "#,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Get a template of the tenant by its name
+pub async fn get_template(conn: &mut DbConn, name: &str, tenant_id: Uuid) -> Result<TaskTemplate> {
+    sqlx::query_as!(
+        TaskTemplate,
+        r#"
+        SELECT id, name, description, task_type, payload,
+               priority as "priority: TaskPriority",
+               metadata, created_by, created_at, updated_at
+        FROM task_templates
+        WHERE name = $1 AND tenant_id = $2
+        "#,
+        name,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound(format!("Task template '{name}' not found")))
+}
+
+/// Create a template in the tenant after checking its defaults would produce a valid task
+pub async fn create_template(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    request: CreateTaskTemplateRequest,
+    created_by: Uuid,
+) -> Result<TaskTemplate> {
+    validate_name(&request.name)?;
+    validate_description(request.description.as_deref())?;
+    validate_defaults(
+        &request.task_type,
+        &request.payload,
+        &request.priority,
+        &request.metadata,
+    )?;
+
+    let metadata = serde_json::to_value(&request.metadata)
+        .map_err(|e| Error::Internal(format!("Failed to serialize metadata: {e}")))?;
+
+    sqlx::query_as!(
+        TaskTemplate,
+        r#"
+        INSERT INTO task_templates
+            (name, description, task_type, payload, priority, metadata, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, name, description, task_type, payload,
+                  priority as "priority: TaskPriority",
+                  metadata, created_by, created_at, updated_at
+        "#,
+        request.name,
+        request.description,
+        request.task_type,
+        request.payload,
+        request.priority as TaskPriority,
+        metadata,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| map_write_error(e, &request.name, &request.task_type))
+}
+
+/// Update the given fields of a template of the tenant
+pub async fn update_template(
+    conn: &mut DbConn,
+    name: &str,
+    tenant_id: Uuid,
+    request: UpdateTaskTemplateRequest,
+) -> Result<TaskTemplate> {
+    let current = get_template(conn, name, tenant_id).await?;
+
+    validate_description(request.description.as_deref())?;
+    let task_type = request.task_type.unwrap_or(current.task_type);
+    let payload = request.payload.unwrap_or(current.payload);
+    let priority = request.priority.unwrap_or(current.priority);
+    let metadata = match request.metadata {
+        Some(metadata) => metadata,
+        None => serde_json::from_value(current.metadata)
+            .map_err(|e| Error::Internal(format!("Invalid stored template metadata: {e}")))?,
+    };
+    validate_defaults(&task_type, &payload, &priority, &metadata)?;
+
+    let metadata = serde_json::to_value(&metadata)
+        .map_err(|e| Error::Internal(format!("Failed to serialize metadata: {e}")))?;
+
+    sqlx::query_as!(
+        TaskTemplate,
+        r#"
+        UPDATE task_templates
+        SET description = COALESCE($2, description),
+            task_type = $3,
+            payload = $4,
+            priority = $5,
+            metadata = $6
+        WHERE name = $1 AND tenant_id = $7
+        RETURNING id, name, description, task_type, payload,
+                  priority as "priority: TaskPriority",
+                  metadata, created_by, created_at, updated_at
+        "#,
+        name,
+        request.description,
+        task_type,
+        payload,
+        priority as TaskPriority,
+        metadata,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| map_write_error(e, name, &task_type))?
+    .ok_or_else(|| Error::NotFound(format!("Task template '{name}' not found")))
+}
+
+/// Delete a template of the tenant; tasks created from it are unaffected
+pub async fn delete_template(conn: &mut DbConn, name: &str, tenant_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "DELETE FROM task_templates WHERE name = $1 AND tenant_id = $2",
+        name,
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound(format!("Task template '{name}' not found")));
+    }
+    Ok(())
+}
+
+/// Apply a JSON merge patch (RFC 7396) to `target`
+///
+/// Objects are merged key by key, `null` removes a key, and any other value
+/// replaces the target outright.
+pub fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = serde_json::json!({});
+    }
+    let target = target.as_object_mut().expect("target is an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(
+                target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                value,
+            );
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(Error::validation(
+            "name",
+            &format!("Template name must be 1-{MAX_NAME_LEN} characters"),
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(Error::validation(
+            "name",
+            "Template name may only contain lowercase letters, digits, '-' and '_'",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_description(description: Option<&str>) -> Result<()> {
+    if description.is_some_and(|d| d.len() > MAX_DESCRIPTION_LEN) {
+        return Err(Error::validation(
+            "description",
+            &format!("Description cannot exceed {MAX_DESCRIPTION_LEN} characters"),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject defaults that task creation would reject
+fn validate_defaults(
+    task_type: &str,
+    payload: &serde_json::Value,
+    priority: &TaskPriority,
+    metadata: &HashMap<String, serde_json::Value>,
+) -> Result<()> {
+    if !payload.is_object() {
+        return Err(Error::validation(
+            "payload",
+            "Template payload must be a JSON object",
+        ));
+    }
+
+    let mut request =
+        CreateTaskRequest::new(task_type, payload.clone()).with_priority(priority.clone());
+    for (key, value) in metadata {
+        request = request.with_metadata(key.clone(), value.clone());
+    }
+    request
+        .validate()
+        .map_err(|e| Error::validation("template", &e))
+}
+
+fn map_write_error(err: sqlx::Error, name: &str, task_type: &str) -> Error {
+    match &err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Task template '{name}' already exists"))
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => Error::validation(
+            "task_type",
+            &format!("Task type '{task_type}' is not registered"),
+        ),
+        _ => Error::from_sqlx(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut payload = json!({
+            "to": "team@example.com",
+            "subject": "Weekly report",
+            "options": {"html": true, "track": true}
+        });
+        merge_patch(
+            &mut payload,
+            &json!({
+                "to": "ops@example.com",
+                "options": {"track": null, "priority": "high"},
+                "cc": ["lead@example.com"]
+            }),
+        );
+
+        assert_eq!(
+            payload,
+            json!({
+                "to": "ops@example.com",
+                "subject": "Weekly report",
+                "options": {"html": true, "priority": "high"},
+                "cc": ["lead@example.com"]
+            })
+        );
+
+        // Non-object patches replace the target
+        merge_patch(&mut payload, &json!([1, 2]));
+        assert_eq!(payload, json!([1, 2]));
+    }
+
+    #[test]
+    fn test_validate_template_name() {
+        assert!(validate_name("weekly-report_v2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("Weekly Report").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
@@ -1,6 +1,8 @@
-use crate::tasks::retry::RetryStrategy;
-use crate::{Error, Result};
-use chrono::{DateTime, Utc};
+use crate::monitoring::{models::CreateEventRequest, services as monitoring_services};
+use crate::tasks::retry::{CircuitState, RetryStrategy};
+use crate::tasks::services::TaskServices;
+use crate::{DbPool, Error, Result};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::collections::HashMap;
@@ -54,10 +56,13 @@ impl std::str::FromStr for TaskStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, utoipa::ToSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskPriority {
     Low,
+    #[default]
     Normal,
     High,
     Critical,
@@ -94,12 +99,6 @@ impl std::str::FromStr for TaskPriority {
     }
 }
 
-impl Default for TaskPriority {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Task {
     pub id: Uuid,
@@ -118,6 +117,12 @@ pub struct Task {
     pub completed_at: Option<DateTime<Utc>>,
     pub created_by: Option<Uuid>,
     pub metadata: serde_json::Value,
+    pub tags: Vec<String>,
+    pub timeout_seconds: Option<i32>,
+    pub dedupe_key: Option<String>,
+    pub parent_task_id: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    pub tenant_id: Uuid,
 }
 
 impl Task {
@@ -175,6 +180,26 @@ pub struct TaskResponse {
     pub created_by: Option<Uuid>,
     #[serde(default)]
     pub metadata: std::collections::HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub timeout_seconds: Option<i32>,
+    pub dedupe_key: Option<String>,
+    /// Task whose handler spawned this one
+    pub parent_task_id: Option<Uuid>,
+    /// Organization whose members can see the task
+    pub org_id: Option<Uuid>,
+    /// Tenant of the user who created the task
+    pub tenant_id: Uuid,
+}
+
+/// One page of tasks, as listed by API v2
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskPage {
+    pub items: Vec<TaskResponse>,
+    pub limit: i64,
+    pub offset: i64,
+    /// Whether tasks past this page match the filter
+    pub has_more: bool,
 }
 
 impl From<Task> for TaskResponse {
@@ -201,6 +226,127 @@ impl From<Task> for TaskResponse {
             completed_at: task.completed_at,
             created_by: task.created_by,
             metadata,
+            tags: task.tags,
+            timeout_seconds: task.timeout_seconds,
+            dedupe_key: task.dedupe_key,
+            parent_task_id: task.parent_task_id,
+            org_id: task.org_id,
+            tenant_id: task.tenant_id,
+        }
+    }
+}
+
+// API response type for tasks moved to the archive table
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ArchivedTaskResponse {
+    #[serde(flatten)]
+    pub task: TaskResponse,
+    pub archived_at: DateTime<Utc>,
+}
+
+// Task quota usage for the current user
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskQuota {
+    pub role: crate::rbac::UserRole,
+    pub pending: QuotaUsage,
+    pub daily: QuotaUsage,
+    /// When the daily counter resets (next UTC midnight)
+    pub daily_resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct QuotaUsage {
+    pub used: i64,
+    /// None when unlimited
+    pub limit: Option<u32>,
+}
+
+// A single execution attempt of a task (error is None for a successful attempt)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskAttempt {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub attempt_number: i32,
+    pub worker_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub error: Option<String>,
+    /// Why the attempt failed, see TaskErrorClass
+    pub error_class: Option<String>,
+}
+
+/// Classification of a failed task attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskErrorClass {
+    /// The handler returned an error
+    Error,
+    /// The handler exceeded its execution timeout
+    TimedOut,
+    /// The task type's circuit breaker was open
+    CircuitOpen,
+    /// No handler is registered for the task type
+    HandlerNotFound,
+    /// The worker stopped renewing its lease, presumably because it died
+    LeaseExpired,
+}
+
+impl TaskErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskErrorClass::Error => "error",
+            TaskErrorClass::TimedOut => "timed_out",
+            TaskErrorClass::CircuitOpen => "circuit_open",
+            TaskErrorClass::HandlerNotFound => "handler_not_found",
+            TaskErrorClass::LeaseExpired => "lease_expired",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskErrorClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// File format for task exports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" => Ok(ExportFormat::Ndjson),
+            _ => Err(Error::validation(
+                "format",
+                "Export format must be 'csv' or 'ndjson'",
+            )),
         }
     }
 }
@@ -217,6 +363,19 @@ pub struct CreateTaskRequest {
     pub created_by: Option<Uuid>,
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Execution timeout override (falls back to the task type default, then the processor)
+    pub timeout_seconds: Option<i32>,
+    /// While a task with the same key from the same creator is pending or running,
+    /// creation returns that task instead of a new one
+    pub dedupe_key: Option<String>,
+    /// Set by `TaskContext::enqueue_child`
+    #[serde(skip)]
+    pub parent_task_id: Option<Uuid>,
+    /// Organization whose members can see the task
+    #[serde(default)]
+    pub org_id: Option<Uuid>,
 }
 
 impl CreateTaskRequest {
@@ -227,6 +386,10 @@ impl CreateTaskRequest {
     const MAX_TOTAL_METADATA_SIZE_BYTES: usize = 64 * 1_024; // 64KB
     const MAX_SCHEDULE_FUTURE_DAYS: i64 = 365;
     const MAX_SCHEDULE_PAST_HOURS: i64 = 1;
+    const MAX_TAGS: usize = 20;
+    const MAX_TAG_LEN: usize = 64;
+    pub const MAX_TIMEOUT_SECONDS: i32 = 24 * 60 * 60; // 1 day
+    const MAX_DEDUPE_KEY_LEN: usize = 255;
 
     pub fn new(task_type: impl Into<String>, payload: serde_json::Value) -> Self {
         Self {
@@ -237,6 +400,11 @@ impl CreateTaskRequest {
             scheduled_at: None,
             created_by: None,
             metadata: HashMap::new(),
+            tags: Vec::new(),
+            timeout_seconds: None,
+            dedupe_key: None,
+            parent_task_id: None,
+            org_id: None,
         }
     }
 
@@ -314,6 +482,53 @@ impl CreateTaskRequest {
             ));
         }
 
+        // Validate tags
+        if self.tags.len() > Self::MAX_TAGS {
+            return Err(format!(
+                "A task cannot have more than {} tags",
+                Self::MAX_TAGS
+            ));
+        }
+
+        for tag in &self.tags {
+            if tag.is_empty() || tag.len() > Self::MAX_TAG_LEN {
+                return Err(format!(
+                    "Tags must be 1-{} characters long",
+                    Self::MAX_TAG_LEN
+                ));
+            }
+
+            if !tag
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(
+                    "Tags can only contain alphanumeric characters, underscores, and hyphens"
+                        .to_string(),
+                );
+            }
+        }
+
+        // Validate timeout override
+        if let Some(timeout_seconds) = self.timeout_seconds
+            && !(1..=Self::MAX_TIMEOUT_SECONDS).contains(&timeout_seconds)
+        {
+            return Err(format!(
+                "Timeout must be between 1 and {} seconds",
+                Self::MAX_TIMEOUT_SECONDS
+            ));
+        }
+
+        // Validate dedupe key
+        if let Some(dedupe_key) = &self.dedupe_key
+            && (dedupe_key.is_empty() || dedupe_key.len() > Self::MAX_DEDUPE_KEY_LEN)
+        {
+            return Err(format!(
+                "Dedupe key must be 1-{} characters long",
+                Self::MAX_DEDUPE_KEY_LEN
+            ));
+        }
+
         // Validate scheduled_at is not too far in the future
         if let Some(scheduled_at) = self.scheduled_at {
             let now = chrono::Utc::now();
@@ -358,10 +573,38 @@ impl CreateTaskRequest {
         self
     }
 
+    pub fn with_org_id(mut self, org_id: Uuid) -> Self {
+        self.org_id = Some(org_id);
+        self
+    }
+
     pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
         self.metadata.insert(key.into(), value);
         self
     }
+
+    pub fn with_timeout_seconds(mut self, timeout_seconds: i32) -> Self {
+        self.timeout_seconds = Some(timeout_seconds);
+        self
+    }
+
+    pub fn with_dedupe_key(mut self, dedupe_key: impl Into<String>) -> Self {
+        self.dedupe_key = Some(dedupe_key.into());
+        self
+    }
+
+    pub fn with_parent_task_id(mut self, parent_task_id: Uuid) -> Self {
+        self.parent_task_id = Some(parent_task_id);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -372,7 +615,160 @@ pub struct TaskContext {
     pub attempt: i32,
     pub metadata: HashMap<String, serde_json::Value>,
     pub created_by: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    /// Tenant of the task; its log events belong to it
+    pub tenant_id: Uuid,
     pub created_at: DateTime<Utc>,
+    /// Used by `log` to write monitoring events and by `enqueue_child`;
+    /// without it logs only go to tracing and children cannot be enqueued
+    pool: Option<DbPool>,
+    services: TaskServices,
+}
+
+// Severity of a task handler log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskLogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl TaskLogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskLogLevel::Debug => "debug",
+            TaskLogLevel::Info => "info",
+            TaskLogLevel::Warn => "warn",
+            TaskLogLevel::Error => "error",
+        }
+    }
+}
+
+impl TaskContext {
+    pub fn with_pool(mut self, pool: DbPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn with_services(mut self, services: TaskServices) -> Self {
+        self.services = services;
+        self
+    }
+
+    /// Shared clients and configuration injected by the processor
+    pub fn services(&self) -> &TaskServices {
+        &self.services
+    }
+
+    /// Database pool for handlers that read or write application data
+    pub fn pool(&self) -> Option<&DbPool> {
+        self.pool.as_ref()
+    }
+
+    /// Enqueue a task linked to this one as its parent
+    ///
+    /// The child is owned by the parent's creator and organization unless the
+    /// request names others, so it shows up in that user's task list and under
+    /// `GET /tasks/{id}/children`.
+    pub async fn enqueue_child(&self, request: CreateTaskRequest) -> TaskResult2<Task> {
+        let Some(pool) = &self.pool else {
+            return Err(TaskError::Execution(
+                "Task context has no database pool; cannot enqueue child tasks".to_string(),
+            ));
+        };
+
+        let mut request = request.with_parent_task_id(self.task_id);
+        if request.created_by.is_none() {
+            request.created_by = self.created_by;
+        }
+        if request.org_id.is_none() {
+            request.org_id = self.org_id;
+        }
+        request.validate().map_err(TaskError::Execution)?;
+
+        let registered = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM task_types WHERE task_type = $1 AND is_active = true) as "exists!""#,
+            request.task_type
+        )
+        .fetch_one(pool)
+        .await?;
+        if !registered {
+            return Err(TaskError::Execution(format!(
+                "Task type '{}' is not registered",
+                request.task_type
+            )));
+        }
+
+        let processor = crate::tasks::processor::TaskProcessor::new(
+            crate::Database { pool: pool.clone() },
+            crate::tasks::processor::ProcessorConfig::default(),
+        );
+        let child = processor.create_task(request).await?;
+
+        tracing::debug!("Task {} enqueued child task {}", self.task_id, child.id);
+        Ok(child)
+    }
+
+    /// Record a log line as a monitoring event tagged with this task
+    pub async fn log(&self, level: TaskLogLevel, message: impl Into<String>) {
+        self.log_with(level, message, HashMap::new()).await;
+    }
+
+    /// Record a log line with structured fields stored in the event payload
+    ///
+    /// Logging never fails the task: if the event cannot be written the line
+    /// is still emitted through tracing.
+    pub async fn log_with(
+        &self,
+        level: TaskLogLevel,
+        message: impl Into<String>,
+        fields: HashMap<String, serde_json::Value>,
+    ) {
+        let message = message.into();
+
+        match level {
+            TaskLogLevel::Debug => tracing::debug!(task_id = %self.task_id, "{}", message),
+            TaskLogLevel::Info => tracing::info!(task_id = %self.task_id, "{}", message),
+            TaskLogLevel::Warn => tracing::warn!(task_id = %self.task_id, "{}", message),
+            TaskLogLevel::Error => tracing::error!(task_id = %self.task_id, "{}", message),
+        }
+
+        let Some(pool) = &self.pool else {
+            return;
+        };
+
+        let event = CreateEventRequest {
+            event_type: "log".to_string(),
+            source: format!("task:{}", self.task_type),
+            message: Some(message),
+            level: Some(level.as_str().to_string()),
+            tags: HashMap::from([
+                ("task_id".to_string(), serde_json::json!(self.task_id)),
+                ("task_type".to_string(), serde_json::json!(self.task_type)),
+                ("attempt".to_string(), serde_json::json!(self.attempt)),
+            ]),
+            payload: fields,
+            recorded_at: None,
+            org_id: self.org_id,
+            tenant_id: Some(self.tenant_id),
+        };
+
+        let result = match pool.acquire().await {
+            Ok(mut conn) => monitoring_services::create_event(conn.as_mut(), event)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(Error::from_sqlx(e)),
+        };
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to record log event for task {}: {}",
+                self.task_id,
+                e
+            );
+        }
+    }
 }
 
 impl From<&Task> for TaskContext {
@@ -388,7 +784,11 @@ impl From<&Task> for TaskContext {
                 .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
                 .unwrap_or_default(),
             created_by: task.created_by,
+            org_id: task.org_id,
+            tenant_id: task.tenant_id,
             created_at: task.created_at,
+            pool: None,
+            services: TaskServices::default(),
         }
     }
 }
@@ -441,8 +841,15 @@ pub struct TaskFilter {
     pub status: Option<TaskStatus>,
     pub priority: Option<TaskPriority>,
     pub created_by: Option<Uuid>,
+    pub org_id: Option<Uuid>,
+    /// Matches tasks created by this user or scoped to one of their organizations
+    pub visible_to: Option<Uuid>,
+    pub tenant_id: Option<Uuid>,
     pub created_after: Option<DateTime<Utc>>,
     pub created_before: Option<DateTime<Utc>>,
+    pub tag: Option<String>,
+    /// Full-text search over payload and metadata values
+    pub search: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -454,14 +861,61 @@ impl Default for TaskFilter {
             status: None,
             priority: None,
             created_by: None,
+            org_id: None,
+            visible_to: None,
+            tenant_id: None,
             created_after: None,
             created_before: None,
+            tag: None,
+            search: None,
             limit: Some(100),
             offset: Some(0),
         }
     }
 }
 
+// Circuit breaker state for a task type, as last reported by a worker
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CircuitBreakerStatus {
+    pub task_type: String,
+    pub state: CircuitState,
+    pub failure_count: i32,
+    /// Manually tripped; stays open until reset
+    pub forced: bool,
+    /// Worker that last reported a state change
+    pub reported_by: Option<String>,
+    /// Admin who last tripped or reset the breaker
+    pub updated_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Pause state of a task type's queue
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskQueueState {
+    /// Task type the queue holds
+    pub queue: String,
+    /// Workers do not claim tasks from a paused queue
+    pub paused: bool,
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Admin who paused the queue
+    pub paused_by: Option<Uuid>,
+    /// Tasks waiting to run (pending or retrying)
+    pub pending: i64,
+    /// Tasks still running; a paused queue is drained once this reaches 0
+    pub running: i64,
+}
+
+// Queue depth and latency for a single task type
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskQueueStats {
+    pub task_type: String,
+    /// Tasks waiting to run (pending or retrying)
+    pub pending: i64,
+    pub running: i64,
+    /// Age in seconds of the oldest task that is due but not yet started
+    pub oldest_pending_age_seconds: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct TaskStats {
     pub total: i64,
@@ -491,6 +945,9 @@ pub enum TaskError {
     Timeout,
     #[error("Task cancelled")]
     Cancelled,
+    /// The task was reclaimed from this worker, whose outcome is discarded
+    #[error("Task lease lost: {0}")]
+    LeaseLost(Uuid),
 }
 
 impl TaskError {
@@ -591,3 +1048,85 @@ impl sqlx::Type<sqlx::Postgres> for TaskPriority {
         <&str as sqlx::Type<sqlx::Postgres>>::type_info()
     }
 }
+
+/// Cron expression to validate and preview
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SchedulePreviewRequest {
+    /// Standard 5-field (`min hour dom month dow`) or 6/7-field expression with seconds and year
+    pub cron: String,
+    /// IANA timezone the expression is evaluated in (default UTC)
+    pub timezone: Option<String>,
+    /// Number of upcoming runs to return (default 5, max 50)
+    pub count: Option<u32>,
+}
+
+/// Upcoming run times for a cron expression
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SchedulePreview {
+    pub cron: String,
+    pub timezone: String,
+    /// Run times with the timezone's UTC offset at each run
+    pub next_runs: Vec<DateTime<FixedOffset>>,
+}
+
+/// Reusable task definition instantiated with `POST /tasks/from-template/{name}`
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub task_type: String,
+    /// Default payload; overrides are applied as a JSON merge patch
+    pub payload: serde_json::Value,
+    pub priority: TaskPriority,
+    pub metadata: serde_json::Value,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateTaskTemplateRequest {
+    /// Unique name used in URLs (lowercase letters, digits, `-` and `_`)
+    pub name: String,
+    pub description: Option<String>,
+    pub task_type: String,
+    #[serde(default = "empty_object")]
+    pub payload: serde_json::Value,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// Fields to change on a template; omitted fields keep their current value
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateTaskTemplateRequest {
+    pub description: Option<String>,
+    pub task_type: Option<String>,
+    pub payload: Option<serde_json::Value>,
+    pub priority: Option<TaskPriority>,
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Per-task overrides merged into a template's defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct TaskFromTemplateRequest {
+    /// JSON merge patch (RFC 7396) applied to the template payload
+    pub payload: Option<serde_json::Value>,
+    pub priority: Option<TaskPriority>,
+    /// Added to the template metadata, replacing keys that already exist
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub scheduled_at: Option<DateTime<Utc>>,
+    pub timeout_seconds: Option<i32>,
+    pub dedupe_key: Option<String>,
+    /// Organization to scope the task to; requires membership
+    pub org_id: Option<Uuid>,
+}
+
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({})
+}
@@ -0,0 +1,200 @@
+use crate::auth::AuthUser;
+use crate::tenants::{
+    models::{CreateTenantRequest, Tenant, TenantScimToken, UpdateTenantRequest},
+    services as tenant_services,
+};
+use crate::{
+    AppState, Error,
+    api::{ApiResponse, ErrorResponse},
+};
+use axum::{
+    Router,
+    extract::{Extension, Path, State},
+    response::Json,
+    routing::{get, patch, post},
+};
+use uuid::Uuid;
+
+#[utoipa::path(
+    get,
+    path = "/admin/tenants",
+    tag = "Tenants",
+    summary = "List tenants",
+    description = "List every tenant of the deployment (admins of the default tenant only)",
+    responses(
+        (status = 200, description = "Tenants retrieved", body = ApiResponse<Vec<Tenant>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_tenants(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<Tenant>>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Tenants are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let tenants = tenant_services::list_tenants(conn.as_mut()).await?;
+    Ok(Json(ApiResponse::success(tenants)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/tenants",
+    tag = "Tenants",
+    summary = "Create tenant",
+    description = "Create a tenant; users sign up in it by naming it with the `X-Tenant` header or its subdomain (admins of the default tenant only)",
+    request_body = CreateTenantRequest,
+    responses(
+        (status = 200, description = "Tenant created", body = ApiResponse<Tenant>),
+        (status = 400, description = "Invalid name or slug", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 409, description = "Slug already taken", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_tenant(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<Json<ApiResponse<Tenant>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Tenants are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let tenant = tenant_services::create_tenant(conn.as_mut(), request).await?;
+    Ok(Json(ApiResponse::success(tenant)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/admin/tenants/{id}",
+    tag = "Tenants",
+    summary = "Update tenant",
+    description = "Rename a tenant, or deactivate it so its users can't sign in (admins of the default tenant only)",
+    params(
+        ("id" = Uuid, Path, description = "Tenant ID")
+    ),
+    request_body = UpdateTenantRequest,
+    responses(
+        (status = 200, description = "Tenant updated", body = ApiResponse<Tenant>),
+        (status = 400, description = "Invalid name, or deactivating the default tenant", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Tenant not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_tenant(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateTenantRequest>,
+) -> Result<Json<ApiResponse<Tenant>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Tenants are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let tenant =
+        tenant_services::update_tenant(conn.as_mut(), &app_state.cache, id, request).await?;
+    Ok(Json(ApiResponse::success(tenant)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/tenants/{id}/scim-token",
+    tag = "Tenants",
+    summary = "Issue tenant SCIM token",
+    description = "Issue the bearer token an identity provider uses to provision the tenant's users through SCIM, replacing its previous one; the token is only returned this once (admins of the default tenant only)",
+    params(
+        ("id" = Uuid, Path, description = "Tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Token issued", body = ApiResponse<TenantScimToken>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Tenant not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn issue_scim_token(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TenantScimToken>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Tenants are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let token = tenant_services::issue_scim_token(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success(token)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/tenants/{id}/scim-token",
+    tag = "Tenants",
+    summary = "Revoke tenant SCIM token",
+    description = "Stop accepting the tenant's SCIM token (admins of the default tenant only)",
+    params(
+        ("id" = Uuid, Path, description = "Tenant ID")
+    ),
+    responses(
+        (status = 200, description = "Token revoked", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Tenant not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn revoke_scim_token(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    tenant_services::require_default_tenant(&auth_user, "Tenants are managed")?;
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    tenant_services::revoke_scim_token(conn.as_mut(), id).await?;
+    Ok(Json(ApiResponse::success("SCIM token revoked".to_string())))
+}
+
+/// Tenant management routes (admin role required)
+pub fn tenants_admin_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_tenants).post(create_tenant))
+        .route("/{id}", patch(update_tenant))
+        .route(
+            "/{id}/scim-token",
+            post(issue_scim_token).delete(revoke_scim_token),
+        )
+}
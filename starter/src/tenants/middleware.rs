@@ -0,0 +1,106 @@
+use crate::tenants::{RequestTenant, services as tenant_services};
+use crate::{AppState, Error};
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Header naming the tenant a request acts in, by slug or ID
+pub const TENANT_HEADER: &str = "x-tenant";
+
+/// Tenant named by the `X-Tenant` header, or else by the subdomain of `base_domain`
+fn named_tenant(headers: &HeaderMap, base_domain: &str) -> Option<String> {
+    if let Some(value) = headers.get(TENANT_HEADER) {
+        return value
+            .to_str()
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+    }
+    if base_domain.is_empty() {
+        return None;
+    }
+    let host = headers.get(header::HOST)?.to_str().ok()?;
+    let host = host.split(':').next().unwrap_or(host);
+    host.strip_suffix(base_domain)?
+        .strip_suffix('.')
+        .filter(|subdomain| !subdomain.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+/// Resolve the tenant a request names into a [`RequestTenant`] extension
+///
+/// Unknown and inactive tenants are rejected as not found. With tenancy
+/// disabled every request gets an empty [`RequestTenant`].
+pub async fn tenant_middleware(
+    State(app_state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let tenancy = &app_state.config.tenancy;
+    let mut tenant = RequestTenant::default();
+
+    if tenancy.enabled
+        && let Some(name) = named_tenant(req.headers(), &tenancy.base_domain)
+    {
+        let mut conn = app_state
+            .database
+            .pool
+            .acquire()
+            .await
+            .map_err(Error::from_sqlx)?;
+        let found = tenant_services::find_tenant_cached(
+            conn.as_mut(),
+            &app_state.cache,
+            app_state.config.tenant_cache_ttl(),
+            &name,
+        )
+        .await?;
+        match found {
+            Some(found) if found.is_active => tenant = RequestTenant(Some(found.id)),
+            _ => return Err(Error::NotFound("Tenant not found".to_string())),
+        }
+    }
+
+    req.extensions_mut().insert(tenant);
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_header_takes_precedence_over_subdomain() {
+        let headers = headers(&[("host", "acme.example.com"), ("x-tenant", " globex ")]);
+        assert_eq!(
+            named_tenant(&headers, "example.com"),
+            Some("globex".to_string())
+        );
+    }
+
+    #[test]
+    fn test_subdomain_of_base_domain() {
+        let named = |host| named_tenant(&headers(&[("host", host)]), "example.com");
+        assert_eq!(named("acme.example.com:3000"), Some("acme".to_string()));
+        assert_eq!(named("ACME.example.com"), Some("acme".to_string()));
+        assert_eq!(named("example.com"), None);
+        assert_eq!(named("acme.other.com"), None);
+        assert_eq!(named("acmeexample.com"), None);
+        assert_eq!(
+            named_tenant(&headers(&[("host", "acme.example.com")]), ""),
+            None
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Tenants sharing one deployment
+//!
+//! Every user belongs to one tenant, and tasks, events and metrics belong to
+//! the tenant they were created in. Requests name a tenant with the
+//! `X-Tenant` header or a subdomain (see [`middleware::tenant_middleware`]);
+//! signed-in users always act in their own tenant, and staff views only
+//! cover that tenant's data.
+
+pub mod api;
+pub mod middleware;
+pub mod models;
+pub mod services;
+
+pub use models::{DEFAULT_TENANT_ID, RequestTenant, Tenant};
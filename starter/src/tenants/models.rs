@@ -0,0 +1,105 @@
+use crate::Error;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tenant that existing data, and data written without a tenant, belongs to
+pub const DEFAULT_TENANT_ID: Uuid = Uuid::from_u128(1);
+
+pub const MAX_TENANT_NAME_LENGTH: usize = 100;
+/// Slugs double as subdomains, so they follow DNS label limits
+pub const MAX_TENANT_SLUG_LENGTH: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Tenant {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    /// Inactive tenants can't be signed in to or named by requests
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Tenant a request names with the `X-Tenant` header or its subdomain
+///
+/// `None` when tenancy is disabled or the request names no tenant; signed-in
+/// users then act in their own tenant and new accounts go to the default one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestTenant(pub Option<Uuid>);
+
+impl RequestTenant {
+    /// Tenant new accounts are created in
+    pub fn id_or_default(&self) -> Uuid {
+        self.0.unwrap_or(DEFAULT_TENANT_ID)
+    }
+
+    /// Whether a user of `tenant_id` may act in this request
+    pub fn admits(&self, tenant_id: Uuid) -> bool {
+        self.0.is_none_or(|id| id == tenant_id)
+    }
+}
+
+/// A new SCIM token of a tenant, which is only returned this once
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TenantScimToken {
+    pub tenant_id: Uuid,
+    /// The identity provider sends it as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct CreateTenantRequest {
+    /// Lowercase letters, digits and dashes; also the tenant's subdomain
+    pub slug: String,
+    pub name: String,
+}
+
+impl CreateTenantRequest {
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name)?;
+        validate_slug(&self.slug)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UpdateTenantRequest {
+    pub name: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+impl UpdateTenantRequest {
+    pub fn validate(&self) -> Result<()> {
+        self.name.as_deref().map_or(Ok(()), validate_name)
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.len() > MAX_TENANT_NAME_LENGTH {
+        return Err(Error::validation(
+            "name",
+            &format!("Name must be between 1 and {MAX_TENANT_NAME_LENGTH} characters"),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_slug(slug: &str) -> Result<()> {
+    let valid = !slug.is_empty()
+        && slug.len() <= MAX_TENANT_SLUG_LENGTH
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !slug.starts_with('-')
+        && !slug.ends_with('-');
+    if !valid {
+        return Err(Error::validation(
+            "slug",
+            &format!(
+                "Slug must be 1 to {MAX_TENANT_SLUG_LENGTH} lowercase letters, digits or dashes"
+            ),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use crate::auth::{AuthUser, services::generate_session_token};
+use crate::core::cache::AppCache;
+use crate::tenants::models::{
+    CreateTenantRequest, DEFAULT_TENANT_ID, Tenant, TenantScimToken, UpdateTenantRequest,
+};
+use crate::{DbConn, Error, Result};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Cached tenants by slug or ID
+pub const TENANT_CACHE_NAMESPACE: &str = "tenants";
+/// Cached tenants by the ID of a user in them
+pub const USER_TENANT_CACHE_NAMESPACE: &str = "user_tenants";
+
+/// Start of every tenant SCIM token
+pub const SCIM_TOKEN_PREFIX: &str = "scim_";
+
+pub async fn list_tenants(conn: &mut DbConn) -> Result<Vec<Tenant>> {
+    sqlx::query_as!(
+        Tenant,
+        r#"
+        SELECT id, slug, name, is_active, created_at, updated_at
+        FROM tenants
+        ORDER BY created_at, slug
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Tenant with the given slug, or ID when `name` is one
+pub async fn find_tenant(conn: &mut DbConn, name: &str) -> Result<Option<Tenant>> {
+    let id = Uuid::parse_str(name).ok();
+    sqlx::query_as!(
+        Tenant,
+        r#"
+        SELECT id, slug, name, is_active, created_at, updated_at
+        FROM tenants
+        WHERE slug = $1 OR id = $2
+        "#,
+        name,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// [`find_tenant`], through the cache
+pub async fn find_tenant_cached(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    ttl: Duration,
+    name: &str,
+) -> Result<Option<Tenant>> {
+    if ttl.is_zero() {
+        return find_tenant(conn, name).await;
+    }
+    if let Some(tenant) = cache.get(TENANT_CACHE_NAMESPACE, name).await {
+        return Ok(Some(tenant));
+    }
+    let tenant = find_tenant(conn, name).await?;
+    if let Some(tenant) = &tenant {
+        cache.set(TENANT_CACHE_NAMESPACE, name, tenant, ttl).await;
+    }
+    Ok(tenant)
+}
+
+/// Tenant the user belongs to, through the cache
+pub async fn user_tenant_cached(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    ttl: Duration,
+    user_id: Uuid,
+) -> Result<Tenant> {
+    let key = user_id.to_string();
+    if !ttl.is_zero()
+        && let Some(tenant) = cache.get(USER_TENANT_CACHE_NAMESPACE, &key).await
+    {
+        return Ok(tenant);
+    }
+    let tenant = sqlx::query_as!(
+        Tenant,
+        r#"
+        SELECT t.id, t.slug, t.name, t.is_active, t.created_at, t.updated_at
+        FROM tenants t
+        JOIN users u ON u.tenant_id = t.id
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    if !ttl.is_zero() {
+        cache
+            .set(USER_TENANT_CACHE_NAMESPACE, &key, &tenant, ttl)
+            .await;
+    }
+    Ok(tenant)
+}
+
+/// Tenant of the user, if they exist
+pub async fn user_tenant_id(conn: &mut DbConn, user_id: Uuid) -> Result<Option<Uuid>> {
+    sqlx::query_scalar!("SELECT tenant_id FROM users WHERE id = $1", user_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)
+}
+
+/// Fail with not found unless the user belongs to the tenant, so other
+/// tenants' users look the same as missing ones
+pub async fn require_user_in_tenant(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<()> {
+    let found = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND tenant_id = $2)",
+        user_id,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .unwrap_or(false);
+    if !found {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Fail with a validation error on `field` unless every user belongs to the
+/// tenant, for requests naming users to notify or put on call
+pub async fn require_users_in_tenant(
+    conn: &mut DbConn,
+    field: &str,
+    user_ids: &[Uuid],
+    tenant_id: Uuid,
+) -> Result<()> {
+    let missing = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM UNNEST($1::UUID[]) AS requested(id)
+            WHERE NOT EXISTS(
+                SELECT 1 FROM users u WHERE u.id = requested.id AND u.tenant_id = $2
+            )
+        ) AS "missing!"
+        "#,
+        user_ids,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if missing {
+        return Err(Error::validation(field, "User not found"));
+    }
+    Ok(())
+}
+
+/// Fail unless the user is in the default tenant, for settings that apply to
+/// every tenant; `what` names them, e.g. "Feature flags are managed"
+pub(crate) fn require_default_tenant(auth_user: &AuthUser, what: &str) -> Result<()> {
+    if auth_user.tenant_id != DEFAULT_TENANT_ID {
+        return Err(Error::Forbidden(format!("{what} from the default tenant")));
+    }
+    Ok(())
+}
+
+pub async fn create_tenant(conn: &mut DbConn, request: CreateTenantRequest) -> Result<Tenant> {
+    request.validate()?;
+    sqlx::query_as!(
+        Tenant,
+        r#"
+        INSERT INTO tenants (slug, name)
+        VALUES ($1, $2)
+        RETURNING id, slug, name, is_active, created_at, updated_at
+        "#,
+        request.slug,
+        request.name.trim()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::Conflict(format!("Tenant '{}' already exists", request.slug))
+        }
+        _ => Error::from_sqlx(e),
+    })
+}
+
+/// Rename or (de)activate a tenant; cached lookups are dropped so the change
+/// applies on every server once their entries expire
+pub async fn update_tenant(
+    conn: &mut DbConn,
+    cache: &AppCache,
+    id: Uuid,
+    request: UpdateTenantRequest,
+) -> Result<Tenant> {
+    request.validate()?;
+    if id == DEFAULT_TENANT_ID && request.is_active == Some(false) {
+        return Err(Error::validation(
+            "is_active",
+            "The default tenant can't be deactivated",
+        ));
+    }
+    let tenant = sqlx::query_as!(
+        Tenant,
+        r#"
+        UPDATE tenants
+        SET name = COALESCE($2, name),
+            is_active = COALESCE($3, is_active),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, slug, name, is_active, created_at, updated_at
+        "#,
+        id,
+        request.name.as_deref().map(str::trim),
+        request.is_active
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("Tenant not found".to_string()))?;
+
+    cache.clear(TENANT_CACHE_NAMESPACE).await;
+    cache.clear(USER_TENANT_CACHE_NAMESPACE).await;
+    Ok(tenant)
+}
+
+/// Issue a new SCIM token for the tenant, replacing its previous one
+pub async fn issue_scim_token(conn: &mut DbConn, tenant_id: Uuid) -> Result<TenantScimToken> {
+    let token = format!("{SCIM_TOKEN_PREFIX}{}", generate_session_token());
+    let updated = sqlx::query!(
+        r#"
+        UPDATE tenants
+        SET scim_token_hash = encode(sha256(convert_to($2, 'UTF8')), 'hex'), updated_at = NOW()
+        WHERE id = $1
+        "#,
+        tenant_id,
+        token
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if updated == 0 {
+        return Err(Error::NotFound("Tenant not found".to_string()));
+    }
+    Ok(TenantScimToken { tenant_id, token })
+}
+
+/// Revoke the tenant's SCIM token, if it has one
+pub async fn revoke_scim_token(conn: &mut DbConn, tenant_id: Uuid) -> Result<()> {
+    let updated = sqlx::query!(
+        "UPDATE tenants SET scim_token_hash = NULL, updated_at = NOW() WHERE id = $1",
+        tenant_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    if updated == 0 {
+        return Err(Error::NotFound("Tenant not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Active tenant the SCIM token was issued for
+pub async fn find_scim_tenant(conn: &mut DbConn, token: &str) -> Result<Option<Uuid>> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM tenants
+        WHERE scim_token_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex') AND is_active = true
+        "#,
+        token
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
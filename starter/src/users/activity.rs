@@ -0,0 +1,69 @@
+use crate::users::models::{UserActivity, UserActivityAction};
+use crate::{DbConn, Error, Result};
+use serde_json::Value;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Append an entry to `user_id`'s activity trail
+///
+/// `actor_id` is `None` for changes made by the system or an identity provider.
+///
+/// The action itself has already succeeded, so a failure to record it is
+/// logged rather than returned.
+pub async fn record_activity(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    actor_id: Option<Uuid>,
+    action: UserActivityAction,
+    details: Value,
+) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_activity (user_id, actor_id, action, details)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        actor_id,
+        action.as_str(),
+        details
+    )
+    .execute(&mut *conn)
+    .await;
+
+    if let Err(e) = result {
+        warn!(
+            "Failed to record {} activity for user {}: {}",
+            action, user_id, e
+        );
+    }
+}
+
+/// Activity trail of one account, newest first
+pub async fn find_activity(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    action: Option<&str>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<UserActivity>> {
+    let limit = limit.unwrap_or(50).min(100);
+    let offset = offset.unwrap_or(0);
+
+    sqlx::query_as!(
+        UserActivity,
+        r#"
+        SELECT id, user_id, actor_id, action, details, created_at
+        FROM user_activity
+        WHERE user_id = $1 AND ($2::text IS NULL OR action = $2)
+        ORDER BY created_at DESC, id
+        LIMIT $3 OFFSET $4
+        "#,
+        user_id,
+        action,
+        limit,
+        offset
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
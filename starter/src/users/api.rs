@@ -1,26 +1,47 @@
-use crate::auth::AuthUser;
-use crate::rbac::services as rbac_services;
+use crate::audit::{self, Actor, AuditResource, AuditResourceType};
+use crate::auth::{AuthUser, services as auth_services, verification};
+use crate::rbac::{
+    Permission, PermissionRoutes, Requirement, Resource, UserRole, role_requests,
+    services as rbac_services,
+};
+use crate::tenants::services as tenant_services;
 use crate::users::{
+    activity, avatar, deletion, export,
+    import::{self, MAX_IMPORT_BYTES},
+    list_export,
     models::{
-        ChangePasswordRequest, CreateUserRequest, DeleteAccountRequest, DeleteUserRequest,
-        ResetPasswordRequest, UpdateProfileRequest, UpdateUserProfileRequest,
-        UpdateUserRoleRequest, UpdateUserStatusRequest, UserProfile, UserStats,
+        AccountDeletion, AvatarUpload, CancelAccountDeletionRequest, ChangePasswordRequest,
+        CreateUserRequest, DataExport, DeactivationSummary, DeleteAccountRequest,
+        DeleteUserRequest, ImportPasswordPolicy, OnlineUsersSummary, ProfileField,
+        ResetPasswordRequest, SortOrder, UpdateInternalMetadataRequest, UpdateProfileRequest,
+        UpdateUserProfileRequest, UpdateUserQuotasRequest, UpdateUserRoleRequest,
+        UpdateUserStatusRequest, UpsertProfileFieldRequest, UserActivity, UserActivityAction,
+        UserFilter, UserImport, UserImportUpload, UserListResponse, UserProfile, UserPurge,
+        UserQuotas, UserStats, UserStatusUpdate,
     },
-    services as user_services,
+    presence, purge, quotas, services as user_services,
 };
 use crate::{
     AppState, Error,
     api::{ApiResponse, ErrorResponse},
+    core::body_limit::limit_body,
 };
 use axum::{
     Router,
-    extract::{Extension, Path, Query, State},
-    response::Json,
+    body::Body,
+    extract::{Extension, Multipart, Path, Query, State},
+    http::{StatusCode, header},
+    response::{Json, Response},
     routing::{delete, get, post, put},
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use serde_json::json;
+use tower_http::decompression::RequestDecompressionLayer;
 use uuid::Uuid;
 
+const MAX_SEARCH_QUERY_LEN: usize = 200;
+
 #[utoipa::path(
     get,
     path = "/users/me/profile",
@@ -47,12 +68,23 @@ pub async fn get_profile(
         .acquire()
         .await
         .map_err(Error::from_sqlx)?;
-    let profile = user_services::get_user_profile(conn.as_mut(), auth_user.id).await?;
+    let Some(mut profile) = user_services::get_user_profile(conn.as_mut(), auth_user.id).await?
+    else {
+        return Err(Error::NotFound("User profile not found".to_string()));
+    };
+    user_services::attach_profiles(
+        conn.as_mut(),
+        std::slice::from_mut(&mut profile),
+        is_staff(&auth_user),
+    )
+    .await?;
 
-    match profile {
-        Some(profile) => Ok(Json(ApiResponse::success(profile))),
-        None => Err(Error::NotFound("User profile not found".to_string())),
-    }
+    Ok(Json(ApiResponse::success(profile)))
+}
+
+/// Moderators and admins see staff profile fields
+fn is_staff(auth_user: &AuthUser) -> bool {
+    auth_user.role.has_role_or_higher(UserRole::Moderator)
 }
 
 #[utoipa::path(
@@ -85,6 +117,8 @@ pub async fn get_user_by_id(
         .await
         .map_err(Error::from_sqlx)?;
 
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
     // For regular users, check if they're trying to access their own profile first (optimization)
     if auth_user.role == crate::rbac::UserRole::User && auth_user.id != id {
         return Err(Error::NotFound("User not found".to_string()));
@@ -101,29 +135,85 @@ pub async fn get_user_by_id(
     rbac_services::can_access_user_profile(&auth_user, id, target_user.role)?;
 
     // Return user profile
-    Ok(Json(ApiResponse::success(target_user.to_profile())))
+    let mut profile = target_user.to_profile();
+    user_services::attach_profiles(
+        conn.as_mut(),
+        std::slice::from_mut(&mut profile),
+        is_staff(&auth_user),
+    )
+    .await?;
+    Ok(Json(ApiResponse::success(profile)))
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ListUsersQuery {
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub is_active: Option<bool>,
+    pub email_verified: Option<bool>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: Option<String>,
+    pub order: Option<SortOrder>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
-/// List all users (Admin/Moderator only)
+impl ListUsersQuery {
+    fn into_filter(self, tenant_id: Uuid) -> Result<UserFilter, Error> {
+        if let Some(ref search) = self.search
+            && search.len() > MAX_SEARCH_QUERY_LEN
+        {
+            return Err(Error::validation(
+                "search",
+                &format!("Search query cannot exceed {MAX_SEARCH_QUERY_LEN} characters"),
+            ));
+        }
+
+        Ok(UserFilter {
+            tenant_id,
+            search: self.search,
+            role: self.role,
+            is_active: self.is_active,
+            email_verified: self.email_verified,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            sort: self
+                .sort
+                .as_deref()
+                .map(str::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            order: self.order,
+            limit: self.limit,
+            offset: self.offset,
+        })
+    }
+}
+
+/// Search users (Admin/Moderator only)
 #[utoipa::path(
     get,
     path = "/users",
     tag = "Users",
     summary = "List users",
-    description = "List all users in the system (Admin/Moderator only)",
+    description = "Search and filter users, with pagination metadata (Admin/Moderator, or a custom role with `users:read`)",
     params(
-        ("limit" = Option<i64>, Query, description = "Maximum number of users to return"),
+        ("search" = Option<String>, Query, description = "Case-insensitive match anywhere in the username or email"),
+        ("role" = Option<UserRole>, Query, description = "Only users with this role"),
+        ("is_active" = Option<bool>, Query, description = "Only active (true) or deactivated (false) users; both when omitted"),
+        ("email_verified" = Option<bool>, Query, description = "Only users with or without a verified email"),
+        ("created_after" = Option<DateTime<Utc>>, Query, description = "Only users created at or after this time"),
+        ("created_before" = Option<DateTime<Utc>>, Query, description = "Only users created at or before this time"),
+        ("sort" = Option<String>, Query, description = "created_at (default), username, email, last_login_at or last_seen_at"),
+        ("order" = Option<SortOrder>, Query, description = "Defaults to desc for dates and asc for username and email"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of users to return (default 50, max 100)"),
         ("offset" = Option<i64>, Query, description = "Number of users to skip")
     ),
     responses(
-        (status = 200, description = "List of users", body = ApiResponse<Vec<UserProfile>>),
-        (status = 403, description = "Forbidden - Moderator access required", body = ErrorResponse),
+        (status = 200, description = "Matching users", body = ApiResponse<UserListResponse>),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Moderator access or `users:read` required", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse)
     ),
     security(
@@ -134,9 +224,8 @@ pub async fn list_users(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Query(params): Query<ListUsersQuery>,
-) -> Result<Json<ApiResponse<Vec<UserProfile>>>, Error> {
-    // Require moderator or higher role
-    rbac_services::require_moderator_or_higher(&auth_user)?;
+) -> Result<Json<ApiResponse<UserListResponse>>, Error> {
+    let filter = params.into_filter(auth_user.tenant_id)?;
 
     let mut conn = app_state
         .database
@@ -145,7 +234,8 @@ pub async fn list_users(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let users = user_services::list_users(conn.as_mut(), params.limit, params.offset).await?;
+    let mut users = user_services::list_users(conn.as_mut(), filter).await?;
+    user_services::attach_profiles(conn.as_mut(), &mut users.items, true).await?;
 
     Ok(Json(ApiResponse::success(users)))
 }
@@ -161,7 +251,7 @@ pub async fn list_users(
     responses(
         (status = 200, description = "User created", body = ApiResponse<UserProfile>),
         (status = 400, description = "Validation error", body = ErrorResponse),
-        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required, or the admin role without an approved role request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 409, description = "Username or email already exists", body = ErrorResponse)
     ),
@@ -176,6 +266,11 @@ pub async fn create_user(
 ) -> Result<Json<ApiResponse<UserProfile>>, Error> {
     // Require admin role
     rbac_services::require_admin(&auth_user)?;
+    role_requests::require_admin_approval(
+        app_state.config.auth.admin_role_requires_approval,
+        request.role.unwrap_or(UserRole::User),
+        None,
+    )?;
 
     let mut conn = app_state
         .database
@@ -184,7 +279,7 @@ pub async fn create_user(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let user = user_services::create_user(conn.as_mut(), request).await?;
+    let user = user_services::create_user(conn.as_mut(), request, auth_user.tenant_id).await?;
 
     Ok(Json(ApiResponse::success(user)))
 }
@@ -195,13 +290,13 @@ pub async fn create_user(
     path = "/users/me/profile",
     tag = "Users",
     summary = "Update own profile",
-    description = "Update own user profile (username, email)",
+    description = "Update own user profile (username, email). The username can be changed once per cooldown, and names other users gave up recently are reserved",
     request_body = UpdateProfileRequest,
     responses(
         (status = 200, description = "Profile updated", body = ApiResponse<UserProfile>),
-        (status = 400, description = "Validation error", body = ErrorResponse),
+        (status = 400, description = "Validation error or username change cooldown", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 409, description = "Username or email already exists", body = ErrorResponse)
+        (status = 409, description = "Username (current or reserved) or email already exists", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
@@ -219,11 +314,109 @@ pub async fn update_own_profile(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let user = user_services::update_user_profile(conn.as_mut(), auth_user.id, request).await?;
+    let fields = request.changed_fields();
+    let mut user = user_services::update_user_profile(
+        conn.as_mut(),
+        auth_user.id,
+        request,
+        &app_state.config.auth,
+    )
+    .await?;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::ProfileUpdated,
+        json!({ "fields": fields }),
+    )
+    .await;
+    user_services::attach_profiles(
+        conn.as_mut(),
+        std::slice::from_mut(&mut user),
+        is_staff(&auth_user),
+    )
+    .await?;
 
     Ok(Json(ApiResponse::success(user)))
 }
 
+/// Upload own avatar
+#[utoipa::path(
+    put,
+    path = "/users/me/avatar",
+    tag = "Users",
+    summary = "Upload own avatar",
+    description = "Upload a PNG, JPEG or WebP image as a multipart `avatar` field; it is cropped to a square and stored as PNG",
+    request_body(content = AvatarUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Avatar updated", body = ApiResponse<UserProfile>),
+        (status = 400, description = "Missing, oversized or undecodable image", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "Request body too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported image type", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn upload_own_avatar(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    mut multipart: Multipart,
+) -> Result<Json<ApiResponse<UserProfile>>, Error> {
+    let multipart_error = |e: axum::extract::multipart::MultipartError| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            Error::PayloadTooLarge {
+                limit_bytes: app_state.config.body_limit.uploads_bytes,
+            }
+        } else {
+            Error::validation("avatar", &e.body_text())
+        }
+    };
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() == Some("avatar") {
+            let content_type = field.content_type().unwrap_or_default().to_string();
+            let bytes = field.bytes().await.map_err(multipart_error)?;
+            upload = Some((content_type, bytes));
+            break;
+        }
+    }
+    let Some((content_type, bytes)) = upload else {
+        return Err(Error::validation("avatar", "Missing 'avatar' file field"));
+    };
+
+    // Decoding is CPU-bound, keep it off the async workers
+    let png = tokio::task::spawn_blocking(move || avatar::process_avatar(&content_type, &bytes))
+        .await
+        .map_err(|e| Error::internal(&format!("Avatar processing failed: {e}")))??;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+    let profile = user_services::set_user_avatar(
+        conn.as_mut(),
+        app_state.storage.as_ref(),
+        auth_user.id,
+        png,
+    )
+    .await?;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::AvatarUpdated,
+        json!({}),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success(profile)))
+}
+
 /// Change own password
 #[utoipa::path(
     put,
@@ -255,6 +448,14 @@ pub async fn change_own_password(
         .map_err(Error::from_sqlx)?;
 
     user_services::change_user_password(conn.as_mut(), auth_user.id, request).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::PasswordChanged,
+        json!({}),
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
         "Password updated successfully".to_string(),
@@ -268,12 +469,13 @@ pub async fn change_own_password(
     path = "/users/me",
     tag = "Users",
     summary = "Delete own account",
-    description = "Delete own user account (soft delete)",
+    description = "Deactivate your account and schedule its erasure after the grace period (`auth.account_deletion_grace_days`). Signs you out everywhere and revokes your API keys; the confirmation email has a link that cancels the deletion until the account is erased",
     request_body = DeleteAccountRequest,
     responses(
-        (status = 200, description = "Account deleted", body = ApiResponse<String>),
+        (status = 200, description = "Account deletion scheduled", body = ApiResponse<AccountDeletion>),
         (status = 400, description = "Missing confirmation or incorrect password", body = ErrorResponse),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 500, description = "The confirmation email could not be sent; the account was kept", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
@@ -283,7 +485,63 @@ pub async fn delete_own_account(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
     Json(request): Json<DeleteAccountRequest>,
-) -> Result<Json<ApiResponse<String>>, Error> {
+) -> Result<Json<ApiResponse<AccountDeletion>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let deletion = deletion::request_deletion(
+        conn.as_mut(),
+        &app_state.database,
+        app_state.services.email(),
+        &app_state.config,
+        auth_user.id,
+        request,
+    )
+    .await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        auth_user.id,
+    )
+    .await;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::AccountDeleted,
+        json!({ "deletion_id": deletion.id, "erase_after": deletion.erase_after }),
+    )
+    .await;
+
+    let message = format!(
+        "Your account has been deactivated and will be erased after {}. Use the link in the confirmation email to cancel.",
+        deletion.erase_after.to_rfc3339()
+    );
+    Ok(Json(ApiResponse::success_with_message(deletion, message)))
+}
+
+/// Cancel a scheduled account deletion (publicly accessible with the email's token)
+#[utoipa::path(
+    post,
+    path = "/account-deletion/cancel",
+    tag = "Users",
+    summary = "Cancel account deletion",
+    description = "Restore an account scheduled for deletion using the token from the confirmation email. Works until the account is erased; sign in again afterwards, since sessions and API keys stay revoked",
+    request_body = CancelAccountDeletionRequest,
+    responses(
+        (status = 200, description = "Account deletion cancelled", body = ApiResponse<AccountDeletion>),
+        (status = 404, description = "Unknown token or the deletion is no longer scheduled", body = ErrorResponse),
+        (status = 409, description = "The account has already been erased", body = ErrorResponse)
+    )
+)]
+pub async fn cancel_account_deletion(
+    State(app_state): State<AppState>,
+    Json(request): Json<CancelAccountDeletionRequest>,
+) -> Result<Json<ApiResponse<AccountDeletion>>, Error> {
     let mut conn = app_state
         .database
         .pool
@@ -291,14 +549,184 @@ pub async fn delete_own_account(
         .await
         .map_err(Error::from_sqlx)?;
 
-    user_services::delete_user_account(conn.as_mut(), auth_user.id, request).await?;
+    let (user_id, deletion) =
+        deletion::cancel_deletion(conn.as_mut(), app_state.services.email(), &request.token)
+            .await?;
+    activity::record_activity(
+        conn.as_mut(),
+        user_id,
+        Some(user_id),
+        UserActivityAction::AccountDeletionCancelled,
+        json!({ "deletion_id": deletion.id }),
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
-        "Account deleted successfully".to_string(),
-        "Your account has been deactivated. All data will be retained for 30 days.".to_string(),
+        deletion,
+        "Account deletion cancelled. Sign in again to keep using your account.".to_string(),
     )))
 }
 
+/// Request an export of own data
+#[utoipa::path(
+    post,
+    path = "/users/me/export",
+    tag = "Users",
+    summary = "Request data export",
+    description = "Start building a zip archive of your profile, sessions, tasks, monitoring events and account activity; poll the export until it is ready, then download it from `download_url`",
+    responses(
+        (status = 200, description = "Export started", body = ApiResponse<DataExport>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 409, description = "An export is already in progress", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn request_own_data_export(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<DataExport>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let data_export =
+        export::create_export(conn.as_mut(), &app_state.database, auth_user.id).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        auth_user.id,
+        Some(auth_user.id),
+        UserActivityAction::DataExportRequested,
+        json!({ "export_id": data_export.id }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success(data_export)))
+}
+
+/// List own data exports
+#[utoipa::path(
+    get,
+    path = "/users/me/exports",
+    tag = "Users",
+    summary = "List data exports",
+    description = "List your data exports, newest first",
+    responses(
+        (status = 200, description = "Data exports", body = ApiResponse<Vec<DataExport>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_own_data_exports(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<DataExport>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let exports = export::find_exports(conn.as_mut(), auth_user.id).await?;
+
+    Ok(Json(ApiResponse::success(exports)))
+}
+
+/// Get own data export
+#[utoipa::path(
+    get,
+    path = "/users/me/exports/{id}",
+    tag = "Users",
+    summary = "Get data export",
+    description = "Get the status of a data export; `download_url` is set once it is ready and until it expires",
+    params(
+        ("id" = Uuid, Path, description = "Data export ID")
+    ),
+    responses(
+        (status = 200, description = "Data export", body = ApiResponse<DataExport>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Data export not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_own_data_export(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DataExport>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let data_export = export::find_export(conn.as_mut(), auth_user.id, id).await?;
+
+    Ok(Json(ApiResponse::success(data_export)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadDataExportQuery {
+    pub token: String,
+}
+
+/// Download a data export (publicly accessible with the link's token)
+#[utoipa::path(
+    get,
+    path = "/exports/{id}/download",
+    tag = "Users",
+    summary = "Download data export",
+    description = "Download a ready data export archive; the link from `download_url` works without logging in until the export expires",
+    params(
+        ("id" = Uuid, Path, description = "Data export ID"),
+        ("token" = String, Query, description = "Download token from `download_url`")
+    ),
+    responses(
+        (status = 200, description = "Zip archive", content_type = "application/zip"),
+        (status = 404, description = "Data export not found, not ready or expired", body = ErrorResponse)
+    )
+)]
+pub async fn download_data_export(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DownloadDataExportQuery>,
+) -> Result<Response, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let key = export::find_download(conn.as_mut(), id, &query.token).await?;
+    let bytes = app_state
+        .storage
+        .get(&key)
+        .await?
+        .ok_or_else(|| Error::NotFound("Data export not found or expired".to_string()))?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"data-export-{id}.zip\""),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from(bytes))
+        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+}
+
 /// Update any user's profile (Admin only)
 #[utoipa::path(
     put,
@@ -338,7 +766,19 @@ pub async fn update_user_profile(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let user = user_services::update_user_profile_admin(conn.as_mut(), id, request).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let fields = request.changed_fields();
+    let mut user = user_services::update_user_profile_admin(conn.as_mut(), id, request).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        Some(auth_user.id),
+        UserActivityAction::ProfileUpdated,
+        json!({ "fields": fields }),
+    )
+    .await;
+    user_services::attach_profiles(conn.as_mut(), std::slice::from_mut(&mut user), true).await?;
 
     Ok(Json(ApiResponse::success(user)))
 }
@@ -349,16 +789,16 @@ pub async fn update_user_profile(
     path = "/users/{id}/status",
     tag = "Users",
     summary = "Update user status",
-    description = "Activate or deactivate a user account (Moderator/Admin)",
+    description = "Activate or deactivate a user account, optionally until `suspended_until` (Moderator/Admin, or a custom role with `users:write`). Deactivating revokes the account's sessions and API keys and cancels its queued tasks; admins can hand its open work to `reassign_to`",
     params(
         ("id" = Uuid, Path, description = "User ID")
     ),
     request_body = UpdateUserStatusRequest,
     responses(
-        (status = 200, description = "User status updated", body = ApiResponse<UserProfile>),
+        (status = 200, description = "User status updated", body = ApiResponse<UserStatusUpdate>),
         (status = 400, description = "Invalid request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - Moderator access required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Moderator access or `users:write` required, or admin access to reassign work", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse)
     ),
     security(
@@ -368,11 +808,13 @@ pub async fn update_user_profile(
 pub async fn update_user_status(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateUserStatusRequest>,
-) -> Result<Json<ApiResponse<UserProfile>>, Error> {
-    // Require moderator or higher role
-    rbac_services::require_moderator_or_higher(&auth_user)?;
+) -> Result<Json<ApiResponse<UserStatusUpdate>>, Error> {
+    if request.reassign_to.is_some() {
+        rbac_services::require_admin(&auth_user)?;
+    }
 
     let mut conn = app_state
         .database
@@ -381,7 +823,53 @@ pub async fn update_user_status(
         .await
         .map_err(Error::from_sqlx)?;
 
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let before = user_services::find_user_by_id(conn.as_mut(), id)
+        .await?
+        .map(|user| {
+            json!({
+                "is_active": user.is_active,
+                "suspended_until": user.suspended_until,
+            })
+        });
+    let actor = actor.with_reason(request.reason.clone());
+    let mut details = json!({
+        "is_active": request.is_active,
+        "reason": actor.reason,
+        "suspended_until": request.suspended_until,
+    });
     let user = user_services::update_user_status(conn.as_mut(), id, request).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        id,
+    )
+    .await;
+    if let Some(deactivation) = &user.deactivation {
+        details["deactivation"] = json!(deactivation);
+    }
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        "user_status_changed",
+        &AuditResource::new(AuditResourceType::User, id),
+        before,
+        Some(json!({
+            "is_active": user.user.is_active,
+            "suspended_until": user.user.suspended_until,
+            "deactivation": user.deactivation,
+        })),
+    )
+    .await;
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        Some(auth_user.id),
+        UserActivityAction::StatusChanged,
+        details,
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
         user,
@@ -395,16 +883,16 @@ pub async fn update_user_status(
     path = "/users/{id}/role",
     tag = "Users",
     summary = "Update user role",
-    description = "Change a user's role (Admin only)",
+    description = "Change a user's role (Admin only), optionally until `expires_at`, when the previous role is restored. Unless `auth.admin_role_requires_approval` is off, the admin role is only granted through an approved role request",
     params(
         ("id" = Uuid, Path, description = "User ID")
     ),
     request_body = UpdateUserRoleRequest,
     responses(
         (status = 200, description = "User role updated", body = ApiResponse<UserProfile>),
-        (status = 400, description = "Invalid role value", body = ErrorResponse),
+        (status = 400, description = "Invalid role value or expiry", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required, or promotion to admin needs an approved role request", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse)
     ),
     security(
@@ -414,6 +902,7 @@ pub async fn update_user_status(
 pub async fn update_user_role(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateUserRoleRequest>,
 ) -> Result<Json<ApiResponse<UserProfile>>, Error> {
@@ -427,7 +916,36 @@ pub async fn update_user_role(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let user = user_services::update_user_role(conn.as_mut(), id, request).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let previous_role = user_services::find_user_by_id(conn.as_mut(), id)
+        .await?
+        .map(|user| user.role);
+    // Promotions to admin need a second admin's approval
+    role_requests::require_admin_approval(
+        app_state.config.auth.admin_role_requires_approval,
+        request.role,
+        previous_role,
+    )?;
+    let reason = actor.with_reason(request.reason.clone()).reason;
+    let request = UpdateUserRoleRequest {
+        reason: reason.clone(),
+        ..request
+    };
+    let user = user_services::update_user_role(conn.as_mut(), id, request, auth_user.id).await?;
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        Some(auth_user.id),
+        UserActivityAction::RoleChanged,
+        json!({
+            "from": previous_role,
+            "to": user.role,
+            "reason": reason,
+            "expires_at": user.role_expires_at,
+        }),
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
         user,
@@ -441,7 +959,7 @@ pub async fn update_user_role(
     path = "/users/{id}/reset-password",
     tag = "Users",
     summary = "Reset user password",
-    description = "Force password reset for a user (Moderator/Admin)",
+    description = "Force password reset for a user (Moderator/Admin, or a custom role with `users:write`)",
     params(
         ("id" = Uuid, Path, description = "User ID")
     ),
@@ -450,7 +968,7 @@ pub async fn update_user_role(
         (status = 200, description = "Password reset", body = ApiResponse<String>),
         (status = 400, description = "Invalid password", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - Moderator access required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Moderator access or `users:write` required", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse)
     ),
     security(
@@ -463,9 +981,6 @@ pub async fn reset_user_password(
     Path(id): Path<Uuid>,
     Json(request): Json<ResetPasswordRequest>,
 ) -> Result<Json<ApiResponse<String>>, Error> {
-    // Require moderator or higher role
-    rbac_services::require_moderator_or_higher(&auth_user)?;
-
     let mut conn = app_state
         .database
         .pool
@@ -473,7 +988,23 @@ pub async fn reset_user_password(
         .await
         .map_err(Error::from_sqlx)?;
 
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
     user_services::reset_user_password(conn.as_mut(), id, request).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        id,
+    )
+    .await;
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        Some(auth_user.id),
+        UserActivityAction::PasswordReset,
+        json!({}),
+    )
+    .await;
 
     Ok(Json(ApiResponse::success_with_message(
         "Password reset successfully".to_string(),
@@ -482,20 +1013,62 @@ pub async fn reset_user_password(
     )))
 }
 
+/// Update a user's staff-only metadata (Moderator/Admin)
+#[utoipa::path(
+    patch,
+    path = "/users/{id}/internal-metadata",
+    tag = "Users",
+    summary = "Update internal metadata",
+    description = "Merge support annotations such as VIP or fraud flags into a user's internal metadata; keys set to null are removed. The metadata is only ever returned to moderators and admins (Moderator/Admin, or a custom role with `users:write`)",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = UpdateInternalMetadataRequest,
+    responses(
+        (status = 200, description = "Metadata updated", body = ApiResponse<UserProfile>),
+        (status = 400, description = "Not an object or too large", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Moderator access or `users:write` required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_internal_metadata(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateInternalMetadataRequest>,
+) -> Result<Json<ApiResponse<UserProfile>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    // Not added to the activity trail, which the user can read
+    let user = user_services::update_internal_metadata(conn.as_mut(), id, request).await?;
+    Ok(Json(ApiResponse::success(user)))
+}
+
 /// Delete user account (Admin only)
 #[utoipa::path(
     delete,
     path = "/users/{id}",
     tag = "Users",
     summary = "Delete user account",
-    description = "Delete a user account (Admin only)",
+    description = "Delete a user account (Admin only). Its sessions and API keys are revoked and its queued tasks cancelled in the same transaction, and its open work can be handed to `reassign_to`",
     params(
         ("id" = Uuid, Path, description = "User ID")
     ),
     request_body = DeleteUserRequest,
     responses(
-        (status = 200, description = "User deleted", body = ApiResponse<String>),
-        (status = 400, description = "Cannot delete own account via this endpoint", body = ErrorResponse),
+        (status = 200, description = "User deleted", body = ApiResponse<DeactivationSummary>),
+        (status = 400, description = "Cannot delete own account via this endpoint, or invalid `reassign_to`", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse)
@@ -507,9 +1080,10 @@ pub async fn reset_user_password(
 pub async fn delete_user(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
+    actor: Actor,
     Path(id): Path<Uuid>,
     Json(request): Json<DeleteUserRequest>,
-) -> Result<Json<ApiResponse<String>>, Error> {
+) -> Result<Json<ApiResponse<DeactivationSummary>>, Error> {
     // Require admin role
     rbac_services::require_admin(&auth_user)?;
 
@@ -528,36 +1102,131 @@ pub async fn delete_user(
         .await
         .map_err(Error::from_sqlx)?;
 
-    user_services::delete_user_admin(conn.as_mut(), id, request).await?;
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let hard_delete = request.hard_delete.unwrap_or(false);
+    let export_ids = if hard_delete {
+        export::find_export_ids(conn.as_mut(), id).await?
+    } else {
+        Vec::new()
+    };
+    let before = user_services::find_user_by_id(conn.as_mut(), id)
+        .await?
+        .map(|user| json!({ "username": user.username, "email": user.email, "role": user.role }));
+    let actor = actor.with_reason(request.reason.clone());
+    let summary = user_services::delete_user_admin(conn.as_mut(), id, request).await?;
+    auth_services::forget_cached_sessions(
+        &app_state.cache,
+        app_state.config.session_cache_ttl(),
+        id,
+    )
+    .await;
+    audit::record(
+        conn.as_mut(),
+        &actor,
+        if hard_delete {
+            "user_purged"
+        } else {
+            "user_deleted"
+        },
+        &AuditResource::new(AuditResourceType::User, id),
+        before,
+        None,
+    )
+    .await;
+
+    // Soft-deleted accounts keep their files for recovery, hard-deleted ones
+    // lose their activity trail along with the row
+    if !hard_delete {
+        activity::record_activity(
+            conn.as_mut(),
+            id,
+            Some(auth_user.id),
+            UserActivityAction::AccountDeleted,
+            json!({ "deactivation": summary }),
+        )
+        .await;
+    } else {
+        let keys = std::iter::once(avatar::avatar_key(id))
+            .chain(export_ids.into_iter().map(export::export_key));
+        for key in keys {
+            if let Err(e) = app_state.storage.delete(&key).await {
+                tracing::warn!("Failed to delete file {key} of user {id}: {e}");
+            }
+        }
+    }
 
     Ok(Json(ApiResponse::success_with_message(
+        summary,
         "User account deleted successfully".to_string(),
-        "User account has been deactivated. Data retained for 30 days for recovery.".to_string(),
     )))
 }
 
-/// Get user statistics (Admin only)
+/// List custom profile fields
 #[utoipa::path(
     get,
-    path = "/admin/users/stats",
+    path = "/users/profile-fields",
+    tag = "Users",
+    summary = "List profile fields",
+    description = "List the custom profile fields users can fill in; moderators and admins also see staff fields",
+    responses(
+        (status = 200, description = "Profile fields", body = ApiResponse<Vec<ProfileField>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_profile_fields(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<ProfileField>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let staff = is_staff(&auth_user);
+    let fields = user_services::find_profile_fields(conn.as_mut())
+        .await?
+        .into_iter()
+        .filter(|field| field.visible_to(staff))
+        .collect();
+
+    Ok(Json(ApiResponse::success(fields)))
+}
+
+/// Create or update a custom profile field (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/users/profile-fields/{name}",
     tag = "Admin",
-    summary = "Get user statistics",
-    description = "Get comprehensive user statistics (Admin only)",
+    summary = "Save profile field",
+    description = "Create or redefine a custom profile field (admins of the default tenant only)",
+    params(
+        ("name" = String, Path, description = "Field name, e.g. job_title")
+    ),
+    request_body = UpsertProfileFieldRequest,
     responses(
-        (status = 200, description = "User statistics", body = ApiResponse<UserStats>),
+        (status = 200, description = "Profile field saved", body = ApiResponse<ProfileField>),
+        (status = 400, description = "Validation error", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse)
     ),
     security(
         ("bearer_auth" = [])
     )
 )]
-pub async fn get_user_stats(
+pub async fn upsert_profile_field(
     State(app_state): State<AppState>,
     Extension(auth_user): Extension<AuthUser>,
-) -> Result<Json<ApiResponse<UserStats>>, Error> {
-    // Require admin role
+    Path(name): Path<String>,
+    Json(request): Json<UpsertProfileFieldRequest>,
+) -> Result<Json<ApiResponse<ProfileField>>, Error> {
     rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Profile fields are managed")?;
 
     let mut conn = app_state
         .database
@@ -566,26 +1235,652 @@ pub async fn get_user_stats(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let stats = user_services::get_user_stats(conn.as_mut()).await?;
+    let field = user_services::upsert_profile_field(conn.as_mut(), &name, request).await?;
 
-    Ok(Json(ApiResponse::success(stats)))
+    Ok(Json(ApiResponse::success(field)))
 }
 
-/// Protected user routes (authentication required)
+/// Delete a custom profile field (Admin only)
+#[utoipa::path(
+    delete,
+    path = "/admin/users/profile-fields/{name}",
+    tag = "Admin",
+    summary = "Delete profile field",
+    description = "Delete a custom profile field; stored values are hidden but kept (admins of the default tenant only)",
+    params(
+        ("name" = String, Path, description = "Field name")
+    ),
+    responses(
+        (status = 200, description = "Profile field deleted", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin of the default tenant required", body = ErrorResponse),
+        (status = 404, description = "Profile field not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_profile_field(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    tenant_services::require_default_tenant(&auth_user, "Profile fields are managed")?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    user_services::delete_profile_field(conn.as_mut(), &name).await?;
+
+    Ok(Json(ApiResponse::success(
+        "Profile field deleted".to_string(),
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUserActivityQuery {
+    pub action: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List own account activity
+#[utoipa::path(
+    get,
+    path = "/users/me/activity",
+    tag = "Users",
+    summary = "List own activity",
+    description = "Significant actions on your account, such as profile and password changes, role changes and task submissions, newest first",
+    params(
+        ("action" = Option<String>, Query, description = "Only return this action, e.g. password_changed"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of entries to return (default 50, max 100)"),
+        ("offset" = Option<i64>, Query, description = "Number of entries to skip")
+    ),
+    responses(
+        (status = 200, description = "Activity entries", body = ApiResponse<Vec<UserActivity>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_own_activity(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ListUserActivityQuery>,
+) -> Result<Json<ApiResponse<Vec<UserActivity>>>, Error> {
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let entries = activity::find_activity(
+        conn.as_mut(),
+        auth_user.id,
+        params.action.as_deref(),
+        params.limit,
+        params.offset,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+/// List a user's account activity (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/{id}/activity",
+    tag = "Admin",
+    summary = "List user activity",
+    description = "Significant actions on a user's account, including those taken by staff, newest first (Admin only)",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        ("action" = Option<String>, Query, description = "Only return this action, e.g. role_changed"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of entries to return (default 50, max 100)"),
+        ("offset" = Option<i64>, Query, description = "Number of entries to skip")
+    ),
+    responses(
+        (status = 200, description = "Activity entries", body = ApiResponse<Vec<UserActivity>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_user_activity(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListUserActivityQuery>,
+) -> Result<Json<ApiResponse<Vec<UserActivity>>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    // Deactivated and deleted accounts keep their trail until they are purged
+    if !user_services::user_exists(conn.as_mut(), id).await? {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    let entries = activity::find_activity(
+        conn.as_mut(),
+        id,
+        params.action.as_deref(),
+        params.limit,
+        params.offset,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportUsersQuery {
+    #[serde(default)]
+    pub password_policy: ImportPasswordPolicy,
+}
+
+/// Import users from CSV (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/users/import",
+    tag = "Admin",
+    summary = "Import users",
+    description = "Upload a CSV as a multipart `file` field (optionally gzip-compressed) with a header row naming `username`, `email` and optionally `role` and `password` columns. The rows are processed by a background task; poll the import for its per-row report (Admin only)",
+    params(
+        ("password_policy" = Option<ImportPasswordPolicy>, Query, description = "provided (default): each row sets its password; locked: accounts can't log in until their password is reset")
+    ),
+    request_body(content = UserImportUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Import started", body = ApiResponse<UserImport>),
+        (status = 400, description = "Missing or malformed file", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn import_users(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ImportUsersQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<ApiResponse<UserImport>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let multipart_error = |e: axum::extract::multipart::MultipartError| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            Error::PayloadTooLarge {
+                limit_bytes: MAX_IMPORT_BODY_BYTES,
+            }
+        } else {
+            Error::validation("file", &e.body_text())
+        }
+    };
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() == Some("file") {
+            upload = Some(field.bytes().await.map_err(multipart_error)?);
+            break;
+        }
+    }
+    let Some(bytes) = upload else {
+        return Err(Error::validation("file", "Missing 'file' field"));
+    };
+    let csv = String::from_utf8(bytes.to_vec())
+        .map_err(|_| Error::validation("file", "File must be UTF-8 encoded CSV"))?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let user_import = import::create_import(
+        conn.as_mut(),
+        &app_state.database,
+        auth_user.tenant_id,
+        auth_user.id,
+        csv,
+        params.password_policy,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(user_import)))
+}
+
+/// Get a user import and its report (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/imports/{id}",
+    tag = "Admin",
+    summary = "Get user import",
+    description = "Status and per-row report of a CSV import (Admin only)",
+    params(
+        ("id" = Uuid, Path, description = "Import ID")
+    ),
+    responses(
+        (status = 200, description = "User import", body = ApiResponse<UserImport>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 404, description = "User import not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user_import(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<UserImport>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let user_import = import::find_import(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    Ok(Json(ApiResponse::success(user_import)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUserPurgesQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List purged accounts (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/purges",
+    tag = "Admin",
+    summary = "List user purges",
+    description = "Audit trail of deleted accounts purged after the retention period, newest first (Admin only)",
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum number of records to return (default 50, max 100)"),
+        ("offset" = Option<i64>, Query, description = "Number of records to skip")
+    ),
+    responses(
+        (status = 200, description = "Purge records", body = ApiResponse<Vec<UserPurge>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_user_purges(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ListUserPurgesQuery>,
+) -> Result<Json<ApiResponse<Vec<UserPurge>>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let purges = purge::find_purges(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        params.limit,
+        params.offset,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(purges)))
+}
+
+/// Mark a user's email as verified (Admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/users/{id}/verify-email",
+    tag = "Admin",
+    summary = "Force-verify email",
+    description = "Mark a user's current email address as verified without a verification link (Admin only)",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Email verified", body = ApiResponse<String>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn force_verify_email(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    if !verification::force_verify_email(conn.as_mut(), id).await? {
+        return Ok(Json(ApiResponse::success(
+            "Email was already verified".to_string(),
+        )));
+    }
+    activity::record_activity(
+        conn.as_mut(),
+        id,
+        Some(auth_user.id),
+        UserActivityAction::EmailVerified,
+        json!({ "method": "admin" }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse::success("Email verified".to_string())))
+}
+
+/// Export users as CSV (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/export",
+    tag = "Admin",
+    summary = "Export users",
+    description = "Stream every user matching the list filters as CSV. Unlike the list endpoint, results are unlimited unless `limit` is set (Admin only)",
+    params(
+        ("search" = Option<String>, Query, description = "Case-insensitive match anywhere in the username or email"),
+        ("role" = Option<UserRole>, Query, description = "Only users with this role"),
+        ("is_active" = Option<bool>, Query, description = "Only active (true) or deactivated (false) users; both when omitted"),
+        ("email_verified" = Option<bool>, Query, description = "Only users with or without a verified email"),
+        ("created_after" = Option<DateTime<Utc>>, Query, description = "Only users created at or after this time"),
+        ("created_before" = Option<DateTime<Utc>>, Query, description = "Only users created at or before this time"),
+        ("sort" = Option<String>, Query, description = "created_at (default), username, email, last_login_at or last_seen_at"),
+        ("order" = Option<SortOrder>, Query, description = "Defaults to desc for dates and asc for username and email"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of users to export (default unlimited)"),
+        ("offset" = Option<i64>, Query, description = "Number of users to skip")
+    ),
+    responses(
+        (status = 200, description = "User export", content_type = "text/csv"),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn export_users(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<ListUsersQuery>,
+) -> Result<Response, Error> {
+    rbac_services::require_admin(&auth_user)?;
+    let filter = params.into_filter(auth_user.tenant_id)?;
+
+    let filename = format!("users-{}.csv", Utc::now().format("%Y%m%dT%H%M%SZ"));
+    let body = list_export::export_users(app_state.database.pool.clone(), filter);
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(body)
+        .map_err(|e| Error::internal(&format!("Failed to build response: {e}")))
+}
+
+/// Get a user's limits and their usage (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/{id}/quotas",
+    tag = "Admin",
+    summary = "Get user quotas",
+    description = "Get the limits on a user's tasks per day, events per day and API keys, with today's usage (Admin only)",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User quotas", body = ApiResponse<UserQuotas>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user_quotas(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<UserQuotas>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let quotas = quotas::get_user_quotas(conn.as_mut(), &app_state.config, id).await?;
+
+    Ok(Json(ApiResponse::success(quotas)))
+}
+
+/// Replace a user's own limits (Admin only)
+#[utoipa::path(
+    put,
+    path = "/admin/users/{id}/quotas",
+    tag = "Admin",
+    summary = "Update user quotas",
+    description = "Replace a user's own limits; a limit left out or null uses the default of the user's role (Admin only)",
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    request_body = UpdateUserQuotasRequest,
+    responses(
+        (status = 200, description = "Quotas updated", body = ApiResponse<UserQuotas>),
+        (status = 400, description = "Invalid limit", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_user_quotas(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateUserQuotasRequest>,
+) -> Result<Json<ApiResponse<UserQuotas>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    tenant_services::require_user_in_tenant(conn.as_mut(), id, auth_user.tenant_id).await?;
+
+    let quotas =
+        quotas::set_user_quotas(conn.as_mut(), &app_state.config, id, request, auth_user.id)
+            .await?;
+
+    Ok(Json(ApiResponse::success(quotas)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnlineUsersQuery {
+    pub limit: Option<i64>,
+}
+
+/// Summarize users online now (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/online",
+    tag = "Admin",
+    summary = "List online users",
+    description = "Active users seen within the online window, with counts by role, most recently seen first (Admin only)",
+    params(
+        ("limit" = Option<i64>, Query, description = "Maximum number of users to list (default 50, max 200); counts cover everyone")
+    ),
+    responses(
+        (status = 200, description = "Online users", body = ApiResponse<OnlineUsersSummary>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_online_users(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Query(params): Query<OnlineUsersQuery>,
+) -> Result<Json<ApiResponse<OnlineUsersSummary>>, Error> {
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let window_minutes = app_state.config.auth.online_window_minutes;
+    let since = Utc::now() - chrono::Duration::minutes(window_minutes as i64);
+    // Include this server's sightings that haven't been flushed yet
+    let pending = app_state.presence.pending_since(since);
+    let summary = presence::find_online_users(
+        conn.as_mut(),
+        auth_user.tenant_id,
+        since,
+        &pending,
+        window_minutes,
+        params.limit,
+    )
+    .await?;
+
+    Ok(Json(ApiResponse::success(summary)))
+}
+
+/// Get user statistics (Admin only)
+#[utoipa::path(
+    get,
+    path = "/admin/users/stats",
+    tag = "Admin",
+    summary = "Get user statistics",
+    description = "Get comprehensive user statistics (Admin only)",
+    responses(
+        (status = 200, description = "User statistics", body = ApiResponse<UserStats>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - Admin access required", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user_stats(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<UserStats>>, Error> {
+    // Require admin role
+    rbac_services::require_admin(&auth_user)?;
+
+    let mut conn = app_state
+        .database
+        .pool
+        .acquire()
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    let stats = user_services::get_user_stats(conn.as_mut(), auth_user.tenant_id).await?;
+
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+/// Protected user routes (authentication required)
 pub fn users_routes() -> Router<AppState> {
     Router::new()
         .route("/{id}", get(get_user_by_id))
+        .route("/profile-fields", get(list_profile_fields))
         .route("/me/profile", get(get_profile).put(update_own_profile))
         .route("/me/password", put(change_own_password))
+        .route("/me/export", post(request_own_data_export))
+        .route("/me/exports", get(list_own_data_exports))
+        .route("/me/exports/{id}", get(get_own_data_export))
+        .route("/me/activity", get(list_own_activity))
         .route("/me", delete(delete_own_account))
+        .merge(users_staff_routes().into_router())
 }
 
-/// Moderator user routes (moderator role required)
-pub fn users_moderator_routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(list_users))
-        .route("/{id}/status", put(update_user_status))
-        .route("/{id}/reset-password", post(reset_user_password))
+/// Protected user upload routes (authentication required), which take larger
+/// bodies than the other user routes
+pub fn users_upload_routes() -> Router<AppState> {
+    Router::new().route("/me/avatar", put(upload_own_avatar))
+}
+
+/// User management for moderators, and users whose custom roles grant the permission
+pub fn users_staff_routes() -> PermissionRoutes<AppState> {
+    let read = Requirement::Staff(Resource::Users, Permission::Read);
+    let write = Requirement::Staff(Resource::Users, Permission::Write);
+    PermissionRoutes::new()
+        .get("/", list_users, read)
+        .put("/{id}/status", update_user_status, write)
+        .post("/{id}/reset-password", reset_user_password, write)
+        .patch("/{id}/internal-metadata", update_internal_metadata, write)
+}
+
+/// Public account deletion routes (the emailed token authorizes the request)
+pub fn account_deletion_public_routes() -> Router<AppState> {
+    Router::new().route("/cancel", post(cancel_account_deletion))
+}
+
+/// Public data export routes (the download token authorizes the request)
+pub fn data_exports_public_routes() -> Router<AppState> {
+    Router::new().route("/{id}/download", get(download_data_export))
 }
 
 /// Admin user routes (admin role required)
@@ -599,5 +1894,29 @@ pub fn users_admin_routes() -> Router<AppState> {
 
 /// Admin user stats routes (for /admin/users path)
 pub fn admin_users_routes() -> Router<AppState> {
-    Router::new().route("/stats", get(get_user_stats))
+    Router::new()
+        .route("/stats", get(get_user_stats))
+        .route("/online", get(get_online_users))
+        .route("/export", get(export_users))
+        .route("/purges", get(list_user_purges))
+        .route("/{id}/activity", get(list_user_activity))
+        .route("/{id}/verify-email", post(force_verify_email))
+        .route("/{id}/quotas", get(get_user_quotas).put(update_user_quotas))
+        .route("/imports/{id}", get(get_user_import))
+        .route(
+            "/profile-fields/{name}",
+            put(upsert_profile_field).delete(delete_profile_field),
+        )
+}
+
+/// Largest decompressed user import body, leaving room for the multipart
+/// framing around the file
+const MAX_IMPORT_BODY_BYTES: usize = MAX_IMPORT_BYTES + 64 * 1024;
+
+/// CSV user import for the `/admin/users` path, which takes larger and
+/// optionally gzip-compressed uploads
+pub fn user_import_routes() -> Router<AppState> {
+    let routes = Router::new().route("/import", post(import_users));
+    // Limits the decompressed body, on top of the limit of upload routes
+    limit_body(routes, MAX_IMPORT_BODY_BYTES).layer(RequestDecompressionLayer::new())
 }
@@ -0,0 +1,122 @@
+use crate::{Error, Result};
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, imageops::FilterType};
+use std::io::Cursor;
+use uuid::Uuid;
+
+/// Largest accepted upload
+pub const MAX_AVATAR_BYTES: usize = 2 * 1024 * 1024;
+/// Stored avatars are square PNGs of this many pixels per side
+pub const AVATAR_SIZE: u32 = 256;
+/// Larger images are refused before decoding
+pub const MAX_AVATAR_DIMENSION: u32 = 4096;
+
+const ALLOWED_TYPES: [&str; 3] = ["image/png", "image/jpeg", "image/webp"];
+
+/// Storage key of a user's avatar
+pub fn avatar_key(user_id: Uuid) -> String {
+    format!("avatars/{user_id}.png")
+}
+
+/// Decode an uploaded image and re-encode it as a square PNG
+///
+/// Re-encoding drops metadata and anything hidden after the image data;
+/// the declared content type must match what the bytes decode as.
+pub fn process_avatar(content_type: &str, bytes: &[u8]) -> Result<Vec<u8>> {
+    if bytes.len() > MAX_AVATAR_BYTES {
+        return Err(Error::validation(
+            "avatar",
+            &format!(
+                "Avatar must be at most {} MB",
+                MAX_AVATAR_BYTES / 1024 / 1024
+            ),
+        ));
+    }
+
+    let format = ALLOWED_TYPES
+        .contains(&content_type)
+        .then(|| ImageFormat::from_mime_type(content_type))
+        .flatten()
+        .ok_or_else(|| {
+            Error::UnsupportedMediaType(format!(
+                "Avatar must be one of {} (got '{content_type}')",
+                ALLOWED_TYPES.join(", ")
+            ))
+        })?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_AVATAR_DIMENSION);
+    limits.max_image_height = Some(MAX_AVATAR_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| {
+        Error::validation(
+            "avatar",
+            &format!("Avatar is not a valid {content_type}: {e}"),
+        )
+    })?;
+
+    let square = image.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3);
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(square.to_rgba8())
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| Error::internal(&format!("Failed to encode avatar: {e}")))?;
+
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut out, format)
+            .unwrap();
+        out.into_inner()
+    }
+
+    #[test]
+    fn test_process_avatar_reencodes_to_square_png() {
+        let png = process_avatar("image/jpeg", &encode(640, 480, ImageFormat::Jpeg)).unwrap();
+        let image = image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap();
+        assert_eq!((image.width(), image.height()), (AVATAR_SIZE, AVATAR_SIZE));
+
+        assert!(process_avatar("image/png", &encode(32, 32, ImageFormat::Png)).is_ok());
+    }
+
+    #[test]
+    fn test_process_avatar_rejects_bad_input() {
+        let png = encode(32, 32, ImageFormat::Png);
+
+        assert!(matches!(
+            process_avatar("image/gif", &png),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            process_avatar("text/html", b"<script>"),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+
+        // Declared type must match the data
+        assert!(matches!(
+            process_avatar("image/jpeg", &png),
+            Err(Error::ValidationError { .. })
+        ));
+        assert!(matches!(
+            process_avatar("image/png", b"not an image"),
+            Err(Error::ValidationError { .. })
+        ));
+
+        let huge = encode(MAX_AVATAR_DIMENSION + 1, 1, ImageFormat::Png);
+        assert!(process_avatar("image/png", &huge).is_err());
+
+        let oversized = vec![0u8; MAX_AVATAR_BYTES + 1];
+        assert!(matches!(
+            process_avatar("image/png", &oversized),
+            Err(Error::ValidationError { .. })
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::tenants::services as tenant_services;
+use crate::users::models::DeactivationSummary;
+use crate::{DbConn, Error, Result};
+use uuid::Uuid;
+
+/// Cut off a deactivated or deleted account and hand over its open work
+///
+/// Revokes the account's sessions and API keys and cancels the tasks it
+/// queued that haven't started. With `reassign_to`, open incidents and
+/// unfinished postmortem action items assigned to the account move to that
+/// user of the same tenant, who also becomes an owner of the organizations
+/// the account owns. Run it in the same transaction as the status change.
+pub async fn revoke_access(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    reassign_to: Option<Uuid>,
+) -> Result<DeactivationSummary> {
+    let mut summary = DeactivationSummary {
+        sessions_revoked: sqlx::query!(
+            "UPDATE sessions SET is_active = false WHERE user_id = $1 AND is_active = true",
+            user_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected(),
+        api_keys_revoked: sqlx::query!(
+            "UPDATE api_keys SET is_active = false WHERE created_by = $1 AND is_active = true",
+            user_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected(),
+        tasks_cancelled: sqlx::query!(
+            r#"
+            UPDATE tasks
+            SET status = 'cancelled', updated_at = NOW(), completed_at = NOW()
+            WHERE created_by = $1 AND status IN ('pending', 'retrying')
+            "#,
+            user_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected(),
+        ..Default::default()
+    };
+
+    let Some(successor) = reassign_to else {
+        return Ok(summary);
+    };
+    if successor == user_id {
+        return Err(Error::validation(
+            "reassign_to",
+            "Work can't be reassigned to the account being deactivated",
+        ));
+    }
+    let tenant_id = tenant_services::user_tenant_id(conn, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    tenant_services::require_user_in_tenant(conn, successor, tenant_id).await?;
+    let successor_active = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM users WHERE id = $1 AND is_active = true AND deleted_at IS NULL
+        ) AS "exists!"
+        "#,
+        successor
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !successor_active {
+        return Err(Error::validation(
+            "reassign_to",
+            "Work can only be reassigned to an active user",
+        ));
+    }
+
+    summary.reassigned_to = Some(successor);
+    summary.incidents_reassigned = sqlx::query!(
+        r#"
+        UPDATE incidents SET assigned_to = $2, updated_at = NOW()
+        WHERE assigned_to = $1 AND status NOT IN ('resolved', 'closed')
+        "#,
+        user_id,
+        successor
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    summary.action_items_reassigned = sqlx::query!(
+        r#"
+        UPDATE postmortem_action_items SET assigned_to = $2, updated_at = NOW()
+        WHERE assigned_to = $1 AND completed_at IS NULL
+        "#,
+        user_id,
+        successor
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    summary.orgs_reassigned = sqlx::query!(
+        r#"
+        INSERT INTO org_members (org_id, user_id, role)
+        SELECT org_id, $2, 'owner' FROM org_members WHERE user_id = $1 AND role = 'owner'
+        ON CONFLICT (org_id, user_id) DO UPDATE
+        SET role = 'owner', updated_at = NOW()
+        WHERE org_members.role <> 'owner'
+        "#,
+        user_id,
+        successor
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+
+    Ok(summary)
+}
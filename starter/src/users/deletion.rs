@@ -0,0 +1,421 @@
+//! Self-serve account deletion
+//!
+//! Deleting your own account signs you out everywhere and schedules the
+//! erasure for after a grace period, during which the link in the
+//! confirmation email restores the account. A task queued for the end of the
+//! grace period then purges the account and emails a last confirmation.
+
+use crate::core::config::AppConfig;
+use crate::tasks::handlers::TaskHandler;
+use crate::tasks::processor::{ProcessorConfig, TaskProcessor};
+use crate::tasks::services::{EmailMessage, EmailSender};
+use crate::tasks::types::{CreateTaskRequest, TaskContext, TaskError, TaskLogLevel, TaskResult};
+use crate::users::models::{AccountDeletion, AccountDeletionStatus, DeleteAccountRequest};
+use crate::users::{deactivation, purge, services as user_services};
+use crate::{Database, DbConn, Error, Result, register_task_handler, require_field};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// Task type that erases accounts once their grace period ends
+pub const ACCOUNT_ERASURE_TASK_TYPE: &str = "user_account_erasure";
+
+/// Random token for the cancel link; only its SHA-256 hash is stored
+fn generate_cancel_token() -> String {
+    use base64::Engine;
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    let bytes: [u8; 32] = rng.random();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+struct AccountDeletionRow {
+    id: Uuid,
+    status: String,
+    requested_at: DateTime<Utc>,
+    erase_after: DateTime<Utc>,
+    cancelled_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountDeletionRow> for AccountDeletion {
+    fn from(row: AccountDeletionRow) -> Self {
+        Self {
+            id: row.id,
+            status: AccountDeletionStatus::from(row.status),
+            requested_at: row.requested_at,
+            erase_after: row.erase_after,
+            cancelled_at: row.cancelled_at,
+            completed_at: row.completed_at,
+        }
+    }
+}
+
+/// Confirm the password, then schedule the account for erasure
+///
+/// The account is deactivated straight away and loses its sessions, API keys
+/// and queued tasks. If the erasure task can't be queued or the confirmation
+/// email can't be sent, the account is restored and the request fails, since
+/// the email carries the only way to cancel.
+pub async fn request_deletion(
+    conn: &mut DbConn,
+    database: &Database,
+    sender: &dyn EmailSender,
+    config: &AppConfig,
+    user_id: Uuid,
+    req: DeleteAccountRequest,
+) -> Result<AccountDeletion> {
+    req.validate()?;
+
+    let user = user_services::find_user_by_id(conn, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    if !user_services::verify_password(&req.password, &user.password_hash)? {
+        return Err(Error::validation("password", "Invalid password"));
+    }
+
+    let token = generate_cancel_token();
+    let erase_after = Utc::now() + config.account_deletion_grace();
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let deletion = sqlx::query_as!(
+        AccountDeletionRow,
+        r#"
+        INSERT INTO account_deletions (user_id, email, cancel_token_hash, erase_after)
+        VALUES ($1, $2, sha256(convert_to($3, 'UTF8')), $4)
+        RETURNING id, status, requested_at, erase_after, cancelled_at, completed_at
+        "#,
+        user_id,
+        user.email,
+        token,
+        erase_after
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("The account is already scheduled for deletion")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+    sqlx::query!(
+        "UPDATE users SET is_active = false, deleted_at = NOW(), updated_at = NOW() WHERE id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    deactivation::revoke_access(&mut tx, user_id, None).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    // Queued once the deletion is committed, so a worker can't pick the task up
+    // before it can see the deletion
+    let scheduled = async {
+        let task_id = queue_erasure(conn, database, deletion.id, erase_after).await?;
+        sqlx::query!(
+            "UPDATE account_deletions SET task_id = $2 WHERE id = $1",
+            deletion.id,
+            task_id
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+        send_scheduled_email(sender, config, &user.email, &token, erase_after).await
+    }
+    .await;
+    if let Err(e) = scheduled {
+        restore_account(conn, deletion.id).await?;
+        return Err(e);
+    }
+
+    Ok(deletion.into())
+}
+
+async fn queue_erasure(
+    conn: &mut DbConn,
+    database: &Database,
+    deletion_id: Uuid,
+    erase_after: DateTime<Utc>,
+) -> Result<Uuid> {
+    // Workers register their task types when they start; add this one so
+    // erasures can be queued before then
+    sqlx::query!(
+        r#"
+        INSERT INTO task_types (task_type, description)
+        VALUES ($1, 'Erase accounts at the end of their deletion grace period')
+        ON CONFLICT (task_type) DO NOTHING
+        "#,
+        ACCOUNT_ERASURE_TASK_TYPE
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // Not created by the user, so revoking or purging their tasks leaves it alone
+    let request = CreateTaskRequest::new(
+        ACCOUNT_ERASURE_TASK_TYPE,
+        serde_json::json!({ "deletion_id": deletion_id }),
+    )
+    .with_scheduled_at(erase_after);
+    let processor = TaskProcessor::new(database.clone(), ProcessorConfig::default());
+    processor
+        .create_task(request)
+        .await
+        .map(|task| task.id)
+        .map_err(|e| Error::Internal(format!("Failed to create account erasure task: {e}")))
+}
+
+/// Undo a deletion request that couldn't be completed
+async fn restore_account(conn: &mut DbConn, deletion_id: Uuid) -> Result<()> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let deletion = sqlx::query!(
+        r#"
+        UPDATE account_deletions
+        SET status = 'cancelled', cancelled_at = NOW(), email = NULL
+        WHERE id = $1
+        RETURNING user_id, task_id
+        "#,
+        deletion_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    sqlx::query!(
+        "UPDATE users SET is_active = true, deleted_at = NULL, updated_at = NOW() WHERE id = $1",
+        deletion.user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    cancel_erasure_task(&mut tx, deletion.task_id).await?;
+    tx.commit().await.map_err(Error::from_sqlx)
+}
+
+async fn cancel_erasure_task(conn: &mut DbConn, task_id: Option<Uuid>) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE tasks
+        SET status = 'cancelled', updated_at = NOW(), completed_at = NOW()
+        WHERE id = $1 AND status IN ('pending', 'retrying')
+        "#,
+        task_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Restore an account scheduled for deletion, using the token from its email
+///
+/// Works until the account has been erased. The account is active again but
+/// its sessions and API keys stay revoked. Returns the account and the
+/// cancelled deletion.
+pub async fn cancel_deletion(
+    conn: &mut DbConn,
+    sender: &dyn EmailSender,
+    token: &str,
+) -> Result<(Uuid, AccountDeletion)> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let scheduled = sqlx::query!(
+        r#"
+        SELECT id, user_id AS "user_id?", email, task_id
+        FROM account_deletions
+        WHERE cancel_token_hash = sha256(convert_to($1, 'UTF8')) AND status = 'scheduled'
+        FOR UPDATE
+        "#,
+        token
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let Some(scheduled) = scheduled else {
+        return Err(Error::NotFound(
+            "Account deletion not found or no longer scheduled".to_string(),
+        ));
+    };
+
+    // The retention purge may have erased the account first
+    let restored = sqlx::query!(
+        r#"
+        UPDATE users SET is_active = true, deleted_at = NULL, updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+        scheduled.user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .rows_affected();
+    let Some(user_id) = scheduled.user_id.filter(|_| restored > 0) else {
+        return Err(Error::conflict("The account has already been erased"));
+    };
+
+    let deletion = sqlx::query_as!(
+        AccountDeletionRow,
+        r#"
+        UPDATE account_deletions
+        SET status = 'cancelled', cancelled_at = NOW(), email = NULL
+        WHERE id = $1
+        RETURNING id, status, requested_at, erase_after, cancelled_at, completed_at
+        "#,
+        scheduled.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    cancel_erasure_task(&mut tx, scheduled.task_id).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    if let Some(email) = scheduled.email {
+        let message = EmailMessage {
+            to: email.clone(),
+            subject: "Your account deletion was cancelled".to_string(),
+            body: "Your account will not be deleted. Sign in again to keep using it; \
+                   API keys revoked with the deletion request need to be created again."
+                .to_string(),
+        };
+        if let Err(e) = sender.send(&message).await {
+            tracing::warn!("Failed to send deletion cancelled email to {email}: {e}");
+        }
+    }
+
+    Ok((user_id, deletion.into()))
+}
+
+async fn send_scheduled_email(
+    sender: &dyn EmailSender,
+    config: &AppConfig,
+    email: &str,
+    token: &str,
+    erase_after: DateTime<Utc>,
+) -> Result<()> {
+    let link = format!(
+        "{}/cancel-account-deletion?token={token}",
+        config.server.public_url.trim_end_matches('/')
+    );
+    let message = EmailMessage {
+        to: email.to_string(),
+        subject: "Your account is scheduled for deletion".to_string(),
+        body: format!(
+            "We received a request to delete your account. It and its data will be \
+             erased after {erase_after}.\n\n\
+             To keep your account, cancel the deletion before then: {link}\n\n\
+             If you didn't ask for this, cancel it and change your password.",
+            erase_after = erase_after.to_rfc3339(),
+        ),
+    };
+    sender.send(&message).await.map_err(|e| {
+        tracing::error!("Failed to send deletion scheduled email to {email}: {e}");
+        Error::Internal("Failed to send account deletion email".to_string())
+    })
+}
+
+/// Erases the account of a deletion whose grace period has ended
+pub struct AccountErasureTaskHandler;
+
+register_task_handler!(
+    "user_account_erasure",
+    "Erase accounts at the end of their deletion grace period",
+    AccountErasureTaskHandler
+);
+
+#[async_trait]
+impl TaskHandler for AccountErasureTaskHandler {
+    async fn handle(&self, context: TaskContext) -> std::result::Result<TaskResult, TaskError> {
+        let deletion_id = require_field!(context.payload, "deletion_id")?;
+        let deletion_id = Uuid::parse_str(deletion_id)
+            .map_err(|_| TaskError::invalid_field_type("deletion_id", "UUID"))?;
+        let (Some(pool), Some(storage)) = (context.pool(), context.services().storage()) else {
+            return Err(TaskError::Execution(
+                "Account erasure needs a database pool and file storage".to_string(),
+            ));
+        };
+        let mode = context
+            .services()
+            .config()
+            .map(|config| config.auth.deleted_user_purge_mode)
+            .unwrap_or_default();
+        let mut conn = pool.acquire().await?;
+
+        // Claimed up front so a cancellation can't restore an account mid-erasure
+        let claimed = sqlx::query!(
+            r#"
+            WITH scheduled AS (
+                SELECT id, email FROM account_deletions
+                WHERE id = $1 AND status = 'scheduled'
+                FOR UPDATE
+            )
+            UPDATE account_deletions d
+            SET status = 'completed', completed_at = NOW(), email = NULL
+            FROM scheduled
+            WHERE d.id = scheduled.id
+            RETURNING d.user_id AS "user_id?", scheduled.email AS notify, d.requested_at
+            "#,
+            deletion_id
+        )
+        .fetch_optional(conn.as_mut())
+        .await?;
+        let Some(claimed) = claimed else {
+            context
+                .log(
+                    TaskLogLevel::Warn,
+                    format!("Account deletion {deletion_id} is no longer scheduled"),
+                )
+                .await;
+            return Ok(TaskResult::success_empty());
+        };
+
+        // Gone already if an admin removed the account or the retention purge ran first
+        if let Some(user_id) = claimed.user_id
+            && let Err(e) = purge::purge_user(conn.as_mut(), storage, user_id, mode).await
+        {
+            sqlx::query!(
+                r#"
+                UPDATE account_deletions
+                SET status = 'scheduled', completed_at = NULL, email = $2
+                WHERE id = $1
+                "#,
+                deletion_id,
+                claimed.notify
+            )
+            .execute(conn.as_mut())
+            .await?;
+            return Err(TaskError::Execution(format!(
+                "Failed to erase account of deletion {deletion_id}: {e}"
+            )));
+        }
+
+        if let Some(email) = claimed.notify {
+            let message = EmailMessage {
+                to: email,
+                subject: "Your account has been deleted".to_string(),
+                body: format!(
+                    "Your account and its data have been erased, as requested on {}.",
+                    claimed.requested_at.to_rfc3339()
+                ),
+            };
+            if let Err(e) = context.services().email().send(&message).await {
+                context
+                    .log(
+                        TaskLogLevel::Warn,
+                        format!("Failed to send account deleted email: {e}"),
+                    )
+                    .await;
+            }
+        }
+
+        context
+            .log(
+                TaskLogLevel::Info,
+                format!("Account deletion {deletion_id} completed"),
+            )
+            .await;
+        Ok(TaskResult::success(serde_json::json!({
+            "deletion_id": deletion_id,
+        })))
+    }
+}
@@ -0,0 +1,579 @@
+use crate::core::config::AppConfig;
+use crate::rbac::UserRole;
+use crate::realtime::{Notification, services as realtime_services};
+use crate::storage::{FileStorage, PRIVATE_PREFIX};
+use crate::tasks::handlers::TaskHandler;
+use crate::tasks::processor::{ProcessorConfig, TaskProcessor};
+use crate::tasks::retry::RetryStrategy;
+use crate::tasks::services::TaskServices;
+use crate::tasks::types::{CreateTaskRequest, TaskContext, TaskError, TaskLogLevel, TaskResult};
+use crate::users::models::{DataExport, DataExportStatus};
+use crate::users::services as user_services;
+use crate::{Database, DbConn, DbPool, Error, Result, register_task_handler, require_field};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::io::{Cursor, Write};
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Task type that builds export archives
+pub const DATA_EXPORT_TASK_TYPE: &str = "user_data_export";
+
+/// Storage key of an export archive, kept out of the public file routes
+pub fn export_key(export_id: Uuid) -> String {
+    format!("{PRIVATE_PREFIX}exports/{export_id}.zip")
+}
+
+/// Random token that authorizes downloading an archive without logging in
+fn generate_download_token() -> String {
+    use base64::Engine;
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    let bytes: [u8; 32] = rng.random();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+struct DataExportRow {
+    id: Uuid,
+    status: String,
+    task_id: Option<Uuid>,
+    size_bytes: Option<i64>,
+    error: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    download_token: String,
+}
+
+impl From<DataExportRow> for DataExport {
+    fn from(row: DataExportRow) -> Self {
+        let status = DataExportStatus::from(row.status);
+        let download_url = (status == DataExportStatus::Ready).then(|| {
+            format!(
+                "/api/v1/exports/{}/download?token={}",
+                row.id, row.download_token
+            )
+        });
+        Self {
+            id: row.id,
+            status,
+            task_id: row.task_id,
+            size_bytes: row.size_bytes,
+            error: row.error,
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+            download_url,
+        }
+    }
+}
+
+/// Start building an archive of the user's data
+///
+/// Only one export per user is built at a time; a second request while one
+/// is pending is a conflict.
+pub async fn create_export(
+    conn: &mut DbConn,
+    database: &Database,
+    user_id: Uuid,
+) -> Result<DataExport> {
+    // Free the slot of an export whose task ended without finishing it
+    sqlx::query!(
+        r#"
+        UPDATE data_exports e
+        SET status = 'failed', error = 'Export task did not finish', completed_at = NOW()
+        WHERE e.user_id = $1 AND e.status = 'pending' AND e.task_id IS NOT NULL
+          AND NOT EXISTS (
+              SELECT 1 FROM tasks t
+              WHERE t.id = e.task_id AND t.status IN ('pending', 'running', 'retrying')
+          )
+        "#,
+        user_id
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let export_id = sqlx::query_scalar!(
+        "INSERT INTO data_exports (user_id, download_token) VALUES ($1, $2) RETURNING id",
+        user_id,
+        generate_download_token()
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Error::conflict("A data export is already in progress")
+        }
+        _ => Error::from_sqlx(e),
+    })?;
+
+    // Workers register their task types when they start; add this one so exports
+    // can be queued before then
+    sqlx::query!(
+        r#"
+        INSERT INTO task_types (task_type, description)
+        VALUES ($1, 'Build archives of a user''s data')
+        ON CONFLICT (task_type) DO NOTHING
+        "#,
+        DATA_EXPORT_TASK_TYPE
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // Exports are not retried; a failed one is reported and can be requested again
+    let request = CreateTaskRequest::new(
+        DATA_EXPORT_TASK_TYPE,
+        serde_json::json!({ "export_id": export_id }),
+    )
+    .with_created_by(user_id)
+    .with_retry_strategy(RetryStrategy::None);
+    let processor = TaskProcessor::new(database.clone(), ProcessorConfig::default());
+    let task = match processor.create_task(request).await {
+        Ok(task) => task,
+        Err(e) => {
+            sqlx::query!("DELETE FROM data_exports WHERE id = $1", export_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(Error::from_sqlx)?;
+            return Err(Error::Internal(format!(
+                "Failed to create export task: {e}"
+            )));
+        }
+    };
+
+    sqlx::query_as!(
+        DataExportRow,
+        r#"
+        UPDATE data_exports SET task_id = $2
+        WHERE id = $1
+        RETURNING id, status, task_id, size_bytes, error, expires_at,
+                  created_at, completed_at, download_token
+        "#,
+        export_id,
+        task.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map(DataExport::from)
+    .map_err(Error::from_sqlx)
+}
+
+/// The user's exports, newest first
+pub async fn find_exports(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<DataExport>> {
+    let rows = sqlx::query_as!(
+        DataExportRow,
+        r#"
+        SELECT id,
+               CASE WHEN status = 'ready' AND expires_at <= NOW() THEN 'expired'
+                    ELSE status END AS "status!",
+               task_id, size_bytes, error, expires_at, created_at, completed_at, download_token
+        FROM data_exports
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(rows.into_iter().map(DataExport::from).collect())
+}
+
+pub async fn find_export(conn: &mut DbConn, user_id: Uuid, export_id: Uuid) -> Result<DataExport> {
+    sqlx::query_as!(
+        DataExportRow,
+        r#"
+        SELECT id,
+               CASE WHEN status = 'ready' AND expires_at <= NOW() THEN 'expired'
+                    ELSE status END AS "status!",
+               task_id, size_bytes, error, expires_at, created_at, completed_at, download_token
+        FROM data_exports
+        WHERE id = $1 AND user_id = $2
+        "#,
+        export_id,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .map(DataExport::from)
+    .ok_or_else(|| Error::NotFound("Data export not found".to_string()))
+}
+
+/// Storage key of a ready archive, if the download token matches and has not expired
+pub async fn find_download(conn: &mut DbConn, export_id: Uuid, token: &str) -> Result<String> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT id FROM data_exports
+        WHERE id = $1 AND download_token = $2 AND status = 'ready' AND expires_at > NOW()
+        "#,
+        export_id,
+        token
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .map(export_key)
+    .ok_or_else(|| Error::NotFound("Data export not found or expired".to_string()))
+}
+
+/// Ids of every export of a user, to remove their archives with the account
+pub async fn find_export_ids(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<Uuid>> {
+    sqlx::query_scalar!("SELECT id FROM data_exports WHERE user_id = $1", user_id)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)
+}
+
+/// Everything stored about a user, as named JSON documents
+async fn collect_user_data(conn: &mut DbConn, user_id: Uuid) -> Result<Vec<(&'static str, Value)>> {
+    let mut profile = user_services::get_user_profile(conn, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    let staff = profile.role.has_role_or_higher(UserRole::Moderator);
+    user_services::attach_profiles(conn, std::slice::from_mut(&mut profile), staff).await?;
+    let profile = serde_json::to_value(profile)
+        .map_err(|e| Error::Internal(format!("Failed to serialize profile: {e}")))?;
+
+    // Session tokens are credentials and are left out
+    let sessions = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(json_agg(json_build_object(
+            'id', id,
+            'user_agent', user_agent,
+            'is_active', is_active,
+            'created_at', created_at,
+            'last_activity_at', last_activity_at,
+            'last_refreshed_at', last_refreshed_at,
+            'expires_at', expires_at
+        ) ORDER BY created_at), '[]') AS "sessions!"
+        FROM sessions
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let tasks = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(json_agg(json_build_object(
+            'id', id,
+            'task_type', task_type,
+            'status', status,
+            'priority', priority,
+            'payload', payload,
+            'metadata', metadata,
+            'tags', tags,
+            'last_error', last_error,
+            'created_at', created_at,
+            'scheduled_at', scheduled_at,
+            'started_at', started_at,
+            'completed_at', completed_at
+        ) ORDER BY created_at), '[]') AS "tasks!"
+        FROM (
+            SELECT id, task_type, status, priority, payload, metadata, tags, last_error,
+                   created_at, scheduled_at, started_at, completed_at
+            FROM tasks WHERE created_by = $1
+            UNION ALL
+            SELECT id, task_type, status, priority, payload, metadata, tags, last_error,
+                   created_at, scheduled_at, started_at, completed_at
+            FROM tasks_archive WHERE created_by = $1
+        ) t
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // Events carry no user column: match the user_id tag and the logs of the user's tasks
+    let events = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(json_agg(json_build_object(
+            'id', id,
+            'event_type', event_type,
+            'source', source,
+            'message', message,
+            'level', level,
+            'tags', tags,
+            'payload', payload,
+            'recorded_at', recorded_at
+        ) ORDER BY recorded_at), '[]') AS "events!"
+        FROM events
+        WHERE tags @> jsonb_build_object('user_id', $1::uuid::text)
+           OR tags->>'task_id' IN (
+               SELECT id::text FROM tasks WHERE created_by = $1
+               UNION ALL
+               SELECT id::text FROM tasks_archive WHERE created_by = $1
+           )
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let activity = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(json_agg(json_build_object(
+            'id', id,
+            'actor_id', actor_id,
+            'action', action,
+            'details', details,
+            'created_at', created_at
+        ) ORDER BY created_at), '[]') AS "activity!"
+        FROM user_activity
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(vec![
+        ("profile.json", profile),
+        ("sessions.json", sessions),
+        ("tasks.json", tasks),
+        ("events.json", events),
+        ("activity.json", activity),
+    ])
+}
+
+/// Zip the documents, one pretty-printed JSON file each
+pub fn build_archive(documents: &[(&str, Value)]) -> Result<Vec<u8>> {
+    let archive_error =
+        |e: &dyn std::fmt::Display| Error::Internal(format!("Failed to build export archive: {e}"));
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, document) in documents {
+        zip.start_file(*name, options)
+            .map_err(|e| archive_error(&e))?;
+        serde_json::to_writer_pretty(&mut zip, document).map_err(|e| archive_error(&e))?;
+        zip.write_all(b"\n").map_err(|e| archive_error(&e))?;
+    }
+    let cursor = zip.finish().map_err(|e| archive_error(&e))?;
+    Ok(cursor.into_inner())
+}
+
+/// Collect the user's data and store the archive, returning its size
+async fn store_archive(
+    conn: &mut DbConn,
+    storage: &dyn FileStorage,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> Result<i64> {
+    let documents = collect_user_data(conn, user_id).await?;
+    let archive = tokio::task::spawn_blocking(move || build_archive(&documents))
+        .await
+        .map_err(|e| Error::Internal(format!("Export archive task failed: {e}")))??;
+    let size = archive.len() as i64;
+    storage.put(&export_key(export_id), archive).await?;
+    Ok(size)
+}
+
+/// Builds the archive requested with `POST /users/me/export`
+pub struct DataExportTaskHandler;
+
+register_task_handler!(
+    "user_data_export",
+    "Build archives of a user's data",
+    DataExportTaskHandler
+);
+
+#[async_trait]
+impl TaskHandler for DataExportTaskHandler {
+    async fn handle(&self, context: TaskContext) -> std::result::Result<TaskResult, TaskError> {
+        let export_id = require_field!(context.payload, "export_id")?;
+        let export_id = Uuid::parse_str(export_id)
+            .map_err(|_| TaskError::invalid_field_type("export_id", "UUID"))?;
+
+        let (Some(pool), Some(storage)) = (context.pool(), context.services().storage()) else {
+            return Err(TaskError::Execution(
+                "Data exports need a database pool and file storage".to_string(),
+            ));
+        };
+        let ttl = context
+            .services()
+            .config()
+            .map(AppConfig::export_ttl)
+            .unwrap_or_else(|| AppConfig::default().export_ttl());
+        let mut conn = pool.acquire().await?;
+
+        let user_id = sqlx::query_scalar!(
+            "SELECT user_id FROM data_exports WHERE id = $1 AND status = 'pending'",
+            export_id
+        )
+        .fetch_optional(conn.as_mut())
+        .await?;
+        let Some(user_id) = user_id else {
+            context
+                .log(
+                    TaskLogLevel::Warn,
+                    format!("Data export {export_id} is no longer pending"),
+                )
+                .await;
+            return Ok(TaskResult::success_empty());
+        };
+
+        match store_archive(conn.as_mut(), storage, user_id, export_id).await {
+            Ok(size) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE data_exports
+                    SET status = 'ready', size_bytes = $2, completed_at = NOW(), expires_at = $3
+                    WHERE id = $1
+                    "#,
+                    export_id,
+                    size,
+                    Utc::now() + ttl
+                )
+                .execute(conn.as_mut())
+                .await?;
+                context
+                    .log(
+                        TaskLogLevel::Info,
+                        format!("Data export {export_id} ready ({size} bytes)"),
+                    )
+                    .await;
+                notify_export_finished(conn.as_mut(), user_id, export_id, true).await;
+                Ok(TaskResult::success(serde_json::json!({
+                    "export_id": export_id,
+                    "size_bytes": size,
+                })))
+            }
+            Err(e) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE data_exports SET status = 'failed', error = $2, completed_at = NOW()
+                    WHERE id = $1
+                    "#,
+                    export_id,
+                    e.to_string()
+                )
+                .execute(conn.as_mut())
+                .await?;
+                notify_export_finished(conn.as_mut(), user_id, export_id, false).await;
+                Err(TaskError::Execution(format!(
+                    "Data export {export_id} failed: {e}"
+                )))
+            }
+        }
+    }
+}
+
+/// Tell the user their export finished; the export itself stands if this fails
+async fn notify_export_finished(conn: &mut DbConn, user_id: Uuid, export_id: Uuid, ready: bool) {
+    let notification = match ready {
+        true => Notification {
+            kind: "data_export_ready".to_string(),
+            message: "Your data export is ready to download".to_string(),
+            data: serde_json::json!({ "export_id": export_id }),
+        },
+        false => Notification {
+            kind: "data_export_failed".to_string(),
+            message: "Your data export failed; request a new one".to_string(),
+            data: serde_json::json!({ "export_id": export_id }),
+        },
+    };
+    if let Err(e) = realtime_services::notify_user(conn, user_id, notification).await {
+        error!("Failed to send data export notification: {}", e);
+    }
+}
+
+/// Background job that deletes the archives of expired exports
+pub async fn data_export_cleanup_job(pool: DbPool, run_interval: Duration, services: TaskServices) {
+    let Some(storage) = services.storage() else {
+        error!("Data export cleanup disabled: no file storage configured");
+        return;
+    };
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            expire_exports(conn.as_mut(), storage).await
+        }
+        .await;
+        match result {
+            Ok(expired) if expired > 0 => {
+                info!("Data export cleanup: {} archives deleted", expired)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to clean up data exports: {}", e),
+        }
+    }
+}
+
+/// Delete the archives of exports past their expiry and mark them expired
+pub async fn expire_exports(conn: &mut DbConn, storage: &dyn FileStorage) -> Result<usize> {
+    let ids = sqlx::query_scalar!(
+        "SELECT id FROM data_exports WHERE status = 'ready' AND expires_at <= NOW()"
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut expired = Vec::with_capacity(ids.len());
+    for id in ids {
+        match storage.delete(&export_key(id)).await {
+            Ok(()) => expired.push(id),
+            Err(e) => error!("Failed to delete data export {}: {}", id, e),
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE data_exports SET status = 'expired' WHERE id = ANY($1)",
+        &expired
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(expired.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_build_archive() {
+        let documents = [
+            ("profile.json", serde_json::json!({ "username": "alice" })),
+            ("tasks.json", serde_json::json!([])),
+        ];
+        let archive = build_archive(&documents).unwrap();
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(zip.len(), 2);
+        let mut profile = String::new();
+        zip.by_name("profile.json")
+            .unwrap()
+            .read_to_string(&mut profile)
+            .unwrap();
+        let profile: Value = serde_json::from_str(&profile).unwrap();
+        assert_eq!(profile["username"], "alice");
+    }
+
+    #[test]
+    fn test_export_key_is_private() {
+        let key = export_key(Uuid::nil());
+        assert!(key.starts_with(PRIVATE_PREFIX));
+        assert!(crate::storage::validate_key(&key).is_ok());
+    }
+}
@@ -0,0 +1,581 @@
+use crate::rbac::{UserRole, role_requests};
+use crate::tasks::handlers::TaskHandler;
+use crate::tasks::processor::{ProcessorConfig, TaskProcessor};
+use crate::tasks::retry::RetryStrategy;
+use crate::tasks::types::{CreateTaskRequest, TaskContext, TaskError, TaskLogLevel, TaskResult};
+use crate::users::models::{
+    ImportPasswordPolicy, ImportRowStatus, UserImport, UserImportRow, UserImportStatus,
+    validate_email, validate_password, validate_username,
+};
+use crate::users::services as user_services;
+use crate::{Database, DbConn, Error, Result, register_task_handler, require_field};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Task type that processes CSV imports
+pub const USER_IMPORT_TASK_TYPE: &str = "user_import";
+
+/// Largest CSV file accepted
+pub const MAX_IMPORT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Most rows accepted in one import
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+
+/// Rows processed between progress updates
+const PROGRESS_INTERVAL: usize = 100;
+
+/// One data row of an import file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRecord {
+    pub line: u64,
+    pub username: String,
+    pub email: String,
+    pub role: String,
+    pub password: String,
+}
+
+/// Parse an import file
+///
+/// The header names the columns in any order; `username` and `email` are
+/// required, `role` is optional and `password` is required by the
+/// `provided` policy. Problems with the file as a whole are returned as
+/// errors, problems with single rows are left to [`import_record`].
+pub fn parse_import(csv: &str, policy: ImportPasswordPolicy) -> Result<Vec<ImportRecord>> {
+    let file_error = |message: &str| Error::validation("file", message);
+
+    let csv = csv.trim_start_matches('\u{feff}');
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| file_error(&format!("Invalid CSV header: {e}")))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+    };
+    let (Some(username), Some(email)) = (column("username"), column("email")) else {
+        return Err(file_error(
+            "The header row must name a username and an email column",
+        ));
+    };
+    let role = column("role");
+    let password = column("password");
+    if policy == ImportPasswordPolicy::Provided && password.is_none() {
+        return Err(file_error(
+            "The provided password policy needs a password column",
+        ));
+    }
+
+    let mut records = Vec::new();
+    let (mut line, mut counted) = (1, 0);
+    for row in reader.records() {
+        let row = row.map_err(|e| file_error(&format!("Invalid CSV: {e}")))?;
+        if row.iter().all(str::is_empty) {
+            continue;
+        }
+        if records.len() == MAX_IMPORT_ROWS {
+            return Err(file_error(&format!(
+                "Imports are limited to {MAX_IMPORT_ROWS} rows"
+            )));
+        }
+
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| row.get(index))
+                .unwrap_or_default()
+                .to_string()
+        };
+        // The reader's position includes the blank lines it skipped before the row
+        let mut offset = row.position().map_or(0, |p| p.byte() as usize);
+        offset += csv.as_bytes()[offset..]
+            .iter()
+            .take_while(|&&b| b == b'\n' || b == b'\r')
+            .count();
+        line += csv.as_bytes()[counted..offset]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count() as u64;
+        counted = offset;
+        records.push(ImportRecord {
+            line,
+            username: field(Some(username)),
+            email: field(Some(email)),
+            role: field(role),
+            password: field(password),
+        });
+    }
+
+    if records.is_empty() {
+        return Err(file_error("The file has no rows to import"));
+    }
+    Ok(records)
+}
+
+/// Check a row before creating its account, returning the role to grant
+fn validate_record(
+    record: &ImportRecord,
+    policy: ImportPasswordPolicy,
+    admin_requires_approval: bool,
+) -> Result<UserRole> {
+    validate_username(&record.username)?;
+    validate_email(&record.email)?;
+    let role = if record.role.is_empty() {
+        UserRole::User
+    } else {
+        UserRole::from_str(&record.role)?
+    };
+    role_requests::require_admin_approval(admin_requires_approval, role, None)?;
+    if policy == ImportPasswordPolicy::Provided {
+        validate_password(&record.password)?;
+    }
+    Ok(role)
+}
+
+/// Create the account of one row, reporting why it was rejected otherwise
+///
+/// A row repeating the username or email of an earlier one fails like a row
+/// naming an existing account, and an `admin` row fails while
+/// `admin_requires_approval` is set.
+pub async fn import_record(
+    conn: &mut DbConn,
+    record: &ImportRecord,
+    policy: ImportPasswordPolicy,
+    tenant_id: Uuid,
+    admin_requires_approval: bool,
+) -> UserImportRow {
+    let result = async {
+        let role = validate_record(record, policy, admin_requires_approval)?;
+        let password_hash = match policy {
+            ImportPasswordPolicy::Provided => user_services::hash_password(&record.password)?,
+            ImportPasswordPolicy::Locked => user_services::LOCKED_PASSWORD_HASH.to_string(),
+        };
+
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (username, email, password_hash, role, tenant_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+            record.username,
+            record.email,
+            password_hash,
+            role.to_string(),
+            tenant_id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)
+    }
+    .await;
+
+    let (status, user_id, error) = match result {
+        Ok(user_id) => (ImportRowStatus::Created, Some(user_id), None),
+        Err(e) => (ImportRowStatus::Failed, None, Some(e.to_string())),
+    };
+    UserImportRow {
+        line: record.line,
+        username: record.username.clone(),
+        email: record.email.clone(),
+        status,
+        user_id,
+        error,
+    }
+}
+
+struct UserImportRecord {
+    id: Uuid,
+    status: String,
+    password_policy: String,
+    task_id: Option<Uuid>,
+    total_rows: i32,
+    created_count: i32,
+    failed_count: i32,
+    results: serde_json::Value,
+    error: Option<String>,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserImportRecord> for UserImport {
+    fn from(row: UserImportRecord) -> Self {
+        Self {
+            id: row.id,
+            status: UserImportStatus::from(row.status),
+            password_policy: ImportPasswordPolicy::from(row.password_policy),
+            task_id: row.task_id,
+            total_rows: row.total_rows,
+            created_count: row.created_count,
+            failed_count: row.failed_count,
+            results: serde_json::from_value(row.results).unwrap_or_default(),
+            error: row.error,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+        }
+    }
+}
+
+/// Check an uploaded file and queue it for import into the tenant
+pub async fn create_import(
+    conn: &mut DbConn,
+    database: &Database,
+    tenant_id: Uuid,
+    created_by: Uuid,
+    csv: String,
+    policy: ImportPasswordPolicy,
+) -> Result<UserImport> {
+    let records = parse_import(&csv, policy)?;
+
+    let import_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO user_imports (password_policy, csv, total_rows, created_by, tenant_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        policy.as_str(),
+        csv,
+        records.len() as i32,
+        created_by,
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // Workers register their task types when they start; add this one so imports
+    // can be queued before then
+    sqlx::query!(
+        r#"
+        INSERT INTO task_types (task_type, description)
+        VALUES ($1, 'Create user accounts from CSV imports')
+        ON CONFLICT (task_type) DO NOTHING
+        "#,
+        USER_IMPORT_TASK_TYPE
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    // A retry would report rows created by the first attempt as duplicates
+    let request = CreateTaskRequest::new(
+        USER_IMPORT_TASK_TYPE,
+        serde_json::json!({ "import_id": import_id }),
+    )
+    .with_created_by(created_by)
+    .with_retry_strategy(RetryStrategy::None);
+    let processor = TaskProcessor::new(database.clone(), ProcessorConfig::default());
+    let task = match processor.create_task(request).await {
+        Ok(task) => task,
+        Err(e) => {
+            sqlx::query!("DELETE FROM user_imports WHERE id = $1", import_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(Error::from_sqlx)?;
+            return Err(Error::Internal(format!(
+                "Failed to create import task: {e}"
+            )));
+        }
+    };
+
+    sqlx::query_as!(
+        UserImportRecord,
+        r#"
+        UPDATE user_imports SET task_id = $2
+        WHERE id = $1
+        RETURNING id, status, password_policy, task_id, total_rows, created_count,
+                  failed_count, results, error, created_by, created_at, completed_at
+        "#,
+        import_id,
+        task.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map(UserImport::from)
+    .map_err(Error::from_sqlx)
+}
+
+/// Import of the tenant with the given ID
+pub async fn find_import(
+    conn: &mut DbConn,
+    import_id: Uuid,
+    tenant_id: Uuid,
+) -> Result<UserImport> {
+    sqlx::query_as!(
+        UserImportRecord,
+        r#"
+        SELECT id, status, password_policy, task_id, total_rows, created_count,
+               failed_count, results, error, created_by, created_at, completed_at
+        FROM user_imports
+        WHERE id = $1 AND tenant_id = $2
+        "#,
+        import_id,
+        tenant_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .map(UserImport::from)
+    .ok_or_else(|| Error::NotFound("User import not found".to_string()))
+}
+
+/// Save the rows processed so far
+async fn save_progress(
+    conn: &mut DbConn,
+    import_id: Uuid,
+    results: &[UserImportRow],
+) -> Result<()> {
+    let created = results
+        .iter()
+        .filter(|row| row.status == ImportRowStatus::Created)
+        .count() as i32;
+    let results_json = serde_json::to_value(results)
+        .map_err(|e| Error::Internal(format!("Failed to serialize import results: {e}")))?;
+
+    sqlx::query!(
+        r#"
+        UPDATE user_imports
+        SET results = $2, created_count = $3, failed_count = $4
+        WHERE id = $1
+        "#,
+        import_id,
+        results_json,
+        created,
+        results.len() as i32 - created
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Create the accounts of a pending import and record each row's outcome
+///
+/// Returns `None` when the import is no longer pending.
+pub async fn process_import(
+    conn: &mut DbConn,
+    import_id: Uuid,
+    admin_requires_approval: bool,
+) -> Result<Option<UserImport>> {
+    // Accounts are created in the tenant of the admin who uploaded the file
+    let pending = sqlx::query!(
+        r#"
+        SELECT csv, password_policy, tenant_id
+        FROM user_imports
+        WHERE id = $1 AND status = 'pending'
+        "#,
+        import_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let Some(pending) = pending else {
+        return Ok(None);
+    };
+    let policy = ImportPasswordPolicy::from(pending.password_policy);
+    let tenant_id = pending.tenant_id;
+
+    let outcome = match parse_import(pending.csv.as_deref().unwrap_or_default(), policy) {
+        Ok(records) => {
+            let mut results = Vec::with_capacity(records.len());
+            for record in &records {
+                results.push(
+                    import_record(conn, record, policy, tenant_id, admin_requires_approval).await,
+                );
+                if results.len() % PROGRESS_INTERVAL == 0 {
+                    save_progress(conn, import_id, &results).await?;
+                }
+            }
+            save_progress(conn, import_id, &results).await?;
+            Ok(())
+        }
+        Err(e) => Err(e.to_string()),
+    };
+
+    // The file may hold passwords, so it is dropped either way
+    let (status, error) = match outcome {
+        Ok(()) => (UserImportStatus::Completed, None),
+        Err(e) => (UserImportStatus::Failed, Some(e)),
+    };
+    let import = sqlx::query_as!(
+        UserImportRecord,
+        r#"
+        UPDATE user_imports
+        SET status = $2, error = $3, csv = NULL, completed_at = NOW()
+        WHERE id = $1
+        RETURNING id, status, password_policy, task_id, total_rows, created_count,
+                  failed_count, results, error, created_by, created_at, completed_at
+        "#,
+        import_id,
+        status.as_str(),
+        error
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(Some(import.into()))
+}
+
+/// Processes files uploaded with `POST /admin/users/import`
+pub struct UserImportTaskHandler;
+
+register_task_handler!(
+    "user_import",
+    "Create user accounts from CSV imports",
+    UserImportTaskHandler
+);
+
+#[async_trait]
+impl TaskHandler for UserImportTaskHandler {
+    async fn handle(&self, context: TaskContext) -> std::result::Result<TaskResult, TaskError> {
+        let import_id = require_field!(context.payload, "import_id")?;
+        let import_id = Uuid::parse_str(import_id)
+            .map_err(|_| TaskError::invalid_field_type("import_id", "UUID"))?;
+
+        let Some(pool) = context.pool() else {
+            return Err(TaskError::Execution(
+                "User imports need a database pool".to_string(),
+            ));
+        };
+        // Without the configuration the default applies: admins need approval
+        let admin_requires_approval = context
+            .services()
+            .config()
+            .is_none_or(|config| config.auth.admin_role_requires_approval);
+        let mut conn = pool.acquire().await?;
+
+        match process_import(conn.as_mut(), import_id, admin_requires_approval).await {
+            Ok(Some(import)) if import.status == UserImportStatus::Completed => {
+                context
+                    .log(
+                        TaskLogLevel::Info,
+                        format!(
+                            "User import {import_id}: {} created, {} failed",
+                            import.created_count, import.failed_count
+                        ),
+                    )
+                    .await;
+                Ok(TaskResult::success(serde_json::json!({
+                    "import_id": import_id,
+                    "created_count": import.created_count,
+                    "failed_count": import.failed_count,
+                })))
+            }
+            Ok(Some(import)) => Err(TaskError::Execution(format!(
+                "User import {import_id} failed: {}",
+                import.error.unwrap_or_default()
+            ))),
+            Ok(None) => {
+                context
+                    .log(
+                        TaskLogLevel::Warn,
+                        format!("User import {import_id} is no longer pending"),
+                    )
+                    .await;
+                Ok(TaskResult::success_empty())
+            }
+            Err(e) => Err(TaskError::Execution(format!(
+                "User import {import_id} failed: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_import_maps_columns_by_header() {
+        let csv = "\u{feff}Email, Username ,role\nalice@example.com,alice,Moderator\n\n bob@example.com ,bob\n";
+        let records = parse_import(csv, ImportPasswordPolicy::Locked).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                ImportRecord {
+                    line: 2,
+                    username: "alice".to_string(),
+                    email: "alice@example.com".to_string(),
+                    role: "Moderator".to_string(),
+                    password: String::new(),
+                },
+                ImportRecord {
+                    line: 4,
+                    username: "bob".to_string(),
+                    email: "bob@example.com".to_string(),
+                    role: String::new(),
+                    password: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_import_rejects_bad_files() {
+        assert!(
+            parse_import(
+                "name,email\nalice,a@example.com\n",
+                ImportPasswordPolicy::Locked
+            )
+            .is_err()
+        );
+        assert!(parse_import("username,email\n", ImportPasswordPolicy::Locked).is_err());
+        // The provided policy needs passwords
+        assert!(
+            parse_import(
+                "username,email\nalice,a@example.com\n",
+                ImportPasswordPolicy::Provided
+            )
+            .is_err()
+        );
+
+        let mut csv = String::from("username,email\n");
+        for i in 0..=MAX_IMPORT_ROWS {
+            csv.push_str(&format!("user{i},user{i}@example.com\n"));
+        }
+        assert!(parse_import(&csv, ImportPasswordPolicy::Locked).is_err());
+    }
+
+    #[test]
+    fn test_validate_record() {
+        let record = |username: &str, email: &str, role: &str, password: &str| ImportRecord {
+            line: 2,
+            username: username.to_string(),
+            email: email.to_string(),
+            role: role.to_string(),
+            password: password.to_string(),
+        };
+
+        let alice = record("alice", "alice@example.com", "admin", "");
+        assert_eq!(
+            validate_record(&alice, ImportPasswordPolicy::Locked, false).unwrap(),
+            UserRole::Admin
+        );
+        // Admin rows need an approved role request when approval is required
+        assert!(matches!(
+            validate_record(&alice, ImportPasswordPolicy::Locked, true),
+            Err(crate::Error::Forbidden(_))
+        ));
+        // Passwords are only checked when they are used
+        assert!(validate_record(&alice, ImportPasswordPolicy::Provided, false).is_err());
+        let alice = record("alice", "alice@example.com", "", "SecurePass123!");
+        assert_eq!(
+            validate_record(&alice, ImportPasswordPolicy::Provided, true).unwrap(),
+            UserRole::User
+        );
+
+        let bad_role = record("alice", "alice@example.com", "owner", "");
+        assert!(validate_record(&bad_role, ImportPasswordPolicy::Locked, false).is_err());
+        let bad_email = record("alice", "alice.example.com", "", "");
+        assert!(validate_record(&bad_email, ImportPasswordPolicy::Locked, false).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use axum::body::{Body, Bytes};
+use futures_util::{TryStreamExt, stream};
+use sqlx::{PgPool, QueryBuilder};
+use tokio::sync::mpsc;
+
+use crate::tasks::export::csv_field;
+use crate::users::models::{User, UserFilter};
+use crate::users::services::push_user_filters;
+
+/// Rows buffered between the database cursor and a slow client
+const EXPORT_BUFFER_ROWS: usize = 256;
+
+const CSV_HEADER: &str = "id,username,email,role,is_active,email_verified,created_at,\
+updated_at,last_login_at,last_seen_at,suspended_until\n";
+
+/// Stream every user matching `filter` as a CSV response body
+///
+/// Rows come in the list endpoint's order and are read through a database
+/// cursor, so memory use stays flat however many accounts there are. `limit`
+/// and `offset` are honored when set; an unset limit exports everything. If
+/// the query fails part way, the body ends with an error so the client sees
+/// a truncated download rather than a silently short one.
+pub fn export_users(pool: PgPool, filter: UserFilter) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<Bytes, sqlx::Error>>(EXPORT_BUFFER_ROWS);
+
+    tokio::spawn(async move {
+        if tx.send(Ok(Bytes::from(CSV_HEADER))).await.is_err() {
+            return;
+        }
+
+        let order = filter.order.unwrap_or_else(|| filter.sort.default_order());
+        let mut query_builder = QueryBuilder::new(
+            "SELECT id, username, email, password_hash, role, is_active, email_verified, \
+             created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, \
+             last_seen_at, role_expires_at, is_service_account FROM users",
+        );
+        push_user_filters(&mut query_builder, &filter);
+        query_builder.push(format!(
+            " ORDER BY {} {} NULLS LAST, id",
+            filter.sort.as_str(),
+            order.as_sql()
+        ));
+        if let Some(limit) = filter.limit {
+            query_builder.push(" LIMIT ");
+            query_builder.push_bind(limit.max(0));
+        }
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(filter.offset.unwrap_or(0).max(0));
+
+        let mut rows = query_builder.build_query_as::<User>().fetch(&pool);
+        loop {
+            let item = match rows.try_next().await {
+                Ok(Some(user)) => Ok(Bytes::from(render_user(&user))),
+                Ok(None) => return,
+                Err(e) => {
+                    tracing::error!("User export failed part way: {}", e);
+                    Err(e)
+                }
+            };
+            let failed = item.is_err();
+            // A closed channel means the client went away
+            if tx.send(item).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    Body::from_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    }))
+}
+
+/// Render one user as a newline-terminated CSV row
+fn render_user(user: &User) -> String {
+    let optional_time =
+        |t: Option<chrono::DateTime<chrono::Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_default();
+    let fields = [
+        user.id.to_string(),
+        user.username.clone(),
+        user.email.clone(),
+        user.role.to_string(),
+        user.is_active.to_string(),
+        user.email_verified.to_string(),
+        user.created_at.to_rfc3339(),
+        user.updated_at.to_rfc3339(),
+        optional_time(user.last_login_at),
+        optional_time(user.last_seen_at),
+        optional_time(user.suspended_until),
+    ];
+    let mut row = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push('\n');
+    row
+}
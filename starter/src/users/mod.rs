@@ -1,3 +1,15 @@
+pub mod activity;
 pub mod api;
+pub mod avatar;
+pub mod deactivation;
+pub mod deletion;
+pub mod export;
+pub mod import;
+pub mod list_export;
 pub mod models;
+pub mod presence;
+pub mod purge;
+pub mod quotas;
+pub mod role_expiry;
 pub mod services;
+pub mod suspension;
@@ -18,6 +18,18 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub avatar_url: Option<String>,
+    /// Custom field values; see [`visible_profile`] before returning them
+    #[serde(skip_serializing)]
+    pub profile: serde_json::Value,
+    /// When a suspended (inactive) account is reactivated automatically
+    pub suspended_until: Option<DateTime<Utc>>,
+    /// Last authenticated request, stored in batches so it may lag a little
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// When a temporary role reverts to the role held before it
+    pub role_expires_at: Option<DateTime<Utc>>,
+    /// Non-human account that authenticates only with API keys
+    pub is_service_account: bool,
 }
 
 impl User {
@@ -39,6 +51,12 @@ impl User {
             email_verified: self.email_verified,
             created_at: self.created_at,
             last_login_at: self.last_login_at,
+            avatar_url: self.avatar_url.clone(),
+            suspended_until: self.suspended_until,
+            last_seen_at: self.last_seen_at,
+            role_expires_at: self.role_expires_at,
+            profile: None,
+            internal_metadata: None,
         }
     }
 }
@@ -53,6 +71,30 @@ pub struct UserProfile {
     pub email_verified: bool,
     pub created_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub avatar_url: Option<String>,
+    /// When a suspended (inactive) account is reactivated automatically
+    pub suspended_until: Option<DateTime<Utc>>,
+    /// Last authenticated request, stored in batches so it may lag a little
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// When a temporary role reverts to the role held before it
+    pub role_expires_at: Option<DateTime<Utc>>,
+    /// Custom profile fields the caller may see; returned by the user endpoints
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub profile: Option<serde_json::Value>,
+    /// Support annotations; only filled in for moderator and admin viewers, and
+    /// left out of the JSON otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub internal_metadata: Option<serde_json::Value>,
+}
+
+/// Multipart form for `PUT /users/me/avatar` (documentation only)
+#[derive(Debug, utoipa::ToSchema)]
+pub struct AvatarUpload {
+    /// PNG, JPEG or WebP image, at most 2 MB
+    #[schema(value_type = String, format = Binary)]
+    pub avatar: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -359,9 +401,27 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 pub struct UpdateProfileRequest {
     pub username: Option<String>,
     pub email: Option<String>,
+    /// Custom field values to set; `null` clears a field
+    #[schema(value_type = Option<Object>)]
+    pub profile: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 impl UpdateProfileRequest {
+    /// Names of the fields this request changes, for the activity trail
+    pub fn changed_fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        if self.username.is_some() {
+            fields.push("username".to_string());
+        }
+        if self.email.is_some() {
+            fields.push("email".to_string());
+        }
+        if let Some(ref profile) = self.profile {
+            fields.extend(profile.keys().map(|key| format!("profile.{key}")));
+        }
+        fields
+    }
+
     pub fn validate(&self) -> Result<()> {
         if let Some(ref username) = self.username {
             validate_username(username)?;
@@ -415,9 +475,30 @@ pub struct UpdateUserProfileRequest {
     pub username: Option<String>,
     pub email: Option<String>,
     pub email_verified: Option<bool>,
+    /// Custom field values to set, including staff fields; `null` clears a field
+    #[schema(value_type = Option<Object>)]
+    pub profile: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 impl UpdateUserProfileRequest {
+    /// Names of the fields this request changes, for the activity trail
+    pub fn changed_fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        if self.username.is_some() {
+            fields.push("username".to_string());
+        }
+        if self.email.is_some() {
+            fields.push("email".to_string());
+        }
+        if self.email_verified.is_some() {
+            fields.push("email_verified".to_string());
+        }
+        if let Some(ref profile) = self.profile {
+            fields.extend(profile.keys().map(|key| format!("profile.{key}")));
+        }
+        fields
+    }
+
     pub fn validate(&self) -> Result<()> {
         if let Some(ref username) = self.username {
             validate_username(username)?;
@@ -433,12 +514,51 @@ impl UpdateUserProfileRequest {
 pub struct UpdateUserStatusRequest {
     pub is_active: bool,
     pub reason: Option<String>,
+    /// Reactivate the account automatically at this time; only with `is_active: false`
+    pub suspended_until: Option<DateTime<Utc>>,
+    /// Active user who takes over the account's open work; only with `is_active: false`
+    pub reassign_to: Option<Uuid>,
+}
+
+impl UpdateUserStatusRequest {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<()> {
+        if self.is_active && self.reassign_to.is_some() {
+            return Err(Error::validation(
+                "reassign_to",
+                "Work is only reassigned when deactivating an account",
+            ));
+        }
+        match self.suspended_until {
+            Some(_) if self.is_active => Err(Error::validation(
+                "suspended_until",
+                "Only inactive accounts can be suspended",
+            )),
+            Some(until) if until <= now => Err(Error::validation(
+                "suspended_until",
+                "Must be in the future",
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct UpdateUserRoleRequest {
     pub role: UserRole,
     pub reason: Option<String>,
+    /// Revert to the previous role at this time, e.g. for temporary elevation
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UpdateUserRoleRequest {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<()> {
+        match self.expires_at {
+            Some(expires_at) if expires_at <= now => {
+                Err(Error::validation("expires_at", "Must be in the future"))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
@@ -455,10 +575,178 @@ impl ResetPasswordRequest {
     }
 }
 
+pub const MAX_INTERNAL_METADATA_BYTES: usize = 16 * 1024;
+
+/// Changes to a user's staff-only metadata
+///
+/// Keys set to `null` are removed; other keys are added or replaced.
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateInternalMetadataRequest {
+    #[schema(value_type = Object, example = json!({"vip": true, "fraud_review": null}))]
+    pub internal_metadata: serde_json::Value,
+}
+
+impl UpdateInternalMetadataRequest {
+    pub fn validate(&self) -> Result<()> {
+        if !self.internal_metadata.is_object() {
+            return Err(Error::validation(
+                "internal_metadata",
+                "Must be a JSON object",
+            ));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct DeleteUserRequest {
     pub reason: Option<String>,
     pub hard_delete: Option<bool>,
+    /// Active user of the same tenant who takes over the account's open work
+    pub reassign_to: Option<Uuid>,
+}
+
+/// What deactivating or deleting an account revoked, cancelled and handed over
+#[derive(Debug, Clone, Default, Serialize, utoipa::ToSchema)]
+pub struct DeactivationSummary {
+    pub sessions_revoked: u64,
+    pub api_keys_revoked: u64,
+    /// Pending and retrying tasks the account created
+    pub tasks_cancelled: u64,
+    /// Who took over the account's open work, if anyone
+    pub reassigned_to: Option<Uuid>,
+    /// Open incidents now assigned to `reassigned_to`
+    pub incidents_reassigned: u64,
+    /// Unfinished postmortem action items now assigned to `reassigned_to`
+    pub action_items_reassigned: u64,
+    /// Organizations the account owned where `reassigned_to` became an owner
+    pub orgs_reassigned: u64,
+}
+
+/// A user after a status change, with what deactivating them did
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UserStatusUpdate {
+    #[serde(flatten)]
+    pub user: UserProfile,
+    /// Present when the account was deactivated
+    pub deactivation: Option<DeactivationSummary>,
+}
+
+/// Column to sort user listings by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UserSortField {
+    #[default]
+    CreatedAt,
+    Username,
+    Email,
+    LastLoginAt,
+    LastSeenAt,
+}
+
+impl UserSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserSortField::CreatedAt => "created_at",
+            UserSortField::Username => "username",
+            UserSortField::Email => "email",
+            UserSortField::LastLoginAt => "last_login_at",
+            UserSortField::LastSeenAt => "last_seen_at",
+        }
+    }
+
+    /// Dates sort newest first unless asked otherwise, names alphabetically
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            UserSortField::CreatedAt | UserSortField::LastLoginAt | UserSortField::LastSeenAt => {
+                SortOrder::Desc
+            }
+            UserSortField::Username | UserSortField::Email => SortOrder::Asc,
+        }
+    }
+}
+
+impl std::str::FromStr for UserSortField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "created_at" => Ok(UserSortField::CreatedAt),
+            "username" => Ok(UserSortField::Username),
+            "email" => Ok(UserSortField::Email),
+            "last_login_at" => Ok(UserSortField::LastLoginAt),
+            "last_seen_at" => Ok(UserSortField::LastSeenAt),
+            _ => Err(Error::validation(
+                "sort",
+                "Sort must be one of: created_at, username, email, last_login_at, last_seen_at",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Filters for searching users
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    /// Only users of this tenant
+    pub tenant_id: Uuid,
+    /// Case-insensitive substring of the username or email
+    pub search: Option<String>,
+    pub role: Option<UserRole>,
+    pub is_active: Option<bool>,
+    pub email_verified: Option<bool>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: UserSortField,
+    /// Defaults to the sort field's natural order
+    pub order: Option<SortOrder>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// One page of users with pagination metadata
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UserListResponse {
+    pub items: Vec<UserProfile>,
+    pub pagination: PaginationInfo,
+}
+
+/// Pagination metadata
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PaginationInfo {
+    pub total_count: i64,
+    pub page_count: i64,
+    pub current_page: i64,
+    pub per_page: i64,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+impl PaginationInfo {
+    pub fn new(total_count: i64, limit: i64, offset: i64) -> Self {
+        Self {
+            total_count,
+            page_count: (total_count + limit - 1) / limit,
+            current_page: (offset / limit) + 1,
+            per_page: limit,
+            has_next: (offset + limit) < total_count,
+            has_prev: offset > 0,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, utoipa::ToSchema)]
@@ -486,3 +774,778 @@ pub struct RecentRegistrations {
     pub last_7d: i64,
     pub last_30d: i64,
 }
+
+/// Active account seen within the online window
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct OnlineUser {
+    pub id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Accounts that made an authenticated request within the last `window_minutes`
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct OnlineUsersSummary {
+    pub window_minutes: u64,
+    pub total: i64,
+    pub by_role: UserRoleStats,
+    /// Most recently seen first, at most `limit` of them
+    pub users: Vec<OnlineUser>,
+}
+
+/// One of a user's limits and how much of it is used
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UserQuota {
+    /// Limit in effect; None when unlimited
+    pub limit: Option<i64>,
+    /// Limit of the user's role, in effect unless `overridden`
+    pub role_default: Option<i64>,
+    /// Whether the user has their own limit
+    pub overridden: bool,
+    pub used: i64,
+}
+
+/// Limits of a user, from their own quotas or their role's defaults
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct UserQuotas {
+    pub user_id: Uuid,
+    pub role: UserRole,
+    /// Tasks created today
+    pub tasks_per_day: UserQuota,
+    /// Monitoring events stored today
+    pub events_per_day: UserQuota,
+    /// Active API keys
+    pub api_keys: UserQuota,
+    /// When the daily limits reset (next UTC midnight)
+    pub daily_resets_at: DateTime<Utc>,
+    /// Last admin to change the user's own limits
+    pub updated_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Replaces a user's own limits; a missing or null limit uses the role's default
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateUserQuotasRequest {
+    #[serde(default)]
+    pub max_tasks_per_day: Option<i64>,
+    #[serde(default)]
+    pub max_events_per_day: Option<i64>,
+    #[serde(default)]
+    pub max_api_keys: Option<i64>,
+}
+
+impl UpdateUserQuotasRequest {
+    pub fn validate(&self) -> Result<()> {
+        for (field, value, max) in [
+            ("max_tasks_per_day", self.max_tasks_per_day, i32::MAX as i64),
+            ("max_events_per_day", self.max_events_per_day, i64::MAX),
+            ("max_api_keys", self.max_api_keys, i32::MAX as i64),
+        ] {
+            if let Some(value) = value
+                && !(0..=max).contains(&value)
+            {
+                return Err(Error::validation(
+                    field,
+                    &format!("{field} must be between 0 and {max}"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub const MAX_PROFILE_FIELD_NAME_LENGTH: usize = 64;
+pub const MAX_PROFILE_TEXT_LENGTH: usize = 1000;
+
+/// Value type of a custom profile field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFieldType {
+    Text,
+    Number,
+    Boolean,
+    /// `YYYY-MM-DD`
+    Date,
+    /// One of the field's `options`
+    Select,
+}
+
+impl ProfileFieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileFieldType::Text => "text",
+            ProfileFieldType::Number => "number",
+            ProfileFieldType::Boolean => "boolean",
+            ProfileFieldType::Date => "date",
+            ProfileFieldType::Select => "select",
+        }
+    }
+}
+
+impl From<String> for ProfileFieldType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "number" => ProfileFieldType::Number,
+            "boolean" => ProfileFieldType::Boolean,
+            "date" => ProfileFieldType::Date,
+            "select" => ProfileFieldType::Select,
+            _ => ProfileFieldType::Text,
+        }
+    }
+}
+
+/// Who sees a custom profile field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFieldVisibility {
+    /// The user sees and edits it; moderators and admins see it too
+    #[default]
+    User,
+    /// Only moderators and admins see it, and only admins set it
+    Staff,
+}
+
+impl ProfileFieldVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileFieldVisibility::User => "user",
+            ProfileFieldVisibility::Staff => "staff",
+        }
+    }
+}
+
+impl From<String> for ProfileFieldVisibility {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "staff" => ProfileFieldVisibility::Staff,
+            _ => ProfileFieldVisibility::User,
+        }
+    }
+}
+
+/// Admin-defined field stored in users' `profile`
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ProfileField {
+    pub name: String,
+    pub label: String,
+    pub field_type: ProfileFieldType,
+    pub required: bool,
+    pub visibility: ProfileFieldVisibility,
+    /// Allowed values of a `select` field
+    pub options: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProfileField {
+    /// Check a value against the field's type
+    pub fn check(&self, value: &serde_json::Value) -> Result<()> {
+        let field = format!("profile.{}", self.name);
+        let valid = match self.field_type {
+            ProfileFieldType::Text => match value.as_str() {
+                Some(text) if text.chars().count() > MAX_PROFILE_TEXT_LENGTH => {
+                    return Err(Error::validation(
+                        &field,
+                        &format!("Must be at most {MAX_PROFILE_TEXT_LENGTH} characters"),
+                    ));
+                }
+                Some(_) => true,
+                None => false,
+            },
+            ProfileFieldType::Number => value.is_number(),
+            ProfileFieldType::Boolean => value.is_boolean(),
+            ProfileFieldType::Date => value
+                .as_str()
+                .is_some_and(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()),
+            ProfileFieldType::Select => value
+                .as_str()
+                .is_some_and(|s| self.options.iter().any(|option| option == s)),
+        };
+        if valid {
+            return Ok(());
+        }
+
+        let message = match self.field_type {
+            ProfileFieldType::Text => "Must be a string".to_string(),
+            ProfileFieldType::Number => "Must be a number".to_string(),
+            ProfileFieldType::Boolean => "Must be true or false".to_string(),
+            ProfileFieldType::Date => "Must be a date (YYYY-MM-DD)".to_string(),
+            ProfileFieldType::Select => format!("Must be one of: {}", self.options.join(", ")),
+        };
+        Err(Error::validation(&field, &message))
+    }
+
+    /// Whether a viewer sees the field; staff are moderators and admins
+    pub fn visible_to(&self, staff: bool) -> bool {
+        staff || self.visibility == ProfileFieldVisibility::User
+    }
+}
+
+/// Keep only the values of defined fields the viewer may see
+///
+/// Values of deleted fields stay stored but are no longer returned.
+pub fn visible_profile(
+    fields: &[ProfileField],
+    profile: &serde_json::Value,
+    staff: bool,
+) -> serde_json::Value {
+    let visible = fields
+        .iter()
+        .filter(|field| field.visible_to(staff))
+        .filter_map(|field| {
+            profile
+                .get(&field.name)
+                .map(|value| (field.name.clone(), value.clone()))
+        })
+        .collect();
+    serde_json::Value::Object(visible)
+}
+
+/// Apply profile changes and validate the result against the schema
+///
+/// A `null` value clears a field. Values of deleted fields are dropped.
+/// `admin` allows setting staff fields and enforces their `required`
+/// flag too; otherwise only user fields can be changed.
+pub fn merge_profile(
+    fields: &[ProfileField],
+    current: &serde_json::Value,
+    changes: &serde_json::Map<String, serde_json::Value>,
+    admin: bool,
+) -> Result<serde_json::Value> {
+    let mut profile: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .filter_map(|field| {
+            current
+                .get(&field.name)
+                .map(|value| (field.name.clone(), value.clone()))
+        })
+        .collect();
+
+    for (name, value) in changes {
+        let Some(field) = fields.iter().find(|field| &field.name == name) else {
+            return Err(Error::validation(
+                &format!("profile.{name}"),
+                "Unknown profile field",
+            ));
+        };
+        if !admin && field.visibility == ProfileFieldVisibility::Staff {
+            return Err(Error::validation(
+                &format!("profile.{name}"),
+                "Only administrators can set this field",
+            ));
+        }
+        if value.is_null() {
+            profile.remove(name);
+        } else {
+            field.check(value)?;
+            profile.insert(name.clone(), value.clone());
+        }
+    }
+
+    for field in fields {
+        let applies = admin || field.visibility == ProfileFieldVisibility::User;
+        if applies && field.required && !profile.contains_key(&field.name) {
+            return Err(Error::validation(
+                &format!("profile.{}", field.name),
+                "This field is required",
+            ));
+        }
+    }
+
+    Ok(serde_json::Value::Object(profile))
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpsertProfileFieldRequest {
+    pub label: String,
+    pub field_type: ProfileFieldType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub visibility: ProfileFieldVisibility,
+    /// Required for `select` fields, not allowed otherwise
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+impl UpsertProfileFieldRequest {
+    pub fn validate(&self, name: &str) -> Result<()> {
+        validate_profile_field_name(name)?;
+        let label = self.label.trim();
+        if label.is_empty() || label.len() > 100 {
+            return Err(Error::validation(
+                "label",
+                "Label must be between 1 and 100 characters",
+            ));
+        }
+        match self.field_type {
+            ProfileFieldType::Select if self.options.is_empty() => Err(Error::validation(
+                "options",
+                "Select fields need at least one option",
+            )),
+            ProfileFieldType::Select
+                if self
+                    .options
+                    .iter()
+                    .any(|option| option.trim().is_empty() || option.len() > 100) =>
+            {
+                Err(Error::validation(
+                    "options",
+                    "Options must be between 1 and 100 characters",
+                ))
+            }
+            ProfileFieldType::Select => Ok(()),
+            _ if !self.options.is_empty() => Err(Error::validation(
+                "options",
+                "Only select fields have options",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Field names are lowercase identifiers such as `job_title`
+pub fn validate_profile_field_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_FIELD_NAME_LENGTH
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation(
+            "name",
+            &format!(
+                "Field names use lowercase letters, digits and underscores, start with a letter and are at most {MAX_PROFILE_FIELD_NAME_LENGTH} characters"
+            ),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DataExportStatus {
+    /// The archive is being built
+    Pending,
+    Ready,
+    Failed,
+    /// Ready past its expiry; the archive is no longer available
+    Expired,
+}
+
+impl DataExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataExportStatus::Pending => "pending",
+            DataExportStatus::Ready => "ready",
+            DataExportStatus::Failed => "failed",
+            DataExportStatus::Expired => "expired",
+        }
+    }
+}
+
+impl From<String> for DataExportStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "ready" => DataExportStatus::Ready,
+            "failed" => DataExportStatus::Failed,
+            "expired" => DataExportStatus::Expired,
+            _ => DataExportStatus::Pending,
+        }
+    }
+}
+
+/// Archive of a user's profile, sessions, tasks and events
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub status: DataExportStatus,
+    /// Background task building the archive
+    pub task_id: Option<Uuid>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    /// Until when the archive can be downloaded
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Link that downloads the archive without authentication; set while ready
+    pub download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountDeletionStatus {
+    /// Waiting out the grace period; the account can still be restored
+    Scheduled,
+    Cancelled,
+    /// The account has been erased
+    Completed,
+}
+
+impl AccountDeletionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountDeletionStatus::Scheduled => "scheduled",
+            AccountDeletionStatus::Cancelled => "cancelled",
+            AccountDeletionStatus::Completed => "completed",
+        }
+    }
+}
+
+impl From<String> for AccountDeletionStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "cancelled" => AccountDeletionStatus::Cancelled,
+            "completed" => AccountDeletionStatus::Completed,
+            _ => AccountDeletionStatus::Scheduled,
+        }
+    }
+}
+
+/// Request to delete an account, erased once its grace period ends
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AccountDeletion {
+    pub id: Uuid,
+    pub status: AccountDeletionStatus,
+    pub requested_at: DateTime<Utc>,
+    /// When the account is erased unless the deletion is cancelled first
+    pub erase_after: DateTime<Utc>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CancelAccountDeletionRequest {
+    /// Token from the link in the deletion email
+    pub token: String,
+}
+
+/// What happens to a deleted account once its retention period ends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PurgeMode {
+    /// Remove the account and everything that cascades from it
+    #[default]
+    Delete,
+    /// Keep the account row with its personal data scrubbed, so references stay intact
+    Anonymize,
+}
+
+impl PurgeMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurgeMode::Delete => "delete",
+            PurgeMode::Anonymize => "anonymize",
+        }
+    }
+}
+
+impl From<String> for PurgeMode {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "anonymize" => PurgeMode::Anonymize,
+            _ => PurgeMode::Delete,
+        }
+    }
+}
+
+/// Audit record of a purged account
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserPurge {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub mode: PurgeMode,
+    /// When the account was deleted
+    pub deleted_at: DateTime<Utc>,
+    pub sessions_deleted: i64,
+    pub api_keys_deleted: i64,
+    pub tasks_deleted: i64,
+    pub purged_at: DateTime<Utc>,
+}
+
+/// Significant action recorded in an account's activity trail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserActivityAction {
+    ProfileUpdated,
+    AvatarUpdated,
+    PasswordChanged,
+    /// A moderator or admin set a new password
+    PasswordReset,
+    RoleChanged,
+    StatusChanged,
+    /// Deletion requested; the account is erased after the grace period
+    AccountDeleted,
+    AccountDeletionCancelled,
+    TaskCreated,
+    DataExportRequested,
+    /// Through a verification link, or forced by an admin
+    EmailVerified,
+    RecoveryCodesGenerated,
+    /// Signed in with a recovery code and set a new password
+    AccountRecovered,
+}
+
+impl UserActivityAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserActivityAction::ProfileUpdated => "profile_updated",
+            UserActivityAction::AvatarUpdated => "avatar_updated",
+            UserActivityAction::PasswordChanged => "password_changed",
+            UserActivityAction::PasswordReset => "password_reset",
+            UserActivityAction::RoleChanged => "role_changed",
+            UserActivityAction::StatusChanged => "status_changed",
+            UserActivityAction::AccountDeleted => "account_deleted",
+            UserActivityAction::AccountDeletionCancelled => "account_deletion_cancelled",
+            UserActivityAction::TaskCreated => "task_created",
+            UserActivityAction::DataExportRequested => "data_export_requested",
+            UserActivityAction::EmailVerified => "email_verified",
+            UserActivityAction::RecoveryCodesGenerated => "recovery_codes_generated",
+            UserActivityAction::AccountRecovered => "account_recovered",
+        }
+    }
+}
+
+impl std::fmt::Display for UserActivityAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Entry in an account's activity trail
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserActivity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Who performed the action; differs from `user_id` for staff actions
+    pub actor_id: Option<Uuid>,
+    /// See UserActivityAction, e.g. `password_changed`
+    pub action: String,
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How imported accounts get their passwords
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportPasswordPolicy {
+    /// Each row sets its password in a `password` column, checked like any other
+    #[default]
+    Provided,
+    /// Accounts can't log in until a moderator or admin resets their password
+    Locked,
+}
+
+impl ImportPasswordPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportPasswordPolicy::Provided => "provided",
+            ImportPasswordPolicy::Locked => "locked",
+        }
+    }
+}
+
+impl From<String> for ImportPasswordPolicy {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "locked" => ImportPasswordPolicy::Locked,
+            _ => ImportPasswordPolicy::Provided,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserImportStatus {
+    /// Rows are being processed
+    Pending,
+    /// Every row was processed; see each row's outcome
+    Completed,
+    /// The import stopped before processing the rows
+    Failed,
+}
+
+impl UserImportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserImportStatus::Pending => "pending",
+            UserImportStatus::Completed => "completed",
+            UserImportStatus::Failed => "failed",
+        }
+    }
+}
+
+impl From<String> for UserImportStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "completed" => UserImportStatus::Completed,
+            "failed" => UserImportStatus::Failed,
+            _ => UserImportStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportRowStatus {
+    Created,
+    Failed,
+}
+
+/// Outcome of one CSV row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserImportRow {
+    /// Line number in the file, the header being line 1
+    pub line: u64,
+    pub username: String,
+    pub email: String,
+    pub status: ImportRowStatus,
+    /// Set when the account was created
+    pub user_id: Option<Uuid>,
+    /// Why the row was rejected
+    pub error: Option<String>,
+}
+
+/// Bulk import of users from CSV, with its per-row report
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct UserImport {
+    pub id: Uuid,
+    pub status: UserImportStatus,
+    pub password_policy: ImportPasswordPolicy,
+    /// Background task processing the rows
+    pub task_id: Option<Uuid>,
+    pub total_rows: i32,
+    pub created_count: i32,
+    pub failed_count: i32,
+    /// One entry per processed row, in file order
+    pub results: Vec<UserImportRow>,
+    pub error: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Multipart form for `POST /admin/users/import` (documentation only)
+#[derive(Debug, utoipa::ToSchema)]
+pub struct UserImportUpload {
+    /// CSV with a header row: `username,email` and optionally `role` and `password`
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(name: &str, field_type: ProfileFieldType) -> ProfileField {
+        ProfileField {
+            name: name.to_string(),
+            label: name.to_string(),
+            field_type,
+            required: false,
+            visibility: ProfileFieldVisibility::User,
+            options: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_profile_field_check() {
+        let mut team = field("team", ProfileFieldType::Select);
+        team.options = vec!["red".to_string(), "blue".to_string()];
+        assert!(team.check(&json!("red")).is_ok());
+        assert!(team.check(&json!("green")).is_err());
+
+        let birthday = field("birthday", ProfileFieldType::Date);
+        assert!(birthday.check(&json!("1990-02-28")).is_ok());
+        assert!(birthday.check(&json!("1990-02-30")).is_err());
+
+        assert!(
+            field("age", ProfileFieldType::Number)
+                .check(&json!(42))
+                .is_ok()
+        );
+        assert!(
+            field("age", ProfileFieldType::Number)
+                .check(&json!("42"))
+                .is_err()
+        );
+        assert!(
+            field("ok", ProfileFieldType::Boolean)
+                .check(&json!(true))
+                .is_ok()
+        );
+
+        let bio = field("bio", ProfileFieldType::Text);
+        assert!(bio.check(&json!("hello")).is_ok());
+        assert!(bio.check(&json!(1)).is_err());
+        assert!(
+            bio.check(&json!("x".repeat(MAX_PROFILE_TEXT_LENGTH + 1)))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_merge_profile() {
+        let mut title = field("title", ProfileFieldType::Text);
+        title.required = true;
+        let mut badge = field("badge", ProfileFieldType::Number);
+        badge.visibility = ProfileFieldVisibility::Staff;
+        badge.required = true;
+        let fields = vec![title, badge];
+
+        let current = json!({"title": "Engineer", "badge": 7, "removed": "x"});
+        let changes = json!({"title": "Lead"});
+        let merged = merge_profile(&fields, &current, changes.as_object().unwrap(), false).unwrap();
+        assert_eq!(merged, json!({"title": "Lead", "badge": 7}));
+
+        // Users can't set staff fields or unknown ones, nor clear required ones
+        for changes in [
+            json!({"badge": 8}),
+            json!({"nope": 1}),
+            json!({"title": null}),
+        ] {
+            assert!(merge_profile(&fields, &current, changes.as_object().unwrap(), false).is_err());
+        }
+
+        // Staff requirements only bind admin updates
+        let changes = json!({"title": "Lead"});
+        assert!(merge_profile(&fields, &json!({}), changes.as_object().unwrap(), false).is_ok());
+        assert!(merge_profile(&fields, &json!({}), changes.as_object().unwrap(), true).is_err());
+
+        assert_eq!(
+            visible_profile(&fields, &current, false),
+            json!({"title": "Engineer"})
+        );
+        assert_eq!(
+            visible_profile(&fields, &current, true),
+            json!({"title": "Engineer", "badge": 7})
+        );
+    }
+
+    #[test]
+    fn test_upsert_profile_field_validation() {
+        let request = UpsertProfileFieldRequest {
+            label: "Team".to_string(),
+            field_type: ProfileFieldType::Select,
+            required: false,
+            visibility: ProfileFieldVisibility::User,
+            options: vec!["red".to_string()],
+        };
+        assert!(request.validate("team").is_ok());
+        assert!(request.validate("Team").is_err());
+        assert!(request.validate("1team").is_err());
+        assert!(request.validate("team-name").is_err());
+
+        let request = UpsertProfileFieldRequest {
+            options: Vec::new(),
+            ..request
+        };
+        assert!(request.validate("team").is_err());
+    }
+}
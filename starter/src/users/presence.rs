@@ -0,0 +1,172 @@
+use crate::rbac::UserRole;
+use crate::users::models::{OnlineUser, OnlineUsersSummary, UserRoleStats};
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::error;
+use uuid::Uuid;
+
+/// Users seen on authenticated requests since their last-seen times were stored
+///
+/// Only the latest time per user is kept, so a burst of requests costs a
+/// single row update when the batch is flushed.
+#[derive(Debug, Default)]
+pub struct Presence {
+    seen: Mutex<HashMap<Uuid, DateTime<Utc>>>,
+}
+
+impl Presence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a user made a request at `at`
+    pub fn observe(&self, user_id: Uuid, at: DateTime<Utc>) {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let last = seen.entry(user_id).or_insert(at);
+        *last = (*last).max(at);
+    }
+
+    /// Users seen since the last call, which are then reset
+    pub fn take(&self) -> HashMap<Uuid, DateTime<Utc>> {
+        std::mem::take(&mut *self.seen.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Put back a batch that could not be stored, keeping newer sightings
+    pub fn restore(&self, batch: HashMap<Uuid, DateTime<Utc>>) {
+        for (user_id, at) in batch {
+            self.observe(user_id, at);
+        }
+    }
+
+    /// Users seen at `since` or later that are not stored yet
+    pub fn pending_since(&self, since: DateTime<Utc>) -> HashMap<Uuid, DateTime<Utc>> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|(_, at)| **at >= since)
+            .map(|(user_id, at)| (*user_id, *at))
+            .collect()
+    }
+}
+
+/// Background job that writes the batched last-seen times
+pub async fn last_seen_flush_job(pool: DbPool, run_interval: Duration, presence: Arc<Presence>) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let batch = presence.take();
+        if batch.is_empty() {
+            continue;
+        }
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            store_last_seen(conn.as_mut(), &batch).await
+        }
+        .await;
+        if let Err(e) = result {
+            error!("Failed to store last seen times: {}", e);
+            presence.restore(batch);
+        }
+    }
+}
+
+/// Store a batch of last-seen times in one statement
+///
+/// A time older than the stored one is ignored, so batches from several
+/// servers can be written in any order.
+pub async fn store_last_seen(
+    conn: &mut DbConn,
+    batch: &HashMap<Uuid, DateTime<Utc>>,
+) -> Result<u64> {
+    let (ids, times): (Vec<Uuid>, Vec<DateTime<Utc>>) = batch.iter().unzip();
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET last_seen_at = GREATEST(users.last_seen_at, seen.at)
+        FROM UNNEST($1::uuid[], $2::timestamptz[]) AS seen(id, at)
+        WHERE users.id = seen.id
+        "#,
+        &ids,
+        &times
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    Ok(result.rows_affected())
+}
+
+/// Active accounts of the tenant seen at `since` or later, including
+/// sightings in `pending` that are not stored yet
+pub async fn find_online_users(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    since: DateTime<Utc>,
+    pending: &HashMap<Uuid, DateTime<Utc>>,
+    window_minutes: u64,
+    limit: Option<i64>,
+) -> Result<OnlineUsersSummary> {
+    let limit = limit.unwrap_or(50).clamp(1, 200) as usize;
+    let pending_ids: Vec<Uuid> = pending.keys().copied().collect();
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, username, role, last_seen_at
+        FROM users
+        WHERE is_active = true
+          AND deleted_at IS NULL
+          AND is_service_account = false
+          AND tenant_id = $3
+          AND (last_seen_at >= $1 OR id = ANY($2))
+        "#,
+        since,
+        &pending_ids,
+        tenant_id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut by_role = UserRoleStats {
+        user: 0,
+        moderator: 0,
+        admin: 0,
+    };
+    let mut users = Vec::with_capacity(rows.len());
+    for row in rows {
+        let Some(last_seen_at) = row.last_seen_at.max(pending.get(&row.id).copied()) else {
+            continue;
+        };
+        let role = UserRole::from(row.role);
+        match role {
+            UserRole::User => by_role.user += 1,
+            UserRole::Moderator => by_role.moderator += 1,
+            UserRole::Admin => by_role.admin += 1,
+        }
+        users.push(OnlineUser {
+            id: row.id,
+            username: row.username,
+            role,
+            last_seen_at,
+        });
+    }
+
+    users.sort_by(|a, b| b.last_seen_at.cmp(&a.last_seen_at).then(a.id.cmp(&b.id)));
+    let total = users.len() as i64;
+    users.truncate(limit);
+
+    Ok(OnlineUsersSummary {
+        window_minutes,
+        total,
+        by_role,
+        users,
+    })
+}
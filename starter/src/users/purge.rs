@@ -0,0 +1,254 @@
+use crate::core::config::AuthConfig;
+use crate::storage::FileStorage;
+use crate::tasks::services::TaskServices;
+use crate::users::models::{PurgeMode, UserPurge};
+use crate::users::{avatar, export, services as user_services};
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use sqlx::Acquire;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Background job that purges deleted accounts past their retention
+pub async fn user_purge_job(
+    pool: DbPool,
+    run_interval: Duration,
+    config: AuthConfig,
+    services: TaskServices,
+) {
+    let Some(storage) = services.storage() else {
+        error!("User purge disabled: no file storage configured");
+        return;
+    };
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            purge_deleted_users(conn.as_mut(), storage, &config, Utc::now()).await
+        }
+        .await;
+        match result {
+            Ok(purged) if !purged.is_empty() => {
+                info!("User purge: {} deleted accounts purged", purged.len())
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to purge deleted users: {}", e),
+        }
+    }
+}
+
+/// Purge every account deleted more than the retention period before `now`
+///
+/// Accounts that fail to purge are logged and retried on the next run.
+pub async fn purge_deleted_users(
+    conn: &mut DbConn,
+    storage: &dyn FileStorage,
+    config: &AuthConfig,
+    now: DateTime<Utc>,
+) -> Result<Vec<UserPurge>> {
+    if config.deleted_user_retention_days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = now - chrono::Duration::days(config.deleted_user_retention_days as i64);
+
+    let user_ids = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE deleted_at <= $1 ORDER BY deleted_at",
+        cutoff
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut purged = Vec::with_capacity(user_ids.len());
+    for user_id in user_ids {
+        match purge_user(conn, storage, user_id, config.deleted_user_purge_mode).await {
+            Ok(Some(purge)) => purged.push(purge),
+            Ok(None) => {}
+            Err(e) => error!("Failed to purge user {}: {}", user_id, e),
+        }
+    }
+    Ok(purged)
+}
+
+/// Purge one deleted account and record it in the audit trail
+///
+/// Sessions, API keys, owned tasks, data exports and the activity trail are
+/// removed in both modes. Returns `None` when the account no longer exists or was
+/// reactivated.
+pub async fn purge_user(
+    conn: &mut DbConn,
+    storage: &dyn FileStorage,
+    user_id: Uuid,
+    mode: PurgeMode,
+) -> Result<Option<UserPurge>> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let user = sqlx::query!(
+        "SELECT deleted_at, tenant_id FROM users WHERE id = $1 AND is_active = false FOR UPDATE",
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    let Some((Some(deleted_at), tenant_id)) = user.map(|user| (user.deleted_at, user.tenant_id))
+    else {
+        return Ok(None);
+    };
+
+    let sessions_deleted = sqlx::query!("DELETE FROM sessions WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected();
+    let api_keys_deleted = sqlx::query!("DELETE FROM api_keys WHERE created_by = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected();
+    let mut tasks_deleted = sqlx::query!("DELETE FROM tasks WHERE created_by = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected();
+    tasks_deleted += sqlx::query!("DELETE FROM tasks_archive WHERE created_by = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?
+        .rows_affected();
+    let export_ids = sqlx::query_scalar!(
+        "DELETE FROM data_exports WHERE user_id = $1 RETURNING id",
+        user_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    sqlx::query!("DELETE FROM user_activity WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    sqlx::query!("DELETE FROM username_history WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    sqlx::query!(
+        "DELETE FROM email_verifications WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    // Acceptances stay on record without where they came from
+    sqlx::query!(
+        "UPDATE legal_acceptances SET ip_address = NULL, user_agent = NULL WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    match mode {
+        PurgeMode::Delete => {
+            sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(Error::from_sqlx)?;
+        }
+        PurgeMode::Anonymize => {
+            sqlx::query!(
+                r#"
+                UPDATE users
+                SET username = 'deleted_' || replace(id::text, '-', ''),
+                    email = 'deleted_' || replace(id::text, '-', '') || '@deleted.invalid',
+                    password_hash = $2,
+                    email_verified = false,
+                    avatar_url = NULL,
+                    profile = '{}',
+                    external_id = NULL,
+                    deleted_at = NULL,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+                user_id,
+                user_services::LOCKED_PASSWORD_HASH
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+        }
+    }
+
+    let purge = sqlx::query_as!(
+        UserPurge,
+        r#"
+        INSERT INTO user_purges
+            (user_id, mode, deleted_at, sessions_deleted, api_keys_deleted, tasks_deleted, tenant_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, user_id, mode, deleted_at, sessions_deleted, api_keys_deleted,
+                  tasks_deleted, purged_at
+        "#,
+        user_id,
+        mode.as_str(),
+        deleted_at,
+        sessions_deleted as i64,
+        api_keys_deleted as i64,
+        tasks_deleted as i64,
+        tenant_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    let keys = std::iter::once(avatar::avatar_key(user_id))
+        .chain(export_ids.into_iter().map(export::export_key));
+    for key in keys {
+        if let Err(e) = storage.delete(&key).await {
+            warn!(
+                "Failed to delete file {} of purged user {}: {}",
+                key, user_id, e
+            );
+        }
+    }
+
+    info!("Purged user {} ({})", user_id, mode.as_str());
+    Ok(Some(purge))
+}
+
+/// Purge audit records of the tenant's accounts, newest first
+pub async fn find_purges(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<UserPurge>> {
+    let limit = limit.unwrap_or(50).min(100);
+    let offset = offset.unwrap_or(0);
+
+    sqlx::query_as!(
+        UserPurge,
+        r#"
+        SELECT id, user_id, mode, deleted_at, sessions_deleted, api_keys_deleted,
+               tasks_deleted, purged_at
+        FROM user_purges
+        WHERE tenant_id = $1
+        ORDER BY purged_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        tenant_id,
+        limit,
+        offset
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
@@ -0,0 +1,201 @@
+//! Per-user limits on tasks, monitoring events and API keys
+//!
+//! Each limit defaults to the one configured for the user's role. The daily
+//! event limit is the user's ingestion quota, so it can also be managed
+//! through the monitoring quota endpoints.
+
+use crate::core::config::AppConfig;
+use crate::monitoring::quotas::quotas_reset_at;
+use crate::rbac::UserRole;
+use crate::tasks::limits::quota_limits_for_role;
+use crate::users::models::{UpdateUserQuotasRequest, UserQuota, UserQuotas};
+use crate::{DbConn, Error, Result};
+use chrono::Utc;
+use sqlx::Acquire;
+use uuid::Uuid;
+
+/// A user's limits and today's usage of them
+pub async fn get_user_quotas(
+    conn: &mut DbConn,
+    config: &AppConfig,
+    user_id: Uuid,
+) -> Result<UserQuotas> {
+    let now = Utc::now();
+    let day_start = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc();
+
+    let row = sqlx::query!(
+        r#"
+        SELECT u.role,
+               q.max_tasks_per_day, q.max_api_keys, q.updated_by, q.updated_at AS "updated_at?",
+               iq.daily_events, iq.updated_by AS events_updated_by,
+               iq.updated_at AS "events_updated_at?",
+               (SELECT COUNT(*) FROM tasks t
+                WHERE t.created_by = u.id AND t.created_at >= $2) AS "tasks_today!",
+               COALESCE((SELECT iu.events FROM ingestion_usage iu
+                         WHERE iu.scope = 'user' AND iu.key = u.id::TEXT AND iu.day = $3), 0)
+                   AS "events_today!",
+               (SELECT COUNT(*) FROM api_keys k
+                WHERE k.created_by = u.id AND k.is_active = true) AS "api_keys!"
+        FROM users u
+        LEFT JOIN user_quotas q ON q.user_id = u.id
+        LEFT JOIN ingestion_quotas iq ON iq.scope = 'user' AND iq.key = u.id::TEXT
+        WHERE u.id = $1 AND u.deleted_at IS NULL
+        "#,
+        user_id,
+        day_start,
+        now.date_naive()
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    let role = UserRole::from(row.role);
+    let tasks_default = quota_limits_for_role(&config.tasks.quotas, role).max_per_day;
+
+    // Either table may hold the latest change
+    let (updated_by, updated_at) = if row.events_updated_at > row.updated_at {
+        (row.events_updated_by, row.events_updated_at)
+    } else {
+        (row.updated_by, row.updated_at)
+    };
+
+    Ok(UserQuotas {
+        user_id,
+        role,
+        tasks_per_day: quota(
+            row.max_tasks_per_day.map(i64::from),
+            (tasks_default > 0).then_some(i64::from(tasks_default)),
+            row.tasks_today,
+        ),
+        events_per_day: quota(
+            row.daily_events,
+            config.monitoring.event_daily_quotas.for_role(role),
+            row.events_today,
+        ),
+        api_keys: quota(
+            row.max_api_keys.map(i64::from),
+            config.auth.api_key_quotas.for_role(role),
+            row.api_keys,
+        ),
+        daily_resets_at: quotas_reset_at(now),
+        updated_by,
+        updated_at,
+    })
+}
+
+fn quota(own: Option<i64>, role_default: Option<i64>, used: i64) -> UserQuota {
+    UserQuota {
+        limit: own.or(role_default),
+        role_default,
+        overridden: own.is_some(),
+        used,
+    }
+}
+
+/// Replace a user's own limits; those left out go back to the role's defaults
+///
+/// The user's daily metric quota, if any, is kept.
+pub async fn set_user_quotas(
+    conn: &mut DbConn,
+    config: &AppConfig,
+    user_id: Uuid,
+    request: UpdateUserQuotasRequest,
+    updated_by: Uuid,
+) -> Result<UserQuotas> {
+    request.validate()?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND deleted_at IS NULL) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if !exists {
+        return Err(Error::NotFound("User not found".to_string()));
+    }
+
+    if request.max_tasks_per_day.is_none() && request.max_api_keys.is_none() {
+        sqlx::query!("DELETE FROM user_quotas WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+    } else {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_quotas (user_id, max_tasks_per_day, max_api_keys, updated_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET max_tasks_per_day = EXCLUDED.max_tasks_per_day,
+                max_api_keys = EXCLUDED.max_api_keys,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            "#,
+            user_id,
+            request.max_tasks_per_day.map(|limit| limit as i32),
+            request.max_api_keys.map(|limit| limit as i32),
+            updated_by
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(Error::from_sqlx)?;
+    }
+
+    let key = user_id.to_string();
+    match request.max_events_per_day {
+        Some(limit) => {
+            sqlx::query!(
+                r#"
+                INSERT INTO ingestion_quotas (scope, key, daily_events, updated_by)
+                VALUES ('user', $1, $2, $3)
+                ON CONFLICT (scope, key) DO UPDATE
+                SET daily_events = EXCLUDED.daily_events,
+                    updated_by = EXCLUDED.updated_by,
+                    updated_at = NOW()
+                "#,
+                key,
+                limit,
+                updated_by
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+        }
+        None => {
+            sqlx::query!(
+                r#"
+                UPDATE ingestion_quotas
+                SET daily_events = NULL, updated_by = $2, updated_at = NOW()
+                WHERE scope = 'user' AND key = $1 AND daily_events IS NOT NULL
+                "#,
+                key,
+                updated_by
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+            sqlx::query!(
+                r#"
+                DELETE FROM ingestion_quotas
+                WHERE scope = 'user' AND key = $1
+                  AND daily_events IS NULL AND daily_metrics IS NULL
+                "#,
+                key
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(Error::from_sqlx)?;
+        }
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    get_user_quotas(conn, config, user_id).await
+}
@@ -0,0 +1,92 @@
+use crate::rbac::audit::{self as rbac_audit, RbacChange};
+use crate::rbac::models::RbacAuditAction;
+use crate::users::activity;
+use crate::users::models::UserActivityAction;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::Acquire;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+const REVERT_REASON: &str = "Temporary role expired";
+
+/// Background job that restores the previous role once a temporary role ends
+pub async fn role_expiry_job(pool: DbPool, run_interval: Duration) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            expire_roles(conn.as_mut(), Utc::now()).await
+        }
+        .await;
+        match result {
+            Ok(reverted) if !reverted.is_empty() => {
+                info!("Role expiry: {} temporary roles reverted", reverted.len())
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to expire temporary roles: {}", e),
+        }
+    }
+}
+
+/// Revert every temporary role that expires at `now` or earlier
+///
+/// Each reversion is recorded in the RBAC audit log, in the same transaction,
+/// and in the account's activity trail.
+pub async fn expire_roles(conn: &mut DbConn, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let reverted = sqlx::query!(
+        r#"
+        UPDATE users u
+        SET role = old.role_revert_to, role_expires_at = NULL, role_revert_to = NULL,
+            updated_at = NOW()
+        FROM users old
+        WHERE u.id = old.id AND u.role_expires_at <= $1
+        RETURNING u.id, old.role AS expired_role, u.role, old.role_expires_at
+        "#,
+        now
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    for user in &reverted {
+        rbac_audit::record_change(
+            &mut tx,
+            RbacChange {
+                actor_id: None,
+                action: RbacAuditAction::UserRoleExpired,
+                target_id: user.id,
+                before: Some(json!({
+                    "role": user.expired_role,
+                    "expires_at": user.role_expires_at,
+                })),
+                after: Some(json!({ "role": user.role })),
+                reason: Some(REVERT_REASON.to_string()),
+            },
+        )
+        .await?;
+    }
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    let mut ids = Vec::with_capacity(reverted.len());
+    for user in reverted {
+        activity::record_activity(
+            conn,
+            user.id,
+            None,
+            UserActivityAction::RoleChanged,
+            json!({ "from": user.expired_role, "to": user.role, "reason": REVERT_REASON }),
+        )
+        .await;
+        ids.push(user.id);
+    }
+    Ok(ids)
+}
@@ -1,10 +1,20 @@
+use crate::core::config::AuthConfig;
 use crate::rbac::UserRole;
-use crate::users::models::{CreateUserRequest, User, UserProfile};
+use crate::rbac::audit::{self as rbac_audit, RbacChange};
+use crate::rbac::models::RbacAuditAction;
+use crate::storage::FileStorage;
+use crate::users::deactivation;
+use crate::users::models::{
+    CreateUserRequest, DeactivationSummary, MAX_INTERNAL_METADATA_BYTES, PaginationInfo,
+    ProfileField, UpsertProfileFieldRequest, User, UserFilter, UserListResponse, UserProfile,
+    UserStatusUpdate, merge_profile, visible_profile,
+};
 use crate::{DbConn, Error, Result};
 use argon2::password_hash::{SaltString, rand_core::OsRng};
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
-use chrono::Utc;
-use sqlx::Acquire;
+use chrono::{DateTime, Utc};
+use sqlx::{Acquire, Postgres, QueryBuilder};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 pub async fn find_user_by_email(conn: &mut DbConn, email: &str) -> Result<Option<User>> {
@@ -13,7 +23,8 @@ pub async fn find_user_by_email(conn: &mut DbConn, email: &str) -> Result<Option
         r#"
         SELECT id, username, email, password_hash, 
                role, is_active, email_verified,
-               created_at, updated_at, last_login_at
+               created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+               role_expires_at, is_service_account
         FROM users 
         WHERE email = $1 AND is_active = true
         "#,
@@ -32,7 +43,8 @@ pub async fn find_user_by_username(conn: &mut DbConn, username: &str) -> Result<
         r#"
         SELECT id, username, email, password_hash,
                role, is_active, email_verified,
-               created_at, updated_at, last_login_at
+               created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+               role_expires_at, is_service_account
         FROM users 
         WHERE username = $1 AND is_active = true
         "#,
@@ -51,7 +63,8 @@ pub async fn find_user_by_id(conn: &mut DbConn, user_id: Uuid) -> Result<Option<
         r#"
         SELECT id, username, email, password_hash,
                role, is_active, email_verified,
-               created_at, updated_at, last_login_at
+               created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+               role_expires_at, is_service_account
         FROM users 
         WHERE id = $1 AND is_active = true
         "#,
@@ -64,28 +77,39 @@ pub async fn find_user_by_id(conn: &mut DbConn, user_id: Uuid) -> Result<Option<
     Ok(user)
 }
 
-pub async fn create_user(conn: &mut DbConn, req: CreateUserRequest) -> Result<UserProfile> {
+/// Argon2 hash of a password, with a fresh salt
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Create a user in the tenant
+pub async fn create_user(
+    conn: &mut DbConn,
+    req: CreateUserRequest,
+    tenant_id: Uuid,
+) -> Result<UserProfile> {
     req.validate()?;
 
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let password_hash = argon2
-        .hash_password(req.password.as_bytes(), &salt)?
-        .to_string();
+    let password_hash = hash_password(&req.password)?;
 
     let user = sqlx::query_as!(
         User,
         r#"
-        INSERT INTO users (username, email, password_hash, role)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO users (username, email, password_hash, role, tenant_id)
+        VALUES ($1, $2, $3, $4, $5)
         RETURNING id, username, email, password_hash,
                   role, is_active, email_verified,
-                  created_at, updated_at, last_login_at
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
         "#,
         req.username,
         req.email,
         password_hash,
-        req.role.unwrap_or(UserRole::User).to_string()
+        req.role.unwrap_or(UserRole::User).to_string(),
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -139,7 +163,24 @@ pub async fn is_username_available(conn: &mut DbConn, username: &str) -> Result<
     }
 }
 
+/// Whether an account exists, including deactivated and deleted ones
+pub async fn user_exists(conn: &mut DbConn, user_id: Uuid) -> Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Stored instead of a hash for accounts that can't log in with a password
+pub const LOCKED_PASSWORD_HASH: &str = "!";
+
 pub fn verify_password(password: &str, password_hash: &str) -> Result<bool> {
+    if password_hash == LOCKED_PASSWORD_HASH {
+        return Ok(false);
+    }
     let parsed_hash = PasswordHash::new(password_hash)
         .map_err(|_| Error::Internal("Invalid password hash".to_string()))?;
 
@@ -148,33 +189,102 @@ pub fn verify_password(password: &str, password_hash: &str) -> Result<bool> {
         .is_ok())
 }
 
-pub async fn list_users(
-    conn: &mut DbConn,
-    limit: Option<i64>,
-    offset: Option<i64>,
-) -> Result<Vec<UserProfile>> {
-    let limit = limit.unwrap_or(50).min(100); // Default 50, max 100
-    let offset = offset.unwrap_or(0);
+/// Append the WHERE conditions of `filter` to a query over `users`
+///
+/// Service accounts are always left out; they are listed on their own.
+pub(crate) fn push_user_filters(
+    query_builder: &mut QueryBuilder<'static, Postgres>,
+    filter: &UserFilter,
+) {
+    query_builder.push(" WHERE is_service_account = false AND tenant_id = ");
+    query_builder.push_bind(filter.tenant_id);
+
+    if let Some(search) = filter
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        // Match the text literally, not as a LIKE pattern
+        let escaped = search
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{escaped}%");
+        query_builder.push(" AND (username ILIKE ");
+        query_builder.push_bind(pattern.clone());
+        query_builder.push(" OR email ILIKE ");
+        query_builder.push_bind(pattern);
+        query_builder.push(")");
+    }
 
-    let users = sqlx::query_as!(
-        User,
-        r#"
-        SELECT id, username, email, password_hash,
-               role, is_active, email_verified,
-               created_at, updated_at, last_login_at
-        FROM users 
-        WHERE is_active = true
-        ORDER BY created_at DESC
-        LIMIT $1 OFFSET $2
-        "#,
-        limit,
-        offset
-    )
-    .fetch_all(&mut *conn)
-    .await
-    .map_err(Error::from_sqlx)?;
+    if let Some(role) = filter.role {
+        query_builder.push(" AND role = ");
+        query_builder.push_bind(role.to_string());
+    }
+
+    if let Some(is_active) = filter.is_active {
+        query_builder.push(" AND is_active = ");
+        query_builder.push_bind(is_active);
+    }
+
+    if let Some(email_verified) = filter.email_verified {
+        query_builder.push(" AND email_verified = ");
+        query_builder.push_bind(email_verified);
+    }
+
+    if let Some(created_after) = filter.created_after {
+        query_builder.push(" AND created_at >= ");
+        query_builder.push_bind(created_after);
+    }
+
+    if let Some(created_before) = filter.created_before {
+        query_builder.push(" AND created_at <= ");
+        query_builder.push_bind(created_before);
+    }
+}
+
+/// Search users, returning one page and the total number of matches
+pub async fn list_users(conn: &mut DbConn, filter: UserFilter) -> Result<UserListResponse> {
+    let limit = filter.limit.unwrap_or(50).clamp(1, 100); // Default 50, max 100
+    let offset = filter.offset.unwrap_or(0).max(0);
+    let order = filter.order.unwrap_or_else(|| filter.sort.default_order());
+
+    let mut query_builder = QueryBuilder::new(
+        "SELECT id, username, email, password_hash, role, is_active, email_verified, \
+         created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at, \
+         role_expires_at, is_service_account FROM users",
+    );
+    push_user_filters(&mut query_builder, &filter);
+    // Users who never logged in sort last either way; id keeps pages stable
+    query_builder.push(format!(
+        " ORDER BY {} {} NULLS LAST, id",
+        filter.sort.as_str(),
+        order.as_sql()
+    ));
+    query_builder.push(" LIMIT ");
+    query_builder.push_bind(limit);
+    query_builder.push(" OFFSET ");
+    query_builder.push_bind(offset);
+
+    let users: Vec<User> = query_builder
+        .build_query_as()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
 
-    Ok(users.into_iter().map(|u| u.to_profile()).collect())
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM users");
+    push_user_filters(&mut count_builder, &filter);
+    let total_count: i64 = count_builder
+        .build_query_scalar()
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    Ok(UserListResponse {
+        items: users.into_iter().map(|u| u.to_profile()).collect(),
+        pagination: PaginationInfo::new(total_count, limit, offset),
+    })
 }
 
 // New service functions for user management
@@ -183,9 +293,17 @@ pub async fn update_user_profile(
     conn: &mut DbConn,
     user_id: Uuid,
     req: crate::users::models::UpdateProfileRequest,
+    config: &AuthConfig,
 ) -> Result<UserProfile> {
     req.validate()?;
 
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let previous_username = locked_username(&mut tx, user_id).await?;
+    if let Some(username) = req.username.as_deref().filter(|u| *u != previous_username) {
+        check_username_change(&mut tx, user_id, username, config, Utc::now()).await?;
+    }
+    let profile = merged_profile(&mut tx, user_id, req.profile.as_ref(), false).await?;
+
     // Update user profile
     let user = sqlx::query_as!(
         User,
@@ -197,15 +315,337 @@ pub async fn update_user_profile(
                 WHEN $3 IS NOT NULL AND $3 != email THEN false 
                 ELSE email_verified 
             END,
+            profile = COALESCE($4, profile),
             updated_at = NOW()
         WHERE id = $1 AND is_active = true
         RETURNING id, username, email, password_hash,
                   role, is_active, email_verified,
-                  created_at, updated_at, last_login_at
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
         "#,
         user_id,
         req.username,
-        req.email
+        req.email,
+        profile
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    record_username_change(&mut tx, user_id, &previous_username, &user.username).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(user.to_profile())
+}
+
+/// Current username of a user, locking the row until the transaction ends
+pub async fn locked_username(conn: &mut DbConn, user_id: Uuid) -> Result<String> {
+    sqlx::query_scalar!(
+        "SELECT username FROM users WHERE id = $1 FOR UPDATE",
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))
+}
+
+/// Check that a user may rename themselves to `username`
+///
+/// Renames are limited to one per cooldown, and names other accounts gave
+/// up within the reservation period can't be taken.
+async fn check_username_change(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    username: &str,
+    config: &AuthConfig,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if config.username_change_cooldown_days > 0 {
+        let last_change = sqlx::query_scalar!(
+            "SELECT MAX(changed_at) FROM username_history WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+        if let Some(last_change) = last_change {
+            let allowed_at =
+                last_change + chrono::Duration::days(config.username_change_cooldown_days as i64);
+            if allowed_at > now {
+                return Err(Error::validation(
+                    "username",
+                    &format!(
+                        "Username can be changed again after {}",
+                        allowed_at.to_rfc3339()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let reserved_since = (config.username_reservation_days > 0)
+        .then(|| now - chrono::Duration::days(config.username_reservation_days as i64));
+    let reserved = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM username_history
+            WHERE lower(username) = lower($1)
+              AND user_id <> $2
+              AND ($3::timestamptz IS NULL OR changed_at > $3)
+        ) AS "reserved!"
+        "#,
+        username,
+        user_id,
+        reserved_since
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    if reserved {
+        return Err(Error::UsernameAlreadyExists);
+    }
+
+    Ok(())
+}
+
+/// Keep `previous` in the user's username history if it was replaced
+pub async fn record_username_change(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    previous: &str,
+    current: &str,
+) -> Result<()> {
+    if previous == current {
+        return Ok(());
+    }
+    sqlx::query!(
+        "INSERT INTO username_history (user_id, username) VALUES ($1, $2)",
+        user_id,
+        previous
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+    Ok(())
+}
+
+/// Validate requested profile changes against the schema and stored values
+///
+/// Locks the user row so concurrent updates don't drop each other's changes.
+async fn merged_profile(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    changes: Option<&serde_json::Map<String, serde_json::Value>>,
+    admin: bool,
+) -> Result<Option<serde_json::Value>> {
+    let Some(changes) = changes else {
+        return Ok(None);
+    };
+    let fields = find_profile_fields(conn).await?;
+    let current = sqlx::query_scalar!(
+        "SELECT profile FROM users WHERE id = $1 FOR UPDATE",
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    merge_profile(&fields, &current, changes, admin).map(Some)
+}
+
+pub async fn find_profile_fields(conn: &mut DbConn) -> Result<Vec<ProfileField>> {
+    sqlx::query_as!(
+        ProfileField,
+        r#"
+        SELECT name, label, field_type, required, visibility, options, created_at, updated_at
+        FROM profile_fields
+        ORDER BY created_at, name
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Create a profile field or replace its definition
+///
+/// Stored values are left alone; ones that no longer fit are reported
+/// the next time the user changes that field.
+pub async fn upsert_profile_field(
+    conn: &mut DbConn,
+    name: &str,
+    req: UpsertProfileFieldRequest,
+) -> Result<ProfileField> {
+    req.validate(name)?;
+    let options: Vec<String> = req
+        .options
+        .iter()
+        .map(|option| option.trim().to_string())
+        .collect();
+
+    sqlx::query_as!(
+        ProfileField,
+        r#"
+        INSERT INTO profile_fields (name, label, field_type, required, visibility, options)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (name) DO UPDATE
+        SET label = EXCLUDED.label,
+            field_type = EXCLUDED.field_type,
+            required = EXCLUDED.required,
+            visibility = EXCLUDED.visibility,
+            options = EXCLUDED.options,
+            updated_at = NOW()
+        RETURNING name, label, field_type, required, visibility, options, created_at, updated_at
+        "#,
+        name,
+        req.label.trim(),
+        req.field_type.as_str(),
+        req.required,
+        req.visibility.as_str(),
+        &options
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)
+}
+
+/// Delete a profile field; stored values are hidden rather than erased
+pub async fn delete_profile_field(conn: &mut DbConn, name: &str) -> Result<()> {
+    let result = sqlx::query!("DELETE FROM profile_fields WHERE name = $1", name)
+        .execute(&mut *conn)
+        .await
+        .map_err(Error::from_sqlx)?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("Profile field not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Fill in the custom fields each profile's viewer may see
+///
+/// `staff` viewers (moderators and admins) also see staff fields.
+pub async fn attach_profiles(
+    conn: &mut DbConn,
+    profiles: &mut [UserProfile],
+    staff: bool,
+) -> Result<()> {
+    if profiles.is_empty() {
+        return Ok(());
+    }
+    let fields = find_profile_fields(conn).await?;
+    let ids: Vec<Uuid> = profiles.iter().map(|p| p.id).collect();
+    let stored: HashMap<Uuid, (serde_json::Value, serde_json::Value)> = sqlx::query!(
+        "SELECT id, profile, internal_metadata FROM users WHERE id = ANY($1)",
+        &ids
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .into_iter()
+    .map(|row| (row.id, (row.profile, row.internal_metadata)))
+    .collect();
+
+    for profile in profiles.iter_mut() {
+        let stored = stored.get(&profile.id);
+        profile.profile = Some(
+            stored
+                .map(|(values, _)| visible_profile(&fields, values, staff))
+                .unwrap_or_else(|| serde_json::json!({})),
+        );
+        // Never set for other viewers, so it can't reach them through any response
+        profile.internal_metadata = if staff {
+            Some(
+                stored
+                    .map(|(_, metadata)| metadata.clone())
+                    .unwrap_or_else(|| serde_json::json!({})),
+            )
+        } else {
+            None
+        };
+    }
+    Ok(())
+}
+
+/// Merge changes into a user's staff-only metadata
+pub async fn update_internal_metadata(
+    conn: &mut DbConn,
+    user_id: Uuid,
+    req: crate::users::models::UpdateInternalMetadataRequest,
+) -> Result<UserProfile> {
+    req.validate()?;
+    let changes = req
+        .internal_metadata
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    let (removed, set): (Vec<_>, Vec<_>) = changes.into_iter().partition(|(_, v)| v.is_null());
+    let removed: Vec<String> = removed.into_iter().map(|(key, _)| key).collect();
+    let set = serde_json::Value::Object(set.into_iter().collect());
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let size = sqlx::query_scalar!(
+        r#"
+        UPDATE users
+        SET internal_metadata = (internal_metadata || $2) - $3::TEXT[], updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING octet_length(internal_metadata::TEXT) AS "size!"
+        "#,
+        user_id,
+        set,
+        &removed
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    if size as usize > MAX_INTERNAL_METADATA_BYTES {
+        return Err(Error::validation(
+            "internal_metadata",
+            &format!("Metadata can be at most {MAX_INTERNAL_METADATA_BYTES} bytes"),
+        ));
+    }
+
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    let mut profile = get_user_profile(conn, user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+    attach_profiles(conn, std::slice::from_mut(&mut profile), true).await?;
+    Ok(profile)
+}
+
+/// Store an already processed avatar and link it from the profile
+///
+/// The URL carries a version so clients refetch after a new upload.
+pub async fn set_user_avatar(
+    conn: &mut DbConn,
+    storage: &dyn FileStorage,
+    user_id: Uuid,
+    png: Vec<u8>,
+) -> Result<UserProfile> {
+    let key = crate::users::avatar::avatar_key(user_id);
+    storage.put(&key, png).await?;
+    let avatar_url = format!("{}?v={}", storage.url(&key), Utc::now().timestamp_millis());
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users
+        SET avatar_url = $2, updated_at = NOW()
+        WHERE id = $1 AND is_active = true
+        RETURNING id, username, email, password_hash,
+                  role, is_active, email_verified,
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
+        "#,
+        user_id,
+        avatar_url
     )
     .fetch_optional(&mut *conn)
     .await
@@ -256,50 +696,6 @@ pub async fn change_user_password(
     Ok(())
 }
 
-pub async fn delete_user_account(
-    conn: &mut DbConn,
-    user_id: Uuid,
-    req: crate::users::models::DeleteAccountRequest,
-) -> Result<()> {
-    req.validate()?;
-
-    // Get current user to verify password
-    let user = find_user_by_id(conn, user_id).await?;
-    let user = match user {
-        Some(user) => user,
-        None => return Err(Error::NotFound("User not found".to_string())),
-    };
-
-    // Verify password
-    if !verify_password(&req.password, &user.password_hash)? {
-        return Err(Error::validation("password", "Invalid password"));
-    }
-
-    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
-
-    // Soft delete user (deactivate)
-    sqlx::query!(
-        "UPDATE users SET is_active = false, updated_at = NOW() WHERE id = $1",
-        user_id
-    )
-    .execute(&mut *tx)
-    .await
-    .map_err(Error::from_sqlx)?;
-
-    // Invalidate all user sessions
-    sqlx::query!(
-        "UPDATE sessions SET is_active = false WHERE user_id = $1",
-        user_id
-    )
-    .execute(&mut *tx)
-    .await
-    .map_err(Error::from_sqlx)?;
-
-    tx.commit().await.map_err(Error::from_sqlx)?;
-
-    Ok(())
-}
-
 pub async fn update_user_profile_admin(
     conn: &mut DbConn,
     user_id: Uuid,
@@ -307,7 +703,11 @@ pub async fn update_user_profile_admin(
 ) -> Result<UserProfile> {
     req.validate()?;
 
-    // Update user profile (admin can update email_verified)
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let previous_username = locked_username(&mut tx, user_id).await?;
+    let profile = merged_profile(&mut tx, user_id, req.profile.as_ref(), true).await?;
+
+    // Update user profile (admin can update email_verified and staff fields)
     let user = sqlx::query_as!(
         User,
         r#"
@@ -315,46 +715,60 @@ pub async fn update_user_profile_admin(
         SET username = COALESCE($2, username),
             email = COALESCE($3, email),
             email_verified = COALESCE($4, email_verified),
+            profile = COALESCE($5, profile),
             updated_at = NOW()
         WHERE id = $1
         RETURNING id, username, email, password_hash,
                   role, is_active, email_verified,
-                  created_at, updated_at, last_login_at
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
         "#,
         user_id,
         req.username,
         req.email,
-        req.email_verified
+        req.email_verified,
+        profile
     )
-    .fetch_optional(&mut *conn)
+    .fetch_optional(&mut *tx)
     .await
-    .map_err(Error::from_sqlx)?;
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
 
-    match user {
-        Some(user) => Ok(user.to_profile()),
-        None => Err(Error::NotFound("User not found".to_string())),
-    }
+    // Admin renames skip the cooldown, but the old name is still reserved
+    record_username_change(&mut tx, user_id, &previous_username, &user.username).await?;
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(user.to_profile())
 }
 
+/// Activate or deactivate an account; deactivating also revokes its access
 pub async fn update_user_status(
     conn: &mut DbConn,
     user_id: Uuid,
     req: crate::users::models::UpdateUserStatusRequest,
-) -> Result<UserProfile> {
+) -> Result<UserStatusUpdate> {
+    req.validate(Utc::now())?;
+
     let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
 
     let user = sqlx::query_as!(
         User,
         r#"
         UPDATE users 
-        SET is_active = $2, updated_at = NOW()
+        SET is_active = $2,
+            -- Reactivating a deleted account cancels its purge
+            deleted_at = CASE WHEN $2 THEN NULL ELSE deleted_at END,
+            suspended_until = $3,
+            updated_at = NOW()
         WHERE id = $1
         RETURNING id, username, email, password_hash,
                   role, is_active, email_verified,
-                  created_at, updated_at, last_login_at
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
         "#,
         user_id,
-        req.is_active
+        req.is_active,
+        req.suspended_until
     )
     .fetch_optional(&mut *tx)
     .await
@@ -362,19 +776,17 @@ pub async fn update_user_status(
 
     match user {
         Some(user) => {
-            // If deactivating user, invalidate all sessions
-            if !req.is_active {
-                sqlx::query!(
-                    "UPDATE sessions SET is_active = false WHERE user_id = $1",
-                    user_id
-                )
-                .execute(&mut *tx)
-                .await
-                .map_err(Error::from_sqlx)?;
-            }
+            let deactivation = if req.is_active {
+                None
+            } else {
+                Some(deactivation::revoke_access(&mut tx, user_id, req.reassign_to).await?)
+            };
 
             tx.commit().await.map_err(Error::from_sqlx)?;
-            Ok(user.to_profile())
+            Ok(UserStatusUpdate {
+                user: user.to_profile(),
+                deactivation,
+            })
         }
         None => {
             if let Err(rollback_error) = tx.rollback().await {
@@ -385,32 +797,87 @@ pub async fn update_user_status(
     }
 }
 
+/// Change a user's built-in role, for good or until `req.expires_at`, and record it
+/// in the RBAC audit log
+///
+/// A temporary role reverts to the role held before it, even when it replaces
+/// another temporary role; a permanent change drops any pending reversion.
 pub async fn update_user_role(
     conn: &mut DbConn,
     user_id: Uuid,
     req: crate::users::models::UpdateUserRoleRequest,
+    actor_id: Uuid,
 ) -> Result<UserProfile> {
+    req.validate(Utc::now())?;
+
+    let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+
+    let previous = sqlx::query!(
+        "SELECT role, role_expires_at, role_revert_to FROM users WHERE id = $1 FOR UPDATE",
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::NotFound("User not found".to_string()))?;
+
+    let revert_to = req.expires_at.map(|_| {
+        previous
+            .role_revert_to
+            .clone()
+            .unwrap_or(previous.role.clone())
+    });
+    if revert_to.as_deref() == Some(req.role.as_str()) {
+        return Err(Error::validation(
+            "expires_at",
+            "A temporary role must differ from the role it reverts to",
+        ));
+    }
+
     let user = sqlx::query_as!(
         User,
         r#"
         UPDATE users 
-        SET role = $2, updated_at = NOW()
+        SET role = $2, role_expires_at = $3, role_revert_to = $4, updated_at = NOW()
         WHERE id = $1
         RETURNING id, username, email, password_hash,
                   role, is_active, email_verified,
-                  created_at, updated_at, last_login_at
+                  created_at, updated_at, last_login_at, avatar_url, profile, suspended_until, last_seen_at,
+                  role_expires_at, is_service_account
         "#,
         user_id,
-        req.role.to_string()
+        req.role.to_string(),
+        req.expires_at,
+        revert_to
     )
-    .fetch_optional(&mut *conn)
+    .fetch_one(&mut *tx)
     .await
     .map_err(Error::from_sqlx)?;
 
-    match user {
-        Some(user) => Ok(user.to_profile()),
-        None => Err(Error::NotFound("User not found".to_string())),
+    if previous.role != user.role.as_str() || previous.role_expires_at != user.role_expires_at {
+        rbac_audit::record_change(
+            &mut tx,
+            RbacChange {
+                actor_id: Some(actor_id),
+                action: RbacAuditAction::UserRoleChanged,
+                target_id: user_id,
+                before: Some(serde_json::json!({
+                    "role": previous.role,
+                    "expires_at": previous.role_expires_at,
+                })),
+                after: Some(serde_json::json!({
+                    "role": user.role,
+                    "expires_at": user.role_expires_at,
+                    "revert_to": revert_to,
+                })),
+                reason: req.reason,
+            },
+        )
+        .await?;
     }
+    tx.commit().await.map_err(Error::from_sqlx)?;
+
+    Ok(user.to_profile())
 }
 
 pub async fn reset_user_password(
@@ -460,14 +927,16 @@ pub async fn reset_user_password(
     Ok(())
 }
 
+/// Delete an account, revoking its access and handing over its open work first
 pub async fn delete_user_admin(
     conn: &mut DbConn,
     user_id: Uuid,
     req: crate::users::models::DeleteUserRequest,
-) -> Result<()> {
+) -> Result<DeactivationSummary> {
     let hard_delete = req.hard_delete.unwrap_or(false);
 
     let mut tx = conn.begin().await.map_err(Error::from_sqlx)?;
+    let summary = deactivation::revoke_access(&mut tx, user_id, req.reassign_to).await?;
 
     if hard_delete {
         // Hard delete - permanently remove user and all related data
@@ -490,9 +959,9 @@ pub async fn delete_user_admin(
             return Err(Error::NotFound("User not found".to_string()));
         }
     } else {
-        // Soft delete - deactivate user (only if currently active)
+        // Soft delete - deactivate user (only if currently active) until it is purged
         let result = sqlx::query!(
-            "UPDATE users SET is_active = false, updated_at = NOW() WHERE id = $1 AND is_active = true",
+            "UPDATE users SET is_active = false, deleted_at = NOW(), updated_at = NOW() WHERE id = $1 AND is_active = true",
             user_id
         )
         .execute(&mut *tx)
@@ -505,41 +974,38 @@ pub async fn delete_user_admin(
             }
             return Err(Error::NotFound("User not found".to_string()));
         }
-
-        // Invalidate all user sessions
-        sqlx::query!(
-            "UPDATE sessions SET is_active = false WHERE user_id = $1",
-            user_id
-        )
-        .execute(&mut *tx)
-        .await
-        .map_err(Error::from_sqlx)?;
     }
 
     tx.commit().await.map_err(Error::from_sqlx)?;
 
-    Ok(())
+    Ok(summary)
 }
 
-pub async fn get_user_stats(conn: &mut DbConn) -> Result<crate::users::models::UserStats> {
+/// User counts of the tenant
+pub async fn get_user_stats(
+    conn: &mut DbConn,
+    tenant_id: Uuid,
+) -> Result<crate::users::models::UserStats> {
     // Get basic user counts
-    let total_users = sqlx::query_scalar!("SELECT COUNT(*) FROM users")
-        .fetch_one(&mut *conn)
-        .await
-        .map_err(Error::from_sqlx)?
-        .ok_or_else(|| Error::Internal("Total users count query returned null".to_string()))?;
+    let total_users =
+        sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1", tenant_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(Error::from_sqlx)?
+            .ok_or_else(|| Error::Internal("Total users count query returned null".to_string()))?;
 
-    let active_users = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE is_active = true")
-        .fetch_one(&mut *conn)
-        .await
-        .map_err(Error::from_sqlx)?
-        .ok_or_else(|| Error::Internal("Active users count query returned null".to_string()))?;
+    let active_users = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND is_active = true",
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::Internal("Active users count query returned null".to_string()))?;
 
     let inactive_users = total_users - active_users;
 
-    let email_verified = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM users WHERE email_verified = true AND is_active = true"
-    )
+    let email_verified = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND email_verified = true AND is_active = true", tenant_id)
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?
@@ -548,48 +1014,44 @@ pub async fn get_user_stats(conn: &mut DbConn) -> Result<crate::users::models::U
     let email_unverified = active_users - email_verified;
 
     // Get user counts by role
-    let user_count =
-        sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE role = 'user' AND is_active = true")
-            .fetch_one(&mut *conn)
-            .await
-            .map_err(Error::from_sqlx)?
-            .ok_or_else(|| Error::Internal("User role count query returned null".to_string()))?;
-
-    let moderator_count = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM users WHERE role = 'moderator' AND is_active = true"
+    let user_count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND role = 'user' AND is_active = true",
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::Internal("User role count query returned null".to_string()))?;
+
+    let moderator_count = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND role = 'moderator' AND is_active = true", tenant_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
     .ok_or_else(|| Error::Internal("Moderator role count query returned null".to_string()))?;
 
-    let admin_count =
-        sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE role = 'admin' AND is_active = true")
-            .fetch_one(&mut *conn)
-            .await
-            .map_err(Error::from_sqlx)?
-            .ok_or_else(|| Error::Internal("Admin role count query returned null".to_string()))?;
+    let admin_count = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND role = 'admin' AND is_active = true",
+        tenant_id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?
+    .ok_or_else(|| Error::Internal("Admin role count query returned null".to_string()))?;
 
     // Get recent registrations
-    let last_24h = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM users WHERE created_at > NOW() - INTERVAL '24 hours'"
-    )
+    let last_24h = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND created_at > NOW() - INTERVAL '24 hours'", tenant_id)
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?
     .ok_or_else(|| Error::Internal("24-hour registration count query returned null".to_string()))?;
 
-    let last_7d = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM users WHERE created_at > NOW() - INTERVAL '7 days'"
-    )
+    let last_7d = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND created_at > NOW() - INTERVAL '7 days'", tenant_id)
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?
     .ok_or_else(|| Error::Internal("7-day registration count query returned null".to_string()))?;
 
-    let last_30d = sqlx::query_scalar!(
-        "SELECT COUNT(*) FROM users WHERE created_at > NOW() - INTERVAL '30 days'"
-    )
+    let last_30d = sqlx::query_scalar!("SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND created_at > NOW() - INTERVAL '30 days'", tenant_id)
     .fetch_one(&mut *conn)
     .await
     .map_err(Error::from_sqlx)?
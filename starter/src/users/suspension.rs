@@ -0,0 +1,75 @@
+use crate::users::activity;
+use crate::users::models::UserActivityAction;
+use crate::{DbConn, DbPool, Error, Result};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// Background job that reactivates accounts whose suspension has ended
+pub async fn suspension_expiry_job(pool: DbPool, run_interval: Duration) {
+    let mut interval = interval(run_interval);
+
+    loop {
+        interval.tick().await;
+
+        let result = async {
+            let mut conn = pool.acquire().await.map_err(Error::from_sqlx)?;
+            expire_suspensions(conn.as_mut(), Utc::now()).await
+        }
+        .await;
+        match result {
+            Ok(reactivated) if !reactivated.is_empty() => {
+                info!(
+                    "Suspension expiry: {} accounts reactivated",
+                    reactivated.len()
+                )
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to expire suspensions: {}", e),
+        }
+    }
+}
+
+/// Reactivate every account suspended until `now` or earlier
+///
+/// Deleted accounts stay inactive. Each reactivation is recorded in the
+/// account's activity trail.
+pub async fn expire_suspensions(conn: &mut DbConn, now: DateTime<Utc>) -> Result<Vec<Uuid>> {
+    let reactivated = sqlx::query!(
+        r#"
+        UPDATE users u
+        SET is_active = true, suspended_until = NULL, updated_at = NOW()
+        FROM users old
+        WHERE u.id = old.id
+          AND u.is_active = false
+          AND u.deleted_at IS NULL
+          AND u.suspended_until <= $1
+        RETURNING u.id, old.suspended_until
+        "#,
+        now
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(Error::from_sqlx)?;
+
+    let mut ids = Vec::with_capacity(reactivated.len());
+    for user in reactivated {
+        activity::record_activity(
+            conn,
+            user.id,
+            None,
+            UserActivityAction::StatusChanged,
+            json!({
+                "is_active": true,
+                "reason": "Suspension ended",
+                "suspended_until": user.suspended_until,
+            }),
+        )
+        .await;
+        ids.push(user.id);
+    }
+    Ok(ids)
+}
@@ -53,19 +53,171 @@ async fn test_api_content_type_json() {
 
 #[tokio::test]
 async fn test_api_rate_limiting() {
-    let app = spawn_app().await;
-
-    // Make multiple rapid requests
-    let mut responses = Vec::new();
-    for _ in 0..20 {
+    let app = spawn_app_with_config(|config| {
+        config.rate_limit.enabled = true;
+        config.rate_limit.public.requests_per_minute = 6;
+        config.rate_limit.public.burst = 3;
+    })
+    .await;
+
+    let mut remaining = Vec::new();
+    for _ in 0..3 {
         let response = app.get("/api/v1/health").await;
-        responses.push(response.status());
+        assert_status(&response, StatusCode::OK);
+        assert_eq!(response.headers()["x-ratelimit-limit"], "3");
+        remaining.push(response.headers()["x-ratelimit-remaining"].clone());
     }
+    assert_eq!(remaining, ["2", "1", "0"]);
+
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["x-ratelimit-remaining"], "0");
+    assert_eq!(response.headers()["retry-after"], "10");
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "RATE_LIMITED");
+}
 
-    // All requests should succeed (basic rate limiting test)
-    for status in responses {
-        assert_eq!(status, StatusCode::OK);
+#[tokio::test]
+async fn test_api_rate_limiting_ignores_untrusted_forwarded_headers() {
+    // One request a minute, so the burst can't refill while the test runs
+    let app = spawn_app_with_config(|config| {
+        config.rate_limit.enabled = true;
+        config.rate_limit.public.requests_per_minute = 1;
+        config.rate_limit.public.burst = 2;
+    })
+    .await;
+    let health = |forwarded_for: String| {
+        app.client
+            .get(format!("{}/api/v1/health", app.address))
+            .header("X-Forwarded-For", forwarded_for.clone())
+            .header("X-Real-IP", forwarded_for)
+            .send()
+    };
+
+    // A new forwarded address on every request doesn't give a new limit
+    for i in 0..2 {
+        let response = health(format!("203.0.113.{i}")).await.unwrap();
+        assert_status(&response, StatusCode::OK);
     }
+    let response = health("203.0.113.99".to_string()).await.unwrap();
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_api_rate_limiting_behind_trusted_proxy() {
+    let app = spawn_app_with_config(|config| {
+        config.rate_limit.enabled = true;
+        config.rate_limit.public.requests_per_minute = 6;
+        config.rate_limit.public.burst = 1;
+        config.server.trusted_proxies = vec!["127.0.0.0/8".to_string()];
+    })
+    .await;
+    let health = |forwarded_for: &str| {
+        app.client
+            .get(format!("{}/api/v1/health", app.address))
+            .header("X-Forwarded-For", forwarded_for)
+            .send()
+    };
+
+    // Clients behind the proxy are told apart by the address it forwards
+    let response = health("203.0.113.1").await.unwrap();
+    assert_status(&response, StatusCode::OK);
+    let response = health("203.0.113.1").await.unwrap();
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    let response = health("203.0.113.2").await.unwrap();
+    assert_status(&response, StatusCode::OK);
+
+    // Only the address the proxy appended counts, not what the client sent
+    let response = health("198.51.100.7, 203.0.113.2").await.unwrap();
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_api_rate_limiting_per_user() {
+    let app = spawn_app_with_config(|config| {
+        config.rate_limit.enabled = true;
+        config.rate_limit.authenticated.requests_per_minute = 60;
+        config.rate_limit.authenticated.burst = 2;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_, alice) = factory.create_authenticated_user("ratelimit_alice").await;
+    let (_, bob) = factory.create_authenticated_user("ratelimit_bob").await;
+
+    for _ in 0..2 {
+        let response = app.get_auth("/api/v1/auth/me", &alice.token).await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let response = app.get_auth("/api/v1/auth/me", &alice.token).await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+
+    // Another user from the same address has their own limit
+    let response = app.get_auth("/api/v1/auth/me", &bob.token).await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["x-ratelimit-remaining"], "1");
+}
+
+#[tokio::test]
+async fn test_api_rate_limits_reload_without_restart() {
+    let app = spawn_app().await;
+
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::OK);
+    assert!(response.headers().get("x-ratelimit-limit").is_none());
+
+    let mut config = app.config.clone();
+    config.rate_limit.enabled = true;
+    config.rate_limit.public.requests_per_minute = 6;
+    config.rate_limit.public.burst = 1;
+    assert_eq!(app.live_config.update(&config), vec!["rate_limit"]);
+
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["x-ratelimit-limit"], "1");
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_api_response_compression() {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let app = spawn_app_with_config(|config| {
+        config.server.compression_min_bytes = 16;
+    })
+    .await;
+
+    let response = app
+        .client
+        .get(format!("{}/api/v1/health", app.address))
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+    let mut body = String::new();
+    GzDecoder::new(&response.bytes().await.unwrap()[..])
+        .read_to_string(&mut body)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["success"], true);
+
+    // Clients that don't ask for compression get plain responses
+    let response = app.get("/api/v1/health").await;
+    assert!(!response.headers().contains_key("content-encoding"));
+
+    // Small responses are not worth compressing
+    let app = spawn_app().await;
+    let response = app
+        .client
+        .get(format!("{}/api/v1/health", app.address))
+        .header("Accept-Encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.headers().contains_key("content-encoding"));
 }
 
 #[tokio::test]
@@ -99,6 +251,32 @@ async fn test_api_request_id_header() {
     assert!(!request_id.is_empty());
 }
 
+#[tokio::test]
+async fn test_api_request_id_is_unique_or_propagated() {
+    let app = spawn_app().await;
+
+    let first = app.get("/api/v1/health").await;
+    let second = app.get("/api/v1/health").await;
+    assert_ne!(
+        first.headers().get("x-request-id"),
+        second.headers().get("x-request-id")
+    );
+
+    // A caller-supplied request ID is kept so logs can be correlated across services
+    let response = app
+        .client
+        .get(format!("{}/api/v1/health", app.address))
+        .header("x-request-id", "upstream-req-42")
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        response.headers().get("x-request-id").unwrap(),
+        "upstream-req-42"
+    );
+}
+
 #[tokio::test]
 async fn test_api_malformed_json() {
     let app = spawn_app().await;
@@ -177,3 +355,73 @@ async fn test_api_security_headers() {
     assert_eq!(headers.get("x-content-type-options").unwrap(), "nosniff");
     assert_eq!(headers.get("x-frame-options").unwrap(), "DENY");
 }
+
+#[tokio::test]
+async fn test_api_v1_announces_deprecation() {
+    let app = spawn_app_with_config(|config| {
+        config.server.api_v1_sunset = "2027-04-17T00:00:00Z".to_string();
+    })
+    .await;
+
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::OK);
+    let headers = response.headers();
+    assert!(headers["deprecation"].to_str().unwrap().starts_with('@'));
+    assert_eq!(headers["sunset"], "Sat, 17 Apr 2027 00:00:00 GMT");
+    assert_eq!(headers["link"], "</api/v2>; rel=\"successor-version\"");
+
+    let response = app.get("/api/v2/health").await;
+    assert_status(&response, StatusCode::OK);
+    assert!(!response.headers().contains_key("deprecation"));
+    assert!(!response.headers().contains_key("sunset"));
+}
+
+#[tokio::test]
+async fn test_api_v2_task_shapes() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("versioned").await;
+
+    for _ in 0..3 {
+        let response = app
+            .post_json_auth(
+                "/api/v2/tasks",
+                &json!({"task_type": "email", "payload": {"to": "a@example.com"}}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    // v1 lists a bare array; v2 a page telling whether more follow
+    let response = app.get_auth("/api/v1/tasks?limit=2", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+
+    let response = app.get_auth("/api/v2/tasks?limit=2", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["items"].as_array().unwrap().len(), 2);
+    assert_eq!(json["data"]["limit"], 2);
+    assert_eq!(json["data"]["has_more"], true);
+
+    let response = app
+        .get_auth("/api/v2/tasks?limit=2&offset=2", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["items"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"]["has_more"], false);
+
+    // A missing task is null data in v1 and a 404 in v2
+    let missing = uuid::Uuid::new_v4();
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{missing}"), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].is_null());
+
+    let response = app
+        .get_auth(&format!("/api/v2/tasks/{missing}"), &token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,211 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn audit_log(app: &TestApp, query: &str, token: &str) -> Vec<serde_json::Value> {
+    let response = app
+        .get_auth(&format!("/api/v1/admin/audit?{query}"), token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    json["data"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_audit_log_records_changes_with_reasons() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (admin, admin_token) = factory.create_authenticated_admin("auditadmin").await;
+    let (_user, user_token) = factory.create_authenticated_user("audituser").await;
+    let target = factory.create_user("audittarget").await;
+
+    // Endpoints without a reason field take it from the header
+    let response = app
+        .client
+        .put(format!("{}/api/v1/users/{}/status", app.address, target.id))
+        .bearer_auth(&admin_token.token)
+        .header("X-Audit-Reason", "Chargeback investigation")
+        .json(&json!({ "is_active": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+
+    let entries = audit_log(
+        &app,
+        &format!("resource_type=user&resource_id={}", target.id),
+        &admin_token.token,
+    )
+    .await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "user_status_changed");
+    assert_eq!(entries[0]["actor_id"], admin.id.to_string());
+    assert_eq!(entries[0]["reason"], "Chargeback investigation");
+    assert_eq!(entries[0]["before"]["is_active"], true);
+    assert_eq!(entries[0]["after"]["is_active"], false);
+
+    // Task changes and RBAC changes land in the same log
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({ "task_type": "email", "payload": {"to": "a@example.com"}, "priority": "normal" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let task: serde_json::Value = response.json().await.unwrap();
+    let task_id = task["data"]["id"].as_str().unwrap();
+    let response = app
+        .post_auth(
+            &format!("/api/v1/tasks/{task_id}/cancel"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let entries = audit_log(
+        &app,
+        &format!("resource_type=task&resource_id={task_id}"),
+        &admin_token.token,
+    )
+    .await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "task_cancelled");
+    assert_eq!(entries[0]["after"]["status"], "cancelled");
+
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/role", target.id),
+            &json!({ "role": "moderator", "reason": "Covering the support queue" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let entries = audit_log(&app, "action=user_role_changed", &admin_token.token).await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["resource_id"], target.id.to_string());
+    assert_eq!(entries[0]["reason"], "Covering the support queue");
+
+    // The log is admin-only and can't be rewritten
+    let response = app.get_auth("/api/v1/admin/audit", &user_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let mut conn = app.db().await;
+    let result = sqlx::query("UPDATE audit_log SET reason = 'edited'")
+        .execute(conn.as_mut())
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_audit_retention_policies() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("retentionadmin").await;
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/audit/retention/task",
+            &json!({ "retention_days": 30 }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth("/api/v1/admin/audit/retention", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["default_retention_days"], 365);
+    assert_eq!(json["data"]["policies"][0]["resource_type"], "task");
+    assert_eq!(json["data"]["policies"][0]["retention_days"], 30);
+
+    for (path, body) in [
+        (
+            "/api/v1/admin/audit/retention/invoice",
+            json!({ "retention_days": 30 }),
+        ),
+        (
+            "/api/v1/admin/audit/retention/task",
+            json!({ "retention_days": 0 }),
+        ),
+    ] {
+        let response = app.put_json_auth(path, &body, &admin_token.token).await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let response = app
+        .delete_auth("/api/v1/admin/audit/retention/task", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .delete_auth("/api/v1/admin/audit/retention/task", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Retention changes are audited too
+    let entries = audit_log(&app, "resource_type=audit", &admin_token.token).await;
+    let actions: Vec<_> = entries.iter().map(|entry| &entry["action"]).collect();
+    assert_eq!(
+        actions,
+        ["retention_policy_removed", "retention_policy_set"]
+    );
+}
+
+#[tokio::test]
+async fn test_audit_purge_follows_retention() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("purgeadmin").await;
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/audit/retention/task",
+            &json!({ "retention_days": 30 }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let mut conn = app.db().await;
+    for (resource_type, age_days) in [("task", 40), ("task", 10), ("user", 40), ("user", 400)] {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (tenant_id, action, resource_type, resource_id, created_at)
+            VALUES ($1, 'backfilled', $2, 'x', NOW() - make_interval(days => $3))
+            "#,
+        )
+        .bind(starter::tenants::DEFAULT_TENANT_ID)
+        .bind(resource_type)
+        .bind(age_days)
+        .execute(conn.as_mut())
+        .await
+        .unwrap();
+    }
+
+    // Tasks keep 30 days, everything else the default 365
+    let deleted = starter::audit::services::purge_expired(&app.db_pool, Some(365))
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+    let remaining: Vec<(String, i32)> = sqlx::query_as(
+        r#"
+        SELECT resource_type, EXTRACT(DAY FROM NOW() - created_at)::int
+        FROM audit_log WHERE action = 'backfilled' ORDER BY resource_type
+        "#,
+    )
+    .fetch_all(conn.as_mut())
+    .await
+    .unwrap();
+    assert_eq!(
+        remaining,
+        [("task".to_string(), 10), ("user".to_string(), 40)]
+    );
+
+    // Outside the purge, deletes are rejected
+    let result = sqlx::query("DELETE FROM audit_log")
+        .execute(conn.as_mut())
+        .await;
+    assert!(result.is_err());
+}
@@ -370,30 +370,23 @@ async fn test_login_timing_attack_protection() {
             .await;
     }
 
-    // Measure timing for existing user with wrong password (should use real hash)
+    // Alternate the two so load from other tests hits both alike
     let mut valid_user_times = Vec::new();
+    let mut invalid_user_times = Vec::new();
     for _ in 0..5 {
+        // Existing user with wrong password (should use real hash)
         let start = std::time::Instant::now();
         let response = app.post_json("/api/v1/auth/login", &valid_login_data).await;
-        let duration = start.elapsed();
-
-        // Should return UNAUTHORIZED for wrong password
+        valid_user_times.push(start.elapsed());
         assert_status(&response, reqwest::StatusCode::UNAUTHORIZED);
-        valid_user_times.push(duration);
-    }
 
-    // Measure timing for non-existent user (should use dummy hash)
-    let mut invalid_user_times = Vec::new();
-    for _ in 0..5 {
+        // Non-existent user (should use dummy hash)
         let start = std::time::Instant::now();
         let response = app
             .post_json("/api/v1/auth/login", &invalid_login_data)
             .await;
-        let duration = start.elapsed();
-
-        // Should return UNAUTHORIZED for non-existent user
+        invalid_user_times.push(start.elapsed());
         assert_status(&response, reqwest::StatusCode::UNAUTHORIZED);
-        invalid_user_times.push(duration);
     }
 
     // Calculate average timing for both scenarios
@@ -888,3 +881,858 @@ async fn test_logout_vs_logout_all_difference() {
         StatusCode::UNAUTHORIZED,
     );
 }
+
+/// Verification emails sent to `to`, oldest first
+fn verification_tokens(app: &TestApp, to: &str) -> Vec<String> {
+    app.sent_emails
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|email| email.to == to)
+        .map(|email| {
+            let (_, rest) = email.body.split_once("token=").unwrap();
+            rest.split_whitespace().next().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_email_verification() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (user, token) = factory.create_authenticated_user("verify_me").await;
+
+    // Unknown and existing addresses get the same answer
+    let response = app
+        .post_json(
+            "/api/v1/auth/resend-verification",
+            &json!({"email": "nobody@example.com"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let unknown: serde_json::Value = response.json().await.unwrap();
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/resend-verification",
+            &json!({"email": "VERIFY_ME@example.com"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let known: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(known, unknown);
+    assert_eq!(verification_tokens(&app, &user.email).len(), 1);
+
+    // A second request within the cooldown sends nothing
+    let response = app
+        .post_json(
+            "/api/v1/auth/resend-verification",
+            &json!({"email": user.email}),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let tokens = verification_tokens(&app, &user.email);
+    assert_eq!(tokens.len(), 1);
+
+    let response = app
+        .post_json("/api/v1/auth/verify-email", &json!({"token": "bogus"}))
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json("/api/v1/auth/verify-email", &json!({"token": tokens[0]}))
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth("/api/v1/users/me/profile", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["email_verified"], true);
+
+    // Links are single use
+    let response = app
+        .post_json("/api/v1/auth/verify-email", &json!({"token": tokens[0]}))
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .get_auth("/api/v1/users/me/activity", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["action"], "email_verified");
+    assert_eq!(json["data"][0]["details"]["method"], "link");
+}
+
+#[tokio::test]
+async fn test_email_verification_limits() {
+    let app = spawn_app_with_config(|config| {
+        config.auth.verification_resend_cooldown_secs = 0;
+        config.auth.verification_daily_limit = 2;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let user = factory.create_user("verify_limit").await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("verify_admin").await;
+    let (_other, other_token) = factory.create_authenticated_user("verify_other").await;
+
+    for _ in 0..3 {
+        let response = app
+            .post_json(
+                "/api/v1/auth/resend-verification",
+                &json!({"email": user.email}),
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let tokens = verification_tokens(&app, &user.email);
+    assert_eq!(tokens.len(), 2);
+
+    // Admins can verify without a link, which retires the outstanding ones
+    let path = format!("/api/v1/admin/users/{}/verify-email", user.id);
+    let response = app.post_auth(&path, &other_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app.post_auth(&path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], "Email verified");
+
+    let response = app.post_auth(&path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], "Email was already verified");
+
+    let response = app
+        .post_json("/api/v1/auth/verify-email", &json!({"token": tokens[1]}))
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_auth(
+            &format!("/api/v1/admin/users/{}/verify-email", uuid::Uuid::new_v4()),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_legal_acceptance() {
+    let app = spawn_app_with_config(|config| {
+        config.auth.legal_acceptance_mode = starter::auth::models::LegalAcceptanceMode::Enforce;
+        config.server.trusted_proxies = vec!["127.0.0.1".to_string(), "10.0.0.0/8".to_string()];
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("legal_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("legal_user").await;
+
+    // Nothing is blocked until a document is published
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/legal",
+            &json!({"kind": "terms", "title": "Terms of Service", "content": "Be nice."}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/legal",
+            &json!({"kind": "terms", "title": "Terms of Service", "content": "Be nice."}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["version"], 1);
+
+    // The admin has to accept it too before publishing anything else
+    let privacy =
+        json!({"kind": "privacy", "title": "Privacy Policy", "content": "We keep little."});
+    let response = app
+        .post_json_auth("/api/v1/admin/legal", &privacy, &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "LEGAL_ACCEPTANCE_REQUIRED");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/legal/accept",
+            &json!({"documents": [{"kind": "terms", "version": 1}]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/admin/legal", &privacy, &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get("/api/v1/auth/legal").await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .get_auth("/api/v1/auth/legal/status", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["acceptance_required"], true);
+    assert_eq!(json["data"]["documents"][0]["accepted"], false);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/legal/accept",
+            &json!({"documents": [{"kind": "terms", "version": 2}]}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .client
+        .post(format!("{}/api/v1/auth/legal/accept", app.address))
+        .bearer_auth(&user_token.token)
+        .header("X-Forwarded-For", "203.0.113.7, 10.0.0.1")
+        .json(&json!({"documents": [
+            {"kind": "terms", "version": 1},
+            {"kind": "privacy", "version": 1}
+        ]}))
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["acceptance_required"], false);
+    assert_eq!(json["data"]["documents"][1]["accepted_version"], 1);
+
+    let mut conn = app.db().await;
+    let ip_address = sqlx::query_scalar!(
+        r#"
+        SELECT a.ip_address FROM legal_acceptances a
+        JOIN legal_documents d ON d.id = a.document_id
+        WHERE a.user_id = $1 AND d.kind = 'terms'
+        "#,
+        user.id
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap();
+    assert_eq!(ip_address.as_deref(), Some("203.0.113.7"));
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // A new version has to be accepted again; the old one no longer can be
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/legal/accept",
+            &json!({"documents": [{"kind": "privacy", "version": 1}]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/legal",
+            &json!({"kind": "terms", "title": "Terms of Service", "content": "Be kind."}),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["version"], 2);
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/legal/accept",
+            &json!({"documents": [{"kind": "terms", "version": 1}]}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // Accepting while signing in or registering
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({
+                "username": "legal_user",
+                "password": "SecurePass123!",
+                "accepted_documents": [{"kind": "terms", "version": 2}]
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/register",
+            &json!({
+                "username": "legal_new",
+                "email": "legal_new@example.com",
+                "password": "SecurePass123!",
+                "accepted_documents": [{"kind": "terms", "version": 1}]
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .post_json(
+            "/api/v1/auth/register",
+            &json!({
+                "username": "legal_new",
+                "email": "legal_new@example.com",
+                "password": "SecurePass123!",
+                "accepted_documents": [
+                    {"kind": "terms", "version": 2},
+                    {"kind": "privacy", "version": 1}
+                ]
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({"username": "legal_new", "password": "SecurePass123!"}),
+        )
+        .await;
+    let new_token = app.extract_auth_token(response).await;
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &new_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth("/api/v1/admin/legal", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/legal/accept",
+            &json!({"documents": [{"kind": "terms", "version": 2}]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth("/api/v1/admin/legal", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let versions = json["data"].as_array().unwrap();
+    assert_eq!(versions.len(), 3);
+    assert_eq!(versions[0]["kind"], "privacy");
+    assert_eq!(versions[0]["acceptances"], 3);
+    assert_eq!(versions[1]["version"], 2);
+    assert_eq!(versions[1]["acceptances"], 3);
+}
+
+#[tokio::test]
+async fn test_recovery_codes() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/register",
+            &json!({
+                "username": "recovery_user",
+                "email": "recovery_user@example.com",
+                "password": "SecurePass123!",
+                "generate_recovery_codes": true
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["username"], "recovery_user");
+    let codes: Vec<String> = json["data"]["recovery_codes"]["codes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(codes.len(), 10);
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/register",
+            &json!({
+                "username": "recovery_plain",
+                "email": "recovery_plain@example.com",
+                "password": "SecurePass123!"
+            }),
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].get("recovery_codes").is_none());
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({"username": "recovery_user", "password": "SecurePass123!"}),
+        )
+        .await;
+    let old_token = app.extract_auth_token(response).await;
+    let response = app
+        .get_auth("/api/v1/auth/recovery-codes", &old_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["remaining"], 10);
+    assert_eq!(json["data"]["used"], 0);
+
+    let recover = |code: &str, new_password: &str| {
+        json!({
+            "username": "recovery_user",
+            "recovery_code": code,
+            "new_password": new_password
+        })
+    };
+    let response = app
+        .post_json(
+            "/api/v1/auth/recover",
+            &recover("aaaaa-aaaaa", "RecoveredPass123!"),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // Case and separators don't matter
+    let typed = codes[0].to_uppercase().replace('-', " ");
+    let response = app
+        .post_json(
+            "/api/v1/auth/recover",
+            &recover(&typed, "RecoveredPass123!"),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let new_token = app.extract_auth_token(response).await;
+
+    let response = app.get_auth("/api/v1/auth/me", &old_token.token).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    let response = app.get_auth("/api/v1/auth/me", &new_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({"username": "recovery_user", "password": "SecurePass123!"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    let response = app
+        .post_json(
+            "/api/v1/auth/recover",
+            &recover(&codes[0], "AnotherPass123!"),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .get_auth("/api/v1/auth/recovery-codes", &new_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["remaining"], 9);
+    assert_eq!(json["data"]["used"], 1);
+
+    // A new set needs the password and retires the old codes
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/recovery-codes",
+            &json!({"password": "SecurePass123!"}),
+            &new_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/recovery-codes",
+            &json!({"password": "RecoveredPass123!"}),
+            &new_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["codes"].as_array().unwrap().len(), 10);
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/recover",
+            &recover(&codes[1], "AnotherPass123!"),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .get_auth("/api/v1/users/me/activity", &new_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["action"], "recovery_codes_generated");
+    assert_eq!(json["data"][1]["action"], "account_recovered");
+    assert_eq!(json["data"][1]["details"]["codes_remaining"], 9);
+}
+
+#[tokio::test]
+async fn test_scoped_sessions() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("scoped_owner").await;
+
+    for (request, status) in [
+        (
+            json!({ "name": "bot", "scopes": [] }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({ "name": "bot", "scopes": ["admin:read"] }),
+            StatusCode::BAD_REQUEST,
+        ),
+        // Regular users can't delete other users, so neither can their sessions
+        (
+            json!({ "name": "bot", "scopes": ["users:delete"] }),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/auth/scoped-sessions", &request, &token.token)
+            .await;
+        assert_status(&response, status);
+    }
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/scoped-sessions",
+            &json!({ "name": "report-bot", "scopes": ["Tasks:Read", "tasks:read"] }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["session"]["scopes"], json!(["tasks:read"]));
+    let scoped_id = json["data"]["session"]["id"].as_str().unwrap().to_string();
+    let scoped_token = json["data"]["session_token"].as_str().unwrap().to_string();
+
+    // The scoped token reads tasks but can't create them or leave its scope
+    assert_status(
+        &app.get_auth("/api/v1/tasks", &scoped_token).await,
+        StatusCode::OK,
+    );
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({
+                "task_type": "email",
+                "payload": { "to": "a@example.com", "subject": "Hi", "body": "Hello" }
+            }),
+            &scoped_token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for path in [
+        "/api/v1/users/me",
+        "/api/v1/orgs",
+        "/api/v1/auth/scoped-sessions",
+    ] {
+        assert_status(
+            &app.get_auth(path, &scoped_token).await,
+            StatusCode::FORBIDDEN,
+        );
+    }
+    let response = app.get_auth("/api/v1/auth/me", &scoped_token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["scopes"], json!(["tasks:read"]));
+
+    let response = app
+        .get_auth("/api/v1/auth/scoped-sessions", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["name"], "report-bot");
+
+    // Revoking ends the session
+    let path = format!("/api/v1/auth/scoped-sessions/{scoped_id}");
+    assert_status(&app.delete_auth(&path, &token.token).await, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/tasks", &scoped_token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+    assert_status(
+        &app.delete_auth(&path, &token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+}
+
+#[tokio::test]
+async fn test_revoked_sessions_are_refused_with_session_cache() {
+    let app = spawn_app_with_config(|config| config.cache.session_ttl_secs = 300).await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("cache_admin").await;
+    let (user, token) = factory.create_authenticated_user("cache_user").await;
+
+    // A revoked scoped session is refused although it is cached
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/scoped-sessions",
+            &json!({ "name": "bot", "scopes": ["tasks:read"] }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let scoped_id = json["data"]["session"]["id"].as_str().unwrap().to_string();
+    let scoped_token = json["data"]["session_token"].as_str().unwrap().to_string();
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &scoped_token).await,
+        StatusCode::OK,
+    );
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/auth/scoped-sessions/{scoped_id}"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &scoped_token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+    // The user's other cached sessions are checked again and still work
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::OK,
+    );
+
+    // So is a session ended by logging out everywhere from another one
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "cache_user", "password": "SecurePass123!" }),
+        )
+        .await;
+    let other = app.extract_auth_token(response).await;
+    assert_status(
+        &app.post_auth("/api/v1/auth/logout-all", &other.token).await,
+        StatusCode::OK,
+    );
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+
+    // One ended by recovering the account
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "cache_user", "password": "SecurePass123!" }),
+        )
+        .await;
+    let token = app.extract_auth_token(response).await;
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/recovery-codes",
+            &json!({ "password": "SecurePass123!" }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let code = json["data"]["codes"][0].as_str().unwrap().to_string();
+    let response = app
+        .post_json(
+            "/api/v1/auth/recover",
+            &json!({
+                "username": "cache_user",
+                "recovery_code": code,
+                "new_password": "RecoveredPass123!"
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+
+    // And one ended by an admin's password reset
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "cache_user", "password": "RecoveredPass123!" }),
+        )
+        .await;
+    let token = app.extract_auth_token(response).await;
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::OK,
+    );
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/users/{}/reset-password", user.id),
+            &json!({ "new_password": "NewTemporaryPassword123!" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+}
+
+#[tokio::test]
+async fn test_service_accounts() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("sa_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("sa_user").await;
+
+    let request = json!({ "username": "billing-worker" });
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/service-accounts",
+            &request,
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/service-accounts",
+            &request,
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "user");
+    let account_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Admin service accounts need an approved role request like any other admin
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/service-accounts",
+            &json!({ "username": "deploy-bot", "role": "admin" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let keys_path = format!("/api/v1/admin/service-accounts/{account_id}/api-keys");
+
+    let response = app
+        .post_json_auth(
+            &keys_path,
+            &json!({ "name": "reader", "scopes": ["tasks:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let scoped_key = json["data"]["api_key"].as_str().unwrap().to_string();
+    let scoped_key_id = json["data"]["key"]["id"].as_str().unwrap().to_string();
+    assert!(scoped_key.starts_with("sak_"));
+    assert_eq!(json["data"]["key"]["scopes"], json!(["tasks:read"]));
+
+    // The key authenticates as the account, limited to its scopes
+    let response = app.get_auth("/api/v1/auth/me", &scoped_key).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["username"], "billing-worker");
+    assert_status(
+        &app.get_auth("/api/v1/tasks", &scoped_key).await,
+        StatusCode::OK,
+    );
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({
+                "task_type": "email",
+                "payload": { "to": "a@example.com", "subject": "Hi", "body": "Hello" }
+            }),
+            &scoped_key,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // A key without scopes has the account's full permissions
+    let response = app
+        .post_json_auth(&keys_path, &json!({ "name": "worker" }), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let full_key = json["data"]["api_key"].as_str().unwrap().to_string();
+    assert_status(
+        &app.get_auth("/api/v1/users/me/profile", &full_key).await,
+        StatusCode::OK,
+    );
+
+    // No password login, and no place in user listings
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "billing-worker", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    let response = app.get_auth("/api/v1/users", &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let usernames: Vec<&str> = json["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["username"].as_str().unwrap())
+        .collect();
+    assert!(usernames.contains(&"sa_user"));
+    assert!(!usernames.contains(&"billing-worker"));
+
+    let response = app
+        .get_auth("/api/v1/admin/service-accounts", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["username"], "billing-worker");
+    assert_eq!(json["data"][0]["active_api_keys"], 2);
+
+    // Revoking a key locks it out
+    let path = format!("{keys_path}/{scoped_key_id}");
+    assert_status(
+        &app.delete_auth(&path, &admin_token.token).await,
+        StatusCode::OK,
+    );
+    assert_status(
+        &app.get_auth("/api/v1/tasks", &scoped_key).await,
+        StatusCode::UNAUTHORIZED,
+    );
+    assert_status(
+        &app.delete_auth(&path, &admin_token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+    let response = app.get_auth(&keys_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+    assert_eq!(json["data"][1]["is_active"], false);
+
+    // Keys are only issued for service accounts
+    let path = format!("/api/v1/admin/service-accounts/{}/api-keys", user.id);
+    let response = app
+        .post_json_auth(&path, &json!({ "name": "nope" }), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
@@ -37,7 +37,7 @@ async fn test_admin_service_list_tasks() {
 
     // Test listing tasks
     let tasks = admin_service
-        .list_tasks(None, None, 10, false)
+        .list_tasks(None, None, None, 10, false)
         .await
         .unwrap();
     assert!(!tasks.is_empty(), "Should have at least 2 tasks");
@@ -45,14 +45,14 @@ async fn test_admin_service_list_tasks() {
 
     // Test with limit
     let limited_tasks = admin_service
-        .list_tasks(None, None, 1, false)
+        .list_tasks(None, None, None, 1, false)
         .await
         .unwrap();
     assert_eq!(limited_tasks.len(), 1, "Should respect limit parameter");
 
     // Test filtering by task_type
     let email_tasks = admin_service
-        .list_tasks(None, Some("email".to_string()), 10, false)
+        .list_tasks(None, Some("email".to_string()), None, 10, false)
         .await
         .unwrap();
     assert!(!email_tasks.is_empty(), "Should find email tasks");
@@ -61,7 +61,7 @@ async fn test_admin_service_list_tasks() {
     }
 
     let data_processing_tasks = admin_service
-        .list_tasks(None, Some("data_processing".to_string()), 10, false)
+        .list_tasks(None, Some("data_processing".to_string()), None, 10, false)
         .await
         .unwrap();
     assert!(
@@ -77,7 +77,7 @@ async fn test_admin_service_list_tasks() {
 
     // Test filtering by status (all newly created tasks should be 'pending')
     let pending_tasks = admin_service
-        .list_tasks(Some("pending".to_string()), None, 10, false)
+        .list_tasks(Some("pending".to_string()), None, None, 10, false)
         .await
         .unwrap();
     assert!(!pending_tasks.is_empty(), "Should find pending tasks");
@@ -90,6 +90,7 @@ async fn test_admin_service_list_tasks() {
         .list_tasks(
             Some("pending".to_string()),
             Some("email".to_string()),
+            None,
             10,
             false,
         )
@@ -106,7 +107,7 @@ async fn test_admin_service_list_tasks() {
 
     // Test filtering with no matches should return empty
     let no_matches = admin_service
-        .list_tasks(None, Some("nonexistent_type".to_string()), 10, false)
+        .list_tasks(None, Some("nonexistent_type".to_string()), None, 10, false)
         .await
         .unwrap();
     assert!(
@@ -115,6 +116,57 @@ async fn test_admin_service_list_tasks() {
     );
 }
 
+#[tokio::test]
+async fn test_admin_service_tag_filtering() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let unique_username = format!("taguser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({
+                "task_type": "email",
+                "payload": {"to": "test@example.com", "subject": "Test", "body": "Test body"},
+                "tags": ["cli_tag"]
+            }),
+            &token.token,
+        )
+        .await;
+    assert_eq!(response.status(), 200);
+
+    let _untagged = factory
+        .create_task(
+            "email",
+            serde_json::json!({
+                "to": "test@example.com",
+                "subject": "Test",
+                "body": "Test body"
+            }),
+        )
+        .await;
+
+    let database = Database {
+        pool: app.db_pool.clone(),
+    };
+    let admin_service = AdminService::new(database);
+
+    let tagged = admin_service
+        .list_tasks(None, None, Some("cli_tag".to_string()), 10, false)
+        .await
+        .unwrap();
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].tags, vec!["cli_tag".to_string()]);
+
+    let stats = admin_service
+        .get_task_stats(Some("cli_tag".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(stats.total, 1);
+}
+
 #[tokio::test]
 async fn test_admin_service_task_stats() {
     let app = spawn_app().await;
@@ -183,7 +235,7 @@ async fn test_admin_service_clear_completed_dry_run() {
 
     // Verify tasks still exist
     let tasks_after = admin_service
-        .list_tasks(None, None, 10, false)
+        .list_tasks(None, None, None, 10, false)
         .await
         .unwrap();
     assert!(
@@ -256,6 +308,109 @@ async fn test_task_type_service() {
     );
 }
 
+#[tokio::test]
+async fn test_admin_service_archive_tasks() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let task = factory
+        .create_task("email", serde_json::json!({"to": "test@example.com"}))
+        .await;
+    let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+
+    sqlx::query!(
+        "UPDATE tasks SET status = 'completed', completed_at = NOW() - INTERVAL '10 days' WHERE id = $1",
+        task_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let database = Database {
+        pool: app.db_pool.clone(),
+    };
+    let admin_service = AdminService::new(database);
+
+    // Dry run reports the task without moving it
+    let count = admin_service.archive_tasks(7, true).await.unwrap();
+    assert_eq!(count, 1);
+
+    let archived = admin_service.archive_tasks(7, false).await.unwrap();
+    assert_eq!(archived, 1);
+
+    let remaining = sqlx::query_scalar!("SELECT COUNT(*) FROM tasks WHERE id = $1", task_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, Some(0));
+
+    let in_archive =
+        sqlx::query_scalar!("SELECT COUNT(*) FROM tasks_archive WHERE id = $1", task_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(in_archive, Some(1));
+}
+
+#[tokio::test]
+async fn test_admin_service_prune_events_by_type_and_level() {
+    use starter::monitoring::event_retention::EventRetention;
+
+    let app = spawn_app().await;
+
+    for (event_type, level, age_days) in [
+        ("log", "debug", 5),
+        ("log", "debug", 1),
+        ("log", "info", 20),
+        ("log", "info", 40),
+        ("log", "error", 100),
+        ("trace", "info", 10),
+    ] {
+        sqlx::query!(
+            "INSERT INTO events (event_type, source, level, recorded_at)
+             VALUES ($1, 'app-retention', $2, NOW() - make_interval(days => $3))",
+            event_type,
+            level,
+            age_days
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+
+    let database = Database {
+        pool: app.db_pool.clone(),
+    };
+    let admin_service = AdminService::new(database);
+    let retention = EventRetention::parse("debug=3,error=180,trace:*=7", 30).unwrap();
+
+    // Dry run reports the old debug, info and trace events without deleting them
+    let count = admin_service.prune_events(&retention, true).await.unwrap();
+    assert_eq!(count, 3);
+
+    let deleted = admin_service.prune_events(&retention, false).await.unwrap();
+    assert_eq!(deleted, 3);
+
+    let remaining = sqlx::query!(
+        "SELECT event_type, level FROM events WHERE source = 'app-retention' ORDER BY recorded_at"
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+    let remaining: Vec<(String, Option<String>)> = remaining
+        .into_iter()
+        .map(|row| (row.event_type, row.level))
+        .collect();
+    assert_eq!(
+        remaining,
+        [
+            ("log".to_string(), Some("error".to_string())),
+            ("log".to_string(), Some("info".to_string())),
+            ("log".to_string(), Some("debug".to_string())),
+        ]
+    );
+}
+
 #[cfg(test)]
 mod unit_tests {
     use starter::cli::models::{TaskInfo, TaskStats};
@@ -270,6 +425,7 @@ mod unit_tests {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             metadata: serde_json::json!({"test": true}),
+            tags: vec!["billing".to_string()],
         };
 
         assert_eq!(task_info.task_type, "email");
@@ -0,0 +1,124 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn features(app: &TestApp, token: &str) -> serde_json::Value {
+    let response = app.get_auth("/api/v1/features", token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    json["data"].clone()
+}
+
+#[tokio::test]
+async fn test_feature_flags_target_users() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("flagadmin").await;
+    let (beta_user, beta_token) = factory.create_authenticated_user("betauser").await;
+    let (_other, other_token) = factory.create_authenticated_user("otheruser").await;
+
+    // A new flag is off until enabled, even for its targeted users
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/features",
+            &json!({
+                "key": "new-dashboard",
+                "description": "Redesigned dashboard",
+                "rollout_percentage": 0,
+                "user_ids": [beta_user.id]
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        features(&app, &beta_token.token).await,
+        json!({"new-dashboard": false})
+    );
+
+    let response = app
+        .patch_json_auth(
+            "/api/v1/admin/features/new-dashboard",
+            &json!({"enabled": true}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        features(&app, &beta_token.token).await["new-dashboard"],
+        true
+    );
+    assert_eq!(
+        features(&app, &other_token.token).await["new-dashboard"],
+        false
+    );
+
+    // A full rollout reaches everyone
+    let response = app
+        .patch_json_auth(
+            "/api/v1/admin/features/new-dashboard",
+            &json!({"rollout_percentage": 100}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        features(&app, &other_token.token).await["new-dashboard"],
+        true
+    );
+
+    let response = app
+        .delete_auth("/api/v1/admin/features/new-dashboard", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(features(&app, &other_token.token).await, json!({}));
+}
+
+#[tokio::test]
+async fn test_feature_flag_management_requires_admin() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("flagadmin").await;
+    let (_user, user_token) = factory.create_authenticated_user("flaguser").await;
+
+    let flag = json!({"key": "checkout-v2"});
+    let response = app
+        .post_json_auth("/api/v1/admin/features", &flag, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth("/api/v1/admin/features", &flag, &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/admin/features", &flag, &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/features",
+            &json!({"key": "Bad Key"}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .patch_json_auth(
+            "/api/v1/admin/features/missing",
+            &json!({"enabled": true}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .get_auth("/api/v1/admin/features", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["key"], "checkout-v2");
+    assert_eq!(json["data"][0]["enabled"], false);
+    assert_eq!(json["data"][0]["rollout_percentage"], 100);
+}
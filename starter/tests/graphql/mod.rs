@@ -0,0 +1,173 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+/// Run a GraphQL request and return its JSON response
+async fn graphql(app: &TestApp, token: &str, query: &str, variables: Value) -> Value {
+    let response = app
+        .post_json_auth(
+            "/api/v1/graphql",
+            &json!({"query": query, "variables": variables}),
+            token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_graphql_requires_authentication() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_json("/api/v1/graphql", &json!({"query": "{ me { id } }"}))
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // GraphiQL is public
+    let response = app.get("/api/v1/graphql").await;
+    assert_status(&response, StatusCode::OK);
+    assert!(response.text().await.unwrap().contains("graphiql"));
+}
+
+#[tokio::test]
+async fn test_graphql_tasks_and_users() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (user, token) = factory.create_authenticated_user("gql_user").await;
+    let (_other, other_token) = factory.create_authenticated_user("gql_other").await;
+    let (_moderator, moderator_token) = factory.create_authenticated_moderator("gql_mod").await;
+
+    let json = graphql(&app, &token.token, "{ me { id username role } }", json!({})).await;
+    assert_eq!(json["data"]["me"]["id"], json!(user.id));
+    assert_eq!(json["data"]["me"]["role"], "USER");
+
+    let create = r#"
+        mutation Create($input: CreateTaskInput!) {
+            createTask(input: $input) { id taskType status priority tags }
+        }
+    "#;
+    let input = json!({"input": {
+        "taskType": "email",
+        "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"},
+        "priority": "HIGH",
+        "tags": ["graphql"]
+    }});
+    let json = graphql(&app, &token.token, create, input).await;
+    let task = &json["data"]["createTask"];
+    assert_eq!(task["status"], "PENDING");
+    assert_eq!(task["priority"], "HIGH");
+    assert_eq!(task["tags"], json!(["graphql"]));
+    let task_id = task["id"].as_str().unwrap();
+
+    let list = r#"{ tasks(filter: {priority: HIGH}) { id } }"#;
+    let json = graphql(&app, &token.token, list, json!({})).await;
+    assert_eq!(json["data"]["tasks"], json!([{"id": task_id}]));
+
+    // Other users can't see or cancel the task
+    let json = graphql(&app, &other_token.token, list, json!({})).await;
+    assert_eq!(json["data"]["tasks"], json!([]));
+    let cancel = r#"mutation Cancel($id: UUID!) { cancelTask(id: $id) { status } }"#;
+    let json = graphql(&app, &other_token.token, cancel, json!({"id": task_id})).await;
+    assert_eq!(json["errors"][0]["extensions"]["code"], "NOT_FOUND");
+
+    let json = graphql(&app, &token.token, cancel, json!({"id": task_id})).await;
+    assert_eq!(json["data"]["cancelTask"]["status"], "CANCELLED");
+
+    // Validation errors carry the REST error code
+    let input = json!({"input": {"taskType": "unregistered", "payload": {}}});
+    let json = graphql(&app, &token.token, create, input).await;
+    assert!(json["data"].is_null());
+    assert_eq!(json["errors"][0]["extensions"]["code"], "VALIDATION_FAILED");
+
+    // Listing users is for staff
+    let users = "{ users(filter: {search: \"gql_\"}) { totalCount items { username } } }";
+    let json = graphql(&app, &token.token, users, json!({})).await;
+    assert_eq!(json["errors"][0]["extensions"]["code"], "FORBIDDEN");
+    let json = graphql(&app, &moderator_token.token, users, json!({})).await;
+    assert_eq!(json["data"]["users"]["totalCount"], 3);
+}
+
+#[tokio::test]
+async fn test_graphql_monitoring() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_user, token) = factory.create_authenticated_user("gql_monitor").await;
+
+    let create = r#"
+        mutation {
+            createEvent(input: {eventType: LOG, source: "app-graphql", level: "error", message: "Boom", tags: {region: "eu"}}) { id eventType }
+            createMetric(input: {name: "http_requests_graphql", metricType: COUNTER, value: 2, labels: {route: "/"}}) { name value }
+        }
+    "#;
+    let json = graphql(&app, &token.token, create, json!({})).await;
+    assert!(json["errors"].is_null(), "{json}");
+    assert_eq!(json["data"]["createEvent"]["eventType"], "LOG");
+    assert_eq!(json["data"]["createMetric"]["value"], 2.0);
+
+    let query = r#"
+        {
+            events(filter: {source: "app-graphql", tags: "region:eu"}) { message tags }
+            metrics(filter: {name: "http_requests_graphql"}) { metricType labels }
+        }
+    "#;
+    let json = graphql(&app, &token.token, query, json!({})).await;
+    assert_eq!(json["data"]["events"][0]["message"], "Boom");
+    assert_eq!(json["data"]["events"][0]["tags"], json!({"region": "eu"}));
+    assert_eq!(json["data"]["metrics"][0]["labels"], json!({"route": "/"}));
+
+    // System sources need a moderator, as over REST
+    let json = graphql(
+        &app,
+        &token.token,
+        r#"mutation { createEvent(input: {eventType: LOG, source: "system-core"}) { id } }"#,
+        json!({}),
+    )
+    .await;
+    assert_eq!(json["errors"][0]["extensions"]["code"], "FORBIDDEN");
+}
+
+#[tokio::test]
+async fn test_graphql_monitoring_honors_permission_denies() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("gql_deny_admin").await;
+    let (user, token) = factory.create_authenticated_user("gql_deny_user").await;
+    for permission in ["monitoring:read", "monitoring:write"] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/admin/permission-denies",
+                &json!({ "user_id": user.id, "permission": permission }),
+                &admin_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    for query in [
+        r#"mutation { createEvent(input: {eventType: LOG, source: "app-graphql", message: "Boom"}) { id } }"#,
+        r#"mutation { createMetric(input: {name: "denied_graphql", metricType: COUNTER, value: 1}) { name } }"#,
+        r#"{ events(filter: {source: "app-graphql"}) { id } }"#,
+        r#"{ metrics(filter: {name: "denied_graphql"}) { name } }"#,
+    ] {
+        let json = graphql(&app, &token.token, query, json!({})).await;
+        assert_eq!(
+            json["errors"][0]["extensions"]["code"], "FORBIDDEN",
+            "{query}"
+        );
+    }
+
+    // Requests are held to the task limit, the smallest by default
+    let message = "x".repeat(1536 * 1024);
+    let response = app
+        .post_json_auth(
+            "/api/v1/graphql",
+            &json!({
+                "query": "mutation($message: String!) { createEvent(input: {eventType: LOG, source: \"app-graphql\", message: $message}) { id } }",
+                "variables": {"message": message}
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -1,8 +1,17 @@
 use once_cell::sync::Lazy;
 use reqwest::redirect::Policy;
 use sqlx::PgPool;
+use starter::core::cache::AppCache;
+use starter::core::reload::LiveConfig;
+use starter::monitoring::instrumentation::HttpMetrics;
+use starter::rbac::cache::PermissionCache;
+use starter::storage::LocalFileStorage;
+use starter::tasks::services::{EmailMessage, EmailSender};
+use starter::tasks::types::TaskError;
+use starter::users::presence::Presence;
 use starter::{AppConfig, Database, core::server};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
 
 #[derive(Debug, Clone)]
@@ -11,6 +20,27 @@ pub struct TestApp {
     pub client: reqwest::Client,
     pub config: AppConfig,
     pub db_pool: PgPool,
+    pub http_metrics: Arc<HttpMetrics>,
+    /// Configuration the server reads reloadable settings from
+    pub live_config: Arc<LiveConfig>,
+    /// Last-seen times not yet stored by the server
+    pub presence: Arc<Presence>,
+    /// Emails sent by the server, newest last
+    pub sent_emails: Arc<Mutex<Vec<EmailMessage>>>,
+    /// File storage shared with the server
+    pub storage: LocalFileStorage,
+}
+
+/// Email sender that records messages instead of delivering them
+#[derive(Debug, Clone, Default)]
+pub struct CapturingEmailSender(pub Arc<Mutex<Vec<EmailMessage>>>);
+
+#[async_trait::async_trait]
+impl EmailSender for CapturingEmailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), TaskError> {
+        self.0.lock().unwrap().push(message.clone());
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +64,14 @@ static TRACING: Lazy<()> = Lazy::new(|| {
 });
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with_config(|_| {}).await
+}
+
+/// Spawn a test app after applying overrides to the loaded configuration
+pub async fn spawn_app_with_config<F>(configure: F) -> TestApp
+where
+    F: FnOnce(&mut AppConfig),
+{
     // Initialize tracing once across all tests
     Lazy::force(&TRACING);
 
@@ -52,6 +90,9 @@ pub async fn spawn_app() -> TestApp {
     config.database.database = test_db.name.clone();
     config.database.max_connections = 5;
     config.database.min_connections = 1;
+    // Tests send requests in quick bursts; rate limit tests enable limiting themselves
+    config.rate_limit.enabled = false;
+    configure(&mut config);
 
     // Create database instance with test pool
     let database = Database {
@@ -59,13 +100,38 @@ pub async fn spawn_app() -> TestApp {
     };
 
     // Build application with state
+    let http_metrics = Arc::new(HttpMetrics::new());
+    let presence = Arc::new(Presence::new());
+    let sent_emails = Arc::new(Mutex::new(Vec::new()));
+    let storage = LocalFileStorage::new(
+        std::env::temp_dir().join(format!("starter-files-{}", test_db.name)),
+        "/api/v1/files",
+    );
+    let cache = Arc::new(
+        AppCache::from_config(&config.cache)
+            .await
+            .expect("Failed to set up cache"),
+    );
+    let live_config = Arc::new(LiveConfig::new(config.clone()));
     let state = starter::AppState {
         config: config.clone(),
+        live_config: live_config.clone(),
         database,
         start_time: std::time::Instant::now(),
+        event_stream: Default::default(),
+        realtime: Arc::new(starter::realtime::RealtimeHub::new()),
+        services: starter::tasks::services::TaskServices::from_config(&config)
+            .with_email_sender(CapturingEmailSender(sent_emails.clone())),
+        http_metrics: http_metrics.clone(),
+        storage: Arc::new(storage.clone()),
+        presence: presence.clone(),
+        permission_cache: Arc::new(PermissionCache::new(
+            cache.clone(),
+            config.permission_cache_ttl(),
+        )),
+        cache,
     };
-    let api_router = server::create_router(state);
-    let app = axum::Router::new().nest("/api/v1", api_router);
+    let app = server::create_versioned_router(state);
 
     // Bind to random port
     let listener = TcpListener::bind("127.0.0.1:0")
@@ -100,6 +166,11 @@ pub async fn spawn_app() -> TestApp {
         client,
         config,
         db_pool: test_db.pool.clone(),
+        http_metrics,
+        live_config,
+        presence,
+        sent_emails,
+        storage,
     }
 }
 
@@ -181,6 +252,79 @@ impl TestApp {
             .expect("Failed to execute PUT request")
     }
 
+    // PATCH with JSON body and auth token
+    pub async fn patch_json_auth<T: serde::Serialize>(
+        &self,
+        path: &str,
+        json: &T,
+        token: &str,
+    ) -> reqwest::Response {
+        let url = format!("{}{}", self.address, path);
+        self.client
+            .patch(url)
+            .header("Authorization", format!("Bearer {token}"))
+            .json(json)
+            .send()
+            .await
+            .expect("Failed to execute PATCH request")
+    }
+
+    // PUT a single-file multipart form with auth token
+    pub async fn put_file_auth(
+        &self,
+        path: &str,
+        field: &str,
+        content_type: &str,
+        bytes: &[u8],
+        token: &str,
+    ) -> reqwest::Response {
+        let url = format!("{}{}", self.address, path);
+        self.file_form(self.client.put(url), field, content_type, bytes, token)
+            .send()
+            .await
+            .expect("Failed to execute PUT request")
+    }
+
+    // POST a single-file multipart form with auth token
+    pub async fn post_file_auth(
+        &self,
+        path: &str,
+        field: &str,
+        content_type: &str,
+        bytes: &[u8],
+        token: &str,
+    ) -> reqwest::Response {
+        let url = format!("{}{}", self.address, path);
+        self.file_form(self.client.post(url), field, content_type, bytes, token)
+            .send()
+            .await
+            .expect("Failed to execute POST request")
+    }
+
+    fn file_form(
+        &self,
+        request: reqwest::RequestBuilder,
+        field: &str,
+        content_type: &str,
+        bytes: &[u8],
+        token: &str,
+    ) -> reqwest::RequestBuilder {
+        let boundary = "starter-test-boundary";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"upload\"\r\nContent-Type: {content_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+        request
+            .header("Authorization", format!("Bearer {token}"))
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+    }
+
     // DELETE request with auth token
     pub async fn delete_auth(&self, path: &str, token: &str) -> reqwest::Response {
         let url = format!("{}{}", self.address, path);
@@ -50,6 +50,12 @@ impl TestDataFactory {
             created_at: chrono::Utc::now(), // Parse from response if needed
             updated_at: chrono::Utc::now(),
             last_login_at: None,
+            avatar_url: None,
+            profile: json!({}),
+            suspended_until: None,
+            last_seen_at: None,
+            role_expires_at: None,
+            is_service_account: false,
         }
     }
 
@@ -96,6 +102,12 @@ impl TestDataFactory {
             created_at: chrono::Utc::now(), // Parse from response if needed
             updated_at: chrono::Utc::now(),
             last_login_at: None,
+            avatar_url: None,
+            profile: json!({}),
+            suspended_until: None,
+            last_seen_at: None,
+            role_expires_at: None,
+            is_service_account: false,
         }
     }
 
@@ -7,13 +7,23 @@
 //! - Comprehensive test data factories
 
 pub mod api;
+pub mod audit;
 pub mod auth;
 pub mod cli;
+pub mod features;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod health;
 pub mod helpers;
+pub mod maintenance;
 pub mod middleware;
 pub mod monitoring;
+pub mod orgs;
+pub mod rbac;
+pub mod realtime;
+pub mod scim;
 pub mod tasks;
+pub mod tenants;
 pub mod users;
 
 // Re-export common test utilities
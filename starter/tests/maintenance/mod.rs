@@ -0,0 +1,167 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+async fn set_maintenance(app: &TestApp, body: serde_json::Value, token: &str) -> serde_json::Value {
+    let response = app
+        .put_json_auth("/api/v1/admin/maintenance", &body, token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    json["data"].clone()
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_refuses_non_admins() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("maintadmin").await;
+    let (_user, user_token) = factory.create_authenticated_user("maintuser").await;
+
+    let status = set_maintenance(
+        &app,
+        json!({ "enabled": true, "message": "Upgrading the database", "retry_after_secs": 120 }),
+        &admin_token.token,
+    )
+    .await;
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["forced_by_config"], false);
+
+    let response = app.get_auth("/api/v1/tasks", &user_token.token).await;
+    assert_status(&response, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()["retry-after"], "120");
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "MAINTENANCE");
+    assert_eq!(json["error"]["message"], "Upgrading the database");
+    assert_eq!(json["error"]["details"]["retry_after_secs"], 120);
+
+    // Public routes and sign-ups are refused, health checks are not
+    let response = app
+        .post_json(
+            "/api/v1/auth/register",
+            &json!({ "username": "newcomer", "email": "newcomer@example.com", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::SERVICE_UNAVAILABLE);
+    let response = app.get("/api/v1/health").await;
+    assert_status(&response, StatusCode::OK);
+
+    // Only admins can sign in and use the API
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "maintuser", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::SERVICE_UNAVAILABLE);
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({ "username": "maintadmin", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth("/api/v1/tasks", &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    set_maintenance(&app, json!({ "enabled": false }), &admin_token.token).await;
+    let response = app.get_auth("/api/v1/tasks", &user_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    // Only admins toggle it
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/maintenance",
+            &json!({ "enabled": true }),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_forced_by_config() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("forcedadmin").await;
+
+    let mut config = app.config.clone();
+    config.maintenance.enabled = true;
+    assert_eq!(app.live_config.update(&config), vec!["maintenance.enabled"]);
+
+    // The toggle can't turn it off
+    let status = set_maintenance(&app, json!({ "enabled": false }), &admin_token.token).await;
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["forced_by_config"], true);
+    assert_eq!(
+        status["retry_after_secs"],
+        config.maintenance.retry_after_secs
+    );
+
+    let response = app.get("/api/v1/tasks/types").await;
+    assert_status(&response, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.headers()["retry-after"],
+        config.maintenance.retry_after_secs.to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_worker_pauses_in_maintenance_mode() {
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("pauseadmin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({
+                "task_type": "email",
+                "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id: uuid::Uuid = json["data"]["id"].as_str().unwrap().parse().unwrap();
+    set_maintenance(&app, json!({ "enabled": true }), &admin_token.token).await;
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(100),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let worker = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let status = || async {
+        sqlx::query_scalar::<_, String>("SELECT status FROM tasks WHERE id = $1")
+            .bind(task_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap()
+    };
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(status().await, "pending");
+
+    set_maintenance(&app, json!({ "enabled": false }), &admin_token.token).await;
+    assert!(wait_for(|| async { status().await == "completed" }, 5000).await);
+    worker.abort();
+}
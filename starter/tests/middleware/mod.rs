@@ -82,24 +82,94 @@ async fn test_json_parsing_edge_cases() {
         "Expected 415 or 400, got {status}"
     );
 
-    // Test extremely large JSON payload
-    let large_reason = "x".repeat(10000);
+    // Test a JSON payload over the default body limit
+    let limit_bytes = app.config.body_limit.default_bytes;
     let large_data = json!({
         "email": "test@example.com",
-        "reason": large_reason
+        "reason": "x".repeat(limit_bytes)
     });
 
     let response = app
         .put_json_auth("/api/v1/users/me/profile", &large_data, &token.token)
         .await;
 
-    let status = response.status();
-    assert!(
-        status == StatusCode::PAYLOAD_TOO_LARGE
-            || status == StatusCode::BAD_REQUEST
-            || status == StatusCode::OK, // If server accepts large payloads
-        "Expected 413, 400, or 200, got {status}"
-    );
+    assert_status(&response, StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(json["error"]["details"]["limit_bytes"], limit_bytes);
+}
+
+#[tokio::test]
+async fn test_body_limits_by_route_group() {
+    let app = spawn_app_with_config(|config| {
+        config.body_limit.default_bytes = 2048;
+        config.body_limit.auth_bytes = 1024;
+        config.body_limit.tasks_bytes = 4096;
+        config.body_limit.ingestion_bytes = 8192;
+    })
+    .await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("limituser").await;
+
+    let assert_too_large = |response: reqwest::Response, limit_bytes: usize| async move {
+        assert_status(&response, StatusCode::PAYLOAD_TOO_LARGE);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+        assert_eq!(json["error"]["details"]["limit_bytes"], limit_bytes);
+    };
+    let task = |size: usize| {
+        json!({
+            "task_type": "email",
+            "payload": {"to": "test@example.com", "subject": "Hi", "body": "x".repeat(size)}
+        })
+    };
+    let event = |size: usize| {
+        json!({
+            "event_type": "log",
+            "source": "test-limits",
+            "message": "x".repeat(size),
+            "level": "info"
+        })
+    };
+
+    // Auth routes, public and signed in
+    let login = json!({ "username": "limituser", "password": "x".repeat(1024) });
+    let response = app.post_json("/api/v1/auth/login", &login).await;
+    assert_too_large(response, 1024).await;
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/logout",
+            &json!({ "x": "x".repeat(1024) }),
+            &token.token,
+        )
+        .await;
+    assert_too_large(response, 1024).await;
+
+    // Tasks take more than other routes, ingestion more still
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task(3000), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task(4096), &token.token)
+        .await;
+    assert_too_large(response, 4096).await;
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &json!({ "bio": "x".repeat(3000) }),
+            &token.token,
+        )
+        .await;
+    assert_too_large(response, 2048).await;
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event(6000), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event(8192), &token.token)
+        .await;
+    assert_too_large(response, 8192).await;
 }
 
 #[tokio::test]
@@ -187,7 +257,7 @@ async fn test_unicode_and_concurrent_operations() {
         .await;
 
     let role_data1 = json!({"role": "moderator", "reason": "First concurrent update"});
-    let role_data2 = json!({"role": "admin", "reason": "Second concurrent update"});
+    let role_data2 = json!({"role": "user", "reason": "Second concurrent update"});
 
     let role_endpoint = format!("/api/v1/users/{}/role", user.id);
     let (response1, response2) = tokio::join!(
@@ -207,3 +277,105 @@ async fn test_unicode_and_concurrent_operations() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_idempotency_key_replays_task_creation() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("idempotent").await;
+    let (_other, other_token) = factory.create_authenticated_user("idempotent2").await;
+
+    let create = |body: serde_json::Value, token: String, key: &'static str| {
+        let app = app.clone();
+        async move {
+            app.client
+                .post(format!("{}/api/v1/tasks", app.address))
+                .header("Authorization", format!("Bearer {token}"))
+                .header("Idempotency-Key", key)
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let task = json!({"task_type": "email", "payload": {"to": "a@example.com"}});
+
+    let first = create(task.clone(), token.token.clone(), "create-1").await;
+    assert_status(&first, StatusCode::OK);
+    assert!(!first.headers().contains_key("idempotent-replayed"));
+    let first: serde_json::Value = first.json().await.unwrap();
+
+    // A retry gets the stored response instead of a second task
+    let retry = create(task.clone(), token.token.clone(), "create-1").await;
+    assert_status(&retry, StatusCode::OK);
+    assert_eq!(retry.headers()["idempotent-replayed"], "true");
+    assert_eq!(
+        retry.headers()["content-type"].to_str().unwrap(),
+        "application/json"
+    );
+    let retry: serde_json::Value = retry.json().await.unwrap();
+    assert_eq!(retry["data"]["id"], first["data"]["id"]);
+
+    let response = app.get_auth("/api/v1/tasks", &token.token).await;
+    let tasks: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(tasks["data"].as_array().unwrap().len(), 1);
+
+    // The key can't be reused for another request
+    let other_task = json!({"task_type": "email", "payload": {"to": "b@example.com"}});
+    let response = create(other_task, token.token.clone(), "create-1").await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // Keys belong to the user who sent them
+    let response = create(task, other_token.token.clone(), "create-1").await;
+    assert_status(&response, StatusCode::OK);
+    assert!(!response.headers().contains_key("idempotent-replayed"));
+}
+
+#[tokio::test]
+async fn test_idempotency_key_retries_rate_limits_within_body_limits() {
+    let app = spawn_app_with_config(|config| {
+        config.tasks.rate_limit_per_minute = 1;
+        config.body_limit.tasks_bytes = 4096;
+    })
+    .await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory
+        .create_authenticated_user("idempotent_headers")
+        .await;
+
+    let create = |body: serde_json::Value, key: &'static str| {
+        let app = app.clone();
+        let token = token.token.clone();
+        async move {
+            app.client
+                .post(format!("{}/api/v1/tasks", app.address))
+                .header("Authorization", format!("Bearer {token}"))
+                .header("Idempotency-Key", key)
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let task = json!({"task_type": "email", "payload": {"to": "a@example.com"}});
+    let response = create(task.clone(), "first").await;
+    assert_status(&response, StatusCode::OK);
+
+    // A rate limited attempt isn't stored, so a retry with its key runs again
+    let limited = create(task.clone(), "limited").await;
+    assert_status(&limited, StatusCode::TOO_MANY_REQUESTS);
+    assert!(limited.headers().contains_key("retry-after"));
+    let retry = create(task, "limited").await;
+    assert_status(&retry, StatusCode::TOO_MANY_REQUESTS);
+    assert!(!retry.headers().contains_key("idempotent-replayed"));
+
+    // Bodies are read with the route group's limit, not a global one
+    let large = json!({
+        "task_type": "email",
+        "payload": {"to": "a@example.com", "body": "x".repeat(8192)}
+    });
+    let response = create(large, "large").await;
+    assert_status(&response, StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["details"]["limit_bytes"], 4096);
+}
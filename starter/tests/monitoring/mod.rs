@@ -91,6 +91,121 @@ async fn test_get_events_with_filters() {
     }
 }
 
+#[tokio::test]
+async fn test_export_events_and_metrics() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_user, token) = factory
+        .create_authenticated_user(&format!("exportuser_{suffix}"))
+        .await;
+    let source = format!("test-export-{suffix}");
+
+    for (hour, message) in [(1, "too early"), (2, "first, in range"), (3, "second")] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/events",
+                &json!({
+                    "event_type": "log",
+                    "source": source,
+                    "message": message,
+                    "level": "info",
+                    "recorded_at": format!("2024-01-15T0{hour}:00:00Z")
+                }),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    // CSV honors the source filter and time range, oldest first
+    let response = app
+        .get_auth(
+            &format!(
+                "/api/v1/monitoring/events/export?source={source}&start_time=2024-01-15T02:00:00Z"
+            ),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    assert!(
+        response.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .starts_with("attachment; filename=\"events-")
+    );
+    let body = response.text().await.unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,event_type,source,message,level,tags,payload,recorded_at,created_at"
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].contains("\"first, in range\""));
+    assert!(lines[2].contains(",second,"));
+
+    // NDJSON carries the same rows
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/events/export?source={source}&format=ndjson"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body = response.text().await.unwrap();
+    let events: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["message"], "too early");
+
+    let metric_name = format!("latency_export_{suffix}");
+    for (minute, value) in [(0, 1.5), (1, -2.0)] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics",
+                &json!({
+                    "name": metric_name,
+                    "metric_type": "gauge",
+                    "value": value,
+                    "recorded_at": format!("2024-01-15T01:0{minute}:00Z")
+                }),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/metrics/export?name={metric_name}&limit=1"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body = response.text().await.unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,name,metric_type,value,labels,recorded_at,created_at"
+    );
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains(&format!(",{metric_name},gauge,1.5,")));
+
+    let response = app
+        .get_auth("/api/v1/monitoring/metrics/export?format=xml", &token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/v1/monitoring/events/export").await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn test_get_events_with_tag_filters() {
     let app = spawn_app().await;
@@ -275,6 +390,188 @@ async fn test_create_alert_as_moderator() {
     assert_json_field(&json["data"], "status", &json!("active"));
 }
 
+#[tokio::test]
+async fn test_alert_test_fire_notifies_channels() {
+    use axum::{Json, Router, extract::State, routing::post};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    // Local endpoint standing in for a webhook or Slack receiver
+    let received: Received = Default::default();
+    let receiver = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(received): State<Received>, Json(body): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(body);
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let receiver_address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("notifymod_{suffix}"))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("notifyuser_{suffix}"))
+        .await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/alerts",
+            &json!({ "name": "High error rate", "query": "error_rate > 5", "threshold_value": 5.0 }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let alert_id = json["data"]["id"].as_str().unwrap().to_string();
+    let test_path = format!("/api/v1/monitoring/alerts/{alert_id}/test");
+
+    // Nothing to notify yet
+    let response = app
+        .post_json_auth(&test_path, &json!({}), &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/notification-channels",
+            &json!({ "name": "Ops webhook", "kind": "webhook", "target": "not a url" }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let mut channel_ids = Vec::new();
+    for (name, kind, target) in [
+        ("Ops webhook", "webhook", format!("{receiver_address}/hook")),
+        ("Ops Slack", "slack", format!("{receiver_address}/hook")),
+        (
+            "Broken webhook",
+            "webhook",
+            format!("{receiver_address}/missing"),
+        ),
+        ("Ops email", "email", "ops@example.com".to_string()),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/notification-channels",
+                &json!({ "name": name, "kind": kind, "target": target }),
+                &mod_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        channel_ids.push(json["data"]["id"].as_str().unwrap().to_string());
+    }
+
+    let channels_path = format!("/api/v1/monitoring/alerts/{alert_id}/channels");
+    let response = app
+        .put_json_auth(
+            &channels_path,
+            &json!({ "channel_ids": [Uuid::new_v4()] }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .put_json_auth(
+            &channels_path,
+            &json!({ "channel_ids": channel_ids }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 4);
+
+    let response = app
+        .post_json_auth(&test_path, &json!({}), &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(&test_path, &json!({}), &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["notification"]["test"], true);
+    assert_eq!(json["data"]["notification"]["status"], "firing");
+
+    let deliveries = json["data"]["deliveries"].as_array().unwrap();
+    let delivery = |name: &str| {
+        deliveries
+            .iter()
+            .find(|delivery| delivery["channel_name"] == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(delivery("Ops webhook")["delivered"], true);
+    assert_eq!(delivery("Ops Slack")["delivered"], true);
+    assert_eq!(delivery("Ops email")["delivered"], true);
+    assert_eq!(delivery("Broken webhook")["delivered"], false);
+    assert!(
+        delivery("Broken webhook")["error"]
+            .as_str()
+            .unwrap()
+            .contains("404")
+    );
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 2);
+    let webhook = received.iter().find(|body| body["test"] == true).unwrap();
+    assert_eq!(webhook["alert_id"], alert_id.as_str());
+    let slack = received
+        .iter()
+        .find(|body| body["text"].is_string())
+        .unwrap();
+    assert!(
+        slack["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("[TEST] Alert firing: High error rate")
+    );
+
+    // The alert itself is untouched
+    let response = app
+        .get_auth("/api/v1/monitoring/alerts", &mod_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let alert = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|alert| alert["id"] == alert_id.as_str())
+        .unwrap();
+    assert_eq!(alert["status"], "active");
+    assert!(alert["triggered_at"].is_null());
+
+    // Deleted channels drop off the alert
+    let response = app
+        .delete_auth(
+            &format!(
+                "/api/v1/monitoring/notification-channels/{}",
+                channel_ids[2]
+            ),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth(&channels_path, &mod_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 3);
+}
+
 #[tokio::test]
 async fn test_create_incident() {
     let app = spawn_app().await;
@@ -396,6 +693,153 @@ async fn test_get_incident_timeline() {
     assert_json_field_exists(&timeline_json["data"], "total_count");
 }
 
+#[tokio::test]
+async fn test_incident_timeline_notes() {
+    use chrono::{SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let (author, author_token) = factory
+        .create_authenticated_user(&format!("noteauthor_{}", &Uuid::new_v4().to_string()[..8]))
+        .await;
+    let (_other, other_token) = factory
+        .create_authenticated_user(&format!("noteother_{}", &Uuid::new_v4().to_string()[..8]))
+        .await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator(&format!("notemod_{}", &Uuid::new_v4().to_string()[..8]))
+        .await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({"title": "Notes incident", "severity": "low"}),
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+    let notes_path = format!("/api/v1/monitoring/incidents/{incident_id}/timeline/notes");
+
+    app.post_json_auth(
+        "/api/v1/monitoring/events",
+        &json!({"event_type": "log", "source": "app-notes", "message": "Disk full", "level": "error"}),
+        &author_token.token,
+    )
+    .await;
+
+    // A note placed before the event sorts ahead of it
+    let noted_at =
+        (Utc::now() - chrono::Duration::seconds(30)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let response = app
+        .post_json_auth(
+            &notes_path,
+            &json!({"body": "  Paged the storage team  ", "noted_at": noted_at}),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["body"], "Paged the storage team");
+    let note_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth(
+            &notes_path,
+            &json!({"body": "Other user's note"}),
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let other_note_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/timeline"),
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"]["entries"].as_array().unwrap();
+    let kinds: Vec<(&str, &str)> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry["entry_type"].as_str().unwrap(),
+                entry["message"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    let first_note = kinds
+        .iter()
+        .position(|kind| *kind == ("note", "Paged the storage team"))
+        .unwrap();
+    let event = kinds
+        .iter()
+        .position(|kind| *kind == ("event", "Disk full"))
+        .unwrap();
+    assert!(first_note < event);
+    assert!(kinds.contains(&("note", "Other user's note")));
+    assert_eq!(entries[first_note]["event_type"], serde_json::Value::Null);
+    assert_eq!(entries[first_note]["author_id"], author.id.to_string());
+    assert_eq!(json["data"]["total_count"], entries.len());
+
+    // Only the author or a moderator may change a note
+    let response = app
+        .put_json_auth(
+            &format!("{notes_path}/{note_id}"),
+            &json!({"body": "Hijacked"}),
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .put_json_auth(
+            &format!("{notes_path}/{note_id}"),
+            &json!({"body": "Paged the storage team; ETA 10 minutes"}),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["body"],
+        "Paged the storage team; ETA 10 minutes"
+    );
+
+    let response = app
+        .delete_auth(
+            &format!("{notes_path}/{other_note_id}"),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    for invalid in [
+        json!({"body": "   "}),
+        json!({"body": "Later", "noted_at": "2999-01-01T00:00:00Z"}),
+    ] {
+        let response = app
+            .post_json_auth(&notes_path, &invalid, &author_token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let response = app
+        .post_json_auth(
+            &format!(
+                "/api/v1/monitoring/incidents/{}/timeline/notes",
+                Uuid::new_v4()
+            ),
+            &json!({"body": "Nowhere"}),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 async fn test_get_monitoring_stats_requires_moderator() {
     let app = spawn_app().await;
@@ -433,6 +877,158 @@ async fn test_get_monitoring_stats_as_moderator() {
     assert_json_field_exists(&json["data"], "metrics_last_hour");
 }
 
+#[tokio::test]
+async fn test_self_instrumentation_records_requests() {
+    use chrono::Utc;
+    use starter::monitoring::services;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("selfmod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let missing = format!("/api/v1/monitoring/events/{}", Uuid::new_v4());
+    for _ in 0..3 {
+        let response = app.get_auth(&missing, &token.token).await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    // Unrouted paths are not recorded
+    app.get("/api/v1/no-such-route").await;
+
+    // What the server's metrics job stores on each tick
+    let rows = app.http_metrics.take_rows(Utc::now());
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    services::create_metrics_batch(conn.as_mut(), &rows)
+        .await
+        .unwrap();
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/metrics?name=http_server_request_duration_seconds_count",
+            &token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let rows = json["data"].as_array().unwrap();
+    assert!(
+        rows.iter()
+            .all(|row| row["labels"]["route"] != "/api/v1/no-such-route")
+    );
+    let lookups = rows
+        .iter()
+        .find(|row| row["labels"]["route"] == "/api/v1/monitoring/events/{id}")
+        .unwrap();
+    assert_eq!(lookups["labels"]["method"], "GET");
+    assert_eq!(lookups["labels"]["status"], "404");
+    assert_eq!(lookups["value"], 3.0);
+
+    let response = app.get_auth("/api/v1/monitoring/stats", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let activity = &json["data"]["app_activity"];
+    assert!(activity["http_requests"].as_i64().unwrap() >= 3);
+    assert_eq!(activity["http_server_errors"], 0);
+    assert_eq!(activity["tasks_completed"], 0);
+}
+
+#[tokio::test]
+async fn test_metric_cardinality_limit_rejects_new_series() {
+    let app =
+        spawn_app_with_config(|config| config.monitoring.metric_max_series_per_name = 2).await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("cardmod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let metric = |host: &str| json!({"name": "requests_total", "metric_type": "counter", "value": 1.0, "labels": {"host": host}});
+    // Known series keep being accepted once the limit is reached
+    for host in ["a", "b", "a"] {
+        let response = app
+            .post_json_auth("/api/v1/monitoring/metrics", &metric(host), &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app
+        .post_json_auth("/api/v1/monitoring/metrics", &metric("c"), &token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "CARDINALITY_LIMIT_EXCEEDED");
+    assert_eq!(
+        json["error"]["details"],
+        json!({"metric": "requests_total", "limit": 2})
+    );
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/metrics",
+            &json!({"name": "requests_total", "metric_type": "counter", "value": 1.0, "labels": {"bad-label": "x"}}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app.get_auth("/api/v1/monitoring/stats", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["metric_series_limit"], 2);
+    assert_eq!(
+        json["data"]["metric_cardinality"],
+        json!([{"name": "requests_total", "series": 2}])
+    );
+}
+
+#[tokio::test]
+async fn test_metric_cardinality_limit_truncates_into_overflow_series() {
+    let app = spawn_app_with_config(|config| {
+        config.monitoring.metric_max_series_per_name = 1;
+        config.monitoring.metric_cardinality_action =
+            starter::monitoring::cardinality::CardinalityLimitAction::Truncate;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("cardmod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    for user_id in ["1", "2", "3"] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics",
+                &json!({"name": "logins", "metric_type": "counter", "value": 1.0, "labels": {"user_id": user_id}}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app
+        .get_auth("/api/v1/monitoring/metrics?name=logins", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let mut labels: Vec<_> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|metric| metric["labels"].to_string())
+        .collect();
+    labels.sort();
+    assert_eq!(
+        labels,
+        [
+            r#"{"overflow":"true"}"#,
+            r#"{"overflow":"true"}"#,
+            r#"{"user_id":"1"}"#
+        ]
+    );
+}
+
 #[tokio::test]
 async fn test_invalid_event_type() {
     let app = spawn_app().await;
@@ -988,3 +1584,2762 @@ async fn test_metric_name_authorization_user_owned_metrics() {
         assert_status(&response, StatusCode::OK);
     }
 }
+
+#[tokio::test]
+async fn test_metric_rollups_and_series_resolution() {
+    use chrono::{DurationRound, SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("seriesuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    // Three samples in one 5-minute bucket two days ago, plus a recent sample
+    let bucket = (Utc::now() - chrono::Duration::days(2))
+        .duration_trunc(chrono::Duration::minutes(5))
+        .unwrap();
+    for (offset, value) in [(10, 1.0), (20, 2.0), (30, 3.0)] {
+        sqlx::query(
+            "INSERT INTO metrics (name, metric_type, value, labels, recorded_at)
+             VALUES ('queue_depth', 'gauge', $1, '{\"queue\": \"default\"}', $2)",
+        )
+        .bind(value)
+        .bind(bucket + chrono::Duration::seconds(offset))
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+    sqlx::query(
+        "INSERT INTO metrics (name, metric_type, value, labels, recorded_at)
+         VALUES ('queue_depth', 'gauge', 7, '{\"queue\": \"default\"}', NOW() - INTERVAL '1 minute')",
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let written = starter::monitoring::retention::rollup_metrics(
+        conn.as_mut(),
+        &app.config.monitoring,
+        Utc::now() + chrono::Duration::seconds(1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(written, 2);
+
+    // Recent ranges are served from raw samples
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/metrics/series?name=queue_depth",
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["resolution"], "raw");
+    assert_eq!(json["data"]["points"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"]["points"][0]["last"], 7.0);
+
+    // The old bucket aggregates its three samples
+    let range = format!(
+        "start_time={}&end_time={}",
+        (bucket - chrono::Duration::minutes(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        (bucket + chrono::Duration::hours(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    let response = app
+        .get_auth(
+            &format!(
+                "/api/v1/monitoring/metrics/series?name=queue_depth&resolution=rollup&{range}"
+            ),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["resolution"], "rollup");
+    assert_eq!(json["data"]["resolution_secs"], 300);
+    let points = json["data"]["points"].as_array().unwrap();
+    assert_eq!(points.len(), 1);
+    assert_eq!(points[0]["count"], 3);
+    assert_eq!(points[0]["sum"], 6.0);
+    assert_eq!(points[0]["min"], 1.0);
+    assert_eq!(points[0]["max"], 3.0);
+    assert_eq!(points[0]["avg"], 2.0);
+    assert_eq!(points[0]["last"], 3.0);
+    assert_eq!(points[0]["labels"]["queue"], "default");
+
+    // Rolling up again is idempotent
+    let written = starter::monitoring::retention::rollup_metrics(
+        conn.as_mut(),
+        &app.config.monitoring,
+        Utc::now() + chrono::Duration::seconds(1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(written, 0);
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/metrics/series?name=queue_depth&start_time=2026-01-02T00:00:00Z&end_time=2026-01-01T00:00:00Z",
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_metric_query_aggregations_and_matchers() {
+    use chrono::{DurationRound, SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("queryuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let start = (Utc::now() - chrono::Duration::minutes(30))
+        .duration_trunc(chrono::Duration::minutes(1))
+        .unwrap();
+    let samples = [
+        (10, 1.0, json!({"host": "web-1", "env": "prod"})),
+        (20, 2.0, json!({"host": "web-1", "env": "prod"})),
+        (30, 3.0, json!({"host": "web-1", "env": "prod"})),
+        (15, 10.0, json!({"host": "web-2", "env": "prod"})),
+        (70, 100.0, json!({"host": "db-1", "env": "staging"})),
+    ];
+    for (offset, value, labels) in samples {
+        sqlx::query(
+            "INSERT INTO metrics (name, metric_type, value, labels, recorded_at)
+             VALUES ('request_latency', 'gauge', $1, $2, $3)",
+        )
+        .bind(value)
+        .bind(labels)
+        .bind(start + chrono::Duration::seconds(offset))
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+
+    let range = format!(
+        "name=request_latency&step=60&start_time={}&end_time={}",
+        start.to_rfc3339_opts(SecondsFormat::Secs, true),
+        (start + chrono::Duration::minutes(2)).to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    let query = |extra: &str| format!("/api/v1/monitoring/metrics/query?{range}&{extra}");
+
+    // One series per host, averaged per step
+    let response = app
+        .get_auth(&query("labels=env=prod&by=host"), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["aggregation"], "avg");
+    assert_eq!(json["data"]["resolution"], "raw");
+    assert_eq!(json["data"]["step_secs"], 60);
+    let series = json["data"]["series"].as_array().unwrap();
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0]["labels"], json!({"host": "web-1"}));
+    assert_eq!(series[0]["points"][0]["value"], 2.0);
+    assert_eq!(series[1]["labels"], json!({"host": "web-2"}));
+    assert_eq!(series[1]["points"][0]["value"], 10.0);
+
+    let response = app
+        .get_auth(&query("aggregation=max&labels=host=~web-.*"), &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let series = json["data"]["series"].as_array().unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0]["labels"], json!({}));
+    assert_eq!(series[0]["points"].as_array().unwrap().len(), 1);
+    assert_eq!(series[0]["points"][0]["value"], 10.0);
+
+    let response = app
+        .get_auth(&query("aggregation=p50&labels=env!=staging"), &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["aggregation"], "p50");
+    assert_eq!(json["data"]["series"][0]["points"][0]["value"], 2.5);
+
+    // Steps without samples are omitted
+    let response = app
+        .get_auth(&query("aggregation=count"), &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let points = json["data"]["series"][0]["points"].as_array().unwrap();
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0]["value"], 4.0);
+    assert_eq!(points[1]["value"], 1.0);
+    assert_eq!(
+        points[1]["timestamp"].as_str().unwrap(),
+        (start + chrono::Duration::minutes(1)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+
+    for invalid in [
+        "aggregation=p100",
+        "labels=host=~(",
+        "labels=host",
+        "step=0",
+        "aggregation=p95&resolution=rollup",
+    ] {
+        let response = app.get_auth(&query(invalid), &token.token).await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_grafana_datasource_queries_metrics() {
+    use chrono::{DurationRound, SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("grafanamod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let start = (Utc::now() - chrono::Duration::minutes(30))
+        .duration_trunc(chrono::Duration::minutes(1))
+        .unwrap();
+    for (offset, value, host, env) in [
+        (10, 1.0, "web-1", "prod"),
+        (20, 3.0, "web-1", "prod"),
+        (15, 10.0, "web-2", "prod"),
+        (70, 100.0, "db-1", "staging"),
+    ] {
+        let metric = json!({
+            "name": "grafana_latency",
+            "metric_type": "gauge",
+            "value": value,
+            "labels": {"host": host, "env": env},
+            "recorded_at": (start + chrono::Duration::seconds(offset))
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+        });
+        let response = app
+            .post_json_auth("/api/v1/monitoring/metrics", &metric, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app
+        .get_auth("/api/v1/monitoring/grafana", &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/grafana/metrics",
+            &json!({"metric": "grafana_lat"}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json[0]["value"], "grafana_latency");
+    assert_eq!(json[0]["payloads"][0]["name"], "aggregation");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/grafana/tag-values",
+            &json!({"key": "env"}),
+            &token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let values: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|value| value["text"].as_str().unwrap())
+        .collect();
+    assert!(values.contains(&"prod") && values.contains(&"staging"));
+
+    let query = json!({
+        "range": {
+            "from": start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            "to": (start + chrono::Duration::minutes(2)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        },
+        "intervalMs": 60000,
+        "maxDataPoints": 500,
+        "targets": [
+            {"refId": "A", "target": "grafana_latency", "payload": {"aggregation": "max", "by": "host"}},
+            {"refId": "B", "target": "grafana_latency", "hide": true}
+        ],
+        "adhocFilters": [{"key": "env", "operator": "=", "value": "prod"}]
+    });
+    let response = app
+        .post_json_auth("/api/v1/monitoring/grafana/query", &query, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let series = json.as_array().unwrap();
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0]["target"], "grafana_latency{host=\"web-1\"}");
+    assert_eq!(series[0]["refId"], "A");
+    assert_eq!(
+        series[0]["datapoints"],
+        json!([[3.0, start.timestamp_millis()]])
+    );
+    assert_eq!(series[1]["target"], "grafana_latency{host=\"web-2\"}");
+
+    let mut invalid = query.clone();
+    invalid["adhocFilters"][0]["operator"] = json!("<");
+    let response = app
+        .post_json_auth("/api/v1/monitoring/grafana/query", &invalid, &token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .client
+        .post(format!("{}/api/v1/monitoring/grafana/query", app.address))
+        .json(&query)
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_histogram_and_summary_metrics() {
+    use chrono::{DurationRound, SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("histuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let start = (Utc::now() - chrono::Duration::minutes(30))
+        .duration_trunc(chrono::Duration::minutes(1))
+        .unwrap();
+    for (offset, counts) in [(10, [2, 6, 2, 0]), (40, [0, 2, 0, 0])] {
+        let histogram = json!({
+            "name": "latency_api",
+            "bounds": [1.0, 2.0, 4.0],
+            "counts": counts,
+            "sum": 20.0,
+            "labels": {"route": "/a"},
+            "recorded_at": (start + chrono::Duration::seconds(offset)).to_rfc3339(),
+        });
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics/histogram",
+                &histogram,
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        // _count, _sum and one _bucket row per bucket
+        assert_eq!(json["data"]["stored"], 6);
+    }
+
+    // Percentiles of a histogram are estimated from the bucket counts of the step
+    let query = |aggregation: &str| {
+        format!(
+            "/api/v1/monitoring/metrics/query?name=latency_api&step=60&by=route&aggregation={aggregation}&start_time={}&end_time={}",
+            start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            (start + chrono::Duration::minutes(2)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+    };
+    let response = app.get_auth(&query("p50"), &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let series = json["data"]["series"].as_array().unwrap();
+    assert_eq!(series.len(), 1);
+    assert_eq!(series[0]["labels"], json!({"route": "/a"}));
+    assert_eq!(series[0]["points"][0]["value"], 1.5);
+
+    let response = app.get_auth(&query("p95"), &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let p95 = json["data"]["series"][0]["points"][0]["value"]
+        .as_f64()
+        .unwrap();
+    assert!((p95 - 3.4).abs() < 1e-9);
+
+    let summary = json!({
+        "name": "latency_db",
+        "quantiles": [{"quantile": 0.5, "value": 0.2}, {"quantile": 0.99, "value": 0.9}],
+        "sum": 12.5,
+        "count": 40,
+    });
+    let response = app
+        .post_json_auth("/api/v1/monitoring/metrics/summary", &summary, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["stored"], 4);
+
+    for invalid in [
+        json!({"name": "latency_api", "bounds": [1.0, 2.0], "counts": [1, 2]}),
+        json!({"name": "latency_api", "bounds": [2.0, 1.0], "counts": [1, 2, 3]}),
+        json!({"name": "latency_api", "bounds": [1.0], "counts": [1, 2], "labels": {"le": "1"}}),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics/histogram",
+                &invalid,
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/metrics/summary",
+            &json!({"name": "latency_db", "quantiles": [{"quantile": 1.5, "value": 1.0}], "count": 1}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/metrics/histogram",
+            &json!({"name": "system_latency", "bounds": [1.0], "counts": [1, 0]}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Exposed as one family each, with buckets summed over the window
+    let response = app
+        .get_auth("/api/v1/monitoring/metrics/prometheus", &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let text = response.text().await.unwrap();
+    assert!(text.contains("# TYPE latency_api histogram"));
+    assert!(text.contains("# TYPE latency_db summary"));
+    assert!(!text.contains("# TYPE latency_api_bucket"));
+    let inf_bucket = text
+        .lines()
+        .find(|line| line.starts_with("latency_api_bucket{") && line.contains(r#"le="+Inf""#))
+        .unwrap();
+    assert_eq!(inf_bucket.split(' ').nth(1), Some("12"));
+    assert!(
+        text.lines()
+            .any(|line| line.starts_with("latency_api_sum{") && line.contains(" 40 "))
+    );
+    assert!(
+        text.lines()
+            .any(|line| line.starts_with("latency_db{") && line.contains(r#"quantile="0.99""#))
+    );
+}
+
+#[tokio::test]
+async fn test_metric_retention_policies_and_pruning() {
+    use chrono::{SecondsFormat, Utc};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("retuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, user_token) = factory.create_authenticated_user(&unique_username).await;
+    let admin_username = format!("retadmin_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_admin, admin_token) = factory.create_authenticated_admin(&admin_username).await;
+
+    let policy = json!({ "raw_retention_days": 1, "rollup_retention_days": 30 });
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/retention/queue_depth",
+            &policy,
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/retention/queue_depth",
+            &json!({ "raw_retention_days": 0, "rollup_retention_days": 30 }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/retention/queue_depth",
+            &policy,
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["metric_name"], "queue_depth");
+    assert_eq!(json["data"]["raw_retention_days"], 1);
+
+    let response = app
+        .get_auth("/api/v1/admin/monitoring/retention", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["default_raw_retention_days"],
+        app.config.monitoring.metric_raw_retention_days
+    );
+    assert_eq!(json["data"]["policies"].as_array().unwrap().len(), 1);
+
+    // Two-day-old samples are past the metric's raw retention but not the default
+    for name in ["queue_depth", "cpu_usage"] {
+        sqlx::query(
+            "INSERT INTO metrics (name, metric_type, value, recorded_at)
+             VALUES ($1, 'gauge', 1, NOW() - INTERVAL '2 days'), ($1, 'gauge', 2, NOW())",
+        )
+        .bind(name)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+    sqlx::query(
+        "INSERT INTO metric_rollups
+            (name, metric_type, resolution_secs, bucket_start, count, sum, min, max, last)
+         VALUES ('queue_depth', 'gauge', 300, NOW() - INTERVAL '10 days', 1, 1, 1, 1, 1),
+                ('queue_depth', 'gauge', 300, NOW() - INTERVAL '40 days', 1, 1, 1, 1, 1)",
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Auto resolution switches to rollups once the range passes raw retention
+    let start = (Utc::now() - chrono::Duration::days(3)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/metrics/series?name=queue_depth&start_time={start}"),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["resolution"], "rollup");
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let (raw_deleted, rollups_deleted) = starter::monitoring::retention::prune_metrics(
+        conn.as_mut(),
+        &app.config.monitoring,
+        Utc::now(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(raw_deleted, 1);
+    assert_eq!(rollups_deleted, 1);
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM metrics")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 3);
+
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/monitoring/retention/queue_depth",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/monitoring/retention/queue_depth",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_otlp_trace_and_log_ingestion() {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("otlpmod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let trace_id = Uuid::new_v4().simple().to_string();
+    let resource = json!({"attributes": [
+        {"key": "service.name", "value": {"stringValue": "checkout"}}
+    ]});
+
+    let traces = json!({
+        "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{
+                "scope": {"name": "checkout-http"},
+                "spans": [{
+                    "traceId": trace_id,
+                    "spanId": "eee19b7ec3c1b174",
+                    "name": "POST /orders",
+                    "kind": 2,
+                    "startTimeUnixNano": "1700000000000000000",
+                    "endTimeUnixNano": "1700000000120000000",
+                    "status": {"code": 2}
+                }]
+            }]
+        }]
+    });
+    let response = app
+        .post_json_auth("/api/v1/monitoring/otlp/traces", &traces, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json, json!({}));
+
+    // Logs arrive gzip-compressed at the SDK-style /v1 path
+    let logs = json!({
+        "resourceLogs": [{
+            "resource": resource,
+            "scopeLogs": [{
+                "logRecords": [{
+                    "timeUnixNano": "1700000000050000000",
+                    "severityNumber": 17,
+                    "body": {"stringValue": "payment declined"},
+                    "traceId": trace_id,
+                    "spanId": "eee19b7ec3c1b174"
+                }]
+            }]
+        }]
+    });
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(logs.to_string().as_bytes()).unwrap();
+    let response = app
+        .client
+        .post(format!("{}/api/v1/monitoring/otlp/v1/logs", app.address))
+        .header("Authorization", format!("Bearer {}", token.token))
+        .header("Content-Type", "application/json")
+        .header("Content-Encoding", "gzip")
+        .body(encoder.finish().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+
+    // The span and the log share the trace_id tag
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/events?tags=trace_id:{trace_id}"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let events = json["data"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+
+    let span = events.iter().find(|e| e["event_type"] == "trace").unwrap();
+    assert_eq!(span["source"], "checkout");
+    assert_eq!(span["message"], "POST /orders");
+    assert_eq!(span["level"], "error");
+    assert_eq!(span["payload"]["duration_ms"], 120.0);
+    assert_eq!(span["recorded_at"], "2023-11-14T22:13:20Z");
+
+    let log = events.iter().find(|e| e["event_type"] == "log").unwrap();
+    assert_eq!(log["message"], "payment declined");
+    assert_eq!(log["level"], "error");
+
+    // Protobuf is not supported
+    let response = app
+        .client
+        .post(format!("{}/api/v1/monitoring/otlp/traces", app.address))
+        .header("Authorization", format!("Bearer {}", token.token))
+        .header("Content-Type", "application/x-protobuf")
+        .body(vec![0u8; 4])
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/otlp/traces",
+            &json!({"resourceSpans": "nope"}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_event_batch_ingestion_reports_item_errors() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("batchuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let post_batch = |content_type: &'static str, body: String| {
+        app.client
+            .post(format!("{}/api/v1/monitoring/events/batch", app.address))
+            .header("Authorization", format!("Bearer {}", token.token))
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+    };
+
+    let ndjson = [
+        r#"{"event_type": "log", "source": "app-batch", "message": "one", "level": "info"}"#,
+        "",
+        "not json",
+        r#"{"event_type": "log", "source": "system-core", "message": "not mine"}"#,
+        r#"{"event_type": "bogus", "source": "app-batch"}"#,
+        r#"{"event_type": "log", "source": "app-batch", "message": "two", "tags": {"run": "1"}}"#,
+    ]
+    .join("\n");
+    let response = post_batch("application/x-ndjson", ndjson).await.unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["accepted"], 2);
+    assert_eq!(json["data"]["rejected"], 3);
+    let errors = json["data"]["errors"].as_array().unwrap();
+    let indexes: Vec<_> = errors
+        .iter()
+        .map(|e| e["index"].as_u64().unwrap())
+        .collect();
+    assert_eq!(indexes, [1, 2, 3]);
+    assert!(
+        errors[1]["message"]
+            .as_str()
+            .unwrap()
+            .contains("not authorized")
+    );
+
+    let array = json!([
+        {"event_type": "log", "source": "app-batch", "message": "three"},
+        {"event_type": "trace", "source": "app-batch", "message": "four"}
+    ]);
+    let response = post_batch("application/json", array.to_string())
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["accepted"], 2);
+    assert_eq!(json["data"]["errors"], json!([]));
+
+    let response = app
+        .get_auth("/api/v1/monitoring/events?source=app-batch", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 4);
+
+    // Over the item limit, not an array, or an unsupported type: the whole batch fails
+    let too_many = vec![json!({"event_type": "log", "source": "app-batch"}); 1001];
+    let response = post_batch("application/json", json!(too_many).to_string())
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = post_batch("application/json", "{}".to_string())
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = post_batch("text/plain", "hello".to_string()).await.unwrap();
+    assert_status(&response, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_event_ingestion_limits_per_source() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("limituser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+    let admin_username = format!("limitadmin_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_admin, admin_token) = factory.create_authenticated_admin(&admin_username).await;
+
+    // Only admins manage the limits, and ratios must be within 0-1
+    let response = app
+        .get_auth("/api/v1/admin/monitoring/event-limits", &token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/event-limits/app-limited",
+            &json!({"events_per_minute": 2, "sample_ratio": 1.5}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    for (source, limits) in [
+        (
+            "app-limited",
+            json!({"events_per_minute": 2, "sample_ratio": 1.0}),
+        ),
+        (
+            "app-sampled",
+            json!({"events_per_minute": 0, "sample_ratio": 0.0}),
+        ),
+    ] {
+        let response = app
+            .put_json_auth(
+                &format!("/api/v1/admin/monitoring/event-limits/{source}"),
+                &limits,
+                &admin_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app
+        .get_auth("/api/v1/admin/monitoring/event-limits", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["default_sample_ratio"], 1.0);
+    let sources: Vec<_> = json["data"]["limits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|limit| limit["source"].as_str().unwrap())
+        .collect();
+    assert_eq!(sources, ["app-limited", "app-sampled"]);
+
+    // The third event within the minute is over the source's limit
+    let event = json!({"event_type": "log", "source": "app-limited", "message": "hello"});
+    for _ in 0..2 {
+        let response = app
+            .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+
+    // Sampled events are acknowledged but not stored
+    let sampled = json!({"event_type": "log", "source": "app-sampled", "message": "hello"});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &sampled, &token.token)
+        .await;
+    assert_status(&response, StatusCode::ACCEPTED);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].is_null());
+    assert!(json["message"].as_str().unwrap().contains("sampled"));
+
+    let batch = json!([sampled, event, {"event_type": "log", "source": "app-other"}]);
+    let response = app
+        .client
+        .post(format!("{}/api/v1/monitoring/events/batch", app.address))
+        .header("Authorization", format!("Bearer {}", token.token))
+        .header("Content-Type", "application/json")
+        .body(batch.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["accepted"], 1);
+    assert_eq!(json["data"]["sampled"], 1);
+    assert_eq!(json["data"]["rejected"], 1);
+    assert_eq!(json["data"]["errors"][0]["index"], 1);
+    assert!(
+        json["data"]["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Rate limit exceeded")
+    );
+
+    let response = app
+        .get_auth("/api/v1/monitoring/events?source=app-sampled", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+
+    // Without the override the source falls back to the defaults
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/monitoring/event-limits/app-sampled",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &sampled, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/monitoring/event-limits/app-sampled",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_daily_ingestion_quotas() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("quotauser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (user, token) = factory.create_authenticated_user(&unique_username).await;
+    let admin_username = format!("quotaadmin_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_admin, admin_token) = factory.create_authenticated_admin(&admin_username).await;
+
+    // Metrics have no source, and user quotas need an existing user
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/quotas/source/app-quota",
+            &json!({"daily_events": 2, "daily_metrics": 5}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/monitoring/quotas/user/{}", Uuid::new_v4()),
+            &json!({"daily_metrics": 1}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/monitoring/quotas/source/app-quota",
+            &json!({"daily_events": 2}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/monitoring/quotas/user/{}", user.id),
+            &json!({"daily_metrics": 1}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Two events fit the source's quota; the third in the batch does not
+    let event = json!({"event_type": "log", "source": "app-quota", "message": "hello"});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let batch = json!([event, event, {"event_type": "log", "source": "app-other"}]);
+    let response = app
+        .client
+        .post(format!("{}/api/v1/monitoring/events/batch", app.address))
+        .header("Authorization", format!("Bearer {}", token.token))
+        .header("Content-Type", "application/json")
+        .body(batch.to_string())
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["accepted"], 2);
+    assert_eq!(json["data"]["errors"][0]["index"], 1);
+    assert!(
+        json["data"]["errors"][0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Daily event quota of 2 for source 'app-quota' reached")
+    );
+
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "QUOTA_EXCEEDED");
+
+    // The user's metric quota allows one row today
+    let metric = json!({"name": "latency_quota", "metric_type": "gauge", "value": 1.0});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/metrics", &metric, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/monitoring/metrics", &metric, &token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+
+    let response = app
+        .get_auth("/api/v1/monitoring/stats", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let usage = json["data"]["ingestion_usage"].as_array().unwrap();
+    let find = |scope: &str, key: &str| {
+        usage
+            .iter()
+            .find(|entry| entry["scope"] == scope && entry["key"] == key)
+            .cloned()
+            .unwrap()
+    };
+    let source = find("source", "app-quota");
+    assert_eq!(source["events"], 2);
+    assert_eq!(source["daily_events"], 2);
+    let user_usage = find("user", &user.id.to_string());
+    assert_eq!(user_usage["events"], 3);
+    assert_eq!(user_usage["metrics"], 1);
+    assert_eq!(user_usage["daily_metrics"], 1);
+    assert_eq!(find("source", "app-other")["daily_events"], json!(null));
+    assert!(json["data"]["quotas_reset_at"].is_string());
+
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/monitoring/quotas/source/app-quota",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_event_stream_pushes_matching_events() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("streamuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let mut stream = app
+        .client
+        .get(format!(
+            "{}/api/v1/monitoring/events/stream?level=error&tags=region:eu",
+            app.address
+        ))
+        .header("Authorization", format!("Bearer {}", token.token))
+        .send()
+        .await
+        .unwrap();
+    assert_status(&stream, StatusCode::OK);
+    assert_eq!(
+        stream.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    for (message, level, region) in [
+        ("skipped level", "info", "eu"),
+        ("skipped region", "error", "us"),
+        ("streamed", "error", "eu"),
+    ] {
+        let event = json!({
+            "event_type": "log",
+            "source": "app-stream",
+            "message": message,
+            "level": level,
+            "tags": {"region": region}
+        });
+        let response = app
+            .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let mut received = String::new();
+    while !received.contains("streamed") {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(10), stream.chunk())
+            .await
+            .expect("no streamed event within 10 seconds")
+            .unwrap()
+            .expect("stream ended");
+        received.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    assert!(received.starts_with("event: event\n"));
+    assert!(!received.contains("skipped"));
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/events/stream?tags=invalid",
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_otlp_metric_ingestion_reports_rejected_points() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("otlpuser_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    // System metrics need moderator access, so that point is rejected
+    let metrics = json!({
+        "resourceMetrics": [{
+            "resource": {"attributes": [
+                {"key": "service.name", "value": {"stringValue": "worker"}}
+            ]},
+            "scopeMetrics": [{
+                "metrics": [{
+                    "name": "cpu_usage",
+                    "gauge": {"dataPoints": [{
+                        "asDouble": 0.42,
+                        "attributes": [{"key": "core", "value": {"intValue": "3"}}]
+                    }]}
+                }, {
+                    "name": "system_load",
+                    "gauge": {"dataPoints": [{"asDouble": 1.5}]}
+                }]
+            }]
+        }]
+    });
+    let response = app
+        .post_json_auth("/api/v1/monitoring/otlp/metrics", &metrics, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["partialSuccess"]["rejectedDataPoints"], 1);
+    assert!(
+        json["partialSuccess"]["errorMessage"]
+            .as_str()
+            .unwrap()
+            .contains("system_load")
+    );
+
+    let response = app
+        .get_auth("/api/v1/monitoring/metrics?name=cpu_usage", &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let stored = json["data"].as_array().unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0]["metric_type"], "gauge");
+    assert_eq!(stored[0]["value"], 0.42);
+    assert_eq!(stored[0]["labels"]["service.name"], "worker");
+    assert_eq!(stored[0]["labels"]["core"], "3");
+}
+
+#[tokio::test]
+async fn test_incident_escalation_policy_runs_until_acknowledged() {
+    use async_trait::async_trait;
+    use chrono::{Duration, Utc};
+    use starter::monitoring::escalation;
+    use starter::tasks::services::{EmailMessage, EmailSender};
+    use starter::tasks::types::TaskError;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct CapturingSender(Arc<Mutex<Vec<EmailMessage>>>);
+
+    #[async_trait]
+    impl EmailSender for CapturingSender {
+        async fn send(&self, message: &EmailMessage) -> Result<(), TaskError> {
+            self.0.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("escmod_{suffix}"))
+        .await;
+    let (first, _first_token) = factory
+        .create_authenticated_user(&format!("escfirst_{suffix}"))
+        .await;
+    let (second, second_token) = factory
+        .create_authenticated_user(&format!("escsecond_{suffix}"))
+        .await;
+    let (_other, other_token) = factory
+        .create_authenticated_user(&format!("escother_{suffix}"))
+        .await;
+
+    let policy = json!({
+        "name": "Primary on-call",
+        "steps": [
+            { "notify_user_id": first.id, "delay_minutes": 0 },
+            { "notify_user_id": second.id, "delay_minutes": 10 }
+        ]
+    });
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &policy,
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &json!({ "name": "Empty", "steps": [] }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &policy,
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let policy_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["steps"].as_array().unwrap().len(), 2);
+    assert_eq!(json["data"]["steps"][1]["step_order"], 1);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &policy,
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Only moderators can attach a policy to an incident
+    let incident = json!({
+        "title": "Checkout is down",
+        "severity": "critical",
+        "escalation_policy_id": policy_id
+    });
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &incident,
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth("/api/v1/monitoring/incidents", &incident, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["escalation_policy_id"], policy_id.as_str());
+    assert!(json["data"]["next_escalation_at"].is_string());
+    assert!(json["data"]["escalation_step"].is_null());
+
+    // The first step is due immediately; the second only after its delay
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let now = Utc::now();
+    assert_eq!(
+        escalation::escalate_due_incidents(conn.as_mut(), now)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        escalation::escalate_due_incidents(conn.as_mut(), now)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}"),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["assigned_to"], first.id.to_string());
+    assert_eq!(json["data"]["escalation_step"], 0);
+
+    assert_eq!(
+        escalation::escalate_due_incidents(conn.as_mut(), now + Duration::minutes(11))
+            .await
+            .unwrap(),
+        1
+    );
+
+    let sender = CapturingSender::default();
+    assert_eq!(
+        escalation::send_pending_notifications(conn.as_mut(), &sender)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        escalation::send_pending_notifications(conn.as_mut(), &sender)
+            .await
+            .unwrap(),
+        0
+    );
+    let sent = sender.0.lock().unwrap().clone();
+    assert_eq!(sent[0].to, first.email);
+    assert_eq!(sent[1].to, second.email);
+    assert!(sent[1].subject.contains("Checkout is down"));
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/escalations"),
+            &second_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let escalations = json["data"].as_array().unwrap();
+    assert_eq!(escalations.len(), 2);
+    assert_eq!(escalations[1]["notified_user_id"], second.id.to_string());
+    assert_eq!(escalations[1]["reason"], "policy");
+    assert!(escalations[1]["notified_at"].is_string());
+
+    // The policy is out of steps
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/escalate"),
+            &json!({}),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // The current assignee can acknowledge; unrelated users cannot
+    let ack_path = format!("/api/v1/monitoring/incidents/{incident_id}/acknowledge");
+    let response = app
+        .post_json_auth(&ack_path, &json!({}), &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(&ack_path, &json!({}), &second_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "investigating");
+    assert_eq!(json["data"]["acknowledged_by"], second.id.to_string());
+    assert!(json["data"]["next_escalation_at"].is_null());
+
+    let response = app
+        .post_json_auth(&ack_path, &json!({}), &second_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let resolve_path = format!("/api/v1/monitoring/incidents/{incident_id}/resolve");
+    let response = app
+        .post_json_auth(
+            &resolve_path,
+            &json!({ "root_cause": "Expired TLS certificate" }),
+            &second_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "resolved");
+    assert_eq!(json["data"]["root_cause"], "Expired TLS certificate");
+    assert!(json["data"]["resolved_at"].is_string());
+
+    let response = app
+        .post_json_auth(&resolve_path, &json!({}), &second_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_incident_manual_escalation_and_policy_deletion() {
+    use chrono::{Duration, Utc};
+    use starter::monitoring::escalation;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("escmod_{suffix}"))
+        .await;
+    let (first, first_token) = factory
+        .create_authenticated_user(&format!("escfirst_{suffix}"))
+        .await;
+    let (second, _second_token) = factory
+        .create_authenticated_user(&format!("escsecond_{suffix}"))
+        .await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &json!({
+                "name": "Slow escalation",
+                "steps": [
+                    { "notify_user_id": first.id, "delay_minutes": 30 },
+                    { "notify_user_id": second.id, "delay_minutes": 30 }
+                ]
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let policy_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/escalation-policies/{policy_id}"),
+            &first_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({
+                "title": "Queue backlog growing",
+                "severity": "high",
+                "escalation_policy_id": policy_id
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Manual escalation skips the first step's delay
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/escalate"),
+            &json!({}),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["assigned_to"], first.id.to_string());
+    assert_eq!(json["data"]["escalation_step"], 0);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/escalations"),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["reason"], "manual");
+    assert_eq!(json["data"][0]["triggered_by"], moderator.id.to_string());
+
+    // Deleting the policy stops further escalation
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/monitoring/escalation-policies/{policy_id}"),
+            &first_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/monitoring/escalation-policies/{policy_id}"),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    assert_eq!(
+        escalation::escalate_due_incidents(conn.as_mut(), Utc::now() + Duration::hours(2))
+            .await
+            .unwrap(),
+        0
+    );
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}"),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"]["escalation_policy_id"].is_null());
+    assert!(json["data"]["next_escalation_at"].is_null());
+    assert_eq!(json["data"]["assigned_to"], first.id.to_string());
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/escalation-policies/{policy_id}"),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_oncall_schedule_resolves_escalation_target() {
+    use chrono::{Duration, Utc};
+    use starter::monitoring::escalation;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("oncallmod_{suffix}"))
+        .await;
+    let (alice, alice_token) = factory
+        .create_authenticated_user(&format!("oncallalice_{suffix}"))
+        .await;
+    let (bob, _bob_token) = factory
+        .create_authenticated_user(&format!("oncallbob_{suffix}"))
+        .await;
+
+    let now = Utc::now();
+    let schedule = json!({
+        "name": "Platform on-call",
+        "rotation_period_hours": 24,
+        "handoff_at": now - Duration::hours(1),
+        "members": [alice.id, bob.id]
+    });
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/oncall/schedules",
+            &schedule,
+            &alice_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/oncall/schedules",
+            &json!({ "name": "Nobody", "rotation_period_hours": 24, "members": [] }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/oncall/schedules",
+            &schedule,
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let schedule_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["members"][1], bob.id.to_string());
+
+    let current_path = format!("/api/v1/monitoring/oncall/current?schedule_id={schedule_id}");
+    let response = app.get_auth(&current_path, &alice_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["user_id"], alice.id.to_string());
+    assert_eq!(json["data"][0]["schedule_name"], "Platform on-call");
+    assert!(json["data"][0]["override_id"].is_null());
+
+    // Bob covers for Alice for the next hour
+    let overrides_path = format!("/api/v1/monitoring/oncall/schedules/{schedule_id}/overrides");
+    let response = app
+        .post_json_auth(
+            &overrides_path,
+            &json!({
+                "user_id": bob.id,
+                "starts_at": now - Duration::minutes(10),
+                "ends_at": now - Duration::minutes(20)
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            &overrides_path,
+            &json!({
+                "user_id": bob.id,
+                "starts_at": now - Duration::minutes(10),
+                "ends_at": now + Duration::hours(1)
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let override_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app.get_auth(&current_path, &alice_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["user_id"], bob.id.to_string());
+    assert_eq!(json["data"][0]["override_id"], override_id.as_str());
+
+    // Steps name either a user or a schedule
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &json!({
+                "name": "Ambiguous",
+                "steps": [
+                    { "notify_user_id": alice.id, "notify_schedule_id": schedule_id, "delay_minutes": 0 }
+                ]
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &json!({
+                "name": "Unknown schedule",
+                "steps": [ { "notify_schedule_id": Uuid::new_v4(), "delay_minutes": 0 } ]
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/escalation-policies",
+            &json!({
+                "name": "Current on-call",
+                "steps": [ { "notify_schedule_id": schedule_id, "delay_minutes": 0 } ]
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let policy_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert!(json["data"]["steps"][0]["notify_user_id"].is_null());
+    assert_eq!(
+        json["data"]["steps"][0]["notify_schedule_id"],
+        schedule_id.as_str()
+    );
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({
+                "title": "API latency spike",
+                "severity": "high",
+                "escalation_policy_id": policy_id
+            }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    assert_eq!(
+        escalation::escalate_due_incidents(conn.as_mut(), Utc::now())
+            .await
+            .unwrap(),
+        1
+    );
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/escalations"),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["notified_user_id"], bob.id.to_string());
+    assert_eq!(json["data"][0]["oncall_schedule_id"], schedule_id.as_str());
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}"),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["assigned_to"], bob.id.to_string());
+
+    // Schedules in use by a policy cannot be deleted
+    let schedule_path = format!("/api/v1/monitoring/oncall/schedules/{schedule_id}");
+    let response = app.delete_auth(&schedule_path, &mod_token.token).await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .delete_auth(&format!("{overrides_path}/{override_id}"), &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth(&current_path, &alice_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["user_id"], alice.id.to_string());
+
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/monitoring/escalation-policies/{policy_id}"),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.delete_auth(&schedule_path, &mod_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth(&current_path, &alice_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_recording_rules_store_aggregated_series() {
+    use chrono::{DurationRound, TimeDelta, Utc};
+    use starter::core::config::AppConfig;
+    use starter::monitoring::recording::{self, RecordingRun};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("recmod_{suffix}"))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("recuser_{suffix}"))
+        .await;
+
+    let rule = json!({
+        "name": "route:latency_ms:avg1m",
+        "expression": "avg(latency_ms{env=prod}) by (route)",
+        "interval_secs": 60
+    });
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/recording-rules",
+            &rule,
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({"name": "x", "expression": "latency_ms", "interval_secs": 60}),
+        json!({"name": "x", "expression": "avg(latency_ms)", "interval_secs": 5}),
+        json!({"name": "latency_ms", "expression": "avg(latency_ms)", "interval_secs": 60}),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/recording-rules",
+                &invalid,
+                &mod_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/recording-rules",
+            &rule,
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let rule_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/recording-rules",
+            &rule,
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Two complete one-minute windows of samples, plus one from staging
+    let base = Utc::now().duration_trunc(TimeDelta::minutes(1)).unwrap() - TimeDelta::minutes(5);
+    for (offset, route, env, value) in [
+        (10, "/a", "prod", 10.0),
+        (20, "/a", "prod", 20.0),
+        (30, "/b", "prod", 5.0),
+        (40, "/b", "staging", 500.0),
+        (70, "/a", "prod", 40.0),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics",
+                &json!({
+                    "name": "latency_ms",
+                    "metric_type": "gauge",
+                    "value": value,
+                    "labels": {"route": route, "env": env},
+                    "recorded_at": base + TimeDelta::seconds(offset)
+                }),
+                &mod_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    sqlx::query("UPDATE recording_rules SET last_window_end = $1")
+        .bind(base)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let config = AppConfig::default().monitoring;
+    let now = base + TimeDelta::seconds(160);
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let run = recording::evaluate_rules(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!(
+        run,
+        RecordingRun {
+            evaluated: 1,
+            recorded: 3,
+            failed: 0
+        }
+    );
+    // Nothing is due until the next window completes
+    let run = recording::evaluate_rules(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!(run, RecordingRun::default());
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/metrics?name=route:latency_ms:avg1m",
+            &user_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let mut points: Vec<(String, String, f64)> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|metric| {
+            (
+                metric["recorded_at"].as_str().unwrap().to_string(),
+                metric["labels"]["route"].as_str().unwrap().to_string(),
+                metric["value"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let at = |offset| {
+        (base + TimeDelta::seconds(offset)).to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+    };
+    assert_eq!(
+        points,
+        [
+            (at(0), "/a".to_string(), 15.0),
+            (at(0), "/b".to_string(), 5.0),
+            (at(60), "/a".to_string(), 40.0),
+        ]
+    );
+
+    let response = app
+        .get_auth("/api/v1/monitoring/recording-rules", &user_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["last_error"], json!(null));
+    assert!(json["data"][0]["last_window_end"].is_string());
+
+    let path = format!("/api/v1/monitoring/recording-rules/{rule_id}");
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_uptime_checks_fire_and_resolve_alerts() {
+    use axum::{Json, Router, extract::State, routing::get, routing::post};
+    use chrono::{TimeDelta, Utc};
+    use starter::monitoring::uptime::{self, UptimeRun};
+    use starter::tasks::services::TaskServices;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Target {
+        down: Arc<AtomicBool>,
+        notifications: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    // Local endpoint to check, which also receives the alert webhooks
+    let target = Target::default();
+    let receiver = Router::new()
+        .route(
+            "/health",
+            get(|State(target): State<Target>| async move {
+                if target.down.load(Ordering::SeqCst) {
+                    (StatusCode::SERVICE_UNAVAILABLE, "maintenance")
+                } else {
+                    (StatusCode::OK, "all good")
+                }
+            }),
+        )
+        .route(
+            "/hook",
+            post(
+                |State(target): State<Target>, Json(body): Json<serde_json::Value>| async move {
+                    target.notifications.lock().unwrap().push(body);
+                },
+            ),
+        )
+        .with_state(target.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let receiver_address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("uptimemod_{suffix}"))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("uptimeuser_{suffix}"))
+        .await;
+
+    let check = json!({
+        "name": "api",
+        "url": format!("{receiver_address}/health"),
+        "expected_body": "all good",
+        "failure_threshold": 2
+    });
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/uptime-checks",
+            &check,
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({"name": "x", "url": "ftp://example.com"}),
+        json!({"name": "x", "url": "http://example.com", "interval_secs": 1}),
+        json!({"name": "x", "url": "http://example.com", "expected_status": 42}),
+        json!({"name": " ", "url": "http://example.com"}),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/uptime-checks",
+                &invalid,
+                &mod_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+    let response = app
+        .post_json_auth("/api/v1/monitoring/uptime-checks", &check, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+    assert_eq!(json["data"]["interval_secs"], 60);
+    let check_id = json["data"]["id"].as_str().unwrap().to_string();
+    let alert_id = json["data"]["alert_id"].as_str().unwrap().to_string();
+    let response = app
+        .post_json_auth("/api/v1/monitoring/uptime-checks", &check, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/notification-channels",
+            &json!({ "name": "Ops webhook", "kind": "webhook", "target": format!("{receiver_address}/hook") }),
+            &mod_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let channel_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/monitoring/alerts/{alert_id}/channels"),
+            &json!({ "channel_ids": [channel_id] }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let alert_status = async || {
+        let response = app
+            .get_auth("/api/v1/monitoring/alerts", &mod_token.token)
+            .await;
+        let json: serde_json::Value = response.json().await.unwrap();
+        json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|alert| alert["id"] == alert_id.as_str())
+            .map(|alert| alert["status"].as_str().unwrap().to_string())
+    };
+    assert_eq!(alert_status().await.as_deref(), Some("resolved"));
+
+    let services = TaskServices::default();
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let start = Utc::now();
+    let mut run_at = async |minutes: i64| {
+        uptime::run_due_checks(
+            conn.as_mut(),
+            &services,
+            start + TimeDelta::minutes(minutes),
+        )
+        .await
+        .unwrap()
+    };
+
+    let run = run_at(0).await;
+    assert_eq!((run.checked, run.failed), (1, 0));
+    // Not due again until its interval passed
+    assert_eq!(run_at(0).await, UptimeRun::default());
+
+    target.down.store(true, Ordering::SeqCst);
+    let run = run_at(1).await;
+    assert_eq!((run.checked, run.failed, run.alerts_fired), (1, 1, 0));
+    let run = run_at(2).await;
+    assert_eq!((run.checked, run.failed, run.alerts_fired), (1, 1, 1));
+    assert_eq!(alert_status().await.as_deref(), Some("active"));
+    let run = run_at(3).await;
+    assert_eq!((run.failed, run.alerts_fired), (1, 0));
+
+    let response = app
+        .get_auth("/api/v1/monitoring/uptime-checks", &mod_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["status"], "down");
+    assert_eq!(json["data"][0]["consecutive_failures"], 3);
+    assert_eq!(json["data"][0]["last_status_code"], 503);
+    assert_eq!(json["data"][0]["last_error"], "Expected HTTP 200, got 503");
+
+    target.down.store(false, Ordering::SeqCst);
+    let run = run_at(4).await;
+    assert_eq!((run.failed, run.alerts_resolved), (0, 1));
+    assert_eq!(alert_status().await.as_deref(), Some("resolved"));
+
+    let notifications = target.notifications.lock().unwrap().clone();
+    let statuses: Vec<_> = notifications
+        .iter()
+        .map(|notification| notification["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["firing", "resolved"]);
+    assert_eq!(notifications[0]["test"], false);
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/metrics?name=uptime_check_up",
+            &user_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let mut values: Vec<(String, f64)> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|metric| {
+            assert_eq!(metric["labels"]["check"], "api");
+            (
+                metric["recorded_at"].as_str().unwrap().to_string(),
+                metric["value"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let values: Vec<f64> = values.into_iter().map(|(_, value)| value).collect();
+    assert_eq!(values, [1.0, 0.0, 0.0, 0.0, 1.0]);
+
+    let path = format!("/api/v1/monitoring/uptime-checks/{check_id}");
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(alert_status().await, None);
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_alert_routing_rules_page_critical_alerts_only() {
+    use axum::{Json, Router, extract::Path, extract::State, routing::post};
+    use starter::monitoring::models::AlertNotification;
+    use starter::monitoring::{notifications, services};
+    use starter::tasks::services::TaskServices;
+    use std::sync::{Arc, Mutex};
+
+    // Local receiver standing in for Slack and the pager webhook
+    let received: Arc<Mutex<Vec<String>>> = Arc::default();
+    let receiver = Router::new()
+        .route(
+            "/{hook}",
+            post(
+                |State(received): State<Arc<Mutex<Vec<String>>>>,
+                 Path(hook): Path<String>,
+                 Json(_): Json<serde_json::Value>| async move {
+                    received.lock().unwrap().push(hook);
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let receiver_address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("routemod_{suffix}"))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("routeuser_{suffix}"))
+        .await;
+
+    let create = async |path: &str, body: serde_json::Value| {
+        let response = app.post_json_auth(path, &body, &mod_token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        json["data"]["id"].as_str().unwrap().to_string()
+    };
+    let slack_id = create(
+        "/api/v1/monitoring/notification-channels",
+        json!({ "name": "Slack", "kind": "slack", "target": format!("{receiver_address}/slack") }),
+    )
+    .await;
+    let pager_id = create(
+        "/api/v1/monitoring/notification-channels",
+        json!({ "name": "Pager", "kind": "webhook", "target": format!("{receiver_address}/pager") }),
+    )
+    .await;
+    let policy_id = create(
+        "/api/v1/monitoring/escalation-policies",
+        json!({ "name": "Payments on-call", "steps": [{ "notify_user_id": moderator.id, "delay_minutes": 5 }] }),
+    )
+    .await;
+
+    let rules_path = "/api/v1/monitoring/alert-routing-rules";
+    let page_rule = json!({
+        "name": "Page payments",
+        "severity": "critical",
+        "labels": { "team": "payments" },
+        "channel_ids": [pager_id],
+        "escalation_policy_id": policy_id
+    });
+    let response = app
+        .post_json_auth(rules_path, &page_rule, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({ "name": "Nowhere", "severity": "critical" }),
+        json!({ "name": "Unknown", "channel_ids": [Uuid::new_v4()] }),
+        json!({ "name": "Bad labels", "labels": { "": "x" }, "channel_ids": [slack_id] }),
+    ] {
+        let response = app
+            .post_json_auth(rules_path, &invalid, &mod_token.token)
+            .await;
+        assert_eq!(response.status().as_u16(), 400, "{invalid}");
+    }
+    let page_rule_id = create(rules_path, page_rule.clone()).await;
+    create(
+        rules_path,
+        json!({ "name": "Warnings to Slack", "severity": "warning", "channel_ids": [slack_id] }),
+    )
+    .await;
+    let response = app
+        .post_json_auth(rules_path, &page_rule, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app.get_auth(rules_path, &mod_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let rules = json["data"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0]["name"], "Page payments");
+    assert_eq!(rules[0]["channel_ids"], json!([pager_id]));
+    assert_eq!(rules[1]["severity"], "warning");
+    assert_eq!(rules[1]["labels"], json!({}));
+
+    let alerts_path = "/api/v1/monitoring/alerts";
+    let response = app
+        .post_json_auth(
+            alerts_path,
+            &json!({ "name": "Bad", "query": "x", "severity": "fatal" }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::UNPROCESSABLE_ENTITY);
+    let warning_id = create(
+        alerts_path,
+        json!({ "name": "Payments latency", "query": "latency", "labels": { "team": "payments" } }),
+    )
+    .await;
+    let critical_id = create(
+        alerts_path,
+        json!({ "name": "Payments down", "query": "up", "severity": "critical", "labels": { "team": "payments", "region": "eu" } }),
+    )
+    .await;
+    let unrouted_id = create(
+        alerts_path,
+        json!({ "name": "Search down", "query": "up", "severity": "critical", "labels": { "team": "search" } }),
+    )
+    .await;
+
+    let test_fire = async |alert_id: &str| {
+        let path = format!("/api/v1/monitoring/alerts/{alert_id}/test");
+        app.post_json_auth(&path, &json!({}), &mod_token.token)
+            .await
+    };
+    let response = test_fire(&warning_id).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["notification"]["severity"], "warning");
+    assert_eq!(json["data"]["deliveries"][0]["channel_name"], "Slack");
+    let response = test_fire(&critical_id).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["deliveries"][0]["channel_name"], "Pager");
+    assert_eq!(json["data"]["deliveries"].as_array().unwrap().len(), 1);
+    let response = test_fire(&unrouted_id).await;
+    assert_status(&response, StatusCode::CONFLICT);
+    assert_eq!(*received.lock().unwrap(), ["slack", "pager"]);
+
+    let incidents = async || {
+        sqlx::query_as::<_, (String, Option<Uuid>, String)>(
+            "SELECT severity, escalation_policy_id, status FROM incidents
+             WHERE correlation_key LIKE 'alert:%'",
+        )
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap()
+    };
+    assert!(incidents().await.is_empty());
+
+    // Real notifications: only the critical alert opens an incident, once
+    let services = TaskServices::default();
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    for alert_id in [&warning_id, &critical_id, &critical_id] {
+        let alert = services::find_alert_by_id(conn.as_mut(), alert_id.parse().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        let notification = AlertNotification::firing(&alert, false);
+        let deliveries = notifications::dispatch(conn.as_mut(), &services, &alert, &notification)
+            .await
+            .unwrap();
+        assert!(deliveries.iter().all(|delivery| delivery.delivered));
+    }
+    let policy_uuid: Uuid = policy_id.parse().unwrap();
+    assert_eq!(
+        incidents().await,
+        [(
+            "critical".to_string(),
+            Some(policy_uuid),
+            "open".to_string()
+        )]
+    );
+
+    let alert = services::find_alert_by_id(conn.as_mut(), critical_id.parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    let notification = AlertNotification::resolved(&alert);
+    notifications::dispatch(conn.as_mut(), &services, &alert, &notification)
+        .await
+        .unwrap();
+    assert_eq!(incidents().await[0].2, "resolved");
+    assert_eq!(
+        *received.lock().unwrap(),
+        ["slack", "pager", "slack", "pager", "pager", "pager"]
+    );
+
+    let path = format!("{rules_path}/{page_rule_id}");
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.delete_auth(&path, &mod_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = test_fire(&critical_id).await;
+    assert_status(&response, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_status_pages_show_uptime_and_active_incidents() {
+    use chrono::Utc;
+    use starter::core::config::AppConfig;
+    use starter::monitoring::retention;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_moderator, mod_token) = factory
+        .create_authenticated_moderator(&format!("statusmod_{suffix}"))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("statususer_{suffix}"))
+        .await;
+
+    let mut check_ids = Vec::new();
+    let mut alert_ids = Vec::new();
+    for name in ["api", "web"] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/uptime-checks",
+                &json!({ "name": name, "url": format!("http://{name}.example.com/health") }),
+                &mod_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        check_ids.push(json["data"]["id"].as_str().unwrap().to_string());
+        alert_ids.push(json["data"]["alert_id"].as_str().unwrap().to_string());
+    }
+
+    // 3 of 4 results passed today, 3 of 5 this week
+    for (value, hours_ago) in [(1.0, 1), (1.0, 2), (0.0, 3), (1.0, 4), (0.0, 72)] {
+        sqlx::query(
+            "INSERT INTO metrics (name, metric_type, value, labels, recorded_at)
+             VALUES ('uptime_check_up', 'gauge', $1, '{\"check\": \"api\"}',
+                     NOW() - make_interval(hours => $2))",
+        )
+        .bind(value)
+        .bind(hours_ago)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    }
+    // Windows past raw retention read rollups
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    retention::rollup_metrics(conn.as_mut(), &AppConfig::default().monitoring, Utc::now())
+        .await
+        .unwrap();
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({ "title": "Degraded search", "severity": "medium" }),
+            &user_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let pages_path = "/api/v1/monitoring/status-pages";
+    let page = json!({
+        "slug": "acme",
+        "title": "Acme Status",
+        "check_ids": [check_ids[0]]
+    });
+    let response = app
+        .post_json_auth(pages_path, &page, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({ "slug": "Acme Status", "title": "Acme" }),
+        json!({ "slug": "-acme", "title": "Acme" }),
+        json!({ "slug": "acme", "title": " " }),
+        json!({ "slug": "acme", "title": "Acme", "check_ids": [Uuid::new_v4()] }),
+        json!({ "slug": "acme", "title": "Acme", "incident_ids": [Uuid::new_v4()] }),
+    ] {
+        let response = app
+            .post_json_auth(pages_path, &invalid, &mod_token.token)
+            .await;
+        assert_eq!(response.status().as_u16(), 400, "{invalid}");
+    }
+    let response = app
+        .post_json_auth(pages_path, &page, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let page_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["check_ids"], json!([check_ids[0]]));
+    assert_eq!(json["data"]["incident_ids"], json!([]));
+    let response = app
+        .post_json_auth(pages_path, &page, &mod_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Public and unauthenticated
+    let response = app.get("/api/v1/status/acme").await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["title"], "Acme Status");
+    assert_eq!(json["data"]["status"], "operational");
+    assert_eq!(json["data"]["incidents"], json!([]));
+    let check = &json["data"]["checks"][0];
+    assert_eq!(check["name"], "api");
+    assert_eq!(check["status"], "pending");
+    assert_eq!(check["uptime_24h"], 75.0);
+    assert_eq!(check["uptime_7d"], 60.0);
+    assert_eq!(check["uptime_30d"], 60.0);
+    assert!(check.get("url").is_none());
+    let response = app.get("/api/v1/status/missing").await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let page_path = format!("{pages_path}/{page_id}");
+    let response = app
+        .put_json_auth(
+            &page_path,
+            &json!({ "check_ids": [check_ids[1], check_ids[0]], "incident_ids": [incident_id] }),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["title"], "Acme Status");
+    assert_eq!(
+        json["data"]["check_ids"],
+        json!([check_ids[1], check_ids[0]])
+    );
+
+    // An incident opened for a check's alert shows up without being attached
+    sqlx::query(
+        "INSERT INTO incidents (title, severity, started_at, correlation_key)
+         VALUES ('Alert firing: Uptime check api is down', 'critical', NOW(), $1)",
+    )
+    .bind(format!("alert:{}", alert_ids[0]))
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE uptime_checks SET status = 'down' WHERE name = 'api'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let public_page = async || {
+        let response = app.get("/api/v1/status/acme").await;
+        let json: serde_json::Value = response.json().await.unwrap();
+        json["data"].clone()
+    };
+    let page = public_page().await;
+    assert_eq!(page["status"], "outage");
+    let names: Vec<_> = page["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| check["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["web", "api"]);
+    assert_eq!(page["checks"][0]["uptime_24h"], serde_json::Value::Null);
+    let titles: Vec<_> = page["incidents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|incident| incident["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles.len(), 2);
+    assert!(titles.contains(&"Degraded search"));
+
+    sqlx::query("UPDATE uptime_checks SET status = 'up' WHERE name = 'api'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE incidents SET status = 'resolved' WHERE correlation_key IS NOT NULL")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let page = public_page().await;
+    assert_eq!(page["status"], "degraded");
+    assert_eq!(page["incidents"][0]["title"], "Degraded search");
+    assert_eq!(page["incidents"][0]["severity"], "medium");
+
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/resolve"),
+            &json!({}),
+            &mod_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(public_page().await["status"], "operational");
+
+    let response = app.delete_auth(&page_path, &mod_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get("/api/v1/status/acme").await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.delete_auth(&page_path, &mod_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_event_correlation_opens_and_resolves_incidents() {
+    use chrono::{Duration, Utc};
+    use starter::core::config::AppConfig;
+    use starter::monitoring::correlation::{self, CorrelationRun};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let unique_username = format!("corrmod_{}", &Uuid::new_v4().to_string()[..8]);
+    let (_moderator, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let mut config = AppConfig::default().monitoring;
+    config.event_correlation_threshold = 3;
+    config.event_correlation_window_secs = 300;
+    config.event_correlation_quiet_secs = 600;
+
+    let now = Utc::now();
+    let insert_events = |count: usize, level: &'static str, tags: serde_json::Value, at| {
+        let pool = app.db_pool.clone();
+        async move {
+            for _ in 0..count {
+                sqlx::query(
+                    "INSERT INTO events (event_type, source, message, level, tags, recorded_at)
+                     VALUES ('log', 'payments-api', 'charge failed', $1, $2, $3)",
+                )
+                .bind(level)
+                .bind(&tags)
+                .bind(at)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+    };
+    let prod = json!({"env": "prod"});
+
+    // Below the threshold, or not errors: nothing opens
+    insert_events(2, "ERROR", prod.clone(), now - Duration::minutes(2)).await;
+    insert_events(5, "info", prod.clone(), now - Duration::minutes(2)).await;
+    insert_events(
+        2,
+        "error",
+        json!({"env": "staging"}),
+        now - Duration::minutes(2),
+    )
+    .await;
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let run = correlation::correlate_events(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!(run, CorrelationRun::default());
+
+    insert_events(1, "critical", prod.clone(), now - Duration::minutes(1)).await;
+    let run = correlation::correlate_events(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!((run.opened, run.attached, run.resolved), (1, 3, 0));
+
+    let response = app
+        .get_auth("/api/v1/monitoring/incidents", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incidents = json["data"].as_array().unwrap();
+    assert_eq!(incidents.len(), 1);
+    let incident = &incidents[0];
+    let incident_id = incident["id"].as_str().unwrap().to_string();
+    assert_eq!(incident["title"], "Error events from payments-api");
+    assert_eq!(incident["status"], "open");
+    assert_eq!(incident["correlated_event_count"], 3);
+    assert!(incident["correlation_key"].is_string());
+
+    // Later matches are deduplicated into the same incident, once each
+    insert_events(2, "error", prod.clone(), now).await;
+    let run = correlation::correlate_events(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!((run.opened, run.attached), (0, 2));
+    let run = correlation::correlate_events(conn.as_mut(), &config, now)
+        .await
+        .unwrap();
+    assert_eq!(run, CorrelationRun::default());
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}/timeline?correlated=true"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["total_count"], 5);
+    assert!(
+        json["data"]["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|entry| entry["tags"]["env"] == "prod")
+    );
+
+    // Resolved after the quiet period
+    let run = correlation::correlate_events(conn.as_mut(), &config, now + Duration::minutes(11))
+        .await
+        .unwrap();
+    assert_eq!(run.resolved, 1);
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}"),
+            &token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "resolved");
+    assert_eq!(json["data"]["correlated_event_count"], 5);
+
+    // A new burst opens a new incident
+    let later = now + Duration::minutes(30);
+    insert_events(3, "fatal", prod, later).await;
+    let run = correlation::correlate_events(conn.as_mut(), &config, later)
+        .await
+        .unwrap();
+    assert_eq!((run.opened, run.attached), (1, 3));
+}
+
+#[tokio::test]
+async fn test_incident_postmortem_with_action_items() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let suffix = &Uuid::new_v4().to_string()[..8];
+    let (_author, author_token) = factory
+        .create_authenticated_user(&format!("pmauthor_{suffix}"))
+        .await;
+    let (_other, other_token) = factory
+        .create_authenticated_user(&format!("pmother_{suffix}"))
+        .await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({ "title": "Payments outage", "severity": "high" }),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["postmortem_required"], true);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &json!({ "title": "Slow dashboard", "severity": "low" }),
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["postmortem_required"], false);
+
+    // High-severity incidents without a postmortem are pending
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/incidents?postmortem_pending=true&limit=100",
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let pending = json["data"].as_array().unwrap();
+    assert!(pending.iter().any(|i| i["id"] == incident_id.as_str()));
+    assert!(pending.iter().all(|i| i["postmortem_required"] == true));
+
+    let postmortem_path = format!("/api/v1/monitoring/incidents/{incident_id}/postmortem");
+    let response = app.get_auth(&postmortem_path, &author_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let postmortem = json!({
+        "body": "## Summary\nCard payments failed for 20 minutes.",
+        "contributing_factors": ["Expired TLS certificate", " No expiry alerting "]
+    });
+    let response = app
+        .put_json_auth(&postmortem_path, &postmortem, &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .put_json_auth(
+            &postmortem_path,
+            &json!({ "body": " " }),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .put_json_auth(&postmortem_path, &postmortem, &author_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["contributing_factors"],
+        json!(["Expired TLS certificate", "No expiry alerting"])
+    );
+    assert_eq!(json["data"]["action_items"], json!([]));
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/incidents?postmortem_pending=true&limit=100",
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(
+        !json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|i| i["id"] == incident_id.as_str())
+    );
+
+    // Action items can be linked to existing tasks only
+    let items_path = format!("{postmortem_path}/action-items");
+    let response = app
+        .post_json_auth(
+            &items_path,
+            &json!({ "title": "Automate renewal", "task_id": Uuid::new_v4() }),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let task = factory
+        .create_task(
+            "email",
+            json!({"to": "ops@example.com", "subject": "Renew", "body": "Renew certs"}),
+        )
+        .await;
+    let task_id = task["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth(
+            &items_path,
+            &json!({ "title": "Automate renewal", "task_id": task_id }),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let item_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["task_id"], task_id.as_str());
+    assert_eq!(json["data"]["task_status"], "pending");
+    assert!(json["data"]["completed_at"].is_null());
+
+    let response = app
+        .put_json_auth(
+            &format!("{items_path}/{item_id}"),
+            &json!({ "completed": true }),
+            &author_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"]["completed_at"].is_string());
+
+    // Rewriting the document keeps its action items
+    let response = app
+        .put_json_auth(
+            &postmortem_path,
+            &json!({ "body": "## Summary\nRevised." }),
+            &author_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["contributing_factors"], json!([]));
+    assert_eq!(json["data"]["action_items"].as_array().unwrap().len(), 1);
+
+    let response = app
+        .delete_auth(&format!("{items_path}/{item_id}"), &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .delete_auth(&format!("{items_path}/{item_id}"), &author_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth(&postmortem_path, &other_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["action_items"], json!([]));
+}
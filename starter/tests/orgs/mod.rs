@@ -0,0 +1,624 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::json;
+
+/// Create an organization as `token`'s user and return its id
+async fn create_org(app: &TestApp, token: &str, slug: &str) -> String {
+    let response = app
+        .post_json_auth(
+            "/api/v1/orgs",
+            &json!({"name": format!("Org {slug}"), "slug": slug}),
+            token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    json["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn set_member(app: &TestApp, token: &str, org_id: &str, user_id: uuid::Uuid, role: &str) {
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{user_id}"),
+            &json!({"role": role}),
+            token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_org_membership_roles() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (owner, owner_token) = factory.create_authenticated_user("org_owner").await;
+    let (member, member_token) = factory.create_authenticated_user("org_member").await;
+    let (outsider, outsider_token) = factory.create_authenticated_user("org_outsider").await;
+
+    let org_id = create_org(&app, &owner_token.token, "acme").await;
+
+    // Slugs are unique
+    let response = app
+        .post_json_auth(
+            "/api/v1/orgs",
+            &json!({"name": "Acme again", "slug": "acme"}),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    set_member(&app, &owner_token.token, &org_id, member.id, "member").await;
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/orgs/{org_id}/members"),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let roles: Vec<(&str, &str)> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["username"].as_str().unwrap(), m["role"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        roles,
+        vec![("org_member", "member"), ("org_owner", "owner")]
+    );
+
+    let response = app.get_auth("/api/v1/orgs", &member_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    // Members can't manage members, outsiders can't see the organization
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{}", outsider.id),
+            &json!({"role": "member"}),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .get_auth(&format!("/api/v1/orgs/{org_id}"), &outsider_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Org admins manage members but not owners
+    set_member(&app, &owner_token.token, &org_id, member.id, "admin").await;
+    set_member(&app, &member_token.token, &org_id, outsider.id, "member").await;
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{}", outsider.id),
+            &json!({"role": "owner"}),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // The last owner can't step down or leave
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{}", owner.id),
+            &json!({"role": "member"}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{}", owner.id),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Members may leave on their own
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/orgs/{org_id}/members/{}", outsider.id),
+            &outsider_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .delete_auth(&format!("/api/v1/orgs/{org_id}"), &member_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .delete_auth(&format!("/api/v1/orgs/{org_id}"), &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_org_tasks_are_shared_with_members() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_owner, owner_token) = factory.create_authenticated_user("task_org_owner").await;
+    let (member, member_token) = factory.create_authenticated_user("task_org_member").await;
+    let (_outsider, outsider_token) = factory.create_authenticated_user("task_org_outsider").await;
+
+    let org_id = create_org(&app, &owner_token.token, "task-org").await;
+    set_member(&app, &owner_token.token, &org_id, member.id, "member").await;
+
+    // Only members can scope tasks to the organization
+    let task =
+        json!({"task_type": "email", "payload": {"to": "team@example.com"}, "org_id": org_id});
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &outsider_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["org_id"], org_id.as_str());
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Members see the organization's tasks, outsiders don't
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks?org_id={org_id}"),
+            &member_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_id}"), &member_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth("/api/v1/tasks", &outsider_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].as_array().unwrap().is_empty());
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_id}"), &outsider_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Managing another member's task takes an org admin
+    let response = app
+        .post_auth(
+            &format!("/api/v1/tasks/{task_id}/cancel"),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    set_member(&app, &owner_token.token, &org_id, member.id, "admin").await;
+    let response = app
+        .post_auth(
+            &format!("/api/v1/tasks/{task_id}/cancel"),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_org_events_are_hidden_from_outsiders() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_owner, owner_token) = factory.create_authenticated_user("event_org_owner").await;
+    let (_outsider, outsider_token) = factory
+        .create_authenticated_user("event_org_outsider")
+        .await;
+
+    let org_id = create_org(&app, &owner_token.token, "event-org").await;
+
+    let event = json!({"event_type": "log", "source": "app-billing", "message": "invoice sent", "org_id": org_id});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &outsider_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let event_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let unscoped = json!({"event_type": "log", "source": "app-billing", "message": "public"});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &unscoped, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/events?org_id={org_id}"),
+            &owner_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    let response = app
+        .get_auth(
+            "/api/v1/monitoring/events?source=app-billing",
+            &outsider_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let messages: Vec<&str> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(messages, vec!["public"]);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/events/{event_id}"),
+            &outsider_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+/// Token from the most recent invitation email sent to `to`
+fn invitation_token(app: &TestApp, to: &str) -> String {
+    let emails = app.sent_emails.lock().unwrap();
+    let email = emails
+        .iter()
+        .rev()
+        .find(|email| email.to == to)
+        .expect("No invitation email sent");
+    let (_, rest) = email.body.split_once("token=").unwrap();
+    rest.split_whitespace().next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_org_invitation_registers_new_user() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_owner, owner_token) = factory.create_authenticated_user("inv_owner").await;
+    let (member, member_token) = factory.create_authenticated_user("inv_member").await;
+
+    let org_id = create_org(&app, &owner_token.token, "invites").await;
+    set_member(&app, &owner_token.token, &org_id, member.id, "member").await;
+    let invitations = format!("/api/v1/orgs/{org_id}/invitations");
+
+    // Only org admins invite, and existing members can't be invited again
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": "newcomer@example.com"}),
+            &member_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": member.email}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": "newcomer@example.com", "role": "admin"}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+    assert_eq!(json["data"]["role"], "admin");
+
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": "newcomer@example.com"}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let token = invitation_token(&app, "newcomer@example.com");
+    assert!(
+        app.sent_emails.lock().unwrap()[0]
+            .body
+            .contains("/invitations/accept?token=")
+    );
+
+    // A new account needs a username and password
+    let response = app
+        .post_json("/api/v1/invitations/accept", &json!({"token": token}))
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json(
+            "/api/v1/invitations/accept",
+            &json!({
+                "token": token,
+                "username": "newcomer",
+                "password": "SecurePass123!"
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["registered"], true);
+    assert_eq!(json["data"]["org"]["id"], org_id.as_str());
+    assert_eq!(json["data"]["member"]["username"], "newcomer");
+    assert_eq!(json["data"]["member"]["role"], "admin");
+
+    let response = app
+        .post_json("/api/v1/invitations/accept", &json!({"token": token}))
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // The new account can sign in and sees the organization
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &json!({"username": "newcomer", "password": "SecurePass123!"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let newcomer_token = app.extract_auth_token(response).await;
+    let response = app
+        .get_auth(&format!("/api/v1/orgs/{org_id}"), &newcomer_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth(&invitations, &owner_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["status"], "accepted");
+}
+
+#[tokio::test]
+async fn test_org_invitation_resend_revoke_and_expiry() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_owner, owner_token) = factory.create_authenticated_user("invr_owner").await;
+    let (invitee, invitee_token) = factory.create_authenticated_user("invr_invitee").await;
+
+    let org_id = create_org(&app, &owner_token.token, "resends").await;
+    let invitations = format!("/api/v1/orgs/{org_id}/invitations");
+
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": invitee.email}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let invitation_id = json["data"]["id"].as_str().unwrap().to_string();
+    let first_token = invitation_token(&app, &invitee.email);
+
+    // Resending replaces the link
+    let response = app
+        .post_auth(
+            &format!("{invitations}/{invitation_id}/resend"),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let second_token = invitation_token(&app, &invitee.email);
+    assert_ne!(first_token, second_token);
+    let response = app
+        .post_json("/api/v1/invitations/accept", &json!({"token": first_token}))
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Revoked links stop working and can't be resent
+    let response = app
+        .delete_auth(
+            &format!("{invitations}/{invitation_id}"),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "revoked");
+    let response = app
+        .post_json(
+            "/api/v1/invitations/accept",
+            &json!({"token": second_token}),
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .post_auth(
+            &format!("{invitations}/{invitation_id}/resend"),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Expired invitations report as such until resent
+    let response = app
+        .post_json_auth(
+            &invitations,
+            &json!({"email": invitee.email}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let invitation_id = json["data"]["id"].as_str().unwrap().to_string();
+    let expired_token = invitation_token(&app, &invitee.email);
+    sqlx::query("UPDATE org_invitations SET expires_at = NOW() - INTERVAL '1 hour' WHERE id = $1")
+        .bind(uuid::Uuid::parse_str(&invitation_id).unwrap())
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = app.get_auth(&invitations, &owner_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["status"], "expired");
+    let response = app
+        .post_json(
+            "/api/v1/invitations/accept",
+            &json!({"token": expired_token}),
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let response = app
+        .post_auth(
+            &format!("{invitations}/{invitation_id}/resend"),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+
+    // Existing accounts join without registering
+    let token = invitation_token(&app, &invitee.email);
+    let response = app
+        .post_json("/api/v1/invitations/accept", &json!({"token": token}))
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["registered"], false);
+    assert_eq!(json["data"]["member"]["role"], "member");
+    let response = app
+        .get_auth(&format!("/api/v1/orgs/{org_id}"), &invitee_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_org_header_selects_organization() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_owner, owner_token) = factory.create_authenticated_user("header_org_owner").await;
+    let (_outsider, outsider_token) = factory
+        .create_authenticated_user("header_org_outsider")
+        .await;
+
+    let org_id = create_org(&app, &owner_token.token, "header-org").await;
+    let other_org_id = create_org(&app, &owner_token.token, "header-org-other").await;
+
+    let with_org = |request: reqwest::RequestBuilder, token: &str, org: &str| {
+        request
+            .header("Authorization", format!("Bearer {token}"))
+            .header("X-Org-Id", org)
+            .send()
+    };
+    let tasks_url = format!("{}/api/v1/tasks", app.address);
+    let task = json!({"task_type": "email", "payload": {"to": "team@example.com"}});
+
+    // Tasks created without an org_id land in the selected organization
+    let response = with_org(
+        app.client.post(&tasks_url).json(&task),
+        &owner_token.token,
+        &org_id,
+    )
+    .await
+    .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["org_id"], org_id.as_str());
+
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Listings only return the selected organization's tasks
+    let response = with_org(app.client.get(&tasks_url), &owner_token.token, &org_id)
+        .await
+        .unwrap();
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    let response = with_org(
+        app.client.get(&tasks_url),
+        &owner_token.token,
+        &other_org_id,
+    )
+    .await
+    .unwrap();
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].as_array().unwrap().is_empty());
+
+    // Naming another organization than the selected one is rejected
+    let response = with_org(
+        app.client.get(format!("{tasks_url}?org_id={other_org_id}")),
+        &owner_token.token,
+        &org_id,
+    )
+    .await
+    .unwrap();
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // Outsiders can't select the organization, and the header must be an id
+    let response = with_org(app.client.get(&tasks_url), &outsider_token.token, &org_id)
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = with_org(app.client.get(&tasks_url), &owner_token.token, "acme")
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_org_role_decides_permissions_in_the_organization() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_owner, owner_token) = factory.create_authenticated_user("perm_org_owner").await;
+    let (admin, admin_token) = factory.create_authenticated_user("perm_org_admin").await;
+    let (member, member_token) = factory.create_authenticated_user("perm_org_member").await;
+
+    let org_id = create_org(&app, &owner_token.token, "perm-org").await;
+    set_member(&app, &owner_token.token, &org_id, admin.id, "admin").await;
+    set_member(&app, &owner_token.token, &org_id, member.id, "member").await;
+
+    let permissions = |token: String, org: Option<String>| {
+        let mut request = app
+            .client
+            .get(format!("{}/api/v1/auth/me/permissions", app.address))
+            .bearer_auth(token);
+        if let Some(org) = org {
+            request = request.header("X-Org-Id", org);
+        }
+        async move {
+            let response = request.send().await.unwrap();
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["permissions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|permission| permission.as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Outside the organization the built-in user role applies
+    let own = permissions(member_token.token.clone(), None).await;
+    assert!(own.contains(&"tasks:delete".to_string()));
+    assert!(!own.contains(&"monitoring:delete".to_string()));
+
+    // In it, org admins may delete its tasks and events, and members may not
+    let org_admin = permissions(admin_token.token.clone(), Some(org_id.clone())).await;
+    assert!(org_admin.contains(&"tasks:delete".to_string()));
+    assert!(org_admin.contains(&"monitoring:delete".to_string()));
+    let org_member = permissions(member_token.token.clone(), Some(org_id.clone())).await;
+    assert!(org_member.contains(&"tasks:write".to_string()));
+    assert!(!org_member.contains(&"tasks:delete".to_string()));
+    assert!(!org_member.contains(&"monitoring:delete".to_string()));
+
+    // Permissions outside the organization's resources keep the built-in role
+    assert!(org_member.contains(&"users:write".to_string()));
+    assert!(!org_admin.contains(&"users:delete".to_string()));
+}
@@ -0,0 +1,1317 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn test_custom_roles() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("roles_admin").await;
+    let (agent, agent_token) = factory.create_authenticated_user("roles_agent").await;
+    let customer = factory.create_user("roles_customer").await;
+
+    // Only admins manage roles
+    let response = app
+        .get_auth("/api/v1/admin/roles", &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    for (role, status) in [
+        (
+            serde_json::json!({ "name": "moderator", "permissions": [] }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            serde_json::json!({ "name": "support", "permissions": ["admin:read"] }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            serde_json::json!({ "name": "support", "permissions": ["billing:read"] }),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/admin/roles", &role, &admin_token.token)
+            .await;
+        assert_status(&response, status);
+    }
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({
+                "name": "support",
+                "description": "Customer support agents",
+                "permissions": ["users:write", "Users:Read", "users:read"]
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["permissions"],
+        serde_json::json!(["users:read", "users:write"])
+    );
+    assert_eq!(json["data"]["member_count"], 0);
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({ "name": "support", "permissions": [] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // A plain user can't reach the support endpoints until they get the role
+    let reset_path = format!("/api/v1/users/{}/reset-password", customer.id);
+    let reset = serde_json::json!({ "new_password": "ResetPassword123!" });
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+
+    let members_path = format!("/api/v1/admin/roles/{role_id}/members");
+    let response = app
+        .put_json_auth(
+            &format!("{members_path}/{}", agent.id),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth(&members_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["username"], "roles_agent");
+
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::OK,
+    );
+    assert_status(
+        &app.post_json_auth(&reset_path, &reset, &agent_token.token)
+            .await,
+        StatusCode::OK,
+    );
+    // Admin endpoints still need the admin role
+    assert_status(
+        &app.get_auth("/api/v1/admin/roles", &agent_token.token)
+            .await,
+        StatusCode::FORBIDDEN,
+    );
+
+    // Narrowing the role applies on the next request
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}"),
+            &serde_json::json!({ "permissions": ["users:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["description"], "Customer support agents");
+    assert_eq!(json["data"]["member_count"], 1);
+    assert_status(
+        &app.post_json_auth(&reset_path, &reset, &agent_token.token)
+            .await,
+        StatusCode::FORBIDDEN,
+    );
+
+    // Unassigning and deleting take the permissions away
+    let response = app
+        .delete_auth(&format!("{members_path}/{}", agent.id), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .delete_auth(&format!("{members_path}/{}", agent.id), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/admin/roles/{role_id}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/roles/{role_id}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Both changes show up in the user's activity trail
+    let response = app
+        .get_auth(
+            "/api/v1/users/me/activity?action=role_changed",
+            &agent_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_resource_sharing() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("share_admin").await;
+    let (owner, owner_token) = factory.create_authenticated_user("share_owner").await;
+    let (teammate, teammate_token) = factory.create_authenticated_user("share_teammate").await;
+    let (operator, operator_token) = factory.create_authenticated_user("share_operator").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({
+                "task_type": "email",
+                "payload": { "to": "team@example.com", "subject": "Report", "body": "Weekly" }
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+    let task_path = format!("/api/v1/tasks/{task_id}");
+
+    assert_status(
+        &app.get_auth(&task_path, &teammate_token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+
+    // Only the owner can share the task
+    let share = serde_json::json!({
+        "resource_type": "tasks",
+        "resource_id": task_id,
+        "user_id": teammate.id,
+        "permission": "read"
+    });
+    let response = app
+        .post_json_auth("/api/v1/shares", &share, &teammate_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_json_auth("/api/v1/shares", &share, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["username"], "share_teammate");
+    let share_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth("/api/v1/shares", &share, &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+    for invalid in [
+        serde_json::json!({
+            "resource_type": "tasks", "resource_id": task_id,
+            "user_id": owner.id, "permission": "read"
+        }),
+        serde_json::json!({
+            "resource_type": "users", "resource_id": owner.id,
+            "user_id": teammate.id, "permission": "read"
+        }),
+        serde_json::json!({
+            "resource_type": "tasks", "resource_id": task_id, "permission": "read"
+        }),
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/shares", &invalid, &owner_token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    // A read share opens the task but not its actions
+    assert_status(
+        &app.get_auth(&task_path, &teammate_token.token).await,
+        StatusCode::OK,
+    );
+    assert_status(
+        &app.post_auth(&format!("{task_path}/cancel"), &teammate_token.token)
+            .await,
+        StatusCode::NOT_FOUND,
+    );
+    let response = app
+        .get_auth("/api/v1/shares/received", &teammate_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["resource_id"], task_id.as_str());
+
+    let list_path = format!("/api/v1/shares?resource_type=tasks&resource_id={task_id}");
+    assert_status(
+        &app.get_auth(&list_path, &teammate_token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+
+    // Sharing with a custom role reaches its members
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({ "name": "operators", "permissions": [] }),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}/members/{}", operator.id),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/shares",
+            &serde_json::json!({
+                "resource_type": "tasks",
+                "resource_id": task_id,
+                "role_id": role_id,
+                "permission": "write"
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth(&list_path, &owner_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 2);
+    assert_eq!(json["data"][1]["role_name"], "operators");
+
+    assert_status(
+        &app.post_auth(&format!("{task_path}/cancel"), &operator_token.token)
+            .await,
+        StatusCode::OK,
+    );
+
+    // Unsharing takes the access away again
+    let response = app
+        .delete_auth(&format!("/api/v1/shares/{share_id}"), &owner_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth(&task_path, &teammate_token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+}
+
+#[tokio::test]
+async fn test_resource_sharing_stays_within_the_tenant() {
+    use crate::tenants::{admin_in_tenant, create_tenant, spawn_tenant_app};
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("share_root").await;
+    let (_owner, owner_token) = factory.create_authenticated_user("share_root_owner").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (acme_admin, acme_token) = admin_in_tenant(&app, "acme", "share_acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({
+                "task_type": "email",
+                "payload": { "to": "team@example.com", "subject": "Report", "body": "Weekly" }
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Other tenants' admins can't see or change the task's shares
+    let list_path = format!("/api/v1/shares?resource_type=tasks&resource_id={task_id}");
+    assert_status(
+        &app.get_auth(&list_path, &acme_token.token).await,
+        StatusCode::NOT_FOUND,
+    );
+    let response = app
+        .post_json_auth(
+            "/api/v1/shares",
+            &serde_json::json!({
+                "resource_type": "tasks",
+                "resource_id": task_id,
+                "user_id": acme_admin,
+                "permission": "write"
+            }),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Users of other tenants can't be shared with
+    let response = app
+        .post_json_auth(
+            "/api/v1/shares",
+            &serde_json::json!({
+                "resource_type": "tasks",
+                "resource_id": task_id,
+                "user_id": acme_admin,
+                "permission": "read"
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.get_auth(&list_path, &owner_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rbac_audit_log() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (admin, admin_token) = factory.create_authenticated_admin("audit_admin").await;
+    let (agent, agent_token) = factory.create_authenticated_user("audit_agent").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({
+                "name": "auditors",
+                "permissions": ["users:read"],
+                "reason": "Quarterly review"
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+    let role_path = format!("/api/v1/admin/roles/{role_id}");
+
+    let response = app
+        .put_json_auth(
+            &role_path,
+            &serde_json::json!({ "permissions": ["users:read", "users:write"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let member_path = format!("{role_path}/members/{}", agent.id);
+    let response = app
+        .put_json_auth(
+            &format!("{member_path}?reason=Joined%20the%20audit%20team"),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/role", agent.id),
+            &serde_json::json!({ "role": "moderator", "reason": "Promoted" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.delete_auth(&role_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    // Only admins read the log
+    let response = app
+        .get_auth("/api/v1/admin/audit/rbac", &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={role_id}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["role_deleted", "role_updated", "role_created"]);
+    assert_eq!(entries[2]["reason"], "Quarterly review");
+    assert_eq!(entries[2]["before"], serde_json::Value::Null);
+    assert_eq!(
+        entries[1]["before"]["permissions"],
+        serde_json::json!(["users:read"])
+    );
+    assert_eq!(
+        entries[1]["after"]["permissions"],
+        serde_json::json!(["users:read", "users:write"])
+    );
+    assert_eq!(entries[0]["after"], serde_json::Value::Null);
+    assert_eq!(entries[0]["actor_id"], admin.id.to_string());
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={}", agent.id),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "user_role_changed");
+    assert_eq!(entries[0]["target_type"], "user");
+    assert_eq!(entries[0]["before"]["role"], "user");
+    assert_eq!(entries[0]["after"]["role"], "moderator");
+    assert_eq!(entries[0]["reason"], "Promoted");
+    assert_eq!(entries[1]["action"], "role_assigned");
+    assert_eq!(entries[1]["after"]["role_name"], "auditors");
+    assert_eq!(entries[1]["reason"], "Joined the audit team");
+
+    let response = app
+        .get_auth(
+            "/api/v1/admin/audit/rbac?action=role_assigned",
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    // Entries can't be changed or removed
+    let result = sqlx::query("UPDATE rbac_audit SET reason = 'edited'")
+        .execute(&app.db_pool)
+        .await;
+    assert!(result.is_err());
+    let result = sqlx::query("DELETE FROM rbac_audit")
+        .execute(&app.db_pool)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_permission_denies() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("deny_admin").await;
+    let (agent, agent_token) = factory.create_authenticated_user("deny_agent").await;
+    let event = serde_json::json!({
+        "event_type": "log",
+        "source": "app-deny",
+        "message": "Ingested",
+        "level": "info"
+    });
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-denies",
+            &serde_json::json!({ "user_id": agent.id, "permission": "monitoring:write" }),
+            &agent_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    for permission in ["monitoring", "admin:read", "billing:write"] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/admin/permission-denies",
+                &serde_json::json!({ "user_id": agent.id, "permission": permission }),
+                &admin_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-denies",
+            &serde_json::json!({
+                "user_id": agent.id,
+                "permission": " Monitoring:Write ",
+                "reason": "Flooding ingestion"
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["permission"], "monitoring:write");
+    assert_eq!(json["data"]["username"], "deny_agent");
+    let deny_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-denies",
+            &serde_json::json!({ "user_id": agent.id, "permission": "monitoring:write" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // The deny wins over the built-in role and over a custom role granting it
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({ "name": "ingesters", "permissions": ["monitoring:write"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}/members/{}", agent.id),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .get_auth("/api/v1/monitoring/events", &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/permission-denies?user_id={}", agent.id),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/admin/permission-denies/{deny_id}?reason=Fixed"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Both changes are in the RBAC audit log
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={}", agent.id),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries[0]["action"], "permission_deny_removed");
+    assert_eq!(entries[0]["before"]["permission"], "monitoring:write");
+    assert_eq!(entries[0]["reason"], "Fixed");
+    assert_eq!(entries[2]["action"], "permission_denied");
+    assert_eq!(entries[2]["reason"], "Flooding ingestion");
+}
+
+#[tokio::test]
+async fn test_custom_role_task_permissions() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("task_role_admin").await;
+    let (_owner, owner_token) = factory.create_authenticated_user("task_role_owner").await;
+    let (agent, agent_token) = factory.create_authenticated_user("task_role_agent").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({
+                "task_type": "email",
+                "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+    let task_path = format!("/api/v1/tasks/{task_id}");
+
+    let response = app.get_auth(&task_path, &agent_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({ "name": "task_readers", "permissions": ["tasks:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}/members/{}", agent.id),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Reading covers every task of the tenant, but changing them needs tasks:write
+    let response = app.get_auth(&task_path, &agent_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth("/api/v1/tasks", &agent_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(
+        json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|task| task["id"] == task_id.as_str())
+    );
+    let response = app
+        .post_auth(&format!("{task_path}/cancel"), &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}"),
+            &serde_json::json!({ "permissions": ["tasks:read", "tasks:write"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_auth(&format!("{task_path}/cancel"), &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_task_permission_denies() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("task_deny_admin").await;
+    let (agent, agent_token) = factory.create_authenticated_user("task_deny_agent").await;
+    let task = serde_json::json!({
+        "task_type": "email",
+        "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}
+    });
+    let deny = |permission: &'static str| {
+        let app = app.clone();
+        let token = admin_token.token.clone();
+        async move {
+            let response = app
+                .post_json_auth(
+                    "/api/v1/admin/permission-denies",
+                    &serde_json::json!({ "user_id": agent.id, "permission": permission }),
+                    &token,
+                )
+                .await;
+            assert_status(&response, StatusCode::OK);
+        }
+    };
+
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_path = format!("/api/v1/tasks/{}", json["data"]["id"].as_str().unwrap());
+
+    deny("tasks:write").await;
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_auth(&format!("{task_path}/cancel"), &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app.get_auth(&task_path, &agent_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    deny("tasks:read").await;
+    for path in [task_path.as_str(), "/api/v1/tasks", "/api/v1/tasks/export"] {
+        let response = app.get_auth(path, &agent_token.token).await;
+        assert_status(&response, StatusCode::FORBIDDEN);
+    }
+
+    deny("tasks:delete").await;
+    let response = app.delete_auth(&task_path, &agent_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_rbac_policy_export_import() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("policy_admin").await;
+    let (_agent, agent_token) = factory.create_authenticated_user("policy_agent").await;
+    let import = |yaml: &'static str, query: &'static str, token: String| {
+        app.client
+            .post(format!("{}/api/v1/admin/rbac/policy{query}", app.address))
+            .header("Authorization", format!("Bearer {token}"))
+            .header("Content-Type", "application/yaml")
+            .body(yaml)
+            .send()
+    };
+    let policy = r#"
+roles:
+  - name: policy_support
+    description: Support agents
+    permissions: [users:read]
+    members: [policy_agent]
+denies:
+  - user: policy_agent
+    permission: monitoring:write
+    reason: Flooding ingestion
+"#;
+
+    let response = app
+        .get_auth("/api/v1/admin/rbac/policy", &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = import(
+        "denies: [{ user: nobody_here, permission: monitoring:write }]",
+        "",
+        admin_token.token.clone(),
+    )
+    .await
+    .unwrap();
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // A dry run lists the changes without making them
+    let response = import(policy, "?dry_run=true", admin_token.token.clone())
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["dry_run"], true);
+    let changes = json["data"]["changes"].as_array().unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0]["op"], "create");
+    assert_eq!(changes[0]["kind"], "role");
+    assert_eq!(changes[0]["target"], "policy_support");
+    let response = app
+        .get_auth("/api/v1/admin/roles", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].as_array().unwrap().is_empty());
+
+    let response = import(policy, "?reason=Promote", admin_token.token.clone())
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["changes"].as_array().unwrap().len(), 3);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/events",
+            &serde_json::json!({ "event_type": "log", "source": "app-policy", "message": "Ingested" }),
+            &agent_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .get_auth("/api/v1/admin/rbac/policy", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("application/yaml")
+    );
+    let exported = response.text().await.unwrap();
+    assert!(exported.contains("name: policy_support"));
+    assert!(exported.contains("- policy_agent"));
+
+    // Importing the export again changes nothing; an empty policy removes everything
+    let response = app
+        .client
+        .post(format!("{}/api/v1/admin/rbac/policy", app.address))
+        .header("Authorization", format!("Bearer {}", admin_token.token))
+        .body(exported)
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"]["changes"].as_array().unwrap().is_empty());
+
+    let response = import("{}", "", admin_token.token.clone()).await.unwrap();
+    let json: serde_json::Value = response.json().await.unwrap();
+    let changes: Vec<_> = json["data"]["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|change| change["target"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            "policy_agent monitoring:write",
+            "policy_support/policy_agent",
+            "policy_support"
+        ]
+    );
+
+    let response = app
+        .get_auth(
+            "/api/v1/admin/audit/rbac?action=role_created",
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["reason"], "Promote");
+}
+
+#[tokio::test]
+async fn test_effective_permissions() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("perms_admin").await;
+    let (_owner, owner_token) = factory.create_authenticated_user("perms_owner").await;
+    let (user, user_token) = factory.create_authenticated_user("perms_user").await;
+
+    let response = app
+        .get_auth("/api/v1/auth/me/permissions", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "user");
+    assert_eq!(json["data"]["scoped"], false);
+    assert_eq!(
+        json["data"]["permissions"],
+        serde_json::json!([
+            "tasks:read",
+            "tasks:write",
+            "tasks:delete",
+            "users:read",
+            "users:write",
+            "monitoring:read",
+            "monitoring:write"
+        ])
+    );
+    assert!(json["data"]["shared"].as_array().unwrap().is_empty());
+
+    // A deny removes a permission; a share adds access to one task
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-denies",
+            &serde_json::json!({ "user_id": user.id, "permission": "monitoring:write" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({
+                "task_type": "email",
+                "payload": { "to": "team@example.com", "subject": "Report", "body": "Weekly" }
+            }),
+            &owner_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .post_json_auth(
+            "/api/v1/shares",
+            &serde_json::json!({
+                "resource_type": "tasks",
+                "resource_id": task_id,
+                "user_id": user.id,
+                "permission": "write"
+            }),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth("/api/v1/auth/me/permissions", &user_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let permissions = json["data"]["permissions"].as_array().unwrap();
+    assert!(!permissions.contains(&serde_json::json!("monitoring:write")));
+    assert!(permissions.contains(&serde_json::json!("monitoring:read")));
+    assert_eq!(json["data"]["shared"][0]["resource_id"], task_id.as_str());
+    assert_eq!(
+        json["data"]["shared"][0]["permissions"],
+        serde_json::json!(["read", "write"])
+    );
+
+    // A scoped session only holds its scopes, and can always ask
+    let response = app
+        .post_json_auth(
+            "/api/v1/auth/scoped-sessions",
+            &serde_json::json!({ "name": "reader", "scopes": ["tasks:read"] }),
+            &user_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let scoped_token = json["data"]["session_token"].as_str().unwrap().to_string();
+    let response = app
+        .get_auth("/api/v1/auth/me/permissions", &scoped_token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["scoped"], true);
+    assert_eq!(
+        json["data"]["permissions"],
+        serde_json::json!(["tasks:read"])
+    );
+    assert_eq!(
+        json["data"]["shared"][0]["permissions"],
+        serde_json::json!(["read"])
+    );
+}
+
+#[tokio::test]
+async fn test_permission_bundles() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("bundles_admin").await;
+    let (agent, agent_token) = factory.create_authenticated_user("bundles_agent").await;
+
+    let response = app
+        .get_auth("/api/v1/admin/permission-bundles", &agent_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-bundles",
+            &serde_json::json!({ "name": "Directory Readonly", "permissions": ["users:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-bundles",
+            &serde_json::json!({
+                "name": "directory-readonly",
+                "description": "Browse the user directory",
+                "permissions": ["Users:Read", "users:read"]
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["permissions"],
+        serde_json::json!(["users:read"])
+    );
+    let bundle_id = json["data"]["id"].as_str().unwrap().to_string();
+    let bundle_path = format!("/api/v1/admin/permission-bundles/{bundle_id}");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-bundles",
+            &serde_json::json!({ "name": "directory-readonly", "permissions": [] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Attached to a user, the bundle grants its permissions to them alone
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let user_path = format!("{bundle_path}/users/{}", agent.id);
+    let response = app
+        .put_json_auth(&user_path, &serde_json::json!({}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::OK,
+    );
+    let response = app
+        .get_auth(&format!("{bundle_path}/users"), &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["username"], "bundles_agent");
+
+    let response = app.delete_auth(&user_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app.delete_auth(&user_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Attached to a custom role, it reaches every member of the role
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &serde_json::json!({ "name": "helpdesk", "permissions": [] }),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/admin/roles/{role_id}/members/{}", agent.id),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .put_json_auth(
+            &format!("{bundle_path}/roles/{role_id}"),
+            &serde_json::json!({}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth(&bundle_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["roles"], serde_json::json!(["helpdesk"]));
+    assert_eq!(json["data"]["user_count"], 0);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::OK,
+    );
+
+    // Bundle changes reach the holders right away
+    let response = app
+        .put_json_auth(
+            &bundle_path,
+            &serde_json::json!({ "permissions": ["monitoring:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .put_json_auth(
+            &bundle_path,
+            &serde_json::json!({ "permissions": ["users:read"] }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={role_id}&action=bundle_attached"),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["target_type"], "role");
+    assert_eq!(
+        json["data"][0]["after"]["bundle_name"],
+        "directory-readonly"
+    );
+
+    let response = app.delete_auth(&bundle_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    assert_status(
+        &app.get_auth("/api/v1/users", &agent_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={bundle_id}"),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let actions: Vec<&str> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "bundle_deleted",
+            "bundle_updated",
+            "bundle_updated",
+            "bundle_created"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_role_requests() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin1, admin1_token) = factory
+        .create_authenticated_admin("escalation_admin1")
+        .await;
+    let (_admin2, admin2_token) = factory
+        .create_authenticated_admin("escalation_admin2")
+        .await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator("escalation_mod")
+        .await;
+    let (user, user_token) = factory.create_authenticated_user("escalation_user").await;
+    let other = factory.create_user("escalation_other").await;
+
+    // Admin can't be granted directly
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/role", user.id),
+            &serde_json::json!({ "role": "admin" }),
+            &admin1_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Only staff can file requests
+    let request = serde_json::json!({
+        "user_id": user.id,
+        "role": "admin",
+        "reason": "On-call lead"
+    });
+    let response = app
+        .post_json_auth("/api/v1/role-requests", &request, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/role-requests",
+            &serde_json::json!({ "user_id": user.id, "role": "user" }),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_json_auth("/api/v1/role-requests", &request, &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+    assert_eq!(json["data"]["current_role"], "user");
+    let request_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth("/api/v1/role-requests", &request, &admin1_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Filing the request changes nothing yet
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "user");
+
+    let response = app
+        .get_auth(
+            "/api/v1/role-requests?status=pending",
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    let approve_path = format!("/api/v1/admin/role-requests/{request_id}/approve");
+    let response = app
+        .post_json_auth(
+            &approve_path,
+            &serde_json::json!({}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth(
+            &format!("{approve_path}?reason=Verified%20with%20the%20team"),
+            &serde_json::json!({}),
+            &admin1_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "approved");
+    assert_eq!(json["data"]["decision_reason"], "Verified with the team");
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "admin");
+
+    let response = app
+        .post_json_auth(&approve_path, &serde_json::json!({}), &admin2_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // The requester can't approve their own request, but can reject it
+    let response = app
+        .post_json_auth(
+            "/api/v1/role-requests",
+            &serde_json::json!({ "user_id": other.id, "role": "moderator" }),
+            &admin1_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let request_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/admin/role-requests/{request_id}/approve"),
+            &serde_json::json!({}),
+            &admin1_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/admin/role-requests/{request_id}/reject"),
+            &serde_json::json!({}),
+            &admin1_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "rejected");
+    assert_eq!(json["data"]["current_role"], "user");
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={}", user.id),
+            &admin2_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let actions: Vec<&str> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert!(actions.contains(&"role_requested"));
+    assert!(actions.contains(&"role_request_approved"));
+    assert!(actions.contains(&"user_role_changed"));
+}
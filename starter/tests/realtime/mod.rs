@@ -0,0 +1,270 @@
+use crate::helpers::*;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, Message, client::IntoClientRequest};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Open the WebSocket the way browsers do, with the token as a subprotocol
+async fn connect(app: &TestApp, token: &str) -> Result<Socket, tungstenite::Error> {
+    let url = format!("{}/api/v1/ws", app.address.replacen("http", "ws", 1));
+    let mut request = url.into_client_request().unwrap();
+    request.headers_mut().insert(
+        "sec-websocket-protocol",
+        format!("bearer, {token}").parse().unwrap(),
+    );
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await?;
+    let ready = receive(&mut socket).await;
+    assert_eq!(ready["type"], "ready");
+    Ok(socket)
+}
+
+/// Next JSON message from the server
+async fn receive(socket: &mut Socket) -> Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(10), socket.next())
+            .await
+            .expect("no message within 10 seconds")
+            .expect("connection closed")
+            .unwrap();
+        match message {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Ping(_) | Message::Pong(_) => continue,
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+}
+
+async fn send(socket: &mut Socket, message: Value) -> Value {
+    socket
+        .send(Message::Text(message.to_string().into()))
+        .await
+        .unwrap();
+    receive(socket).await
+}
+
+#[tokio::test]
+async fn test_websocket_requires_authentication() {
+    let app = spawn_app().await;
+
+    let url = format!("{}/api/v1/ws", app.address.replacen("http", "ws", 1));
+    match tokio_tungstenite::connect_async(url).await {
+        Err(tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("expected 401, got {other:?}"),
+    }
+
+    let error = connect(&app, "not-a-token").await.unwrap_err();
+    assert!(
+        matches!(error, tungstenite::Error::Http(response) if response.status() == StatusCode::UNAUTHORIZED)
+    );
+
+    // Plain requests to the endpoint aren't upgraded
+    let factory = TestDataFactory::new(app.clone());
+    let (_user, token) = factory.create_authenticated_user("plain_client").await;
+    let response = app.get_auth("/api/v1/ws", &token.token).await;
+    assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn test_websocket_sends_subscribed_task_updates() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (user, token) = factory.create_authenticated_user("ws_user").await;
+    let (_other, other_token) = factory.create_authenticated_user("ws_other").await;
+
+    let mut socket = connect(&app, &token.token).await.unwrap();
+    let reply = send(&mut socket, json!({"type": "subscribe", "topic": "tasks"})).await;
+    assert_eq!(reply, json!({"type": "subscribed", "topic": "tasks"}));
+    let reply = send(&mut socket, json!({"type": "ping"})).await;
+    assert_eq!(reply, json!({"type": "pong"}));
+    let reply = send(
+        &mut socket,
+        json!({"type": "subscribe", "topic": "weather"}),
+    )
+    .await;
+    assert_eq!(reply["type"], "error");
+
+    // Another user's task isn't sent, the user's own is
+    let task = json!({"task_type": "email", "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}});
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let created: Value = response.json().await.unwrap();
+
+    let update = receive(&mut socket).await;
+    assert_eq!(update["type"], "message");
+    assert_eq!(update["topic"], "tasks");
+    assert_eq!(update["data"]["id"], created["data"]["id"]);
+    assert_eq!(update["data"]["status"], "pending");
+
+    // Notifications reach the user they are addressed to
+    let reply = send(
+        &mut socket,
+        json!({"type": "subscribe", "topic": "notifications"}),
+    )
+    .await;
+    assert_eq!(reply["type"], "subscribed");
+    let mut conn = app.db().await;
+    starter::realtime::services::notify_user(
+        conn.as_mut(),
+        user.id,
+        starter::realtime::Notification {
+            kind: "test".to_string(),
+            message: "Hello".to_string(),
+            data: json!({}),
+        },
+    )
+    .await
+    .unwrap();
+    let notification = receive(&mut socket).await;
+    assert_eq!(notification["topic"], "notifications");
+    assert_eq!(notification["data"]["kind"], "test");
+}
+
+#[tokio::test]
+async fn test_websocket_connection_limit_and_listing() {
+    let app = spawn_app_with_config(|config| config.realtime.max_connections_per_user = 1).await;
+    let factory = TestDataFactory::new(app.clone());
+    let (user, token) = factory.create_authenticated_user("ws_user").await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("ws_admin").await;
+
+    let mut socket = connect(&app, &token.token).await.unwrap();
+    send(&mut socket, json!({"type": "subscribe", "topic": "alerts"})).await;
+    let error = connect(&app, &token.token).await.unwrap_err();
+    assert!(
+        matches!(error, tungstenite::Error::Http(response) if response.status() == StatusCode::TOO_MANY_REQUESTS)
+    );
+
+    let response = app
+        .get_auth("/api/v1/admin/realtime/connections", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: Value = response.json().await.unwrap();
+    let connections = json["data"].as_array().unwrap();
+    assert_eq!(connections.len(), 1);
+    assert_eq!(connections[0]["user_id"], json!(user.id));
+    assert_eq!(connections[0]["topics"], json!(["alerts"]));
+
+    let response = app
+        .get_auth("/api/v1/admin/realtime/connections", &token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Closing the connection makes room for another
+    socket.close(None).await.unwrap();
+    drop(socket);
+    for _ in 0..50 {
+        if connect(&app, &token.token).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("closed connection still counted");
+}
+
+#[tokio::test]
+async fn test_websocket_closes_when_session_ends() {
+    let app = spawn_app_with_config(|config| {
+        config.realtime.heartbeat_interval_secs = 1;
+        config.cache.session_ttl_secs = 0;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_user, token) = factory.create_authenticated_user("ws_user").await;
+
+    let mut socket = connect(&app, &token.token).await.unwrap();
+    let response = app.post_auth("/api/v1/auth/logout", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    let closed = tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(message) = socket.next().await {
+            if let Ok(Message::Close(frame)) = message {
+                return frame;
+            }
+        }
+        None
+    })
+    .await
+    .expect("connection not closed");
+    assert_eq!(closed.unwrap().reason, "Session ended");
+}
+
+/// Read a Server-Sent Events response until it contains `needle`
+async fn read_until(response: &mut reqwest::Response, needle: &str) -> String {
+    let mut received = String::new();
+    while !received.contains(needle) {
+        let chunk = tokio::time::timeout(Duration::from_secs(10), response.chunk())
+            .await
+            .expect("no message within 10 seconds")
+            .unwrap()
+            .expect("stream ended");
+        received.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    received
+}
+
+#[tokio::test]
+async fn test_sse_streams_task_changes_and_notifications() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (user, token) = factory.create_authenticated_user("sse_user").await;
+    let (_other, other_token) = factory.create_authenticated_user("sse_other").await;
+
+    let mut tasks = app.get_auth("/api/v1/tasks/stream", &token.token).await;
+    assert_status(&tasks, StatusCode::OK);
+    assert_eq!(
+        tasks.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let mut notifications = app
+        .get_auth("/api/v1/notifications/stream", &token.token)
+        .await;
+    assert_status(&notifications, StatusCode::OK);
+
+    // Another user's task isn't sent, the user's own is
+    let task = json!({"task_type": "email", "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}});
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &other_token.token)
+        .await;
+    let hidden: Value = response.json().await.unwrap();
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &token.token)
+        .await;
+    let created: Value = response.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+
+    let received = read_until(&mut tasks, id).await;
+    assert!(received.starts_with("event: task\n"));
+    assert!(received.contains(&format!("id: {id}\n")));
+    assert!(!received.contains(hidden["data"]["id"].as_str().unwrap()));
+
+    let mut conn = app.db().await;
+    starter::realtime::services::notify_user(
+        conn.as_mut(),
+        user.id,
+        starter::realtime::Notification {
+            kind: "test".to_string(),
+            message: "Hello".to_string(),
+            data: json!({}),
+        },
+    )
+    .await
+    .unwrap();
+    let received = read_until(&mut notifications, "Hello").await;
+    assert!(received.starts_with("event: notification\n"));
+    assert!(received.contains(r#""kind":"test""#));
+
+    let response = app.get("/api/v1/notifications/stream").await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
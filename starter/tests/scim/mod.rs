@@ -0,0 +1,378 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use secrecy::SecretString;
+use serde_json::json;
+
+const SCIM_TOKEN: &str = "test-scim-token";
+
+async fn spawn_scim_app() -> TestApp {
+    spawn_app_with_config(|config| config.scim_token = Some(SecretString::from(SCIM_TOKEN))).await
+}
+
+#[tokio::test]
+async fn test_scim_users() {
+    // The API isn't served without a configured token
+    let app = spawn_app().await;
+    let response = app.get_auth("/api/v1/scim/v2/Users", SCIM_TOKEN).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let app = spawn_scim_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("scim_admin").await;
+
+    // Session tokens aren't accepted
+    let response = app
+        .get_auth("/api/v1/scim/v2/Users", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["content-type"], "application/scim+json");
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["status"], "401");
+
+    // Provision an account without a password
+    let response = app
+        .post_json_auth(
+            "/api/v1/scim/v2/Users",
+            &json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": "scim_alice",
+                "externalId": "00u1",
+                "name": { "givenName": "Alice", "familyName": "Smith" },
+                "emails": [{ "value": "scim_alice@example.com", "type": "work", "primary": true }],
+                "active": true
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::CREATED);
+    let user: serde_json::Value = response.json().await.unwrap();
+    let user_id = user["id"].as_str().unwrap().to_string();
+    assert_eq!(user["userName"], "scim_alice");
+    assert_eq!(user["externalId"], "00u1");
+    assert_eq!(user["active"], true);
+    assert_eq!(user["groups"][0]["value"], "user");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/scim/v2/Users",
+            &json!({
+                "userName": "scim_alice",
+                "emails": [{ "value": "other@example.com" }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["scimType"], "uniqueness");
+
+    // Usernames match case-insensitively
+    let response = app
+        .get_auth(
+            "/api/v1/scim/v2/Users?filter=userName%20eq%20%22SCIM_ALICE%22",
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["totalResults"], 1);
+    assert_eq!(json["Resources"][0]["id"], user_id);
+
+    let response = app
+        .get_auth(
+            "/api/v1/scim/v2/Users?filter=userName%20sw%20%22scim%22",
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["scimType"], "invalidFilter");
+
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/scim/v2/Users/{user_id}"),
+            &json!({
+                "userName": "scim_alice",
+                "externalId": "00u1",
+                "emails": [{ "value": "alice.smith@example.com", "primary": true }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["emails"][0]["value"], "alice.smith@example.com");
+
+    // Deactivating a user signs them out
+    let (user, user_token) = factory.create_authenticated_user("scim_bob").await;
+    let response = app
+        .patch_json_auth(
+            &format!("/api/v1/scim/v2/Users/{}", user.id),
+            &json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [{ "op": "Replace", "path": "active", "value": "False" }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["active"], false);
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/users/{}/activity", user.id),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["action"], "status_changed");
+    assert_eq!(json["data"][0]["actor_id"], serde_json::Value::Null);
+    assert_eq!(json["data"][0]["details"]["source"], "scim");
+
+    // Deleted users disappear from SCIM but stay recoverable until purged
+    let response = app
+        .delete_auth(&format!("/api/v1/scim/v2/Users/{user_id}"), SCIM_TOKEN)
+        .await;
+    assert_status(&response, StatusCode::NO_CONTENT);
+    let response = app
+        .get_auth(&format!("/api/v1/scim/v2/Users/{user_id}"), SCIM_TOKEN)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .get_auth("/api/v1/scim/v2/Users/not-a-uuid", SCIM_TOKEN)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app.get_auth("/api/v1/scim/v2/Users", SCIM_TOKEN).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let ids: Vec<&str> = json["Resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["id"].as_str().unwrap())
+        .collect();
+    assert!(!ids.contains(&user_id.as_str()));
+    assert!(ids.contains(&admin.id.to_string().as_str()));
+}
+
+#[tokio::test]
+async fn test_scim_groups() {
+    let app = spawn_scim_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let user = factory.create_user("scim_carol").await;
+
+    let response = app.get_auth("/api/v1/scim/v2/Groups", SCIM_TOKEN).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["totalResults"], 3);
+
+    let response = app
+        .get_auth(
+            "/api/v1/scim/v2/Groups?filter=displayName%20eq%20%22moderator%22&excludedAttributes=members",
+            SCIM_TOKEN,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["totalResults"], 1);
+    assert_eq!(json["Resources"][0]["id"], "moderator");
+    assert!(json["Resources"][0].get("members").is_none());
+
+    // Adding a member grants the role
+    let response = app
+        .patch_json_auth(
+            "/api/v1/scim/v2/Groups/moderator",
+            &json!({
+                "Operations": [{ "op": "add", "path": "members", "value": [{ "value": user.id }] }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::NO_CONTENT);
+
+    let response = app
+        .get_auth(&format!("/api/v1/scim/v2/Users/{}", user.id), SCIM_TOKEN)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["groups"][0]["value"], "moderator");
+
+    let response = app
+        .get_auth("/api/v1/scim/v2/Groups/moderator", SCIM_TOKEN)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["members"][0]["value"], user.id.to_string());
+    assert_eq!(json["members"][0]["display"], "scim_carol");
+
+    // Removing it moves the user back to the user role
+    let response = app
+        .patch_json_auth(
+            "/api/v1/scim/v2/Groups/moderator",
+            &json!({
+                "Operations": [{
+                    "op": "remove",
+                    "path": format!("members[value eq \"{}\"]", user.id)
+                }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::NO_CONTENT);
+
+    let response = app
+        .get_auth(&format!("/api/v1/scim/v2/Users/{}", user.id), SCIM_TOKEN)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["groups"][0]["value"], "user");
+
+    // Groups are fixed roles
+    let response = app
+        .put_json_auth(
+            "/api/v1/scim/v2/Groups/admin",
+            &json!({ "displayName": "Owners", "members": [] }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["scimType"], "mutability");
+
+    let response = app
+        .patch_json_auth(
+            "/api/v1/scim/v2/Groups/admin",
+            &json!({
+                "Operations": [{ "op": "add", "path": "members", "value": [{ "value": uuid::Uuid::new_v4() }] }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .get_auth("/api/v1/scim/v2/Groups/owners", SCIM_TOKEN)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Admin is only granted through an approved role request, and a rejected
+    // replacement leaves the current admins in place
+    let (admin, _) = factory.create_authenticated_admin("scim_dan").await;
+    let response = app
+        .patch_json_auth(
+            "/api/v1/scim/v2/Groups/admin",
+            &json!({
+                "Operations": [{ "op": "add", "path": "members", "value": [{ "value": user.id }] }]
+            }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .put_json_auth(
+            "/api/v1/scim/v2/Groups/admin",
+            &json!({ "members": [{ "value": user.id }] }),
+            SCIM_TOKEN,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .get_auth("/api/v1/scim/v2/Groups/admin", SCIM_TOKEN)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["members"][0]["value"], admin.id.to_string());
+    assert_eq!(json["members"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_scim_tokens_are_scoped_to_their_tenant() {
+    use crate::tenants::{admin_in_tenant, create_tenant};
+
+    let app = spawn_app_with_config(|config| {
+        config.tenancy.enabled = true;
+        config.scim_token = Some(SecretString::from(SCIM_TOKEN));
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("scim_root").await;
+    let tenant_id = create_tenant(&app, &admin_token.token, "acme").await;
+    let (acme_admin, acme_token) = admin_in_tenant(&app, "acme", "scim_acme_admin").await;
+    let token_path = format!("/api/v1/admin/tenants/{tenant_id}/scim-token");
+
+    // Only the default tenant's admins issue tenant tokens
+    let response = app.post_auth(&token_path, &acme_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app.post_auth(&token_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let acme_scim = json["data"]["token"].as_str().unwrap().to_string();
+
+    // The tenant token only lists and provisions the tenant's users
+    let response = app.get_auth("/api/v1/scim/v2/Users", &acme_scim).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["totalResults"], 1);
+    assert_eq!(json["Resources"][0]["id"], acme_admin.to_string());
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/scim/v2/Users",
+            &json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": "scim_acme_dave",
+                "emails": [{ "value": "dave@acme.example.com", "primary": true }]
+            }),
+            &acme_scim,
+        )
+        .await;
+    assert_status(&response, StatusCode::CREATED);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let dave = uuid::Uuid::parse_str(json["id"].as_str().unwrap()).unwrap();
+    let dave_tenant: uuid::Uuid = sqlx::query_scalar("SELECT tenant_id FROM users WHERE id = $1")
+        .bind(dave)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(dave_tenant.to_string(), tenant_id);
+
+    // Other tenants' users read as missing and can't be put in a group
+    let admin_path = format!("/api/v1/scim/v2/Users/{}", admin.id);
+    let response = app.get_auth(&admin_path, &acme_scim).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.delete_auth(&admin_path, &acme_scim).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .patch_json_auth(
+            "/api/v1/scim/v2/Groups/moderator",
+            &json!({
+                "Operations": [{ "op": "add", "path": "members", "value": [{ "value": admin.id }] }]
+            }),
+            &acme_scim,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .get_auth("/api/v1/scim/v2/Groups/admin", &acme_scim)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["members"],
+        json!([{ "value": acme_admin.to_string(), "display": "scim_acme_admin" }])
+    );
+
+    // The configured token provisions the default tenant only
+    let response = app
+        .get_auth(&format!("/api/v1/scim/v2/Users/{dave}"), SCIM_TOKEN)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Revoked tokens stop working
+    let response = app.delete_auth(&token_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth("/api/v1/scim/v2/Users", &acme_scim).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
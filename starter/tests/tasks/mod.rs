@@ -1552,16 +1552,14 @@ async fn test_security_payload_size_limits() {
     let response = app
         .post_json_auth("/api/v1/tasks", &oversized_task_data, &token.token)
         .await;
-    // Server may return 413 (Payload Too Large) or 400 (Bad Request) depending on where validation occurs
-    assert!(
-        response.status() == StatusCode::BAD_REQUEST
-            || response.status() == StatusCode::PAYLOAD_TOO_LARGE
+    // Refused by the tasks body limit before the payload is validated
+    assert_status(&response, StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(
+        json["error"]["details"]["limit_bytes"],
+        app.config.body_limit.tasks_bytes
     );
-
-    if response.status() == StatusCode::BAD_REQUEST {
-        let json: serde_json::Value = response.json().await.unwrap();
-        assert!(json["error"]["message"].as_str().unwrap().contains("1MB"));
-    }
 }
 
 #[tokio::test]
@@ -1830,3 +1828,2391 @@ async fn test_security_concurrent_task_processing_race_conditions() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_archive_old_terminal_tasks() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let other_username = format!("otheruser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_other, other_token) = factory.create_authenticated_user(&other_username).await;
+
+    let mut task_ids = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .post_json_auth(
+                "/api/v1/tasks",
+                &json!({"task_type": "email", "payload": {"to": "test@example.com"}}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        task_ids.push(uuid::Uuid::parse_str(json["data"]["id"].as_str().unwrap()).unwrap());
+    }
+
+    // Old completed task, old failed task, and a recently completed task
+    sqlx::query!(
+        "UPDATE tasks SET status = 'completed', completed_at = NOW() - INTERVAL '40 days' WHERE id = $1",
+        task_ids[0]
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE tasks SET status = 'failed', last_error = 'boom', completed_at = NOW() - INTERVAL '40 days' WHERE id = $1",
+        task_ids[1]
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE tasks SET status = 'completed', completed_at = NOW() WHERE id = $1",
+        task_ids[2]
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(30);
+    let mut conn = app.db().await;
+    let archived = starter::tasks::archive::archive_tasks(conn.as_mut(), cutoff)
+        .await
+        .unwrap();
+    assert_eq!(archived, 2);
+
+    // Archived tasks are gone from the hot table
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{}", task_ids[0]), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].is_null());
+
+    // Recent task stays in the hot table
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{}", task_ids[2]), &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "completed");
+
+    // Owner can query archived history
+    let response = app.get_auth("/api/v1/tasks/archive", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let archived_tasks = json["data"].as_array().unwrap();
+    assert_eq!(archived_tasks.len(), 2);
+    assert!(archived_tasks.iter().all(|t| t["archived_at"].is_string()));
+
+    let response = app
+        .get_auth("/api/v1/tasks/archive?status=failed", &token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let failed = json["data"].as_array().unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["last_error"], "boom");
+
+    // Other users cannot see someone else's archived tasks
+    let response = app
+        .get_auth("/api/v1/tasks/archive", &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(json["data"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_archived_task_keeps_its_attempts() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("archive_owner").await;
+    let (_other, other_token) = factory.create_authenticated_user("archive_other").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "test@example.com"}}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = uuid::Uuid::parse_str(json["data"]["id"].as_str().unwrap()).unwrap();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO task_attempts (task_id, attempt_number, worker_id, finished_at, error, error_class)
+        VALUES ($1, 1, 'worker-1', NOW(), 'smtp timeout', 'error')
+        "#,
+        task_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE tasks SET status = 'failed', completed_at = NOW() - INTERVAL '40 days' WHERE id = $1",
+        task_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(30);
+    let mut conn = app.db().await;
+    let archived = starter::tasks::archive::archive_tasks(conn.as_mut(), cutoff)
+        .await
+        .unwrap();
+    assert_eq!(archived, 1);
+
+    let path = format!("/api/v1/tasks/{task_id}/attempts");
+    let response = app.get_auth(&path, &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let attempts = json["data"].as_array().unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0]["worker_id"], "worker-1");
+    assert_eq!(attempts[0]["error"], "smtp timeout");
+
+    // The archived task's attempts are as private as the task
+    let response = app.get_auth(&path, &other_token.token).await;
+    assert!(!response.status().is_success());
+}
+
+#[tokio::test]
+async fn test_task_attempts_history() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::{DelayTaskHandler, EmailTaskHandler};
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    // A task that succeeds on its first attempt
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "delay_task", "payload": {"delay_seconds": 0}}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let ok_task_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    // A task whose payload is missing required fields, limited to a single attempt
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {}}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let failing_task_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    sqlx::query!(
+        "UPDATE tasks SET max_attempts = 1 WHERE id = $1",
+        uuid::Uuid::parse_str(&failing_task_id).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // No attempts are recorded before a worker picks the task up
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{ok_task_id}/attempts"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"].as_array().unwrap().len(), 0);
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(100),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let worker_id = processor.worker_id().to_string();
+    let processor_handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    // Wait for both tasks to reach a terminal state
+    for _ in 0..50 {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM tasks WHERE created_by IS NOT NULL AND status IN ('completed', 'failed')"
+        )
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+        if count == Some(2) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    processor_handle.abort();
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{ok_task_id}/attempts"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let attempts = body["data"].as_array().unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0]["attempt_number"], 1);
+    assert_eq!(attempts[0]["worker_id"], worker_id.as_str());
+    assert!(attempts[0]["error"].is_null());
+    assert!(!attempts[0]["finished_at"].is_null());
+    assert!(attempts[0]["duration_ms"].as_i64().unwrap() >= 0);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{failing_task_id}/attempts"),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let attempts = body["data"].as_array().unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert!(attempts[0]["error"].as_str().unwrap().contains("to"));
+
+    // Other users cannot see the attempt history (404 to prevent enumeration)
+    let other_username = format!("other_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_other, other_token) = factory.create_authenticated_user(&other_username).await;
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{ok_task_id}/attempts"),
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{}/attempts", uuid::Uuid::new_v4()),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_task_tags_filtering() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let email_payload = json!({
+        "to": "test@example.com",
+        "subject": "Test",
+        "body": "Test body"
+    });
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": email_payload, "tags": ["billing", "urgent"]}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["tags"], json!(["billing", "urgent"]));
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": email_payload, "tags": ["billing"]}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": email_payload}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["tags"], json!([]));
+
+    let response = app
+        .get_auth("/api/v1/tasks?tag=billing", &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+    let response = app.get_auth("/api/v1/tasks?tag=urgent", &token.token).await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    let tasks = body["data"].as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["tags"], json!(["billing", "urgent"]));
+
+    let response = app
+        .get_auth("/api/v1/tasks?tag=unknown", &token.token)
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"].as_array().unwrap().len(), 0);
+
+    // Invalid tags are rejected
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": email_payload, "tags": ["bad tag!"]}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_task_submission_rate_limit() {
+    let app = spawn_app_with_config(|config| config.tasks.rate_limit_per_minute = 2).await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let task_data = json!({
+        "task_type": "email",
+        "payload": {
+            "to": "test@example.com",
+            "subject": "Test",
+            "body": "Test body"
+        }
+    });
+
+    for _ in 0..2 {
+        let response = app
+            .post_json_auth("/api/v1/tasks", &task_data, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    // Third submission within the same minute is rejected
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .headers()
+        .get("retry-after")
+        .expect("Retry-After header should be set")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=60).contains(&retry_after));
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "RATE_LIMITED");
+
+    // The limit is tracked per user
+    let other_username = format!("other_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_other, other_token) = factory.create_authenticated_user(&other_username).await;
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_task_quotas_by_role() {
+    let app = spawn_app_with_config(|config| {
+        config.tasks.quotas.user.max_pending = 2;
+        config.tasks.quotas.user.max_per_day = 0;
+        config.tasks.quotas.moderator.max_pending = 0;
+        config.tasks.quotas.moderator.max_per_day = 1;
+    })
+    .await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let task_data = json!({
+        "task_type": "email",
+        "payload": {
+            "to": "test@example.com",
+            "subject": "Test",
+            "body": "Test body"
+        }
+    });
+
+    // Regular users are capped on unfinished tasks
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let mut task_ids = Vec::new();
+    for _ in 0..2 {
+        let response = app
+            .post_json_auth("/api/v1/tasks", &task_data, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let body: serde_json::Value = response.json().await.unwrap();
+        task_ids.push(body["data"]["id"].as_str().unwrap().to_string());
+    }
+
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "QUOTA_EXCEEDED");
+
+    let response = app.get_auth("/api/v1/tasks/quota", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["role"], "user");
+    assert_eq!(body["data"]["pending"]["used"], 2);
+    assert_eq!(body["data"]["pending"]["limit"], 2);
+    assert_eq!(body["data"]["daily"]["used"], 2);
+    assert!(body["data"]["daily"]["limit"].is_null());
+    assert_json_field_exists(&body["data"], "daily_resets_at");
+
+    // Cancelling a task frees a pending slot
+    let response = app
+        .post_auth(
+            &format!("/api/v1/tasks/{}/cancel", task_ids[0]),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Moderators resolve to their own role's quota
+    let moderator_username = format!("mod_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator(&moderator_username)
+        .await;
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task_data, &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+
+    let response = app
+        .get_auth("/api/v1/tasks/quota", &moderator_token.token)
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["role"], "moderator");
+    assert_eq!(body["data"]["daily"]["limit"], 1);
+    assert!(body["data"]["pending"]["limit"].is_null());
+}
+
+#[tokio::test]
+async fn test_scheduled_task_runs_on_time() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    // Poll interval far longer than the schedule delay: only the scheduler wakeup can run it on time
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_secs(30),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+
+    let scheduled_at = chrono::Utc::now() + chrono::Duration::seconds(2);
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({
+                "task_type": "delay_task",
+                "payload": {"delay_seconds": 0},
+                "scheduled_at": scheduled_at
+            }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let task_id = uuid::Uuid::parse_str(body["data"]["id"].as_str().unwrap()).unwrap();
+
+    let processor_handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    // Not picked up before it is due
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    let status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+
+    let mut started_at = None;
+    for _ in 0..40 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        started_at = sqlx::query_scalar!("SELECT started_at FROM tasks WHERE id = $1", task_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if started_at.is_some() {
+            break;
+        }
+    }
+    processor_handle.abort();
+
+    let started_at = started_at.expect("Scheduled task should have started");
+    let lateness = started_at - scheduled_at;
+    assert!(
+        lateness >= chrono::Duration::zero() && lateness < chrono::Duration::seconds(1),
+        "Task started {}ms after its scheduled time",
+        lateness.num_milliseconds()
+    );
+}
+
+#[tokio::test]
+async fn test_task_timeout_overrides() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    // Per-type default timeout
+    let response = app
+        .post_json(
+            "/api/v1/tasks/types",
+            &json!({
+                "task_type": "delay_task",
+                "description": "Delay tasks",
+                "timeout_seconds": 1
+            }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["timeout_seconds"], 1);
+
+    // Re-registering without a timeout keeps the existing default
+    factory.register_task_types().await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "delay_task", "payload": {"delay_seconds": 0}, "timeout_seconds": 0}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let create = |timeout_seconds: Option<i32>, delay_seconds: u64| {
+        let app = app.clone();
+        let token = token.token.clone();
+        async move {
+            let mut task = json!({
+                "task_type": "delay_task",
+                "payload": {"delay_seconds": delay_seconds}
+            });
+            if let Some(timeout_seconds) = timeout_seconds {
+                task["timeout_seconds"] = json!(timeout_seconds);
+            }
+            let response = app.post_json_auth("/api/v1/tasks", &task, &token).await;
+            assert_status(&response, StatusCode::OK);
+            let body: serde_json::Value = response.json().await.unwrap();
+            let id = uuid::Uuid::parse_str(body["data"]["id"].as_str().unwrap()).unwrap();
+            sqlx::query!("UPDATE tasks SET max_attempts = 1 WHERE id = $1", id)
+                .execute(&app.db_pool)
+                .await
+                .unwrap();
+            id
+        }
+    };
+
+    let type_default_task = create(None, 3).await;
+    let task_override_short = create(Some(1), 3).await;
+    let task_override_long = create(Some(10), 2).await;
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(100),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+    let processor_handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    for _ in 0..60 {
+        let unfinished = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM tasks WHERE id = ANY($1) AND status NOT IN ('completed', 'failed')",
+            &[type_default_task, task_override_short, task_override_long]
+        )
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+        if unfinished == Some(0) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    processor_handle.abort();
+
+    for task_id in [type_default_task, task_override_short] {
+        let response = app
+            .get_auth(&format!("/api/v1/tasks/{task_id}/attempts"), &token.token)
+            .await;
+        let body: serde_json::Value = response.json().await.unwrap();
+        let attempts = body["data"].as_array().unwrap();
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0]["error_class"], "timed_out");
+        assert!(
+            attempts[0]["error"]
+                .as_str()
+                .unwrap()
+                .contains("timed out after 1s")
+        );
+    }
+
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_override_long}"), &token.token)
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["status"], "completed");
+    assert_eq!(body["data"]["timeout_seconds"], 10);
+}
+
+#[tokio::test]
+async fn test_task_queue_stats() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let unique_username = format!("testuser_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_user, token) = factory.create_authenticated_user(&unique_username).await;
+    let unique_moderator = format!("moderator_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator(&unique_moderator)
+        .await;
+
+    let due = factory
+        .create_task("email", json!({"to": "test@example.com"}))
+        .await;
+    let due_id = uuid::Uuid::parse_str(due["data"]["id"].as_str().unwrap()).unwrap();
+    sqlx::query!(
+        "UPDATE tasks SET created_at = NOW() - INTERVAL '90 seconds', scheduled_at = NOW() - INTERVAL '90 seconds' WHERE id = $1",
+        due_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // A future task counts toward depth but not toward the oldest pending age
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({
+                "task_type": "email",
+                "payload": {"to": "test@example.com"},
+                "scheduled_at": (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339()
+            }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth("/api/v1/tasks/queue-stats", &token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .get_auth("/api/v1/tasks/queue-stats", &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let stats = body["data"].as_array().unwrap();
+
+    let email = stats.iter().find(|s| s["task_type"] == "email").unwrap();
+    assert_eq!(email["pending"], 2);
+    assert_eq!(email["running"], 0);
+    let age = email["oldest_pending_age_seconds"].as_f64().unwrap();
+    assert!((89.0..600.0).contains(&age), "unexpected age {age}");
+
+    // Idle registered types are reported with zeros
+    let idle = stats
+        .iter()
+        .find(|s| s["task_type"] == "data_processing")
+        .unwrap();
+    assert_eq!(idle["pending"], 0);
+    assert_eq!(idle["oldest_pending_age_seconds"], 0.0);
+
+    let response = app.get("/api/v1/monitoring/metrics/prometheus").await;
+    assert_status(&response, StatusCode::OK);
+    let text = response.text().await.unwrap();
+    assert!(text.contains("# TYPE task_queue_pending gauge"));
+    assert!(text.contains("task_queue_pending{task_type=\"email\"} 2"));
+    assert!(text.contains("task_queue_running{task_type=\"email\"} 0"));
+}
+
+#[tokio::test]
+async fn test_task_dedupe_key() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    let (_user, token) = factory
+        .create_authenticated_user(&format!(
+            "dedupe_{}",
+            &uuid::Uuid::new_v4().to_string()[..8]
+        ))
+        .await;
+    let (_other, other_token) = factory
+        .create_authenticated_user(&format!(
+            "dedupe_{}",
+            &uuid::Uuid::new_v4().to_string()[..8]
+        ))
+        .await;
+
+    let task = json!({
+        "task_type": "email",
+        "payload": {"to": "test@example.com"},
+        "dedupe_key": "sync-account-42"
+    });
+
+    let create = |token: String| {
+        let app = app.clone();
+        let task = task.clone();
+        async move {
+            let response = app.post_json_auth("/api/v1/tasks", &task, &token).await;
+            assert_status(&response, StatusCode::OK);
+            let body: serde_json::Value = response.json().await.unwrap();
+            body["data"]["id"].as_str().unwrap().to_string()
+        }
+    };
+
+    let first = create(token.token.clone()).await;
+    let second = create(token.token.clone()).await;
+    assert_eq!(first, second, "active task with the same key is returned");
+
+    // Keys are scoped to the creator
+    let other = create(other_token.token.clone()).await;
+    assert_ne!(first, other);
+
+    // Once the task finishes the key can be reused
+    sqlx::query!(
+        "UPDATE tasks SET status = 'completed', completed_at = NOW() WHERE id = $1",
+        uuid::Uuid::parse_str(&first).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let third = create(token.token.clone()).await;
+    assert_ne!(first, third);
+
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{third}"), &token.token)
+        .await;
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["dedupe_key"], "sync-account-42");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {}, "dedupe_key": ""}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_batch_claims_are_disjoint_across_workers() {
+    let app = spawn_app().await;
+    let _factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ClaimStrategy, ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    sqlx::query!(
+        r#"INSERT INTO tasks (task_type, payload, max_attempts)
+           SELECT 'delay_task', '{"delay_seconds": 0}', 1 FROM generate_series(1, 60)"#
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let mut handles = Vec::new();
+    for _ in 0..3 {
+        let processor = TaskProcessor::new(
+            Database {
+                pool: app.db_pool.clone(),
+            },
+            ProcessorConfig {
+                poll_interval: Duration::from_millis(20),
+                batch_size: 10,
+                claim_strategy: ClaimStrategy::Batch,
+                ..Default::default()
+            },
+        );
+        processor
+            .register_handler("delay_task".to_string(), DelayTaskHandler)
+            .await;
+        handles.push(tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        }));
+    }
+
+    let mut completed = Some(0);
+    for _ in 0..100 {
+        completed = sqlx::query_scalar!("SELECT COUNT(*) FROM tasks WHERE status = 'completed'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if completed == Some(60) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    for handle in handles {
+        handle.abort();
+    }
+    assert_eq!(completed, Some(60));
+
+    // Every task ran exactly once
+    let duplicated = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM (SELECT task_id FROM task_attempts GROUP BY task_id HAVING COUNT(*) > 1) d"
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(duplicated, Some(0));
+}
+
+#[tokio::test]
+async fn test_batch_claims_are_limited_to_free_slots() {
+    let app = spawn_app().await;
+    let _factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ClaimStrategy, ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    sqlx::query!(
+        r#"INSERT INTO tasks (task_type, payload, max_attempts)
+           SELECT 'delay_task', '{"delay_seconds": 1}', 1 FROM generate_series(1, 8)"#
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(20),
+            batch_size: 50,
+            max_concurrent_tasks: 3,
+            claim_strategy: ClaimStrategy::Batch,
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+    let handle = tokio::spawn(async move {
+        let _ = processor.start_worker().await;
+    });
+
+    // Tasks only turn running once a slot is free to run them
+    let mut max_running = 0;
+    let mut completed = Some(0);
+    for _ in 0..200 {
+        let running = sqlx::query_scalar!("SELECT COUNT(*) FROM tasks WHERE status = 'running'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap()
+            .unwrap_or(0);
+        max_running = max_running.max(running);
+        completed = sqlx::query_scalar!("SELECT COUNT(*) FROM tasks WHERE status = 'completed'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if completed == Some(8) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    handle.abort();
+    assert_eq!(completed, Some(8));
+    assert!(max_running > 0 && max_running <= 3, "{max_running} running");
+}
+
+/// Claim throughput benchmark comparing claim strategies across worker counts.
+///
+/// Run with:
+/// `cargo test --test lib bench_claim_strategies -- --ignored --nocapture`
+#[tokio::test]
+#[ignore = "benchmark"]
+async fn bench_claim_strategies() {
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ClaimStrategy, ProcessorConfig, TaskProcessor};
+    use std::time::{Duration, Instant};
+
+    const TASKS: i64 = 2_000;
+
+    println!("strategy  workers  seconds  tasks/s  attempts");
+    for strategy in [ClaimStrategy::Single, ClaimStrategy::Batch] {
+        for workers in [1, 2, 4, 8] {
+            let app = spawn_app().await;
+            let _factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+            sqlx::query!(
+                r#"INSERT INTO tasks (task_type, payload, max_attempts)
+                   SELECT 'delay_task', '{"delay_seconds": 0}', 1 FROM generate_series(1, $1::BIGINT)"#,
+                TASKS
+            )
+            .execute(&app.db_pool)
+            .await
+            .unwrap();
+
+            let started = Instant::now();
+            let mut handles = Vec::new();
+            for _ in 0..workers {
+                let processor = TaskProcessor::new(
+                    Database {
+                        pool: app.db_pool.clone(),
+                    },
+                    ProcessorConfig {
+                        poll_interval: Duration::from_millis(10),
+                        max_concurrent_tasks: 10,
+                        batch_size: 50,
+                        claim_strategy: strategy,
+                        ..Default::default()
+                    },
+                );
+                processor
+                    .register_handler("delay_task".to_string(), DelayTaskHandler)
+                    .await;
+                handles.push(tokio::spawn(async move {
+                    let _ = processor.start_worker().await;
+                }));
+            }
+
+            loop {
+                let remaining = sqlx::query_scalar!(
+                    "SELECT COUNT(*) FROM tasks WHERE status IN ('pending', 'running')"
+                )
+                .fetch_one(&app.db_pool)
+                .await
+                .unwrap();
+                if remaining == Some(0) || started.elapsed() > Duration::from_secs(300) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            let elapsed = started.elapsed().as_secs_f64();
+            for handle in handles {
+                handle.abort();
+            }
+
+            let attempts = sqlx::query_scalar!("SELECT COUNT(*) FROM task_attempts")
+                .fetch_one(&app.db_pool)
+                .await
+                .unwrap()
+                .unwrap_or(0);
+
+            println!(
+                "{:<8}  {:>7}  {:>7.2}  {:>7.0}  {:>8}",
+                format!("{strategy:?}").to_lowercase(),
+                workers,
+                elapsed,
+                TASKS as f64 / elapsed,
+                attempts
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_expired_task_leases_are_reaped() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::tasks::leases::reap_expired_leases;
+
+    let mut task_ids = Vec::new();
+    for max_attempts in [3, 1] {
+        let task = factory
+            .create_task("email", json!({"to": "test@example.com"}))
+            .await;
+        let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+        sqlx::query!(
+            r#"UPDATE tasks SET status = 'running', started_at = NOW(), max_attempts = $2,
+                  claimed_by = 'dead-worker', lease_expires_at = NOW() - INTERVAL '1 second'
+               WHERE id = $1"#,
+            task_id,
+            max_attempts
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "INSERT INTO task_attempts (task_id, attempt_number, worker_id, started_at) VALUES ($1, 1, 'dead-worker', NOW())",
+            task_id
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+        task_ids.push(task_id);
+    }
+
+    // A running task with a live lease is left alone
+    let live = factory
+        .create_task("email", json!({"to": "test@example.com"}))
+        .await;
+    let live_id = uuid::Uuid::parse_str(live["data"]["id"].as_str().unwrap()).unwrap();
+    sqlx::query!(
+        r#"UPDATE tasks SET status = 'running', claimed_by = 'live-worker',
+              lease_expires_at = NOW() + INTERVAL '1 minute'
+           WHERE id = $1"#,
+        live_id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let mut conn = app.db().await;
+    let reaped = reap_expired_leases(conn.as_mut()).await.unwrap();
+    assert_eq!(reaped.len(), 2);
+    assert!(
+        reaped
+            .iter()
+            .all(|t| t.claimed_by.as_deref() == Some("dead-worker"))
+    );
+
+    let retried = sqlx::query!(
+        "SELECT status, current_attempt, claimed_by, lease_expires_at FROM tasks WHERE id = $1",
+        task_ids[0]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(retried.status, "pending");
+    assert_eq!(retried.current_attempt, 1);
+    assert!(retried.claimed_by.is_none() && retried.lease_expires_at.is_none());
+
+    let exhausted = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_ids[1])
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(exhausted, "failed");
+
+    let live_status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", live_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(live_status, "running");
+
+    let open_attempts = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM task_attempts WHERE task_id = ANY($1) AND (finished_at IS NULL OR error_class <> 'lease_expired')",
+        &task_ids
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(open_attempts, Some(0));
+
+    let events =
+        sqlx::query_scalar!("SELECT COUNT(*) FROM events WHERE source = 'task-lease-reaper'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(events, Some(2));
+}
+
+#[tokio::test]
+async fn test_running_worker_renews_task_lease() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::leases::reap_expired_leases;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let task = factory
+        .create_task("delay_task", json!({"delay_seconds": 2}))
+        .await;
+    let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            lease_duration: Duration::from_millis(600),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    // The task runs for longer than its lease; heartbeats must keep it owned
+    let mut status = String::new();
+    for _ in 0..40 {
+        let mut conn = app.db().await;
+        let reaped = reap_expired_leases(conn.as_mut()).await.unwrap();
+        assert!(reaped.is_empty(), "live task lease was reaped");
+
+        status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if status == "completed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+    assert_eq!(status, "completed");
+}
+
+#[tokio::test]
+async fn test_reclaimed_task_is_not_finished_by_old_worker() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::DelayTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let task = factory
+        .create_task("delay_task", json!({"delay_seconds": 1}))
+        .await;
+    let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("delay_task".to_string(), DelayTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    // While the handler runs, another worker takes the task over as if the
+    // lease had expired and it was reclaimed
+    let mut reclaimed = false;
+    for _ in 0..20 {
+        reclaimed = sqlx::query!(
+            "UPDATE tasks SET claimed_by = 'new-worker' WHERE id = $1 AND status = 'running'",
+            task_id
+        )
+        .execute(&app.db_pool)
+        .await
+        .unwrap()
+        .rows_affected()
+            == 1;
+        if reclaimed {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert!(reclaimed, "task was never claimed");
+
+    // The old worker's outcome is discarded once the handler returns
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    handle.abort();
+    let row = sqlx::query!(
+        "SELECT status, claimed_by, completed_at FROM tasks WHERE id = $1",
+        task_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(row.status, "running");
+    assert_eq!(row.claimed_by.as_deref(), Some("new-worker"));
+    assert!(row.completed_at.is_none());
+}
+
+#[tokio::test]
+async fn test_task_handler_logs_become_events() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let unique_username = format!("moderator_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (_moderator, token) = factory
+        .create_authenticated_moderator(&unique_username)
+        .await;
+
+    let task = factory
+        .create_task(
+            "email",
+            json!({"to": "test@example.com", "subject": "Hello", "body": "Hi"}),
+        )
+        .await;
+    let task_id = task["data"]["id"].as_str().unwrap().to_string();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let mut events = Vec::new();
+    for _ in 0..40 {
+        let response = app
+            .get_auth(
+                &format!("/api/v1/monitoring/events?tags=task_id:{task_id}"),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let body: serde_json::Value = response.json().await.unwrap();
+        events = body["data"].as_array().unwrap().clone();
+        if !events.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event["event_type"], "log");
+    assert_eq!(event["source"], "task:email");
+    assert_eq!(event["level"], "info");
+    assert_eq!(event["tags"]["task_type"], "email");
+    assert!(
+        event["message"]
+            .as_str()
+            .unwrap()
+            .contains("Sending email to: test@example.com")
+    );
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_admin_controls() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let (_admin, admin_token) = factory
+        .create_authenticated_admin(&format!("admin_{}", &uuid::Uuid::new_v4().to_string()[..8]))
+        .await;
+    let (_user, user_token) = factory
+        .create_authenticated_user(&format!("user_{}", &uuid::Uuid::new_v4().to_string()[..8]))
+        .await;
+
+    let response = app
+        .get_auth("/api/v1/admin/tasks/circuit-breakers", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/circuit-breakers/not_a_type/trip",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let create_email = |body: &'static str| {
+        let factory = &factory;
+        let app = &app;
+        async move {
+            let task = factory
+                .create_task(
+                    "email",
+                    json!({"to": "test@example.com", "subject": "Hi", "body": body}),
+                )
+                .await;
+            let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+            sqlx::query!("UPDATE tasks SET max_attempts = 1 WHERE id = $1", task_id)
+                .execute(&app.db_pool)
+                .await
+                .unwrap();
+            task_id
+        }
+    };
+    let wait_for_status = |task_id: uuid::Uuid| {
+        let app = &app;
+        async move {
+            for _ in 0..60 {
+                let status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+                    .fetch_one(&app.db_pool)
+                    .await
+                    .unwrap();
+                if status == "completed" || status == "failed" {
+                    return status;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            panic!("task {task_id} did not finish");
+        }
+    };
+    let email_breaker = |token: String| {
+        let app = &app;
+        async move {
+            let response = app
+                .get_auth("/api/v1/admin/tasks/circuit-breakers", &token)
+                .await;
+            assert_status(&response, StatusCode::OK);
+            let body: serde_json::Value = response.json().await.unwrap();
+            body["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|b| b["task_type"] == "email")
+                .cloned()
+                .unwrap()
+        }
+    };
+
+    assert_eq!(
+        email_breaker(admin_token.token.clone()).await["state"],
+        "closed"
+    );
+
+    // Repeated failures open the breaker and the worker reports it
+    let mut failing = Vec::new();
+    for _ in 0..5 {
+        failing.push(create_email("please fail").await);
+    }
+    for task_id in failing {
+        assert_eq!(wait_for_status(task_id).await, "failed");
+    }
+    let breaker = email_breaker(admin_token.token.clone()).await;
+    assert_eq!(breaker["state"], "open");
+    assert_eq!(breaker["forced"], false);
+    assert_eq!(breaker["reported_by"], processor.worker_id());
+
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/circuit-breakers/email/reset",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["state"], "closed");
+    assert_eq!(
+        wait_for_status(create_email("hello").await).await,
+        "completed"
+    );
+
+    // A manual trip blocks the task type until reset
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/circuit-breakers/email/trip",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["state"], "open");
+    assert_eq!(body["data"]["forced"], true);
+
+    let blocked = create_email("hello").await;
+    assert_eq!(wait_for_status(blocked).await, "failed");
+    let error_class = sqlx::query_scalar!(
+        "SELECT error_class FROM task_attempts WHERE task_id = $1",
+        blocked
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(error_class.as_deref(), Some("circuit_open"));
+
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/circuit-breakers/email/reset",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        wait_for_status(create_email("hello").await).await,
+        "completed"
+    );
+    handle.abort();
+
+    let events =
+        sqlx::query_scalar!("SELECT COUNT(*) FROM events WHERE source = 'task-circuit-breaker'")
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+    // Worker-reported open, manual reset, manual trip, manual reset
+    assert_eq!(events, Some(4));
+}
+
+#[tokio::test]
+async fn test_worker_metrics_endpoint() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::metrics::serve_metrics;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let ok = factory
+        .create_task(
+            "email",
+            json!({"to": "test@example.com", "subject": "Hi", "body": "hello"}),
+        )
+        .await;
+    let failing = factory
+        .create_task(
+            "email",
+            json!({"to": "test@example.com", "subject": "Hi", "body": "please fail"}),
+        )
+        .await;
+    let ok_id = uuid::Uuid::parse_str(ok["data"]["id"].as_str().unwrap()).unwrap();
+    let failing_id = uuid::Uuid::parse_str(failing["data"]["id"].as_str().unwrap()).unwrap();
+    let database = Database {
+        pool: app.db_pool.clone(),
+    };
+    let processor = TaskProcessor::new(
+        database.clone(),
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            enable_circuit_breaker: false,
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    for _ in 0..50 {
+        let done = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM tasks
+               WHERE (id = $1 AND status = 'completed') OR (id = $2 AND status = 'failed')"#,
+            ok_id,
+            failing_id
+        )
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+        if done == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_metrics(listener, processor.metrics(), database));
+
+    let response = reqwest::get(format!("http://{address}/metrics"))
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let body = response.text().await.unwrap();
+    server.abort();
+
+    assert!(body.contains("task_completed_total{task_type=\"email\"} 1"));
+    assert!(body.contains("task_failed_total{task_type=\"email\",error_class=\"error\"} 1"));
+    assert!(body.contains("# TYPE task_retries_total counter"));
+    assert!(body.contains("task_handler_duration_seconds_count{task_type=\"email\"} 2"));
+    // Queue depth gauges are included alongside the execution metrics
+    assert!(body.contains("# TYPE task_queue_pending gauge"));
+}
+
+#[tokio::test]
+async fn test_schedule_preview() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_user, token) = factory.create_authenticated_user("scheduleuser").await;
+
+    let response = app
+        .post_json(
+            "/api/v1/tasks/schedules/preview",
+            &json!({"cron": "0 9 * * 1-5"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks/schedules/preview",
+            &json!({"cron": "0 9 * * 1-5", "timezone": "Asia/Tokyo", "count": 3}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["timezone"], "Asia/Tokyo");
+    let runs = body["data"]["next_runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 3);
+    for run in runs {
+        let run = chrono::DateTime::parse_from_rfc3339(run.as_str().unwrap()).unwrap();
+        assert_eq!(run.offset().local_minus_utc(), 9 * 3600);
+        assert_eq!(run.format("%H:%M").to_string(), "09:00");
+        assert!(run > chrono::Utc::now());
+    }
+
+    for invalid in [
+        json!({"cron": "not a cron"}),
+        json!({"cron": "0 25 * * *"}),
+        json!({"cron": "0 9 * * *", "timezone": "Nowhere/City"}),
+        json!({"cron": "0 9 * * *", "count": 500}),
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/tasks/schedules/preview", &invalid, &token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_task_templates() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_moderator, moderator_token) = factory.create_authenticated_moderator("tplmod").await;
+    let (user, user_token) = factory.create_authenticated_user("tpluser").await;
+
+    let template = json!({
+        "name": "weekly-report",
+        "description": "Weekly team report email",
+        "task_type": "email",
+        "payload": {
+            "to": "team@example.com",
+            "subject": "Weekly report",
+            "body": "See attached",
+            "options": {"html": true, "track": true}
+        },
+        "priority": "high",
+        "metadata": {"source": "template", "team": "core"}
+    });
+
+    // Only moderators and above manage templates
+    let response = app
+        .post_json_auth("/api/v1/tasks/templates", &template, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_json_auth("/api/v1/tasks/templates", &template, &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth("/api/v1/tasks/templates", &template, &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    for invalid in [
+        json!({"name": "Bad Name", "task_type": "email"}),
+        json!({"name": "unknown-type", "task_type": "not_registered"}),
+        json!({"name": "array-payload", "task_type": "email", "payload": [1, 2]}),
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/tasks/templates", &invalid, &moderator_token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    // Any authenticated user can read templates
+    let response = app
+        .get_auth("/api/v1/tasks/templates", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"][0]["priority"], "high");
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/tasks/templates/weekly-report",
+            &json!({"priority": "low"}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/tasks/templates/weekly-report",
+            &json!({"priority": "normal", "description": "Updated"}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["priority"], "normal");
+    assert_eq!(body["data"]["description"], "Updated");
+    assert_eq!(body["data"]["payload"]["subject"], "Weekly report");
+
+    // Overrides are merged into the template defaults
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks/from-template/weekly-report",
+            &json!({
+                "payload": {"to": "ops@example.com", "options": {"track": null}},
+                "priority": "critical",
+                "metadata": {"team": "ops"},
+                "tags": ["reports"]
+            }),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let task = &body["data"];
+    assert_eq!(task["task_type"], "email");
+    assert_eq!(task["priority"], "critical");
+    let task_id = uuid::Uuid::parse_str(task["id"].as_str().unwrap()).unwrap();
+    let payload = sqlx::query_scalar!("SELECT payload FROM tasks WHERE id = $1", task_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        payload,
+        json!({
+            "to": "ops@example.com",
+            "subject": "Weekly report",
+            "body": "See attached",
+            "options": {"html": true}
+        })
+    );
+    assert_eq!(task["metadata"]["team"], "ops");
+    assert_eq!(task["metadata"]["source"], "template");
+    assert_eq!(task["metadata"]["template"], "weekly-report");
+    assert_eq!(task["tags"], json!(["reports"]));
+    assert_eq!(task["created_by"], user.id.to_string());
+
+    // Without overrides the template defaults are used as-is
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks/from-template/weekly-report",
+            &json!({}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["priority"], "normal");
+    let task_id = uuid::Uuid::parse_str(body["data"]["id"].as_str().unwrap()).unwrap();
+    let payload = sqlx::query_scalar!("SELECT payload FROM tasks WHERE id = $1", task_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(payload["to"], "team@example.com");
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks/from-template/missing",
+            &json!({}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .delete_auth(
+            "/api/v1/tasks/templates/weekly-report",
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth("/api/v1/tasks/templates/weekly-report", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_child_tasks_are_linked_to_parent() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (user, token) = factory.create_authenticated_user("parentowner").await;
+    let (_other, other_token) = factory.create_authenticated_user("otheruser").await;
+
+    use async_trait::async_trait;
+    use starter::Database;
+    use starter::tasks::CreateTaskRequest;
+    use starter::tasks::handlers::TaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use starter::tasks::types::{TaskContext, TaskError, TaskResult};
+    use std::time::Duration;
+
+    // Fans out one email per account in the payload
+    struct FanOutHandler;
+
+    #[async_trait]
+    impl TaskHandler for FanOutHandler {
+        async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
+            let accounts = context.payload["accounts"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            for account in &accounts {
+                context
+                    .enqueue_child(CreateTaskRequest::new(
+                        "email",
+                        json!({"to": account, "subject": "Report", "body": "Attached"}),
+                    ))
+                    .await?;
+            }
+            // Unregistered child task types are rejected
+            assert!(
+                context
+                    .enqueue_child(CreateTaskRequest::new("not_registered", json!({})))
+                    .await
+                    .is_err()
+            );
+            Ok(TaskResult::success(json!({"children": accounts.len()})))
+        }
+    }
+
+    let response = app
+        .post_json(
+            "/api/v1/tasks/types",
+            &json!({"task_type": "fan_out", "description": "Fan-out test tasks"}),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    let parent = processor
+        .create_task(
+            CreateTaskRequest::new(
+                "fan_out",
+                json!({"accounts": ["a@example.com", "b@example.com", "c@example.com"]}),
+            )
+            .with_created_by(user.id),
+        )
+        .await
+        .unwrap();
+
+    processor
+        .register_handler("fan_out".to_string(), FanOutHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let mut status = String::new();
+    for _ in 0..50 {
+        status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", parent.id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if status == "completed" || status == "failed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+    assert_eq!(status, "completed");
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{}/children", parent.id),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let children = body["data"].as_array().unwrap();
+    assert_eq!(children.len(), 3);
+    for child in children {
+        assert_eq!(child["task_type"], "email");
+        assert_eq!(child["parent_task_id"], parent.id.to_string());
+        // Children inherit the parent's owner
+        assert_eq!(child["created_by"], user.id.to_string());
+    }
+
+    // Each child links back to its parent
+    let child_id = children[0]["id"].as_str().unwrap();
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{child_id}"), &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["parent_task_id"], parent.id.to_string());
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks/{}/children", parent.id),
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_can_pause_and_resume_task_queue() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("queueadmin").await;
+    let (_user, user_token) = factory.create_authenticated_user("queueuser").await;
+
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let response = app
+        .post_auth("/api/v1/admin/tasks/queues/email/pause", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/queues/not_a_queue/pause",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .post_auth("/api/v1/admin/tasks/queues/email/pause", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["queue"], "email");
+    assert_eq!(body["data"]["paused"], true);
+    assert!(body["data"]["paused_at"].is_string());
+
+    // Paused queues still accept tasks
+    let task = factory
+        .create_task(
+            "email",
+            json!({"to": "test@example.com", "subject": "Hi", "body": "hello"}),
+        )
+        .await;
+    let task_id = uuid::Uuid::parse_str(task["data"]["id"].as_str().unwrap()).unwrap();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    let status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+
+    let response = app
+        .get_auth("/api/v1/admin/tasks/queues", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let email = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|q| q["queue"] == "email")
+        .unwrap();
+    assert_eq!(email["paused"], true);
+    assert_eq!(email["pending"], 1);
+    assert_eq!(email["running"], 0);
+
+    let response = app
+        .post_auth(
+            "/api/v1/admin/tasks/queues/email/resume",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["paused"], false);
+    assert!(body["data"]["paused_at"].is_null());
+
+    let mut status = String::new();
+    for _ in 0..40 {
+        status = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+            .fetch_one(&app.db_pool)
+            .await
+            .unwrap();
+        if status == "completed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+    assert_eq!(status, "completed");
+
+    let events = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM events WHERE source = 'task-queue-control'"#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(events, 2);
+}
+
+#[tokio::test]
+async fn test_list_tasks_full_text_search() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("searchuser").await;
+    let (_other, other_token) = factory.create_authenticated_user("searchother").await;
+
+    for (to, subject, customer) in [
+        ("bob@example.com", "Weekly report", "acme"),
+        ("alice@example.com", "Weekly digest", "globex"),
+        ("carol@example.com", "Invoice 4821", "acme"),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/tasks",
+                &json!({
+                    "task_type": "email",
+                    "payload": {"to": to, "subject": subject, "body": "Hello"},
+                    "metadata": {"customer": customer}
+                }),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let search = |q: &'static str, token: String| {
+        let app = app.clone();
+        async move {
+            let response = app.get_auth(&format!("/api/v1/tasks?q={q}"), &token).await;
+            assert_status(&response, StatusCode::OK);
+            let body: serde_json::Value = response.json().await.unwrap();
+            body["data"].as_array().unwrap().len()
+        }
+    };
+
+    assert_eq!(search("bob@example.com", token.token.clone()).await, 1);
+    assert_eq!(search("weekly", token.token.clone()).await, 2);
+    assert_eq!(
+        search("%22weekly%20report%22", token.token.clone()).await,
+        1
+    );
+    assert_eq!(search("weekly%20-digest", token.token.clone()).await, 1);
+    assert_eq!(search("4821", token.token.clone()).await, 1);
+    // Metadata values are searchable too
+    assert_eq!(search("acme", token.token.clone()).await, 2);
+    assert_eq!(search("nobody@example.com", token.token.clone()).await, 0);
+    // Blank queries are ignored
+    assert_eq!(search("%20", token.token.clone()).await, 3);
+    // Search never widens a user's visibility
+    assert_eq!(
+        search("bob@example.com", other_token.token.clone()).await,
+        0
+    );
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/tasks?q={}", "a".repeat(201)),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_export_tasks_as_csv_and_ndjson() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_user, token) = factory.create_authenticated_user("exportuser").await;
+    let (_other, other_token) = factory.create_authenticated_user("exportother").await;
+
+    for (to, tags) in [
+        ("bob@example.com", json!(["billing"])),
+        ("alice@example.com", json!([])),
+        ("carol@example.com", json!(["billing", "urgent"])),
+    ] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/tasks",
+                &json!({
+                    "task_type": "email",
+                    "payload": {"to": to, "subject": "Report, \"final\"", "body": "Hello"},
+                    "tags": tags
+                }),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "dave@example.com", "subject": "Hi", "body": "Hello"}}),
+            &other_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // CSV is the default format
+    let response = app.get_auth("/api/v1/tasks/export", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"tasks-"));
+    assert!(disposition.ends_with(".csv\""));
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("id,task_type,status,priority,tags,payload,metadata"));
+    // Oldest first, with the JSON payload quoted as a single field
+    assert!(lines[1].contains(",email,pending,normal,billing,\"{"));
+    assert!(lines[1].contains("\"\"bob@example.com\"\""));
+    assert!(lines[3].contains(",billing;urgent,"));
+    assert!(!csv.contains("dave@example.com"));
+
+    // NDJSON honors the list filters, including search
+    let response = app
+        .get_auth(
+            "/api/v1/tasks/export?format=ndjson&tag=billing&q=bob@example.com",
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+    let body = response.text().await.unwrap();
+    let records: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["payload"]["to"], "bob@example.com");
+    assert_eq!(records[0]["task_type"], "email");
+    assert_eq!(records[0]["status"], "pending");
+    assert_eq!(records[0]["tags"], json!(["billing"]));
+
+    let response = app
+        .get_auth("/api/v1/tasks/export?format=ndjson&limit=2", &token.token)
+        .await;
+    assert_eq!(response.text().await.unwrap().lines().count(), 2);
+
+    let response = app
+        .get_auth("/api/v1/tasks/export?format=xlsx", &token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/v1/tasks/export").await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_task_services_are_injected_into_handlers() {
+    let app = spawn_app().await;
+
+    use async_trait::async_trait;
+    use starter::Database;
+    use starter::tasks::CreateTaskRequest;
+    use starter::tasks::handlers::{EmailTaskHandler, TaskHandler};
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use starter::tasks::services::{
+        EmailMessage, EmailSender, HttpClient, RetryPolicy, TaskServices,
+    };
+    use starter::tasks::types::{TaskContext, TaskError, TaskResult};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct CapturingSender(Arc<Mutex<Vec<EmailMessage>>>);
+
+    #[async_trait]
+    impl EmailSender for CapturingSender {
+        async fn send(&self, message: &EmailMessage) -> Result<(), TaskError> {
+            self.0.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    // Calls the URL in the payload with the shared client
+    struct PingHandler;
+
+    #[async_trait]
+    impl TaskHandler for PingHandler {
+        async fn handle(&self, context: TaskContext) -> Result<TaskResult, TaskError> {
+            let url = context.payload["url"].as_str().unwrap_or_default();
+            let http = context.services().http();
+            let response = http
+                .send(http.post(url).json(&json!({"task_id": context.task_id})))
+                .await
+                .map_err(|e| TaskError::Execution(e.to_string()))?;
+            Ok(TaskResult::success(
+                json!({"status": response.status().as_u16()}),
+            ))
+        }
+    }
+
+    // Upstream that fails twice before succeeding
+    let calls = Arc::new(AtomicUsize::new(0));
+    let upstream = {
+        let calls = calls.clone();
+        axum::Router::new().route(
+            "/ping",
+            axum::routing::post(move || {
+                let calls = calls.clone();
+                async move {
+                    if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                        axum::http::StatusCode::SERVICE_UNAVAILABLE
+                    } else {
+                        axum::http::StatusCode::OK
+                    }
+                }
+            }),
+        )
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream_url = format!("http://{}/ping", listener.local_addr().unwrap());
+    let upstream_handle = tokio::spawn(async move {
+        let _ = axum::serve(listener, upstream).await;
+    });
+
+    for (task_type, description) in [
+        ("email", "Email notification tasks"),
+        ("ping", "Service injection test tasks"),
+    ] {
+        let response = app
+            .post_json(
+                "/api/v1/tasks/types",
+                &json!({"task_type": task_type, "description": description}),
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let sender = CapturingSender::default();
+    let services = TaskServices::default()
+        .with_email_sender(sender.clone())
+        .with_http(HttpClient::new(
+            RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(10),
+            },
+            Duration::from_secs(5),
+        ));
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    )
+    .with_services(services);
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    processor
+        .register_handler("ping".to_string(), PingHandler)
+        .await;
+
+    let email = processor
+        .create_task(CreateTaskRequest::new(
+            "email",
+            json!({"to": "bob@example.com", "subject": "Hi", "body": "Hello"}),
+        ))
+        .await
+        .unwrap();
+    let ping = processor
+        .create_task(CreateTaskRequest::new("ping", json!({"url": upstream_url})))
+        .await
+        .unwrap();
+
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let mut statuses = Vec::new();
+    for _ in 0..50 {
+        statuses = sqlx::query_scalar!(
+            "SELECT status FROM tasks WHERE id = ANY($1) ORDER BY id",
+            &[email.id, ping.id]
+        )
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+        if statuses.iter().all(|s| s == "completed" || s == "failed") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+    upstream_handle.abort();
+    assert_eq!(statuses, vec!["completed", "completed"]);
+
+    // The email went through the injected sender
+    let sent = sender.0.lock().unwrap().clone();
+    assert_eq!(
+        sent,
+        vec![EmailMessage {
+            to: "bob@example.com".to_string(),
+            subject: "Hi".to_string(),
+            body: "Hello".to_string(),
+        }]
+    );
+
+    // The shared client retried the two 503s
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,1685 @@
+use crate::helpers::*;
+use reqwest::StatusCode;
+use serde_json::json;
+
+pub(crate) async fn spawn_tenant_app() -> TestApp {
+    spawn_app_with_config(|config| config.tenancy.enabled = true).await
+}
+
+/// Create a tenant as the default tenant's admin `token` and return its id
+pub(crate) async fn create_tenant(app: &TestApp, token: &str, slug: &str) -> String {
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/tenants",
+            &json!({"slug": slug, "name": format!("Tenant {slug}")}),
+            token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    json["data"]["id"].as_str().unwrap().to_string()
+}
+
+/// Register `username` in `tenant` and return its id
+async fn register_in_tenant(app: &TestApp, tenant: &str, username: &str) -> uuid::Uuid {
+    let response = app
+        .client
+        .post(format!("{}/api/v1/auth/register", app.address))
+        .header("X-Tenant", tenant)
+        .json(&json!({
+            "username": username,
+            "email": format!("{username}@example.com"),
+            "password": "SecurePass123!"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    uuid::Uuid::parse_str(json["data"]["id"].as_str().unwrap()).unwrap()
+}
+
+/// Register `username` in `tenant` as an admin and sign them in
+pub(crate) async fn admin_in_tenant(
+    app: &TestApp,
+    tenant: &str,
+    username: &str,
+) -> (uuid::Uuid, AuthToken) {
+    let id = register_in_tenant(app, tenant, username).await;
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+        .bind(id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let response = login_in_tenant(app, tenant, username).await;
+    (id, app.extract_auth_token(response).await)
+}
+
+async fn login_in_tenant(app: &TestApp, tenant: &str, username: &str) -> reqwest::Response {
+    app.client
+        .post(format!("{}/api/v1/auth/login", app.address))
+        .header("X-Tenant", tenant)
+        .json(&json!({"username": username, "password": "SecurePass123!"}))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_in_tenant(app: &TestApp, tenant: &str, path: &str, token: &str) -> reqwest::Response {
+    app.client
+        .get(format!("{}{}", app.address, path))
+        .header("X-Tenant", tenant)
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_tenant_sign_in_is_scoped_to_the_named_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+
+    create_tenant(&app, &admin_token.token, "acme").await;
+    register_in_tenant(&app, "acme", "acme_user").await;
+
+    let response = login_in_tenant(&app, "acme", "acme_user").await;
+    assert_status(&response, StatusCode::OK);
+    let token = app.extract_auth_token(response).await;
+
+    // Another tenant's users can't sign in to it
+    let response = login_in_tenant(&app, "default", "acme_user").await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    let response = login_in_tenant(&app, "acme", "root_admin").await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // Tokens only work in their user's tenant
+    let response = get_in_tenant(&app, "acme", "/api/v1/auth/me", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = get_in_tenant(&app, "default", "/api/v1/auth/me", &token.token).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let response = login_in_tenant(&app, "missing", "acme_user").await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_tenant_management() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+
+    let tenant_id = create_tenant(&app, &admin_token.token, "acme").await;
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/tenants",
+            &json!({"slug": "acme", "name": "Acme again"}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/tenants",
+            &json!({"slug": "Not A Slug", "name": "Bad"}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // Admins of other tenants can't manage tenants
+    let acme_admin = register_in_tenant(&app, "acme", "acme_admin").await;
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+        .bind(acme_admin)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let response = login_in_tenant(&app, "acme", "acme_admin").await;
+    let acme_token = app.extract_auth_token(response).await;
+    let response = app
+        .get_auth("/api/v1/admin/tenants", &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Deactivated tenants can't be named or signed in to
+    let response = app
+        .patch_json_auth(
+            &format!("/api/v1/admin/tenants/{tenant_id}"),
+            &json!({"is_active": false}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = login_in_tenant(&app, "acme", "acme_admin").await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.get_auth("/api/v1/auth/me", &acme_token.token).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // The default tenant stays active
+    let response = app
+        .patch_json_auth(
+            "/api/v1/admin/tenants/00000000-0000-0000-0000-000000000001",
+            &json!({"is_active": false}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_tenant_users_and_tasks_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let acme_admin = register_in_tenant(&app, "acme", "acme_admin").await;
+    sqlx::query("UPDATE users SET role = 'admin' WHERE id = $1")
+        .bind(acme_admin)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let response = login_in_tenant(&app, "acme", "acme_admin").await;
+    let acme_token = app.extract_auth_token(response).await;
+
+    // Each tenant's admins only see their own users
+    let response = app.get_auth("/api/v1/users", &acme_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let usernames: Vec<&str> = json["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["username"].as_str().unwrap())
+        .collect();
+    assert_eq!(usernames, vec!["acme_admin"]);
+
+    let response = app
+        .get_auth(&format!("/api/v1/users/{}", admin.id), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .get_auth(&format!("/api/v1/users/{acme_admin}"), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Tasks belong to their creator's tenant
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(json["data"]["tenant_id"], json!(acme_tenant_id(&app).await));
+
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_id}"), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_id}"), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app.get_auth("/api/v1/tasks", &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert!(
+        !json.to_string().contains(&task_id),
+        "task of another tenant listed"
+    );
+
+    // Task and queue statistics only count the caller's tenant
+    for (token, expected) in [(&acme_token, 1), (&admin_token, 0)] {
+        let response = app.get_auth("/api/v1/tasks/stats", &token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json["data"]["total"], expected);
+
+        let response = app
+            .get_auth("/api/v1/tasks/queue-stats", &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        let email = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|stats| stats["task_type"] == "email")
+            .unwrap();
+        assert_eq!(email["pending"], expected);
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_service_account_keys_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/service-accounts",
+            &json!({"username": "billing-worker"}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let keys_path = format!(
+        "/api/v1/admin/service-accounts/{}/api-keys",
+        json["data"]["id"].as_str().unwrap()
+    );
+    let response = app
+        .post_json_auth(&keys_path, &json!({"name": "worker"}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let key_id = json["data"]["key"]["id"].as_str().unwrap().to_string();
+
+    // Another tenant's admins can't issue, list or revoke its keys
+    let response = app
+        .post_json_auth(&keys_path, &json!({"name": "stolen"}), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.get_auth(&keys_path, &acme_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .delete_auth(&format!("{keys_path}/{key_id}"), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app.get_auth(&keys_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let keys = json["data"].as_array().unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0]["is_active"], true);
+}
+
+#[tokio::test]
+async fn test_tenant_rbac_changes_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator("root_moderator")
+        .await;
+    let user = factory.create_user("root_user").await;
+
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &json!({"name": "support", "permissions": ["users:read"]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let member_path = format!(
+        "/api/v1/admin/roles/{}/members/{}",
+        json["data"]["id"].as_str().unwrap(),
+        user.id
+    );
+
+    // Another tenant's admins can't change its users' roles or denies
+    let response = app
+        .put_json_auth(&member_path, &json!({}), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .put_json_auth(&member_path, &json!({}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.delete_auth(&member_path, &acme_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let deny = json!({"user_id": user.id, "permission": "monitoring:write"});
+    let response = app
+        .post_json_auth("/api/v1/admin/permission-denies", &deny, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .post_json_auth("/api/v1/admin/permission-denies", &deny, &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let deny_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .delete_auth(
+            &format!("/api/v1/admin/permission-denies/{deny_id}"),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .get_auth("/api/v1/admin/permission-denies", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+
+    // Nor see or decide on its role requests
+    let response = app
+        .post_json_auth(
+            "/api/v1/role-requests",
+            &json!({"user_id": user.id, "role": "moderator"}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let request_id = json["data"]["id"].as_str().unwrap().to_string();
+    let response = app
+        .get_auth("/api/v1/role-requests", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+    for decision in ["approve", "reject"] {
+        let response = app
+            .post_auth(
+                &format!("/api/v1/admin/role-requests/{request_id}/{decision}"),
+                &acme_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    let response = app
+        .post_json_auth(
+            "/api/v1/role-requests",
+            &json!({"user_id": user.id, "role": "moderator"}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .get_auth("/api/v1/role-requests?status=pending", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["id"], json!(request_id));
+}
+
+#[tokio::test]
+async fn test_tenant_custom_roles_are_managed_from_the_default_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    let user = factory.create_user("root_user").await;
+
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+    let acme_user = register_in_tenant(&app, "acme", "acme_user").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/roles",
+            &json!({"name": "support", "permissions": ["users:read"]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let role_path = format!(
+        "/api/v1/admin/roles/{}",
+        json["data"]["id"].as_str().unwrap()
+    );
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/permission-bundles",
+            &json!({"name": "readonly", "permissions": ["monitoring:read"]}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let bundle_id = json["data"]["id"].as_str().unwrap().to_string();
+    let bundle_path = format!("/api/v1/admin/permission-bundles/{bundle_id}");
+
+    // Role and bundle definitions and the policy file span every tenant
+    let role = json!({"name": "acme_support", "permissions": []});
+    let response = app
+        .post_json_auth("/api/v1/admin/roles", &role, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .put_json_auth(&role_path, &json!({"permissions": []}), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    assert_status(
+        &app.delete_auth(&role_path, &acme_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .post_json_auth("/api/v1/admin/permission-bundles", &role, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    assert_status(
+        &app.delete_auth(&bundle_path, &acme_token.token).await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .put_json_auth(
+            &format!(
+                "{bundle_path}/roles/{}",
+                role_path.rsplit('/').next().unwrap()
+            ),
+            &json!({}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    assert_status(
+        &app.get_auth("/api/v1/admin/rbac/policy", &acme_token.token)
+            .await,
+        StatusCode::FORBIDDEN,
+    );
+    let response = app
+        .client
+        .post(format!("{}/api/v1/admin/rbac/policy", app.address))
+        .bearer_auth(&acme_token.token)
+        .body("{}")
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Each tenant's admins assign them to their own users and only see those
+    for (user_id, token) in [(user.id, &admin_token), (acme_user, &acme_token)] {
+        let response = app
+            .put_json_auth(
+                &format!("{role_path}/members/{user_id}"),
+                &json!({}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let response = app
+            .put_json_auth(
+                &format!("{bundle_path}/users/{user_id}"),
+                &json!({}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app.get_auth(&role_path, &acme_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["member_count"], 1);
+    let response = app
+        .get_auth(&format!("{role_path}/members"), &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"][0]["username"], "acme_user");
+    let response = app
+        .get_auth("/api/v1/admin/permission-bundles", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["user_count"], 1);
+    let response = app
+        .get_auth(&format!("{bundle_path}/users"), &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"][0]["username"], "acme_user");
+    let response = app
+        .get_auth(&format!("{role_path}/members"), &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["username"], "root_user");
+
+    // The RBAC audit log only holds the tenant's own changes
+    let response = app
+        .get_auth("/api/v1/admin/audit/rbac", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(
+        entries
+            .iter()
+            .all(|entry| entry["actor_id"] == json!(acme_admin))
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_metrics_are_isolated() {
+    use chrono::{SecondsFormat, Utc};
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/metrics",
+            &json!({
+                "name": "tenant_latency",
+                "metric_type": "gauge",
+                "value": 42.0,
+                "labels": {"host": "acme-1"}
+            }),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    starter::monitoring::retention::rollup_metrics(
+        conn.as_mut(),
+        &app.config.monitoring,
+        Utc::now() + chrono::Duration::seconds(1),
+    )
+    .await
+    .unwrap();
+
+    let range = format!(
+        "start_time={}&end_time={}",
+        (Utc::now() - chrono::Duration::hours(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+        (Utc::now() + chrono::Duration::minutes(1)).to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    let points = |token: String, resolution: &'static str| {
+        let app = app.clone();
+        let range = range.clone();
+        async move {
+            let response = app
+                .get_auth(
+                    &format!(
+                        "/api/v1/monitoring/metrics/series?name=tenant_latency&resolution={resolution}&{range}"
+                    ),
+                    &token,
+                )
+                .await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["points"].as_array().unwrap().len()
+        }
+    };
+    assert_eq!(points(acme_token.token.clone(), "raw").await, 1);
+    assert_eq!(points(acme_token.token.clone(), "rollup").await, 1);
+
+    // Another tenant's staff don't see the metric at any resolution
+    assert_eq!(points(admin_token.token.clone(), "raw").await, 0);
+    assert_eq!(points(admin_token.token.clone(), "rollup").await, 0);
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/metrics/query?name=tenant_latency&{range}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["series"], json!([]));
+
+    for (path, body) in [
+        (
+            "/api/v1/monitoring/grafana/metrics",
+            json!({"metric": "tenant_lat"}),
+        ),
+        ("/api/v1/monitoring/grafana/tag-keys", json!({})),
+        (
+            "/api/v1/monitoring/grafana/tag-values",
+            json!({"key": "host"}),
+        ),
+    ] {
+        let response = app.post_json_auth(path, &body, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert!(
+            !json.to_string().contains("tenant_latency") && !json.to_string().contains("acme-1"),
+            "{path} listed another tenant's metric: {json}"
+        );
+    }
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/grafana/tag-values",
+            &json!({"key": "host"}),
+            &acme_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json, json!([{"text": "acme-1"}]));
+}
+
+#[tokio::test]
+async fn test_tenant_user_admin_views_are_isolated() {
+    use starter::users::models::PurgeMode;
+    use starter::users::purge::purge_user;
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    // Online users only list the admin's own tenant
+    let online = |token: String| {
+        let app = app.clone();
+        async move {
+            let response = app.get_auth("/api/v1/admin/users/online", &token).await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["users"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|user| user["username"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(online(admin_token.token.clone()).await, ["root_admin"]);
+    assert_eq!(online(acme_token.token.clone()).await, ["acme_admin"]);
+
+    // Imports are only found in the tenant they were uploaded to
+    let response = app
+        .post_file_auth(
+            "/api/v1/admin/users/import",
+            "file",
+            "text/csv",
+            b"username,email,password\nacme_bob,bob@example.com,SecurePass123!\n",
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let import_path = format!(
+        "/api/v1/admin/users/imports/{}",
+        json["data"]["id"].as_str().unwrap()
+    );
+    let response = app.get_auth(&import_path, &acme_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let response = app.get_auth(&import_path, &admin_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Purge records stay with the purged account's tenant
+    let gone = register_in_tenant(&app, "acme", "acme_gone").await;
+    sqlx::query("UPDATE users SET is_active = false, deleted_at = NOW() WHERE id = $1")
+        .bind(gone)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    purge_user(conn.as_mut(), &app.storage, gone, PurgeMode::Delete)
+        .await
+        .unwrap()
+        .unwrap();
+    let purges = |token: String| {
+        let app = app.clone();
+        async move {
+            let response = app.get_auth("/api/v1/admin/users/purges", &token).await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"].as_array().unwrap().len()
+        }
+    };
+    assert_eq!(purges(acme_token.token.clone()).await, 1);
+    assert_eq!(purges(admin_token.token.clone()).await, 0);
+}
+
+#[tokio::test]
+async fn test_tenant_event_stream_is_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let mut stream = app
+        .client
+        .get(format!(
+            "{}/api/v1/monitoring/events/stream?source=app-stream",
+            app.address
+        ))
+        .bearer_auth(&admin_token.token)
+        .send()
+        .await
+        .unwrap();
+    assert_status(&stream, StatusCode::OK);
+
+    // The other tenant's event is stored first, so it would arrive first
+    for (message, token) in [("acme secret", &acme_token), ("own event", &admin_token)] {
+        let event = json!({
+            "event_type": "log",
+            "source": "app-stream",
+            "message": message,
+            "level": "info"
+        });
+        let response = app
+            .post_json_auth("/api/v1/monitoring/events", &event, &token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let mut received = String::new();
+    while !received.contains("own event") {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(10), stream.chunk())
+            .await
+            .expect("no streamed event within 10 seconds")
+            .unwrap()
+            .expect("stream ended");
+        received.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    assert!(!received.contains("acme secret"), "{received}");
+}
+
+#[tokio::test]
+async fn test_tenant_templates_alerts_and_incidents_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let create = |path: &'static str, body: serde_json::Value, token: String| {
+        let app = app.clone();
+        async move {
+            let response = app.post_json_auth(path, &body, &token).await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["id"].as_str().unwrap().to_string()
+        }
+    };
+    create(
+        "/api/v1/tasks/templates",
+        json!({"name": "nightly", "task_type": "email"}),
+        acme_token.token.clone(),
+    )
+    .await;
+    let alert_id = create(
+        "/api/v1/monitoring/alerts",
+        json!({"name": "Acme CPU", "query": "cpu_usage > 80", "threshold_value": 80.0}),
+        acme_token.token.clone(),
+    )
+    .await;
+    let incident_id = create(
+        "/api/v1/monitoring/incidents",
+        json!({"title": "Acme outage", "severity": "high"}),
+        acme_token.token.clone(),
+    )
+    .await;
+
+    // Listings of the other tenant leave them out
+    for path in [
+        "/api/v1/tasks/templates",
+        "/api/v1/monitoring/alerts",
+        "/api/v1/monitoring/incidents",
+    ] {
+        let response = app.get_auth(path, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json["data"], json!([]), "{path}");
+    }
+
+    // Template names are only unique within a tenant
+    create(
+        "/api/v1/tasks/templates",
+        json!({"name": "nightly", "task_type": "email", "priority": "high"}),
+        admin_token.token.clone(),
+    )
+    .await;
+    let response = app
+        .get_auth("/api/v1/tasks/templates/nightly", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["priority"], "normal");
+
+    let incident = format!("/api/v1/monitoring/incidents/{incident_id}");
+    for path in [incident.clone(), format!("{incident}/timeline")] {
+        let response = app.get_auth(&path, &admin_token.token).await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    let response = app
+        .put_json_auth(&incident, &json!({"status": "closed"}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .post_json_auth(
+            &format!("{incident}/timeline/notes"),
+            &json!({"body": "peeking"}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/monitoring/alerts/{alert_id}/channels"),
+            &json!({"channel_ids": []}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app.get_auth(&incident, &acme_token.token).await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenant_monitoring_stats_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let event = json!({
+        "event_type": "log",
+        "source": "acme-billing",
+        "message": "invoice sent",
+        "level": "info"
+    });
+    let response = app
+        .post_json_auth("/api/v1/monitoring/events", &event, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let metric = json!({"name": "acme_invoices", "metric_type": "counter", "value": 1.0});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/metrics", &metric, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let stats = |token: String| {
+        let app = app.clone();
+        async move {
+            let response = app.get_auth("/api/v1/monitoring/stats", &token).await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"].clone()
+        }
+    };
+    let names = |stats: &serde_json::Value, list: &str, field: &str| -> Vec<String> {
+        stats[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item[field].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let acme = stats(acme_token.token.clone()).await;
+    assert_eq!(acme["total_events"], 1);
+    assert_eq!(acme["total_metrics"], 1);
+    assert!(names(&acme, "metric_cardinality", "name").contains(&"acme_invoices".to_string()));
+    assert!(names(&acme, "ingestion_usage", "key").contains(&"acme-billing".to_string()));
+
+    let own = stats(admin_token.token.clone()).await;
+    assert_eq!(own["total_events"], 0);
+    assert_eq!(own["total_metrics"], 0);
+    assert!(!names(&own, "metric_cardinality", "name").contains(&"acme_invoices".to_string()));
+    assert!(!names(&own, "ingestion_usage", "key").contains(&"acme-billing".to_string()));
+}
+
+#[tokio::test]
+async fn test_tenant_oncall_escalation_and_channels_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let create = |path: &'static str, body: serde_json::Value, token: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .post_json_auth(&format!("/api/v1/monitoring/{path}"), &body, &token)
+                .await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["id"].as_str().unwrap().to_string()
+        }
+    };
+    let channel = json!({"name": "Ops", "kind": "email", "target": "ops@example.com"});
+    let channel_id = create(
+        "notification-channels",
+        channel.clone(),
+        admin_token.token.clone(),
+    )
+    .await;
+    let schedule_id = create(
+        "oncall/schedules",
+        json!({"name": "Platform", "rotation_period_hours": 24, "members": [admin.id]}),
+        admin_token.token.clone(),
+    )
+    .await;
+    let policy_id = create(
+        "escalation-policies",
+        json!({
+            "name": "Primary",
+            "steps": [{"notify_schedule_id": schedule_id, "delay_minutes": 0}]
+        }),
+        admin_token.token.clone(),
+    )
+    .await;
+
+    // Another tenant neither sees nor changes them
+    for path in [
+        "notification-channels",
+        "alert-routing-rules",
+        "escalation-policies",
+        "oncall/schedules",
+        "oncall/current",
+    ] {
+        let response = app
+            .get_auth(&format!("/api/v1/monitoring/{path}"), &acme_token.token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(
+            json["data"],
+            json!([]),
+            "{path} listed another tenant's data"
+        );
+    }
+    for path in [
+        format!("escalation-policies/{policy_id}"),
+        format!("oncall/schedules/{schedule_id}"),
+        format!("oncall/current?schedule_id={schedule_id}"),
+    ] {
+        let response = app
+            .get_auth(&format!("/api/v1/monitoring/{path}"), &acme_token.token)
+            .await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    for path in [
+        format!("notification-channels/{channel_id}"),
+        format!("escalation-policies/{policy_id}"),
+        format!("oncall/schedules/{schedule_id}"),
+    ] {
+        let response = app
+            .delete_auth(&format!("/api/v1/monitoring/{path}"), &acme_token.token)
+            .await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/monitoring/oncall/schedules/{schedule_id}/overrides"),
+            &json!({
+                "user_id": acme_admin,
+                "starts_at": "2030-01-01T00:00:00Z",
+                "ends_at": "2030-01-02T00:00:00Z"
+            }),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Nor points its own at them or at the tenant's users
+    for (path, body) in [
+        (
+            "oncall/schedules",
+            json!({"name": "Acme", "rotation_period_hours": 24, "members": [admin.id]}),
+        ),
+        (
+            "escalation-policies",
+            json!({
+                "name": "Acme",
+                "steps": [{"notify_schedule_id": schedule_id, "delay_minutes": 0}]
+            }),
+        ),
+        (
+            "escalation-policies",
+            json!({
+                "name": "Acme",
+                "steps": [{"notify_user_id": admin.id, "delay_minutes": 0}]
+            }),
+        ),
+        (
+            "alert-routing-rules",
+            json!({"name": "Acme", "channel_ids": [channel_id]}),
+        ),
+        (
+            "alert-routing-rules",
+            json!({"name": "Acme", "escalation_policy_id": policy_id}),
+        ),
+        (
+            "incidents",
+            json!({"title": "Outage", "severity": "high", "escalation_policy_id": policy_id}),
+        ),
+    ] {
+        let response = app
+            .post_json_auth(
+                &format!("/api/v1/monitoring/{path}"),
+                &body,
+                &acme_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    // Names only have to be unique within a tenant
+    create("notification-channels", channel, acme_token.token.clone()).await;
+
+    // Legal documents apply to every tenant
+    let response = app.get_auth("/api/v1/admin/legal", &acme_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .post_json_auth(
+            "/api/v1/admin/legal",
+            &json!({"kind": "terms", "title": "Terms of Service", "content": "Be nice."}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_tenant_deactivation_only_reassigns_within_the_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    let root_user = factory.create_user("root_user").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+    let leaver = register_in_tenant(&app, "acme", "acme_leaver").await;
+
+    // Work can't be handed to another tenant's user, who looks missing
+    let path = format!("/api/v1/users/{leaver}/status");
+    let response = app
+        .put_json_auth(
+            &path,
+            &json!({"is_active": false, "reassign_to": root_user.id}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .delete_json_auth(
+            &format!("/api/v1/users/{leaver}"),
+            &json!({"reassign_to": root_user.id}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app
+        .put_json_auth(
+            &path,
+            &json!({"is_active": false, "reassign_to": acme_admin}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["deactivation"]["reassigned_to"],
+        acme_admin.to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_uptime_checks_are_isolated() {
+    use starter::monitoring::uptime;
+    use starter::tasks::services::TaskServices;
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let check = json!({"name": "api", "url": format!("{}/api/v1/health", app.address)});
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/uptime-checks",
+            &check,
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let check_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .get_auth("/api/v1/monitoring/uptime-checks", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+    let path = format!("/api/v1/monitoring/uptime-checks/{check_id}");
+    let response = app.delete_auth(&path, &admin_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Check names are only unique within a tenant
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/uptime-checks",
+            &check,
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Results are recorded in each check's tenant
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let run = uptime::run_due_checks(conn.as_mut(), &TaskServices::default(), chrono::Utc::now())
+        .await
+        .unwrap();
+    assert_eq!(run.checked, 2);
+    for token in [&acme_token.token, &admin_token.token] {
+        let response = app
+            .get_auth("/api/v1/monitoring/metrics?name=uptime_check_up", token)
+            .await;
+        let json: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    }
+
+    let response = app.delete_auth(&path, &acme_token.token).await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenant_status_pages_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/uptime-checks",
+            &json!({"name": "api", "url": "http://localhost/health"}),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let check_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Checks of another tenant can't be listed
+    let page = json!({"slug": "acme", "title": "Acme", "check_ids": [check_id]});
+    let response = app
+        .post_json_auth("/api/v1/monitoring/status-pages", &page, &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/status-pages",
+            &json!({"slug": "acme", "title": "Acme"}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let page_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .get_auth("/api/v1/monitoring/status-pages", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+    let path = format!("/api/v1/monitoring/status-pages/{page_id}");
+    let response = app
+        .put_json_auth(&path, &json!({"title": "Taken over"}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.delete_auth(&path, &admin_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let response = app.get("/api/v1/status/acme").await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["title"], "Acme");
+    let response = app.delete_auth(&path, &acme_token.token).await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenant_task_queues_are_controlled_from_the_default_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    for action in ["pause", "resume"] {
+        let path = format!("/api/v1/admin/tasks/queues/email/{action}");
+        let response = app.post_auth(&path, &acme_token.token).await;
+        assert_status(&response, StatusCode::FORBIDDEN);
+        let response = app.post_auth(&path, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_circuit_breakers_are_managed_from_the_default_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .get_auth("/api/v1/admin/tasks/circuit-breakers", &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    for action in ["trip", "reset"] {
+        let path = format!("/api/v1/admin/tasks/circuit-breakers/email/{action}");
+        let response = app.post_auth(&path, &acme_token.token).await;
+        assert_status(&response, StatusCode::FORBIDDEN);
+        let response = app.post_auth(&path, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_task_log_events_belong_to_the_task_tenant() {
+    use starter::Database;
+    use starter::tasks::handlers::EmailTaskHandler;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use std::time::Duration;
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let events_path = format!(
+        "/api/v1/monitoring/events?tags=task_id:{}",
+        json["data"]["id"].as_str().unwrap()
+    );
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    processor
+        .register_handler("email".to_string(), EmailTaskHandler)
+        .await;
+    let handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let mut events = json!([]);
+    for _ in 0..40 {
+        let response = app.get_auth(&events_path, &acme_token.token).await;
+        let json: serde_json::Value = response.json().await.unwrap();
+        events = json["data"].clone();
+        if events != json!([]) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    handle.abort();
+    assert_eq!(events[0]["source"], "task:email");
+
+    let response = app.get_auth(&events_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+}
+
+#[tokio::test]
+async fn test_tenant_lease_expiry_events_belong_to_the_task_tenant() {
+    use starter::tasks::leases::reap_expired_leases;
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "a@example.com", "subject": "Hi", "body": "Hello"}}),
+            &acme_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+    sqlx::query(
+        "UPDATE tasks SET status = 'running', claimed_by = 'dead-worker',
+             lease_expires_at = NOW() - INTERVAL '1 second'
+         WHERE id = $1::uuid",
+    )
+    .bind(&task_id)
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let mut conn = app.db().await;
+    let reaped = reap_expired_leases(conn.as_mut()).await.unwrap();
+    assert_eq!(reaped.len(), 1);
+
+    let events_path = format!("/api/v1/monitoring/events?tags=task_id:{task_id}");
+    let response = app.get_auth(&events_path, &acme_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"][0]["source"], "task-lease-reaper");
+    let response = app.get_auth(&events_path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+}
+
+#[tokio::test]
+async fn test_tenant_account_recovery_is_scoped_to_the_named_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    let tenant_id = create_tenant(&app, &admin_token.token, "acme").await;
+
+    let response = app
+        .client
+        .post(format!("{}/api/v1/auth/register", app.address))
+        .header("X-Tenant", "acme")
+        .json(&json!({
+            "username": "acme_user",
+            "email": "acme_user@example.com",
+            "password": "SecurePass123!",
+            "generate_recovery_codes": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = response.json().await.unwrap();
+    let code = json["data"]["recovery_codes"]["codes"][0].clone();
+
+    let recover = |tenant: Option<&'static str>| {
+        let app = app.clone();
+        let code = code.clone();
+        async move {
+            let mut request = app
+                .client
+                .post(format!("{}/api/v1/auth/recover", app.address))
+                .json(&json!({
+                    "username": "acme_user",
+                    "recovery_code": code,
+                    "new_password": "RecoveredPass123!"
+                }));
+            if let Some(tenant) = tenant {
+                request = request.header("X-Tenant", tenant);
+            }
+            request.send().await.unwrap()
+        }
+    };
+    let response = recover(Some("default")).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    let tenant_path = format!("/api/v1/admin/tenants/{tenant_id}");
+    let response = app
+        .patch_json_auth(
+            &tenant_path,
+            &json!({"is_active": false}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = recover(None).await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // The rejected attempts left the code unused
+    let response = app
+        .patch_json_auth(
+            &tenant_path,
+            &json!({"is_active": true}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = recover(Some("acme")).await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenant_orgs_are_isolated() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    let (owner, owner_token) = factory.create_authenticated_user("root_owner").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let create_org = |token: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .post_json_auth(
+                    "/api/v1/orgs",
+                    &json!({"name": "Core", "slug": "core"}),
+                    &token,
+                )
+                .await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"]["id"].as_str().unwrap().to_string()
+        }
+    };
+    let org_id = create_org(owner_token.token.clone()).await;
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &json!({"task_type": "email", "payload": {"to": "a@example.com"}, "org_id": org_id}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Another tenant's admin neither sees nor acts as owner of the organization
+    let response = app.get_auth("/api/v1/orgs", &acme_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"], json!([]));
+    let org_path = format!("/api/v1/orgs/{org_id}");
+    for path in [org_path.clone(), format!("{org_path}/members")] {
+        let response = app.get_auth(&path, &acme_token.token).await;
+        assert_status(&response, StatusCode::NOT_FOUND);
+    }
+    let response = app.delete_auth(&org_path, &acme_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .client
+        .get(format!("{}/api/v1/tasks", app.address))
+        .bearer_auth(&acme_token.token)
+        .header("X-Org-Id", &org_id)
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    // Slugs are only unique within a tenant, and members come from the tenant
+    let acme_org_id = create_org(acme_token.token.clone()).await;
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/orgs/{acme_org_id}/members/{}", owner.id),
+            &json!({"role": "member"}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .post_json_auth(
+            &format!("/api/v1/orgs/{acme_org_id}/invitations"),
+            &json!({"email": owner.email}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app.get_auth(&org_path, &owner_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let (tasks,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tasks WHERE org_id = $1::uuid")
+        .bind(&org_id)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(tasks, 1);
+}
+
+#[tokio::test]
+async fn test_tenant_audit_retention_is_managed_from_the_default_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let path = "/api/v1/admin/audit/retention/task";
+    let body = json!({"retention_days": 1});
+    let response = app.put_json_auth(path, &body, &acme_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app.put_json_auth(path, &body, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.delete_auth(path, &acme_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app.delete_auth(path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tenant_shared_monitoring_and_profile_settings_are_managed_from_the_default_tenant() {
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let settings = [
+        (
+            "/api/v1/admin/monitoring/retention/http_requests_total".to_string(),
+            json!({"raw_retention_days": 1, "rollup_retention_days": 30}),
+        ),
+        (
+            "/api/v1/admin/monitoring/event-limits/web".to_string(),
+            json!({"events_per_minute": 10, "sample_ratio": 1.0}),
+        ),
+        (
+            format!("/api/v1/admin/monitoring/quotas/user/{}", admin.id),
+            json!({"daily_events": 0}),
+        ),
+        (
+            "/api/v1/admin/users/profile-fields/team".to_string(),
+            json!({"label": "Team", "field_type": "text"}),
+        ),
+    ];
+    for (path, body) in &settings {
+        let response = app.put_json_auth(path, body, &acme_token.token).await;
+        assert_status(&response, StatusCode::FORBIDDEN);
+        let response = app.put_json_auth(path, body, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let response = app.delete_auth(path, &acme_token.token).await;
+        assert_status(&response, StatusCode::FORBIDDEN);
+        let response = app.delete_auth(path, &admin_token.token).await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    let response = app
+        .get_auth("/api/v1/admin/monitoring/quotas", &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_tenant_recording_rules_use_their_tenant_metrics() {
+    use chrono::{DurationRound, TimeDelta, Utc};
+    use starter::core::config::AppConfig;
+    use starter::monitoring::recording;
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("root_admin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acme_admin").await;
+
+    let rule = json!({
+        "name": "latency_ms:avg1m",
+        "expression": "avg(latency_ms)",
+        "interval_secs": 60
+    });
+    let mut rule_ids = Vec::new();
+    for token in [&acme_token.token, &admin_token.token] {
+        let response = app
+            .post_json_auth("/api/v1/monitoring/recording-rules", &rule, token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        rule_ids.push(json["data"]["id"].as_str().unwrap().to_string());
+    }
+    let response = app
+        .get_auth("/api/v1/monitoring/recording-rules", &acme_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    assert_eq!(json["data"][0]["id"], rule_ids[0].as_str());
+    let path = format!("/api/v1/monitoring/recording-rules/{}", rule_ids[1]);
+    let response = app.delete_auth(&path, &acme_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let base = Utc::now().duration_trunc(TimeDelta::minutes(1)).unwrap() - TimeDelta::minutes(5);
+    for (token, value) in [(&acme_token.token, 10.0), (&admin_token.token, 500.0)] {
+        let response = app
+            .post_json_auth(
+                "/api/v1/monitoring/metrics",
+                &json!({
+                    "name": "latency_ms",
+                    "metric_type": "gauge",
+                    "value": value,
+                    "recorded_at": base + TimeDelta::seconds(10)
+                }),
+                token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    sqlx::query("UPDATE recording_rules SET last_window_end = $1")
+        .bind(base)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    recording::evaluate_rules(
+        conn.as_mut(),
+        &AppConfig::default().monitoring,
+        base + TimeDelta::seconds(100),
+    )
+    .await
+    .unwrap();
+
+    for (token, value) in [(&acme_token.token, 10.0), (&admin_token.token, 500.0)] {
+        let response = app
+            .get_auth("/api/v1/monitoring/metrics?name=latency_ms:avg1m", token)
+            .await;
+        let json: serde_json::Value = response.json().await.unwrap();
+        let values: Vec<f64> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|metric| metric["value"].as_f64().unwrap())
+            .collect();
+        assert_eq!(values, [value]);
+    }
+}
+
+async fn acme_tenant_id(app: &TestApp) -> String {
+    let (id,): (uuid::Uuid,) = sqlx::query_as("SELECT id FROM tenants WHERE slug = 'acme'")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    id.to_string()
+}
@@ -162,6 +162,52 @@ async fn test_create_user_as_non_admin() {
     assert_status(&response, StatusCode::FORBIDDEN);
 }
 
+#[tokio::test]
+async fn test_create_admin_requires_approval() {
+    let new_admin = serde_json::json!({
+        "username": "direct_admin",
+        "email": "direct_admin@example.com",
+        "password": "SecurePassword123!",
+        "role": "admin"
+    });
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, token) = factory.create_authenticated_admin("approval_admin").await;
+    let response = app
+        .post_json_auth("/api/v1/users", &new_admin, &token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Moderators don't need approval
+    let response = app
+        .post_json_auth(
+            "/api/v1/users",
+            &serde_json::json!({
+                "username": "direct_moderator",
+                "email": "direct_moderator@example.com",
+                "password": "SecurePassword123!",
+                "role": "moderator"
+            }),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let app = spawn_app_with_config(|config| {
+        config.auth.admin_role_requires_approval = false;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, token) = factory.create_authenticated_admin("approval_admin").await;
+    let response = app
+        .post_json_auth("/api/v1/users", &new_admin, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "admin");
+}
+
 #[tokio::test]
 async fn test_change_own_password() {
     let app = spawn_app().await;
@@ -224,7 +270,210 @@ async fn test_delete_own_account() {
 
     assert_status(&response, StatusCode::OK);
     let json: serde_json::Value = response.json().await.unwrap();
-    assert_eq!(json["data"], "Account deleted successfully");
+    assert_eq!(json["data"]["status"], "scheduled");
+}
+
+/// Token from the cancel link in the latest deletion email sent to `to`
+fn deletion_cancel_token(app: &TestApp, to: &str) -> String {
+    let emails = app.sent_emails.lock().unwrap();
+    let email = emails
+        .iter()
+        .rev()
+        .find(|email| email.to == to && email.subject.contains("scheduled for deletion"))
+        .unwrap();
+    let (_, rest) = email.body.split_once("token=").unwrap();
+    rest.split_whitespace().next().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_account_deletion_grace_period() {
+    use starter::Database;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use starter::tasks::services::TaskServices;
+    use starter::users::deletion::AccountErasureTaskHandler;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (user, token) = factory.create_authenticated_user("grace_delete").await;
+    let delete_data = serde_json::json!({
+        "password": "SecurePass123!",
+        "confirmation": "DELETE"
+    });
+    let login_data = serde_json::json!({
+        "username": "grace_delete",
+        "password": "SecurePass123!"
+    });
+
+    let response = app
+        .delete_json_auth("/api/v1/users/me", &delete_data, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "scheduled");
+    let erase_after: chrono::DateTime<chrono::Utc> =
+        serde_json::from_value(json["data"]["erase_after"].clone()).unwrap();
+    let grace = erase_after - chrono::Utc::now();
+    assert!(grace > chrono::Duration::days(13) && grace <= chrono::Duration::days(14));
+
+    // Signed out and unable to sign in during the grace period
+    assert_status(
+        &app.get_auth("/api/v1/auth/me", &token.token).await,
+        StatusCode::UNAUTHORIZED,
+    );
+    assert_status(
+        &app.post_json("/api/v1/auth/login", &login_data).await,
+        StatusCode::UNAUTHORIZED,
+    );
+
+    // The emailed link restores the account, once
+    let cancel_token = deletion_cancel_token(&app, &user.email);
+    let response = app
+        .post_json(
+            "/api/v1/account-deletion/cancel",
+            &serde_json::json!({ "token": "wrong" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .post_json(
+            "/api/v1/account-deletion/cancel",
+            &serde_json::json!({ "token": cancel_token }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "cancelled");
+    let response = app
+        .post_json(
+            "/api/v1/account-deletion/cancel",
+            &serde_json::json!({ "token": cancel_token }),
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    assert!(
+        app.sent_emails
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|email| email.to == user.email && email.subject.contains("cancelled"))
+    );
+    let erasure_tasks =
+        sqlx::query_scalar!(r#"SELECT status FROM tasks WHERE task_type = 'user_account_erasure'"#)
+            .fetch_all(&app.db_pool)
+            .await
+            .unwrap();
+    assert_eq!(erasure_tasks, vec!["cancelled".to_string()]);
+
+    // Delete again and let the grace period run out
+    let response = app.post_json("/api/v1/auth/login", &login_data).await;
+    assert_status(&response, StatusCode::OK);
+    let token = app.extract_auth_token(response).await;
+    let response = app
+        .delete_json_auth("/api/v1/users/me", &delete_data, &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let deletion_id = uuid::Uuid::parse_str(json["data"]["id"].as_str().unwrap()).unwrap();
+    let cancel_token = deletion_cancel_token(&app, &user.email);
+    sqlx::query!(
+        r#"
+        UPDATE tasks SET scheduled_at = NOW() - INTERVAL '1 minute'
+        WHERE task_type = 'user_account_erasure' AND status = 'pending'
+        "#
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(100),
+            ..Default::default()
+        },
+    )
+    .with_services(
+        TaskServices::from_config(&app.config)
+            .with_storage(Arc::new(app.storage.clone()))
+            .with_email_sender(CapturingEmailSender(app.sent_emails.clone())),
+    );
+    processor
+        .register_handler(
+            "user_account_erasure".to_string(),
+            AccountErasureTaskHandler,
+        )
+        .await;
+    let processor_handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    // The deletion is marked completed before the account is erased, so wait
+    // for the erasure task itself to finish
+    let mut task_status = String::new();
+    for _ in 0..50 {
+        task_status = sqlx::query_scalar!(
+            r#"
+            SELECT status FROM tasks
+            WHERE task_type = 'user_account_erasure' AND status <> 'cancelled'
+            "#
+        )
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+        if task_status == "completed" || task_status == "failed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    processor_handle.abort();
+    assert_eq!(task_status, "completed");
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM account_deletions WHERE id = $1",
+        deletion_id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(status, "completed");
+
+    let remaining = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM users WHERE id = $1"#,
+        user.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, 0);
+    assert!(
+        app.sent_emails
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|email| email.to == user.email && email.subject.contains("has been deleted"))
+    );
+
+    // Nothing left to cancel, and the address is no longer kept
+    let response = app
+        .post_json(
+            "/api/v1/account-deletion/cancel",
+            &serde_json::json!({ "token": cancel_token }),
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let kept_emails = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM account_deletions WHERE email IS NOT NULL"#
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(kept_emails, 0);
 }
 
 #[tokio::test]
@@ -361,7 +610,7 @@ async fn test_delete_user_as_admin() {
 
     assert_status(&response, StatusCode::OK);
     let json: serde_json::Value = response.json().await.unwrap();
-    assert_eq!(json["data"], "User account deleted successfully");
+    assert_eq!(json["message"], "User account deleted successfully");
 }
 
 #[tokio::test]
@@ -642,7 +891,7 @@ async fn test_concurrent_account_deletion() {
 
     assert_status(&response, StatusCode::OK);
     let json: serde_json::Value = response.json().await.unwrap();
-    assert_eq!(json["data"], "Account deleted successfully");
+    assert_eq!(json["data"]["status"], "scheduled");
 
     // Verify that the token is no longer valid (sessions should be invalidated)
     let me_response = app.get_auth("/api/v1/auth/me", &token.token).await;
@@ -667,7 +916,7 @@ async fn test_concurrent_account_deletion() {
 
     assert_status(&response, StatusCode::OK);
     let json: serde_json::Value = response.json().await.unwrap();
-    assert_eq!(json["data"], "User account deleted successfully");
+    assert_eq!(json["message"], "User account deleted successfully");
 
     // Verify the user is marked as inactive - should return NOT_FOUND
     let get_response = app
@@ -820,7 +1069,7 @@ async fn test_admin_cannot_delete_self_via_any_endpoint() {
 
     assert_status(&response, StatusCode::OK);
     let json: serde_json::Value = response.json().await.unwrap();
-    assert_eq!(json["data"], "Account deleted successfully");
+    assert_eq!(json["data"]["status"], "scheduled");
 
     // Verify the token is no longer valid after self-deletion
     let me_response_final = app.get_auth("/api/v1/auth/me", &admin_token.token).await;
@@ -958,3 +1207,1677 @@ async fn test_transaction_rollback_on_failures() {
         .await;
     assert_status(&get_response, StatusCode::NOT_FOUND);
 }
+
+#[tokio::test]
+async fn test_upload_own_avatar() {
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use std::io::Cursor;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let unique_username = format!("avatar_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (user, token) = factory.create_authenticated_user(&unique_username).await;
+
+    let mut jpeg = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(RgbImage::new(400, 300))
+        .write_to(&mut jpeg, ImageFormat::Jpeg)
+        .unwrap();
+    let jpeg = jpeg.into_inner();
+
+    let response = app
+        .put_file_auth(
+            "/api/v1/users/me/avatar",
+            "avatar",
+            "image/jpeg",
+            &jpeg,
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let avatar_url = json["data"]["avatar_url"].as_str().unwrap().to_string();
+    assert!(avatar_url.starts_with(&format!("/api/v1/files/avatars/{}.png?v=", user.id)));
+
+    let response = app.get_auth("/api/v1/users/me/profile", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["avatar_url"], avatar_url.as_str());
+
+    // Stored as a square PNG, served without authentication
+    let response = app.get(&avatar_url).await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let stored = response.bytes().await.unwrap();
+    let stored = image::load_from_memory_with_format(&stored, ImageFormat::Png).unwrap();
+    assert_eq!((stored.width(), stored.height()), (256, 256));
+
+    // Unsupported types, mismatched data and missing fields are refused
+    let response = app
+        .put_file_auth(
+            "/api/v1/users/me/avatar",
+            "avatar",
+            "image/gif",
+            b"GIF89a",
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let response = app
+        .put_file_auth(
+            "/api/v1/users/me/avatar",
+            "avatar",
+            "image/png",
+            &jpeg,
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .put_file_auth(
+            "/api/v1/users/me/avatar",
+            "picture",
+            "image/jpeg",
+            &jpeg,
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .put_file_auth(
+            "/api/v1/users/me/avatar",
+            "avatar",
+            "image/png",
+            &vec![0u8; 3 * 1024 * 1024],
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app.get("/api/v1/files/avatars/missing.png").await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_custom_profile_fields() {
+    use serde_json::json;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+    let (_admin, admin_token) = factory
+        .create_authenticated_admin(&format!("pfadmin_{suffix}"))
+        .await;
+    let (user, token) = factory
+        .create_authenticated_user(&format!("pfuser_{suffix}"))
+        .await;
+
+    for (name, field) in [
+        (
+            "job_title",
+            json!({"label": "Job title", "field_type": "text", "required": true}),
+        ),
+        (
+            "team",
+            json!({"label": "Team", "field_type": "select", "options": ["red", "blue"]}),
+        ),
+        (
+            "badge",
+            json!({"label": "Badge", "field_type": "number", "visibility": "staff"}),
+        ),
+    ] {
+        let response = app
+            .put_json_auth(
+                &format!("/api/v1/admin/users/profile-fields/{name}"),
+                &field,
+                &admin_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+
+    // Only admins manage the schema; users don't see staff fields
+    let response = app
+        .put_json_auth(
+            "/api/v1/admin/users/profile-fields/other",
+            &json!({"label": "Other", "field_type": "text"}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .get_auth("/api/v1/users/profile-fields", &token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let names: Vec<&str> = json["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["job_title", "team"]);
+
+    // Values are checked against the schema
+    for profile in [
+        json!({"team": "red"}),
+        json!({"job_title": "Engineer", "team": "green"}),
+        json!({"job_title": 7}),
+        json!({"job_title": "Engineer", "badge": 1}),
+        json!({"job_title": "Engineer", "unknown": 1}),
+    ] {
+        let response = app
+            .put_json_auth(
+                "/api/v1/users/me/profile",
+                &json!({"profile": profile}),
+                &token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &json!({"profile": {"job_title": "Engineer", "team": "red"}}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["profile"],
+        json!({"job_title": "Engineer", "team": "red"})
+    );
+
+    // Admins set staff fields, which stay hidden from the user
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/profile", user.id),
+            &json!({"profile": {"badge": 42}}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["profile"]["badge"], 42);
+
+    let response = app.get_auth("/api/v1/users/me/profile", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["profile"],
+        json!({"job_title": "Engineer", "team": "red"})
+    );
+
+    // Deleted fields drop out of profiles
+    let response = app
+        .delete_auth(
+            "/api/v1/admin/users/profile-fields/team",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth(&format!("/api/v1/users/{}", user.id), &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["profile"],
+        json!({"job_title": "Engineer", "badge": 42})
+    );
+
+    // Updates without `profile` leave it alone
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &json!({"email": format!("pfuser_{suffix}@example.org")}),
+            &token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["profile"], json!({"job_title": "Engineer"}));
+}
+
+#[tokio::test]
+async fn test_data_export() {
+    use starter::Database;
+    use starter::tasks::processor::{ProcessorConfig, TaskProcessor};
+    use starter::tasks::services::TaskServices;
+    use starter::users::export::{DataExportTaskHandler, expire_exports};
+    use std::io::{Cursor, Read};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let unique_username = format!("export_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let (user, token) = factory.create_authenticated_user(&unique_username).await;
+    let (_other, other_token) = factory.create_authenticated_user("export_other").await;
+
+    let task_id = sqlx::query_scalar!(
+        "INSERT INTO tasks (task_type, status, created_by) VALUES ('email', 'completed', $1) RETURNING id",
+        user.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO events (event_type, source, message, tags) VALUES ('log', 'test', 'task log', $1)",
+        serde_json::json!({ "task_id": task_id })
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let response = app.post_auth("/api/v1/users/me/export", &token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+    assert!(json["data"]["download_url"].is_null());
+    let export_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // One export at a time
+    let response = app.post_auth("/api/v1/users/me/export", &token.token).await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    let processor = TaskProcessor::new(
+        Database {
+            pool: app.db_pool.clone(),
+        },
+        ProcessorConfig {
+            poll_interval: Duration::from_millis(100),
+            ..Default::default()
+        },
+    )
+    .with_services(
+        TaskServices::from_config(&app.config).with_storage(Arc::new(app.storage.clone())),
+    );
+    processor
+        .register_handler("user_data_export".to_string(), DataExportTaskHandler)
+        .await;
+    let processor_handle = {
+        let processor = processor.clone();
+        tokio::spawn(async move {
+            let _ = processor.start_worker().await;
+        })
+    };
+
+    let path = format!("/api/v1/users/me/exports/{export_id}");
+    let mut export = serde_json::json!(null);
+    for _ in 0..50 {
+        let response = app.get_auth(&path, &token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        export = json["data"].clone();
+        if export["status"] != "pending" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    processor_handle.abort();
+    assert_eq!(export["status"], "ready");
+
+    // Owner only
+    let response = app.get_auth(&path, &other_token.token).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app.get_auth("/api/v1/users/me/exports", &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"].as_array().unwrap().len(), 1);
+
+    // The link downloads without authentication; the archive is not a public file
+    let download_url = export["download_url"].as_str().unwrap().to_string();
+    let response = app.get(&download_url).await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/zip");
+    let archive = response.bytes().await.unwrap();
+    let response = app
+        .get(&format!("/api/v1/exports/{export_id}/download?token=wrong"))
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let response = app
+        .get(&format!("/api/v1/files/private/exports/{export_id}.zip"))
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive.to_vec())).unwrap();
+    let mut read = |name: &str| {
+        let mut contents = String::new();
+        zip.by_name(name)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    };
+    let profile: serde_json::Value = serde_json::from_str(&read("profile.json")).unwrap();
+    assert_eq!(profile["username"], unique_username.as_str());
+    let sessions = read("sessions.json");
+    assert!(!sessions.contains(&token.token));
+    let sessions: serde_json::Value = serde_json::from_str(&sessions).unwrap();
+    assert!(!sessions.as_array().unwrap().is_empty());
+    let tasks: serde_json::Value = serde_json::from_str(&read("tasks.json")).unwrap();
+    let task_ids: Vec<&str> = tasks
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert!(task_ids.contains(&task_id.to_string().as_str()));
+    let events: serde_json::Value = serde_json::from_str(&read("events.json")).unwrap();
+    assert!(
+        events
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["message"] == "task log")
+    );
+
+    // Expired links stop working and the archive is deleted
+    sqlx::query!(
+        "UPDATE data_exports SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1",
+        uuid::Uuid::parse_str(&export_id).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+    let response = app.get_auth(&path, &token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "expired");
+    assert!(json["data"]["download_url"].is_null());
+    let response = app.get(&download_url).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let expired = expire_exports(conn.as_mut(), &app.storage).await.unwrap();
+    assert_eq!(expired, 1);
+}
+
+#[tokio::test]
+async fn test_purge_deleted_users() {
+    use starter::users::models::PurgeMode;
+    use starter::users::purge::{purge_deleted_users, purge_user};
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("purge_admin").await;
+    let (gone, gone_token) = factory.create_authenticated_user("purge_gone").await;
+    let (recent, recent_token) = factory.create_authenticated_user("purge_recent").await;
+    let suspended = factory.create_user("purge_suspended").await;
+
+    sqlx::query!(
+        "INSERT INTO tasks (task_type, status, created_by) VALUES ('email', 'completed', $1)",
+        gone.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let delete_data = serde_json::json!({
+        "password": "SecurePass123!",
+        "confirmation": "DELETE"
+    });
+    for token in [&gone_token.token, &recent_token.token] {
+        let response = app
+            .delete_json_auth("/api/v1/users/me", &delete_data, token)
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/status", suspended.id),
+            &serde_json::json!({ "is_active": false }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Only the account deleted before the retention period is purged
+    sqlx::query!(
+        "UPDATE users SET deleted_at = NOW() - INTERVAL '31 days' WHERE id = $1",
+        gone.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let purged = purge_deleted_users(
+        conn.as_mut(),
+        &app.storage,
+        &app.config.auth,
+        chrono::Utc::now(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(purged.len(), 1);
+    assert_eq!(purged[0].user_id, gone.id);
+    assert_eq!(purged[0].mode, PurgeMode::Delete);
+    assert_eq!(purged[0].tasks_deleted, 1);
+    assert!(purged[0].sessions_deleted >= 1);
+
+    let remaining = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM users WHERE id = ANY($1)"#,
+        &[gone.id, recent.id, suspended.id][..]
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, 2);
+    let tasks = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM tasks WHERE created_by = $1"#,
+        gone.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(tasks, 0);
+
+    // Anonymizing keeps the row without personal data
+    let purge = purge_user(conn.as_mut(), &app.storage, recent.id, PurgeMode::Anonymize)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(purge.mode, PurgeMode::Anonymize);
+    let user = sqlx::query!(
+        "SELECT username, email, is_active, deleted_at FROM users WHERE id = $1",
+        recent.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert!(user.username.starts_with("deleted_"));
+    assert!(user.email.ends_with("@deleted.invalid"));
+    assert!(!user.is_active);
+    assert!(user.deleted_at.is_none());
+
+    // Suspended accounts were never deleted and are left alone
+    let purge = purge_user(conn.as_mut(), &app.storage, suspended.id, PurgeMode::Delete)
+        .await
+        .unwrap();
+    assert!(purge.is_none());
+
+    let response = app
+        .get_auth("/api/v1/admin/users/purges", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let purges = json["data"].as_array().unwrap();
+    assert_eq!(purges.len(), 2);
+    assert_eq!(purges[1]["user_id"], gone.id.to_string());
+    assert_eq!(purges[1]["mode"], "delete");
+}
+
+#[tokio::test]
+async fn test_temporary_suspension() {
+    use starter::users::suspension::expire_suspensions;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("suspend_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("suspend_user").await;
+    let now = chrono::Utc::now();
+    let status_path = format!("/api/v1/users/{}/status", user.id);
+
+    // Only future suspensions of inactive accounts are accepted
+    for body in [
+        serde_json::json!({ "is_active": true, "suspended_until": now + chrono::Duration::hours(1) }),
+        serde_json::json!({ "is_active": false, "suspended_until": now - chrono::Duration::hours(1) }),
+    ] {
+        let response = app
+            .put_json_auth(&status_path, &body, &admin_token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let until = now + chrono::Duration::hours(1);
+    let response = app
+        .put_json_auth(
+            &status_path,
+            &serde_json::json!({ "is_active": false, "reason": "Cooling off", "suspended_until": until }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["is_active"], false);
+    assert!(json["data"]["suspended_until"].is_string());
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+
+    // Nothing happens before the suspension ends
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let reactivated = expire_suspensions(conn.as_mut(), now).await.unwrap();
+    assert!(reactivated.is_empty());
+    let reactivated = expire_suspensions(conn.as_mut(), until + chrono::Duration::minutes(1))
+        .await
+        .unwrap();
+    assert_eq!(reactivated, vec![user.id]);
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &serde_json::json!({ "username": "suspend_user", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Both transitions are in the activity trail
+    let response = app
+        .get_auth(
+            &format!(
+                "/api/v1/admin/users/{}/activity?action=status_changed",
+                user.id
+            ),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["actor_id"], serde_json::Value::Null);
+    assert_eq!(entries[0]["details"]["is_active"], true);
+    assert_eq!(entries[0]["details"]["reason"], "Suspension ended");
+    assert_eq!(entries[1]["actor_id"], admin.id.to_string());
+    assert_eq!(entries[1]["details"]["is_active"], false);
+    assert!(entries[1]["details"]["suspended_until"].is_string());
+
+    // Reactivating by hand clears a pending suspension
+    let response = app
+        .put_json_auth(
+            &status_path,
+            &serde_json::json!({ "is_active": false, "suspended_until": until }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .put_json_auth(
+            &status_path,
+            &serde_json::json!({ "is_active": true }),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["is_active"], true);
+    assert_eq!(json["data"]["suspended_until"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_temporary_role() {
+    use starter::users::role_expiry::expire_roles;
+
+    // Grants admin directly, without a role request
+    let app = spawn_app_with_config(|config| {
+        config.auth.admin_role_requires_approval = false;
+    })
+    .await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("elevate_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("elevate_user").await;
+    let now = chrono::Utc::now();
+    let role_path = format!("/api/v1/users/{}/role", user.id);
+
+    // Expiry must be in the future and the role must change
+    for body in [
+        serde_json::json!({ "role": "moderator", "expires_at": now - chrono::Duration::hours(1) }),
+        serde_json::json!({ "role": "user", "expires_at": now + chrono::Duration::hours(1) }),
+    ] {
+        let response = app
+            .put_json_auth(&role_path, &body, &admin_token.token)
+            .await;
+        assert_status(&response, StatusCode::BAD_REQUEST);
+    }
+
+    let response = app
+        .put_json_auth(
+            &role_path,
+            &serde_json::json!({
+                "role": "moderator",
+                "reason": "On call",
+                "expires_at": now + chrono::Duration::hours(1),
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "moderator");
+    assert!(json["data"]["role_expires_at"].is_string());
+
+    let response = app.get_auth("/api/v1/users", &user_token.token).await;
+    assert_status(&response, StatusCode::OK);
+
+    // A second temporary role still reverts to the original one
+    let until = now + chrono::Duration::hours(2);
+    let response = app
+        .put_json_auth(
+            &role_path,
+            &serde_json::json!({ "role": "admin", "expires_at": until }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let reverted = expire_roles(conn.as_mut(), now).await.unwrap();
+    assert!(reverted.is_empty());
+    let reverted = expire_roles(conn.as_mut(), until + chrono::Duration::minutes(1))
+        .await
+        .unwrap();
+    assert_eq!(reverted, vec![user.id]);
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "user");
+    assert_eq!(json["data"]["role_expires_at"], serde_json::Value::Null);
+    let response = app.get_auth("/api/v1/users", &user_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // The grants and the reversion are in the RBAC audit log
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/audit/rbac?target_id={}", user.id),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["action"], "user_role_expired");
+    assert_eq!(entries[0]["actor_id"], serde_json::Value::Null);
+    assert_eq!(entries[0]["before"]["role"], "admin");
+    assert_eq!(entries[0]["after"]["role"], "user");
+    assert_eq!(entries[1]["action"], "user_role_changed");
+    assert_eq!(entries[1]["after"]["revert_to"], "user");
+    assert_eq!(entries[2]["reason"], "On call");
+
+    // A permanent change cancels a pending reversion
+    let response = app
+        .put_json_auth(
+            &role_path,
+            &serde_json::json!({ "role": "moderator", "expires_at": until }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .put_json_auth(
+            &role_path,
+            &serde_json::json!({ "role": "moderator" }),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role_expires_at"], serde_json::Value::Null);
+    let reverted = expire_roles(conn.as_mut(), until + chrono::Duration::minutes(1))
+        .await
+        .unwrap();
+    assert!(reverted.is_empty());
+}
+
+#[tokio::test]
+async fn test_user_activity() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (admin, admin_token) = factory.create_authenticated_admin("activity_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("activity_user").await;
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &serde_json::json!({ "username": "activity_renamed" }),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/tasks",
+            &serde_json::json!({ "task_type": "email", "payload": {"to": "a@example.com"} }),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/role", user.id),
+            &serde_json::json!({ "role": "moderator", "reason": "Promoted" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth("/api/v1/users/me/activity", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["role_changed", "task_created", "profile_updated"]);
+    assert_eq!(entries[0]["actor_id"], admin.id.to_string());
+    assert_eq!(entries[0]["details"]["from"], "user");
+    assert_eq!(entries[0]["details"]["to"], "moderator");
+    assert_eq!(entries[2]["actor_id"], user.id.to_string());
+    assert_eq!(
+        entries[2]["details"]["fields"],
+        serde_json::json!(["username"])
+    );
+
+    // Admins read the same trail, filtered by action
+    let response = app
+        .get_auth(
+            &format!(
+                "/api/v1/admin/users/{}/activity?action=task_created",
+                user.id
+            ),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let entries = json["data"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["details"]["task_type"], "email");
+
+    // The user is a moderator now, which is still not enough
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/users/{}/activity", admin.id),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_username_changes() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("rename_admin").await;
+    let (alice, alice_token) = factory.create_authenticated_user("rename_alice").await;
+    let (_bob, bob_token) = factory.create_authenticated_user("rename_bob").await;
+
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &serde_json::json!({ "username": "rename_alice2" }),
+            &alice_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["username"], "rename_alice2");
+
+    // Resubmitting the current name isn't a rename
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &serde_json::json!({ "username": "rename_alice2" }),
+            &alice_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // A second rename has to wait for the cooldown
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &serde_json::json!({ "username": "rename_alice3" }),
+            &alice_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    // The name Alice gave up is reserved for her, whatever the case
+    let response = app
+        .put_json_auth(
+            "/api/v1/users/me/profile",
+            &serde_json::json!({ "username": "Rename_Alice" }),
+            &bob_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::CONFLICT);
+
+    // Admins aren't held to the cooldown and can give it back to her
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/profile", alice.id),
+            &serde_json::json!({ "username": "rename_alice" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let history = sqlx::query_scalar!(
+        "SELECT username FROM username_history WHERE user_id = $1 ORDER BY changed_at",
+        alice.id
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(history, ["rename_alice", "rename_alice2"]);
+
+    // Without a cooldown users rename freely, including back to their own old names
+    let app = spawn_app_with_config(|config| config.auth.username_change_cooldown_days = 0).await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_carol, carol_token) = factory.create_authenticated_user("rename_carol").await;
+    for username in ["rename_carol2", "rename_carol3", "rename_carol"] {
+        let response = app
+            .put_json_auth(
+                "/api/v1/users/me/profile",
+                &serde_json::json!({ "username": username }),
+                &carol_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_online_users() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("online_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("online_user").await;
+    factory.create_user("online_idle").await;
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    // Sightings show up before they are stored
+    let response = app
+        .get_auth("/api/v1/admin/users/online", &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["window_minutes"], 5);
+    assert_eq!(json["data"]["total"], 2);
+    assert_eq!(json["data"]["by_role"]["admin"], 1);
+    assert_eq!(json["data"]["by_role"]["user"], 1);
+    assert_eq!(json["data"]["users"][0]["id"], admin.id.to_string());
+
+    let response = app
+        .get_auth("/api/v1/admin/users/online?limit=1", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["total"], 2);
+    assert_eq!(json["data"]["users"].as_array().unwrap().len(), 1);
+
+    // One batched write stores everyone's last request
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    let stored = starter::users::presence::store_last_seen(conn.as_mut(), &app.presence.take())
+        .await
+        .unwrap();
+    assert_eq!(stored, 2);
+
+    let response = app
+        .get_auth(
+            "/api/v1/users?search=online_&sort=last_seen_at",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let items = json["data"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert!(items[0]["last_seen_at"].is_string());
+    assert!(items[1]["last_seen_at"].is_string());
+    assert_eq!(items[2]["username"], "online_idle");
+    assert_eq!(items[2]["last_seen_at"], serde_json::Value::Null);
+
+    let response = app
+        .get_auth("/api/v1/admin/users/online", &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Deactivated users aren't online, even with a recent request
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/status", user.id),
+            &serde_json::json!({ "is_active": false }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .get_auth("/api/v1/admin/users/online", &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["total"], 1);
+}
+
+#[tokio::test]
+async fn test_search_users() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_moderator, token) = factory
+        .create_authenticated_moderator("search_moderator")
+        .await;
+    for name in ["search_carol", "search_alice", "search_bob"] {
+        factory.create_user(name).await;
+    }
+    let admin = factory
+        .create_user_with_role("search_admin_dave", starter::rbac::UserRole::Admin)
+        .await;
+    let bob = factory.create_user("search_x_bob").await;
+    sqlx::query!("UPDATE users SET is_active = false WHERE id = $1", bob.id)
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let list = |query: &'static str| {
+        let app = app.clone();
+        let token = token.token.clone();
+        async move {
+            let response = app
+                .get_auth(&format!("/api/v1/users?{query}"), &token)
+                .await;
+            assert_status(&response, StatusCode::OK);
+            let json: serde_json::Value = response.json().await.unwrap();
+            json["data"].clone()
+        }
+    };
+    let usernames = |data: &serde_json::Value| -> Vec<String> {
+        data["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|user| user["username"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Substring search is case-insensitive and sorted alphabetically
+    let data = list("search=SEARCH_&is_active=true&sort=username").await;
+    assert_eq!(
+        usernames(&data),
+        [
+            "search_admin_dave",
+            "search_alice",
+            "search_bob",
+            "search_carol",
+            "search_moderator"
+        ]
+    );
+    assert_eq!(data["pagination"]["total_count"], 5);
+
+    // Deactivated accounts are included unless filtered out
+    let data = list("search=bob&sort=username&order=desc").await;
+    assert_eq!(usernames(&data), ["search_x_bob", "search_bob"]);
+    let data = list("search=bob&is_active=false").await;
+    assert_eq!(usernames(&data), ["search_x_bob"]);
+
+    let data = list("search=search_&role=admin").await;
+    assert_eq!(data["items"][0]["id"], admin.id.to_string());
+    assert_eq!(data["pagination"]["total_count"], 1);
+
+    // The underscore is matched literally, not as a wildcard
+    let data = list("search=search_x").await;
+    assert_eq!(usernames(&data), ["search_x_bob"]);
+    let data = list("search=searchx").await;
+    assert_eq!(data["pagination"]["total_count"], 0);
+
+    let data = list("search=search_&sort=username&limit=2&offset=2").await;
+    assert_eq!(usernames(&data), ["search_bob", "search_carol"]);
+    let pagination = &data["pagination"];
+    assert_eq!(pagination["total_count"], 6);
+    assert_eq!(pagination["page_count"], 3);
+    assert_eq!(pagination["current_page"], 2);
+    assert_eq!(pagination["has_next"], true);
+    assert_eq!(pagination["has_prev"], true);
+
+    let response = app
+        .get_auth("/api/v1/users?sort=password_hash", &token.token)
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_import_users() {
+    use starter::users::import::process_import;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("import_admin").await;
+    let (_user, user_token) = factory.create_authenticated_user("import_user").await;
+    factory.create_user("import_taken").await;
+
+    let csv = "username,email,role,password\n\
+               import_alice,alice@example.com,moderator,SecurePass123!\n\
+               import_bob,not-an-email,,SecurePass123!\n\
+               import_taken,taken2@example.com,,SecurePass123!\n\
+               import_carol,carol@example.com,owner,SecurePass123!\n\
+               import_alice,alice2@example.com,,SecurePass123!\n\
+               import_dave,dave@example.com,,SecurePass123!\n";
+
+    let response = app
+        .post_file_auth(
+            "/api/v1/admin/users/import",
+            "file",
+            "text/csv",
+            csv.as_bytes(),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    // Files without the required columns are rejected up front
+    let response = app
+        .post_file_auth(
+            "/api/v1/admin/users/import",
+            "file",
+            "text/csv",
+            b"name,mail\nalice,alice@example.com\n",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .post_file_auth(
+            "/api/v1/admin/users/import",
+            "file",
+            "text/csv",
+            csv.as_bytes(),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "pending");
+    assert_eq!(json["data"]["password_policy"], "provided");
+    assert_eq!(json["data"]["total_rows"], 6);
+    let import_id: uuid::Uuid = json["data"]["id"].as_str().unwrap().parse().unwrap();
+
+    let mut conn = app.db_pool.acquire().await.unwrap();
+    process_import(conn.as_mut(), import_id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    // A processed import is not run again
+    assert!(
+        process_import(conn.as_mut(), import_id, true)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/users/imports/{import_id}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let report = &json["data"];
+    assert_eq!(report["status"], "completed");
+    assert_eq!(report["created_count"], 2);
+    assert_eq!(report["failed_count"], 4);
+    let rows = report["results"].as_array().unwrap();
+    let outcomes: Vec<(u64, &str)> = rows
+        .iter()
+        .map(|row| {
+            (
+                row["line"].as_u64().unwrap(),
+                row["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            (2, "created"),
+            (3, "failed"),
+            (4, "failed"),
+            (5, "failed"),
+            (6, "failed"),
+            (7, "created")
+        ]
+    );
+    assert!(rows[1]["error"].as_str().unwrap().contains("email"));
+    assert_eq!(rows[2]["error"], "Username already exists");
+
+    // Imported accounts log in with their password and get their role
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &serde_json::json!({ "username": "import_alice", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["user"]["role"], "moderator");
+
+    // Locked accounts exist but can't log in until their password is reset
+    let response = app
+        .post_file_auth(
+            "/api/v1/admin/users/import?password_policy=locked",
+            "file",
+            "text/csv",
+            b"email,username\nerin@example.com,import_erin\n",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let import_id: uuid::Uuid = json["data"]["id"].as_str().unwrap().parse().unwrap();
+    let import = process_import(conn.as_mut(), import_id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(import.created_count, 1);
+
+    // Admin rows need an approved role request unless the check is turned off
+    let csv = b"username,email,role,password\n\
+                import_frank,frank@example.com,admin,SecurePass123!\n";
+    for (admin_requires_approval, created, failed) in [(true, 0, 1), (false, 1, 0)] {
+        let response = app
+            .post_file_auth(
+                "/api/v1/admin/users/import",
+                "file",
+                "text/csv",
+                csv,
+                &admin_token.token,
+            )
+            .await;
+        assert_status(&response, StatusCode::OK);
+        let json: serde_json::Value = response.json().await.unwrap();
+        let import_id: uuid::Uuid = json["data"]["id"].as_str().unwrap().parse().unwrap();
+        let import = process_import(conn.as_mut(), import_id, admin_requires_approval)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(import.created_count, created);
+        assert_eq!(import.failed_count, failed);
+    }
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            &serde_json::json!({ "username": "import_erin", "password": "SecurePass123!" }),
+        )
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_import_users_gzip_compressed() {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (_admin, admin_token) = factory.create_authenticated_admin("gzimport_admin").await;
+
+    let csv = "username,email,password\n\
+               gzimport_alice,gzalice@example.com,SecurePass123!\n\
+               gzimport_bob,gzbob@example.com,SecurePass123!\n";
+    let body = format!(
+        "--boundary\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"users.csv\"\r\n\
+         Content-Type: text/csv\r\n\r\n\
+         {csv}\r\n\
+         --boundary--\r\n"
+    );
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes()).unwrap();
+
+    let response = app
+        .client
+        .post(format!("{}/api/v1/admin/users/import", app.address))
+        .header("Authorization", format!("Bearer {}", admin_token.token))
+        .header("Content-Type", "multipart/form-data; boundary=boundary")
+        .header("Content-Encoding", "gzip")
+        .body(encoder.finish().unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["total_rows"], 2);
+}
+
+#[tokio::test]
+async fn test_user_quotas() {
+    let app = spawn_app_with_config(|config| {
+        config.tasks.quotas.user.max_per_day = 5;
+        config.monitoring.event_daily_quotas.user = 1;
+    })
+    .await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (admin, admin_token) = factory.create_authenticated_admin("quota_admin").await;
+    let (user, user_token) = factory.create_authenticated_user("quota_user").await;
+    let path = format!("/api/v1/admin/users/{}/quotas", user.id);
+
+    // Without their own limits, users get their role's defaults
+    let response = app.get_auth(&path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["role"], "user");
+    assert_eq!(json["data"]["tasks_per_day"]["limit"], 5);
+    assert_eq!(json["data"]["tasks_per_day"]["overridden"], false);
+    assert_eq!(json["data"]["events_per_day"]["limit"], 1);
+    assert_eq!(json["data"]["api_keys"]["limit"], 10);
+    assert_eq!(json["data"]["api_keys"]["used"], 0);
+
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "max_tasks_per_day": -1 }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "max_tasks_per_day": 1, "max_events_per_day": 2 }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["tasks_per_day"]["limit"], 1);
+    assert_eq!(json["data"]["tasks_per_day"]["role_default"], 5);
+    assert_eq!(json["data"]["tasks_per_day"]["overridden"], true);
+    assert_eq!(json["data"]["events_per_day"]["limit"], 2);
+    assert_eq!(json["data"]["api_keys"]["overridden"], false);
+    assert_eq!(json["data"]["updated_by"], admin.id.to_string());
+
+    // Task creation and event ingestion enforce the user's own limits
+    let task = serde_json::json!({
+        "task_type": "email",
+        "payload": { "to": "test@example.com", "subject": "Test", "body": "Test body" }
+    });
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::TOO_MANY_REQUESTS);
+
+    let event =
+        serde_json::json!({ "event_type": "log", "source": "app-user-quota", "message": "hi" });
+    for expected in [
+        StatusCode::OK,
+        StatusCode::OK,
+        StatusCode::TOO_MANY_REQUESTS,
+    ] {
+        let response = app
+            .post_json_auth("/api/v1/monitoring/events", &event, &user_token.token)
+            .await;
+        assert_status(&response, expected);
+    }
+
+    let response = app.get_auth(&path, &admin_token.token).await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["tasks_per_day"]["used"], 1);
+    assert_eq!(json["data"]["events_per_day"]["used"], 2);
+
+    // Leaving limits out goes back to the role's defaults
+    let response = app
+        .put_json_auth(&path, &serde_json::json!({}), &admin_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["tasks_per_day"]["limit"], 5);
+    assert_eq!(json["data"]["events_per_day"]["limit"], 1);
+    assert_eq!(json["data"]["events_per_day"]["overridden"], false);
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &user_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app.get_auth(&path, &user_token.token).await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/users/{}/quotas", uuid::Uuid::new_v4()),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_export_users_csv() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (admin, admin_token) = factory.create_authenticated_admin("export_admin").await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator("export_moderator")
+        .await;
+    let alice = factory.create_user("export_alice").await;
+    let bob = factory.create_user("export_bob").await;
+
+    let response = app
+        .put_json_auth(
+            &format!("/api/v1/users/{}/status", bob.id),
+            &serde_json::json!({ "is_active": false }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+
+    let response = app
+        .get_auth(
+            "/api/v1/admin/users/export?search=export_&sort=username",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"users-"));
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("id,username,email,role,is_active,email_verified,created_at"));
+    assert!(lines[1].starts_with(&format!("{},export_admin,", admin.id)));
+    assert!(lines[2].starts_with(&format!("{},export_alice,", alice.id)));
+    assert!(lines[3].contains(",export_bob,") && lines[3].contains(",user,false,"));
+    assert!(!csv.contains("password"));
+
+    // The list filters apply
+    let response = app
+        .get_auth(
+            "/api/v1/admin/users/export?search=export_&role=user&is_active=true",
+            &admin_token.token,
+        )
+        .await;
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with(&alice.id.to_string()));
+
+    let tomorrow = (chrono::Utc::now() + chrono::Duration::days(1)).format("%Y-%m-%dT%H:%M:%SZ");
+    let response = app
+        .get_auth(
+            &format!("/api/v1/admin/users/export?created_after={tomorrow}"),
+            &admin_token.token,
+        )
+        .await;
+    assert_eq!(response.text().await.unwrap().lines().count(), 1);
+
+    let response = app
+        .get_auth(
+            "/api/v1/admin/users/export?sort=password",
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .get_auth("/api/v1/admin/users/export", &moderator_token.token)
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_deactivation_cascade() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new_with_task_types(app.clone()).await;
+    let (_admin, admin_token) = factory.create_authenticated_admin("cascade_admin").await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator("cascade_moderator")
+        .await;
+    let (leaver, leaver_token) = factory.create_authenticated_user("cascade_leaver").await;
+    let successor = factory.create_user("cascade_successor").await;
+
+    // Work the account leaves behind: a queued task, an API key, an incident and an org
+    let task = serde_json::json!({
+        "task_type": "email",
+        "payload": { "to": "test@example.com", "subject": "Test", "body": "Test body" }
+    });
+    let response = app
+        .post_json_auth("/api/v1/tasks", &task, &leaver_token.token)
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let task_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO api_keys (name, key_hash, key_prefix, created_by)
+        VALUES ('ci', 'cascade-key-hash', 'sk_test', $1)
+        "#,
+        leaver.id
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/monitoring/incidents",
+            &serde_json::json!({
+                "title": "Queue backlog",
+                "severity": "high",
+                "assigned_to": leaver.id
+            }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let incident_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/orgs",
+            &serde_json::json!({ "name": "Leaver Org", "slug": "leaver-org" }),
+            &leaver_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    let org_id = json["data"]["id"].as_str().unwrap().to_string();
+
+    // Only admins hand work over, and only to another active account
+    let path = format!("/api/v1/users/{}/status", leaver.id);
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "is_active": false, "reassign_to": successor.id }),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "is_active": false, "reassign_to": leaver.id }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "is_active": false, "reassign_to": successor.id }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["is_active"], false);
+    let summary = &json["data"]["deactivation"];
+    assert_eq!(summary["sessions_revoked"], 1);
+    assert_eq!(summary["api_keys_revoked"], 1);
+    assert_eq!(summary["tasks_cancelled"], 1);
+    assert_eq!(summary["reassigned_to"], successor.id.to_string());
+    assert_eq!(summary["incidents_reassigned"], 1);
+    assert_eq!(summary["orgs_reassigned"], 1);
+
+    let response = app
+        .get_auth("/api/v1/users/me/profile", &leaver_token.token)
+        .await;
+    assert_status(&response, StatusCode::UNAUTHORIZED);
+    let response = app
+        .get_auth(&format!("/api/v1/tasks/{task_id}"), &admin_token.token)
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["status"], "cancelled");
+    let response = app
+        .get_auth(
+            &format!("/api/v1/monitoring/incidents/{incident_id}"),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["assigned_to"], successor.id.to_string());
+    let owner_role = sqlx::query_scalar!(
+        "SELECT role FROM org_members WHERE org_id = $1 AND user_id = $2",
+        uuid::Uuid::parse_str(&org_id).unwrap(),
+        successor.id
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(owner_role, "owner");
+
+    // Reactivating reports no cascade; deleting runs it again with nothing left
+    let response = app
+        .put_json_auth(
+            &path,
+            &serde_json::json!({ "is_active": true }),
+            &admin_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["deactivation"], serde_json::Value::Null);
+
+    let response = app
+        .delete_json_auth(
+            &format!("/api/v1/users/{}", leaver.id),
+            &serde_json::json!({ "reason": "Left the company" }),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["api_keys_revoked"], 0);
+    assert_eq!(json["data"]["tasks_cancelled"], 0);
+    assert_eq!(json["data"]["reassigned_to"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_internal_metadata_is_staff_only() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+    let (user, user_token) = factory.create_authenticated_user("annotated_user").await;
+    let (_moderator, moderator_token) = factory
+        .create_authenticated_moderator("annotating_mod")
+        .await;
+    let path = format!("/api/v1/users/{}/internal-metadata", user.id);
+
+    let response = app
+        .patch_json_auth(
+            &path,
+            &serde_json::json!({"internal_metadata": {"vip": true}}),
+            &user_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::FORBIDDEN);
+
+    let response = app
+        .patch_json_auth(
+            &path,
+            &serde_json::json!({"internal_metadata": {"vip": true, "fraud_review": "open"}}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["internal_metadata"]["vip"], true);
+
+    let response = app
+        .patch_json_auth(
+            &path,
+            &serde_json::json!({"internal_metadata": {"fraud_review": null}}),
+            &moderator_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        json["data"]["internal_metadata"],
+        serde_json::json!({"vip": true})
+    );
+
+    // Staff see it wherever they look at the user
+    let response = app
+        .get_auth(
+            &format!("/api/v1/users/{}", user.id),
+            &moderator_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["internal_metadata"]["vip"], true);
+    let response = app
+        .get_auth(
+            "/api/v1/users?search=annotated_user",
+            &moderator_token.token,
+        )
+        .await;
+    let json: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(json["data"]["items"][0]["internal_metadata"]["vip"], true);
+
+    // The user never does
+    for path in [
+        "/api/v1/users/me/profile".to_string(),
+        format!("/api/v1/users/{}", user.id),
+        "/api/v1/auth/me".to_string(),
+        "/api/v1/users/me/activity".to_string(),
+    ] {
+        let response = app.get_auth(&path, &user_token.token).await;
+        assert_status(&response, StatusCode::OK);
+        let body = response.text().await.unwrap();
+        assert!(!body.contains("internal_metadata"), "{path}: {body}");
+        assert!(!body.contains("vip"), "{path}: {body}");
+    }
+
+    let response = app
+        .patch_json_auth(
+            &path,
+            &serde_json::json!({"internal_metadata": ["vip"]}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .patch_json_auth(
+            &path,
+            &serde_json::json!({"internal_metadata": {"notes": "x".repeat(17 * 1024)}}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::BAD_REQUEST);
+    let response = app
+        .patch_json_auth(
+            &format!("/api/v1/users/{}/internal-metadata", uuid::Uuid::new_v4()),
+            &serde_json::json!({"internal_metadata": {"vip": true}}),
+            &moderator_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+}
@@ -12,7 +12,7 @@
 
 use crate::{
     auth::AuthUser,
-    rbac::services as rbac_services,
+    rbac::ownership,
     __MODULE_NAME_PLURAL__::{models::*, services::*},
     AppState, Error, Result,
     api::{ApiResponse, ErrorResponse},
@@ -113,10 +113,6 @@ pub async fn list___MODULE_NAME_PLURAL__(
     Extension(auth_user): Extension<AuthUser>,
     Query(query): Query<List__MODULE_STRUCT__Query>,
 ) -> Result<Json<ApiResponse<Vec<__MODULE_STRUCT__>>>> {
-    // Basic authenticated access - user just needs to be logged in
-    // The auth_user parameter ensures user is authenticated
-    let _ = &auth_user; // Explicitly acknowledge the auth requirement
-
     let limit = query.limit.unwrap_or(50).min(100);
     let offset = query.offset.unwrap_or(0);
 
@@ -134,6 +130,8 @@ pub async fn list___MODULE_NAME_PLURAL__(
             offset,
             search: query.search,
         },
+        // Users only list their own items; moderators and admins list all of their tenant's
+        ownership::owner_scope(&auth_user),
     )
     .await?;
 
@@ -163,10 +161,6 @@ pub async fn get___MODULE_NAME__(
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<__MODULE_STRUCT__>>> {
-    // Basic authenticated access - user just needs to be logged in
-    // The auth_user parameter ensures user is authenticated
-    let _ = &auth_user; // Explicitly acknowledge the auth requirement
-
     let mut conn = app_state
         .database
         .pool
@@ -174,7 +168,11 @@ pub async fn get___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let __MODULE_NAME__ = get___MODULE_NAME___service(conn.as_mut(), id).await?;
+    // Users only see their own items; moderators and admins see all
+    let __MODULE_NAME__ = ownership::authorize_owned(
+        &auth_user,
+        get___MODULE_NAME___service(conn.as_mut(), id).await?,
+    )?;
     Ok(Json(ApiResponse::success(__MODULE_NAME__)))
 }
 
@@ -210,7 +208,7 @@ pub async fn create___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let __MODULE_NAME__ = create___MODULE_NAME___service(conn.as_mut(), request, auth_user.id).await?;
+    let __MODULE_NAME__ = create___MODULE_NAME___service(conn.as_mut(), request, auth_user.id, auth_user.tenant_id).await?;
     Ok(Json(ApiResponse::success(__MODULE_NAME__)))
 }
 
@@ -248,13 +246,10 @@ pub async fn update___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // First get the item to check ownership
-    let existing_item = get___MODULE_NAME___service(&mut tx, id).await?;
-    
-    // Check RBAC authorization - Admin/Moderator can update any item, users only their own
-    rbac_services::can_access_own_resource(&auth_user, existing_item.created_by)?;
+    // Admin/Moderator can update any item, users only their own; others' items read as not found
+    ownership::authorize_owned(&auth_user, get___MODULE_NAME___service(&mut tx, id).await?)?;
 
-    let __MODULE_NAME__ = update___MODULE_NAME___service(&mut tx, id, request).await?;
+    let __MODULE_NAME__ = update___MODULE_NAME___service(&mut tx, id, auth_user.tenant_id, request).await?;
     
     tx.commit()
         .await
@@ -293,13 +288,10 @@ pub async fn delete___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // First get the item to check ownership
-    let existing_item = get___MODULE_NAME___service(&mut tx, id).await?;
-    
-    // Check RBAC authorization - Admin/Moderator can delete any item, users only their own
-    rbac_services::can_access_own_resource(&auth_user, existing_item.created_by)?;
+    // Admin/Moderator can delete any item, users only their own; others' items read as not found
+    ownership::authorize_owned(&auth_user, get___MODULE_NAME___service(&mut tx, id).await?)?;
 
-    delete___MODULE_NAME___service(&mut tx, id).await?;
+    delete___MODULE_NAME___service(&mut tx, id, auth_user.tenant_id).await?;
     
     tx.commit()
         .await
@@ -1,5 +1,6 @@
 //! __MODULE_STRUCT__ data models and request/response types
 
+use crate::rbac::Owned;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -13,10 +14,21 @@ pub struct __MODULE_STRUCT__ {
     pub name: String,
     pub description: Option<String>,
     pub created_by: Uuid,
+    pub tenant_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Owned for __MODULE_STRUCT__ {
+    fn owner_id(&self) -> Uuid {
+        self.created_by
+    }
+
+    fn tenant_id(&self) -> Uuid {
+        self.tenant_id
+    }
+}
+
 /// Request to create a new __MODULE_NAME__
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct Create__MODULE_STRUCT__Request {
@@ -41,12 +53,13 @@ pub struct List__MODULE_STRUCT__Request {
 
 impl __MODULE_STRUCT__ {
     /// Create a new __MODULE_NAME__ instance
-    pub fn new(name: String, description: Option<String>, created_by: Uuid) -> Self {
+    pub fn new(name: String, description: Option<String>, created_by: Uuid, tenant_id: Uuid) -> Self {
         Self {
             id: Uuid::new_v4(),
             name,
             description,
             created_by,
+            tenant_id,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -71,15 +84,18 @@ mod tests {
     #[test]
     fn test___MODULE_NAME___creation() {
         let created_by = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
         let __MODULE_NAME__ = __MODULE_STRUCT__::new(
             "Test __MODULE_STRUCT__".to_string(),
             Some("Test description".to_string()),
             created_by,
+            tenant_id,
         );
 
         assert_eq!(__MODULE_NAME__.name, "Test __MODULE_STRUCT__");
         assert_eq!(__MODULE_NAME__.description, Some("Test description".to_string()));
         assert_eq!(__MODULE_NAME__.created_by, created_by);
+        assert_eq!(__MODULE_NAME__.tenant_id, tenant_id);
         assert!(__MODULE_NAME__.created_at <= Utc::now());
         assert!(__MODULE_NAME__.updated_at <= Utc::now());
     }
@@ -91,6 +107,7 @@ mod tests {
             "Original Name".to_string(),
             Some("Original description".to_string()),
             created_by,
+            Uuid::new_v4(),
         );
 
         let original_created_at = __MODULE_NAME__.created_at;
@@ -1,13 +1,18 @@
 //! __MODULE_STRUCT__ business logic and database operations
 
 use crate::{DbConn, Result, Error};
+use crate::rbac::OwnerScope;
 use super::models::*;
 use uuid::Uuid;
 
 /// List __MODULE_NAME_PLURAL__ with optional filtering
+///
+/// Only the scope's tenant is listed, and with an owner only that user's
+/// __MODULE_NAME_PLURAL__; see [`crate::rbac::owner_scope`].
 pub async fn list___MODULE_NAME_PLURAL___service(
     conn: &mut DbConn,
     request: List__MODULE_STRUCT__Request,
+    scope: OwnerScope,
 ) -> Result<Vec<__MODULE_STRUCT__>> {
     // Use sqlx! macro for compile-time query validation
 
@@ -15,14 +20,18 @@ pub async fn list___MODULE_NAME_PLURAL___service(
         let search_param = format!("%{search}%");
         sqlx::query_as!(
             __MODULE_STRUCT__,
-            "SELECT id, name, description, created_by, created_at, updated_at 
+            "SELECT id, name, description, created_by, tenant_id, created_at, updated_at 
              FROM __MODULE_TABLE__ 
-             WHERE name ILIKE $1 OR description ILIKE $1
+             WHERE (name ILIKE $1 OR description ILIKE $1)
+               AND tenant_id = $4
+               AND ($5::UUID IS NULL OR created_by = $5)
              ORDER BY created_at DESC 
              LIMIT $2 OFFSET $3",
             search_param,
             request.limit as i64,
-            request.offset as i64
+            request.offset as i64,
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_all(&mut *conn)
         .await
@@ -30,12 +39,15 @@ pub async fn list___MODULE_NAME_PLURAL___service(
     } else {
         sqlx::query_as!(
             __MODULE_STRUCT__,
-            "SELECT id, name, description, created_by, created_at, updated_at 
+            "SELECT id, name, description, created_by, tenant_id, created_at, updated_at 
              FROM __MODULE_TABLE__ 
+             WHERE tenant_id = $3 AND ($4::UUID IS NULL OR created_by = $4)
              ORDER BY created_at DESC 
              LIMIT $1 OFFSET $2",
             request.limit as i64,
-            request.offset as i64
+            request.offset as i64,
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_all(&mut *conn)
         .await
@@ -52,7 +64,7 @@ pub async fn get___MODULE_NAME___service(
 ) -> Result<__MODULE_STRUCT__> {
     let __MODULE_NAME__ = sqlx::query_as!(
         __MODULE_STRUCT__,
-        "SELECT id, name, description, created_by, created_at, updated_at 
+        "SELECT id, name, description, created_by, tenant_id, created_at, updated_at 
          FROM __MODULE_TABLE__ 
          WHERE id = $1",
         id
@@ -70,23 +82,25 @@ pub async fn create___MODULE_NAME___service(
     conn: &mut DbConn,
     request: Create__MODULE_STRUCT__Request,
     created_by: Uuid,
+    tenant_id: Uuid,
 ) -> Result<__MODULE_STRUCT__> {
     // Validate request
     if request.name.trim().is_empty() {
         return Err(Error::validation("name", "Name cannot be empty"));
     }
 
-    let __MODULE_NAME__ = __MODULE_STRUCT__::new(request.name, request.description, created_by);
+    let __MODULE_NAME__ = __MODULE_STRUCT__::new(request.name, request.description, created_by, tenant_id);
 
     let created___MODULE_NAME__ = sqlx::query_as!(
         __MODULE_STRUCT__,
-        "INSERT INTO __MODULE_TABLE__ (id, name, description, created_by, created_at, updated_at)
-         VALUES ($1, $2, $3, $4, $5, $6)
-         RETURNING id, name, description, created_by, created_at, updated_at",
+        "INSERT INTO __MODULE_TABLE__ (id, name, description, created_by, tenant_id, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING id, name, description, created_by, tenant_id, created_at, updated_at",
         __MODULE_NAME__.id,
         __MODULE_NAME__.name,
         __MODULE_NAME__.description,
         __MODULE_NAME__.created_by,
+        __MODULE_NAME__.tenant_id,
         __MODULE_NAME__.created_at,
         __MODULE_NAME__.updated_at
     )
@@ -97,14 +111,18 @@ pub async fn create___MODULE_NAME___service(
     Ok(created___MODULE_NAME__)
 }
 
-/// Update an existing __MODULE_NAME__
+/// Update an existing __MODULE_NAME__ of the tenant
 pub async fn update___MODULE_NAME___service(
     conn: &mut DbConn,
     id: Uuid,
+    tenant_id: Uuid,
     request: Update__MODULE_STRUCT__Request,
 ) -> Result<__MODULE_STRUCT__> {
-    // Get existing __MODULE_NAME__
+    // Get existing __MODULE_NAME__; other tenants' read as not found
     let mut __MODULE_NAME__ = get___MODULE_NAME___service(conn, id).await?;
+    if __MODULE_NAME__.tenant_id != tenant_id {
+        return Err(Error::NotFound(format!("__MODULE_STRUCT__ with id {id}")));
+    }
 
     // Validate request
     if let Some(ref name) = request.name
@@ -120,12 +138,13 @@ pub async fn update___MODULE_NAME___service(
         __MODULE_STRUCT__,
         "UPDATE __MODULE_TABLE__ 
          SET name = $2, description = $3, updated_at = $4
-         WHERE id = $1
-         RETURNING id, name, description, created_by, created_at, updated_at",
+         WHERE id = $1 AND tenant_id = $5
+         RETURNING id, name, description, created_by, tenant_id, created_at, updated_at",
         __MODULE_NAME__.id,
         __MODULE_NAME__.name,
         __MODULE_NAME__.description,
-        __MODULE_NAME__.updated_at
+        __MODULE_NAME__.updated_at,
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -134,14 +153,16 @@ pub async fn update___MODULE_NAME___service(
     Ok(updated___MODULE_NAME__)
 }
 
-/// Delete a __MODULE_NAME__
+/// Delete a __MODULE_NAME__ of the tenant
 pub async fn delete___MODULE_NAME___service(
     conn: &mut DbConn,
     id: Uuid,
+    tenant_id: Uuid,
 ) -> Result<()> {
     let rows_affected = sqlx::query!(
-        "DELETE FROM __MODULE_TABLE__ WHERE id = $1",
-        id
+        "DELETE FROM __MODULE_TABLE__ WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
     )
     .execute(&mut *conn)
     .await
@@ -4,6 +4,7 @@ CREATE TABLE __MODULE_TABLE__ (
     name TEXT NOT NULL,
     description TEXT,
     created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id),
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -14,5 +15,8 @@ CREATE INDEX idx___MODULE_TABLE___name ON __MODULE_TABLE__(name);
 -- Create index on created_by for ownership queries  
 CREATE INDEX idx___MODULE_TABLE___created_by ON __MODULE_TABLE__(created_by);
 
+-- Create index on tenant_id for tenant-scoped listings
+CREATE INDEX idx___MODULE_TABLE___tenant_id ON __MODULE_TABLE__(tenant_id, created_at DESC);
+
 -- Create index on created_at for sorting
 CREATE INDEX idx___MODULE_TABLE___created_at ON __MODULE_TABLE__(created_at);
\ No newline at end of file
@@ -22,7 +22,7 @@ use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     auth::AuthUser,
-    rbac::services as rbac_services,
+    rbac::{ownership, services as rbac_services},
     __MODULE_NAME_PLURAL__::{models::*, services::*},
     AppState, Error, Result,
     api::{ApiResponse, ErrorResponse},
@@ -128,8 +128,6 @@ pub async fn list___MODULE_NAME_PLURAL__(
     Extension(auth_user): Extension<AuthUser>,
     Query(params): Query<List__MODULE_STRUCT__QueryParams>,
 ) -> Result<Json<ApiResponse<__MODULE_STRUCT__ListResponse>>> {
-    // Authenticated access required - user must be logged in
-    let _ = &auth_user; // Explicitly acknowledge the auth requirement
     // Parse status filter
     let status = params.status.map(|status_str| {
         status_str
@@ -209,7 +207,13 @@ pub async fn list___MODULE_NAME_PLURAL__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let response = list___MODULE_NAME_PLURAL___service(conn.as_mut(), request).await?;
+    // Users only list their own items; moderators and admins list all of their tenant's
+    let response = list___MODULE_NAME_PLURAL___service(
+        conn.as_mut(),
+        request,
+        ownership::owner_scope(&auth_user),
+    )
+    .await?;
     Ok(Json(ApiResponse::success(response)))
 }
 
@@ -232,8 +236,6 @@ pub async fn get___MODULE_NAME__(
     Extension(auth_user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<__MODULE_STRUCT__>>> {
-    // Authenticated access required - user must be logged in
-    let _ = &auth_user; // Explicitly acknowledge the auth requirement
     let mut conn = app_state
         .database
         .pool
@@ -241,7 +243,11 @@ pub async fn get___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let __MODULE_NAME__ = get___MODULE_NAME___service(conn.as_mut(), id).await?;
+    // Users only see their own items; moderators and admins see all
+    let __MODULE_NAME__ = ownership::authorize_owned(
+        &auth_user,
+        get___MODULE_NAME___service(conn.as_mut(), id).await?,
+    )?;
     Ok(Json(ApiResponse::success(__MODULE_NAME__)))
 }
 
@@ -269,7 +275,7 @@ pub async fn create___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    let __MODULE_NAME__ = create___MODULE_NAME___service(conn.as_mut(), request, auth_user.id).await?;
+    let __MODULE_NAME__ = create___MODULE_NAME___service(conn.as_mut(), request, auth_user.id, auth_user.tenant_id).await?;
     Ok(Json(ApiResponse::success(__MODULE_NAME__)))
 }
 
@@ -303,13 +309,10 @@ pub async fn update___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // First get the item to check ownership
-    let existing_item = get___MODULE_NAME___service(&mut tx, id).await?;
-    
-    // Check RBAC authorization - Admin/Moderator can update any item, users only their own
-    rbac_services::can_access_own_resource(&auth_user, existing_item.created_by)?;
+    // Admin/Moderator can update any item, users only their own; others' items read as not found
+    ownership::authorize_owned(&auth_user, get___MODULE_NAME___service(&mut tx, id).await?)?;
 
-    let __MODULE_NAME__ = update___MODULE_NAME___service(&mut tx, id, request).await?;
+    let __MODULE_NAME__ = update___MODULE_NAME___service(&mut tx, id, auth_user.tenant_id, request).await?;
     
     tx.commit()
         .await
@@ -344,13 +347,10 @@ pub async fn delete___MODULE_NAME__(
         .await
         .map_err(Error::from_sqlx)?;
 
-    // First get the item to check ownership
-    let existing_item = get___MODULE_NAME___service(&mut tx, id).await?;
-    
-    // Check RBAC authorization - Admin/Moderator can delete any item, users only their own
-    rbac_services::can_access_own_resource(&auth_user, existing_item.created_by)?;
+    // Admin/Moderator can delete any item, users only their own; others' items read as not found
+    ownership::authorize_owned(&auth_user, get___MODULE_NAME___service(&mut tx, id).await?)?;
 
-    delete___MODULE_NAME___service(&mut tx, id).await?;
+    delete___MODULE_NAME___service(&mut tx, id, auth_user.tenant_id).await?;
     
     tx.commit()
         .await
@@ -391,7 +391,7 @@ pub async fn bulk_create___MODULE_NAME_PLURAL__(
         .map_err(Error::from_sqlx)?;
     
     let skip_errors = request.skip_errors.unwrap_or(false);
-    let response = bulk_create___MODULE_NAME_PLURAL___service(&mut tx, request, auth_user.id).await?;
+    let response = bulk_create___MODULE_NAME_PLURAL___service(&mut tx, request, auth_user.id, auth_user.tenant_id).await?;
     
     // If there are errors and skip_errors is false, return a 400 error
     if !skip_errors && !response.errors.is_empty() {
@@ -438,7 +438,7 @@ pub async fn bulk_update___MODULE_NAME_PLURAL__(
         .map_err(Error::from_sqlx)?;
     
     let skip_errors = request.skip_errors.unwrap_or(false);
-    let response = bulk_update___MODULE_NAME_PLURAL___service(&mut tx, request).await?;
+    let response = bulk_update___MODULE_NAME_PLURAL___service(&mut tx, auth_user.tenant_id, request).await?;
     
     // If there are errors and skip_errors is false, return a 400 error
     if !skip_errors && !response.errors.is_empty() {
@@ -486,7 +486,7 @@ pub async fn bulk_delete___MODULE_NAME_PLURAL__(
         .map_err(Error::from_sqlx)?;
     
     let skip_errors = request.skip_errors.unwrap_or(false);
-    let response = bulk_delete___MODULE_NAME_PLURAL___service(&mut tx, request).await?;
+    let response = bulk_delete___MODULE_NAME_PLURAL___service(&mut tx, auth_user.tenant_id, request).await?;
     
     // If there are errors and skip_errors is false, return a 400 error
     if !skip_errors && !response.errors.is_empty() {
@@ -11,6 +11,7 @@ DROP INDEX IF EXISTS idx___MODULE_TABLE___active_items;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___status_priority;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___updated_at;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___created_at;
+DROP INDEX IF EXISTS idx___MODULE_TABLE___tenant_id;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___priority;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___status;
 DROP INDEX IF EXISTS idx___MODULE_TABLE___name;
@@ -1,5 +1,6 @@
 //! __MODULE_STRUCT__ data models and request/response types with advanced features
 
+use crate::rbac::Owned;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -16,10 +17,21 @@ pub struct __MODULE_STRUCT__ {
     pub priority: i32,
     pub metadata: serde_json::Value,
     pub created_by: Uuid,
+    pub tenant_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Owned for __MODULE_STRUCT__ {
+    fn owner_id(&self) -> Uuid {
+        self.created_by
+    }
+
+    fn tenant_id(&self) -> Uuid {
+        self.tenant_id
+    }
+}
+
 /// __MODULE_STRUCT__ status enumeration
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "__MODULE_NAME___status", rename_all = "lowercase")]
@@ -192,6 +204,7 @@ impl __MODULE_STRUCT__ {
         priority: Option<i32>,
         metadata: Option<serde_json::Value>,
         created_by: Uuid,
+        tenant_id: Uuid,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
@@ -201,6 +214,7 @@ impl __MODULE_STRUCT__ {
             priority: priority.unwrap_or(0),
             metadata: metadata.unwrap_or_else(|| serde_json::json!({})),
             created_by,
+            tenant_id,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -234,6 +248,7 @@ mod tests {
     #[test]
     fn test___MODULE_NAME___creation() {
         let created_by = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
         let __MODULE_NAME__ = __MODULE_STRUCT__::new(
             "Test __MODULE_STRUCT__".to_string(),
             Some("Test description".to_string()),
@@ -241,6 +256,7 @@ mod tests {
             Some(10),
             Some(serde_json::json!({"key": "value"})),
             created_by,
+            tenant_id,
         );
 
         assert_eq!(__MODULE_NAME__.name, "Test __MODULE_STRUCT__");
@@ -249,6 +265,7 @@ mod tests {
         assert_eq!(__MODULE_NAME__.priority, 10);
         assert_eq!(__MODULE_NAME__.metadata["key"], "value");
         assert_eq!(__MODULE_NAME__.created_by, created_by);
+        assert_eq!(__MODULE_NAME__.tenant_id, tenant_id);
         assert!(__MODULE_NAME__.created_at <= Utc::now());
         assert!(__MODULE_NAME__.updated_at <= Utc::now());
     }
@@ -263,6 +280,7 @@ mod tests {
             Some(5),
             None,
             created_by,
+            Uuid::new_v4(),
         );
 
         let update_request = Update__MODULE_STRUCT__Request {
@@ -1,13 +1,18 @@
 //! __MODULE_STRUCT__ business logic and database operations
 
 use super::models::*;
+use crate::rbac::OwnerScope;
 use crate::{DbConn, Result, Error};
 use uuid::Uuid;
 
 /// List __MODULE_NAME_PLURAL__ with optional filtering
+///
+/// Only the scope's tenant is listed and counted, and with an owner only that
+/// user's __MODULE_NAME_PLURAL__; see [`crate::rbac::owner_scope`].
 pub async fn list___MODULE_NAME_PLURAL___service(
     conn: &mut DbConn,
     request: List__MODULE_STRUCT__Request,
+    scope: OwnerScope,
 ) -> Result<__MODULE_STRUCT__ListResponse> {
     let limit = request.limit.unwrap_or(20).clamp(1, 100) as i64;
     let offset = request.offset.unwrap_or(0) as i64;
@@ -16,14 +21,18 @@ pub async fn list___MODULE_NAME_PLURAL___service(
         let search_param = format!("%{search}%");
         sqlx::query_as!(
             __MODULE_STRUCT__,
-            r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, created_at, updated_at 
+            r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, tenant_id, created_at, updated_at 
                FROM __MODULE_TABLE__ 
-               WHERE name ILIKE $1 OR description ILIKE $1
+               WHERE (name ILIKE $1 OR description ILIKE $1)
+                 AND tenant_id = $4
+                 AND ($5::UUID IS NULL OR created_by = $5)
                ORDER BY created_at DESC 
                LIMIT $2 OFFSET $3"#,
             search_param,
             limit,
-            offset
+            offset,
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_all(&mut *conn)
         .await
@@ -31,12 +40,15 @@ pub async fn list___MODULE_NAME_PLURAL___service(
     } else {
         sqlx::query_as!(
             __MODULE_STRUCT__,
-            r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, created_at, updated_at 
+            r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, tenant_id, created_at, updated_at 
                FROM __MODULE_TABLE__ 
+               WHERE tenant_id = $3 AND ($4::UUID IS NULL OR created_by = $4)
                ORDER BY created_at DESC 
                LIMIT $1 OFFSET $2"#,
             limit,
-            offset
+            offset,
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_all(&mut *conn)
         .await
@@ -47,8 +59,13 @@ pub async fn list___MODULE_NAME_PLURAL___service(
     let total_count = if let Some(search) = &request.search {
         let search_param = format!("%{search}%");
         sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM __MODULE_TABLE__ WHERE name ILIKE $1 OR description ILIKE $1",
-            search_param
+            "SELECT COUNT(*) FROM __MODULE_TABLE__
+             WHERE (name ILIKE $1 OR description ILIKE $1)
+               AND tenant_id = $2
+               AND ($3::UUID IS NULL OR created_by = $3)",
+            search_param,
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_one(&mut *conn)
         .await
@@ -56,7 +73,10 @@ pub async fn list___MODULE_NAME_PLURAL___service(
         .unwrap_or(0)
     } else {
         sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM __MODULE_TABLE__"
+            "SELECT COUNT(*) FROM __MODULE_TABLE__
+             WHERE tenant_id = $1 AND ($2::UUID IS NULL OR created_by = $2)",
+            scope.tenant_id,
+            scope.owner
         )
         .fetch_one(&mut *conn)
         .await
@@ -88,7 +108,7 @@ pub async fn get___MODULE_NAME___service(
 ) -> Result<__MODULE_STRUCT__> {
     let __MODULE_NAME__ = sqlx::query_as!(
         __MODULE_STRUCT__,
-        r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, created_at, updated_at 
+        r#"SELECT id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, tenant_id, created_at, updated_at 
            FROM __MODULE_TABLE__ 
            WHERE id = $1"#,
         id
@@ -106,6 +126,7 @@ pub async fn create___MODULE_NAME___service(
     conn: &mut DbConn,
     request: Create__MODULE_STRUCT__Request,
     created_by: Uuid,
+    tenant_id: Uuid,
 ) -> Result<__MODULE_STRUCT__> {
     // Validate request
     if request.name.trim().is_empty() {
@@ -119,13 +140,14 @@ pub async fn create___MODULE_NAME___service(
         request.priority,
         request.metadata,
         created_by,
+        tenant_id,
     );
 
     let created___MODULE_NAME__ = sqlx::query_as!(
         __MODULE_STRUCT__,
-        r#"INSERT INTO __MODULE_TABLE__ (id, name, description, status, priority, metadata, created_by, created_at, updated_at)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-           RETURNING id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, created_at, updated_at"#,
+        r#"INSERT INTO __MODULE_TABLE__ (id, name, description, status, priority, metadata, created_by, tenant_id, created_at, updated_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+           RETURNING id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, tenant_id, created_at, updated_at"#,
         __MODULE_NAME__.id,
         __MODULE_NAME__.name,
         __MODULE_NAME__.description,
@@ -133,6 +155,7 @@ pub async fn create___MODULE_NAME___service(
         __MODULE_NAME__.priority,
         __MODULE_NAME__.metadata,
         __MODULE_NAME__.created_by,
+        __MODULE_NAME__.tenant_id,
         __MODULE_NAME__.created_at,
         __MODULE_NAME__.updated_at
     )
@@ -143,14 +166,18 @@ pub async fn create___MODULE_NAME___service(
     Ok(created___MODULE_NAME__)
 }
 
-/// Update an existing __MODULE_NAME__
+/// Update an existing __MODULE_NAME__ of the tenant
 pub async fn update___MODULE_NAME___service(
     conn: &mut DbConn,
     id: Uuid,
+    tenant_id: Uuid,
     request: Update__MODULE_STRUCT__Request,
 ) -> Result<__MODULE_STRUCT__> {
-    // Get existing __MODULE_NAME__
+    // Get existing __MODULE_NAME__; other tenants' read as not found
     let mut __MODULE_NAME__ = get___MODULE_NAME___service(conn, id).await?;
+    if __MODULE_NAME__.tenant_id != tenant_id {
+        return Err(Error::NotFound(format!("__MODULE_STRUCT__ with id {id}")));
+    }
 
     // Validate request
     if let Some(ref name) = request.name
@@ -166,15 +193,16 @@ pub async fn update___MODULE_NAME___service(
         __MODULE_STRUCT__,
         r#"UPDATE __MODULE_TABLE__ 
            SET name = $2, description = $3, status = $4, priority = $5, metadata = $6, updated_at = $7
-           WHERE id = $1
-           RETURNING id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, created_at, updated_at"#,
+           WHERE id = $1 AND tenant_id = $8
+           RETURNING id, name, description, status as "status: __MODULE_STRUCT__Status", priority, metadata, created_by, tenant_id, created_at, updated_at"#,
         __MODULE_NAME__.id,
         __MODULE_NAME__.name,
         __MODULE_NAME__.description,
         __MODULE_NAME__.status as __MODULE_STRUCT__Status,
         __MODULE_NAME__.priority,
         __MODULE_NAME__.metadata,
-        __MODULE_NAME__.updated_at
+        __MODULE_NAME__.updated_at,
+        tenant_id
     )
     .fetch_one(&mut *conn)
     .await
@@ -183,14 +211,16 @@ pub async fn update___MODULE_NAME___service(
     Ok(updated___MODULE_NAME__)
 }
 
-/// Delete a __MODULE_NAME__
+/// Delete a __MODULE_NAME__ of the tenant
 pub async fn delete___MODULE_NAME___service(
     conn: &mut DbConn,
     id: Uuid,
+    tenant_id: Uuid,
 ) -> Result<()> {
     let rows_affected = sqlx::query!(
-        "DELETE FROM __MODULE_TABLE__ WHERE id = $1",
-        id
+        "DELETE FROM __MODULE_TABLE__ WHERE id = $1 AND tenant_id = $2",
+        id,
+        tenant_id
     )
     .execute(&mut *conn)
     .await
@@ -209,13 +239,14 @@ pub async fn bulk_create___MODULE_NAME_PLURAL___service(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     request: Bulk__MODULE_STRUCT__CreateRequest,
     created_by: Uuid,
+    tenant_id: Uuid,
 ) -> Result<BulkOperationResponse<__MODULE_STRUCT__>> {
     let mut results = Vec::new();
     let mut errors = Vec::new();
     let skip_errors = request.skip_errors.unwrap_or(false);
 
     for (index, item) in request.items.into_iter().enumerate() {
-        match create___MODULE_NAME___service(tx.as_mut(), item, created_by).await {
+        match create___MODULE_NAME___service(tx.as_mut(), item, created_by, tenant_id).await {
             Ok(__MODULE_NAME__) => results.push(__MODULE_NAME__),
             Err(error) => {
                 errors.push(BulkOperationError {
@@ -238,9 +269,10 @@ pub async fn bulk_create___MODULE_NAME_PLURAL___service(
     })
 }
 
-/// Bulk update __MODULE_NAME_PLURAL__
+/// Bulk update __MODULE_NAME_PLURAL__ of the tenant
 pub async fn bulk_update___MODULE_NAME_PLURAL___service(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: Uuid,
     request: Bulk__MODULE_STRUCT__UpdateRequest,
 ) -> Result<BulkOperationResponse<__MODULE_STRUCT__>> {
     let mut results = Vec::new();
@@ -248,7 +280,7 @@ pub async fn bulk_update___MODULE_NAME_PLURAL___service(
     let skip_errors = request.skip_errors.unwrap_or(false);
 
     for (index, item) in request.items.into_iter().enumerate() {
-        match update___MODULE_NAME___service(tx.as_mut(), item.id, item.data).await {
+        match update___MODULE_NAME___service(tx.as_mut(), item.id, tenant_id, item.data).await {
             Ok(__MODULE_NAME__) => results.push(__MODULE_NAME__),
             Err(error) => {
                 errors.push(BulkOperationError {
@@ -271,9 +303,10 @@ pub async fn bulk_update___MODULE_NAME_PLURAL___service(
     })
 }
 
-/// Bulk delete __MODULE_NAME_PLURAL__
+/// Bulk delete __MODULE_NAME_PLURAL__ of the tenant
 pub async fn bulk_delete___MODULE_NAME_PLURAL___service(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: Uuid,
     request: Bulk__MODULE_STRUCT__DeleteRequest,
 ) -> Result<BulkOperationResponse<Uuid>> {
     let mut results = Vec::new();
@@ -281,7 +314,7 @@ pub async fn bulk_delete___MODULE_NAME_PLURAL___service(
     let skip_errors = request.skip_errors.unwrap_or(false);
 
     for (index, id) in request.ids.into_iter().enumerate() {
-        match delete___MODULE_NAME___service(tx.as_mut(), id).await {
+        match delete___MODULE_NAME___service(tx.as_mut(), id, tenant_id).await {
             Ok(()) => results.push(id),
             Err(error) => {
                 errors.push(BulkOperationError {
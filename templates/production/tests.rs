@@ -352,6 +352,97 @@ async fn test___MODULE_NAME___rbac_permissions() {
     assert_status(&response, StatusCode::FORBIDDEN);
 }
 
+#[tokio::test]
+async fn test___MODULE_NAME___ownership() {
+    let app = spawn_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let (_owner, owner_token) = factory.create_authenticated_user("owneruser").await;
+    let (_other, other_token) = factory.create_authenticated_user("otheruser").await;
+    let (_moderator, moderator_token) = factory.create_authenticated_moderator("moderatoruser").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/__MODULE_NAME_PLURAL__",
+            &json!({"name": "Owned Item", "status": "active", "priority": 1}),
+            &owner_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let created: serde_json::Value = response.json().await.unwrap();
+    let path = format!("/api/v1/__MODULE_NAME_PLURAL__/{}", created["data"]["id"].as_str().unwrap());
+
+    // Other users can't see or change the item, which reads as not found
+    let response = app.get_auth("/api/v1/__MODULE_NAME_PLURAL__", &other_token.token).await;
+    let list: serde_json::Value = response.json().await.unwrap();
+    assert!(list["data"]["items"].as_array().unwrap().is_empty());
+    assert_status(&app.get_auth(&path, &other_token.token).await, StatusCode::NOT_FOUND);
+    let response = app
+        .put_json_auth(&path, &json!({"name": "Taken over"}), &other_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    assert_status(&app.delete_auth(&path, &other_token.token).await, StatusCode::NOT_FOUND);
+
+    // Moderators reach every item
+    let response = app.get_auth("/api/v1/__MODULE_NAME_PLURAL__", &moderator_token.token).await;
+    let list: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(list["data"]["items"].as_array().unwrap().len(), 1);
+    assert_status(&app.get_auth(&path, &moderator_token.token).await, StatusCode::OK);
+    assert_status(&app.delete_auth(&path, &owner_token.token).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test___MODULE_NAME___tenant_isolation() {
+    use crate::tenants::{admin_in_tenant, create_tenant, spawn_tenant_app};
+
+    let app = spawn_tenant_app().await;
+    let factory = TestDataFactory::new(app.clone());
+
+    let (_admin, admin_token) = factory.create_authenticated_admin("rootadmin").await;
+    create_tenant(&app, &admin_token.token, "acme").await;
+    let (_acme_admin, acme_token) = admin_in_tenant(&app, "acme", "acmeadmin").await;
+
+    let response = app
+        .post_json_auth(
+            "/api/v1/__MODULE_NAME_PLURAL__",
+            &json!({"name": "Tenant Item", "status": "active", "priority": 1}),
+            &admin_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let created: serde_json::Value = response.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap().to_string();
+    let path = format!("/api/v1/__MODULE_NAME_PLURAL__/{id}");
+
+    // Admins of other tenants can't see or change the item, which reads as not found
+    let response = app.get_auth("/api/v1/__MODULE_NAME_PLURAL__", &acme_token.token).await;
+    let list: serde_json::Value = response.json().await.unwrap();
+    assert!(list["data"]["items"].as_array().unwrap().is_empty());
+    assert_status(&app.get_auth(&path, &acme_token.token).await, StatusCode::NOT_FOUND);
+    let response = app
+        .put_json_auth(&path, &json!({"name": "Taken over"}), &acme_token.token)
+        .await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    assert_status(&app.delete_auth(&path, &acme_token.token).await, StatusCode::NOT_FOUND);
+
+    // Bulk changes by other tenants' moderators fail like missing items
+    let response = app
+        .delete_json_auth(
+            "/api/v1/__MODULE_NAME_PLURAL__/bulk",
+            &json!({"ids": [id], "skip_errors": true}),
+            &acme_token.token,
+        )
+        .await;
+    assert_status(&response, StatusCode::OK);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(result["data"]["success_count"], 0);
+
+    let response = app.get_auth(&path, &admin_token.token).await;
+    assert_status(&response, StatusCode::OK);
+    let item: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(item["data"]["name"], "Tenant Item");
+}
+
 #[tokio::test]
 async fn test___MODULE_NAME___pagination_and_filtering() {
     let app = spawn_app().await;
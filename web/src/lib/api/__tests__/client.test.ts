@@ -412,17 +412,28 @@ describe("ApiClient Unit Tests", () => {
 		});
 
 		it("should get users list", async () => {
-			const params = { limit: 10, offset: 5 }; // Use non-zero offset
+			const params = { search: "jo", limit: 10, offset: 5 }; // Use non-zero offset
+			const page = {
+				items: [mockUserProfile],
+				pagination: {
+					total_count: 6,
+					page_count: 1,
+					current_page: 1,
+					per_page: 10,
+					has_next: false,
+					has_prev: true,
+				},
+			};
 
 			mockFetch.mockResolvedValueOnce(
-				createMockResponse(mockApiResponse([mockUserProfile])),
+				createMockResponse(mockApiResponse(page)),
 			);
 
 			const result = await apiClient.getUsers(params);
 
-			expect(result.data).toEqual([mockUserProfile]);
+			expect(result.data).toEqual(page);
 			expect(mockFetch).toHaveBeenCalledWith(
-				"/api/v1/users?limit=10&offset=5",
+				"/api/v1/users?search=jo&limit=10&offset=5",
 				expect.any(Object),
 			);
 		});
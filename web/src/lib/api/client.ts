@@ -33,6 +33,22 @@ export type RefreshResponse =
 	components["schemas"]["ApiResponse_RefreshResponse"];
 export type BasicResponse = components["schemas"]["ApiResponse_String"];
 
+// Paginated user search (GET /users)
+export type PaginationInfo = {
+	total_count: number;
+	page_count: number;
+	current_page: number;
+	per_page: number;
+	has_next: boolean;
+	has_prev: boolean;
+};
+export type UserListResponse = Omit<
+	components["schemas"]["ApiResponse_Vec_UserProfile"],
+	"data"
+> & {
+	data?: { items: UserProfile[]; pagination: PaginationInfo };
+};
+
 // Token management
 let authToken: string | null = null;
 
@@ -121,6 +137,20 @@ class ApiClient {
 		return response;
 	}
 
+	async resendVerification(email: string): Promise<BasicResponse> {
+		return this.request<BasicResponse>("/auth/resend-verification", {
+			method: "POST",
+			body: JSON.stringify({ email }),
+		});
+	}
+
+	async verifyEmail(token: string): Promise<BasicResponse> {
+		return this.request<BasicResponse>("/auth/verify-email", {
+			method: "POST",
+			body: JSON.stringify({ token }),
+		});
+	}
+
 	async logout(): Promise<BasicResponse> {
 		const response = await this.request<BasicResponse>("/auth/logout", {
 			method: "POST",
@@ -309,24 +339,36 @@ class ApiClient {
 
 	// Moderator+ user management
 	async getUsers(params?: {
+		search?: string;
+		role?: "user" | "moderator" | "admin";
+		is_active?: boolean;
+		email_verified?: boolean;
+		sort?: "created_at" | "username" | "email" | "last_login_at" | "last_seen_at";
+		order?: "asc" | "desc";
 		limit?: number;
 		offset?: number;
-	}): Promise<components["schemas"]["ApiResponse_Vec_UserProfile"]> {
+	}): Promise<UserListResponse> {
 		const searchParams = new URLSearchParams();
+		if (params?.search) searchParams.set("search", params.search);
+		if (params?.role) searchParams.set("role", params.role);
+		if (params?.is_active !== undefined)
+			searchParams.set("is_active", params.is_active.toString());
+		if (params?.email_verified !== undefined)
+			searchParams.set("email_verified", params.email_verified.toString());
+		if (params?.sort) searchParams.set("sort", params.sort);
+		if (params?.order) searchParams.set("order", params.order);
 		if (params?.limit) searchParams.set("limit", params.limit.toString());
 		if (params?.offset) searchParams.set("offset", params.offset.toString());
 
 		const query = searchParams.toString();
 		const endpoint = query ? `/users?${query}` : "/users";
 
-		return this.request<components["schemas"]["ApiResponse_Vec_UserProfile"]>(
-			endpoint,
-		);
+		return this.request<UserListResponse>(endpoint);
 	}
 
 	async updateUserStatus(
 		id: string,
-		data: { is_active: boolean; reason?: string },
+		data: { is_active: boolean; reason?: string; suspended_until?: string },
 	): Promise<UserProfileResponse> {
 		return this.request<UserProfileResponse>(`/users/${id}/status`, {
 			method: "PUT",
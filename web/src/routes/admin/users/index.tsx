@@ -67,6 +67,7 @@ function UsersPage() {
 		queryKey: ["admin", "users", currentPage, searchTerm],
 		queryFn: async () => {
 			const response = await apiClient.getUsers({
+				search: searchTerm || undefined,
 				limit: pageSize,
 				offset,
 			});
@@ -75,20 +76,11 @@ function UsersPage() {
 		enabled: isModeratorOrHigher(), // Only fetch if user has permission
 	});
 
-	const users = usersResponse?.data || [];
-
-	// For search functionality, we'll need to implement server-side search
-	// For now, keeping client-side search on current page
-	const filteredUsers = users.filter(
-		(user) =>
-			user.username.toLowerCase().includes(searchTerm.toLowerCase()) ||
-			user.email.toLowerCase().includes(searchTerm.toLowerCase()),
-	);
-
-	// Calculate total pages (since API doesn't return total count, we'll estimate)
-	// In production, the API should return total count for accurate pagination
-	const hasNextPage = users.length === pageSize;
-	const totalPages = hasNextPage ? currentPage + 1 : currentPage;
+	// Search and pagination happen on the server
+	const users = usersResponse?.data?.items || [];
+	const pagination = usersResponse?.data?.pagination;
+	const hasNextPage = pagination?.has_next ?? false;
+	const totalPages = pagination?.page_count ?? currentPage;
 
 	// User status update mutation (Moderator+)
 	const updateUserStatusMutation = useMutation({
@@ -324,7 +316,10 @@ function UsersPage() {
 						<Input
 							placeholder="Search users..."
 							value={searchTerm}
-							onChange={(e) => setSearchTerm(e.target.value)}
+							onChange={(e) => {
+								setSearchTerm(e.target.value);
+								setCurrentPage(1);
+							}}
 							className="pl-8"
 						/>
 					</div>
@@ -351,7 +346,7 @@ function UsersPage() {
 											Loading users...
 										</TableCell>
 									</TableRow>
-								) : filteredUsers.length === 0 ? (
+								) : users.length === 0 ? (
 									<TableRow>
 										<TableCell colSpan={6} className="text-center py-4">
 											{searchTerm
@@ -360,7 +355,7 @@ function UsersPage() {
 										</TableCell>
 									</TableRow>
 								) : (
-									filteredUsers.map((user) => (
+									users.map((user) => (
 										<TableRow key={user.id}>
 											<TableCell>
 												<div className="flex items-center space-x-2">
@@ -486,7 +481,7 @@ function UsersPage() {
 								currentPage * pageSize,
 								(currentPage - 1) * pageSize + users.length,
 							)}{" "}
-							of users
+							of {pagination?.total_count ?? users.length} users
 						</div>
 						<Pagination>
 							<PaginationContent>